@@ -10,7 +10,7 @@ use gpui::{
 use language::{
     LanguageRegistry, LanguageServerBinaryStatus, LanguageServerId, LanguageServerName,
 };
-use project::{LanguageServerProgress, Project};
+use project::{LanguageServerFinishedWork, LanguageServerProgress, Project};
 use smallvec::SmallVec;
 use std::{cmp::Reverse, fmt::Write, sync::Arc, time::Duration};
 use ui::{prelude::*, ButtonLike, ContextMenu, PopoverMenu, PopoverMenuHandle};
@@ -18,6 +18,8 @@ use workspace::{item::ItemHandle, StatusItemView, Workspace};
 
 actions!(activity_indicator, [ShowErrorMessage]);
 
+const MAX_FINISHED_WORK_ENTRIES: usize = 5;
+
 pub enum Event {
     ShowError { lsp_name: Arc<str>, error: String },
 }
@@ -36,10 +38,16 @@ struct LspStatus {
 
 struct PendingWork<'a> {
     language_server_id: LanguageServerId,
+    language_server_name: &'a str,
     progress_token: &'a str,
     progress: &'a LanguageServerProgress,
 }
 
+struct FinishedWork<'a> {
+    language_server_name: &'a str,
+    work: &'a LanguageServerFinishedWork,
+}
+
 struct Content {
     icon: Option<gpui::AnyElement>,
     message: String,
@@ -161,6 +169,7 @@ impl ActivityIndicator {
                         .iter()
                         .map(|(token, progress)| PendingWork {
                             language_server_id: server_id,
+                            language_server_name: status.name.as_str(),
                             progress_token: token.as_str(),
                             progress,
                         })
@@ -172,6 +181,23 @@ impl ActivityIndicator {
             .flatten()
     }
 
+    fn finished_language_server_work<'a>(&self, cx: &'a AppContext) -> Vec<FinishedWork<'a>> {
+        let mut finished_work = self
+            .project
+            .read(cx)
+            .language_server_statuses(cx)
+            .flat_map(|(_, status)| {
+                status.finished_work.iter().map(|work| FinishedWork {
+                    language_server_name: status.name.as_str(),
+                    work,
+                })
+            })
+            .collect::<Vec<_>>();
+        finished_work.sort_by_key(|finished| Reverse(finished.work.finished_at));
+        finished_work.truncate(MAX_FINISHED_WORK_ENTRIES);
+        finished_work
+    }
+
     fn content_to_render(&mut self, cx: &mut ViewContext<Self>) -> Option<Content> {
         // Show any language server has pending activity.
         let mut pending_work = self.pending_language_server_work(cx);
@@ -291,6 +317,48 @@ impl ActivityIndicator {
             });
         }
 
+        // Show the progress of scanning a project's files.
+        if let Some(worktree) = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .find(|worktree| {
+                worktree
+                    .read(cx)
+                    .as_local()
+                    .map_or(false, |worktree| worktree.is_scanning())
+            })
+        {
+            let worktree_handle = worktree.downgrade();
+            let worktree = worktree.read(cx);
+            return Some(Content {
+                icon: Some(
+                    Icon::new(IconName::ArrowCircle)
+                        .size(IconSize::Small)
+                        .with_animation(
+                            "arrow-circle",
+                            Animation::new(Duration::from_secs(2)).repeat(),
+                            |icon, delta| icon.transform(Transformation::rotate(percentage(delta))),
+                        )
+                        .into_any_element(),
+                ),
+                message: format!(
+                    "Scanning {}… {} files. Click to stop.",
+                    worktree.root_name(),
+                    worktree.file_count()
+                ),
+                on_click: Some(Arc::new(move |_, cx| {
+                    worktree_handle
+                        .update(cx, |worktree, _| {
+                            if let Some(worktree) = worktree.as_local() {
+                                worktree.cancel_scan();
+                            }
+                        })
+                        .ok();
+                })),
+            });
+        }
+
         // Show any application auto-update info.
         if let Some(updater) = &self.auto_updater {
             return match &updater.read(cx).status() {
@@ -407,26 +475,58 @@ impl Render for ActivityIndicator {
                         for work in strong_this.read(cx).pending_language_server_work(cx) {
                             has_work = true;
                             let this = this.clone();
-                            let mut title = work
-                                .progress
-                                .title
-                                .as_deref()
-                                .unwrap_or(work.progress_token)
-                                .to_owned();
+                            let title = SharedString::from(
+                                work.progress
+                                    .title
+                                    .as_deref()
+                                    .unwrap_or(work.progress_token)
+                                    .to_owned(),
+                            );
+                            let server_name =
+                                SharedString::from(work.language_server_name.to_owned());
+                            let message = work.progress.message.clone().map(SharedString::from);
+                            let percentage = work.progress.percentage;
+                            let render_work = move |cancellable: bool, cx: &mut WindowContext| {
+                                v_flex()
+                                    .w_full()
+                                    .gap_1()
+                                    .child(
+                                        h_flex()
+                                            .w_full()
+                                            .justify_between()
+                                            .gap_2()
+                                            .child(Label::new(title.clone()))
+                                            .child(
+                                                h_flex()
+                                                    .gap_1()
+                                                    .child(
+                                                        Label::new(server_name.clone())
+                                                            .size(LabelSize::Small)
+                                                            .color(Color::Muted),
+                                                    )
+                                                    .when(cancellable, |this| {
+                                                        this.child(Icon::new(IconName::XCircle))
+                                                    }),
+                                            ),
+                                    )
+                                    .when_some(message.clone(), |this, message| {
+                                        this.child(
+                                            Label::new(message)
+                                                .size(LabelSize::Small)
+                                                .color(Color::Muted),
+                                        )
+                                    })
+                                    .when_some(percentage, |this, percentage| {
+                                        this.child(render_progress_bar(percentage, cx))
+                                    })
+                                    .into_any_element()
+                            };
 
                             if work.progress.is_cancellable {
                                 let language_server_id = work.language_server_id;
                                 let token = work.progress_token.to_string();
-                                let title = SharedString::from(title);
                                 menu = menu.custom_entry(
-                                    move |_| {
-                                        h_flex()
-                                            .w_full()
-                                            .justify_between()
-                                            .child(Label::new(title.clone()))
-                                            .child(Icon::new(IconName::XCircle))
-                                            .into_any_element()
-                                    },
+                                    move |cx| render_work(true, cx),
                                     move |cx| {
                                         this.update(cx, |this, cx| {
                                             this.project.update(cx, |project, cx| {
@@ -443,12 +543,47 @@ impl Render for ActivityIndicator {
                                     },
                                 );
                             } else {
-                                if let Some(progress_message) = work.progress.message.as_ref() {
-                                    title.push_str(": ");
-                                    title.push_str(progress_message);
-                                }
+                                menu = menu.custom_row(move |cx| render_work(false, cx));
+                            }
+                        }
 
-                                menu = menu.label(title);
+                        let finished_work = strong_this.read(cx).finished_language_server_work(cx);
+                        if !finished_work.is_empty() {
+                            if has_work {
+                                menu = menu.separator();
+                            }
+                            has_work = true;
+                            menu = menu.header("Recent");
+                            let now = cx.background_executor().now();
+                            for FinishedWork {
+                                language_server_name,
+                                work,
+                            } in finished_work
+                            {
+                                let mut title =
+                                    work.title.clone().unwrap_or_else(|| work.token.clone());
+                                if work.was_cancelled {
+                                    title.push_str(" (cancelled)");
+                                }
+                                let title = SharedString::from(title);
+                                let details = SharedString::from(format!(
+                                    "{language_server_name} · took {} · {} ago",
+                                    format_duration(work.finished_at - work.started_at),
+                                    format_duration(
+                                        now.saturating_duration_since(work.finished_at)
+                                    ),
+                                ));
+                                menu = menu.custom_row(move |_| {
+                                    v_flex()
+                                        .w_full()
+                                        .child(Label::new(title.clone()).color(Color::Muted))
+                                        .child(
+                                            Label::new(details.clone())
+                                                .size(LabelSize::Small)
+                                                .color(Color::Muted),
+                                        )
+                                        .into_any_element()
+                                });
                             }
                         }
                         menu
@@ -459,6 +594,47 @@ impl Render for ActivityIndicator {
     }
 }
 
+fn render_progress_bar(percentage: usize, cx: &WindowContext) -> impl IntoElement {
+    let fraction = percentage.min(100) as f32 / 100.;
+    div()
+        .w_full()
+        .h_1()
+        .rounded_sm()
+        .bg(cx.theme().colors().element_background)
+        .child(
+            div()
+                .h_full()
+                .w(relative(fraction))
+                .rounded_sm()
+                .bg(cx.theme().status().info),
+        )
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 60 * 60 {
+        format!("{}h {}m", seconds / (60 * 60), (seconds / 60) % 60)
+    } else if seconds >= 60 {
+        format!("{}m {}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{:.1}s", duration.as_secs_f32())
+    }
+}
+
 impl StatusItemView for ActivityIndicator {
     fn set_active_pane_item(&mut self, _: Option<&dyn ItemHandle>, _: &mut ViewContext<Self>) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(1_500)), "1.5s");
+        assert_eq!(format_duration(Duration::from_millis(12_340)), "12.3s");
+        assert_eq!(format_duration(Duration::from_secs(59)), "59.0s");
+        assert_eq!(format_duration(Duration::from_secs(60)), "1m 0s");
+        assert_eq!(format_duration(Duration::from_secs(61 * 60 + 5)), "1h 1m");
+    }
+}
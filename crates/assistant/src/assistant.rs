@@ -35,7 +35,7 @@ use language_model::{
 pub(crate) use model_selector::*;
 pub use prompts::PromptBuilder;
 use prompts::PromptLoadingParams;
-use semantic_index::{CloudEmbeddingProvider, SemanticIndex};
+use semantic_index::{SemanticIndex, SemanticIndexSettings};
 use serde::{Deserialize, Serialize};
 use settings::{update_settings_file, Settings, SettingsStore};
 use slash_command::{
@@ -64,6 +64,9 @@ actions!(
         DeployPromptLibrary,
         ConfirmCommand,
         ToggleModelSelector,
+        InlineTransform,
+        AcceptHunk,
+        RejectHunk,
     ]
 );
 
@@ -183,6 +186,7 @@ pub fn init(
     cx.set_global(Assistant::default());
     AssistantSettings::register(cx);
     SlashCommandSettings::register(cx);
+    semantic_index::init(cx);
 
     // TODO: remove this when 0.148.0 is released.
     if AssistantSettings::get_global(cx).using_outdated_settings_version {
@@ -197,10 +201,11 @@ pub fn init(
     cx.spawn(|mut cx| {
         let client = client.clone();
         async move {
-            let embedding_provider = CloudEmbeddingProvider::new(client.clone());
+            let settings = cx.update(|cx| SemanticIndexSettings::get_global(cx).clone())?;
+            let embedding_provider = settings.embedding_provider(client, &cx).await?;
             let semantic_index = SemanticIndex::new(
-                paths::embeddings_dir().join("semantic-index-db.0.mdb"),
-                Arc::new(embedding_provider),
+                paths::embeddings_dir().join(settings.provider.db_dir_name()),
+                embedding_provider,
                 &mut cx,
             )
             .await?;
@@ -343,8 +348,20 @@ fn update_active_language_model_from_settings(cx: &mut AppContext) {
     let settings = AssistantSettings::get_global(cx);
     let provider_name = LanguageModelProviderId::from(settings.default_model.provider.clone());
     let model_id = LanguageModelId::from(settings.default_model.model.clone());
+    let inline_assistant_model = settings.inline_assistant_model.as_ref().map(|selection| {
+        (
+            LanguageModelProviderId::from(selection.provider.clone()),
+            LanguageModelId::from(selection.model.clone()),
+        )
+    });
     LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
         registry.select_active_model(&provider_name, &model_id, cx);
+        registry.select_inline_assistant_model(
+            inline_assistant_model
+                .as_ref()
+                .map(|(provider, model_id)| (provider, model_id)),
+            cx,
+        );
     });
 }
 
@@ -13,10 +13,10 @@ use crate::{
     terminal_inline_assistant::TerminalInlineAssistant,
     Assist, CacheStatus, ConfirmCommand, Context, ContextEvent, ContextId, ContextStore,
     ContextStoreEvent, CycleMessageRole, DeployHistory, DeployPromptLibrary, InlineAssistId,
-    InlineAssistant, InsertIntoEditor, Message, MessageId, MessageMetadata, MessageStatus,
-    ModelPickerDelegate, ModelSelector, PendingSlashCommand, PendingSlashCommandStatus,
-    QuoteSelection, RemoteContextMetadata, SavedContextMetadata, Split, ToggleFocus,
-    ToggleModelSelector, WorkflowStepResolution,
+    InlineAssistant, InlineTransform, InsertIntoEditor, Message, MessageId, MessageMetadata,
+    MessageStatus, ModelPickerDelegate, ModelSelector, PendingSlashCommand,
+    PendingSlashCommandStatus, QuoteSelection, RemoteContextMetadata, SavedContextMetadata, Split,
+    ToggleFocus, ToggleModelSelector, WorkflowStepResolution,
 };
 use anyhow::{anyhow, Result};
 use assistant_slash_command::{SlashCommand, SlashCommandOutputSection};
@@ -93,6 +93,7 @@ pub fn init(cx: &mut AppContext) {
                     workspace.toggle_panel_focus::<AssistantPanel>(cx);
                 })
                 .register_action(AssistantPanel::inline_assist)
+                .register_action(AssistantPanel::inline_transform)
                 .register_action(ContextEditor::quote_selection)
                 .register_action(ContextEditor::insert_selection)
                 .register_action(AssistantPanel::show_configuration);
@@ -424,6 +425,8 @@ impl AssistantPanel {
                     | language_model::Event::RemovedProvider(_) => {
                         this.ensure_authenticated(cx);
                     }
+                    language_model::Event::InlineAssistantModelChanged
+                    | language_model::Event::TokenUsageChanged => {}
                 },
             ),
         ];
@@ -801,6 +804,42 @@ impl AssistantPanel {
         }
     }
 
+    /// Deploys the inline assistant to rewrite the selected code, selecting the
+    /// lines of any empty selections so that there's code to rewrite.
+    pub fn inline_transform(
+        workspace: &mut Workspace,
+        _: &InlineTransform,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        if let Some(assistant_panel) = workspace.panel::<AssistantPanel>(cx) {
+            if let Some(InlineAssistTarget::Editor(editor, _)) =
+                Self::resolve_inline_assist_target(workspace, &assistant_panel, cx)
+            {
+                editor.update(cx, |editor, cx| {
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let ranges = editor
+                        .selections
+                        .all::<Point>(cx)
+                        .into_iter()
+                        .map(|selection| {
+                            if selection.is_empty() {
+                                let row = selection.head().row;
+                                Point::new(row, 0)
+                                    ..Point::new(row, snapshot.line_len(MultiBufferRow(row)))
+                            } else {
+                                selection.range()
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    editor
+                        .change_selections(None, cx, |selections| selections.select_ranges(ranges));
+                });
+            }
+        }
+
+        Self::inline_assist(workspace, &InlineAssist::default(), cx);
+    }
+
     fn resolve_inline_assist_target(
         workspace: &mut Workspace,
         assistant_panel: &View<AssistantPanel>,
@@ -4499,6 +4538,7 @@ impl ConfigurationView {
                 language_model::Event::RemovedProvider(provider_id) => {
                     this.remove_configuration_view(provider_id);
                 }
+                language_model::Event::TokenUsageChanged => cx.notify(),
                 _ => {}
             },
         );
@@ -4591,6 +4631,45 @@ impl ConfigurationView {
                     }),
             )
     }
+
+    fn render_token_usage(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
+        let registry = LanguageModelRegistry::read_global(cx);
+        let rows = registry
+            .token_usage()
+            .map(|(provider_name, model_name, usage)| {
+                h_flex()
+                    .justify_between()
+                    .child(Label::new(format!(
+                        "{} ({})",
+                        model_name.0, provider_name.0
+                    )))
+                    .child(
+                        Label::new(format!(
+                            "{} requests, {} input tokens, {} output tokens",
+                            usage.requests, usage.input_tokens, usage.output_tokens
+                        ))
+                        .color(Color::Muted),
+                    )
+            })
+            .collect::<Vec<_>>();
+        if rows.is_empty() {
+            return None;
+        }
+
+        Some(
+            v_flex()
+                .gap_2()
+                .child(Headline::new("Token Usage").size(HeadlineSize::Small))
+                .child(
+                    Label::new(
+                        "Tokens used since Zed was started, estimated with each model's tokenizer.",
+                    )
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+                )
+                .children(rows),
+        )
+    }
 }
 
 impl Render for ConfigurationView {
@@ -4627,7 +4706,8 @@ impl Render for ConfigurationView {
                     .mt_1()
                     .gap_6()
                     .flex_1()
-                    .children(provider_views),
+                    .children(provider_views)
+                    .children(self.render_token_usage(cx)),
             )
             .into_any();
 
@@ -59,6 +59,7 @@ pub struct AssistantSettings {
     pub default_width: Pixels,
     pub default_height: Pixels,
     pub default_model: LanguageModelSelection,
+    pub inline_assistant_model: Option<LanguageModelSelection>,
     pub using_outdated_settings_version: bool,
 }
 
@@ -232,6 +233,7 @@ impl AssistantSettingsContent {
                                 })
                             }
                         }),
+                    inline_assistant_model: None,
                 },
                 VersionedAssistantSettingsContent::V2(settings) => settings.clone(),
             },
@@ -250,6 +252,7 @@ impl AssistantSettingsContent {
                         .id()
                         .to_string(),
                 }),
+                inline_assistant_model: None,
             },
         }
     }
@@ -365,6 +368,7 @@ impl Default for VersionedAssistantSettingsContent {
             default_width: None,
             default_height: None,
             default_model: None,
+            inline_assistant_model: None,
         })
     }
 }
@@ -393,6 +397,11 @@ pub struct AssistantSettingsContentV2 {
     default_height: Option<f32>,
     /// The default model to use when creating new contexts.
     default_model: Option<LanguageModelSelection>,
+    /// The model to use for the inline assistant, in editors and in the terminal,
+    /// instead of the default model.
+    ///
+    /// Default: null
+    inline_assistant_model: Option<LanguageModelSelection>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
@@ -517,6 +526,10 @@ impl Settings for AssistantSettings {
                 &mut settings.default_model,
                 value.default_model.map(Into::into),
             );
+            merge(
+                &mut settings.inline_assistant_model,
+                value.inline_assistant_model.map(Some),
+            );
         }
 
         Ok(settings)
@@ -569,6 +582,7 @@ mod tests {
                                 provider: "test-provider".into(),
                                 model: "gpt-99".into(),
                             }),
+                            inline_assistant_model: None,
                             enabled: None,
                             button: None,
                             dock: None,
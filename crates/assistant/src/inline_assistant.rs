@@ -1,7 +1,7 @@
 use crate::{
     assistant_settings::AssistantSettings, humanize_token_count, prompts::PromptBuilder,
-    AssistantPanel, AssistantPanelEvent, CharOperation, LineDiff, LineOperation, ModelSelector,
-    StreamingDiff,
+    AcceptHunk, AssistantPanel, AssistantPanelEvent, CharOperation, LineDiff, LineOperation,
+    ModelSelector, RejectHunk, StreamingDiff,
 };
 use anyhow::{anyhow, Context as _, Result};
 use client::{telemetry::Telemetry, ErrorExt};
@@ -552,6 +552,51 @@ impl InlineAssistant {
         cx.propagate();
     }
 
+    fn handle_editor_review_hunk(
+        &mut self,
+        editor: View<Editor>,
+        accept: bool,
+        cx: &mut WindowContext,
+    ) {
+        let Some(editor_assists) = self.assists_by_editor.get(&editor.downgrade()) else {
+            return;
+        };
+
+        let row = editor.read(cx).selections.newest::<Point>(cx).head().row;
+        let buffer = editor.read(cx).buffer().read(cx).snapshot(cx);
+        for assist_id in editor_assists.assist_ids.clone() {
+            let assist = &self.assists[&assist_id];
+            let codegen = assist.codegen.clone();
+            if assist.decorations.is_none()
+                || !matches!(codegen.read(cx).status, CodegenStatus::Done)
+            {
+                continue;
+            }
+            let assist_range = assist.range.to_point(&buffer);
+            if !(assist_range.start.row..=assist_range.end.row).contains(&row) {
+                continue;
+            }
+            let Some(hunk) = codegen.read(cx).hunk_for_row(row, cx) else {
+                continue;
+            };
+
+            codegen.update(cx, |codegen, cx| {
+                if accept {
+                    codegen.accept_hunk(&hunk, cx);
+                } else {
+                    codegen.reject_hunk(&hunk, cx);
+                }
+            });
+            // Once every hunk has been reviewed, there's nothing left to confirm.
+            if codegen.read(cx).diff.is_empty() {
+                self.finish_assist(assist_id, false, cx);
+            }
+            return;
+        }
+
+        cx.propagate();
+    }
+
     fn handle_editor_cancel(&mut self, editor: View<Editor>, cx: &mut WindowContext) {
         let Some(editor_assists) = self.assists_by_editor.get(&editor.downgrade()) else {
             return;
@@ -1286,6 +1331,26 @@ impl EditorInlineAssists {
                         },
                     )
                 }),
+                editor.update(cx, |editor, cx| {
+                    let editor_handle = cx.view().downgrade();
+                    editor.register_action(move |_: &AcceptHunk, cx: &mut WindowContext| {
+                        InlineAssistant::update_global(cx, |this, cx| {
+                            if let Some(editor) = editor_handle.upgrade() {
+                                this.handle_editor_review_hunk(editor, true, cx)
+                            }
+                        })
+                    })
+                }),
+                editor.update(cx, |editor, cx| {
+                    let editor_handle = cx.view().downgrade();
+                    editor.register_action(move |_: &RejectHunk, cx: &mut WindowContext| {
+                        InlineAssistant::update_global(cx, |this, cx| {
+                            if let Some(editor) = editor_handle.upgrade() {
+                                this.handle_editor_review_hunk(editor, false, cx)
+                            }
+                        })
+                    })
+                }),
             ],
         }
     }
@@ -1484,7 +1549,7 @@ impl Render for PromptEditor {
                                         format!(
                                             "Using {}",
                                             LanguageModelRegistry::read_global(cx)
-                                                .active_model()
+                                                .inline_assistant_model()
                                                 .map(|model| model.name().0)
                                                 .unwrap_or_else(|| "No model selected".into()),
                                         ),
@@ -1845,7 +1910,7 @@ impl PromptEditor {
     }
 
     fn render_token_count(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
-        let model = LanguageModelRegistry::read_global(cx).active_model()?;
+        let model = LanguageModelRegistry::read_global(cx).inline_assistant_model()?;
         let token_counts = self.token_counts?;
         let max_token_count = model.max_token_count();
 
@@ -2207,6 +2272,81 @@ impl Diff {
     fn is_empty(&self) -> bool {
         self.deleted_row_ranges.is_empty() && self.inserted_row_ranges.is_empty()
     }
+
+    /// Groups the deleted and inserted rows into hunks, pairing rows that were
+    /// deleted with the rows inserted in their place.
+    fn hunks(&self, snapshot: &MultiBufferSnapshot) -> Vec<DiffHunk> {
+        let mut hunks = Vec::new();
+        let mut deletions = self.deleted_row_ranges.iter().peekable();
+        let mut insertions = self.inserted_row_ranges.iter().peekable();
+        loop {
+            let deletion_row = deletions
+                .peek()
+                .map(|(position, _)| position.to_point(snapshot).row);
+            let insertion_row = insertions
+                .peek()
+                .map(|range| range.start().to_point(snapshot).row);
+            let hunk = match (deletion_row, insertion_row) {
+                (None, None) => break,
+                (Some(deletion_row), Some(insertion_row)) if deletion_row == insertion_row => {
+                    DiffHunk {
+                        deleted_rows: deletions.next().cloned(),
+                        inserted_rows: insertions.next().cloned(),
+                    }
+                }
+                (Some(deletion_row), Some(insertion_row)) if deletion_row > insertion_row => {
+                    DiffHunk {
+                        deleted_rows: None,
+                        inserted_rows: insertions.next().cloned(),
+                    }
+                }
+                (Some(_), _) => DiffHunk {
+                    deleted_rows: deletions.next().cloned(),
+                    inserted_rows: None,
+                },
+                (None, Some(_)) => DiffHunk {
+                    deleted_rows: None,
+                    inserted_rows: insertions.next().cloned(),
+                },
+            };
+            hunks.push(hunk);
+        }
+        hunks
+    }
+
+    fn remove_hunk(&mut self, hunk: &DiffHunk) {
+        if let Some(deleted_rows) = hunk.deleted_rows.as_ref() {
+            self.deleted_row_ranges
+                .retain(|(_, old_rows)| *old_rows != deleted_rows.1);
+        }
+        if let Some(inserted_rows) = hunk.inserted_rows.as_ref() {
+            self.inserted_row_ranges
+                .retain(|new_rows| new_rows != inserted_rows);
+        }
+    }
+}
+
+/// A change made by a transformation, which can be accepted or rejected on its
+/// own while the transformation is reviewed.
+#[derive(Clone, Debug, PartialEq)]
+struct DiffHunk {
+    /// Where the deleted rows are shown, and their rows in the original buffer.
+    deleted_rows: Option<(Anchor, RangeInclusive<u32>)>,
+    inserted_rows: Option<RangeInclusive<Anchor>>,
+}
+
+impl DiffHunk {
+    fn rows(&self, snapshot: &MultiBufferSnapshot) -> RangeInclusive<u32> {
+        let start = match (&self.deleted_rows, &self.inserted_rows) {
+            (_, Some(inserted_rows)) => inserted_rows.start().to_point(snapshot).row,
+            (Some((position, _)), None) => position.to_point(snapshot).row,
+            (None, None) => 0,
+        };
+        let end = self.inserted_rows.as_ref().map_or(start, |inserted_rows| {
+            inserted_rows.end().to_point(snapshot).row
+        });
+        start..=end
+    }
 }
 
 impl EventEmitter<CodegenEvent> for Codegen {}
@@ -2285,7 +2425,7 @@ impl Codegen {
         assistant_panel_context: Option<LanguageModelRequest>,
         cx: &AppContext,
     ) -> BoxFuture<'static, Result<TokenCounts>> {
-        if let Some(model) = LanguageModelRegistry::read_global(cx).active_model() {
+        if let Some(model) = LanguageModelRegistry::read_global(cx).inline_assistant_model() {
             let request =
                 self.build_request(user_prompt, assistant_panel_context.clone(), edit_range, cx);
             match request {
@@ -2318,7 +2458,7 @@ impl Codegen {
         cx: &mut ModelContext<Self>,
     ) -> Result<()> {
         let model = LanguageModelRegistry::read_global(cx)
-            .active_model()
+            .inline_assistant_model()
             .context("no active model")?;
 
         if let Some(transformation_transaction_id) = self.transformation_transaction_id.take() {
@@ -2341,7 +2481,9 @@ impl Codegen {
                 self.build_request(user_prompt, assistant_panel_context, edit_range.clone(), cx)?;
 
             let chunks =
-                cx.spawn(|_, cx| async move { model.stream_completion(request, &cx).await });
+                cx.spawn(
+                    |_, cx| async move { model.stream_completion_with_usage(request, &cx).await },
+                );
             async move { Ok(chunks.await?.boxed()) }.boxed_local()
         };
         self.handle_stream(telemetry_id, edit_range, chunks, cx);
@@ -2700,6 +2842,99 @@ impl Codegen {
         });
     }
 
+    /// Returns the hunk on the given row of the buffer, or else the first one after
+    /// it, or the last one if there are none after it.
+    fn hunk_for_row(&self, row: u32, cx: &AppContext) -> Option<DiffHunk> {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut hunks = self.diff.hunks(&snapshot);
+        let ix = hunks
+            .iter()
+            .position(|hunk| *hunk.rows(&snapshot).end() >= row)
+            .or(hunks.len().checked_sub(1))?;
+        Some(hunks.swap_remove(ix))
+    }
+
+    /// Keeps the changes of a hunk, which is no longer shown as part of the diff.
+    fn accept_hunk(&mut self, hunk: &DiffHunk, cx: &mut ModelContext<Self>) {
+        self.diff.remove_hunk(hunk);
+        cx.notify();
+    }
+
+    /// Restores the rows a hunk deleted and removes those it inserted, as part of
+    /// the transformation's transaction so that undoing it still restores the
+    /// original text.
+    fn reject_hunk(&mut self, hunk: &DiffHunk, cx: &mut ModelContext<Self>) {
+        let old_text = hunk.deleted_rows.as_ref().map(|(_, old_rows)| {
+            let end_row = *old_rows.end();
+            self.snapshot
+                .text_for_range(
+                    Point::new(*old_rows.start(), 0)
+                        ..Point::new(end_row, self.snapshot.line_len(MultiBufferRow(end_row))),
+                )
+                .collect::<String>()
+        });
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let inserted_rows = hunk.inserted_rows.as_ref().map(|inserted_rows| {
+            inserted_rows.start().to_point(&snapshot).row
+                ..=inserted_rows.end().to_point(&snapshot).row
+        });
+        let (range, text) = match (inserted_rows, old_text) {
+            (Some(inserted_rows), Some(old_text)) => {
+                let end_row = *inserted_rows.end();
+                (
+                    Point::new(*inserted_rows.start(), 0)
+                        ..Point::new(end_row, snapshot.line_len(MultiBufferRow(end_row))),
+                    old_text,
+                )
+            }
+            (Some(inserted_rows), None) => {
+                let (start_row, end_row) = inserted_rows.into_inner();
+                let range = if end_row < snapshot.max_point().row {
+                    Point::new(start_row, 0)..Point::new(end_row + 1, 0)
+                } else if start_row > 0 {
+                    // Remove the newline before the last rows, as there is none after them.
+                    let previous_row = start_row - 1;
+                    Point::new(
+                        previous_row,
+                        snapshot.line_len(MultiBufferRow(previous_row)),
+                    )..snapshot.max_point()
+                } else {
+                    Point::zero()..snapshot.max_point()
+                };
+                (range, String::new())
+            }
+            (None, Some(old_text)) => {
+                let Some((position, _)) = hunk.deleted_rows.as_ref() else {
+                    return;
+                };
+                let position = position.to_point(&snapshot);
+                // The deleted rows are shown before the start of a row, unless they
+                // were at the end of a buffer without a trailing newline.
+                let text = if position.column == 0 {
+                    old_text + "\n"
+                } else {
+                    format!("\n{old_text}")
+                };
+                (position..position, text)
+            }
+            (None, None) => return,
+        };
+
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.finalize_last_transaction(cx);
+            buffer.start_transaction(cx);
+            buffer.edit([(range, text)], None, cx);
+            if let Some(transaction) = buffer.end_transaction(cx) {
+                if let Some(first_transaction) = self.transformation_transaction_id {
+                    buffer.merge_transactions(transaction, first_transaction, cx);
+                }
+            }
+        });
+        self.diff.remove_hunk(hunk);
+        cx.notify();
+    }
+
     fn reapply_line_based_diff(
         &mut self,
         edit_range: Range<Anchor>,
@@ -3317,6 +3552,80 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_review_hunks(cx: &mut TestAppContext) {
+        cx.set_global(cx.update(SettingsStore::test));
+        cx.update(language_model::LanguageModelRegistry::test);
+        cx.update(language_settings::init);
+
+        let buffer = cx.new_model(|cx| Buffer::local("a\nb\nc\nd\ne\n", cx));
+        let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+        let range = buffer.read_with(cx, |buffer, cx| {
+            let snapshot = buffer.snapshot(cx);
+            snapshot.anchor_before(Point::new(0, 0))..snapshot.anchor_after(Point::new(3, 1))
+        });
+        let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+        let codegen = cx.new_model(|cx| {
+            Codegen::new(
+                buffer.clone(),
+                range.clone(),
+                None,
+                None,
+                prompt_builder,
+                cx,
+            )
+        });
+
+        let (chunks_tx, chunks_rx) = mpsc::unbounded();
+        codegen.update(cx, |codegen, cx| {
+            codegen.handle_stream(
+                String::new(),
+                range,
+                future::ready(Ok(chunks_rx.map(|chunk| Ok(chunk)).boxed())),
+                cx,
+            )
+        });
+        chunks_tx
+            .unbounded_send("x\na\nB\nc\nd".to_string())
+            .unwrap();
+        drop(chunks_tx);
+        cx.background_executor.run_until_parked();
+        assert_eq!(
+            buffer.read_with(cx, |buffer, cx| buffer.snapshot(cx).text()),
+            "x\na\nB\nc\nd\ne\n"
+        );
+
+        codegen.update(cx, |codegen, cx| {
+            let snapshot = buffer.read(cx).snapshot(cx);
+            let hunks = codegen.diff.hunks(&snapshot);
+            assert_eq!(hunks.len(), 2);
+            assert_eq!(hunks[0].deleted_rows, None);
+            assert_eq!(hunks[0].rows(&snapshot), 0..=0);
+            assert_eq!(hunks[1].deleted_rows.as_ref().unwrap().1, 1..=1);
+            assert_eq!(hunks[1].rows(&snapshot), 2..=2);
+
+            // The inserted line is kept, and the replaced one is restored.
+            let hunk = codegen.hunk_for_row(0, cx).unwrap();
+            assert_eq!(hunk, hunks[0]);
+            codegen.accept_hunk(&hunk, cx);
+            let hunk = codegen.hunk_for_row(0, cx).unwrap();
+            assert_eq!(hunk, hunks[1]);
+            codegen.reject_hunk(&hunk, cx);
+            assert!(codegen.diff.is_empty());
+        });
+        assert_eq!(
+            buffer.read_with(cx, |buffer, cx| buffer.snapshot(cx).text()),
+            "x\na\nb\nc\nd\ne\n"
+        );
+
+        // Undoing the transformation undoes the rejections along with it.
+        codegen.update(cx, |codegen, cx| codegen.undo(cx));
+        assert_eq!(
+            buffer.read_with(cx, |buffer, cx| buffer.snapshot(cx).text()),
+            "a\nb\nc\nd\ne\n"
+        );
+    }
+
     #[gpui::test]
     async fn test_strip_invalid_spans_from_codeblock() {
         assert_chunks("Lorem ipsum dolor", "Lorem ipsum dolor").await;
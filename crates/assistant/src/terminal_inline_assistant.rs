@@ -574,7 +574,7 @@ impl Render for PromptEditor {
                                     format!(
                                         "Using {}",
                                         LanguageModelRegistry::read_global(cx)
-                                            .active_model()
+                                            .inline_assistant_model()
                                             .map(|model| model.name().0)
                                             .unwrap_or_else(|| "No model selected".into()),
                                     ),
@@ -717,7 +717,7 @@ impl PromptEditor {
 
     fn count_tokens(&mut self, cx: &mut ViewContext<Self>) {
         let assist_id = self.id;
-        let Some(model) = LanguageModelRegistry::read_global(cx).active_model() else {
+        let Some(model) = LanguageModelRegistry::read_global(cx).inline_assistant_model() else {
             return;
         };
         self.pending_token_count = cx.spawn(|this, mut cx| async move {
@@ -856,7 +856,7 @@ impl PromptEditor {
     }
 
     fn render_token_count(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
-        let model = LanguageModelRegistry::read_global(cx).active_model()?;
+        let model = LanguageModelRegistry::read_global(cx).inline_assistant_model()?;
         let token_count = self.token_count?;
         let max_token_count = model.max_token_count();
 
@@ -998,7 +998,7 @@ impl Codegen {
     }
 
     pub fn start(&mut self, prompt: LanguageModelRequest, cx: &mut ModelContext<Self>) {
-        let Some(model) = LanguageModelRegistry::read_global(cx).active_model() else {
+        let Some(model) = LanguageModelRegistry::read_global(cx).inline_assistant_model() else {
             return;
         };
 
@@ -1007,7 +1007,7 @@ impl Codegen {
         self.transaction = Some(TerminalTransaction::start(self.terminal.clone()));
         self.generation = cx.spawn(|this, mut cx| async move {
             let model_telemetry_id = model.telemetry_id();
-            let response = model.stream_completion(prompt, &cx).await;
+            let response = model.stream_completion_with_usage(prompt, &cx).await;
             let generate = async {
                 let (mut hunks_tx, mut hunks_rx) = mpsc::channel(1);
 
@@ -0,0 +1,441 @@
+//! A journal of the unsaved changes of buffers, from which they are recovered after
+//! Zed crashes, once they're reviewed.
+mod buffer_recovery_settings;
+mod journal;
+mod recovery_review;
+
+use collections::HashMap;
+use gpui::{
+    actions, AppContext, Context as _, EntityId, Global, Model, ModelContext, Subscription, Task,
+    ViewContext, WeakModel,
+};
+use language::Buffer;
+use project::{buffer_store::BufferStoreEvent, Project};
+use settings::Settings as _;
+use std::{mem, sync::Arc, time::Duration};
+use util::ResultExt as _;
+use workspace::{
+    notifications::{simple_message_notification::MessageNotification, NotificationId},
+    AppState, Workspace,
+};
+
+pub use buffer_recovery_settings::{BufferRecoverySettings, BufferRecoverySettingsContent};
+pub use journal::{Delta, DeltaEdit, Journal, RecoveredBuffer};
+pub use recovery_review::RecoveryReview;
+
+actions!(
+    buffer_recovery,
+    [ReviewRecoveredChanges, DiscardRecoveredChanges]
+);
+
+/// How many deltas are written to a buffer's journal before its full text is written
+/// again, so that recovering it doesn't replay too many deltas.
+const MAX_DELTAS_PER_CHECKPOINT: u64 = 64;
+
+/// The shortest interval between writes to the journal.
+const MIN_JOURNAL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub fn init(app_state: &Arc<AppState>, cx: &mut AppContext) {
+    BufferRecoverySettings::register(cx);
+
+    let session_id: Arc<str> = app_state.session.read(cx).id().into();
+    let journal = Journal::new(app_state.fs.clone(), paths::recovery_dir().clone());
+    let buffer_recovery = cx.new_model(|cx| BufferRecovery::new(journal, session_id, cx));
+    cx.set_global(GlobalBufferRecovery(buffer_recovery.clone()));
+
+    cx.observe_new_views(
+        move |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
+            buffer_recovery.update(cx, |buffer_recovery, cx| {
+                buffer_recovery.track_project(workspace.project(), cx)
+            });
+            RecoveryReview::register(workspace, cx);
+            if !buffer_recovery.read(cx).recovered.is_empty() {
+                show_recovery_notification(workspace, cx);
+            }
+        },
+    )
+    .detach();
+}
+
+struct GlobalBufferRecovery(Model<BufferRecovery>);
+
+impl Global for GlobalBufferRecovery {}
+
+/// Journals the unsaved changes of the buffers of every project, and holds the
+/// changes recovered from sessions that crashed.
+pub struct BufferRecovery {
+    journal: Arc<Journal>,
+    session_id: Arc<str>,
+    buffers: HashMap<EntityId, JournaledBuffer>,
+    /// The buffers whose journals are removed on the next write, as their changes
+    /// were saved or discarded.
+    removed_buffers: Vec<u64>,
+    recovered: Vec<RecoveredBuffer>,
+    _write_task: Task<()>,
+    _quit_subscription: Subscription,
+}
+
+struct JournaledBuffer {
+    buffer: WeakModel<Buffer>,
+    /// The version of the buffer at its latest write to the journal, or `None` when
+    /// the buffer has no journal.
+    version: Option<clock::Global>,
+    /// The sequence number of the buffer's latest write to the journal.
+    seq: u64,
+    /// How many deltas were written since the latest checkpoint.
+    deltas_since_checkpoint: u64,
+    _subscriptions: [Subscription; 2],
+}
+
+/// A write to the journal of a buffer.
+enum JournalWrite {
+    Checkpoint {
+        buffer_id: u64,
+        abs_path: Option<std::path::PathBuf>,
+        text: String,
+        seq: u64,
+    },
+    Delta {
+        buffer_id: u64,
+        seq: u64,
+        delta: Delta,
+    },
+}
+
+impl BufferRecovery {
+    fn new(journal: Journal, session_id: Arc<str>, cx: &mut ModelContext<Self>) -> Self {
+        let journal = Arc::new(journal);
+
+        let recover = cx.background_executor().spawn({
+            let journal = journal.clone();
+            let session_id = session_id.clone();
+            async move { journal.recover(&session_id).await }
+        });
+        cx.spawn(|this, mut cx| async move {
+            let recovered = recover.await?;
+            if !recovered.is_empty() {
+                this.update(&mut cx, |this, cx| {
+                    this.recovered = recovered;
+                    cx.notify();
+                })?;
+                cx.update(|cx| {
+                    let workspace = cx.active_window()?.downcast::<Workspace>()?;
+                    workspace
+                        .update(cx, |workspace, cx| {
+                            show_recovery_notification(workspace, cx)
+                        })
+                        .log_err()
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+
+        // Writes are made one after the other, so that a buffer's journal is written
+        // in order.
+        let write_task = cx.spawn(|this, mut cx| async move {
+            loop {
+                let Ok(interval) = this.update(&mut cx, |_, cx| {
+                    let interval = BufferRecoverySettings::get_global(cx).journal_interval_ms;
+                    Duration::from_millis(interval).max(MIN_JOURNAL_INTERVAL)
+                }) else {
+                    break;
+                };
+                cx.background_executor().timer(interval).await;
+                let Ok(write) = this.update(&mut cx, |this, cx| this.write_journal(cx)) else {
+                    break;
+                };
+                write.await;
+            }
+        });
+
+        // The journals of a session that ends cleanly have nothing to recover, as
+        // the unsaved changes were either saved, discarded, or kept by restoring
+        // unsaved buffers.
+        let quit_subscription = cx.on_app_quit(|this, cx| {
+            let journal = this.journal.clone();
+            let session_id = this.session_id.clone();
+            cx.background_executor().spawn(async move {
+                journal.remove_session(&session_id).await.log_err();
+            })
+        });
+
+        Self {
+            journal,
+            session_id,
+            buffers: HashMap::default(),
+            removed_buffers: Vec::new(),
+            recovered: Vec::new(),
+            _write_task: write_task,
+            _quit_subscription: quit_subscription,
+        }
+    }
+
+    pub fn global(cx: &AppContext) -> Option<Model<Self>> {
+        cx.try_global::<GlobalBufferRecovery>()
+            .map(|buffer_recovery| buffer_recovery.0.clone())
+    }
+
+    /// The unsaved changes recovered from sessions that crashed, which weren't
+    /// restored or discarded yet.
+    pub fn recovered(&self) -> &[RecoveredBuffer] {
+        &self.recovered
+    }
+
+    /// Removes the journal of a recovered buffer, which was restored or discarded.
+    pub fn discard(&mut self, buffer: &RecoveredBuffer, cx: &mut ModelContext<Self>) {
+        self.recovered.retain(|recovered| recovered != buffer);
+        let journal = self.journal.clone();
+        let buffer = buffer.clone();
+        cx.background_executor()
+            .spawn(async move { journal.discard(&buffer).await.log_err() })
+            .detach();
+        cx.notify();
+    }
+
+    pub fn discard_all(&mut self, cx: &mut ModelContext<Self>) {
+        for buffer in self.recovered.clone() {
+            self.discard(&buffer, cx);
+        }
+    }
+
+    /// Loads the text of a recovered buffer's file, to compare it with the buffer.
+    pub fn load_file_text(&self, buffer: &RecoveredBuffer, cx: &AppContext) -> Task<String> {
+        let journal = self.journal.clone();
+        let buffer = buffer.clone();
+        cx.background_executor()
+            .spawn(async move { journal.load_file_text(&buffer).await })
+    }
+
+    fn track_project(&mut self, project: &Model<Project>, cx: &mut ModelContext<Self>) {
+        for buffer in project.read(cx).opened_buffers(cx) {
+            self.track_buffer(&buffer, cx);
+        }
+        let buffer_store = project.read(cx).buffer_store();
+        cx.subscribe(&buffer_store, |this, _, event, cx| {
+            if let BufferStoreEvent::BufferAdded(buffer) = event {
+                this.track_buffer(buffer, cx);
+            }
+        })
+        .detach();
+    }
+
+    fn track_buffer(&mut self, buffer: &Model<Buffer>, cx: &mut ModelContext<Self>) {
+        let entity_id = buffer.entity_id();
+        if self.buffers.contains_key(&entity_id) {
+            return;
+        }
+        let subscriptions = [
+            cx.observe_release(buffer, move |this, _, _| {
+                if let Some(journaled) = this.buffers.remove(&entity_id) {
+                    if journaled.version.is_some() {
+                        this.removed_buffers.push(entity_id.as_u64());
+                    }
+                }
+            }),
+            cx.subscribe(buffer, move |this, _, event, _| {
+                // A checkpoint records the new path of a buffer whose file changed.
+                if let language::Event::FileHandleChanged = event {
+                    if let Some(journaled) = this.buffers.get_mut(&entity_id) {
+                        journaled.deltas_since_checkpoint = MAX_DELTAS_PER_CHECKPOINT;
+                    }
+                }
+            }),
+        ];
+        self.buffers.insert(
+            entity_id,
+            JournaledBuffer {
+                buffer: buffer.downgrade(),
+                version: None,
+                seq: 0,
+                deltas_since_checkpoint: 0,
+                _subscriptions: subscriptions,
+            },
+        );
+    }
+
+    /// Writes the changes made to dirty buffers since their latest write, and
+    /// removes the journals of the buffers that aren't dirty anymore.
+    fn write_journal(&mut self, cx: &mut ModelContext<Self>) -> Task<()> {
+        let enabled = BufferRecoverySettings::get_global(cx).enabled;
+        let mut removed_buffers = mem::take(&mut self.removed_buffers);
+        let mut writes = Vec::new();
+        for (entity_id, journaled) in &mut self.buffers {
+            let Some(buffer) = journaled.buffer.upgrade() else {
+                continue;
+            };
+            let buffer = buffer.read(cx);
+            let abs_path = match buffer.file() {
+                Some(file) => match file.as_local() {
+                    Some(file) => Some(file.abs_path(cx)),
+                    // The changes to remote buffers are kept by their hosts.
+                    None => continue,
+                },
+                None => None,
+            };
+            // The journal is stored in plain text, so the changes to private files,
+            // like `.env` files and keys, aren't written to it.
+            let is_private = buffer.file().map_or(false, |file| file.is_private());
+            if !enabled || is_private || !buffer.is_dirty() {
+                if journaled.version.take().is_some() {
+                    removed_buffers.push(entity_id.as_u64());
+                }
+                continue;
+            }
+
+            let snapshot = buffer.text_snapshot();
+            if journaled.version.as_ref() == Some(snapshot.version()) {
+                continue;
+            }
+            journaled.seq += 1;
+            let buffer_id = entity_id.as_u64();
+            let seq = journaled.seq;
+            let write = match &journaled.version {
+                Some(version) if journaled.deltas_since_checkpoint < MAX_DELTAS_PER_CHECKPOINT => {
+                    journaled.deltas_since_checkpoint += 1;
+                    let edits = snapshot
+                        .edits_since::<usize>(version)
+                        .map(|edit| DeltaEdit {
+                            range: edit.old,
+                            text: snapshot.text_for_range(edit.new).collect(),
+                        })
+                        .collect();
+                    JournalWrite::Delta {
+                        buffer_id,
+                        seq,
+                        delta: Delta { edits },
+                    }
+                }
+                _ => {
+                    journaled.deltas_since_checkpoint = 0;
+                    JournalWrite::Checkpoint {
+                        buffer_id,
+                        abs_path,
+                        text: snapshot.text(),
+                        seq,
+                    }
+                }
+            };
+            journaled.version = Some(snapshot.version().clone());
+            writes.push(write);
+        }
+
+        if removed_buffers.is_empty() && writes.is_empty() {
+            return Task::ready(());
+        }
+        let journal = self.journal.clone();
+        let session_id = self.session_id.clone();
+        cx.background_executor().spawn(async move {
+            for buffer_id in removed_buffers {
+                journal.remove(&session_id, buffer_id).await.log_err();
+            }
+            for write in writes {
+                match write {
+                    JournalWrite::Checkpoint {
+                        buffer_id,
+                        abs_path,
+                        text,
+                        seq,
+                    } => journal
+                        .write_checkpoint(&session_id, buffer_id, abs_path, text, seq)
+                        .await
+                        .log_err(),
+                    JournalWrite::Delta {
+                        buffer_id,
+                        seq,
+                        delta,
+                    } => journal
+                        .write_delta(&session_id, buffer_id, seq, &delta)
+                        .await
+                        .log_err(),
+                };
+            }
+        })
+    }
+}
+
+struct RecoveryNotification;
+
+fn show_recovery_notification(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let Some(buffer_recovery) = BufferRecovery::global(cx) else {
+        return;
+    };
+    let count = buffer_recovery.read(cx).recovered.len();
+    let weak_workspace = cx.view().downgrade();
+    workspace.show_notification_once(NotificationId::unique::<RecoveryNotification>(), cx, |cx| {
+        cx.new_view(|_| {
+            let files = if count == 1 { "file" } else { "files" };
+            MessageNotification::new(format!(
+                "Zed quit unexpectedly, with unsaved changes to {count} {files}. \
+                    Review them to compare them with the files, and restore them."
+            ))
+            .with_click_message("Review Changes")
+            .on_click(move |cx| {
+                weak_workspace
+                    .update(cx, |workspace, cx| RecoveryReview::toggle(workspace, cx))
+                    .log_err();
+            })
+            .with_secondary_click_message("Discard Changes")
+            .on_secondary_click(move |cx| {
+                buffer_recovery.update(cx, |buffer_recovery, cx| buffer_recovery.discard_all(cx))
+            })
+        })
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::{SemanticVersion, TestAppContext};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::{Path, PathBuf};
+
+    #[gpui::test]
+    async fn test_private_buffers_are_not_journaled(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            release_channel::init(SemanticVersion::default(), cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            BufferRecoverySettings::register(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/project", json!({ "a.txt": "one", ".env": "SECRET=one" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/project".as_ref()], cx).await;
+        let journal = Journal::new(fs.clone(), PathBuf::from("/recovery"));
+        let buffer_recovery = cx.new_model(|cx| BufferRecovery::new(journal, "current".into(), cx));
+        buffer_recovery.update(cx, |buffer_recovery, cx| {
+            buffer_recovery.track_project(&project, cx)
+        });
+
+        for (path, text) in [("a.txt", "two"), (".env", "SECRET=two")] {
+            let buffer = project
+                .update(cx, |project, cx| {
+                    project.open_local_buffer(Path::new("/project").join(path), cx)
+                })
+                .await
+                .unwrap();
+            buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
+        }
+        buffer_recovery
+            .update(cx, |buffer_recovery, cx| buffer_recovery.write_journal(cx))
+            .await;
+
+        let recovered = Journal::new(fs.clone(), PathBuf::from("/recovery"))
+            .recover("next")
+            .await
+            .unwrap();
+        assert_eq!(
+            recovered
+                .iter()
+                .map(|buffer| (buffer.abs_path.clone(), buffer.text.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(Some(PathBuf::from("/project/a.txt")), "two")]
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use anyhow;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct BufferRecoverySettings {
+    pub enabled: bool,
+    pub journal_interval_ms: u64,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct BufferRecoverySettingsContent {
+    /// Whether to journal the unsaved changes of buffers, so that they can be
+    /// recovered after Zed crashes.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// How often the unsaved changes are written to the journal, in milliseconds.
+    ///
+    /// Default: 2000
+    pub journal_interval_ms: Option<u64>,
+}
+
+impl Settings for BufferRecoverySettings {
+    const KEY: Option<&'static str> = Some("buffer_recovery");
+
+    type FileContent = BufferRecoverySettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
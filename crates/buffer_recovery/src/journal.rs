@@ -0,0 +1,383 @@
+use anyhow::{anyhow, Context as _, Result};
+use fs::{Fs, RemoveOptions};
+use futures::StreamExt as _;
+use serde::{Deserialize, Serialize};
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use util::ResultExt as _;
+
+const CHECKPOINT_FILE_NAME: &str = "checkpoint.json";
+const DELTA_EXTENSION: &str = "delta";
+
+/// The full text of a buffer, from which the deltas written after it are replayed.
+#[derive(Debug, Serialize, Deserialize)]
+struct Checkpoint {
+    /// The path of the buffer's file, or `None` for an untitled buffer.
+    abs_path: Option<PathBuf>,
+    text: String,
+    /// The sequence number of the last write that the text includes.
+    seq: u64,
+}
+
+/// The edits made to a buffer between two writes to its journal.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Delta {
+    /// The edits, sorted by their ranges, which don't overlap.
+    pub edits: Vec<DeltaEdit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeltaEdit {
+    /// The range of the replaced text, in the text from before the delta.
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+impl Delta {
+    fn apply(&self, text: &mut String) -> Result<()> {
+        // Edits are applied from the last one, so that the ranges of the earlier
+        // ones stay valid.
+        for edit in self.edits.iter().rev() {
+            let range = edit.range.clone();
+            if range.start > range.end
+                || range.end > text.len()
+                || !text.is_char_boundary(range.start)
+                || !text.is_char_boundary(range.end)
+            {
+                return Err(anyhow!("edit range {range:?} is out of bounds"));
+            }
+            text.replace_range(range, &edit.text);
+        }
+        Ok(())
+    }
+}
+
+/// The unsaved changes of a buffer, from a session that ended without removing its
+/// journal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveredBuffer {
+    /// The path of the buffer's file, or `None` for an untitled buffer.
+    pub abs_path: Option<PathBuf>,
+    /// The text of the buffer at its last write to the journal.
+    pub text: String,
+    dir: PathBuf,
+}
+
+/// A write-ahead journal of the unsaved changes of buffers.
+///
+/// Each session has a directory, with one directory per buffer within it. A buffer's
+/// journal is a checkpoint of its full text, followed by the deltas written after
+/// it, each in a file of its own, so that a crash mid-write loses the latest write
+/// at most. Sessions remove their journals when they end cleanly, so the journals
+/// left behind are those of sessions that crashed.
+pub struct Journal {
+    fs: Arc<dyn Fs>,
+    root: PathBuf,
+}
+
+impl Journal {
+    pub fn new(fs: Arc<dyn Fs>, root: PathBuf) -> Self {
+        Self { fs, root }
+    }
+
+    /// Writes the full text of the buffer, replacing the writes up to `seq`.
+    pub async fn write_checkpoint(
+        &self,
+        session_id: &str,
+        buffer_id: u64,
+        abs_path: Option<PathBuf>,
+        text: String,
+        seq: u64,
+    ) -> Result<()> {
+        let dir = self.buffer_dir(session_id, buffer_id);
+        self.fs.create_dir(&dir).await?;
+        let checkpoint = Checkpoint {
+            abs_path,
+            text,
+            seq,
+        };
+        self.fs
+            .atomic_write(
+                dir.join(CHECKPOINT_FILE_NAME),
+                serde_json::to_string(&checkpoint)?,
+            )
+            .await
+            .context("writing journal checkpoint")?;
+        for (delta_seq, path) in self.delta_paths(&dir).await? {
+            if delta_seq <= seq {
+                self.fs.remove_file(&path, remove_options()).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the edits made to the buffer since its previous write, `seq - 1`.
+    pub async fn write_delta(
+        &self,
+        session_id: &str,
+        buffer_id: u64,
+        seq: u64,
+        delta: &Delta,
+    ) -> Result<()> {
+        let dir = self.buffer_dir(session_id, buffer_id);
+        self.fs.create_dir(&dir).await?;
+        self.fs
+            .atomic_write(
+                dir.join(format!("{seq:010}.{DELTA_EXTENSION}")),
+                serde_json::to_string(delta)?,
+            )
+            .await
+            .context("writing journal delta")
+    }
+
+    /// Removes the journal of a buffer, whose changes were saved or discarded.
+    pub async fn remove(&self, session_id: &str, buffer_id: u64) -> Result<()> {
+        self.fs
+            .remove_dir(&self.buffer_dir(session_id, buffer_id), remove_options())
+            .await
+    }
+
+    /// Removes the journals of a session.
+    pub async fn remove_session(&self, session_id: &str) -> Result<()> {
+        self.fs
+            .remove_dir(&self.root.join(session_id), remove_options())
+            .await
+    }
+
+    /// Replays the journals left behind by sessions other than the current one. The
+    /// buffers whose text is the same as their file's, or that are empty and
+    /// untitled, have nothing to recover and are removed.
+    pub async fn recover(&self, current_session_id: &str) -> Result<Vec<RecoveredBuffer>> {
+        let mut recovered = Vec::new();
+        if !self.fs.is_dir(&self.root).await {
+            return Ok(recovered);
+        }
+        for session_dir in self.read_dir(&self.root).await? {
+            if session_dir.file_name() == Some(current_session_id.as_ref()) {
+                continue;
+            }
+            for dir in self.read_dir(&session_dir).await? {
+                match self.replay(&dir).await {
+                    Ok(Some(buffer)) => recovered.push(buffer),
+                    Ok(None) => self.fs.remove_dir(&dir, remove_options()).await?,
+                    Err(error) => {
+                        log::error!("failed to replay the journal in {dir:?}: {error:?}");
+                    }
+                }
+            }
+            self.remove_if_empty(&session_dir).await?;
+        }
+        recovered.sort_by(|a, b| a.abs_path.cmp(&b.abs_path));
+        Ok(recovered)
+    }
+
+    /// Removes the journal of a recovered buffer, once it was restored or discarded.
+    pub async fn discard(&self, buffer: &RecoveredBuffer) -> Result<()> {
+        self.fs.remove_dir(&buffer.dir, remove_options()).await?;
+        if let Some(session_dir) = buffer.dir.parent() {
+            self.remove_if_empty(session_dir).await?;
+        }
+        Ok(())
+    }
+
+    /// Loads the text of a recovered buffer's file, which is empty for untitled
+    /// buffers and deleted files.
+    pub async fn load_file_text(&self, buffer: &RecoveredBuffer) -> String {
+        match &buffer.abs_path {
+            Some(abs_path) if self.fs.is_file(abs_path).await => {
+                self.fs.load(abs_path).await.log_err().unwrap_or_default()
+            }
+            _ => String::new(),
+        }
+    }
+
+    async fn replay(&self, dir: &Path) -> Result<Option<RecoveredBuffer>> {
+        let checkpoint_path = dir.join(CHECKPOINT_FILE_NAME);
+        if !self.fs.is_file(&checkpoint_path).await {
+            // The session crashed before its first write finished.
+            return Ok(None);
+        }
+        let checkpoint: Checkpoint = serde_json::from_str(&self.fs.load(&checkpoint_path).await?)
+            .with_context(|| format!("parsing {checkpoint_path:?}"))?;
+        let mut text = checkpoint.text;
+        for (seq, path) in self.delta_paths(dir).await? {
+            if seq <= checkpoint.seq {
+                continue;
+            }
+            let delta: Delta = serde_json::from_str(&self.fs.load(&path).await?)
+                .with_context(|| format!("parsing {path:?}"))?;
+            delta.apply(&mut text)?;
+        }
+
+        let buffer = RecoveredBuffer {
+            abs_path: checkpoint.abs_path,
+            text,
+            dir: dir.to_path_buf(),
+        };
+        let is_unchanged = match &buffer.abs_path {
+            Some(_) => self.load_file_text(&buffer).await == buffer.text,
+            None => buffer.text.is_empty(),
+        };
+        Ok((!is_unchanged).then_some(buffer))
+    }
+
+    /// The delta files in the directory, sorted by their sequence numbers.
+    async fn delta_paths(&self, dir: &Path) -> Result<Vec<(u64, PathBuf)>> {
+        let mut deltas = self
+            .read_dir(dir)
+            .await?
+            .into_iter()
+            .filter(|path| path.extension() == Some(DELTA_EXTENSION.as_ref()))
+            .filter_map(|path| {
+                let seq = path.file_stem()?.to_str()?.parse().ok()?;
+                Some((seq, path))
+            })
+            .collect::<Vec<_>>();
+        deltas.sort();
+        Ok(deltas)
+    }
+
+    async fn read_dir(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut entries = self.fs.read_dir(dir).await?;
+        let mut paths = Vec::new();
+        while let Some(path) = entries.next().await {
+            paths.push(path?);
+        }
+        Ok(paths)
+    }
+
+    async fn remove_if_empty(&self, dir: &Path) -> Result<()> {
+        if self.read_dir(dir).await?.is_empty() {
+            self.fs.remove_dir(dir, remove_options()).await?;
+        }
+        Ok(())
+    }
+
+    fn buffer_dir(&self, session_id: &str, buffer_id: u64) -> PathBuf {
+        self.root.join(session_id).join(buffer_id.to_string())
+    }
+}
+
+fn remove_options() -> RemoveOptions {
+    RemoveOptions {
+        recursive: true,
+        ignore_if_not_exists: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    fn edit(range: Range<usize>, text: &str) -> DeltaEdit {
+        DeltaEdit {
+            range,
+            text: text.to_string(),
+        }
+    }
+
+    #[gpui::test]
+    async fn test_journal(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/project", json!({ "a.txt": "one two", "b.txt": "saved" }))
+            .await;
+        let journal = Journal::new(fs.clone(), PathBuf::from("/recovery"));
+        let a = Some(PathBuf::from("/project/a.txt"));
+        let b = Some(PathBuf::from("/project/b.txt"));
+
+        journal
+            .write_checkpoint("crashed", 1, a.clone(), "one two".into(), 0)
+            .await
+            .unwrap();
+        let delta = Delta {
+            edits: vec![edit(0..3, "1"), edit(7..7, " three")],
+        };
+        journal.write_delta("crashed", 1, 1, &delta).await.unwrap();
+        let delta = Delta {
+            edits: vec![edit(0..1, "zero")],
+        };
+        journal.write_delta("crashed", 1, 2, &delta).await.unwrap();
+        // Buffers whose text is the same as their file's, or that are empty and
+        // untitled, aren't recovered.
+        journal
+            .write_checkpoint("crashed", 2, b.clone(), "saved".into(), 0)
+            .await
+            .unwrap();
+        journal
+            .write_checkpoint("crashed", 3, None, String::new(), 0)
+            .await
+            .unwrap();
+        journal
+            .write_checkpoint("crashed", 4, None, "draft".into(), 0)
+            .await
+            .unwrap();
+        // The journals of the current session aren't recovered.
+        journal
+            .write_checkpoint("current", 1, a.clone(), "current".into(), 0)
+            .await
+            .unwrap();
+
+        let recovered = journal.recover("current").await.unwrap();
+        assert_eq!(
+            recovered
+                .iter()
+                .map(|buffer| (buffer.abs_path.clone(), buffer.text.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(None, "draft"), (a.clone(), "zero two three")]
+        );
+        assert!(!fs.is_dir(Path::new("/recovery/crashed/2")).await);
+        assert!(!fs.is_dir(Path::new("/recovery/crashed/3")).await);
+
+        // A checkpoint replaces the deltas it includes.
+        journal
+            .write_checkpoint("current", 1, a.clone(), "checkpoint".into(), 2)
+            .await
+            .unwrap();
+        journal
+            .write_delta(
+                "current",
+                1,
+                3,
+                &Delta {
+                    edits: vec![edit(0..0, "a ")],
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            journal
+                .delta_paths(Path::new("/recovery/current/1"))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        let recovered_current = journal.recover("other").await.unwrap();
+        assert!(recovered_current
+            .iter()
+            .any(|buffer| buffer.text == "a checkpoint"));
+
+        // Discarding the last recovered buffer of a session removes the session.
+        for buffer in &recovered {
+            journal.discard(buffer).await.unwrap();
+        }
+        assert!(!fs.is_dir(Path::new("/recovery/crashed")).await);
+        journal.remove_session("current").await.unwrap();
+        assert!(journal.recover("current").await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_delta_out_of_bounds() {
+        let mut text = "abc".to_string();
+        let delta = Delta {
+            edits: vec![edit(1..2, "x"), edit(2..5, "y")],
+        };
+        assert!(delta.apply(&mut text).is_err());
+    }
+}
@@ -0,0 +1,321 @@
+use crate::{BufferRecovery, DiscardRecoveredChanges, RecoveredBuffer, ReviewRecoveredChanges};
+use anyhow::anyhow;
+use editor::{Editor, MultiBuffer};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, ParentElement,
+    Render, Styled, Task, View, ViewContext, VisualContext, WeakView,
+};
+use language::Buffer;
+use picker::{Picker, PickerDelegate};
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt as _;
+use workspace::{ModalView, Workspace};
+
+/// A modal listing the unsaved changes recovered after a crash, from which they can
+/// be compared with their files and restored.
+pub struct RecoveryReview {
+    picker: View<Picker<RecoveryReviewDelegate>>,
+}
+
+impl RecoveryReview {
+    pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &ReviewRecoveredChanges, cx| {
+            Self::toggle(workspace, cx);
+        });
+        workspace.register_action(|_, _: &DiscardRecoveredChanges, cx| {
+            if let Some(buffer_recovery) = BufferRecovery::global(cx) {
+                buffer_recovery.update(cx, |buffer_recovery, cx| buffer_recovery.discard_all(cx));
+            }
+        });
+    }
+
+    pub(crate) fn toggle(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+        let Some(buffer_recovery) = BufferRecovery::global(cx) else {
+            return;
+        };
+        let weak_workspace = cx.view().downgrade();
+        workspace.toggle_modal(cx, |cx| Self::new(weak_workspace, buffer_recovery, cx));
+    }
+
+    fn new(
+        workspace: WeakView<Workspace>,
+        buffer_recovery: Model<BufferRecovery>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let recovered = buffer_recovery.read(cx).recovered().to_vec();
+        let candidates = recovered
+            .iter()
+            .enumerate()
+            .map(|(ix, buffer)| StringMatchCandidate::new(ix, buffer_label(buffer)))
+            .collect();
+        let delegate = RecoveryReviewDelegate {
+            review: cx.view().downgrade(),
+            workspace,
+            buffer_recovery,
+            recovered,
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for RecoveryReview {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for RecoveryReview {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for RecoveryReview {}
+impl ModalView for RecoveryReview {}
+
+pub struct RecoveryReviewDelegate {
+    review: WeakView<RecoveryReview>,
+    workspace: WeakView<Workspace>,
+    buffer_recovery: Model<BufferRecovery>,
+    recovered: Vec<RecoveredBuffer>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl RecoveryReviewDelegate {
+    /// Opens the recovered text in a read-only editor, showing how it differs from
+    /// the file's.
+    fn compare(&self, recovered: RecoveredBuffer, cx: &mut ViewContext<Picker<Self>>) {
+        let load = self.buffer_recovery.read(cx).load_file_text(&recovered, cx);
+        let workspace = self.workspace.clone();
+        cx.spawn(|_, mut cx| async move {
+            let file_text = load.await;
+            let language =
+                match &recovered.abs_path {
+                    Some(abs_path) => workspace
+                        .update(&mut cx, |workspace, cx| {
+                            let languages = workspace.project().read(cx).languages().clone();
+                            let abs_path = abs_path.clone();
+                            async move { languages.language_for_file_path(&abs_path).await.ok() }
+                        })?
+                        .await,
+                    None => None,
+                };
+            workspace.update(&mut cx, |workspace, cx| {
+                let project = workspace.project().clone();
+                let recovered_buffer = cx.new_model(|cx| {
+                    let mut buffer = Buffer::local(recovered.text.clone(), cx);
+                    buffer.set_language_registry(project.read(cx).languages().clone());
+                    buffer.set_language(language, cx);
+                    buffer.set_diff_base(Some(file_text), cx);
+                    buffer
+                });
+                let multibuffer = cx.new_model(|cx| {
+                    MultiBuffer::singleton(recovered_buffer, cx)
+                        .with_title(format!("{} (recovered)", buffer_title(&recovered)))
+                });
+                let editor = cx.new_view(|cx| {
+                    let mut editor = Editor::for_multibuffer(multibuffer, Some(project), true, cx);
+                    editor.set_read_only(true);
+                    editor
+                });
+                workspace.add_item_to_active_pane(Box::new(editor), None, true, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Opens the recovered buffer's file, or a new untitled buffer, and replaces its
+    /// text with the recovered text, as an edit that can be undone.
+    fn restore(&self, recovered: RecoveredBuffer, cx: &mut ViewContext<Picker<Self>>) {
+        let workspace = self.workspace.clone();
+        let buffer_recovery = self.buffer_recovery.clone();
+        cx.spawn(|_, mut cx| async move {
+            let buffer = match &recovered.abs_path {
+                Some(abs_path) => {
+                    let item = workspace
+                        .update(&mut cx, |workspace, cx| {
+                            workspace.open_abs_path(abs_path.clone(), true, cx)
+                        })?
+                        .await?;
+                    let editor = item
+                        .downcast::<Editor>()
+                        .ok_or_else(|| anyhow!("{abs_path:?} wasn't opened in an editor"))?;
+                    editor
+                        .update(&mut cx, |editor, cx| {
+                            editor.buffer().read(cx).as_singleton()
+                        })?
+                        .ok_or_else(|| anyhow!("{abs_path:?} wasn't opened in a buffer"))?
+                }
+                None => {
+                    let buffer = workspace
+                        .update(&mut cx, |workspace, cx| {
+                            workspace
+                                .project()
+                                .update(cx, |project, cx| project.create_buffer(cx))
+                        })?
+                        .await?;
+                    workspace.update(&mut cx, |workspace, cx| {
+                        let project = workspace.project().clone();
+                        let editor =
+                            cx.new_view(|cx| Editor::for_buffer(buffer.clone(), Some(project), cx));
+                        workspace.add_item_to_active_pane(Box::new(editor), None, true, cx);
+                    })?;
+                    buffer
+                }
+            };
+
+            let diff = buffer
+                .update(&mut cx, |buffer, cx| {
+                    buffer.diff(recovered.text.clone(), cx)
+                })?
+                .await;
+            buffer.update(&mut cx, |buffer, cx| {
+                buffer.finalize_last_transaction();
+                buffer.apply_diff(diff, cx);
+                buffer.finalize_last_transaction();
+            })?;
+            buffer_recovery.update(&mut cx, |buffer_recovery, cx| {
+                buffer_recovery.discard(&recovered, cx)
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+impl PickerDelegate for RecoveryReviewDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Search recovered files...".into()
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        if self.recovered.is_empty() {
+            "No unsaved changes to recover".into()
+        } else {
+            "No matches".into()
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(mat) = self.matches.get(self.selected_index) {
+            let recovered = self.recovered[mat.candidate_id].clone();
+            if secondary {
+                self.restore(recovered, cx);
+            } else {
+                self.compare(recovered, cx);
+            }
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.review
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let recovered = &self.recovered[mat.candidate_id];
+        let line_count = recovered.text.lines().count();
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                ))
+                .end_slot(
+                    Label::new(format!("{line_count} lines"))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+        )
+    }
+}
+
+/// The path of a recovered buffer's file, or "untitled".
+fn buffer_label(buffer: &RecoveredBuffer) -> String {
+    buffer.abs_path.as_ref().map_or_else(
+        || "untitled".to_string(),
+        |abs_path| abs_path.to_string_lossy().into_owned(),
+    )
+}
+
+fn buffer_title(buffer: &RecoveredBuffer) -> String {
+    buffer
+        .abs_path
+        .as_ref()
+        .and_then(|abs_path| abs_path.file_name())
+        .map_or_else(
+            || "untitled".to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        )
+}
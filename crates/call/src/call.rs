@@ -18,8 +18,9 @@ use room::Event;
 use settings::Settings;
 use std::sync::Arc;
 
+pub use live_kit_client::ScreenRegion;
 pub use participant::ParticipantLocation;
-pub use room::Room;
+pub use room::{Room, ScreenShareSource};
 
 struct GlobalActiveCall(Model<ActiveCall>);
 
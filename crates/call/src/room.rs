@@ -10,12 +10,15 @@ use client::{
 };
 use collections::{BTreeMap, HashMap, HashSet};
 use fs::Fs;
-use futures::{FutureExt, StreamExt};
+use futures::{future::LocalBoxFuture, FutureExt, StreamExt};
 use gpui::{
     AppContext, AsyncAppContext, Context, EventEmitter, Model, ModelContext, Task, WeakModel,
 };
 use language::LanguageRegistry;
-use live_kit_client::{LocalAudioTrack, LocalTrackPublication, LocalVideoTrack, RoomUpdate};
+use live_kit_client::{
+    LocalAudioTrack, LocalTrackPublication, LocalVideoTrack, MacOSDisplay, MacOSWindow, RoomUpdate,
+    ScreenRegion,
+};
 use postage::{sink::Sink, stream::Stream, watch};
 use project::Project;
 use settings::Settings as _;
@@ -24,6 +27,29 @@ use util::{post_inc, ResultExt, TryFutureExt};
 
 pub const RECONNECT_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// What to share when sharing the screen.
+#[derive(Clone)]
+pub enum ScreenShareSource {
+    Display(MacOSDisplay),
+    Window(MacOSWindow),
+    Region {
+        display: MacOSDisplay,
+        region: ScreenRegion,
+    },
+}
+
+impl ScreenShareSource {
+    fn local_track(&self) -> LocalVideoTrack {
+        match self {
+            Self::Display(display) => LocalVideoTrack::screen_share_for_display(display),
+            Self::Window(window) => LocalVideoTrack::screen_share_for_window(window),
+            Self::Region { display, region } => {
+                LocalVideoTrack::screen_share_for_region(display, *region)
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Event {
     RoomJoined {
@@ -1330,6 +1356,17 @@ impl Room {
             .map_or(false, |live_kit| live_kit.speaking)
     }
 
+    /// Whether the user, local or remote, is speaking in the call.
+    pub fn is_user_speaking(&self, user_id: u64) -> bool {
+        if self.client.user_id() == Some(user_id) {
+            self.is_speaking()
+        } else {
+            self.remote_participants
+                .get(&user_id)
+                .map_or(false, |participant| participant.speaking)
+        }
+    }
+
     pub fn is_deafened(&self) -> Option<bool> {
         self.live_kit.as_ref().map(|live_kit| live_kit.deafened)
     }
@@ -1427,29 +1464,72 @@ impl Room {
         })
     }
 
+    /// The displays and windows that can be shared, displays first.
+    pub fn screen_share_sources(
+        &self,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<ScreenShareSource>>> {
+        let Some(live_kit) = self.live_kit.as_ref() else {
+            return Task::ready(Err(anyhow!("live-kit was not initialized")));
+        };
+        let displays = live_kit.room.display_sources();
+        let windows = live_kit.room.window_sources();
+        cx.foreground_executor().spawn(async move {
+            let mut sources = displays
+                .await?
+                .into_iter()
+                .map(ScreenShareSource::Display)
+                .collect::<Vec<_>>();
+            sources.extend(windows.await?.into_iter().map(ScreenShareSource::Window));
+            Ok(sources)
+        })
+    }
+
+    /// Shares the main display.
     pub fn share_screen(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        self.share_screen_source(None, cx)
+    }
+
+    /// Shares a display, a window or a region of a display, or the main
+    /// display if no source is given.
+    pub fn share_screen_source(
+        &mut self,
+        source: Option<ScreenShareSource>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
         if self.status.is_offline() {
             return Task::ready(Err(anyhow!("room is offline")));
         } else if self.is_screen_sharing() {
             return Task::ready(Err(anyhow!("screen was already shared")));
         }
 
-        let (displays, publish_id) = if let Some(live_kit) = self.live_kit.as_mut() {
+        let (source, publish_id) = if let Some(live_kit) = self.live_kit.as_mut() {
             let publish_id = post_inc(&mut live_kit.next_publish_id);
             live_kit.screen_track = LocalTrack::Pending { publish_id };
             cx.notify();
-            (live_kit.room.display_sources(), publish_id)
+            let source: LocalBoxFuture<'static, Result<ScreenShareSource>> = match source {
+                Some(source) => async move { Ok(source) }.boxed_local(),
+                None => {
+                    let displays = live_kit.room.display_sources();
+                    async move {
+                        let display = displays
+                            .await?
+                            .into_iter()
+                            .next()
+                            .ok_or_else(|| anyhow!("no display found"))?;
+                        Ok(ScreenShareSource::Display(display))
+                    }
+                    .boxed_local()
+                }
+            };
+            (source, publish_id)
         } else {
             return Task::ready(Err(anyhow!("live-kit was not initialized")));
         };
 
         cx.spawn(move |this, mut cx| async move {
             let publish_track = async {
-                let displays = displays.await?;
-                let display = displays
-                    .first()
-                    .ok_or_else(|| anyhow!("no display found"))?;
-                let track = LocalVideoTrack::screen_share_for_display(display);
+                let track = source.await?.local_track();
                 this.upgrade()
                     .ok_or_else(|| anyhow!("room was dropped"))?
                     .update(&mut cx, |this, _| {
@@ -1655,6 +1735,15 @@ impl Room {
             .room
             .set_display_sources(sources);
     }
+
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn set_window_sources(&self, sources: Vec<live_kit_client::MacOSWindow>) {
+        self.live_kit
+            .as_ref()
+            .unwrap()
+            .room
+            .set_window_sources(sources);
+    }
 }
 
 struct LiveKitRoom {
@@ -0,0 +1,5 @@
+create table synced_settings (
+    user_id integer primary key references users (id) on delete cascade,
+    snapshot text not null,
+    updated_at timestamp without time zone not null default now()
+);
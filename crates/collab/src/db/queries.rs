@@ -19,4 +19,5 @@ pub mod projects;
 pub mod rate_buckets;
 pub mod rooms;
 pub mod servers;
+pub mod synced_settings;
 pub mod users;
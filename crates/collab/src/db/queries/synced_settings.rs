@@ -0,0 +1,43 @@
+use super::*;
+use crate::db::tables::synced_settings;
+use chrono::Utc;
+use sea_orm::EntityTrait;
+
+impl Database {
+    /// Returns the settings snapshot the given user last synced, if any.
+    pub async fn get_synced_settings(&self, user_id: UserId) -> Result<Option<String>> {
+        self.transaction(|tx| async move {
+            let synced_settings = synced_settings::Entity::find_by_id(user_id)
+                .one(&*tx)
+                .await?;
+            Ok(synced_settings.map(|synced_settings| synced_settings.snapshot))
+        })
+        .await
+    }
+
+    /// Replaces the settings snapshot synced by the given user.
+    pub async fn set_synced_settings(&self, user_id: UserId, snapshot: String) -> Result<()> {
+        self.transaction(|tx| {
+            let snapshot = snapshot.clone();
+            async move {
+                synced_settings::Entity::insert(synced_settings::ActiveModel {
+                    user_id: ActiveValue::set(user_id),
+                    snapshot: ActiveValue::set(snapshot),
+                    updated_at: ActiveValue::set(Utc::now().naive_utc()),
+                })
+                .on_conflict(
+                    OnConflict::column(synced_settings::Column::UserId)
+                        .update_columns([
+                            synced_settings::Column::Snapshot,
+                            synced_settings::Column::UpdatedAt,
+                        ])
+                        .to_owned(),
+                )
+                .exec(&*tx)
+                .await?;
+                Ok(())
+            }
+        })
+        .await
+    }
+}
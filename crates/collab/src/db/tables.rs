@@ -33,6 +33,7 @@ pub mod room;
 pub mod room_participant;
 pub mod server;
 pub mod signup;
+pub mod synced_settings;
 pub mod user;
 pub mod user_feature;
 pub mod worktree;
@@ -43,3 +43,42 @@ async fn test_accepted_tos(db: &Arc<Database>) {
     let user = db.get_user_by_id(user_id).await.unwrap().unwrap();
     assert!(user.accepted_tos_at.is_none());
 }
+
+test_both_dbs!(
+    test_synced_settings,
+    test_synced_settings_postgres,
+    test_synced_settings_sqlite
+);
+
+async fn test_synced_settings(db: &Arc<Database>) {
+    let user_id = db
+        .create_user(
+            "user1@example.com",
+            false,
+            NewUserParams {
+                github_login: "user1".to_string(),
+                github_user_id: 1,
+            },
+        )
+        .await
+        .unwrap()
+        .user_id;
+
+    assert_eq!(db.get_synced_settings(user_id).await.unwrap(), None);
+
+    db.set_synced_settings(user_id, "first".to_string())
+        .await
+        .unwrap();
+    assert_eq!(
+        db.get_synced_settings(user_id).await.unwrap().as_deref(),
+        Some("first")
+    );
+
+    db.set_synced_settings(user_id, "second".to_string())
+        .await
+        .unwrap();
+    assert_eq!(
+        db.get_synced_settings(user_id).await.unwrap().as_deref(),
+        Some("second")
+    );
+}
@@ -519,6 +519,15 @@ impl Server {
             .add_request_handler(user_handler(
                 forward_mutating_project_request::<proto::ApplyCodeAction>,
             ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetCodeLens>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::ResolveCodeLens>,
+            ))
+            .add_request_handler(user_handler(
+                forward_mutating_project_request::<proto::ExecuteCodeLens>,
+            ))
             .add_request_handler(user_handler(
                 forward_mutating_project_request::<proto::PrepareRename>,
             ))
@@ -607,6 +616,8 @@ impl Server {
             .add_request_handler(user_handler(get_private_user_info))
             .add_request_handler(user_handler(get_llm_api_token))
             .add_request_handler(user_handler(accept_terms_of_service))
+            .add_request_handler(user_handler(get_synced_settings))
+            .add_request_handler(user_handler(update_synced_settings))
             .add_message_handler(user_message_handler(acknowledge_channel_message))
             .add_message_handler(user_message_handler(acknowledge_buffer_version))
             .add_request_handler(user_handler(get_supermaven_api_key))
@@ -621,6 +632,22 @@ impl Server {
             ))
             .add_message_handler(broadcast_project_message_from_host::<proto::AdvertiseContexts>)
             .add_message_handler(update_context)
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::OpenSharedTerminal>,
+            ))
+            .add_request_handler(user_handler(
+                forward_mutating_project_request::<proto::SharedTerminalInput>,
+            ))
+            .add_message_handler(
+                broadcast_project_message_from_host::<proto::AdvertiseSharedTerminals>,
+            )
+            .add_message_handler(broadcast_project_message_from_host::<proto::UpdateSharedTerminal>)
+            .add_message_handler(
+                broadcast_project_message_from_host::<proto::UpdateGuestPermissions>,
+            )
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::RequestEditAccess>,
+            ))
             .add_request_handler({
                 let app_state = app_state.clone();
                 move |request, response, session| {
@@ -4923,6 +4950,38 @@ async fn accept_terms_of_service(
     Ok(())
 }
 
+/// Get the settings the current user synced from their machines
+async fn get_synced_settings(
+    _request: proto::GetSyncedSettings,
+    response: Response<proto::GetSyncedSettings>,
+    session: UserSession,
+) -> Result<()> {
+    let db = session.db().await;
+    let snapshot = db.get_synced_settings(session.user_id()).await?;
+    response.send(proto::GetSyncedSettingsResponse { snapshot })?;
+    Ok(())
+}
+
+/// The largest settings snapshot a user can sync.
+const MAX_SYNCED_SETTINGS_LEN: usize = 5 * 1024 * 1024;
+
+/// Replace the settings the current user synced from their machines
+async fn update_synced_settings(
+    request: proto::UpdateSyncedSettings,
+    response: Response<proto::UpdateSyncedSettings>,
+    session: UserSession,
+) -> Result<()> {
+    if request.snapshot.len() > MAX_SYNCED_SETTINGS_LEN {
+        Err(anyhow!("synced settings are too large"))?
+    }
+
+    let db = session.db().await;
+    db.set_synced_settings(session.user_id(), request.snapshot)
+        .await?;
+    response.send(proto::Ack {})?;
+    Ok(())
+}
+
 /// The minimum account age an account must have in order to use the LLM service.
 const MIN_ACCOUNT_AGE_FOR_LLM_USE: chrono::Duration = chrono::Duration::days(30);
 
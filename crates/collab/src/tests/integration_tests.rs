@@ -7,7 +7,7 @@ use crate::{
 };
 use anyhow::{anyhow, Result};
 use assistant::{ContextStore, PromptBuilder};
-use call::{room, ActiveCall, ParticipantLocation, Room};
+use call::{room, ActiveCall, ParticipantLocation, Room, ScreenShareSource};
 use client::{User, RECEIVE_TIMEOUT};
 use collections::{HashMap, HashSet};
 use fs::{FakeFs, Fs as _, RemoveOptions};
@@ -24,12 +24,12 @@ use language::{
     tree_sitter_rust, Diagnostic, DiagnosticEntry, FakeLspAdapter, Language, LanguageConfig,
     LanguageMatcher, LineEnding, OffsetRangeExt, Point, Rope,
 };
-use live_kit_client::MacOSDisplay;
+use live_kit_client::{MacOSDisplay, MacOSWindow};
 use lsp::LanguageServerId;
 use parking_lot::Mutex;
 use project::{
-    search::SearchQuery, search::SearchResult, DiagnosticSummary, FormatTrigger, HoverBlockKind,
-    Project, ProjectPath,
+    project_settings::ProjectSettings, search::SearchQuery, search::SearchResult,
+    DiagnosticSummary, FormatTrigger, HoverBlockKind, Project, ProjectPath,
 };
 use rand::prelude::*;
 use serde_json::json;
@@ -3481,6 +3481,126 @@ async fn test_buffer_conflict_after_save(
     });
 }
 
+#[gpui::test(iterations = 10)]
+async fn test_guest_edit_permissions(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+    let user_b = client_b.user_id().unwrap();
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/dir",
+            json!({
+                "a.txt": "a-contents",
+                ".env": "SECRET=1",
+            }),
+        )
+        .await;
+    let (project_a, worktree_id) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_dev_server_project(project_id, cx_b).await;
+    let buffer_b = project_b
+        .update(cx_b, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    assert!(!buffer_b.read_with(cx_b, |buffer, _| buffer.read_only()));
+
+    // The host makes the project read-only for the guest.
+    project_a.update(cx_a, |project, cx| {
+        project.set_guest_can_edit(user_b, false, cx)
+    });
+    executor.run_until_parked();
+    project_b.read_with(cx_b, |project, _| {
+        assert!(project.is_read_only());
+        assert!(project.can_request_edit_access());
+    });
+    assert!(buffer_b.read_with(cx_b, |buffer, _| buffer.read_only()));
+
+    // The host rejects writes from the guest, even if the guest ignores it.
+    project_b
+        .update(cx_b, |project, cx| {
+            project.create_entry((worktree_id, "b.txt"), false, cx)
+        })
+        .await
+        .unwrap_err();
+    project_b
+        .update(cx_b, |project, cx| {
+            project.save_buffer(buffer_b.clone(), cx)
+        })
+        .await
+        .unwrap_err();
+
+    // The guest asks to edit, and the host allows it.
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    cx_a.update(|cx| {
+        let requests = requests.clone();
+        cx.subscribe(&project_a, move |_, event, _| {
+            if let project::Event::EditAccessRequested { user_id } = event {
+                requests.lock().push(*user_id);
+            }
+        })
+        .detach();
+    });
+    project_b
+        .update(cx_b, |project, cx| project.request_edit_access(cx))
+        .await
+        .unwrap();
+    assert!(project_b.read_with(cx_b, |project, _| project.edit_access_requested()));
+    executor.run_until_parked();
+    assert_eq!(*requests.lock(), [user_b]);
+
+    project_a.update(cx_a, |project, cx| {
+        project.set_guest_can_edit(user_b, true, cx)
+    });
+    executor.run_until_parked();
+    project_b.read_with(cx_b, |project, _| {
+        assert!(!project.is_read_only());
+        assert!(!project.edit_access_requested());
+    });
+    assert!(!buffer_b.read_with(cx_b, |buffer, _| buffer.read_only()));
+    project_b
+        .update(cx_b, |project, cx| {
+            project.create_entry((worktree_id, "b.txt"), false, cx)
+        })
+        .await
+        .unwrap();
+
+    // Paths the host made read-only can't be written by any guest.
+    cx_a.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.collaboration.read_only_paths = vec!["**/.env".to_string()];
+            });
+        });
+    });
+    executor.run_until_parked();
+    let env_buffer_b = project_b
+        .update(cx_b, |p, cx| p.open_buffer((worktree_id, ".env"), cx))
+        .await
+        .unwrap();
+    assert!(env_buffer_b.read_with(cx_b, |buffer, _| buffer.read_only()));
+    project_b
+        .update(cx_b, |project, cx| {
+            project.save_buffer(env_buffer_b.clone(), cx)
+        })
+        .await
+        .unwrap_err();
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_buffer_reloading(
     executor: BackgroundExecutor,
@@ -6111,6 +6231,91 @@ async fn test_join_call_after_screen_was_shared(
     });
 }
 
+#[gpui::test(iterations = 10)]
+async fn test_screen_share_sources_and_active_speakers(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let room_a = cx_a.read(|cx| ActiveCall::global(cx).read(cx).room().unwrap().clone());
+    let room_b = cx_b.read(|cx| ActiveCall::global(cx).read(cx).room().unwrap().clone());
+    let user_a = client_a.user_id().unwrap();
+    let user_b = client_b.user_id().unwrap();
+
+    // User A lists the displays and windows they can share.
+    let display = MacOSDisplay::new();
+    let window = MacOSWindow::new("notes.md", "TextEdit");
+    let sources = room_a
+        .update(cx_a, |room, cx| {
+            room.set_display_sources(vec![display.clone()]);
+            room.set_window_sources(vec![window.clone()]);
+            room.screen_share_sources(cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(sources.len(), 2);
+    assert!(matches!(sources[0], ScreenShareSource::Display(_)));
+    let ScreenShareSource::Window(shared_window) = &sources[1] else {
+        panic!("expected a window source");
+    };
+    assert_eq!(shared_window.title().as_deref(), Some("notes.md"));
+    assert_eq!(
+        shared_window.application_name().as_deref(),
+        Some("TextEdit")
+    );
+
+    // User A shares the window, and user B receives its frames.
+    room_a
+        .update(cx_a, |room, cx| {
+            room.share_screen_source(Some(sources[1].clone()), cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    let track = room_b.read_with(cx_b, |room, _| {
+        room.remote_participants()[&user_a]
+            .video_tracks
+            .values()
+            .next()
+            .unwrap()
+            .clone()
+    });
+    let mut frames = track.frames();
+    let frame = live_kit_client::Frame {
+        label: "window".into(),
+        width: 800,
+        height: 600,
+    };
+    window.send_frame(frame.clone());
+    assert_eq!(frames.next().await, Some(frame));
+
+    // Both users see who is speaking.
+    server
+        .test_live_kit_server
+        .set_active_speakers(&[user_a.to_string().as_str()]);
+    executor.run_until_parked();
+    room_a.read_with(cx_a, |room, _| {
+        assert!(room.is_speaking());
+        assert!(room.is_user_speaking(user_a));
+        assert!(!room.is_user_speaking(user_b));
+    });
+    room_b.read_with(cx_b, |room, _| {
+        assert!(room.remote_participants()[&user_a].speaking);
+        assert!(room.is_user_speaking(user_a));
+        assert!(!room.is_speaking());
+    });
+
+    server.test_live_kit_server.set_active_speakers(&[]);
+    executor.run_until_parked();
+    room_b.read_with(cx_b, |room, _| assert!(!room.is_user_speaking(user_a)));
+}
+
 #[gpui::test]
 async fn test_right_click_menu_behind_collab_panel(cx: &mut TestAppContext) {
     let mut server = TestServer::start(cx.executor().clone()).await;
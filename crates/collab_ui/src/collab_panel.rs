@@ -844,12 +844,23 @@ impl CollabPanel {
             self.user_store.read(cx).current_user().map(|user| user.id) == Some(user_id);
         let tooltip = format!("Follow {}", user.github_login);
 
-        let is_call_admin = ActiveCall::global(cx).read(cx).room().is_some_and(|room| {
-            room.read(cx).local_participant().role == proto::ChannelRole::Admin
-        });
+        let room = ActiveCall::global(cx)
+            .read(cx)
+            .room()
+            .map(|room| room.read(cx));
+        let is_call_admin =
+            room.is_some_and(|room| room.local_participant().role == proto::ChannelRole::Admin);
+        let is_speaking = !is_pending && room.is_some_and(|room| room.is_user_speaking(user_id));
 
         ListItem::new(SharedString::from(user.github_login.clone()))
-            .start_slot(Avatar::new(user.avatar_uri.clone()))
+            .start_slot(
+                Avatar::new(user.avatar_uri.clone()).border_color(if is_speaking {
+                    cx.theme().status().info
+                } else {
+                    // Keep the border transparent so the avatar doesn't shift when it's shown.
+                    gpui::transparent_black()
+                }),
+            )
             .child(Label::new(user.github_login.clone()))
             .selected(is_selected)
             .end_slot(if is_pending {
@@ -880,11 +891,16 @@ impl CollabPanel {
                             .ok();
                     }))
             })
-            .when(is_call_admin, |el| {
-                el.on_secondary_mouse_down(cx.listener(move |this, event: &MouseDownEvent, cx| {
-                    this.deploy_participant_context_menu(event.position, user_id, role, cx)
-                }))
-            })
+            .when(
+                is_call_admin || (!is_current_user && self.hosted_shared_project(cx).is_some()),
+                |el| {
+                    el.on_secondary_mouse_down(cx.listener(
+                        move |this, event: &MouseDownEvent, cx| {
+                            this.deploy_participant_context_menu(event.position, user_id, role, cx)
+                        },
+                    ))
+                },
+            )
     }
 
     fn render_participant_project(
@@ -1073,6 +1089,16 @@ impl CollabPanel {
         })
     }
 
+    /// The project of the workspace, when it's hosted and shared.
+    fn hosted_shared_project(&self, cx: &AppContext) -> Option<Model<Project>> {
+        let project = self.workspace.upgrade()?.read(cx).project().clone();
+        let is_hosted_and_shared = {
+            let project = project.read(cx);
+            project.is_shared() && !project.is_via_collab()
+        };
+        is_hosted_and_shared.then_some(project)
+    }
+
     fn deploy_participant_context_menu(
         &mut self,
         position: Point<Pixels>,
@@ -1081,14 +1107,39 @@ impl CollabPanel {
         cx: &mut ViewContext<Self>,
     ) {
         let this = cx.view().clone();
-        if !(role == proto::ChannelRole::Guest
-            || role == proto::ChannelRole::Talker
-            || role == proto::ChannelRole::Member)
-        {
+        let is_call_admin = ActiveCall::global(cx).read(cx).room().is_some_and(|room| {
+            room.read(cx).local_participant().role == proto::ChannelRole::Admin
+        });
+        let manages_role = is_call_admin
+            && (role == proto::ChannelRole::Guest
+                || role == proto::ChannelRole::Talker
+                || role == proto::ChannelRole::Member);
+        let project = self.hosted_shared_project(cx);
+        if !manages_role && project.is_none() {
             return;
         }
 
         let context_menu = ContextMenu::build(cx, |mut context_menu, cx| {
+            if let Some(project) = project {
+                let can_edit = project.read(cx).guest_permissions().can_edit(user_id);
+                context_menu = context_menu.toggleable_entry(
+                    "Can Edit Shared Project",
+                    can_edit,
+                    IconPosition::Start,
+                    None,
+                    move |cx| {
+                        project.update(cx, |project, cx| {
+                            project.set_guest_can_edit(user_id, !can_edit, cx)
+                        });
+                    },
+                );
+                if manages_role {
+                    context_menu = context_menu.separator();
+                }
+            }
+            if !manages_role {
+                return context_menu;
+            }
             if role == proto::ChannelRole::Guest {
                 context_menu = context_menu.entry(
                     "Grant Mic Access",
@@ -0,0 +1,346 @@
+use std::sync::Arc;
+
+use call::{report_call_event_for_room, ActiveCall, Room, ScreenRegion, ScreenShareSource};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, Render, Task, View,
+    ViewContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use title_bar::SelectScreenShareSource;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{notifications::DetachAndPromptErr, ModalView, Workspace};
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &SelectScreenShareSource, cx| {
+            ScreenSharePicker::toggle(workspace, cx);
+        });
+    })
+    .detach();
+}
+
+/// A modal to choose the display, window or region of a display to share
+/// with the call.
+pub struct ScreenSharePicker {
+    picker: View<Picker<ScreenSharePickerDelegate>>,
+}
+
+impl ScreenSharePicker {
+    fn toggle(_: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+        let Some(room) = ActiveCall::global(cx).read(cx).room().cloned() else {
+            return;
+        };
+        let sources = room.update(cx, |room, cx| room.screen_share_sources(cx));
+        cx.spawn(|workspace, mut cx| async move {
+            let sources = sources.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                workspace.toggle_modal(cx, |cx| Self::new(room, sources, cx));
+            })
+        })
+        .detach_and_prompt_err("Sharing Screen Failed", cx, screen_share_error_detail);
+    }
+
+    fn new(room: Model<Room>, sources: Vec<ScreenShareSource>, cx: &mut ViewContext<Self>) -> Self {
+        let delegate =
+            ScreenSharePickerDelegate::new(cx.view().downgrade(), room, share_options(sources));
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for ScreenSharePicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for ScreenSharePicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for ScreenSharePicker {}
+impl ModalView for ScreenSharePicker {}
+
+fn screen_share_error_detail(error: &anyhow::Error, _: &mut WindowContext) -> Option<String> {
+    Some(format!(
+        "{:?}\n\nPlease check that you have given Zed permissions to record your screen in Settings.",
+        error
+    ))
+}
+
+struct ShareOption {
+    label: String,
+    source: ScreenShareSource,
+}
+
+/// Lists every display and its halves, followed by every window.
+fn share_options(sources: Vec<ScreenShareSource>) -> Vec<ShareOption> {
+    let display_count = sources
+        .iter()
+        .filter(|source| matches!(source, ScreenShareSource::Display(_)))
+        .count();
+    let mut options = Vec::new();
+    let mut display_ix = 0;
+    for source in sources {
+        match source {
+            ScreenShareSource::Display(display) => {
+                display_ix += 1;
+                let name = if display_count == 1 {
+                    "Entire Screen".to_string()
+                } else {
+                    format!("Screen {display_ix}")
+                };
+                let regions = region_presets(display.width() as f64, display.height() as f64);
+                options.push(ShareOption {
+                    label: name.clone(),
+                    source: ScreenShareSource::Display(display.clone()),
+                });
+                for (region_name, region) in regions {
+                    options.push(ShareOption {
+                        label: format!("{name}: {region_name}"),
+                        source: ScreenShareSource::Region {
+                            display: display.clone(),
+                            region,
+                        },
+                    });
+                }
+            }
+            ScreenShareSource::Window(window) => {
+                let label = match (window.application_name(), window.title()) {
+                    (Some(application), Some(title)) if !title.is_empty() => {
+                        format!("{application}: {title}")
+                    }
+                    (Some(application), _) => application,
+                    (None, Some(title)) => title,
+                    (None, None) => "Untitled Window".to_string(),
+                };
+                options.push(ShareOption {
+                    label,
+                    source: ScreenShareSource::Window(window),
+                });
+            }
+            source @ ScreenShareSource::Region { .. } => options.push(ShareOption {
+                label: "Region".to_string(),
+                source,
+            }),
+        }
+    }
+    options
+}
+
+/// The halves of a display that can be shared instead of all of it.
+fn region_presets(width: f64, height: f64) -> [(&'static str, ScreenRegion); 4] {
+    let half_width = width / 2.;
+    let half_height = height / 2.;
+    [
+        (
+            "Left Half",
+            ScreenRegion {
+                x: 0.,
+                y: 0.,
+                width: half_width,
+                height,
+            },
+        ),
+        (
+            "Right Half",
+            ScreenRegion {
+                x: half_width,
+                y: 0.,
+                width: width - half_width,
+                height,
+            },
+        ),
+        (
+            "Top Half",
+            ScreenRegion {
+                x: 0.,
+                y: 0.,
+                width,
+                height: half_height,
+            },
+        ),
+        (
+            "Bottom Half",
+            ScreenRegion {
+                x: 0.,
+                y: half_height,
+                width,
+                height: height - half_height,
+            },
+        ),
+    ]
+}
+
+pub struct ScreenSharePickerDelegate {
+    screen_share_picker: WeakView<ScreenSharePicker>,
+    room: Model<Room>,
+    options: Vec<ShareOption>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl ScreenSharePickerDelegate {
+    fn new(
+        screen_share_picker: WeakView<ScreenSharePicker>,
+        room: Model<Room>,
+        options: Vec<ShareOption>,
+    ) -> Self {
+        let candidates = options
+            .iter()
+            .enumerate()
+            .map(|(id, option)| StringMatchCandidate::new(id, option.label.clone()))
+            .collect();
+        Self {
+            screen_share_picker,
+            room,
+            options,
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for ScreenSharePickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Select a screen, region or window to share...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(option) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|mat| self.options.get(mat.candidate_id))
+        else {
+            return;
+        };
+        let source = option.source.clone();
+        let client = ActiveCall::global(cx).read(cx).client();
+        let share = self.room.update(cx, |room, cx| {
+            if room.is_screen_sharing() {
+                if let Err(error) = room.unshare_screen(cx) {
+                    return Task::ready(Err(error));
+                }
+            }
+            report_call_event_for_room(
+                "enable screen share",
+                room.id(),
+                room.channel_id(),
+                &client,
+            );
+            room.share_screen_source(Some(source), cx)
+        });
+        share.detach_and_prompt_err("Sharing Screen Failed", cx, screen_share_error_detail);
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.screen_share_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let option = self.options.get(mat.candidate_id)?;
+        let icon = match option.source {
+            ScreenShareSource::Window(_) => IconName::Maximize,
+            ScreenShareSource::Display(_) | ScreenShareSource::Region { .. } => IconName::Screen,
+        };
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .start_slot(Icon::new(icon).color(Color::Muted))
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                )),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_presets() {
+        let regions = region_presets(1921., 1080.);
+        assert_eq!(
+            regions.map(|(name, _)| name),
+            ["Left Half", "Right Half", "Top Half", "Bottom Half"]
+        );
+        let [(_, left), (_, right), (_, top), (_, bottom)] = regions;
+        assert_eq!(left.x + left.width, right.x);
+        assert_eq!(left.width + right.width, 1921.);
+        assert_eq!(right.height, 1080.);
+        assert_eq!(top.y + top.height, bottom.y);
+        assert_eq!(top.height + bottom.height, 1080.);
+    }
+}
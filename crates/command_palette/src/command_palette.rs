@@ -7,7 +7,7 @@ use std::{
 use client::{parse_zed_link, telemetry::Telemetry};
 use collections::HashMap;
 use command_palette_hooks::{
-    CommandInterceptResult, CommandPaletteFilter, CommandPaletteInterceptor,
+    CommandInterceptResult, CommandPaletteCommands, CommandPaletteFilter, CommandPaletteInterceptor,
 };
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
@@ -79,7 +79,7 @@ impl CommandPalette {
     ) -> Self {
         let filter = CommandPaletteFilter::try_global(cx);
 
-        let commands = cx
+        let mut commands = cx
             .available_actions()
             .into_iter()
             .filter_map(|action| {
@@ -92,7 +92,13 @@ impl CommandPalette {
                     action,
                 })
             })
-            .collect();
+            .collect::<Vec<_>>();
+        if let Some(additional_commands) = CommandPaletteCommands::try_global(cx) {
+            commands.extend(additional_commands.commands().map(|command| Command {
+                name: command.name.clone(),
+                action: command.action.boxed_clone(),
+            }));
+        }
 
         let delegate = CommandPaletteDelegate::new(
             cx.view().downgrade(),
@@ -457,6 +463,7 @@ mod tests {
     use std::sync::Arc;
 
     use super::*;
+    use command_palette_hooks::CommandPaletteCommand;
     use editor::Editor;
     use go_to_line::GoToLine;
     use gpui::TestAppContext;
@@ -584,6 +591,50 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_additional_commands(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        cx.update(|cx| {
+            CommandPaletteCommands::update_global(cx, |commands, _| {
+                commands.set_commands(
+                    "test",
+                    vec![CommandPaletteCommand {
+                        name: "my extension: Create Scratch File".to_string(),
+                        action: Box::new(workspace::NewFile),
+                    }],
+                );
+            })
+        });
+
+        cx.simulate_keystrokes("cmd-n");
+        cx.simulate_keystrokes("cmd-shift-p");
+        cx.simulate_input("my extension: create scratch");
+
+        let palette = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<CommandPalette>(cx)
+                .unwrap()
+                .read(cx)
+                .picker
+                .clone()
+        });
+        palette.update(cx, |palette, _| {
+            let first_match = &palette.delegate.matches[0];
+            assert_eq!(
+                palette.delegate.commands[first_match.candidate_id].name,
+                "my extension: Create Scratch File"
+            );
+        });
+
+        cx.simulate_keystrokes("enter");
+        workspace.update(cx, |workspace, cx| {
+            assert_eq!(workspace.active_pane().read(cx).items_len(), 2);
+        });
+    }
+
     fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
         cx.update(|cx| {
             let app_state = AppState::test(cx);
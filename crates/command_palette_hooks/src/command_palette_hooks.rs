@@ -4,7 +4,7 @@
 
 use std::any::TypeId;
 
-use collections::HashSet;
+use collections::{HashMap, HashSet};
 use derive_more::{Deref, DerefMut};
 use gpui::{Action, AppContext, BorrowAppContext, Global};
 
@@ -12,6 +12,7 @@ use gpui::{Action, AppContext, BorrowAppContext, Global};
 pub fn init(cx: &mut AppContext) {
     cx.set_global(GlobalCommandPaletteFilter::default());
     cx.set_global(GlobalCommandPaletteInterceptor::default());
+    cx.set_global(GlobalCommandPaletteCommands::default());
 }
 
 /// A filter for the command palette.
@@ -78,6 +79,56 @@ impl CommandPaletteFilter {
     }
 }
 
+/// A command shown in the command palette in addition to the available actions.
+pub struct CommandPaletteCommand {
+    /// The name of the command, as shown in the command palette.
+    pub name: String,
+    /// The action dispatched when the command is run.
+    pub action: Box<dyn Action>,
+}
+
+/// Commands shown in the command palette in addition to the available actions,
+/// such as those contributed by extensions.
+#[derive(Default)]
+pub struct CommandPaletteCommands {
+    commands_by_source: HashMap<&'static str, Vec<CommandPaletteCommand>>,
+}
+
+#[derive(Deref, DerefMut, Default)]
+struct GlobalCommandPaletteCommands(CommandPaletteCommands);
+
+impl Global for GlobalCommandPaletteCommands {}
+
+impl CommandPaletteCommands {
+    /// Returns the global [`CommandPaletteCommands`], if set.
+    pub fn try_global(cx: &AppContext) -> Option<&CommandPaletteCommands> {
+        cx.try_global::<GlobalCommandPaletteCommands>()
+            .map(|commands| &commands.0)
+    }
+
+    /// Updates the global [`CommandPaletteCommands`] using the given closure.
+    pub fn update_global<F, R>(cx: &mut AppContext, update: F) -> R
+    where
+        F: FnOnce(&mut Self, &mut AppContext) -> R,
+    {
+        cx.update_global(|this: &mut GlobalCommandPaletteCommands, cx| update(&mut this.0, cx))
+    }
+
+    /// Replaces the commands added by the given source.
+    pub fn set_commands(&mut self, source: &'static str, commands: Vec<CommandPaletteCommand>) {
+        if commands.is_empty() {
+            self.commands_by_source.remove(source);
+        } else {
+            self.commands_by_source.insert(source, commands);
+        }
+    }
+
+    /// Returns the commands added by all sources.
+    pub fn commands(&self) -> impl Iterator<Item = &CommandPaletteCommand> {
+        self.commands_by_source.values().flatten()
+    }
+}
+
 /// The result of intercepting a command palette command.
 pub struct CommandInterceptResult {
     /// The action produced as a result of the interception.
@@ -0,0 +1,14 @@
+use gpui::{actions, AppContext};
+use workspace::Workspace;
+
+pub mod csv_table;
+pub mod csv_table_view;
+
+actions!(csv, [ToggleTableView, CopySelection]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, cx| {
+        csv_table_view::CsvTableView::register(workspace, cx);
+    })
+    .detach();
+}
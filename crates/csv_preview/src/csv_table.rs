@@ -0,0 +1,232 @@
+//! Reads the rows of CSV and TSV files, as described by RFC 4180: fields may
+//! be quoted, and quoted fields may contain delimiters, doubled quotes, and
+//! line breaks.
+use std::{cmp::Ordering, ops::Range};
+
+/// The widest a column is shown, in characters. Longer cells are truncated.
+pub const MAX_COLUMN_WIDTH: usize = 48;
+
+/// A field of a row, unquoted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cell {
+    pub text: String,
+    /// The offsets of the field in the text it was read from, including its
+    /// quotes.
+    pub range: Range<usize>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Table {
+    pub rows: Vec<Vec<Cell>>,
+    /// The number of fields in the longest row.
+    pub column_count: usize,
+}
+
+impl Table {
+    pub fn cell(&self, row: usize, column: usize) -> Option<&Cell> {
+        self.rows.get(row)?.get(column)
+    }
+
+    /// Returns whether every cell of the column below the first row is a
+    /// number or empty, so that it's aligned to the right and sorted by value.
+    pub fn is_numeric_column(&self, column: usize) -> bool {
+        let mut cells = self.rows.iter().skip(1).filter_map(|row| row.get(column));
+        let mut any_numbers = false;
+        let all_numbers = cells.all(|cell| {
+            let text = cell.text.trim();
+            let is_number = text.parse::<f64>().is_ok();
+            any_numbers |= is_number;
+            text.is_empty() || is_number
+        });
+        all_numbers && any_numbers
+    }
+
+    /// Returns the width of each column in characters: that of its widest
+    /// cell, up to [`MAX_COLUMN_WIDTH`]. Line breaks within cells count as a
+    /// single character, as they're shown as one.
+    pub fn column_widths(&self) -> Vec<usize> {
+        let mut widths = vec![1; self.column_count];
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                let cell_width = cell.text.replace("\r\n", "\n").chars().count();
+                *width = (*width).max(cell_width.min(MAX_COLUMN_WIDTH));
+            }
+        }
+        widths
+    }
+
+    /// Returns the indices of the rows below the first, sorted by the column.
+    /// Numeric columns are sorted by value, and rows without the column last.
+    pub fn sorted_row_order(&self, column: usize, ascending: bool) -> Vec<usize> {
+        let numeric = self.is_numeric_column(column);
+        let mut order = (1..self.rows.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| {
+            let (a, b) = (self.cell(*a, column), self.cell(*b, column));
+            let (a, b) = match (a, b) {
+                (Some(a), Some(b)) => (a.text.trim(), b.text.trim()),
+                (Some(_), None) => return Ordering::Less,
+                (None, Some(_)) => return Ordering::Greater,
+                (None, None) => return Ordering::Equal,
+            };
+            let ordering = if numeric {
+                match (a.parse::<f64>().ok(), b.parse::<f64>().ok()) {
+                    (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+                    (Some(_), None) => return Ordering::Less,
+                    (None, Some(_)) => return Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                }
+            } else {
+                a.cmp(b)
+            };
+            if ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        });
+        order
+    }
+}
+
+/// Returns the delimiter of a file with the given extension, or the one that
+/// its first line is split by most often, for files without a known one. Ties
+/// go to the more common delimiter.
+pub fn detect_delimiter(extension: Option<&str>, text: &str) -> char {
+    match extension {
+        Some("tsv" | "tab") => '\t',
+        Some("csv") => ',',
+        _ => {
+            let first_line = text.lines().next().unwrap_or_default();
+            // The last of the delimiters that are split by most often is
+            // chosen, so they're listed from the least common.
+            ['|', ';', '\t', ',']
+                .into_iter()
+                .max_by_key(|delimiter| first_line.matches(*delimiter).count())
+                .unwrap_or(',')
+        }
+    }
+}
+
+/// Reads the rows of the text, leaving out blank lines.
+pub fn parse_table(text: &str, delimiter: char) -> Table {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut field_start = 0;
+    let mut in_quotes = false;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((ix, ch)) = chars.next() {
+        if in_quotes {
+            if ch == '"' {
+                if chars.peek().map(|(_, next)| *next) == Some('"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                field.push(ch);
+            }
+        } else if ch == '"' && ix == field_start {
+            in_quotes = true;
+        } else if ch == delimiter {
+            row.push(Cell {
+                text: std::mem::take(&mut field),
+                range: field_start..ix,
+            });
+            field_start = ix + ch.len_utf8();
+        } else if ch == '\n' || ch == '\r' {
+            let mut line_end = ix + 1;
+            if ch == '\r' && chars.peek().map(|(_, next)| *next) == Some('\n') {
+                chars.next();
+                line_end += 1;
+            }
+            let is_blank_line = row.is_empty() && field_start == ix;
+            if !is_blank_line {
+                row.push(Cell {
+                    text: std::mem::take(&mut field),
+                    range: field_start..ix,
+                });
+                rows.push(std::mem::take(&mut row));
+            }
+            field_start = line_end;
+        } else {
+            field.push(ch);
+        }
+    }
+    if !row.is_empty() || field_start < text.len() {
+        row.push(Cell {
+            text: field,
+            range: field_start..text.len(),
+        });
+        rows.push(row);
+    }
+
+    let column_count = rows.iter().map(Vec::len).max().unwrap_or(0);
+    Table { rows, column_count }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(table: &Table) -> Vec<Vec<&str>> {
+        table
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.text.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_table() {
+        let text = "name,note\r\nAda,\"says \"\"hi\"\", twice\"\n\nBob,\"two\nlines\"\nCy";
+        let table = parse_table(text, ',');
+        assert_eq!(
+            texts(&table),
+            [
+                vec!["name", "note"],
+                vec!["Ada", "says \"hi\", twice"],
+                vec!["Bob", "two\nlines"],
+                vec!["Cy"],
+            ]
+        );
+        assert_eq!(table.column_count, 2);
+
+        // Ranges include the quotes of quoted fields.
+        let note = table.cell(1, 1).unwrap();
+        assert_eq!(&text[note.range.clone()], "\"says \"\"hi\"\", twice\"");
+        let name = table.cell(3, 0).unwrap();
+        assert_eq!(&text[name.range.clone()], "Cy");
+
+        // Trailing line breaks and empty fields don't add rows, but are kept
+        // within rows.
+        let table = parse_table("a\tb\t\n1\t\t3\n", '\t');
+        assert_eq!(texts(&table), [vec!["a", "b", ""], vec!["1", "", "3"]]);
+        assert_eq!(parse_table("", ','), Table::default());
+    }
+
+    #[test]
+    fn test_detect_delimiter() {
+        assert_eq!(detect_delimiter(Some("tsv"), "a,b,c"), '\t');
+        assert_eq!(detect_delimiter(Some("csv"), "a;b;c"), ',');
+        assert_eq!(detect_delimiter(None, "a;b;c\n1,5;2;3"), ';');
+        assert_eq!(detect_delimiter(Some("txt"), "a|b|c"), '|');
+        assert_eq!(detect_delimiter(None, "abc"), ',');
+    }
+
+    #[test]
+    fn test_sorting_and_widths() {
+        let table = parse_table("id,name\n10,Bea\n9,\n,Al\n100,Cy,extra\n11", ',');
+        assert!(table.is_numeric_column(0));
+        assert!(!table.is_numeric_column(1));
+        assert_eq!(table.sorted_row_order(0, true), [2, 1, 5, 4, 3]);
+        assert_eq!(table.sorted_row_order(0, false), [4, 5, 1, 2, 3]);
+        assert_eq!(table.sorted_row_order(1, true), [2, 3, 1, 4, 5]);
+        assert_eq!(table.column_widths(), [3, 4, 5]);
+
+        let long = "x".repeat(MAX_COLUMN_WIDTH + 10);
+        assert_eq!(parse_table(&long, ',').column_widths(), [MAX_COLUMN_WIDTH]);
+    }
+}
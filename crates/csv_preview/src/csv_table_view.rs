@@ -0,0 +1,443 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use editor::{scroll::Autoscroll, Bias, Editor, EditorEvent};
+use gpui::{
+    uniform_list, AppContext, ClickEvent, ClipboardItem, EventEmitter, FocusHandle, FocusableView,
+    FontWeight, Render, Stateful, Subscription, Task, UniformListScrollHandle, View, ViewContext,
+    WeakView,
+};
+use settings::Settings;
+use theme::ThemeSettings;
+use ui::prelude::*;
+use workspace::item::{Item, ItemEvent};
+use workspace::Workspace;
+
+use crate::csv_table::{detect_delimiter, parse_table, Table};
+use crate::{CopySelection, ToggleTableView};
+
+const REPARSE_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// The room left in the headings of columns for their sort buttons.
+const SORT_BUTTON_WIDTH: Pixels = px(20.);
+
+/// The horizontal padding of cells.
+const CELL_PADDING: Pixels = px(8.);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TableSort {
+    column: usize,
+    ascending: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TableSelection {
+    Cell { row: usize, column: usize },
+    Column(usize),
+}
+
+/// Shows the rows of a CSV or TSV file as a table, with its columns aligned
+/// and its first row kept in view as a heading. Sorting the table only
+/// changes the order its rows are shown in, not the file.
+pub struct CsvTableView {
+    workspace: WeakView<Workspace>,
+    editor: View<Editor>,
+    focus_handle: FocusHandle,
+    table: Table,
+    numeric_columns: Vec<bool>,
+    /// The widths of the columns, in characters.
+    column_widths: Vec<usize>,
+    /// The indices of the rows below the heading, in the order they're shown
+    /// in.
+    row_order: Vec<usize>,
+    sort: Option<TableSort>,
+    selection: Option<TableSelection>,
+    /// The width of a character of the buffer font, as of the last render.
+    em_width: Pixels,
+    scroll_handle: UniformListScrollHandle,
+    tab_description: Option<String>,
+    parsing_task: Option<Task<Result<()>>>,
+    _subscription: Subscription,
+}
+
+impl CsvTableView {
+    pub fn register(workspace: &mut Workspace, _cx: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &ToggleTableView, cx| {
+            let Some(item) = workspace.active_item(cx) else {
+                return;
+            };
+            let pane = workspace.active_pane().clone();
+            if let Some(table_view) = item.act_as::<CsvTableView>(cx) {
+                let editor = table_view.read(cx).editor.clone();
+                pane.update(cx, |pane, cx| {
+                    pane.add_item(Box::new(editor), true, true, None, cx)
+                });
+            } else if let Some(editor) = item.act_as::<Editor>(cx) {
+                if !Self::is_csv_file(&editor, cx) {
+                    return;
+                }
+                let existing_view = pane
+                    .read(cx)
+                    .items_of_type::<CsvTableView>()
+                    .find(|view| view.read(cx).editor == editor);
+                let view = existing_view.unwrap_or_else(|| {
+                    let workspace = workspace.weak_handle();
+                    cx.new_view(|cx| CsvTableView::new(workspace, editor, cx))
+                });
+                pane.update(cx, |pane, cx| {
+                    pane.add_item(Box::new(view), true, true, None, cx)
+                });
+            }
+        });
+    }
+
+    /// Returns whether the editor shows a CSV or TSV file, by its extension.
+    pub fn is_csv_file(editor: &View<Editor>, cx: &AppContext) -> bool {
+        file_extension(editor, cx).map_or(false, |extension| {
+            matches!(extension.as_str(), "csv" | "tsv" | "tab")
+        })
+    }
+
+    fn new(
+        workspace: WeakView<Workspace>,
+        editor: View<Editor>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let subscription = cx.subscribe(&editor, |this, _, event: &EditorEvent, cx| {
+            if let EditorEvent::BufferEdited = event {
+                this.parse(true, cx);
+            }
+        });
+        let tab_description = editor
+            .read(cx)
+            .tab_description(0, cx)
+            .map(|description| format!("Table {description}"));
+
+        let mut this = Self {
+            workspace,
+            editor,
+            focus_handle: cx.focus_handle(),
+            table: Table::default(),
+            numeric_columns: Vec::new(),
+            column_widths: Vec::new(),
+            row_order: Vec::new(),
+            sort: None,
+            selection: None,
+            em_width: px(0.),
+            scroll_handle: UniformListScrollHandle::new(),
+            tab_description,
+            parsing_task: None,
+            _subscription: subscription,
+        };
+        this.parse(false, cx);
+        this
+    }
+
+    fn parse(&mut self, wait_for_debounce: bool, cx: &mut ViewContext<Self>) {
+        let editor = self.editor.clone();
+        self.parsing_task = Some(cx.spawn(|view, mut cx| async move {
+            if wait_for_debounce {
+                // Wait for the user to stop typing
+                cx.background_executor().timer(REPARSE_DEBOUNCE).await;
+            }
+
+            let (text, extension) = view.update(&mut cx, |_, cx| {
+                let text = editor.read(cx).buffer().read(cx).snapshot(cx).text();
+                (text, file_extension(&editor, cx))
+            })?;
+            let table = cx
+                .background_executor()
+                .spawn(async move {
+                    let delimiter = detect_delimiter(extension.as_deref(), &text);
+                    parse_table(&text, delimiter)
+                })
+                .await;
+            view.update(&mut cx, |view, cx| view.set_table(table, cx))
+        }));
+    }
+
+    fn set_table(&mut self, table: Table, cx: &mut ViewContext<Self>) {
+        self.numeric_columns = (0..table.column_count)
+            .map(|column| table.is_numeric_column(column))
+            .collect();
+        self.column_widths = table.column_widths();
+        self.table = table;
+        self.sort = self
+            .sort
+            .filter(|sort| sort.column < self.table.column_count);
+        self.selection = self.selection.filter(|selection| match *selection {
+            TableSelection::Cell { row, column } => self.table.cell(row, column).is_some(),
+            TableSelection::Column(column) => column < self.table.column_count,
+        });
+        self.update_row_order();
+        cx.notify();
+    }
+
+    fn update_row_order(&mut self) {
+        self.row_order = match self.sort {
+            Some(sort) => self.table.sorted_row_order(sort.column, sort.ascending),
+            None => (1..self.table.rows.len()).collect(),
+        };
+    }
+
+    /// Sorts by the column in ascending, then descending order, then goes back
+    /// to the order of the file.
+    fn toggle_sort(&mut self, column: usize, cx: &mut ViewContext<Self>) {
+        self.sort = match self.sort {
+            Some(sort) if sort.column == column && sort.ascending => Some(TableSort {
+                column,
+                ascending: false,
+            }),
+            Some(sort) if sort.column == column => None,
+            _ => Some(TableSort {
+                column,
+                ascending: true,
+            }),
+        };
+        self.update_row_order();
+        cx.notify();
+    }
+
+    fn select(&mut self, selection: TableSelection, cx: &mut ViewContext<Self>) {
+        self.selection = Some(selection);
+        cx.focus(&self.focus_handle);
+        cx.notify();
+    }
+
+    /// Copies the selected cell, or the cells of the selected column in the
+    /// order they're shown in.
+    fn copy_selection(&mut self, _: &CopySelection, cx: &mut ViewContext<Self>) {
+        let text = match self.selection {
+            None => return,
+            Some(TableSelection::Cell { row, column }) => self
+                .table
+                .cell(row, column)
+                .map(|cell| cell.text.clone())
+                .unwrap_or_default(),
+            Some(TableSelection::Column(column)) => std::iter::once(0)
+                .chain(self.row_order.iter().copied())
+                .map(|row| {
+                    self.table
+                        .cell(row, column)
+                        .map_or("", |cell| cell.text.as_str())
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+        cx.write_to_clipboard(ClipboardItem::new_string(text));
+    }
+
+    /// Switches to the raw text of the file, with the cursor at the start of
+    /// the cell.
+    fn reveal_cell(&mut self, row: usize, column: usize, cx: &mut ViewContext<Self>) {
+        let Some(cell) = self.table.cell(row, column) else {
+            return;
+        };
+        let offset = cell.range.start;
+        self.editor.update(cx, |editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let offset = snapshot.clip_offset(offset.min(snapshot.len()), Bias::Left);
+            editor.change_selections(Some(Autoscroll::center()), cx, |selections| {
+                selections.select_ranges([offset..offset])
+            });
+        });
+        let editor = self.editor.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace.active_pane().update(cx, |pane, cx| {
+                    pane.add_item(Box::new(editor), true, true, None, cx)
+                });
+            })
+            .ok();
+    }
+
+    fn column_width(&self, column: usize) -> Pixels {
+        self.em_width * self.column_widths[column] as f32 + SORT_BUTTON_WIDTH + CELL_PADDING * 2.
+    }
+
+    fn total_width(&self) -> Pixels {
+        (0..self.table.column_count)
+            .map(|column| self.column_width(column))
+            .fold(px(0.), |total, width| total + width)
+    }
+
+    fn is_selected(&self, row: usize, column: usize) -> bool {
+        match self.selection {
+            Some(TableSelection::Cell {
+                row: selected_row,
+                column: selected_column,
+            }) => selected_row == row && selected_column == column,
+            Some(TableSelection::Column(selected_column)) => selected_column == column,
+            None => false,
+        }
+    }
+
+    fn render_cell(&self, row: usize, column: usize, cx: &mut ViewContext<Self>) -> Stateful<Div> {
+        // Line breaks within cells are shown as single characters, so that
+        // every row is one line tall.
+        let text = self
+            .table
+            .cell(row, column)
+            .map(|cell| cell.text.replace("\r\n", "\n").replace('\n', "↵"))
+            .unwrap_or_default();
+        let is_numeric = row > 0 && self.numeric_columns[column];
+
+        h_flex()
+            .id(("csv-cell", row * self.table.column_count + column))
+            .flex_none()
+            .w(self.column_width(column))
+            .px(CELL_PADDING)
+            .overflow_hidden()
+            .border_r_1()
+            .border_color(cx.theme().colors().border_variant)
+            .when(is_numeric, |cell| cell.justify_end())
+            .when(self.is_selected(row, column), |cell| {
+                cell.bg(cx.theme().colors().element_selected)
+            })
+            .child(div().whitespace_nowrap().text_ellipsis().child(text))
+            .on_click(cx.listener(move |this, event: &ClickEvent, cx| {
+                if event.down.click_count == 2 {
+                    this.reveal_cell(row, column, cx);
+                } else if row == 0 {
+                    this.select(TableSelection::Column(column), cx);
+                } else {
+                    this.select(TableSelection::Cell { row, column }, cx);
+                }
+            }))
+    }
+
+    fn render_header(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let cells = (0..self.table.column_count).map(|column| {
+            let sort_icon = match self.sort {
+                Some(sort) if sort.column == column && sort.ascending => IconName::ArrowUp,
+                Some(sort) if sort.column == column => IconName::ArrowDown,
+                _ => IconName::ChevronUpDown,
+            };
+            self.render_cell(0, column, cx)
+                .justify_between()
+                .font_weight(FontWeight::BOLD)
+                .child(
+                    IconButton::new(("csv-sort", column), sort_icon)
+                        .icon_size(IconSize::XSmall)
+                        .icon_color(Color::Muted)
+                        .on_click(cx.listener(move |this, _, cx| this.toggle_sort(column, cx))),
+                )
+        });
+        h_flex()
+            .w(self.total_width())
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().title_bar_background)
+            .children(cells.collect::<Vec<_>>())
+    }
+
+    fn render_row(&self, ix: usize, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let row = self.row_order[ix];
+        h_flex()
+            .w(self.total_width())
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .children(
+                (0..self.table.column_count)
+                    .map(|column| self.render_cell(row, column, cx))
+                    .collect::<Vec<_>>(),
+            )
+    }
+}
+
+fn file_extension(editor: &View<Editor>, cx: &AppContext) -> Option<String> {
+    let buffer = editor.read(cx).buffer().read(cx).as_singleton()?;
+    let file = buffer.read(cx).file()?;
+    let extension = file.path().extension()?.to_str()?;
+    Some(extension.to_lowercase())
+}
+
+impl FocusableView for CsvTableView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CsvTableViewEvent {}
+
+impl EventEmitter<CsvTableViewEvent> for CsvTableView {}
+
+impl Item for CsvTableView {
+    type Event = CsvTableViewEvent;
+
+    fn tab_icon(&self, _cx: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::FileDoc))
+    }
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some(
+            self.tab_description
+                .clone()
+                .unwrap_or_else(|| "Table".to_string())
+                .into(),
+        )
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("csv table view")
+    }
+
+    fn to_item_events(_event: &Self::Event, _f: impl FnMut(ItemEvent)) {}
+}
+
+impl Render for CsvTableView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let font = settings.buffer_font.clone();
+        let font_size = settings.buffer_font_size(cx);
+        let font_id = cx.text_system().resolve_font(&font);
+        self.em_width = cx
+            .text_system()
+            .advance(font_id, font_size, 'm')
+            .map(|advance| advance.width)
+            .unwrap_or(font_size * 0.6);
+
+        let table = if self.table.rows.is_empty() {
+            div()
+                .p_4()
+                .child(Label::new("This file has no rows").color(Color::Muted))
+                .into_any_element()
+        } else {
+            // The heading stays in view as the rows scroll vertically, and
+            // scrolls with them horizontally.
+            div()
+                .id("csv-table")
+                .size_full()
+                .overflow_x_scroll()
+                .child(
+                    v_flex()
+                        .w(self.total_width())
+                        .h_full()
+                        .child(self.render_header(cx))
+                        .child(
+                            uniform_list(
+                                cx.view().clone(),
+                                "csv-table-rows",
+                                self.row_order.len(),
+                                |this, range, cx| range.map(|ix| this.render_row(ix, cx)).collect(),
+                            )
+                            .flex_grow()
+                            .track_scroll(self.scroll_handle.clone()),
+                        ),
+                )
+                .into_any_element()
+        };
+
+        v_flex()
+            .id("CsvTableView")
+            .key_context("CsvTableView")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::copy_selection))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .font(font)
+            .text_size(font_size)
+            .child(table)
+    }
+}
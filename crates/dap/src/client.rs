@@ -0,0 +1,409 @@
+use std::{
+    path::PathBuf,
+    process::Stdio,
+    sync::{
+        atomic::{AtomicU64, Ordering::SeqCst},
+        Arc,
+    },
+};
+
+use anyhow::{anyhow, Context as _, Result};
+use collections::HashMap;
+use futures::{
+    channel::{
+        mpsc::{self, UnboundedReceiver, UnboundedSender},
+        oneshot,
+    },
+    AsyncRead, AsyncWrite,
+};
+use gpui::{BackgroundExecutor, Task};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use smol::{
+    channel,
+    io::{AsyncBufReadExt as _, BufReader},
+    process::{self, Child},
+};
+use util::ResultExt as _;
+
+use crate::{
+    requests::{Disconnect, Initialize},
+    transport::{read_message, write_message},
+    Capabilities, DisconnectArguments, Event, InitializeArguments, Message, Request,
+};
+
+type ResponseHandlers = Arc<Mutex<Option<HashMap<u64, oneshot::Sender<Result<Value>>>>>>;
+
+/// The command starting a debug adapter, which communicates over its stdio.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DebugAdapterBinary {
+    pub command: String,
+    pub args: Vec<String>,
+    pub env: Option<HashMap<String, String>>,
+    pub cwd: PathBuf,
+}
+
+/// A running debug adapter process.
+pub struct DebugAdapterClient {
+    name: String,
+    next_seq: Arc<AtomicU64>,
+    outbound_tx: channel::Sender<String>,
+    response_handlers: ResponseHandlers,
+    capabilities: Mutex<Capabilities>,
+    process: Mutex<Option<Child>>,
+    _io_tasks: Vec<Task<Option<()>>>,
+}
+
+impl DebugAdapterClient {
+    /// Starts a debug adapter process. The events it sends are delivered
+    /// through the returned channel.
+    pub fn start(
+        binary: DebugAdapterBinary,
+        executor: &BackgroundExecutor,
+    ) -> Result<(Self, UnboundedReceiver<Event>)> {
+        log::info!(
+            "starting debug adapter. command: {:?}, working directory: {:?}, args: {:?}",
+            binary.command,
+            binary.cwd,
+            binary.args
+        );
+
+        let mut process = process::Command::new(&binary.command)
+            .current_dir(&binary.cwd)
+            .args(&binary.args)
+            .envs(binary.env.clone().unwrap_or_default())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("failed to spawn debug adapter {:?}", binary.command))?;
+
+        let stdin = process
+            .stdin
+            .take()
+            .context("missing debug adapter stdin")?;
+        let stdout = process
+            .stdout
+            .take()
+            .context("missing debug adapter stdout")?;
+        let stderr = process
+            .stderr
+            .take()
+            .context("missing debug adapter stderr")?;
+
+        let (client, events) = Self::new_internal(stdin, stdout, Some(stderr), executor);
+        *client.process.lock() = Some(process);
+        Ok((
+            Self {
+                name: binary.command,
+                ..client
+            },
+            events,
+        ))
+    }
+
+    fn new_internal<Stdin, Stdout, Stderr>(
+        stdin: Stdin,
+        stdout: Stdout,
+        stderr: Option<Stderr>,
+        executor: &BackgroundExecutor,
+    ) -> (Self, UnboundedReceiver<Event>)
+    where
+        Stdin: AsyncWrite + Unpin + Send + 'static,
+        Stdout: AsyncRead + Unpin + Send + 'static,
+        Stderr: AsyncRead + Unpin + Send + 'static,
+    {
+        let (outbound_tx, outbound_rx) = channel::unbounded::<String>();
+        let (events_tx, events_rx) = mpsc::unbounded();
+        let response_handlers = ResponseHandlers::new(Mutex::new(Some(HashMap::default())));
+        let next_seq = Arc::new(AtomicU64::new(1));
+
+        let mut io_tasks = vec![
+            executor.spawn(
+                Self::handle_input(
+                    stdout,
+                    response_handlers.clone(),
+                    events_tx,
+                    outbound_tx.clone(),
+                    next_seq.clone(),
+                )
+                .log_err(),
+            ),
+            executor.spawn(Self::handle_output(stdin, outbound_rx).log_err()),
+        ];
+        if let Some(stderr) = stderr {
+            io_tasks.push(executor.spawn(Self::handle_stderr(stderr).log_err()));
+        }
+
+        (
+            Self {
+                name: String::new(),
+                next_seq,
+                outbound_tx,
+                response_handlers,
+                capabilities: Mutex::default(),
+                process: Mutex::default(),
+                _io_tasks: io_tasks,
+            },
+            events_rx,
+        )
+    }
+
+    async fn handle_input<Stdout>(
+        stdout: Stdout,
+        response_handlers: ResponseHandlers,
+        events_tx: UnboundedSender<Event>,
+        outbound_tx: channel::Sender<String>,
+        next_seq: Arc<AtomicU64>,
+    ) -> Result<()>
+    where
+        Stdout: AsyncRead + Unpin + Send + 'static,
+    {
+        let _clear_response_handlers = util::defer({
+            let response_handlers = response_handlers.clone();
+            move || {
+                response_handlers.lock().take();
+            }
+        });
+        let mut stdout = BufReader::new(stdout);
+        let mut buffer = Vec::new();
+        while let Some(message) = read_message(&mut stdout, &mut buffer).await? {
+            log::trace!("incoming debug adapter message: {message}");
+            match serde_json::from_str::<Message>(&message) {
+                Ok(Message::Response(response)) => {
+                    let handler = response_handlers
+                        .lock()
+                        .as_mut()
+                        .and_then(|handlers| handlers.remove(&response.request_seq));
+                    if let Some(handler) = handler {
+                        let result = if response.success {
+                            Ok(response.body.unwrap_or(Value::Null))
+                        } else {
+                            Err(anyhow!(response.error_message()))
+                        };
+                        handler.send(result).ok();
+                    }
+                }
+                Ok(Message::Event(event)) => {
+                    if let Some(event) = Event::from_message(event).log_err() {
+                        events_tx.unbounded_send(event).ok();
+                    }
+                }
+                Ok(Message::Request(request)) => {
+                    // Reverse requests, such as `runInTerminal`, aren't supported.
+                    let response = json!({
+                        "seq": next_seq.fetch_add(1, SeqCst),
+                        "type": "response",
+                        "request_seq": request.seq,
+                        "command": request.command,
+                        "success": false,
+                        "message": "unsupported request",
+                    });
+                    outbound_tx.send(response.to_string()).await?;
+                }
+                Err(error) => {
+                    log::warn!("failed to deserialize debug adapter message: {error}\n{message}")
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_output<Stdin>(
+        stdin: Stdin,
+        outbound_rx: channel::Receiver<String>,
+    ) -> Result<()>
+    where
+        Stdin: AsyncWrite + Unpin + Send + 'static,
+    {
+        let mut stdin = futures::io::BufWriter::new(stdin);
+        while let Ok(message) = outbound_rx.recv().await {
+            log::trace!("outgoing debug adapter message: {message}");
+            write_message(&mut stdin, &message).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_stderr<Stderr>(stderr: Stderr) -> Result<()>
+    where
+        Stderr: AsyncRead + Unpin + Send + 'static,
+    {
+        let mut stderr = BufReader::new(stderr);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if stderr.read_line(&mut line).await? == 0 {
+                return Ok(());
+            }
+            log::debug!("debug adapter stderr: {}", line.trim_end());
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities.lock().clone()
+    }
+
+    /// Sends a request and waits for its response.
+    pub async fn request<R: Request>(&self, arguments: R::Arguments) -> Result<R::Response> {
+        let seq = self.next_seq.fetch_add(1, SeqCst);
+        let mut message = json!({
+            "seq": seq,
+            "type": "request",
+            "command": R::COMMAND,
+        });
+        let arguments = serde_json::to_value(arguments)?;
+        if !arguments.is_null() {
+            message["arguments"] = arguments;
+        }
+
+        let (tx, rx) = oneshot::channel();
+        self.response_handlers
+            .lock()
+            .as_mut()
+            .ok_or_else(|| anyhow!("debug adapter {} has exited", self.name))?
+            .insert(seq, tx);
+        self.outbound_tx.send(message.to_string()).await?;
+
+        let body = rx
+            .await
+            .map_err(|_| anyhow!("debug adapter {} has exited", self.name))??;
+        serde_json::from_value(body)
+            .with_context(|| format!("invalid response to the {} request", R::COMMAND))
+    }
+
+    /// Sends the `initialize` request, which must be the first request sent.
+    pub async fn initialize(&self, adapter_id: &str) -> Result<Capabilities> {
+        let capabilities = self
+            .request::<Initialize>(InitializeArguments {
+                client_id: "zed".into(),
+                client_name: "Zed".into(),
+                adapter_id: adapter_id.into(),
+                lines_start_at1: true,
+                columns_start_at1: true,
+                path_format: "path".into(),
+                supports_variable_type: true,
+                supports_run_in_terminal_request: false,
+            })
+            .await?;
+        *self.capabilities.lock() = capabilities.clone();
+        Ok(capabilities)
+    }
+
+    /// Ends the session, terminating the debuggee, and stops the adapter.
+    pub async fn shutdown(&self) -> Result<()> {
+        let result = self
+            .request::<Disconnect>(DisconnectArguments {
+                terminate_debuggee: Some(true),
+            })
+            .await;
+        if let Some(mut process) = self.process.lock().take() {
+            process.kill().log_err();
+        }
+        result.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{requests::Threads, StoppedEvent, Thread};
+    use futures::StreamExt as _;
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_debug_adapter_client(cx: &mut TestAppContext) {
+        let (client_stdin, adapter_stdin) = async_pipe::pipe();
+        let (adapter_stdout, client_stdout) = async_pipe::pipe();
+        let (client, mut events) = DebugAdapterClient::new_internal(
+            client_stdin,
+            client_stdout,
+            None::<async_pipe::PipeReader>,
+            &cx.executor(),
+        );
+        let mut adapter_stdin = BufReader::new(adapter_stdin);
+        let mut adapter_stdout = adapter_stdout;
+
+        let client = Arc::new(client);
+        let request = cx.executor().spawn({
+            let client = client.clone();
+            async move { client.request::<Threads>(Value::Null).await }
+        });
+        let mut buffer = Vec::new();
+        let message = read_message(&mut adapter_stdin, &mut buffer)
+            .await
+            .unwrap()
+            .unwrap();
+        let message = serde_json::from_str::<Value>(&message).unwrap();
+        assert_eq!(message["command"], "threads");
+        assert_eq!(message["arguments"], Value::Null);
+
+        write_message(
+            &mut adapter_stdout,
+            &json!({
+                "seq": 1,
+                "type": "event",
+                "event": "stopped",
+                "body": { "reason": "pause", "threadId": 7 }
+            })
+            .to_string(),
+        )
+        .await
+        .unwrap();
+        write_message(
+            &mut adapter_stdout,
+            &json!({
+                "seq": 2,
+                "type": "response",
+                "request_seq": message["seq"],
+                "command": "threads",
+                "success": true,
+                "body": { "threads": [{ "id": 7, "name": "main" }] }
+            })
+            .to_string(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            request.await.unwrap().threads,
+            vec![Thread {
+                id: 7,
+                name: "main".into()
+            }]
+        );
+        assert_eq!(
+            events.next().await,
+            Some(Event::Stopped(StoppedEvent {
+                reason: "pause".into(),
+                description: None,
+                thread_id: Some(7),
+                all_threads_stopped: false,
+            }))
+        );
+
+        // Reverse requests are rejected.
+        write_message(
+            &mut adapter_stdout,
+            r#"{"seq":3,"type":"request","command":"runInTerminal","arguments":{}}"#,
+        )
+        .await
+        .unwrap();
+        let message = read_message(&mut adapter_stdin, &mut buffer)
+            .await
+            .unwrap()
+            .unwrap();
+        let message = serde_json::from_str::<Value>(&message).unwrap();
+        assert_eq!(message["request_seq"], 3);
+        assert_eq!(message["success"], false);
+
+        // Requests fail once the adapter exits.
+        drop(adapter_stdout);
+        cx.run_until_parked();
+        assert!(client.request::<Threads>(Value::Null).await.is_err());
+    }
+}
@@ -0,0 +1,8 @@
+//! A client for debug adapters speaking the Debug Adapter Protocol.
+
+mod client;
+mod transport;
+mod types;
+
+pub use client::*;
+pub use types::*;
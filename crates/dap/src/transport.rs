@@ -0,0 +1,91 @@
+use anyhow::{anyhow, Context as _, Result};
+use futures::{
+    AsyncBufRead, AsyncBufReadExt as _, AsyncReadExt as _, AsyncWrite, AsyncWriteExt as _,
+};
+
+const CONTENT_LEN_HEADER: &str = "Content-Length: ";
+
+/// Reads the next message sent by a debug adapter. The base protocol frames
+/// messages with the same headers as the language server protocol.
+///
+/// Returns `None` once the adapter closes its output.
+pub(crate) async fn read_message<Input>(
+    input: &mut Input,
+    buffer: &mut Vec<u8>,
+) -> Result<Option<String>>
+where
+    Input: AsyncBufRead + Unpin,
+{
+    let mut content_len = None;
+    loop {
+        buffer.clear();
+        if input.read_until(b'\n', buffer).await? == 0 {
+            return Ok(None);
+        }
+        let header = std::str::from_utf8(buffer)?.trim_end();
+        if header.is_empty() {
+            if content_len.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some(len) = header.strip_prefix(CONTENT_LEN_HEADER) {
+            content_len = Some(
+                len.parse::<usize>()
+                    .with_context(|| format!("invalid debug adapter header {header:?}"))?,
+            );
+        }
+    }
+
+    let content_len = content_len.ok_or_else(|| anyhow!("missing content length"))?;
+    buffer.resize(content_len, 0);
+    input.read_exact(buffer).await?;
+    Ok(Some(String::from_utf8(buffer.clone())?))
+}
+
+pub(crate) async fn write_message<Output>(output: &mut Output, message: &str) -> Result<()>
+where
+    Output: AsyncWrite + Unpin,
+{
+    output
+        .write_all(format!("{CONTENT_LEN_HEADER}{}\r\n\r\n", message.len()).as_bytes())
+        .await?;
+    output.write_all(message.as_bytes()).await?;
+    output.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_read_and_write_messages() {
+        let mut output = Vec::new();
+        write_message(&mut output, r#"{"seq":1}"#).await.unwrap();
+        write_message(&mut output, r#"{"seq":2,"body":"é"}"#)
+            .await
+            .unwrap();
+        assert!(output.starts_with(b"Content-Length: 9\r\n\r\n{\"seq\":1}"));
+
+        let mut input = smol::io::BufReader::new(output.as_slice());
+        let mut buffer = Vec::new();
+        assert_eq!(
+            read_message(&mut input, &mut buffer).await.unwrap(),
+            Some(r#"{"seq":1}"#.to_string())
+        );
+        assert_eq!(
+            read_message(&mut input, &mut buffer).await.unwrap(),
+            Some(r#"{"seq":2,"body":"é"}"#.to_string())
+        );
+        assert_eq!(read_message(&mut input, &mut buffer).await.unwrap(), None);
+
+        let mut input = smol::io::BufReader::new(
+            b"Content-Type: application/json\r\nContent-Length: 2\r\n\r\n{}" as &[u8],
+        );
+        assert_eq!(
+            read_message(&mut input, &mut buffer).await.unwrap(),
+            Some("{}".to_string())
+        );
+    }
+}
@@ -0,0 +1,361 @@
+//! The subset of the [Debug Adapter Protocol](https://microsoft.github.io/debug-adapter-protocol/specification)
+//! used by Zed.
+
+use anyhow::Result;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+/// A request sent to a debug adapter.
+pub trait Request {
+    type Arguments: Serialize;
+    type Response: DeserializeOwned;
+    const COMMAND: &'static str;
+}
+
+/// A message sent by a debug adapter.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum Message {
+    Request(RequestMessage),
+    Response(ResponseMessage),
+    Event(EventMessage),
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RequestMessage {
+    pub seq: u64,
+    pub command: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ResponseMessage {
+    pub request_seq: u64,
+    pub success: bool,
+    pub message: Option<String>,
+    pub body: Option<Value>,
+}
+
+impl ResponseMessage {
+    /// The error of a failed request, preferring the detailed error in the
+    /// body over the short message.
+    pub fn error_message(&self) -> String {
+        self.body
+            .as_ref()
+            .and_then(|body| body.get("error")?.get("format")?.as_str())
+            .or(self.message.as_deref())
+            .unwrap_or("request failed")
+            .to_string()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct EventMessage {
+    pub event: String,
+    pub body: Option<Value>,
+}
+
+/// An event sent by a debug adapter.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    Initialized,
+    Stopped(StoppedEvent),
+    Continued(ContinuedEvent),
+    Exited(ExitedEvent),
+    Terminated,
+    Output(OutputEvent),
+    Other(String),
+}
+
+impl Event {
+    pub(crate) fn from_message(message: EventMessage) -> Result<Self> {
+        let body = message.body.unwrap_or(Value::Null);
+        Ok(match message.event.as_str() {
+            "initialized" => Self::Initialized,
+            "stopped" => Self::Stopped(serde_json::from_value(body)?),
+            "continued" => Self::Continued(serde_json::from_value(body)?),
+            "exited" => Self::Exited(serde_json::from_value(body)?),
+            "terminated" => Self::Terminated,
+            "output" => Self::Output(serde_json::from_value(body)?),
+            _ => Self::Other(message.event),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoppedEvent {
+    pub reason: String,
+    pub description: Option<String>,
+    pub thread_id: Option<u64>,
+    #[serde(default)]
+    pub all_threads_stopped: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContinuedEvent {
+    pub thread_id: u64,
+    #[serde(default)]
+    pub all_threads_continued: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExitedEvent {
+    pub exit_code: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct OutputEvent {
+    pub category: Option<String>,
+    pub output: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Capabilities {
+    pub supports_configuration_done_request: bool,
+    pub supports_conditional_breakpoints: bool,
+    pub supports_evaluate_for_hovers: bool,
+    pub supports_terminate_request: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeArguments {
+    pub client_id: String,
+    pub client_name: String,
+    pub adapter_id: String,
+    pub lines_start_at1: bool,
+    pub columns_start_at1: bool,
+    pub path_format: String,
+    pub supports_variable_type: bool,
+    pub supports_run_in_terminal_request: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Source {
+    pub name: Option<String>,
+    pub path: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SourceBreakpoint {
+    pub line: u32,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SetBreakpointsArguments {
+    pub source: Source,
+    pub breakpoints: Vec<SourceBreakpoint>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SetBreakpointsResponse {
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Breakpoint {
+    pub verified: bool,
+    pub line: Option<u32>,
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ThreadsResponse {
+    pub threads: Vec<Thread>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Thread {
+    pub id: u64,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StackTraceArguments {
+    pub thread_id: u64,
+    pub levels: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StackTraceResponse {
+    pub stack_frames: Vec<StackFrame>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct StackFrame {
+    pub id: u64,
+    pub name: String,
+    pub source: Option<Source>,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopesArguments {
+    pub frame_id: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ScopesResponse {
+    pub scopes: Vec<Scope>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scope {
+    pub name: String,
+    pub variables_reference: u64,
+    #[serde(default)]
+    pub expensive: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariablesArguments {
+    pub variables_reference: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct VariablesResponse {
+    pub variables: Vec<Variable>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Variable {
+    pub name: String,
+    pub value: String,
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    #[serde(default)]
+    pub variables_reference: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadArguments {
+    pub thread_id: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateArguments {
+    pub expression: String,
+    pub frame_id: Option<u64>,
+    pub context: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateResponse {
+    pub result: String,
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    #[serde(default)]
+    pub variables_reference: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisconnectArguments {
+    pub terminate_debuggee: Option<bool>,
+}
+
+macro_rules! request {
+    ($name:ident, $command:literal, $arguments:ty, $response:ty) => {
+        pub enum $name {}
+
+        impl Request for $name {
+            type Arguments = $arguments;
+            type Response = $response;
+            const COMMAND: &'static str = $command;
+        }
+    };
+}
+
+pub mod requests {
+    use super::*;
+
+    request!(Initialize, "initialize", InitializeArguments, Capabilities);
+    request!(Launch, "launch", Value, Value);
+    request!(Attach, "attach", Value, Value);
+    request!(
+        SetBreakpoints,
+        "setBreakpoints",
+        SetBreakpointsArguments,
+        SetBreakpointsResponse
+    );
+    request!(ConfigurationDone, "configurationDone", Value, Value);
+    request!(Threads, "threads", Value, ThreadsResponse);
+    request!(
+        StackTrace,
+        "stackTrace",
+        StackTraceArguments,
+        StackTraceResponse
+    );
+    request!(Scopes, "scopes", ScopesArguments, ScopesResponse);
+    request!(
+        Variables,
+        "variables",
+        VariablesArguments,
+        VariablesResponse
+    );
+    request!(Continue, "continue", ThreadArguments, Value);
+    request!(Next, "next", ThreadArguments, Value);
+    request!(StepIn, "stepIn", ThreadArguments, Value);
+    request!(StepOut, "stepOut", ThreadArguments, Value);
+    request!(Pause, "pause", ThreadArguments, Value);
+    request!(Evaluate, "evaluate", EvaluateArguments, EvaluateResponse);
+    request!(Disconnect, "disconnect", DisconnectArguments, Value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_messages() {
+        let message = serde_json::from_str::<Message>(
+            r#"{"seq":3,"type":"event","event":"stopped","body":{"reason":"breakpoint","threadId":1,"allThreadsStopped":true}}"#,
+        )
+        .unwrap();
+        let Message::Event(event) = message else {
+            panic!("expected an event, got {message:?}");
+        };
+        assert_eq!(
+            Event::from_message(event).unwrap(),
+            Event::Stopped(StoppedEvent {
+                reason: "breakpoint".into(),
+                description: None,
+                thread_id: Some(1),
+                all_threads_stopped: true,
+            })
+        );
+
+        let message = serde_json::from_str::<Message>(
+            r#"{"seq":4,"type":"response","request_seq":2,"success":false,"command":"evaluate","message":"error","body":{"error":{"id":1,"format":"unknown variable"}}}"#,
+        )
+        .unwrap();
+        let Message::Response(response) = message else {
+            panic!("expected a response, got {message:?}");
+        };
+        assert_eq!(response.request_seq, 2);
+        assert!(!response.success);
+        assert_eq!(response.error_message(), "unknown variable");
+
+        let message = serde_json::from_str::<Message>(
+            r#"{"seq":5,"type":"request","command":"runInTerminal","arguments":{}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            message,
+            Message::Request(RequestMessage { seq: 5, .. })
+        ));
+    }
+}
@@ -0,0 +1,297 @@
+//! Connections to Postgres, MySQL, and SQLite databases. The drivers run on a
+//! Tokio runtime of their own, and connections are pooled by URL, so that
+//! statements run one after another reuse them.
+use std::{
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context as _, Result};
+use collections::HashMap;
+use futures::TryStreamExt as _;
+use gpui::{AppContext, Global, Task};
+use parking_lot::Mutex;
+use sqlx::{
+    mysql::MySqlPoolOptions, postgres::PgPoolOptions, sqlite::SqlitePoolOptions, Column as _,
+    ColumnIndex, Decode, Either, Row, ValueRef as _,
+};
+use tokio::runtime::Runtime;
+
+use crate::QueryResults;
+
+/// The most rows fetched for a statement.
+pub const MAX_ROWS: usize = 10_000;
+
+/// The most connections kept open to each database.
+const MAX_CONNECTIONS: u32 = 2;
+
+/// How long connections to a database are kept open after they were last used.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DatabaseKind {
+    Postgres,
+    MySql,
+    Sqlite,
+}
+
+impl DatabaseKind {
+    /// Returns the kind of database at the URL, by its scheme.
+    pub fn for_url(url: &str) -> Option<Self> {
+        let (scheme, _) = url.split_once(':')?;
+        match scheme.to_ascii_lowercase().as_str() {
+            "postgres" | "postgresql" => Some(Self::Postgres),
+            "mysql" => Some(Self::MySql),
+            "sqlite" => Some(Self::Sqlite),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the URL with the path of a SQLite database made absolute, relative
+/// to the root of the project it's configured in.
+pub fn resolve_url(url: &str, project_root: Option<&Path>) -> String {
+    if DatabaseKind::for_url(url) != Some(DatabaseKind::Sqlite) {
+        return url.to_string();
+    }
+    let rest = &url["sqlite:".len()..];
+    let rest = rest.strip_prefix("//").unwrap_or(rest);
+    let (path, query) = match rest.find('?') {
+        Some(ix) => rest.split_at(ix),
+        None => (rest, ""),
+    };
+    match project_root {
+        Some(root) if path != ":memory:" && Path::new(path).is_relative() => {
+            format!("sqlite://{}{query}", root.join(path).display())
+        }
+        _ => url.to_string(),
+    }
+}
+
+#[derive(Clone)]
+enum Pool {
+    Postgres(sqlx::PgPool),
+    MySql(sqlx::MySqlPool),
+    Sqlite(sqlx::SqlitePool),
+}
+
+/// Runs a statement on a pool, collecting its rows and the number of rows it
+/// affected. It's a macro as the drivers share no trait for the results of
+/// statements.
+macro_rules! fetch_results {
+    ($pool:expr, $statement:expr) => {{
+        let mut results = QueryResults::default();
+        let mut stream = sqlx::raw_sql($statement).fetch_many($pool);
+        while let Some(item) = stream.try_next().await? {
+            match item {
+                Either::Left(done) => results.rows_affected += done.rows_affected(),
+                Either::Right(row) => {
+                    if results.rows.len() == MAX_ROWS {
+                        results.truncated = true;
+                        break;
+                    }
+                    if results.columns.is_empty() {
+                        results.columns = row
+                            .columns()
+                            .iter()
+                            .map(|column| column.name().to_string())
+                            .collect();
+                    }
+                    results
+                        .rows
+                        .push((0..row.len()).map(|ix| cell_text(&row, ix)).collect());
+                }
+            }
+        }
+        results
+    }};
+}
+
+impl Pool {
+    fn connect_lazy(url: &str) -> Result<Self> {
+        Ok(match DatabaseKind::for_url(url) {
+            Some(DatabaseKind::Postgres) => Pool::Postgres(
+                PgPoolOptions::new()
+                    .max_connections(MAX_CONNECTIONS)
+                    .idle_timeout(IDLE_TIMEOUT)
+                    .connect_lazy(url)?,
+            ),
+            Some(DatabaseKind::MySql) => Pool::MySql(
+                MySqlPoolOptions::new()
+                    .max_connections(MAX_CONNECTIONS)
+                    .idle_timeout(IDLE_TIMEOUT)
+                    .connect_lazy(url)?,
+            ),
+            Some(DatabaseKind::Sqlite) => Pool::Sqlite(
+                SqlitePoolOptions::new()
+                    .max_connections(MAX_CONNECTIONS)
+                    .idle_timeout(IDLE_TIMEOUT)
+                    .connect_lazy(url)?,
+            ),
+            None => bail!("database URLs must start with postgres:, mysql:, or sqlite:"),
+        })
+    }
+
+    async fn run(&self, statement: &str) -> Result<QueryResults> {
+        let started_at = Instant::now();
+        let mut results = match self {
+            Pool::Postgres(pool) => fetch_results!(pool, statement),
+            Pool::MySql(pool) => fetch_results!(pool, statement),
+            Pool::Sqlite(pool) => fetch_results!(pool, statement),
+        };
+        results.elapsed = started_at.elapsed();
+        Ok(results)
+    }
+
+    async fn close(&self) {
+        match self {
+            Pool::Postgres(pool) => pool.close().await,
+            Pool::MySql(pool) => pool.close().await,
+            Pool::Sqlite(pool) => pool.close().await,
+        }
+    }
+}
+
+/// Returns the text of a value, or `None` for nulls. Statements are run with
+/// the drivers' text protocols, so values of every type can be read as text,
+/// except for binary data that isn't UTF-8.
+fn cell_text<'r, R: Row>(row: &'r R, index: usize) -> Option<String>
+where
+    usize: ColumnIndex<R>,
+    String: Decode<'r, R::Database>,
+    Vec<u8>: Decode<'r, R::Database>,
+{
+    if row.try_get_raw(index).map_or(true, |value| value.is_null()) {
+        return None;
+    }
+    row.try_get_unchecked::<String, _>(index).ok().or_else(|| {
+        let bytes = row.try_get_unchecked::<Vec<u8>, _>(index).ok()?;
+        Some(format!("<{} bytes>", bytes.len()))
+    })
+}
+
+/// Keeps the pools of connections to the databases that statements were run
+/// on.
+#[derive(Default)]
+pub struct ConnectionManager {
+    runtime: Mutex<Option<Arc<Runtime>>>,
+    pools: Mutex<HashMap<String, Pool>>,
+}
+
+impl Global for ConnectionManager {}
+
+impl ConnectionManager {
+    pub fn global(cx: &AppContext) -> &Self {
+        cx.global::<Self>()
+    }
+
+    /// Returns the runtime the drivers run on, starting it if it hasn't been.
+    fn runtime(&self) -> Result<Arc<Runtime>> {
+        let mut runtime = self.runtime.lock();
+        if let Some(runtime) = runtime.as_ref() {
+            return Ok(runtime.clone());
+        }
+        let new_runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("database")
+            .enable_all()
+            .build()
+            .context("starting the runtime for database connections")?;
+        Ok(runtime.insert(Arc::new(new_runtime)).clone())
+    }
+
+    fn pool(&self, url: &str, runtime: &Runtime) -> Result<Pool> {
+        let mut pools = self.pools.lock();
+        if let Some(pool) = pools.get(url) {
+            return Ok(pool.clone());
+        }
+        // Pools start their maintenance tasks on the current runtime.
+        let _guard = runtime.enter();
+        let pool = Pool::connect_lazy(url)?;
+        pools.insert(url.to_string(), pool.clone());
+        Ok(pool)
+    }
+
+    /// Runs a statement on the database at the URL.
+    pub fn run(&self, url: &str, statement: String, cx: &AppContext) -> Task<Result<QueryResults>> {
+        let pool = self
+            .runtime()
+            .and_then(|runtime| Ok((self.pool(url, &runtime)?, runtime)));
+        cx.background_executor().spawn(async move {
+            let (pool, runtime) = pool?;
+            runtime
+                .spawn(async move { pool.run(&statement).await })
+                .await
+                .context("the statement was cancelled")?
+        })
+    }
+
+    /// Closes the connections to every database, returning how many databases
+    /// had been connected to.
+    pub fn close_all(&self) -> usize {
+        let pools = std::mem::take(&mut *self.pools.lock());
+        let count = pools.len();
+        if let Some(runtime) = self.runtime.lock().as_ref() {
+            runtime.spawn(async move {
+                for pool in pools.into_values() {
+                    pool.close().await;
+                }
+            });
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_database_kind() {
+        assert_eq!(
+            DatabaseKind::for_url("postgres://localhost/app"),
+            Some(DatabaseKind::Postgres)
+        );
+        assert_eq!(
+            DatabaseKind::for_url("PostgreSQL://localhost"),
+            Some(DatabaseKind::Postgres)
+        );
+        assert_eq!(
+            DatabaseKind::for_url("mysql://root@localhost:3306/app"),
+            Some(DatabaseKind::MySql)
+        );
+        assert_eq!(
+            DatabaseKind::for_url("sqlite:app.db"),
+            Some(DatabaseKind::Sqlite)
+        );
+        assert_eq!(DatabaseKind::for_url("mongodb://localhost"), None);
+        assert_eq!(DatabaseKind::for_url("app.db"), None);
+    }
+
+    #[test]
+    fn test_resolve_url() {
+        let root = Path::new("/projects/app");
+        assert_eq!(
+            resolve_url("sqlite:data/app.db?mode=ro", Some(root)),
+            "sqlite:///projects/app/data/app.db?mode=ro"
+        );
+        assert_eq!(
+            resolve_url("sqlite://app.db", Some(root)),
+            "sqlite:///projects/app/app.db"
+        );
+        assert_eq!(
+            resolve_url("sqlite:///var/app.db", Some(root)),
+            "sqlite:///var/app.db"
+        );
+        assert_eq!(
+            resolve_url("sqlite::memory:", Some(root)),
+            "sqlite::memory:"
+        );
+        assert_eq!(resolve_url("sqlite:app.db", None), "sqlite:app.db");
+        assert_eq!(
+            resolve_url("postgres://localhost/app", Some(root)),
+            "postgres://localhost/app"
+        );
+    }
+}
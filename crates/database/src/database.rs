@@ -0,0 +1,503 @@
+//! Runs the statements of SQL files on Postgres, MySQL, and SQLite databases.
+//! Each statement of a file gets a "Run" button above it, and the rows it
+//! returns are shown in a grid split off to the side.
+mod connection;
+mod results;
+mod results_view;
+mod statements;
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use collections::HashSet;
+use editor::{
+    display_map::{BlockContext, BlockDisposition, BlockProperties, BlockStyle, CustomBlockId},
+    Addon, Editor, EditorEvent, EditorMode,
+};
+use gpui::{actions, AppContext, KeyContext, Model, Render, Task, View, ViewContext, WeakView};
+use language::{Buffer, Point};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsLocation, SettingsSources};
+use ui::prelude::*;
+use workspace::{notifications::NotificationId, SplitDirection, Toast, Workspace};
+
+pub use connection::*;
+pub use results::*;
+pub use results_view::*;
+pub use statements::*;
+
+/// How long to wait after a SQL file is edited before finding its statements
+/// again.
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(200);
+
+actions!(
+    database,
+    [
+        RunStatement,
+        CloseConnections,
+        NextPage,
+        PreviousPage,
+        CopyCell,
+        ExportCsv
+    ]
+);
+
+/// Settings for the databases that SQL files can be run on.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct DatabaseSettings {
+    /// The databases that SQL files can be run on.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub connections: Vec<ConnectionSettings>,
+    /// The name of the connection that SQL files are run on, unless they name
+    /// one in a `-- connection: name` comment. When unset, the first
+    /// connection is used.
+    ///
+    /// Default: null
+    #[serde(default)]
+    pub default_connection: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ConnectionSettings {
+    pub name: String,
+    /// The URL of the database, such as `postgres://user@localhost/app`,
+    /// `mysql://user@localhost/app`, or `sqlite:data/app.db`. The paths of
+    /// SQLite databases are relative to the project.
+    pub url: String,
+}
+
+impl Settings for DatabaseSettings {
+    const KEY: Option<&'static str> = Some("database");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+impl DatabaseSettings {
+    /// Returns the connection a SQL file is run on.
+    fn connection_for(&self, text: &str) -> Result<&ConnectionSettings> {
+        let name = connection_comment(text).or(self.default_connection.as_deref());
+        match name {
+            Some(name) => self
+                .connections
+                .iter()
+                .find(|connection| connection.name == name)
+                .ok_or_else(|| anyhow!("there's no database connection named {name:?}")),
+            None => self.connections.first().ok_or_else(|| {
+                anyhow!("no database connections are configured in the `database` settings")
+            }),
+        }
+    }
+}
+
+pub fn init(cx: &mut AppContext) {
+    DatabaseSettings::register(cx);
+    cx.set_global(ConnectionManager::default());
+    cx.observe_new_views(SqlFileView::register).detach();
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(close_connections);
+    })
+    .detach();
+}
+
+fn close_connections(
+    workspace: &mut Workspace,
+    _: &CloseConnections,
+    cx: &mut ViewContext<Workspace>,
+) {
+    struct ConnectionsClosed;
+
+    let count = ConnectionManager::global(cx).close_all();
+    let message = match count {
+        1 => "Closed the connections to 1 database".to_string(),
+        count => format!("Closed the connections to {count} databases"),
+    };
+    workspace.show_toast(
+        Toast::new(NotificationId::unique::<ConnectionsClosed>(), message),
+        cx,
+    );
+}
+
+struct SqlFileAddon {
+    view: View<SqlFileView>,
+}
+
+impl Addon for SqlFileAddon {
+    fn extend_key_context(&self, key_context: &mut KeyContext, _: &AppContext) {
+        key_context.add("sql_file");
+    }
+
+    fn to_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// The state of an editor of a SQL file.
+struct SqlFileView {
+    editor: WeakView<Editor>,
+    /// The rows of the first lines of the statements that have "Run" buttons.
+    statement_rows: Vec<u32>,
+    /// The name of the connection the file is run on, if it has one.
+    connection_name: Option<SharedString>,
+    blocks: Vec<CustomBlockId>,
+    results_view: Option<WeakView<QueryResultsView>>,
+    refresh_task: Option<Task<()>>,
+}
+
+impl Render for SqlFileView {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        gpui::Empty
+    }
+}
+
+impl SqlFileView {
+    fn register(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        if editor.mode() != EditorMode::Full {
+            return;
+        }
+        let multibuffer = editor.buffer().clone();
+        if !multibuffer.read(cx).is_singleton() {
+            return;
+        }
+
+        cx.subscribe(&multibuffer, |editor, _, event, cx| {
+            if let multi_buffer::Event::LanguageChanged(_) = event {
+                Self::update_sql_mode(editor, cx);
+            }
+        })
+        .detach();
+        Self::update_sql_mode(editor, cx);
+    }
+
+    /// Turns running statements on or off, as the editor's buffer is or isn't
+    /// a SQL file.
+    fn update_sql_mode(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        let is_sql = editor
+            .buffer()
+            .read(cx)
+            .as_singleton()
+            .map_or(false, |buffer| is_sql_file(buffer.read(cx)));
+        let is_active = editor.addon::<SqlFileAddon>().is_some();
+        if is_sql && !is_active {
+            Self::activate(editor, cx);
+        } else if !is_sql && is_active {
+            Self::deactivate(editor, cx);
+        }
+    }
+
+    fn activate(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        let view = Self::new(cx);
+        editor.register_addon(SqlFileAddon { view: view.clone() });
+
+        view.update(cx, |view, cx| {
+            let subscription = editor.register_action(
+                cx.listener(|view, _: &RunStatement, cx| view.run_statement_at_cursor(cx)),
+            );
+            cx.on_release(|_, _, _| drop(subscription)).detach();
+            view.refresh(cx);
+        });
+    }
+
+    fn deactivate(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        if let Some(addon) = editor.addon::<SqlFileAddon>() {
+            let blocks = addon
+                .view
+                .update(cx, |view, _| std::mem::take(&mut view.blocks));
+            editor.remove_blocks(blocks.into_iter().collect(), None, cx);
+        }
+        editor.unregister_addon::<SqlFileAddon>();
+    }
+
+    fn new(cx: &mut ViewContext<Editor>) -> View<Self> {
+        let editor = cx.view().clone();
+        cx.new_view(|cx: &mut ViewContext<Self>| {
+            cx.subscribe(&editor, |view, _, event, cx| {
+                if let EditorEvent::BufferEdited = event {
+                    view.refresh(cx);
+                }
+            })
+            .detach();
+            cx.observe_global::<settings::SettingsStore>(|view, cx| view.refresh(cx))
+                .detach();
+
+            Self {
+                editor: editor.downgrade(),
+                statement_rows: Vec::new(),
+                connection_name: None,
+                blocks: Vec::new(),
+                results_view: None,
+                refresh_task: None,
+            }
+        })
+    }
+
+    fn buffer(&self, cx: &AppContext) -> Option<Model<Buffer>> {
+        let editor = self.editor.upgrade()?;
+        let buffer = editor.read(cx).buffer().read(cx).as_singleton()?;
+        Some(buffer)
+    }
+
+    /// Finds the statements of the file again, to update their buttons.
+    fn refresh(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(buffer) = self.buffer(cx) else {
+            return;
+        };
+        self.refresh_task = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(REFRESH_DEBOUNCE).await;
+            let Some(snapshot) = buffer.update(&mut cx, |buffer, _| buffer.snapshot()).ok() else {
+                return;
+            };
+            let (text, statements) = cx
+                .background_executor()
+                .spawn({
+                    let snapshot = snapshot.clone();
+                    async move {
+                        let text = snapshot.text();
+                        let statements = split_statements(&text);
+                        (text, statements)
+                    }
+                })
+                .await;
+            this.update(&mut cx, |this, cx| {
+                let statement_rows = statements
+                    .iter()
+                    .map(|statement| snapshot.offset_to_point(statement.start).row)
+                    .collect::<Vec<_>>();
+                let connection_name = buffer
+                    .read(cx)
+                    .file()
+                    .map_or_else(
+                        || DatabaseSettings::get_global(cx),
+                        |file| {
+                            DatabaseSettings::get(
+                                Some(SettingsLocation {
+                                    worktree_id: file.worktree_id(),
+                                    path: file.path(),
+                                }),
+                                cx,
+                            )
+                        },
+                    )
+                    .connection_for(&text)
+                    .ok()
+                    .map(|connection| SharedString::from(connection.name.clone()));
+                if statement_rows != this.statement_rows || connection_name != this.connection_name
+                {
+                    this.statement_rows = statement_rows;
+                    this.connection_name = connection_name;
+                    this.update_blocks(cx);
+                }
+            })
+            .ok();
+        }));
+    }
+
+    fn update_blocks(&mut self, cx: &mut ViewContext<Self>) {
+        let old_blocks = self.blocks.drain(..).collect::<HashSet<_>>();
+        let view = cx.view().downgrade();
+        let statement_rows = self.statement_rows.clone();
+        let label = match &self.connection_name {
+            Some(name) => SharedString::from(format!("Run on {name}")),
+            None => SharedString::from("Run"),
+        };
+        self.blocks = self
+            .editor
+            .update(cx, |editor, cx| {
+                editor.remove_blocks(old_blocks, None, cx);
+                // The points of a singleton multibuffer are those of its
+                // buffer.
+                let multibuffer = editor.buffer().read(cx).snapshot(cx);
+                let blocks = statement_rows.into_iter().map(|row| {
+                    let view = view.clone();
+                    let label = label.clone();
+                    BlockProperties {
+                        position: multibuffer.anchor_before(Point::new(row, 0)),
+                        height: 1,
+                        style: BlockStyle::Flex,
+                        render: Box::new(move |cx: &mut BlockContext| {
+                            render_run_button(view.clone(), row, label.clone(), cx)
+                        }),
+                        disposition: BlockDisposition::Above,
+                        priority: 0,
+                    }
+                });
+                editor.insert_blocks(blocks, None, cx)
+            })
+            .unwrap_or_default();
+    }
+
+    /// Runs the selected text, or else the statement at the cursor.
+    fn run_statement_at_cursor(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(selection) = self
+            .editor
+            .update(cx, |editor, cx| {
+                editor.selections.newest::<usize>(cx).range()
+            })
+            .ok()
+        else {
+            return;
+        };
+        if selection.is_empty() {
+            self.run_statement_at(selection.start, cx);
+        } else {
+            let Some(buffer) = self.buffer(cx) else {
+                return;
+            };
+            let text = buffer
+                .read(cx)
+                .text_for_range(selection)
+                .collect::<String>();
+            self.run_statement(text, cx);
+        }
+    }
+
+    fn run_statement_at_row(&mut self, row: u32, cx: &mut ViewContext<Self>) {
+        let Some(buffer) = self.buffer(cx) else {
+            return;
+        };
+        let offset = buffer.read(cx).point_to_offset(Point::new(row, 0));
+        self.run_statement_at(offset, cx);
+    }
+
+    fn run_statement_at(&mut self, offset: usize, cx: &mut ViewContext<Self>) {
+        let Some(buffer) = self.buffer(cx) else {
+            return;
+        };
+        let text = buffer.read(cx).text();
+        if let Some(statement) = statement_at(&split_statements(&text), offset) {
+            self.run_statement(text[statement].to_string(), cx);
+        }
+    }
+
+    /// Runs a statement on the file's connection, and shows its results.
+    fn run_statement(&mut self, statement: String, cx: &mut ViewContext<Self>) {
+        let Some(buffer) = self.buffer(cx) else {
+            return;
+        };
+        let Some(workspace) = self
+            .editor
+            .upgrade()
+            .and_then(|editor| editor.read(cx).workspace())
+        else {
+            return;
+        };
+        let statement = statement.trim().to_string();
+        if statement.is_empty() {
+            return;
+        }
+
+        let buffer = buffer.read(cx);
+        let file = buffer.file();
+        let project_root = file.and_then(|file| worktree_root(file.as_local()?, cx));
+        let settings = match file {
+            Some(file) => DatabaseSettings::get(
+                Some(SettingsLocation {
+                    worktree_id: file.worktree_id(),
+                    path: file.path(),
+                }),
+                cx,
+            ),
+            None => DatabaseSettings::get_global(cx),
+        };
+        let connection = match settings.connection_for(&buffer.text()).cloned() {
+            Ok(connection) => connection,
+            Err(error) => {
+                struct DatabaseError;
+
+                workspace.update(cx, |workspace, cx| {
+                    workspace.show_toast(
+                        Toast::new(
+                            NotificationId::unique::<DatabaseError>(),
+                            format!("Couldn't run the statement: {error:#}"),
+                        ),
+                        cx,
+                    );
+                });
+                return;
+            }
+        };
+
+        let url = resolve_url(&connection.url, project_root.as_deref());
+        let task = ConnectionManager::global(cx).run(&url, statement.clone(), cx);
+        let connection_name = SharedString::from(connection.name);
+
+        if let Some(results_view) = self.results_view.as_ref().and_then(|view| view.upgrade()) {
+            results_view.update(cx, |results_view, cx| {
+                results_view.run(connection_name, statement, task, cx)
+            });
+            workspace.update(cx, |workspace, cx| {
+                if !workspace.activate_item(&results_view, true, false, cx) {
+                    workspace.split_item(SplitDirection::Right, Box::new(results_view), cx);
+                }
+            });
+            return;
+        }
+
+        let fs = workspace.read(cx).app_state().fs.clone();
+        let export_directory = project_root.unwrap_or_else(|| util::paths::home_dir().clone());
+        let results_view = cx.new_view(|cx| {
+            let mut results_view = QueryResultsView::new(fs, export_directory, cx);
+            results_view.run(connection_name, statement, task, cx);
+            results_view
+        });
+        self.results_view = Some(results_view.downgrade());
+        workspace.update(cx, |workspace, cx| {
+            workspace.split_item(SplitDirection::Right, Box::new(results_view), cx);
+        });
+    }
+}
+
+fn is_sql_file(buffer: &Buffer) -> bool {
+    let is_sql_language = buffer
+        .language()
+        .map_or(false, |language| language.name().as_ref() == "SQL");
+    // SQL support comes from an extension, which may not be installed.
+    let has_sql_extension = buffer.file().map_or(false, |file| {
+        file.path()
+            .extension()
+            .map_or(false, |extension| extension.eq_ignore_ascii_case("sql"))
+    });
+    is_sql_language || has_sql_extension
+}
+
+/// Returns the root of the worktree the file is in.
+fn worktree_root(file: &dyn language::LocalFile, cx: &AppContext) -> Option<PathBuf> {
+    let abs_path = file.abs_path(cx);
+    let depth = file.path().components().count();
+    abs_path.ancestors().nth(depth).map(Path::to_path_buf)
+}
+
+fn render_run_button(
+    view: WeakView<SqlFileView>,
+    row: u32,
+    label: SharedString,
+    cx: &mut BlockContext,
+) -> gpui::AnyElement {
+    h_flex()
+        .id(("run-statement-row", row as usize))
+        .h(cx.line_height)
+        .child(
+            Button::new(("run-statement", row as usize), label)
+                .icon(IconName::Play)
+                .icon_position(IconPosition::Start)
+                .icon_size(IconSize::XSmall)
+                .icon_color(Color::Muted)
+                .label_size(LabelSize::Small)
+                .color(Color::Muted)
+                .style(ButtonStyle::Transparent)
+                .on_click(move |_, cx| {
+                    view.update(cx, |view, cx| view.run_statement_at_row(row, cx))
+                        .ok();
+                }),
+        )
+        .into_any_element()
+}
@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+/// The results of running a statement.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QueryResults {
+    pub columns: Vec<String>,
+    /// The values of the rows, with `None` for nulls.
+    pub rows: Vec<Vec<Option<String>>>,
+    pub rows_affected: u64,
+    /// Whether there were more rows than were fetched.
+    pub truncated: bool,
+    pub elapsed: Duration,
+}
+
+impl QueryResults {
+    /// Returns a short summary of the results, such as `42 rows, 12 ms`.
+    pub fn summary(&self) -> String {
+        let elapsed = self.elapsed.as_millis();
+        if self.columns.is_empty() {
+            let rows = plural(self.rows_affected as usize, "row");
+            return format!("{rows} affected, {elapsed} ms");
+        }
+        let rows = plural(self.rows.len(), "row");
+        if self.truncated {
+            format!("first {rows}, {elapsed} ms")
+        } else {
+            format!("{rows}, {elapsed} ms")
+        }
+    }
+
+    /// Writes the results as CSV, with a heading of the column names. Nulls
+    /// are written as empty fields.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        let mut write_row = |fields: &mut dyn Iterator<Item = &str>| {
+            let fields = fields.map(csv_field).collect::<Vec<_>>();
+            csv.push_str(&fields.join(","));
+            csv.push_str("\r\n");
+        };
+        write_row(&mut self.columns.iter().map(String::as_str));
+        for row in &self.rows {
+            write_row(&mut row.iter().map(|value| value.as_deref().unwrap_or("")));
+        }
+        csv
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+/// Quotes a CSV field if it contains a delimiter, a quote, or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_csv() {
+        let results = QueryResults {
+            columns: vec!["id".into(), "name, full".into(), "note".into()],
+            rows: vec![
+                vec![Some("1".into()), Some("Ada \"The Countess\"".into()), None],
+                vec![
+                    Some("2".into()),
+                    Some("Grace".into()),
+                    Some("line\nbreak".into()),
+                ],
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            results.to_csv(),
+            "id,\"name, full\",note\r\n\
+             1,\"Ada \"\"The Countess\"\"\",\r\n\
+             2,Grace,\"line\nbreak\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut results = QueryResults {
+            rows_affected: 1,
+            elapsed: Duration::from_millis(7),
+            ..Default::default()
+        };
+        assert_eq!(results.summary(), "1 row affected, 7 ms");
+
+        results.columns = vec!["id".into()];
+        results.rows = vec![vec![None]; 3];
+        assert_eq!(results.summary(), "3 rows, 7 ms");
+        results.truncated = true;
+        assert_eq!(results.summary(), "first 3 rows, 7 ms");
+    }
+}
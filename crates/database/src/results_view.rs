@@ -0,0 +1,464 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use gpui::{
+    uniform_list, AppContext, ClipboardItem, EventEmitter, FocusHandle, FocusableView, FontWeight,
+    Render, Stateful, Task, UniformListScrollHandle, ViewContext,
+};
+use project::Fs;
+use settings::Settings;
+use theme::ThemeSettings;
+use ui::{prelude::*, Tooltip};
+use util::ResultExt;
+use workspace::item::{Item, ItemEvent};
+
+use crate::{CopyCell, ExportCsv, NextPage, PreviousPage, QueryResults};
+
+/// The number of rows shown on each page of results.
+pub const PAGE_SIZE: usize = 100;
+
+/// The widest a column is shown, in characters. Longer values are truncated.
+const MAX_COLUMN_WIDTH: usize = 48;
+
+/// The horizontal padding of cells.
+const CELL_PADDING: Pixels = px(8.);
+
+enum ResultsState {
+    Running,
+    Done(QueryResults),
+    Failed(String),
+}
+
+/// Shows the rows returned by a statement in a grid, a page at a time.
+pub struct QueryResultsView {
+    focus_handle: FocusHandle,
+    fs: Arc<dyn Fs>,
+    /// The directory that exported results are first offered to be saved in.
+    export_directory: PathBuf,
+    connection_name: SharedString,
+    statement: SharedString,
+    state: ResultsState,
+    page: usize,
+    /// The widths of the columns, in characters.
+    column_widths: Vec<usize>,
+    selected_cell: Option<(usize, usize)>,
+    /// The width of a character of the buffer font, as of the last render.
+    em_width: Pixels,
+    scroll_handle: UniformListScrollHandle,
+    running_task: Option<Task<()>>,
+}
+
+impl QueryResultsView {
+    pub fn new(fs: Arc<dyn Fs>, export_directory: PathBuf, cx: &mut ViewContext<Self>) -> Self {
+        Self {
+            focus_handle: cx.focus_handle(),
+            fs,
+            export_directory,
+            connection_name: SharedString::default(),
+            statement: SharedString::default(),
+            state: ResultsState::Done(QueryResults::default()),
+            page: 0,
+            column_widths: Vec::new(),
+            selected_cell: None,
+            em_width: px(0.),
+            scroll_handle: UniformListScrollHandle::new(),
+            running_task: None,
+        }
+    }
+
+    /// Shows the results of a statement once it has run, replacing those of the
+    /// last statement.
+    pub fn run(
+        &mut self,
+        connection_name: SharedString,
+        statement: String,
+        task: Task<Result<QueryResults>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.connection_name = connection_name;
+        // Statements spanning several lines are shown on one.
+        self.statement = statement
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .into();
+        self.state = ResultsState::Running;
+        cx.emit(ItemEvent::UpdateTab);
+        cx.notify();
+        self.running_task = Some(cx.spawn(|this, mut cx| async move {
+            let results = task.await;
+            this.update(&mut cx, |this, cx| this.set_results(results, cx))
+                .log_err();
+        }));
+    }
+
+    fn set_results(&mut self, results: Result<QueryResults>, cx: &mut ViewContext<Self>) {
+        self.page = 0;
+        self.selected_cell = None;
+        self.state = match results {
+            Ok(results) => {
+                self.column_widths = column_widths(&results);
+                ResultsState::Done(results)
+            }
+            Err(error) => ResultsState::Failed(format!("{error:#}")),
+        };
+        self.running_task = None;
+        self.scroll_handle.scroll_to_item(0);
+        cx.emit(ItemEvent::UpdateTab);
+        cx.notify();
+    }
+
+    fn results(&self) -> Option<&QueryResults> {
+        match &self.state {
+            ResultsState::Done(results) => Some(results),
+            _ => None,
+        }
+    }
+
+    fn page_count(&self) -> usize {
+        self.results()
+            .map_or(0, |results| results.rows.len().div_ceil(PAGE_SIZE))
+    }
+
+    fn set_page(&mut self, page: usize, cx: &mut ViewContext<Self>) {
+        if page < self.page_count() && page != self.page {
+            self.page = page;
+            self.selected_cell = None;
+            self.scroll_handle.scroll_to_item(0);
+            cx.notify();
+        }
+    }
+
+    fn next_page(&mut self, _: &NextPage, cx: &mut ViewContext<Self>) {
+        self.set_page(self.page + 1, cx);
+    }
+
+    fn previous_page(&mut self, _: &PreviousPage, cx: &mut ViewContext<Self>) {
+        if let Some(page) = self.page.checked_sub(1) {
+            self.set_page(page, cx);
+        }
+    }
+
+    fn copy_cell(&mut self, _: &CopyCell, cx: &mut ViewContext<Self>) {
+        let Some((row, column)) = self.selected_cell else {
+            return;
+        };
+        let Some(results) = self.results() else {
+            return;
+        };
+        if let Some(value) = results.rows.get(row).and_then(|row| row.get(column)) {
+            let text = value.clone().unwrap_or_default();
+            cx.write_to_clipboard(ClipboardItem::new_string(text));
+        }
+    }
+
+    /// Saves every fetched row, not just those of the current page, as CSV.
+    fn export_csv(&mut self, _: &ExportCsv, cx: &mut ViewContext<Self>) {
+        let Some(csv) = self.results().map(QueryResults::to_csv) else {
+            return;
+        };
+        let fs = self.fs.clone();
+        let abs_path = cx.prompt_for_new_path(&self.export_directory);
+        cx.spawn(|_, _| async move {
+            let Some(mut abs_path) = abs_path.await?? else {
+                return Ok(());
+            };
+            if abs_path.extension().is_none() {
+                abs_path.set_extension("csv");
+            }
+            fs.atomic_write(abs_path, csv).await
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn column_width(&self, column: usize) -> Pixels {
+        self.em_width * self.column_widths[column] as f32 + CELL_PADDING * 2.
+    }
+
+    fn total_width(&self) -> Pixels {
+        (0..self.column_widths.len())
+            .map(|column| self.column_width(column))
+            .fold(px(0.), |total, width| total + width)
+    }
+
+    fn render_cell(
+        &self,
+        id: usize,
+        column: usize,
+        text: Option<&str>,
+        cx: &mut ViewContext<Self>,
+    ) -> Stateful<Div> {
+        h_flex()
+            .id(("results-cell", id))
+            .flex_none()
+            .w(self.column_width(column))
+            .px(CELL_PADDING)
+            .overflow_hidden()
+            .border_r_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(match text {
+                // Line breaks within values are shown as single characters,
+                // so that every row is one line tall.
+                Some(text) => div()
+                    .whitespace_nowrap()
+                    .text_ellipsis()
+                    .child(text.replace("\r\n", "\n").replace('\n', "↵"))
+                    .into_any_element(),
+                None => Label::new("NULL").color(Color::Muted).into_any_element(),
+            })
+    }
+
+    fn render_header(&self, results: &QueryResults, cx: &mut ViewContext<Self>) -> Div {
+        let cells = results.columns.iter().enumerate().map(|(column, name)| {
+            self.render_cell(column, column, Some(name), cx)
+                .font_weight(FontWeight::BOLD)
+        });
+        h_flex()
+            .w(self.total_width())
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().title_bar_background)
+            .children(cells.collect::<Vec<_>>())
+    }
+
+    fn render_row(&self, row: usize, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let column_count = self.column_widths.len();
+        let values = self
+            .results()
+            .and_then(|results| results.rows.get(row))
+            .cloned()
+            .unwrap_or_default();
+        h_flex()
+            .w(self.total_width())
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .children(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(column, value)| {
+                        // Cell ids follow those of the heading.
+                        let id = (row + 1) * column_count + column;
+                        let is_selected = self.selected_cell == Some((row, column));
+                        self.render_cell(id, column, value.as_deref(), cx)
+                            .when(is_selected, |cell| {
+                                cell.bg(cx.theme().colors().element_selected)
+                            })
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.selected_cell = Some((row, column));
+                                cx.focus(&this.focus_handle);
+                                cx.notify();
+                            }))
+                    })
+                    .collect::<Vec<_>>(),
+            )
+    }
+
+    fn render_toolbar(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let summary = match &self.state {
+            ResultsState::Running => "Running…".to_string(),
+            ResultsState::Done(results) => results.summary(),
+            ResultsState::Failed(_) => "Failed".to_string(),
+        };
+        let row_count = self.results().map_or(0, |results| results.rows.len());
+        let first_row = self.page * PAGE_SIZE;
+        let last_row = (first_row + PAGE_SIZE).min(row_count);
+        let focus_handle = self.focus_handle.clone();
+
+        h_flex()
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .overflow_hidden()
+                    .child(Label::new(self.connection_name.clone()))
+                    .child(Label::new(summary).color(Color::Muted))
+                    .child(
+                        div()
+                            .whitespace_nowrap()
+                            .text_ellipsis()
+                            .text_color(cx.theme().colors().text_muted)
+                            .child(self.statement.clone()),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .flex_none()
+                    .gap_1()
+                    .when(self.page_count() > 1, |toolbar| {
+                        let previous_focus = focus_handle.clone();
+                        let next_focus = focus_handle.clone();
+                        toolbar
+                            .child(
+                                IconButton::new("previous-page", IconName::ChevronLeft)
+                                    .icon_size(IconSize::Small)
+                                    .disabled(self.page == 0)
+                                    .tooltip(move |cx| {
+                                        Tooltip::for_action_in(
+                                            "Previous Page",
+                                            &PreviousPage,
+                                            &previous_focus,
+                                            cx,
+                                        )
+                                    })
+                                    .on_click(cx.listener(|this, _, cx| {
+                                        this.previous_page(&PreviousPage, cx)
+                                    })),
+                            )
+                            .child(
+                                Label::new(format!(
+                                    "Rows {}–{last_row} of {row_count}",
+                                    first_row + 1
+                                ))
+                                .size(LabelSize::Small),
+                            )
+                            .child(
+                                IconButton::new("next-page", IconName::ChevronRight)
+                                    .icon_size(IconSize::Small)
+                                    .disabled(self.page + 1 >= self.page_count())
+                                    .tooltip(move |cx| {
+                                        Tooltip::for_action_in(
+                                            "Next Page",
+                                            &NextPage,
+                                            &next_focus,
+                                            cx,
+                                        )
+                                    })
+                                    .on_click(
+                                        cx.listener(|this, _, cx| this.next_page(&NextPage, cx)),
+                                    ),
+                            )
+                    })
+                    .child(
+                        Button::new("export-csv", "Export CSV")
+                            .icon(IconName::Download)
+                            .icon_position(IconPosition::Start)
+                            .icon_size(IconSize::Small)
+                            .label_size(LabelSize::Small)
+                            .disabled(self.results().map_or(true, |r| r.columns.is_empty()))
+                            .on_click(cx.listener(|this, _, cx| this.export_csv(&ExportCsv, cx))),
+                    ),
+            )
+    }
+}
+
+/// Returns the width of each column in characters: that of its widest value
+/// or its name, up to [`MAX_COLUMN_WIDTH`].
+fn column_widths(results: &QueryResults) -> Vec<usize> {
+    let mut widths = results
+        .columns
+        .iter()
+        .map(|name| name.chars().count().clamp(4, MAX_COLUMN_WIDTH))
+        .collect::<Vec<_>>();
+    for row in &results.rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            let value_width = value.as_deref().map_or(4, |value| value.chars().count());
+            *width = (*width).max(value_width.min(MAX_COLUMN_WIDTH));
+        }
+    }
+    widths
+}
+
+impl FocusableView for QueryResultsView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<ItemEvent> for QueryResultsView {}
+
+impl Item for QueryResultsView {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _cx: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::DatabaseZap))
+    }
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some(format!("Results: {}", self.connection_name).into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("query results")
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
+
+impl Render for QueryResultsView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let font = settings.buffer_font.clone();
+        let font_size = settings.buffer_font_size(cx);
+        let font_id = cx.text_system().resolve_font(&font);
+        self.em_width = cx
+            .text_system()
+            .advance(font_id, font_size, 'm')
+            .map(|advance| advance.width)
+            .unwrap_or(font_size * 0.6);
+
+        let body = match &self.state {
+            ResultsState::Running => div().p_4().into_any_element(),
+            ResultsState::Failed(error) => div()
+                .p_4()
+                .child(Label::new(error.clone()).color(Color::Error))
+                .into_any_element(),
+            ResultsState::Done(results) if results.columns.is_empty() => div()
+                .p_4()
+                .child(Label::new("The statement returned no rows").color(Color::Muted))
+                .into_any_element(),
+            ResultsState::Done(results) => {
+                let first_row = self.page * PAGE_SIZE;
+                let page_rows = (results.rows.len() - first_row).min(PAGE_SIZE);
+                // The heading stays in view as the rows scroll vertically, and
+                // scrolls with them horizontally.
+                div()
+                    .id("results-grid")
+                    .size_full()
+                    .overflow_x_scroll()
+                    .child(
+                        v_flex()
+                            .w(self.total_width())
+                            .h_full()
+                            .child(self.render_header(results, cx))
+                            .child(
+                                uniform_list(
+                                    cx.view().clone(),
+                                    "results-rows",
+                                    page_rows,
+                                    move |this, range, cx| {
+                                        range
+                                            .map(|ix| this.render_row(first_row + ix, cx))
+                                            .collect()
+                                    },
+                                )
+                                .flex_grow()
+                                .track_scroll(self.scroll_handle.clone()),
+                            ),
+                    )
+                    .into_any_element()
+            }
+        };
+
+        v_flex()
+            .id("QueryResultsView")
+            .key_context("QueryResultsView")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::next_page))
+            .on_action(cx.listener(Self::previous_page))
+            .on_action(cx.listener(Self::copy_cell))
+            .on_action(cx.listener(Self::export_csv))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_toolbar(cx))
+            .child(div().flex_1().font(font).text_size(font_size).child(body))
+    }
+}
@@ -0,0 +1,190 @@
+//! Splits SQL scripts into their statements, at the semicolons that aren't in
+//! strings, quoted identifiers, comments, or Postgres dollar-quoted bodies.
+use std::ops::Range;
+
+/// Returns the offsets of the statements of the text, without the comments
+/// and whitespace around them or the semicolons after them.
+pub fn split_statements(text: &str) -> Vec<Range<usize>> {
+    let bytes = text.as_bytes();
+    let mut statements = Vec::new();
+    // The end of the last token of the statement that isn't a comment.
+    let mut statement_end = None;
+    // The start of the first token of the statement that isn't a comment.
+    let mut first_token = None;
+    let mut ix = 0;
+
+    while ix < bytes.len() {
+        let token_start = ix;
+        match bytes[ix] {
+            b'-' if bytes.get(ix + 1) == Some(&b'-') => {
+                ix = text[ix..].find('\n').map_or(bytes.len(), |end| ix + end);
+                continue;
+            }
+            b'/' if bytes.get(ix + 1) == Some(&b'*') => {
+                ix = text[ix + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| ix + 2 + end + 2);
+                continue;
+            }
+            b';' => {
+                if let (Some(start), Some(end)) = (first_token, statement_end) {
+                    statements.push(start..end);
+                }
+                ix += 1;
+                first_token = None;
+                statement_end = None;
+                continue;
+            }
+            byte if byte.is_ascii_whitespace() => {
+                ix += 1;
+                continue;
+            }
+            quote @ (b'\'' | b'"' | b'`') => {
+                ix = quoted_end(bytes, ix, quote);
+            }
+            b'$' => {
+                ix = match dollar_quote_tag(&text[ix..]) {
+                    Some(tag) => text[ix + tag.len()..]
+                        .find(tag)
+                        .map_or(bytes.len(), |end| ix + tag.len() + end + tag.len()),
+                    None => ix + 1,
+                };
+            }
+            _ => {
+                ix += text[ix..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+        first_token.get_or_insert(token_start);
+        statement_end = Some(ix);
+    }
+
+    if let (Some(start), Some(end)) = (first_token, statement_end) {
+        statements.push(start..end);
+    }
+    statements
+}
+
+/// Returns the offset after the string or quoted identifier starting at the
+/// offset, in which quotes are escaped by doubling them.
+fn quoted_end(bytes: &[u8], start: usize, quote: u8) -> usize {
+    let mut ix = start + 1;
+    while ix < bytes.len() {
+        if bytes[ix] == quote {
+            if bytes.get(ix + 1) == Some(&quote) {
+                ix += 2;
+                continue;
+            }
+            return ix + 1;
+        }
+        // MySQL strings may also escape quotes with backslashes.
+        if bytes[ix] == b'\\' && quote != b'`' {
+            ix += 1;
+        }
+        ix += 1;
+    }
+    bytes.len()
+}
+
+/// Returns the opening tag of a Postgres dollar-quoted string, such as `$$` or
+/// `$body$`, at the start of the text. Parameters such as `$1` aren't tags.
+fn dollar_quote_tag(text: &str) -> Option<&str> {
+    let rest = text.strip_prefix('$')?;
+    let tag_len = rest.find('$')?;
+    let tag = &rest[..tag_len];
+    let is_tag = tag.chars().all(|ch| ch.is_alphanumeric() || ch == '_')
+        && !tag.starts_with(|ch: char| ch.is_ascii_digit());
+    is_tag.then(|| &text[..tag_len + 2])
+}
+
+/// Returns the statement the offset is in, or the one after it if it's
+/// between statements, or else the last statement.
+pub fn statement_at(statements: &[Range<usize>], offset: usize) -> Option<Range<usize>> {
+    statements
+        .iter()
+        .find(|statement| statement.end >= offset)
+        .or(statements.last())
+        .cloned()
+}
+
+/// Returns the name of the connection chosen for a script with a
+/// `-- connection: name` comment on a line of its own.
+pub fn connection_comment(text: &str) -> Option<&str> {
+    text.lines().find_map(|line| {
+        let comment = line.trim().strip_prefix("--")?.trim_start();
+        let name = comment.strip_prefix("connection:")?.trim();
+        (!name.is_empty()).then_some(name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statements(text: &str) -> Vec<&str> {
+        split_statements(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(
+            statements(
+                "-- connection: dev\n\
+                 SELECT * FROM users WHERE name = 'a;b' AND note = 'it''s';\n\
+                 \n\
+                 /* totals; by day */\n\
+                 SELECT \"odd;name\", `x;y` FROM t -- trailing; comment\n\
+                 ;\n\
+                 ;\n\
+                 INSERT INTO t VALUES ('\\';')"
+            ),
+            [
+                "SELECT * FROM users WHERE name = 'a;b' AND note = 'it''s'",
+                "SELECT \"odd;name\", `x;y` FROM t",
+                "INSERT INTO t VALUES ('\\';')",
+            ]
+        );
+
+        assert_eq!(
+            statements(
+                "CREATE FUNCTION f() RETURNS int AS $body$ SELECT 1; $body$ LANGUAGE sql;\n\
+                 SELECT $$a;b$$, $1;"
+            ),
+            [
+                "CREATE FUNCTION f() RETURNS int AS $body$ SELECT 1; $body$ LANGUAGE sql",
+                "SELECT $$a;b$$, $1",
+            ]
+        );
+
+        assert_eq!(
+            statements("  -- only a comment\n/* and another */ "),
+            [] as [&str; 0]
+        );
+        assert_eq!(
+            statements("SELECT 'unterminated; string"),
+            ["SELECT 'unterminated; string"]
+        );
+    }
+
+    #[test]
+    fn test_statement_at() {
+        let text = "SELECT 1;\n\nSELECT 2;\n";
+        let statements = split_statements(text);
+        assert_eq!(statement_at(&statements, 3), Some(0..8));
+        assert_eq!(statement_at(&statements, 8), Some(0..8));
+        assert_eq!(statement_at(&statements, 10), Some(11..19));
+        assert_eq!(statement_at(&statements, text.len()), Some(11..19));
+        assert_eq!(statement_at(&[], 0), None);
+    }
+
+    #[test]
+    fn test_connection_comment() {
+        assert_eq!(
+            connection_comment("-- Reports\n  --  connection: prod db \nSELECT 1;"),
+            Some("prod db")
+        );
+        assert_eq!(connection_comment("SELECT 1; -- connection:"), None);
+    }
+}
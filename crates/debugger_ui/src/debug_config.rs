@@ -0,0 +1,240 @@
+use std::path::Path;
+
+use anyhow::Result;
+use collections::HashMap;
+use dap::DebugAdapterBinary;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use task::SpawnInTerminal;
+
+/// The variable replaced by the absolute path of the worktree containing the
+/// configuration.
+const WORKTREE_ROOT_VARIABLE: &str = "$ZED_WORKTREE_ROOT";
+
+/// A launch configuration, as defined in a `.zed/debug.json` file.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct DebugConfig {
+    /// The name of the configuration, shown in the debug panel.
+    pub label: String,
+    /// The debug adapter to start.
+    pub adapter: DebugAdapterConfig,
+    /// Whether to launch the program or attach to a running one.
+    #[serde(default)]
+    pub request: DebugRequestKind,
+    /// The arguments of the launch or attach request, which are specific to
+    /// the debug adapter.
+    #[serde(flatten)]
+    pub arguments: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct DebugAdapterConfig {
+    /// The command starting the debug adapter.
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// The identifier of the adapter sent to it on initialization, such as
+    /// `debugpy` or `lldb`. Defaults to the name of the command.
+    pub id: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DebugRequestKind {
+    #[default]
+    Launch,
+    Attach,
+}
+
+impl DebugConfig {
+    /// Parses the contents of a `.zed/debug.json` file.
+    pub fn parse_all(contents: &str) -> Result<Vec<Self>> {
+        if contents.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json_lenient::from_str(contents)?)
+    }
+
+    /// Replaces the worktree root variable in the configuration.
+    pub fn resolve(mut self, worktree_root: &Path) -> Self {
+        let root = worktree_root.to_string_lossy();
+        let substitute = |value: &mut String| {
+            if value.contains(WORKTREE_ROOT_VARIABLE) {
+                *value = value.replace(WORKTREE_ROOT_VARIABLE, &root);
+            }
+        };
+        substitute(&mut self.adapter.command);
+        self.adapter.args.iter_mut().for_each(substitute);
+        self.adapter.env.values_mut().for_each(substitute);
+        self.arguments
+            .values_mut()
+            .for_each(|value| substitute_value(value, &substitute));
+        self
+    }
+
+    pub fn adapter_id(&self) -> String {
+        self.adapter.id.clone().unwrap_or_else(|| {
+            Path::new(&self.adapter.command).file_stem().map_or_else(
+                || self.adapter.command.clone(),
+                |stem| stem.to_string_lossy().into_owned(),
+            )
+        })
+    }
+
+    /// Launches the command of a task, rather than the configured program,
+    /// with the debug adapter of the configuration.
+    pub fn for_task(mut self, task: &SpawnInTerminal) -> Self {
+        // Task arguments are left for the shell to expand.
+        let expand = |value: &str| {
+            shellexpand::env_with_context_no_errors(value, |name| task.env.get(name)).into_owned()
+        };
+        self.label = task.label.clone();
+        self.request = DebugRequestKind::Launch;
+        self.arguments
+            .insert("program".into(), Value::String(expand(&task.command)));
+        self.arguments.insert(
+            "args".into(),
+            task.args
+                .iter()
+                .map(|arg| Value::String(expand(arg)))
+                .collect(),
+        );
+        if let Some(cwd) = &task.cwd {
+            self.arguments.insert(
+                "cwd".into(),
+                Value::String(cwd.to_string_lossy().into_owned()),
+            );
+        }
+        self.arguments.insert(
+            "env".into(),
+            task.env
+                .iter()
+                .map(|(name, value)| (name.clone(), Value::String(value.clone())))
+                .collect(),
+        );
+        self
+    }
+
+    pub fn binary(&self, worktree_root: &Path) -> DebugAdapterBinary {
+        DebugAdapterBinary {
+            command: self.adapter.command.clone(),
+            args: self.adapter.args.clone(),
+            env: (!self.adapter.env.is_empty()).then(|| self.adapter.env.clone()),
+            cwd: worktree_root.to_path_buf(),
+        }
+    }
+}
+
+fn substitute_value(value: &mut Value, substitute: &impl Fn(&mut String)) {
+    match value {
+        Value::String(string) => substitute(string),
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| substitute_value(value, substitute)),
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|value| substitute_value(value, substitute)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_debug_configs() {
+        let configs = DebugConfig::parse_all(
+            r#"[
+                // Comments are allowed.
+                {
+                    "label": "Debug main.py",
+                    "adapter": { "command": "python3", "args": ["-m", "debugpy.adapter"], "id": "debugpy" },
+                    "program": "$ZED_WORKTREE_ROOT/main.py",
+                    "args": ["--verbose"],
+                    "env": { "ROOT": "$ZED_WORKTREE_ROOT" }
+                },
+                {
+                    "label": "Attach",
+                    "adapter": { "command": "/usr/bin/lldb-dap" },
+                    "request": "attach",
+                    "pid": 42,
+                },
+            ]"#,
+        )
+        .unwrap();
+
+        let config = configs[0].clone().resolve(Path::new("/project"));
+        assert_eq!(config.request, DebugRequestKind::Launch);
+        assert_eq!(config.adapter_id(), "debugpy");
+        assert_eq!(
+            Value::Object(config.arguments),
+            json!({
+                "program": "/project/main.py",
+                "args": ["--verbose"],
+                "env": { "ROOT": "/project" },
+            })
+        );
+
+        let config = &configs[1];
+        assert_eq!(config.request, DebugRequestKind::Attach);
+        assert_eq!(config.adapter_id(), "lldb-dap");
+        assert_eq!(
+            Value::Object(config.arguments.clone()),
+            json!({ "pid": 42 })
+        );
+
+        assert_eq!(DebugConfig::parse_all("  \n").unwrap(), Vec::new());
+        assert!(DebugConfig::parse_all(r#"[{ "label": "No adapter" }]"#).is_err());
+    }
+
+    #[test]
+    fn test_debug_config_for_task() {
+        let config = DebugConfig::parse_all(
+            r#"[{
+                "label": "Attach",
+                "adapter": { "command": "lldb-dap" },
+                "request": "attach",
+                "pid": 42
+            }]"#,
+        )
+        .unwrap()
+        .remove(0);
+        let task = SpawnInTerminal {
+            id: task::TaskId("test".into()),
+            full_label: "run main".into(),
+            label: "run main".into(),
+            command: "$ZED_WORKTREE_ROOT/target/debug/main".into(),
+            args: vec!["--name".into(), "$ZED_SYMBOL".into()],
+            command_label: "run main".into(),
+            cwd: Some("/project".into()),
+            env: HashMap::from_iter([
+                ("ZED_WORKTREE_ROOT".into(), "/project".into()),
+                ("ZED_SYMBOL".into(), "main".into()),
+            ]),
+            use_new_terminal: false,
+            allow_concurrent_runs: false,
+            reveal: task::RevealStrategy::Always,
+            hide: task::HideStrategy::Never,
+            shell: task::Shell::System,
+            problem_matcher: None,
+        };
+
+        let config = config.for_task(&task);
+        assert_eq!(config.label, "run main");
+        assert_eq!(config.request, DebugRequestKind::Launch);
+        assert_eq!(
+            Value::Object(config.arguments),
+            json!({
+                "pid": 42,
+                "program": "/project/target/debug/main",
+                "args": ["--name", "main"],
+                "cwd": "/project",
+                "env": { "ZED_WORKTREE_ROOT": "/project", "ZED_SYMBOL": "main" },
+            })
+        );
+    }
+}
@@ -0,0 +1,978 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use collections::HashSet;
+use db::kvp::KEY_VALUE_STORE;
+use editor::{scroll::Autoscroll, Editor};
+use gpui::{
+    Action, AnyElement, AppContext, AsyncWindowContext, EventEmitter, FocusHandle, FocusableView,
+    Model, Subscription, Task, View, WeakView,
+};
+use language::Point;
+use project::{Fs, Project, ProjectPath, WorktreeId};
+use settings::Settings;
+use task::SpawnInTerminal;
+use ui::{prelude::*, IconButtonShape, ListItem, Tooltip};
+use util::ResultExt;
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    Workspace,
+};
+
+use crate::{
+    debug_config::DebugConfig,
+    debug_session::{DebugSession, DebugSessionEvent, SessionStatus},
+    debugger_settings::{DebugPanelDockPosition, DebugPanelSettings},
+    inline_values::inline_values,
+    Continue, Pause, Start, StepInto, StepOut, StepOver, Stop, ToggleFocus,
+};
+
+const BREAKPOINTS_KEY_PREFIX: &str = "debugger_breakpoints";
+
+/// The number of lines before the stopped line searched for variables whose
+/// values are shown inline.
+const MAX_INLINE_VALUE_ROWS: u32 = 50;
+
+enum DebugStoppedLine {}
+
+#[derive(Clone)]
+struct WorktreeDebugConfig {
+    worktree_root: Arc<Path>,
+    config: DebugConfig,
+}
+
+pub struct DebugPanel {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    width: Option<Pixels>,
+    height: Option<Pixels>,
+    configs: Vec<WorktreeDebugConfig>,
+    selected_config: usize,
+    session: Option<Model<DebugSession>>,
+    error: Option<SharedString>,
+    watch_editor: View<Editor>,
+    stopped_editor: Option<WeakView<Editor>>,
+    restored_worktrees: HashSet<WorktreeId>,
+    load_configs_task: Task<()>,
+    show_frame_task: Task<()>,
+    pending_serialization: Task<Option<()>>,
+    session_subscriptions: Vec<Subscription>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl DebugPanel {
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<View<Self>> {
+        workspace.update(&mut cx, |workspace, cx| Self::new(workspace, cx))
+    }
+
+    fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let project = workspace.project().clone();
+        let fs = workspace.app_state().fs.clone();
+        let workspace_handle = cx.view().downgrade();
+        cx.new_view(|cx| {
+            let project_subscription = cx.subscribe(&project, |this, _, event, cx| match event {
+                project::Event::WorktreeAdded => {
+                    this.restore_breakpoints(cx);
+                    this.load_configs(cx);
+                }
+                project::Event::WorktreeRemoved(_) => this.load_configs(cx),
+                project::Event::WorktreeUpdatedEntries(_, changes) => {
+                    let debug_file = paths::local_debug_file_relative_path();
+                    if changes
+                        .iter()
+                        .any(|(path, _, _)| path.as_ref() == debug_file)
+                    {
+                        this.load_configs(cx);
+                    }
+                }
+                _ => {}
+            });
+            let breakpoint_store = project.read(cx).breakpoint_store().clone();
+            let breakpoints_subscription = cx.subscribe(&breakpoint_store, |this, _, _, cx| {
+                this.serialize_breakpoints(cx);
+                cx.notify();
+            });
+            let watch_editor = cx.new_view(|cx| {
+                let mut editor = Editor::single_line(cx);
+                editor.set_placeholder_text("Add watch expression…", cx);
+                editor
+            });
+            let settings_subscription = cx.observe_global::<settings::SettingsStore>(|this, cx| {
+                this.update_inline_values(cx);
+                cx.notify();
+            });
+
+            let mut this = Self {
+                workspace: workspace_handle,
+                project,
+                fs,
+                focus_handle: cx.focus_handle(),
+                width: None,
+                height: None,
+                configs: Vec::new(),
+                selected_config: 0,
+                session: None,
+                error: None,
+                watch_editor,
+                stopped_editor: None,
+                restored_worktrees: HashSet::default(),
+                load_configs_task: Task::ready(()),
+                show_frame_task: Task::ready(()),
+                pending_serialization: Task::ready(None),
+                session_subscriptions: Vec::new(),
+                _subscriptions: vec![
+                    project_subscription,
+                    breakpoints_subscription,
+                    settings_subscription,
+                ],
+            };
+            this.restore_breakpoints(cx);
+            this.load_configs(cx);
+            this
+        })
+    }
+
+    /// Reads the launch configurations from the `.zed/debug.json` files of
+    /// the worktrees. Debug adapters are started as local processes, so
+    /// remote projects have no configurations.
+    fn load_configs(&mut self, cx: &mut ViewContext<Self>) {
+        let project = self.project.read(cx);
+        if !project.is_local() {
+            return;
+        }
+        let roots = project
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).abs_path())
+            .collect::<Vec<_>>();
+        let fs = self.fs.clone();
+        self.load_configs_task = cx.spawn(|this, mut cx| async move {
+            let mut configs = Vec::new();
+            for root in roots {
+                let path = root.join(paths::local_debug_file_relative_path());
+                let Ok(contents) = fs.load(&path).await else {
+                    continue;
+                };
+                match DebugConfig::parse_all(&contents) {
+                    Ok(parsed) => {
+                        configs.extend(parsed.into_iter().map(|config| WorktreeDebugConfig {
+                            worktree_root: root.clone(),
+                            config,
+                        }))
+                    }
+                    Err(error) => log::error!("failed to parse {path:?}: {error:#}"),
+                }
+            }
+            this.update(&mut cx, |this, cx| {
+                this.selected_config = this.selected_config.min(configs.len().saturating_sub(1));
+                this.configs = configs;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn is_debugging(&self, cx: &AppContext) -> bool {
+        self.session.as_ref().map_or(false, |session| {
+            session.read(cx).status() != SessionStatus::Exited
+        })
+    }
+
+    /// Debugs the command of a task, with the debug adapter of the selected
+    /// configuration.
+    pub(crate) fn debug_task(&mut self, task: &SpawnInTerminal, cx: &mut ViewContext<Self>) {
+        if self.is_debugging(cx) {
+            self.error = Some("Stop the running debug session to debug the task".into());
+            cx.notify();
+            return;
+        }
+        self.error = None;
+        let Some(config) = self.configs.get(self.selected_config).cloned() else {
+            self.error = Some(
+                "No debug configurations found in .zed/debug.json to debug the task with".into(),
+            );
+            cx.notify();
+            return;
+        };
+        match DebugSession::start(
+            config.config.for_task(task),
+            &config.worktree_root,
+            self.project.clone(),
+            cx,
+        ) {
+            Ok(session) => self.set_session(session, cx),
+            Err(error) => self.error = Some(format!("{error:#}").into()),
+        }
+        cx.notify();
+    }
+
+    /// Starts debugging with the selected configuration, or resumes the
+    /// session if it's paused.
+    pub(crate) fn start(&mut self, cx: &mut ViewContext<Self>) {
+        if self.is_debugging(cx) {
+            self.update_session(cx, |session, cx| {
+                if session.status() == SessionStatus::Stopped {
+                    session.continue_(cx);
+                }
+            });
+            return;
+        }
+        self.error = None;
+        let Some(config) = self.configs.get(self.selected_config).cloned() else {
+            self.error = Some("No debug configurations found in .zed/debug.json".into());
+            cx.notify();
+            return;
+        };
+        match DebugSession::start(
+            config.config,
+            &config.worktree_root,
+            self.project.clone(),
+            cx,
+        ) {
+            Ok(session) => self.set_session(session, cx),
+            Err(error) => self.error = Some(format!("{error:#}").into()),
+        }
+        cx.notify();
+    }
+
+    fn set_session(&mut self, session: Model<DebugSession>, cx: &mut ViewContext<Self>) {
+        self.clear_stopped_editor(cx);
+        self.session_subscriptions = vec![
+            cx.observe(&session, |_, _, cx| cx.notify()),
+            cx.subscribe(&session, |this, _, event, cx| match event {
+                DebugSessionEvent::Stopped => this.show_selected_frame(cx),
+                DebugSessionEvent::FrameUpdated => this.update_inline_values(cx),
+                DebugSessionEvent::Continued | DebugSessionEvent::Exited => {
+                    this.clear_stopped_editor(cx)
+                }
+            }),
+        ];
+        self.session = Some(session);
+    }
+
+    pub(crate) fn update_session(
+        &mut self,
+        cx: &mut ViewContext<Self>,
+        update: impl FnOnce(&mut DebugSession, &mut gpui::ModelContext<DebugSession>),
+    ) {
+        if let Some(session) = self.session.clone() {
+            session.update(cx, update);
+        }
+    }
+
+    fn select_frame(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        self.update_session(cx, |session, cx| session.select_frame(ix, cx));
+        self.show_selected_frame(cx);
+    }
+
+    /// Opens the source of the selected stack frame and highlights the line
+    /// being executed.
+    fn show_selected_frame(&mut self, cx: &mut ViewContext<Self>) {
+        self.clear_stopped_editor(cx);
+        let Some(session) = self.session.as_ref() else {
+            return;
+        };
+        let Some(frame) = session.read(cx).selected_frame() else {
+            return;
+        };
+        let Some(path) = frame.source.as_ref().and_then(|source| source.path.clone()) else {
+            return;
+        };
+        let row = frame.line.saturating_sub(1);
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let open_task = workspace.update(cx, |workspace, cx| {
+            workspace.open_abs_path(PathBuf::from(path), false, cx)
+        });
+        self.show_frame_task = cx.spawn(|this, mut cx| async move {
+            let Some(item) = open_task.await.log_err() else {
+                return;
+            };
+            let Some(editor) = item.downcast::<Editor>() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                editor.update(cx, |editor, cx| {
+                    let point = Point::new(row, 0);
+                    let anchor = editor.buffer().read(cx).snapshot(cx).anchor_before(point);
+                    editor.change_selections(Some(Autoscroll::center()), cx, |selections| {
+                        selections.select_ranges([point..point])
+                    });
+                    editor.highlight_rows::<DebugStoppedLine>(
+                        anchor..=anchor,
+                        Some(cx.theme().colors().editor_highlighted_line_background),
+                        false,
+                        cx,
+                    );
+                });
+                this.stopped_editor = Some(editor.downgrade());
+                this.update_inline_values(cx);
+            })
+            .ok();
+        });
+    }
+
+    fn clear_stopped_editor(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(editor) = self
+            .stopped_editor
+            .take()
+            .and_then(|editor| editor.upgrade())
+        {
+            editor.update(cx, |editor, cx| {
+                editor.clear_row_highlights::<DebugStoppedLine>();
+                editor.clear_debug_values(cx);
+            });
+        }
+    }
+
+    /// Shows the values of the selected frame's variables next to the lines
+    /// leading up to the stopped line.
+    fn update_inline_values(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(editor) = self
+            .stopped_editor
+            .as_ref()
+            .and_then(|editor| editor.upgrade())
+        else {
+            return;
+        };
+        let Some(session) = self.session.as_ref() else {
+            return;
+        };
+        let session = session.read(cx);
+        let Some(frame) = session.selected_frame() else {
+            return;
+        };
+        let stopped_row = frame.line.saturating_sub(1);
+        let variables = if DebugPanelSettings::get_global(cx).inline_values {
+            session
+                .scopes()
+                .iter()
+                .flat_map(|scope| &scope.variables)
+                .map(|variable| (variable.name.clone(), variable.value.clone()))
+                .collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
+
+        editor.update(cx, |editor, cx| {
+            let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+                return;
+            };
+            let buffer = buffer.read(cx).snapshot();
+            let stopped_row = stopped_row.min(buffer.max_point().row);
+            let lines = (stopped_row.saturating_sub(MAX_INLINE_VALUE_ROWS)..=stopped_row)
+                .map(|row| {
+                    let end = Point::new(row, buffer.line_len(row));
+                    (
+                        row,
+                        buffer
+                            .text_for_range(Point::new(row, 0)..end)
+                            .collect::<String>(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let variables = variables
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect::<Vec<_>>();
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let values = inline_values(
+                lines.iter().map(|(row, line)| (*row, line.as_str())),
+                &variables,
+            )
+            .into_iter()
+            .map(|(row, text)| {
+                let position = snapshot.anchor_after(Point::new(row, buffer.line_len(row)));
+                (position, text)
+            })
+            .collect();
+            editor.set_debug_values(values, cx);
+        });
+    }
+
+    fn add_watch(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let expression = self.watch_editor.update(cx, |editor, cx| {
+            let text = editor.text(cx);
+            editor.clear(cx);
+            text
+        });
+        self.update_session(cx, |session, cx| {
+            session.add_watch(expression.trim().to_string(), cx)
+        });
+    }
+
+    fn open_breakpoint(&mut self, path: ProjectPath, row: u32, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let open_task = workspace.update(cx, |workspace, cx| {
+            workspace.open_path(path, None, true, cx)
+        });
+        cx.spawn(|_, mut cx| async move {
+            let item = open_task.await?;
+            if let Some(editor) = item.downcast::<Editor>() {
+                editor.update(&mut cx, |editor, cx| {
+                    let point = Point::new(row, 0);
+                    editor.change_selections(Some(Autoscroll::center()), cx, |selections| {
+                        selections.select_ranges([point..point])
+                    });
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn restore_breakpoints(&mut self, cx: &mut ViewContext<Self>) {
+        let project = self.project.read(cx);
+        let worktrees = project
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx))
+            .filter(|worktree| !self.restored_worktrees.contains(&worktree.id()))
+            .map(|worktree| (worktree.id(), breakpoints_key(&worktree.abs_path())))
+            .collect::<Vec<_>>();
+        if worktrees.is_empty() {
+            return;
+        }
+        let breakpoint_store = project.breakpoint_store().clone();
+        cx.spawn(|this, mut cx| async move {
+            let mut restored = Vec::new();
+            for (worktree_id, key) in &worktrees {
+                let key = key.clone();
+                let serialized = cx
+                    .background_executor()
+                    .spawn(async move { KEY_VALUE_STORE.read_kvp(&key) })
+                    .await
+                    .log_err()
+                    .flatten();
+                let breakpoints = serialized
+                    .and_then(|serialized| {
+                        serde_json::from_str::<BTreeMap<PathBuf, Vec<u32>>>(&serialized).log_err()
+                    })
+                    .unwrap_or_default();
+                restored.extend(breakpoints.into_iter().map(|(path, rows)| {
+                    let path = ProjectPath {
+                        worktree_id: *worktree_id,
+                        path: path.into(),
+                    };
+                    (path, rows)
+                }));
+            }
+            this.update(&mut cx, |this, cx| {
+                this.restored_worktrees
+                    .extend(worktrees.into_iter().map(|(worktree_id, _)| worktree_id));
+                breakpoint_store.update(cx, |store, cx| {
+                    for (path, rows) in restored {
+                        if store.rows(&path).is_empty() {
+                            store.set_rows(path, rows, cx);
+                        }
+                    }
+                });
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Stores the breakpoints of each worktree, so they're restored when the
+    /// worktree is opened again.
+    fn serialize_breakpoints(&mut self, cx: &mut ViewContext<Self>) {
+        let project = self.project.read(cx);
+        let store = project.breakpoint_store().read(cx);
+        let entries = project
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx))
+            .filter(|worktree| self.restored_worktrees.contains(&worktree.id()))
+            .map(|worktree| {
+                let breakpoints = store
+                    .breakpoints()
+                    .filter(|(path, _)| path.worktree_id == worktree.id())
+                    .map(|(path, rows)| (path.path.to_path_buf(), rows.to_vec()))
+                    .collect::<BTreeMap<_, _>>();
+                (breakpoints_key(&worktree.abs_path()), breakpoints)
+            })
+            .collect::<Vec<_>>();
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                for (key, breakpoints) in entries {
+                    if breakpoints.is_empty() {
+                        KEY_VALUE_STORE.delete_kvp(key).await?;
+                    } else {
+                        KEY_VALUE_STORE
+                            .write_kvp(key, serde_json::to_string(&breakpoints)?)
+                            .await?;
+                    }
+                }
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn render_toolbar(&self, cx: &ViewContext<Self>) -> impl IntoElement {
+        let status = self
+            .session
+            .as_ref()
+            .map(|session| session.read(cx).status());
+        let is_stopped = status == Some(SessionStatus::Stopped);
+        let is_running = status == Some(SessionStatus::Running);
+        let is_debugging = status.map_or(false, |status| status != SessionStatus::Exited);
+        let title = match &self.session {
+            Some(session) => {
+                let session = session.read(cx);
+                let status = match session.status() {
+                    SessionStatus::Starting => "Starting",
+                    SessionStatus::Running => "Running",
+                    SessionStatus::Stopped => "Paused",
+                    SessionStatus::Exited => "Exited",
+                };
+                format!("{} ({status})", session.config().label)
+            }
+            None => "Not debugging".to_string(),
+        };
+
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(Label::new(title).single_line())
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(if is_debugging {
+                        IconButton::new("debug-continue", IconName::Play)
+                            .icon_size(IconSize::Small)
+                            .icon_color(Color::Success)
+                            .disabled(!is_stopped)
+                            .tooltip(|cx| Tooltip::for_action("Continue", &Continue, cx))
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.update_session(cx, |session, cx| session.continue_(cx))
+                            }))
+                    } else {
+                        IconButton::new("debug-start", IconName::Play)
+                            .icon_size(IconSize::Small)
+                            .icon_color(Color::Success)
+                            .disabled(self.configs.is_empty())
+                            .tooltip(|cx| Tooltip::for_action("Start Debugging", &Start, cx))
+                            .on_click(cx.listener(|this, _, cx| this.start(cx)))
+                    })
+                    .child(
+                        IconButton::new("debug-pause", IconName::ReplPause)
+                            .icon_size(IconSize::Small)
+                            .disabled(!is_running)
+                            .tooltip(|cx| Tooltip::for_action("Pause", &Pause, cx))
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.update_session(cx, |session, cx| session.pause(cx))
+                            })),
+                    )
+                    .child(
+                        IconButton::new("debug-step-over", IconName::ArrowRight)
+                            .icon_size(IconSize::Small)
+                            .disabled(!is_stopped)
+                            .tooltip(|cx| Tooltip::for_action("Step Over", &StepOver, cx))
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.update_session(cx, |session, cx| session.step_over(cx))
+                            })),
+                    )
+                    .child(
+                        IconButton::new("debug-step-into", IconName::ArrowDownFromLine)
+                            .icon_size(IconSize::Small)
+                            .disabled(!is_stopped)
+                            .tooltip(|cx| Tooltip::for_action("Step Into", &StepInto, cx))
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.update_session(cx, |session, cx| session.step_into(cx))
+                            })),
+                    )
+                    .child(
+                        IconButton::new("debug-step-out", IconName::ArrowUpFromLine)
+                            .icon_size(IconSize::Small)
+                            .disabled(!is_stopped)
+                            .tooltip(|cx| Tooltip::for_action("Step Out", &StepOut, cx))
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.update_session(cx, |session, cx| session.step_out(cx))
+                            })),
+                    )
+                    .child(
+                        IconButton::new("debug-stop", IconName::Stop)
+                            .icon_size(IconSize::Small)
+                            .icon_color(Color::Error)
+                            .disabled(!is_debugging)
+                            .tooltip(|cx| Tooltip::for_action("Stop Debugging", &Stop, cx))
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.update_session(cx, |session, cx| session.stop(cx))
+                            })),
+                    ),
+            )
+    }
+
+    fn render_configs(&self, cx: &ViewContext<Self>) -> AnyElement {
+        if self.configs.is_empty() {
+            return Label::new("Add launch configurations to .zed/debug.json")
+                .color(Color::Muted)
+                .into_any_element();
+        }
+        v_flex()
+            .children(self.configs.iter().enumerate().map(|(ix, config)| {
+                ListItem::new(("debug-config", ix))
+                    .selected(ix == self.selected_config)
+                    .child(Label::new(config.config.label.clone()).single_line())
+                    .on_click(cx.listener(move |this, _, cx| {
+                        this.selected_config = ix;
+                        cx.notify();
+                    }))
+            }))
+            .into_any_element()
+    }
+
+    fn render_stack_frames(&self, session: &DebugSession, cx: &ViewContext<Self>) -> AnyElement {
+        if session.stack_frames().is_empty() {
+            let message = match session.status() {
+                SessionStatus::Stopped => "Loading…",
+                _ => "Not paused",
+            };
+            return Label::new(message).color(Color::Muted).into_any_element();
+        }
+        v_flex()
+            .when_some(session.stopped_thread(), |this, thread| {
+                this.child(
+                    Label::new(format!("Thread: {}", thread.name))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+            .children(
+                session
+                    .stack_frames()
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, frame)| {
+                        let location = frame
+                            .source
+                            .as_ref()
+                            .and_then(|source| source.name.clone().or_else(|| source.path.clone()))
+                            .map(|name| format!("{name}:{}", frame.line));
+                        ListItem::new(("stack-frame", ix))
+                            .selected(ix == session.selected_frame_ix())
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(Label::new(frame.name.clone()).single_line())
+                                    .children(location.map(|location| {
+                                        Label::new(location)
+                                            .size(LabelSize::Small)
+                                            .color(Color::Muted)
+                                            .single_line()
+                                    })),
+                            )
+                            .on_click(cx.listener(move |this, _, cx| this.select_frame(ix, cx)))
+                    }),
+            )
+            .into_any_element()
+    }
+
+    fn render_variables(&self, session: &DebugSession) -> AnyElement {
+        if session.scopes().is_empty() {
+            return Label::new("No variables")
+                .color(Color::Muted)
+                .into_any_element();
+        }
+        v_flex()
+            .children(session.scopes().iter().map(|scope| {
+                v_flex()
+                    .child(
+                        Label::new(scope.name.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .when(scope.expensive, |this| {
+                        this.child(Label::new("Not loaded").color(Color::Muted))
+                    })
+                    .children(scope.variables.iter().map(|variable| {
+                        h_flex()
+                            .pl_2()
+                            .gap_1()
+                            .child(Label::new(format!("{} =", variable.name)).single_line())
+                            .child(
+                                Label::new(variable.value.clone())
+                                    .color(Color::Info)
+                                    .single_line(),
+                            )
+                    }))
+            }))
+            .into_any_element()
+    }
+
+    fn render_watches(&self, session: Option<&DebugSession>, cx: &ViewContext<Self>) -> Div {
+        v_flex()
+            .gap_1()
+            .child(
+                div()
+                    .px_1()
+                    .border_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .rounded_md()
+                    .on_action(cx.listener(Self::add_watch))
+                    .child(self.watch_editor.clone()),
+            )
+            .children(session.into_iter().flat_map(|session| {
+                session.watches().iter().enumerate().map(|(ix, watch)| {
+                    let (value, color) = match &watch.result {
+                        Some(Ok(value)) => (value.clone(), Color::Info),
+                        Some(Err(error)) => (error.clone(), Color::Error),
+                        None => ("not available".to_string(), Color::Muted),
+                    };
+                    h_flex()
+                        .gap_1()
+                        .child(Label::new(format!("{} =", watch.expression)).single_line())
+                        .child(Label::new(value).color(color).single_line())
+                        .child(
+                            IconButton::new(("remove-watch", ix), IconName::Close)
+                                .shape(IconButtonShape::Square)
+                                .icon_size(IconSize::XSmall)
+                                .tooltip(|cx| Tooltip::text("Remove Watch", cx))
+                                .on_click(cx.listener(move |this, _, cx| {
+                                    this.update_session(cx, |session, cx| {
+                                        session.remove_watch(ix, cx)
+                                    })
+                                })),
+                        )
+                })
+            }))
+    }
+
+    fn render_breakpoints(&self, cx: &ViewContext<Self>) -> AnyElement {
+        let store = self.project.read(cx).breakpoint_store().read(cx);
+        let breakpoints = store
+            .breakpoints()
+            .flat_map(|(path, rows)| rows.iter().map(move |row| (path.clone(), *row)))
+            .collect::<Vec<_>>();
+        if breakpoints.is_empty() {
+            return Label::new("Alt-click the gutter or press F9 to add breakpoints")
+                .color(Color::Muted)
+                .into_any_element();
+        }
+        v_flex()
+            .children(
+                breakpoints
+                    .into_iter()
+                    .enumerate()
+                    .map(|(ix, (path, row))| {
+                        let label = format!("{}:{}", path.path.to_string_lossy(), row + 1);
+                        let open_path = path.clone();
+                        ListItem::new(("breakpoint", ix))
+                            .start_slot(
+                                Icon::new(IconName::Indicator)
+                                    .size(IconSize::XSmall)
+                                    .color(Color::Error),
+                            )
+                            .child(Label::new(label).single_line())
+                            .end_slot(
+                                IconButton::new(("remove-breakpoint", ix), IconName::Close)
+                                    .shape(IconButtonShape::Square)
+                                    .icon_size(IconSize::XSmall)
+                                    .tooltip(|cx| Tooltip::text("Remove Breakpoint", cx))
+                                    .on_click(cx.listener(move |this, _, cx| {
+                                        let store =
+                                            this.project.read(cx).breakpoint_store().clone();
+                                        store.update(cx, |store, cx| store.remove(&path, row, cx));
+                                    })),
+                            )
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.open_breakpoint(open_path.clone(), row, cx)
+                            }))
+                    }),
+            )
+            .into_any_element()
+    }
+
+    fn render_output(&self, session: &DebugSession) -> Div {
+        v_flex().children(
+            session
+                .output()
+                .iter()
+                .map(|line| Label::new(line.clone()).size(LabelSize::Small)),
+        )
+    }
+}
+
+fn breakpoints_key(worktree_root: &Path) -> String {
+    format!("{BREAKPOINTS_KEY_PREFIX}:{}", worktree_root.display())
+}
+
+fn render_section(
+    id: &'static str,
+    title: &'static str,
+    content: impl IntoElement,
+    cx: &ViewContext<DebugPanel>,
+) -> impl IntoElement {
+    v_flex()
+        .id(id)
+        .flex_1()
+        .min_w_0()
+        .h_full()
+        .p_2()
+        .gap_1()
+        .overflow_y_scroll()
+        .border_r_1()
+        .border_color(cx.theme().colors().border_variant)
+        .child(Label::new(title).size(LabelSize::Small).color(Color::Muted))
+        .child(content)
+}
+
+impl Panel for DebugPanel {
+    fn persistent_name() -> &'static str {
+        "Debug Panel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        match DebugPanelSettings::get_global(cx).dock {
+            DebugPanelDockPosition::Left => DockPosition::Left,
+            DebugPanelDockPosition::Bottom => DockPosition::Bottom,
+            DebugPanelDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, _: DockPosition) -> bool {
+        true
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<DebugPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| {
+                let dock = match position {
+                    DockPosition::Left => DebugPanelDockPosition::Left,
+                    DockPosition::Bottom => DebugPanelDockPosition::Bottom,
+                    DockPosition::Right => DebugPanelDockPosition::Right,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        let settings = DebugPanelSettings::get_global(cx);
+        match self.position(cx) {
+            DockPosition::Left | DockPosition::Right => {
+                self.width.unwrap_or(settings.default_width)
+            }
+            DockPosition::Bottom => self.height.unwrap_or(settings.default_height),
+        }
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        match self.position(cx) {
+            DockPosition::Left | DockPosition::Right => self.width = size,
+            DockPosition::Bottom => self.height = size,
+        }
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        DebugPanelSettings::get_global(cx)
+            .button
+            .then(|| IconName::Indicator)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Debug Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+}
+
+impl FocusableView for DebugPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for DebugPanel {}
+
+impl Render for DebugPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let session = self.session.clone();
+        let session = session.as_ref().map(|session| session.read(cx));
+
+        let first_section = match session {
+            Some(session) if session.status() != SessionStatus::Exited => render_section(
+                "debug-call-stack",
+                "Call Stack",
+                self.render_stack_frames(session, cx),
+                cx,
+            )
+            .into_any_element(),
+            _ => render_section(
+                "debug-configurations",
+                "Configurations",
+                self.render_configs(cx),
+                cx,
+            )
+            .into_any_element(),
+        };
+        let variables = match session {
+            Some(session) => self.render_variables(session),
+            None => Label::new("Not debugging")
+                .color(Color::Muted)
+                .into_any_element(),
+        };
+
+        v_flex()
+            .id("debug-panel")
+            .size_full()
+            .key_context("DebugPanel")
+            .track_focus(&self.focus_handle)
+            .child(self.render_toolbar(cx))
+            .when_some(self.error.clone(), |this, error| {
+                this.child(
+                    h_flex()
+                        .px_2()
+                        .gap_1()
+                        .child(Icon::new(IconName::XCircle).color(Color::Error))
+                        .child(Label::new(error).color(Color::Error)),
+                )
+            })
+            .child(
+                h_flex()
+                    .flex_1()
+                    .min_h_0()
+                    .items_start()
+                    .child(first_section)
+                    .child(render_section(
+                        "debug-variables",
+                        "Variables",
+                        variables,
+                        cx,
+                    ))
+                    .child(render_section(
+                        "debug-watch",
+                        "Watch",
+                        self.render_watches(session, cx),
+                        cx,
+                    ))
+                    .child(render_section(
+                        "debug-breakpoints",
+                        "Breakpoints",
+                        self.render_breakpoints(cx),
+                        cx,
+                    ))
+                    .children(session.map(|session| {
+                        render_section("debug-output", "Output", self.render_output(session), cx)
+                    })),
+            )
+    }
+}
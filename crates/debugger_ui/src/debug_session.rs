@@ -0,0 +1,539 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::Result;
+use dap::{
+    requests::{
+        Attach, ConfigurationDone, Continue, Evaluate, Launch, Next, Pause, Scopes, SetBreakpoints,
+        StackTrace, StepIn, StepOut, Threads, Variables,
+    },
+    DebugAdapterClient, EvaluateArguments, Event, Request, ScopesArguments,
+    SetBreakpointsArguments, Source, SourceBreakpoint, StackFrame, StackTraceArguments, Thread,
+    ThreadArguments, Variable, VariablesArguments,
+};
+use futures::{future::join_all, StreamExt as _};
+use gpui::{AppContext, EventEmitter, Model, ModelContext, Subscription, Task};
+use project::{BreakpointStoreEvent, Project, ProjectPath};
+use serde_json::Value;
+use util::ResultExt;
+
+use crate::debug_config::{DebugConfig, DebugRequestKind};
+
+/// The number of output lines kept, dropping the oldest ones first.
+const MAX_OUTPUT_LINES: usize = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SessionStatus {
+    Starting,
+    Running,
+    Stopped,
+    Exited,
+}
+
+pub struct VariableScope {
+    pub name: String,
+    pub variables: Vec<Variable>,
+    /// Whether the variables weren't fetched because the adapter reported
+    /// them as expensive to retrieve.
+    pub expensive: bool,
+}
+
+pub struct Watch {
+    pub expression: String,
+    pub result: Option<Result<String, String>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugSessionEvent {
+    /// Execution stopped and the stack of the stopped thread was fetched.
+    Stopped,
+    /// The variables of the selected stack frame were fetched.
+    FrameUpdated,
+    Continued,
+    Exited,
+}
+
+/// A debug adapter debugging a program launched from, or attached to by, a
+/// [`DebugConfig`].
+pub struct DebugSession {
+    client: Arc<DebugAdapterClient>,
+    config: DebugConfig,
+    project: Model<Project>,
+    status: SessionStatus,
+    is_configured: bool,
+    threads: Vec<Thread>,
+    stopped_thread: Option<u64>,
+    stack_frames: Vec<StackFrame>,
+    selected_frame: usize,
+    scopes: Vec<VariableScope>,
+    watches: Vec<Watch>,
+    output: Vec<String>,
+    stack_task: Option<Task<()>>,
+    frame_task: Option<Task<()>>,
+    _event_task: Task<()>,
+    _breakpoints_subscription: Subscription,
+}
+
+impl EventEmitter<DebugSessionEvent> for DebugSession {}
+
+impl DebugSession {
+    pub fn start(
+        config: DebugConfig,
+        worktree_root: &Path,
+        project: Model<Project>,
+        cx: &mut AppContext,
+    ) -> Result<Model<Self>> {
+        let config = config.resolve(worktree_root);
+        let (client, mut events) =
+            DebugAdapterClient::start(config.binary(worktree_root), cx.background_executor())?;
+        Ok(cx.new_model(|cx| {
+            let breakpoint_store = project.read(cx).breakpoint_store().clone();
+            let breakpoints_subscription = cx.subscribe(&breakpoint_store, |this, _, event, cx| {
+                let BreakpointStoreEvent::BreakpointsChanged(path) = event;
+                if this.is_configured && this.status != SessionStatus::Exited {
+                    this.send_breakpoints(path.clone(), cx).detach();
+                }
+            });
+            let event_task = cx.spawn(|this, mut cx| async move {
+                while let Some(event) = events.next().await {
+                    if this
+                        .update(&mut cx, |this, cx| this.handle_event(event, cx))
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+                this.update(&mut cx, |this, cx| this.set_exited(cx)).ok();
+            });
+
+            let mut this = Self {
+                client: Arc::new(client),
+                config,
+                project,
+                status: SessionStatus::Starting,
+                is_configured: false,
+                threads: Vec::new(),
+                stopped_thread: None,
+                stack_frames: Vec::new(),
+                selected_frame: 0,
+                scopes: Vec::new(),
+                watches: Vec::new(),
+                output: Vec::new(),
+                stack_task: None,
+                frame_task: None,
+                _event_task: event_task,
+                _breakpoints_subscription: breakpoints_subscription,
+            };
+            this.initialize(cx);
+            this
+        }))
+    }
+
+    pub fn config(&self) -> &DebugConfig {
+        &self.config
+    }
+
+    pub fn status(&self) -> SessionStatus {
+        self.status
+    }
+
+    pub fn stack_frames(&self) -> &[StackFrame] {
+        &self.stack_frames
+    }
+
+    pub fn selected_frame_ix(&self) -> usize {
+        self.selected_frame
+    }
+
+    pub fn selected_frame(&self) -> Option<&StackFrame> {
+        self.stack_frames.get(self.selected_frame)
+    }
+
+    pub fn scopes(&self) -> &[VariableScope] {
+        &self.scopes
+    }
+
+    pub fn watches(&self) -> &[Watch] {
+        &self.watches
+    }
+
+    pub fn output(&self) -> &[String] {
+        &self.output
+    }
+
+    pub fn stopped_thread(&self) -> Option<&Thread> {
+        let thread_id = self.stopped_thread?;
+        self.threads.iter().find(|thread| thread.id == thread_id)
+    }
+
+    /// Sends the `initialize` request, followed by the launch or attach
+    /// request. The breakpoints are sent once the adapter reports that it's
+    /// initialized.
+    fn initialize(&mut self, cx: &mut ModelContext<Self>) {
+        let client = self.client.clone();
+        let adapter_id = self.config.adapter_id();
+        let request = self.config.request;
+        let arguments = Value::Object(self.config.arguments.clone());
+        cx.spawn(|this, mut cx| async move {
+            let result = async {
+                client.initialize(&adapter_id).await?;
+                match request {
+                    DebugRequestKind::Launch => client.request::<Launch>(arguments).await?,
+                    DebugRequestKind::Attach => client.request::<Attach>(arguments).await?,
+                };
+                anyhow::Ok(())
+            }
+            .await;
+            if let Err(error) = result {
+                this.update(&mut cx, |this, cx| {
+                    this.push_output(format!("Failed to start debugging: {error:#}"), cx);
+                    this.set_exited(cx);
+                })
+                .ok();
+            }
+        })
+        .detach();
+    }
+
+    fn handle_event(&mut self, event: Event, cx: &mut ModelContext<Self>) {
+        match event {
+            Event::Initialized => self.configure(cx),
+            Event::Stopped(event) => {
+                self.status = SessionStatus::Stopped;
+                self.stopped_thread = event.thread_id.or(self.stopped_thread);
+                if let Some(description) = event.description {
+                    self.push_output(description, cx);
+                }
+                self.fetch_stack(cx);
+            }
+            Event::Continued(_) => self.set_running(cx),
+            Event::Exited(event) => {
+                self.push_output(format!("Process exited with code {}", event.exit_code), cx)
+            }
+            Event::Terminated => self.set_exited(cx),
+            Event::Output(event) => {
+                if event.category.as_deref() != Some("telemetry") {
+                    self.push_output(event.output, cx);
+                }
+            }
+            Event::Other(event) => log::debug!("unhandled debug adapter event {event:?}"),
+        }
+    }
+
+    fn configure(&mut self, cx: &mut ModelContext<Self>) {
+        self.is_configured = true;
+        let paths = self
+            .project
+            .read(cx)
+            .breakpoint_store()
+            .read(cx)
+            .breakpoints()
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        let requests = paths
+            .into_iter()
+            .map(|path| self.send_breakpoints(path, cx))
+            .collect::<Vec<_>>();
+        let client = self.client.clone();
+        cx.spawn(|this, mut cx| async move {
+            join_all(requests).await;
+            if client.capabilities().supports_configuration_done_request {
+                client
+                    .request::<ConfigurationDone>(Value::Null)
+                    .await
+                    .log_err();
+            }
+            let threads = client.request::<Threads>(Value::Null).await.log_err();
+            this.update(&mut cx, |this, cx| {
+                if let Some(threads) = threads {
+                    this.threads = threads.threads;
+                }
+                if this.status == SessionStatus::Starting {
+                    this.status = SessionStatus::Running;
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Replaces the breakpoints the adapter has for a file.
+    fn send_breakpoints(&self, path: ProjectPath, cx: &AppContext) -> Task<()> {
+        let project = self.project.read(cx);
+        let Some(abs_path) = project.absolute_path(&path, cx) else {
+            return Task::ready(());
+        };
+        let arguments = SetBreakpointsArguments {
+            source: Source {
+                name: path
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned()),
+                path: Some(abs_path.to_string_lossy().into_owned()),
+            },
+            breakpoints: project
+                .breakpoint_store()
+                .read(cx)
+                .rows(&path)
+                .iter()
+                .map(|row| SourceBreakpoint { line: row + 1 })
+                .collect(),
+        };
+        let client = self.client.clone();
+        cx.background_executor().spawn(async move {
+            client.request::<SetBreakpoints>(arguments).await.log_err();
+        })
+    }
+
+    fn fetch_stack(&mut self, cx: &mut ModelContext<Self>) {
+        let client = self.client.clone();
+        let stopped_thread = self.stopped_thread;
+        self.stack_task = Some(cx.spawn(|this, mut cx| async move {
+            let threads = client
+                .request::<Threads>(Value::Null)
+                .await
+                .log_err()
+                .map(|response| response.threads)
+                .unwrap_or_default();
+            let Some(thread_id) =
+                stopped_thread.or_else(|| threads.first().map(|thread| thread.id))
+            else {
+                return;
+            };
+            let stack_frames = client
+                .request::<StackTrace>(StackTraceArguments {
+                    thread_id,
+                    levels: None,
+                })
+                .await
+                .log_err()
+                .map(|response| response.stack_frames)
+                .unwrap_or_default();
+            this.update(&mut cx, |this, cx| {
+                this.threads = threads;
+                this.stopped_thread = Some(thread_id);
+                this.stack_frames = stack_frames;
+                this.select_frame(0, cx);
+                cx.emit(DebugSessionEvent::Stopped);
+            })
+            .ok();
+        }));
+    }
+
+    /// Selects a frame of the stopped thread's stack, fetching its
+    /// variables and evaluating the watch expressions in it.
+    pub fn select_frame(&mut self, ix: usize, cx: &mut ModelContext<Self>) {
+        self.selected_frame = ix;
+        self.scopes.clear();
+        cx.notify();
+        let Some(frame_id) = self.selected_frame().map(|frame| frame.id) else {
+            return;
+        };
+
+        let client = self.client.clone();
+        let expressions = self
+            .watches
+            .iter()
+            .map(|watch| watch.expression.clone())
+            .collect::<Vec<_>>();
+        self.frame_task = Some(cx.spawn(|this, mut cx| async move {
+            let scopes = client
+                .request::<Scopes>(ScopesArguments { frame_id })
+                .await
+                .log_err()
+                .map(|response| response.scopes)
+                .unwrap_or_default();
+            let mut variable_scopes = Vec::new();
+            for scope in scopes {
+                let variables = if scope.expensive {
+                    Vec::new()
+                } else {
+                    client
+                        .request::<Variables>(VariablesArguments {
+                            variables_reference: scope.variables_reference,
+                        })
+                        .await
+                        .log_err()
+                        .map(|response| response.variables)
+                        .unwrap_or_default()
+                };
+                variable_scopes.push(VariableScope {
+                    name: scope.name,
+                    variables,
+                    expensive: scope.expensive,
+                });
+            }
+
+            let mut results = Vec::new();
+            for expression in expressions {
+                let result = evaluate(&client, expression.clone(), frame_id).await;
+                results.push((expression, result));
+            }
+
+            this.update(&mut cx, |this, cx| {
+                this.scopes = variable_scopes;
+                for (expression, result) in results {
+                    if let Some(watch) = this
+                        .watches
+                        .iter_mut()
+                        .find(|watch| watch.expression == expression)
+                    {
+                        watch.result = Some(result);
+                    }
+                }
+                cx.emit(DebugSessionEvent::FrameUpdated);
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    pub fn add_watch(&mut self, expression: String, cx: &mut ModelContext<Self>) {
+        if expression.trim().is_empty()
+            || self
+                .watches
+                .iter()
+                .any(|watch| watch.expression == expression)
+        {
+            return;
+        }
+        self.watches.push(Watch {
+            expression: expression.clone(),
+            result: None,
+        });
+        cx.notify();
+
+        let Some(frame_id) = self.selected_frame().map(|frame| frame.id) else {
+            return;
+        };
+        let client = self.client.clone();
+        cx.spawn(|this, mut cx| async move {
+            let result = evaluate(&client, expression.clone(), frame_id).await;
+            this.update(&mut cx, |this, cx| {
+                if let Some(watch) = this
+                    .watches
+                    .iter_mut()
+                    .find(|watch| watch.expression == expression)
+                {
+                    watch.result = Some(result);
+                    cx.notify();
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    pub fn remove_watch(&mut self, ix: usize, cx: &mut ModelContext<Self>) {
+        if ix < self.watches.len() {
+            self.watches.remove(ix);
+            cx.notify();
+        }
+    }
+
+    pub fn continue_(&mut self, cx: &mut ModelContext<Self>) {
+        self.thread_request::<Continue>(true, cx);
+    }
+
+    pub fn step_over(&mut self, cx: &mut ModelContext<Self>) {
+        self.thread_request::<Next>(true, cx);
+    }
+
+    pub fn step_into(&mut self, cx: &mut ModelContext<Self>) {
+        self.thread_request::<StepIn>(true, cx);
+    }
+
+    pub fn step_out(&mut self, cx: &mut ModelContext<Self>) {
+        self.thread_request::<StepOut>(true, cx);
+    }
+
+    pub fn pause(&mut self, cx: &mut ModelContext<Self>) {
+        if self.status == SessionStatus::Running {
+            self.thread_request::<Pause>(false, cx);
+        }
+    }
+
+    fn thread_request<R>(&mut self, resumes: bool, cx: &mut ModelContext<Self>)
+    where
+        R: Request<Arguments = ThreadArguments>,
+    {
+        if resumes && self.status != SessionStatus::Stopped {
+            return;
+        }
+        let Some(thread_id) = self
+            .stopped_thread
+            .or_else(|| self.threads.first().map(|thread| thread.id))
+        else {
+            return;
+        };
+        if resumes {
+            self.set_running(cx);
+        }
+        let client = self.client.clone();
+        cx.background_executor()
+            .spawn(async move {
+                client
+                    .request::<R>(ThreadArguments { thread_id })
+                    .await
+                    .log_err();
+            })
+            .detach();
+    }
+
+    /// Ends the session, terminating the debugged program.
+    pub fn stop(&mut self, cx: &mut ModelContext<Self>) {
+        if self.status == SessionStatus::Exited {
+            return;
+        }
+        let client = self.client.clone();
+        cx.spawn(|this, mut cx| async move {
+            client.shutdown().await.log_err();
+            this.update(&mut cx, |this, cx| this.set_exited(cx)).ok();
+        })
+        .detach();
+    }
+
+    fn set_running(&mut self, cx: &mut ModelContext<Self>) {
+        self.status = SessionStatus::Running;
+        self.stack_frames.clear();
+        self.scopes.clear();
+        self.stack_task = None;
+        self.frame_task = None;
+        cx.emit(DebugSessionEvent::Continued);
+        cx.notify();
+    }
+
+    fn set_exited(&mut self, cx: &mut ModelContext<Self>) {
+        if self.status == SessionStatus::Exited {
+            return;
+        }
+        self.set_running(cx);
+        self.status = SessionStatus::Exited;
+        cx.emit(DebugSessionEvent::Exited);
+    }
+
+    fn push_output(&mut self, output: String, cx: &mut ModelContext<Self>) {
+        self.output
+            .extend(output.trim_end_matches('\n').lines().map(str::to_string));
+        if self.output.len() > MAX_OUTPUT_LINES {
+            self.output.drain(..self.output.len() - MAX_OUTPUT_LINES);
+        }
+        cx.notify();
+    }
+}
+
+async fn evaluate(
+    client: &DebugAdapterClient,
+    expression: String,
+    frame_id: u64,
+) -> Result<String, String> {
+    client
+        .request::<Evaluate>(EvaluateArguments {
+            expression,
+            frame_id: Some(frame_id),
+            context: Some("watch".into()),
+        })
+        .await
+        .map(|response| response.result)
+        .map_err(|error| error.to_string())
+}
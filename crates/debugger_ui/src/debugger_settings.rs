@@ -0,0 +1,58 @@
+use gpui::Pixels;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DebugPanelDockPosition {
+    Left,
+    Bottom,
+    Right,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct DebugPanelSettings {
+    pub button: bool,
+    pub dock: DebugPanelDockPosition,
+    pub default_width: Pixels,
+    pub default_height: Pixels,
+    pub inline_values: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct DebugPanelSettingsContent {
+    /// Whether to show the debug panel button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// The position of the debug panel.
+    ///
+    /// Default: bottom
+    pub dock: Option<DebugPanelDockPosition>,
+    /// Default width (in pixels) of the debug panel when docked to the left or right.
+    ///
+    /// Default: 640
+    pub default_width: Option<f32>,
+    /// Default height (in pixels) of the debug panel when docked to the bottom.
+    ///
+    /// Default: 320
+    pub default_height: Option<f32>,
+    /// Whether to show the values of variables inline in the editor while paused.
+    ///
+    /// Default: true
+    pub inline_values: Option<bool>,
+}
+
+impl Settings for DebugPanelSettings {
+    const KEY: Option<&'static str> = Some("debug_panel");
+
+    type FileContent = DebugPanelSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
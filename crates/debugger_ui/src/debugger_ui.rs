@@ -0,0 +1,95 @@
+mod debug_config;
+mod debug_panel;
+mod debug_session;
+mod debugger_settings;
+mod inline_values;
+
+use debugger_settings::DebugPanelSettings;
+use gpui::{actions, AppContext};
+use settings::Settings;
+use workspace::Workspace;
+
+pub use debug_panel::DebugPanel;
+
+actions!(
+    debugger,
+    [
+        ToggleFocus,
+        Start,
+        Continue,
+        Pause,
+        StepOver,
+        StepInto,
+        StepOut,
+        Stop
+    ]
+);
+
+pub fn init(cx: &mut AppContext) {
+    DebugPanelSettings::register(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, cx| {
+        let workspace_handle = cx.view().clone();
+        cx.subscribe(&workspace_handle, |workspace, _, event, cx| {
+            if let workspace::Event::DebugTask(task) = event {
+                if let Some(panel) = workspace.panel::<DebugPanel>(cx) {
+                    workspace.open_panel::<DebugPanel>(cx);
+                    panel.update(cx, |panel, cx| panel.debug_task(task, cx));
+                }
+            }
+        })
+        .detach();
+        workspace.register_action(|workspace, _: &ToggleFocus, cx| {
+            workspace.toggle_panel_focus::<DebugPanel>(cx);
+        });
+        workspace.register_action(|workspace, _: &Start, cx| {
+            if let Some(panel) = workspace.panel::<DebugPanel>(cx) {
+                workspace.open_panel::<DebugPanel>(cx);
+                panel.update(cx, |panel, cx| panel.start(cx));
+            }
+        });
+        workspace.register_action(|workspace, _: &Continue, cx| {
+            if let Some(panel) = workspace.panel::<DebugPanel>(cx) {
+                panel.update(cx, |panel, cx| {
+                    panel.update_session(cx, |session, cx| session.continue_(cx))
+                });
+            }
+        });
+        workspace.register_action(|workspace, _: &Pause, cx| {
+            if let Some(panel) = workspace.panel::<DebugPanel>(cx) {
+                panel.update(cx, |panel, cx| {
+                    panel.update_session(cx, |session, cx| session.pause(cx))
+                });
+            }
+        });
+        workspace.register_action(|workspace, _: &StepOver, cx| {
+            if let Some(panel) = workspace.panel::<DebugPanel>(cx) {
+                panel.update(cx, |panel, cx| {
+                    panel.update_session(cx, |session, cx| session.step_over(cx))
+                });
+            }
+        });
+        workspace.register_action(|workspace, _: &StepInto, cx| {
+            if let Some(panel) = workspace.panel::<DebugPanel>(cx) {
+                panel.update(cx, |panel, cx| {
+                    panel.update_session(cx, |session, cx| session.step_into(cx))
+                });
+            }
+        });
+        workspace.register_action(|workspace, _: &StepOut, cx| {
+            if let Some(panel) = workspace.panel::<DebugPanel>(cx) {
+                panel.update(cx, |panel, cx| {
+                    panel.update_session(cx, |session, cx| session.step_out(cx))
+                });
+            }
+        });
+        workspace.register_action(|workspace, _: &Stop, cx| {
+            if let Some(panel) = workspace.panel::<DebugPanel>(cx) {
+                panel.update(cx, |panel, cx| {
+                    panel.update_session(cx, |session, cx| session.stop(cx))
+                });
+            }
+        });
+    })
+    .detach();
+}
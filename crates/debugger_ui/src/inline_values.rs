@@ -0,0 +1,87 @@
+use collections::HashMap;
+
+/// Values longer than this are truncated when shown inline.
+const MAX_VALUE_LEN: usize = 40;
+
+/// Determines the rows on which to show the values of variables inline, along
+/// with the text shown at the end of those rows.
+///
+/// Each variable is shown once, on the last of the given lines mentioning it,
+/// which is the closest mention before the line where execution stopped.
+pub(crate) fn inline_values<'a>(
+    lines: impl IntoIterator<Item = (u32, &'a str)>,
+    variables: &[(&str, &str)],
+) -> Vec<(u32, String)> {
+    let mut last_mentions = HashMap::default();
+    for (row, line) in lines {
+        for word in line.split(|c: char| !c.is_alphanumeric() && c != '_') {
+            if let Some(ix) = variables.iter().position(|(name, _)| *name == word) {
+                last_mentions.insert(ix, row);
+            }
+        }
+    }
+
+    let mut values_by_row = HashMap::<u32, Vec<usize>>::default();
+    for (ix, row) in last_mentions {
+        values_by_row.entry(row).or_default().push(ix);
+    }
+    let mut values = values_by_row
+        .into_iter()
+        .map(|(row, mut ixs)| {
+            ixs.sort_unstable();
+            let text = ixs
+                .into_iter()
+                .map(|ix| {
+                    let (name, value) = variables[ix];
+                    format!("{name} = {}", truncate_value(value))
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            (row, text)
+        })
+        .collect::<Vec<_>>();
+    values.sort_unstable();
+    values
+}
+
+fn truncate_value(value: &str) -> String {
+    let value = value.lines().next().unwrap_or_default();
+    if value.chars().count() > MAX_VALUE_LEN {
+        let mut truncated = value.chars().take(MAX_VALUE_LEN).collect::<String>();
+        truncated.push('…');
+        truncated
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_values() {
+        let lines = [
+            (10, "fn main() {"),
+            (11, "    let count = 1;"),
+            (12, "    let name = format!(\"{count}\");"),
+            (13, "    let names = vec![name];"),
+            (14, "    println!(\"{names:?}\");"),
+        ];
+        let long_value = "x".repeat(50);
+        let variables = [
+            ("count", "1"),
+            ("name", "\"1\""),
+            ("names", long_value.as_str()),
+            ("unused", "0"),
+        ];
+        assert_eq!(
+            inline_values(lines, &variables),
+            vec![
+                (12, "count = 1".to_string()),
+                (13, "name = \"1\"".to_string()),
+                (14, format!("names = {}…", "x".repeat(40))),
+            ]
+        );
+    }
+}
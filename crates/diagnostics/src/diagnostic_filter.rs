@@ -0,0 +1,123 @@
+use collections::BTreeSet;
+use language::{Diagnostic, DiagnosticSeverity};
+use std::path::Path;
+use util::{paths::PathMatcher, ResultExt};
+
+use crate::project_diagnostics_settings::ProjectDiagnosticsSettings;
+
+/// Determines which diagnostic groups are shown in the project diagnostics view,
+/// based on their primary diagnostic.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DiagnosticFilter {
+    pub include_warnings: bool,
+    pub include_info: bool,
+    pub excluded_sources: BTreeSet<String>,
+    pub query: String,
+    pub exclude_dependency_warnings: bool,
+    dependency_paths: PathMatcher,
+}
+
+impl DiagnosticFilter {
+    pub fn new(settings: &ProjectDiagnosticsSettings) -> Self {
+        Self {
+            include_warnings: settings.include_warnings,
+            exclude_dependency_warnings: settings.exclude_dependency_warnings,
+            dependency_paths: PathMatcher::new(&settings.dependency_paths)
+                .log_err()
+                .unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_dependency_path(&self, path: &Path) -> bool {
+        self.dependency_paths.is_match(path)
+    }
+
+    pub fn matches(&self, diagnostic: &Diagnostic, is_dependency: bool) -> bool {
+        let include_severity = match diagnostic.severity {
+            DiagnosticSeverity::ERROR => true,
+            DiagnosticSeverity::WARNING => self.include_warnings,
+            _ => self.include_info,
+        };
+        if !include_severity {
+            return false;
+        }
+        if is_dependency
+            && self.exclude_dependency_warnings
+            && diagnostic.severity != DiagnosticSeverity::ERROR
+        {
+            return false;
+        }
+        if diagnostic
+            .source
+            .as_ref()
+            .map_or(false, |source| self.excluded_sources.contains(source))
+        {
+            return false;
+        }
+        self.matches_query(diagnostic)
+    }
+
+    fn matches_query(&self, diagnostic: &Diagnostic) -> bool {
+        let query = self.query.trim().to_lowercase();
+        if query.is_empty() {
+            return true;
+        }
+        [
+            Some(&diagnostic.message),
+            diagnostic.source.as_ref(),
+            diagnostic.code.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|text| text.to_lowercase().contains(&query))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(severity: DiagnosticSeverity, source: &str, code: &str) -> Diagnostic {
+        Diagnostic {
+            severity,
+            source: Some(source.to_string()),
+            code: Some(code.to_string()),
+            message: "unused variable `foo`".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diagnostic_filter() {
+        let mut filter = DiagnosticFilter {
+            include_warnings: true,
+            exclude_dependency_warnings: true,
+            dependency_paths: PathMatcher::new(&["**/target/**".to_string()]).unwrap(),
+            ..Default::default()
+        };
+        let error = diagnostic(DiagnosticSeverity::ERROR, "rustc", "E0425");
+        let warning = diagnostic(DiagnosticSeverity::WARNING, "clippy", "needless_return");
+        let hint = diagnostic(DiagnosticSeverity::HINT, "rust-analyzer", "inactive-code");
+
+        assert!(filter.matches(&error, false));
+        assert!(filter.matches(&warning, false));
+        assert!(!filter.matches(&hint, false));
+
+        assert!(filter.is_dependency_path(Path::new("target/debug/build/out.rs")));
+        assert!(!filter.is_dependency_path(Path::new("src/main.rs")));
+        assert!(filter.matches(&error, true));
+        assert!(!filter.matches(&warning, true));
+
+        filter.include_info = true;
+        filter.excluded_sources.insert("clippy".to_string());
+        assert!(filter.matches(&hint, false));
+        assert!(!filter.matches(&warning, false));
+
+        filter.query = "e0425".to_string();
+        assert!(filter.matches(&error, false));
+        assert!(!filter.matches(&hint, false));
+        filter.query = "FOO".to_string();
+        assert!(filter.matches(&hint, false));
+    }
+}
@@ -1,3 +1,4 @@
+mod diagnostic_filter;
 pub mod items;
 mod project_diagnostics_settings;
 mod toolbar_controls;
@@ -6,7 +7,8 @@ mod toolbar_controls;
 mod diagnostics_tests;
 
 use anyhow::Result;
-use collections::{BTreeSet, HashSet};
+use collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use diagnostic_filter::DiagnosticFilter;
 use editor::{
     diagnostic_block_renderer,
     display_map::{BlockDisposition, BlockProperties, BlockStyle, CustomBlockId, RenderBlock},
@@ -25,10 +27,12 @@ use gpui::{
     WeakView, WindowContext,
 };
 use language::{
-    Bias, Buffer, Diagnostic, DiagnosticEntry, DiagnosticSeverity, Point, Selection, SelectionGoal,
+    Bias, Buffer, Diagnostic, DiagnosticEntry, DiagnosticGroup, DiagnosticSeverity, Point,
+    Selection, SelectionGoal,
 };
 use lsp::LanguageServerId;
 use project::{DiagnosticSummary, Project, ProjectPath};
+pub use project_diagnostics_settings::DiagnosticsGroupBy;
 use project_diagnostics_settings::ProjectDiagnosticsSettings;
 use settings::Settings;
 use std::{
@@ -36,17 +40,28 @@ use std::{
     cmp::Ordering,
     mem,
     ops::Range,
+    sync::Arc,
 };
 use theme::ActiveTheme;
 pub use toolbar_controls::ToolbarControls;
-use ui::{h_flex, prelude::*, Icon, IconName, Label};
+use ui::{h_flex, prelude::*, Icon, IconButton, IconName, IconSize, Label, Tooltip};
 use util::ResultExt;
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent, ItemHandle, TabContentParams},
     ItemNavHistory, ToolbarItemLocation, Workspace,
 };
 
-actions!(diagnostics, [Deploy, ToggleWarnings]);
+actions!(
+    diagnostics,
+    [
+        Deploy,
+        ToggleWarnings,
+        ToggleInfo,
+        GroupByFile,
+        GroupByCode,
+        ApplyQuickFix
+    ]
+);
 
 pub fn init(cx: &mut AppContext) {
     ProjectDiagnosticsSettings::register(cx);
@@ -63,7 +78,9 @@ struct ProjectDiagnosticsEditor {
     excerpts: Model<MultiBuffer>,
     path_states: Vec<PathState>,
     paths_to_update: BTreeSet<(ProjectPath, LanguageServerId)>,
-    include_warnings: bool,
+    filter: DiagnosticFilter,
+    group_by: DiagnosticsGroupBy,
+    known_sources: BTreeSet<String>,
     context: u32,
     update_paths_tx: UnboundedSender<(ProjectPath, Option<LanguageServerId>)>,
     _update_excerpts_task: Task<Result<()>>,
@@ -71,10 +88,18 @@ struct ProjectDiagnosticsEditor {
 }
 
 struct PathState {
+    /// The code of the diagnostics in this state when grouping by code.
+    group_key: Option<String>,
     path: ProjectPath,
     diagnostic_groups: Vec<DiagnosticGroupState>,
 }
 
+impl PathState {
+    fn key(&self) -> (&Option<String>, &ProjectPath) {
+        (&self.group_key, &self.path)
+    }
+}
+
 struct DiagnosticGroupState {
     language_server_id: LanguageServerId,
     primary_diagnostic: DiagnosticEntry<language::Anchor>,
@@ -107,6 +132,10 @@ impl Render for ProjectDiagnosticsEditor {
             })
             .size_full()
             .on_action(cx.listener(Self::toggle_warnings))
+            .on_action(cx.listener(Self::toggle_info))
+            .on_action(cx.listener(Self::group_by_file))
+            .on_action(cx.listener(Self::group_by_code))
+            .on_action(cx.listener(Self::apply_quick_fix))
             .child(child)
     }
 }
@@ -185,6 +214,7 @@ impl ProjectDiagnosticsEditor {
         let (update_excerpts_tx, mut update_excerpts_rx) = mpsc::unbounded();
 
         let project = project_handle.read(cx);
+        let settings = ProjectDiagnosticsSettings::get_global(cx);
         let mut this = Self {
             project: project_handle.clone(),
             context,
@@ -195,7 +225,9 @@ impl ProjectDiagnosticsEditor {
             editor,
             path_states: Default::default(),
             paths_to_update: Default::default(),
-            include_warnings: ProjectDiagnosticsSettings::get_global(cx).include_warnings,
+            filter: DiagnosticFilter::new(settings),
+            group_by: settings.group_by,
+            known_sources: Default::default(),
             update_paths_tx: update_excerpts_tx,
             _update_excerpts_task: cx.spawn(move |this, mut cx| async move {
                 while let Some((path, language_server_id)) = update_excerpts_rx.next().await {
@@ -243,11 +275,175 @@ impl ProjectDiagnosticsEditor {
     }
 
     fn toggle_warnings(&mut self, _: &ToggleWarnings, cx: &mut ViewContext<Self>) {
-        self.include_warnings = !self.include_warnings;
+        self.filter.include_warnings = !self.filter.include_warnings;
+        self.enqueue_update_all_excerpts(cx);
+        cx.notify();
+    }
+
+    fn toggle_info(&mut self, _: &ToggleInfo, cx: &mut ViewContext<Self>) {
+        self.filter.include_info = !self.filter.include_info;
+        self.enqueue_update_all_excerpts(cx);
+        cx.notify();
+    }
+
+    fn toggle_source(&mut self, source: &str, cx: &mut ViewContext<Self>) {
+        if !self.filter.excluded_sources.remove(source) {
+            self.filter.excluded_sources.insert(source.to_string());
+        }
+        self.enqueue_update_all_excerpts(cx);
+        cx.notify();
+    }
+
+    fn toggle_dependency_warnings(&mut self, cx: &mut ViewContext<Self>) {
+        self.filter.exclude_dependency_warnings = !self.filter.exclude_dependency_warnings;
+        self.enqueue_update_all_excerpts(cx);
+        cx.notify();
+    }
+
+    fn set_query(&mut self, query: String, cx: &mut ViewContext<Self>) {
+        if self.filter.query != query {
+            self.filter.query = query;
+            self.enqueue_update_all_excerpts(cx);
+            cx.notify();
+        }
+    }
+
+    fn group_by_file(&mut self, _: &GroupByFile, cx: &mut ViewContext<Self>) {
+        self.set_group_by(DiagnosticsGroupBy::File, cx);
+    }
+
+    fn group_by_code(&mut self, _: &GroupByCode, cx: &mut ViewContext<Self>) {
+        self.set_group_by(DiagnosticsGroupBy::Code, cx);
+    }
+
+    /// Changes how diagnostics are grouped, rebuilding all excerpts since their
+    /// order depends on the grouping.
+    fn set_group_by(&mut self, group_by: DiagnosticsGroupBy, cx: &mut ViewContext<Self>) {
+        if self.group_by == group_by {
+            return;
+        }
+        self.group_by = group_by;
+
+        let path_states = mem::take(&mut self.path_states);
+        let blocks_to_remove = path_states
+            .iter()
+            .flat_map(|state| &state.diagnostic_groups)
+            .flat_map(|group| group.blocks.iter().copied())
+            .collect::<HashSet<_>>();
+        self.editor.update(cx, |editor, cx| {
+            editor.remove_blocks(blocks_to_remove, None, cx);
+        });
+        self.excerpts.update(cx, |excerpts, cx| excerpts.clear(cx));
         self.enqueue_update_all_excerpts(cx);
         cx.notify();
     }
 
+    fn apply_quick_fix(&mut self, _: &ApplyQuickFix, cx: &mut ViewContext<Self>) {
+        let excerpt_id = self
+            .editor
+            .read(cx)
+            .selections
+            .newest_anchor()
+            .head()
+            .excerpt_id;
+        let Some(primary_diagnostic) = self
+            .path_states
+            .iter()
+            .flat_map(|state| &state.diagnostic_groups)
+            .find(|group| group.excerpts.contains(&excerpt_id))
+            .map(|group| group.primary_diagnostic.clone())
+        else {
+            return;
+        };
+        let Some(buffer) = primary_diagnostic
+            .range
+            .start
+            .buffer_id
+            .and_then(|buffer_id| self.excerpts.read(cx).buffer(buffer_id))
+        else {
+            return;
+        };
+        self.quick_fix(buffer, primary_diagnostic.range, cx);
+    }
+
+    /// Applies the preferred quick fix for the diagnostic range, or shows the
+    /// available code actions when there is no single obvious fix.
+    fn quick_fix(
+        &mut self,
+        buffer: Model<Buffer>,
+        range: Range<language::Anchor>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let code_actions = self.project.update(cx, |project, cx| {
+            project.code_actions(&buffer, range.clone(), cx)
+        });
+        cx.spawn(|this, mut cx| async move {
+            let mut quick_fixes = code_actions
+                .await
+                .into_iter()
+                .filter(|action| {
+                    action.lsp_action.kind.as_ref().map_or(false, |kind| {
+                        kind.as_str()
+                            .starts_with(lsp::CodeActionKind::QUICKFIX.as_str())
+                    })
+                })
+                .collect::<Vec<_>>();
+            let preferred_ix = quick_fixes
+                .iter()
+                .position(|action| action.lsp_action.is_preferred == Some(true))
+                .or_else(|| (quick_fixes.len() == 1).then_some(0));
+
+            if let Some(ix) = preferred_ix {
+                let action = quick_fixes.swap_remove(ix);
+                this.update(&mut cx, |this, cx| {
+                    this.project.update(cx, |project, cx| {
+                        project.apply_code_action(buffer, action, true, cx)
+                    })
+                })?
+                .await?;
+            } else {
+                this.update(&mut cx, |this, cx| {
+                    let excerpts = this.excerpts.read(cx).snapshot(cx);
+                    let Some(position) = excerpts
+                        .excerpts()
+                        .find(|(_, excerpt_buffer, excerpt_range)| {
+                            excerpt_buffer.remote_id() == buffer.read(cx).remote_id()
+                                && excerpt_range
+                                    .context
+                                    .start
+                                    .cmp(&range.start, excerpt_buffer)
+                                    .is_le()
+                                && excerpt_range
+                                    .context
+                                    .end
+                                    .cmp(&range.start, excerpt_buffer)
+                                    .is_ge()
+                        })
+                        .and_then(|(excerpt_id, _, _)| {
+                            excerpts.anchor_in_excerpt(excerpt_id, range.start)
+                        })
+                    else {
+                        return;
+                    };
+                    this.editor.update(cx, |editor, cx| {
+                        editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                            s.select_anchor_ranges([position..position])
+                        });
+                        editor.toggle_code_actions(
+                            &editor::actions::ToggleCodeActions {
+                                deployed_from_indicator: None,
+                            },
+                            cx,
+                        );
+                    });
+                    cx.focus_view(&this.editor);
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn focus_in(&mut self, cx: &mut ViewContext<Self>) {
         if self.focus_handle.is_focused(cx) && !self.path_states.is_empty() {
             self.editor.focus_handle(cx).focus(cx)
@@ -301,17 +497,92 @@ impl ProjectDiagnosticsEditor {
                 || server_to_update.map_or(false, |to_update| *server_id != to_update)
         });
 
-        let was_empty = self.path_states.is_empty();
         let snapshot = buffer.read(cx).snapshot();
+        let is_dependency = self.is_dependency(&path_to_update, cx);
+        let mut groups_by_key = BTreeMap::<Option<String>, Vec<_>>::default();
+        for state in &self.path_states {
+            if state.path == path_to_update {
+                groups_by_key.entry(state.group_key.clone()).or_default();
+            }
+        }
+        for (language_server_id, group) in snapshot.diagnostic_groups(server_to_update) {
+            let primary = &group.entries[group.primary_ix].diagnostic;
+            if let Some(source) = &primary.source {
+                self.known_sources.insert(source.clone());
+            }
+            if !self.filter.matches(primary, is_dependency) {
+                continue;
+            }
+            let group_key = match self.group_by {
+                DiagnosticsGroupBy::File => None,
+                DiagnosticsGroupBy::Code => primary.code.clone(),
+            };
+            groups_by_key
+                .entry(group_key)
+                .or_default()
+                .push((language_server_id, group));
+        }
+
+        for (group_key, new_groups) in groups_by_key {
+            self.update_excerpts_for_group(
+                &path_to_update,
+                group_key,
+                server_to_update,
+                &buffer,
+                &snapshot,
+                new_groups,
+                cx,
+            );
+        }
+
+        if self.path_states.is_empty() {
+            if self.editor.focus_handle(cx).is_focused(cx) {
+                cx.focus(&self.focus_handle);
+            }
+        } else if self.focus_handle.is_focused(cx) {
+            let focus_handle = self.editor.focus_handle(cx);
+            cx.focus(&focus_handle);
+        }
+
+        #[cfg(test)]
+        self.check_invariants(cx);
+
+        cx.notify();
+    }
+
+    /// Returns whether the path belongs to a dependency, rather than to the
+    /// project itself.
+    fn is_dependency(&self, path: &ProjectPath, cx: &AppContext) -> bool {
+        let is_visible = self
+            .project
+            .read(cx)
+            .worktree_for_id(path.worktree_id, cx)
+            .map_or(false, |worktree| worktree.read(cx).is_visible());
+        !is_visible || self.filter.is_dependency_path(&path.path)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn update_excerpts_for_group(
+        &mut self,
+        path_to_update: &ProjectPath,
+        group_key: Option<String>,
+        server_to_update: Option<LanguageServerId>,
+        buffer: &Model<Buffer>,
+        snapshot: &language::BufferSnapshot,
+        new_groups: Vec<(LanguageServerId, DiagnosticGroup<language::Anchor>)>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let was_empty = self.path_states.is_empty();
         let path_ix = match self
             .path_states
-            .binary_search_by_key(&&path_to_update, |e| &e.path)
+            .binary_search_by(|probe| probe.key().cmp(&(&group_key, path_to_update)))
         {
             Ok(ix) => ix,
             Err(ix) => {
                 self.path_states.insert(
                     ix,
                     PathState {
+                        group_key: group_key.clone(),
                         path: path_to_update.clone(),
                         diagnostic_groups: Default::default(),
                     },
@@ -335,23 +606,13 @@ impl ProjectDiagnosticsEditor {
         let mut blocks_to_add = Vec::new();
         let mut blocks_to_remove = HashSet::default();
         let mut first_excerpt_id = None;
-        let max_severity = if self.include_warnings {
-            DiagnosticSeverity::WARNING
-        } else {
-            DiagnosticSeverity::ERROR
-        };
+        let view = cx.view().downgrade();
         let excerpts_snapshot = self.excerpts.update(cx, |excerpts, cx| {
             let mut old_groups = mem::take(&mut path_state.diagnostic_groups)
                 .into_iter()
                 .enumerate()
                 .peekable();
-            let mut new_groups = snapshot
-                .diagnostic_groups(server_to_update)
-                .into_iter()
-                .filter(|(_, group)| {
-                    group.entries[group.primary_ix].diagnostic.severity <= max_severity
-                })
-                .peekable();
+            let mut new_groups = new_groups.into_iter().peekable();
             loop {
                 let mut to_insert = None;
                 let mut to_remove = None;
@@ -369,7 +630,7 @@ impl ProjectDiagnosticsEditor {
                     (Some((_, old_group)), Some((new_language_server_id, new_group))) => {
                         let old_primary = &old_group.primary_diagnostic;
                         let new_primary = &new_group.entries[new_group.primary_ix];
-                        match compare_diagnostics(old_primary, new_primary, &snapshot)
+                        match compare_diagnostics(old_primary, new_primary, snapshot)
                             .then_with(|| old_group.language_server_id.cmp(new_language_server_id))
                         {
                             Ordering::Less => {
@@ -402,7 +663,7 @@ impl ProjectDiagnosticsEditor {
                     let mut pending_range: Option<(Range<Point>, usize)> = None;
                     let mut is_first_excerpt_for_group = true;
                     for (ix, entry) in group.entries.iter().map(Some).chain([None]).enumerate() {
-                        let resolved_entry = entry.map(|e| e.resolve::<Point>(&snapshot));
+                        let resolved_entry = entry.map(|e| e.resolve::<Point>(snapshot));
                         if let Some((range, start_ix)) = &mut pending_range {
                             if let Some(entry) = resolved_entry.as_ref() {
                                 if entry.range.start.row <= range.end.row + 1 + self.context * 2 {
@@ -447,7 +708,14 @@ impl ProjectDiagnosticsEditor {
                                     position: header_position,
                                     height: 2,
                                     style: BlockStyle::Sticky,
-                                    render: diagnostic_header_renderer(primary),
+                                    render: diagnostic_header_renderer(
+                                        primary,
+                                        Some(QuickFixTarget {
+                                            view: view.clone(),
+                                            buffer: buffer.clone(),
+                                            range: group.entries[group.primary_ix].range.clone(),
+                                        }),
+                                    ),
                                     disposition: BlockDisposition::Above,
                                     priority: 0,
                                 });
@@ -581,39 +849,51 @@ impl ProjectDiagnosticsEditor {
             });
             Some(())
         });
-
-        if self.path_states.is_empty() {
-            if self.editor.focus_handle(cx).is_focused(cx) {
-                cx.focus(&self.focus_handle);
-            }
-        } else if self.focus_handle.is_focused(cx) {
-            let focus_handle = self.editor.focus_handle(cx);
-            cx.focus(&focus_handle);
-        }
-
-        #[cfg(test)]
-        self.check_invariants(cx);
-
-        cx.notify();
     }
 
     #[cfg(test)]
     fn check_invariants(&self, cx: &mut ViewContext<Self>) {
+        for states in self.path_states.windows(2) {
+            if states[0].key() >= states[1].key() {
+                panic!(
+                    "path states are not sorted by key {:?}",
+                    self.path_states
+                        .iter()
+                        .map(|state| state.key())
+                        .collect::<Vec<_>>()
+                );
+            }
+        }
+
+        // When grouping by code, the excerpts of each code come before those of the
+        // next one, and are sorted by path within it.
+        let group_keys = self
+            .path_states
+            .iter()
+            .flat_map(|state| {
+                state
+                    .diagnostic_groups
+                    .iter()
+                    .flat_map(|group| group.excerpts.iter())
+                    .map(|excerpt_id| (*excerpt_id, &state.group_key))
+            })
+            .collect::<HashMap<_, _>>();
         let mut excerpts = Vec::new();
         for (id, buffer, _) in self.excerpts.read(cx).snapshot(cx).excerpts() {
             if let Some(file) = buffer.file() {
-                excerpts.push((id, file.path().clone()));
+                let group_key = group_keys.get(&id).copied().cloned().flatten();
+                excerpts.push((id, group_key, file.path().clone()));
             }
         }
 
-        let mut prev_path = None;
-        for (_, path) in &excerpts {
-            if let Some(prev_path) = prev_path {
-                if path < prev_path {
-                    panic!("excerpts are not sorted by path {:?}", excerpts);
+        let mut prev_key = None;
+        for (_, group_key, path) in &excerpts {
+            if let Some(prev_key) = prev_key {
+                if (group_key, path) < prev_key {
+                    panic!("excerpts are not sorted by code and path {:?}", excerpts);
                 }
             }
-            prev_path = Some(path);
+            prev_key = Some((group_key, path));
         }
     }
 }
@@ -778,9 +1058,20 @@ impl Item for ProjectDiagnosticsEditor {
 
 const DIAGNOSTIC_HEADER: &'static str = "diagnostic header";
 
-fn diagnostic_header_renderer(diagnostic: Diagnostic) -> RenderBlock {
+/// The diagnostic that a header's quick fix button applies to.
+struct QuickFixTarget {
+    view: WeakView<ProjectDiagnosticsEditor>,
+    buffer: Model<Buffer>,
+    range: Range<language::Anchor>,
+}
+
+fn diagnostic_header_renderer(
+    diagnostic: Diagnostic,
+    quick_fix_target: Option<QuickFixTarget>,
+) -> RenderBlock {
     let (message, code_ranges) = highlight_diagnostic_message(&diagnostic, None);
     let message: SharedString = message;
+    let quick_fix_target = quick_fix_target.map(Arc::new);
     Box::new(move |cx| {
         let highlight_style: HighlightStyle = cx.theme().colors().text_accent.into();
         h_flex()
@@ -839,6 +1130,25 @@ fn diagnostic_header_renderer(diagnostic: Diagnostic) -> RenderBlock {
                                 .child(SharedString::from(source.clone()))
                                 .text_color(cx.theme().colors().text_muted),
                         )
+                    })
+                    .when_some(quick_fix_target.clone(), |stack, target| {
+                        stack.child(
+                            IconButton::new("quick-fix", IconName::Bolt)
+                                .icon_size(IconSize::Small)
+                                .icon_color(Color::Muted)
+                                .tooltip(|cx| Tooltip::for_action("Quick Fix", &ApplyQuickFix, cx))
+                                .on_click(move |_, cx| {
+                                    if let Some(view) = target.view.upgrade() {
+                                        view.update(cx, |view, cx| {
+                                            view.quick_fix(
+                                                target.buffer.clone(),
+                                                target.range.clone(),
+                                                cx,
+                                            )
+                                        });
+                                    }
+                                }),
+                        )
                     }),
             )
             .into_any_element()
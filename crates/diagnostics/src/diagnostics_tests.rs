@@ -719,6 +719,14 @@ async fn test_random_diagnostics(cx: &mut TestAppContext, mut rng: StdRng) {
         assert!(view.focus_handle.is_focused(cx));
     });
 
+    let group_by = if rng.gen_bool(0.5) {
+        DiagnosticsGroupBy::Code
+    } else {
+        DiagnosticsGroupBy::File
+    };
+    log::info!("grouping diagnostics by {group_by:?}");
+    mutated_view.update(cx, |view, cx| view.set_group_by(group_by, cx));
+
     let mut next_group_id = 0;
     let mut next_filename = 0;
     let mut language_server_ids = vec![LanguageServerId(0)];
@@ -807,7 +815,14 @@ async fn test_random_diagnostics(cx: &mut TestAppContext, mut rng: StdRng) {
 
     log::info!("constructing reference diagnostics view");
     let reference_view = window.build_view(cx, |cx| {
-        ProjectDiagnosticsEditor::new_with_context(1, project.clone(), workspace.downgrade(), cx)
+        let mut view = ProjectDiagnosticsEditor::new_with_context(
+            1,
+            project.clone(),
+            workspace.downgrade(),
+            cx,
+        );
+        view.set_group_by(group_by, cx);
+        view
     });
     cx.run_until_parked();
 
@@ -945,18 +960,22 @@ fn random_diagnostic(
         DiagnosticSeverity::ERROR
     };
     let message = format!("diagnostic group {group_id}");
+    let code = rng
+        .gen_bool(0.5)
+        .then(|| format!("E{}", rng.gen_range(0..3)));
 
     DiagnosticEntry {
         range,
         diagnostic: Diagnostic {
             source: None, // (optional) service that created the diagnostic
-            code: None,   // (optional) machine-readable code that identifies the diagnostic
+            code,         // (optional) machine-readable code that identifies the diagnostic
             severity,
             message,
             group_id,
             is_primary,
             is_disk_based: false,
             is_unnecessary: false,
+            is_deprecated: false,
             data: None,
         },
     }
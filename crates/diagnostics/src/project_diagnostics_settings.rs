@@ -7,6 +7,20 @@ use settings::{Settings, SettingsSources};
 #[derive(Deserialize, Debug)]
 pub struct ProjectDiagnosticsSettings {
     pub include_warnings: bool,
+    pub group_by: DiagnosticsGroupBy,
+    pub exclude_dependency_warnings: bool,
+    pub dependency_paths: Vec<String>,
+}
+
+/// How diagnostics are grouped in the project diagnostics view.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticsGroupBy {
+    /// Group diagnostics by the file they belong to.
+    #[default]
+    File,
+    /// Group diagnostics by their code, then by the file they belong to.
+    Code,
 }
 
 /// Diagnostics configuration.
@@ -16,6 +30,19 @@ pub struct ProjectDiagnosticsSettingsContent {
     ///
     /// Default: true
     include_warnings: Option<bool>,
+    /// How to group diagnostics by default.
+    ///
+    /// Default: file
+    group_by: Option<DiagnosticsGroupBy>,
+    /// Whether to hide warnings, and other non-error diagnostics, reported for
+    /// dependencies: files matching `dependency_paths` or outside of the project.
+    ///
+    /// Default: false
+    exclude_dependency_warnings: Option<bool>,
+    /// Globs matching the paths that contain dependencies or build output.
+    ///
+    /// Default: ["**/target/**", "**/node_modules/**", "**/vendor/**"]
+    dependency_paths: Option<Vec<String>>,
 }
 
 impl Settings for ProjectDiagnosticsSettings {
@@ -1,11 +1,14 @@
-use crate::ProjectDiagnosticsEditor;
-use gpui::{EventEmitter, ParentElement, Render, View, ViewContext, WeakView};
+use crate::{DiagnosticsGroupBy, GroupByCode, GroupByFile, ProjectDiagnosticsEditor, ToggleInfo};
+use editor::{Editor, EditorEvent};
+use gpui::{EventEmitter, ParentElement, Render, Subscription, View, ViewContext, WeakView};
 use ui::prelude::*;
-use ui::{IconButton, IconName, Tooltip};
+use ui::{ContextMenu, IconButton, IconName, PopoverMenu, Tooltip};
 use workspace::{item::ItemHandle, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView};
 
 pub struct ToolbarControls {
     editor: Option<WeakView<ProjectDiagnosticsEditor>>,
+    query_editor: View<Editor>,
+    _query_editor_subscription: Subscription,
 }
 
 impl Render for ToolbarControls {
@@ -16,7 +19,7 @@ impl Render for ToolbarControls {
 
         if let Some(editor) = self.editor() {
             let editor = editor.read(cx);
-            include_warnings = editor.include_warnings;
+            include_warnings = editor.filter.include_warnings;
             has_stale_excerpts = !editor.paths_to_update.is_empty();
             is_updating = editor.update_paths_tx.len() > 0
                 || editor
@@ -34,6 +37,17 @@ impl Render for ToolbarControls {
         };
 
         h_flex()
+            .gap_1()
+            .child(
+                h_flex()
+                    .w(rems(12.))
+                    .px_2()
+                    .py_0p5()
+                    .border_1()
+                    .border_color(cx.theme().colors().border)
+                    .rounded_md()
+                    .child(self.query_editor.clone()),
+            )
             .when(has_stale_excerpts, |div| {
                 div.child(
                     IconButton::new("update-excerpts", IconName::Update)
@@ -60,6 +74,17 @@ impl Render for ToolbarControls {
                         }
                     })),
             )
+            .when_some(self.editor(), |div, editor| {
+                div.child(
+                    PopoverMenu::new("diagnostics-filter")
+                        .trigger(
+                            IconButton::new("diagnostics-filter-button", IconName::Filter)
+                                .tooltip(|cx| Tooltip::text("Filter Diagnostics", cx)),
+                        )
+                        .anchor(gpui::AnchorCorner::TopRight)
+                        .menu(move |cx| Some(Self::build_filter_menu(editor.clone(), cx))),
+                )
+            })
     }
 }
 
@@ -69,11 +94,17 @@ impl ToolbarItemView for ToolbarControls {
     fn set_active_pane_item(
         &mut self,
         active_pane_item: Option<&dyn ItemHandle>,
-        _: &mut ViewContext<Self>,
+        cx: &mut ViewContext<Self>,
     ) -> ToolbarItemLocation {
         if let Some(pane_item) = active_pane_item.as_ref() {
             if let Some(editor) = pane_item.downcast::<ProjectDiagnosticsEditor>() {
+                let query = editor.read(cx).filter.query.clone();
                 self.editor = Some(editor.downgrade());
+                self.query_editor.update(cx, |query_editor, cx| {
+                    if query_editor.text(cx) != query {
+                        query_editor.set_text(query, cx);
+                    }
+                });
                 ToolbarItemLocation::PrimaryRight
             } else {
                 ToolbarItemLocation::Hidden
@@ -85,11 +116,123 @@ impl ToolbarItemView for ToolbarControls {
 }
 
 impl ToolbarControls {
-    pub fn new() -> Self {
-        ToolbarControls { editor: None }
+    pub fn new(cx: &mut ViewContext<Self>) -> Self {
+        let query_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Filter diagnostics…", cx);
+            editor
+        });
+        let subscription = cx.subscribe(&query_editor, |this, query_editor, event, cx| {
+            if let EditorEvent::BufferEdited = event {
+                let query = query_editor.read(cx).text(cx);
+                if let Some(editor) = this.editor() {
+                    editor.update(cx, |editor, cx| editor.set_query(query, cx));
+                }
+            }
+        });
+        ToolbarControls {
+            editor: None,
+            query_editor,
+            _query_editor_subscription: subscription,
+        }
     }
 
     fn editor(&self) -> Option<View<ProjectDiagnosticsEditor>> {
         self.editor.as_ref()?.upgrade()
     }
+
+    fn build_filter_menu(
+        editor: View<ProjectDiagnosticsEditor>,
+        cx: &mut WindowContext,
+    ) -> View<ContextMenu> {
+        let diagnostics = editor.read(cx);
+        let filter = diagnostics.filter.clone();
+        let group_by = diagnostics.group_by;
+        let known_sources = diagnostics.known_sources.clone();
+
+        ContextMenu::build(cx, move |mut menu, _| {
+            menu = menu
+                .header("Severity")
+                .toggleable_entry(
+                    "Warnings",
+                    filter.include_warnings,
+                    IconPosition::Start,
+                    None,
+                    {
+                        let editor = editor.clone();
+                        move |cx| {
+                            editor.update(cx, |editor, cx| {
+                                editor.toggle_warnings(&Default::default(), cx)
+                            })
+                        }
+                    },
+                )
+                .toggleable_entry(
+                    "Information and Hints",
+                    filter.include_info,
+                    IconPosition::Start,
+                    Some(Box::new(ToggleInfo)),
+                    {
+                        let editor = editor.clone();
+                        move |cx| {
+                            editor.update(cx, |editor, cx| editor.toggle_info(&ToggleInfo, cx))
+                        }
+                    },
+                )
+                .toggleable_entry(
+                    "Exclude Dependency Warnings",
+                    filter.exclude_dependency_warnings,
+                    IconPosition::Start,
+                    None,
+                    {
+                        let editor = editor.clone();
+                        move |cx| {
+                            editor.update(cx, |editor, cx| editor.toggle_dependency_warnings(cx))
+                        }
+                    },
+                );
+
+            if !known_sources.is_empty() {
+                menu = menu.separator().header("Sources");
+                for source in &known_sources {
+                    let editor = editor.clone();
+                    let source = source.clone();
+                    menu = menu.toggleable_entry(
+                        source.clone(),
+                        !filter.excluded_sources.contains(&source),
+                        IconPosition::Start,
+                        None,
+                        move |cx| editor.update(cx, |editor, cx| editor.toggle_source(&source, cx)),
+                    );
+                }
+            }
+
+            menu.separator()
+                .header("Group By")
+                .toggleable_entry(
+                    "File",
+                    group_by == DiagnosticsGroupBy::File,
+                    IconPosition::Start,
+                    Some(Box::new(GroupByFile)),
+                    {
+                        let editor = editor.clone();
+                        move |cx| {
+                            editor.update(cx, |editor, cx| editor.group_by_file(&GroupByFile, cx))
+                        }
+                    },
+                )
+                .toggleable_entry(
+                    "Code",
+                    group_by == DiagnosticsGroupBy::Code,
+                    IconPosition::Start,
+                    Some(Box::new(GroupByCode)),
+                    {
+                        let editor = editor.clone();
+                        move |cx| {
+                            editor.update(cx, |editor, cx| editor.group_by_code(&GroupByCode, cx))
+                        }
+                    },
+                )
+        })
+    }
 }
@@ -0,0 +1,316 @@
+//! Dictation, which records speech from the microphone and inserts its transcript
+//! at the cursor of the active editor.
+mod dictation_indicator;
+mod recorder;
+mod spoken_punctuation;
+mod transcription;
+
+use std::time::Duration;
+
+use anyhow::Result;
+use editor::Editor;
+use gpui::{
+    actions, AppContext, Context as _, Global, Model, ModelContext, Task, ViewContext, WeakView,
+};
+use recorder::{Audio, Recording};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use util::ResultExt as _;
+use workspace::Workspace;
+
+pub use dictation_indicator::DictationIndicator;
+pub use spoken_punctuation::apply_spoken_punctuation;
+
+actions!(dictation, [ToggleDictation, CancelDictation]);
+
+const DEFAULT_API_URL: &str = "https://api.openai.com/v1";
+const OPENAI_API_KEY_VAR: &str = "OPENAI_API_KEY";
+/// Recordings shorter than this are taken to be accidental, and aren't sent to be
+/// transcribed.
+const MIN_DURATION: Duration = Duration::from_millis(300);
+
+/// Settings for `dictation: toggle dictation`.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct DictationSettings {
+    /// The service that transcribes recordings.
+    ///
+    /// Default: open_ai
+    pub backend: SpeechToTextBackend,
+    /// The URL of an API compatible with OpenAI's transcription API, such as a
+    /// local Whisper server, when `backend` is `open_ai`.
+    ///
+    /// Default: null, for https://api.openai.com/v1
+    pub api_url: Option<String>,
+    /// The transcription model, when `backend` is `open_ai`.
+    ///
+    /// Default: whisper-1
+    pub model: String,
+    /// The language spoken, as an ISO-639-1 code such as `en`, or null to have
+    /// it detected.
+    ///
+    /// Default: null
+    pub language: Option<String>,
+    /// The local program that transcribes recordings, when `backend` is `command`.
+    ///
+    /// Default: null
+    pub command: Option<DictationCommand>,
+    /// Whether spoken commands such as "comma" and "new line" are replaced with
+    /// the punctuation they stand for.
+    ///
+    /// Default: true
+    pub spoken_punctuation: bool,
+}
+
+impl Default for DictationSettings {
+    fn default() -> Self {
+        Self {
+            backend: SpeechToTextBackend::default(),
+            api_url: None,
+            model: "whisper-1".to_string(),
+            language: None,
+            command: None,
+            spoken_punctuation: true,
+        }
+    }
+}
+
+impl DictationSettings {
+    pub fn api_url(&self) -> &str {
+        self.api_url.as_deref().unwrap_or(DEFAULT_API_URL)
+    }
+}
+
+impl Settings for DictationSettings {
+    const KEY: Option<&'static str> = Some("dictation");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+/// A service that transcribes speech.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SpeechToTextBackend {
+    /// An API compatible with OpenAI's transcription API. The API key is the one
+    /// of the OpenAI language model provider for the same URL, or else the
+    /// `OPENAI_API_KEY` environment variable.
+    #[default]
+    OpenAi,
+    /// A local program, such as whisper.cpp's `whisper-cli`.
+    Command,
+}
+
+/// A program that is given the path of a WAV file, and prints its transcript.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DictationCommand {
+    pub program: String,
+    /// The arguments of the program, where `{file}` is replaced with the path of
+    /// the recording. The path is passed last when no argument contains `{file}`.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DictationStatus {
+    Idle,
+    Recording,
+    Transcribing,
+}
+
+/// The state of dictation, which is shared by all windows since there's only one
+/// microphone.
+pub struct Dictation {
+    status: DictationStatus,
+    recording: Option<Recording>,
+    editor: Option<WeakView<Editor>>,
+    transcribe_task: Option<Task<()>>,
+}
+
+struct GlobalDictation(Model<Dictation>);
+
+impl Global for GlobalDictation {}
+
+pub fn init(cx: &mut AppContext) {
+    DictationSettings::register(cx);
+    let dictation = cx.new_model(|_| Dictation {
+        status: DictationStatus::Idle,
+        recording: None,
+        editor: None,
+        transcribe_task: None,
+    });
+    cx.set_global(GlobalDictation(dictation));
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(toggle_dictation);
+        workspace.register_action(|_, _: &CancelDictation, cx| {
+            if let Some(dictation) = Dictation::global(cx) {
+                dictation.update(cx, |dictation, cx| dictation.cancel(cx));
+            }
+        });
+    })
+    .detach();
+}
+
+impl Dictation {
+    pub fn global(cx: &AppContext) -> Option<Model<Self>> {
+        cx.try_global::<GlobalDictation>()
+            .map(|dictation| dictation.0.clone())
+    }
+
+    pub fn status(&self) -> DictationStatus {
+        self.status
+    }
+
+    fn start(&mut self, editor: WeakView<Editor>, cx: &mut ModelContext<Self>) -> Result<()> {
+        self.recording = Some(Recording::start()?);
+        self.editor = Some(editor);
+        self.status = DictationStatus::Recording;
+        cx.notify();
+        Ok(())
+    }
+
+    /// Stops recording, returning the recording and the editor that was active
+    /// when it started.
+    fn stop_recording(
+        &mut self,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<(Audio, Option<WeakView<Editor>>)> {
+        let recording = self.recording.take()?;
+        self.status = DictationStatus::Transcribing;
+        cx.notify();
+        Some((recording.stop(), self.editor.take()))
+    }
+
+    fn finish_transcribing(&mut self, cx: &mut ModelContext<Self>) {
+        self.status = DictationStatus::Idle;
+        cx.notify();
+    }
+
+    /// Stops recording or transcribing, discarding the recording.
+    fn cancel(&mut self, cx: &mut ModelContext<Self>) {
+        if let Some(recording) = self.recording.take() {
+            recording.stop();
+        }
+        self.editor = None;
+        self.transcribe_task = None;
+        self.status = DictationStatus::Idle;
+        cx.notify();
+    }
+}
+
+fn toggle_dictation(
+    workspace: &mut Workspace,
+    _: &ToggleDictation,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(dictation) = Dictation::global(cx) else {
+        return;
+    };
+    match dictation.read(cx).status {
+        DictationStatus::Idle => {
+            let Some(editor) = workspace
+                .active_item(cx)
+                .and_then(|item| item.act_as::<Editor>(cx))
+            else {
+                return;
+            };
+            let started =
+                dictation.update(cx, |dictation, cx| dictation.start(editor.downgrade(), cx));
+            if let Err(error) = started {
+                workspace.show_error(&error, cx);
+            }
+        }
+        DictationStatus::Recording => {
+            let Some((audio, editor)) =
+                dictation.update(cx, |dictation, cx| dictation.stop_recording(cx))
+            else {
+                return;
+            };
+            let settings = DictationSettings::get_global(cx).clone();
+            let http_client = cx.http_client();
+            let read_credentials = (settings.backend == SpeechToTextBackend::OpenAi)
+                .then(|| cx.read_credentials(settings.api_url()));
+            let transcribe_task = cx.spawn(|workspace, mut cx| async move {
+                let transcribe = async {
+                    if audio.duration() < MIN_DURATION {
+                        return Ok(String::new());
+                    }
+                    let wav = cx
+                        .background_executor()
+                        .spawn(async move { audio.to_wav() })
+                        .await?;
+                    let api_key = match read_credentials {
+                        Some(read_credentials) => {
+                            match read_credentials.await.log_err().flatten() {
+                                Some((_, api_key)) => Some(String::from_utf8(api_key)?),
+                                None => std::env::var(OPENAI_API_KEY_VAR).ok(),
+                            }
+                        }
+                        None => None,
+                    };
+                    let transcript =
+                        transcription::transcribe(wav, &settings, http_client, api_key).await?;
+                    anyhow::Ok(if settings.spoken_punctuation {
+                        apply_spoken_punctuation(&transcript)
+                    } else {
+                        transcript
+                    })
+                };
+                let result = transcribe.await;
+
+                if let Some(dictation) = cx.update(|cx| Dictation::global(cx)).ok().flatten() {
+                    dictation
+                        .update(&mut cx, |dictation, cx| dictation.finish_transcribing(cx))
+                        .ok();
+                }
+                match result {
+                    Ok(transcript) if !transcript.is_empty() => {
+                        if let Some(editor) = editor {
+                            editor
+                                .update(&mut cx, |editor, cx| {
+                                    insert_transcript(editor, &transcript, cx)
+                                })
+                                .ok();
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        log::error!("failed to transcribe dictation: {error:#}");
+                        workspace
+                            .update(&mut cx, |workspace, cx| workspace.show_error(&error, cx))
+                            .ok();
+                    }
+                }
+            });
+            dictation.update(cx, |dictation, _| {
+                dictation.transcribe_task = Some(transcribe_task)
+            });
+        }
+        DictationStatus::Transcribing => {}
+    }
+}
+
+/// Inserts a transcript at the cursor, separating it with a space from the word
+/// before the cursor.
+fn insert_transcript(editor: &mut Editor, transcript: &str, cx: &mut ViewContext<Editor>) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let cursor = editor.selections.newest::<usize>(cx).start;
+    let follows_word = snapshot
+        .reversed_chars_at(cursor)
+        .next()
+        .map_or(false, |c| !c.is_whitespace() && !"([{\"'`".contains(c));
+    let starts_with_punctuation = transcript
+        .chars()
+        .next()
+        .map_or(false, |c| c.is_ascii_punctuation() || c == '\n');
+    if follows_word && !starts_with_punctuation {
+        editor.insert(&format!(" {transcript}"), cx);
+    } else {
+        editor.insert(transcript, cx);
+    }
+}
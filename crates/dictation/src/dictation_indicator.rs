@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use gpui::{
+    percentage, Animation, AnimationExt, Render, Subscription, Transformation, ViewContext,
+};
+use ui::{prelude::*, ButtonLike, Tooltip};
+use workspace::{item::ItemHandle, StatusItemView};
+
+use crate::{CancelDictation, Dictation, DictationStatus, ToggleDictation};
+
+/// Shows whether speech is being recorded or transcribed for dictation.
+pub struct DictationIndicator {
+    _observe_dictation: Option<Subscription>,
+}
+
+impl DictationIndicator {
+    pub fn new(cx: &mut ViewContext<Self>) -> Self {
+        Self {
+            _observe_dictation: Dictation::global(cx)
+                .map(|dictation| cx.observe(&dictation, |_, _, cx| cx.notify())),
+        }
+    }
+}
+
+impl Render for DictationIndicator {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let status = Dictation::global(cx).map_or(DictationStatus::Idle, |dictation| {
+            dictation.read(cx).status()
+        });
+        match status {
+            DictationStatus::Idle => div().into_any_element(),
+            DictationStatus::Recording => ButtonLike::new("dictation-indicator")
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            Icon::new(IconName::Mic)
+                                .size(IconSize::Small)
+                                .color(Color::Error),
+                        )
+                        .child(Label::new("Recording…").size(LabelSize::Small)),
+                )
+                .tooltip(|cx| {
+                    Tooltip::for_action("Stop and Insert Dictation", &ToggleDictation, cx)
+                })
+                .on_click(|_, cx| cx.dispatch_action(Box::new(ToggleDictation)))
+                .into_any_element(),
+            DictationStatus::Transcribing => ButtonLike::new("dictation-indicator")
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            Icon::new(IconName::ArrowCircle)
+                                .size(IconSize::Small)
+                                .color(Color::Muted)
+                                .with_animation(
+                                    "transcribing",
+                                    Animation::new(Duration::from_secs(2)).repeat(),
+                                    |icon, delta| {
+                                        icon.transform(Transformation::rotate(percentage(delta)))
+                                    },
+                                ),
+                        )
+                        .child(Label::new("Transcribing…").size(LabelSize::Small)),
+                )
+                .tooltip(|cx| Tooltip::for_action("Cancel Dictation", &CancelDictation, cx))
+                .on_click(|_, cx| cx.dispatch_action(Box::new(CancelDictation)))
+                .into_any_element(),
+        }
+    }
+}
+
+impl StatusItemView for DictationIndicator {
+    fn set_active_pane_item(&mut self, _: Option<&dyn ItemHandle>, _: &mut ViewContext<Self>) {}
+}
@@ -0,0 +1,144 @@
+use std::{
+    io::Cursor,
+    mem,
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Context as _, Result};
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    FromSample, Sample, SizedSample,
+};
+use parking_lot::Mutex;
+
+/// Audio recorded from a microphone, mixed down to one channel.
+pub struct Audio {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+impl Audio {
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.samples.len() as f64 / self.sample_rate.max(1) as f64)
+    }
+
+    /// Encodes the audio as a WAV file of 16-bit samples, which speech-to-text
+    /// services accept.
+    pub fn to_wav(&self) -> Result<Vec<u8>> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: self.sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut bytes = Vec::new();
+        let mut writer = hound::WavWriter::new(Cursor::new(&mut bytes), spec)?;
+        for sample in &self.samples {
+            writer.write_sample((sample.clamp(-1., 1.) * i16::MAX as f32) as i16)?;
+        }
+        writer.finalize()?;
+        Ok(bytes)
+    }
+}
+
+/// Records the default input device until it's stopped.
+pub struct Recording {
+    stop_tx: mpsc::Sender<()>,
+    thread: thread::JoinHandle<()>,
+    samples: Arc<Mutex<Vec<f32>>>,
+    sample_rate: u32,
+}
+
+impl Recording {
+    pub fn start() -> Result<Self> {
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let (stop_tx, stop_rx) = mpsc::channel();
+        let (started_tx, started_rx) = mpsc::channel();
+        let thread = thread::Builder::new().name("dictation".into()).spawn({
+            let samples = samples.clone();
+            move || {
+                // Input streams can't be moved between threads on every platform,
+                // so the stream is kept on this thread until the recording stops.
+                let stream = match open_input_stream(samples) {
+                    Ok((stream, sample_rate)) => {
+                        started_tx.send(Ok(sample_rate)).ok();
+                        stream
+                    }
+                    Err(error) => {
+                        started_tx.send(Err(error)).ok();
+                        return;
+                    }
+                };
+                stop_rx.recv().ok();
+                drop(stream);
+            }
+        })?;
+        let sample_rate = started_rx
+            .recv()
+            .context("the recording thread stopped unexpectedly")??;
+        Ok(Self {
+            stop_tx,
+            thread,
+            samples,
+            sample_rate,
+        })
+    }
+
+    pub fn stop(self) -> Audio {
+        self.stop_tx.send(()).ok();
+        self.thread.join().ok();
+        let samples = mem::take(&mut *self.samples.lock());
+        Audio {
+            samples,
+            sample_rate: self.sample_rate,
+        }
+    }
+}
+
+fn open_input_stream(samples: Arc<Mutex<Vec<f32>>>) -> Result<(cpal::Stream, u32)> {
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or_else(|| anyhow!("no microphone was found"))?;
+    let config = device.default_input_config()?;
+    let sample_rate = config.sample_rate().0;
+    let sample_format = config.sample_format();
+    let config = config.into();
+    let stream = match sample_format {
+        cpal::SampleFormat::I16 => build_input_stream::<i16>(&device, &config, samples)?,
+        cpal::SampleFormat::U16 => build_input_stream::<u16>(&device, &config, samples)?,
+        cpal::SampleFormat::I32 => build_input_stream::<i32>(&device, &config, samples)?,
+        cpal::SampleFormat::F32 => build_input_stream::<f32>(&device, &config, samples)?,
+        format => bail!("the microphone's sample format {format:?} isn't supported"),
+    };
+    stream.play()?;
+    Ok((stream, sample_rate))
+}
+
+fn build_input_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    samples: Arc<Mutex<Vec<f32>>>,
+) -> Result<cpal::Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = (config.channels as usize).max(1);
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            samples.lock().extend(data.chunks(channels).map(|frame| {
+                frame
+                    .iter()
+                    .map(|&sample| f32::from_sample(sample))
+                    .sum::<f32>()
+                    / frame.len() as f32
+            }));
+        },
+        |error| log::error!("error while recording for dictation: {error}"),
+        None,
+    )?;
+    Ok(stream)
+}
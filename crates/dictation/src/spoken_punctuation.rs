@@ -0,0 +1,84 @@
+/// Spoken commands and the text they're replaced with. Longer commands come first,
+/// so that they're matched before the commands they start with.
+const COMMANDS: &[(&str, &str)] = &[
+    ("new paragraph", "\n\n"),
+    ("new line", "\n"),
+    ("question mark", "?"),
+    ("exclamation mark", "!"),
+    ("exclamation point", "!"),
+    ("full stop", "."),
+    ("period", "."),
+    ("comma", ","),
+    ("semicolon", ";"),
+    ("colon", ":"),
+];
+
+/// Replaces spoken punctuation commands in a transcript, such as "comma" or "new
+/// line", with the punctuation they stand for, capitalizing the words that start
+/// the sentences they end.
+pub fn apply_spoken_punctuation(transcript: &str) -> String {
+    let words = transcript.split_whitespace().collect::<Vec<_>>();
+    let mut output = String::new();
+    let mut capitalize_next = false;
+    let mut ix = 0;
+    'words: while ix < words.len() {
+        for (command, replacement) in COMMANDS {
+            let len = command.split(' ').count();
+            let Some(spoken) = words.get(ix..ix + len) else {
+                continue;
+            };
+            // Recognizers often punctuate the commands themselves, as in "comma,".
+            let spoken = spoken
+                .iter()
+                .map(|word| word.trim_end_matches(is_punctuation).to_lowercase())
+                .collect::<Vec<_>>()
+                .join(" ");
+            if spoken == *command {
+                output.truncate(output.trim_end_matches(' ').len());
+                output.push_str(replacement);
+                capitalize_next = matches!(*replacement, "." | "?" | "!" | "\n" | "\n\n");
+                ix += len;
+                continue 'words;
+            }
+        }
+
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push(' ');
+        }
+        let mut chars = words[ix].chars();
+        if capitalize_next {
+            output.extend(chars.next().into_iter().flat_map(char::to_uppercase));
+            capitalize_next = false;
+        }
+        output.extend(chars);
+        ix += 1;
+    }
+    output
+}
+
+fn is_punctuation(c: char) -> bool {
+    matches!(c, '.' | ',' | '?' | '!' | ';' | ':')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_spoken_punctuation() {
+        assert_eq!(
+            apply_spoken_punctuation("hello comma world period how are you question mark"),
+            "hello, world. How are you?"
+        );
+        assert_eq!(
+            apply_spoken_punctuation("First item new line second item new paragraph done"),
+            "First item\nSecond item\n\nDone"
+        );
+        // Punctuation added by the recognizer is replaced by the command's.
+        assert_eq!(
+            apply_spoken_punctuation("Wait comma, what exclamation point."),
+            "Wait, what!"
+        );
+        assert_eq!(apply_spoken_punctuation("  "), "");
+    }
+}
@@ -0,0 +1,156 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context as _, Result};
+use futures::AsyncReadExt as _;
+use http_client::{AsyncBody, HttpClient, Method, Request};
+use serde::Deserialize;
+use util::ResultExt as _;
+
+use crate::{DictationCommand, DictationSettings, SpeechToTextBackend};
+
+const BOUNDARY: &str = "zed-dictation-boundary";
+
+/// Transcribes speech recorded as a WAV file with the backend chosen in the
+/// settings. The API key is only sent to HTTP backends.
+pub(crate) async fn transcribe(
+    wav: Vec<u8>,
+    settings: &DictationSettings,
+    http_client: Arc<dyn HttpClient>,
+    api_key: Option<String>,
+) -> Result<String> {
+    match settings.backend {
+        SpeechToTextBackend::OpenAi => {
+            transcribe_with_http(wav, settings, http_client, api_key).await
+        }
+        SpeechToTextBackend::Command => {
+            let command = settings.command.as_ref().ok_or_else(|| {
+                anyhow!("`dictation.command` must be set for the command backend")
+            })?;
+            transcribe_with_command(wav, command).await
+        }
+    }
+}
+
+/// Sends the audio to an endpoint compatible with OpenAI's transcription API,
+/// which local servers wrapping Whisper also provide.
+async fn transcribe_with_http(
+    wav: Vec<u8>,
+    settings: &DictationSettings,
+    http_client: Arc<dyn HttpClient>,
+    api_key: Option<String>,
+) -> Result<String> {
+    let mut fields = vec![("model", settings.model.as_str())];
+    if let Some(language) = settings.language.as_deref() {
+        fields.push(("language", language));
+    }
+    let body = multipart_body(&fields, "dictation.wav", &wav);
+
+    let url = format!(
+        "{}/audio/transcriptions",
+        settings.api_url().trim_end_matches('/')
+    );
+    let mut request = Request::builder().method(Method::POST).uri(url).header(
+        "Content-Type",
+        format!("multipart/form-data; boundary={BOUNDARY}"),
+    );
+    if let Some(api_key) = api_key {
+        request = request.header("Authorization", format!("Bearer {api_key}"));
+    }
+    let request = request
+        .body(AsyncBody::from(body))
+        .map_err(|error| anyhow!("invalid request: {error}"))?;
+
+    let mut response = http_client.send(request).await?;
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+    if !response.status().is_success() {
+        bail!(
+            "the transcription service responded with {}: {}",
+            response.status(),
+            body.trim()
+        );
+    }
+
+    #[derive(Deserialize)]
+    struct Transcription {
+        text: String,
+    }
+    let transcription: Transcription =
+        serde_json::from_str(&body).context("failed to parse the transcription")?;
+    Ok(transcription.text.trim().to_string())
+}
+
+/// Runs a local speech-to-text program with the path of the audio, and reads the
+/// transcript from its output.
+async fn transcribe_with_command(wav: Vec<u8>, command: &DictationCommand) -> Result<String> {
+    let path = std::env::temp_dir().join(format!("zed-dictation-{}.wav", std::process::id()));
+    smol::fs::write(&path, wav).await?;
+    let path_arg = path.to_string_lossy().into_owned();
+    let mut args = command
+        .args
+        .iter()
+        .map(|arg| arg.replace("{file}", &path_arg))
+        .collect::<Vec<_>>();
+    if !command.args.iter().any(|arg| arg.contains("{file}")) {
+        args.push(path_arg);
+    }
+
+    let output = smol::process::Command::new(&command.program)
+        .args(&args)
+        .output()
+        .await;
+    smol::fs::remove_file(&path).await.log_err();
+    let output = output.with_context(|| format!("failed to run {}", command.program))?;
+    if !output.status.success() {
+        bail!(
+            "{} failed: {}",
+            command.program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Encodes text fields and a WAV file as `multipart/form-data`.
+fn multipart_body(fields: &[(&str, &str)], file_name: &str, file: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; \
+             filename=\"{file_name}\"\r\nContent-Type: audio/wav\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(file);
+    body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multipart_body() {
+        let body = multipart_body(&[("model", "whisper-1")], "a.wav", b"RIFF");
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            "--zed-dictation-boundary\r\n\
+             Content-Disposition: form-data; name=\"model\"\r\n\r\n\
+             whisper-1\r\n\
+             --zed-dictation-boundary\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"a.wav\"\r\n\
+             Content-Type: audio/wav\r\n\r\n\
+             RIFF\r\n\
+             --zed-dictation-boundary--\r\n"
+        );
+    }
+}
@@ -27,6 +27,13 @@ pub struct SelectToBeginningOfLine {
     pub(super) stop_at_soft_wraps: bool,
 }
 
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct SendSelectionToTerminal {
+    /// Sends the paragraph at the cursor rather than its line, when nothing is selected.
+    #[serde(default)]
+    pub paragraph: bool,
+}
+
 #[derive(PartialEq, Clone, Deserialize, Default)]
 pub struct MovePageUp {
     #[serde(default)]
@@ -82,6 +89,16 @@ pub struct ToggleComments {
     pub advance_downwards: bool,
 }
 
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct GoToNextDiagnosticFromSource {
+    pub source: String,
+}
+
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct GoToPrevDiagnosticFromSource {
+    pub source: String,
+}
+
 #[derive(PartialEq, Clone, Deserialize, Default)]
 pub struct FoldAt {
     pub buffer_row: MultiBufferRow,
@@ -141,17 +158,48 @@ pub struct ShowCompletions {
 #[derive(PartialEq, Clone, Deserialize, Default)]
 pub struct HandleInput(pub String);
 
+/// Copies an image of the selected lines, or of the buffer, to the clipboard.
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct CopySelectionAsImage {
+    #[serde(default = "default_true")]
+    pub line_numbers: bool,
+}
+
+/// Saves the buffer, or the lines of the selection, as an HTML file highlighted with
+/// the current theme.
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct ExportAsHtml {
+    #[serde(default = "default_true")]
+    pub line_numbers: bool,
+    #[serde(default = "default_true")]
+    pub wrap_lines: bool,
+}
+
+/// Highlights the lines that have the same content as other lines, or removes
+/// the highlights when they're shown.
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct HighlightDuplicateLines {
+    /// Whether lines that only differ by their whitespace are duplicates.
+    #[serde(default)]
+    pub ignore_whitespace: bool,
+}
+
 impl_actions!(
     editor,
     [
         ConfirmCodeAction,
         ConfirmCompletion,
         ComposeCompletion,
+        CopySelectionAsImage,
         ExpandExcerpts,
         ExpandExcerptsUp,
         ExpandExcerptsDown,
+        ExportAsHtml,
         FoldAt,
+        GoToNextDiagnosticFromSource,
+        GoToPrevDiagnosticFromSource,
         HandleInput,
+        HighlightDuplicateLines,
         MoveDownByLines,
         MovePageDown,
         MovePageUp,
@@ -164,6 +212,7 @@ impl_actions!(
         SelectToBeginningOfLine,
         SelectToEndOfLine,
         SelectUpByLines,
+        SendSelectionToTerminal,
         ShowCompletions,
         ToggleCodeActions,
         ToggleComments,
@@ -174,14 +223,20 @@ impl_actions!(
 gpui::actions!(
     editor,
     [
+        AcceptConflictBoth,
+        AcceptConflictOurs,
+        AcceptConflictTheirs,
         AcceptPartialCopilotSuggestion,
         AcceptInlineCompletion,
         AcceptPartialInlineCompletion,
+        AddReviewComment,
         AddSelectionAbove,
         AddSelectionBelow,
         Backspace,
         Cancel,
         CancelLanguageServerWork,
+        CompareConflict,
+        CompareWithClipboard,
         ConfirmRename,
         ContextMenuFirst,
         ContextMenuLast,
@@ -216,10 +271,13 @@ gpui::actions!(
         DuplicateLineUp,
         ExpandAllHunkDiffs,
         ExpandMacroRecursively,
+        ExportPatchBundle,
+        FilterLines,
         FindAllReferences,
         Fold,
         FoldSelectedRanges,
         Format,
+        FormatTable,
         GoToDefinition,
         GoToDefinitionSplit,
         GoToDeclaration,
@@ -228,14 +286,27 @@ gpui::actions!(
         GoToHunk,
         GoToImplementation,
         GoToImplementationSplit,
+        GoToMatchingBracket,
+        GoToNextConflict,
+        GoToNextDuplicateLines,
+        GoToNextError,
+        GoToNextOutlineItem,
+        GoToNextWarning,
+        GoToPrevConflict,
         GoToPrevDiagnostic,
+        GoToPrevDuplicateLines,
+        GoToPrevError,
         GoToPrevHunk,
+        GoToPrevOutlineItem,
+        GoToPrevWarning,
         GoToTypeDefinition,
         GoToTypeDefinitionSplit,
         HalfPageDown,
         HalfPageUp,
         Hover,
+        ImportPatchBundle,
         Indent,
+        InspectTreeSitterScopes,
         JoinLines,
         LineDown,
         LineUp,
@@ -269,6 +340,7 @@ gpui::actions!(
         PageUp,
         Paste,
         PreviousInlineCompletion,
+        PrintToPdf,
         Redo,
         RedoSelection,
         Rename,
@@ -285,6 +357,7 @@ gpui::actions!(
         SelectAllMatches,
         SelectDown,
         SelectLargerSyntaxNode,
+        SelectEnclosingBracketContents,
         SelectEnclosingSymbol,
         SelectLeft,
         SelectLine,
@@ -308,27 +381,34 @@ gpui::actions!(
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
         SplitSelectionIntoLines,
+        StageSelectedHunks,
+        SubmitReviewComment,
         SwitchSourceHeader,
         Tab,
         TabPrev,
         ToggleAutoSignatureHelp,
+        ToggleBreakpoint,
         ToggleGitBlame,
         ToggleGitBlameInline,
         ToggleSelectionMenu,
         ToggleHunkDiff,
         ToggleInlayHints,
         ToggleInlineCompletions,
+        ToggleFocusMode,
         ToggleLineNumbers,
+        ToggleProfilingHud,
         ToggleRelativeLineNumbers,
         ToggleIndentGuides,
         ToggleSoftWrap,
         ToggleTabBar,
         Transpose,
         Undo,
+        UndoSelectNext,
         UndoSelection,
         UnfoldLines,
         UniqueLinesCaseInsensitive,
         UniqueLinesCaseSensitive,
+        UnstageSelectedHunks,
     ]
 );
 
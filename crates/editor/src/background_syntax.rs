@@ -0,0 +1,45 @@
+use language::{Bias, Point};
+use ui::prelude::*;
+
+use crate::{Editor, EditorMode, ToPoint as _};
+
+/// Asks the buffers shown in the visible rows of an editor, and a screen above
+/// and below them, to find their syntax highlights in the background, if they're
+/// large enough for their highlight queries not to run on the main thread.
+///
+/// Highlights are found again whenever the buffer is reparsed, or rows outside
+/// of those last highlighted are scrolled into view. In between, the previous
+/// highlights are shown.
+pub(crate) fn refresh_background_syntax_highlights(
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+    let Some(visible_line_count) = editor.visible_line_count() else {
+        return;
+    };
+
+    let multibuffer = editor.buffer.read(cx);
+    let multibuffer_snapshot = multibuffer.snapshot(cx);
+    let visible_start = editor
+        .scroll_manager
+        .anchor()
+        .anchor
+        .to_point(&multibuffer_snapshot);
+    let screen_rows = visible_line_count.ceil() as u32 + 1;
+    let start = Point::new(visible_start.row.saturating_sub(screen_rows), 0);
+    let end = multibuffer_snapshot.clip_point(
+        Point::new(visible_start.row + 2 * screen_rows, 0),
+        Bias::Left,
+    );
+    let buffer_ranges = multibuffer
+        .range_to_buffer_ranges(start..end, cx)
+        .into_iter()
+        .filter(|(buffer, _, _)| buffer.read(cx).highlights_in_background(cx))
+        .collect::<Vec<_>>();
+    for (buffer, range, _) in buffer_ranges {
+        buffer.update(cx, |buffer, cx| buffer.highlight_in_background(range, cx));
+    }
+}
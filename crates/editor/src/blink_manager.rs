@@ -1,5 +1,5 @@
 use crate::EditorSettings;
-use gpui::ModelContext;
+use gpui::{AppContext, ModelContext};
 use settings::Settings;
 use settings::SettingsStore;
 use smol::Timer;
@@ -15,15 +15,16 @@ pub struct BlinkManager {
 }
 
 impl BlinkManager {
-    pub fn new(blink_interval: Duration, cx: &mut ModelContext<Self>) -> Self {
+    pub fn new(cx: &mut ModelContext<Self>) -> Self {
         // Make sure we blink the cursors if the setting is re-enabled
         cx.observe_global::<SettingsStore>(move |this, cx| {
+            this.blink_interval = Self::interval(cx);
             this.blink_cursors(this.blink_epoch, cx)
         })
         .detach();
 
         Self {
-            blink_interval,
+            blink_interval: Self::interval(cx),
 
             blink_epoch: 0,
             blinking_paused: false,
@@ -32,6 +33,15 @@ impl BlinkManager {
         }
     }
 
+    fn interval(cx: &AppContext) -> Duration {
+        // Keep the cursor from flickering, or pausing for so long it looks stuck.
+        Duration::from_millis(
+            EditorSettings::get_global(cx)
+                .cursor_blink_interval
+                .clamp(100, 5000),
+        )
+    }
+
     fn next_blink_epoch(&mut self) -> usize {
         self.blink_epoch += 1;
         self.blink_epoch
@@ -57,7 +67,7 @@ impl BlinkManager {
     }
 
     fn blink_cursors(&mut self, epoch: usize, cx: &mut ModelContext<Self>) {
-        if EditorSettings::get_global(cx).cursor_blink {
+        if EditorSettings::get_global(cx).cursor_blink && !cx.reduce_motion() {
             if epoch == self.blink_epoch && self.enabled && !self.blinking_paused {
                 self.visible = !self.visible;
                 cx.notify();
@@ -0,0 +1,120 @@
+use collections::HashSet;
+use gpui::AppContext;
+use language::Point;
+use multi_buffer::MultiBufferRow;
+use text::ToPoint as _;
+use ui::prelude::*;
+use util::post_inc;
+
+use crate::{display_map::DisplayRow, Anchor, Editor, Inlay, ToggleBreakpoint};
+
+impl Editor {
+    pub fn toggle_breakpoint(&mut self, _: &ToggleBreakpoint, cx: &mut ViewContext<Self>) {
+        let rows = self
+            .selections
+            .all::<Point>(cx)
+            .into_iter()
+            .map(|selection| MultiBufferRow(selection.head().row))
+            .collect::<HashSet<_>>();
+        for row in rows {
+            self.toggle_breakpoint_at_row(row, cx);
+        }
+    }
+
+    pub(crate) fn toggle_breakpoint_at_row(
+        &mut self,
+        row: MultiBufferRow,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let Some((buffer, point, _)) = self
+            .buffer
+            .read(cx)
+            .point_to_buffer_point(Point::new(row.0, 0), cx)
+        else {
+            return;
+        };
+        project
+            .read(cx)
+            .breakpoint_store()
+            .clone()
+            .update(cx, |store, cx| store.toggle(&buffer, point.row, cx));
+    }
+
+    /// The rows of the multibuffer with a breakpoint.
+    pub(crate) fn breakpoint_rows(&self, cx: &AppContext) -> Vec<MultiBufferRow> {
+        let Some(project) = self.project.as_ref() else {
+            return Vec::new();
+        };
+        let store = project.read(cx).breakpoint_store().read(cx);
+        let multibuffer = self.buffer.read(cx);
+        let snapshot = multibuffer.snapshot(cx);
+        let mut rows = Vec::new();
+        for buffer in multibuffer.all_buffers() {
+            let buffer_rows = store.rows_for_buffer(&buffer, cx);
+            if buffer_rows.is_empty() {
+                continue;
+            }
+            let buffer_snapshot = buffer.read(cx).text_snapshot();
+            for (excerpt_id, range) in multibuffer.excerpts_for_buffer(&buffer, cx) {
+                let start_row = range.context.start.to_point(&buffer_snapshot).row;
+                let end_row = range.context.end.to_point(&buffer_snapshot).row;
+                for row in buffer_rows {
+                    if (start_row..=end_row).contains(row) {
+                        let anchor = buffer_snapshot.anchor_after(Point::new(*row, 0));
+                        if let Some(anchor) = snapshot.anchor_in_excerpt(excerpt_id, anchor) {
+                            rows.push(MultiBufferRow(anchor.to_point(&snapshot).row));
+                        }
+                    }
+                }
+            }
+        }
+        rows.sort_unstable();
+        rows
+    }
+
+    pub(crate) fn render_breakpoint_indicator(
+        &self,
+        display_row: DisplayRow,
+        row: MultiBufferRow,
+        cx: &mut ViewContext<Self>,
+    ) -> IconButton {
+        IconButton::new(
+            ("breakpoint_indicator", display_row.0 as usize),
+            IconName::Indicator,
+        )
+        .shape(ui::IconButtonShape::Square)
+        .icon_size(IconSize::XSmall)
+        .icon_color(Color::Error)
+        .tooltip(|cx| ui::Tooltip::text("Remove Breakpoint", cx))
+        .on_click(cx.listener(move |editor, _, cx| {
+            editor.toggle_breakpoint_at_row(row, cx);
+        }))
+    }
+
+    /// Shows the values of variables inline, after the given positions, while
+    /// a debugger is paused.
+    pub fn set_debug_values(&mut self, values: Vec<(Anchor, String)>, cx: &mut ViewContext<Self>) {
+        let to_remove = std::mem::take(&mut self.debug_value_inlays);
+        let to_insert = values
+            .into_iter()
+            .map(|(position, text)| {
+                Inlay::debug_value(
+                    post_inc(&mut self.next_inlay_id),
+                    position,
+                    format!(" {text}"),
+                )
+            })
+            .collect::<Vec<_>>();
+        self.debug_value_inlays = to_insert.iter().map(|inlay| inlay.id).collect();
+        self.splice_inlays(to_remove, to_insert, cx);
+    }
+
+    pub fn clear_debug_values(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.debug_value_inlays.is_empty() {
+            self.set_debug_values(Vec::new(), cx);
+        }
+    }
+}
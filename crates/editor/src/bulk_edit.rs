@@ -0,0 +1,230 @@
+use std::sync::Arc;
+
+use collections::HashMap;
+use gpui::{PromptLevel, Task, ViewContext};
+use multi_buffer::ToOffset as _;
+use settings::Settings;
+use smol::future::yield_now;
+use text::{Selection, TransactionId};
+use util::ResultExt as _;
+use workspace::{notifications::NotificationId, Toast};
+
+use crate::{scroll::Autoscroll, Editor, EditorSettings};
+
+/// How many cursors are edited at once when typing with many cursors. Edits for more
+/// cursors than this are applied over several frames, so that the UI keeps responding.
+pub(crate) const BULK_EDIT_BATCH_SIZE: usize = 1000;
+
+/// An edit at many cursors that's being applied in batches.
+pub(crate) struct BulkEdit {
+    /// The edits made while the edit is applied, which are made in order once it's done.
+    pending_edits: Vec<PendingEdit>,
+    _task: Task<()>,
+}
+
+type PendingEdit = Box<dyn FnOnce(&mut Editor, &mut ViewContext<Editor>)>;
+
+struct BulkEditProgress;
+
+impl Editor {
+    /// Queues an edit while an edit at many cursors is applied, returning whether it
+    /// was queued. Edits made in between would apply to the cursors of a half-done
+    /// edit, so any action changing the buffer waits for it to be done.
+    pub(crate) fn queue_bulk_edit(
+        &mut self,
+        edit: impl FnOnce(&mut Editor, &mut ViewContext<Editor>) + 'static,
+    ) -> bool {
+        match self.bulk_edit.as_mut() {
+            Some(bulk_edit) => {
+                bulk_edit.pending_edits.push(Box::new(edit));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Makes the edits queued during an edit at many cursors. When one of them starts
+    /// another such edit, the rest are queued again until it's done.
+    fn apply_pending_edits(&mut self, pending_edits: Vec<PendingEdit>, cx: &mut ViewContext<Self>) {
+        let mut pending_edits = pending_edits.into_iter();
+        while let Some(edit) = pending_edits.next() {
+            edit(self, cx);
+            if let Some(bulk_edit) = self.bulk_edit.as_mut() {
+                bulk_edit.pending_edits.extend(pending_edits);
+                return;
+            }
+        }
+    }
+
+    /// Replaces the text of every selection with `text`, in batches. Autoindent and
+    /// bracket autoclosing are skipped, as they'd be too slow for so many cursors.
+    pub(crate) fn insert_in_batches(&mut self, text: Arc<str>, cx: &mut ViewContext<Self>) {
+        let selections = self.selections.disjoint_anchors();
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let total = selections.len();
+
+        let task = cx.spawn(|editor, mut cx| async move {
+            // Resolving where the cursors go after the edit takes a while with so many
+            // cursors, so it's done off the main thread.
+            let cursors = cx
+                .background_executor()
+                .spawn({
+                    let selections = selections.clone();
+                    async move {
+                        selections
+                            .iter()
+                            .map(|selection| {
+                                let head = selection.head().to_offset(&snapshot);
+                                let anchor = snapshot.anchor_after(head);
+                                selection.map(|_| anchor)
+                            })
+                            .collect::<Vec<_>>()
+                    }
+                })
+                .await;
+
+            let mut first_transaction: Option<TransactionId> = None;
+            for (batch_ix, batch) in selections.chunks(BULK_EDIT_BATCH_SIZE).enumerate() {
+                let result = editor.update(&mut cx, |editor, cx| {
+                    let transaction = editor.transact(cx, |editor, cx| {
+                        editor.buffer.update(cx, |buffer, cx| {
+                            buffer.edit(
+                                batch.iter().map(|selection| {
+                                    (selection.start..selection.end, text.clone())
+                                }),
+                                None,
+                                cx,
+                            )
+                        });
+                    });
+                    first_transaction = first_transaction.or(transaction);
+
+                    let completed = (batch_ix + 1) * BULK_EDIT_BATCH_SIZE;
+                    show_progress(editor, completed.min(total), total, cx);
+                });
+                if result.is_err() {
+                    return;
+                }
+                // Let the UI render, and respond to input, between batches.
+                yield_now().await;
+            }
+
+            editor
+                .update(&mut cx, |editor, cx| {
+                    if let Some(transaction) = first_transaction {
+                        // Undo the whole edit at once.
+                        editor.buffer.update(cx, |buffer, cx| {
+                            buffer.group_until_transaction(transaction, cx)
+                        });
+                    }
+                    // Selections made while the edit was applied are kept, as their anchors
+                    // moved with its edits. The ones it started from, wherever they're still
+                    // the same, become cursors after the text they were replaced with.
+                    let snapshot = editor.buffer.read(cx).snapshot(cx);
+                    let mut cursors = selections
+                        .iter()
+                        .zip(cursors)
+                        .map(|(selection, cursor)| (selection.id, (selection, cursor)))
+                        .collect::<HashMap<_, _>>();
+                    editor.change_selections(Some(Autoscroll::fit()), cx, |selections| {
+                        let new_selections = selections
+                            .disjoint_anchors()
+                            .iter()
+                            .map(|selection| match cursors.remove(&selection.id) {
+                                Some((original, cursor))
+                                    if original.start.cmp(&selection.start, &snapshot).is_eq()
+                                        && original.end.cmp(&selection.end, &snapshot).is_eq() =>
+                                {
+                                    cursor
+                                }
+                                _ => selection.clone(),
+                            })
+                            .collect::<Vec<_>>();
+                        selections.select_anchors(new_selections)
+                    });
+                    let pending_edits = editor
+                        .bulk_edit
+                        .take()
+                        .map(|bulk_edit| bulk_edit.pending_edits)
+                        .unwrap_or_default();
+                    if let Some(workspace) = editor.workspace() {
+                        workspace.update(cx, |workspace, cx| {
+                            workspace
+                                .dismiss_toast(&NotificationId::unique::<BulkEditProgress>(), cx)
+                        });
+                    }
+                    editor.apply_pending_edits(pending_edits, cx);
+                })
+                .ok();
+        });
+        self.bulk_edit = Some(BulkEdit {
+            pending_edits: Vec::new(),
+            _task: task,
+        });
+    }
+
+    /// Selects the given selections, first asking whether to go ahead when there are
+    /// more than `multi_cursor_prompt_threshold` of them, unless it's 0.
+    pub(crate) fn select_many(
+        &mut self,
+        new_selections: Vec<Selection<usize>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let threshold = EditorSettings::get_global(cx).multi_cursor_prompt_threshold;
+        if threshold == 0 || new_selections.len() <= threshold {
+            self.select_disjoint(new_selections, cx);
+            return;
+        }
+
+        let answer = cx.prompt(
+            PromptLevel::Warning,
+            &format!("Create {} cursors?", new_selections.len()),
+            Some("Editing with this many cursors can be slow."),
+            &["Create Cursors", "Cancel"],
+        );
+        cx.spawn(|editor, mut cx| async move {
+            if answer.await.ok() != Some(0) {
+                return;
+            }
+            editor
+                .update(&mut cx, |editor, cx| {
+                    editor.select_disjoint(new_selections, cx)
+                })
+                .log_err();
+        })
+        .detach();
+    }
+
+    fn select_disjoint(
+        &mut self,
+        new_selections: Vec<Selection<usize>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.unfold_ranges(
+            new_selections.iter().map(|selection| selection.range()),
+            false,
+            false,
+            cx,
+        );
+        self.change_selections(Some(Autoscroll::fit()), cx, |selections| {
+            selections.select(new_selections)
+        });
+    }
+}
+
+fn show_progress(editor: &Editor, completed: usize, total: usize, cx: &mut ViewContext<Editor>) {
+    if let Some(workspace) = editor.workspace() {
+        workspace.update(cx, |workspace, cx| {
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::unique::<BulkEditProgress>(),
+                    format!(
+                        "Editing at {total} cursors… {}%",
+                        completed * 100 / total.max(1)
+                    ),
+                ),
+                cx,
+            )
+        });
+    }
+}
@@ -0,0 +1,166 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use anyhow::Result;
+use gpui::{
+    point, px, rgb, size, Bounds, Font, Hsla, Image, ImageFormat, OffscreenCanvas, Pixels,
+    ShapedLine, TextRun, WindowContext,
+};
+
+use crate::html_export::HighlightedLine;
+
+/// The transparent space around the window.
+const MARGIN: Pixels = px(32.);
+const PADDING: Pixels = px(20.);
+const TITLE_BAR_HEIGHT: Pixels = px(40.);
+const CORNER_RADIUS: Pixels = px(10.);
+const TRAFFIC_LIGHT_DIAMETER: Pixels = px(12.);
+const TRAFFIC_LIGHT_SPACING: Pixels = px(8.);
+const TRAFFIC_LIGHT_COLORS: [u32; 3] = [0xff5f57, 0xfebc2e, 0x28c840];
+/// The space between the line numbers and the code.
+const GUTTER_PADDING: Pixels = px(16.);
+const TAB: &str = "    ";
+
+/// How an image of code is drawn.
+pub(crate) struct CodeImageStyle {
+    pub title: String,
+    pub font: Font,
+    pub font_size: Pixels,
+    pub line_height: Pixels,
+    pub background: Hsla,
+    pub foreground: Hsla,
+    pub border: Hsla,
+    pub title_color: Hsla,
+    pub line_number_color: Hsla,
+    /// The number of the first line, or `None` to leave out line numbers.
+    pub first_line_number: Option<u32>,
+}
+
+/// Renders lines of highlighted code as a PNG image of them in a window, with its
+/// title bar showing the name of the file.
+pub(crate) fn render_code_image(
+    lines: &[HighlightedLine],
+    style: &CodeImageStyle,
+    cx: &WindowContext,
+) -> Result<Image> {
+    let text_system = cx.text_system();
+    let plain_run = |len: usize, color: Hsla| TextRun {
+        len,
+        font: style.font.clone(),
+        color,
+        background_color: None,
+        underline: None,
+        strikethrough: None,
+    };
+
+    let code_lines = lines
+        .iter()
+        .map(|line| {
+            let mut text = String::new();
+            let mut runs = Vec::new();
+            for (chunk, highlight) in line {
+                let chunk = chunk.replace('\t', TAB);
+                text.push_str(&chunk);
+                runs.push(TextRun {
+                    len: chunk.len(),
+                    font: Font {
+                        weight: highlight.font_weight.unwrap_or(style.font.weight),
+                        style: highlight.font_style.unwrap_or(style.font.style),
+                        ..style.font.clone()
+                    },
+                    color: highlight.color.unwrap_or(style.foreground),
+                    background_color: None,
+                    underline: None,
+                    strikethrough: None,
+                });
+            }
+            text_system.shape_line(text.into(), style.font_size, &runs)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let line_numbers = match style.first_line_number {
+        Some(first) => (0..lines.len() as u32)
+            .map(|ix| {
+                let number = (first + ix).to_string();
+                let run = plain_run(number.len(), style.line_number_color);
+                text_system.shape_line(number.into(), style.font_size, &[run])
+            })
+            .collect::<Result<Vec<_>>>()?,
+        None => Vec::new(),
+    };
+    let title = text_system.shape_line(
+        style.title.clone().into(),
+        style.font_size,
+        &[plain_run(style.title.len(), style.title_color)],
+    )?;
+
+    let widest = |lines: &[ShapedLine]| lines.iter().map(|line| line.width).max();
+    let gutter_width = widest(&line_numbers).map_or(px(0.), |width| width + GUTTER_PADDING);
+    let traffic_lights_width = (TRAFFIC_LIGHT_DIAMETER + TRAFFIC_LIGHT_SPACING) * 3.;
+    let code_width = widest(&code_lines).unwrap_or_default();
+    let window_width =
+        (gutter_width + code_width).max(title.width + traffic_lights_width * 2.) + PADDING * 2.;
+    let window_height = TITLE_BAR_HEIGHT + style.line_height * code_lines.len() as f32 + PADDING;
+    let window = Bounds::new(point(MARGIN, MARGIN), size(window_width, window_height));
+
+    let mut canvas = OffscreenCanvas::new(
+        size(window_width + MARGIN * 2., window_height + MARGIN * 2.),
+        cx.scale_factor().max(2.),
+    );
+    canvas.fill(window, CORNER_RADIUS, style.border);
+    canvas.fill(
+        Bounds::new(
+            window.origin + point(px(1.), px(1.)),
+            size(window_width - px(2.), window_height - px(2.)),
+        ),
+        CORNER_RADIUS - px(1.),
+        style.background,
+    );
+
+    let title_bar_center = window.origin.y + TITLE_BAR_HEIGHT / 2.;
+    for (ix, color) in TRAFFIC_LIGHT_COLORS.into_iter().enumerate() {
+        let x = window.origin.x
+            + PADDING
+            + (TRAFFIC_LIGHT_DIAMETER + TRAFFIC_LIGHT_SPACING) * ix as f32;
+        canvas.fill(
+            Bounds::new(
+                point(x, title_bar_center - TRAFFIC_LIGHT_DIAMETER / 2.),
+                size(TRAFFIC_LIGHT_DIAMETER, TRAFFIC_LIGHT_DIAMETER),
+            ),
+            TRAFFIC_LIGHT_DIAMETER / 2.,
+            rgb(color).into(),
+        );
+    }
+    canvas.paint_line(
+        &title,
+        point(
+            window.origin.x + (window_width - title.width) / 2.,
+            title_bar_center - style.line_height / 2.,
+        ),
+        style.line_height,
+        text_system,
+    )?;
+
+    let code_origin = window.origin + point(PADDING, TITLE_BAR_HEIGHT);
+    for (ix, line) in code_lines.iter().enumerate() {
+        let y = code_origin.y + style.line_height * ix as f32;
+        if let Some(number) = line_numbers.get(ix) {
+            // Line numbers are aligned to the right of the gutter.
+            let x = code_origin.x + gutter_width - GUTTER_PADDING - number.width;
+            canvas.paint_line(number, point(x, y), style.line_height, text_system)?;
+        }
+        canvas.paint_line(
+            line,
+            point(code_origin.x + gutter_width, y),
+            style.line_height,
+            text_system,
+        )?;
+    }
+
+    let bytes = canvas.to_png()?;
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    Ok(Image {
+        format: ImageFormat::Png,
+        id: hasher.finish(),
+        bytes,
+    })
+}
@@ -0,0 +1,338 @@
+use std::time::Duration;
+
+use collections::{BTreeMap, HashSet};
+use futures::future::join_all;
+use gpui::{Model, Task, WeakView};
+use language::{language_settings::language_settings, Buffer, Point};
+use multi_buffer::MultiBufferRow;
+use project::CodeLens;
+use text::ToPoint as _;
+use ui::prelude::*;
+use util::ResultExt;
+
+use crate::{
+    display_map::{
+        BlockContext, BlockDisposition, BlockProperties, BlockStyle, CustomBlockId, DisplayRow,
+        RenderBlock,
+    },
+    scroll::Autoscroll,
+    DisplayPoint, Editor, EditorMode, FindAllReferences, ToggleCodeActions,
+};
+
+const CODE_LENS_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// The code lenses shown above the lines of a singleton buffer.
+///
+/// Code lenses are fetched without their command when the language server
+/// supports resolving them, and are only resolved once they are scrolled into
+/// view.
+#[derive(Default)]
+pub(crate) struct CodeLensState {
+    lenses: Vec<CodeLens>,
+    requested_resolves: HashSet<usize>,
+    blocks: Vec<CustomBlockId>,
+    fetch_task: Option<Task<()>>,
+    resolve_tasks: Vec<Task<()>>,
+}
+
+/// What clicking a code lens does, based on the name of its command.
+#[derive(Debug, PartialEq, Eq)]
+enum CodeLensCommand {
+    /// Client-side commands that show the references of the symbol.
+    ShowReferences,
+    /// Client-side commands that run or debug the code at the code lens.
+    Run,
+    /// Any other command, which is executed by the language server.
+    Server,
+}
+
+impl CodeLensCommand {
+    fn for_command(command: &str) -> Self {
+        let name = command.rsplit('.').next().unwrap_or(command);
+        match name {
+            "showReferences" | "findReferences" | "peekReferences" => Self::ShowReferences,
+            "runSingle" | "debugSingle" | "run" | "debug" => Self::Run,
+            _ => Self::Server,
+        }
+    }
+}
+
+fn code_lens_buffer(editor: &Editor, cx: &ViewContext<Editor>) -> Option<Model<Buffer>> {
+    if editor.mode != EditorMode::Full || editor.project.is_none() {
+        return None;
+    }
+    let buffer = editor.buffer.read(cx).as_singleton()?;
+    let enabled = {
+        let buffer = buffer.read(cx);
+        language_settings(buffer.language(), buffer.file(), cx).code_lens
+    };
+    enabled.then_some(buffer)
+}
+
+pub(crate) fn refresh_code_lens(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let Some(buffer) = code_lens_buffer(editor, cx) else {
+        if !editor.code_lens.lenses.is_empty() || !editor.code_lens.blocks.is_empty() {
+            editor.code_lens.lenses.clear();
+            editor.code_lens.requested_resolves.clear();
+            editor.code_lens.fetch_task = None;
+            editor.code_lens.resolve_tasks.clear();
+            update_code_lens_blocks(editor, cx);
+        }
+        return;
+    };
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    editor.code_lens.fetch_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(CODE_LENS_DEBOUNCE).await;
+        let Some(fetch) = project
+            .update(&mut cx, |project, cx| project.code_lens(&buffer, cx))
+            .ok()
+        else {
+            return;
+        };
+        let Some(mut lenses) = fetch.await.log_err() else {
+            return;
+        };
+        editor
+            .update(&mut cx, |editor, cx| {
+                let snapshot = buffer.read(cx).snapshot();
+                lenses.sort_by(|a, b| a.range.start.cmp(&b.range.start, &snapshot));
+                editor.code_lens.lenses = lenses;
+                editor.code_lens.requested_resolves.clear();
+                editor.code_lens.resolve_tasks.clear();
+                update_code_lens_blocks(editor, cx);
+                resolve_visible_code_lens(editor, cx);
+            })
+            .ok();
+    }));
+}
+
+/// Resolves the code lenses in the visible rows that have no command yet.
+pub(crate) fn resolve_visible_code_lens(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.code_lens.lenses.is_empty() {
+        return;
+    }
+    let Some(buffer) = code_lens_buffer(editor, cx) else {
+        return;
+    };
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    let Some(visible_line_count) = editor.visible_line_count() else {
+        return;
+    };
+
+    let snapshot = editor.snapshot(cx);
+    let top = snapshot.scroll_position().y.floor() as u32;
+    let bottom = top + visible_line_count.ceil() as u32 + 1;
+    let visible_rows = DisplayPoint::new(DisplayRow(top), 0)
+        .to_point(&snapshot.display_snapshot)
+        .row
+        ..=DisplayPoint::new(DisplayRow(bottom), 0)
+            .to_point(&snapshot.display_snapshot)
+            .row;
+
+    let buffer_snapshot = buffer.read(cx).snapshot();
+    let resolves = editor
+        .code_lens
+        .lenses
+        .iter()
+        .enumerate()
+        .filter(|(ix, lens)| {
+            lens.lsp_lens.command.is_none()
+                && !editor.code_lens.requested_resolves.contains(ix)
+                && visible_rows.contains(&lens.range.start.to_point(&buffer_snapshot).row)
+        })
+        .map(|(ix, lens)| (ix, lens.clone()))
+        .collect::<Vec<_>>();
+    if resolves.is_empty() {
+        return;
+    }
+
+    editor
+        .code_lens
+        .requested_resolves
+        .extend(resolves.iter().map(|(ix, _)| *ix));
+    let resolves = project.update(cx, |project, cx| {
+        resolves
+            .into_iter()
+            .map(|(ix, lens)| {
+                let resolve = project.resolve_code_lens(&buffer, lens, cx);
+                async move { (ix, resolve.await) }
+            })
+            .collect::<Vec<_>>()
+    });
+    let task = cx.spawn(|editor, mut cx| async move {
+        let resolved = join_all(resolves).await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                for (ix, lens) in resolved {
+                    if let Some((lens, slot)) =
+                        lens.log_err().zip(editor.code_lens.lenses.get_mut(ix))
+                    {
+                        *slot = lens;
+                    }
+                }
+                update_code_lens_blocks(editor, cx);
+            })
+            .ok();
+    });
+    editor.code_lens.resolve_tasks.push(task);
+}
+
+fn update_code_lens_blocks(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let old_blocks = editor.code_lens.blocks.drain(..).collect::<HashSet<_>>();
+    if !old_blocks.is_empty() {
+        editor.remove_blocks(old_blocks, None, cx);
+    }
+    let Some(buffer) = editor.buffer.read(cx).as_singleton() else {
+        return;
+    };
+
+    let buffer_snapshot = buffer.read(cx).snapshot();
+    let mut lenses_by_row = BTreeMap::<u32, Vec<CodeLens>>::new();
+    for lens in &editor.code_lens.lenses {
+        let row = lens.range.start.to_point(&buffer_snapshot).row;
+        lenses_by_row.entry(row).or_default().push(lens.clone());
+    }
+
+    let multi_buffer_snapshot = editor.buffer.read(cx).snapshot(cx);
+    let weak_editor = cx.view().downgrade();
+    let blocks = lenses_by_row
+        .into_iter()
+        .map(|(row, lenses)| {
+            let indent = multi_buffer_snapshot.indent_size_for_line(MultiBufferRow(row));
+            BlockProperties {
+                position: multi_buffer_snapshot.anchor_before(Point::new(row, indent.len)),
+                height: 1,
+                style: BlockStyle::Flex,
+                render: render_code_lens_row(weak_editor.clone(), row, lenses),
+                disposition: BlockDisposition::Above,
+                priority: 0,
+            }
+        })
+        .collect::<Vec<_>>();
+    editor.code_lens.blocks = editor.insert_blocks(blocks, None, cx);
+}
+
+fn render_code_lens_row(editor: WeakView<Editor>, row: u32, lenses: Vec<CodeLens>) -> RenderBlock {
+    Box::new(move |cx: &mut BlockContext| {
+        let mut children = Vec::new();
+        for (ix, lens) in lenses.iter().enumerate() {
+            let Some(command) = lens.lsp_lens.command.as_ref() else {
+                continue;
+            };
+            if !children.is_empty() {
+                children.push(
+                    Label::new("|")
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .into_any_element(),
+                );
+            }
+            let editor = editor.clone();
+            let lens = lens.clone();
+            children.push(
+                Button::new(("code-lens", ix), command.title.clone())
+                    .label_size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .style(ButtonStyle::Transparent)
+                    .on_click(move |_, cx| {
+                        editor
+                            .update(cx, |editor, cx| editor.run_code_lens(lens.clone(), cx))
+                            .ok();
+                    })
+                    .into_any_element(),
+            );
+        }
+
+        h_flex()
+            .id(("code-lens-row", row as usize))
+            .h(cx.line_height)
+            .pl(cx.anchor_x)
+            .gap_1()
+            .children(children)
+            .into_any_element()
+    })
+}
+
+impl Editor {
+    fn run_code_lens(&mut self, lens: CodeLens, cx: &mut ViewContext<Self>) {
+        let Some(command) = lens.lsp_lens.command.as_ref() else {
+            return;
+        };
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return;
+        };
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+
+        match CodeLensCommand::for_command(&command.command) {
+            CodeLensCommand::ShowReferences => {
+                self.select_code_lens_start(&lens, cx);
+                if let Some(task) = self.find_all_references(&FindAllReferences, cx) {
+                    task.detach_and_log_err(cx);
+                }
+            }
+            CodeLensCommand::Run => {
+                self.select_code_lens_start(&lens, cx);
+                let row = self.selections.newest_display(cx).head().row();
+                self.toggle_code_actions(
+                    &ToggleCodeActions {
+                        deployed_from_indicator: Some(row),
+                    },
+                    cx,
+                );
+            }
+            CodeLensCommand::Server => {
+                project
+                    .update(cx, |project, cx| {
+                        project.execute_code_lens(&buffer, lens.clone(), cx)
+                    })
+                    .detach_and_log_err(cx);
+            }
+        }
+    }
+
+    fn select_code_lens_start(&mut self, lens: &CodeLens, cx: &mut ViewContext<Self>) {
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return;
+        };
+        let start = lens.range.start.to_point(&buffer.read(cx).snapshot());
+        self.change_selections(Some(Autoscroll::fit()), cx, |selections| {
+            selections.select_ranges([start..start]);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_lens_command() {
+        assert_eq!(
+            CodeLensCommand::for_command("rust-analyzer.showReferences"),
+            CodeLensCommand::ShowReferences
+        );
+        assert_eq!(
+            CodeLensCommand::for_command("editor.action.showReferences"),
+            CodeLensCommand::ShowReferences
+        );
+        assert_eq!(
+            CodeLensCommand::for_command("rust-analyzer.runSingle"),
+            CodeLensCommand::Run
+        );
+        assert_eq!(
+            CodeLensCommand::for_command("rust-analyzer.debugSingle"),
+            CodeLensCommand::Run
+        );
+        assert_eq!(
+            CodeLensCommand::for_command("gopls.run_tests"),
+            CodeLensCommand::Server
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use gpui::{Context as _, ViewContext, VisualContext as _};
+use language::{Buffer, Language};
+use multi_buffer::MultiBuffer;
+use workspace::Workspace;
+
+use crate::{CompareWithClipboard, Editor, ExpandAllHunkDiffs};
+
+/// Opens the new text in a read-only editor, showing how it differs from the old
+/// text with the hunks expanded.
+pub fn open_comparison(
+    workspace: &mut Workspace,
+    title: String,
+    old_text: String,
+    new_text: String,
+    language: Option<Arc<Language>>,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let project = workspace.project().clone();
+    let buffer = cx.new_model(|cx| {
+        let mut buffer = Buffer::local(new_text, cx);
+        buffer.set_language_registry(project.read(cx).languages().clone());
+        buffer.set_language(language, cx);
+        buffer.set_diff_base(Some(old_text), cx);
+        buffer
+    });
+    let multibuffer =
+        cx.new_model(|cx| MultiBuffer::singleton(buffer.clone(), cx).with_title(title));
+    let editor = cx.new_view(|cx| {
+        let mut editor = Editor::for_multibuffer(multibuffer, Some(project), true, cx);
+        editor.set_read_only(true);
+        // The hunks are known once the diff with the old text is computed.
+        cx.subscribe(&buffer, |editor, _, event, cx| {
+            if let language::Event::DiffBaseChanged = event {
+                editor.expand_all_hunk_diffs(&ExpandAllHunkDiffs, cx);
+            }
+        })
+        .detach();
+        editor
+    });
+    workspace.add_item_to_active_pane(Box::new(editor), None, true, cx);
+}
+
+impl Editor {
+    /// Shows how the text of the editor differs from the text in the clipboard.
+    pub fn compare_with_clipboard(&mut self, _: &CompareWithClipboard, cx: &mut ViewContext<Self>) {
+        let Some(clipboard_text) = cx.read_from_clipboard().and_then(|item| item.text()) else {
+            return;
+        };
+        let Some(workspace) = self.workspace() else {
+            return;
+        };
+        let buffer = self.buffer.read(cx);
+        let text = buffer.snapshot(cx).text();
+        let language = buffer
+            .as_singleton()
+            .and_then(|buffer| buffer.read(cx).language().cloned());
+        let title = format!("Clipboard ↔ {}", self.title(cx));
+        workspace.update(cx, |workspace, cx| {
+            open_comparison(workspace, title, clipboard_text, text, language, cx);
+        });
+    }
+}
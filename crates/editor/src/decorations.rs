@@ -0,0 +1,261 @@
+use std::ops::Range;
+
+use gpui::{AppContext, HighlightStyle, Hsla};
+use language::Point;
+use multi_buffer::MultiBufferRow;
+use ui::{prelude::*, Tooltip};
+use util::post_inc;
+
+use crate::{
+    display_map::DisplayRow, hover_links::InlayHighlight, Anchor, Editor, Inlay, InlayId,
+    ToPoint as _,
+};
+
+/// How a decoration is colored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DecorationSeverity {
+    Hint,
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl DecorationSeverity {
+    fn color(self) -> Color {
+        match self {
+            Self::Hint => Color::Hint,
+            Self::Info => Color::Info,
+            Self::Success => Color::Success,
+            Self::Warning => Color::Warning,
+            Self::Error => Color::Error,
+        }
+    }
+
+    fn background_color(self, cx: &AppContext) -> Hsla {
+        let status = cx.theme().status();
+        match self {
+            Self::Hint => status.hint_background,
+            Self::Info => status.info_background,
+            Self::Success => status.success_background,
+            Self::Warning => status.warning_background,
+            Self::Error => status.error_background,
+        }
+    }
+}
+
+/// Something shown in an editor on behalf of someone other than the editor,
+/// such as an extension, which moves with the text around it as it's edited.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Decoration {
+    /// Highlights the background of a range.
+    Highlight {
+        range: Range<Anchor>,
+        severity: DecorationSeverity,
+    },
+    /// Shows text after a position, like an inlay hint.
+    Inlay {
+        position: Anchor,
+        text: String,
+        severity: DecorationSeverity,
+    },
+    /// Shows an icon in the gutter, next to the row of a position.
+    GutterIcon {
+        position: Anchor,
+        severity: DecorationSeverity,
+        tooltip: Option<SharedString>,
+    },
+}
+
+#[derive(Default)]
+pub(crate) struct DecorationSet {
+    decorations: Vec<Decoration>,
+    inlay_ids: Vec<InlayId>,
+}
+
+enum HintDecoration {}
+enum InfoDecoration {}
+enum SuccessDecoration {}
+enum WarningDecoration {}
+enum ErrorDecoration {}
+
+impl Editor {
+    /// Replaces the decorations set under the given key.
+    pub fn set_decorations(
+        &mut self,
+        key: impl Into<SharedString>,
+        decorations: Vec<Decoration>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let key = key.into();
+        let to_remove = self
+            .decorations
+            .remove(&key)
+            .map(|set| set.inlay_ids)
+            .unwrap_or_default();
+        let to_insert = decorations
+            .iter()
+            .filter_map(|decoration| match decoration {
+                Decoration::Inlay { position, text, .. } => Some(Inlay::decoration(
+                    post_inc(&mut self.next_inlay_id),
+                    *position,
+                    format!(" {text}"),
+                )),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if !decorations.is_empty() {
+            self.decorations.insert(
+                key,
+                DecorationSet {
+                    decorations,
+                    inlay_ids: to_insert.iter().map(|inlay| inlay.id).collect(),
+                },
+            );
+        }
+        if !to_remove.is_empty() || !to_insert.is_empty() {
+            self.splice_inlays(to_remove, to_insert, cx);
+        }
+        self.refresh_decoration_highlights(cx);
+    }
+
+    /// Removes the decorations set under the given key.
+    pub fn clear_decorations(&mut self, key: &str, cx: &mut ViewContext<Self>) {
+        if self.decorations.contains_key(key) {
+            self.set_decorations(SharedString::from(key.to_string()), Vec::new(), cx);
+        }
+    }
+
+    /// The keys decorations are set under.
+    pub fn decoration_keys(&self) -> impl Iterator<Item = &SharedString> {
+        self.decorations.keys()
+    }
+
+    fn refresh_decoration_highlights(&mut self, cx: &mut ViewContext<Self>) {
+        self.set_decoration_highlights::<HintDecoration>(DecorationSeverity::Hint, cx);
+        self.set_decoration_highlights::<InfoDecoration>(DecorationSeverity::Info, cx);
+        self.set_decoration_highlights::<SuccessDecoration>(DecorationSeverity::Success, cx);
+        self.set_decoration_highlights::<WarningDecoration>(DecorationSeverity::Warning, cx);
+        self.set_decoration_highlights::<ErrorDecoration>(DecorationSeverity::Error, cx);
+    }
+
+    fn set_decoration_highlights<T: 'static>(
+        &mut self,
+        severity: DecorationSeverity,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let mut ranges = Vec::new();
+        let mut inlay_highlights = Vec::new();
+        for set in self.decorations.values() {
+            let mut inlay_ids = set.inlay_ids.iter();
+            for decoration in &set.decorations {
+                match decoration {
+                    Decoration::Highlight {
+                        range,
+                        severity: decoration_severity,
+                    } => {
+                        if *decoration_severity == severity {
+                            ranges.push(range.clone());
+                        }
+                    }
+                    Decoration::Inlay {
+                        position,
+                        text,
+                        severity: decoration_severity,
+                    } => {
+                        let inlay = inlay_ids.next().copied();
+                        if let Some(inlay) = inlay.filter(|_| *decoration_severity == severity) {
+                            inlay_highlights.push(InlayHighlight {
+                                inlay,
+                                inlay_position: *position,
+                                range: 0..text.len() + 1,
+                            });
+                        }
+                    }
+                    Decoration::GutterIcon { .. } => {}
+                }
+            }
+        }
+
+        self.clear_highlights::<T>(cx);
+        if !ranges.is_empty() {
+            let snapshot = self.buffer.read(cx).snapshot(cx);
+            ranges.sort_by(|a, b| a.start.cmp(&b.start, &snapshot));
+            let style = HighlightStyle {
+                background_color: Some(severity.background_color(cx)),
+                ..Default::default()
+            };
+            self.highlight_text::<T>(ranges, style, cx);
+        }
+        if !inlay_highlights.is_empty() {
+            let style = HighlightStyle {
+                color: Some(severity.color().color(cx)),
+                ..Default::default()
+            };
+            self.highlight_inlays::<T>(inlay_highlights, style, cx);
+        }
+    }
+
+    /// The rows of the multibuffer with decoration gutter icons, with the most
+    /// severe severity of each row's icons and their tooltips.
+    pub(crate) fn decoration_gutter_icons(
+        &self,
+        cx: &AppContext,
+    ) -> Vec<(MultiBufferRow, DecorationSeverity, Vec<SharedString>)> {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut icons = self
+            .decorations
+            .values()
+            .flat_map(|set| &set.decorations)
+            .filter_map(|decoration| match decoration {
+                Decoration::GutterIcon {
+                    position,
+                    severity,
+                    tooltip,
+                } => {
+                    let row = MultiBufferRow(position.to_point(&snapshot).row);
+                    Some((row, *severity, tooltip.clone()))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        icons.sort_by_key(|(row, _, _)| *row);
+
+        let mut rows: Vec<(MultiBufferRow, DecorationSeverity, Vec<SharedString>)> = Vec::new();
+        for (row, severity, tooltip) in icons {
+            match rows.last_mut() {
+                Some((last_row, last_severity, tooltips)) if *last_row == row => {
+                    *last_severity = (*last_severity).max(severity);
+                    tooltips.extend(tooltip);
+                }
+                _ => rows.push((row, severity, tooltip.into_iter().collect())),
+            }
+        }
+        rows
+    }
+
+    pub(crate) fn render_decoration_indicator(
+        &self,
+        display_row: DisplayRow,
+        row: MultiBufferRow,
+        severity: DecorationSeverity,
+        tooltips: &[SharedString],
+        cx: &mut ViewContext<Self>,
+    ) -> IconButton {
+        let tooltip = SharedString::from(tooltips.join("\n"));
+        IconButton::new(
+            ("decoration_indicator", display_row.0 as usize),
+            IconName::Indicator,
+        )
+        .shape(ui::IconButtonShape::Square)
+        .icon_size(IconSize::XSmall)
+        .icon_color(severity.color())
+        .when(!tooltip.is_empty(), |button| {
+            button.tooltip(move |cx| Tooltip::text(tooltip.clone(), cx))
+        })
+        .on_click(cx.listener(move |editor, _, cx| {
+            let position = Point::new(row.0, 0);
+            editor.change_selections(None, cx, |s| s.select_ranges([position..position]));
+        }))
+    }
+}
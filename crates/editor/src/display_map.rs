@@ -37,7 +37,8 @@ pub use crease_map::*;
 pub use fold_map::{Fold, FoldId, FoldPlaceholder, FoldPoint};
 use fold_map::{FoldMap, FoldSnapshot};
 use gpui::{
-    AnyElement, Font, HighlightStyle, LineLayout, Model, ModelContext, Pixels, UnderlineStyle,
+    AnyElement, Font, HighlightStyle, LineLayout, Model, ModelContext, Pixels, StrikethroughStyle,
+    UnderlineStyle,
 };
 pub(crate) use inlay_map::Inlay;
 use inlay_map::{InlayMap, InlaySnapshot};
@@ -56,6 +57,7 @@ use std::{
     any::TypeId,
     borrow::Cow,
     fmt::Debug,
+    mem,
     num::NonZeroU32,
     ops::{Add, Range, Sub},
     sync::Arc,
@@ -392,6 +394,19 @@ impl DisplayMap {
         self.inlay_map.current_inlays()
     }
 
+    /// An estimate of the bytes used by the inlays, folds, and custom blocks in this
+    /// map.
+    pub fn estimated_memory_usage(&self) -> usize {
+        let inlays = self
+            .inlay_map
+            .current_inlays()
+            .map(|inlay| mem::size_of::<Inlay>() + inlay.text.len())
+            .sum::<usize>();
+        let folds = self.fold_map.fold_count() * mem::size_of::<Fold>();
+        let blocks = self.block_map.custom_block_count() * mem::size_of::<block_map::CustomBlock>();
+        inlays + folds + blocks
+    }
+
     pub(crate) fn splice_inlays(
         &mut self,
         to_remove: Vec<InlayId>,
@@ -692,9 +707,19 @@ impl DisplaySnapshot {
                 diagnostic_highlight.fade_out = Some(editor_style.unnecessary_code_fade);
             }
 
+            if chunk.is_deprecated && editor_style.deprecated_code_strikethrough {
+                diagnostic_highlight.strikethrough = Some(StrikethroughStyle {
+                    thickness: 1.0.into(),
+                    color: None,
+                });
+            }
+
             if let Some(severity) = chunk.diagnostic_severity {
-                // Omit underlines for HINT/INFO diagnostics on 'unnecessary' code.
-                if severity <= DiagnosticSeverity::WARNING || !chunk.is_unnecessary {
+                // Omit underlines for HINT/INFO diagnostics on 'unnecessary' or
+                // 'deprecated' code, which are styled instead.
+                if severity <= DiagnosticSeverity::WARNING
+                    || !(chunk.is_unnecessary || chunk.is_deprecated)
+                {
                     let diagnostic_color = super::diagnostic_style(severity, &editor_style.status);
                     diagnostic_highlight.underline = Some(UnderlineStyle {
                         color: Some(diagnostic_color),
@@ -949,6 +974,69 @@ impl DisplaySnapshot {
             .unwrap_or(false)
     }
 
+    /// Returns whether an outline item, like a heading, starts on the given row in a language
+    /// that folds outline items.
+    pub fn starts_outline_item(&self, buffer_row: MultiBufferRow) -> bool {
+        let Some((buffer, _)) = self.buffer_snapshot.buffer_line_for_row(buffer_row) else {
+            return false;
+        };
+        if !buffer
+            .language()
+            .map_or(false, |language| language.default_scope().outline_folding())
+        {
+            return false;
+        }
+
+        let start = Point::new(buffer_row.0, 0);
+        let end = Point::new(buffer_row.0, self.buffer_snapshot.line_len(buffer_row));
+        self.buffer_snapshot
+            .outline_items_containing(start..end, false, None)
+            .map_or(false, |items| {
+                items.iter().any(|item| {
+                    item.range.start.to_point(&self.buffer_snapshot).row == buffer_row.0
+                })
+            })
+    }
+
+    /// Returns the range folded under the outline item starting on the given row, which ends
+    /// before the next item at the same or a lower depth.
+    fn outline_item_fold_range(&self, buffer_row: MultiBufferRow) -> Option<Range<Point>> {
+        if !self.starts_outline_item(buffer_row) {
+            return None;
+        }
+
+        let outline = self.buffer_snapshot.outline(None)?;
+        let mut items = outline.items.iter().map(|item| {
+            let range = item.range.to_point(&self.buffer_snapshot);
+            (item.depth, range.start.row, range.end.row)
+        });
+        let (depth, _, item_end_row) = items.find(|(_, row, _)| *row == buffer_row.0)?;
+        // Items spanning their whole section, like those of line-based outlines, end
+        // where their section does. Others end before the next item at the same or a
+        // lower depth.
+        let mut end_row = if item_end_row > buffer_row.0 {
+            item_end_row
+        } else {
+            items
+                .find(|(item_depth, row, _)| *row > buffer_row.0 && *item_depth <= depth)
+                .map_or(self.buffer_snapshot.max_point().row, |(_, row, _)| row - 1)
+        };
+        while end_row > buffer_row.0 && self.buffer_snapshot.is_line_blank(MultiBufferRow(end_row))
+        {
+            end_row -= 1;
+        }
+        if end_row == buffer_row.0 {
+            return None;
+        }
+
+        let start = Point::new(buffer_row.0, self.buffer_snapshot.line_len(buffer_row));
+        let end = Point::new(
+            end_row,
+            self.buffer_snapshot.line_len(MultiBufferRow(end_row)),
+        );
+        Some(start..end)
+    }
+
     pub fn foldable_range(
         &self,
         buffer_row: MultiBufferRow,
@@ -962,6 +1050,11 @@ impl DisplaySnapshot {
                 crease.range.to_point(&self.buffer_snapshot),
                 crease.placeholder.clone(),
             ))
+        } else if let Some(range) = self
+            .outline_item_fold_range(buffer_row)
+            .filter(|_| !self.is_line_folded(buffer_row))
+        {
+            Some((range, self.fold_placeholder.clone()))
         } else if self.starts_indent(MultiBufferRow(start.row))
             && !self.is_line_folded(MultiBufferRow(start.row))
         {
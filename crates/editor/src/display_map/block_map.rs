@@ -341,6 +341,10 @@ impl BlockMap {
         map
     }
 
+    pub(crate) fn custom_block_count(&self) -> usize {
+        self.custom_blocks.len()
+    }
+
     pub fn read(&self, wrap_snapshot: WrapSnapshot, edits: Patch<u32>) -> BlockMapReader {
         self.sync(&wrap_snapshot, edits);
         *self.wrap_snapshot.borrow_mut() = wrap_snapshot.clone();
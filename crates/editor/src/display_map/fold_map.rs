@@ -250,6 +250,10 @@ impl FoldMap {
         (this, snapshot)
     }
 
+    pub(crate) fn fold_count(&self) -> usize {
+        self.snapshot.folds.summary().count
+    }
+
     pub fn read(
         &mut self,
         inlay_snapshot: InlaySnapshot,
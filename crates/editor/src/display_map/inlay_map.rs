@@ -69,6 +69,22 @@ impl Inlay {
             text: text.into(),
         }
     }
+
+    pub fn debug_value<T: Into<Rope>>(id: usize, position: Anchor, text: T) -> Self {
+        Self {
+            id: InlayId::DebugValue(id),
+            position,
+            text: text.into(),
+        }
+    }
+
+    pub fn decoration<T: Into<Rope>>(id: usize, position: Anchor, text: T) -> Self {
+        Self {
+            id: InlayId::Decoration(id),
+            position,
+            text: text.into(),
+        }
+    }
 }
 
 impl sum_tree::Item for Transform {
@@ -311,7 +327,9 @@ impl<'a> Iterator for InlayChunks<'a> {
 
                 let mut highlight_style = match inlay.id {
                     InlayId::Suggestion(_) => self.highlight_styles.suggestion,
-                    InlayId::Hint(_) => self.highlight_styles.inlay_hint,
+                    InlayId::Hint(_) | InlayId::DebugValue(_) | InlayId::Decoration(_) => {
+                        self.highlight_styles.inlay_hint
+                    }
                 };
                 let next_inlay_highlight_endpoint;
                 let offset_in_inlay = self.output_offset - self.transforms.start().0;
@@ -0,0 +1,252 @@
+use std::{borrow::Cow, ops::Range, time::Duration};
+
+use collections::HashMap;
+use gpui::Task;
+use language::Point;
+use multi_buffer::{Anchor, MultiBufferRow};
+use ui::prelude::*;
+
+use crate::{
+    scroll::Autoscroll, Editor, EditorMode, GoToNextDuplicateLines, GoToPrevDuplicateLines,
+    HighlightDuplicateLines,
+};
+
+const DUPLICATE_LINES_DEBOUNCE: Duration = Duration::from_millis(300);
+
+enum DuplicateLinesHighlight {}
+enum ActiveDuplicateLinesHighlight {}
+
+/// Returns the rows of the lines whose content is the same as the content of
+/// other lines, grouped by content, in the order of the first line of each group.
+///
+/// Lines without letters or digits, like blank lines and closing braces, are
+/// never duplicates.
+fn find_duplicate_lines(text: &str, ignore_whitespace: bool) -> Vec<Vec<u32>> {
+    let mut groups: Vec<Vec<u32>> = Vec::new();
+    let mut group_ixs: HashMap<Cow<str>, usize> = HashMap::default();
+    for (row, line) in text.lines().enumerate() {
+        if !line.chars().any(char::is_alphanumeric) {
+            continue;
+        }
+        let content = if ignore_whitespace {
+            Cow::Owned(line.chars().filter(|c| !c.is_whitespace()).collect())
+        } else {
+            Cow::Borrowed(line)
+        };
+        let group_ix = *group_ixs.entry(content).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[group_ix].push(row as u32);
+    }
+    groups.retain(|rows| rows.len() > 1);
+    groups
+}
+
+/// The lines of an editor that have the same content as other lines, which are
+/// found again whenever the buffer is edited.
+pub(crate) struct DuplicateLinesState {
+    ignore_whitespace: bool,
+    /// The ranges of the duplicate lines, grouped by content.
+    groups: Vec<Vec<Range<Anchor>>>,
+    /// The group the cursor was last moved to.
+    active_group: Option<usize>,
+    scan_task: Option<Task<()>>,
+}
+
+impl DuplicateLinesState {
+    pub(crate) fn group_count(&self) -> usize {
+        self.groups.len()
+    }
+}
+
+pub(crate) fn refresh_duplicate_lines(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let Some(state) = editor.duplicate_lines.as_mut() else {
+        return;
+    };
+
+    let ignore_whitespace = state.ignore_whitespace;
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    state.scan_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor()
+            .timer(DUPLICATE_LINES_DEBOUNCE)
+            .await;
+        let groups = cx
+            .background_executor()
+            .spawn(async move {
+                find_duplicate_lines(&snapshot.text(), ignore_whitespace)
+                    .into_iter()
+                    .map(|rows| {
+                        rows.into_iter()
+                            .map(|row| {
+                                let line_len = snapshot.line_len(MultiBufferRow(row));
+                                snapshot.anchor_before(Point::new(row, 0))
+                                    ..snapshot.anchor_after(Point::new(row, line_len))
+                            })
+                            .collect()
+                    })
+                    .collect()
+            })
+            .await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                set_duplicate_groups(editor, groups, cx)
+            })
+            .ok();
+    }));
+}
+
+fn set_duplicate_groups(
+    editor: &mut Editor,
+    groups: Vec<Vec<Range<Anchor>>>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let Some(state) = editor.duplicate_lines.as_mut() else {
+        return;
+    };
+    state.groups = groups;
+    // The groups may have changed, so none of them is the active one anymore.
+    state.active_group = None;
+
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let mut ranges = state.groups.concat();
+    ranges.sort_by(|a, b| a.start.cmp(&b.start, &snapshot));
+    editor.clear_row_highlights::<ActiveDuplicateLinesHighlight>();
+    editor.highlight_background::<DuplicateLinesHighlight>(
+        &ranges,
+        |colors| colors.editor_highlighted_line_background,
+        cx,
+    );
+    cx.notify();
+}
+
+impl Editor {
+    /// Highlights the lines that have the same content as other lines, or
+    /// removes the highlights when they're shown.
+    pub fn highlight_duplicate_lines(
+        &mut self,
+        action: &HighlightDuplicateLines,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if self.mode != EditorMode::Full {
+            return;
+        }
+        if let Some(state) = self.duplicate_lines.take() {
+            self.clear_background_highlights::<DuplicateLinesHighlight>(cx);
+            self.clear_row_highlights::<ActiveDuplicateLinesHighlight>();
+            cx.notify();
+            if state.ignore_whitespace == action.ignore_whitespace {
+                return;
+            }
+        }
+
+        self.duplicate_lines = Some(DuplicateLinesState {
+            ignore_whitespace: action.ignore_whitespace,
+            groups: Vec::new(),
+            active_group: None,
+            scan_task: None,
+        });
+        refresh_duplicate_lines(self, cx);
+    }
+
+    /// Moves the cursor to the next group of duplicate lines.
+    pub fn go_to_next_duplicate_lines(
+        &mut self,
+        _: &GoToNextDuplicateLines,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(state) = self.duplicate_lines.as_ref() else {
+            return;
+        };
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest_anchor().head();
+        let groups = &state.groups;
+        let next = match state.active_group {
+            Some(ix) => Some((ix + 1) % groups.len().max(1)),
+            None => groups
+                .iter()
+                .position(|group| group[0].start.cmp(&head, &snapshot).is_gt())
+                .or((!groups.is_empty()).then_some(0)),
+        };
+        if let Some(ix) = next {
+            self.activate_duplicate_group(ix, cx);
+        }
+    }
+
+    /// Moves the cursor to the previous group of duplicate lines.
+    pub fn go_to_prev_duplicate_lines(
+        &mut self,
+        _: &GoToPrevDuplicateLines,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(state) = self.duplicate_lines.as_ref() else {
+            return;
+        };
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest_anchor().head();
+        let groups = &state.groups;
+        let prev = match state.active_group {
+            Some(ix) => Some((ix + groups.len() - 1) % groups.len().max(1)),
+            None => groups
+                .iter()
+                .rposition(|group| group[0].start.cmp(&head, &snapshot).is_lt())
+                .or(groups.len().checked_sub(1)),
+        };
+        if let Some(ix) = prev {
+            self.activate_duplicate_group(ix, cx);
+        }
+    }
+
+    /// Highlights the rows of the group of duplicate lines, and moves the cursor
+    /// to the start of its first line.
+    fn activate_duplicate_group(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(state) = self.duplicate_lines.as_mut() else {
+            return;
+        };
+        let Some(group) = state.groups.get(ix).cloned() else {
+            return;
+        };
+        state.active_group = Some(ix);
+
+        self.clear_row_highlights::<ActiveDuplicateLinesHighlight>();
+        let color = cx.theme().status().warning_background;
+        for range in &group {
+            self.highlight_rows::<ActiveDuplicateLinesHighlight>(
+                range.start..=range.end,
+                Some(color),
+                false,
+                cx,
+            );
+        }
+        let position = group[0].start;
+        self.change_selections(Some(Autoscroll::center()), cx, |selections| {
+            selections.select_anchor_ranges([position..position]);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_duplicate_lines() {
+        let text = "\
+            greeting = hello\n\
+            farewell = bye\n\
+            }\n\
+            \n\
+            greeting = hello\n\
+            }\n\
+            \n\
+            farewell=bye\n\
+            greeting = hello\n";
+        assert_eq!(find_duplicate_lines(text, false), [vec![0, 4, 8]]);
+        assert_eq!(
+            find_duplicate_lines(text, true),
+            [vec![0, 4, 8], vec![1, 7]]
+        );
+        assert!(find_duplicate_lines("a\nb\r\nc\n", false).is_empty());
+        assert_eq!(find_duplicate_lines("a\r\nb\na\n", false), [vec![0, 2]]);
+    }
+}
@@ -13,18 +13,28 @@
 //!
 //! If you're looking to improve Vim mode, you should check out Vim crate that wraps Editor and overrides its behavior.
 pub mod actions;
+mod background_syntax;
 mod blame_entry_tooltip;
 mod blink_manager;
+mod breakpoints;
+mod bulk_edit;
 mod clangd_ext;
+mod code_image;
+mod code_lens;
+mod compare;
 mod debounced_delay;
+mod decorations;
 pub mod display_map;
+mod duplicate_lines;
 mod editor_settings;
 mod editor_settings_controls;
 mod element;
+mod focus_mode;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
 mod hover_popover;
+mod html_export;
 mod hunk_diff;
 mod indent_guides;
 mod inlay_hint_cache;
@@ -32,13 +42,20 @@ mod inline_completion_provider;
 pub mod items;
 mod linked_editing_ranges;
 mod lsp_ext;
+mod markdown_ext;
+mod merge_conflicts;
 mod mouse_context_menu;
 pub mod movement;
 mod persistence;
+mod profiling_hud;
+mod review;
 mod rust_analyzer_ext;
+mod scratch;
 pub mod scroll;
 mod selections_collection;
+mod semantic_rainbow;
 pub mod tasks;
+mod word_highlights;
 
 #[cfg(test)]
 mod editor_tests;
@@ -52,14 +69,22 @@ pub(crate) use actions::*;
 use aho_corasick::AhoCorasick;
 use anyhow::{anyhow, Context as _, Result};
 use blink_manager::BlinkManager;
+use bulk_edit::{BulkEdit, BULK_EDIT_BATCH_SIZE};
 use client::{Collaborator, ParticipantIndex};
 use clock::ReplicaId;
+use code_lens::CodeLensState;
 use collections::{BTreeMap, Bound, HashMap, HashSet, VecDeque};
+pub use compare::open_comparison;
 use convert_case::{Case, Casing};
 use debounced_delay::DebouncedDelay;
+use decorations::DecorationSet;
+pub use decorations::{Decoration, DecorationSeverity};
 use display_map::*;
 pub use display_map::{DisplayPoint, FoldPlaceholder};
-pub use editor_settings::{CurrentLineHighlight, EditorSettings, ScrollBeyondLastLine};
+use duplicate_lines::DuplicateLinesState;
+pub use editor_settings::{
+    CurrentLineHighlight, EditorSettings, EnclosingBracketRangeHighlight, ScrollBeyondLastLine,
+};
 pub use editor_settings_controls::*;
 use element::LineWithInvisibles;
 pub use element::{
@@ -70,14 +95,15 @@ use fuzzy::{StringMatch, StringMatchCandidate};
 use git::blame::GitBlame;
 use git::diff_hunk_to_display;
 use gpui::{
-    div, impl_actions, point, prelude::*, px, relative, size, uniform_list, Action, AnyElement,
-    AppContext, AsyncWindowContext, AvailableSpace, BackgroundExecutor, Bounds, ClipboardEntry,
-    ClipboardItem, Context, DispatchPhase, ElementId, EntityId, EventEmitter, FocusHandle,
-    FocusOutEvent, FocusableView, FontId, FontWeight, HighlightStyle, Hsla, InteractiveText,
-    KeyContext, ListSizingBehavior, Model, MouseButton, PaintQuad, ParentElement, Pixels, Render,
-    SharedString, Size, StrikethroughStyle, Styled, StyledText, Subscription, Task, TextStyle,
-    UTF16Selection, UnderlineStyle, UniformListScrollHandle, View, ViewContext, ViewInputHandler,
-    VisualContext, WeakFocusHandle, WeakView, WindowContext,
+    div, impl_actions, point, prelude::*, px, relative, size, uniform_list, AccessibilityRole,
+    AccessibleText, Action, AnyElement, AppContext, AsyncWindowContext, AvailableSpace,
+    BackgroundExecutor, Bounds, ClipboardEntry, ClipboardItem, Context, DispatchPhase, ElementId,
+    EntityId, EventEmitter, FocusHandle, FocusOutEvent, FocusableView, FontId, FontWeight,
+    HighlightStyle, Hsla, InteractiveText, KeyContext, ListSizingBehavior, Model, MouseButton,
+    PaintQuad, ParentElement, PathPromptOptions, Pixels, Render, SharedString, Size,
+    StrikethroughStyle, Styled, StyledText, Subscription, Task, TextStyle, UTF16Selection,
+    UnderlineStyle, UniformListScrollHandle, View, ViewContext, ViewInputHandler, VisualContext,
+    WeakFocusHandle, WeakView, WindowContext,
 };
 use highlight_matching_bracket::refresh_matching_bracket_highlights;
 use hover_popover::{hide_hover, HoverState};
@@ -89,13 +115,16 @@ pub use inline_completion_provider::*;
 pub use items::MAX_TAB_TITLE_LEN;
 use itertools::Itertools;
 use language::{
-    language_settings::{self, all_language_settings, InlayHintSettings},
+    language_settings::{self, all_language_settings, InlayHintSettings, SendToTerminalSettings},
     markdown, point_from_lsp, AutoindentMode, BracketPair, Buffer, Capability, CharKind, CodeLabel,
     CursorShape, Diagnostic, Documentation, IndentKind, IndentSize, Language, OffsetRangeExt,
     Point, Selection, SelectionGoal, TransactionId,
 };
-use language::{point_to_lsp, BufferRow, CharClassifier, Runnable, RunnableRange};
+use language::{point_to_lsp, BufferRow, CharClassifier, Runnable, RunnableKind, RunnableRange};
 use linked_editing_ranges::refresh_linked_ranges;
+use merge_conflicts::MergeConflictState;
+use profiling_hud::ProfilingHud;
+use review::ReviewState;
 use task::{ResolvedTask, TaskTemplate, TaskVariables};
 
 use hover_links::{find_file, HoverLink, HoveredLinkState, InlayHighlight};
@@ -115,13 +144,16 @@ use ordered_float::OrderedFloat;
 use parking_lot::{Mutex, RwLock};
 use project::project_settings::{GitGutterSetting, ProjectSettings};
 use project::{
-    CodeAction, Completion, CompletionIntent, FormatTrigger, Item, Location, Project, ProjectPath,
-    ProjectTransaction, TaskSourceKind, WorktreeId,
+    CodeAction, Completion, CompletionIntent, DirectoryLister, DocumentHighlight, FormatTrigger,
+    Item, Location, Project, ProjectPath, ProjectTransaction, TaskSourceKind, WorktreeId,
+    PATCH_BUNDLE_EXTENSION,
 };
 use rand::prelude::*;
 use rpc::{proto::*, ErrorExt};
+pub use scratch::{new_scratch_buffer, open_scratch, saved_scratches, Scratch, ScratchId};
 use scroll::{Autoscroll, OngoingScroll, ScrollAnchor, ScrollManager, ScrollbarAutoHide};
 use selections_collection::{resolve_multiple, MutableSelectionsCollection, SelectionsCollection};
+use semantic_rainbow::SemanticRainbowState;
 use serde::{Deserialize, Serialize};
 use settings::{update_settings_file, Settings, SettingsStore};
 use smallvec::SmallVec;
@@ -165,7 +197,6 @@ pub const FILE_HEADER_HEIGHT: u32 = 1;
 pub const MULTI_BUFFER_EXCERPT_HEADER_HEIGHT: u32 = 1;
 pub const MULTI_BUFFER_EXCERPT_FOOTER_HEIGHT: u32 = 1;
 pub const DEFAULT_MULTIBUFFER_CONTEXT: u32 = 2;
-const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
 const MAX_LINE_LEN: usize = 1024;
 const MIN_NAVIGATION_HISTORY_ROW_DELTA: i64 = 10;
 const MAX_SELECTION_HISTORY_LEN: usize = 1024;
@@ -245,6 +276,8 @@ pub fn render_parsed_markdown(
 pub(crate) enum InlayId {
     Suggestion(usize),
     Hint(usize),
+    DebugValue(usize),
+    Decoration(usize),
 }
 
 impl InlayId {
@@ -252,6 +285,8 @@ impl InlayId {
         match self {
             Self::Suggestion(id) => *id,
             Self::Hint(id) => *id,
+            Self::DebugValue(id) => *id,
+            Self::Decoration(id) => *id,
         }
     }
 }
@@ -299,6 +334,7 @@ pub fn init(cx: &mut AppContext) {
             workspace.register_action(Editor::new_file);
             workspace.register_action(Editor::new_file_vertical);
             workspace.register_action(Editor::new_file_horizontal);
+            workspace.register_action(Editor::import_patch_bundle);
         },
     )
     .detach();
@@ -388,6 +424,7 @@ pub struct EditorStyle {
     pub inlay_hints_style: HighlightStyle,
     pub suggestions_style: HighlightStyle,
     pub unnecessary_code_fade: f32,
+    pub deprecated_code_strikethrough: bool,
 }
 
 impl Default for EditorStyle {
@@ -405,6 +442,7 @@ impl Default for EditorStyle {
             inlay_hints_style: HighlightStyle::default(),
             suggestions_style: HighlightStyle::default(),
             unnecessary_code_fade: Default::default(),
+            deprecated_code_strikethrough: false,
         }
     }
 }
@@ -429,6 +467,7 @@ impl EditorActionId {
 
 type BackgroundHighlight = (fn(&ThemeColors) -> Hsla, Arc<[Range<Anchor>]>);
 type GutterHighlight = (fn(&AppContext) -> Hsla, Arc<[Range<Anchor>]>);
+type RenderBufferHeader = Rc<dyn Fn(&language::BufferSnapshot, &mut WindowContext) -> AnyElement>;
 
 #[derive(Default)]
 struct ScrollbarMarkerState {
@@ -447,6 +486,7 @@ impl ScrollbarMarkerState {
 #[derive(Clone, Debug)]
 struct RunnableTasks {
     templates: Vec<(TaskSourceKind, TaskTemplate)>,
+    kind: RunnableKind,
     offset: MultiBufferOffset,
     // We need the column at which the task context evaluation should take place (when we're spawning it via gutter).
     column: u32,
@@ -460,6 +500,29 @@ struct RunnableTasks {
 struct ResolvedTasks {
     templates: SmallVec<[(TaskSourceKind, ResolvedTask); 1]>,
     position: Anchor,
+    // Whether the tasks are also listed to be started in the debugger, after the ones to run.
+    debuggable: bool,
+}
+
+impl ResolvedTasks {
+    fn len(&self) -> usize {
+        if self.debuggable {
+            self.templates.len() * 2
+        } else {
+            self.templates.len()
+        }
+    }
+
+    fn get(&self, index: usize) -> Option<CodeActionsItem> {
+        if let Some((kind, task)) = self.templates.get(index) {
+            Some(CodeActionsItem::Task(kind.clone(), task.clone()))
+        } else if self.debuggable {
+            let (kind, task) = self.templates.get(index - self.templates.len())?;
+            Some(CodeActionsItem::DebugTask(kind.clone(), task.clone()))
+        } else {
+            None
+        }
+    }
 }
 #[derive(Copy, Clone, Debug)]
 struct MultiBufferOffset(usize);
@@ -517,6 +580,8 @@ pub struct Editor {
     show_runnables: Option<bool>,
     show_wrap_guides: Option<bool>,
     show_indent_guides: Option<bool>,
+    focus_mode: bool,
+    profiling_hud: Option<ProfilingHud>,
     placeholder_text: Option<Arc<str>>,
     highlight_order: usize,
     highlighted_rows: HashMap<TypeId, Vec<RowHighlight>>,
@@ -537,10 +602,21 @@ pub struct Editor {
     code_actions_task: Option<Task<()>>,
     document_highlights_task: Option<Task<()>>,
     linked_editing_range_task: Option<Task<Option<()>>>,
+    code_lens: CodeLensState,
+    semantic_rainbow: SemanticRainbowState,
+    merge_conflicts: MergeConflictState,
+    duplicate_lines: Option<DuplicateLinesState>,
+    bulk_edit: Option<BulkEdit>,
+    review: ReviewState,
     linked_edit_ranges: linked_editing_ranges::LinkedEditingRanges,
     pending_rename: Option<RenameState>,
     searchable: bool,
     cursor_shape: CursorShape,
+    /// The cursor shape from the settings, which modal editing may replace.
+    configured_cursor_shape: CursorShape,
+    /// Whether a mode, like Vim's, has set the cursor shape, so that the one
+    /// from the settings isn't applied when they change.
+    cursor_shape_overridden: bool,
     current_line_highlight: Option<CurrentLineHighlight>,
     collapse_matches: bool,
     autoindent_mode: Option<AutoindentMode>,
@@ -562,6 +638,8 @@ pub struct Editor {
     inlay_hint_cache: InlayHintCache,
     expanded_hunks: ExpandedHunks,
     next_inlay_id: usize,
+    debug_value_inlays: Vec<InlayId>,
+    decorations: BTreeMap<SharedString, DecorationSet>,
     _subscriptions: Vec<Subscription>,
     pixel_position_of_newest_cursor: Option<gpui::Point<Pixels>>,
     gutter_dimensions: GutterDimensions,
@@ -585,6 +663,7 @@ pub struct Editor {
                 + Fn(&mut Self, DisplayPoint, &mut ViewContext<Self>) -> Option<View<ui::ContextMenu>>,
         >,
     >,
+    custom_buffer_header: Option<RenderBufferHeader>,
     last_bounds: Option<Bounds<Pixels>>,
     expect_bounds_change: Option<Bounds<Pixels>>,
     tasks: BTreeMap<(BufferId, BufferRow), RunnableTasks>,
@@ -790,6 +869,18 @@ struct SelectNextState {
     query: AhoCorasick,
     wordwise: bool,
     done: bool,
+    /// The selections added for each match, most recent last, so that they
+    /// can be removed again one at a time.
+    added_selections: Vec<AddedSelection>,
+}
+
+/// A selection added for a match by `SelectNext` or `SelectPrevious`.
+#[derive(Clone, Debug)]
+struct AddedSelection {
+    id: usize,
+    /// The ID and range of the selection the match was selected instead of,
+    /// when skipping the newest match.
+    replaced: Option<(usize, Range<Anchor>)>,
 }
 
 impl std::fmt::Debug for SelectNextState {
@@ -797,6 +888,7 @@ impl std::fmt::Debug for SelectNextState {
         f.debug_struct(std::any::type_name::<Self>())
             .field("wordwise", &self.wordwise)
             .field("done", &self.done)
+            .field("added_selections", &self.added_selections)
             .finish()
     }
 }
@@ -1333,8 +1425,8 @@ struct CodeActionContents {
 impl CodeActionContents {
     fn len(&self) -> usize {
         match (&self.tasks, &self.actions) {
-            (Some(tasks), Some(actions)) => actions.len() + tasks.templates.len(),
-            (Some(tasks), None) => tasks.templates.len(),
+            (Some(tasks), Some(actions)) => actions.len() + tasks.len(),
+            (Some(tasks), None) => tasks.len(),
             (None, Some(actions)) => actions.len(),
             (None, None) => 0,
         }
@@ -1352,12 +1444,7 @@ impl CodeActionContents {
     fn iter(&self) -> impl Iterator<Item = CodeActionsItem> + '_ {
         self.tasks
             .iter()
-            .flat_map(|tasks| {
-                tasks
-                    .templates
-                    .iter()
-                    .map(|(kind, task)| CodeActionsItem::Task(kind.clone(), task.clone()))
-            })
+            .flat_map(|tasks| (0..tasks.len()).filter_map(|ix| tasks.get(ix)))
             .chain(self.actions.iter().flat_map(|actions| {
                 actions
                     .iter()
@@ -1367,24 +1454,16 @@ impl CodeActionContents {
     fn get(&self, index: usize) -> Option<CodeActionsItem> {
         match (&self.tasks, &self.actions) {
             (Some(tasks), Some(actions)) => {
-                if index < tasks.templates.len() {
-                    tasks
-                        .templates
-                        .get(index)
-                        .cloned()
-                        .map(|(kind, task)| CodeActionsItem::Task(kind, task))
+                if index < tasks.len() {
+                    tasks.get(index)
                 } else {
                     actions
-                        .get(index - tasks.templates.len())
+                        .get(index - tasks.len())
                         .cloned()
                         .map(CodeActionsItem::CodeAction)
                 }
             }
-            (Some(tasks), None) => tasks
-                .templates
-                .get(index)
-                .cloned()
-                .map(|(kind, task)| CodeActionsItem::Task(kind, task)),
+            (Some(tasks), None) => tasks.get(index),
             (None, Some(actions)) => actions.get(index).cloned().map(CodeActionsItem::CodeAction),
             (None, None) => None,
         }
@@ -1395,15 +1474,16 @@ impl CodeActionContents {
 #[derive(Clone)]
 enum CodeActionsItem {
     Task(TaskSourceKind, ResolvedTask),
+    DebugTask(TaskSourceKind, ResolvedTask),
     CodeAction(CodeAction),
 }
 
 impl CodeActionsItem {
     fn as_task(&self) -> Option<&ResolvedTask> {
-        let Self::Task(_, task) = self else {
-            return None;
-        };
-        Some(task)
+        match self {
+            Self::Task(_, task) | Self::DebugTask(_, task) => Some(task),
+            Self::CodeAction(_) => None,
+        }
     }
     fn as_code_action(&self) -> Option<&CodeAction> {
         let Self::CodeAction(action) = self else {
@@ -1415,6 +1495,7 @@ impl CodeActionsItem {
         match self {
             Self::CodeAction(action) => action.lsp_action.title.clone(),
             Self::Task(_, task) => task.resolved_label.clone(),
+            Self::DebugTask(_, task) => format!("Debug: {}", task.resolved_label),
         }
     }
 }
@@ -1519,7 +1600,7 @@ impl CodeActionsMenu {
                                 // TASK: It would be good to make lsp_action.title a SharedString to avoid allocating here.
                                 .child(SharedString::from(action.lsp_action.title.clone()))
                             })
-                            .when_some(action.as_task(), |this, task| {
+                            .when_some(action.as_task(), |this, _| {
                                 this.on_mouse_down(
                                     MouseButton::Left,
                                     cx.listener(move |editor, _, cx| {
@@ -1534,7 +1615,7 @@ impl CodeActionsMenu {
                                         }
                                     }),
                                 )
-                                .child(SharedString::from(task.resolved_label.clone()))
+                                .child(SharedString::from(action.label()))
                             })
                     })
                     .collect()
@@ -1550,10 +1631,7 @@ impl CodeActionsMenu {
             self.actions
                 .iter()
                 .enumerate()
-                .max_by_key(|(_, action)| match action {
-                    CodeActionsItem::Task(_, task) => task.resolved_label.chars().count(),
-                    CodeActionsItem::CodeAction(action) => action.lsp_action.title.chars().count(),
-                })
+                .max_by_key(|(_, action)| action.label().chars().count())
                 .map(|(ix, _)| ix),
         )
         .with_sizing_behavior(ListSizingBehavior::Infer)
@@ -1578,6 +1656,50 @@ struct ActiveDiagnosticGroup {
     is_valid: bool,
 }
 
+/// Which diagnostics to stop at when navigating between diagnostics.
+#[derive(Clone, Debug)]
+struct DiagnosticNavigationFilter {
+    severities: RangeInclusive<DiagnosticSeverity>,
+    sources: Vec<String>,
+    wrap: bool,
+}
+
+impl DiagnosticNavigationFilter {
+    fn from_settings(cx: &AppContext) -> Self {
+        let settings = &EditorSettings::get_global(cx).diagnostics_navigation;
+        Self {
+            severities: DiagnosticSeverity::ERROR..=settings.severity.max_severity(),
+            sources: settings.include_sources.clone(),
+            wrap: settings.wrap,
+        }
+    }
+
+    fn only(severity: DiagnosticSeverity, cx: &AppContext) -> Self {
+        Self {
+            severities: severity..=severity,
+            sources: Vec::new(),
+            ..Self::from_settings(cx)
+        }
+    }
+
+    fn from_source(source: &str, cx: &AppContext) -> Self {
+        Self {
+            severities: DiagnosticSeverity::ERROR..=DiagnosticSeverity::HINT,
+            sources: vec![source.to_string()],
+            ..Self::from_settings(cx)
+        }
+    }
+
+    fn matches(&self, diagnostic: &Diagnostic) -> bool {
+        self.severities.contains(&diagnostic.severity)
+            && (self.sources.is_empty()
+                || diagnostic
+                    .source
+                    .as_ref()
+                    .map_or(false, |source| self.sources.contains(source)))
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ClipboardSelection {
     pub len: usize,
@@ -1629,6 +1751,15 @@ pub(crate) struct FocusedBlock {
     focus_handle: WeakFocusHandle,
 }
 
+/// An estimate of the memory that an editor uses besides its buffers, in bytes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct EditorMemoryUsage {
+    /// The inlay hints cached for the editor's buffers.
+    pub inlay_hints: usize,
+    /// The inlays, folds, and blocks of the editor's display map.
+    pub display_map: usize,
+}
+
 impl Editor {
     pub fn single_line(cx: &mut ViewContext<Self>) -> Self {
         let buffer = cx.new_model(|cx| Buffer::local("", cx));
@@ -1770,7 +1901,7 @@ impl Editor {
 
         let selections = SelectionsCollection::new(display_map.clone(), buffer.clone());
 
-        let blink_manager = cx.new_model(|cx| BlinkManager::new(CURSOR_BLINK_INTERVAL, cx));
+        let blink_manager = cx.new_model(BlinkManager::new);
 
         let soft_wrap_mode_override = matches!(mode, EditorMode::SingleLine { .. })
             .then(|| language_settings::SoftWrap::PreferLine);
@@ -1786,6 +1917,10 @@ impl Editor {
                 project_subscriptions.push(cx.subscribe(project, |editor, _, event, cx| {
                     if let project::Event::RefreshInlayHints = event {
                         editor.refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
+                    } else if let project::Event::RefreshCodeLens
+                    | project::Event::LanguageServerAdded(_) = event
+                    {
+                        code_lens::refresh_code_lens(editor, cx);
                     } else if let project::Event::SnippetEdit(id, snippet_edits) = event {
                         if let Some(buffer) = editor.buffer.read(cx).buffer(*id) {
                             let focus_handle = editor.focus_handle(cx);
@@ -1806,6 +1941,14 @@ impl Editor {
                 project_subscriptions.push(cx.observe(&task_inventory, |editor, _, cx| {
                     editor.tasks_update_task = Some(editor.refresh_runnables(cx));
                 }));
+                let breakpoint_store = project.read(cx).breakpoint_store().clone();
+                project_subscriptions.push(cx.subscribe(&breakpoint_store, |_, _, _, cx| {
+                    cx.notify();
+                }));
+                let review_store = project.read(cx).review_store().clone();
+                project_subscriptions.push(cx.subscribe(&review_store, |editor, _, _, cx| {
+                    review::refresh_review_threads(editor, cx);
+                }));
             }
         }
 
@@ -1827,6 +1970,14 @@ impl Editor {
         } else {
             None
         };
+        let singleton_file = buffer
+            .read(cx)
+            .as_singleton()
+            .and_then(|buffer| buffer.read(cx).file().cloned());
+        let editor_settings = EditorSettings::for_file(singleton_file.as_ref(), cx);
+        let show_breadcrumbs = editor_settings.toolbar.breadcrumbs;
+        let cursor_shape = editor_settings.cursor_shape;
+        let vertical_scroll_margin = editor_settings.vertical_scroll_margin;
 
         let mut this = Self {
             focus_handle,
@@ -1853,7 +2004,7 @@ impl Editor {
             blink_manager: blink_manager.clone(),
             show_local_selections: true,
             mode,
-            show_breadcrumbs: EditorSettings::get_global(cx).toolbar.breadcrumbs,
+            show_breadcrumbs,
             show_gutter: mode == EditorMode::Full,
             show_line_numbers: None,
             use_relative_line_numbers: None,
@@ -1862,6 +2013,8 @@ impl Editor {
             show_runnables: None,
             show_wrap_guides: None,
             show_indent_guides,
+            focus_mode: false,
+            profiling_hud: None,
             placeholder_text: None,
             highlight_order: 0,
             highlighted_rows: HashMap::default(),
@@ -1879,13 +2032,23 @@ impl Editor {
             next_completion_id: 0,
             completion_documentation_pre_resolve_debounce: DebouncedDelay::new(),
             next_inlay_id: 0,
+            debug_value_inlays: Vec::new(),
+            decorations: BTreeMap::default(),
             available_code_actions: Default::default(),
             code_actions_task: Default::default(),
             document_highlights_task: Default::default(),
             linked_editing_range_task: Default::default(),
+            code_lens: CodeLensState::default(),
+            semantic_rainbow: SemanticRainbowState::default(),
+            merge_conflicts: MergeConflictState::default(),
+            duplicate_lines: None,
+            bulk_edit: None,
+            review: ReviewState::default(),
             pending_rename: Default::default(),
             searchable: true,
-            cursor_shape: Default::default(),
+            cursor_shape,
+            configured_cursor_shape: cursor_shape,
+            cursor_shape_overridden: false,
             current_line_highlight: None,
             autoindent_mode: Some(AutoindentMode::EachLine),
             collapse_matches: false,
@@ -1917,6 +2080,7 @@ impl Editor {
             show_inline_completions_override: None,
             enable_inline_completions: true,
             custom_context_menu: None,
+            custom_buffer_header: None,
             show_git_blame_gutter: false,
             show_git_blame_inline: false,
             show_selection_menu: None,
@@ -1956,8 +2120,12 @@ impl Editor {
             addons: HashMap::default(),
             _scroll_cursor_center_top_bottom_task: Task::ready(()),
         };
+        this.scroll_manager.vertical_scroll_margin = vertical_scroll_margin;
         this.tasks_update_task = Some(this.refresh_runnables(cx));
         this._subscriptions.extend(project_subscriptions);
+        code_lens::refresh_code_lens(&mut this, cx);
+        merge_conflicts::refresh_merge_conflicts(&mut this, cx);
+        review::refresh_review_threads(&mut this, cx);
 
         this.end_selection(cx);
         this.scroll_manager.show_scrollbar(cx);
@@ -2133,6 +2301,13 @@ impl Editor {
         &self.buffer
     }
 
+    /// The editor settings, as overridden by the project settings for the edited file.
+    /// Editors of several buffers use the user settings.
+    pub fn editor_settings<'a>(&self, cx: &'a AppContext) -> &'a EditorSettings {
+        let buffer = self.buffer.read(cx).as_singleton();
+        EditorSettings::for_file(buffer.and_then(|buffer| buffer.read(cx).file()), cx)
+    }
+
     pub fn workspace(&self) -> Option<View<Workspace>> {
         self.workspace.as_ref()?.0.upgrade()
     }
@@ -2157,7 +2332,7 @@ impl Editor {
             is_focused: self.focus_handle.is_focused(cx),
             current_line_highlight: self
                 .current_line_highlight
-                .unwrap_or_else(|| EditorSettings::get_global(cx).current_line_highlight),
+                .unwrap_or_else(|| self.editor_settings(cx).current_line_highlight),
             gutter_hovered: self.gutter_hovered,
         }
     }
@@ -2203,6 +2378,14 @@ impl Editor {
         self.custom_context_menu = Some(Box::new(f))
     }
 
+    /// Renders the headers of the buffers of the multibuffer, in place of their paths.
+    pub fn set_custom_buffer_header(
+        &mut self,
+        render: impl 'static + Fn(&language::BufferSnapshot, &mut WindowContext) -> AnyElement,
+    ) {
+        self.custom_buffer_header = Some(Rc::new(render));
+    }
+
     pub fn set_completion_provider(&mut self, provider: Box<dyn CompletionProvider>) {
         self.completion_provider = Some(provider);
     }
@@ -2230,6 +2413,44 @@ impl Editor {
         self.placeholder_text.as_deref()
     }
 
+    /// The text of the given rows and the newest selection within them, which are
+    /// exposed to screen readers while the editor is focused.
+    pub fn accessible_text(&self, rows: Range<MultiBufferRow>, cx: &AppContext) -> AccessibleText {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let max_row = buffer.max_point().row;
+        let start_row = rows.start.0.min(max_row);
+        let end_row = rows.end.0.max(start_row).min(max_row);
+        let start = buffer.point_to_offset(Point::new(start_row, 0));
+        let end = buffer.point_to_offset(Point::new(
+            end_row,
+            buffer.line_len(MultiBufferRow(end_row)),
+        ));
+
+        let selection = self.selections.newest::<usize>(cx);
+        let start_utf16 = buffer.offset_to_offset_utf16(start).0;
+        let to_utf16 =
+            |offset: usize| buffer.offset_to_offset_utf16(offset.clamp(start, end)).0 - start_utf16;
+
+        let (role, label) = match self.mode {
+            EditorMode::SingleLine { .. } => (
+                AccessibilityRole::TextField,
+                self.placeholder_text
+                    .as_deref()
+                    .unwrap_or_default()
+                    .to_string(),
+            ),
+            EditorMode::AutoHeight { .. } | EditorMode::Full => {
+                (AccessibilityRole::TextArea, self.title(cx).into_owned())
+            }
+        };
+        AccessibleText {
+            role,
+            label: label.into(),
+            text: buffer.text_for_range(start..end).collect::<String>().into(),
+            selected_range_utf16: to_utf16(selection.start)..to_utf16(selection.end),
+        }
+    }
+
     pub fn set_placeholder_text(
         &mut self,
         placeholder_text: impl Into<Arc<str>>,
@@ -2242,7 +2463,38 @@ impl Editor {
         }
     }
 
+    pub fn cursor_shape(&self) -> CursorShape {
+        self.cursor_shape
+    }
+
+    /// Sets the shape of the cursor, in place of the one from the settings until
+    /// [`Editor::reset_cursor_shape`] is called.
     pub fn set_cursor_shape(&mut self, cursor_shape: CursorShape, cx: &mut ViewContext<Self>) {
+        self.cursor_shape_overridden = true;
+        self.apply_cursor_shape(cursor_shape, cx);
+    }
+
+    /// Sets the shape of the cursor back to the one from the settings.
+    pub fn reset_cursor_shape(&mut self, cx: &mut ViewContext<Self>) {
+        self.cursor_shape_overridden = false;
+        self.apply_cursor_shape(self.configured_cursor_shape, cx);
+    }
+
+    fn apply_cursor_shape(&mut self, cursor_shape: CursorShape, cx: &mut ViewContext<Self>) {
+        if self.cursor_shape != cursor_shape
+            && self.focus_handle.is_focused(cx)
+            && self.leader_peer_id.is_none()
+        {
+            // Show collaborators the new shape.
+            self.buffer.update(cx, |buffer, cx| {
+                buffer.set_active_selections(
+                    &self.selections.disjoint_anchors(),
+                    self.selections.line_mode,
+                    cursor_shape,
+                    cx,
+                )
+            });
+        }
         self.cursor_shape = cursor_shape;
 
         // Disrupt blink for immediate user feedback that the cursor shape has changed
@@ -2501,6 +2753,7 @@ impl Editor {
             }
         }
 
+        focus_mode::refresh_focus_mode(self, cx);
         self.blink_manager.update(cx, BlinkManager::pause_blinking);
         cx.emit(EditorEvent::SelectionsChanged { local });
 
@@ -3093,7 +3346,16 @@ impl Editor {
     pub fn handle_input(&mut self, text: &str, cx: &mut ViewContext<Self>) {
         let text: Arc<str> = text.into();
 
-        if self.read_only(cx) {
+        if self.read_only(cx)
+            || self.queue_bulk_edit({
+                let text = text.clone();
+                move |editor, cx| editor.handle_input(&text, cx)
+            })
+        {
+            return;
+        }
+        if self.selections.count() > BULK_EDIT_BATCH_SIZE {
+            self.insert_in_batches(text, cx);
             return;
         }
 
@@ -3379,7 +3641,7 @@ impl Editor {
                 s.select(new_selections)
             });
 
-            if !bracket_inserted && EditorSettings::get_global(cx).use_on_type_format {
+            if !bracket_inserted && this.editor_settings(cx).use_on_type_format {
                 if let Some(on_type_format_task) =
                     this.trigger_on_type_formatting(text.to_string(), cx)
                 {
@@ -3387,7 +3649,7 @@ impl Editor {
                 }
             }
 
-            let editor_settings = EditorSettings::get_global(cx);
+            let editor_settings = this.editor_settings(cx);
             if bracket_inserted
                 && (editor_settings.auto_signature_help
                     || editor_settings.show_signature_help_after_edits)
@@ -3544,6 +3806,37 @@ impl Editor {
                             (None, false)
                         };
 
+                        // Like comments, lists and block quotes are only continued for cursor selections
+                        let list_continuation = maybe!({
+                            let language = language_scope.as_ref()?;
+                            if !selection_is_empty || !language.markdown_editing() {
+                                return None;
+                            }
+                            let (snapshot, range) =
+                                buffer.buffer_line_for_row(MultiBufferRow(start_point.row))?;
+                            let line = snapshot.text_for_range(range).collect::<String>();
+                            markdown_ext::list_continuation(&line, start_point.column as usize)
+                        });
+
+                        let anchor = buffer.anchor_after(end);
+                        let new_selection = selection.map(|_| anchor);
+                        match list_continuation {
+                            Some(markdown_ext::ListContinuation::End) => {
+                                let line_start = start - start_point.column as usize;
+                                return (
+                                    (line_start..end, String::new()),
+                                    (false, new_selection, false),
+                                );
+                            }
+                            Some(markdown_ext::ListContinuation::Continue { prefix, renumber }) => {
+                                let mut new_text = String::from("\n");
+                                new_text.extend(indent.chars());
+                                new_text.push_str(&prefix);
+                                return ((start..end, new_text), (false, new_selection, renumber));
+                            }
+                            None => {}
+                        }
+
                         let capacity_for_delimiter = comment_delimiter
                             .as_deref()
                             .map(str::len)
@@ -3559,11 +3852,9 @@ impl Editor {
                             new_text = new_text.repeat(2);
                         }
 
-                        let anchor = buffer.anchor_after(end);
-                        let new_selection = selection.map(|_| anchor);
                         (
                             (start..end, new_text),
-                            (insert_extra_newline, new_selection),
+                            (insert_extra_newline, new_selection, false),
                         )
                     })
                     .unzip()
@@ -3571,19 +3862,26 @@ impl Editor {
 
             this.edit_with_autoindent(edits, cx);
             let buffer = this.buffer.read(cx).snapshot(cx);
+            let mut renumbered_rows = Vec::new();
             let new_selections = selection_fixup_info
                 .into_iter()
-                .map(|(extra_newline_inserted, new_selection)| {
+                .map(|(extra_newline_inserted, new_selection, renumber_list)| {
                     let mut cursor = new_selection.end.to_point(&buffer);
                     if extra_newline_inserted {
                         cursor.row -= 1;
                         cursor.column = buffer.line_len(MultiBufferRow(cursor.row));
                     }
+                    if renumber_list {
+                        renumbered_rows.push(cursor.row);
+                    }
                     new_selection.map(|_| cursor)
                 })
                 .collect();
 
             this.change_selections(Some(Autoscroll::fit()), cx, |s| s.select(new_selections));
+            if !renumbered_rows.is_empty() {
+                markdown_ext::renumber_ordered_lists(this, renumbered_rows, cx);
+            }
             this.refresh_inline_completion(true, false, cx);
         });
     }
@@ -3718,11 +4016,21 @@ impl Editor {
         autoindent_mode: Option<AutoindentMode>,
         cx: &mut ViewContext<Self>,
     ) {
-        if self.read_only(cx) {
+        let text: Arc<str> = text.into();
+        if self.read_only(cx)
+            || self.queue_bulk_edit({
+                let text = text.clone();
+                let autoindent_mode = autoindent_mode.clone();
+                move |editor, cx| editor.insert_with_autoindent_mode(&text, autoindent_mode, cx)
+            })
+        {
             return;
         }
 
-        let text: Arc<str> = text.into();
+        if self.selections.count() > BULK_EDIT_BATCH_SIZE {
+            self.insert_in_batches(text, cx);
+            return;
+        }
         self.transact(cx, |this, cx| {
             let old_selections = this.selections.all_adjusted(cx);
             let selection_anchors = this.buffer.update(cx, |buffer, cx| {
@@ -4025,6 +4333,30 @@ impl Editor {
         }
     }
 
+    pub fn memory_usage(&self, cx: &AppContext) -> EditorMemoryUsage {
+        EditorMemoryUsage {
+            inlay_hints: self.inlay_hint_cache.estimated_memory_usage(),
+            display_map: self.display_map.read(cx).estimated_memory_usage(),
+        }
+    }
+
+    /// Drops the caches that can be rebuilt. Inlay hints are fetched again for the
+    /// visible part of the editor.
+    pub fn release_caches(&mut self, cx: &mut ViewContext<Self>) {
+        self.inlay_hint_cache.clear();
+        self.splice_inlays(
+            self.visible_inlay_hints(cx)
+                .iter()
+                .map(|inlay| inlay.id)
+                .collect(),
+            Vec::new(),
+            cx,
+        );
+        if self.inlay_hint_cache.enabled {
+            self.refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
+        }
+    }
+
     fn visible_inlay_hints(&self, cx: &ViewContext<'_, Editor>) -> Vec<Inlay> {
         self.display_map
             .read(cx)
@@ -4491,7 +4823,7 @@ impl Editor {
             cx,
         );
 
-        let editor_settings = EditorSettings::get_global(cx);
+        let editor_settings = self.editor_settings(cx);
         if editor_settings.show_signature_help_after_edits || editor_settings.auto_signature_help {
             // After the code completion is finished, users often want to know what signatures are needed.
             // so we should automatically call signature_help
@@ -4619,11 +4951,12 @@ impl Editor {
                                         multibuffer_point.row,
                                         tasks.column,
                                     )),
+                                    debuggable: tasks.kind.is_debuggable(),
                                 })
                             });
                         let spawn_straight_away = resolved_tasks
                             .as_ref()
-                            .map_or(false, |tasks| tasks.templates.len() == 1)
+                            .map_or(false, |tasks| tasks.len() == 1)
                             && code_actions
                                 .as_ref()
                                 .map_or(true, |actions| actions.is_empty());
@@ -4702,6 +5035,12 @@ impl Editor {
                     Some(Task::ready(Ok(())))
                 })
             }
+            CodeActionsItem::DebugTask(_, resolved_task) => {
+                workspace.update(cx, |_, cx| {
+                    workspace::tasks::debug_resolved_task(resolved_task, cx);
+                });
+                Some(Task::ready(Ok(())))
+            }
             CodeActionsItem::CodeAction(action) => {
                 let apply_code_actions = workspace
                     .read(cx)
@@ -4887,12 +5226,37 @@ impl Editor {
             return None;
         }
 
+        // Highlight the occurrences of the word under the cursor instead when
+        // no language server can highlight the occurrences of its symbol.
+        let fallback = self.editor_settings(cx).document_highlight_fallback;
+        let fallback_snapshot = if fallback.enabled
+            && self.mode == EditorMode::Full
+            && !self.supports_document_highlights(&project, &cursor_buffer, cx)
+        {
+            Some(cursor_buffer.read(cx).snapshot())
+        } else {
+            None
+        };
+
         self.document_highlights_task = Some(cx.spawn(|this, mut cx| async move {
             cx.background_executor()
                 .timer(DOCUMENT_HIGHLIGHTS_DEBOUNCE_TIMEOUT)
                 .await;
 
-            let highlights = if let Some(highlights) = project
+            let highlights = if let Some(snapshot) = fallback_snapshot {
+                let highlights = cx.background_executor().spawn(async move {
+                    let offset = snapshot.summary_for_anchor::<usize>(&cursor_buffer_position);
+                    word_highlights::word_occurrences(&snapshot, offset, &fallback)
+                        .into_iter()
+                        .map(|range| DocumentHighlight {
+                            range: snapshot.anchor_after(range.start)
+                                ..snapshot.anchor_before(range.end),
+                            kind: lsp::DocumentHighlightKind::TEXT,
+                        })
+                        .collect::<Vec<_>>()
+                });
+                Some(highlights.await)
+            } else if let Some(highlights) = project
                 .update(&mut cx, |project, cx| {
                     project.document_highlights(&cursor_buffer, cursor_buffer_position, cx)
                 })
@@ -4972,6 +5336,24 @@ impl Editor {
         None
     }
 
+    /// Returns whether a language server of the buffer can highlight the
+    /// occurrences of symbols in it.
+    fn supports_document_highlights(
+        &self,
+        project: &Model<Project>,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> bool {
+        let project = project.read(cx);
+        // Collaborators don't know which language servers the host runs.
+        if !project.is_local_or_ssh() {
+            return true;
+        }
+        project
+            .language_servers_for_buffer(buffer.read(cx), cx)
+            .any(|(_, server)| server.capabilities().document_highlight_provider.is_some())
+    }
+
     pub fn refresh_inline_completion(
         &mut self,
         debounce: bool,
@@ -5266,11 +5648,19 @@ impl Editor {
     fn render_run_indicator(
         &self,
         _style: &EditorStyle,
+        kind: RunnableKind,
         is_active: bool,
         row: DisplayRow,
         cx: &mut ViewContext<Self>,
     ) -> IconButton {
-        IconButton::new(("run_indicator", row.0 as usize), ui::IconName::Play)
+        let icon = match kind {
+            RunnableKind::Test | RunnableKind::Main => ui::IconName::Play,
+            RunnableKind::Bench => ui::IconName::CountdownTimer,
+            RunnableKind::DocTest => ui::IconName::Book,
+            RunnableKind::Script => ui::IconName::Terminal,
+            RunnableKind::Target => ui::IconName::Hash,
+        };
+        IconButton::new(("run_indicator", row.0 as usize), icon)
             .shape(ui::IconButtonShape::Square)
             .icon_size(IconSize::XSmall)
             .icon_color(Color::Muted)
@@ -6078,6 +6468,124 @@ impl Editor {
         }
     }
 
+    pub fn stage_selected_hunks(&mut self, _: &StageSelectedHunks, cx: &mut ViewContext<Self>) {
+        let multi_buffer_snapshot = self.buffer.read(cx).snapshot(cx);
+        let hunks =
+            hunks_for_selections(&multi_buffer_snapshot, &self.selections.disjoint_anchors());
+        self.stage_hunks(hunks, cx);
+    }
+
+    /// Writes the changes of the hunks to the git index, so that they're part of
+    /// the next commit and don't appear as changes anymore.
+    pub(crate) fn stage_hunks(
+        &mut self,
+        hunks: Vec<DiffHunk<MultiBufferRow>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let mut hunks_by_buffer: HashMap<BufferId, Vec<DiffHunk<MultiBufferRow>>> =
+            HashMap::default();
+        for hunk in hunks {
+            hunks_by_buffer
+                .entry(hunk.buffer_id)
+                .or_default()
+                .push(hunk);
+        }
+        for (buffer_id, hunks) in hunks_by_buffer {
+            let Some(buffer) = self.buffer.read(cx).buffer(buffer_id) else {
+                continue;
+            };
+            let buffer_snapshot = buffer.read(cx).snapshot();
+            let Some(index_text) = buffer
+                .read(cx)
+                .diff_base()
+                .map(|diff_base| ::git::diff::stage_hunks(diff_base, &buffer_snapshot, hunks))
+            else {
+                continue;
+            };
+            self.set_index_text(buffer, Task::ready(Ok(index_text)), cx);
+        }
+    }
+
+    pub fn unstage_selected_hunks(&mut self, _: &UnstageSelectedHunks, cx: &mut ViewContext<Self>) {
+        let mut rows_by_buffer: HashMap<BufferId, (Model<Buffer>, Vec<Range<u32>>)> =
+            HashMap::default();
+        for selection in self.selections.all::<Point>(cx) {
+            let buffer_ranges = self
+                .buffer
+                .read(cx)
+                .range_to_buffer_ranges(selection.range(), cx);
+            for (buffer, range, _) in buffer_ranges {
+                let start = buffer.read(cx).offset_to_point(range.start);
+                let end = buffer.read(cx).offset_to_point(range.end);
+                let end_row = if end.column == 0 && end.row > start.row {
+                    end.row
+                } else {
+                    end.row + 1
+                };
+                rows_by_buffer
+                    .entry(buffer.read(cx).remote_id())
+                    .or_insert_with(|| (buffer.clone(), Vec::new()))
+                    .1
+                    .push(start.row..end_row);
+            }
+        }
+        for (buffer, rows) in rows_by_buffer.into_values() {
+            self.unstage_rows(buffer, rows, cx);
+        }
+    }
+
+    /// Reverts the changes staged in the git index for the given rows of the
+    /// buffer to their content in HEAD.
+    pub(crate) fn unstage_rows(
+        &mut self,
+        buffer: Model<Buffer>,
+        rows: Vec<Range<u32>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let Some(index) = buffer.read(cx).diff_base().cloned() else {
+            return;
+        };
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        let unstaged_hunks = buffer_snapshot
+            .git_diff_hunks_intersecting_range(text::Anchor::MIN..text::Anchor::MAX)
+            .collect::<Vec<_>>();
+        let head_text = project.read(cx).load_head_text(&buffer, cx);
+        let index_text = cx.background_executor().spawn(async move {
+            let head = Rope::from(head_text.await?.unwrap_or_default());
+            anyhow::Ok(
+                ::git::diff::unstage_rows(&head, &index, &buffer_snapshot, unstaged_hunks, &rows)
+                    .await,
+            )
+        });
+        self.set_index_text(buffer, index_text, cx);
+    }
+
+    fn set_index_text(
+        &mut self,
+        buffer: Model<Buffer>,
+        index_text: Task<Result<String>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        cx.spawn(|_, mut cx| async move {
+            let index_text = index_text.await?;
+            project
+                .update(&mut cx, |project, cx| {
+                    project.set_index_text(&buffer, index_text.clone(), cx)
+                })?
+                .await?;
+            buffer.update(&mut cx, |buffer, cx| {
+                buffer.set_diff_base(Some(index_text), cx)
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     pub fn open_active_item_in_terminal(&mut self, _: &OpenInTerminal, cx: &mut ViewContext<Self>) {
         if let Some(working_directory) = self.active_excerpt(cx).and_then(|(_, buffer, _)| {
             let project_path = buffer.read(cx).project_path(cx)?;
@@ -6834,6 +7342,12 @@ impl Editor {
 
     pub fn paste(&mut self, _: &Paste, cx: &mut ViewContext<Self>) {
         if let Some(item) = cx.read_from_clipboard() {
+            if let Some(text) = item.text() {
+                if markdown_ext::paste_as_link(self, &text, cx) {
+                    return;
+                }
+            }
+
             let entries = item.entries();
 
             match entries.first() {
@@ -7708,6 +8222,56 @@ impl Editor {
         });
     }
 
+    /// Takes the code to send to a terminal with [`SendSelectionToTerminal`]: the selected
+    /// text, or else the line or paragraph at the cursor, moving the cursor to the next
+    /// one so that code can be sent piece by piece.
+    pub fn take_code_for_terminal(
+        &mut self,
+        action: &SendSelectionToTerminal,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<(String, SendToTerminalSettings)> {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let newest = self.selections.newest::<Point>(cx);
+        if !newest.is_empty() {
+            let text = self
+                .selections
+                .all::<Point>(cx)
+                .into_iter()
+                .filter(|selection| !selection.is_empty())
+                .map(|selection| buffer.text_for_range(selection.range()).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n");
+            let settings = buffer.settings_at(newest.start, cx).send_to_terminal;
+            return Some((text, settings));
+        }
+
+        // Blank lines are skipped, both before the code and after it.
+        let max_row = buffer.max_point().row;
+        let is_blank = |row: u32| buffer.is_line_blank(MultiBufferRow(row));
+        let mut start_row = (newest.head().row..=max_row).find(|row| !is_blank(*row))?;
+        let mut end_row = start_row;
+        if action.paragraph {
+            while start_row > 0 && !is_blank(start_row - 1) {
+                start_row -= 1;
+            }
+            while end_row < max_row && !is_blank(end_row + 1) {
+                end_row += 1;
+            }
+        }
+        let start = Point::new(start_row, 0);
+        let end = Point::new(end_row, buffer.line_len(MultiBufferRow(end_row)));
+        let text = buffer.text_for_range(start..end).collect::<String>();
+        let settings = buffer.settings_at(start, cx).send_to_terminal;
+        let next = (end_row + 1..=max_row)
+            .find(|row| !is_blank(*row))
+            .map_or(end, |row| Point::new(row, 0));
+
+        self.change_selections(Some(Autoscroll::fit()), cx, |s| {
+            s.select_ranges([next..next]);
+        });
+        Some((text, settings))
+    }
+
     pub fn select_line(&mut self, _: &SelectLine, cx: &mut ViewContext<Self>) {
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
         let mut selections = self.selections.all::<Point>(cx);
@@ -7872,14 +8436,21 @@ impl Editor {
             replace_newest: bool,
             auto_scroll: Option<Autoscroll>,
             cx: &mut ViewContext<Editor>,
-        ) {
+        ) -> AddedSelection {
             this.unfold_ranges([range.clone()], false, true, cx);
+            let mut replaced = None;
             this.change_selections(auto_scroll, cx, |s| {
                 if replace_newest {
-                    s.delete(s.newest_anchor().id);
+                    let newest = s.newest_anchor().clone();
+                    s.delete(newest.id);
+                    replaced = Some((newest.id, newest.range()));
                 }
                 s.insert_range(range.clone());
             });
+            AddedSelection {
+                id: this.selections.newest_anchor().id,
+                replaced,
+            }
         }
 
         let buffer = &display_map.buffer_snapshot;
@@ -7926,13 +8497,14 @@ impl Editor {
                 }
 
                 if let Some(next_selected_range) = next_selected_range {
-                    select_next_match_ranges(
+                    let added_selection = select_next_match_ranges(
                         self,
                         next_selected_range,
                         replace_newest,
                         autoscroll,
                         cx,
                     );
+                    select_next_state.added_selections.push(added_selection);
                 } else {
                     select_next_state.done = true;
                 }
@@ -8004,6 +8576,7 @@ impl Editor {
                         query: AhoCorasick::new(&[query])?,
                         wordwise: true,
                         done: is_empty,
+                        added_selections: Vec::new(),
                     };
                     self.select_next_state = Some(select_state);
                 } else {
@@ -8014,6 +8587,7 @@ impl Editor {
                     query: AhoCorasick::new(&[selected_text])?,
                     wordwise: false,
                     done: false,
+                    added_selections: Vec::new(),
                 });
                 self.select_next_match_internal(display_map, replace_newest, autoscroll, cx)?;
             }
@@ -8044,17 +8618,17 @@ impl Editor {
             .query
             .stream_find_iter(buffer.bytes_in_range(0..buffer.len()));
 
-        for query_match in query_matches {
-            let query_match = query_match.unwrap(); // can only fail due to I/O
-            let offset_range = query_match.start()..query_match.end();
-            let display_range = offset_range.start.to_display_point(&display_map)
-                ..offset_range.end.to_display_point(&display_map);
+        self.selections.change_with(cx, |selections| {
+            for query_match in query_matches {
+                let query_match = query_match.unwrap(); // can only fail due to I/O
+                let offset_range = query_match.start()..query_match.end();
+                let display_range = offset_range.start.to_display_point(&display_map)
+                    ..offset_range.end.to_display_point(&display_map);
 
-            if !select_next_state.wordwise
-                || (!movement::is_inside_word(&display_map, display_range.start)
-                    && !movement::is_inside_word(&display_map, display_range.end))
-            {
-                self.selections.change_with(cx, |selections| {
+                if !select_next_state.wordwise
+                    || (!movement::is_inside_word(&display_map, display_range.start)
+                        && !movement::is_inside_word(&display_map, display_range.end))
+                {
                     new_selections.push(Selection {
                         id: selections.new_selection_id(),
                         start: offset_range.start,
@@ -8062,9 +8636,9 @@ impl Editor {
                         reversed: false,
                         goal: SelectionGoal::None,
                     });
-                });
+                }
             }
-        }
+        });
 
         new_selections.sort_by_key(|selection| selection.start);
         let mut ix = 0;
@@ -8083,15 +8657,7 @@ impl Editor {
         }
 
         select_next_state.done = true;
-        self.unfold_ranges(
-            new_selections.iter().map(|selection| selection.range()),
-            false,
-            false,
-            cx,
-        );
-        self.change_selections(Some(Autoscroll::fit()), cx, |selections| {
-            selections.select(new_selections)
-        });
+        self.select_many(new_selections, cx);
 
         Ok(())
     }
@@ -8154,12 +8720,19 @@ impl Editor {
 
                 if let Some(next_selected_range) = next_selected_range {
                     self.unfold_ranges([next_selected_range.clone()], false, true, cx);
+                    let mut replaced = None;
                     self.change_selections(Some(Autoscroll::newest()), cx, |s| {
                         if action.replace_newest {
-                            s.delete(s.newest_anchor().id);
+                            let newest = s.newest_anchor().clone();
+                            s.delete(newest.id);
+                            replaced = Some((newest.id, newest.range()));
                         }
                         s.insert_range(next_selected_range);
                     });
+                    select_prev_state.added_selections.push(AddedSelection {
+                        id: self.selections.newest_anchor().id,
+                        replaced,
+                    });
                 } else {
                     select_prev_state.done = true;
                 }
@@ -8223,6 +8796,7 @@ impl Editor {
                         query: AhoCorasick::new(&[query.chars().rev().collect::<String>()])?,
                         wordwise: true,
                         done: is_empty,
+                        added_selections: Vec::new(),
                     };
                     self.select_prev_state = Some(select_state);
                 } else {
@@ -8243,6 +8817,7 @@ impl Editor {
                     query: AhoCorasick::new(&[selected_text.chars().rev().collect::<String>()])?,
                     wordwise: false,
                     done: false,
+                    added_selections: Vec::new(),
                 });
                 self.select_previous(action, cx)?;
             }
@@ -8250,6 +8825,61 @@ impl Editor {
         Ok(())
     }
 
+    /// Removes the selection most recently added by `SelectNext` or
+    /// `SelectPrevious`, restoring the selection it replaced if the newest
+    /// match was skipped.
+    pub fn undo_select_next(&mut self, _: &UndoSelectNext, cx: &mut ViewContext<Self>) {
+        let is_select_next = self.select_next_state.is_some();
+        let state = if is_select_next {
+            &mut self.select_next_state
+        } else {
+            &mut self.select_prev_state
+        };
+        let Some(mut state) = state.take() else {
+            return;
+        };
+        let Some(added_selection) = state.added_selections.pop() else {
+            if is_select_next {
+                self.select_next_state = Some(state);
+            } else {
+                self.select_prev_state = Some(state);
+            }
+            return;
+        };
+
+        self.push_to_selection_history();
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let replaced = added_selection.replaced.map(|(id, range)| {
+            (
+                id,
+                range.start.to_offset(&buffer)..range.end.to_offset(&buffer),
+            )
+        });
+        self.change_selections(Some(Autoscroll::newest()), cx, |s| {
+            if let Some((_, range)) = replaced.clone() {
+                s.insert_range(range);
+            }
+            s.delete(added_selection.id);
+        });
+
+        // The restored selection has a new ID, which earlier matches must
+        // refer to in order to be removed in turn.
+        if let Some((replaced_id, _)) = replaced {
+            let restored_id = self.selections.newest_anchor().id;
+            for added_selection in &mut state.added_selections {
+                if added_selection.id == replaced_id {
+                    added_selection.id = restored_id;
+                }
+            }
+        }
+        state.done = false;
+        if is_select_next {
+            self.select_next_state = Some(state);
+        } else {
+            self.select_prev_state = Some(state);
+        }
+    }
+
     pub fn toggle_comments(&mut self, action: &ToggleComments, cx: &mut ViewContext<Self>) {
         let text_layout_details = &self.text_layout_details(cx);
         self.transact(cx, |this, cx| {
@@ -8637,7 +9267,7 @@ impl Editor {
     }
 
     fn refresh_runnables(&mut self, cx: &mut ViewContext<Self>) -> Task<()> {
-        if !EditorSettings::get_global(cx).gutter.runnables {
+        if !self.editor_settings(cx).gutter.runnables {
             self.clear_tasks();
             return Task::ready(());
         }
@@ -8720,6 +9350,7 @@ impl Editor {
                     (runnable.buffer_id, row),
                     RunnableTasks {
                         templates: tasks,
+                        kind: runnable.runnable.kind,
                         offset: MultiBufferOffset(runnable.run_range.start),
                         context_range,
                         column: point.column,
@@ -8841,6 +9472,109 @@ impl Editor {
         });
     }
 
+    /// Moves each cursor to the bracket matching the one it's on or next to,
+    /// or to the closing bracket of the innermost pair it's inside of.
+    pub fn go_to_matching_bracket(&mut self, _: &GoToMatchingBracket, cx: &mut ViewContext<Self>) {
+        self.change_selections(Some(Autoscroll::fit()), cx, |s| {
+            s.move_offsets_with(|snapshot, selection| {
+                let head = selection.head();
+                let Some(enclosing_bracket_ranges) = snapshot.enclosing_bracket_ranges(head..head)
+                else {
+                    return;
+                };
+
+                // Being on a bracket takes precedence over being after one,
+                // which takes precedence over being inside of a pair.
+                let mut best = None;
+                for (open, close) in enclosing_bracket_ranges {
+                    let candidate = if open.start == head {
+                        (0, close.start)
+                    } else if close.start == head {
+                        (0, open.start)
+                    } else if open.end == head {
+                        (1, close.start)
+                    } else if close.end == head {
+                        (1, open.start)
+                    } else if open.end < head && head < close.start {
+                        (2, close.start)
+                    } else {
+                        continue;
+                    };
+                    let length = close.end - open.start;
+                    if best.map_or(true, |(priority, best_length, _)| {
+                        (candidate.0, length) < (priority, best_length)
+                    }) {
+                        best = Some((candidate.0, length, candidate.1));
+                    }
+                }
+
+                if let Some((_, _, destination)) = best {
+                    selection.collapse_to(destination, SelectionGoal::None);
+                }
+            })
+        });
+    }
+
+    /// Selects the contents of the innermost bracket pair enclosing each
+    /// selection, or the whole pair if its contents are already selected.
+    pub fn select_enclosing_bracket_contents(
+        &mut self,
+        _: &SelectEnclosingBracketContents,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let old_selections = self.selections.all::<usize>(cx).into_boxed_slice();
+
+        fn update_selection(
+            selection: &Selection<usize>,
+            buffer_snap: &MultiBufferSnapshot,
+        ) -> Option<Selection<usize>> {
+            let enclosing_bracket_ranges =
+                buffer_snap.enclosing_bracket_ranges(selection.range())?;
+            let mut best_range: Option<Range<usize>> = None;
+            for (open, close) in enclosing_bracket_ranges {
+                for range in [open.end..close.start, open.start..close.end] {
+                    let contains_selection = range.start <= selection.start
+                        && selection.end <= range.end
+                        && range != selection.range();
+                    if contains_selection
+                        && best_range
+                            .as_ref()
+                            .map_or(true, |best_range| range.len() < best_range.len())
+                    {
+                        best_range = Some(range);
+                    }
+                }
+            }
+            let new_range = best_range?;
+            Some(Selection {
+                id: selection.id,
+                start: new_range.start,
+                end: new_range.end,
+                goal: SelectionGoal::None,
+                reversed: selection.reversed,
+            })
+        }
+
+        let mut selected_enclosing_range = false;
+        let new_selections = old_selections
+            .iter()
+            .map(|selection| match update_selection(selection, &buffer) {
+                Some(new_selection) => {
+                    selected_enclosing_range = true;
+                    new_selection
+                }
+                None => selection.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        if selected_enclosing_range {
+            self.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select(new_selections);
+            });
+        }
+    }
+
     pub fn undo_selection(&mut self, _: &UndoSelection, cx: &mut ViewContext<Self>) {
         self.end_selection(cx);
         self.selection_history.mode = SelectionHistoryMode::Undoing;
@@ -8930,13 +9664,66 @@ impl Editor {
         self.go_to_diagnostic_impl(Direction::Prev, cx)
     }
 
+    fn go_to_next_error(&mut self, _: &GoToNextError, cx: &mut ViewContext<Self>) {
+        let filter = DiagnosticNavigationFilter::only(DiagnosticSeverity::ERROR, cx);
+        self.go_to_diagnostic_with_filter(Direction::Next, filter, cx)
+    }
+
+    fn go_to_prev_error(&mut self, _: &GoToPrevError, cx: &mut ViewContext<Self>) {
+        let filter = DiagnosticNavigationFilter::only(DiagnosticSeverity::ERROR, cx);
+        self.go_to_diagnostic_with_filter(Direction::Prev, filter, cx)
+    }
+
+    fn go_to_next_warning(&mut self, _: &GoToNextWarning, cx: &mut ViewContext<Self>) {
+        let filter = DiagnosticNavigationFilter::only(DiagnosticSeverity::WARNING, cx);
+        self.go_to_diagnostic_with_filter(Direction::Next, filter, cx)
+    }
+
+    fn go_to_prev_warning(&mut self, _: &GoToPrevWarning, cx: &mut ViewContext<Self>) {
+        let filter = DiagnosticNavigationFilter::only(DiagnosticSeverity::WARNING, cx);
+        self.go_to_diagnostic_with_filter(Direction::Prev, filter, cx)
+    }
+
+    fn go_to_next_diagnostic_from_source(
+        &mut self,
+        action: &GoToNextDiagnosticFromSource,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let filter = DiagnosticNavigationFilter::from_source(&action.source, cx);
+        self.go_to_diagnostic_with_filter(Direction::Next, filter, cx)
+    }
+
+    fn go_to_prev_diagnostic_from_source(
+        &mut self,
+        action: &GoToPrevDiagnosticFromSource,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let filter = DiagnosticNavigationFilter::from_source(&action.source, cx);
+        self.go_to_diagnostic_with_filter(Direction::Prev, filter, cx)
+    }
+
     pub fn go_to_diagnostic_impl(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
+        let filter = DiagnosticNavigationFilter::from_settings(cx);
+        self.go_to_diagnostic_with_filter(direction, filter, cx)
+    }
+
+    fn go_to_diagnostic_with_filter(
+        &mut self,
+        direction: Direction,
+        filter: DiagnosticNavigationFilter,
+        cx: &mut ViewContext<Self>,
+    ) {
         let buffer = self.buffer.read(cx).snapshot(cx);
         let selection = self.selections.newest::<usize>(cx);
 
         // If there is an active Diagnostic Popover jump to its diagnostic instead.
         if direction == Direction::Next {
-            if let Some(popover) = self.hover_state.diagnostic_popover.as_ref() {
+            if let Some(popover) = self
+                .hover_state
+                .diagnostic_popover
+                .as_ref()
+                .filter(|popover| filter.matches(popover.diagnostic()))
+            {
                 let (group_id, jump_to) = popover.activation_info();
                 if self.activate_diagnostics(group_id, cx) {
                     self.change_selections(Some(Autoscroll::fit()), cx, |s| {
@@ -8987,7 +9774,7 @@ impl Editor {
                 })
                 .find_map(|entry| {
                     if entry.diagnostic.is_primary
-                        && entry.diagnostic.severity <= DiagnosticSeverity::WARNING
+                        && filter.matches(&entry.diagnostic)
                         && !entry.range.is_empty()
                         // if we match with the active diagnostic, skip it
                         && Some(entry.diagnostic.group_id)
@@ -9012,6 +9799,8 @@ impl Editor {
                     });
                 }
                 break;
+            } else if !filter.wrap {
+                break;
             } else {
                 // Cycle around to the start of the buffer, potentially moving back to the start of
                 // the currently active diagnostic.
@@ -9087,6 +9876,37 @@ impl Editor {
         }
     }
 
+    /// Moves the cursor to the next outline item, like the next heading of a document.
+    pub fn go_to_next_outline_item(&mut self, _: &GoToNextOutlineItem, cx: &mut ViewContext<Self>) {
+        self.go_to_outline_item(Direction::Next, cx);
+    }
+
+    /// Moves the cursor to the previous outline item, like the previous heading of a document.
+    pub fn go_to_prev_outline_item(&mut self, _: &GoToPrevOutlineItem, cx: &mut ViewContext<Self>) {
+        self.go_to_outline_item(Direction::Prev, cx);
+    }
+
+    fn go_to_outline_item(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let Some(outline) = buffer.outline(None) else {
+            return;
+        };
+        let cursor_row = self.selections.newest::<Point>(cx).head().row;
+        let mut item_starts = outline
+            .items
+            .iter()
+            .map(|item| item.range.start.to_point(&buffer));
+        let target = match direction {
+            Direction::Next => item_starts.find(|start| start.row > cursor_row),
+            Direction::Prev => item_starts.filter(|start| start.row < cursor_row).last(),
+        };
+        if let Some(target) = target {
+            self.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select_ranges([target..target])
+            });
+        }
+    }
+
     fn seek_in_direction(
         &mut self,
         snapshot: &DisplaySnapshot,
@@ -10657,6 +11477,15 @@ impl Editor {
         self.show_indent_guides
     }
 
+    pub fn toggle_focus_mode(&mut self, _: &ToggleFocusMode, cx: &mut ViewContext<Self>) {
+        self.focus_mode = !self.focus_mode;
+        focus_mode::refresh_focus_mode(self, cx);
+    }
+
+    pub fn focus_mode(&self) -> bool {
+        self.focus_mode
+    }
+
     pub fn toggle_line_numbers(&mut self, _: &ToggleLineNumbers, cx: &mut ViewContext<Self>) {
         let mut editor_settings = EditorSettings::get_global(cx).clone();
         editor_settings.gutter.line_numbers = !editor_settings.gutter.line_numbers;
@@ -10665,7 +11494,7 @@ impl Editor {
 
     pub fn should_use_relative_line_numbers(&self, cx: &WindowContext) -> bool {
         self.use_relative_line_numbers
-            .unwrap_or(EditorSettings::get_global(cx).relative_line_numbers)
+            .unwrap_or(self.editor_settings(cx).relative_line_numbers)
     }
 
     pub fn toggle_relative_line_numbers(
@@ -10803,14 +11632,14 @@ impl Editor {
         self.show_selection_menu = self
             .show_selection_menu
             .map(|show_selections_menu| !show_selections_menu)
-            .or_else(|| Some(!EditorSettings::get_global(cx).toolbar.selections_menu));
+            .or_else(|| Some(!self.editor_settings(cx).toolbar.selections_menu));
 
         cx.notify();
     }
 
     pub fn selection_menu_enabled(&self, cx: &AppContext) -> bool {
         self.show_selection_menu
-            .unwrap_or_else(|| EditorSettings::get_global(cx).toolbar.selections_menu)
+            .unwrap_or_else(|| self.editor_settings(cx).toolbar.selections_menu)
     }
 
     fn start_git_blame(&mut self, user_triggered: bool, cx: &mut ViewContext<Self>) {
@@ -10921,22 +11750,20 @@ impl Editor {
                 (buffer.clone(), selection)
             };
 
-            let path = buffer
-                .read(cx)
-                .file()?
-                .as_local()?
-                .path()
-                .to_str()?
-                .to_string();
-            let repo = project.get_repo(&buffer.read(cx).project_path(cx)?, cx)?;
+            // The path is relative to the repository, which may be nested in
+            // the worktree.
+            let (repo, path) = project.local_repository_for_buffer(&buffer, cx).ok()?;
+            let path = path.to_str()?.to_string();
             Some((path, selection, repo))
         })
         .ok_or_else(|| anyhow!("unable to open git repository"))?;
 
-        const REMOTE_NAME: &str = "origin";
+        let remote_name = repo
+            .default_remote_name()
+            .ok_or_else(|| anyhow!("no remote found"))?;
         let origin_url = repo
-            .remote_url(REMOTE_NAME)
-            .ok_or_else(|| anyhow!("remote \"{REMOTE_NAME}\" not found"))?;
+            .remote_url(&remote_name)
+            .ok_or_else(|| anyhow!("remote \"{remote_name}\" not found"))?;
         let sha = repo
             .head_sha()
             .ok_or_else(|| anyhow!("failed to read HEAD SHA"))?;
@@ -10992,6 +11819,61 @@ impl Editor {
         }
     }
 
+    /// Shows the language, syntax style, and tree-sitter nodes at the newest
+    /// cursor, which are what syntax theme overrides apply to.
+    pub fn inspect_tree_sitter_scopes(
+        &mut self,
+        _: &InspectTreeSitterScopes,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(workspace) = self.workspace() else {
+            return;
+        };
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let position = self.selections.newest::<usize>(cx).head();
+        let syntax_theme = cx.theme().syntax().clone();
+
+        let language = snapshot
+            .language_at(position)
+            .map(|language| language.name());
+        let mut message = format!("Language: {}", language.as_deref().unwrap_or("none"));
+
+        let end = snapshot.clip_offset((position + 1).min(snapshot.len()), Bias::Right);
+        let highlight_id = snapshot
+            .chunks(position..end, true)
+            .find_map(|chunk| chunk.syntax_highlight_id);
+        match highlight_id.and_then(|id| Some((id, id.name(&syntax_theme)?))) {
+            Some((id, name)) if syntax_theme.is_language_specific(id.0 as usize) => {
+                message.push_str(&format!("\nSyntax style: {name} (language-specific)"));
+            }
+            Some((_, name)) => message.push_str(&format!("\nSyntax style: {name}")),
+            None => message.push_str("\nSyntax style: none"),
+        }
+
+        if let Some((buffer, offset)) = snapshot.point_to_buffer_offset(position) {
+            if let Some(layer) = buffer.syntax_layer_at(offset) {
+                let mut node = layer.node().descendant_for_byte_range(offset, offset);
+                let mut kinds = Vec::new();
+                while let Some(current) = node {
+                    if current.is_named() {
+                        kinds.push(current.kind());
+                    }
+                    node = current.parent();
+                }
+                message.push_str(&format!("\nTree-sitter nodes: {}", kinds.join(" < ")));
+            }
+        }
+
+        workspace.update(cx, |workspace, cx| {
+            struct TreeSitterScopes;
+
+            workspace.show_toast(
+                Toast::new(NotificationId::unique::<TreeSitterScopes>(), message),
+                cx,
+            )
+        });
+    }
+
     pub fn open_permalink_to_line(&mut self, _: &OpenPermalinkToLine, cx: &mut ViewContext<Self>) {
         let permalink = self.get_permalink_to_line(cx);
 
@@ -11018,6 +11900,241 @@ impl Editor {
         }
     }
 
+    /// Saves the edits made to the file since it was opened as a patch bundle,
+    /// for someone else to merge into their copy of the file.
+    pub fn export_patch_bundle(&mut self, _: &ExportPatchBundle, cx: &mut ViewContext<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return;
+        };
+        let directory = buffer
+            .read(cx)
+            .file()
+            .and_then(|file| file.as_local())
+            .and_then(|file| file.abs_path(cx).parent().map(Path::to_path_buf))
+            .unwrap_or_else(|| util::paths::home_dir().clone());
+        let abs_path = cx.prompt_for_new_path(&directory);
+        cx.spawn(|_, mut cx| async move {
+            let Some(mut abs_path) = abs_path.await?? else {
+                return Ok(());
+            };
+            if abs_path.extension().is_none() {
+                abs_path.set_extension(PATCH_BUNDLE_EXTENSION);
+            }
+            project
+                .update(&mut cx, |project, cx| {
+                    project.export_patch_bundle(&buffer, abs_path, cx)
+                })?
+                .await
+        })
+        .detach_and_prompt_err("Failed to export patch bundle", cx, |_, _| None);
+    }
+
+    /// Saves the buffer, or the lines of the selection, as an HTML file highlighted
+    /// with the current theme.
+    pub fn export_as_html(&mut self, action: &ExportAsHtml, cx: &mut ViewContext<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return;
+        };
+        let Some(html) = self.render_as_html(action.line_numbers, action.wrap_lines, false, cx)
+        else {
+            return;
+        };
+        let directory = buffer
+            .read(cx)
+            .file()
+            .and_then(|file| file.as_local())
+            .and_then(|file| file.abs_path(cx).parent().map(Path::to_path_buf))
+            .unwrap_or_else(|| util::paths::home_dir().clone());
+        let fs = project.read(cx).fs().clone();
+        let abs_path = cx.prompt_for_new_path(&directory);
+        cx.spawn(|_, _| async move {
+            let Some(mut abs_path) = abs_path.await?? else {
+                return Ok(());
+            };
+            if abs_path.extension().is_none() {
+                abs_path.set_extension("html");
+            }
+            fs.atomic_write(abs_path, html).await
+        })
+        .detach_and_prompt_err("Failed to export as HTML", cx, |_, _| None);
+    }
+
+    /// Opens the buffer, or the lines of the selection, in the browser to be printed
+    /// or saved as a PDF file, as there's no way to print from Zed itself.
+    pub fn print_to_pdf(&mut self, _: &PrintToPdf, cx: &mut ViewContext<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let Some(html) = self.render_as_html(true, true, true, cx) else {
+            return;
+        };
+        let file_name = self
+            .buffer
+            .read(cx)
+            .as_singleton()
+            .and_then(|buffer| Some(buffer.read(cx).file()?.file_name(cx).to_owned()))
+            .unwrap_or_else(|| "untitled".into());
+        let mut abs_path = std::env::temp_dir().join(file_name);
+        abs_path.as_mut_os_string().push(".html");
+        let fs = project.read(cx).fs().clone();
+        cx.spawn(|_, mut cx| async move {
+            fs.atomic_write(abs_path.clone(), html).await?;
+            let url = url::Url::from_file_path(&abs_path)
+                .map_err(|_| anyhow!("invalid path {abs_path:?}"))?;
+            cx.update(|cx| cx.open_url(url.as_str()))
+        })
+        .detach_and_prompt_err("Failed to print", cx, |_, _| None);
+    }
+
+    /// Copies an image of the lines of the newest selection, or of the whole buffer
+    /// when nothing is selected, to the clipboard.
+    pub fn copy_selection_as_image(
+        &mut self,
+        action: &CopySelectionAsImage,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some((title, first_row, lines)) = self.highlighted_lines_for_export(cx) else {
+            return;
+        };
+        let settings = ThemeSettings::get_global(cx);
+        let colors = cx.theme().colors();
+        let font_size = settings.buffer_font_size(cx);
+        let style = code_image::CodeImageStyle {
+            title,
+            font: settings.buffer_font.clone(),
+            font_size,
+            line_height: font_size * settings.line_height(),
+            background: colors.editor_background,
+            foreground: colors.editor_foreground,
+            border: colors.border,
+            title_color: colors.text_muted,
+            line_number_color: colors.editor_line_number,
+            first_line_number: action.line_numbers.then_some(first_row + 1),
+        };
+        match code_image::render_code_image(&lines, &style, cx) {
+            Ok(image) => cx.write_to_clipboard(ClipboardItem::new_image(&image)),
+            Err(error) => log::error!("failed to render an image of the selection: {error:#}"),
+        }
+    }
+
+    /// Returns the name of the file and the lines of the newest selection, or of
+    /// the whole buffer when nothing is selected, along with the first row of them.
+    fn highlighted_lines_for_export(
+        &self,
+        cx: &AppContext,
+    ) -> Option<(String, u32, Vec<html_export::HighlightedLine>)> {
+        let buffer = self.buffer.read(cx).as_singleton()?;
+        let snapshot = buffer.read(cx).snapshot();
+        let selection = self.selections.newest::<Point>(cx);
+        let rows = if selection.is_empty() {
+            0..=snapshot.max_point().row
+        } else {
+            let mut end_row = selection.end.row;
+            // Leave out the line after a selection of whole lines.
+            if selection.end.column == 0 && end_row > selection.start.row {
+                end_row -= 1;
+            }
+            selection.start.row..=end_row
+        };
+        let range = snapshot.point_to_offset(Point::new(*rows.start(), 0))
+            ..snapshot.point_to_offset(Point::new(*rows.end(), snapshot.line_len(*rows.end())));
+        let mut lines = html_export::highlighted_lines(&snapshot, range, cx.theme().syntax());
+        // Leave out the empty line after the newline at the end of the buffer.
+        if lines.len() > 1 && lines.last().map_or(false, |line| line.is_empty()) {
+            lines.pop();
+        }
+        let title = buffer
+            .read(cx)
+            .file()
+            .map(|file| file.file_name(cx).to_string_lossy().into_owned())
+            .unwrap_or_else(|| "untitled".to_string());
+        Some((title, *rows.start(), lines))
+    }
+
+    /// Renders the buffer as a standalone HTML document, or only the lines of the
+    /// newest selection when it isn't empty.
+    fn render_as_html(
+        &self,
+        line_numbers: bool,
+        wrap_lines: bool,
+        print: bool,
+        cx: &AppContext,
+    ) -> Option<String> {
+        let (title, first_row, lines) = self.highlighted_lines_for_export(cx)?;
+        let settings = ThemeSettings::get_global(cx);
+        let colors = cx.theme().colors();
+        let options = html_export::HtmlOptions {
+            title,
+            font_family: settings.buffer_font.family.to_string(),
+            font_size: settings.buffer_font_size(cx).0,
+            background: colors.editor_background,
+            foreground: colors.editor_foreground,
+            line_number_color: colors.editor_line_number,
+            first_line_number: line_numbers.then_some(first_row + 1),
+            wrap_lines,
+            print,
+        };
+        Some(html_export::render_html(&lines, &options))
+    }
+
+    /// Merges a patch bundle into the file it was exported from, and opens it.
+    fn import_patch_bundle(
+        workspace: &mut Workspace,
+        _: &ImportPatchBundle,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let project = workspace.project().clone();
+        let paths = workspace.prompt_for_open_path(
+            PathPromptOptions {
+                files: true,
+                directories: false,
+                multiple: false,
+            },
+            DirectoryLister::Local(workspace.app_state().fs.clone()),
+            cx,
+        );
+        cx.spawn(|workspace, mut cx| async move {
+            let Some(abs_path) = paths.await?.and_then(|paths| paths.into_iter().next()) else {
+                return Ok(());
+            };
+            let import = project
+                .update(&mut cx, |project, cx| {
+                    project.import_patch_bundle(abs_path, cx)
+                })?
+                .await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let path = import
+                    .buffer
+                    .read(cx)
+                    .file()
+                    .map(|file| file.path().display().to_string())
+                    .unwrap_or_default();
+                let pane = workspace.active_pane().clone();
+                workspace.open_project_item::<Editor>(pane, import.buffer, true, true, cx);
+
+                let message = match (import.edit_count, import.authors.as_slice()) {
+                    (0, _) => format!("{path} already contains every edit of the patch bundle"),
+                    (count, []) => format!("Merged {count} edits into {path}"),
+                    (count, authors) => format!(
+                        "Merged {count} edits by {} into {path}",
+                        authors.iter().map(|author| format!("@{author}")).join(", ")
+                    ),
+                };
+                workspace.show_toast(
+                    Toast::new(NotificationId::unique::<ImportPatchBundle>(), message),
+                    cx,
+                );
+            })
+        })
+        .detach_and_prompt_err("Failed to import patch bundle", cx, |_, _| None);
+    }
+
     /// Adds or removes (on `None` color) a highlight for the rows corresponding to the anchor range given.
     /// On matching anchor range, replaces the old highlight; does not clear the other existing highlights.
     /// If multiple anchor ranges will produce highlights for the same row, the last range added will be used.
@@ -11536,6 +12653,12 @@ impl Editor {
                     }
                 }
 
+                code_lens::refresh_code_lens(self, cx);
+                merge_conflicts::refresh_merge_conflicts(self, cx);
+                duplicate_lines::refresh_duplicate_lines(self, cx);
+                semantic_rainbow::refresh_semantic_rainbow(self, cx);
+                focus_mode::refresh_focus_mode(self, cx);
+
                 let Some(project) = &self.project else { return };
                 let telemetry = project.read(cx).client().telemetry().clone();
                 refresh_linked_ranges(self, cx);
@@ -11553,9 +12676,13 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                review::refresh_review_threads(self, cx);
+                semantic_rainbow::refresh_semantic_rainbow(self, cx);
+                background_syntax::refresh_background_syntax_highlights(self, cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
+                review::refresh_review_threads(self, cx);
                 cx.emit(EditorEvent::ExcerptsRemoved { ids: ids.clone() })
             }
             multi_buffer::Event::ExcerptsEdited { ids } => {
@@ -11566,11 +12693,17 @@ impl Editor {
             }
             multi_buffer::Event::Reparsed(buffer_id) => {
                 self.tasks_update_task = Some(self.refresh_runnables(cx));
+                semantic_rainbow::refresh_semantic_rainbow(self, cx);
+                focus_mode::refresh_focus_mode(self, cx);
+                background_syntax::refresh_background_syntax_highlights(self, cx);
 
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
             }
             multi_buffer::Event::LanguageChanged(buffer_id) => {
                 linked_editing_ranges::refresh_linked_ranges(self, cx);
+                code_lens::refresh_code_lens(self, cx);
+                semantic_rainbow::refresh_semantic_rainbow(self, cx);
+                background_syntax::refresh_background_syntax_highlights(self, cx);
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
                 cx.notify();
             }
@@ -11613,9 +12746,20 @@ impl Editor {
             )),
             cx,
         );
-        let editor_settings = EditorSettings::get_global(cx);
+        code_lens::refresh_code_lens(self, cx);
+        semantic_rainbow::refresh_semantic_rainbow(self, cx);
+        focus_mode::refresh_focus_mode(self, cx);
+        background_syntax::refresh_background_syntax_highlights(self, cx);
+        let editor_settings = self.editor_settings(cx);
         self.scroll_manager.vertical_scroll_margin = editor_settings.vertical_scroll_margin;
         self.show_breadcrumbs = editor_settings.toolbar.breadcrumbs;
+        let cursor_shape = editor_settings.cursor_shape;
+        if self.configured_cursor_shape != cursor_shape {
+            self.configured_cursor_shape = cursor_shape;
+            if !self.cursor_shape_overridden {
+                self.apply_cursor_shape(cursor_shape, cx);
+            }
+        }
 
         let project_settings = ProjectSettings::get_global(cx);
         self.serialize_dirty_buffers = project_settings.session.restore_unsaved_buffers;
@@ -12493,6 +13637,12 @@ fn ending_row(next_selection: &Selection<Point>, display_map: &DisplaySnapshot)
 }
 
 impl EditorSnapshot {
+    /// The editor settings, as overridden by the project settings for the edited file.
+    pub fn editor_settings<'a>(&self, cx: &'a AppContext) -> &'a EditorSettings {
+        let buffer = self.buffer_snapshot.as_singleton();
+        EditorSettings::for_file(buffer.and_then(|(_, _, buffer)| buffer.file()), cx)
+    }
+
     pub fn remote_selections_in_range<'a>(
         &'a self,
         range: &'a Range<Anchor>,
@@ -12559,7 +13709,7 @@ impl EditorSnapshot {
                 Some(GitGutterSetting::TrackedFiles)
             )
         });
-        let gutter_settings = EditorSettings::get_global(cx).gutter;
+        let gutter_settings = self.editor_settings(cx).gutter;
         let show_line_numbers = self
             .show_line_numbers
             .unwrap_or(gutter_settings.line_numbers);
@@ -12643,7 +13793,8 @@ impl EditorSnapshot {
                 cx,
             ))
         } else if folded
-            || (self.starts_indent(buffer_row) && (row_contains_cursor || self.gutter_hovered))
+            || ((self.starts_indent(buffer_row) || self.starts_outline_item(buffer_row))
+                && (row_contains_cursor || self.gutter_hovered))
         {
             Some(
                 Disclosure::new(("indent-fold-indicator", buffer_row.0), !folded)
@@ -12793,6 +13944,8 @@ impl Render for Editor {
                     ..HighlightStyle::default()
                 },
                 unnecessary_code_fade: ThemeSettings::get_global(cx).unnecessary_code_fade,
+                deprecated_code_strikethrough: ThemeSettings::get_global(cx)
+                    .deprecated_code_strikethrough,
             },
         )
     }
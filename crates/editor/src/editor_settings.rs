@@ -1,12 +1,18 @@
 use gpui::AppContext;
+use language::{CursorShape, File};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
+use std::sync::Arc;
 
 #[derive(Deserialize, Clone)]
 pub struct EditorSettings {
     pub cursor_blink: bool,
+    pub cursor_blink_interval: u64,
+    pub cursor_shape: CursorShape,
+    pub cursor_bar_width: f32,
     pub current_line_highlight: CurrentLineHighlight,
+    pub enclosing_bracket_range_highlight: EnclosingBracketRangeHighlight,
     pub hover_popover_enabled: bool,
     pub show_completions_on_input: bool,
     pub show_completion_documentation: bool,
@@ -15,12 +21,17 @@ pub struct EditorSettings {
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
     pub gutter: Gutter,
+    pub inline_diagnostics: InlineDiagnostics,
+    pub focus_mode: FocusMode,
+    pub diagnostics_navigation: DiagnosticsNavigation,
+    pub document_highlight_fallback: DocumentHighlightFallback,
     pub scroll_beyond_last_line: ScrollBeyondLastLine,
     pub vertical_scroll_margin: f32,
     pub scroll_sensitivity: f32,
     pub relative_line_numbers: bool,
     pub seed_search_query_from_cursor: SeedQuerySetting,
     pub multi_cursor_modifier: MultiCursorModifier,
+    pub multi_cursor_prompt_threshold: usize,
     pub redact_private_values: bool,
     pub expand_excerpt_lines: u32,
     pub middle_click_paste: bool,
@@ -45,6 +56,18 @@ pub enum CurrentLineHighlight {
     All,
 }
 
+/// How to highlight the range between the brackets enclosing the cursor.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EnclosingBracketRangeHighlight {
+    /// Only highlight the brackets.
+    None,
+    /// Tint the background of the range.
+    Tint,
+    /// Underline the range.
+    Underline,
+}
+
 /// When to populate a new search's query based on the text under the cursor.
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -111,6 +134,89 @@ pub struct Gutter {
     pub folds: bool,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct InlineDiagnostics {
+    pub enabled: bool,
+    pub error_lens: bool,
+    pub max_length: usize,
+    pub severities: Vec<InlineDiagnosticSeverity>,
+}
+
+impl InlineDiagnostics {
+    /// Returns whether diagnostics with the given severity are shown inline.
+    pub fn shows(&self, severity: lsp::DiagnosticSeverity) -> bool {
+        self.severities
+            .iter()
+            .any(|shown_severity| shown_severity.matches(severity))
+    }
+}
+
+/// The severity of diagnostics to show inline.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InlineDiagnosticSeverity {
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+impl InlineDiagnosticSeverity {
+    fn matches(self, severity: lsp::DiagnosticSeverity) -> bool {
+        match self {
+            InlineDiagnosticSeverity::Error => severity == lsp::DiagnosticSeverity::ERROR,
+            InlineDiagnosticSeverity::Warning => severity == lsp::DiagnosticSeverity::WARNING,
+            InlineDiagnosticSeverity::Info => severity == lsp::DiagnosticSeverity::INFORMATION,
+            InlineDiagnosticSeverity::Hint => severity == lsp::DiagnosticSeverity::HINT,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct FocusMode {
+    pub dimming: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct DiagnosticsNavigation {
+    pub severity: DiagnosticsNavigationSeverity,
+    pub include_sources: Vec<String>,
+    pub wrap: bool,
+}
+
+/// The least severe diagnostics to stop at when navigating between diagnostics.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticsNavigationSeverity {
+    /// Only stop at errors.
+    Error,
+    /// Stop at errors and warnings.
+    Warning,
+    /// Stop at errors, warnings and information.
+    Info,
+    /// Stop at all diagnostics.
+    Hint,
+}
+
+impl DiagnosticsNavigationSeverity {
+    /// Returns the least severe [`lsp::DiagnosticSeverity`] to navigate to.
+    pub fn max_severity(self) -> lsp::DiagnosticSeverity {
+        match self {
+            DiagnosticsNavigationSeverity::Error => lsp::DiagnosticSeverity::ERROR,
+            DiagnosticsNavigationSeverity::Warning => lsp::DiagnosticSeverity::WARNING,
+            DiagnosticsNavigationSeverity::Info => lsp::DiagnosticSeverity::INFORMATION,
+            DiagnosticsNavigationSeverity::Hint => lsp::DiagnosticSeverity::HINT,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct DocumentHighlightFallback {
+    pub enabled: bool,
+    pub case_sensitive: bool,
+    pub ignore_comments_and_strings: bool,
+}
+
 /// When to show the scrollbar in the editor.
 ///
 /// Default: auto
@@ -161,10 +267,29 @@ pub struct EditorSettingsContent {
     ///
     /// Default: true
     pub cursor_blink: Option<bool>,
+    /// How long the cursor stays shown, and then hidden, when it blinks, in
+    /// milliseconds.
+    ///
+    /// Default: 500
+    pub cursor_blink_interval: Option<u64>,
+    /// The shape of the cursor in the editor: bar, block, underline, or hollow.
+    /// Vim mode has its own shapes for its modes, under `vim.cursor_shape`.
+    ///
+    /// Default: bar
+    pub cursor_shape: Option<CursorShape>,
+    /// The width of the bar cursor, in pixels.
+    ///
+    /// Default: 2
+    pub cursor_bar_width: Option<f32>,
     /// How to highlight the current line in the editor.
     ///
     /// Default: all
     pub current_line_highlight: Option<CurrentLineHighlight>,
+    /// How to highlight the range between the brackets enclosing the cursor,
+    /// in addition to the brackets themselves.
+    ///
+    /// Default: none
+    pub enclosing_bracket_range_highlight: Option<EnclosingBracketRangeHighlight>,
     /// Whether to show the informational hover box when moving the mouse
     /// over symbols in the editor.
     ///
@@ -197,6 +322,16 @@ pub struct EditorSettingsContent {
     pub scrollbar: Option<ScrollbarContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
+    /// Inline diagnostics related settings
+    pub inline_diagnostics: Option<InlineDiagnosticsContent>,
+    /// Focus mode related settings
+    pub focus_mode: Option<FocusModeContent>,
+    /// Diagnostic navigation related settings
+    pub diagnostics_navigation: Option<DiagnosticsNavigationContent>,
+    /// Settings for highlighting the occurrences of the word under the cursor
+    /// in buffers whose language servers don't highlight the occurrences of
+    /// symbols.
+    pub document_highlight_fallback: Option<DocumentHighlightFallbackContent>,
     /// Whether the editor will scroll beyond the last line.
     ///
     /// Default: one_page
@@ -222,6 +357,11 @@ pub struct EditorSettingsContent {
     ///
     /// Default: alt
     pub multi_cursor_modifier: Option<MultiCursorModifier>,
+    /// How many cursors can be created at once, such as by selecting all matches,
+    /// before Zed asks whether to go ahead. 0 means it never asks.
+    ///
+    /// Default: 10000
+    pub multi_cursor_prompt_threshold: Option<usize>,
     /// Hide the values of variables in `private` files, as defined by the
     /// private_files setting. This only changes the visual representation,
     /// the values are still present in the file and can be selected / copied / pasted
@@ -331,10 +471,88 @@ pub struct GutterContent {
     pub folds: Option<bool>,
 }
 
+/// Inline diagnostics related settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct InlineDiagnosticsContent {
+    /// Whether to show the first diagnostic of each line after its code.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// Whether to render inline diagnostics as an error lens: the message spans
+    /// to the end of the line, and the whole line is tinted with the color of
+    /// the diagnostic's severity.
+    ///
+    /// Default: false
+    pub error_lens: Option<bool>,
+    /// The maximum number of characters of a diagnostic message to show.
+    ///
+    /// Default: 120
+    pub max_length: Option<usize>,
+    /// The severities of the diagnostics to show inline.
+    ///
+    /// Default: ["error", "warning"]
+    pub severities: Option<Vec<InlineDiagnosticSeverity>>,
+}
+
+/// Focus mode related settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct FocusModeContent {
+    /// How much the text outside of the focused scope is dimmed in focus mode,
+    /// from 0 (not at all) to 1 (invisible).
+    ///
+    /// Default: 0.6
+    pub dimming: Option<f32>,
+}
+
+/// Diagnostic navigation related settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct DiagnosticsNavigationContent {
+    /// The least severe diagnostics to stop at when going to the next or
+    /// previous diagnostic.
+    ///
+    /// Default: warning
+    pub severity: Option<DiagnosticsNavigationSeverity>,
+    /// The sources of the diagnostics to stop at, such as "clippy" or
+    /// "eslint". When empty, diagnostics from all sources are navigated to.
+    ///
+    /// Default: []
+    pub include_sources: Option<Vec<String>>,
+    /// Whether to wrap around to the start (or end) of the buffer after the
+    /// last (or first) diagnostic.
+    ///
+    /// Default: true
+    pub wrap: Option<bool>,
+}
+
+/// Settings for highlighting the occurrences of the word under the cursor
+/// without a language server.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct DocumentHighlightFallbackContent {
+    /// Whether to highlight the occurrences of the word under the cursor when
+    /// no language server highlights the occurrences of the symbol under it.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// Whether occurrences must match the case of the word under the cursor.
+    ///
+    /// Default: true
+    pub case_sensitive: Option<bool>,
+    /// Whether to leave occurrences in comments and strings unhighlighted.
+    ///
+    /// Default: false
+    pub ignore_comments_and_strings: Option<bool>,
+}
+
 impl EditorSettings {
     pub fn jupyter_enabled(cx: &AppContext) -> bool {
         EditorSettings::get_global(cx).jupyter.enabled
     }
+
+    /// The editor settings for a file, as overridden by the settings files of the
+    /// project directories containing it.
+    pub fn for_file<'a>(file: Option<&Arc<dyn File>>, cx: &'a AppContext) -> &'a Self {
+        EditorSettings::get(file.map(|file| file.as_ref().into()), cx)
+    }
 }
 
 impl Settings for EditorSettings {
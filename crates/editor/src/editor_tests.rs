@@ -2371,6 +2371,60 @@ async fn test_newline_comments(cx: &mut gpui::TestAppContext) {
     "});
 }
 
+#[gpui::test]
+async fn test_newline_markdown_lists(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let language = Arc::new(Language::new(
+        LanguageConfig {
+            markdown_editing: true,
+            ..LanguageConfig::default()
+        },
+        None,
+    ));
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(language), cx));
+
+    cx.set_state(indoc! {"
+        - [x] oneˇ
+    "});
+    cx.update_editor(|e, cx| e.newline(&Newline, cx));
+    cx.assert_editor_state(indoc! {"
+        - [x] one
+        - [ ] ˇ
+    "});
+
+    // An empty item ends the list.
+    cx.update_editor(|e, cx| e.newline(&Newline, cx));
+    cx.assert_editor_state(indoc! {"
+        - [x] one
+        ˇ
+    "});
+
+    // Items inserted in ordered lists renumber the following ones.
+    cx.set_state(indoc! {"
+        1. oneˇ
+        2. two
+        3. three
+    "});
+    cx.update_editor(|e, cx| e.newline(&Newline, cx));
+    cx.assert_editor_state(indoc! {"
+        1. one
+        2. ˇ
+        3. two
+        4. three
+    "});
+
+    cx.set_state(indoc! {"
+        > quoteˇ
+    "});
+    cx.update_editor(|e, cx| e.newline(&Newline, cx));
+    cx.assert_editor_state(indoc! {"
+        > quote
+        > ˇ
+    "});
+}
+
 #[gpui::test]
 fn test_insert_with_old_selections(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -4108,6 +4162,76 @@ fn test_select_all(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_take_code_for_terminal(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    let mut cx = EditorTestContext::new(cx).await;
+    let line = SendSelectionToTerminal { paragraph: false };
+    let paragraph = SendSelectionToTerminal { paragraph: true };
+    let take_code = |cx: &mut EditorTestContext, action: &SendSelectionToTerminal| {
+        cx.update_editor(|editor, cx| {
+            editor
+                .take_code_for_terminal(action, cx)
+                .map(|(code, _)| code)
+        })
+    };
+
+    // Lines are sent one after the other, skipping blank lines.
+    cx.set_state(indoc! {"
+        ˇx = 1
+
+        y = 2
+        z = 3
+    "});
+    assert_eq!(take_code(&mut cx, &line).as_deref(), Some("x = 1"));
+    cx.assert_editor_state(indoc! {"
+        x = 1
+
+        ˇy = 2
+        z = 3
+    "});
+    assert_eq!(take_code(&mut cx, &line).as_deref(), Some("y = 2"));
+    assert_eq!(take_code(&mut cx, &line).as_deref(), Some("z = 3"));
+    cx.assert_editor_state(indoc! {"
+        x = 1
+
+        y = 2
+        z = 3ˇ
+    "});
+
+    // The whole paragraph is sent, wherever the cursor is in it.
+    cx.set_state(indoc! {"
+        def f():
+            ˇreturn 1
+
+        f()
+    "});
+    assert_eq!(
+        take_code(&mut cx, &paragraph).as_deref(),
+        Some("def f():\n    return 1")
+    );
+    cx.assert_editor_state(indoc! {"
+        def f():
+            return 1
+
+        ˇf()
+    "});
+
+    // Selections are sent as they are, without moving the cursor.
+    cx.set_state(indoc! {"
+        «print(1)ˇ»
+        print(2)
+    "});
+    assert_eq!(take_code(&mut cx, &paragraph).as_deref(), Some("print(1)"));
+    cx.assert_editor_state(indoc! {"
+        «print(1)ˇ»
+        print(2)
+    "});
+
+    cx.set_state("x = 1\nˇ\n");
+    assert_eq!(take_code(&mut cx, &line), None);
+}
+
 #[gpui::test]
 fn test_select_line(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -4511,6 +4635,48 @@ async fn test_select_next(cx: &mut gpui::TestAppContext) {
     cx.assert_editor_state("«abcˇ»\n«abcˇ» «abcˇ»\ndefabc\n«abcˇ»");
 }
 
+#[gpui::test]
+async fn test_undo_select_next(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state("abc\nˇabc abc\ndefabc\nabc");
+
+    cx.update_editor(|e, cx| e.select_next(&SelectNext::default(), cx))
+        .unwrap();
+    cx.update_editor(|e, cx| e.select_next(&SelectNext::default(), cx))
+        .unwrap();
+    cx.assert_editor_state("abc\n«abcˇ» «abcˇ»\ndefabc\nabc");
+
+    // Skip the newest match.
+    cx.update_editor(|e, cx| {
+        e.select_next(
+            &SelectNext {
+                replace_newest: true,
+            },
+            cx,
+        )
+    })
+    .unwrap();
+    cx.assert_editor_state("abc\n«abcˇ» abc\ndefabc\n«abcˇ»");
+
+    // Undoing the skip restores the skipped match.
+    cx.update_editor(|e, cx| e.undo_select_next(&UndoSelectNext, cx));
+    cx.assert_editor_state("abc\n«abcˇ» «abcˇ»\ndefabc\nabc");
+
+    cx.update_editor(|e, cx| e.undo_select_next(&UndoSelectNext, cx));
+    cx.assert_editor_state("abc\n«abcˇ» abc\ndefabc\nabc");
+
+    // The selection of the word under the cursor isn't a match to remove.
+    cx.update_editor(|e, cx| e.undo_select_next(&UndoSelectNext, cx));
+    cx.assert_editor_state("abc\n«abcˇ» abc\ndefabc\nabc");
+
+    // Matches are selected again after being removed.
+    cx.update_editor(|e, cx| e.select_next(&SelectNext::default(), cx))
+        .unwrap();
+    cx.assert_editor_state("abc\n«abcˇ» «abcˇ»\ndefabc\nabc");
+}
+
 #[gpui::test]
 async fn test_select_all_matches(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -4523,6 +4689,208 @@ async fn test_select_all_matches(cx: &mut gpui::TestAppContext) {
     cx.assert_editor_state("«abcˇ»\n«abcˇ» «abcˇ»\ndefabc\n«abcˇ»");
 }
 
+#[gpui::test]
+async fn test_select_all_matches_over_prompt_threshold(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                settings.multi_cursor_prompt_threshold = Some(3);
+            });
+        });
+    });
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state("ˇabc abc\nabc abc");
+    cx.update_editor(|e, cx| e.select_all_matches(&SelectAllMatches, cx))
+        .unwrap();
+    assert!(cx.has_pending_prompt());
+    cx.simulate_prompt_answer(1);
+    cx.run_until_parked();
+    cx.assert_editor_state("«abcˇ» abc\nabc abc");
+
+    cx.set_state("ˇabc abc\nabc abc");
+    cx.update_editor(|e, cx| e.select_all_matches(&SelectAllMatches, cx))
+        .unwrap();
+    cx.simulate_prompt_answer(0);
+    cx.run_until_parked();
+    cx.assert_editor_state("«abcˇ» «abcˇ»\n«abcˇ» «abcˇ»");
+}
+
+#[gpui::test]
+async fn test_cursor_shape_setting(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    let mut cx = EditorTestContext::new(cx).await;
+    let set_cursor_shape_setting = |cursor_shape, cx: &mut EditorTestContext| {
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.cursor_shape = Some(cursor_shape);
+                });
+            });
+        });
+    };
+    cx.editor(|editor, _| assert_eq!(editor.cursor_shape(), CursorShape::Bar));
+
+    set_cursor_shape_setting(CursorShape::Block, &mut cx);
+    cx.editor(|editor, _| assert_eq!(editor.cursor_shape(), CursorShape::Block));
+
+    // The shape set by a mode is kept when the setting changes.
+    cx.update_editor(|editor, cx| editor.set_cursor_shape(CursorShape::Underscore, cx));
+    set_cursor_shape_setting(CursorShape::Hollow, &mut cx);
+    cx.editor(|editor, _| assert_eq!(editor.cursor_shape(), CursorShape::Underscore));
+
+    cx.update_editor(|editor, cx| editor.reset_cursor_shape(cx));
+    cx.editor(|editor, _| assert_eq!(editor.cursor_shape(), CursorShape::Hollow));
+    set_cursor_shape_setting(CursorShape::Bar, &mut cx);
+    cx.editor(|editor, _| assert_eq!(editor.cursor_shape(), CursorShape::Bar));
+}
+
+#[gpui::test]
+async fn test_insert_at_many_cursors(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let line_count = BULK_EDIT_BATCH_SIZE * 2 + 1;
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state(&format!("ˇ{}", "a\n".repeat(line_count)));
+    cx.update_editor(|editor, cx| {
+        editor.change_selections(None, cx, |selections| {
+            selections.select_ranges((0..line_count).map(|row| {
+                let point = Point::new(row as u32, 0);
+                point..point
+            }))
+        });
+        editor.handle_input("b", cx);
+    });
+    cx.run_until_parked();
+    cx.update_editor(|editor, cx| {
+        assert_eq!(editor.text(cx), "ba\n".repeat(line_count));
+        assert_eq!(editor.selections.count(), line_count);
+        assert_eq!(
+            editor.selections.newest::<Point>(cx).head(),
+            Point::new(line_count as u32 - 1, 1)
+        );
+
+        editor.undo(&Undo, cx);
+        assert_eq!(editor.text(cx), "a\n".repeat(line_count));
+    });
+}
+
+#[gpui::test]
+async fn test_input_while_inserting_at_many_cursors(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let line_count = BULK_EDIT_BATCH_SIZE * 2 + 1;
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state(&format!("ˇ{}", "a\n".repeat(line_count)));
+    cx.update_editor(|editor, cx| {
+        editor.change_selections(None, cx, |selections| {
+            selections.select_ranges((0..line_count).map(|row| {
+                let point = Point::new(row as u32, 0);
+                point..point
+            }))
+        });
+        editor.handle_input("b", cx);
+        // Typed while the first edit is still applied.
+        editor.handle_input("c", cx);
+        editor.insert("d", cx);
+    });
+    cx.run_until_parked();
+    cx.update_editor(|editor, cx| {
+        assert_eq!(editor.text(cx), "bcda\n".repeat(line_count));
+        assert_eq!(editor.selections.count(), line_count);
+        assert_eq!(
+            editor.selections.newest::<Point>(cx).head(),
+            Point::new(line_count as u32 - 1, 3)
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_edit_actions_while_inserting_at_many_cursors(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let line_count = BULK_EDIT_BATCH_SIZE * 2 + 1;
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state(&format!("ˇ{}", "a\n".repeat(line_count)));
+    cx.update_editor(|editor, cx| {
+        editor.change_selections(None, cx, |selections| {
+            selections.select_ranges((0..line_count).map(|row| {
+                let point = Point::new(row as u32, 0);
+                point..point
+            }))
+        });
+        editor.handle_input("b", cx);
+        // Dispatched while the first edit is still applied. The backspace is also
+        // made in batches, so the input and the delete wait for it in turn.
+        cx.dispatch_action(Backspace.boxed_clone());
+    });
+    cx.update_editor(|editor, cx| {
+        assert!(editor.bulk_edit.is_some());
+        editor.handle_input("c", cx);
+        cx.dispatch_action(Delete.boxed_clone());
+    });
+    cx.run_until_parked();
+    cx.update_editor(|editor, cx| {
+        assert!(editor.bulk_edit.is_none());
+        assert_eq!(editor.text(cx), "c\n".repeat(line_count));
+        assert_eq!(editor.selections.count(), line_count);
+        assert_eq!(
+            editor.selections.newest::<Point>(cx).head(),
+            Point::new(line_count as u32 - 1, 1)
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_selections_made_while_inserting_at_many_cursors(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let line_count = BULK_EDIT_BATCH_SIZE * 2 + 1;
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state(&format!("ˇ{}", "a\n".repeat(line_count)));
+    cx.update_editor(|editor, cx| {
+        editor.change_selections(None, cx, |selections| {
+            selections.select_ranges((0..line_count).map(|row| {
+                let point = Point::new(row as u32, 0);
+                point..point
+            }))
+        });
+        editor.handle_input("b", cx);
+    });
+    cx.run_until_parked();
+    cx.update_editor(|editor, cx| {
+        assert!(editor.bulk_edit.is_none());
+        assert_eq!(editor.selections.count(), line_count);
+        editor.handle_input("c", cx);
+        // Moved while the second edit is still applied, which keeps the cursors
+        // that were moved and puts the others after the inserted text.
+        editor.change_selections(None, cx, |selections| {
+            selections.move_cursors_with(|map, head, _| {
+                if head.row() == DisplayRow(0) {
+                    (map.max_point(), SelectionGoal::None)
+                } else {
+                    (head, SelectionGoal::None)
+                }
+            })
+        });
+    });
+    cx.run_until_parked();
+    cx.update_editor(|editor, cx| {
+        assert_eq!(editor.text(cx), "bca\n".repeat(line_count));
+        let heads = editor
+            .selections
+            .all::<Point>(cx)
+            .into_iter()
+            .map(|selection| selection.head())
+            .collect::<Vec<_>>();
+        assert_eq!(heads.len(), line_count);
+        assert_eq!(heads[0], Point::new(1, 2));
+        assert_eq!(heads[line_count - 2], Point::new(line_count as u32 - 1, 2));
+        assert_eq!(heads[line_count - 1], Point::new(line_count as u32, 0));
+    });
+}
+
 #[gpui::test]
 async fn test_select_next_with_multiple_carets(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -9123,66 +9491,201 @@ async fn go_to_prev_overlapping_diagnostic(
 }
 
 #[gpui::test]
-async fn test_diagnostics_with_links(cx: &mut TestAppContext) {
+async fn test_go_to_diagnostic_by_severity_and_source(
+    executor: BackgroundExecutor,
+    cx: &mut gpui::TestAppContext,
+) {
     init_test(cx, |_| {});
 
     let mut cx = EditorTestContext::new(cx).await;
+    let project = cx.update_editor(|editor, _| editor.project.clone().unwrap());
 
     cx.set_state(indoc! {"
-        fn func(abˇc def: i32) -> u32 {
+        ˇfn func(abc def: i32) -> u32 {
         }
     "});
-    let project = cx.update_editor(|editor, _| editor.project.clone().unwrap());
 
     cx.update(|cx| {
         project.update(cx, |project, cx| {
-            project.update_diagnostics(
-                LanguageServerId(0),
-                lsp::PublishDiagnosticsParams {
-                    uri: lsp::Url::from_file_path("/root/file").unwrap(),
-                    version: None,
-                    diagnostics: vec![lsp::Diagnostic {
-                        range: lsp::Range::new(lsp::Position::new(0, 8), lsp::Position::new(0, 12)),
-                        severity: Some(lsp::DiagnosticSeverity::ERROR),
-                        message: "we've had problems with <https://link.one>, and <https://link.two> is broken".to_string(),
-                        ..Default::default()
-                    }],
-                },
-                &[],
-                cx,
-            )
-        })
-    }).unwrap();
-    cx.run_until_parked();
-    cx.update_editor(|editor, cx| hover_popover::hover(editor, &Default::default(), cx));
-    cx.run_until_parked();
-    cx.update_editor(|editor, _| assert!(editor.hover_state.diagnostic_popover.is_some()))
-}
-
-#[gpui::test]
-async fn go_to_hunk(executor: BackgroundExecutor, cx: &mut gpui::TestAppContext) {
-    init_test(cx, |_| {});
-
-    let mut cx = EditorTestContext::new(cx).await;
-
-    let diff_base = r#"
-        use some::mod;
-
-        const A: u32 = 42;
-
-        fn main() {
-            println!("hello");
-
-            println!("world");
-        }
-        "#
-    .unindent();
-
-    // Edits are modified, removed, modified, added
-    cx.set_state(
-        &r#"
-        use some::modified;
-
+            project
+                .update_diagnostics(
+                    LanguageServerId(0),
+                    lsp::PublishDiagnosticsParams {
+                        uri: lsp::Url::from_file_path("/root/file").unwrap(),
+                        version: None,
+                        diagnostics: vec![
+                            lsp::Diagnostic {
+                                range: lsp::Range::new(
+                                    lsp::Position::new(0, 3),
+                                    lsp::Position::new(0, 7),
+                                ),
+                                severity: Some(lsp::DiagnosticSeverity::WARNING),
+                                source: Some("clippy".to_string()),
+                                ..Default::default()
+                            },
+                            lsp::Diagnostic {
+                                range: lsp::Range::new(
+                                    lsp::Position::new(0, 8),
+                                    lsp::Position::new(0, 11),
+                                ),
+                                severity: Some(lsp::DiagnosticSeverity::ERROR),
+                                source: Some("rustc".to_string()),
+                                ..Default::default()
+                            },
+                            lsp::Diagnostic {
+                                range: lsp::Range::new(
+                                    lsp::Position::new(0, 12),
+                                    lsp::Position::new(0, 15),
+                                ),
+                                severity: Some(lsp::DiagnosticSeverity::HINT),
+                                source: Some("rust-analyzer".to_string()),
+                                ..Default::default()
+                            },
+                        ],
+                    },
+                    &[],
+                    cx,
+                )
+                .unwrap()
+        });
+    });
+    executor.run_until_parked();
+
+    // By default, hints are skipped and navigation wraps around.
+    cx.update_editor(|editor, cx| editor.go_to_diagnostic(&GoToDiagnostic, cx));
+    cx.assert_editor_state(indoc! {"
+        fn ˇfunc(abc def: i32) -> u32 {
+        }
+    "});
+    cx.update_editor(|editor, cx| editor.go_to_diagnostic(&GoToDiagnostic, cx));
+    cx.assert_editor_state(indoc! {"
+        fn func(ˇabc def: i32) -> u32 {
+        }
+    "});
+    cx.update_editor(|editor, cx| editor.go_to_diagnostic(&GoToDiagnostic, cx));
+    cx.assert_editor_state(indoc! {"
+        fn ˇfunc(abc def: i32) -> u32 {
+        }
+    "});
+
+    cx.update_editor(|editor, cx| editor.go_to_next_error(&GoToNextError, cx));
+    cx.assert_editor_state(indoc! {"
+        fn func(ˇabc def: i32) -> u32 {
+        }
+    "});
+    cx.update_editor(|editor, cx| editor.go_to_prev_warning(&GoToPrevWarning, cx));
+    cx.assert_editor_state(indoc! {"
+        fn ˇfunc(abc def: i32) -> u32 {
+        }
+    "});
+
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                settings.diagnostics_navigation =
+                    Some(editor_settings::DiagnosticsNavigationContent {
+                        severity: Some(editor_settings::DiagnosticsNavigationSeverity::Hint),
+                        include_sources: None,
+                        wrap: Some(false),
+                    });
+            });
+        });
+    });
+
+    cx.update_editor(|editor, cx| {
+        editor.go_to_next_diagnostic_from_source(
+            &GoToNextDiagnosticFromSource {
+                source: "rust-analyzer".to_string(),
+            },
+            cx,
+        )
+    });
+    cx.assert_editor_state(indoc! {"
+        fn func(abc ˇdef: i32) -> u32 {
+        }
+    "});
+
+    // Without wrapping, navigation stops at the last diagnostic.
+    cx.update_editor(|editor, cx| editor.go_to_diagnostic(&GoToDiagnostic, cx));
+    cx.assert_editor_state(indoc! {"
+        fn func(abc ˇdef: i32) -> u32 {
+        }
+    "});
+    cx.update_editor(|editor, cx| {
+        editor.go_to_prev_diagnostic_from_source(
+            &GoToPrevDiagnosticFromSource {
+                source: "clippy".to_string(),
+            },
+            cx,
+        )
+    });
+    cx.assert_editor_state(indoc! {"
+        fn ˇfunc(abc def: i32) -> u32 {
+        }
+    "});
+}
+
+#[gpui::test]
+async fn test_diagnostics_with_links(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    cx.set_state(indoc! {"
+        fn func(abˇc def: i32) -> u32 {
+        }
+    "});
+    let project = cx.update_editor(|editor, _| editor.project.clone().unwrap());
+
+    cx.update(|cx| {
+        project.update(cx, |project, cx| {
+            project.update_diagnostics(
+                LanguageServerId(0),
+                lsp::PublishDiagnosticsParams {
+                    uri: lsp::Url::from_file_path("/root/file").unwrap(),
+                    version: None,
+                    diagnostics: vec![lsp::Diagnostic {
+                        range: lsp::Range::new(lsp::Position::new(0, 8), lsp::Position::new(0, 12)),
+                        severity: Some(lsp::DiagnosticSeverity::ERROR),
+                        message: "we've had problems with <https://link.one>, and <https://link.two> is broken".to_string(),
+                        ..Default::default()
+                    }],
+                },
+                &[],
+                cx,
+            )
+        })
+    }).unwrap();
+    cx.run_until_parked();
+    cx.update_editor(|editor, cx| hover_popover::hover(editor, &Default::default(), cx));
+    cx.run_until_parked();
+    cx.update_editor(|editor, _| assert!(editor.hover_state.diagnostic_popover.is_some()))
+}
+
+#[gpui::test]
+async fn go_to_hunk(executor: BackgroundExecutor, cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    let diff_base = r#"
+        use some::mod;
+
+        const A: u32 = 42;
+
+        fn main() {
+            println!("hello");
+
+            println!("world");
+        }
+        "#
+    .unindent();
+
+    // Edits are modified, removed, modified, added
+    cx.set_state(
+        &r#"
+        use some::modified;
+
         ˇ
         fn main() {
             println!("hello there");
@@ -9372,6 +9875,71 @@ async fn test_move_to_enclosing_bracket(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_go_to_matching_bracket(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new_typescript(Default::default(), cx).await;
+    let mut assert = |before, after| {
+        let _state_context = cx.set_state(before);
+        cx.update_editor(|editor, cx| editor.go_to_matching_bracket(&GoToMatchingBracket, cx));
+        cx.assert_editor_state(after);
+    };
+
+    // On a bracket jumps onto the matching bracket
+    assert("console.logˇ(var);", "console.log(varˇ);");
+    assert("console.log(varˇ);", "console.logˇ(var);");
+
+    // After a bracket jumps onto the matching bracket
+    assert("console.log(var)ˇ;", "console.logˇ(var);");
+    assert("console.log(ˇvar);", "console.log(varˇ);");
+
+    // Being on a bracket takes precedence over being after one
+    assert("console.log([1]ˇ);", "console.logˇ([1]);");
+
+    // Inside a pair jumps to its closing bracket
+    assert("console.log(vˇar);", "console.log(varˇ);");
+    assert(
+        "console.log('foo', [1, ˇ2, 3]);",
+        "console.log('foo', [1, 2, 3ˇ]);",
+    );
+}
+
+#[gpui::test]
+async fn test_select_enclosing_bracket_contents(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new_typescript(Default::default(), cx).await;
+    cx.set_state("console.log('foo', [1, ˇ2, 3]);");
+
+    cx.update_editor(|editor, cx| {
+        editor.select_enclosing_bracket_contents(&SelectEnclosingBracketContents, cx)
+    });
+    cx.assert_editor_state("console.log('foo', [«1, 2, 3ˇ»]);");
+
+    cx.update_editor(|editor, cx| {
+        editor.select_enclosing_bracket_contents(&SelectEnclosingBracketContents, cx)
+    });
+    cx.assert_editor_state("console.log('foo', «[1, 2, 3]ˇ»);");
+
+    cx.update_editor(|editor, cx| {
+        editor.select_enclosing_bracket_contents(&SelectEnclosingBracketContents, cx)
+    });
+    cx.assert_editor_state("console.log(«'foo', [1, 2, 3]ˇ»);");
+
+    cx.update_editor(|editor, cx| {
+        editor.select_enclosing_bracket_contents(&SelectEnclosingBracketContents, cx)
+    });
+    cx.assert_editor_state("console.log«('foo', [1, 2, 3])ˇ»;");
+
+    // Empty pairs are selected whole
+    cx.set_state("console.log(ˇ);");
+    cx.update_editor(|editor, cx| {
+        editor.select_enclosing_bracket_contents(&SelectEnclosingBracketContents, cx)
+    });
+    cx.assert_editor_state("console.log«()ˇ»;");
+}
+
 #[gpui::test]
 async fn test_on_type_formatting_not_triggered(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -13411,6 +13979,475 @@ async fn test_goto_definition_with_find_all_references_fallback(cx: &mut gpui::T
     });
 }
 
+#[gpui::test]
+async fn test_breakpoints_and_debug_values(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/a", json!({ "main.rs": "one\ntwo\nthree\n" }))
+        .await;
+    let project = Project::test(fs, ["/a".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/a/main.rs", cx)
+        })
+        .await
+        .unwrap();
+    let multibuffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+    let (editor, cx) = cx.add_window_view(|cx| {
+        Editor::for_multibuffer(multibuffer, Some(project.clone()), true, cx)
+    });
+
+    editor.update(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([
+                Point::new(0, 1)..Point::new(0, 1),
+                Point::new(2, 0)..Point::new(2, 0),
+            ])
+        });
+        editor.toggle_breakpoint(&ToggleBreakpoint, cx);
+        assert_eq!(
+            editor.breakpoint_rows(cx),
+            [MultiBufferRow(0), MultiBufferRow(2)]
+        );
+
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(2, 2)..Point::new(2, 2)])
+        });
+        editor.toggle_breakpoint(&ToggleBreakpoint, cx);
+        assert_eq!(editor.breakpoint_rows(cx), [MultiBufferRow(0)]);
+    });
+    project.update(cx, |project, cx| {
+        let breakpoints = project
+            .breakpoint_store()
+            .read(cx)
+            .breakpoints()
+            .map(|(path, rows)| (path.path.to_path_buf(), rows.to_vec()))
+            .collect::<Vec<_>>();
+        assert_eq!(breakpoints, [(PathBuf::from("main.rs"), vec![0])]);
+    });
+
+    editor.update(cx, |editor, cx| {
+        let position = editor
+            .buffer()
+            .read(cx)
+            .snapshot(cx)
+            .anchor_after(Point::new(0, 3));
+        editor.set_debug_values(vec![(position, "= 1".into())], cx);
+        assert_eq!(editor.display_text(cx), "one = 1\ntwo\nthree\n");
+
+        editor.clear_debug_values(cx);
+        assert_eq!(editor.display_text(cx), "one\ntwo\nthree\n");
+    });
+}
+
+#[gpui::test]
+fn test_decorations(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let editor = cx.add_window(|cx| {
+        let buffer = MultiBuffer::build_simple("one\ntwo\nthree\n", cx);
+        build_editor(buffer, cx)
+    });
+
+    _ = editor.update(cx, |editor, cx| {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        editor.set_decorations(
+            "coverage",
+            vec![
+                Decoration::Highlight {
+                    range: snapshot.anchor_before(Point::new(1, 0))
+                        ..snapshot.anchor_after(Point::new(1, 3)),
+                    severity: DecorationSeverity::Success,
+                },
+                Decoration::Inlay {
+                    position: snapshot.anchor_after(Point::new(1, 3)),
+                    text: "3 hits".into(),
+                    severity: DecorationSeverity::Hint,
+                },
+                Decoration::GutterIcon {
+                    position: snapshot.anchor_after(Point::new(1, 0)),
+                    severity: DecorationSeverity::Success,
+                    tooltip: Some("covered".into()),
+                },
+            ],
+            cx,
+        );
+        editor.set_decorations(
+            "lints",
+            vec![Decoration::GutterIcon {
+                position: snapshot.anchor_after(Point::new(1, 0)),
+                severity: DecorationSeverity::Warning,
+                tooltip: Some("unused".into()),
+            }],
+            cx,
+        );
+        assert_eq!(editor.display_text(cx), "one\ntwo 3 hits\nthree\n");
+        assert_eq!(
+            editor.decoration_gutter_icons(cx),
+            [(
+                MultiBufferRow(1),
+                DecorationSeverity::Warning,
+                vec!["covered".into(), "unused".into()]
+            )]
+        );
+
+        // Decorations stay attached to their text as the buffer is edited.
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(0, 0)..Point::new(0, 0)])
+        });
+        editor.insert("zero\n", cx);
+        assert_eq!(editor.display_text(cx), "zero\none\ntwo 3 hits\nthree\n");
+        assert_eq!(
+            editor
+                .decoration_gutter_icons(cx)
+                .into_iter()
+                .map(|(row, severity, _)| (row, severity))
+                .collect::<Vec<_>>(),
+            [(MultiBufferRow(2), DecorationSeverity::Warning)]
+        );
+
+        editor.clear_decorations("lints", cx);
+        assert_eq!(
+            editor
+                .decoration_gutter_icons(cx)
+                .into_iter()
+                .map(|(row, severity, _)| (row, severity))
+                .collect::<Vec<_>>(),
+            [(MultiBufferRow(2), DecorationSeverity::Success)]
+        );
+        assert_eq!(
+            editor.decoration_keys().cloned().collect::<Vec<_>>(),
+            [SharedString::from("coverage")]
+        );
+
+        editor.set_decorations("coverage", Vec::new(), cx);
+        assert_eq!(editor.display_text(cx), "zero\none\ntwo\nthree\n");
+        assert!(editor.decoration_gutter_icons(cx).is_empty());
+        assert_eq!(editor.decoration_keys().count(), 0);
+    });
+}
+
+#[gpui::test]
+fn test_focus_mode(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let editor = cx.add_window(|cx| {
+        let buffer = MultiBuffer::build_simple("one\ntwo\n\nthree\nfour\n\nfive", cx);
+        build_editor(buffer, cx)
+    });
+
+    fn dimmed_ranges(editor: &Editor, cx: &AppContext) -> Vec<Range<Point>> {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        editor
+            .text_highlights::<focus_mode::FocusModeDimming>(cx)
+            .map(|(_, ranges)| {
+                ranges
+                    .iter()
+                    .map(|range| range.start.to_point(&snapshot)..range.end.to_point(&snapshot))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    _ = editor.update(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(3, 1)..Point::new(3, 1)])
+        });
+        assert!(dimmed_ranges(editor, cx).is_empty());
+
+        // Without an outline, the paragraph containing the cursor is focused.
+        editor.toggle_focus_mode(&ToggleFocusMode, cx);
+        assert_eq!(
+            dimmed_ranges(editor, cx),
+            [
+                Point::new(0, 0)..Point::new(3, 0),
+                Point::new(4, 4)..Point::new(6, 4)
+            ]
+        );
+
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(6, 2)..Point::new(6, 2)])
+        });
+        assert_eq!(
+            dimmed_ranges(editor, cx),
+            [Point::new(0, 0)..Point::new(6, 0)]
+        );
+
+        // A selection focuses the lines it spans.
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(1, 1)..Point::new(3, 2)])
+        });
+        assert_eq!(
+            dimmed_ranges(editor, cx),
+            [
+                Point::new(0, 0)..Point::new(1, 0),
+                Point::new(3, 5)..Point::new(6, 4)
+            ]
+        );
+
+        editor.toggle_focus_mode(&ToggleFocusMode, cx);
+        assert!(dimmed_ranges(editor, cx).is_empty());
+    });
+}
+
+#[gpui::test]
+async fn test_stage_and_unstage_hunks(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let head_text = "one\ntwo\nthree\nfour\n".to_string();
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/a",
+        json!({
+            ".git": {},
+            "main.rs": "one\nTWO\nthree\nFOUR\nfive\n",
+        }),
+    )
+    .await;
+    fs.set_head_for_repo(
+        Path::new("/a/.git"),
+        &[(Path::new("main.rs"), head_text.clone())],
+    );
+    fs.set_index_for_repo(
+        Path::new("/a/.git"),
+        &[(Path::new("main.rs"), head_text.clone())],
+    );
+    let project = Project::test(fs, ["/a".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/a/main.rs", cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    let multibuffer = cx.new_model(|cx| MultiBuffer::singleton(buffer.clone(), cx));
+    let (editor, cx) = cx.add_window_view(|cx| {
+        Editor::for_multibuffer(multibuffer, Some(project.clone()), true, cx)
+    });
+    let diff_base = |cx: &mut VisualTestContext| {
+        buffer.read_with(cx, |buffer, _| {
+            buffer.diff_base().map(|diff_base| diff_base.to_string())
+        })
+    };
+    assert_eq!(diff_base(cx).as_deref(), Some(head_text.as_str()));
+
+    // Staging a hunk writes its changes to the index, which is the diff base.
+    editor.update(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(1, 0)..Point::new(1, 0)])
+        });
+        editor.stage_selected_hunks(&StageSelectedHunks, cx);
+    });
+    cx.executor().run_until_parked();
+    assert_eq!(diff_base(cx).as_deref(), Some("one\nTWO\nthree\nfour\n"));
+
+    // Unstaging the lines restores their content in HEAD.
+    editor.update(cx, |editor, cx| {
+        editor.unstage_selected_hunks(&UnstageSelectedHunks, cx);
+    });
+    cx.executor().run_until_parked();
+    assert_eq!(diff_base(cx).as_deref(), Some(head_text.as_str()));
+
+    editor.update(cx, |editor, cx| {
+        editor.select_all(&SelectAll, cx);
+        editor.stage_selected_hunks(&StageSelectedHunks, cx);
+    });
+    cx.executor().run_until_parked();
+    assert_eq!(
+        diff_base(cx).as_deref(),
+        Some("one\nTWO\nthree\nFOUR\nfive\n")
+    );
+
+    // Only the staged changes intersecting the selected lines are unstaged.
+    editor.update(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(3, 1)..Point::new(3, 1)])
+        });
+        editor.unstage_selected_hunks(&UnstageSelectedHunks, cx);
+    });
+    cx.executor().run_until_parked();
+    assert_eq!(diff_base(cx).as_deref(), Some("one\nTWO\nthree\nfour\n"));
+}
+
+#[gpui::test]
+async fn test_resolve_merge_conflicts(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state(indoc! {"
+        ˇfn main() {
+        <<<<<<< HEAD
+            one();
+        =======
+            two();
+        >>>>>>> feature
+        }
+
+        <<<<<<< HEAD
+        const A: u32 = 1;
+        =======
+        const A: u32 = 2;
+        >>>>>>> feature
+    "});
+    cx.executor().advance_clock(Duration::from_millis(200));
+    cx.run_until_parked();
+    cx.update_editor(|editor, _| assert_eq!(editor.merge_conflicts.count(), 2));
+
+    cx.update_editor(|editor, cx| editor.go_to_next_conflict(&GoToNextConflict, cx));
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+        ˇ<<<<<<< HEAD
+            one();
+        =======
+            two();
+        >>>>>>> feature
+        }
+
+        <<<<<<< HEAD
+        const A: u32 = 1;
+        =======
+        const A: u32 = 2;
+        >>>>>>> feature
+    "});
+
+    cx.update_editor(|editor, cx| editor.accept_conflict_both(&AcceptConflictBoth, cx));
+    cx.executor().advance_clock(Duration::from_millis(200));
+    cx.run_until_parked();
+    cx.update_editor(|editor, _| assert_eq!(editor.merge_conflicts.count(), 1));
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+        ˇ    one();
+            two();
+        }
+
+        <<<<<<< HEAD
+        const A: u32 = 1;
+        =======
+        const A: u32 = 2;
+        >>>>>>> feature
+    "});
+
+    // Navigation wraps around to the first conflict.
+    cx.update_editor(|editor, cx| {
+        editor.go_to_prev_conflict(&GoToPrevConflict, cx);
+        editor.accept_conflict_theirs(&AcceptConflictTheirs, cx);
+    });
+    cx.executor().advance_clock(Duration::from_millis(200));
+    cx.run_until_parked();
+    cx.update_editor(|editor, _| assert_eq!(editor.merge_conflicts.count(), 0));
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+            one();
+            two();
+        }
+
+        ˇconst A: u32 = 2;
+    "});
+
+    // Resolving a conflict is undone in a single step.
+    cx.update_editor(|editor, cx| editor.undo(&Undo, cx));
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+            one();
+            two();
+        }
+
+        ˇ<<<<<<< HEAD
+        const A: u32 = 1;
+        =======
+        const A: u32 = 2;
+        >>>>>>> feature
+    "});
+}
+
+#[gpui::test]
+async fn test_highlight_duplicate_lines(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state(indoc! {"
+        ˇ[en]
+        greeting = \"Hello\"
+        farewell = \"Bye\"
+        greeting = \"Hello\"
+        farewell=\"Bye\"
+    "});
+    let group_count = |cx: &mut EditorTestContext| {
+        cx.update_editor(|editor, _| {
+            editor
+                .duplicate_lines
+                .as_ref()
+                .map(|duplicate_lines| duplicate_lines.group_count())
+        })
+    };
+
+    cx.update_editor(|editor, cx| {
+        editor.highlight_duplicate_lines(&HighlightDuplicateLines::default(), cx)
+    });
+    cx.executor().advance_clock(Duration::from_millis(400));
+    cx.run_until_parked();
+    assert_eq!(group_count(&mut cx), Some(1));
+
+    // Lines differing by their whitespace are duplicates when whitespace is ignored.
+    cx.update_editor(|editor, cx| {
+        editor.highlight_duplicate_lines(
+            &HighlightDuplicateLines {
+                ignore_whitespace: true,
+            },
+            cx,
+        )
+    });
+    cx.executor().advance_clock(Duration::from_millis(400));
+    cx.run_until_parked();
+    assert_eq!(group_count(&mut cx), Some(2));
+
+    cx.update_editor(|editor, cx| editor.go_to_next_duplicate_lines(&GoToNextDuplicateLines, cx));
+    cx.assert_editor_state(indoc! {"
+        [en]
+        ˇgreeting = \"Hello\"
+        farewell = \"Bye\"
+        greeting = \"Hello\"
+        farewell=\"Bye\"
+    "});
+    cx.update_editor(|editor, cx| editor.go_to_next_duplicate_lines(&GoToNextDuplicateLines, cx));
+    cx.assert_editor_state(indoc! {"
+        [en]
+        greeting = \"Hello\"
+        ˇfarewell = \"Bye\"
+        greeting = \"Hello\"
+        farewell=\"Bye\"
+    "});
+    // Moving between groups wraps around.
+    cx.update_editor(|editor, cx| editor.go_to_next_duplicate_lines(&GoToNextDuplicateLines, cx));
+    cx.assert_editor_state(indoc! {"
+        [en]
+        ˇgreeting = \"Hello\"
+        farewell = \"Bye\"
+        greeting = \"Hello\"
+        farewell=\"Bye\"
+    "});
+
+    // The duplicates are found again as the buffer is edited.
+    cx.update_editor(|editor, cx| {
+        editor.move_to_end_of_line(&MoveToEndOfLine::default(), cx);
+        editor.handle_input("!", cx);
+    });
+    cx.executor().advance_clock(Duration::from_millis(400));
+    cx.run_until_parked();
+    assert_eq!(group_count(&mut cx), Some(1));
+
+    cx.update_editor(|editor, cx| {
+        editor.highlight_duplicate_lines(
+            &HighlightDuplicateLines {
+                ignore_whitespace: true,
+            },
+            cx,
+        )
+    });
+    assert_eq!(group_count(&mut cx), None);
+}
+
 fn empty_range(row: usize, column: usize) -> Range<DisplayPoint> {
     let point = DisplayPoint::new(DisplayRow(row as u32), column as u32);
     point..point
@@ -1,11 +1,12 @@
 use crate::{
     blame_entry_tooltip::{blame_entry_relative_timestamp, BlameEntryTooltip},
+    diagnostic_style,
     display_map::{
         Block, BlockContext, BlockStyle, DisplaySnapshot, HighlightedChunk, ToDisplayPoint,
     },
     editor_settings::{
-        CurrentLineHighlight, DoubleClickInMultibuffer, MultiCursorModifier, ScrollBeyondLastLine,
-        ShowScrollbar,
+        CurrentLineHighlight, DoubleClickInMultibuffer, InlineDiagnostics, MultiCursorModifier,
+        ScrollBeyondLastLine, ShowScrollbar,
     },
     git::{
         blame::{CommitDetails, GitBlame},
@@ -19,8 +20,8 @@ use crate::{
     items::BufferSearchHighlights,
     mouse_context_menu::{self, MenuPosition, MouseContextMenu},
     scroll::scroll_amount::ScrollAmount,
-    BlockId, CodeActionsMenu, CursorShape, CustomBlockId, DisplayPoint, DisplayRow,
-    DocumentHighlightRead, DocumentHighlightWrite, Editor, EditorMode, EditorSettings,
+    BlockId, CodeActionsMenu, CursorShape, CustomBlockId, DecorationSeverity, DisplayPoint,
+    DisplayRow, DocumentHighlightRead, DocumentHighlightWrite, Editor, EditorMode, EditorSettings,
     EditorSnapshot, EditorStyle, ExpandExcerpts, FocusedBlock, GutterDimensions, HalfPageDown,
     HalfPageUp, HandleInput, HoveredCursor, HoveredHunk, LineDown, LineUp, OpenExcerpts, PageDown,
     PageUp, Point, RangeToAnchorExt, RowExt, RowRangeExt, SelectPhase, Selection, SoftWrap,
@@ -46,7 +47,7 @@ use language::{
         IndentGuideBackgroundColoring, IndentGuideColoring, IndentGuideSettings,
         ShowWhitespaceSetting,
     },
-    ChunkRendererContext,
+    ChunkRendererContext, Diagnostic,
 };
 use lsp::DiagnosticSeverity;
 use multi_buffer::{Anchor, MultiBufferPoint, MultiBufferRow};
@@ -65,6 +66,7 @@ use std::{
     ops::{Deref, Range},
     rc::Rc,
     sync::Arc,
+    time::Instant,
 };
 use sum_tree::Bias;
 use theme::{ActiveTheme, PlayerColor};
@@ -74,6 +76,11 @@ use util::RangeExt;
 use util::ResultExt;
 use workspace::{item::Item, Workspace};
 
+const DEFAULT_CURSOR_BAR_WIDTH: Pixels = px(2.);
+const MIN_CURSOR_BAR_WIDTH: f32 = 1.;
+const MAX_CURSOR_BAR_WIDTH: f32 = 10.;
+const HIGH_CONTRAST_CURSOR_BAR_WIDTH: f32 = 4.;
+
 struct SelectionLayout {
     head: DisplayPoint,
     cursor_shape: CursorShape,
@@ -170,6 +177,7 @@ impl EditorElement {
 
         crate::rust_analyzer_ext::apply_related_actions(view, cx);
         crate::clangd_ext::apply_related_actions(view, cx);
+        crate::markdown_ext::apply_related_actions(view, cx);
         register_action(view, cx, Editor::move_left);
         register_action(view, cx, Editor::move_right);
         register_action(view, cx, Editor::move_down);
@@ -181,46 +189,46 @@ impl EditorElement {
         register_action(view, cx, Editor::select_page_down);
         register_action(view, cx, Editor::select_page_up);
         register_action(view, cx, Editor::cancel);
-        register_action(view, cx, Editor::newline);
-        register_action(view, cx, Editor::newline_above);
-        register_action(view, cx, Editor::newline_below);
-        register_action(view, cx, Editor::backspace);
-        register_action(view, cx, Editor::delete);
-        register_action(view, cx, Editor::tab);
-        register_action(view, cx, Editor::tab_prev);
-        register_action(view, cx, Editor::indent);
-        register_action(view, cx, Editor::outdent);
-        register_action(view, cx, Editor::delete_line);
-        register_action(view, cx, Editor::join_lines);
-        register_action(view, cx, Editor::sort_lines_case_sensitive);
-        register_action(view, cx, Editor::sort_lines_case_insensitive);
-        register_action(view, cx, Editor::reverse_lines);
-        register_action(view, cx, Editor::shuffle_lines);
-        register_action(view, cx, Editor::convert_to_upper_case);
-        register_action(view, cx, Editor::convert_to_lower_case);
-        register_action(view, cx, Editor::convert_to_title_case);
-        register_action(view, cx, Editor::convert_to_snake_case);
-        register_action(view, cx, Editor::convert_to_kebab_case);
-        register_action(view, cx, Editor::convert_to_upper_camel_case);
-        register_action(view, cx, Editor::convert_to_lower_camel_case);
-        register_action(view, cx, Editor::convert_to_opposite_case);
-        register_action(view, cx, Editor::delete_to_previous_word_start);
-        register_action(view, cx, Editor::delete_to_previous_subword_start);
-        register_action(view, cx, Editor::delete_to_next_word_end);
-        register_action(view, cx, Editor::delete_to_next_subword_end);
-        register_action(view, cx, Editor::delete_to_beginning_of_line);
-        register_action(view, cx, Editor::delete_to_end_of_line);
-        register_action(view, cx, Editor::cut_to_end_of_line);
-        register_action(view, cx, Editor::duplicate_line_up);
-        register_action(view, cx, Editor::duplicate_line_down);
-        register_action(view, cx, Editor::move_line_up);
-        register_action(view, cx, Editor::move_line_down);
-        register_action(view, cx, Editor::transpose);
-        register_action(view, cx, Editor::cut);
+        register_edit_action(view, cx, Editor::newline);
+        register_edit_action(view, cx, Editor::newline_above);
+        register_edit_action(view, cx, Editor::newline_below);
+        register_edit_action(view, cx, Editor::backspace);
+        register_edit_action(view, cx, Editor::delete);
+        register_edit_action(view, cx, Editor::tab);
+        register_edit_action(view, cx, Editor::tab_prev);
+        register_edit_action(view, cx, Editor::indent);
+        register_edit_action(view, cx, Editor::outdent);
+        register_edit_action(view, cx, Editor::delete_line);
+        register_edit_action(view, cx, Editor::join_lines);
+        register_edit_action(view, cx, Editor::sort_lines_case_sensitive);
+        register_edit_action(view, cx, Editor::sort_lines_case_insensitive);
+        register_edit_action(view, cx, Editor::reverse_lines);
+        register_edit_action(view, cx, Editor::shuffle_lines);
+        register_edit_action(view, cx, Editor::convert_to_upper_case);
+        register_edit_action(view, cx, Editor::convert_to_lower_case);
+        register_edit_action(view, cx, Editor::convert_to_title_case);
+        register_edit_action(view, cx, Editor::convert_to_snake_case);
+        register_edit_action(view, cx, Editor::convert_to_kebab_case);
+        register_edit_action(view, cx, Editor::convert_to_upper_camel_case);
+        register_edit_action(view, cx, Editor::convert_to_lower_camel_case);
+        register_edit_action(view, cx, Editor::convert_to_opposite_case);
+        register_edit_action(view, cx, Editor::delete_to_previous_word_start);
+        register_edit_action(view, cx, Editor::delete_to_previous_subword_start);
+        register_edit_action(view, cx, Editor::delete_to_next_word_end);
+        register_edit_action(view, cx, Editor::delete_to_next_subword_end);
+        register_edit_action(view, cx, Editor::delete_to_beginning_of_line);
+        register_edit_action(view, cx, Editor::delete_to_end_of_line);
+        register_edit_action(view, cx, Editor::cut_to_end_of_line);
+        register_edit_action(view, cx, Editor::duplicate_line_up);
+        register_edit_action(view, cx, Editor::duplicate_line_down);
+        register_edit_action(view, cx, Editor::move_line_up);
+        register_edit_action(view, cx, Editor::move_line_down);
+        register_edit_action(view, cx, Editor::transpose);
+        register_edit_action(view, cx, Editor::cut);
         register_action(view, cx, Editor::copy);
-        register_action(view, cx, Editor::paste);
-        register_action(view, cx, Editor::undo);
-        register_action(view, cx, Editor::redo);
+        register_edit_action(view, cx, Editor::paste);
+        register_edit_action(view, cx, Editor::undo);
+        register_edit_action(view, cx, Editor::redo);
         register_action(view, cx, Editor::move_page_up);
         register_action(view, cx, Editor::move_page_down);
         register_action(view, cx, Editor::next_screen);
@@ -290,11 +298,14 @@ impl EditorElement {
         register_action(view, cx, |editor, action, cx| {
             editor.select_previous(action, cx).log_err();
         });
-        register_action(view, cx, Editor::toggle_comments);
+        register_edit_action(view, cx, Editor::toggle_comments);
         register_action(view, cx, Editor::select_larger_syntax_node);
         register_action(view, cx, Editor::select_smaller_syntax_node);
         register_action(view, cx, Editor::select_enclosing_symbol);
         register_action(view, cx, Editor::move_to_enclosing_bracket);
+        register_action(view, cx, Editor::go_to_matching_bracket);
+        register_action(view, cx, Editor::select_enclosing_bracket_contents);
+        register_action(view, cx, Editor::undo_select_next);
         register_action(view, cx, Editor::undo_selection);
         register_action(view, cx, Editor::redo_selection);
         if !view.read(cx).is_singleton(cx) {
@@ -304,8 +315,26 @@ impl EditorElement {
         }
         register_action(view, cx, Editor::go_to_diagnostic);
         register_action(view, cx, Editor::go_to_prev_diagnostic);
+        register_action(view, cx, Editor::go_to_next_error);
+        register_action(view, cx, Editor::go_to_prev_error);
+        register_action(view, cx, Editor::go_to_next_warning);
+        register_action(view, cx, Editor::go_to_prev_warning);
+        register_action(view, cx, Editor::go_to_next_diagnostic_from_source);
+        register_action(view, cx, Editor::go_to_prev_diagnostic_from_source);
         register_action(view, cx, Editor::go_to_hunk);
         register_action(view, cx, Editor::go_to_prev_hunk);
+        register_action(view, cx, Editor::go_to_next_conflict);
+        register_action(view, cx, Editor::go_to_prev_conflict);
+        register_edit_action(view, cx, Editor::accept_conflict_ours);
+        register_edit_action(view, cx, Editor::accept_conflict_theirs);
+        register_edit_action(view, cx, Editor::accept_conflict_both);
+        register_action(view, cx, Editor::compare_conflict);
+        register_action(view, cx, Editor::highlight_duplicate_lines);
+        register_action(view, cx, Editor::go_to_next_duplicate_lines);
+        register_action(view, cx, Editor::go_to_prev_duplicate_lines);
+        register_action(view, cx, Editor::compare_with_clipboard);
+        register_action(view, cx, Editor::go_to_next_outline_item);
+        register_action(view, cx, Editor::go_to_prev_outline_item);
         register_action(view, cx, |editor, a, cx| {
             editor.go_to_definition(a, cx).detach_and_log_err(cx);
         });
@@ -350,6 +379,8 @@ impl EditorElement {
         register_action(view, cx, Editor::toggle_line_numbers);
         register_action(view, cx, Editor::toggle_relative_line_numbers);
         register_action(view, cx, Editor::toggle_indent_guides);
+        register_action(view, cx, Editor::toggle_focus_mode);
+        register_action(view, cx, Editor::toggle_profiling_hud);
         register_action(view, cx, Editor::toggle_inlay_hints);
         register_action(view, cx, Editor::toggle_inline_completions);
         register_action(view, cx, hover_popover::hover);
@@ -360,6 +391,9 @@ impl EditorElement {
         register_action(view, cx, Editor::copy_permalink_to_line);
         register_action(view, cx, Editor::open_permalink_to_line);
         register_action(view, cx, Editor::copy_file_location);
+        register_action(view, cx, Editor::inspect_tree_sitter_scopes);
+        register_action(view, cx, Editor::toggle_breakpoint);
+        register_action(view, cx, Editor::add_review_comment);
         register_action(view, cx, Editor::toggle_git_blame);
         register_action(view, cx, Editor::toggle_git_blame_inline);
         register_action(view, cx, Editor::toggle_hunk_diff);
@@ -425,12 +459,18 @@ impl EditorElement {
         register_action(view, cx, Editor::context_menu_next);
         register_action(view, cx, Editor::context_menu_last);
         register_action(view, cx, Editor::display_cursor_names);
-        register_action(view, cx, Editor::unique_lines_case_insensitive);
-        register_action(view, cx, Editor::unique_lines_case_sensitive);
-        register_action(view, cx, Editor::accept_partial_inline_completion);
-        register_action(view, cx, Editor::accept_inline_completion);
-        register_action(view, cx, Editor::revert_file);
-        register_action(view, cx, Editor::revert_selected_hunks);
+        register_edit_action(view, cx, Editor::unique_lines_case_insensitive);
+        register_edit_action(view, cx, Editor::unique_lines_case_sensitive);
+        register_edit_action(view, cx, Editor::accept_partial_inline_completion);
+        register_edit_action(view, cx, Editor::accept_inline_completion);
+        register_edit_action(view, cx, Editor::revert_file);
+        register_edit_action(view, cx, Editor::revert_selected_hunks);
+        register_action(view, cx, Editor::stage_selected_hunks);
+        register_action(view, cx, Editor::unstage_selected_hunks);
+        register_action(view, cx, Editor::export_patch_bundle);
+        register_action(view, cx, Editor::export_as_html);
+        register_action(view, cx, Editor::copy_selection_as_image);
+        register_action(view, cx, Editor::print_to_pdf);
         register_action(view, cx, Editor::open_active_item_in_terminal)
     }
 
@@ -515,6 +555,17 @@ impl EditorElement {
             cx.notify();
             return;
         } else if gutter_hitbox.is_hovered(cx) {
+            if modifiers.alt {
+                let position = position_map
+                    .point_for_position(text_hitbox.bounds, event.position)
+                    .previous_valid;
+                let row = position
+                    .to_point(&position_map.snapshot.display_snapshot)
+                    .row;
+                editor.toggle_breakpoint_at_row(MultiBufferRow(row), cx);
+                cx.stop_propagation();
+                return;
+            }
             click_count = 3; // Simulate triple-click when clicking the gutter to select lines
         } else if !text_hitbox.is_hovered(cx) {
             return;
@@ -1008,6 +1059,17 @@ impl EditorElement {
         cx: &mut WindowContext,
     ) -> Vec<CursorLayout> {
         let mut autoscroll_bounds = None;
+        // Presentation mode draws the local cursors wider, in the color of the text.
+        let high_contrast_color = workspace::presentation_mode::high_contrast_cursor(cx)
+            .then(|| cx.theme().colors().text);
+        let mut bar_width = snapshot
+            .editor_settings(cx)
+            .cursor_bar_width
+            .clamp(MIN_CURSOR_BAR_WIDTH, MAX_CURSOR_BAR_WIDTH);
+        if high_contrast_color.is_some() {
+            bar_width = bar_width.max(HIGH_CONTRAST_CURSOR_BAR_WIDTH);
+        }
+        let bar_width = px(bar_width);
         let cursor_layouts = self.editor.update(cx, |editor, cx| {
             let mut cursors = Vec::new();
             for (player_color, selections) in selections {
@@ -1097,9 +1159,13 @@ impl EditorElement {
                         }
                     }
 
+                    let color = high_contrast_color
+                        .filter(|_| selection.is_local)
+                        .unwrap_or(player_color.cursor);
                     let mut cursor = CursorLayout {
-                        color: player_color.cursor,
+                        color,
                         block_width,
+                        bar_width,
                         origin: point(x, y),
                         line_height,
                         shape: selection.cursor_shape,
@@ -1134,7 +1200,7 @@ impl EditorElement {
         non_visible_cursors: bool,
         cx: &mut WindowContext,
     ) -> Option<ScrollbarLayout> {
-        let scrollbar_settings = EditorSettings::get_global(cx).scrollbar;
+        let scrollbar_settings = snapshot.editor_settings(cx).scrollbar;
         let show_scrollbars = match scrollbar_settings.show {
             ShowScrollbar::Auto => {
                 let editor = self.editor.read(cx);
@@ -1180,7 +1246,7 @@ impl EditorElement {
             point(bounds.lower_right().x, bounds.lower_left().y),
         );
 
-        let settings = EditorSettings::get_global(cx);
+        let settings = snapshot.editor_settings(cx);
         let scroll_beyond_last_line: f32 = match settings.scroll_beyond_last_line {
             ScrollBeyondLastLine::OnePage => rows_per_page,
             ScrollBeyondLastLine::Off => 1.0,
@@ -1402,6 +1468,88 @@ impl EditorElement {
         Some(element)
     }
 
+    /// Returns the first primary diagnostic of each visible display row whose
+    /// severity is shown inline.
+    fn collect_inline_diagnostics(
+        &self,
+        snapshot: &EditorSnapshot,
+        range: Range<Anchor>,
+        cx: &WindowContext,
+    ) -> BTreeMap<DisplayRow, Diagnostic> {
+        let settings = &snapshot.editor_settings(cx).inline_diagnostics;
+        let mut diagnostics = BTreeMap::new();
+        if !settings.enabled || snapshot.mode != EditorMode::Full {
+            return diagnostics;
+        }
+
+        for entry in snapshot
+            .buffer_snapshot
+            .diagnostics_in_range::<_, Point>(range, false)
+        {
+            if !entry.diagnostic.is_primary || !settings.shows(entry.diagnostic.severity) {
+                continue;
+            }
+            let display_row = entry.range.start.to_display_point(snapshot).row();
+            diagnostics.entry(display_row).or_insert(entry.diagnostic);
+        }
+        diagnostics
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn layout_inline_diagnostics(
+        &self,
+        diagnostics: BTreeMap<DisplayRow, Diagnostic>,
+        start_row: DisplayRow,
+        line_layouts: &[LineWithInvisibles],
+        crease_trailers: &[Option<CreaseTrailerLayout>],
+        em_width: Pixels,
+        content_origin: gpui::Point<Pixels>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        line_height: Pixels,
+        text_right: Pixels,
+        cx: &mut WindowContext,
+    ) -> BTreeMap<DisplayRow, AnyElement> {
+        const INLINE_DIAGNOSTIC_PADDING_EM_WIDTHS: f32 = 4.;
+
+        let settings = self
+            .editor
+            .read(cx)
+            .editor_settings(cx)
+            .inline_diagnostics
+            .clone();
+        let mut elements = BTreeMap::new();
+        for (display_row, diagnostic) in diagnostics {
+            let Some(line_ix) = display_row.0.checked_sub(start_row.0) else {
+                continue;
+            };
+            let Some(line_layout) = line_layouts.get(line_ix as usize) else {
+                continue;
+            };
+
+            let line_end = if let Some(crease_trailer) = crease_trailers
+                .get(line_ix as usize)
+                .and_then(|trailer| trailer.as_ref())
+            {
+                crease_trailer.bounds.right()
+            } else {
+                content_origin.x - scroll_pixel_position.x + line_layout.width
+            };
+            let start_x = line_end + em_width * INLINE_DIAGNOSTIC_PADDING_EM_WIDTHS;
+            let start_y = content_origin.y
+                + line_height * (display_row.as_f32() - scroll_pixel_position.y / line_height);
+            let width = settings
+                .error_lens
+                .then(|| text_right - start_x)
+                .filter(|width| *width > px(0.));
+
+            let mut element =
+                render_inline_diagnostic(&diagnostic, &settings, width, &self.style, cx);
+            element.prepaint_as_root(point(start_x, start_y), AvailableSpace::min_size(), cx);
+            elements.insert(display_row, element);
+        }
+        elements
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_blame_entries(
         &self,
@@ -1586,6 +1734,124 @@ impl EditorElement {
         (offset_y, length)
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn layout_breakpoint_indicators(
+        &self,
+        line_height: Pixels,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        rows_with_hunk_bounds: &HashMap<DisplayRow, Bounds<Pixels>>,
+        breakpoint_rows: &[MultiBufferRow],
+        snapshot: &EditorSnapshot,
+        cx: &mut WindowContext,
+    ) -> Vec<AnyElement> {
+        self.editor.update(cx, |editor, cx| {
+            breakpoint_rows
+                .iter()
+                .filter_map(|row| {
+                    if snapshot.is_line_folded(*row) {
+                        return None;
+                    }
+                    let display_row = Point::new(row.0, 0).to_display_point(snapshot).row();
+                    let button = editor.render_breakpoint_indicator(display_row, *row, cx);
+                    let button = prepaint_gutter_button(
+                        button,
+                        display_row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_pixel_position,
+                        gutter_hitbox,
+                        rows_with_hunk_bounds,
+                        cx,
+                    );
+                    Some(button)
+                })
+                .collect_vec()
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn layout_review_thread_indicators(
+        &self,
+        line_height: Pixels,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        rows_with_hunk_bounds: &HashMap<DisplayRow, Bounds<Pixels>>,
+        review_thread_rows: &[(MultiBufferRow, bool)],
+        snapshot: &EditorSnapshot,
+        cx: &mut WindowContext,
+    ) -> Vec<AnyElement> {
+        self.editor.update(cx, |editor, cx| {
+            review_thread_rows
+                .iter()
+                .filter_map(|(row, resolved)| {
+                    if snapshot.is_line_folded(*row) {
+                        return None;
+                    }
+                    let display_row = Point::new(row.0, 0).to_display_point(snapshot).row();
+                    let button =
+                        editor.render_review_thread_indicator(display_row, *row, *resolved, cx);
+                    let button = prepaint_gutter_button(
+                        button,
+                        display_row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_pixel_position,
+                        gutter_hitbox,
+                        rows_with_hunk_bounds,
+                        cx,
+                    );
+                    Some(button)
+                })
+                .collect_vec()
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn layout_decoration_indicators(
+        &self,
+        line_height: Pixels,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        rows_with_hunk_bounds: &HashMap<DisplayRow, Bounds<Pixels>>,
+        decoration_rows: &[(MultiBufferRow, DecorationSeverity, Vec<SharedString>)],
+        snapshot: &EditorSnapshot,
+        cx: &mut WindowContext,
+    ) -> Vec<AnyElement> {
+        self.editor.update(cx, |editor, cx| {
+            decoration_rows
+                .iter()
+                .filter_map(|(row, severity, tooltips)| {
+                    if snapshot.is_line_folded(*row) {
+                        return None;
+                    }
+                    let display_row = Point::new(row.0, 0).to_display_point(snapshot).row();
+                    let button = editor.render_decoration_indicator(
+                        display_row,
+                        *row,
+                        *severity,
+                        tooltips,
+                        cx,
+                    );
+                    let button = prepaint_gutter_button(
+                        button,
+                        display_row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_pixel_position,
+                        gutter_hitbox,
+                        rows_with_hunk_bounds,
+                        cx,
+                    );
+                    Some(button)
+                })
+                .collect_vec()
+        })
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_run_indicators(
         &self,
@@ -1594,6 +1860,7 @@ impl EditorElement {
         gutter_dimensions: &GutterDimensions,
         gutter_hitbox: &Hitbox,
         rows_with_hunk_bounds: &HashMap<DisplayRow, Bounds<Pixels>>,
+        occupied_rows: &[MultiBufferRow],
         snapshot: &EditorSnapshot,
         cx: &mut WindowContext,
     ) -> Vec<AnyElement> {
@@ -1619,12 +1886,17 @@ impl EditorElement {
                 .filter_map(|(_, tasks)| {
                     let multibuffer_point = tasks.offset.0.to_point(&snapshot.buffer_snapshot);
                     let multibuffer_row = MultiBufferRow(multibuffer_point.row);
-                    if snapshot.is_line_folded(multibuffer_row) {
+                    // Breakpoints and review threads take the place of run
+                    // indicators.
+                    if snapshot.is_line_folded(multibuffer_row)
+                        || occupied_rows.binary_search(&multibuffer_row).is_ok()
+                    {
                         return None;
                     }
                     let display_row = multibuffer_point.to_display_point(snapshot).row();
                     let button = editor.render_run_indicator(
                         &self.style,
+                        tasks.kind,
                         Some(display_row) == active_task_indicator_row,
                         display_row,
                         cx,
@@ -1756,7 +2028,7 @@ impl EditorElement {
         cx: &mut WindowContext,
     ) -> Vec<Option<ShapedLine>> {
         let include_line_numbers = snapshot.show_line_numbers.unwrap_or_else(|| {
-            EditorSettings::get_global(cx).gutter.line_numbers && snapshot.mode == EditorMode::Full
+            snapshot.editor_settings(cx).gutter.line_numbers && snapshot.mode == EditorMode::Full
         });
         if !include_line_numbers {
             return Vec::new();
@@ -1828,7 +2100,7 @@ impl EditorElement {
         snapshot: &EditorSnapshot,
         cx: &mut WindowContext,
     ) -> Vec<Option<AnyElement>> {
-        let include_fold_statuses = EditorSettings::get_global(cx).gutter.folds
+        let include_fold_statuses = snapshot.editor_settings(cx).gutter.folds
             && snapshot.mode == EditorMode::Full
             && self.editor.read(cx).is_singleton(cx);
         if include_fold_statuses {
@@ -2074,7 +2346,17 @@ impl EditorElement {
                 let icon_offset = gutter_dimensions.width
                     - (gutter_dimensions.left_padding + gutter_dimensions.margin);
 
-                let element = if *starts_new_buffer {
+                let custom_buffer_header = self.editor.read(cx).custom_buffer_header.clone();
+
+                let element = if let Some(render_header) =
+                    custom_buffer_header.filter(|_| *starts_new_buffer)
+                {
+                    div()
+                        .id(("custom buffer header", EntityId::from(block_id)))
+                        .w_full()
+                        .h(*height as f32 * cx.line_height())
+                        .child(render_header(buffer, cx))
+                } else if *starts_new_buffer {
                     let path = buffer.resolve_file_path(cx, include_root);
                     let mut filename = None;
                     let mut parent_path = None;
@@ -2938,6 +3220,36 @@ impl EditorElement {
         }
     }
 
+    /// Draws the profiling HUD over the top right corner of the text, showing the
+    /// timings of the previous frame, then records the timings of this frame's layout.
+    fn layout_profiling_hud(
+        &self,
+        text_hitbox: &Hitbox,
+        line_count: usize,
+        prepaint_start: Instant,
+        cx: &mut WindowContext,
+    ) {
+        let Some(profiling_hud) = self.editor.read(cx).profiling_hud else {
+            return;
+        };
+
+        const PROFILING_HUD_MARGIN: Pixels = px(8.);
+        let mut element = profiling_hud.render(cx.frame_stats(), cx);
+        let size = element.layout_as_root(AvailableSpace::min_size(), cx);
+        let origin = point(
+            text_hitbox.bounds.right() - size.width - PROFILING_HUD_MARGIN,
+            text_hitbox.bounds.top() + PROFILING_HUD_MARGIN,
+        );
+        cx.defer_draw(element, origin, 0);
+
+        self.editor.update(cx, |editor, _| {
+            if let Some(profiling_hud) = editor.profiling_hud.as_mut() {
+                profiling_hud.layout_duration = prepaint_start.elapsed();
+                profiling_hud.line_count = line_count;
+            }
+        });
+    }
+
     fn paint_background(&self, layout: &EditorLayout, cx: &mut WindowContext) {
         cx.paint_layer(layout.hitbox.bounds, |cx| {
             let scroll_top = layout.position_map.snapshot.scroll_position().y;
@@ -3321,6 +3633,15 @@ impl EditorElement {
             for test_indicator in layout.test_indicators.iter_mut() {
                 test_indicator.paint(cx);
             }
+            for breakpoint_indicator in layout.breakpoint_indicators.iter_mut() {
+                breakpoint_indicator.paint(cx);
+            }
+            for review_thread_indicator in layout.review_thread_indicators.iter_mut() {
+                review_thread_indicator.paint(cx);
+            }
+            for decoration_indicator in layout.decoration_indicators.iter_mut() {
+                decoration_indicator.paint(cx);
+            }
             for close_indicator in layout.close_indicators.iter_mut() {
                 close_indicator.paint(cx);
             }
@@ -3418,6 +3739,7 @@ impl EditorElement {
                 self.paint_redactions(layout, cx);
                 self.paint_cursors(layout, cx);
                 self.paint_inline_blame(layout, cx);
+                self.paint_inline_diagnostics(layout, cx);
                 cx.with_element_namespace("crease_trailers", |cx| {
                     for trailer in layout.crease_trailers.iter_mut().flatten() {
                         trailer.element.paint(cx);
@@ -3670,7 +3992,14 @@ impl EditorElement {
         cx: &mut WindowContext,
     ) -> Vec<PaintQuad> {
         const LIMIT: usize = 100;
-        if !EditorSettings::get_global(cx).scrollbar.cursors || layout.cursors.len() > LIMIT {
+        if !layout
+            .position_map
+            .snapshot
+            .editor_settings(cx)
+            .scrollbar
+            .cursors
+            || layout.cursors.len() > LIMIT
+        {
             return vec![];
         }
         let cursor_ranges = layout
@@ -3704,7 +4033,7 @@ impl EditorElement {
             let background_highlights = editor.background_highlights.clone();
             let snapshot = layout.position_map.snapshot.clone();
             let theme = cx.theme().clone();
-            let scrollbar_settings = EditorSettings::get_global(cx).scrollbar;
+            let scrollbar_settings = snapshot.editor_settings(cx).scrollbar;
 
             editor.scrollbar_marker_state.dirty = false;
             editor.scrollbar_marker_state.pending_refresh =
@@ -3909,6 +4238,18 @@ impl EditorElement {
         }
     }
 
+    fn paint_inline_diagnostics(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
+        let inline_diagnostics = mem::take(&mut layout.inline_diagnostics);
+        if inline_diagnostics.is_empty() {
+            return;
+        }
+        cx.paint_layer(layout.text_hitbox.bounds, |cx| {
+            for mut element in inline_diagnostics.into_values() {
+                element.paint(cx);
+            }
+        })
+    }
+
     fn paint_blocks(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
         for mut block in layout.blocks.drain(..) {
             block.element.paint(cx);
@@ -3930,7 +4271,12 @@ impl EditorElement {
 
             // Set a minimum scroll_sensitivity of 0.01 to make sure the user doesn't
             // accidentally turn off their scrolling.
-            let scroll_sensitivity = EditorSettings::get_global(cx).scroll_sensitivity.max(0.01);
+            let scroll_sensitivity = layout
+                .position_map
+                .snapshot
+                .editor_settings(cx)
+                .scroll_sensitivity
+                .max(0.01);
 
             move |event: &ScrollWheelEvent, phase, cx| {
                 if phase == DispatchPhase::Bubble && hitbox.is_hovered(cx) {
@@ -4219,6 +4565,37 @@ fn render_inline_blame_entry(
         .into_any()
 }
 
+fn render_inline_diagnostic(
+    diagnostic: &Diagnostic,
+    settings: &InlineDiagnostics,
+    width: Option<Pixels>,
+    style: &EditorStyle,
+    cx: &mut WindowContext,
+) -> AnyElement {
+    let color = diagnostic_style(diagnostic.severity, cx.theme().status());
+    let first_line = diagnostic.message.lines().next().unwrap_or_default();
+    let message = if first_line.chars().count() > settings.max_length {
+        let mut message = first_line
+            .chars()
+            .take(settings.max_length.saturating_sub(1))
+            .collect::<String>();
+        message.push('…');
+        message
+    } else {
+        first_line.to_string()
+    };
+
+    h_flex()
+        .font_family(style.text.font().family)
+        .line_height(style.text.line_height)
+        .text_color(Hsla { a: 0.75, ..color })
+        .when_some(width, |this, width| {
+            this.w(width).px_1().bg(Hsla { a: 0.08, ..color })
+        })
+        .child(message)
+        .into_any()
+}
+
 fn render_blame_entry(
     ix: usize,
     blame: &gpui::Model<GitBlame>,
@@ -4955,6 +5332,7 @@ impl Element for EditorElement {
         _: &mut Self::RequestLayoutState,
         cx: &mut WindowContext,
     ) -> Self::PrepaintState {
+        let prepaint_start = Instant::now();
         let text_style = TextStyleRefinement {
             font_size: Some(self.style.text.font_size),
             line_height: Some(self.style.text.line_height),
@@ -5061,7 +5439,7 @@ impl Element for EditorElement {
                     let max_scroll_top = if matches!(snapshot.mode, EditorMode::AutoHeight { .. }) {
                         (max_row - height_in_lines + 1.).max(0.)
                     } else {
-                        let settings = EditorSettings::get_global(cx);
+                        let settings = snapshot.editor_settings(cx);
                         match settings.scroll_beyond_last_line {
                             ScrollBeyondLastLine::OnePage => max_row,
                             ScrollBeyondLastLine::Off => (max_row - height_in_lines + 1.).max(0.),
@@ -5115,9 +5493,21 @@ impl Element for EditorElement {
                         )
                     };
 
-                    let highlighted_rows = self
+                    let inline_diagnostics =
+                        self.collect_inline_diagnostics(&snapshot, start_anchor..end_anchor, cx);
+                    let mut highlighted_rows = self
                         .editor
                         .update(cx, |editor, cx| editor.highlighted_display_rows(cx));
+                    if snapshot.editor_settings(cx).inline_diagnostics.error_lens {
+                        for (display_row, diagnostic) in &inline_diagnostics {
+                            highlighted_rows
+                                .entry(*display_row)
+                                .or_insert_with(|| Hsla {
+                                    a: 0.12,
+                                    ..diagnostic_style(diagnostic.severity, cx.theme().status())
+                                });
+                        }
+                    }
                     let highlighted_ranges = self.editor.read(cx).background_highlights_in_range(
                         start_anchor..end_anchor,
                         &snapshot.display_snapshot,
@@ -5282,10 +5672,25 @@ impl Element for EditorElement {
                         )
                     });
 
+                    let inline_diagnostics = self.layout_inline_diagnostics(
+                        inline_diagnostics,
+                        start_row,
+                        &line_layouts,
+                        &crease_trailers,
+                        em_width,
+                        content_origin,
+                        scroll_pixel_position,
+                        line_height,
+                        text_hitbox.bounds.right(),
+                        cx,
+                    );
+
                     let mut inline_blame = None;
                     if let Some(newest_selection_head) = newest_selection_head {
                         let display_row = newest_selection_head.row();
-                        if (start_row..end_row).contains(&display_row) {
+                        if (start_row..end_row).contains(&display_row)
+                            && !inline_diagnostics.contains_key(&display_row)
+                        {
                             let line_ix = display_row.minus(start_row) as usize;
                             let line_layout = &line_layouts[line_ix];
                             let crease_trailer_layout = crease_trailers[line_ix].as_ref();
@@ -5389,7 +5794,7 @@ impl Element for EditorElement {
                         cx,
                     );
 
-                    let gutter_settings = EditorSettings::get_global(cx).gutter;
+                    let gutter_settings = snapshot.editor_settings(cx).gutter;
 
                     let expanded_add_hunks_by_rows = self.editor.update(cx, |editor, _| {
                         editor
@@ -5486,6 +5891,17 @@ impl Element for EditorElement {
                         }
                     }
 
+                    let breakpoint_rows = self.editor.read(cx).breakpoint_rows(cx);
+                    let mut review_thread_rows = self.editor.read(cx).review_thread_rows(cx);
+                    review_thread_rows
+                        .retain(|(row, _)| breakpoint_rows.binary_search(row).is_err());
+                    let mut occupied_rows = breakpoint_rows.clone();
+                    occupied_rows.extend(review_thread_rows.iter().map(|(row, _)| *row));
+                    occupied_rows.sort_unstable();
+                    let mut decoration_rows = self.editor.read(cx).decoration_gutter_icons(cx);
+                    decoration_rows.retain(|(row, _, _)| occupied_rows.binary_search(row).is_err());
+                    occupied_rows.extend(decoration_rows.iter().map(|(row, _, _)| *row));
+                    occupied_rows.sort_unstable();
                     let test_indicators = if gutter_settings.runnables {
                         self.layout_run_indicators(
                             line_height,
@@ -5493,12 +5909,43 @@ impl Element for EditorElement {
                             &gutter_dimensions,
                             &gutter_hitbox,
                             &rows_with_hunk_bounds,
+                            &occupied_rows,
                             &snapshot,
                             cx,
                         )
                     } else {
                         Vec::new()
                     };
+                    let breakpoint_indicators = self.layout_breakpoint_indicators(
+                        line_height,
+                        scroll_pixel_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        &rows_with_hunk_bounds,
+                        &breakpoint_rows,
+                        &snapshot,
+                        cx,
+                    );
+                    let review_thread_indicators = self.layout_review_thread_indicators(
+                        line_height,
+                        scroll_pixel_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        &rows_with_hunk_bounds,
+                        &review_thread_rows,
+                        &snapshot,
+                        cx,
+                    );
+                    let decoration_indicators = self.layout_decoration_indicators(
+                        line_height,
+                        scroll_pixel_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        &rows_with_hunk_bounds,
+                        &decoration_rows,
+                        &snapshot,
+                        cx,
+                    );
 
                     let close_indicators = self.layout_hunk_diff_close_indicators(
                         line_height,
@@ -5540,6 +5987,8 @@ impl Element for EditorElement {
                     let mouse_context_menu =
                         self.layout_mouse_context_menu(&snapshot, start_row..end_row, cx);
 
+                    self.layout_profiling_hud(&text_hitbox, line_layouts.len(), prepaint_start, cx);
+
                     cx.with_element_namespace("gutter_fold_toggles", |cx| {
                         self.prepaint_gutter_fold_toggles(
                             &mut gutter_fold_toggles,
@@ -5615,12 +6064,16 @@ impl Element for EditorElement {
                         line_numbers,
                         blamed_display_rows,
                         inline_blame,
+                        inline_diagnostics,
                         blocks,
                         cursors,
                         visible_cursors,
                         selections,
                         mouse_context_menu,
                         test_indicators,
+                        breakpoint_indicators,
+                        review_thread_indicators,
+                        decoration_indicators,
                         close_indicators,
                         code_actions_indicator,
                         gutter_fold_toggles,
@@ -5641,6 +6094,7 @@ impl Element for EditorElement {
         layout: &mut Self::PrepaintState,
         cx: &mut WindowContext,
     ) {
+        let paint_start = Instant::now();
         let focus_handle = self.editor.focus_handle(cx);
         let key_context = self.editor.update(cx, |editor, cx| editor.key_context(cx));
         cx.set_key_context(key_context);
@@ -5648,6 +6102,21 @@ impl Element for EditorElement {
             &focus_handle,
             ElementInputHandler::new(bounds, self.editor.clone()),
         );
+        if focus_handle.is_focused(cx) {
+            let display_snapshot = &layout.position_map.snapshot.display_snapshot;
+            let visible_rows = &layout.visible_display_row_range;
+            let start_row = DisplayPoint::new(visible_rows.start, 0)
+                .to_point(display_snapshot)
+                .row;
+            let end_row = DisplayPoint::new(visible_rows.end, 0)
+                .to_point(display_snapshot)
+                .row;
+            let accessible_text = self
+                .editor
+                .read(cx)
+                .accessible_text(MultiBufferRow(start_row)..MultiBufferRow(end_row), cx);
+            cx.set_accessible_text(&focus_handle, accessible_text);
+        }
         self.register_actions(cx);
         self.register_key_listeners(cx, layout);
 
@@ -5713,7 +6182,13 @@ impl Element for EditorElement {
                     self.paint_mouse_context_menu(layout, cx);
                 });
             })
-        })
+        });
+
+        self.editor.update(cx, |editor, _| {
+            if let Some(profiling_hud) = editor.profiling_hud.as_mut() {
+                profiling_hud.paint_duration = paint_start.elapsed();
+            }
+        });
     }
 }
 
@@ -5754,6 +6229,7 @@ pub struct EditorLayout {
     display_hunks: Vec<(DisplayDiffHunk, Option<Hitbox>)>,
     blamed_display_rows: Option<Vec<AnyElement>>,
     inline_blame: Option<AnyElement>,
+    inline_diagnostics: BTreeMap<DisplayRow, AnyElement>,
     blocks: Vec<BlockLayout>,
     highlighted_ranges: Vec<(Range<DisplayPoint>, Hsla)>,
     highlighted_gutter_ranges: Vec<(Range<DisplayPoint>, Hsla)>,
@@ -5763,6 +6239,9 @@ pub struct EditorLayout {
     selections: Vec<(PlayerColor, Vec<SelectionLayout>)>,
     code_actions_indicator: Option<AnyElement>,
     test_indicators: Vec<AnyElement>,
+    breakpoint_indicators: Vec<AnyElement>,
+    review_thread_indicators: Vec<AnyElement>,
+    decoration_indicators: Vec<AnyElement>,
     close_indicators: Vec<AnyElement>,
     gutter_fold_toggles: Vec<Option<AnyElement>>,
     crease_trailers: Vec<Option<CreaseTrailerLayout>>,
@@ -6006,6 +6485,7 @@ pub struct IndentGuideLayout {
 pub struct CursorLayout {
     origin: gpui::Point<Pixels>,
     block_width: Pixels,
+    bar_width: Pixels,
     line_height: Pixels,
     color: Hsla,
     shape: CursorShape,
@@ -6032,6 +6512,7 @@ impl CursorLayout {
         CursorLayout {
             origin,
             block_width,
+            bar_width: DEFAULT_CURSOR_BAR_WIDTH,
             line_height,
             color,
             shape,
@@ -6051,7 +6532,7 @@ impl CursorLayout {
         match self.shape {
             CursorShape::Bar => Bounds {
                 origin: self.origin + origin,
-                size: size(px(2.0), self.line_height),
+                size: size(self.bar_width, self.line_height),
             },
             CursorShape::Block | CursorShape::Hollow => Bounds {
                 origin: self.origin + origin,
@@ -6272,13 +6753,16 @@ fn scale_horizontal_mouse_autoscroll_delta(delta: Pixels) -> f32 {
 mod tests {
     use super::*;
     use crate::{
+        actions::ToggleProfilingHud,
         display_map::{BlockDisposition, BlockProperties},
+        editor_settings::{InlineDiagnosticSeverity, InlineDiagnosticsContent},
         editor_tests::{init_test, update_test_language_settings},
         Editor, MultiBuffer,
     };
     use gpui::{TestAppContext, VisualTestContext};
-    use language::language_settings;
+    use language::{language_settings, DiagnosticEntry, DiagnosticSet, LanguageServerId};
     use log::info;
+    use settings::SettingsStore;
     use std::num::NonZeroU32;
     use ui::Context;
     use util::test::sample_text;
@@ -6557,6 +7041,119 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_profiling_hud(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+
+        let window = cx.add_window(|cx| {
+            let buffer = MultiBuffer::build_simple(&sample_text(6, 6, 'a'), cx);
+            Editor::new(EditorMode::Full, buffer, None, true, cx)
+        });
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let editor = window.root(cx).unwrap();
+        editor.update(cx, |_, cx| cx.focus_self());
+        assert!(editor.update(cx, |editor, _| editor.profiling_hud.is_none()));
+
+        // The HUD records the timings of each frame it's drawn in.
+        cx.dispatch_action(ToggleProfilingHud);
+        let profiling_hud = editor.update(cx, |editor, _| editor.profiling_hud);
+        assert_eq!(profiling_hud.map(|hud| hud.line_count), Some(6));
+        let line_layouts = cx.update(|cx| cx.frame_stats().line_layouts);
+        assert!(line_layouts.shaped_lines + line_layouts.reused_lines > 0);
+
+        cx.dispatch_action(ToggleProfilingHud);
+        assert!(editor.update(cx, |editor, _| editor.profiling_hud.is_none()));
+    }
+
+    #[gpui::test]
+    fn test_inline_diagnostics(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+
+        let window = cx.add_window(|cx| {
+            let buffer = MultiBuffer::build_simple("one\ntwo\nthree\nfour\n", cx);
+            Editor::new(EditorMode::Full, buffer, None, true, cx)
+        });
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let editor = window.root(cx).unwrap();
+        let style = cx.update(|cx| editor.read(cx).style().unwrap().clone());
+
+        let buffer = cx.update(|cx| editor.read(cx).buffer().read(cx).as_singleton().unwrap());
+        buffer.update(cx, |buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let entry = |row: u32, severity, is_primary, message: &str| DiagnosticEntry {
+                range: snapshot.anchor_before(Point::new(row, 0))
+                    ..snapshot.anchor_after(Point::new(row, 3)),
+                diagnostic: Diagnostic {
+                    severity,
+                    message: message.to_string(),
+                    is_primary,
+                    ..Default::default()
+                },
+            };
+            let diagnostics = DiagnosticSet::from_sorted_entries(
+                [
+                    entry(0, DiagnosticSeverity::ERROR, true, "first error"),
+                    entry(0, DiagnosticSeverity::WARNING, true, "second warning"),
+                    entry(1, DiagnosticSeverity::WARNING, true, "a warning"),
+                    entry(2, DiagnosticSeverity::HINT, true, "a hint"),
+                    entry(3, DiagnosticSeverity::ERROR, false, "a related error"),
+                ],
+                &snapshot,
+            );
+            buffer.update_diagnostics(LanguageServerId(0), diagnostics, cx);
+        });
+
+        // Inline diagnostics are disabled by default.
+        let (_, state) = cx.draw(point(px(500.), px(500.)), size(px(500.), px(500.)), |_| {
+            EditorElement::new(&editor, style.clone())
+        });
+        assert!(state.inline_diagnostics.is_empty());
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.inline_diagnostics = Some(InlineDiagnosticsContent {
+                        enabled: Some(true),
+                        ..Default::default()
+                    });
+                });
+            });
+        });
+        let (_, state) = cx.draw(point(px(500.), px(500.)), size(px(500.), px(500.)), |_| {
+            EditorElement::new(&editor, style.clone())
+        });
+        assert_eq!(
+            state.inline_diagnostics.keys().copied().collect::<Vec<_>>(),
+            [DisplayRow(0), DisplayRow(1)]
+        );
+        assert!(state.highlighted_rows.is_empty());
+
+        // In error lens mode, the lines with diagnostics are highlighted.
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.inline_diagnostics = Some(InlineDiagnosticsContent {
+                        enabled: Some(true),
+                        error_lens: Some(true),
+                        severities: Some(vec![InlineDiagnosticSeverity::Hint]),
+                        ..Default::default()
+                    });
+                });
+            });
+        });
+        let (_, state) = cx.draw(point(px(500.), px(500.)), size(px(500.), px(500.)), |_| {
+            EditorElement::new(&editor, style.clone())
+        });
+        assert_eq!(
+            state.inline_diagnostics.keys().copied().collect::<Vec<_>>(),
+            [DisplayRow(2)]
+        );
+        assert_eq!(
+            state.highlighted_rows.keys().copied().collect::<Vec<_>>(),
+            [DisplayRow(2)]
+        );
+    }
+
     #[gpui::test]
     fn test_all_invisibles_drawing(cx: &mut TestAppContext) {
         const TAB_SIZE: u32 = 4;
@@ -6750,6 +7347,26 @@ mod tests {
     }
 }
 
+/// Registers an action that edits the buffer, which waits for any edit at many
+/// cursors that's being applied in batches to be done.
+fn register_edit_action<T: Action>(
+    view: &View<Editor>,
+    cx: &mut WindowContext,
+    listener: impl Fn(&mut Editor, &T, &mut ViewContext<Editor>) + 'static,
+) {
+    let listener = Rc::new(listener);
+    register_action(view, cx, move |editor, action: &T, cx| {
+        let queued = editor.queue_bulk_edit({
+            let listener = listener.clone();
+            let action = action.boxed_clone();
+            move |editor, cx| listener(editor, action.as_any().downcast_ref().unwrap(), cx)
+        });
+        if !queued {
+            listener(editor, action, cx);
+        }
+    });
+}
+
 pub fn register_action<T: Action>(
     view: &View<Editor>,
     cx: &mut WindowContext,
@@ -0,0 +1,86 @@
+use std::ops::Range;
+
+use gpui::HighlightStyle;
+use multi_buffer::{MultiBufferRow, MultiBufferSnapshot};
+use ui::prelude::*;
+
+use crate::{Editor, EditorMode, Point, ToPoint as _};
+
+pub(crate) enum FocusModeDimming {}
+
+/// Dims the text outside of the scope containing the newest selection, when focus
+/// mode is enabled.
+///
+/// The focused scope is the selected lines when the selection isn't empty, and
+/// otherwise the innermost outline item (a function, a type, ...) containing the
+/// cursor or, when there is none, the paragraph containing it.
+pub(crate) fn refresh_focus_mode(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if !editor.focus_mode || editor.mode != EditorMode::Full {
+        editor.clear_highlights::<FocusModeDimming>(cx);
+        return;
+    }
+
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let selection = editor.selections.newest::<Point>(cx);
+    let focused_range = focused_range(&snapshot, selection.range());
+    let dimmed_ranges = [
+        Point::zero()..focused_range.start,
+        focused_range.end..snapshot.max_point(),
+    ]
+    .into_iter()
+    .filter(|range| !range.is_empty())
+    .map(|range| snapshot.anchor_after(range.start)..snapshot.anchor_before(range.end))
+    .collect::<Vec<_>>();
+
+    let dimming = editor.editor_settings(cx).focus_mode.dimming.clamp(0., 1.);
+    editor.highlight_text::<FocusModeDimming>(
+        dimmed_ranges,
+        HighlightStyle {
+            fade_out: Some(dimming),
+            ..HighlightStyle::default()
+        },
+        cx,
+    );
+}
+
+fn focused_range(snapshot: &MultiBufferSnapshot, selection: Range<Point>) -> Range<Point> {
+    let (start_row, end_row) = if !selection.is_empty() {
+        // A selection ending at the start of a line doesn't include that line.
+        let end_row = if selection.end.column == 0 && selection.end.row > selection.start.row {
+            selection.end.row - 1
+        } else {
+            selection.end.row
+        };
+        (selection.start.row, end_row)
+    } else if let Some(item) = snapshot
+        .symbols_containing(selection.start, None)
+        .and_then(|(_, items)| items.last().cloned())
+    {
+        (
+            item.range.start.to_point(snapshot).row,
+            item.range.end.to_point(snapshot).row,
+        )
+    } else {
+        paragraph_rows(snapshot, selection.start.row)
+    };
+
+    Point::new(start_row, 0)..Point::new(end_row, snapshot.line_len(MultiBufferRow(end_row)))
+}
+
+/// Returns the first and last rows of the paragraph containing the row, which is
+/// delimited by blank lines.
+fn paragraph_rows(snapshot: &MultiBufferSnapshot, row: u32) -> (u32, u32) {
+    if snapshot.is_line_blank(MultiBufferRow(row)) {
+        return (row, row);
+    }
+    let mut start_row = row;
+    while start_row > 0 && !snapshot.is_line_blank(MultiBufferRow(start_row - 1)) {
+        start_row -= 1;
+    }
+    let max_row = snapshot.max_buffer_row().0;
+    let mut end_row = row;
+    while end_row < max_row && !snapshot.is_line_blank(MultiBufferRow(end_row + 1)) {
+        end_row += 1;
+    }
+    (start_row, end_row)
+}
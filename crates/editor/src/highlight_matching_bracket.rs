@@ -1,11 +1,15 @@
-use gpui::ViewContext;
+use gpui::{px, HighlightStyle, Hsla, UnderlineStyle, ViewContext};
+use theme::ActiveTheme;
 
-use crate::{Editor, RangeToAnchorExt};
+use crate::{Editor, EnclosingBracketRangeHighlight, RangeToAnchorExt};
 
 enum MatchingBracketHighlight {}
+enum EnclosingBracketRange {}
 
 pub fn refresh_matching_bracket_highlights(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
     editor.clear_background_highlights::<MatchingBracketHighlight>(cx);
+    editor.clear_background_highlights::<EnclosingBracketRange>(cx);
+    editor.clear_highlights::<EnclosingBracketRange>(cx);
 
     let newest_selection = editor.selections.newest::<usize>(cx);
     // Don't highlight brackets if the selection isn't empty
@@ -26,7 +30,40 @@ pub fn refresh_matching_bracket_highlights(editor: &mut Editor, cx: &mut ViewCon
             ],
             |theme| theme.editor_document_highlight_read_background,
             cx,
-        )
+        );
+
+        let enclosing_range =
+            (opening_range.start..closing_range.end).to_anchors(&snapshot.buffer_snapshot);
+        match snapshot
+            .editor_settings(cx)
+            .enclosing_bracket_range_highlight
+        {
+            EnclosingBracketRangeHighlight::None => {}
+            EnclosingBracketRangeHighlight::Tint => {
+                editor.highlight_background::<EnclosingBracketRange>(
+                    &[enclosing_range],
+                    |theme| {
+                        let color = theme.editor_document_highlight_read_background;
+                        Hsla {
+                            a: color.a * 0.4,
+                            ..color
+                        }
+                    },
+                    cx,
+                );
+            }
+            EnclosingBracketRangeHighlight::Underline => {
+                let style = HighlightStyle {
+                    underline: Some(UnderlineStyle {
+                        color: Some(cx.theme().colors().text_muted),
+                        thickness: px(1.),
+                        wavy: false,
+                    }),
+                    ..Default::default()
+                };
+                editor.highlight_text::<EnclosingBracketRange>(vec![enclosing_range], style, cx);
+            }
+        }
     }
 }
 
@@ -2,8 +2,8 @@ use crate::{
     display_map::{InlayOffset, ToDisplayPoint},
     hover_links::{InlayHighlight, RangeInEditor},
     scroll::ScrollAmount,
-    Anchor, AnchorRangeExt, DisplayPoint, DisplayRow, Editor, EditorSettings, EditorSnapshot,
-    Hover, RangeToAnchorExt,
+    Anchor, AnchorRangeExt, DisplayPoint, DisplayRow, Editor, EditorSnapshot, Hover,
+    RangeToAnchorExt,
 };
 use gpui::{
     div, px, AnyElement, AsyncWindowContext, FontWeight, Hsla, InteractiveElement, IntoElement,
@@ -11,7 +11,7 @@ use gpui::{
     StyleRefinement, Styled, Task, TextStyleRefinement, View, ViewContext,
 };
 use itertools::Itertools;
-use language::{DiagnosticEntry, Language, LanguageRegistry};
+use language::{Diagnostic, DiagnosticEntry, Language, LanguageRegistry};
 use lsp::DiagnosticSeverity;
 use markdown::{Markdown, MarkdownStyle};
 use multi_buffer::ToOffset;
@@ -39,7 +39,7 @@ pub fn hover(editor: &mut Editor, _: &Hover, cx: &mut ViewContext<Editor>) {
 /// The internal hover action dispatches between `show_hover` or `hide_hover`
 /// depending on whether a point to hover over is provided.
 pub fn hover_at(editor: &mut Editor, anchor: Option<Anchor>, cx: &mut ViewContext<Editor>) {
-    if EditorSettings::get_global(cx).hover_popover_enabled {
+    if editor.editor_settings(cx).hover_popover_enabled {
         if show_keyboard_hover(editor, cx) {
             return;
         }
@@ -105,7 +105,7 @@ pub fn find_hovered_hint_part(
 }
 
 pub fn hover_at_inlay(editor: &mut Editor, inlay_hover: InlayHover, cx: &mut ViewContext<Editor>) {
-    if EditorSettings::get_global(cx).hover_popover_enabled {
+    if editor.editor_settings(cx).hover_popover_enabled {
         if editor.pending_rename.is_some() {
             return;
         }
@@ -764,6 +764,14 @@ impl DiagnosticPopover {
 
         (entry.diagnostic.group_id, entry.range.start)
     }
+
+    pub fn diagnostic(&self) -> &Diagnostic {
+        &self
+            .primary_diagnostic
+            .as_ref()
+            .unwrap_or(&self.local_diagnostic)
+            .diagnostic
+    }
 }
 
 #[cfg(test)]
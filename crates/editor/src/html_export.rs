@@ -0,0 +1,250 @@
+use std::{fmt::Write as _, ops::Range};
+
+use gpui::{FontStyle, HighlightStyle, Hsla, Rgba};
+use language::BufferSnapshot;
+use theme::SyntaxTheme;
+
+/// A line of a buffer, as runs of text and the syntax highlighting they're shown with.
+pub(crate) type HighlightedLine = Vec<(String, HighlightStyle)>;
+
+/// How a buffer is laid out when it's exported as HTML.
+pub(crate) struct HtmlOptions {
+    pub title: String,
+    pub font_family: String,
+    pub font_size: f32,
+    pub background: Hsla,
+    pub foreground: Hsla,
+    pub line_number_color: Hsla,
+    /// The number of the first line, or `None` to leave out line numbers.
+    pub first_line_number: Option<u32>,
+    pub wrap_lines: bool,
+    /// Whether to open the print dialog once the file is loaded in a browser.
+    pub print: bool,
+}
+
+/// Splits a range of a buffer into lines of highlighted text, merging adjacent
+/// chunks that are highlighted the same way.
+pub(crate) fn highlighted_lines(
+    snapshot: &BufferSnapshot,
+    range: Range<usize>,
+    syntax: &SyntaxTheme,
+) -> Vec<HighlightedLine> {
+    let mut lines = Vec::new();
+    let mut line = HighlightedLine::new();
+    for chunk in snapshot.chunks(range, true) {
+        let style = chunk
+            .syntax_highlight_id
+            .and_then(|id| id.style(syntax))
+            .unwrap_or_default();
+        let mut chunk_lines = chunk.text.split('\n').peekable();
+        while let Some(text) = chunk_lines.next() {
+            if !text.is_empty() {
+                match line.last_mut() {
+                    Some((last_text, last_style)) if *last_style == style => {
+                        last_text.push_str(text)
+                    }
+                    _ => line.push((text.to_string(), style)),
+                }
+            }
+            if chunk_lines.peek().is_some() {
+                lines.push(std::mem::take(&mut line));
+            }
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+/// Renders highlighted lines as a standalone HTML document.
+pub(crate) fn render_html(lines: &[HighlightedLine], options: &HtmlOptions) -> String {
+    let line_number_width = options.first_line_number.map(|first| {
+        (first as usize + lines.len().saturating_sub(1))
+            .to_string()
+            .len()
+    });
+    let white_space = if options.wrap_lines {
+        "pre-wrap"
+    } else {
+        "pre"
+    };
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    writeln!(html, "<title>{}</title>", escape_html(&options.title)).ok();
+    html.push_str("<style>\n");
+    writeln!(
+        html,
+        "body {{ margin: 0; background: {}; color: {}; }}",
+        css_color(options.background),
+        css_color(options.foreground)
+    )
+    .ok();
+    writeln!(
+        html,
+        ".code {{ padding: 16px; font-family: \"{}\", monospace; font-size: {}px; \
+         line-height: 1.5; }}",
+        options.font_family.replace('"', ""),
+        options.font_size
+    )
+    .ok();
+    html.push_str(".line { display: flex; min-height: 1.5em; }\n");
+    writeln!(
+        html,
+        ".text {{ flex: 1; min-width: 0; white-space: {white_space}; overflow-wrap: anywhere; }}"
+    )
+    .ok();
+    if let Some(width) = line_number_width {
+        writeln!(
+            html,
+            ".number {{ flex: none; width: {width}ch; padding-right: 2ch; text-align: right; \
+             color: {}; user-select: none; }}",
+            css_color(options.line_number_color)
+        )
+        .ok();
+    }
+    html.push_str(
+        "@media print { body { -webkit-print-color-adjust: exact; print-color-adjust: exact; } }\n",
+    );
+    html.push_str("</style>\n</head>\n<body>\n<div class=\"code\">\n");
+
+    for (ix, line) in lines.iter().enumerate() {
+        html.push_str("<div class=\"line\">");
+        if let Some(first) = options.first_line_number {
+            write!(
+                html,
+                "<span class=\"number\">{}</span>",
+                first as usize + ix
+            )
+            .ok();
+        }
+        html.push_str("<span class=\"text\">");
+        for (text, style) in line {
+            let css = css_style(style);
+            if css.is_empty() {
+                html.push_str(&escape_html(text));
+            } else {
+                write!(html, "<span style=\"{css}\">{}</span>", escape_html(text)).ok();
+            }
+        }
+        html.push_str("</span></div>\n");
+    }
+
+    html.push_str("</div>\n");
+    if options.print {
+        html.push_str(
+            "<script>window.addEventListener(\"load\", () => window.print());</script>\n",
+        );
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn css_style(style: &HighlightStyle) -> String {
+    let mut properties = Vec::new();
+    if let Some(color) = style.color {
+        properties.push(format!("color: {}", css_color(color)));
+    }
+    if let Some(background) = style.background_color {
+        properties.push(format!("background: {}", css_color(background)));
+    }
+    if let Some(weight) = style.font_weight {
+        properties.push(format!("font-weight: {}", weight.0));
+    }
+    match style.font_style {
+        Some(FontStyle::Italic) => properties.push("font-style: italic".to_string()),
+        Some(FontStyle::Oblique) => properties.push("font-style: oblique".to_string()),
+        Some(FontStyle::Normal) | None => {}
+    }
+    properties.join("; ")
+}
+
+fn css_color(color: Hsla) -> String {
+    let rgba = Rgba::from(color);
+    let channel = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}{:02x}",
+        channel(rgba.r),
+        channel(rgba.g),
+        channel(rgba.b),
+        channel(rgba.a)
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{black, white, FontWeight};
+
+    use super::*;
+
+    fn options(first_line_number: Option<u32>) -> HtmlOptions {
+        HtmlOptions {
+            title: "a <b>.rs".to_string(),
+            font_family: "Zed Mono".to_string(),
+            font_size: 14.,
+            background: white(),
+            foreground: black(),
+            line_number_color: black(),
+            first_line_number,
+            wrap_lines: true,
+            print: false,
+        }
+    }
+
+    #[test]
+    fn test_render_html() {
+        let keyword = HighlightStyle {
+            color: Some(black()),
+            font_weight: Some(FontWeight::BOLD),
+            ..Default::default()
+        };
+        let lines = vec![
+            vec![
+                ("fn".to_string(), keyword),
+                (" a() -> &'a str {".to_string(), HighlightStyle::default()),
+            ],
+            vec![],
+            vec![("}".to_string(), HighlightStyle::default())],
+        ];
+
+        let html = render_html(&lines, &options(Some(9)));
+        assert!(html.contains("<title>a &lt;b&gt;.rs</title>"));
+        assert!(html.contains(
+            "<div class=\"line\"><span class=\"number\">9</span><span class=\"text\">\
+             <span style=\"color: #000000ff; font-weight: 700\">fn</span> a() -&gt; &amp;'a str {\
+             </span></div>"
+        ));
+        assert!(html.contains(
+            "<div class=\"line\"><span class=\"number\">10</span><span class=\"text\"></span></div>"
+        ));
+        assert!(html.contains(".number { flex: none; width: 2ch;"));
+        assert!(html.contains("white-space: pre-wrap;"));
+        assert!(!html.contains("window.print()"));
+
+        let html = render_html(
+            &lines,
+            &HtmlOptions {
+                wrap_lines: false,
+                print: true,
+                ..options(None)
+            },
+        );
+        assert!(!html.contains("class=\"number\""));
+        assert!(html.contains("<div class=\"line\"><span class=\"text\">}</span></div>"));
+        assert!(html.contains("white-space: pre;"));
+        assert!(html.contains("window.print()"));
+    }
+}
@@ -25,7 +25,7 @@ use crate::{
     mouse_context_menu::MouseContextMenu,
     BlockDisposition, BlockProperties, BlockStyle, CustomBlockId, DiffRowHighlight, Editor,
     EditorElement, EditorSnapshot, ExpandAllHunkDiffs, RangeToAnchorExt, RevertFile,
-    RevertSelectedHunks, ToDisplayPoint, ToggleHunkDiff,
+    RevertSelectedHunks, StageSelectedHunks, ToDisplayPoint, ToggleHunkDiff, UnstageSelectedHunks,
 };
 
 #[derive(Debug, Clone)]
@@ -73,6 +73,14 @@ impl Editor {
         cx: &mut ViewContext<Editor>,
     ) {
         let focus_handle = self.focus_handle.clone();
+        // Show the hunk's diff along with the menu.
+        if !self
+            .expanded_hunks
+            .hunks(false)
+            .any(|expanded_hunk| expanded_hunk.hunk_range == hovered_hunk.multi_buffer_range)
+        {
+            self.toggle_hovered_hunk(&hovered_hunk, cx);
+        }
         let expanded = self
             .expanded_hunks
             .hunks(false)
@@ -139,12 +147,48 @@ impl Editor {
                             }
                         }
                     })
+                    .entry("Stage Hunk", Some(StageSelectedHunks.boxed_clone()), {
+                        let editor = editor_handle.clone();
+                        let hunk = hovered_hunk.clone();
+                        move |cx| {
+                            editor.update(cx, |editor, cx| {
+                                let multi_buffer_snapshot = editor.buffer().read(cx).snapshot(cx);
+                                if let Some(hunk) = to_diff_hunk(&hunk, &multi_buffer_snapshot) {
+                                    editor.stage_hunks(vec![hunk], cx);
+                                }
+                            });
+                        }
+                    })
+                    .entry("Unstage Hunk", Some(UnstageSelectedHunks.boxed_clone()), {
+                        let editor = editor_handle.clone();
+                        let hunk = hovered_hunk.clone();
+                        move |cx| {
+                            editor.update(cx, |editor, cx| editor.unstage_hovered_hunk(&hunk, cx));
+                        }
+                    })
                     .action("Revert File", RevertFile.boxed_clone())
             }),
             cx,
         )
     }
 
+    fn unstage_hovered_hunk(&mut self, hovered_hunk: &HoveredHunk, cx: &mut ViewContext<Editor>) {
+        let Some((_, buffer, _)) = self
+            .buffer
+            .read(cx)
+            .excerpt_containing(hovered_hunk.multi_buffer_range.start, cx)
+        else {
+            return;
+        };
+        let start = buffer
+            .read(cx)
+            .summary_for_anchor::<Point>(&hovered_hunk.multi_buffer_range.start.text_anchor);
+        let end = buffer
+            .read(cx)
+            .summary_for_anchor::<Point>(&hovered_hunk.multi_buffer_range.end.text_anchor);
+        self.unstage_rows(buffer, vec![start.row..end.row], cx);
+    }
+
     pub(super) fn toggle_hovered_hunk(
         &mut self,
         hovered_hunk: &HoveredHunk,
@@ -848,6 +892,32 @@ fn editor_with_deleted_text(
                     .ok();
             })
             .detach();
+        let parent_editor_for_staging = parent_editor.clone();
+        let hunk_for_staging = hunk.clone();
+        editor
+            .register_action::<StageSelectedHunks>(move |_, cx| {
+                parent_editor_for_staging
+                    .update(cx, |editor, cx| {
+                        let multi_buffer_snapshot = editor.buffer().read(cx).snapshot(cx);
+                        if let Some(hunk) = to_diff_hunk(&hunk_for_staging, &multi_buffer_snapshot)
+                        {
+                            editor.stage_hunks(vec![hunk], cx);
+                        }
+                    })
+                    .ok();
+            })
+            .detach();
+        let parent_editor_for_unstaging = parent_editor.clone();
+        let hunk_for_unstaging = hunk.clone();
+        editor
+            .register_action::<UnstageSelectedHunks>(move |_, cx| {
+                parent_editor_for_unstaging
+                    .update(cx, |editor, cx| {
+                        editor.unstage_hovered_hunk(&hunk_for_unstaging, cx);
+                    })
+                    .ok();
+            })
+            .detach();
         let hunk = hunk.clone();
         editor
             .register_action::<ToggleHunkDiff>(move |_, cx| {
@@ -8,6 +8,8 @@
 /// Due to the way the data is stored for both visible inlays and the cache, every inlay (and inlay hint) collection is editor-specific, so a single buffer may have multiple sets of inlays of open on different panes.
 use std::{
     cmp,
+    hash::{Hash, Hasher},
+    mem,
     ops::{ControlFlow, Range},
     sync::Arc,
     time::Duration,
@@ -22,9 +24,12 @@ use futures::future;
 use gpui::{AsyncWindowContext, Model, ModelContext, Task, ViewContext};
 use language::{language_settings::InlayHintKind, Buffer, BufferSnapshot};
 use parking_lot::RwLock;
-use project::{InlayHint, ResolveState};
+use project::{
+    InlayHint, InlayHintLabel, LspRequestPriority, LspRequestSchedule, LspRequestSuperseded,
+    ResolveState,
+};
 
-use collections::{hash_map, HashMap, HashSet};
+use collections::{hash_map, FxHasher, HashMap, HashSet};
 use language::language_settings::InlayHintSettings;
 use smol::lock::Semaphore;
 use sum_tree::Bias;
@@ -537,6 +542,30 @@ impl InlayHintCache {
         }
     }
 
+    /// An estimate of the bytes used by the cached hints.
+    pub(super) fn estimated_memory_usage(&self) -> usize {
+        self.hints
+            .values()
+            .map(|excerpt_hints| {
+                let excerpt_hints = excerpt_hints.read();
+                excerpt_hints
+                    .hints_by_id
+                    .values()
+                    .map(|hint| {
+                        let label = match &hint.label {
+                            InlayHintLabel::String(label) => label.len(),
+                            InlayHintLabel::LabelParts(parts) => parts
+                                .iter()
+                                .map(|part| mem::size_of_val(part) + part.value.len())
+                                .sum(),
+                        };
+                        mem::size_of::<(InlayId, InlayHint)>() + label
+                    })
+                    .sum::<usize>()
+            })
+            .sum()
+    }
+
     pub(super) fn clear(&mut self) {
         if !self.update_tasks.is_empty() || !self.hints.is_empty() {
             self.version += 1;
@@ -820,6 +849,7 @@ fn new_update_task(
                                 query,
                                 visible_range.clone(),
                                 query.invalidate.should_invalidate(),
+                                LspRequestSchedule::default(),
                                 cx,
                             )
                         })
@@ -835,7 +865,10 @@ fn new_update_task(
 
         let query_range_failed =
             |range: &Range<language::Anchor>, e: anyhow::Error, cx: &mut AsyncWindowContext| {
-                log::error!("inlay hint update task for range failed: {e:#?}");
+                // Superseded ranges are refetched once they're queried again.
+                if e.downcast_ref::<LspRequestSuperseded>().is_none() {
+                    log::error!("inlay hint update task for range failed: {e:#?}");
+                }
                 editor
                     .update(cx, |editor, cx| {
                         if let Some(task_ranges) = editor
@@ -861,8 +894,24 @@ fn new_update_task(
             query_ranges
                 .before_visible
                 .into_iter()
-                .chain(query_ranges.after_visible.into_iter())
-                .filter_map(|invisible_range| {
+                .enumerate()
+                .map(|(ix, range)| (range, (true, ix)))
+                .chain(
+                    query_ranges
+                        .after_visible
+                        .into_iter()
+                        .enumerate()
+                        .map(|(ix, range)| (range, (false, ix))),
+                )
+                .filter_map(|(invisible_range, position)| {
+                    // While scrolling, the requests for the ranges around the previous
+                    // viewport are superseded by the ones around the new viewport.
+                    let mut hasher = FxHasher::default();
+                    (editor.entity_id(), query.excerpt_id, position).hash(&mut hasher);
+                    let schedule = LspRequestSchedule {
+                        priority: LspRequestPriority::Background,
+                        supersede_key: Some(hasher.finish()),
+                    };
                     let fetch_task = editor
                         .update(&mut cx, |_, cx| {
                             fetch_and_update_hints(
@@ -870,6 +919,7 @@ fn new_update_task(
                                 query,
                                 invisible_range.clone(),
                                 false, // visible screen request already invalidated the entries
+                                schedule,
                                 cx,
                             )
                         })
@@ -891,6 +941,7 @@ fn fetch_and_update_hints(
     query: ExcerptQuery,
     fetch_range: Range<language::Anchor>,
     invalidate: bool,
+    schedule: LspRequestSchedule,
     cx: &mut ViewContext<Editor>,
 ) -> Task<anyhow::Result<()>> {
     cx.spawn(|editor, mut cx| async move {
@@ -950,7 +1001,7 @@ fn fetch_and_update_hints(
                     .and_then(|buffer| {
                         let project = editor.project.as_ref()?;
                         Some(project.update(cx, |project, cx| {
-                            project.inlay_hints(buffer, fetch_range.clone(), cx)
+                            project.inlay_hints(buffer, fetch_range.clone(), schedule, cx)
                         }))
                     })
             })
@@ -1,6 +1,7 @@
 use crate::{
     editor_settings::SeedQuerySetting,
     persistence::{SerializedEditor, DB},
+    scratch,
     scroll::ScrollAnchor,
     Anchor, Autoscroll, Editor, EditorEvent, EditorSettings, ExcerptId, ExcerptRange, MultiBuffer,
     MultiBufferSnapshot, NavigationData, SearchWithinRange, ToPoint as _,
@@ -12,8 +13,8 @@ use futures::future::try_join_all;
 use git::repository::GitFileStatus;
 use gpui::{
     point, AnyElement, AppContext, AsyncWindowContext, Context, Entity, EntityId, EventEmitter,
-    IntoElement, Model, ParentElement, Pixels, SharedString, Styled, Task, View, ViewContext,
-    VisualContext, WeakView, WindowContext,
+    IntoElement, Model, ParentElement, Pixels, PromptLevel, SharedString, Styled, Task, View,
+    ViewContext, VisualContext, WeakView, WindowContext,
 };
 use language::{
     proto::serialize_anchor as serialize_text_anchor, Bias, Buffer, CharKind, Point, SelectionGoal,
@@ -633,6 +634,7 @@ impl Item for Editor {
 
             Some(util::truncate_and_trailoff(&description, MAX_TAB_TITLE_LEN))
         });
+        let conflict_count = self.merge_conflicts.count();
 
         h_flex()
             .gap_2()
@@ -641,6 +643,18 @@ impl Item for Editor {
                     .color(label_color)
                     .italic(params.preview),
             )
+            .when(conflict_count > 0, |this| {
+                let conflicts = if conflict_count == 1 {
+                    "1 conflict".to_string()
+                } else {
+                    format!("{conflict_count} conflicts")
+                };
+                this.child(
+                    Label::new(conflicts)
+                        .size(LabelSize::XSmall)
+                        .color(Color::Conflict),
+                )
+            })
             .when_some(description, |this, description| {
                 this.child(
                     Label::new(description)
@@ -730,9 +744,39 @@ impl Item for Editor {
 
             if buffers.len() == 1 {
                 // Apply full save routine for singleton buffers, to allow to `touch` the file via the editor.
-                project
-                    .update(&mut cx, |project, cx| project.save_buffers(buffers, cx))?
-                    .await?;
+                let save = project
+                    .update(&mut cx, |project, cx| {
+                        project.save_buffers(buffers.clone(), cx)
+                    })?
+                    .await;
+                if let Err(error) = save {
+                    if !project::is_permission_denied(&error) {
+                        return Err(error);
+                    }
+                    // Offer to save files the user isn't allowed to write as an administrator.
+                    let buffer = buffers.into_iter().next().context("no buffer to save")?;
+                    let answer = this.update(&mut cx, |_, cx| {
+                        let path = buffer
+                            .read(cx)
+                            .file()
+                            .map(|file| file.full_path(cx).to_string_lossy().into_owned())
+                            .unwrap_or_default();
+                        cx.prompt(
+                            PromptLevel::Warning,
+                            &format!("You don't have permission to save {path}."),
+                            Some("Do you want to save it as an administrator?"),
+                            &["Save as Administrator", "Cancel"],
+                        )
+                    })?;
+                    if !matches!(answer.await, Ok(0)) {
+                        return Err(error);
+                    }
+                    project
+                        .update(&mut cx, |project, cx| {
+                            project.save_buffer_elevated(buffer, cx)
+                        })?
+                        .await?;
+                }
             } else {
                 // For multi-buffers, only format and save the buffers with changes.
                 // For clean buffers, we simulate saving by calling `Buffer::did_save`,
@@ -948,6 +992,7 @@ impl SerializableItem for Editor {
                         contents: None,
                         language: None,
                         mtime: None,
+                        scratch_id: serialized_editor.scratch_id,
                     }
                 }
             }
@@ -959,7 +1004,12 @@ impl SerializableItem for Editor {
             }
         };
 
+        let scratch_id = serialized_editor.scratch_id;
         let buffer_task = match serialized_editor {
+            SerializedEditor {
+                scratch_id: Some(scratch_id),
+                ..
+            } => scratch::open_scratch_buffer(project.clone(), scratch_id, cx),
             SerializedEditor {
                 path: None,
                 contents: Some(contents),
@@ -1055,7 +1105,12 @@ impl SerializableItem for Editor {
 
             pane.update(&mut cx, |_, cx| {
                 cx.new_view(|cx| {
-                    let mut editor = Editor::for_buffer(buffer, Some(project), cx);
+                    let mut editor = match scratch_id {
+                        Some(scratch_id) => {
+                            Editor::for_scratch_buffer(buffer, project, scratch_id, cx)
+                        }
+                        None => Editor::for_buffer(buffer, Some(project), cx),
+                    };
 
                     editor.read_scroll_position_from_db(item_id, workspace_id, cx);
                     editor
@@ -1080,14 +1135,17 @@ impl SerializableItem for Editor {
             serialize_dirty_buffers = false;
         }
 
-        if closing && !serialize_dirty_buffers {
+        let buffer = self.buffer().read(cx).as_singleton()?;
+        // Scratch buffers are saved on their own, so they're restored whether
+        // or not dirty buffers are.
+        let scratch_id = scratch::scratch_id_for_buffer(&buffer, cx);
+
+        if closing && !serialize_dirty_buffers && scratch_id.is_none() {
             return None;
         }
 
         let workspace_id = workspace.database_id()?;
 
-        let buffer = self.buffer().read(cx).as_singleton()?;
-
         let is_dirty = buffer.read(cx).is_dirty();
         let local_file = buffer.read(cx).file().and_then(|file| file.as_local());
         let path = local_file.map(|file| file.abs_path(cx));
@@ -1098,19 +1156,21 @@ impl SerializableItem for Editor {
         Some(cx.spawn(|_this, cx| async move {
             cx.background_executor()
                 .spawn(async move {
-                    let (contents, language) = if serialize_dirty_buffers && is_dirty {
-                        let contents = snapshot.text();
-                        let language = snapshot.language().map(|lang| lang.name().to_string());
-                        (Some(contents), language)
-                    } else {
-                        (None, None)
-                    };
+                    let (contents, language) =
+                        if serialize_dirty_buffers && is_dirty && scratch_id.is_none() {
+                            let contents = snapshot.text();
+                            let language = snapshot.language().map(|lang| lang.name().to_string());
+                            (Some(contents), language)
+                        } else {
+                            (None, None)
+                        };
 
                     let editor = SerializedEditor {
                         path,
                         contents,
                         language,
                         mtime,
+                        scratch_id,
                     };
 
                     DB.save_serialized_editor(item_id, workspace_id, editor)
@@ -1624,6 +1684,7 @@ mod tests {
                 contents: Some("fn main() {}".to_string()),
                 language: Some("Rust".to_string()),
                 mtime: Some(now),
+                scratch_id: None,
             };
 
             DB.save_serialized_editor(item_id, workspace_id, serialized_editor.clone())
@@ -1655,6 +1716,7 @@ mod tests {
                 contents: None,
                 language: None,
                 mtime: None,
+                scratch_id: None,
             };
 
             DB.save_serialized_editor(item_id, workspace_id, serialized_editor)
@@ -1690,6 +1752,7 @@ mod tests {
                 contents: Some("hello".to_string()),
                 language: Some("Rust".to_string()),
                 mtime: None,
+                scratch_id: None,
             };
 
             DB.save_serialized_editor(item_id, workspace_id, serialized_editor)
@@ -1728,6 +1791,7 @@ mod tests {
                 contents: Some("fn main() {}".to_string()),
                 language: Some("Rust".to_string()),
                 mtime: Some(old_mtime),
+                scratch_id: None,
             };
 
             DB.save_serialized_editor(item_id, workspace_id, serialized_editor)
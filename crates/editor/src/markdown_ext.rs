@@ -0,0 +1,568 @@
+use std::ops::Range;
+
+use gpui::{View, ViewContext, WindowContext};
+use language::Point;
+use multi_buffer::{MultiBufferRow, MultiBufferSnapshot};
+use url::Url;
+
+use crate::{element::register_action, scroll::Autoscroll, Editor, FormatTable};
+
+/// The prefix of a line in a list or a block quote, like `> 1. [ ] `.
+#[derive(Debug, PartialEq)]
+struct ListPrefix<'a> {
+    /// The block quote markers before the list marker, like `> > `.
+    quote: &'a str,
+    marker: Option<ListMarker>,
+    /// The range of the bullet, or of the number of an ordered list item, in the line.
+    marker_range: Range<usize>,
+    task: bool,
+    /// The length of the prefix in bytes, including the leading whitespace.
+    len: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ListMarker {
+    Bullet(char),
+    Ordered { number: u32, delimiter: char },
+}
+
+impl ListPrefix<'_> {
+    fn continuation(&self) -> String {
+        let mut continuation = self.quote.to_string();
+        match self.marker {
+            Some(ListMarker::Bullet(bullet)) => {
+                continuation.push(bullet);
+                continuation.push(' ');
+            }
+            Some(ListMarker::Ordered { number, delimiter }) => {
+                continuation.push_str(&(number + 1).to_string());
+                continuation.push(delimiter);
+                continuation.push(' ');
+            }
+            None => {}
+        }
+        if self.task {
+            continuation.push_str("[ ] ");
+        }
+        continuation
+    }
+}
+
+fn parse_list_prefix(line: &str) -> Option<ListPrefix<'_>> {
+    let indent = line.len() - line.trim_start().len();
+    let mut ix = indent;
+
+    let bytes = line.as_bytes();
+    while bytes.get(ix) == Some(&b'>') {
+        ix += 1;
+        if bytes.get(ix) == Some(&b' ') {
+            ix += 1;
+        }
+    }
+    let quote = &line[indent..ix];
+
+    let marker_start = ix;
+    let marker = match bytes.get(ix) {
+        Some(&bullet @ (b'-' | b'*' | b'+')) => {
+            ix += 1;
+            Some(ListMarker::Bullet(bullet as char))
+        }
+        Some(byte) if byte.is_ascii_digit() => {
+            let digits = line[ix..]
+                .bytes()
+                .take_while(|byte| byte.is_ascii_digit())
+                .count();
+            match bytes.get(ix + digits) {
+                Some(&delimiter @ (b'.' | b')')) if digits <= 9 => {
+                    let number = line[ix..ix + digits].parse().ok()?;
+                    ix += digits + 1;
+                    Some(ListMarker::Ordered {
+                        number,
+                        delimiter: delimiter as char,
+                    })
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    let marker_range = match marker {
+        Some(ListMarker::Ordered { .. }) => marker_start..ix - 1,
+        _ => marker_start..ix,
+    };
+
+    let mut task = false;
+    if marker.is_some() {
+        // A list marker must be followed by a space, otherwise the line is a paragraph.
+        if bytes.get(ix) != Some(&b' ') {
+            return None;
+        }
+        ix += 1;
+        if let Some(rest) = line.get(ix..) {
+            if ["[ ] ", "[x] ", "[X] "]
+                .iter()
+                .any(|checkbox| rest.starts_with(checkbox))
+            {
+                task = true;
+                ix += 4;
+            }
+        }
+    } else if quote.is_empty() {
+        return None;
+    }
+
+    Some(ListPrefix {
+        quote,
+        marker,
+        marker_range,
+        task,
+        len: ix,
+    })
+}
+
+/// What to do with the list or block quote of the current line when inserting a newline.
+#[derive(Debug, PartialEq)]
+pub(crate) enum ListContinuation {
+    /// Start the new line with the given prefix, renumbering the items of an ordered list
+    /// after it.
+    Continue { prefix: String, renumber: bool },
+    /// End the list by removing the prefix of its empty last item.
+    End,
+}
+
+/// Returns how to continue the list or block quote of a line when inserting a newline at the
+/// given column, which has to be after the list marker.
+pub(crate) fn list_continuation(line: &str, column: usize) -> Option<ListContinuation> {
+    let prefix = parse_list_prefix(line)?;
+    if column < prefix.len.min(line.trim_end().len()) {
+        return None;
+    }
+    if line[prefix.len.min(line.len())..].trim().is_empty() {
+        return Some(ListContinuation::End);
+    }
+    Some(ListContinuation::Continue {
+        prefix: prefix.continuation(),
+        renumber: matches!(prefix.marker, Some(ListMarker::Ordered { .. })),
+    })
+}
+
+/// Returns the edits that renumber the items of the ordered list started by the first of the
+/// given lines, as the index of the line, the range of the number in it and the new number.
+fn ordered_list_renumbering<'a>(
+    lines: impl IntoIterator<Item = &'a str>,
+) -> Vec<(usize, Range<usize>, String)> {
+    let mut edits = Vec::new();
+    let mut lines = lines.into_iter().enumerate();
+    let Some((_, first_line)) = lines.next() else {
+        return edits;
+    };
+    let Some(first_item) = parse_list_prefix(first_line) else {
+        return edits;
+    };
+    let Some(ListMarker::Ordered {
+        number,
+        delimiter: list_delimiter,
+    }) = first_item.marker
+    else {
+        return edits;
+    };
+    let indent = first_line.len() - first_line.trim_start().len();
+
+    let mut next_number = number + 1;
+    for (ix, line) in lines {
+        if line.trim().is_empty() {
+            break;
+        }
+        // Skip the nested lists and paragraphs of the items.
+        let line_indent = line.len() - line.trim_start().len();
+        if line_indent > indent {
+            continue;
+        }
+        if line_indent < indent {
+            break;
+        }
+
+        let Some(item) = parse_list_prefix(line) else {
+            break;
+        };
+        match item.marker {
+            Some(ListMarker::Ordered { number, delimiter })
+                if delimiter == list_delimiter && item.quote == first_item.quote =>
+            {
+                if number != next_number {
+                    edits.push((ix, item.marker_range, next_number.to_string()));
+                }
+                next_number += 1;
+            }
+            _ => break,
+        }
+    }
+    edits
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnAlignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+fn split_table_row(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = match line.strip_suffix('|') {
+        Some(stripped) if !stripped.ends_with('\\') => stripped,
+        _ => line,
+    };
+
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut escaped = false;
+    for c in line.chars() {
+        if c == '|' && !escaped {
+            cells.push(cell.trim().to_string());
+            cell.clear();
+        } else {
+            cell.push(c);
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+fn parse_delimiter_cell(cell: &str) -> Option<ColumnAlignment> {
+    let left = cell.starts_with(':');
+    let right = cell.ends_with(':') && cell.len() > 1;
+    let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+    if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+        return None;
+    }
+    Some(match (left, right) {
+        (true, true) => ColumnAlignment::Center,
+        (true, false) => ColumnAlignment::Left,
+        (false, true) => ColumnAlignment::Right,
+        (false, false) => ColumnAlignment::None,
+    })
+}
+
+/// Aligns the columns of a pipe table, returning `None` when the lines aren't a table.
+pub(crate) fn format_table(lines: &[&str]) -> Option<String> {
+    let indent = lines.first()?.len() - lines.first()?.trim_start().len();
+    let indent = &lines[0][..indent];
+
+    let mut rows = lines
+        .iter()
+        .map(|line| split_table_row(line))
+        .collect::<Vec<_>>();
+    let alignments = rows
+        .get(1)?
+        .iter()
+        .map(|cell| parse_delimiter_cell(cell))
+        .collect::<Option<Vec<_>>>()?;
+    if !lines.iter().all(|line| line.contains('|')) {
+        return None;
+    }
+
+    let column_count = rows.iter().map(Vec::len).max()?;
+    for row in &mut rows {
+        row.resize(column_count, String::new());
+    }
+    let widths = (0..column_count)
+        .map(|column| {
+            rows.iter()
+                .enumerate()
+                .filter(|(ix, _)| *ix != 1)
+                .map(|(_, row)| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+                .max(3)
+        })
+        .collect::<Vec<_>>();
+
+    let formatted_rows = rows.iter().enumerate().map(|(row_ix, row)| {
+        let cells = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, &width))| {
+                let alignment = alignments
+                    .get(column)
+                    .copied()
+                    .unwrap_or(ColumnAlignment::None);
+                if row_ix == 1 {
+                    return match alignment {
+                        ColumnAlignment::None => "-".repeat(width),
+                        ColumnAlignment::Left => format!(":{}", "-".repeat(width - 1)),
+                        ColumnAlignment::Right => format!("{}:", "-".repeat(width - 1)),
+                        ColumnAlignment::Center => format!(":{}:", "-".repeat(width - 2)),
+                    };
+                }
+
+                let padding = width - cell.chars().count();
+                let (left, right) = match alignment {
+                    ColumnAlignment::None | ColumnAlignment::Left => (0, padding),
+                    ColumnAlignment::Right => (padding, 0),
+                    ColumnAlignment::Center => (padding / 2, padding - padding / 2),
+                };
+                format!("{}{cell}{}", " ".repeat(left), " ".repeat(right))
+            });
+        format!("{indent}| {} |", cells.collect::<Vec<_>>().join(" | "))
+    });
+    Some(formatted_rows.collect::<Vec<_>>().join("\n"))
+}
+
+fn is_url(text: &str) -> bool {
+    !text.contains(char::is_whitespace)
+        && Url::parse(text).map_or(false, |url| {
+            matches!(url.scheme(), "http" | "https" | "ftp" | "mailto")
+        })
+}
+
+/// Returns the link to insert when pasting the given clipboard text over the selected text,
+/// if the clipboard holds a URL.
+pub(crate) fn markdown_link(selected_text: &str, clipboard_text: &str) -> Option<String> {
+    let url = clipboard_text.trim();
+    if selected_text.trim().is_empty()
+        || selected_text.contains('\n')
+        || is_url(selected_text.trim())
+        || !is_url(url)
+    {
+        return None;
+    }
+    Some(format!("[{selected_text}]({url})"))
+}
+
+fn markdown_editing_at(snapshot: &MultiBufferSnapshot, offset: usize) -> bool {
+    snapshot
+        .language_scope_at(offset)
+        .map_or(false, |scope| scope.markdown_editing())
+}
+
+fn line_text(snapshot: &MultiBufferSnapshot, row: u32) -> String {
+    let end = Point::new(row, snapshot.line_len(MultiBufferRow(row)));
+    snapshot.text_for_range(Point::new(row, 0)..end).collect()
+}
+
+/// Renumbers the ordered lists whose items were inserted on the given rows.
+pub(crate) fn renumber_ordered_lists(
+    editor: &mut Editor,
+    rows: Vec<u32>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let max_row = snapshot.max_point().row;
+    let mut edits = Vec::new();
+    for row in rows {
+        let lines = (row..=max_row)
+            .map(|row| line_text(&snapshot, row))
+            .take_while(|line| !line.trim().is_empty())
+            .collect::<Vec<_>>();
+        for (ix, range, number) in ordered_list_renumbering(lines.iter().map(String::as_str)) {
+            let row = row + ix as u32;
+            edits.push((
+                Point::new(row, range.start as u32)..Point::new(row, range.end as u32),
+                number,
+            ));
+        }
+    }
+    if !edits.is_empty() {
+        editor.edit(edits, cx);
+    }
+}
+
+/// Replaces each selection with a link when pasting a URL over selected text in Markdown,
+/// returning whether it did.
+pub(crate) fn paste_as_link(
+    editor: &mut Editor,
+    clipboard_text: &str,
+    cx: &mut ViewContext<Editor>,
+) -> bool {
+    if editor.read_only(cx) {
+        return false;
+    }
+
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let selections = editor.selections.all::<usize>(cx);
+    let Some(edits) = selections
+        .iter()
+        .map(|selection| {
+            if selection.is_empty() || !markdown_editing_at(&snapshot, selection.start) {
+                return None;
+            }
+            let selected_text = snapshot
+                .text_for_range(selection.range())
+                .collect::<String>();
+            let link = markdown_link(&selected_text, clipboard_text)?;
+            Some((selection.range(), link))
+        })
+        .collect::<Option<Vec<_>>>()
+    else {
+        return false;
+    };
+
+    editor.transact(cx, |editor, cx| {
+        editor.edit(edits, cx);
+        let selections = editor.selections.all::<usize>(cx);
+        editor.change_selections(Some(Autoscroll::fit()), cx, |s| s.select(selections));
+    });
+    true
+}
+
+fn format_table_action(editor: &mut Editor, _: &FormatTable, cx: &mut ViewContext<Editor>) {
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let max_row = snapshot.max_point().row;
+    let is_table_line = |row: u32| {
+        let line = line_text(&snapshot, row);
+        !line.trim().is_empty() && line.contains('|')
+    };
+
+    let mut table_rows: Vec<Range<u32>> = Vec::new();
+    for selection in editor.selections.all::<Point>(cx) {
+        let row = selection.head().row;
+        if !is_table_line(row)
+            || table_rows.iter().any(|rows| rows.contains(&row))
+            || !markdown_editing_at(&snapshot, snapshot.point_to_offset(selection.head()))
+        {
+            continue;
+        }
+        let mut start_row = row;
+        while start_row > 0 && is_table_line(start_row - 1) {
+            start_row -= 1;
+        }
+        let mut end_row = row;
+        while end_row < max_row && is_table_line(end_row + 1) {
+            end_row += 1;
+        }
+        table_rows.push(start_row..end_row + 1);
+    }
+
+    let edits = table_rows
+        .into_iter()
+        .filter_map(|rows| {
+            let lines = rows
+                .clone()
+                .map(|row| line_text(&snapshot, row))
+                .collect::<Vec<_>>();
+            let lines = lines.iter().map(String::as_str).collect::<Vec<_>>();
+            let formatted = format_table(&lines)?;
+            let end_row = rows.end - 1;
+            let range = Point::new(rows.start, 0)
+                ..Point::new(end_row, snapshot.line_len(MultiBufferRow(end_row)));
+            Some((range, formatted))
+        })
+        .collect::<Vec<_>>();
+    if !edits.is_empty() {
+        editor.transact(cx, |editor, cx| editor.edit(edits, cx));
+    }
+}
+
+pub fn apply_related_actions(editor: &View<Editor>, cx: &mut WindowContext) {
+    if editor.update(cx, |editor, cx| {
+        let snapshot = editor.buffer.read(cx).snapshot(cx);
+        let offset = editor.selections.newest::<usize>(cx).head();
+        markdown_editing_at(&snapshot, offset)
+    }) {
+        register_action(editor, cx, format_table_action);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_continuation() {
+        let continue_with = |prefix: &str, renumber| {
+            Some(ListContinuation::Continue {
+                prefix: prefix.to_string(),
+                renumber,
+            })
+        };
+
+        assert_eq!(list_continuation("- one", 5), continue_with("- ", false));
+        assert_eq!(list_continuation("  * one", 7), continue_with("* ", false));
+        assert_eq!(list_continuation("9. nine", 7), continue_with("10. ", true));
+        assert_eq!(list_continuation("1) one", 6), continue_with("2) ", true));
+        assert_eq!(list_continuation("> quote", 7), continue_with("> ", false));
+        assert_eq!(
+            list_continuation("> - [x] done", 12),
+            continue_with("> - [ ] ", false)
+        );
+
+        // Cursor before the marker, or lines that aren't list items.
+        assert_eq!(list_continuation("- one", 1), None);
+        assert_eq!(list_continuation("-one", 4), None);
+        assert_eq!(list_continuation("2024.", 5), None);
+        assert_eq!(list_continuation("text", 4), None);
+
+        assert_eq!(list_continuation("- ", 2), Some(ListContinuation::End));
+        assert_eq!(list_continuation("3. ", 3), Some(ListContinuation::End));
+        assert_eq!(list_continuation("> ", 2), Some(ListContinuation::End));
+    }
+
+    #[test]
+    fn test_ordered_list_renumbering() {
+        let lines = [
+            "3. new",
+            "3. three",
+            "   nested paragraph",
+            "   1. nested item",
+            "4. four",
+            "- other list",
+            "5. five",
+        ];
+        assert_eq!(
+            ordered_list_renumbering(lines),
+            vec![(1, 0..1, "4".to_string()), (4, 0..1, "5".to_string())]
+        );
+
+        let lines = ["> 9) new", "> 9) nine", "> 1. other delimiter"];
+        assert_eq!(
+            ordered_list_renumbering(lines),
+            vec![(1, 2..3, "10".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_format_table() {
+        let table = ["| Name | Value |", "|:-|-:|", "| a | 1 |", "|long name|"];
+        assert_eq!(
+            format_table(&table).unwrap(),
+            [
+                "| Name      | Value |",
+                "| :-------- | ----: |",
+                "| a         |     1 |",
+                "| long name |       |",
+            ]
+            .join("\n")
+        );
+
+        let table = ["  a | b \\| c", "  :-: | ---", "  x | y"];
+        assert_eq!(
+            format_table(&table).unwrap(),
+            [
+                "  |  a  | b \\| c |",
+                "  | :-: | ------ |",
+                "  |  x  | y      |",
+            ]
+            .join("\n")
+        );
+
+        assert_eq!(format_table(&["| a | b |", "| c | d |"]), None);
+        assert_eq!(format_table(&["| a | b |"]), None);
+    }
+
+    #[test]
+    fn test_markdown_link() {
+        assert_eq!(
+            markdown_link("Zed", "https://zed.dev\n"),
+            Some("[Zed](https://zed.dev)".to_string())
+        );
+        assert_eq!(markdown_link("Zed", "zed.dev"), None);
+        assert_eq!(markdown_link("Zed", "not a url"), None);
+        assert_eq!(markdown_link("https://zed.dev", "https://zed.dev"), None);
+        assert_eq!(markdown_link("two\nlines", "https://zed.dev"), None);
+    }
+}
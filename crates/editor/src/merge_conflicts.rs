@@ -0,0 +1,498 @@
+use std::{
+    ops::{Range, RangeInclusive},
+    time::Duration,
+};
+
+use collections::HashSet;
+use gpui::{Model, Task, WeakView};
+use language::Buffer;
+use multi_buffer::{Anchor, MultiBufferSnapshot};
+use ui::{prelude::*, Tooltip};
+
+use crate::{
+    display_map::{
+        BlockContext, BlockDisposition, BlockProperties, BlockStyle, CustomBlockId, RenderBlock,
+    },
+    open_comparison,
+    scroll::Autoscroll,
+    AcceptConflictBoth, AcceptConflictOurs, AcceptConflictTheirs, CompareConflict, Editor,
+    EditorEvent, EditorMode, GoToNextConflict, GoToPrevConflict,
+};
+
+const MERGE_CONFLICTS_DEBOUNCE: Duration = Duration::from_millis(100);
+
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR_MARKER: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+
+/// A conflict region delimited by conflict markers, as byte ranges of the text.
+#[derive(Debug, PartialEq, Eq)]
+struct ConflictRegion {
+    /// The whole region, from the start of the `<<<<<<<` line to the end of the
+    /// `>>>>>>>` line.
+    range: Range<usize>,
+    /// The lines between the `<<<<<<<` marker and the `|||||||` or `=======`
+    /// marker.
+    ours: Range<usize>,
+    /// The lines between the `=======` and `>>>>>>>` markers.
+    theirs: Range<usize>,
+    ours_label: String,
+    theirs_label: String,
+}
+
+/// Returns the conflict regions of the text. Incomplete regions are ignored.
+fn parse_conflict_regions(text: &str) -> Vec<ConflictRegion> {
+    struct PartialRegion {
+        start: usize,
+        ours_start: usize,
+        ours_end: Option<usize>,
+        theirs_start: Option<usize>,
+        ours_label: String,
+    }
+
+    let mut regions = Vec::new();
+    let mut current: Option<PartialRegion> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        if let Some(label) = marker_label(line, OURS_MARKER) {
+            current = Some(PartialRegion {
+                start: line_start,
+                ours_start: offset,
+                ours_end: None,
+                theirs_start: None,
+                ours_label: label.to_string(),
+            });
+            continue;
+        }
+        let Some(region) = current.as_mut() else {
+            continue;
+        };
+        if marker_label(line, BASE_MARKER).is_some() {
+            region.ours_end.get_or_insert(line_start);
+        } else if line.trim_end() == SEPARATOR_MARKER && region.theirs_start.is_none() {
+            region.ours_end.get_or_insert(line_start);
+            region.theirs_start = Some(offset);
+        } else if let Some(label) = marker_label(line, THEIRS_MARKER) {
+            if let Some(region) = current.take() {
+                if let Some((ours_end, theirs_start)) = region.ours_end.zip(region.theirs_start) {
+                    regions.push(ConflictRegion {
+                        range: region.start..offset,
+                        ours: region.ours_start..ours_end,
+                        theirs: theirs_start..line_start,
+                        ours_label: region.ours_label,
+                        theirs_label: label.to_string(),
+                    });
+                }
+            }
+        }
+    }
+    regions
+}
+
+/// Returns the label following the marker when the line is a conflict marker.
+fn marker_label<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+    let rest = line.strip_prefix(marker)?;
+    if rest.is_empty() {
+        Some(rest)
+    } else {
+        rest.strip_prefix(' ').map(str::trim)
+    }
+}
+
+/// A merge conflict in the buffer of an editor.
+#[derive(Clone, Debug)]
+pub(crate) struct Conflict {
+    range: Range<Anchor>,
+    ours: Range<Anchor>,
+    theirs: Range<Anchor>,
+    /// The rows of the `<<<<<<<` marker and of our side.
+    ours_rows: RangeInclusive<Anchor>,
+    /// The rows of their side and of the `>>>>>>>` marker.
+    theirs_rows: RangeInclusive<Anchor>,
+    ours_label: SharedString,
+    theirs_label: SharedString,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Resolution {
+    Ours,
+    Theirs,
+    Both,
+}
+
+struct ConflictOursHighlight;
+struct ConflictTheirsHighlight;
+
+/// The merge conflicts of a singleton buffer, which are found by parsing the
+/// conflict markers of its text.
+#[derive(Default)]
+pub(crate) struct MergeConflictState {
+    conflicts: Vec<Conflict>,
+    blocks: Vec<CustomBlockId>,
+    parse_task: Option<Task<()>>,
+}
+
+impl MergeConflictState {
+    pub(crate) fn count(&self) -> usize {
+        self.conflicts.len()
+    }
+}
+
+fn conflicts_buffer(editor: &Editor, cx: &ViewContext<Editor>) -> Option<Model<Buffer>> {
+    if editor.mode != EditorMode::Full || editor.read_only(cx) {
+        return None;
+    }
+    editor.buffer.read(cx).as_singleton()
+}
+
+pub(crate) fn refresh_merge_conflicts(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let Some(buffer) = conflicts_buffer(editor, cx) else {
+        editor.merge_conflicts.parse_task = None;
+        if !editor.merge_conflicts.conflicts.is_empty() {
+            set_conflicts(editor, Vec::new(), cx);
+        }
+        return;
+    };
+
+    let snapshot = buffer.read(cx).snapshot();
+    editor.merge_conflicts.parse_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor()
+            .timer(MERGE_CONFLICTS_DEBOUNCE)
+            .await;
+        let (regions, snapshot) = cx
+            .background_executor()
+            .spawn(async move { (parse_conflict_regions(&snapshot.text()), snapshot) })
+            .await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                if regions.is_empty() && editor.merge_conflicts.conflicts.is_empty() {
+                    return;
+                }
+                let multi_buffer_snapshot = editor.buffer.read(cx).snapshot(cx);
+                let Some((excerpt_id, _, _)) = multi_buffer_snapshot.as_singleton() else {
+                    return;
+                };
+                let anchor = |offset: usize| {
+                    multi_buffer_snapshot
+                        .anchor_in_excerpt(*excerpt_id, snapshot.anchor_before(offset))
+                };
+                let conflicts = regions
+                    .into_iter()
+                    .filter_map(|region| {
+                        Some(Conflict {
+                            range: anchor(region.range.start)?..anchor(region.range.end)?,
+                            ours: anchor(region.ours.start)?..anchor(region.ours.end)?,
+                            theirs: anchor(region.theirs.start)?..anchor(region.theirs.end)?,
+                            ours_rows: anchor(region.range.start)?
+                                ..=anchor(region.ours.end.saturating_sub(1))?,
+                            theirs_rows: anchor(region.theirs.start)?
+                                ..=anchor(region.range.end.saturating_sub(1))?,
+                            ours_label: region.ours_label.into(),
+                            theirs_label: region.theirs_label.into(),
+                        })
+                    })
+                    .collect();
+                set_conflicts(editor, conflicts, cx);
+            })
+            .ok();
+    }));
+}
+
+fn set_conflicts(editor: &mut Editor, conflicts: Vec<Conflict>, cx: &mut ViewContext<Editor>) {
+    let count_changed = editor.merge_conflicts.conflicts.len() != conflicts.len();
+    editor.merge_conflicts.conflicts = conflicts;
+
+    let old_blocks = editor
+        .merge_conflicts
+        .blocks
+        .drain(..)
+        .collect::<HashSet<_>>();
+    if !old_blocks.is_empty() {
+        editor.remove_blocks(old_blocks, None, cx);
+    }
+    editor.clear_row_highlights::<ConflictOursHighlight>();
+    editor.clear_row_highlights::<ConflictTheirsHighlight>();
+
+    let ours_color = cx.theme().status().created_background;
+    let theirs_color = cx.theme().status().info_background;
+    let weak_editor = cx.view().downgrade();
+    let mut blocks = Vec::new();
+    for (ix, conflict) in editor
+        .merge_conflicts
+        .conflicts
+        .clone()
+        .into_iter()
+        .enumerate()
+    {
+        editor.highlight_rows::<ConflictOursHighlight>(
+            conflict.ours_rows.clone(),
+            Some(ours_color),
+            false,
+            cx,
+        );
+        editor.highlight_rows::<ConflictTheirsHighlight>(
+            conflict.theirs_rows.clone(),
+            Some(theirs_color),
+            false,
+            cx,
+        );
+        blocks.push(BlockProperties {
+            position: conflict.range.start,
+            height: 1,
+            style: BlockStyle::Flex,
+            render: render_conflict_actions(weak_editor.clone(), ix, conflict),
+            disposition: BlockDisposition::Above,
+            priority: 0,
+        });
+    }
+    editor.merge_conflicts.blocks = editor.insert_blocks(blocks, None, cx);
+
+    if count_changed {
+        cx.emit(EditorEvent::TitleChanged);
+    }
+    cx.notify();
+}
+
+fn render_conflict_actions(editor: WeakView<Editor>, ix: usize, conflict: Conflict) -> RenderBlock {
+    Box::new(move |cx: &mut BlockContext| {
+        let button = |id: &'static str, label: &'static str, tooltip: Option<SharedString>| {
+            Button::new((id, ix), label)
+                .label_size(LabelSize::Small)
+                .color(Color::Muted)
+                .style(ButtonStyle::Transparent)
+                .when_some(tooltip, |button, tooltip| {
+                    button.tooltip(move |cx| Tooltip::text(tooltip.clone(), cx))
+                })
+        };
+        let on_click = |resolution: Option<Resolution>| {
+            let editor = editor.clone();
+            let conflict = conflict.clone();
+            move |_: &gpui::ClickEvent, cx: &mut WindowContext| {
+                editor
+                    .update(cx, |editor, cx| match resolution {
+                        Some(resolution) => editor.resolve_conflict(&conflict, resolution, cx),
+                        None => editor.compare_conflict_sides(&conflict, cx),
+                    })
+                    .ok();
+            }
+        };
+        let label = |label: &SharedString| (!label.is_empty()).then(|| label.clone());
+
+        h_flex()
+            .id(("conflict-actions", ix))
+            .h(cx.line_height)
+            .pl(cx.anchor_x)
+            .gap_1()
+            .child(
+                button("accept-ours", "Accept Ours", label(&conflict.ours_label))
+                    .on_click(on_click(Some(Resolution::Ours))),
+            )
+            .child(
+                button(
+                    "accept-theirs",
+                    "Accept Theirs",
+                    label(&conflict.theirs_label),
+                )
+                .on_click(on_click(Some(Resolution::Theirs))),
+            )
+            .child(
+                button("accept-both", "Accept Both", None)
+                    .on_click(on_click(Some(Resolution::Both))),
+            )
+            .child(button("compare", "Compare", None).on_click(on_click(None)))
+            .into_any_element()
+    })
+}
+
+impl Editor {
+    /// Moves the cursor to the start of the next merge conflict.
+    pub fn go_to_next_conflict(&mut self, _: &GoToNextConflict, cx: &mut ViewContext<Self>) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest_anchor().head();
+        let conflicts = &self.merge_conflicts.conflicts;
+        let next = conflicts
+            .iter()
+            .find(|conflict| conflict.range.start.cmp(&head, &snapshot).is_gt())
+            .or_else(|| conflicts.first());
+        if let Some(position) = next.map(|conflict| conflict.range.start) {
+            self.select_conflict_start(position, cx);
+        }
+    }
+
+    /// Moves the cursor to the start of the previous merge conflict.
+    pub fn go_to_prev_conflict(&mut self, _: &GoToPrevConflict, cx: &mut ViewContext<Self>) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest_anchor().head();
+        let conflicts = &self.merge_conflicts.conflicts;
+        let prev = conflicts
+            .iter()
+            .rev()
+            .find(|conflict| conflict.range.start.cmp(&head, &snapshot).is_lt())
+            .or_else(|| conflicts.last());
+        if let Some(position) = prev.map(|conflict| conflict.range.start) {
+            self.select_conflict_start(position, cx);
+        }
+    }
+
+    pub fn accept_conflict_ours(&mut self, _: &AcceptConflictOurs, cx: &mut ViewContext<Self>) {
+        if let Some(conflict) = self.conflict_at_cursor(cx) {
+            self.resolve_conflict(&conflict, Resolution::Ours, cx);
+        }
+    }
+
+    pub fn accept_conflict_theirs(&mut self, _: &AcceptConflictTheirs, cx: &mut ViewContext<Self>) {
+        if let Some(conflict) = self.conflict_at_cursor(cx) {
+            self.resolve_conflict(&conflict, Resolution::Theirs, cx);
+        }
+    }
+
+    pub fn accept_conflict_both(&mut self, _: &AcceptConflictBoth, cx: &mut ViewContext<Self>) {
+        if let Some(conflict) = self.conflict_at_cursor(cx) {
+            self.resolve_conflict(&conflict, Resolution::Both, cx);
+        }
+    }
+
+    pub fn compare_conflict(&mut self, _: &CompareConflict, cx: &mut ViewContext<Self>) {
+        if let Some(conflict) = self.conflict_at_cursor(cx) {
+            self.compare_conflict_sides(&conflict, cx);
+        }
+    }
+
+    fn conflict_at_cursor(&self, cx: &ViewContext<Self>) -> Option<Conflict> {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest_anchor().head();
+        self.merge_conflicts
+            .conflicts
+            .iter()
+            .find(|conflict| {
+                conflict.range.start.cmp(&head, &snapshot).is_le()
+                    && conflict.range.end.cmp(&head, &snapshot).is_ge()
+            })
+            .cloned()
+    }
+
+    fn select_conflict_start(&mut self, position: Anchor, cx: &mut ViewContext<Self>) {
+        self.change_selections(Some(Autoscroll::center()), cx, |selections| {
+            selections.select_anchor_ranges([position..position]);
+        });
+    }
+
+    /// Replaces the conflict region, markers included, with the chosen sides,
+    /// as a single transaction.
+    fn resolve_conflict(
+        &mut self,
+        conflict: &Conflict,
+        resolution: Resolution,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if self.read_only(cx) {
+            return;
+        }
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let text = match resolution {
+            Resolution::Ours => side_text(&snapshot, &conflict.ours),
+            Resolution::Theirs => side_text(&snapshot, &conflict.theirs),
+            Resolution::Both => {
+                let mut text = side_text(&snapshot, &conflict.ours);
+                text.push_str(&side_text(&snapshot, &conflict.theirs));
+                text
+            }
+        };
+        let range = conflict.range.clone();
+        self.transact(cx, |editor, cx| {
+            editor.buffer.update(cx, |buffer, cx| {
+                buffer.edit([(range.clone(), text)], None, cx);
+            });
+            editor.change_selections(Some(Autoscroll::fit()), cx, |selections| {
+                selections.select_anchor_ranges([range.start..range.start]);
+            });
+        });
+    }
+
+    /// Opens their side of the conflict in a read-only editor, showing how it
+    /// differs from our side.
+    fn compare_conflict_sides(&mut self, conflict: &Conflict, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace() else {
+            return;
+        };
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let ours = side_text(&snapshot, &conflict.ours);
+        let theirs = side_text(&snapshot, &conflict.theirs);
+        let language = self
+            .buffer
+            .read(cx)
+            .as_singleton()
+            .and_then(|buffer| buffer.read(cx).language().cloned());
+        let label = |label: &SharedString, fallback: &str| {
+            if label.is_empty() {
+                fallback.to_string()
+            } else {
+                label.to_string()
+            }
+        };
+        let title = format!(
+            "{} ({} ↔ {})",
+            self.title(cx),
+            label(&conflict.ours_label, "ours"),
+            label(&conflict.theirs_label, "theirs")
+        );
+        workspace.update(cx, |workspace, cx| {
+            open_comparison(workspace, title, ours, theirs, language, cx);
+        });
+    }
+}
+
+fn side_text(snapshot: &MultiBufferSnapshot, range: &Range<Anchor>) -> String {
+    snapshot.text_for_range(range.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_conflict_regions() {
+        let text = "\
+fn main() {
+<<<<<<< HEAD
+    println!(\"ours\");
+=======
+    println!(\"theirs\");
+>>>>>>> feature
+}
+<<<<<<< ours
+a
+||||||| base
+b
+=======
+>>>>>>> theirs
+<<<<<<< unterminated
+c
+";
+        let regions = parse_conflict_regions(text);
+        let slice = |range: &Range<usize>| &text[range.clone()];
+        assert_eq!(regions.len(), 2);
+
+        assert_eq!(
+            slice(&regions[0].range),
+            "<<<<<<< HEAD\n    println!(\"ours\");\n=======\n    println!(\"theirs\");\n>>>>>>> feature\n"
+        );
+        assert_eq!(slice(&regions[0].ours), "    println!(\"ours\");\n");
+        assert_eq!(slice(&regions[0].theirs), "    println!(\"theirs\");\n");
+        assert_eq!(regions[0].ours_label, "HEAD");
+        assert_eq!(regions[0].theirs_label, "feature");
+
+        assert_eq!(slice(&regions[1].ours), "a\n");
+        assert_eq!(slice(&regions[1].theirs), "");
+        assert_eq!(regions[1].ours_label, "ours");
+        assert_eq!(regions[1].theirs_label, "theirs");
+
+        assert!(parse_conflict_regions("a\n=======\n>>>>>>> b\n").is_empty());
+        assert!(parse_conflict_regions("<<<<<<<< not a marker\n=======\n>>>>>>>\n").is_empty());
+    }
+}
@@ -1,10 +1,10 @@
 use std::ops::Range;
 
 use crate::{
-    actions::Format, selections_collection::SelectionsCollection, Copy, CopyPermalinkToLine, Cut,
-    DisplayPoint, DisplaySnapshot, Editor, EditorMode, FindAllReferences, GoToDeclaration,
-    GoToDefinition, GoToImplementation, GoToTypeDefinition, Paste, Rename, RevealInFileManager,
-    SelectMode, ToDisplayPoint, ToggleCodeActions,
+    actions::Format, selections_collection::SelectionsCollection, AddReviewComment, Copy,
+    CopyPermalinkToLine, Cut, DisplayPoint, DisplaySnapshot, Editor, EditorMode, FindAllReferences,
+    GoToDeclaration, GoToDefinition, GoToImplementation, GoToTypeDefinition, OpenPermalinkToLine,
+    Paste, Rename, RevealInFileManager, SelectMode, ToDisplayPoint, ToggleCodeActions,
 };
 use gpui::prelude::FluentBuilder;
 use gpui::{DismissEvent, Pixels, Point, Subscription, View, ViewContext};
@@ -175,6 +175,7 @@ pub fn deploy_context_menu(
                         deployed_from_indicator: None,
                     }),
                 )
+                .action("Add Review Comment", Box::new(AddReviewComment))
                 .separator()
                 .action("Cut", Box::new(Cut))
                 .action("Copy", Box::new(Copy))
@@ -187,7 +188,8 @@ pub fn deploy_context_menu(
                     builder.action("Reveal in File Manager", Box::new(RevealInFileManager))
                 })
                 .action("Open in Terminal", Box::new(OpenInTerminal))
-                .action("Copy Permalink", Box::new(CopyPermalinkToLine));
+                .action("Copy Permalink", Box::new(CopyPermalinkToLine))
+                .action("Open Permalink", Box::new(OpenPermalinkToLine));
             match focus {
                 Some(focus) => builder.context(focus),
                 None => builder,
@@ -9,17 +9,20 @@ use db::{define_connection, query};
 
 use workspace::{ItemId, WorkspaceDb, WorkspaceId};
 
+use crate::scratch::ScratchId;
+
 #[derive(Clone, Debug, PartialEq, Default)]
 pub(crate) struct SerializedEditor {
     pub(crate) path: Option<PathBuf>,
     pub(crate) contents: Option<String>,
     pub(crate) language: Option<String>,
     pub(crate) mtime: Option<SystemTime>,
+    pub(crate) scratch_id: Option<ScratchId>,
 }
 
 impl StaticColumnCount for SerializedEditor {
     fn column_count() -> usize {
-        5
+        6
     }
 }
 
@@ -45,6 +48,7 @@ impl Bind for SerializedEditor {
                 statement.bind::<Option<i32>>(&None, start_index)?
             }
         };
+        let start_index = statement.bind(&self.scratch_id, start_index)?;
         Ok(start_index)
     }
 }
@@ -60,6 +64,8 @@ impl Column for SerializedEditor {
             Column::column(statement, start_index)?;
         let (mtime_nanos, start_index): (Option<i32>, i32) =
             Column::column(statement, start_index)?;
+        let (scratch_id, start_index): (Option<ScratchId>, i32) =
+            Column::column(statement, start_index)?;
 
         let mtime = mtime_seconds
             .zip(mtime_nanos)
@@ -70,6 +76,7 @@ impl Column for SerializedEditor {
             contents,
             language,
             mtime,
+            scratch_id,
         };
         Ok((editor, start_index))
     }
@@ -88,6 +95,14 @@ define_connection!(
     //   language: Option<String>,
     //   mtime_seconds: Option<i64>,
     //   mtime_nanos: Option<i32>,
+    //   scratch_id: Option<i64>,
+    // )
+    //
+    // scratches(
+    //   scratch_id: i64,
+    //   contents: Option<String>,
+    //   language: Option<String>,
+    //   updated_at: String,
     // )
     pub static ref DB: EditorDb<WorkspaceDb> =
         &[sql! (
@@ -136,13 +151,23 @@ define_connection!(
             ALTER TABLE editors ADD COLUMN mtime_seconds INTEGER DEFAULT NULL;
             ALTER TABLE editors ADD COLUMN mtime_nanos INTEGER DEFAULT NULL;
         ),
+        sql! (
+            CREATE TABLE scratches(
+                scratch_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                contents TEXT,
+                language TEXT,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP NOT NULL
+            ) STRICT;
+
+            ALTER TABLE editors ADD COLUMN scratch_id INTEGER DEFAULT NULL;
+        ),
         ];
 );
 
 impl EditorDb {
     query! {
         pub fn get_serialized_editor(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<SerializedEditor>> {
-            SELECT path, contents, language, mtime_seconds, mtime_nanos FROM editors
+            SELECT path, contents, language, mtime_seconds, mtime_nanos, scratch_id FROM editors
             WHERE item_id = ? AND workspace_id = ?
         }
     }
@@ -150,9 +175,10 @@ impl EditorDb {
     query! {
         pub async fn save_serialized_editor(item_id: ItemId, workspace_id: WorkspaceId, serialized_editor: SerializedEditor) -> Result<()> {
             INSERT INTO editors
-                (item_id, workspace_id, path, contents, language, mtime_seconds, mtime_nanos)
+                (item_id, workspace_id, path, contents, language, mtime_seconds, mtime_nanos,
+                 scratch_id)
             VALUES
-                (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
             ON CONFLICT DO UPDATE SET
                 item_id = ?1,
                 workspace_id = ?2,
@@ -160,7 +186,8 @@ impl EditorDb {
                 contents = ?4,
                 language = ?5,
                 mtime_seconds = ?6,
-                mtime_nanos = ?7
+                mtime_nanos = ?7,
+                scratch_id = ?8
         }
     }
 
@@ -190,6 +217,46 @@ impl EditorDb {
         }
     }
 
+    query! {
+        pub async fn next_scratch_id() -> Result<ScratchId> {
+            INSERT INTO scratches DEFAULT VALUES RETURNING scratch_id
+        }
+    }
+
+    query! {
+        pub async fn save_scratch(
+            scratch_id: ScratchId,
+            contents: String,
+            language: Option<String>
+        ) -> Result<()> {
+            UPDATE scratches
+            SET
+                contents = ?2,
+                language = ?3,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE scratch_id = ?1
+        }
+    }
+
+    // Returns the contents and language of the scratch
+    query! {
+        pub fn get_scratch(scratch_id: ScratchId) -> Result<Option<(Option<String>, Option<String>)>> {
+            SELECT contents, language
+            FROM scratches
+            WHERE scratch_id = ?
+        }
+    }
+
+    // Returns the scratches that aren't empty, the most recently edited first
+    query! {
+        pub fn scratches() -> Result<Vec<(ScratchId, String, Option<String>)>> {
+            SELECT scratch_id, contents, language
+            FROM scratches
+            WHERE length(contents) > 0
+            ORDER BY updated_at DESC, scratch_id DESC
+        }
+    }
+
     pub async fn delete_unloaded_items(
         &self,
         workspace: WorkspaceId,
@@ -231,6 +298,7 @@ mod tests {
             contents: None,
             language: None,
             mtime: None,
+            scratch_id: None,
         };
 
         DB.save_serialized_editor(1234, workspace_id, serialized_editor.clone())
@@ -249,6 +317,7 @@ mod tests {
             contents: Some("Test".to_owned()),
             language: Some("Go".to_owned()),
             mtime: None,
+            scratch_id: None,
         };
 
         DB.save_serialized_editor(1234, workspace_id, serialized_editor.clone())
@@ -267,6 +336,7 @@ mod tests {
             contents: None,
             language: None,
             mtime: None,
+            scratch_id: None,
         };
 
         DB.save_serialized_editor(1234, workspace_id, serialized_editor.clone())
@@ -286,6 +356,26 @@ mod tests {
             contents: None,
             language: None,
             mtime: Some(now),
+            scratch_id: None,
+        };
+
+        DB.save_serialized_editor(1234, workspace_id, serialized_editor.clone())
+            .await
+            .unwrap();
+
+        let have = DB
+            .get_serialized_editor(1234, workspace_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(have, serialized_editor);
+
+        // Storing and retrieving the scratch of an editor
+        let serialized_editor = SerializedEditor {
+            path: None,
+            contents: None,
+            language: None,
+            mtime: None,
+            scratch_id: Some(12),
         };
 
         DB.save_serialized_editor(1234, workspace_id, serialized_editor.clone())
@@ -298,4 +388,37 @@ mod tests {
             .unwrap();
         assert_eq!(have, serialized_editor);
     }
+
+    #[gpui::test]
+    async fn test_save_and_get_scratches() {
+        let first = DB.next_scratch_id().await.unwrap();
+        let second = DB.next_scratch_id().await.unwrap();
+        assert_ne!(first, second);
+        assert_eq!(DB.get_scratch(first).unwrap(), Some((None, None)));
+
+        DB.save_scratch(first, "fn main() {}".to_owned(), Some("Rust".to_owned()))
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_scratch(first).unwrap(),
+            Some((Some("fn main() {}".to_owned()), Some("Rust".to_owned())))
+        );
+
+        // Empty scratches aren't listed
+        assert_eq!(
+            DB.scratches().unwrap(),
+            vec![(first, "fn main() {}".to_owned(), Some("Rust".to_owned()))]
+        );
+
+        DB.save_scratch(second, "notes".to_owned(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.scratches().unwrap(),
+            vec![
+                (second, "notes".to_owned(), None),
+                (first, "fn main() {}".to_owned(), Some("Rust".to_owned())),
+            ]
+        );
+    }
 }
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use gpui::{AnyElement, FrameStats};
+use ui::prelude::*;
+
+use crate::{actions::ToggleProfilingHud, Editor};
+
+/// Timings of the last frame in which an editor was drawn, which are shown over the
+/// editor to help diagnose slow rendering.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ProfilingHud {
+    /// How long the editor element took to lay out.
+    pub layout_duration: Duration,
+    /// How long the editor element took to paint.
+    pub paint_duration: Duration,
+    /// How many lines the editor element laid out.
+    pub line_count: usize,
+}
+
+impl ProfilingHud {
+    /// Renders the timings of the editor's last frame, along with the window's frame
+    /// statistics.
+    pub(crate) fn render(&self, frame_stats: FrameStats, cx: &mut WindowContext) -> AnyElement {
+        let line_layouts = frame_stats.line_layouts;
+        let laid_out_lines = line_layouts.shaped_lines + line_layouts.reused_lines;
+        let cache_hit_rate = if laid_out_lines == 0 {
+            100
+        } else {
+            line_layouts.reused_lines * 100 / laid_out_lines
+        };
+        let rows = [
+            format!("Frame: {}", format_duration(frame_stats.frame_duration)),
+            format!("Editor layout: {}", format_duration(self.layout_duration)),
+            format!("Editor paint: {}", format_duration(self.paint_duration)),
+            format!("Editor lines: {}", self.line_count),
+            format!(
+                "Shaped lines: {} ({cache_hit_rate}% cached)",
+                line_layouts.shaped_lines
+            ),
+            format!("Dropped frames: {}", frame_stats.dropped_frames),
+        ];
+
+        v_flex()
+            .elevation_2(cx)
+            .px_1()
+            .children(
+                rows.into_iter()
+                    .map(|row| Label::new(row).size(LabelSize::XSmall)),
+            )
+            .into_any_element()
+    }
+}
+
+impl Editor {
+    pub fn toggle_profiling_hud(&mut self, _: &ToggleProfilingHud, cx: &mut ViewContext<Self>) {
+        self.profiling_hud = match self.profiling_hud {
+            Some(_) => None,
+            None => Some(ProfilingHud::default()),
+        };
+        cx.notify();
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.)
+}
@@ -0,0 +1,560 @@
+use std::ops::Range;
+
+use collections::{HashMap, HashSet};
+use gpui::{AppContext, FontWeight, View, WeakView};
+use language::Point;
+use multi_buffer::{Anchor, MultiBufferRow};
+use project::{ReviewComment, ReviewThread, ReviewThreadId};
+use text::ToPoint as _;
+use time::{OffsetDateTime, UtcOffset};
+use ui::{prelude::*, Tooltip};
+
+use crate::{
+    display_map::{
+        BlockContext, BlockDisposition, BlockProperties, BlockStyle, CustomBlockId, DisplayRow,
+        RenderBlock,
+    },
+    scroll::Autoscroll,
+    AddReviewComment, Editor, EditorMode, SubmitReviewComment,
+};
+
+/// The state of the review comment threads shown in an editor.
+#[derive(Default)]
+pub(crate) struct ReviewState {
+    /// The threads whose comments are shown below their range.
+    expanded: HashSet<ReviewThreadId>,
+    blocks: HashSet<CustomBlockId>,
+    reply_editors: HashMap<ReviewThreadId, View<Editor>>,
+    /// A thread being started, until its first comment is submitted.
+    draft: Option<DraftThread>,
+}
+
+struct DraftThread {
+    range: Range<Anchor>,
+    editor: View<Editor>,
+}
+
+enum ReviewThreadHighlight {}
+
+type ThreadAction = fn(&mut Editor, ReviewThreadId, &mut ViewContext<Editor>);
+
+/// A thread of the project, and its range in the editor's multibuffer.
+struct LocatedThread {
+    thread: ReviewThread,
+    range: Range<Anchor>,
+}
+
+pub(crate) fn refresh_review_threads(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+    let threads = located_threads(editor, cx);
+    let ReviewState {
+        expanded,
+        blocks,
+        reply_editors,
+        draft,
+    } = &mut editor.review;
+    expanded.retain(|id| threads.iter().any(|located| located.thread.id == *id));
+    reply_editors.retain(|id, _| expanded.contains(id));
+    if threads.is_empty() && blocks.is_empty() && draft.is_none() {
+        return;
+    }
+
+    let old_blocks = std::mem::take(&mut editor.review.blocks);
+    if !old_blocks.is_empty() {
+        editor.remove_blocks(old_blocks, None, cx);
+    }
+    editor.clear_row_highlights::<ReviewThreadHighlight>();
+
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let highlight_color = cx
+        .theme()
+        .colors()
+        .editor_document_highlight_read_background;
+    let weak_editor = cx.view().downgrade();
+    let mut blocks = Vec::new();
+    for located in threads {
+        let id = located.thread.id;
+        if !editor.review.expanded.contains(&id) {
+            continue;
+        }
+        let start_row = located.range.start.to_point(&snapshot).row;
+        let end_row = located.range.end.to_point(&snapshot).row;
+        editor.highlight_rows::<ReviewThreadHighlight>(
+            snapshot.anchor_before(Point::new(start_row, 0))
+                ..=snapshot.anchor_before(Point::new(end_row, 0)),
+            Some(highlight_color),
+            false,
+            cx,
+        );
+        let reply_editor = editor
+            .review
+            .reply_editors
+            .entry(id)
+            .or_insert_with(|| {
+                cx.new_view(|cx| {
+                    let mut editor = Editor::single_line(cx);
+                    editor.set_placeholder_text("Reply…", cx);
+                    editor
+                })
+            })
+            .clone();
+        let height = located
+            .thread
+            .comments
+            .iter()
+            .map(|comment| 1 + comment.body.lines().count().max(1) as u32)
+            .sum::<u32>()
+            + 3;
+        blocks.push(BlockProperties {
+            position: located.range.end,
+            height,
+            style: BlockStyle::Flex,
+            render: render_thread(weak_editor.clone(), located.thread, reply_editor),
+            disposition: BlockDisposition::Below,
+            priority: 0,
+        });
+    }
+    if let Some(draft) = editor.review.draft.as_ref() {
+        blocks.push(BlockProperties {
+            position: draft.range.end,
+            height: 3,
+            style: BlockStyle::Flex,
+            render: render_draft(weak_editor, draft.editor.clone()),
+            disposition: BlockDisposition::Below,
+            priority: 0,
+        });
+    }
+    editor.review.blocks = editor.insert_blocks(blocks, None, cx).into_iter().collect();
+    cx.notify();
+}
+
+fn located_threads(editor: &Editor, cx: &AppContext) -> Vec<LocatedThread> {
+    let Some(project) = editor.project.as_ref() else {
+        return Vec::new();
+    };
+    let store = project.read(cx).review_store().read(cx);
+    let multibuffer = editor.buffer.read(cx);
+    let snapshot = multibuffer.snapshot(cx);
+    let mut threads = Vec::new();
+    for buffer in multibuffer.all_buffers() {
+        let buffer_threads = store.threads_for_buffer(&buffer, cx);
+        if buffer_threads.is_empty() {
+            continue;
+        }
+        let buffer_snapshot = buffer.read(cx).text_snapshot();
+        for (excerpt_id, range) in multibuffer.excerpts_for_buffer(&buffer, cx) {
+            let context_start = range.context.start.to_point(&buffer_snapshot);
+            let context_end = range.context.end.to_point(&buffer_snapshot);
+            for thread in &buffer_threads {
+                if thread.range.start < context_start || thread.range.start > context_end {
+                    continue;
+                }
+                let start = buffer_snapshot.anchor_after(thread.range.start);
+                let end = buffer_snapshot.anchor_before(thread.range.end.min(context_end));
+                if let Some((start, end)) = snapshot
+                    .anchor_in_excerpt(excerpt_id, start)
+                    .zip(snapshot.anchor_in_excerpt(excerpt_id, end))
+                {
+                    threads.push(LocatedThread {
+                        thread: (*thread).clone(),
+                        range: start..end,
+                    });
+                }
+            }
+        }
+    }
+    threads.sort_by(|a, b| a.range.start.cmp(&b.range.start, &snapshot));
+    threads
+}
+
+fn render_thread(
+    editor: WeakView<Editor>,
+    thread: ReviewThread,
+    reply_editor: View<Editor>,
+) -> RenderBlock {
+    let id = thread.id;
+    Box::new(move |cx: &mut BlockContext| {
+        let on_click = |action: ThreadAction| {
+            let editor = editor.clone();
+            move |_: &gpui::ClickEvent, cx: &mut WindowContext| {
+                editor.update(cx, |editor, cx| action(editor, id, cx)).ok();
+            }
+        };
+        let (resolve_label, resolve_action): (_, ThreadAction) = if thread.resolved {
+            ("Reopen", Editor::reopen_review_thread)
+        } else {
+            ("Resolve", Editor::resolve_review_thread)
+        };
+        let status = if thread.resolved { "Resolved" } else { "Open" };
+        let comments = thread.comments.iter().map(|comment| {
+            v_flex()
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(
+                            Label::new(comment.author.clone().unwrap_or_else(|| "You".into()))
+                                .size(LabelSize::Small)
+                                .weight(FontWeight::BOLD),
+                        )
+                        .child(
+                            Label::new(comment_timestamp(comment))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                )
+                .children(
+                    comment
+                        .body
+                        .lines()
+                        .map(|line| Label::new(line.to_string()).size(LabelSize::Small)),
+                )
+        });
+
+        v_flex()
+            .id(("review-thread", id.0))
+            .ml(cx.gutter_dimensions.full_width())
+            .mr_4()
+            .px_2()
+            .border_l_2()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().editor_subheader_background)
+            .child(
+                h_flex()
+                    .h(cx.line_height)
+                    .gap_1()
+                    .child(
+                        Label::new(status)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(div().flex_1())
+                    .child(
+                        Button::new(("review-resolve", id.0), resolve_label)
+                            .label_size(LabelSize::Small)
+                            .style(ButtonStyle::Transparent)
+                            .on_click(on_click(resolve_action)),
+                    )
+                    .child(
+                        Button::new(("review-delete", id.0), "Delete")
+                            .label_size(LabelSize::Small)
+                            .style(ButtonStyle::Transparent)
+                            .on_click(on_click(Editor::delete_review_thread)),
+                    )
+                    .child(
+                        IconButton::new(("review-collapse", id.0), IconName::Close)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::text("Collapse Thread", cx))
+                            .on_click(on_click(Editor::collapse_review_thread)),
+                    ),
+            )
+            .children(comments)
+            .child(
+                div()
+                    .key_context("ReviewComment")
+                    .on_action({
+                        let editor = editor.clone();
+                        move |_: &SubmitReviewComment, cx| {
+                            editor
+                                .update(cx, |editor, cx| editor.reply_to_review_thread(id, cx))
+                                .ok();
+                        }
+                    })
+                    .py_0p5()
+                    .child(reply_editor.clone()),
+            )
+            .into_any_element()
+    })
+}
+
+fn render_draft(editor: WeakView<Editor>, draft_editor: View<Editor>) -> RenderBlock {
+    Box::new(move |cx: &mut BlockContext| {
+        v_flex()
+            .id("review-draft")
+            .ml(cx.gutter_dimensions.full_width())
+            .mr_4()
+            .px_2()
+            .border_l_2()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().editor_subheader_background)
+            .child(
+                h_flex()
+                    .h(cx.line_height)
+                    .child(
+                        Label::new("New Comment")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(div().flex_1())
+                    .child(
+                        Button::new("review-draft-cancel", "Cancel")
+                            .label_size(LabelSize::Small)
+                            .style(ButtonStyle::Transparent)
+                            .on_click({
+                                let editor = editor.clone();
+                                move |_, cx| {
+                                    editor
+                                        .update(cx, |editor, cx| editor.cancel_review_draft(cx))
+                                        .ok();
+                                }
+                            }),
+                    ),
+            )
+            .child(
+                div()
+                    .key_context("ReviewComment")
+                    .on_action({
+                        let editor = editor.clone();
+                        move |_: &SubmitReviewComment, cx| {
+                            editor
+                                .update(cx, |editor, cx| editor.submit_review_draft(cx))
+                                .ok();
+                        }
+                    })
+                    .py_0p5()
+                    .child(draft_editor.clone()),
+            )
+            .into_any_element()
+    })
+}
+
+fn comment_timestamp(comment: &ReviewComment) -> String {
+    let Ok(time) = OffsetDateTime::from_unix_timestamp(comment.created_at) else {
+        return String::new();
+    };
+    let local = chrono::Local::now().offset().local_minus_utc();
+    time_format::format_localized_timestamp(
+        time,
+        OffsetDateTime::now_utc(),
+        UtcOffset::from_whole_seconds(local).unwrap_or(UtcOffset::UTC),
+        time_format::TimestampFormat::Relative,
+    )
+}
+
+impl Editor {
+    /// Starts a review thread on the newest selection, or its line when it's
+    /// empty. When a thread already starts on that line, it's expanded so it
+    /// can be replied to.
+    pub fn add_review_comment(&mut self, _: &AddReviewComment, cx: &mut ViewContext<Self>) {
+        if self.project.is_none() || self.mode != EditorMode::Full {
+            return;
+        }
+        let selection = self.selections.newest::<Point>(cx);
+        let row = selection.head().row;
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let existing = located_threads(self, cx)
+            .into_iter()
+            .find(|located| located.range.start.to_point(&snapshot).row == row);
+        if let Some(located) = existing {
+            self.show_review_thread(located.thread.id, cx);
+            if let Some(reply_editor) = self.review.reply_editors.get(&located.thread.id) {
+                cx.focus_view(reply_editor);
+            }
+            return;
+        }
+
+        let range = if selection.is_empty() {
+            Point::new(row, 0)..Point::new(row, snapshot.line_len(MultiBufferRow(row)))
+        } else {
+            selection.range()
+        };
+        let draft_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Add a comment…", cx);
+            editor
+        });
+        cx.focus_view(&draft_editor);
+        self.review.draft = Some(DraftThread {
+            range: snapshot.anchor_after(range.start)..snapshot.anchor_before(range.end),
+            editor: draft_editor,
+        });
+        refresh_review_threads(self, cx);
+    }
+
+    /// Expands a review thread and moves the cursor to its start.
+    pub fn show_review_thread(&mut self, id: ReviewThreadId, cx: &mut ViewContext<Self>) {
+        let Some(located) = located_threads(self, cx)
+            .into_iter()
+            .find(|located| located.thread.id == id)
+        else {
+            return;
+        };
+        self.review.expanded.insert(id);
+        refresh_review_threads(self, cx);
+        let position = located.range.start;
+        self.change_selections(Some(Autoscroll::center()), cx, |selections| {
+            selections.select_anchor_ranges([position..position])
+        });
+    }
+
+    /// Expands the review threads starting on a row, or collapses them when
+    /// they're all expanded.
+    pub(crate) fn toggle_review_threads_at_row(
+        &mut self,
+        row: MultiBufferRow,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let ids = located_threads(self, cx)
+            .into_iter()
+            .filter(|located| located.range.start.to_point(&snapshot).row == row.0)
+            .map(|located| located.thread.id)
+            .collect::<Vec<_>>();
+        if ids.iter().all(|id| self.review.expanded.contains(id)) {
+            for id in &ids {
+                self.review.expanded.remove(id);
+            }
+        } else {
+            self.review.expanded.extend(ids);
+        }
+        refresh_review_threads(self, cx);
+    }
+
+    /// The rows of the multibuffer where review threads start, and whether
+    /// all of the row's threads are resolved.
+    pub(crate) fn review_thread_rows(&self, cx: &AppContext) -> Vec<(MultiBufferRow, bool)> {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut rows: Vec<(MultiBufferRow, bool)> = Vec::new();
+        for located in located_threads(self, cx) {
+            let row = MultiBufferRow(located.range.start.to_point(&snapshot).row);
+            match rows.last_mut() {
+                Some((last_row, resolved)) if *last_row == row => {
+                    *resolved &= located.thread.resolved;
+                }
+                _ => rows.push((row, located.thread.resolved)),
+            }
+        }
+        rows
+    }
+
+    pub(crate) fn render_review_thread_indicator(
+        &self,
+        display_row: DisplayRow,
+        row: MultiBufferRow,
+        resolved: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> IconButton {
+        IconButton::new(
+            ("review_thread_indicator", display_row.0 as usize),
+            IconName::MessageBubbles,
+        )
+        .shape(ui::IconButtonShape::Square)
+        .icon_size(IconSize::XSmall)
+        .icon_color(if resolved {
+            Color::Muted
+        } else {
+            Color::Accent
+        })
+        .tooltip(|cx| Tooltip::text("Toggle Review Comments", cx))
+        .on_click(cx.listener(move |editor, _, cx| {
+            editor.toggle_review_threads_at_row(row, cx);
+        }))
+    }
+
+    fn collapse_review_thread(&mut self, id: ReviewThreadId, cx: &mut ViewContext<Self>) {
+        self.review.expanded.remove(&id);
+        refresh_review_threads(self, cx);
+    }
+
+    fn resolve_review_thread(&mut self, id: ReviewThreadId, cx: &mut ViewContext<Self>) {
+        self.update_review_store(cx, |store, cx| store.set_resolved(id, true, cx));
+    }
+
+    fn reopen_review_thread(&mut self, id: ReviewThreadId, cx: &mut ViewContext<Self>) {
+        self.update_review_store(cx, |store, cx| store.set_resolved(id, false, cx));
+    }
+
+    fn delete_review_thread(&mut self, id: ReviewThreadId, cx: &mut ViewContext<Self>) {
+        self.update_review_store(cx, |store, cx| store.delete_thread(id, cx));
+    }
+
+    fn reply_to_review_thread(&mut self, id: ReviewThreadId, cx: &mut ViewContext<Self>) {
+        let Some(reply_editor) = self.review.reply_editors.get(&id).cloned() else {
+            return;
+        };
+        let Some(comment) = self.new_review_comment(&reply_editor, cx) else {
+            return;
+        };
+        reply_editor.update(cx, |editor, cx| editor.clear(cx));
+        self.update_review_store(cx, |store, cx| store.add_comment(id, comment, cx));
+    }
+
+    fn submit_review_draft(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(draft) = self.review.draft.as_ref() else {
+            return;
+        };
+        let Some(comment) = self.new_review_comment(&draft.editor, cx) else {
+            return;
+        };
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let start = draft.range.start.to_point(&snapshot);
+        let end = draft.range.end.to_point(&snapshot);
+        let multibuffer = self.buffer.read(cx);
+        let Some(((buffer, start, _), (end_buffer, end, _))) = multibuffer
+            .point_to_buffer_point(start, cx)
+            .zip(multibuffer.point_to_buffer_point(end, cx))
+        else {
+            return;
+        };
+        let end = if end_buffer == buffer {
+            end.max(start)
+        } else {
+            start
+        };
+        self.review.draft = None;
+        let id = self
+            .update_review_store(cx, |store, cx| {
+                store.create_thread(&buffer, start..end, comment, cx)
+            })
+            .flatten();
+        if let Some(id) = id {
+            self.review.expanded.insert(id);
+        }
+        cx.focus(&self.focus_handle);
+        refresh_review_threads(self, cx);
+    }
+
+    fn cancel_review_draft(&mut self, cx: &mut ViewContext<Self>) {
+        if self.review.draft.take().is_some() {
+            cx.focus(&self.focus_handle);
+            refresh_review_threads(self, cx);
+        }
+    }
+
+    fn new_review_comment(
+        &self,
+        comment_editor: &View<Editor>,
+        cx: &AppContext,
+    ) -> Option<ReviewComment> {
+        let body = comment_editor.read(cx).text(cx).trim().to_string();
+        if body.is_empty() {
+            return None;
+        }
+        let author = self.project.as_ref().and_then(|project| {
+            project
+                .read(cx)
+                .user_store()
+                .read(cx)
+                .current_user()
+                .map(|user| user.github_login.clone())
+        });
+        Some(ReviewComment {
+            author,
+            body,
+            created_at: OffsetDateTime::now_utc().unix_timestamp(),
+        })
+    }
+
+    fn update_review_store<R>(
+        &mut self,
+        cx: &mut ViewContext<Self>,
+        update: impl FnOnce(
+            &mut project::ReviewStore,
+            &mut gpui::ModelContext<project::ReviewStore>,
+        ) -> R,
+    ) -> Option<R> {
+        let store = self.project.as_ref()?.read(cx).review_store().clone();
+        Some(store.update(cx, update))
+    }
+}
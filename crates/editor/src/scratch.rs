@@ -0,0 +1,264 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Context as _, Result};
+use collections::HashMap;
+use gpui::{
+    AppContext, AsyncAppContext, Context as _, Global, Model, Subscription, Task, View,
+    ViewContext, VisualContext as _, WeakModel,
+};
+use language::Buffer;
+use multi_buffer::MultiBuffer;
+use project::Project;
+use util::ResultExt as _;
+use workspace::Workspace;
+
+use crate::{persistence::DB, Editor, EditorMode};
+
+/// The identifier of a scratch buffer in the database.
+pub type ScratchId = i64;
+
+/// How long to wait after the last edit of a scratch buffer before saving it.
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// The longest summary of a scratch buffer shown in lists.
+const MAX_SUMMARY_LEN: usize = 60;
+
+/// A scratch buffer, as it was last saved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scratch {
+    pub id: ScratchId,
+    /// The first non-blank line of the scratch buffer.
+    pub summary: String,
+    pub language: Option<String>,
+}
+
+impl Scratch {
+    pub fn title(&self) -> String {
+        scratch_title(self.id)
+    }
+}
+
+/// Returns the scratch buffers that aren't empty, the most recently edited first.
+pub fn saved_scratches() -> Result<Vec<Scratch>> {
+    Ok(DB
+        .scratches()?
+        .into_iter()
+        .map(|(id, contents, language)| Scratch {
+            id,
+            summary: scratch_summary(&contents),
+            language,
+        })
+        .collect())
+}
+
+fn scratch_title(scratch_id: ScratchId) -> String {
+    format!("Scratch {scratch_id}")
+}
+
+fn scratch_summary(contents: &str) -> String {
+    let line = contents
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    util::truncate_and_trailoff(line, MAX_SUMMARY_LEN)
+}
+
+struct OpenScratch {
+    buffer: WeakModel<Buffer>,
+    save_task: Option<Task<()>>,
+    _subscription: Subscription,
+}
+
+/// The scratch buffers that are loaded, which are saved as they're edited.
+#[derive(Default)]
+struct OpenScratches(HashMap<ScratchId, OpenScratch>);
+
+impl Global for OpenScratches {}
+
+/// Returns the scratch the buffer was created for, if any.
+pub fn scratch_id_for_buffer(buffer: &Model<Buffer>, cx: &AppContext) -> Option<ScratchId> {
+    let scratches = cx.try_global::<OpenScratches>()?;
+    scratches
+        .0
+        .iter()
+        .find(|(_, scratch)| scratch.buffer == *buffer)
+        .map(|(scratch_id, _)| *scratch_id)
+}
+
+fn register_scratch(scratch_id: ScratchId, buffer: &Model<Buffer>, cx: &mut AppContext) {
+    let subscription = cx.subscribe(buffer, move |buffer, event, cx| match event {
+        language::Event::Edited | language::Event::LanguageChanged => {
+            schedule_save(scratch_id, buffer, cx)
+        }
+        _ => {}
+    });
+    let scratches = cx.default_global::<OpenScratches>();
+    scratches
+        .0
+        .retain(|_, scratch| scratch.buffer.upgrade().is_some());
+    scratches.0.insert(
+        scratch_id,
+        OpenScratch {
+            buffer: buffer.downgrade(),
+            save_task: None,
+            _subscription: subscription,
+        },
+    );
+}
+
+fn schedule_save(scratch_id: ScratchId, buffer: Model<Buffer>, cx: &mut AppContext) {
+    let task = cx.spawn(|mut cx| async move {
+        cx.background_executor().timer(SAVE_DEBOUNCE).await;
+        save_scratch(scratch_id, buffer, &mut cx).await.log_err();
+    });
+    if let Some(scratch) = cx.default_global::<OpenScratches>().0.get_mut(&scratch_id) {
+        scratch.save_task = Some(task);
+    }
+}
+
+async fn save_scratch(
+    scratch_id: ScratchId,
+    buffer: Model<Buffer>,
+    cx: &mut AsyncAppContext,
+) -> Result<()> {
+    let (contents, language, version) = buffer.read_with(cx, |buffer, _| {
+        let language = buffer
+            .language()
+            .map(|language| language.name().to_string());
+        (buffer.text(), language, buffer.version())
+    })?;
+    DB.save_scratch(scratch_id, contents, language)
+        .await
+        .context("failed to save scratch buffer")?;
+    // The scratch buffer has no file, so it's only saved in the database and
+    // closing it doesn't ask where to save it.
+    buffer.update(cx, |buffer, cx| buffer.did_save(version, None, cx))
+}
+
+/// Opens the buffer of the scratch, reusing it when it's already loaded.
+pub(crate) fn open_scratch_buffer(
+    project: Model<Project>,
+    scratch_id: ScratchId,
+    cx: &mut AppContext,
+) -> Task<Result<Model<Buffer>>> {
+    let open_buffer = cx
+        .try_global::<OpenScratches>()
+        .and_then(|scratches| scratches.0.get(&scratch_id))
+        .and_then(|scratch| scratch.buffer.upgrade());
+    if let Some(buffer) = open_buffer {
+        return Task::ready(Ok(buffer));
+    }
+    if project.read(cx).is_via_collab() {
+        return Task::ready(Err(anyhow!(
+            "Scratch buffers can't be opened in remote projects"
+        )));
+    }
+
+    cx.spawn(|mut cx| async move {
+        let (contents, language_name) = DB
+            .get_scratch(scratch_id)?
+            .with_context(|| format!("No scratch buffer with id {scratch_id}"))?;
+        let language = match language_name {
+            Some(language_name) => {
+                let language_registry =
+                    project.update(&mut cx, |project, _| project.languages().clone())?;
+                // Rather open the scratch buffer without a language than not at all.
+                language_registry
+                    .language_for_name(&language_name)
+                    .await
+                    .ok()
+            }
+            None => None,
+        };
+        let buffer = project.update(&mut cx, |project, cx| {
+            project.create_local_buffer(&contents.unwrap_or_default(), language, cx)
+        })?;
+        cx.update(|cx| register_scratch(scratch_id, &buffer, cx))?;
+        Ok(buffer)
+    })
+}
+
+/// Creates an empty scratch buffer and opens it in the workspace.
+pub fn new_scratch_buffer(
+    workspace: &mut Workspace,
+    cx: &mut ViewContext<Workspace>,
+) -> Task<Result<View<Editor>>> {
+    let project = workspace.project().clone();
+    if project.read(cx).is_via_collab() {
+        return Task::ready(Err(anyhow!(
+            "Scratch buffers can't be created in remote projects"
+        )));
+    }
+
+    cx.spawn(|workspace, mut cx| async move {
+        let scratch_id = DB.next_scratch_id().await?;
+        let buffer = project.update(&mut cx, |project, cx| {
+            project.create_local_buffer("", None, cx)
+        })?;
+        cx.update(|cx| register_scratch(scratch_id, &buffer, cx))?;
+        workspace.update(&mut cx, |workspace, cx| {
+            let editor =
+                cx.new_view(|cx| Editor::for_scratch_buffer(buffer, project, scratch_id, cx));
+            workspace.add_item_to_active_pane(Box::new(editor.clone()), None, true, cx);
+            editor
+        })
+    })
+}
+
+/// Opens the scratch buffer in the workspace, activating its editor when it's
+/// already open.
+pub fn open_scratch(
+    workspace: &mut Workspace,
+    scratch_id: ScratchId,
+    cx: &mut ViewContext<Workspace>,
+) -> Task<Result<View<Editor>>> {
+    let project = workspace.project().clone();
+    let open_buffer = open_scratch_buffer(project.clone(), scratch_id, cx);
+
+    cx.spawn(|workspace, mut cx| async move {
+        let buffer = open_buffer.await?;
+        workspace.update(&mut cx, |workspace, cx| {
+            let existing_editor = workspace.items_of_type::<Editor>(cx).find(|editor| {
+                editor.read(cx).buffer().read(cx).as_singleton().as_ref() == Some(&buffer)
+            });
+            if let Some(editor) = existing_editor {
+                workspace.activate_item(&editor, true, true, cx);
+                return editor;
+            }
+            let editor =
+                cx.new_view(|cx| Editor::for_scratch_buffer(buffer, project, scratch_id, cx));
+            workspace.add_item_to_active_pane(Box::new(editor.clone()), None, true, cx);
+            editor
+        })
+    })
+}
+
+impl Editor {
+    pub(crate) fn for_scratch_buffer(
+        buffer: Model<Buffer>,
+        project: Model<Project>,
+        scratch_id: ScratchId,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let buffer = cx.new_model(|cx| {
+            MultiBuffer::singleton(buffer, cx).with_title(scratch_title(scratch_id))
+        });
+        Self::new(EditorMode::Full, buffer, Some(project), false, cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scratch_summary() {
+        assert_eq!(scratch_summary(""), "");
+        assert_eq!(scratch_summary("\n  \n  fn main() {\n}\n"), "fn main() {");
+        assert_eq!(
+            scratch_summary(&"a".repeat(100)),
+            format!("{}…", "a".repeat(MAX_SUMMARY_LEN))
+        );
+    }
+}
@@ -4,11 +4,12 @@ pub(crate) mod scroll_amount;
 
 use crate::editor_settings::ScrollBeyondLastLine;
 use crate::{
+    background_syntax, code_lens,
     display_map::{DisplaySnapshot, ToDisplayPoint},
     hover_popover::hide_hover,
     persistence::DB,
-    Anchor, DisplayPoint, DisplayRow, Editor, EditorEvent, EditorMode, EditorSettings,
-    InlayHintRefreshReason, MultiBufferSnapshot, RowExt, ToPoint,
+    semantic_rainbow, Anchor, DisplayPoint, DisplayRow, Editor, EditorEvent, EditorMode,
+    EditorSettings, InlayHintRefreshReason, MultiBufferSnapshot, RowExt, ToPoint,
 };
 pub use autoscroll::{Autoscroll, AutoscrollStrategy};
 use gpui::{point, px, AppContext, Entity, Global, Pixels, Task, ViewContext, WindowContext};
@@ -201,7 +202,11 @@ impl ScrollManager {
             )
         } else {
             let scroll_top = scroll_position.y;
-            let scroll_top = match EditorSettings::get_global(cx).scroll_beyond_last_line {
+            let file = map
+                .buffer_snapshot
+                .as_singleton()
+                .and_then(|(_, _, buffer)| buffer.file());
+            let scroll_top = match EditorSettings::for_file(file, cx).scroll_beyond_last_line {
                 ScrollBeyondLastLine::OnePage => scroll_top,
                 ScrollBeyondLastLine::Off => {
                     if let Some(height_in_lines) = self.visible_line_count {
@@ -366,7 +371,10 @@ impl Editor {
             cx.spawn(|editor, mut cx| async move {
                 editor
                     .update(&mut cx, |editor, cx| {
-                        editor.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx)
+                        editor.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                        code_lens::resolve_visible_code_lens(editor, cx);
+                        semantic_rainbow::refresh_semantic_rainbow(editor, cx);
+                        background_syntax::refresh_background_syntax_highlights(editor, cx);
                     })
                     .ok()
             })
@@ -430,6 +438,9 @@ impl Editor {
         );
 
         self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+        code_lens::resolve_visible_code_lens(self, cx);
+        semantic_rainbow::refresh_semantic_rainbow(self, cx);
+        background_syntax::refresh_background_syntax_highlights(self, cx);
     }
 
     pub fn scroll_position(&self, cx: &mut ViewContext<Self>) -> gpui::Point<f32> {
@@ -0,0 +1,155 @@
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Range,
+    time::Duration,
+};
+
+use gpui::{HighlightStyle, Task};
+use language::{language_settings::language_settings, Bias, Point};
+use ui::prelude::*;
+
+use crate::{Anchor, Editor, EditorMode, ToPoint as _};
+
+const SEMANTIC_RAINBOW_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// The number of colors local variables are colored with, which are the
+/// first accent colors of the theme.
+const SEMANTIC_RAINBOW_COLORS: usize = 8;
+
+enum SemanticRainbow<const COLOR: usize> {}
+
+/// The coloring of the local variables in the visible rows of an editor, when
+/// the `semantic_rainbow` setting is enabled.
+///
+/// Occurrences are found again whenever the buffer is edited or reparsed, or
+/// new rows are scrolled into view. In between, the highlights move with the
+/// text around them.
+#[derive(Default)]
+pub(crate) struct SemanticRainbowState {
+    highlighted: bool,
+    refresh_task: Option<Task<()>>,
+}
+
+pub(crate) fn refresh_semantic_rainbow(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+    let Some(visible_line_count) = editor.visible_line_count() else {
+        return;
+    };
+
+    let multibuffer = editor.buffer.read(cx);
+    let multibuffer_snapshot = multibuffer.snapshot(cx);
+    let visible_start = editor
+        .scroll_manager
+        .anchor()
+        .anchor
+        .to_point(&multibuffer_snapshot);
+    let visible_end = multibuffer_snapshot.clip_point(
+        visible_start + Point::new(visible_line_count.ceil() as u32 + 1, 0),
+        Bias::Left,
+    );
+    let excerpts = multibuffer
+        .range_to_buffer_ranges(visible_start..visible_end, cx)
+        .into_iter()
+        .filter_map(|(buffer, range, excerpt_id)| {
+            let buffer = buffer.read(cx);
+            language_settings(buffer.language(), buffer.file(), cx)
+                .semantic_rainbow
+                .then(|| (excerpt_id, buffer.snapshot(), range))
+        })
+        .collect::<Vec<_>>();
+    if excerpts.is_empty() {
+        editor.semantic_rainbow.refresh_task = None;
+        if editor.semantic_rainbow.highlighted {
+            set_semantic_rainbow_highlights(editor, vec![Vec::new(); SEMANTIC_RAINBOW_COLORS], cx);
+        }
+        return;
+    }
+
+    editor.semantic_rainbow.refresh_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor()
+            .timer(SEMANTIC_RAINBOW_DEBOUNCE)
+            .await;
+        let occurrences = cx
+            .background_executor()
+            .spawn(async move {
+                let mut occurrences = vec![Vec::new(); SEMANTIC_RAINBOW_COLORS];
+                for (excerpt_id, snapshot, range) in excerpts {
+                    for occurrence in snapshot.local_occurrences(range) {
+                        let name = snapshot
+                            .text_for_range(occurrence.definition_range)
+                            .collect::<String>();
+                        occurrences[color_for_name(&name)].push((
+                            excerpt_id,
+                            snapshot.anchor_before(occurrence.range.start)
+                                ..snapshot.anchor_after(occurrence.range.end),
+                        ));
+                    }
+                }
+                occurrences
+            })
+            .await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                let multibuffer_snapshot = editor.buffer.read(cx).snapshot(cx);
+                let ranges = occurrences
+                    .into_iter()
+                    .map(|occurrences| {
+                        occurrences
+                            .into_iter()
+                            .filter_map(|(excerpt_id, range)| {
+                                let start = multibuffer_snapshot
+                                    .anchor_in_excerpt(excerpt_id, range.start)?;
+                                let end = multibuffer_snapshot
+                                    .anchor_in_excerpt(excerpt_id, range.end)?;
+                                Some(start..end)
+                            })
+                            .collect()
+                    })
+                    .collect();
+                set_semantic_rainbow_highlights(editor, ranges, cx);
+            })
+            .ok();
+    }));
+}
+
+/// Returns the index of the color of local variables with the given name,
+/// which is the same in every function and every session.
+fn color_for_name(name: &str) -> usize {
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    (hasher.finish() % SEMANTIC_RAINBOW_COLORS as u64) as usize
+}
+
+fn set_semantic_rainbow_highlights(
+    editor: &mut Editor,
+    mut ranges: Vec<Vec<Range<Anchor>>>,
+    cx: &mut ViewContext<Editor>,
+) {
+    editor.semantic_rainbow.highlighted = ranges.iter().any(|ranges| !ranges.is_empty());
+    set_color_highlights::<0>(editor, &mut ranges, cx);
+    set_color_highlights::<1>(editor, &mut ranges, cx);
+    set_color_highlights::<2>(editor, &mut ranges, cx);
+    set_color_highlights::<3>(editor, &mut ranges, cx);
+    set_color_highlights::<4>(editor, &mut ranges, cx);
+    set_color_highlights::<5>(editor, &mut ranges, cx);
+    set_color_highlights::<6>(editor, &mut ranges, cx);
+    set_color_highlights::<7>(editor, &mut ranges, cx);
+}
+
+fn set_color_highlights<const COLOR: usize>(
+    editor: &mut Editor,
+    ranges: &mut [Vec<Range<Anchor>>],
+    cx: &mut ViewContext<Editor>,
+) {
+    let ranges = std::mem::take(&mut ranges[COLOR]);
+    editor.clear_highlights::<SemanticRainbow<COLOR>>(cx);
+    if !ranges.is_empty() {
+        let style = HighlightStyle {
+            color: Some(cx.theme().accents().color_for_index(COLOR as u32)),
+            ..Default::default()
+        };
+        editor.highlight_text::<SemanticRainbow<COLOR>>(ranges, style, cx);
+    }
+}
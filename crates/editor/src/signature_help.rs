@@ -2,11 +2,10 @@ mod popover;
 mod state;
 
 use crate::actions::ShowSignatureHelp;
-use crate::{Editor, EditorSettings, ToggleAutoSignatureHelp};
+use crate::{Editor, ToggleAutoSignatureHelp};
 use gpui::{AppContext, ViewContext};
 use language::markdown::parse_markdown;
 use multi_buffer::{Anchor, ToOffset};
-use settings::Settings;
 use std::ops::Range;
 
 pub use popover::SignatureHelpPopover;
@@ -31,7 +30,7 @@ impl Editor {
         self.auto_signature_help = self
             .auto_signature_help
             .map(|auto_signature_help| !auto_signature_help)
-            .or_else(|| Some(!EditorSettings::get_global(cx).auto_signature_help));
+            .or_else(|| Some(!self.editor_settings(cx).auto_signature_help));
         match self.auto_signature_help {
             Some(auto_signature_help) if auto_signature_help => {
                 self.show_signature_help(&ShowSignatureHelp, cx);
@@ -63,7 +62,7 @@ impl Editor {
         if let Some(auto_signature_help) = self.auto_signature_help {
             auto_signature_help
         } else {
-            EditorSettings::get_global(cx).auto_signature_help
+            self.editor_settings(cx).auto_signature_help
         }
     }
 
@@ -0,0 +1,171 @@
+use std::ops::Range;
+
+use language::{BufferSnapshot, CharKind};
+
+use crate::editor_settings::DocumentHighlightFallback;
+
+/// The most occurrences of a word to highlight, so that common words in large
+/// buffers don't take long to find.
+const MAX_WORD_OCCURRENCES: usize = 1000;
+
+/// Finds the occurrences of the word at the given offset, for highlighting them
+/// in buffers without a language server that highlights a symbol's
+/// occurrences.
+///
+/// Only whole words match, so that `foo` isn't found in `foobar`.
+pub(crate) fn word_occurrences(
+    buffer: &BufferSnapshot,
+    offset: usize,
+    settings: &DocumentHighlightFallback,
+) -> Vec<Range<usize>> {
+    let (word_range, kind) = buffer.surrounding_word(offset);
+    if kind != Some(CharKind::Word) || word_range.is_empty() {
+        return Vec::new();
+    }
+    if settings.ignore_comments_and_strings && is_in_comment_or_string(buffer, word_range.start) {
+        return Vec::new();
+    }
+
+    let word = buffer.text_for_range(word_range).collect::<String>();
+    let matches = |candidate: &str| {
+        if settings.case_sensitive {
+            candidate == word
+        } else {
+            candidate.to_lowercase() == word.to_lowercase()
+        }
+    };
+
+    let classifier = buffer.char_classifier_at(offset);
+    let mut occurrences = Vec::new();
+    let mut candidate = String::new();
+    let mut candidate_start = None;
+    let mut position = 0;
+    // Follow the text with whitespace so that a word at its end is checked.
+    for ch in buffer.chars_at(0).chain(Some(' ')) {
+        if classifier.is_word(ch) {
+            candidate_start.get_or_insert(position);
+            candidate.push(ch);
+        } else if let Some(start) = candidate_start.take() {
+            if matches(&candidate)
+                && !(settings.ignore_comments_and_strings && is_in_comment_or_string(buffer, start))
+            {
+                occurrences.push(start..position);
+                if occurrences.len() == MAX_WORD_OCCURRENCES {
+                    break;
+                }
+            }
+            candidate.clear();
+        }
+        position += ch.len_utf8();
+    }
+    occurrences
+}
+
+fn is_in_comment_or_string(buffer: &BufferSnapshot, offset: usize) -> bool {
+    buffer.language_scope_at(offset).map_or(false, |scope| {
+        matches!(scope.override_name(), Some("comment" | "string"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use gpui::{Context, TestAppContext};
+    use language::{Buffer, Language, LanguageConfig, LanguageMatcher};
+
+    use super::*;
+
+    fn settings(
+        case_sensitive: bool,
+        ignore_comments_and_strings: bool,
+    ) -> DocumentHighlightFallback {
+        DocumentHighlightFallback {
+            enabled: true,
+            case_sensitive,
+            ignore_comments_and_strings,
+        }
+    }
+
+    fn occurrences(
+        buffer: &BufferSnapshot,
+        offset: usize,
+        settings: &DocumentHighlightFallback,
+    ) -> Vec<String> {
+        word_occurrences(buffer, offset, settings)
+            .into_iter()
+            .map(|range| {
+                format!(
+                    "{}:{}",
+                    range.start,
+                    buffer.text_for_range(range).collect::<String>()
+                )
+            })
+            .collect()
+    }
+
+    #[gpui::test]
+    fn test_word_occurrences(cx: &mut TestAppContext) {
+        let text = "foo foobar Foo\nfoo_bar foo.bar foo";
+        let buffer = cx.new_model(|cx| Buffer::local(text, cx));
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+
+        // Only whole words match.
+        assert_eq!(
+            occurrences(&snapshot, 1, &settings(true, false)),
+            ["0:foo", "23:foo", "31:foo"]
+        );
+        assert_eq!(
+            occurrences(&snapshot, 1, &settings(false, false)),
+            ["0:foo", "11:Foo", "23:foo", "31:foo"]
+        );
+        assert_eq!(
+            occurrences(&snapshot, 17, &settings(true, false)),
+            ["15:foo_bar"]
+        );
+
+        // There's nothing to highlight in whitespace or punctuation.
+        let buffer = cx.new_model(|cx| Buffer::local("foo  +  foo", cx));
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+        assert!(occurrences(&snapshot, 4, &settings(true, false)).is_empty());
+        assert!(occurrences(&snapshot, 6, &settings(true, false)).is_empty());
+    }
+
+    #[gpui::test]
+    fn test_word_occurrences_ignoring_comments_and_strings(cx: &mut TestAppContext) {
+        let language = Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::language()),
+            )
+            .with_override_query(
+                r#"
+                [(line_comment) (block_comment)] @comment
+                (string_literal) @string
+                "#,
+            )
+            .unwrap(),
+        );
+        let text = "let foo = \"foo\"; // foo\nfoo += 1;";
+        let buffer = cx.new_model(|cx| Buffer::local(text, cx).with_language(language, cx));
+        cx.run_until_parked();
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+
+        assert_eq!(
+            occurrences(&snapshot, 4, &settings(true, false)),
+            ["4:foo", "11:foo", "20:foo", "24:foo"]
+        );
+        assert_eq!(
+            occurrences(&snapshot, 4, &settings(true, true)),
+            ["4:foo", "24:foo"]
+        );
+        assert!(occurrences(&snapshot, 21, &settings(true, true)).is_empty());
+    }
+}
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use futures::FutureExt;
+
+use crate::wasm_host::{
+    wit::{CommandContext, CommandEdit},
+    WasmExtension,
+};
+
+/// A command contributed by an extension, which can be run from the command
+/// palette or bound to a key.
+pub struct ExtensionCommand {
+    pub(crate) extension: WasmExtension,
+    pub(crate) id: Arc<str>,
+    pub(crate) title: Arc<str>,
+}
+
+impl ExtensionCommand {
+    pub fn extension_id(&self) -> &Arc<str> {
+        &self.extension.manifest.id
+    }
+
+    pub fn id(&self) -> &Arc<str> {
+        &self.id
+    }
+
+    pub fn title(&self) -> &Arc<str> {
+        &self.title
+    }
+
+    /// Runs the command, returning the edits to make to the file open in the
+    /// active editor.
+    pub async fn run(&self, context: CommandContext) -> Result<Vec<CommandEdit>> {
+        self.extension
+            .call({
+                let id = self.id.clone();
+                |extension, store| {
+                    async move {
+                        extension
+                            .call_run_command(store, id.as_ref(), &context)
+                            .await?
+                            .map_err(|err| anyhow!("{err:?}"))
+                    }
+                    .boxed()
+                }
+            })
+            .await
+    }
+}
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use futures::FutureExt;
+
+use crate::wasm_host::{wit::Decoration, WasmExtension};
+
+/// Provides decorations for the editors of files, by calling into an extension.
+pub struct ExtensionDecorationProvider {
+    pub(crate) extension: WasmExtension,
+    pub(crate) id: Arc<str>,
+    pub(crate) languages: Vec<Arc<str>>,
+}
+
+impl ExtensionDecorationProvider {
+    pub fn extension_id(&self) -> &Arc<str> {
+        &self.extension.manifest.id
+    }
+
+    pub fn id(&self) -> &Arc<str> {
+        &self.id
+    }
+
+    /// Whether this provider decorates files of the given language.
+    pub fn decorates_language(&self, language: Option<&str>) -> bool {
+        self.languages.is_empty()
+            || language.map_or(false, |language| {
+                self.languages
+                    .iter()
+                    .any(|provider_language| provider_language.as_ref() == language)
+            })
+    }
+
+    /// Returns the decorations for the file at the given path, whose offsets
+    /// are into the given text.
+    pub async fn decorate_file(&self, path: String, text: String) -> Result<Vec<Decoration>> {
+        self.extension
+            .call({
+                let id = self.id.clone();
+                |extension, store| {
+                    async move {
+                        extension
+                            .call_decorate_file(store, id.as_ref(), &path, &text)
+                            .await?
+                            .map_err(|err| anyhow!("{err:?}"))
+                    }
+                    .boxed()
+                }
+            })
+            .await
+    }
+}
@@ -79,6 +79,12 @@ pub struct ExtensionManifest {
     #[serde(default)]
     pub indexed_docs_providers: BTreeMap<Arc<str>, IndexedDocsProviderEntry>,
     #[serde(default)]
+    pub decoration_providers: BTreeMap<Arc<str>, DecorationProviderEntry>,
+    #[serde(default)]
+    pub panels: BTreeMap<Arc<str>, PanelManifestEntry>,
+    #[serde(default)]
+    pub commands: BTreeMap<Arc<str>, CommandManifestEntry>,
+    #[serde(default)]
     pub snippets: Option<PathBuf>,
 }
 
@@ -143,6 +149,26 @@ pub struct SlashCommandManifestEntry {
 #[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 pub struct IndexedDocsProviderEntry {}
 
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct DecorationProviderEntry {
+    /// The languages of the files to decorate. Files of any language are
+    /// decorated if this is empty.
+    #[serde(default)]
+    pub languages: Vec<Arc<str>>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct PanelManifestEntry {
+    /// The title of the panel, shown in the panel's tab.
+    pub title: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct CommandManifestEntry {
+    /// The title of the command, shown in the command palette.
+    pub title: String,
+}
+
 impl ExtensionManifest {
     pub async fn load(fs: Arc<dyn Fs>, extension_dir: &Path) -> Result<Self> {
         let extension_name = extension_dir
@@ -207,6 +233,9 @@ fn manifest_from_old_manifest(
         language_servers: Default::default(),
         slash_commands: BTreeMap::default(),
         indexed_docs_providers: BTreeMap::default(),
+        decoration_providers: BTreeMap::default(),
+        panels: BTreeMap::default(),
+        commands: BTreeMap::default(),
         snippets: None,
     }
 }
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::FutureExt;
+
+use crate::wasm_host::{
+    wit::{PanelEvent, PanelItem},
+    WasmExtension,
+};
+
+/// Provides the contents of a panel contributed by an extension.
+#[async_trait]
+pub trait PanelProvider: Send + Sync {
+    /// The ID of the extension contributing the panel.
+    fn extension_id(&self) -> &Arc<str>;
+
+    /// The ID of the panel, unique among the panels of its extension.
+    fn id(&self) -> &Arc<str>;
+
+    fn title(&self) -> &Arc<str>;
+
+    /// Returns the items to show in the panel.
+    async fn render(&self) -> Result<Vec<PanelItem>>;
+
+    /// Lets the extension handle something the user did in the panel.
+    async fn handle_event(&self, event: PanelEvent) -> Result<()>;
+}
+
+/// Provides the contents of a panel, by calling into an extension.
+pub struct ExtensionPanelProvider {
+    pub(crate) extension: WasmExtension,
+    pub(crate) id: Arc<str>,
+    pub(crate) title: Arc<str>,
+}
+
+#[async_trait]
+impl PanelProvider for ExtensionPanelProvider {
+    fn extension_id(&self) -> &Arc<str> {
+        &self.extension.manifest.id
+    }
+
+    fn id(&self) -> &Arc<str> {
+        &self.id
+    }
+
+    fn title(&self) -> &Arc<str> {
+        &self.title
+    }
+
+    async fn render(&self) -> Result<Vec<PanelItem>> {
+        self.extension
+            .call({
+                let id = self.id.clone();
+                |extension, store| {
+                    async move {
+                        extension
+                            .call_render_panel(store, id.as_ref())
+                            .await?
+                            .map_err(|err| anyhow!("{err:?}"))
+                    }
+                    .boxed()
+                }
+            })
+            .await
+    }
+
+    async fn handle_event(&self, event: PanelEvent) -> Result<()> {
+        self.extension
+            .call({
+                let id = self.id.clone();
+                |extension, store| {
+                    async move {
+                        extension
+                            .call_handle_panel_event(store, id.as_ref(), &event)
+                            .await?
+                            .map_err(|err| anyhow!("{err:?}"))
+                    }
+                    .boxed()
+                }
+            })
+            .await
+    }
+}
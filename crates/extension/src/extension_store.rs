@@ -1,7 +1,10 @@
 pub mod extension_builder;
+mod extension_command;
+mod extension_decoration_provider;
 mod extension_indexed_docs_provider;
 mod extension_lsp_adapter;
 mod extension_manifest;
+mod extension_panel_provider;
 mod extension_settings;
 mod extension_slash_command;
 mod wasm_host;
@@ -61,9 +64,19 @@ use wasm_host::{
     WasmExtension, WasmHost,
 };
 
+pub use wasm_host::wit::{
+    CommandContext, CommandEdit, Decoration, DecorationSeverity, GutterIconDecoration,
+    HighlightDecoration, InlayDecoration, PanelButton, PanelEvent, PanelInput,
+    PanelInputSubmission, PanelItem, PanelTreeItem, Range,
+};
+
+pub use extension_command::ExtensionCommand;
+pub use extension_decoration_provider::ExtensionDecorationProvider;
 pub use extension_manifest::{
-    ExtensionLibraryKind, ExtensionManifest, GrammarManifestEntry, OldExtensionManifest,
+    CommandManifestEntry, DecorationProviderEntry, ExtensionLibraryKind, ExtensionManifest,
+    GrammarManifestEntry, OldExtensionManifest, PanelManifestEntry,
 };
+pub use extension_panel_provider::{ExtensionPanelProvider, PanelProvider};
 pub use extension_settings::ExtensionSettings;
 
 const RELOAD_DEBOUNCE_DURATION: Duration = Duration::from_millis(200);
@@ -120,6 +133,9 @@ pub struct ExtensionStore {
     modified_extensions: HashSet<Arc<str>>,
     wasm_host: Arc<WasmHost>,
     wasm_extensions: Vec<(Arc<ExtensionManifest>, WasmExtension)>,
+    decoration_providers: Vec<Arc<ExtensionDecorationProvider>>,
+    panel_providers: Vec<Arc<dyn PanelProvider>>,
+    commands: Vec<Arc<ExtensionCommand>>,
     tasks: Vec<Task<()>>,
 }
 
@@ -136,6 +152,9 @@ pub enum Event {
     StartedReloading,
     ExtensionInstalled(Arc<str>),
     ExtensionFailedToLoad(Arc<str>),
+    DecorationProvidersUpdated,
+    PanelProvidersUpdated,
+    CommandsUpdated,
 }
 
 impl EventEmitter<Event> for ExtensionStore {}
@@ -261,6 +280,9 @@ impl ExtensionStore {
                 cx,
             ),
             wasm_extensions: Vec::new(),
+            decoration_providers: Vec::new(),
+            panel_providers: Vec::new(),
+            commands: Vec::new(),
             fs,
             http_client,
             telemetry,
@@ -426,6 +448,33 @@ impl ExtensionStore {
             .filter_map(|extension| extension.dev.then_some(&extension.manifest))
     }
 
+    /// Returns the providers of decorations for files of the given language.
+    pub fn decoration_providers<'a>(
+        &'a self,
+        language: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a Arc<ExtensionDecorationProvider>> {
+        self.decoration_providers
+            .iter()
+            .filter(move |provider| provider.decorates_language(language))
+    }
+
+    /// Returns the providers of the panels contributed by extensions.
+    pub fn panel_providers(&self) -> &[Arc<dyn PanelProvider>] {
+        &self.panel_providers
+    }
+
+    /// Returns the commands contributed by extensions.
+    pub fn commands(&self) -> &[Arc<ExtensionCommand>] {
+        &self.commands
+    }
+
+    /// Returns the command with the given ID contributed by the given extension.
+    pub fn command(&self, extension_id: &str, command_id: &str) -> Option<&Arc<ExtensionCommand>> {
+        self.commands.iter().find(|command| {
+            command.extension_id().as_ref() == extension_id && command.id().as_ref() == command_id
+        })
+    }
+
     /// Returns the names of themes provided by extensions.
     pub fn extension_themes<'a>(
         &'a self,
@@ -1044,6 +1093,24 @@ impl ExtensionStore {
 
         self.wasm_extensions
             .retain(|(extension, _)| !extensions_to_unload.contains(&extension.id));
+        let decoration_provider_count = self.decoration_providers.len();
+        self.decoration_providers
+            .retain(|provider| !extensions_to_unload.contains(provider.extension_id()));
+        if self.decoration_providers.len() != decoration_provider_count {
+            cx.emit(Event::DecorationProvidersUpdated);
+        }
+        let panel_provider_count = self.panel_providers.len();
+        self.panel_providers
+            .retain(|provider| !extensions_to_unload.contains(provider.extension_id()));
+        if self.panel_providers.len() != panel_provider_count {
+            cx.emit(Event::PanelProvidersUpdated);
+        }
+        let command_count = self.commands.len();
+        self.commands
+            .retain(|command| !extensions_to_unload.contains(command.extension_id()));
+        if self.commands.len() != command_count {
+            cx.emit(Event::CommandsUpdated);
+        }
         self.theme_registry.remove_user_themes(&themes_to_remove);
         self.language_registry
             .remove_languages(&languages_to_remove, &grammars_to_remove);
@@ -1238,8 +1305,51 @@ impl ExtensionStore {
                             },
                         ));
                     }
+
+                    for (provider_id, provider) in &manifest.decoration_providers {
+                        this.decoration_providers
+                            .push(Arc::new(ExtensionDecorationProvider {
+                                extension: wasm_extension.clone(),
+                                id: provider_id.clone(),
+                                languages: provider.languages.clone(),
+                            }));
+                    }
+
+                    for (panel_id, panel) in &manifest.panels {
+                        this.panel_providers.push(Arc::new(ExtensionPanelProvider {
+                            extension: wasm_extension.clone(),
+                            id: panel_id.clone(),
+                            title: panel.title.as_str().into(),
+                        }));
+                    }
+
+                    for (command_id, command) in &manifest.commands {
+                        this.commands.push(Arc::new(ExtensionCommand {
+                            extension: wasm_extension.clone(),
+                            id: command_id.clone(),
+                            title: command.title.as_str().into(),
+                        }));
+                    }
                 }
 
+                if wasm_extensions
+                    .iter()
+                    .any(|(manifest, _)| !manifest.decoration_providers.is_empty())
+                {
+                    cx.emit(Event::DecorationProvidersUpdated);
+                }
+                if wasm_extensions
+                    .iter()
+                    .any(|(manifest, _)| !manifest.panels.is_empty())
+                {
+                    cx.emit(Event::PanelProvidersUpdated);
+                }
+                if wasm_extensions
+                    .iter()
+                    .any(|(manifest, _)| !manifest.commands.is_empty())
+                {
+                    cx.emit(Event::CommandsUpdated);
+                }
                 this.wasm_extensions.extend(wasm_extensions);
                 ThemeSettings::reload_current_theme(cx)
             })
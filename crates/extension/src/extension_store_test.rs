@@ -161,6 +161,9 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         language_servers: BTreeMap::default(),
                         slash_commands: BTreeMap::default(),
                         indexed_docs_providers: BTreeMap::default(),
+                        decoration_providers: BTreeMap::default(),
+                        panels: BTreeMap::default(),
+                        commands: BTreeMap::default(),
                         snippets: None,
                     }),
                     dev: false,
@@ -187,6 +190,9 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         language_servers: BTreeMap::default(),
                         slash_commands: BTreeMap::default(),
                         indexed_docs_providers: BTreeMap::default(),
+                        decoration_providers: BTreeMap::default(),
+                        panels: BTreeMap::default(),
+                        commands: BTreeMap::default(),
                         snippets: None,
                     }),
                     dev: false,
@@ -350,6 +356,9 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                 language_servers: BTreeMap::default(),
                 slash_commands: BTreeMap::default(),
                 indexed_docs_providers: BTreeMap::default(),
+                decoration_providers: BTreeMap::default(),
+                panels: BTreeMap::default(),
+                commands: BTreeMap::default(),
                 snippets: None,
             }),
             dev: false,
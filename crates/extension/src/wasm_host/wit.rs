@@ -2,9 +2,10 @@ mod since_v0_0_1;
 mod since_v0_0_4;
 mod since_v0_0_6;
 mod since_v0_1_0;
+mod since_v0_2_0;
 use indexed_docs::IndexedDocsDatabase;
 use release_channel::ReleaseChannel;
-use since_v0_1_0 as latest;
+use since_v0_2_0 as latest;
 
 use super::{wasm_engine, WasmState};
 use anyhow::{anyhow, Context, Result};
@@ -19,7 +20,14 @@ use wasmtime::{
 #[cfg(test)]
 pub use latest::CodeLabelSpanLiteral;
 pub use latest::{
+    zed::extension::commands::{CommandContext, CommandEdit},
+    zed::extension::editor::{
+        Decoration, DecorationSeverity, GutterIconDecoration, HighlightDecoration, InlayDecoration,
+    },
     zed::extension::lsp::{Completion, CompletionKind, InsertTextFormat, Symbol, SymbolKind},
+    zed::extension::panel::{
+        PanelButton, PanelEvent, PanelInput, PanelInputSubmission, PanelItem, PanelTreeItem,
+    },
     zed::extension::slash_command::{SlashCommandArgumentCompletion, SlashCommandOutput},
     CodeLabel, CodeLabelSpan, Command, Range, SlashCommand,
 };
@@ -56,6 +64,7 @@ pub fn wasm_api_version_range(release_channel: ReleaseChannel) -> RangeInclusive
 }
 
 pub enum Extension {
+    V020(since_v0_2_0::Extension),
     V010(since_v0_1_0::Extension),
     V006(since_v0_0_6::Extension),
     V004(since_v0_0_4::Extension),
@@ -77,6 +86,15 @@ impl Extension {
                 latest::Extension::instantiate_async(store, &component, latest::linker())
                     .await
                     .context("failed to instantiate wasm extension")?;
+            Ok((Self::V020(extension), instance))
+        } else if version >= since_v0_1_0::MIN_VERSION {
+            let (extension, instance) = since_v0_1_0::Extension::instantiate_async(
+                store,
+                &component,
+                since_v0_1_0::linker(),
+            )
+            .await
+            .context("failed to instantiate wasm extension")?;
             Ok((Self::V010(extension), instance))
         } else if version >= since_v0_0_6::MIN_VERSION {
             let (extension, instance) = since_v0_0_6::Extension::instantiate_async(
@@ -110,6 +128,7 @@ impl Extension {
 
     pub async fn call_init_extension(&self, store: &mut Store<WasmState>) -> Result<()> {
         match self {
+            Extension::V020(ext) => ext.call_init_extension(store).await,
             Extension::V010(ext) => ext.call_init_extension(store).await,
             Extension::V006(ext) => ext.call_init_extension(store).await,
             Extension::V004(ext) => ext.call_init_extension(store).await,
@@ -125,10 +144,14 @@ impl Extension {
         resource: Resource<Arc<dyn LspAdapterDelegate>>,
     ) -> Result<Result<Command, String>> {
         match self {
-            Extension::V010(ext) => {
+            Extension::V020(ext) => {
                 ext.call_language_server_command(store, &language_server_id.0, resource)
                     .await
             }
+            Extension::V010(ext) => Ok(ext
+                .call_language_server_command(store, &language_server_id.0, resource)
+                .await?
+                .map(|command| command.into())),
             Extension::V006(ext) => Ok(ext
                 .call_language_server_command(store, &language_server_id.0, resource)
                 .await?
@@ -152,6 +175,14 @@ impl Extension {
         resource: Resource<Arc<dyn LspAdapterDelegate>>,
     ) -> Result<Result<Option<String>, String>> {
         match self {
+            Extension::V020(ext) => {
+                ext.call_language_server_initialization_options(
+                    store,
+                    &language_server_id.0,
+                    resource,
+                )
+                .await
+            }
             Extension::V010(ext) => {
                 ext.call_language_server_initialization_options(
                     store,
@@ -190,6 +221,14 @@ impl Extension {
         resource: Resource<Arc<dyn LspAdapterDelegate>>,
     ) -> Result<Result<Option<String>, String>> {
         match self {
+            Extension::V020(ext) => {
+                ext.call_language_server_workspace_configuration(
+                    store,
+                    &language_server_id.0,
+                    resource,
+                )
+                .await
+            }
             Extension::V010(ext) => {
                 ext.call_language_server_workspace_configuration(
                     store,
@@ -217,10 +256,19 @@ impl Extension {
         completions: Vec<latest::Completion>,
     ) -> Result<Result<Vec<Option<CodeLabel>>, String>> {
         match self {
-            Extension::V010(ext) => {
+            Extension::V020(ext) => {
                 ext.call_labels_for_completions(store, &language_server_id.0, &completions)
                     .await
             }
+            Extension::V010(ext) => Ok(ext
+                .call_labels_for_completions(store, &language_server_id.0, &completions)
+                .await?
+                .map(|labels| {
+                    labels
+                        .into_iter()
+                        .map(|label| label.map(Into::into))
+                        .collect()
+                })),
             Extension::V006(ext) => Ok(ext
                 .call_labels_for_completions(store, &language_server_id.0, &completions)
                 .await?
@@ -241,10 +289,19 @@ impl Extension {
         symbols: Vec<latest::Symbol>,
     ) -> Result<Result<Vec<Option<CodeLabel>>, String>> {
         match self {
-            Extension::V010(ext) => {
+            Extension::V020(ext) => {
                 ext.call_labels_for_symbols(store, &language_server_id.0, &symbols)
                     .await
             }
+            Extension::V010(ext) => Ok(ext
+                .call_labels_for_symbols(store, &language_server_id.0, &symbols)
+                .await?
+                .map(|labels| {
+                    labels
+                        .into_iter()
+                        .map(|label| label.map(Into::into))
+                        .collect()
+                })),
             Extension::V006(ext) => Ok(ext
                 .call_labels_for_symbols(store, &language_server_id.0, &symbols)
                 .await?
@@ -265,6 +322,10 @@ impl Extension {
         arguments: &[String],
     ) -> Result<Result<Vec<SlashCommandArgumentCompletion>, String>> {
         match self {
+            Extension::V020(ext) => {
+                ext.call_complete_slash_command_argument(store, command, arguments)
+                    .await
+            }
             Extension::V010(ext) => {
                 ext.call_complete_slash_command_argument(store, command, arguments)
                     .await
@@ -281,6 +342,10 @@ impl Extension {
         resource: Option<Resource<Arc<dyn LspAdapterDelegate>>>,
     ) -> Result<Result<SlashCommandOutput, String>> {
         match self {
+            Extension::V020(ext) => {
+                ext.call_run_slash_command(store, command, arguments, resource)
+                    .await
+            }
             Extension::V010(ext) => {
                 ext.call_run_slash_command(store, command, arguments, resource)
                     .await
@@ -297,6 +362,7 @@ impl Extension {
         provider: &str,
     ) -> Result<Result<Vec<String>, String>> {
         match self {
+            Extension::V020(ext) => ext.call_suggest_docs_packages(store, provider).await,
             Extension::V010(ext) => ext.call_suggest_docs_packages(store, provider).await,
             Extension::V001(_) | Extension::V004(_) | Extension::V006(_) => Err(anyhow!(
                 "`suggest_docs_packages` not available prior to v0.1.0"
@@ -312,6 +378,10 @@ impl Extension {
         database: Resource<Arc<IndexedDocsDatabase>>,
     ) -> Result<Result<(), String>> {
         match self {
+            Extension::V020(ext) => {
+                ext.call_index_docs(store, provider, package_name, database)
+                    .await
+            }
             Extension::V010(ext) => {
                 ext.call_index_docs(store, provider, package_name, database)
                     .await
@@ -321,6 +391,64 @@ impl Extension {
             }
         }
     }
+
+    pub async fn call_decorate_file(
+        &self,
+        store: &mut Store<WasmState>,
+        provider: &str,
+        path: &str,
+        text: &str,
+    ) -> Result<Result<Vec<Decoration>, String>> {
+        match self {
+            Extension::V020(ext) => ext.call_decorate_file(store, provider, path, text).await,
+            Extension::V001(_) | Extension::V004(_) | Extension::V006(_) | Extension::V010(_) => {
+                Err(anyhow!("`decorate_file` not available prior to v0.2.0"))
+            }
+        }
+    }
+
+    pub async fn call_render_panel(
+        &self,
+        store: &mut Store<WasmState>,
+        panel: &str,
+    ) -> Result<Result<Vec<PanelItem>, String>> {
+        match self {
+            Extension::V020(ext) => ext.call_render_panel(store, panel).await,
+            Extension::V001(_) | Extension::V004(_) | Extension::V006(_) | Extension::V010(_) => {
+                Err(anyhow!("`render_panel` not available prior to v0.2.0"))
+            }
+        }
+    }
+
+    pub async fn call_handle_panel_event(
+        &self,
+        store: &mut Store<WasmState>,
+        panel: &str,
+        event: &PanelEvent,
+    ) -> Result<Result<(), String>> {
+        match self {
+            Extension::V020(ext) => ext.call_handle_panel_event(store, panel, event).await,
+            Extension::V001(_) | Extension::V004(_) | Extension::V006(_) | Extension::V010(_) => {
+                Err(anyhow!(
+                    "`handle_panel_event` not available prior to v0.2.0"
+                ))
+            }
+        }
+    }
+
+    pub async fn call_run_command(
+        &self,
+        store: &mut Store<WasmState>,
+        command: &str,
+        context: &CommandContext,
+    ) -> Result<Result<Vec<CommandEdit>, String>> {
+        match self {
+            Extension::V020(ext) => ext.call_run_command(store, command, context).await,
+            Extension::V001(_) | Extension::V004(_) | Extension::V006(_) | Extension::V010(_) => {
+                Err(anyhow!("`run_command` not available prior to v0.2.0"))
+            }
+        }
+    }
 }
 
 trait ToWasmtimeResult<T> {
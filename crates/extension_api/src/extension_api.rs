@@ -15,6 +15,10 @@ pub use serde_json;
 // that we may want to shadow to provide a cleaner Rust API.
 pub use wit::{
     download_file, make_file_executable,
+    zed::extension::commands::{CommandContext, CommandEdit},
+    zed::extension::editor::{
+        Decoration, DecorationSeverity, GutterIconDecoration, HighlightDecoration, InlayDecoration,
+    },
     zed::extension::github::{
         github_release_by_tag_name, latest_github_release, GithubRelease, GithubReleaseAsset,
         GithubReleaseOptions,
@@ -23,6 +27,9 @@ pub use wit::{
         node_binary_path, npm_install_package, npm_package_installed_version,
         npm_package_latest_version,
     },
+    zed::extension::panel::{
+        PanelButton, PanelEvent, PanelInput, PanelInputSubmission, PanelItem, PanelTreeItem,
+    },
     zed::extension::platform::{current_platform, Architecture, Os},
     zed::extension::slash_command::{
         SlashCommand, SlashCommandArgumentCompletion, SlashCommandOutput, SlashCommandOutputSection,
@@ -147,6 +154,40 @@ pub trait Extension: Send + Sync {
     ) -> Result<(), String> {
         Err("`index_docs` not implemented".to_string())
     }
+
+    /// Returns the decorations to show in the editors of the file at the given path.
+    ///
+    /// This is called when the file is opened and each time it is saved. The
+    /// offsets in the decorations are into the given text.
+    fn decorate_file(
+        &self,
+        _provider: String,
+        _path: String,
+        _text: String,
+    ) -> Result<Vec<Decoration>, String> {
+        Ok(Vec::new())
+    }
+
+    /// Returns the items to show in the specified panel.
+    fn render_panel(&self, _panel: String) -> Result<Vec<PanelItem>, String> {
+        Err("`render_panel` not implemented".to_string())
+    }
+
+    /// Handles something the user did in the specified panel, which is
+    /// rendered again afterwards.
+    fn handle_panel_event(&self, _panel: String, _event: PanelEvent) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Runs the specified command in the given context, returning the edits
+    /// to make to the file open in the active editor.
+    fn run_command(
+        &self,
+        _command: String,
+        _context: CommandContext,
+    ) -> Result<Vec<CommandEdit>, String> {
+        Err("`run_command` not implemented".to_string())
+    }
 }
 
 /// Registers the provided type as a Zed extension.
@@ -186,7 +227,7 @@ mod wit {
 
     wit_bindgen::generate!({
         skip: ["init-extension"],
-        path: "./wit/since_v0.1.0",
+        path: "./wit/since_v0.2.0",
     });
 }
 
@@ -281,6 +322,26 @@ impl wit::Guest for Component {
     ) -> Result<(), String> {
         extension().index_docs(provider, package, database)
     }
+
+    fn decorate_file(
+        provider: String,
+        path: String,
+        text: String,
+    ) -> Result<Vec<Decoration>, String> {
+        extension().decorate_file(provider, path, text)
+    }
+
+    fn render_panel(panel: String) -> Result<Vec<PanelItem>, String> {
+        extension().render_panel(panel)
+    }
+
+    fn handle_panel_event(panel: String, event: PanelEvent) -> Result<(), String> {
+        extension().handle_panel_event(panel, event)
+    }
+
+    fn run_command(command: String, context: CommandContext) -> Result<Vec<CommandEdit>, String> {
+        extension().run_command(command, context)
+    }
 }
 
 /// The ID of a language server.
@@ -0,0 +1,642 @@
+//! A panel showing the panels contributed by extensions, whose contents are
+//! described by the extensions and rendered natively.
+mod extension_panel_settings;
+
+use std::sync::Arc;
+
+use collections::HashMap;
+use editor::Editor;
+use extension::{ExtensionStore, PanelEvent, PanelItem, PanelProvider};
+use gpui::{
+    actions, Action, AnyElement, AppContext, AsyncWindowContext, EventEmitter, FocusHandle,
+    FocusableView, Subscription, Task, View, WeakView,
+};
+use menu::Confirm;
+use project::Fs;
+use settings::Settings;
+use ui::{prelude::*, ListItem};
+use workspace::{
+    dock::{DockPosition, Panel},
+    notifications::NotifyTaskExt,
+    Workspace,
+};
+
+pub use extension_panel_settings::{
+    ExtensionPanelDockPosition, ExtensionPanelSettings, ExtensionPanelSettingsContent,
+};
+
+actions!(extension_panel, [ToggleFocus]);
+
+pub fn init(cx: &mut AppContext) {
+    ExtensionPanelSettings::register(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, cx| {
+            workspace.toggle_panel_focus::<ExtensionPanel>(cx);
+        });
+    })
+    .detach();
+}
+
+pub struct ExtensionPanel {
+    providers: Vec<Arc<dyn PanelProvider>>,
+    active_provider: usize,
+    items: Result<Vec<PanelItem>, SharedString>,
+    inputs: HashMap<String, View<Editor>>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    size: Option<Pixels>,
+    render_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ExtensionPanel {
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<View<Self>> {
+        workspace.update(&mut cx, |workspace, cx| {
+            let fs = workspace.app_state().fs.clone();
+            cx.new_view(|cx| Self::new(fs, cx))
+        })
+    }
+
+    fn new(fs: Arc<dyn Fs>, cx: &mut ViewContext<Self>) -> Self {
+        let mut subscriptions = Vec::new();
+        let store = ExtensionStore::try_global(cx);
+        if let Some(store) = &store {
+            subscriptions.push(cx.subscribe(store, |this, store, event, cx| {
+                if let extension::Event::PanelProvidersUpdated = event {
+                    let providers = store.read(cx).panel_providers().to_vec();
+                    this.set_providers(providers, cx);
+                }
+            }));
+        }
+
+        let mut this = Self {
+            providers: Vec::new(),
+            active_provider: 0,
+            items: Ok(Vec::new()),
+            inputs: HashMap::default(),
+            fs,
+            focus_handle: cx.focus_handle(),
+            size: None,
+            render_task: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        let providers = store
+            .map(|store| store.read(cx).panel_providers().to_vec())
+            .unwrap_or_default();
+        this.set_providers(providers, cx);
+        this
+    }
+
+    /// Lists the panels of the extensions that are loaded, keeping the active one
+    /// if its extension is still loaded.
+    fn set_providers(
+        &mut self,
+        providers: Vec<Arc<dyn PanelProvider>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let active_provider = self.active_provider().cloned();
+        self.providers = providers;
+        let active_provider = active_provider.and_then(|active_provider| {
+            self.providers.iter().position(|provider| {
+                provider.extension_id() == active_provider.extension_id()
+                    && provider.id() == active_provider.id()
+            })
+        });
+        if active_provider.is_none() {
+            self.items = Ok(Vec::new());
+            self.inputs.clear();
+        }
+        self.active_provider = active_provider.unwrap_or(0);
+        self.refresh(cx);
+    }
+
+    fn active_provider(&self) -> Option<&Arc<dyn PanelProvider>> {
+        self.providers.get(self.active_provider)
+    }
+
+    fn activate_provider(&mut self, index: usize, cx: &mut ViewContext<Self>) {
+        if index != self.active_provider {
+            self.active_provider = index;
+            self.items = Ok(Vec::new());
+            self.inputs.clear();
+            self.refresh(cx);
+        }
+    }
+
+    /// Asks the active panel's extension for the panel's items.
+    fn refresh(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(provider) = self.active_provider().cloned() else {
+            self.items = Ok(Vec::new());
+            self.inputs.clear();
+            cx.notify();
+            return;
+        };
+        self.render_task = cx.spawn(|this, mut cx| async move {
+            let items = provider.render().await;
+            this.update(&mut cx, |this, cx| this.set_items(items, cx))
+                .ok();
+        });
+    }
+
+    fn set_items(&mut self, items: anyhow::Result<Vec<PanelItem>>, cx: &mut ViewContext<Self>) {
+        let items = match items {
+            Ok(items) => items,
+            Err(error) => {
+                self.items = Err(error.to_string().into());
+                cx.notify();
+                return;
+            }
+        };
+
+        // Keep the editors of inputs that are still shown, so that re-rendering
+        // the panel doesn't discard text the user has typed.
+        let mut inputs = HashMap::default();
+        for item in &items {
+            if let PanelItem::Input(input) = item {
+                let editor = self.inputs.remove(&input.id).unwrap_or_else(|| {
+                    cx.new_view(|cx| {
+                        let mut editor = Editor::single_line(cx);
+                        editor.set_placeholder_text(input.placeholder.clone(), cx);
+                        editor.set_text(input.value.clone(), cx);
+                        editor
+                    })
+                });
+                inputs.insert(input.id.clone(), editor);
+            }
+        }
+        self.inputs = inputs;
+        self.items = Ok(items);
+        cx.notify();
+    }
+
+    /// Lets the active panel's extension handle an event, then re-renders the
+    /// panel to show its effects.
+    fn send_event(&mut self, event: PanelEvent, cx: &mut ViewContext<Self>) {
+        let Some(provider) = self.active_provider().cloned() else {
+            return;
+        };
+        cx.spawn(|this, mut cx| async move {
+            let result = provider.handle_event(event).await;
+            this.update(&mut cx, |this, cx| this.refresh(cx))?;
+            result
+        })
+        .detach_and_notify_err(cx);
+    }
+
+    fn confirm(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
+        let Some((id, editor)) = self
+            .inputs
+            .iter()
+            .find(|(_, editor)| editor.focus_handle(cx).is_focused(cx))
+        else {
+            return;
+        };
+        let submission = extension::PanelInputSubmission {
+            id: id.clone(),
+            value: editor.read(cx).text(cx),
+        };
+        self.send_event(PanelEvent::Submitted(submission), cx);
+    }
+
+    fn render_tabs(&self, cx: &ViewContext<Self>) -> impl IntoElement {
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .children(self.providers.iter().enumerate().map(|(index, provider)| {
+                Button::new(("extension-panel-tab", index), provider.title().to_string())
+                    .style(ButtonStyle::Subtle)
+                    .selected(index == self.active_provider)
+                    .on_click(cx.listener(move |this, _, cx| this.activate_provider(index, cx)))
+            }))
+    }
+
+    fn render_item(&self, index: usize, item: &PanelItem, cx: &ViewContext<Self>) -> AnyElement {
+        match item {
+            PanelItem::Heading(text) => div()
+                .px_2()
+                .pt_2()
+                .child(
+                    Label::new(text.clone())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .into_any_element(),
+            PanelItem::Text(text) => div()
+                .px_2()
+                .py_1()
+                .child(Label::new(text.clone()))
+                .into_any_element(),
+            PanelItem::TreeItem(tree_item) => {
+                let clicked_id = tree_item.id.clone();
+                let toggled_id = tree_item.id.clone();
+                ListItem::new(("extension-panel-item", index))
+                    .indent_level(tree_item.depth as usize)
+                    .toggle(tree_item.expanded)
+                    .on_toggle(cx.listener(move |this, _, cx| {
+                        this.send_event(PanelEvent::Toggled(toggled_id.clone()), cx)
+                    }))
+                    .on_click(cx.listener(move |this, _, cx| {
+                        this.send_event(PanelEvent::Clicked(clicked_id.clone()), cx)
+                    }))
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Label::new(tree_item.label.clone()).single_line())
+                            .children(tree_item.detail.clone().map(|detail| {
+                                Label::new(detail)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted)
+                                    .single_line()
+                            })),
+                    )
+                    .into_any_element()
+            }
+            PanelItem::Button(button) => {
+                let id = button.id.clone();
+                div()
+                    .px_2()
+                    .py_1()
+                    .child(
+                        Button::new(("extension-panel-item", index), button.label.clone())
+                            .style(ButtonStyle::Filled)
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.send_event(PanelEvent::Clicked(id.clone()), cx)
+                            })),
+                    )
+                    .into_any_element()
+            }
+            PanelItem::Input(input) => div()
+                .mx_2()
+                .my_1()
+                .px_2()
+                .py_1()
+                .border_1()
+                .border_color(cx.theme().colors().border)
+                .rounded_md()
+                .children(self.inputs.get(&input.id).cloned())
+                .into_any_element(),
+        }
+    }
+}
+
+impl Panel for ExtensionPanel {
+    fn persistent_name() -> &'static str {
+        "Extension Panel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        match ExtensionPanelSettings::get_global(cx).dock {
+            ExtensionPanelDockPosition::Left => DockPosition::Left,
+            ExtensionPanelDockPosition::Right => DockPosition::Right,
+            ExtensionPanelDockPosition::Bottom => DockPosition::Bottom,
+        }
+    }
+
+    fn position_is_valid(&self, _: DockPosition) -> bool {
+        true
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<ExtensionPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| {
+                let dock = match position {
+                    DockPosition::Left => ExtensionPanelDockPosition::Left,
+                    DockPosition::Right => ExtensionPanelDockPosition::Right,
+                    DockPosition::Bottom => ExtensionPanelDockPosition::Bottom,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.size
+            .unwrap_or_else(|| ExtensionPanelSettings::get_global(cx).default_size)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.size = size;
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        (!self.providers.is_empty() && ExtensionPanelSettings::get_global(cx).button)
+            .then_some(IconName::Code)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Extension Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn set_active(&mut self, active: bool, cx: &mut ViewContext<Self>) {
+        if active {
+            self.refresh(cx);
+        }
+    }
+}
+
+impl FocusableView for ExtensionPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<workspace::dock::PanelEvent> for ExtensionPanel {}
+
+impl Render for ExtensionPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let content = if self.providers.is_empty() {
+            div()
+                .p_2()
+                .child(Label::new("No extensions contribute panels").color(Color::Muted))
+                .into_any_element()
+        } else {
+            match &self.items {
+                Err(error) => div()
+                    .p_2()
+                    .child(Label::new(error.clone()).color(Color::Error))
+                    .into_any_element(),
+                Ok(items) => v_flex()
+                    .id("extension-panel-items")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .children(
+                        items
+                            .iter()
+                            .enumerate()
+                            .map(|(index, item)| self.render_item(index, item, cx)),
+                    )
+                    .into_any_element(),
+            }
+        };
+
+        v_flex()
+            .id("extension-panel")
+            .size_full()
+            .key_context("ExtensionPanel")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::confirm))
+            .when(self.providers.len() > 1, |this| {
+                this.child(self.render_tabs(cx))
+            })
+            .child(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use extension::{PanelButton, PanelInput, PanelTreeItem};
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use settings::SettingsStore;
+    use std::sync::Mutex;
+
+    struct FakePanelProvider {
+        extension_id: Arc<str>,
+        id: Arc<str>,
+        items: Mutex<Vec<PanelItem>>,
+        events: Mutex<Vec<String>>,
+    }
+
+    impl FakePanelProvider {
+        fn new(extension_id: &str, id: &str, items: Vec<PanelItem>) -> Arc<Self> {
+            Arc::new(Self {
+                extension_id: extension_id.into(),
+                id: id.into(),
+                items: Mutex::new(items),
+                events: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl PanelProvider for FakePanelProvider {
+        fn extension_id(&self) -> &Arc<str> {
+            &self.extension_id
+        }
+
+        fn id(&self) -> &Arc<str> {
+            &self.id
+        }
+
+        fn title(&self) -> &Arc<str> {
+            &self.id
+        }
+
+        async fn render(&self) -> anyhow::Result<Vec<PanelItem>> {
+            Ok(self.items.lock().unwrap().clone())
+        }
+
+        async fn handle_event(&self, event: PanelEvent) -> anyhow::Result<()> {
+            let event = match event {
+                PanelEvent::Clicked(id) => format!("clicked {id}"),
+                PanelEvent::Toggled(id) => format!("toggled {id}"),
+                PanelEvent::Submitted(submission) => {
+                    format!("submitted {}: {}", submission.id, submission.value)
+                }
+            };
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    #[gpui::test]
+    async fn test_panel_listing(cx: &mut TestAppContext) {
+        let (panel, cx) = init_test(cx);
+        let database = FakePanelProvider::new(
+            "database-explorer",
+            "database",
+            vec![
+                PanelItem::Heading("Tables".into()),
+                tree_item("users", 0),
+                tree_item("id", 1),
+            ],
+        );
+        let tasks = FakePanelProvider::new(
+            "task-board",
+            "tasks",
+            vec![PanelItem::Text("Nothing to do".into())],
+        );
+        panel.update(cx, |panel, cx| {
+            assert!(panel.providers.is_empty());
+            assert_eq!(panel.icon(cx), None);
+        });
+
+        set_providers(&panel, vec![database.clone(), tasks.clone()], cx);
+        panel.update(cx, |panel, cx| {
+            assert_eq!(titles(panel), ["database", "tasks"]);
+            assert_eq!(panel.icon(cx), Some(IconName::Code));
+        });
+        assert_eq!(labels(&panel, cx), ["Tables", "users", "id"]);
+
+        panel.update(cx, |panel, cx| panel.activate_provider(1, cx));
+        cx.run_until_parked();
+        assert_eq!(labels(&panel, cx), ["Nothing to do"]);
+    }
+
+    #[gpui::test]
+    async fn test_panel_install_and_uninstall(cx: &mut TestAppContext) {
+        let (panel, cx) = init_test(cx);
+        let database =
+            FakePanelProvider::new("database-explorer", "database", vec![tree_item("users", 0)]);
+        let tasks = FakePanelProvider::new(
+            "task-board",
+            "tasks",
+            vec![PanelItem::Text("Nothing to do".into())],
+        );
+        set_providers(&panel, vec![database.clone()], cx);
+        assert_eq!(labels(&panel, cx), ["users"]);
+
+        // The panel that was shown stays shown when another extension is installed.
+        set_providers(&panel, vec![tasks.clone(), database.clone()], cx);
+        panel.update(cx, |panel, _| {
+            assert_eq!(titles(panel), ["tasks", "database"]);
+            assert_eq!(panel.active_provider, 1);
+        });
+        assert_eq!(labels(&panel, cx), ["users"]);
+
+        // The panels of uninstalled extensions are no longer listed.
+        set_providers(&panel, vec![tasks.clone()], cx);
+        panel.update(cx, |panel, _| {
+            assert_eq!(titles(panel), ["tasks"]);
+            assert_eq!(panel.active_provider, 0);
+        });
+        assert_eq!(labels(&panel, cx), ["Nothing to do"]);
+
+        set_providers(&panel, Vec::new(), cx);
+        panel.update(cx, |panel, cx| {
+            assert!(panel.providers.is_empty());
+            assert_eq!(panel.icon(cx), None);
+        });
+        assert!(labels(&panel, cx).is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_panel_events(cx: &mut TestAppContext) {
+        let (panel, cx) = init_test(cx);
+        let database = FakePanelProvider::new(
+            "database-explorer",
+            "database",
+            vec![
+                PanelItem::Input(PanelInput {
+                    id: "query".into(),
+                    placeholder: "Query".into(),
+                    value: String::new(),
+                }),
+                PanelItem::Button(PanelButton {
+                    id: "run".into(),
+                    label: "Run".into(),
+                }),
+            ],
+        );
+        set_providers(&panel, vec![database.clone()], cx);
+
+        let input = panel.update(cx, |panel, _| panel.inputs["query"].clone());
+        input.update(cx, |editor, cx| editor.set_text("select 1", cx));
+        cx.focus_view(&input);
+        panel.update(cx, |panel, cx| panel.confirm(&Confirm, cx));
+        cx.run_until_parked();
+        panel.update(cx, |panel, cx| {
+            panel.send_event(PanelEvent::Clicked("run".into()), cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            *database.events.lock().unwrap(),
+            ["submitted query: select 1", "clicked run"]
+        );
+
+        // The panel is rendered again after each event, keeping the text typed
+        // into its inputs.
+        database
+            .items
+            .lock()
+            .unwrap()
+            .push(PanelItem::Text("1 row".into()));
+        panel.update(cx, |panel, cx| {
+            panel.send_event(PanelEvent::Toggled("users".into()), cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(labels(&panel, cx), ["query", "Run", "1 row"]);
+        panel.update(cx, |panel, cx| {
+            assert_eq!(panel.inputs["query"].entity_id(), input.entity_id());
+            assert_eq!(input.read(cx).text(cx), "select 1");
+        });
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> (View<ExtensionPanel>, &mut VisualTestContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init_settings(cx);
+            ExtensionPanelSettings::register(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        cx.add_window_view(|cx| ExtensionPanel::new(fs, cx))
+    }
+
+    fn set_providers(
+        panel: &View<ExtensionPanel>,
+        providers: Vec<Arc<FakePanelProvider>>,
+        cx: &mut VisualTestContext,
+    ) {
+        let providers = providers
+            .into_iter()
+            .map(|provider| provider as Arc<dyn PanelProvider>)
+            .collect();
+        panel.update(cx, |panel, cx| panel.set_providers(providers, cx));
+        cx.run_until_parked();
+    }
+
+    fn tree_item(label: &str, depth: u32) -> PanelItem {
+        PanelItem::TreeItem(PanelTreeItem {
+            id: label.into(),
+            label: label.into(),
+            detail: None,
+            depth,
+            expanded: None,
+        })
+    }
+
+    fn titles(panel: &ExtensionPanel) -> Vec<String> {
+        panel
+            .providers
+            .iter()
+            .map(|provider| provider.title().to_string())
+            .collect()
+    }
+
+    /// Returns the text of the panel's items, or the IDs of its inputs.
+    fn labels(panel: &View<ExtensionPanel>, cx: &mut VisualTestContext) -> Vec<String> {
+        panel.update(cx, |panel, _| {
+            let Ok(items) = &panel.items else {
+                return Vec::new();
+            };
+            items
+                .iter()
+                .map(|item| match item {
+                    PanelItem::Heading(text) | PanelItem::Text(text) => text.clone(),
+                    PanelItem::TreeItem(tree_item) => tree_item.label.clone(),
+                    PanelItem::Button(button) => button.label.clone(),
+                    PanelItem::Input(input) => input.id.clone(),
+                })
+                .collect()
+        })
+    }
+}
@@ -0,0 +1,49 @@
+use gpui::Pixels;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtensionPanelDockPosition {
+    Left,
+    Right,
+    Bottom,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ExtensionPanelSettings {
+    pub button: bool,
+    pub default_size: Pixels,
+    pub dock: ExtensionPanelDockPosition,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct ExtensionPanelSettingsContent {
+    /// Whether to show the extension panel button in the status bar when
+    /// extensions contribute panels.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Customize default width or height (in pixels) taken by the extension panel
+    ///
+    /// Default: 240
+    pub default_size: Option<f32>,
+    /// The position of the extension panel
+    ///
+    /// Default: right
+    pub dock: Option<ExtensionPanelDockPosition>,
+}
+
+impl Settings for ExtensionPanelSettings {
+    const KEY: Option<&'static str> = Some("extension_panel");
+
+    type FileContent = ExtensionPanelSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
@@ -0,0 +1,184 @@
+use std::ops::Range;
+
+use anyhow::anyhow;
+use command_palette_hooks::{CommandPaletteCommand, CommandPaletteCommands};
+use editor::{Anchor, Editor};
+use extension::{CommandContext, ExtensionStore};
+use gpui::{impl_actions, AppContext, Model};
+use language::BufferSnapshot;
+use serde::Deserialize;
+use ui::ViewContext;
+use workspace::{notifications::NotifyTaskExt, Workspace};
+
+/// Runs a command contributed by an extension, in the context of the active
+/// editor.
+///
+/// Bind it in the keymap with the IDs of the extension and its command:
+/// `["extensions::RunCommand", { "extension": "my-extension", "command": "my-command" }]`
+#[derive(Clone, PartialEq, Deserialize)]
+pub struct RunCommand {
+    pub extension: String,
+    pub command: String,
+}
+
+impl_actions!(extensions, [RunCommand]);
+
+/// The source of the command palette commands that run extensions' commands.
+const COMMAND_PALETTE_SOURCE: &str = "extensions";
+
+pub(crate) fn init(cx: &mut AppContext) {
+    if let Some(store) = ExtensionStore::try_global(cx) {
+        update_command_palette(&store, cx);
+        cx.subscribe(&store, |store, event, cx| {
+            if let extension::Event::CommandsUpdated = event {
+                update_command_palette(&store, cx);
+            }
+        })
+        .detach();
+    }
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(run_command);
+    })
+    .detach();
+}
+
+fn update_command_palette(store: &Model<ExtensionStore>, cx: &mut AppContext) {
+    let commands = store
+        .read(cx)
+        .commands()
+        .iter()
+        .map(|command| CommandPaletteCommand {
+            name: format!("{}: {}", command.extension_id(), command.title()),
+            action: Box::new(RunCommand {
+                extension: command.extension_id().to_string(),
+                command: command.id().to_string(),
+            }),
+        })
+        .collect();
+    CommandPaletteCommands::update_global(cx, |palette_commands, _| {
+        palette_commands.set_commands(COMMAND_PALETTE_SOURCE, commands);
+    });
+}
+
+fn run_command(workspace: &mut Workspace, action: &RunCommand, cx: &mut ViewContext<Workspace>) {
+    let Some(command) = ExtensionStore::try_global(cx).and_then(|store| {
+        store
+            .read(cx)
+            .command(&action.extension, &action.command)
+            .cloned()
+    }) else {
+        let error = anyhow!(
+            "extension {:?} has no command {:?}",
+            action.extension,
+            action.command
+        );
+        workspace.show_error(&error, cx);
+        return;
+    };
+
+    let editor = workspace.active_item_as::<Editor>(cx);
+    let (context, snapshot) = match &editor {
+        Some(editor) => command_context(editor.read(cx), cx),
+        None => (
+            CommandContext {
+                path: None,
+                language: None,
+                text: None,
+                selection: None,
+                selected_text: None,
+            },
+            None,
+        ),
+    };
+
+    cx.spawn(|_, mut cx| async move {
+        let edits = command.run(context).await?;
+        let (Some(editor), Some(snapshot)) = (editor, snapshot) else {
+            return Ok(());
+        };
+        if edits.is_empty() {
+            return Ok(());
+        }
+
+        // Anchor the edits in the snapshot the command was given, so that
+        // they apply to the same text even if the buffer was edited since.
+        let edits = edits
+            .into_iter()
+            .map(|edit| {
+                let start = clip_offset(&snapshot, edit.range.start);
+                let end = clip_offset(&snapshot, edit.range.end).max(start);
+                (
+                    snapshot.anchor_before(start)..snapshot.anchor_after(end),
+                    edit.new_text,
+                )
+            })
+            .collect::<Vec<_>>();
+        editor.update(&mut cx, |editor, cx| {
+            let multibuffer = editor.buffer().read(cx).snapshot(cx);
+            let Some((excerpt_id, _, _)) = multibuffer.as_singleton() else {
+                return;
+            };
+            let excerpt_id = *excerpt_id;
+            let edits = edits
+                .into_iter()
+                .filter_map(|(range, new_text)| {
+                    let start = multibuffer.anchor_in_excerpt(excerpt_id, range.start)?;
+                    let end = multibuffer.anchor_in_excerpt(excerpt_id, range.end)?;
+                    Some((start..end, new_text))
+                })
+                .collect::<Vec<(Range<Anchor>, String)>>();
+            editor.transact(cx, |editor, cx| editor.edit(edits, cx));
+        })
+    })
+    .detach_and_notify_err(cx);
+}
+
+/// Describes the editor a command is run in, returning the snapshot of its
+/// buffer that offsets in the context are into, if the editor edits a single
+/// buffer.
+fn command_context(editor: &Editor, cx: &AppContext) -> (CommandContext, Option<BufferSnapshot>) {
+    let multibuffer = editor.buffer().read(cx);
+    let multibuffer_snapshot = multibuffer.snapshot(cx);
+    let selection = editor.selections.newest::<usize>(cx);
+    let selected_text = (!selection.is_empty()).then(|| {
+        multibuffer_snapshot
+            .text_for_range(selection.range())
+            .collect::<String>()
+    });
+
+    let Some(buffer) = multibuffer.as_singleton() else {
+        let context = CommandContext {
+            path: None,
+            language: None,
+            text: None,
+            selection: None,
+            selected_text,
+        };
+        return (context, None);
+    };
+    let buffer = buffer.read(cx);
+    let snapshot = buffer.snapshot();
+    let context = CommandContext {
+        // Extensions run on this machine, so they can only read local files.
+        path: buffer
+            .file()
+            .and_then(|file| file.as_local())
+            .map(|file| file.abs_path(cx).to_string_lossy().to_string()),
+        language: buffer
+            .language()
+            .map(|language| language.name().to_string()),
+        text: Some(snapshot.text()),
+        // Offsets into a singleton multibuffer are offsets into its buffer.
+        selection: Some(extension::Range {
+            start: selection.start as u32,
+            end: selection.end as u32,
+        }),
+        selected_text,
+    };
+    (context, Some(snapshot))
+}
+
+fn clip_offset(snapshot: &BufferSnapshot, offset: u32) -> usize {
+    snapshot.clip_offset((offset as usize).min(snapshot.len()), language::Bias::Left)
+}
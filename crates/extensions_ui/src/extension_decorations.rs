@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use collections::HashSet;
+use editor::{Decoration, DecorationSeverity, Editor, EditorMode, ExcerptId, MultiBufferSnapshot};
+use extension::{ExtensionDecorationProvider, ExtensionStore};
+use gpui::{AppContext, Model};
+use language::{Bias, Buffer, BufferSnapshot, Point};
+use ui::{SharedString, ViewContext};
+use util::ResultExt;
+
+/// Prefixes the keys of the decorations extensions set on editors.
+const DECORATION_KEY_PREFIX: &str = "extension:";
+
+/// Shows the decorations extensions provide for files in the editors of those
+/// files, asking for them when a file is opened and each time it's saved.
+pub(crate) fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|editor: &mut Editor, cx: &mut ViewContext<Editor>| {
+        if editor.mode() != EditorMode::Full {
+            return;
+        }
+        let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+            return;
+        };
+        let Some(store) = ExtensionStore::try_global(cx) else {
+            return;
+        };
+
+        decorate(editor, &buffer, cx);
+        cx.subscribe(&buffer, |editor, buffer, event, cx| {
+            if let language::Event::Saved = event {
+                decorate(editor, &buffer, cx);
+            }
+        })
+        .detach();
+        cx.subscribe(&store, move |editor, _, event, cx| {
+            if let extension::Event::DecorationProvidersUpdated = event {
+                decorate(editor, &buffer, cx);
+            }
+        })
+        .detach();
+    })
+    .detach();
+}
+
+fn decorate(editor: &mut Editor, buffer: &Model<Buffer>, cx: &mut ViewContext<Editor>) {
+    let buffer = buffer.read(cx);
+    // Extensions run on this machine, so they can only decorate local files.
+    let Some(path) = buffer
+        .file()
+        .and_then(|file| file.as_local())
+        .map(|file| file.abs_path(cx).to_string_lossy().to_string())
+    else {
+        return;
+    };
+    let language = buffer.language().map(|language| language.name());
+    let snapshot = buffer.snapshot();
+    let providers = ExtensionStore::global(cx)
+        .read(cx)
+        .decoration_providers(language.as_deref())
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let keys = providers.iter().map(decoration_key).collect::<HashSet<_>>();
+    let stale_keys = editor
+        .decoration_keys()
+        .filter(|key| key.starts_with(DECORATION_KEY_PREFIX) && !keys.contains(*key))
+        .cloned()
+        .collect::<Vec<_>>();
+    for key in stale_keys {
+        editor.clear_decorations(&key, cx);
+    }
+
+    let text = snapshot.text();
+    for provider in providers {
+        let key = decoration_key(&provider);
+        let path = path.clone();
+        let text = text.clone();
+        let snapshot = snapshot.clone();
+        cx.spawn(|editor, mut cx| async move {
+            let decorations = provider
+                .decorate_file(path, text)
+                .await
+                .log_err()
+                .unwrap_or_default();
+            editor.update(&mut cx, |editor, cx| {
+                let multibuffer = editor.buffer().read(cx).snapshot(cx);
+                let Some((excerpt_id, _, _)) = multibuffer.as_singleton() else {
+                    return;
+                };
+                let decorations = decorations
+                    .into_iter()
+                    .filter_map(|decoration| {
+                        editor_decoration(decoration, &snapshot, &multibuffer, *excerpt_id)
+                    })
+                    .collect();
+                editor.set_decorations(key, decorations, cx);
+            })
+        })
+        .detach();
+    }
+}
+
+fn decoration_key(provider: &Arc<ExtensionDecorationProvider>) -> SharedString {
+    format!(
+        "{DECORATION_KEY_PREFIX}{}/{}",
+        provider.extension_id(),
+        provider.id()
+    )
+    .into()
+}
+
+/// Anchors a decoration in the snapshot of the buffer its offsets are into,
+/// so that it stays in place as the buffer is edited afterwards.
+fn editor_decoration(
+    decoration: extension::Decoration,
+    snapshot: &BufferSnapshot,
+    multibuffer: &MultiBufferSnapshot,
+    excerpt_id: ExcerptId,
+) -> Option<Decoration> {
+    let anchor = |offset: u32, bias: Bias| {
+        let offset = snapshot.clip_offset((offset as usize).min(snapshot.len()), bias);
+        let anchor = match bias {
+            Bias::Left => snapshot.anchor_before(offset),
+            Bias::Right => snapshot.anchor_after(offset),
+        };
+        multibuffer.anchor_in_excerpt(excerpt_id, anchor)
+    };
+
+    Some(match decoration {
+        extension::Decoration::Highlight(highlight) => Decoration::Highlight {
+            range: anchor(highlight.range.start, Bias::Left)?
+                ..anchor(highlight.range.end, Bias::Right)?,
+            severity: severity(highlight.severity),
+        },
+        extension::Decoration::Inlay(inlay) => Decoration::Inlay {
+            position: anchor(inlay.position, Bias::Right)?,
+            text: inlay.text,
+            severity: severity(inlay.severity),
+        },
+        extension::Decoration::GutterIcon(icon) => {
+            if icon.line > snapshot.max_point().row {
+                return None;
+            }
+            let position = snapshot.anchor_after(Point::new(icon.line, 0));
+            Decoration::GutterIcon {
+                position: multibuffer.anchor_in_excerpt(excerpt_id, position)?,
+                severity: severity(icon.severity),
+                tooltip: icon.tooltip.map(Into::into),
+            }
+        }
+    })
+}
+
+fn severity(severity: extension::DecorationSeverity) -> DecorationSeverity {
+    match severity {
+        extension::DecorationSeverity::Hint => DecorationSeverity::Hint,
+        extension::DecorationSeverity::Info => DecorationSeverity::Info,
+        extension::DecorationSeverity::Warning => DecorationSeverity::Warning,
+        extension::DecorationSeverity::Error => DecorationSeverity::Error,
+        extension::DecorationSeverity::Success => DecorationSeverity::Success,
+    }
+}
@@ -1,4 +1,6 @@
 mod components;
+mod extension_commands;
+mod extension_decorations;
 mod extension_suggest;
 mod extension_version_selector;
 
@@ -38,6 +40,9 @@ use crate::extension_version_selector::{
 actions!(zed, [Extensions, InstallDevExtension]);
 
 pub fn init(cx: &mut AppContext) {
+    extension_commands::init(cx);
+    extension_decorations::init(cx);
+
     cx.observe_new_views(move |workspace: &mut Workspace, cx| {
         workspace
             .register_action(move |workspace, _: &Extensions, cx| {
@@ -0,0 +1,219 @@
+//! Resolves the changes made to files on disk by other programs while their buffers
+//! have unsaved changes: the changes are merged into the buffers when they don't
+//! conflict, and otherwise resolved in a three-way merge view.
+mod merge;
+mod merge_view;
+
+use anyhow::Result;
+use gpui::{
+    actions, AppContext, Context as _, Model, SharedString, Task, ViewContext, VisualContext as _,
+};
+use language::{language_settings::ExternalChangesSettings, Buffer, LineEnding};
+use project::buffer_store::BufferStoreEvent;
+use std::time::SystemTime;
+use util::ResultExt as _;
+use workspace::{
+    notifications::{simple_message_notification::MessageNotification, NotificationId},
+    Toast, Workspace,
+};
+
+pub use merge::{ChunkOrigin, Conflict, Merge, MergeChunk, Resolution};
+pub use merge_view::MergeView;
+
+actions!(external_changes, [MergeFileChanges]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(
+        |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
+            track_project(workspace, cx);
+            MergeView::register(workspace, cx);
+        },
+    )
+    .detach();
+}
+
+/// The text of a buffer, the text of its file on disk, and the text they have in
+/// common, merged.
+#[derive(Clone, Debug)]
+pub struct FileChanges {
+    pub base: String,
+    pub buffer: String,
+    pub disk: String,
+    /// When the file was last modified.
+    pub mtime: Option<SystemTime>,
+    /// The version of the buffer whose text was merged.
+    pub buffer_version: clock::Global,
+    pub merge: Merge,
+}
+
+impl FileChanges {
+    /// Loads the changes made to the buffer's file on disk, if the buffer conflicts
+    /// with its file, and merges them with the buffer's unsaved changes.
+    pub fn load(buffer: &Model<Buffer>, cx: &AppContext) -> Option<Task<Result<Self>>> {
+        let buffer = buffer.read(cx);
+        if !buffer.has_conflict() {
+            return None;
+        }
+        let file = buffer.file()?.as_local()?;
+        let mtime = file.mtime();
+        let load = file.load(cx);
+        // The buffer's saved text is what its file contained when the buffer was last
+        // saved or reloaded.
+        let base = buffer.rope_for_version(buffer.saved_version()).to_string();
+        let buffer_text = buffer.text();
+        let buffer_version = buffer.version();
+        Some(cx.background_executor().spawn(async move {
+            let mut disk = load.await?;
+            LineEnding::normalize(&mut disk);
+            let merge = Merge::new(&base, &buffer_text, &disk);
+            Ok(Self {
+                base,
+                buffer: buffer_text,
+                disk,
+                mtime,
+                buffer_version,
+                merge,
+            })
+        }))
+    }
+
+    /// Replaces the buffer's text with the merged text, as an edit that can be undone,
+    /// and marks the buffer as not conflicting with its file anymore.
+    pub fn apply(
+        &self,
+        buffer: Model<Buffer>,
+        merged: String,
+        cx: &mut AppContext,
+    ) -> Task<Result<()>> {
+        let diff = buffer.update(cx, |buffer, cx| buffer.diff(merged, cx));
+        let disk = self.disk.clone();
+        let mtime = self.mtime;
+        cx.spawn(|mut cx| async move {
+            let diff = diff.await;
+            buffer.update(&mut cx, |buffer, cx| {
+                buffer.finalize_last_transaction();
+                buffer.apply_diff(diff, cx);
+                buffer.finalize_last_transaction();
+                let matches_file = buffer.text() == disk;
+                buffer.did_merge_file_changes(matches_file, mtime, cx);
+            })
+        })
+    }
+}
+
+fn track_project(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let project = workspace.project().clone();
+    for buffer in project.read(cx).opened_buffers(cx) {
+        track_buffer(&buffer, cx);
+    }
+    let buffer_store = project.read(cx).buffer_store().clone();
+    cx.subscribe(&buffer_store, |_, _, event, cx| {
+        if let BufferStoreEvent::BufferAdded(buffer) = event {
+            track_buffer(buffer, cx);
+        }
+    })
+    .detach();
+}
+
+fn track_buffer(buffer: &Model<Buffer>, cx: &mut ViewContext<Workspace>) {
+    cx.subscribe(buffer, |workspace, buffer, event, cx| {
+        if let language::Event::FileHandleChanged = event {
+            file_changed(workspace, buffer, cx);
+        }
+    })
+    .detach();
+}
+
+struct MergedFileChanges;
+struct ConflictingFileChanges;
+
+/// Merges the changes made to a buffer's file on disk into the buffer when they
+/// don't conflict with its unsaved changes, or else offers to resolve them.
+fn file_changed(workspace: &mut Workspace, buffer: Model<Buffer>, cx: &mut ViewContext<Workspace>) {
+    let Some(load) = FileChanges::load(&buffer, cx) else {
+        return;
+    };
+    cx.spawn(|workspace, mut cx| async move {
+        let changes = load.await?;
+        workspace.update(&mut cx, |workspace, cx| {
+            if let Some(merge_view) = MergeView::find(workspace, &buffer, cx) {
+                merge_view.update(cx, |merge_view, cx| merge_view.set_changes(changes, cx));
+                return;
+            }
+            if buffer.read(cx).version() != changes.buffer_version {
+                // The buffer was edited while the file was loaded.
+                file_changed(workspace, buffer, cx);
+                return;
+            }
+            // Buffers without unsaved changes conflict with their files when they
+            // aren't reloaded automatically, and stay as they are.
+            if changes.buffer == changes.base {
+                return;
+            }
+            if changes.buffer == changes.disk {
+                buffer.update(cx, |buffer, cx| {
+                    buffer.did_merge_file_changes(true, changes.mtime, cx)
+                });
+                return;
+            }
+
+            let file_name = file_name(&buffer, cx);
+            let notification_id = NotificationId::identified::<ConflictingFileChanges>((
+                "file-changes",
+                buffer.entity_id(),
+            ));
+            if ExternalChangesSettings::get_or_default(cx).auto_merge {
+                if let Some(merged) = changes.merge.text() {
+                    changes.apply(buffer, merged, cx).detach_and_log_err(cx);
+                    workspace.dismiss_notification(&notification_id, cx);
+                    workspace.show_toast(
+                        Toast::new(
+                            NotificationId::unique::<MergedFileChanges>(),
+                            format!(
+                                "Merged the changes made to {file_name} on disk \
+                                with its unsaved changes"
+                            ),
+                        )
+                        .autohide(),
+                        cx,
+                    );
+                    return;
+                }
+            }
+
+            let message = match changes.merge.conflict_count() {
+                0 => format!("{file_name} was changed on disk while it has unsaved changes."),
+                1 => format!(
+                    "{file_name} was changed on disk, in conflict with its unsaved changes."
+                ),
+                count => format!(
+                    "{file_name} was changed on disk, \
+                    in {count} conflicts with its unsaved changes."
+                ),
+            };
+            let weak_workspace = cx.view().downgrade();
+            workspace.show_notification(notification_id, cx, |cx| {
+                cx.new_view(|_| {
+                    MessageNotification::new(message)
+                        .with_click_message("Merge Changes")
+                        .on_click(move |cx| {
+                            let buffer = buffer.clone();
+                            weak_workspace
+                                .update(cx, |workspace, cx| MergeView::open(workspace, buffer, cx))
+                                .log_err();
+                        })
+                })
+            });
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+fn file_name(buffer: &Model<Buffer>, cx: &AppContext) -> SharedString {
+    buffer
+        .read(cx)
+        .file()
+        .map(|file| file.file_name(cx).to_string_lossy().into_owned())
+        .unwrap_or_else(|| "untitled".to_string())
+        .into()
+}
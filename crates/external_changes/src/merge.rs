@@ -0,0 +1,292 @@
+use similar::{DiffTag, TextDiff};
+use std::ops::Range;
+
+/// Where the text of a chunk that merged without conflict comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkOrigin {
+    /// Neither the buffer nor the file changed the text.
+    Base,
+    /// Only the buffer changed the text.
+    Buffer,
+    /// Only the file on disk changed the text.
+    Disk,
+    /// The buffer and the file changed the text in the same way.
+    Both,
+}
+
+/// Text that the buffer and the file on disk changed in different ways.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    pub base: String,
+    pub buffer: String,
+    pub disk: String,
+}
+
+/// How a conflict is resolved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    Buffer,
+    Disk,
+    /// The buffer's text, followed by the file's.
+    Both,
+    Base,
+}
+
+impl Conflict {
+    pub fn resolve(&self, resolution: Resolution) -> String {
+        match resolution {
+            Resolution::Buffer => self.buffer.clone(),
+            Resolution::Disk => self.disk.clone(),
+            Resolution::Both => format!("{}{}", self.buffer, self.disk),
+            Resolution::Base => self.base.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MergeChunk {
+    Resolved { text: String, origin: ChunkOrigin },
+    Conflict(Conflict),
+}
+
+/// A line-based three-way merge of the changes made to a buffer, and of those made
+/// to its file on disk, since the text they have in common, which is the text the
+/// buffer was last saved or reloaded with.
+///
+/// Changes to the same or adjacent lines conflict, unless they're the same.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Merge {
+    pub chunks: Vec<MergeChunk>,
+}
+
+/// Lines of the base text that one side replaced with some of its lines.
+struct Hunk {
+    base: Range<usize>,
+    new: Range<usize>,
+}
+
+impl Merge {
+    pub fn new(base: &str, buffer: &str, disk: &str) -> Self {
+        let base_lines = base.split_inclusive('\n').collect::<Vec<_>>();
+        let buffer_lines = buffer.split_inclusive('\n').collect::<Vec<_>>();
+        let disk_lines = disk.split_inclusive('\n').collect::<Vec<_>>();
+        let buffer_hunks = hunks(base, buffer);
+        let disk_hunks = hunks(base, disk);
+
+        let mut chunks = Vec::new();
+        let mut base_ix = 0;
+        let (mut buffer_ix, mut disk_ix) = (0, 0);
+        loop {
+            let start = match (buffer_hunks.get(buffer_ix), disk_hunks.get(disk_ix)) {
+                (Some(a), Some(b)) => a.base.start.min(b.base.start),
+                (Some(a), None) => a.base.start,
+                (None, Some(b)) => b.base.start,
+                (None, None) => break,
+            };
+            push_resolved(
+                &mut chunks,
+                base_lines[base_ix..start].concat(),
+                ChunkOrigin::Base,
+            );
+
+            // Gather the hunks of both sides that overlap or touch the region.
+            let mut end = start;
+            let buffer_start = buffer_ix;
+            let disk_start = disk_ix;
+            loop {
+                if let Some(hunk) = buffer_hunks.get(buffer_ix).filter(|h| h.base.start <= end) {
+                    end = end.max(hunk.base.end);
+                    buffer_ix += 1;
+                } else if let Some(hunk) = disk_hunks.get(disk_ix).filter(|h| h.base.start <= end) {
+                    end = end.max(hunk.base.end);
+                    disk_ix += 1;
+                } else {
+                    break;
+                }
+            }
+
+            let region = start..end;
+            let buffer_region = &buffer_hunks[buffer_start..buffer_ix];
+            let disk_region = &disk_hunks[disk_start..disk_ix];
+            let chunk = if disk_region.is_empty() {
+                MergeChunk::Resolved {
+                    text: apply(&base_lines, &buffer_lines, buffer_region, region),
+                    origin: ChunkOrigin::Buffer,
+                }
+            } else if buffer_region.is_empty() {
+                MergeChunk::Resolved {
+                    text: apply(&base_lines, &disk_lines, disk_region, region),
+                    origin: ChunkOrigin::Disk,
+                }
+            } else {
+                let buffer = apply(&base_lines, &buffer_lines, buffer_region, region.clone());
+                let disk = apply(&base_lines, &disk_lines, disk_region, region.clone());
+                if buffer == disk {
+                    MergeChunk::Resolved {
+                        text: buffer,
+                        origin: ChunkOrigin::Both,
+                    }
+                } else {
+                    MergeChunk::Conflict(Conflict {
+                        base: base_lines[region].concat(),
+                        buffer,
+                        disk,
+                    })
+                }
+            };
+            chunks.push(chunk);
+            base_ix = end;
+        }
+        push_resolved(
+            &mut chunks,
+            base_lines[base_ix..].concat(),
+            ChunkOrigin::Base,
+        );
+
+        Self { chunks }
+    }
+
+    pub fn conflicts(&self) -> impl Iterator<Item = &Conflict> {
+        self.chunks.iter().filter_map(|chunk| match chunk {
+            MergeChunk::Conflict(conflict) => Some(conflict),
+            MergeChunk::Resolved { .. } => None,
+        })
+    }
+
+    pub fn conflict_count(&self) -> usize {
+        self.conflicts().count()
+    }
+
+    /// The merged text, with the conflicts resolved in order by `resolutions`, or
+    /// `None` if a conflict isn't resolved.
+    pub fn resolve(&self, resolutions: &[Option<Resolution>]) -> Option<String> {
+        let mut resolutions = resolutions.iter();
+        let mut text = String::new();
+        for chunk in &self.chunks {
+            match chunk {
+                MergeChunk::Resolved { text: chunk, .. } => text.push_str(chunk),
+                MergeChunk::Conflict(conflict) => {
+                    let resolution = (*resolutions.next()?)?;
+                    text.push_str(&conflict.resolve(resolution));
+                }
+            }
+        }
+        Some(text)
+    }
+
+    /// The merged text, if there are no conflicts.
+    pub fn text(&self) -> Option<String> {
+        self.resolve(&[])
+    }
+}
+
+/// The hunks of the line-based diff between the base text and a side's text.
+fn hunks(base: &str, new: &str) -> Vec<Hunk> {
+    TextDiff::from_lines(base, new)
+        .ops()
+        .iter()
+        .filter(|op| op.tag() != DiffTag::Equal)
+        .map(|op| Hunk {
+            base: op.old_range(),
+            new: op.new_range(),
+        })
+        .collect()
+}
+
+/// The text of a side in a region of the base text, which contains the hunks.
+fn apply(base_lines: &[&str], new_lines: &[&str], hunks: &[Hunk], region: Range<usize>) -> String {
+    let mut text = String::new();
+    let mut base_ix = region.start;
+    for hunk in hunks {
+        text.push_str(&base_lines[base_ix..hunk.base.start].concat());
+        text.push_str(&new_lines[hunk.new.clone()].concat());
+        base_ix = hunk.base.end;
+    }
+    text.push_str(&base_lines[base_ix..region.end].concat());
+    text
+}
+
+fn push_resolved(chunks: &mut Vec<MergeChunk>, text: String, origin: ChunkOrigin) {
+    if !text.is_empty() {
+        chunks.push(MergeChunk::Resolved { text, origin });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_without_conflicts() {
+        let base = "one\ntwo\nthree\nfour\nfive\n";
+        let buffer = "one\nTWO\nthree\nfour\nfive\n";
+        let disk = "one\ntwo\nthree\nfour\nFIVE\nsix\n";
+        let merge = Merge::new(base, buffer, disk);
+        assert_eq!(merge.conflict_count(), 0);
+        assert_eq!(
+            merge.text().as_deref(),
+            Some("one\nTWO\nthree\nfour\nFIVE\nsix\n")
+        );
+        assert_eq!(
+            merge
+                .chunks
+                .iter()
+                .map(|chunk| match chunk {
+                    MergeChunk::Resolved { origin, .. } => Some(*origin),
+                    MergeChunk::Conflict(_) => None,
+                })
+                .collect::<Vec<_>>(),
+            vec![
+                Some(ChunkOrigin::Base),
+                Some(ChunkOrigin::Buffer),
+                Some(ChunkOrigin::Base),
+                Some(ChunkOrigin::Disk),
+            ]
+        );
+
+        // The same change on both sides doesn't conflict.
+        let merge = Merge::new("a\nb\n", "a\nc\n", "a\nc\n");
+        assert_eq!(merge.text().as_deref(), Some("a\nc\n"));
+    }
+
+    #[test]
+    fn test_merge_with_conflicts() {
+        let base = "one\ntwo\nthree\nfour\n";
+        let buffer = "one\nbuffer\nthree\nfour\n";
+        let disk = "one\ndisk\nthree\nfour!\n";
+        let merge = Merge::new(base, buffer, disk);
+        assert_eq!(merge.text(), None);
+        assert_eq!(
+            merge.conflicts().collect::<Vec<_>>(),
+            vec![&Conflict {
+                base: "two\n".into(),
+                buffer: "buffer\n".into(),
+                disk: "disk\n".into(),
+            }]
+        );
+        assert_eq!(merge.resolve(&[None]), None);
+        assert_eq!(
+            merge.resolve(&[Some(Resolution::Buffer)]).as_deref(),
+            Some("one\nbuffer\nthree\nfour!\n")
+        );
+        assert_eq!(
+            merge.resolve(&[Some(Resolution::Both)]).as_deref(),
+            Some("one\nbuffer\ndisk\nthree\nfour!\n")
+        );
+
+        // Changes to adjacent lines conflict.
+        let merge = Merge::new("a\nb\nc\n", "A\nb\nc\n", "a\nB\nc\n");
+        assert_eq!(
+            merge.conflicts().collect::<Vec<_>>(),
+            vec![&Conflict {
+                base: "a\nb\n".into(),
+                buffer: "A\nb\n".into(),
+                disk: "a\nB\n".into(),
+            }]
+        );
+        assert_eq!(
+            merge.resolve(&[Some(Resolution::Disk)]).as_deref(),
+            Some("a\nB\nc\n")
+        );
+    }
+}
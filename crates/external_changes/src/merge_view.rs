@@ -0,0 +1,375 @@
+use crate::{
+    file_name, ChunkOrigin, Conflict, FileChanges, MergeChunk, MergeFileChanges, Resolution,
+};
+use editor::Editor;
+use gpui::{
+    AnyElement, AppContext, EventEmitter, FocusHandle, FocusableView, Model, Render, Subscription,
+    Task, View, ViewContext, VisualContext as _,
+};
+use language::Buffer;
+use settings::Settings as _;
+use theme::ThemeSettings;
+use ui::prelude::*;
+use util::ResultExt as _;
+use workspace::{
+    item::{Item, ItemEvent},
+    notifications::NotificationId,
+    Toast, Workspace,
+};
+
+/// How many lines of a chunk that merged without conflict are shown.
+const MAX_PREVIEW_LINES: usize = 12;
+
+pub enum MergeViewEvent {
+    Close,
+}
+
+/// A three-way merge of a buffer's unsaved changes with the changes made to its file
+/// on disk, in which each conflict is resolved by keeping the file's text, the
+/// buffer's, both, or the text they had in common.
+pub struct MergeView {
+    buffer: Model<Buffer>,
+    changes: FileChanges,
+    /// The resolution of each conflict, in order.
+    resolutions: Vec<Option<Resolution>>,
+    focus_handle: FocusHandle,
+    load_task: Option<Task<()>>,
+    apply_task: Option<Task<()>>,
+    _subscription: Subscription,
+}
+
+struct NoFileChanges;
+
+impl MergeView {
+    pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &MergeFileChanges, cx| {
+            let Some(editor) = workspace
+                .active_item(cx)
+                .and_then(|item| item.act_as::<Editor>(cx))
+            else {
+                return;
+            };
+            if let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() {
+                Self::open(workspace, buffer, cx);
+            }
+        });
+    }
+
+    pub(crate) fn find(
+        workspace: &Workspace,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Option<View<Self>> {
+        workspace
+            .items_of_type::<Self>(cx)
+            .find(|merge_view| merge_view.read(cx).buffer == *buffer)
+    }
+
+    /// Opens the merge of the buffer's unsaved changes with the changes made to its
+    /// file on disk.
+    pub fn open(workspace: &mut Workspace, buffer: Model<Buffer>, cx: &mut ViewContext<Workspace>) {
+        if let Some(merge_view) = Self::find(workspace, &buffer, cx) {
+            workspace.activate_item(&merge_view, true, true, cx);
+            return;
+        }
+        let Some(load) = FileChanges::load(&buffer, cx) else {
+            let file_name = file_name(&buffer, cx);
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::unique::<NoFileChanges>(),
+                    format!("{file_name} doesn't conflict with its file on disk"),
+                )
+                .autohide(),
+                cx,
+            );
+            return;
+        };
+        cx.spawn(|workspace, mut cx| async move {
+            let changes = load.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let merge_view = cx.new_view(|cx| Self::new(buffer, changes, cx));
+                workspace.add_item_to_active_pane(Box::new(merge_view), None, true, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn new(buffer: Model<Buffer>, changes: FileChanges, cx: &mut ViewContext<Self>) -> Self {
+        let subscription = cx.subscribe(&buffer, |this, _, event, cx| match event {
+            language::Event::Edited
+            | language::Event::Saved
+            | language::Event::Reloaded
+            | language::Event::FileHandleChanged => this.reload(cx),
+            _ => {}
+        });
+        Self {
+            buffer,
+            resolutions: vec![None; changes.merge.conflict_count()],
+            changes,
+            focus_handle: cx.focus_handle(),
+            load_task: None,
+            apply_task: None,
+            _subscription: subscription,
+        }
+    }
+
+    /// Replaces the merge, keeping the resolutions of the conflicts if there are as
+    /// many of them.
+    pub(crate) fn set_changes(&mut self, changes: FileChanges, cx: &mut ViewContext<Self>) {
+        let conflict_count = changes.merge.conflict_count();
+        if conflict_count != self.resolutions.len() {
+            self.resolutions = vec![None; conflict_count];
+        }
+        self.changes = changes;
+        cx.notify();
+    }
+
+    /// Merges the buffer's changes again, as the buffer or its file changed. The view
+    /// is closed once the buffer doesn't conflict with its file anymore.
+    fn reload(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(load) = FileChanges::load(&self.buffer, cx) else {
+            cx.emit(MergeViewEvent::Close);
+            return;
+        };
+        self.load_task = Some(cx.spawn(|this, mut cx| async move {
+            if let Some(changes) = load.await.log_err() {
+                this.update(&mut cx, |this, cx| this.set_changes(changes, cx))
+                    .ok();
+            }
+        }));
+    }
+
+    fn resolve(&mut self, conflict_ix: usize, resolution: Resolution, cx: &mut ViewContext<Self>) {
+        if let Some(slot) = self.resolutions.get_mut(conflict_ix) {
+            *slot = Some(resolution);
+            cx.notify();
+        }
+    }
+
+    fn resolve_all(&mut self, resolution: Resolution, cx: &mut ViewContext<Self>) {
+        self.resolutions.fill(Some(resolution));
+        cx.notify();
+    }
+
+    /// Replaces the buffer's text with the merged text, and closes the view.
+    fn apply(&mut self, cx: &mut ViewContext<Self>) {
+        if self.buffer.read(cx).version() != self.changes.buffer_version {
+            self.reload(cx);
+            return;
+        }
+        let Some(merged) = self.changes.merge.resolve(&self.resolutions) else {
+            return;
+        };
+        let apply = self.changes.apply(self.buffer.clone(), merged, cx);
+        self.apply_task = Some(cx.spawn(|this, mut cx| async move {
+            if apply.await.log_err().is_some() {
+                this.update(&mut cx, |_, cx| cx.emit(MergeViewEvent::Close))
+                    .ok();
+            }
+        }));
+    }
+
+    fn render_header(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let resolved_count = self.resolutions.iter().flatten().count();
+        let conflict_count = self.resolutions.len();
+        let summary = if conflict_count == 0 {
+            "The changes on disk don't conflict with the unsaved changes".to_string()
+        } else {
+            format!("{resolved_count} of {conflict_count} conflicts resolved")
+        };
+
+        h_flex()
+            .p_2()
+            .gap_2()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(Label::new(summary))
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new("keep-all-buffer", "Keep Unsaved Changes")
+                            .disabled(conflict_count == 0)
+                            .on_click(
+                                cx.listener(|this, _, cx| this.resolve_all(Resolution::Buffer, cx)),
+                            ),
+                    )
+                    .child(
+                        Button::new("take-all-disk", "Take Changes on Disk")
+                            .disabled(conflict_count == 0)
+                            .on_click(
+                                cx.listener(|this, _, cx| this.resolve_all(Resolution::Disk, cx)),
+                            ),
+                    )
+                    .child(
+                        Button::new("apply-merge", "Apply Merge")
+                            .style(ButtonStyle::Filled)
+                            .disabled(resolved_count < conflict_count)
+                            .on_click(cx.listener(|this, _, cx| this.apply(cx))),
+                    ),
+            )
+    }
+
+    fn render_conflict(
+        &self,
+        conflict_ix: usize,
+        conflict: &Conflict,
+        cx: &mut ViewContext<Self>,
+    ) -> AnyElement {
+        let selected = self.resolutions[conflict_ix];
+        let resolution_button = |label: &'static str, resolution: Resolution| {
+            Button::new((label, conflict_ix), label)
+                .selected(selected == Some(resolution))
+                .on_click(cx.listener(move |this, _, cx| this.resolve(conflict_ix, resolution, cx)))
+        };
+        let side = |title: &'static str, text: &str| {
+            v_flex()
+                .flex_1()
+                .min_w_0()
+                .gap_1()
+                .child(Label::new(title).size(LabelSize::Small).color(Color::Muted))
+                .child(render_lines(text, usize::MAX, cx))
+        };
+
+        v_flex()
+            .gap_2()
+            .p_2()
+            .rounded_md()
+            .border_1()
+            .border_color(cx.theme().status().conflict)
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Label::new(format!("Conflict {}", conflict_ix + 1)).color(Color::Conflict),
+                    )
+                    .child(div().flex_1())
+                    .child(resolution_button("Use Disk", Resolution::Disk))
+                    .child(resolution_button("Use Unsaved", Resolution::Buffer))
+                    .child(resolution_button("Use Both", Resolution::Both))
+                    .child(resolution_button("Use Base", Resolution::Base)),
+            )
+            .child(
+                h_flex()
+                    .items_start()
+                    .gap_2()
+                    .child(side("On Disk", &conflict.disk))
+                    .child(side("Base", &conflict.base))
+                    .child(side("Unsaved Changes", &conflict.buffer)),
+            )
+            .into_any_element()
+    }
+
+    fn render_resolved(
+        &self,
+        text: &str,
+        origin: ChunkOrigin,
+        cx: &ViewContext<Self>,
+    ) -> AnyElement {
+        let line_count = text.lines().count();
+        let description = match origin {
+            ChunkOrigin::Base => {
+                return Label::new(format!("{line_count} unchanged lines"))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .into_any_element();
+            }
+            ChunkOrigin::Buffer => "Unsaved changes",
+            ChunkOrigin::Disk => "Changes on disk",
+            ChunkOrigin::Both => "Changed the same way on disk and in the buffer",
+        };
+        v_flex()
+            .gap_1()
+            .child(
+                Label::new(description)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(render_lines(text, MAX_PREVIEW_LINES, cx))
+            .into_any_element()
+    }
+}
+
+/// The lines of the text, in the buffer font, up to `max_lines` of them.
+fn render_lines(text: &str, max_lines: usize, cx: &WindowContext) -> impl IntoElement {
+    let line_count = text.lines().count();
+    v_flex()
+        .p_1()
+        .rounded_sm()
+        .bg(cx.theme().colors().editor_background)
+        .font_buffer(cx)
+        .text_size(ThemeSettings::get_global(cx).buffer_font_size(cx))
+        .children(
+            text.lines()
+                .take(max_lines)
+                .map(|line| div().whitespace_nowrap().child(line.to_string())),
+        )
+        .when(line_count > max_lines, |this| {
+            this.child(
+                Label::new(format!("{} more lines", line_count - max_lines))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+        })
+}
+
+impl EventEmitter<MergeViewEvent> for MergeView {}
+
+impl FocusableView for MergeView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for MergeView {
+    type Event = MergeViewEvent;
+
+    fn tab_icon(&self, _cx: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::FileGit))
+    }
+
+    fn tab_content_text(&self, cx: &WindowContext) -> Option<SharedString> {
+        Some(format!("Merge {}", file_name(&self.buffer, cx)).into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("merge view")
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        match event {
+            MergeViewEvent::Close => f(ItemEvent::CloseItem),
+        }
+    }
+}
+
+impl Render for MergeView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let mut conflict_ix = 0;
+        let mut chunks = Vec::new();
+        for chunk in &self.changes.merge.chunks {
+            chunks.push(match chunk {
+                MergeChunk::Resolved { text, origin } => self.render_resolved(text, *origin, cx),
+                MergeChunk::Conflict(conflict) => {
+                    conflict_ix += 1;
+                    self.render_conflict(conflict_ix - 1, conflict, cx)
+                }
+            });
+        }
+
+        v_flex()
+            .key_context("MergeView")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(cx))
+            .child(
+                div()
+                    .id("merge-chunks")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .child(v_flex().p_2().gap_2().children(chunks)),
+            )
+    }
+}
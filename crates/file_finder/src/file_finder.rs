@@ -5,8 +5,8 @@ mod new_path_prompt;
 mod open_path_prompt;
 
 use collections::HashMap;
-use editor::{scroll::Autoscroll, Bias, Editor};
-use fuzzy::{CharBag, PathMatch, PathMatchCandidate};
+use editor::{scroll::Autoscroll, Bias, Editor, Scratch};
+use fuzzy::{CharBag, PathMatch, PathMatchCandidate, StringMatchCandidate};
 use gpui::{
     actions, rems, Action, AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle,
     FocusableView, Model, Modifiers, ModifiersChangedEvent, ParentElement, Render, Styled, Task,
@@ -166,6 +166,19 @@ pub struct FileFinderDelegate {
     history_items: Vec<FoundPath>,
     separate_history: bool,
     first_update: bool,
+    /// The saved scratch buffers, listed after the files.
+    scratches: Vec<Scratch>,
+    scratch_matches: Vec<ScratchMatch>,
+    scratch_search: Task<()>,
+}
+
+#[derive(Debug, Clone)]
+struct ScratchMatch {
+    scratch: Scratch,
+    /// The positions of the matched characters in the title of the scratch.
+    title_positions: Vec<usize>,
+    /// The positions of the matched characters in the summary of the scratch.
+    summary_positions: Vec<usize>,
 }
 
 /// Use a custom ordering for file finder: the regular one
@@ -442,6 +455,8 @@ impl FoundPath {
 
 const MAX_RECENT_SELECTIONS: usize = 20;
 
+const MAX_SCRATCH_MATCHES: usize = 10;
+
 #[cfg(not(test))]
 fn history_file_exists(abs_path: &PathBuf) -> bool {
     abs_path.exists()
@@ -484,6 +499,11 @@ impl FileFinderDelegate {
         cx: &mut ViewContext<FileFinder>,
     ) -> Self {
         Self::subscribe_to_updates(&project, cx);
+        let scratches = if project.read(cx).is_via_collab() {
+            Vec::new()
+        } else {
+            editor::saved_scratches().log_err().unwrap_or_default()
+        };
         Self {
             file_finder,
             workspace,
@@ -500,9 +520,63 @@ impl FileFinderDelegate {
             history_items,
             separate_history,
             first_update: true,
+            scratches,
+            scratch_matches: Vec::new(),
+            scratch_search: Task::ready(()),
         }
     }
 
+    fn search_scratches(&self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        if self.scratches.is_empty() {
+            return Task::ready(());
+        }
+        let candidates = self
+            .scratches
+            .iter()
+            .enumerate()
+            .map(|(id, scratch)| {
+                StringMatchCandidate::new(id, format!("{} {}", scratch.title(), scratch.summary))
+            })
+            .collect::<Vec<_>>();
+        cx.spawn(|picker, mut cx| async move {
+            let matches = fuzzy::match_strings(
+                &candidates,
+                &query,
+                false,
+                MAX_SCRATCH_MATCHES,
+                &Default::default(),
+                cx.background_executor().clone(),
+            )
+            .await;
+            picker
+                .update(&mut cx, |picker, cx| {
+                    let delegate = &mut picker.delegate;
+                    delegate.scratch_matches = matches
+                        .into_iter()
+                        .take(MAX_SCRATCH_MATCHES)
+                        .map(|string_match| {
+                            let scratch = delegate.scratches[string_match.candidate_id].clone();
+                            let summary_start = scratch.title().len() + 1;
+                            let (title_positions, summary_positions) = string_match
+                                .positions
+                                .into_iter()
+                                .partition::<Vec<_>, _>(|position| *position < summary_start);
+                            ScratchMatch {
+                                scratch,
+                                title_positions,
+                                summary_positions: summary_positions
+                                    .into_iter()
+                                    .map(|position| position - summary_start)
+                                    .collect(),
+                            }
+                        })
+                        .collect();
+                    cx.notify();
+                })
+                .log_err();
+        })
+    }
+
     fn subscribe_to_updates(project: &Model<Project>, cx: &mut ViewContext<FileFinder>) {
         cx.subscribe(project, |file_finder, _, event, cx| {
             match event {
@@ -820,7 +894,7 @@ impl PickerDelegate for FileFinderDelegate {
     }
 
     fn match_count(&self) -> usize {
-        self.matches.len()
+        self.matches.len() + self.scratch_matches.len()
     }
 
     fn selected_index(&self) -> usize {
@@ -834,6 +908,7 @@ impl PickerDelegate for FileFinderDelegate {
     }
 
     fn separators_after_indices(&self) -> Vec<usize> {
+        let mut separators = Vec::new();
         if self.separate_history {
             let first_non_history_index = self
                 .matches
@@ -844,11 +919,15 @@ impl PickerDelegate for FileFinderDelegate {
                 .map(|(i, _)| i);
             if let Some(first_non_history_index) = first_non_history_index {
                 if first_non_history_index > 0 {
-                    return vec![first_non_history_index - 1];
+                    separators.push(first_non_history_index - 1);
                 }
             }
         }
-        Vec::new()
+        // The scratches are listed in their own section, after the files.
+        if !self.scratch_matches.is_empty() && self.matches.len() > 0 {
+            separators.push(self.matches.len() - 1);
+        }
+        separators
     }
 
     fn update_matches(
@@ -858,6 +937,7 @@ impl PickerDelegate for FileFinderDelegate {
     ) -> Task<()> {
         let raw_query = raw_query.replace(' ', "");
         let raw_query = raw_query.trim();
+        self.scratch_search = self.search_scratches(raw_query.to_string(), cx);
         if raw_query.is_empty() {
             // if there was no query before, and we already have some (history) matches
             // there's no need to update anything, since nothing has changed.
@@ -912,6 +992,23 @@ impl PickerDelegate for FileFinderDelegate {
     }
 
     fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<FileFinderDelegate>>) {
+        if let Some(scratch_match) = self
+            .selected_index()
+            .checked_sub(self.matches.len())
+            .and_then(|ix| self.scratch_matches.get(ix))
+        {
+            let scratch_id = scratch_match.scratch.id;
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    editor::open_scratch(workspace, scratch_id, cx).detach_and_log_err(cx);
+                })
+                .log_err();
+            self.file_finder
+                .update(cx, |_, cx| cx.emit(DismissEvent))
+                .log_err();
+            return;
+        }
+
         if let Some(m) = self.matches.get(self.selected_index()) {
             if let Some(workspace) = self.workspace.upgrade() {
                 let open_task = workspace.update(cx, move |workspace, cx| {
@@ -1041,6 +1138,13 @@ impl PickerDelegate for FileFinderDelegate {
         selected: bool,
         cx: &mut ViewContext<Picker<Self>>,
     ) -> Option<Self::ListItem> {
+        if let Some(scratch_match) = ix
+            .checked_sub(self.matches.len())
+            .and_then(|ix| self.scratch_matches.get(ix))
+        {
+            return Some(render_scratch_match(ix, selected, scratch_match));
+        }
+
         let path_match = self
             .matches
             .get(ix)
@@ -1080,6 +1184,35 @@ impl PickerDelegate for FileFinderDelegate {
     }
 }
 
+fn render_scratch_match(ix: usize, selected: bool, scratch_match: &ScratchMatch) -> ListItem {
+    ListItem::new(ix)
+        .spacing(ListItemSpacing::Sparse)
+        .end_slot(
+            Icon::new(IconName::FileText)
+                .color(Color::Muted)
+                .size(IconSize::Small),
+        )
+        .inset(true)
+        .selected(selected)
+        .child(
+            h_flex()
+                .gap_2()
+                .py_px()
+                .child(HighlightedLabel::new(
+                    scratch_match.scratch.title(),
+                    scratch_match.title_positions.clone(),
+                ))
+                .child(
+                    HighlightedLabel::new(
+                        scratch_match.scratch.summary.clone(),
+                        scratch_match.summary_positions.clone(),
+                    )
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+                ),
+        )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
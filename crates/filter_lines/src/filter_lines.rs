@@ -0,0 +1,346 @@
+//! Shows only the lines of a buffer that match a regex, in a read-only view
+//! that keeps up with edits to the buffer.
+use std::{sync::Arc, time::Duration};
+
+use editor::{actions::FilterLines, Addon, Editor, EditorEvent};
+use gpui::{
+    div, prelude::*, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model,
+    ModelContext, Render, SharedString, Styled, Subscription, Task, View, ViewContext,
+};
+use language::{Buffer, BufferSnapshot, Capability, Point};
+use multi_buffer::MultiBuffer;
+use regex::Regex;
+use theme::ActiveTheme;
+use ui::{h_flex, prelude::*, v_flex, Label};
+use workspace::{ModalView, Workspace};
+
+/// How long to wait after the buffer is edited before filtering its lines again.
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Decides whether a line of a buffer is shown in a filtered view.
+pub type LinePredicate = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(FilterLinesModal::register).detach();
+}
+
+/// Prompts for the regex to filter the lines of the active editor's buffer by.
+pub struct FilterLinesModal {
+    query_editor: View<Editor>,
+    active_editor: View<Editor>,
+    status: SharedString,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ModalView for FilterLinesModal {}
+
+impl FocusableView for FilterLinesModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.query_editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for FilterLinesModal {}
+
+impl FilterLinesModal {
+    fn register(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        let handle = cx.view().downgrade();
+        editor
+            .register_action(move |_: &FilterLines, cx| {
+                let Some(editor) = handle.upgrade() else {
+                    return;
+                };
+                let Some(workspace) = editor.read(cx).workspace() else {
+                    return;
+                };
+                workspace.update(cx, |workspace, cx| {
+                    workspace.toggle_modal(cx, move |cx| FilterLinesModal::new(editor, cx));
+                })
+            })
+            .detach();
+    }
+
+    fn new(active_editor: View<Editor>, cx: &mut ViewContext<Self>) -> Self {
+        let query_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Filter lines by regex…", cx);
+            editor
+        });
+        let query_editor_change = cx.subscribe(&query_editor, Self::on_query_editor_event);
+        let mut this = Self {
+            query_editor,
+            active_editor,
+            status: SharedString::default(),
+            _subscriptions: vec![query_editor_change],
+        };
+        this.update_status(cx);
+        this
+    }
+
+    fn on_query_editor_event(
+        &mut self,
+        _: View<Editor>,
+        event: &EditorEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match event {
+            EditorEvent::Blurred => cx.emit(DismissEvent),
+            EditorEvent::BufferEdited { .. } => self.update_status(cx),
+            _ => {}
+        }
+    }
+
+    fn source_buffer(&self, cx: &AppContext) -> Option<Model<Buffer>> {
+        self.active_editor.read(cx).buffer().read(cx).as_singleton()
+    }
+
+    fn regex(&self, cx: &AppContext) -> Option<Result<Regex, regex::Error>> {
+        let query = self.query_editor.read(cx).text(cx);
+        (!query.is_empty()).then(|| Regex::new(&query))
+    }
+
+    fn update_status(&mut self, cx: &mut ViewContext<Self>) {
+        self.status = match (self.source_buffer(cx), self.regex(cx)) {
+            (None, _) => "Only the lines of a single buffer can be filtered".into(),
+            (Some(_), None) => "Type a regex to show only the lines that match it".into(),
+            (Some(_), Some(Err(_))) => "Invalid regex".into(),
+            (Some(buffer), Some(Ok(regex))) => {
+                let count =
+                    matching_rows(&buffer.read(cx).snapshot(), &|line| regex.is_match(line)).len();
+                match count {
+                    1 => "1 matching line".into(),
+                    count => format!("{count} matching lines").into(),
+                }
+            }
+        };
+        cx.notify();
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let (Some(buffer), Some(Ok(regex))) = (self.source_buffer(cx), self.regex(cx)) else {
+            return;
+        };
+        let Some(workspace) = self.active_editor.read(cx).workspace() else {
+            return;
+        };
+        let title = format!("Filter: {}", regex.as_str());
+        let predicate: LinePredicate = Arc::new(move |line| regex.is_match(line));
+        workspace.update(cx, |workspace, cx| {
+            open_filtered_lines(workspace, buffer, title, predicate, cx);
+        });
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for FilterLinesModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        div()
+            .elevation_2(cx)
+            .key_context("FilterLines")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .w_96()
+            .child(
+                v_flex()
+                    .px_1()
+                    .pt_0p5()
+                    .gap_px()
+                    .child(
+                        v_flex()
+                            .py_0p5()
+                            .px_1()
+                            .child(div().px_1().py_0p5().child(self.query_editor.clone())),
+                    )
+                    .child(
+                        div()
+                            .h_px()
+                            .w_full()
+                            .bg(cx.theme().colors().element_background),
+                    )
+                    .child(
+                        h_flex()
+                            .justify_between()
+                            .px_2()
+                            .py_1()
+                            .child(Label::new(self.status.clone()).color(Color::Muted)),
+                    ),
+            )
+    }
+}
+
+/// Opens a read-only view of the lines of the buffer that the predicate
+/// accepts, which is kept up to date as the buffer changes.
+pub fn open_filtered_lines(
+    workspace: &mut Workspace,
+    buffer: Model<Buffer>,
+    title: String,
+    predicate: LinePredicate,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let multibuffer = cx.new_model(|_| MultiBuffer::new(0, Capability::ReadOnly).with_title(title));
+    let filtered_lines =
+        cx.new_model(|cx| FilteredLines::new(buffer, multibuffer.clone(), predicate, cx));
+    let project = workspace.project().clone();
+    let editor = cx.new_view(|cx| {
+        let mut editor = Editor::for_multibuffer(multibuffer, Some(project), false, cx);
+        editor.register_addon(FilteredLinesAddon {
+            _filtered_lines: filtered_lines,
+        });
+        editor
+    });
+    workspace.add_item_to_active_pane(Box::new(editor), None, true, cx);
+}
+
+/// Keeps the lines shown by a filtered view up to date for as long as the view
+/// is open.
+struct FilteredLinesAddon {
+    _filtered_lines: Model<FilteredLines>,
+}
+
+impl Addon for FilteredLinesAddon {
+    fn to_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// The lines of a buffer that a predicate accepts, each shown in an excerpt
+/// of a multibuffer.
+struct FilteredLines {
+    buffer: Model<Buffer>,
+    multibuffer: Model<MultiBuffer>,
+    predicate: LinePredicate,
+    rows: Vec<u32>,
+    refresh_task: Task<()>,
+    _subscription: Subscription,
+}
+
+impl FilteredLines {
+    fn new(
+        buffer: Model<Buffer>,
+        multibuffer: Model<MultiBuffer>,
+        predicate: LinePredicate,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let subscription = cx.subscribe(&buffer, |this, _, event, cx| {
+            if let language::Event::Edited = event {
+                this.refresh(cx);
+            }
+        });
+        let mut this = Self {
+            buffer,
+            multibuffer,
+            predicate,
+            rows: Vec::new(),
+            refresh_task: Task::ready(()),
+            _subscription: subscription,
+        };
+        let rows = matching_rows(&this.buffer.read(cx).snapshot(), &*this.predicate);
+        this.set_rows(rows, cx);
+        this
+    }
+
+    fn refresh(&mut self, cx: &mut ModelContext<Self>) {
+        let snapshot = self.buffer.read(cx).snapshot();
+        let predicate = self.predicate.clone();
+        self.refresh_task = cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(REFRESH_DEBOUNCE).await;
+            let rows = cx
+                .background_executor()
+                .spawn(async move { matching_rows(&snapshot, &*predicate) })
+                .await;
+            this.update(&mut cx, |this, cx| this.set_rows(rows, cx))
+                .ok();
+        });
+    }
+
+    fn set_rows(&mut self, rows: Vec<u32>, cx: &mut ModelContext<Self>) {
+        if rows == self.rows {
+            return;
+        }
+
+        // Lines appended to the buffer, as to a log, are shown without
+        // disturbing the lines already shown.
+        let new_rows = if rows.starts_with(&self.rows) {
+            &rows[self.rows.len()..]
+        } else {
+            self.multibuffer
+                .update(cx, |multibuffer, cx| multibuffer.clear(cx));
+            &rows[..]
+        };
+        let ranges = new_rows
+            .iter()
+            .map(|row| Point::new(*row, 0)..Point::new(*row, 0))
+            .collect();
+        let buffer = self.buffer.clone();
+        self.multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.push_excerpts_with_context_lines(buffer, ranges, 0, cx);
+        });
+        self.rows = rows;
+    }
+}
+
+/// Returns the rows of the lines of the buffer that the predicate accepts.
+fn matching_rows(snapshot: &BufferSnapshot, predicate: &dyn Fn(&str) -> bool) -> Vec<u32> {
+    snapshot
+        .text()
+        .split('\n')
+        .enumerate()
+        .filter(|(_, line)| predicate(line))
+        .map(|(row, _)| row as u32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{Context, TestAppContext};
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_filtered_lines(cx: &mut TestAppContext) {
+        let buffer = cx.new_model(|cx| {
+            Buffer::local(
+                "INFO start\nERROR disk full\nINFO retry\nERROR disk still full",
+                cx,
+            )
+        });
+        let multibuffer = cx.new_model(|_| MultiBuffer::new(0, Capability::ReadOnly));
+        let _filtered_lines = cx.new_model(|cx| {
+            FilteredLines::new(
+                buffer.clone(),
+                multibuffer.clone(),
+                Arc::new(|line| line.starts_with("ERROR")),
+                cx,
+            )
+        });
+        let text = |cx: &mut TestAppContext| {
+            multibuffer.read_with(cx, |multibuffer, cx| multibuffer.read(cx).text())
+        };
+        assert_eq!(text(cx), "ERROR disk full\nERROR disk still full");
+
+        // Appended lines are shown once the filter is refreshed.
+        buffer.update(cx, |buffer, cx| {
+            let len = buffer.len();
+            buffer.edit([(len..len, "\nERROR giving up\nINFO stop")], None, cx);
+        });
+        cx.executor().advance_clock(REFRESH_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(
+            text(cx),
+            "ERROR disk full\nERROR disk still full\nERROR giving up"
+        );
+
+        // Lines that no longer match are removed.
+        buffer.update(cx, |buffer, cx| {
+            let offset = buffer.text().find("ERROR disk full").unwrap();
+            buffer.edit([(offset..offset + "ERROR".len(), "WARN")], None, cx);
+        });
+        cx.executor().advance_clock(REFRESH_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(text(cx), "ERROR disk still full\nERROR giving up");
+    }
+}
@@ -94,8 +94,21 @@ pub trait Fs: Send + Sync {
         Ok(String::from_utf8(self.load_bytes(path).await?)?)
     }
     async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>>;
-    async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()>;
-    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()>;
+    async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()> {
+        self.atomic_write_bytes(path, text.into_bytes()).await
+    }
+    async fn atomic_write_bytes(&self, path: PathBuf, data: Vec<u8>) -> Result<()>;
+    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+        self.save_with_options(path, text, line_ending, SaveOptions::default())
+            .await
+    }
+    async fn save_with_options(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        options: SaveOptions,
+    ) -> Result<()>;
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf>;
     async fn is_file(&self, path: &Path) -> bool;
     async fn is_dir(&self, path: &Path) -> bool;
@@ -165,6 +178,19 @@ pub struct RemoveOptions {
     pub ignore_if_not_exists: bool,
 }
 
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SaveOptions {
+    /// Write to a temporary file that then replaces the file, keeping its
+    /// permissions, owner and extended attributes, so that the file is never left
+    /// partially written.
+    pub atomic: bool,
+    /// Copy the file to [`backup_path`] before it's saved over.
+    pub backup: bool,
+    /// Write the file as an administrator, after asking for their credentials.
+    /// The file is written in place, and isn't backed up.
+    pub elevated: bool,
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct Metadata {
     pub inode: u64,
@@ -367,7 +393,7 @@ impl Fs for RealFs {
         Ok(bytes)
     }
 
-    async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
+    async fn atomic_write_bytes(&self, path: PathBuf, data: Vec<u8>) -> Result<()> {
         smol::unblock(move || {
             let mut tmp_file = if cfg!(target_os = "linux") {
                 // Use the directory of the destination as temp dir to avoid
@@ -387,7 +413,7 @@ impl Fs for RealFs {
             } else {
                 NamedTempFile::new()
             }?;
-            tmp_file.write_all(data.as_bytes())?;
+            tmp_file.write_all(&data)?;
             tmp_file.persist(path)?;
             Ok::<(), anyhow::Error>(())
         })
@@ -396,11 +422,32 @@ impl Fs for RealFs {
         Ok(())
     }
 
-    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
-        let buffer_size = text.summary().len.min(10 * 1024);
+    async fn save_with_options(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        options: SaveOptions,
+    ) -> Result<()> {
+        // Files that need to be saved as an administrator can't be backed up next
+        // to them.
+        if options.backup && !options.elevated && smol::fs::metadata(path).await.is_ok() {
+            smol::fs::copy(path, backup_path(path)).await?;
+        }
+        if options.elevated {
+            let content = chunks(text, line_ending).collect::<String>();
+            return save_elevated(path, content).await;
+        }
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
         }
+        if options.atomic {
+            let path = path.to_path_buf();
+            let content = chunks(text, line_ending).collect::<String>();
+            return smol::unblock(move || atomic_save(&path, content.as_bytes())).await;
+        }
+
+        let buffer_size = text.summary().len.min(10 * 1024);
         let file = smol::fs::File::create(path).await?;
         let mut writer = smol::io::BufWriter::with_capacity(buffer_size, file);
         for chunk in chunks(text, line_ending) {
@@ -1120,6 +1167,14 @@ impl FakeFs {
         })
     }
 
+    pub fn insert_branches(&self, dot_git: &Path, branches: &[&str]) {
+        self.with_git_state(dot_git, true, |state| {
+            state
+                .branches
+                .extend(branches.iter().map(|branch| branch.to_string()));
+        })
+    }
+
     pub fn set_index_for_repo(&self, dot_git: &Path, head_state: &[(&Path, String)]) {
         self.with_git_state(dot_git, true, |state| {
             state.index_contents.clear();
@@ -1131,6 +1186,17 @@ impl FakeFs {
         });
     }
 
+    pub fn set_head_for_repo(&self, dot_git: &Path, head_state: &[(&Path, String)]) {
+        self.with_git_state(dot_git, true, |state| {
+            state.head_contents.clear();
+            state.head_contents.extend(
+                head_state
+                    .iter()
+                    .map(|(path, content)| (path.to_path_buf(), content.clone())),
+            );
+        });
+    }
+
     pub fn set_blame_for_repo(&self, dot_git: &Path, blames: Vec<(&Path, git::blame::Blame)>) {
         self.with_git_state(dot_git, true, |state| {
             state.blames.clear();
@@ -1593,16 +1659,27 @@ impl Fs for FakeFs {
         self.load_internal(path).await
     }
 
-    async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
+    async fn atomic_write_bytes(&self, path: PathBuf, data: Vec<u8>) -> Result<()> {
         self.simulate_random_delay().await;
         let path = normalize_path(path.as_path());
-        self.write_file_internal(path, data.into_bytes())?;
+        self.write_file_internal(path, data)?;
         Ok(())
     }
 
-    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+    async fn save_with_options(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        options: SaveOptions,
+    ) -> Result<()> {
         self.simulate_random_delay().await;
         let path = normalize_path(path);
+        if options.backup {
+            if let Ok(content) = self.load_internal(&path).await {
+                self.write_file_internal(backup_path(&path), content)?;
+            }
+        }
         let content = chunks(text, line_ending).collect::<String>();
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
@@ -1767,6 +1844,111 @@ impl Fs for FakeFs {
     }
 }
 
+/// The path of the copy of a file that's made before the file is saved over, which
+/// is the file's path followed by `~`.
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push("~");
+    path.with_file_name(file_name)
+}
+
+/// Whether an error is caused by a lack of permission to access a file.
+pub fn is_permission_denied(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<io::Error>()
+            .is_some_and(|error| error.kind() == io::ErrorKind::PermissionDenied)
+    })
+}
+
+/// Replaces the file at `path` with a temporary file containing `content`, which
+/// gets the file's permissions, owner and extended attributes.
+fn atomic_save(path: &Path, content: &[u8]) -> Result<()> {
+    // Replace the file a symlink points to, rather than the symlink.
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    // New files get the default permissions, which a temporary file doesn't have.
+    let Ok(metadata) = std::fs::metadata(&path) else {
+        std::fs::write(&path, content)?;
+        return Ok(());
+    };
+    let mut tmp_file = NamedTempFile::new_in(path.parent().unwrap_or(&paths::temp_dir()))?;
+    tmp_file.write_all(content)?;
+    std::fs::set_permissions(tmp_file.path(), metadata.permissions())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::chown;
+
+        // Without privileges, only the group of a file can be changed.
+        let tmp_path = tmp_file.path();
+        chown(tmp_path, Some(metadata.uid()), Some(metadata.gid()))
+            .or_else(|_| chown(tmp_path, None, Some(metadata.gid())))
+            .ok();
+        copy_xattrs(&path, tmp_path).log_err();
+    }
+    tmp_file.as_file().sync_all()?;
+    tmp_file.persist(&path)?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_xattrs(source: &Path, target: &Path) -> Result<()> {
+    if !xattr::SUPPORTED_PLATFORM {
+        return Ok(());
+    }
+    for name in xattr::list(source)? {
+        if let Some(value) = xattr::get(source, &name)? {
+            xattr::set(target, &name, &value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes a file as an administrator, by copying a temporary file over it with a
+/// helper that asks for their credentials.
+async fn save_elevated(path: &Path, content: String) -> Result<()> {
+    let mut tmp_file = NamedTempFile::new()?;
+    tmp_file.write_all(content.as_bytes())?;
+    tmp_file.as_file().sync_all()?;
+
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        fn quoted(path: &Path) -> String {
+            let path = path.to_string_lossy();
+            format!("\"{}\"", path.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+        let script = format!(
+            "do shell script \"/bin/cp \" & quoted form of {} & \" \" & quoted form of {} \
+            with administrator privileges",
+            quoted(tmp_file.path()),
+            quoted(path)
+        );
+        let mut command = smol::process::Command::new("osascript");
+        command.arg("-e").arg(script);
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = {
+        let mut command = smol::process::Command::new("pkexec");
+        command.arg("cp").arg("--").arg(tmp_file.path()).arg(path);
+        command
+    };
+    #[cfg(target_os = "windows")]
+    {
+        anyhow::bail!("saving as an administrator isn't supported on Windows");
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let output = command.output().await?;
+        anyhow::ensure!(
+            output.status.success(),
+            "failed to save {path:?} as an administrator: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        Ok(())
+    }
+}
+
 fn chunks(rope: &Rope, line_ending: LineEnding) -> impl Iterator<Item = &str> {
     rope.chunks().flat_map(move |chunk| {
         let mut newline = false;
@@ -1948,6 +2130,73 @@ mod tests {
             "D",
         );
     }
+
+    #[gpui::test]
+    async fn test_save_with_backup(executor: BackgroundExecutor) {
+        let fs = FakeFs::new(executor.clone());
+        fs.insert_tree("/root", json!({ "a.txt": "old" })).await;
+
+        let options = SaveOptions {
+            backup: true,
+            ..Default::default()
+        };
+        fs.save_with_options(
+            "/root/a.txt".as_ref(),
+            &"new".into(),
+            LineEnding::Unix,
+            options,
+        )
+        .await
+        .unwrap();
+        assert_eq!(fs.load("/root/a.txt".as_ref()).await.unwrap(), "new");
+        assert_eq!(fs.load("/root/a.txt~".as_ref()).await.unwrap(), "old");
+
+        // New files have nothing to back up.
+        fs.save_with_options(
+            "/root/b.txt".as_ref(),
+            &"b".into(),
+            LineEnding::Unix,
+            options,
+        )
+        .await
+        .unwrap();
+        assert!(!fs.is_file("/root/b.txt~".as_ref()).await);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_atomic_save_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("script.sh");
+        std::fs::write(&path, "old").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o750)).unwrap();
+        let link = dir.path().join("link.sh");
+        std::os::unix::fs::symlink(&path, &link).unwrap();
+
+        let options = SaveOptions {
+            atomic: true,
+            ..Default::default()
+        };
+        smol::block_on(RealFs::default().save_with_options(
+            &link,
+            &"new".into(),
+            LineEnding::Unix,
+            options,
+        ))
+        .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o750
+        );
+        // The symlink's target is replaced, rather than the symlink.
+        assert!(std::fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+    }
 }
 
 #[cfg(target_os = "linux")]
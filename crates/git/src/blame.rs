@@ -1,8 +1,10 @@
+use crate::command::{self, GitCommandOptions};
 use crate::commit::get_messages;
 use crate::{parse_git_remote_url, BuildCommitPermalinkParams, GitHostingProviderRegistry, Oid};
 use anyhow::{anyhow, Context, Result};
 use collections::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
 use std::io::Write;
 use std::process::{Command, Stdio};
 use std::sync::Arc;
@@ -192,6 +194,13 @@ impl BlameEntry {
         })
     }
 
+    /// Returns the parent of the entry's commit and the path of the file in it,
+    /// from which the previous revision of the entry's lines can be loaded.
+    pub fn previous_revision(&self) -> Option<(Oid, String)> {
+        let (sha, filename) = self.previous.as_ref()?.split_once(' ')?;
+        Some((sha.parse().ok()?, filename.to_string()))
+    }
+
     pub fn author_offset_date_time(&self) -> Result<time::OffsetDateTime> {
         if let (Some(author_time), Some(author_tz)) = (self.author_time, &self.author_tz) {
             let format = format_description!("[offset_hour][offset_minute]");
@@ -206,6 +215,34 @@ impl BlameEntry {
     }
 }
 
+/// Returns the blame of one line of the file as of the given commit, the row
+/// being 0-indexed.
+pub(crate) fn blame_line_at_revision(
+    git_binary: &Path,
+    working_directory: &Path,
+    sha: Oid,
+    path: &Path,
+    row: u32,
+) -> Result<Option<BlameEntry>> {
+    let line_range = format!("{},{}", row + 1, row + 1);
+    let sha = sha.to_string();
+    let output = command::run(
+        git_binary,
+        working_directory,
+        [
+            OsStr::new("blame"),
+            OsStr::new("--incremental"),
+            OsStr::new("-L"),
+            OsStr::new(&line_range),
+            OsStr::new(&sha),
+            OsStr::new("--"),
+            path.as_os_str(),
+        ],
+        GitCommandOptions::default(),
+    )?;
+    Ok(parse_git_blame(&output)?.into_iter().next())
+}
+
 // parse_git_blame parses the output of `git blame --incremental`, which returns
 // all the blame-entries for a given path incrementally, as it finds them.
 //
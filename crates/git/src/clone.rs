@@ -0,0 +1,125 @@
+use crate::command::{self, Credentials, GitCommandOptions};
+use anyhow::{anyhow, Result};
+use std::{ffi::OsString, path::Path};
+
+/// How a repository is cloned.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CloneOptions {
+    /// Whether to only fetch the latest commit, rather than the whole history.
+    pub shallow: bool,
+    /// Whether to clone the submodules of the repository too.
+    pub recurse_submodules: bool,
+}
+
+/// Clones the repository at the URL into the directory, which must not exist or be
+/// empty, reporting the progress of the transfer.
+///
+/// Fails with [`command::AuthenticationRequired`] when the remote requires
+/// credentials that weren't given, or rejects them.
+pub fn clone_repository(
+    git_binary: &Path,
+    url: &str,
+    directory: &Path,
+    options: CloneOptions,
+    credentials: Option<&Credentials>,
+    progress: &dyn Fn(&str),
+) -> Result<()> {
+    let parent = directory
+        .parent()
+        .ok_or_else(|| anyhow!("invalid clone directory {directory:?}"))?;
+    command::run(
+        git_binary,
+        parent,
+        clone_args(url, directory, options),
+        GitCommandOptions {
+            credentials,
+            progress: Some(progress),
+            ..Default::default()
+        },
+    )?;
+    Ok(())
+}
+
+fn clone_args(url: &str, directory: &Path, options: CloneOptions) -> Vec<OsString> {
+    let mut args = vec!["clone".into(), "--progress".into()];
+    if options.shallow {
+        args.extend(["--depth".into(), "1".into()]);
+    }
+    if options.recurse_submodules {
+        args.push("--recurse-submodules".into());
+        if options.shallow {
+            args.push("--shallow-submodules".into());
+        }
+    }
+    args.extend(["--".into(), url.into(), directory.into()]);
+    args
+}
+
+/// Returns the name of the directory a repository is cloned into by default, which
+/// is the last component of its URL without the `.git` suffix, like `git clone`.
+pub fn clone_directory_name(url: &str) -> Option<&str> {
+    let path = url.trim().trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let name = path.rsplit(['/', ':']).next()?;
+    (!name.is_empty() && name != path).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clone_args() {
+        let directory = Path::new("/code/zed");
+        assert_eq!(
+            clone_args("https://host/zed.git", directory, CloneOptions::default()),
+            [
+                "clone",
+                "--progress",
+                "--",
+                "https://host/zed.git",
+                "/code/zed"
+            ]
+        );
+        assert_eq!(
+            clone_args(
+                "https://host/zed.git",
+                directory,
+                CloneOptions {
+                    shallow: true,
+                    recurse_submodules: true,
+                }
+            ),
+            [
+                "clone",
+                "--progress",
+                "--depth",
+                "1",
+                "--recurse-submodules",
+                "--shallow-submodules",
+                "--",
+                "https://host/zed.git",
+                "/code/zed"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clone_directory_name() {
+        assert_eq!(
+            clone_directory_name("https://github.com/zed-industries/zed.git"),
+            Some("zed")
+        );
+        assert_eq!(
+            clone_directory_name("https://github.com/zed-industries/zed/"),
+            Some("zed")
+        );
+        assert_eq!(
+            clone_directory_name("git@github.com:zed-industries/zed.git"),
+            Some("zed")
+        );
+        assert_eq!(clone_directory_name("git@host:repo.git"), Some("repo"));
+        assert_eq!(clone_directory_name("zed"), None);
+        assert_eq!(clone_directory_name(""), None);
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::{anyhow, Context as _, Result};
+use std::{
+    ffi::OsStr,
+    fmt,
+    io::{Read, Write},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// The credentials used to authenticate with a remote.
+#[derive(Clone, Debug)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// The error returned by git commands that failed because the remote required
+/// credentials that weren't given, or rejected them.
+#[derive(Debug)]
+pub struct AuthenticationRequired;
+
+impl fmt::Display for AuthenticationRequired {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "authentication required")
+    }
+}
+
+impl std::error::Error for AuthenticationRequired {}
+
+/// A credential helper answering git's credential requests with the values of
+/// environment variables, so that credentials are never written to disk nor
+/// passed as arguments.
+const CREDENTIAL_HELPER: &str =
+    "!f() { echo \"username=$ZED_GIT_USERNAME\"; echo \"password=$ZED_GIT_PASSWORD\"; }; f";
+
+/// The messages printed by git when a remote requires credentials.
+const AUTHENTICATION_ERRORS: &[&str] = &[
+    "Authentication failed",
+    "could not read Username",
+    "could not read Password",
+    "terminal prompts disabled",
+    "Invalid username or password",
+];
+
+/// Options of a git command run with [`run`].
+#[derive(Default)]
+pub struct GitCommandOptions<'a> {
+    /// The text written to the command's standard input.
+    pub stdin: Option<&'a str>,
+    /// The credentials given to the remote, if it requires them.
+    pub credentials: Option<&'a Credentials>,
+    /// Called with each line of progress reported by the command, such as the
+    /// objects counted or received while fetching.
+    pub progress: Option<&'a dyn Fn(&str)>,
+}
+
+/// Runs git with the given arguments in the working directory, and returns its
+/// standard output.
+///
+/// Git never prompts for credentials in a terminal: the command fails with
+/// [`AuthenticationRequired`] instead, so that the credentials can be asked for
+/// and given in the options.
+pub fn run<I, S>(
+    git_binary: &Path,
+    working_directory: &Path,
+    args: I,
+    options: GitCommandOptions,
+) -> Result<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut command = Command::new(git_binary);
+    command
+        .current_dir(working_directory)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(if options.stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(credentials) = options.credentials {
+        command
+            .args(["-c", "credential.helper="])
+            .args(["-c", &format!("credential.helper={CREDENTIAL_HELPER}")])
+            .env("ZED_GIT_USERNAME", &credentials.username)
+            .env("ZED_GIT_PASSWORD", &credentials.password);
+    }
+    command.args(args);
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(windows::Win32::System::Threading::CREATE_NO_WINDOW.0);
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| anyhow!("Failed to start git process: {}", e))?;
+    if let Some(stdin) = options.stdin {
+        let mut child_stdin = child.stdin.take().context("failed to get git stdin")?;
+        child_stdin.write_all(stdin.as_bytes())?;
+    }
+
+    let mut stdout = child.stdout.take().context("failed to get git stdout")?;
+    let stdout_reader = std::thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+
+    let mut stderr = child.stderr.take().context("failed to get git stderr")?;
+    let mut errors = Vec::new();
+    let mut pending = Vec::new();
+    let mut chunk = [0; 1024];
+    loop {
+        let len = stderr.read(&mut chunk)?;
+        if len == 0 {
+            break;
+        }
+        errors.extend_from_slice(&chunk[..len]);
+        pending.extend_from_slice(&chunk[..len]);
+        let lines = take_progress_lines(&mut pending);
+        if let Some(progress) = options.progress {
+            for line in lines {
+                progress(&line);
+            }
+        }
+    }
+
+    let output = stdout_reader
+        .join()
+        .map_err(|_| anyhow!("failed to read git output"))??;
+    let status = child.wait()?;
+    if !status.success() {
+        let errors = String::from_utf8_lossy(&errors);
+        if AUTHENTICATION_ERRORS
+            .iter()
+            .any(|message| errors.contains(message))
+        {
+            return Err(AuthenticationRequired.into());
+        }
+        return Err(anyhow!("git process failed: {}", errors.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+/// Removes the complete lines from the output and returns them. Progress is
+/// reported by rewriting the same line, so lines end with either a carriage
+/// return or a newline.
+fn take_progress_lines(output: &mut Vec<u8>) -> Vec<String> {
+    let Some(end) = output.iter().rposition(|&b| b == b'\r' || b == b'\n') else {
+        return Vec::new();
+    };
+    output
+        .drain(..=end)
+        .collect::<Vec<_>>()
+        .split(|&b| b == b'\r' || b == b'\n')
+        .map(|line| String::from_utf8_lossy(line).trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_progress_lines() {
+        let mut output =
+            b"Counting objects:  50% (1/2)\rCounting objects: 100% (2/2), done.\nRecei".to_vec();
+        assert_eq!(
+            take_progress_lines(&mut output),
+            vec![
+                "Counting objects:  50% (1/2)",
+                "Counting objects: 100% (2/2), done."
+            ]
+        );
+        assert_eq!(output, b"Recei");
+
+        assert!(take_progress_lines(&mut output).is_empty());
+        output.extend_from_slice(b"ving objects: 10%\r\n");
+        assert_eq!(
+            take_progress_lines(&mut output),
+            vec!["Receiving objects: 10%"]
+        );
+        assert!(output.is_empty());
+    }
+}
@@ -178,7 +178,13 @@ impl BufferDiff {
         if let Some(patch) = patch {
             let mut divergence = 0;
             for hunk_index in 0..patch.num_hunks() {
-                let hunk = Self::process_patch_hunk(&patch, hunk_index, buffer, &mut divergence);
+                let hunk = Self::process_patch_hunk(
+                    &patch,
+                    hunk_index,
+                    diff_base,
+                    buffer,
+                    &mut divergence,
+                );
                 tree.push(hunk, buffer);
             }
         }
@@ -219,6 +225,7 @@ impl BufferDiff {
     fn process_patch_hunk(
         patch: &GitPatch<'_>,
         hunk_index: usize,
+        diff_base: &Rope,
         buffer: &text::BufferSnapshot,
         buffer_row_divergence: &mut i64,
     ) -> DiffHunk<Anchor> {
@@ -270,8 +277,18 @@ impl BufferDiff {
             row..row
         });
 
-        //unwrap_or addition without deletion
-        let diff_base_byte_range = diff_base_byte_range.unwrap_or(0..0);
+        //unwrap_or addition without deletion: the lines were inserted after the
+        //hunk's old start row in the diff base
+        let diff_base_byte_range = diff_base_byte_range.unwrap_or_else(|| {
+            let (hunk, _) = patch.hunk(hunk_index).unwrap();
+            let row = hunk.old_start();
+            let offset = if row > diff_base.max_point().row {
+                diff_base.len()
+            } else {
+                diff_base.point_to_offset(Point::new(row, 0))
+            };
+            offset..offset
+        });
 
         let start = Point::new(buffer_row_range.start, 0);
         let end = Point::new(buffer_row_range.end, 0);
@@ -285,6 +302,118 @@ impl BufferDiff {
     }
 }
 
+/// Returns the diff base with the changes of the given hunks of the buffer
+/// applied to it, e.g. to stage them in the git index.
+pub fn stage_hunks<T>(
+    diff_base: &Rope,
+    buffer: &BufferSnapshot,
+    hunks: impl IntoIterator<Item = DiffHunk<T>>,
+) -> String {
+    let mut changes = hunks
+        .into_iter()
+        .map(|hunk| {
+            let text = buffer
+                .text_for_range(hunk.buffer_range.to_offset(buffer))
+                .collect::<String>();
+            (hunk.diff_base_byte_range, text)
+        })
+        .collect::<Vec<_>>();
+    changes.sort_by_key(|(range, _)| (range.start, range.end));
+    changes.dedup_by_key(|(range, _)| range.clone());
+
+    let mut staged = diff_base.clone();
+    for (range, text) in changes.into_iter().rev() {
+        staged.replace(range, &text);
+    }
+    staged.to_string()
+}
+
+/// Returns the index text with the staged changes to the given rows of the
+/// buffer reverted to their content in HEAD, to unstage them.
+///
+/// `unstaged_hunks` are the hunks of the buffer's diff against the index.
+pub async fn unstage_rows(
+    head: &Rope,
+    index: &Rope,
+    buffer: &BufferSnapshot,
+    unstaged_hunks: impl IntoIterator<Item = DiffHunk<u32>>,
+    rows: &[Range<u32>],
+) -> String {
+    let unstaged_hunks = unstaged_hunks
+        .into_iter()
+        .map(|hunk| {
+            let index_rows = index.offset_to_point(hunk.diff_base_byte_range.start).row
+                ..index.offset_to_point(hunk.diff_base_byte_range.end).row;
+            (hunk.associated_range, index_rows)
+        })
+        .collect::<Vec<_>>();
+    let index_rows = rows
+        .iter()
+        .map(|rows| {
+            index_row(rows.start, &unstaged_hunks, false)
+                ..index_row(rows.end, &unstaged_hunks, true)
+        })
+        .collect::<Vec<_>>();
+
+    let index_buffer = text::Buffer::new(0, buffer.remote_id(), index.to_string());
+    let index_snapshot = index_buffer.snapshot();
+    let mut staged_diff = BufferDiff::new();
+    staged_diff.update(head, &index_snapshot).await;
+    let staged_hunks = staged_diff
+        .hunks_intersecting_range(
+            index_snapshot.anchor_before(0)..index_snapshot.anchor_after(index_snapshot.len()),
+            &index_snapshot,
+        )
+        .filter(|hunk| {
+            index_rows
+                .iter()
+                .any(|rows| rows_intersect(&hunk.associated_range, rows))
+        })
+        .collect::<Vec<_>>();
+
+    let mut unstaged = index.clone();
+    for hunk in staged_hunks.into_iter().rev() {
+        let range = index.point_to_offset(Point::new(hunk.associated_range.start, 0))
+            ..index.point_to_offset(Point::new(hunk.associated_range.end, 0));
+        let text = head.slice(hunk.diff_base_byte_range).to_string();
+        unstaged.replace(range, &text);
+    }
+    unstaged.to_string()
+}
+
+/// Maps a row of the buffer to the corresponding row of the index, given the
+/// buffer rows and index rows of the hunks between them. Rows inside of a hunk
+/// are mapped to the start of its index rows, or their end for `is_end`.
+fn index_row(row: u32, hunks: &[(Range<u32>, Range<u32>)], is_end: bool) -> u32 {
+    let mut index_row = row;
+    for (buffer_rows, index_rows) in hunks {
+        if buffer_rows.end <= row {
+            index_row = (row as i64 + index_rows.end as i64 - buffer_rows.end as i64) as u32;
+        } else if buffer_rows.start < row {
+            return if is_end {
+                index_rows.end
+            } else {
+                index_rows.start
+            };
+        } else {
+            break;
+        }
+    }
+    index_row
+}
+
+/// Returns whether the staged hunk's index rows intersect the given index rows.
+/// A removal intersects the row following it.
+fn rows_intersect(hunk_rows: &Range<u32>, rows: &Range<u32>) -> bool {
+    if hunk_rows.is_empty() {
+        rows.contains(&hunk_rows.start) || *rows == *hunk_rows
+    } else if rows.is_empty() {
+        hunk_rows.start <= rows.start && rows.start <= hunk_rows.end
+    } else {
+        hunk_rows.start < rows.end && rows.start < hunk_rows.end
+    }
+}
+
 /// Range (crossing new lines), old, new
 #[cfg(any(test, feature = "test-support"))]
 #[track_caller]
@@ -421,4 +550,110 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_stage_hunks() {
+        let diff_base = "
+            one
+            two
+            three
+            four
+        "
+        .unindent();
+        let diff_base_rope = Rope::from(diff_base.clone());
+
+        let buffer_text = "
+            zero
+            one
+            TWO
+            three
+            five
+        "
+        .unindent();
+
+        let buffer = Buffer::new(0, BufferId::new(1).unwrap(), buffer_text.clone());
+        let mut diff = BufferDiff::new();
+        smol::block_on(diff.update(&diff_base_rope, &buffer));
+        assert_hunks(
+            diff.hunks(&buffer),
+            &buffer,
+            &diff_base,
+            &[
+                (0..1, "", "zero\n"),
+                (2..3, "two\n", "TWO\n"),
+                (4..5, "four\n", "five\n"),
+            ],
+        );
+
+        // Added lines are staged where they were inserted.
+        let hunks = diff.hunks_in_row_range(0..1, &buffer).collect::<Vec<_>>();
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(
+            stage_hunks(&diff_base_rope, &buffer, hunks),
+            "zero\none\ntwo\nthree\nfour\n"
+        );
+
+        let hunks = diff.hunks_in_row_range(2..5, &buffer).collect::<Vec<_>>();
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(
+            stage_hunks(&diff_base_rope, &buffer, hunks),
+            "one\nTWO\nthree\nfive\n"
+        );
+
+        assert_eq!(
+            stage_hunks(&diff_base_rope, &buffer, diff.hunks(&buffer)),
+            buffer_text
+        );
+    }
+
+    #[test]
+    fn test_unstage_rows() {
+        let head = Rope::from(
+            "
+            one
+            two
+            three
+            four
+            five
+            "
+            .unindent(),
+        );
+        // The change of the second line and the removal of the fourth are staged.
+        let index = Rope::from(
+            "
+            one
+            TWO
+            three
+            five
+            "
+            .unindent(),
+        );
+        let buffer_text = "
+            zero
+            one
+            TWO
+            three
+            FIVE
+        "
+        .unindent();
+
+        let buffer = Buffer::new(0, BufferId::new(1).unwrap(), buffer_text);
+        let mut diff = BufferDiff::new();
+        smol::block_on(diff.update(&index, &buffer));
+        let unstage = |rows: &[Range<u32>]| {
+            smol::block_on(unstage_rows(
+                &head,
+                &index,
+                &buffer,
+                diff.hunks(&buffer),
+                rows,
+            ))
+        };
+
+        assert_eq!(unstage(&[2..3]), "one\ntwo\nthree\nfive\n");
+        assert_eq!(unstage(&[3..4]), index.to_string());
+        assert_eq!(unstage(&[4..5]), "one\nTWO\nthree\nfour\nfive\n");
+        assert_eq!(unstage(&[0..1]), index.to_string());
+        assert_eq!(unstage(&[0..5]), head.to_string());
+    }
 }
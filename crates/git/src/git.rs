@@ -12,8 +12,11 @@ pub use git2 as libgit;
 pub use crate::hosting_provider::*;
 
 pub mod blame;
+pub mod clone;
+pub mod command;
 pub mod commit;
 pub mod diff;
+pub mod history;
 pub mod repository;
 pub mod status;
 
@@ -0,0 +1,133 @@
+use crate::{
+    command::{self, GitCommandOptions},
+    repository::RepoPath,
+    Oid,
+};
+use anyhow::Result;
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+/// A commit that changed a file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileCommit {
+    pub sha: Oid,
+    pub author: String,
+    /// The time of the commit, in seconds since the Unix epoch.
+    pub time: i64,
+    pub summary: String,
+    /// The path of the file in the commit, which differs from its current path
+    /// when the file was renamed since.
+    pub path: RepoPath,
+}
+
+/// Separates the commits in the output of `git log`.
+const COMMIT_SEPARATOR: char = '\x1e';
+
+/// Returns the commits that changed the file, from the newest to the oldest,
+/// following the file across renames.
+pub(crate) fn file_history(
+    git_binary: &Path,
+    working_directory: &Path,
+    path: &Path,
+) -> Result<Vec<FileCommit>> {
+    let format = format!("--format={COMMIT_SEPARATOR}%H%x00%an%x00%at%x00%s");
+    let output = command::run(
+        git_binary,
+        working_directory,
+        [
+            OsStr::new("log"),
+            OsStr::new("--follow"),
+            OsStr::new("--name-only"),
+            OsStr::new(&format),
+            OsStr::new("--"),
+            path.as_os_str(),
+        ],
+        GitCommandOptions::default(),
+    )?;
+    Ok(parse_file_history(&output, path))
+}
+
+/// Parses the output of `git log --name-only`, where each commit is formatted
+/// as its fields separated by NUL characters, followed by a blank line and the
+/// path of the file. Merge commits have no path, and keep the path of the
+/// newer commit.
+fn parse_file_history(output: &str, path: &Path) -> Vec<FileCommit> {
+    let mut commits = Vec::new();
+    let mut current_path = path.to_path_buf();
+    for commit in output.split(COMMIT_SEPARATOR) {
+        let mut lines = commit.lines();
+        let Some(header) = lines.next() else {
+            continue;
+        };
+        let mut fields = header.split('\0');
+        let (Some(sha), Some(author), Some(time), Some(summary)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let Ok(sha) = sha.parse::<Oid>() else {
+            continue;
+        };
+        if let Some(path) = lines.map(str::trim).find(|line| !line.is_empty()) {
+            current_path = PathBuf::from(path);
+        }
+        commits.push(FileCommit {
+            sha,
+            author: author.to_string(),
+            time: time.parse().unwrap_or_default(),
+            summary: summary.to_string(),
+            path: RepoPath(current_path.clone()),
+        });
+    }
+    commits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_history() {
+        let output = "\x1e1111111111111111111111111111111111111111\0Ada\01700000300\0Tweak the parser\n\nsrc/parser.rs\n\
+            \x1e2222222222222222222222222222222222222222\0Grace\01700000200\0Merge branch 'fix'\n\
+            \x1e3333333333333333333333333333333333333333\0Ada\01700000100\0Add the parser\n\nsrc/parse.rs\n";
+        let commits = parse_file_history(output, Path::new("src/parser.rs"));
+        assert_eq!(
+            commits
+                .iter()
+                .map(|commit| (
+                    commit.sha.display_short(),
+                    commit.author.as_str(),
+                    commit.time,
+                    commit.summary.as_str(),
+                    commit.path.to_string_lossy().into_owned()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "1111111".to_string(),
+                    "Ada",
+                    1700000300,
+                    "Tweak the parser",
+                    "src/parser.rs".to_string()
+                ),
+                (
+                    "2222222".to_string(),
+                    "Grace",
+                    1700000200,
+                    "Merge branch 'fix'",
+                    "src/parser.rs".to_string()
+                ),
+                (
+                    "3333333".to_string(),
+                    "Ada",
+                    1700000100,
+                    "Add the parser",
+                    "src/parse.rs".to_string()
+                ),
+            ]
+        );
+    }
+}
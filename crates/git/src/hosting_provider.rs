@@ -151,6 +151,11 @@ impl GitHostingProviderRegistry {
             .providers
             .insert(provider.name(), provider);
     }
+
+    /// Removes the [`GitHostingProvider`] with the given name from the registry.
+    pub fn unregister_hosting_provider(&self, name: &str) {
+        self.state.write().providers.remove(name);
+    }
 }
 
 #[derive(Debug)]
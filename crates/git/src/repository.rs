@@ -1,13 +1,20 @@
-use crate::GitHostingProviderRegistry;
-use crate::{blame::Blame, status::GitStatus};
+use crate::command::{self, Credentials, GitCommandOptions};
+use crate::history::{self, FileCommit};
+use crate::status::{self, FileStatus};
+use crate::{
+    blame::{self, Blame, BlameEntry},
+    status::GitStatus,
+};
+use crate::{GitHostingProviderRegistry, Oid};
 use anyhow::{Context, Result};
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use git2::BranchType;
 use parking_lot::Mutex;
 use rope::Rope;
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
+    ffi::OsStr,
     path::{Component, Path, PathBuf},
     sync::Arc,
 };
@@ -20,6 +27,16 @@ pub struct Branch {
     pub name: Box<str>,
     /// Timestamp of most recent commit, normalized to Unix Epoch format.
     pub unix_timestamp: Option<i64>,
+    /// How the branch compares with its upstream branch, if it has one.
+    pub upstream: Option<UpstreamTracking>,
+}
+
+/// The number of commits of a branch that aren't in its upstream branch, and
+/// the other way around.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct UpstreamTracking {
+    pub ahead: usize,
+    pub behind: usize,
 }
 
 pub trait GitRepository: Send + Sync {
@@ -29,8 +46,21 @@ pub trait GitRepository: Send + Sync {
     /// Note that for symlink entries, this will return the contents of the symlink, not the target.
     fn load_index_text(&self, relative_file_path: &Path) -> Option<String>;
 
+    /// Loads a git repository entry's contents in the HEAD commit.
+    fn load_head_text(&self, relative_file_path: &Path) -> Option<String>;
+
+    /// Loads a git repository entry's contents in the HEAD commit, which may
+    /// not be text.
+    fn load_head_bytes(&self, relative_file_path: &Path) -> Option<Vec<u8>>;
+
+    /// Writes the contents of a file to the index, staging them.
+    fn set_index_text(&self, relative_file_path: &Path, content: String) -> Result<()>;
+
     /// Returns the URL of the remote with the given name.
     fn remote_url(&self, name: &str) -> Option<String>;
+    /// Returns the name of the remote tracked by the current branch, falling
+    /// back to `origin`, or to the only remote of the repository.
+    fn default_remote_name(&self) -> Option<String>;
     fn branch_name(&self) -> Option<String>;
 
     /// Returns the SHA of the current HEAD.
@@ -41,8 +71,77 @@ pub trait GitRepository: Send + Sync {
     fn branches(&self) -> Result<Vec<Branch>>;
     fn change_branch(&self, _: &str) -> Result<()>;
     fn create_branch(&self, _: &str) -> Result<()>;
+    /// Deletes the local branch, whether or not it was merged.
+    fn delete_branch(&self, name: &str) -> Result<()>;
+    fn rename_branch(&self, old_name: &str, new_name: &str) -> Result<()>;
+    /// Stashes the uncommitted changes, including the untracked files, leaving
+    /// the working tree clean.
+    fn stash_all(&self, message: &str) -> Result<()>;
 
     fn blame(&self, path: &Path, content: Rope) -> Result<crate::blame::Blame>;
+
+    /// Returns the absolute path of the repository's working directory.
+    fn work_directory(&self) -> Option<PathBuf>;
+
+    /// Returns the changes to the files of the repository, staged or not.
+    fn file_statuses(&self) -> Result<Vec<FileStatus>>;
+
+    /// Stages the changes to the files, including their removal.
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()>;
+
+    /// Unstages the changes to the files.
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()>;
+
+    /// Returns the diff of the staged changes, as printed by `git diff --cached`.
+    fn staged_diff(&self) -> Result<String>;
+
+    /// Returns the message of the HEAD commit.
+    fn head_commit_message(&self) -> Option<String>;
+
+    /// Commits the staged changes, or replaces the HEAD commit with them when
+    /// `amend` is true.
+    fn commit(&self, message: &str, amend: bool) -> Result<()>;
+
+    /// Fetches from, pulls from or pushes to the remote of the current branch,
+    /// reporting the progress of the operation line by line.
+    fn remote_operation(
+        &self,
+        operation: RemoteOperation,
+        credentials: Option<&Credentials>,
+        progress: &dyn Fn(&str),
+    ) -> Result<()>;
+
+    /// Returns the commits that changed the file, from the newest to the oldest.
+    fn file_history(&self, path: &RepoPath) -> Result<Vec<FileCommit>>;
+
+    /// Returns the content of the file in the commit, or `None` if the file
+    /// doesn't exist in it.
+    fn file_at_revision(&self, sha: Oid, path: &Path) -> Result<Option<String>>;
+
+    /// Returns the first parent of the commit.
+    fn parent_revision(&self, sha: Oid) -> Option<Oid>;
+
+    /// Returns the blame of a line of the file as of the commit.
+    fn blame_line_at_revision(&self, sha: Oid, path: &Path, row: u32)
+        -> Result<Option<BlameEntry>>;
+}
+
+/// An operation synchronizing the repository with its remote.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemoteOperation {
+    Fetch,
+    Pull,
+    Push,
+}
+
+impl RemoteOperation {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RemoteOperation::Fetch => "Fetch",
+            RemoteOperation::Pull => "Pull",
+            RemoteOperation::Push => "Push",
+        }
+    }
 }
 
 impl std::fmt::Debug for dyn GitRepository {
@@ -69,6 +168,42 @@ impl RealGitRepository {
             hosting_provider_registry,
         }
     }
+
+    fn working_directory(&self) -> Result<PathBuf> {
+        self.repository
+            .lock()
+            .workdir()
+            .context("failed to read git work directory")
+            .map(Path::to_path_buf)
+    }
+
+    fn run_git<I, S>(&self, args: I, options: GitCommandOptions) -> Result<String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        command::run(
+            &self.git_binary_path,
+            &self.working_directory()?,
+            args,
+            options,
+        )
+    }
+
+    /// Returns whether the current branch has an upstream branch to push to.
+    fn has_upstream(&self) -> bool {
+        let repo = self.repository.lock();
+        let Some(branch_name) = repo
+            .head()
+            .ok()
+            .and_then(|head| head.shorthand().map(str::to_string))
+        else {
+            return false;
+        };
+        repo.find_branch(&branch_name, BranchType::Local)
+            .and_then(|branch| branch.upstream())
+            .is_ok()
+    }
 }
 
 // https://git-scm.com/book/en/v2/Git-Internals-Git-Objects
@@ -105,12 +240,95 @@ impl GitRepository for RealGitRepository {
         None
     }
 
+    fn load_head_text(&self, relative_file_path: &Path) -> Option<String> {
+        let content = self.load_head_bytes(relative_file_path)?;
+        match String::from_utf8(content) {
+            Ok(text) => Some(text),
+            Err(err) => {
+                log::error!("Error loading head text: {:?}", err);
+                None
+            }
+        }
+    }
+
+    fn load_head_bytes(&self, relative_file_path: &Path) -> Option<Vec<u8>> {
+        fn logic(repo: &git2::Repository, relative_file_path: &Path) -> Result<Option<Vec<u8>>> {
+            let tree = repo.head()?.peel_to_tree()?;
+            let entry = match tree.get_path(relative_file_path) {
+                Ok(entry) if entry.filemode() != GIT_MODE_SYMLINK as i32 => entry,
+                _ => return Ok(None),
+            };
+
+            Ok(Some(repo.find_blob(entry.id())?.content().to_owned()))
+        }
+
+        match logic(&self.repository.lock(), relative_file_path) {
+            Ok(value) => return value,
+            Err(err) => log::error!("Error loading head contents: {:?}", err),
+        }
+        None
+    }
+
+    fn set_index_text(&self, relative_file_path: &Path, content: String) -> Result<()> {
+        const STAGE_NORMAL: i32 = 0;
+        const GIT_MODE_FILE: u32 = 0o100644;
+
+        check_path_to_repo_path_errors(relative_file_path)?;
+        let repo = self.repository.lock();
+        let mut index = repo.index()?;
+        let entry = match index.get_path(relative_file_path, STAGE_NORMAL) {
+            Some(entry) => entry,
+            None => git2::IndexEntry {
+                ctime: git2::IndexTime::new(0, 0),
+                mtime: git2::IndexTime::new(0, 0),
+                dev: 0,
+                ino: 0,
+                mode: GIT_MODE_FILE,
+                uid: 0,
+                gid: 0,
+                file_size: 0,
+                id: git2::Oid::zero(),
+                flags: 0,
+                flags_extended: 0,
+                path: relative_file_path
+                    .to_str()
+                    .context("repo path is not valid UTF-8")?
+                    .replace('\\', "/")
+                    .into_bytes(),
+            },
+        };
+        index
+            .add_frombuffer(&entry, content.as_bytes())
+            .with_context(|| format!("staging {relative_file_path:?}"))?;
+        index.write().context("writing the index")
+    }
+
     fn remote_url(&self, name: &str) -> Option<String> {
         let repo = self.repository.lock();
         let remote = repo.find_remote(name).ok()?;
         remote.url().map(|url| url.to_string())
     }
 
+    fn default_remote_name(&self) -> Option<String> {
+        let repo = self.repository.lock();
+        let upstream_remote = repo.head().ok().and_then(|head| {
+            let upstream_remote = repo.branch_upstream_remote(head.name()?).ok()?;
+            upstream_remote.as_str().map(ToString::to_string)
+        });
+        if upstream_remote.is_some() {
+            return upstream_remote;
+        }
+
+        let remotes = repo.remotes().ok()?;
+        if remotes.iter().flatten().any(|name| name == "origin") {
+            Some("origin".to_string())
+        } else if remotes.len() == 1 {
+            remotes.get(0).map(ToString::to_string)
+        } else {
+            None
+        }
+    }
+
     fn branch_name(&self) -> Option<String> {
         let repo = self.repository.lock();
         let head = repo.head().log_err()?;
@@ -147,10 +365,17 @@ impl GitRepository for RealGitRepository {
                         time::UtcOffset::from_whole_seconds(timezone_offset * 60).ok()?;
                     let unix_timestamp =
                         time::OffsetDateTime::from_unix_timestamp(unix_timestamp).ok()?;
+                    let upstream = branch.upstream().ok().and_then(|upstream| {
+                        let local = branch.get().target()?;
+                        let upstream = upstream.get().target()?;
+                        let (ahead, behind) = repo.graph_ahead_behind(local, upstream).ok()?;
+                        Some(UpstreamTracking { ahead, behind })
+                    });
                     Some(Branch {
                         is_head,
                         name,
                         unix_timestamp: Some(unix_timestamp.to_offset(utc_offset).unix_timestamp()),
+                        upstream,
                     })
                 })
             })
@@ -179,6 +404,28 @@ impl GitRepository for RealGitRepository {
         Ok(())
     }
 
+    fn delete_branch(&self, name: &str) -> Result<()> {
+        let repo = self.repository.lock();
+        let mut branch = repo.find_branch(name, BranchType::Local)?;
+        branch.delete()?;
+        Ok(())
+    }
+
+    fn rename_branch(&self, old_name: &str, new_name: &str) -> Result<()> {
+        let repo = self.repository.lock();
+        let mut branch = repo.find_branch(old_name, BranchType::Local)?;
+        branch.rename(new_name, false)?;
+        Ok(())
+    }
+
+    fn stash_all(&self, message: &str) -> Result<()> {
+        self.run_git(
+            ["stash", "push", "--include-untracked", "--message", message],
+            GitCommandOptions::default(),
+        )?;
+        Ok(())
+    }
+
     fn blame(&self, path: &Path, content: Rope) -> Result<crate::blame::Blame> {
         let working_directory = self
             .repository
@@ -199,6 +446,128 @@ impl GitRepository for RealGitRepository {
             self.hosting_provider_registry.clone(),
         )
     }
+
+    fn work_directory(&self) -> Option<PathBuf> {
+        self.working_directory().ok()
+    }
+
+    fn file_statuses(&self) -> Result<Vec<FileStatus>> {
+        status::file_statuses(&self.git_binary_path, &self.working_directory()?)
+    }
+
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut args = vec![OsStr::new("add"), OsStr::new("-A"), OsStr::new("--")];
+        args.extend(paths.iter().map(|path| path.as_os_str()));
+        self.run_git(args, GitCommandOptions::default())?;
+        Ok(())
+    }
+
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        // Without a HEAD commit to reset the index to, the files are removed from it.
+        let mut args = if self.head_sha().is_some() {
+            vec![OsStr::new("reset"), OsStr::new("-q"), OsStr::new("--")]
+        } else {
+            vec![
+                OsStr::new("rm"),
+                OsStr::new("--cached"),
+                OsStr::new("-r"),
+                OsStr::new("-q"),
+                OsStr::new("--"),
+            ]
+        };
+        args.extend(paths.iter().map(|path| path.as_os_str()));
+        self.run_git(args, GitCommandOptions::default())?;
+        Ok(())
+    }
+
+    fn staged_diff(&self) -> Result<String> {
+        self.run_git(
+            ["diff", "--cached", "--no-color", "--no-ext-diff"],
+            GitCommandOptions::default(),
+        )
+    }
+
+    fn head_commit_message(&self) -> Option<String> {
+        let repo = self.repository.lock();
+        let commit = repo.head().ok()?.peel_to_commit().ok()?;
+        commit.message().map(str::to_string)
+    }
+
+    fn commit(&self, message: &str, amend: bool) -> Result<()> {
+        let mut args = vec!["commit", "--quiet", "--cleanup=strip", "--file=-"];
+        if amend {
+            args.push("--amend");
+        }
+        self.run_git(
+            args,
+            GitCommandOptions {
+                stdin: Some(message),
+                ..Default::default()
+            },
+        )?;
+        Ok(())
+    }
+
+    fn remote_operation(
+        &self,
+        operation: RemoteOperation,
+        credentials: Option<&Credentials>,
+        progress: &dyn Fn(&str),
+    ) -> Result<()> {
+        let args: &[&str] = match operation {
+            RemoteOperation::Fetch => &["fetch", "--progress"],
+            RemoteOperation::Pull => &["pull", "--progress"],
+            RemoteOperation::Push if self.has_upstream() => &["push", "--progress"],
+            RemoteOperation::Push => &["push", "--progress", "--set-upstream", "origin", "HEAD"],
+        };
+        self.run_git(
+            args,
+            GitCommandOptions {
+                credentials,
+                progress: Some(progress),
+                ..Default::default()
+            },
+        )?;
+        Ok(())
+    }
+
+    fn file_history(&self, path: &RepoPath) -> Result<Vec<FileCommit>> {
+        history::file_history(&self.git_binary_path, &self.working_directory()?, path)
+    }
+
+    fn file_at_revision(&self, sha: Oid, path: &Path) -> Result<Option<String>> {
+        let repo = self.repository.lock();
+        let tree = repo.find_commit(sha.0)?.tree()?;
+        let entry = match tree.get_path(path) {
+            Ok(entry) => entry,
+            Err(error) if error.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let object = entry.to_object(&repo)?;
+        let blob = object.peel_to_blob()?;
+        Ok(Some(String::from_utf8_lossy(blob.content()).into_owned()))
+    }
+
+    fn parent_revision(&self, sha: Oid) -> Option<Oid> {
+        let repo = self.repository.lock();
+        let commit = repo.find_commit(sha.0).ok()?;
+        commit.parent_id(0).ok().map(Oid)
+    }
+
+    fn blame_line_at_revision(
+        &self,
+        sha: Oid,
+        path: &Path,
+        row: u32,
+    ) -> Result<Option<BlameEntry>> {
+        blame::blame_line_at_revision(
+            &self.git_binary_path,
+            &self.working_directory()?,
+            sha,
+            path,
+            row,
+        )
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -209,9 +578,14 @@ pub struct FakeGitRepository {
 #[derive(Debug, Clone, Default)]
 pub struct FakeGitRepositoryState {
     pub index_contents: HashMap<PathBuf, String>,
+    pub head_contents: HashMap<PathBuf, String>,
     pub blames: HashMap<PathBuf, Blame>,
     pub worktree_statuses: HashMap<RepoPath, GitFileStatus>,
     pub branch_name: Option<String>,
+    /// The local branches, including the current one.
+    pub branches: HashSet<String>,
+    pub staged_paths: HashSet<RepoPath>,
+    pub commit_messages: Vec<String>,
 }
 
 impl FakeGitRepository {
@@ -228,10 +602,33 @@ impl GitRepository for FakeGitRepository {
         state.index_contents.get(path).cloned()
     }
 
+    fn load_head_text(&self, path: &Path) -> Option<String> {
+        let state = self.state.lock();
+        state.head_contents.get(path).cloned()
+    }
+
+    fn load_head_bytes(&self, path: &Path) -> Option<Vec<u8>> {
+        let state = self.state.lock();
+        state
+            .head_contents
+            .get(path)
+            .map(|text| text.clone().into_bytes())
+    }
+
+    fn set_index_text(&self, path: &Path, content: String) -> Result<()> {
+        let mut state = self.state.lock();
+        state.index_contents.insert(path.to_path_buf(), content);
+        Ok(())
+    }
+
     fn remote_url(&self, _name: &str) -> Option<String> {
         None
     }
 
+    fn default_remote_name(&self) -> Option<String> {
+        None
+    }
+
     fn branch_name(&self) -> Option<String> {
         let state = self.state.lock();
         state.branch_name.clone()
@@ -264,18 +661,69 @@ impl GitRepository for FakeGitRepository {
     }
 
     fn branches(&self) -> Result<Vec<Branch>> {
-        Ok(vec![])
+        let state = self.state.lock();
+        let current_branch = state.branch_name.as_deref();
+        Ok(state
+            .branches
+            .iter()
+            .map(|name| Branch {
+                is_head: Some(name.as_str()) == current_branch,
+                name: name.as_str().into(),
+                unix_timestamp: None,
+                upstream: None,
+            })
+            .collect())
     }
 
+    /// Fails when there are uncommitted changes, as if they conflicted with the
+    /// branch.
     fn change_branch(&self, name: &str) -> Result<()> {
         let mut state = self.state.lock();
+        if !state.branches.contains(name) {
+            anyhow::bail!("no branch named '{name}'");
+        }
+        if !state.worktree_statuses.is_empty() {
+            anyhow::bail!("uncommitted changes would be overwritten by checking out '{name}'");
+        }
         state.branch_name = Some(name.to_owned());
         Ok(())
     }
 
     fn create_branch(&self, name: &str) -> Result<()> {
         let mut state = self.state.lock();
-        state.branch_name = Some(name.to_owned());
+        if !state.branches.insert(name.to_owned()) {
+            anyhow::bail!("a branch named '{name}' already exists");
+        }
+        Ok(())
+    }
+
+    fn delete_branch(&self, name: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        if state.branch_name.as_deref() == Some(name) {
+            anyhow::bail!("cannot delete the current branch '{name}'");
+        }
+        if !state.branches.remove(name) {
+            anyhow::bail!("no branch named '{name}'");
+        }
+        Ok(())
+    }
+
+    fn rename_branch(&self, old_name: &str, new_name: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        if !state.branches.remove(old_name) {
+            anyhow::bail!("no branch named '{old_name}'");
+        }
+        state.branches.insert(new_name.to_owned());
+        if state.branch_name.as_deref() == Some(old_name) {
+            state.branch_name = Some(new_name.to_owned());
+        }
+        Ok(())
+    }
+
+    fn stash_all(&self, _message: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        state.worktree_statuses.clear();
+        state.staged_paths.clear();
         Ok(())
     }
 
@@ -287,6 +735,99 @@ impl GitRepository for FakeGitRepository {
             .with_context(|| format!("failed to get blame for {:?}", path))
             .cloned()
     }
+
+    fn work_directory(&self) -> Option<PathBuf> {
+        None
+    }
+
+    fn file_statuses(&self) -> Result<Vec<FileStatus>> {
+        let state = self.state.lock();
+        let mut statuses = state
+            .worktree_statuses
+            .iter()
+            .map(|(path, status)| {
+                let change = match status {
+                    GitFileStatus::Added => status::FileChange::Added,
+                    GitFileStatus::Modified => status::FileChange::Modified,
+                    GitFileStatus::Conflict => status::FileChange::Conflicted,
+                };
+                let is_staged = state.staged_paths.contains(path);
+                FileStatus {
+                    path: path.clone(),
+                    staged: is_staged.then_some(change),
+                    unstaged: (!is_staged).then_some(change),
+                }
+            })
+            .collect::<Vec<_>>();
+        statuses.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+        Ok(statuses)
+    }
+
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        state.staged_paths.extend(paths.iter().cloned());
+        Ok(())
+    }
+
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        for path in paths {
+            state.staged_paths.remove(path);
+        }
+        Ok(())
+    }
+
+    fn staged_diff(&self) -> Result<String> {
+        Ok(String::new())
+    }
+
+    fn head_commit_message(&self) -> Option<String> {
+        let state = self.state.lock();
+        state.commit_messages.last().cloned()
+    }
+
+    fn commit(&self, message: &str, amend: bool) -> Result<()> {
+        let mut state = self.state.lock();
+        if amend {
+            state.commit_messages.pop();
+        }
+        state.commit_messages.push(message.to_string());
+        let staged_paths = std::mem::take(&mut state.staged_paths);
+        state
+            .worktree_statuses
+            .retain(|path, _| !staged_paths.contains(path));
+        Ok(())
+    }
+
+    fn remote_operation(
+        &self,
+        _operation: RemoteOperation,
+        _credentials: Option<&Credentials>,
+        _progress: &dyn Fn(&str),
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn file_history(&self, _path: &RepoPath) -> Result<Vec<FileCommit>> {
+        Ok(Vec::new())
+    }
+
+    fn file_at_revision(&self, _sha: Oid, _path: &Path) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn parent_revision(&self, _sha: Oid) -> Option<Oid> {
+        None
+    }
+
+    fn blame_line_at_revision(
+        &self,
+        _sha: Oid,
+        _path: &Path,
+        _row: u32,
+    ) -> Result<Option<BlameEntry>> {
+        Ok(None)
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
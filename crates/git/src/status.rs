@@ -1,4 +1,7 @@
-use crate::repository::{GitFileStatus, RepoPath};
+use crate::{
+    command::{self, GitCommandOptions},
+    repository::{GitFileStatus, RepoPath},
+};
 use anyhow::{anyhow, Result};
 use std::{
     path::{Path, PathBuf},
@@ -99,3 +102,124 @@ impl Default for GitStatus {
         }
     }
 }
+
+/// A kind of change to a file, in the index or in the working tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FileChange {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+    Untracked,
+    Conflicted,
+}
+
+/// The changes to a file that are staged in the index, and the ones that aren't.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileStatus {
+    pub path: RepoPath,
+    pub staged: Option<FileChange>,
+    pub unstaged: Option<FileChange>,
+}
+
+pub(crate) fn file_statuses(
+    git_binary: &Path,
+    working_directory: &Path,
+) -> Result<Vec<FileStatus>> {
+    let output = command::run(
+        git_binary,
+        working_directory,
+        [
+            "--no-optional-locks",
+            "status",
+            "--porcelain=v1",
+            "--untracked-files=all",
+            "-z",
+        ],
+        GitCommandOptions::default(),
+    )?;
+    Ok(parse_file_statuses(&output))
+}
+
+/// Parses the output of `git status --porcelain=v1 -z`, whose entries have the
+/// status of the file in the index and in the working tree, followed by its
+/// path and, for renames, by its original path.
+fn parse_file_statuses(output: &str) -> Vec<FileStatus> {
+    fn change(code: char) -> Option<FileChange> {
+        match code {
+            'M' | 'T' => Some(FileChange::Modified),
+            'A' => Some(FileChange::Added),
+            'D' => Some(FileChange::Deleted),
+            'R' | 'C' => Some(FileChange::Renamed),
+            _ => None,
+        }
+    }
+
+    let mut statuses = Vec::new();
+    let mut entries = output.split('\0');
+    while let Some(entry) = entries.next() {
+        if entry.len() < 4 || !entry.is_char_boundary(3) {
+            continue;
+        }
+        let (code, path) = entry.split_at(3);
+        let mut code = code.chars();
+        let (Some(index), Some(worktree)) = (code.next(), code.next()) else {
+            continue;
+        };
+        if index == 'R' || index == 'C' {
+            entries.next();
+        }
+
+        let is_conflicted = index == 'U'
+            || worktree == 'U'
+            || (index == 'A' && worktree == 'A')
+            || (index == 'D' && worktree == 'D');
+        let (staged, unstaged) = if is_conflicted {
+            (None, Some(FileChange::Conflicted))
+        } else if index == '?' {
+            (None, Some(FileChange::Untracked))
+        } else if index == '!' {
+            continue;
+        } else {
+            (change(index), change(worktree))
+        };
+        statuses.push(FileStatus {
+            path: RepoPath(PathBuf::from(path)),
+            staged,
+            unstaged,
+        });
+    }
+    statuses.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+    statuses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_statuses() {
+        let output = "M  staged.rs\0 M unstaged.rs\0MM both.rs\0R  new.rs\0old.rs\0?? untracked.rs\0UU conflict.rs\0 D deleted.rs\0";
+        let status = |path: &str, staged, unstaged| FileStatus {
+            path: RepoPath(PathBuf::from(path)),
+            staged,
+            unstaged,
+        };
+        assert_eq!(
+            parse_file_statuses(output),
+            vec![
+                status(
+                    "both.rs",
+                    Some(FileChange::Modified),
+                    Some(FileChange::Modified)
+                ),
+                status("conflict.rs", None, Some(FileChange::Conflicted)),
+                status("deleted.rs", None, Some(FileChange::Deleted)),
+                status("new.rs", Some(FileChange::Renamed), None),
+                status("staged.rs", Some(FileChange::Modified), None),
+                status("unstaged.rs", None, Some(FileChange::Modified)),
+                status("untracked.rs", None, Some(FileChange::Untracked)),
+            ]
+        );
+    }
+}
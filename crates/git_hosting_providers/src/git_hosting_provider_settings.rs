@@ -0,0 +1,66 @@
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+/// The software run by a Git hosting provider, which determines the layout of
+/// its URLs.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GitHostingProviderKind {
+    #[default]
+    Github,
+    Gitlab,
+    Bitbucket,
+    Gitea,
+}
+
+/// A self-hosted Git hosting provider.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct GitHostingProviderConfig {
+    /// The name of the provider.
+    pub name: String,
+    /// The base URL of the provider, such as `https://git.example.com`. The
+    /// remotes whose URL has the same host are hosted by the provider.
+    pub base_url: String,
+    /// The software run by the provider.
+    ///
+    /// Default: github
+    #[serde(default)]
+    pub provider: GitHostingProviderKind,
+    /// A template overriding the URL of a file at a commit, with the
+    /// `{base_url}`, `{owner}`, `{repo}`, `{sha}`, and `{path}` placeholders.
+    pub permalink: Option<String>,
+    /// A template overriding the URL of a commit, with the `{base_url}`,
+    /// `{owner}`, `{repo}`, and `{sha}` placeholders.
+    pub commit_permalink: Option<String>,
+    /// A template overriding the URL fragment of a line, with the `{line}`
+    /// placeholder.
+    pub line: Option<String>,
+    /// A template overriding the URL fragment of a range of lines, with the
+    /// `{start}` and `{end}` placeholders.
+    pub lines: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct GitHostingProviderSettings {
+    /// The self-hosted Git hosting providers, which permalinks to the files of
+    /// their repositories are built for.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub git_hosting_providers: Vec<GitHostingProviderConfig>,
+}
+
+impl Settings for GitHostingProviderSettings {
+    const KEY: Option<&'static str> = None;
+
+    type FileContent = Self;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
@@ -1,15 +1,38 @@
+mod git_hosting_provider_settings;
 mod providers;
 
 use std::sync::Arc;
 
 use git::GitHostingProviderRegistry;
 use gpui::AppContext;
+use settings::{Settings, SettingsStore};
 
+pub use crate::git_hosting_provider_settings::*;
 pub use crate::providers::*;
 
 /// Initializes the Git hosting providers.
 pub fn init(cx: &mut AppContext) {
+    GitHostingProviderSettings::register(cx);
+
     let provider_registry = GitHostingProviderRegistry::global(cx);
+    let mut self_hosted_names = Vec::new();
+    register_hosting_providers(&provider_registry, &mut self_hosted_names, cx);
+    cx.observe_global::<SettingsStore>(move |cx| {
+        register_hosting_providers(&provider_registry, &mut self_hosted_names, cx);
+    })
+    .detach();
+}
+
+/// Registers the built-in providers and the self-hosted providers of the
+/// settings, replacing the self-hosted providers registered previously.
+fn register_hosting_providers(
+    provider_registry: &GitHostingProviderRegistry,
+    self_hosted_names: &mut Vec<String>,
+    cx: &AppContext,
+) {
+    for name in self_hosted_names.drain(..) {
+        provider_registry.unregister_hosting_provider(&name);
+    }
 
     // The providers are stored in a `BTreeMap`, so insertion order matters.
     // GitHub comes first.
@@ -23,4 +46,16 @@ pub fn init(cx: &mut AppContext) {
     provider_registry.register_hosting_provider(Arc::new(Bitbucket));
     provider_registry.register_hosting_provider(Arc::new(Sourcehut));
     provider_registry.register_hosting_provider(Arc::new(Codeberg));
+
+    // Then the self-hosted providers, which replace the built-in ones of the
+    // same name.
+    for config in &GitHostingProviderSettings::get_global(cx).git_hosting_providers {
+        match SelfHosted::new(config) {
+            Ok(provider) => {
+                self_hosted_names.push(config.name.clone());
+                provider_registry.register_hosting_provider(Arc::new(provider));
+            }
+            Err(error) => log::error!("invalid Git hosting provider {:?}: {error:#}", config.name),
+        }
+    }
 }
@@ -0,0 +1,270 @@
+use anyhow::{Context as _, Result};
+use url::Url;
+
+use git::{BuildCommitPermalinkParams, BuildPermalinkParams, GitHostingProvider, ParsedGitRemote};
+
+use crate::{GitHostingProviderConfig, GitHostingProviderKind};
+
+/// A self-hosted Git hosting provider, whose URLs follow the layout of the
+/// software it runs unless overridden by templates.
+pub struct SelfHosted {
+    name: String,
+    base_url: Url,
+    permalink: String,
+    commit_permalink: String,
+    line: String,
+    lines: String,
+}
+
+impl SelfHosted {
+    pub fn new(config: &GitHostingProviderConfig) -> Result<Self> {
+        let base_url = Url::parse(&config.base_url)
+            .with_context(|| format!("invalid base URL {:?}", config.base_url))?;
+        base_url
+            .host_str()
+            .with_context(|| format!("base URL {:?} has no host", config.base_url))?;
+
+        let (permalink, commit_permalink, line, lines) = match config.provider {
+            GitHostingProviderKind::Github => (
+                "{base_url}/{owner}/{repo}/blob/{sha}/{path}",
+                "{base_url}/{owner}/{repo}/commit/{sha}",
+                "L{line}",
+                "L{start}-L{end}",
+            ),
+            GitHostingProviderKind::Gitlab => (
+                "{base_url}/{owner}/{repo}/-/blob/{sha}/{path}",
+                "{base_url}/{owner}/{repo}/-/commit/{sha}",
+                "L{line}",
+                "L{start}-{end}",
+            ),
+            GitHostingProviderKind::Bitbucket => (
+                "{base_url}/{owner}/{repo}/src/{sha}/{path}",
+                "{base_url}/{owner}/{repo}/commits/{sha}",
+                "lines-{line}",
+                "lines-{start}:{end}",
+            ),
+            GitHostingProviderKind::Gitea => (
+                "{base_url}/{owner}/{repo}/src/commit/{sha}/{path}",
+                "{base_url}/{owner}/{repo}/commit/{sha}",
+                "L{line}",
+                "L{start}-L{end}",
+            ),
+        };
+        let template = |custom: &Option<String>, default: &str| {
+            custom.clone().unwrap_or_else(|| default.to_string())
+        };
+
+        Ok(Self {
+            name: config.name.clone(),
+            base_url,
+            permalink: template(&config.permalink, permalink),
+            commit_permalink: template(&config.commit_permalink, commit_permalink),
+            line: template(&config.line, line),
+            lines: template(&config.lines, lines),
+        })
+    }
+
+    /// Returns the URL of the template, falling back to the base URL when the
+    /// template doesn't expand to a valid URL.
+    fn expand_url(&self, template: &str, remote: &ParsedGitRemote, sha: &str, path: &str) -> Url {
+        let base_url = self.base_url.as_str().trim_end_matches('/');
+        let url = template
+            .replace("{base_url}", base_url)
+            .replace("{owner}", remote.owner)
+            .replace("{repo}", remote.repo)
+            .replace("{sha}", sha)
+            .replace("{path}", path);
+        Url::parse(&url).unwrap_or_else(|_| self.base_url.clone())
+    }
+}
+
+/// Returns the path of the repository in the URL of a remote, if the remote is
+/// on the host.
+fn remote_path<'a>(url: &'a str, host: &str) -> Option<&'a str> {
+    let (authority, path) = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?,
+        // The scp-like syntax of SSH remotes, such as `git@host:owner/repo.git`.
+        None => url.split_once(':')?,
+    };
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, authority)| authority);
+    let authority_host = authority.split(':').next()?;
+    authority_host.eq_ignore_ascii_case(host).then_some(path)
+}
+
+impl GitHostingProvider for SelfHosted {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn base_url(&self) -> Url {
+        self.base_url.clone()
+    }
+
+    fn supports_avatars(&self) -> bool {
+        false
+    }
+
+    fn format_line_number(&self, line: u32) -> String {
+        self.line.replace("{line}", &line.to_string())
+    }
+
+    fn format_line_numbers(&self, start_line: u32, end_line: u32) -> String {
+        self.lines
+            .replace("{start}", &start_line.to_string())
+            .replace("{end}", &end_line.to_string())
+    }
+
+    fn parse_remote_url<'a>(&self, url: &'a str) -> Option<ParsedGitRemote<'a>> {
+        let host = self.base_url.host_str()?;
+        let mut path = remote_path(url, host)?.trim_matches('/');
+        // The base URL may include a path when the provider isn't served at
+        // the root of the host.
+        let base_path = self.base_url.path().trim_matches('/');
+        if !base_path.is_empty() {
+            if let Some(rest) = path.strip_prefix(base_path) {
+                path = rest.trim_start_matches('/');
+            }
+        }
+        let path = path.trim_end_matches(".git");
+
+        // The owner is a group path for providers with nested groups, such as
+        // GitLab.
+        let (owner, repo) = path.rsplit_once('/')?;
+        if owner.is_empty() || repo.is_empty() {
+            return None;
+        }
+        Some(ParsedGitRemote { owner, repo })
+    }
+
+    fn build_commit_permalink(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCommitPermalinkParams,
+    ) -> Url {
+        let BuildCommitPermalinkParams { sha } = params;
+        self.expand_url(&self.commit_permalink, remote, sha, "")
+    }
+
+    fn build_permalink(&self, remote: ParsedGitRemote, params: BuildPermalinkParams) -> Url {
+        let BuildPermalinkParams {
+            sha,
+            path,
+            selection,
+        } = params;
+
+        let mut permalink = self.expand_url(&self.permalink, &remote, sha, path);
+        permalink.set_fragment(
+            selection
+                .map(|selection| self.line_fragment(&selection))
+                .as_deref(),
+        );
+        permalink
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(provider: GitHostingProviderKind, base_url: &str) -> GitHostingProviderConfig {
+        GitHostingProviderConfig {
+            name: "Example".to_string(),
+            base_url: base_url.to_string(),
+            provider,
+            permalink: None,
+            commit_permalink: None,
+            line: None,
+            lines: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_self_hosted_remote_urls() {
+        let provider = SelfHosted::new(&config(
+            GitHostingProviderKind::Gitlab,
+            "https://git.example.com",
+        ))
+        .unwrap();
+        for url in [
+            "git@git.example.com:group/subgroup/project.git",
+            "ssh://git@git.example.com:2222/group/subgroup/project.git",
+            "https://git.example.com/group/subgroup/project.git",
+            "https://user@git.example.com/group/subgroup/project",
+        ] {
+            let remote = provider.parse_remote_url(url).unwrap();
+            assert_eq!(remote.owner, "group/subgroup", "{url}");
+            assert_eq!(remote.repo, "project", "{url}");
+        }
+        assert!(provider
+            .parse_remote_url("git@gitlab.com:group/project.git")
+            .is_none());
+        assert!(provider
+            .parse_remote_url("https://git.example.com/project.git")
+            .is_none());
+
+        let provider = SelfHosted::new(&config(
+            GitHostingProviderKind::Gitea,
+            "https://example.com/gitea/",
+        ))
+        .unwrap();
+        let remote = provider
+            .parse_remote_url("https://example.com/gitea/owner/repo.git")
+            .unwrap();
+        assert_eq!((remote.owner, remote.repo), ("owner", "repo"));
+    }
+
+    #[test]
+    fn test_build_self_hosted_permalinks() {
+        let remote = ParsedGitRemote {
+            owner: "group/subgroup",
+            repo: "project",
+        };
+        let params = || BuildPermalinkParams {
+            sha: "e6ebe7974deb6bb6cc0e2595c8ec31f0c71084b7",
+            path: "src/main.rs",
+            selection: Some(6..9),
+        };
+
+        let provider = SelfHosted::new(&config(
+            GitHostingProviderKind::Gitlab,
+            "https://git.example.com",
+        ))
+        .unwrap();
+        assert_eq!(
+            provider
+                .build_permalink(
+                    ParsedGitRemote {
+                        owner: remote.owner,
+                        repo: remote.repo
+                    },
+                    params()
+                )
+                .to_string(),
+            "https://git.example.com/group/subgroup/project/-/blob/e6ebe7974deb6bb6cc0e2595c8ec31f0c71084b7/src/main.rs#L7-10"
+        );
+        assert_eq!(
+            provider
+                .build_commit_permalink(
+                    &remote,
+                    BuildCommitPermalinkParams {
+                        sha: "e6ebe7974deb6bb6cc0e2595c8ec31f0c71084b7"
+                    }
+                )
+                .to_string(),
+            "https://git.example.com/group/subgroup/project/-/commit/e6ebe7974deb6bb6cc0e2595c8ec31f0c71084b7"
+        );
+
+        let provider = SelfHosted::new(&GitHostingProviderConfig {
+            permalink: Some("{base_url}/browse/{owner}/{repo}/{path}?at={sha}".to_string()),
+            lines: Some("{start}-{end}".to_string()),
+            ..config(GitHostingProviderKind::Github, "https://code.example.com")
+        })
+        .unwrap();
+        assert_eq!(
+            provider.build_permalink(remote, params()).to_string(),
+            "https://code.example.com/browse/group/subgroup/project/src/main.rs?at=e6ebe7974deb6bb6cc0e2595c8ec31f0c71084b7#7-10"
+        );
+    }
+}
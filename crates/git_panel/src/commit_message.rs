@@ -0,0 +1,111 @@
+use crate::CommitMessageStyle;
+
+/// The length in bytes past which the diff sent to the model is cut off, to keep
+/// requests within the context windows of smaller models.
+const MAX_DIFF_LEN: usize = 32 * 1024;
+
+/// Builds the prompt asking a model for the message of a commit of the diff.
+pub(crate) fn commit_message_prompt(
+    diff: &str,
+    style: CommitMessageStyle,
+    instructions: Option<&str>,
+) -> String {
+    let mut prompt = String::from(
+        "Write the message of a git commit of the following staged changes. \
+         Respond with the commit message only, without code fences or commentary.\n\n",
+    );
+    prompt.push_str(match style {
+        CommitMessageStyle::Conventional => {
+            "Follow the Conventional Commits format: a summary line of at most 72 characters \
+             in the form `type(scope): description`, where the type is one of feat, fix, docs, \
+             style, refactor, perf, test, build, ci or chore, and the scope is optional. \
+             Write the description in the imperative mood, in lowercase, without a trailing \
+             period."
+        }
+        CommitMessageStyle::Plain => {
+            "Start with a summary line of at most 72 characters in the imperative mood, \
+             without a trailing period."
+        }
+    });
+    prompt.push_str(
+        " If the changes need explaining, follow the summary with a blank line and a body \
+         wrapped at 72 characters saying what changed and why.\n",
+    );
+    if let Some(instructions) = instructions.filter(|instructions| !instructions.trim().is_empty())
+    {
+        prompt.push('\n');
+        prompt.push_str(instructions.trim());
+        prompt.push('\n');
+    }
+
+    prompt.push_str("\n```diff\n");
+    if diff.len() > MAX_DIFF_LEN {
+        // Cut the diff at the end of a line, so that no line is left half done.
+        let mut end = MAX_DIFF_LEN;
+        while !diff.is_char_boundary(end) {
+            end -= 1;
+        }
+        let end = diff[..end].rfind('\n').map_or(end, |ix| ix + 1);
+        prompt.push_str(&diff[..end]);
+        prompt.push_str("[The rest of the diff was left out because it is too long.]\n");
+    } else {
+        prompt.push_str(diff);
+        if !diff.ends_with('\n') {
+            prompt.push('\n');
+        }
+    }
+    prompt.push_str("```\n");
+    prompt
+}
+
+/// Removes the whitespace and code fences that models sometimes wrap messages in.
+pub(crate) fn clean_commit_message(response: &str) -> String {
+    let mut message = response.trim();
+    if let Some(rest) = message.strip_prefix("```") {
+        // Skip the language of the fence, such as `text`.
+        message = rest.split_once('\n').map_or("", |(_, rest)| rest);
+        message = message.trim_end().strip_suffix("```").unwrap_or(message);
+    }
+    message.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_message_prompt() {
+        let diff = "diff --git a/a.rs b/a.rs\n+fn a() {}\n";
+        let prompt = commit_message_prompt(diff, CommitMessageStyle::Conventional, None);
+        assert!(prompt.contains("Conventional Commits"));
+        assert!(prompt.ends_with(&format!("```diff\n{diff}```\n")));
+
+        let prompt = commit_message_prompt(
+            diff,
+            CommitMessageStyle::Plain,
+            Some("Mention the issue number."),
+        );
+        assert!(!prompt.contains("Conventional Commits"));
+        assert!(prompt.contains("\nMention the issue number.\n"));
+
+        // Long diffs are cut off at the end of a line.
+        let line = "+let a = \"ü\";\n";
+        let diff = line.repeat(MAX_DIFF_LEN / line.len() + 10);
+        let prompt = commit_message_prompt(&diff, CommitMessageStyle::Plain, None);
+        assert!(prompt.len() < MAX_DIFF_LEN + 1024);
+        assert!(prompt.contains(&format!("{line}[The rest of the diff was left out")));
+    }
+
+    #[test]
+    fn test_clean_commit_message() {
+        assert_eq!(
+            clean_commit_message("\n  feat: add a\n\nBody.\n"),
+            "feat: add a\n\nBody."
+        );
+        assert_eq!(
+            clean_commit_message("```text\nfix(git): b\n```"),
+            "fix(git): b"
+        );
+        assert_eq!(clean_commit_message("```\nfix: c\n```\n"), "fix: c");
+    }
+}
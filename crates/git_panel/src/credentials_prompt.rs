@@ -0,0 +1,107 @@
+use editor::Editor;
+use futures::channel::oneshot;
+use git::command::Credentials;
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render, SharedString, View,
+    ViewContext,
+};
+use ui::prelude::*;
+use workspace::ModalView;
+
+/// A modal asking for the credentials of a remote that requires them. The
+/// credentials are sent once confirmed, and the sender is dropped if the modal
+/// is dismissed instead.
+pub struct CredentialsPrompt {
+    operation: SharedString,
+    username_editor: View<Editor>,
+    password_editor: View<Editor>,
+    tx: Option<oneshot::Sender<Credentials>>,
+}
+
+impl CredentialsPrompt {
+    pub fn new(
+        operation: impl Into<SharedString>,
+        tx: oneshot::Sender<Credentials>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let username_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Username", cx);
+            editor
+        });
+        let password_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Password or access token", cx);
+            editor.set_masked(true, cx);
+            editor
+        });
+        Self {
+            operation: operation.into(),
+            username_editor,
+            password_editor,
+            tx: Some(tx),
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        if self.username_editor.focus_handle(cx).is_focused(cx) {
+            self.password_editor.focus_handle(cx).focus(cx);
+            return;
+        }
+
+        let username = self.username_editor.read(cx).text(cx);
+        let password = self.password_editor.read(cx).text(cx);
+        if let Some(tx) = self.tx.take() {
+            tx.send(Credentials { username, password }).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+}
+
+impl ModalView for CredentialsPrompt {}
+
+impl FocusableView for CredentialsPrompt {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.username_editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for CredentialsPrompt {}
+
+impl Render for CredentialsPrompt {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let field = |editor: &View<Editor>| {
+            div()
+                .px_2()
+                .py_1()
+                .rounded_md()
+                .border_1()
+                .border_color(cx.theme().colors().border)
+                .child(editor.clone())
+        };
+
+        v_flex()
+            .elevation_2(cx)
+            .key_context("GitCredentialsPrompt")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .w_96()
+            .p_2()
+            .gap_2()
+            .child(Label::new(format!(
+                "{} requires authentication",
+                self.operation
+            )))
+            .child(field(&self.username_editor))
+            .child(field(&self.password_editor))
+            .child(
+                Label::new("The credentials are only used for this operation.")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+    }
+}
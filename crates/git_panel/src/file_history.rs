@@ -0,0 +1,335 @@
+use crate::revision::{register_revision_buffer, Revision};
+use anyhow::anyhow;
+use chrono::Local;
+use editor::{actions::ExpandAllHunkDiffs, Editor, MultiBuffer};
+use git::{history::FileCommit, repository::GitRepository, repository::RepoPath};
+use gpui::{
+    uniform_list, AnyElement, AppContext, EventEmitter, FocusHandle, FocusableView, Model, Task,
+    UniformListScrollHandle, View,
+};
+use language::{Buffer, Language};
+use project::Project;
+use std::sync::Arc;
+use time::{OffsetDateTime, UtcOffset};
+use time_format::TimestampFormat;
+use ui::{prelude::*, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{item::Item, Workspace};
+
+/// The commits that changed a file, with the changes of the selected commit
+/// previewed next to them.
+pub struct FileHistoryView {
+    project: Model<Project>,
+    repo: Arc<dyn GitRepository>,
+    path: RepoPath,
+    language: Option<Arc<Language>>,
+    /// The commits that changed the file, from the newest to the oldest.
+    commits: Vec<FileCommit>,
+    is_loading: bool,
+    selected_index: Option<usize>,
+    preview: Option<View<Editor>>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    timezone: UtcOffset,
+    _load_task: Task<()>,
+    _preview_task: Option<Task<()>>,
+}
+
+impl FileHistoryView {
+    /// Opens the history of the file of the active editor.
+    pub(crate) fn open(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+        let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+            return;
+        };
+        let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+            return;
+        };
+        let project = workspace.project().clone();
+        let repository = project.read(cx).local_repository_for_buffer(&buffer, cx);
+        let (repo, path) = match repository {
+            Ok(repository) => repository,
+            Err(error) => {
+                workspace.show_error(&error, cx);
+                return;
+            }
+        };
+        let language = buffer.read(cx).language().cloned();
+        let view = cx.new_view(|cx| Self::new(project, repo, path, language, cx));
+        workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+    }
+
+    fn new(
+        project: Model<Project>,
+        repo: Arc<dyn GitRepository>,
+        path: RepoPath,
+        language: Option<Arc<Language>>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let local = Local::now().offset().local_minus_utc();
+        let timezone = UtcOffset::from_whole_seconds(local).unwrap_or(UtcOffset::UTC);
+        let load_task = cx.spawn({
+            let repo = repo.clone();
+            let path = path.clone();
+            |this, mut cx| async move {
+                let commits = cx
+                    .background_executor()
+                    .spawn(async move { repo.file_history(&path) })
+                    .await;
+                this.update(&mut cx, |this, cx| {
+                    this.is_loading = false;
+                    this.commits = commits.log_err().unwrap_or_default();
+                    if !this.commits.is_empty() {
+                        this.select(0, cx);
+                    }
+                    cx.notify();
+                })
+                .ok();
+            }
+        });
+
+        Self {
+            project,
+            repo,
+            path,
+            language,
+            commits: Vec::new(),
+            is_loading: true,
+            selected_index: None,
+            preview: None,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            timezone,
+            _load_task: load_task,
+            _preview_task: None,
+        }
+    }
+
+    fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, cx: &mut ViewContext<Self>) {
+        let next = self.selected_index.map_or(0, |ix| ix + 1);
+        if next < self.commits.len() {
+            self.select(next, cx);
+        }
+    }
+
+    fn select_prev(&mut self, _: &menu::SelectPrev, cx: &mut ViewContext<Self>) {
+        if let Some(ix) = self.selected_index.filter(|ix| *ix > 0) {
+            self.select(ix - 1, cx);
+        }
+    }
+
+    /// Selects the commit and previews the file as of the commit, with the
+    /// changes it made expanded.
+    fn select(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        if self.selected_index == Some(ix) {
+            return;
+        }
+        self.selected_index = Some(ix);
+        self.scroll_handle.scroll_to_item(ix);
+        cx.notify();
+
+        let commit = self.commits[ix].clone();
+        // The file had the path of the previous commit that changed it in the
+        // commit's parent, unless it was renamed by the commit.
+        let parent_path = self
+            .commits
+            .get(ix + 1)
+            .map_or_else(|| commit.path.clone(), |previous| previous.path.clone());
+        let repo = self.repo.clone();
+        self._preview_task = Some(cx.spawn(|this, mut cx| async move {
+            let texts = cx
+                .background_executor()
+                .spawn({
+                    let repo = repo.clone();
+                    let commit = commit.clone();
+                    async move {
+                        let text = repo
+                            .file_at_revision(commit.sha, &commit.path)?
+                            .ok_or_else(|| {
+                                anyhow!("{:?} not found in {}", commit.path, commit.sha)
+                            })?;
+                        let parent_text = match repo.parent_revision(commit.sha) {
+                            Some(parent_sha) => repo.file_at_revision(parent_sha, &parent_path)?,
+                            None => None,
+                        };
+                        anyhow::Ok((text, parent_text))
+                    }
+                })
+                .await;
+            let Some((text, parent_text)) = texts.log_err() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                let revision = Revision {
+                    repo,
+                    sha: commit.sha,
+                    path: commit.path.to_path_buf(),
+                };
+                this.show_preview(revision, text, parent_text, cx)
+            })
+            .ok();
+        }));
+    }
+
+    fn show_preview(
+        &mut self,
+        revision: Revision,
+        text: String,
+        parent_text: Option<String>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let title = format!("{} @ {}", self.file_name(), revision.sha.display_short());
+        let language = self.language.clone();
+        let languages = self.project.read(cx).languages().clone();
+        let buffer = cx.new_model(|cx| {
+            let mut buffer = Buffer::local(text, cx);
+            buffer.set_language_registry(languages);
+            buffer.set_language(language, cx);
+            // A file added by the commit is entirely new.
+            buffer.set_diff_base(Some(parent_text.unwrap_or_default()), cx);
+            buffer
+        });
+        register_revision_buffer(&buffer, revision, cx);
+
+        let multibuffer =
+            cx.new_model(|cx| MultiBuffer::singleton(buffer.clone(), cx).with_title(title));
+        let project = self.project.clone();
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, Some(project), true, cx);
+            editor.set_read_only(true);
+            editor
+        });
+        // The hunks are known once the diff with the parent is computed.
+        cx.subscribe(&buffer, {
+            let editor = editor.downgrade();
+            move |_, _, event, cx| {
+                if let language::Event::DiffBaseChanged = event {
+                    editor
+                        .update(cx, |editor, cx| {
+                            editor.expand_all_hunk_diffs(&ExpandAllHunkDiffs, cx)
+                        })
+                        .ok();
+                }
+            }
+        })
+        .detach();
+        self.preview = Some(editor);
+        cx.notify();
+    }
+
+    fn render_commit(&self, ix: usize, commit: &FileCommit, cx: &ViewContext<Self>) -> AnyElement {
+        let time = OffsetDateTime::from_unix_timestamp(commit.time)
+            .map(|time| {
+                time_format::format_localized_timestamp(
+                    time,
+                    OffsetDateTime::now_utc(),
+                    self.timezone,
+                    TimestampFormat::Relative,
+                )
+            })
+            .unwrap_or_default();
+        ListItem::new(ix)
+            .spacing(ListItemSpacing::Sparse)
+            .selected(self.selected_index == Some(ix))
+            .child(
+                v_flex()
+                    .child(Label::new(commit.summary.clone()).single_line())
+                    .child(
+                        Label::new(format!(
+                            "{} · {} · {time}",
+                            commit.sha.display_short(),
+                            commit.author
+                        ))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .single_line(),
+                    ),
+            )
+            .on_click(cx.listener(move |this, _, cx| this.select(ix, cx)))
+            .into_any_element()
+    }
+}
+
+impl EventEmitter<()> for FileHistoryView {}
+
+impl FocusableView for FileHistoryView {
+    fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for FileHistoryView {
+    type Event = ();
+
+    fn tab_icon(&self, _cx: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::FileGit))
+    }
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some(format!("History: {}", self.file_name()).into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("git file history")
+    }
+
+    fn to_item_events(_event: &Self::Event, _f: impl FnMut(workspace::item::ItemEvent)) {}
+}
+
+impl Render for FileHistoryView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let commits = if self.commits.is_empty() {
+            let message = if self.is_loading {
+                "Loading history..."
+            } else {
+                "No commits changed this file"
+            };
+            v_flex()
+                .size_full()
+                .justify_center()
+                .child(
+                    h_flex()
+                        .justify_center()
+                        .child(Label::new(message).color(Color::Muted)),
+                )
+                .into_any_element()
+        } else {
+            uniform_list(
+                cx.view().clone(),
+                "file-history-commits",
+                self.commits.len(),
+                |this, range, cx| {
+                    range
+                        .map(|ix| this.render_commit(ix, &this.commits[ix], cx))
+                        .collect()
+                },
+            )
+            .size_full()
+            .track_scroll(self.scroll_handle.clone())
+            .into_any_element()
+        };
+
+        h_flex()
+            .key_context("FileHistory")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                div()
+                    .w(rems(24.))
+                    .h_full()
+                    .border_r_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(commits),
+            )
+            .child(div().flex_1().h_full().children(self.preview.clone()))
+    }
+}
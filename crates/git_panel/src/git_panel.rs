@@ -0,0 +1,961 @@
+//! A source control panel showing the changed files of the project's
+//! repositories, from which changes are staged, committed, and synchronized
+//! with remotes.
+mod commit_message;
+mod credentials_prompt;
+mod file_history;
+mod git_panel_settings;
+mod revision;
+mod status_tree;
+
+use std::{path::Path, sync::Arc};
+
+use commit_message::{clean_commit_message, commit_message_prompt};
+use credentials_prompt::CredentialsPrompt;
+use editor::{Editor, MultiBuffer};
+use file_history::FileHistoryView;
+use futures::{
+    channel::{mpsc, oneshot},
+    StreamExt as _,
+};
+use git::{
+    command::{AuthenticationRequired, Credentials},
+    repository::{GitRepository, RemoteOperation, RepoPath},
+    status::FileChange,
+};
+use gpui::{
+    actions, uniform_list, Action, AnyElement, AppContext, AsyncWindowContext, EventEmitter,
+    FocusHandle, FocusableView, Model, Subscription, Task, UniformListScrollHandle, View, WeakView,
+};
+use language::Buffer;
+use language_model::{
+    LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage, Role,
+};
+use project::{Fs, Project, WorktreeId};
+use settings::Settings;
+use status_tree::{StagingState, StatusEntry, StatusEntryKind, StatusTree};
+use ui::{prelude::*, Checkbox, ContextMenu, IconButtonShape, ListItem, PopoverMenu, Tooltip};
+use util::ResultExt;
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    Workspace,
+};
+
+pub use git_panel_settings::{
+    CommitMessageStyle, GitPanelDockPosition, GitPanelSettings, GitPanelSettingsContent,
+};
+
+actions!(
+    git_panel,
+    [
+        ToggleFocus,
+        Commit,
+        GenerateCommitMessage,
+        PreviewCommit,
+        StageAll,
+        UnstageAll,
+        Fetch,
+        Pull,
+        Push
+    ]
+);
+
+actions!(git, [FileHistory, ShowPreviousRevisionOfLine]);
+
+pub fn init(cx: &mut AppContext) {
+    GitPanelSettings::register(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, cx| {
+            workspace.toggle_panel_focus::<GitPanel>(cx);
+        });
+        workspace.register_action(|workspace, _: &Commit, cx| {
+            if let Some(panel) = workspace.panel::<GitPanel>(cx) {
+                panel.update(cx, |panel, cx| panel.commit(cx));
+            }
+        });
+        workspace.register_action(|workspace, _: &GenerateCommitMessage, cx| {
+            if let Some(panel) = workspace.panel::<GitPanel>(cx) {
+                panel.update(cx, |panel, cx| panel.generate_commit_message(cx));
+            }
+        });
+        workspace.register_action(|workspace, _: &Fetch, cx| {
+            if let Some(panel) = workspace.panel::<GitPanel>(cx) {
+                panel.update(cx, |panel, cx| {
+                    panel.run_remote_operation(RemoteOperation::Fetch, None, cx)
+                });
+            }
+        });
+        workspace.register_action(|workspace, _: &Pull, cx| {
+            if let Some(panel) = workspace.panel::<GitPanel>(cx) {
+                panel.update(cx, |panel, cx| {
+                    panel.run_remote_operation(RemoteOperation::Pull, None, cx)
+                });
+            }
+        });
+        workspace.register_action(|workspace, _: &Push, cx| {
+            if let Some(panel) = workspace.panel::<GitPanel>(cx) {
+                panel.update(cx, |panel, cx| {
+                    panel.run_remote_operation(RemoteOperation::Push, None, cx)
+                });
+            }
+        });
+        workspace
+            .register_action(|workspace, _: &FileHistory, cx| FileHistoryView::open(workspace, cx));
+        workspace.register_action(|workspace, _: &ShowPreviousRevisionOfLine, cx| {
+            revision::show_previous_revision_of_line(workspace, cx)
+        });
+    })
+    .detach();
+}
+
+/// A repository of one of the project's local worktrees.
+struct Repository {
+    worktree_id: WorktreeId,
+    /// The path of the repository's work directory in the worktree.
+    work_directory: Arc<Path>,
+    name: SharedString,
+    branch: Option<Arc<str>>,
+    repo: Arc<dyn GitRepository>,
+}
+
+pub struct GitPanel {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    width: Option<Pixels>,
+    repositories: Vec<Repository>,
+    active_repository: usize,
+    tree: StatusTree,
+    entries: Vec<StatusEntry>,
+    commit_editor: View<Editor>,
+    amend: bool,
+    /// The progress or the outcome of the last operation.
+    status_message: Option<SharedString>,
+    remote_operation_task: Option<Task<()>>,
+    generate_commit_message_task: Option<Task<()>>,
+    _refresh_task: Option<Task<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl GitPanel {
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<View<Self>> {
+        workspace.update(&mut cx, |workspace, cx| Self::new(workspace, cx))
+    }
+
+    fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let project = workspace.project().clone();
+        let fs = workspace.app_state().fs.clone();
+        let workspace_handle = cx.view().downgrade();
+        cx.new_view(|cx| {
+            let project_subscription = cx.subscribe(&project, |this, _, event, cx| match event {
+                project::Event::WorktreeAdded
+                | project::Event::WorktreeRemoved(_)
+                | project::Event::WorktreeUpdatedGitRepositories => this.update_repositories(cx),
+                project::Event::WorktreeUpdatedEntries(..) => this.refresh_statuses(cx),
+                _ => {}
+            });
+            let commit_editor = cx.new_view(|cx| {
+                let mut editor = Editor::auto_height(8, cx);
+                editor.set_placeholder_text("Commit message", cx);
+                editor
+            });
+
+            let mut this = Self {
+                workspace: workspace_handle,
+                project,
+                fs,
+                focus_handle: cx.focus_handle(),
+                scroll_handle: UniformListScrollHandle::new(),
+                width: None,
+                repositories: Vec::new(),
+                active_repository: 0,
+                tree: StatusTree::default(),
+                entries: Vec::new(),
+                commit_editor,
+                amend: false,
+                status_message: None,
+                remote_operation_task: None,
+                generate_commit_message_task: None,
+                _refresh_task: None,
+                _subscriptions: vec![project_subscription],
+            };
+            this.update_repositories(cx);
+            this
+        })
+    }
+
+    /// Collects the repositories of the visible worktrees. Git commands are run
+    /// as local processes, so remote projects have no repositories.
+    fn update_repositories(&mut self, cx: &mut ViewContext<Self>) {
+        let active = self
+            .repositories
+            .get(self.active_repository)
+            .map(|repository| (repository.worktree_id, repository.work_directory.clone()));
+
+        let mut repositories = Vec::new();
+        for worktree in self.project.read(cx).visible_worktrees(cx) {
+            let worktree = worktree.read(cx);
+            let Some(local_worktree) = worktree.as_local() else {
+                continue;
+            };
+            for (work_directory, entry) in local_worktree.repositories() {
+                let Some(local_repo) = local_worktree.get_local_repo(entry) else {
+                    continue;
+                };
+                let name = if work_directory.as_os_str().is_empty() {
+                    worktree.root_name().to_string()
+                } else {
+                    format!("{}/{}", worktree.root_name(), work_directory.display())
+                };
+                repositories.push(Repository {
+                    worktree_id: worktree.id(),
+                    work_directory: work_directory.clone(),
+                    name: name.into(),
+                    branch: entry.branch(),
+                    repo: local_repo.repo().clone(),
+                });
+            }
+        }
+
+        self.active_repository = active
+            .and_then(|(worktree_id, work_directory)| {
+                repositories.iter().position(|repository| {
+                    repository.worktree_id == worktree_id
+                        && repository.work_directory == work_directory
+                })
+            })
+            .unwrap_or(0);
+        self.repositories = repositories;
+        self.refresh_statuses(cx);
+    }
+
+    fn active_repository(&self) -> Option<&Repository> {
+        self.repositories.get(self.active_repository)
+    }
+
+    fn select_repository(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        if ix < self.repositories.len() && ix != self.active_repository {
+            self.active_repository = ix;
+            self.amend = false;
+            self.status_message = None;
+            self.refresh_statuses(cx);
+        }
+    }
+
+    fn refresh_statuses(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(repo) = self
+            .active_repository()
+            .map(|repository| repository.repo.clone())
+        else {
+            self.tree.set_statuses(Vec::new());
+            self.update_entries(cx);
+            return;
+        };
+        let statuses = cx
+            .background_executor()
+            .spawn(async move { repo.file_statuses() });
+        self._refresh_task = Some(cx.spawn(|this, mut cx| async move {
+            let Some(statuses) = statuses.await.log_err() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                this.tree.set_statuses(statuses);
+                this.update_entries(cx);
+            })
+            .ok();
+        }));
+    }
+
+    fn update_entries(&mut self, cx: &mut ViewContext<Self>) {
+        self.entries = self.tree.entries();
+        cx.notify();
+    }
+
+    fn toggle_expanded(&mut self, path: &Path, cx: &mut ViewContext<Self>) {
+        self.tree.toggle_expanded(path);
+        self.update_entries(cx);
+    }
+
+    /// Stages or unstages the changes to the files at or below the path.
+    fn set_staged(&mut self, path: &Path, staged: bool, cx: &mut ViewContext<Self>) {
+        let paths = self.tree.file_paths(path);
+        self.update_index(paths, staged, cx);
+    }
+
+    fn stage_all(&mut self, cx: &mut ViewContext<Self>) {
+        let paths = self.tree.file_paths(Path::new(""));
+        self.update_index(paths, true, cx);
+    }
+
+    fn unstage_all(&mut self, cx: &mut ViewContext<Self>) {
+        let paths = self.tree.file_paths(Path::new(""));
+        self.update_index(paths, false, cx);
+    }
+
+    fn update_index(&mut self, paths: Vec<RepoPath>, staged: bool, cx: &mut ViewContext<Self>) {
+        let Some(repo) = self
+            .active_repository()
+            .map(|repository| repository.repo.clone())
+        else {
+            return;
+        };
+        if paths.is_empty() {
+            return;
+        }
+        let update = cx.background_executor().spawn(async move {
+            if staged {
+                repo.stage_paths(&paths)
+            } else {
+                repo.unstage_paths(&paths)
+            }
+        });
+        cx.spawn(|this, mut cx| async move {
+            let result = update.await;
+            this.update(&mut cx, |this, cx| {
+                if let Err(error) = result {
+                    this.set_status_message(format!("Failed to update the index: {error}"), cx);
+                }
+                this.refresh_statuses(cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn set_amend(&mut self, amend: bool, cx: &mut ViewContext<Self>) {
+        self.amend = amend;
+        // Amending starts from the message of the commit being replaced.
+        if amend && self.commit_editor.read(cx).text(cx).trim().is_empty() {
+            let message = self
+                .active_repository()
+                .and_then(|repository| repository.repo.head_commit_message());
+            if let Some(message) = message {
+                self.commit_editor.update(cx, |editor, cx| {
+                    editor.set_text(message.trim_end().to_string(), cx)
+                });
+            }
+        }
+        cx.notify();
+    }
+
+    fn commit(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(repo) = self
+            .active_repository()
+            .map(|repository| repository.repo.clone())
+        else {
+            return;
+        };
+        let message = self.commit_editor.read(cx).text(cx);
+        if message.trim().is_empty() {
+            self.set_status_message("Enter a commit message", cx);
+            return;
+        }
+        if self.tree.staged_count() == 0 && !self.amend {
+            self.set_status_message("No changes are staged", cx);
+            return;
+        }
+
+        let amend = self.amend;
+        let commit = cx
+            .background_executor()
+            .spawn(async move { repo.commit(&message, amend) });
+        cx.spawn(|this, mut cx| async move {
+            let result = commit.await;
+            this.update(&mut cx, |this, cx| {
+                match result {
+                    Ok(()) => {
+                        this.amend = false;
+                        this.commit_editor
+                            .update(cx, |editor, cx| editor.set_text("", cx));
+                        this.set_status_message("Committed", cx);
+                    }
+                    Err(error) => this.set_status_message(format!("Failed to commit: {error}"), cx),
+                }
+                this.refresh_statuses(cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Asks the assistant's model for a message describing the staged changes,
+    /// which is streamed into the commit editor to be edited before committing.
+    fn generate_commit_message(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(repo) = self
+            .active_repository()
+            .map(|repository| repository.repo.clone())
+        else {
+            return;
+        };
+        if self.tree.staged_count() == 0 {
+            self.set_status_message("No changes are staged", cx);
+            return;
+        }
+        let Some(model) = LanguageModelRegistry::read_global(cx).active_model() else {
+            self.set_status_message("Configure a model in the assistant panel first", cx);
+            return;
+        };
+        let settings = GitPanelSettings::get_global(cx);
+        let style = settings.commit_message_style;
+        let instructions = settings.commit_message_instructions.clone();
+
+        let diff = cx
+            .background_executor()
+            .spawn(async move { repo.staged_diff() });
+        self.set_status_message("Generating a commit message…", cx);
+        self.generate_commit_message_task = Some(cx.spawn(|this, mut cx| async move {
+            let generate = async {
+                let prompt = commit_message_prompt(&diff.await?, style, instructions.as_deref());
+                let request = LanguageModelRequest {
+                    messages: vec![LanguageModelRequestMessage {
+                        role: Role::User,
+                        content: vec![prompt.into()],
+                        cache: false,
+                    }],
+                    tools: Vec::new(),
+                    stop: Vec::new(),
+                    temperature: 1.0,
+                };
+                let mut chunks = model.stream_completion_with_usage(request, &cx).await?;
+                let mut message = String::new();
+                while let Some(chunk) = chunks.next().await {
+                    message.push_str(&chunk?);
+                    this.update(&mut cx, |this, cx| {
+                        this.commit_editor
+                            .update(cx, |editor, cx| editor.set_text(message.trim_start(), cx));
+                    })?;
+                }
+                anyhow::Ok(message)
+            };
+            let result = generate.await;
+            this.update(&mut cx, |this, cx| {
+                this.generate_commit_message_task = None;
+                match result {
+                    Ok(message) => {
+                        this.commit_editor.update(cx, |editor, cx| {
+                            editor.set_text(clean_commit_message(&message), cx)
+                        });
+                        this.status_message = None;
+                        cx.notify();
+                    }
+                    Err(error) => this.set_status_message(
+                        format!("Failed to generate a commit message: {error}"),
+                        cx,
+                    ),
+                }
+            })
+            .log_err();
+        }));
+        cx.notify();
+    }
+
+    /// Opens the commit message followed by the diff of the staged changes, as
+    /// `git commit --verbose` shows them.
+    fn preview_commit(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(repo) = self
+            .active_repository()
+            .map(|repository| repository.repo.clone())
+        else {
+            return;
+        };
+        let message = self.commit_editor.read(cx).text(cx);
+        let diff = cx
+            .background_executor()
+            .spawn(async move { repo.staged_diff() });
+        let workspace = self.workspace.clone();
+        cx.spawn(|_, mut cx| async move {
+            let diff = diff.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let project = workspace.project().clone();
+                let text = format!(
+                    "{}\n\n# ------------------------ >8 ------------------------\n{}",
+                    message.trim_end(),
+                    diff
+                );
+                let buffer = cx.new_model(|cx| Buffer::local(text, cx));
+                let multibuffer = cx.new_model(|cx| {
+                    MultiBuffer::singleton(buffer, cx).with_title("Commit Preview".to_string())
+                });
+                let editor = cx.new_view(|cx| {
+                    let mut editor = Editor::for_multibuffer(multibuffer, Some(project), true, cx);
+                    editor.set_read_only(true);
+                    editor
+                });
+                workspace.add_item_to_active_pane(Box::new(editor), None, true, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Runs the operation with the remote of the active repository, showing its
+    /// progress. When the remote requires credentials, they are prompted for
+    /// and the operation is run again with them.
+    fn run_remote_operation(
+        &mut self,
+        operation: RemoteOperation,
+        credentials: Option<Credentials>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(repo) = self
+            .active_repository()
+            .map(|repository| repository.repo.clone())
+        else {
+            return;
+        };
+        if self.remote_operation_task.is_some() {
+            return;
+        }
+
+        let (progress_tx, mut progress_rx) = mpsc::unbounded::<String>();
+        let run = cx.background_executor().spawn(async move {
+            repo.remote_operation(operation, credentials.as_ref(), &|line: &str| {
+                progress_tx.unbounded_send(line.to_string()).ok();
+            })
+        });
+        self.set_status_message(format!("{}...", operation.label()), cx);
+        self.remote_operation_task = Some(cx.spawn(|this, mut cx| async move {
+            while let Some(line) = progress_rx.next().await {
+                this.update(&mut cx, |this, cx| {
+                    this.set_status_message(format!("{}: {line}", operation.label()), cx)
+                })
+                .ok();
+            }
+            let result = run.await;
+            this.update(&mut cx, |this, cx| {
+                this.remote_operation_task = None;
+                match result {
+                    Ok(()) => {
+                        this.set_status_message(format!("{} completed", operation.label()), cx)
+                    }
+                    Err(error) if error.is::<AuthenticationRequired>() => {
+                        this.set_status_message(
+                            format!("{} requires authentication", operation.label()),
+                            cx,
+                        );
+                        this.prompt_for_credentials(operation, cx);
+                    }
+                    Err(error) => this
+                        .set_status_message(format!("{} failed: {error}", operation.label()), cx),
+                }
+                this.refresh_statuses(cx);
+            })
+            .ok();
+        }));
+    }
+
+    fn prompt_for_credentials(&mut self, operation: RemoteOperation, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let (tx, rx) = oneshot::channel();
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(cx, |cx| CredentialsPrompt::new(operation.label(), tx, cx));
+        });
+        cx.spawn(|this, mut cx| async move {
+            // The prompt drops the sender when it is dismissed.
+            if let Ok(credentials) = rx.await {
+                this.update(&mut cx, |this, cx| {
+                    this.run_remote_operation(operation, Some(credentials), cx)
+                })
+                .ok();
+            }
+        })
+        .detach();
+    }
+
+    fn set_status_message(&mut self, message: impl Into<SharedString>, cx: &mut ViewContext<Self>) {
+        self.status_message = Some(message.into());
+        cx.notify();
+    }
+
+    fn open_file(&mut self, path: &Path, cx: &mut ViewContext<Self>) {
+        let Some(work_directory) = self
+            .active_repository()
+            .and_then(|repository| repository.repo.work_directory())
+        else {
+            return;
+        };
+        let abs_path = work_directory.join(path);
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .open_abs_path(abs_path, true, cx)
+                    .detach_and_log_err(cx);
+            })
+            .ok();
+    }
+
+    fn render_header(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let is_busy = self.remote_operation_task.is_some();
+        let remote_button = |id: &'static str, icon, label: &'static str, action: &dyn Action| {
+            let action = action.boxed_clone();
+            IconButton::new(id, icon)
+                .icon_size(IconSize::Small)
+                .disabled(is_busy || self.repositories.is_empty())
+                .tooltip(move |cx| Tooltip::for_action(label, &*action, cx))
+        };
+
+        let repository_label = match self.active_repository() {
+            Some(repository) => match &repository.branch {
+                Some(branch) => format!("{} ({branch})", repository.name),
+                None => repository.name.to_string(),
+            },
+            None => "No repository".to_string(),
+        };
+        let repositories = self
+            .repositories
+            .iter()
+            .map(|repository| repository.name.clone())
+            .collect::<Vec<_>>();
+        let panel = cx.view().downgrade();
+
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                PopoverMenu::new("git-repository-menu")
+                    .trigger(
+                        Button::new("git-repository", repository_label)
+                            .icon(IconName::FileGit)
+                            .icon_position(IconPosition::Start)
+                            .icon_size(IconSize::Small)
+                            .label_size(LabelSize::Small)
+                            .disabled(repositories.len() < 2),
+                    )
+                    .menu(move |cx| {
+                        let panel = panel.clone();
+                        let repositories = repositories.clone();
+                        Some(ContextMenu::build(cx, move |mut menu, _| {
+                            for (ix, name) in repositories.into_iter().enumerate() {
+                                let panel = panel.clone();
+                                menu = menu.entry(name, None, move |cx| {
+                                    panel
+                                        .update(cx, |panel, cx| panel.select_repository(ix, cx))
+                                        .ok();
+                                });
+                            }
+                            menu
+                        }))
+                    }),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        remote_button("git-fetch", IconName::Update, "Fetch", &Fetch).on_click(
+                            cx.listener(|this, _, cx| {
+                                this.run_remote_operation(RemoteOperation::Fetch, None, cx)
+                            }),
+                        ),
+                    )
+                    .child(
+                        remote_button("git-pull", IconName::ArrowDown, "Pull", &Pull).on_click(
+                            cx.listener(|this, _, cx| {
+                                this.run_remote_operation(RemoteOperation::Pull, None, cx)
+                            }),
+                        ),
+                    )
+                    .child(
+                        remote_button("git-push", IconName::ArrowUp, "Push", &Push).on_click(
+                            cx.listener(|this, _, cx| {
+                                this.run_remote_operation(RemoteOperation::Push, None, cx)
+                            }),
+                        ),
+                    ),
+            )
+    }
+
+    fn render_commit_section(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let staged_count = self.tree.staged_count();
+        let can_commit = !self.repositories.is_empty() && (staged_count > 0 || self.amend);
+        let commit_label = if self.amend {
+            "Amend".to_string()
+        } else {
+            format!("Commit ({staged_count})")
+        };
+
+        v_flex()
+            .p_2()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                div()
+                    .px_2()
+                    .py_1()
+                    .rounded_md()
+                    .border_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(self.commit_editor.clone()),
+            )
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                Checkbox::new(
+                                    "git-amend",
+                                    if self.amend {
+                                        Selection::Selected
+                                    } else {
+                                        Selection::Unselected
+                                    },
+                                )
+                                .on_click(cx.listener(
+                                    |this, selection: &Selection, cx| {
+                                        this.set_amend(*selection == Selection::Selected, cx)
+                                    },
+                                )),
+                            )
+                            .child(Label::new("Amend").size(LabelSize::Small)),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                IconButton::new("git-generate-commit-message", IconName::Sparkle)
+                                    .icon_size(IconSize::Small)
+                                    .disabled(
+                                        staged_count == 0
+                                            || self.generate_commit_message_task.is_some(),
+                                    )
+                                    .tooltip(|cx| {
+                                        Tooltip::for_action(
+                                            "Generate Commit Message",
+                                            &GenerateCommitMessage,
+                                            cx,
+                                        )
+                                    })
+                                    .on_click(
+                                        cx.listener(|this, _, cx| this.generate_commit_message(cx)),
+                                    ),
+                            )
+                            .child(
+                                IconButton::new("git-preview-commit", IconName::Eye)
+                                    .icon_size(IconSize::Small)
+                                    .disabled(self.repositories.is_empty())
+                                    .tooltip(|cx| {
+                                        Tooltip::for_action("Preview Commit", &PreviewCommit, cx)
+                                    })
+                                    .on_click(cx.listener(|this, _, cx| this.preview_commit(cx))),
+                            )
+                            .child(
+                                Button::new("git-commit", commit_label)
+                                    .label_size(LabelSize::Small)
+                                    .style(ButtonStyle::Filled)
+                                    .disabled(!can_commit)
+                                    .tooltip(|cx| Tooltip::for_action("Commit", &Commit, cx))
+                                    .on_click(cx.listener(|this, _, cx| this.commit(cx))),
+                            ),
+                    ),
+            )
+    }
+
+    fn render_entry(&self, ix: usize, entry: &StatusEntry, cx: &ViewContext<Self>) -> AnyElement {
+        let settings = GitPanelSettings::get_global(cx);
+        let selection = match entry.staging {
+            StagingState::Staged => Selection::Selected,
+            StagingState::PartiallyStaged => Selection::Indeterminate,
+            StagingState::Unstaged => Selection::Unselected,
+        };
+        let (is_expanded, change) = match entry.kind {
+            StatusEntryKind::Directory { is_expanded } => (Some(is_expanded), None),
+            StatusEntryKind::File { change } => (None, Some(change)),
+        };
+
+        let stage_path = entry.path.clone();
+        let click_path = entry.path.clone();
+        let toggle_path = entry.path.clone();
+        ListItem::new(ix)
+            .indent_level(entry.depth)
+            .indent_step_size(px(settings.indent_size))
+            .toggle(is_expanded)
+            .on_toggle(cx.listener(move |this, _, cx| this.toggle_expanded(&toggle_path, cx)))
+            .start_slot(
+                Checkbox::new(("git-stage", ix), selection).on_click(cx.listener(
+                    move |this, selection: &Selection, cx| {
+                        this.set_staged(&stage_path, *selection == Selection::Selected, cx)
+                    },
+                )),
+            )
+            .child(Label::new(entry.name()).single_line())
+            .end_slot::<AnyElement>(change.map(|change| change_label(change).into_any_element()))
+            .on_click(cx.listener(move |this, _, cx| {
+                if is_expanded.is_some() {
+                    this.toggle_expanded(&click_path, cx);
+                } else {
+                    this.open_file(&click_path, cx);
+                }
+            }))
+            .into_any_element()
+    }
+}
+
+fn change_label(change: FileChange) -> Label {
+    let (text, color) = match change {
+        FileChange::Added => ("A", Color::Created),
+        FileChange::Modified => ("M", Color::Modified),
+        FileChange::Deleted => ("D", Color::Deleted),
+        FileChange::Renamed => ("R", Color::Modified),
+        FileChange::Untracked => ("U", Color::Created),
+        FileChange::Conflicted => ("!", Color::Conflict),
+    };
+    Label::new(text).size(LabelSize::Small).color(color)
+}
+
+impl Panel for GitPanel {
+    fn persistent_name() -> &'static str {
+        "Git Panel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        match GitPanelSettings::get_global(cx).dock {
+            GitPanelDockPosition::Left => DockPosition::Left,
+            GitPanelDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<GitPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| {
+                let dock = match position {
+                    DockPosition::Left | DockPosition::Bottom => GitPanelDockPosition::Left,
+                    DockPosition::Right => GitPanelDockPosition::Right,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.width
+            .unwrap_or_else(|| GitPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        GitPanelSettings::get_global(cx)
+            .button
+            .then(|| IconName::FileGit)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Git Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+}
+
+impl FocusableView for GitPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for GitPanel {}
+
+impl Render for GitPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let panel = v_flex()
+            .id("git-panel")
+            .size_full()
+            .key_context("GitPanel")
+            .on_action(cx.listener(|this, _: &Commit, cx| this.commit(cx)))
+            .on_action(cx.listener(|this, _: &PreviewCommit, cx| this.preview_commit(cx)))
+            .on_action(cx.listener(|this, _: &StageAll, cx| this.stage_all(cx)))
+            .on_action(cx.listener(|this, _: &UnstageAll, cx| this.unstage_all(cx)))
+            .track_focus(&self.focus_handle)
+            .child(self.render_header(cx))
+            .child(self.render_commit_section(cx))
+            .when_some(self.status_message.clone(), |panel, message| {
+                panel.child(
+                    div().px_2().py_1().child(
+                        Label::new(message)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .single_line(),
+                    ),
+                )
+            })
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_1()
+                    .justify_between()
+                    .child(Label::new("Changes").size(LabelSize::Small))
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                IconButton::new("git-stage-all", IconName::Plus)
+                                    .shape(IconButtonShape::Square)
+                                    .icon_size(IconSize::Small)
+                                    .tooltip(|cx| Tooltip::for_action("Stage All", &StageAll, cx))
+                                    .on_click(cx.listener(|this, _, cx| this.stage_all(cx))),
+                            )
+                            .child(
+                                IconButton::new("git-unstage-all", IconName::Dash)
+                                    .shape(IconButtonShape::Square)
+                                    .icon_size(IconSize::Small)
+                                    .tooltip(|cx| {
+                                        Tooltip::for_action("Unstage All", &UnstageAll, cx)
+                                    })
+                                    .on_click(cx.listener(|this, _, cx| this.unstage_all(cx))),
+                            ),
+                    ),
+            );
+
+        if self.entries.is_empty() {
+            let message = if self.repositories.is_empty() {
+                "No git repository found"
+            } else {
+                "No changes"
+            };
+            panel.child(
+                v_flex().size_full().justify_center().child(
+                    h_flex()
+                        .justify_center()
+                        .child(Label::new(message).color(Color::Muted)),
+                ),
+            )
+        } else {
+            panel.child(
+                uniform_list(
+                    cx.view().clone(),
+                    "git-panel-entries",
+                    self.entries.len(),
+                    |this, range, cx| {
+                        range
+                            .map(|ix| this.render_entry(ix, &this.entries[ix], cx))
+                            .collect()
+                    },
+                )
+                .size_full()
+                .track_scroll(self.scroll_handle.clone()),
+            )
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use gpui::Pixels;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GitPanelDockPosition {
+    Left,
+    Right,
+}
+
+/// The format of generated commit messages.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitMessageStyle {
+    /// A `type(scope): description` summary, following Conventional Commits.
+    #[default]
+    Conventional,
+    /// A summary in the imperative mood.
+    Plain,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct GitPanelSettings {
+    pub button: bool,
+    pub default_width: Pixels,
+    pub dock: GitPanelDockPosition,
+    pub indent_size: f32,
+    pub commit_message_style: CommitMessageStyle,
+    pub commit_message_instructions: Option<String>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct GitPanelSettingsContent {
+    /// Whether to show the git panel button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Customize default width (in pixels) taken by the git panel
+    ///
+    /// Default: 240
+    pub default_width: Option<f32>,
+    /// The position of the git panel
+    ///
+    /// Default: left
+    pub dock: Option<GitPanelDockPosition>,
+    /// Amount of indentation (in pixels) for nested files.
+    ///
+    /// Default: 20
+    pub indent_size: Option<f32>,
+    /// The format of the commit messages generated by the assistant's model.
+    ///
+    /// Default: conventional
+    pub commit_message_style: Option<CommitMessageStyle>,
+    /// More instructions for the model when generating commit messages, such as
+    /// to mention the issue that a branch is for.
+    ///
+    /// Default: null
+    pub commit_message_instructions: Option<String>,
+}
+
+impl Settings for GitPanelSettings {
+    const KEY: Option<&'static str> = Some("git_panel");
+
+    type FileContent = GitPanelSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
@@ -0,0 +1,192 @@
+use anyhow::Context as _;
+use collections::HashMap;
+use editor::{scroll::Autoscroll, Editor, MultiBuffer};
+use git::{blame::BlameEntry, repository::GitRepository, Oid};
+use gpui::{AppContext, Context, EntityId, Global, Model, Task, ViewContext, VisualContext};
+use language::{Buffer, Language, Point};
+use std::{path::PathBuf, sync::Arc};
+use workspace::{notifications::NotificationId, Toast, Workspace};
+
+/// A revision of a file from the history of a repository.
+#[derive(Clone)]
+pub(crate) struct Revision {
+    pub repo: Arc<dyn GitRepository>,
+    pub sha: Oid,
+    pub path: PathBuf,
+}
+
+/// The revisions of files opened in buffers, by buffer, from which the history
+/// of their lines can be walked further.
+#[derive(Default)]
+struct OpenRevisions(HashMap<EntityId, Revision>);
+
+impl Global for OpenRevisions {}
+
+/// Remembers that the buffer holds the content of the revision, until the
+/// buffer is released.
+pub(crate) fn register_revision_buffer(
+    buffer: &Model<Buffer>,
+    revision: Revision,
+    cx: &mut AppContext,
+) {
+    let buffer_id = buffer.entity_id();
+    cx.default_global::<OpenRevisions>()
+        .0
+        .insert(buffer_id, revision);
+    cx.observe_release(buffer, move |_, cx| {
+        cx.default_global::<OpenRevisions>().0.remove(&buffer_id);
+    })
+    .detach();
+}
+
+struct LineHistoryNotification;
+
+/// Opens the file as it was before the last commit that changed the line under
+/// the cursor, with the cursor on the line. In a revision opened this way, the
+/// line's history is walked further back.
+pub(crate) fn show_previous_revision_of_line(
+    workspace: &mut Workspace,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+        return;
+    };
+    let row = editor.update(cx, |editor, cx| {
+        editor.selections.newest::<Point>(cx).head().row
+    });
+    let language = buffer.read(cx).language().cloned();
+
+    let open_revision = cx
+        .try_global::<OpenRevisions>()
+        .and_then(|revisions| revisions.0.get(&buffer.entity_id()).cloned());
+    let blame: Task<anyhow::Result<Option<(Arc<dyn GitRepository>, BlameEntry)>>> =
+        match open_revision {
+            Some(revision) => cx.background_executor().spawn(async move {
+                let entry =
+                    revision
+                        .repo
+                        .blame_line_at_revision(revision.sha, &revision.path, row)?;
+                Ok(entry.map(|entry| (revision.repo, entry)))
+            }),
+            None => {
+                let project = workspace.project().clone();
+                let repo = project.read(cx).local_repository_for_buffer(&buffer, cx);
+                let repo = match repo {
+                    Ok((repo, _)) => repo,
+                    Err(error) => {
+                        workspace.show_error(&error, cx);
+                        return;
+                    }
+                };
+                let blame = project.read(cx).blame_buffer(&buffer, None, cx);
+                cx.spawn(|_, _| async move {
+                    let blame = blame.await?;
+                    let entry = blame
+                        .entries
+                        .into_iter()
+                        .find(|entry| entry.range.contains(&row));
+                    Ok(entry.map(|entry| (repo, entry)))
+                })
+            }
+        };
+
+    cx.spawn(|workspace, mut cx| async move {
+        let Some((repo, entry)) = blame.await? else {
+            return workspace.update(&mut cx, |workspace, cx| {
+                show_message(workspace, "This line isn't committed yet", cx)
+            });
+        };
+        let Some((parent_sha, path)) = entry.previous_revision() else {
+            return workspace.update(&mut cx, |workspace, cx| {
+                show_message(
+                    workspace,
+                    &format!(
+                        "This line was added in {}, which has no previous revision of the file",
+                        entry.sha.display_short()
+                    ),
+                    cx,
+                )
+            });
+        };
+
+        let revision = Revision {
+            repo,
+            sha: parent_sha,
+            path: PathBuf::from(path),
+        };
+        let text = cx
+            .background_executor()
+            .spawn({
+                let revision = revision.clone();
+                async move {
+                    revision
+                        .repo
+                        .file_at_revision(revision.sha, &revision.path)?
+                        .with_context(|| {
+                            format!("{:?} not found in {}", revision.path, revision.sha)
+                        })
+                }
+            })
+            .await?;
+        // The line is at its position in the commit that changed it, which is
+        // close to its position in the parent commit.
+        let row = entry.original_line_number.saturating_sub(1);
+        workspace.update(&mut cx, |workspace, cx| {
+            open_revision(workspace, revision, text, language, row, cx)
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Opens the content of the file in the revision in a read-only editor, with
+/// the cursor at the row.
+pub(crate) fn open_revision(
+    workspace: &mut Workspace,
+    revision: Revision,
+    text: String,
+    language: Option<Arc<Language>>,
+    row: u32,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let project = workspace.project().clone();
+    let file_name = revision
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let title = format!("{file_name} @ {}", revision.sha.display_short());
+    let buffer = cx.new_model(|cx| {
+        let mut buffer = Buffer::local(text, cx);
+        buffer.set_language_registry(project.read(cx).languages().clone());
+        buffer.set_language(language, cx);
+        buffer
+    });
+    register_revision_buffer(&buffer, revision, cx);
+
+    let multibuffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx).with_title(title));
+    let editor = cx.new_view(|cx| {
+        let mut editor = Editor::for_multibuffer(multibuffer, Some(project), true, cx);
+        editor.set_read_only(true);
+        editor
+    });
+    workspace.add_item_to_active_pane(Box::new(editor.clone()), None, true, cx);
+    editor.update(cx, |editor, cx| {
+        let point = Point::new(row, 0);
+        editor.change_selections(Some(Autoscroll::center()), cx, |selections| {
+            selections.select_ranges([point..point])
+        });
+    });
+}
+
+fn show_message(workspace: &mut Workspace, message: &str, cx: &mut ViewContext<Workspace>) {
+    workspace.show_toast(
+        Toast::new(
+            NotificationId::unique::<LineHistoryNotification>(),
+            message.to_string(),
+        ),
+        cx,
+    );
+}
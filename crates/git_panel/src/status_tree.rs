@@ -0,0 +1,251 @@
+use collections::{HashMap, HashSet};
+use git::{
+    repository::RepoPath,
+    status::{FileChange, FileStatus},
+};
+use std::path::{Path, PathBuf};
+
+/// Whether the changes to a file, or to the files in a directory, are staged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StagingState {
+    Staged,
+    PartiallyStaged,
+    Unstaged,
+}
+
+impl StagingState {
+    fn of_file(status: &FileStatus) -> Self {
+        match (status.staged.is_some(), status.unstaged.is_some()) {
+            (true, false) => StagingState::Staged,
+            (true, true) => StagingState::PartiallyStaged,
+            (false, _) => StagingState::Unstaged,
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        if self == other {
+            self
+        } else {
+            StagingState::PartiallyStaged
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StatusEntryKind {
+    Directory { is_expanded: bool },
+    File { change: FileChange },
+}
+
+/// A changed file or a directory containing changed files, as shown in the panel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatusEntry {
+    pub path: PathBuf,
+    pub depth: usize,
+    pub kind: StatusEntryKind,
+    pub staging: StagingState,
+}
+
+impl StatusEntry {
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// The changed files of a repository, organized by directory.
+#[derive(Default)]
+pub struct StatusTree {
+    /// The statuses of the changed files, sorted by path.
+    statuses: Vec<FileStatus>,
+    collapsed_directories: HashSet<PathBuf>,
+}
+
+impl StatusTree {
+    pub fn set_statuses(&mut self, mut statuses: Vec<FileStatus>) {
+        statuses.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+        self.statuses = statuses;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.statuses.is_empty()
+    }
+
+    pub fn staged_count(&self) -> usize {
+        self.statuses
+            .iter()
+            .filter(|status| status.staged.is_some())
+            .count()
+    }
+
+    pub fn toggle_expanded(&mut self, directory: &Path) {
+        if !self.collapsed_directories.remove(directory) {
+            self.collapsed_directories.insert(directory.to_path_buf());
+        }
+    }
+
+    /// Returns the paths of the changed files at or below the path.
+    pub fn file_paths(&self, path: &Path) -> Vec<RepoPath> {
+        self.statuses
+            .iter()
+            .filter(|status| status.path.starts_with(path))
+            .map(|status| status.path.clone())
+            .collect()
+    }
+
+    /// Returns the entries that are visible, the files of collapsed directories
+    /// being hidden.
+    pub fn entries(&self) -> Vec<StatusEntry> {
+        let mut directory_states = HashMap::<&Path, StagingState>::default();
+        for status in &self.statuses {
+            let state = StagingState::of_file(status);
+            for directory in directories(&status.path) {
+                directory_states
+                    .entry(directory)
+                    .and_modify(|directory_state| *directory_state = directory_state.merge(state))
+                    .or_insert(state);
+            }
+        }
+
+        let mut entries = Vec::new();
+        let mut visited_directories = HashSet::default();
+        for status in &self.statuses {
+            let directories = directories(&status.path);
+            let mut is_visible = true;
+            for (depth, directory) in directories.iter().enumerate() {
+                if is_visible && visited_directories.insert(*directory) {
+                    let is_expanded = !self.collapsed_directories.contains(*directory);
+                    entries.push(StatusEntry {
+                        path: directory.to_path_buf(),
+                        depth,
+                        kind: StatusEntryKind::Directory { is_expanded },
+                        staging: directory_states[directory],
+                    });
+                }
+                is_visible &= !self.collapsed_directories.contains(*directory);
+            }
+            if is_visible {
+                if let Some(change) = status.unstaged.or(status.staged) {
+                    entries.push(StatusEntry {
+                        path: status.path.to_path_buf(),
+                        depth: directories.len(),
+                        kind: StatusEntryKind::File { change },
+                        staging: StagingState::of_file(status),
+                    });
+                }
+            }
+        }
+        entries
+    }
+}
+
+/// Returns the directories containing the file, from the outermost.
+fn directories(path: &Path) -> Vec<&Path> {
+    let mut directories = path
+        .ancestors()
+        .skip(1)
+        .filter(|directory| !directory.as_os_str().is_empty())
+        .collect::<Vec<_>>();
+    directories.reverse();
+    directories
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(path: &str, staged: Option<FileChange>, unstaged: Option<FileChange>) -> FileStatus {
+        FileStatus {
+            path: RepoPath(PathBuf::from(path)),
+            staged,
+            unstaged,
+        }
+    }
+
+    fn summary(tree: &StatusTree) -> Vec<String> {
+        tree.entries()
+            .into_iter()
+            .map(|entry| {
+                let checkbox = match entry.staging {
+                    StagingState::Staged => "[x]",
+                    StagingState::PartiallyStaged => "[-]",
+                    StagingState::Unstaged => "[ ]",
+                };
+                let suffix = match entry.kind {
+                    StatusEntryKind::Directory { is_expanded: true } => "/",
+                    StatusEntryKind::Directory { is_expanded: false } => "/...",
+                    StatusEntryKind::File { .. } => "",
+                };
+                format!(
+                    "{}{checkbox} {}{suffix}",
+                    "  ".repeat(entry.depth),
+                    entry.name()
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_status_tree() {
+        let mut tree = StatusTree::default();
+        tree.set_statuses(vec![
+            status("src/main.rs", Some(FileChange::Modified), None),
+            status("README.md", None, Some(FileChange::Modified)),
+            status("src/lib/mod.rs", None, Some(FileChange::Untracked)),
+            status("src/lib/util.rs", Some(FileChange::Added), None),
+            status(
+                "docs/guide.md",
+                Some(FileChange::Added),
+                Some(FileChange::Modified),
+            ),
+        ]);
+        assert_eq!(
+            summary(&tree),
+            vec![
+                "[ ] README.md",
+                "[-] docs/",
+                "  [-] guide.md",
+                "[-] src/",
+                "  [-] lib/",
+                "    [ ] mod.rs",
+                "    [x] util.rs",
+                "  [x] main.rs",
+            ]
+        );
+        assert_eq!(tree.staged_count(), 3);
+        assert_eq!(
+            tree.file_paths(Path::new("src")),
+            vec![
+                RepoPath(PathBuf::from("src/lib/mod.rs")),
+                RepoPath(PathBuf::from("src/lib/util.rs")),
+                RepoPath(PathBuf::from("src/main.rs")),
+            ]
+        );
+
+        tree.toggle_expanded(Path::new("src/lib"));
+        assert_eq!(
+            summary(&tree),
+            vec![
+                "[ ] README.md",
+                "[-] docs/",
+                "  [-] guide.md",
+                "[-] src/",
+                "  [-] lib/...",
+                "  [x] main.rs",
+            ]
+        );
+
+        tree.toggle_expanded(Path::new("src"));
+        assert_eq!(
+            summary(&tree),
+            vec![
+                "[ ] README.md",
+                "[-] docs/",
+                "  [-] guide.md",
+                "[-] src/..."
+            ]
+        );
+    }
+}
@@ -1,5 +1,5 @@
-use editor::{Editor, ToPoint};
-use gpui::{AppContext, Subscription, View, WeakView};
+use editor::{Editor, MultiBufferSnapshot, ToPoint};
+use gpui::{AppContext, EntityId, Subscription, View, WeakView};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
@@ -17,11 +17,23 @@ pub(crate) struct SelectionStats {
     pub lines: usize,
     pub characters: usize,
     pub selections: usize,
+    /// The number of occurrences of the newest selection's text in the buffer.
+    pub matches: usize,
+}
+
+/// The number of occurrences of a selection's text, which is only counted again
+/// when the text or the buffer changes.
+struct MatchCount {
+    editor_id: EntityId,
+    edit_count: usize,
+    query: String,
+    count: usize,
 }
 
 pub struct CursorPosition {
     position: Option<Point>,
     selected_count: SelectionStats,
+    match_count: Option<MatchCount>,
     workspace: WeakView<Workspace>,
     _observe_active_editor: Option<Subscription>,
 }
@@ -31,12 +43,14 @@ impl CursorPosition {
         Self {
             position: None,
             selected_count: Default::default(),
+            match_count: None,
             workspace: workspace.weak_handle(),
             _observe_active_editor: None,
         }
     }
 
     fn update_position(&mut self, editor: View<Editor>, cx: &mut ViewContext<Self>) {
+        let editor_id = editor.entity_id();
         let editor = editor.read(cx);
         let buffer = editor.buffer().read(cx).snapshot(cx);
 
@@ -65,9 +79,42 @@ impl CursorPosition {
         }
         self.position = last_selection.map(|s| s.head().to_point(&buffer));
 
+        let newest_selection = editor.selections.newest::<usize>(cx);
+        if !newest_selection.is_empty() {
+            let query = buffer
+                .text_for_range(newest_selection.range())
+                .collect::<String>();
+            self.selected_count.matches = self.count_matches(editor_id, &buffer, query);
+        }
+
         cx.notify();
     }
 
+    fn count_matches(
+        &mut self,
+        editor_id: EntityId,
+        buffer: &MultiBufferSnapshot,
+        query: String,
+    ) -> usize {
+        if let Some(match_count) = &self.match_count {
+            if match_count.editor_id == editor_id
+                && match_count.edit_count == buffer.edit_count()
+                && match_count.query == query
+            {
+                return match_count.count;
+            }
+        }
+
+        let count = buffer.text().matches(query.as_str()).count();
+        self.match_count = Some(MatchCount {
+            editor_id,
+            edit_count: buffer.edit_count(),
+            query,
+            count,
+        });
+        count
+    }
+
     fn write_position(&self, text: &mut String, cx: &AppContext) {
         if self.selected_count
             <= (SelectionStats {
@@ -82,25 +129,34 @@ impl CursorPosition {
             lines,
             characters,
             selections,
+            matches,
         } = self.selected_count;
         let format = LineIndicatorFormat::get(None, cx);
         let is_short_format = format == &LineIndicatorFormat::Short;
         let lines = (lines > 1).then_some((lines, "line"));
         let selections = (selections > 1).then_some((selections, "selection"));
         let characters = (characters > 0).then_some((characters, "character"));
+        let matches = (matches > 0).then_some((matches, "match"));
         if (None, None, None) == (characters, selections, lines) {
             // Nothing to display.
             return;
         }
         write!(text, " (").unwrap();
         let mut wrote_once = false;
-        for (count, name) in [selections, lines, characters].into_iter().flatten() {
+        for (count, name) in [selections, lines, characters, matches]
+            .into_iter()
+            .flatten()
+        {
             if wrote_once {
                 write!(text, ", ").unwrap();
             }
             let name = if is_short_format { &name[..1] } else { &name };
             let plural_suffix = if count > 1 && !is_short_format {
-                "s"
+                if name.ends_with("ch") {
+                    "es"
+                } else {
+                    "s"
+                }
             } else {
                 ""
             };
@@ -197,9 +253,10 @@ impl Settings for LineIndicatorFormat {
         sources: SettingsSources<Self::FileContent>,
         _: &mut AppContext,
     ) -> anyhow::Result<Self> {
-        let format = [sources.release_channel, sources.user]
-            .into_iter()
-            .find_map(|value| value.copied().flatten())
+        let format = sources
+            .user_customizations()
+            .filter_map(|value| *value)
+            .last()
             .unwrap_or(sources.default.ok_or_else(Self::missing_default)?);
 
         Ok(format.0)
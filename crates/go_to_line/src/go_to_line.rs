@@ -383,6 +383,7 @@ mod tests {
                     lines: 0,
                     characters: 0,
                     selections: 1,
+                    matches: 0,
                 },
                 workspace
                     .status_bar()
@@ -401,6 +402,7 @@ mod tests {
                     lines: 1,
                     characters: 3,
                     selections: 1,
+                    matches: 1,
                 },
                 workspace
                     .status_bar()
@@ -0,0 +1,124 @@
+use std::ops::Range;
+
+use crate::SharedString;
+
+/// What kind of element is exposed to assistive technologies, such as screen readers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessibilityRole {
+    /// Text spanning multiple lines, such as a code editor.
+    TextArea,
+    /// A single line of text, such as a search query.
+    TextField,
+}
+
+/// The text of the focused element and its selection, which are exposed to assistive
+/// technologies through the platform's accessibility APIs so that they can read lines
+/// and announce where the cursor moves.
+///
+/// Offsets are in UTF-16 code units, as the platform APIs count them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessibleText {
+    /// What kind of element the text belongs to.
+    pub role: AccessibilityRole,
+    /// A description of the element, such as the name of the file being edited.
+    pub label: SharedString,
+    /// The text, which may be only the part of a longer document that's around the
+    /// cursor.
+    pub text: SharedString,
+    /// The selected range of `text`, which is empty where there's only a cursor.
+    pub selected_range_utf16: Range<usize>,
+}
+
+impl AccessibleText {
+    /// The length of the text in UTF-16 code units.
+    pub fn len_utf16(&self) -> usize {
+        self.text.encode_utf16().count()
+    }
+
+    /// The zero-based line of the text that contains the UTF-16 offset.
+    pub fn line_for_offset_utf16(&self, offset_utf16: usize) -> usize {
+        let offset = self.offset_from_utf16(offset_utf16);
+        self.text[..offset].matches('\n').count()
+    }
+
+    /// The UTF-16 range of a zero-based line of the text, including its newline.
+    pub fn range_for_line_utf16(&self, line: usize) -> Option<Range<usize>> {
+        let mut start_utf16 = 0;
+        for (ix, line_text) in self.text.split_inclusive('\n').enumerate() {
+            let len_utf16 = line_text.encode_utf16().count();
+            if ix == line {
+                return Some(start_utf16..start_utf16 + len_utf16);
+            }
+            start_utf16 += len_utf16;
+        }
+        // The text is empty, or ends with a newline followed by an empty line.
+        let line_count = self.text.split_inclusive('\n').count();
+        (line == line_count && (self.text.is_empty() || self.text.ends_with('\n')))
+            .then_some(start_utf16..start_utf16)
+    }
+
+    /// The text in a UTF-16 range, or `None` if the range is out of bounds.
+    pub fn text_for_range_utf16(&self, range_utf16: Range<usize>) -> Option<&str> {
+        if range_utf16.start > range_utf16.end || range_utf16.end > self.len_utf16() {
+            return None;
+        }
+        let start = self.offset_from_utf16(range_utf16.start);
+        let end = self.offset_from_utf16(range_utf16.end);
+        Some(&self.text[start..end])
+    }
+
+    /// The selected text, which is empty where there's only a cursor.
+    pub fn selected_text(&self) -> &str {
+        self.text_for_range_utf16(self.selected_range_utf16.clone())
+            .unwrap_or_default()
+    }
+
+    /// Converts a UTF-16 offset into a byte offset, clipping it to the text.
+    fn offset_from_utf16(&self, offset_utf16: usize) -> usize {
+        let mut utf16_count = 0;
+        for (offset, c) in self.text.char_indices() {
+            if utf16_count >= offset_utf16 {
+                return offset;
+            }
+            utf16_count += c.len_utf16();
+        }
+        self.text.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accessible_text(text: &'static str, selected_range_utf16: Range<usize>) -> AccessibleText {
+        AccessibleText {
+            role: AccessibilityRole::TextArea,
+            label: "main.rs".into(),
+            text: text.into(),
+            selected_range_utf16,
+        }
+    }
+
+    #[test]
+    fn test_accessible_text_lines() {
+        let text = accessible_text("fn main() {\n    \"π😀\";\n}\n", 17..20);
+        assert_eq!(text.len_utf16(), 25);
+        assert_eq!(text.selected_text(), "π😀");
+        assert_eq!(text.line_for_offset_utf16(0), 0);
+        assert_eq!(text.line_for_offset_utf16(12), 1);
+        assert_eq!(text.line_for_offset_utf16(23), 2);
+        assert_eq!(text.line_for_offset_utf16(25), 3);
+
+        assert_eq!(text.range_for_line_utf16(0), Some(0..12));
+        assert_eq!(text.range_for_line_utf16(1), Some(12..23));
+        assert_eq!(text.range_for_line_utf16(2), Some(23..25));
+        assert_eq!(text.range_for_line_utf16(3), Some(25..25));
+        assert_eq!(text.range_for_line_utf16(4), None);
+        assert_eq!(text.text_for_range_utf16(12..23), Some("    \"π😀\";\n"));
+        assert_eq!(text.text_for_range_utf16(20..30), None);
+
+        let text = accessible_text("", 0..0);
+        assert_eq!(text.range_for_line_utf16(0), Some(0..0));
+        assert_eq!(text.selected_text(), "");
+    }
+}
@@ -254,6 +254,7 @@ pub struct AppContext {
     pub(crate) propagate_event: bool,
     pub(crate) prompt_builder: Option<PromptBuilder>,
     pub(crate) time_to_first_window_draw: Option<TimeToFirstWindowDraw>,
+    reduce_motion: bool,
 }
 
 impl AppContext {
@@ -308,6 +309,7 @@ impl AppContext {
                 propagate_event: true,
                 prompt_builder: Some(PromptBuilder::Default),
                 time_to_first_window_draw: None,
+                reduce_motion: false,
             }),
         });
 
@@ -685,6 +687,20 @@ impl AppContext {
         self.http_client.clone()
     }
 
+    /// Sets whether animations should be skipped, for people who are sensitive to motion.
+    /// Animations then render in their final state, or in their first state if they repeat.
+    pub fn set_reduce_motion(&mut self, reduce_motion: bool) {
+        if self.reduce_motion != reduce_motion {
+            self.reduce_motion = reduce_motion;
+            self.refresh();
+        }
+    }
+
+    /// Returns whether animations should be skipped. See [`Self::set_reduce_motion`].
+    pub fn reduce_motion(&self) -> bool {
+        self.reduce_motion
+    }
+
     /// Returns the SVG renderer GPUI uses
     pub(crate) fn svg_renderer(&self) -> SvgRenderer {
         self.svg_renderer.clone()
@@ -1283,6 +1299,21 @@ impl AppContext {
         self.loading_assets.remove(&asset_id);
     }
 
+    /// How many assets are in GPUI's cache
+    pub fn cached_asset_count(&self) -> usize {
+        self.loading_assets.len()
+    }
+
+    /// The decoded size, in bytes, of the images in GPUI's cache
+    pub fn cached_image_bytes(&self) -> usize {
+        crate::elements::cached_image_bytes(self)
+    }
+
+    /// Remove every asset from GPUI's cache, so that they're loaded again when they're next used
+    pub fn clear_cached_assets(&mut self) {
+        self.loading_assets.clear();
+    }
+
     /// Asynchronously load an asset, if the asset hasn't finished loading this will return None.
     ///
     /// Note that the multiple calls to this method will only result in one `Asset::load` call at a
@@ -2,15 +2,16 @@ use crate::{
     px, AbsoluteLength, AppContext, Asset, Bounds, DefiniteLength, Element, ElementId,
     GlobalElementId, Hitbox, Image, InteractiveElement, Interactivity, IntoElement, LayoutId,
     Length, ObjectFit, Pixels, RenderImage, SharedString, SharedUri, Size, StyleRefinement, Styled,
-    SvgSize, UriOrPath, WindowContext,
+    SvgSize, Task, UriOrPath, WindowContext,
 };
-use futures::{AsyncReadExt, Future};
+use futures::{future::Shared, AsyncReadExt, Future};
 use http_client;
 use image::{
     codecs::gif::GifDecoder, AnimationDecoder, Frame, ImageBuffer, ImageError, ImageFormat,
 };
 use smallvec::SmallVec;
 use std::{
+    any::TypeId,
     fs,
     io::Cursor,
     path::PathBuf,
@@ -311,6 +312,35 @@ impl ImageSource {
     }
 }
 
+/// The decoded size, in bytes, of the images in GPUI's asset cache.
+pub(crate) fn cached_image_bytes(cx: &AppContext) -> usize {
+    fn loaded_image<A: Asset<Output = Result<Arc<RenderImage>, E>>, E: 'static>(
+        task: &dyn std::any::Any,
+    ) -> Option<Arc<RenderImage>> {
+        let task = task.downcast_ref::<Shared<Task<A::Output>>>()?;
+        task.peek()?.as_ref().ok().cloned()
+    }
+
+    cx.loading_assets
+        .iter()
+        .filter_map(|((type_id, _), task)| {
+            if *type_id == TypeId::of::<ImageAsset>() {
+                loaded_image::<ImageAsset, _>(task.as_ref())
+            } else if *type_id == TypeId::of::<ImageDecoder>() {
+                loaded_image::<ImageDecoder, _>(task.as_ref())
+            } else {
+                None
+            }
+        })
+        .map(|image| {
+            (0..image.frame_count())
+                .filter_map(|frame_index| image.as_bytes(frame_index))
+                .map(<[u8]>::len)
+                .sum::<usize>()
+        })
+        .sum()
+}
+
 #[derive(Clone)]
 enum ImageDecoder {}
 
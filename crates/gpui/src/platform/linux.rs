@@ -1,3 +1,4 @@
+mod atspi;
 mod dispatcher;
 mod headless;
 mod platform;
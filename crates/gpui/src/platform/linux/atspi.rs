@@ -0,0 +1,762 @@
+//! Exposes the focused text to screen readers, such as Orca, through AT-SPI
+//!
+//! The application and the focused text are served as accessible objects on the
+//! accessibility bus with [zbus], and the registry is told about them, so that
+//! screen readers can read lines and follow the cursor.
+
+use std::{cell::Cell, collections::HashMap, ops::Range, rc::Rc, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use parking_lot::Mutex;
+use smol::channel::{Receiver, Sender};
+use zbus::{
+    names::BusName,
+    zvariant::{ObjectPath, OwnedObjectPath, Value},
+};
+
+use crate::{AccessibilityRole, AccessibleText, BackgroundExecutor, WindowId};
+
+const ROOT_PATH: &str = "/org/a11y/atspi/accessible/root";
+const TEXT_PATH: &str = "/org/a11y/atspi/accessible/text";
+const NULL_PATH: &str = "/org/a11y/atspi/null";
+
+const ACCESSIBLE_INTERFACE: &str = "org.a11y.atspi.Accessible";
+const APPLICATION_INTERFACE: &str = "org.a11y.atspi.Application";
+const TEXT_INTERFACE: &str = "org.a11y.atspi.Text";
+
+// The AT-SPI roles of the objects.
+const ROLE_APPLICATION: u32 = 75;
+const ROLE_TEXT: u32 = 61;
+const ROLE_ENTRY: u32 = 79;
+
+// The AT-SPI states of the focused text.
+const STATE_EDITABLE: u32 = 7;
+const STATE_ENABLED: u32 = 8;
+const STATE_FOCUSABLE: u32 = 11;
+const STATE_FOCUSED: u32 = 12;
+const STATE_MULTI_LINE: u32 = 17;
+const STATE_SENSITIVE: u32 = 24;
+const STATE_SHOWING: u32 = 25;
+const STATE_SINGLE_LINE: u32 = 26;
+const STATE_VISIBLE: u32 = 30;
+const STATE_SELECTABLE_TEXT: u32 = 38;
+
+/// Sends the focused text of each window to the accessibility bus. Nothing is
+/// connected to until some text is focused, so that headless applications and
+/// applications without text don't talk to the bus at all.
+#[derive(Clone)]
+pub(crate) struct AtSpiBridge {
+    executor: BackgroundExecutor,
+    updates_tx: Sender<(WindowId, Option<AccessibleText>)>,
+    updates_rx: Receiver<(WindowId, Option<AccessibleText>)>,
+    started: Rc<Cell<bool>>,
+}
+
+impl AtSpiBridge {
+    pub fn new(executor: BackgroundExecutor) -> Self {
+        let (updates_tx, updates_rx) = smol::channel::unbounded();
+        Self {
+            executor,
+            updates_tx,
+            updates_rx,
+            started: Rc::new(Cell::new(false)),
+        }
+    }
+
+    /// Exposes the focused text of a window, or hides it when there's none.
+    pub fn update_text(&self, window_id: WindowId, text: Option<AccessibleText>) {
+        if !self.started.get() {
+            if text.is_none() {
+                return;
+            }
+            self.started.set(true);
+            let updates_rx = self.updates_rx.clone();
+            self.executor
+                .spawn(async move {
+                    if let Err(error) = run(updates_rx).await {
+                        log::info!("screen readers won't see the focused text: {error:#}");
+                    }
+                })
+                .detach();
+        }
+        // The bridge stops when the accessibility bus can't be reached, and
+        // the updates are dropped along with the receiver.
+        self.updates_tx.try_send((window_id, text)).ok();
+    }
+}
+
+/// The texts exposed by the windows, of which the one updated last is focused.
+#[derive(Default)]
+struct AtSpiState {
+    bus_name: String,
+    desktop: Option<(String, OwnedObjectPath)>,
+    texts: Vec<(WindowId, AccessibleText)>,
+}
+
+impl AtSpiState {
+    fn focused_text(&self) -> Option<&AccessibleText> {
+        self.texts.last().map(|(_, text)| text)
+    }
+
+    fn set_text(&mut self, window_id: WindowId, text: Option<AccessibleText>) {
+        self.texts.retain(|(id, _)| *id != window_id);
+        if let Some(text) = text {
+            self.texts.push((window_id, text));
+        }
+    }
+
+    fn object_ref(&self, path: &'static str) -> (String, OwnedObjectPath) {
+        (self.bus_name.clone(), object_path(path))
+    }
+}
+
+async fn run(updates_rx: Receiver<(WindowId, Option<AccessibleText>)>) -> Result<()> {
+    let session = zbus::Connection::session().await?;
+    let address: String = session
+        .call_method(
+            Some("org.a11y.Bus"),
+            "/org/a11y/bus",
+            Some("org.a11y.Bus"),
+            "GetAddress",
+            &(),
+        )
+        .await
+        .context("failed to find the accessibility bus")?
+        .body()
+        .deserialize()?;
+
+    let state = Arc::new(Mutex::new(AtSpiState::default()));
+    let connection = zbus::connection::Builder::address(address.as_str())?
+        .serve_at(ROOT_PATH, AccessibleNode::new(Node::Application, &state))?
+        .serve_at(ROOT_PATH, ApplicationNode { id: 0 })?
+        .serve_at(TEXT_PATH, AccessibleNode::new(Node::Text, &state))?
+        .serve_at(TEXT_PATH, TextNode(state.clone()))?
+        .build()
+        .await
+        .context("failed to connect to the accessibility bus")?;
+    let bus_name = connection
+        .unique_name()
+        .context("no name on the accessibility bus")?
+        .to_string();
+    state.lock().bus_name = bus_name.clone();
+
+    let desktop: (String, OwnedObjectPath) = connection
+        .call_method(
+            Some("org.a11y.atspi.Registry"),
+            ROOT_PATH,
+            Some("org.a11y.atspi.Socket"),
+            "Embed",
+            &((bus_name, ObjectPath::try_from(ROOT_PATH)?),),
+        )
+        .await
+        .context("failed to register with the accessibility registry")?
+        .body()
+        .deserialize()?;
+    state.lock().desktop = Some(desktop);
+
+    while let Ok((window_id, text)) = updates_rx.recv().await {
+        let (previous_text, focused_text) = {
+            let mut state = state.lock();
+            let previous_text = state.focused_text().cloned();
+            state.set_text(window_id, text);
+            (previous_text, state.focused_text().cloned())
+        };
+        for event in events_between(previous_text.as_ref(), focused_text.as_ref()) {
+            let properties = HashMap::<String, Value>::default();
+            connection
+                .emit_signal(
+                    None::<BusName>,
+                    TEXT_PATH,
+                    event.interface,
+                    event.member,
+                    &(
+                        event.kind,
+                        event.detail1,
+                        event.detail2,
+                        Value::from(event.any_data),
+                        properties,
+                    ),
+                )
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// An event that tells screen readers how the focused text changed.
+#[derive(Debug, PartialEq)]
+struct AtSpiEvent {
+    interface: &'static str,
+    member: &'static str,
+    kind: &'static str,
+    detail1: i32,
+    detail2: i32,
+    any_data: String,
+}
+
+impl AtSpiEvent {
+    fn object(member: &'static str, kind: &'static str, detail1: usize, detail2: usize) -> Self {
+        Self {
+            interface: "org.a11y.atspi.Event.Object",
+            member,
+            kind,
+            detail1: detail1 as i32,
+            detail2: detail2 as i32,
+            any_data: String::new(),
+        }
+    }
+}
+
+fn events_between(
+    previous: Option<&AccessibleText>,
+    current: Option<&AccessibleText>,
+) -> Vec<AtSpiEvent> {
+    let Some(current) = current else {
+        return previous
+            .map(|_| AtSpiEvent::object("StateChanged", "focused", 0, 0))
+            .into_iter()
+            .collect();
+    };
+    let caret_moved = AtSpiEvent::object("TextCaretMoved", "", caret_offset(current), 0);
+
+    let previous = match previous {
+        Some(previous) if previous.role == current.role && previous.label == current.label => {
+            previous
+        }
+        // Another text was focused.
+        _ => {
+            return vec![
+                AtSpiEvent::object("StateChanged", "focused", 1, 0),
+                AtSpiEvent {
+                    interface: "org.a11y.atspi.Event.Focus",
+                    member: "Focus",
+                    ..AtSpiEvent::object("", "", 0, 0)
+                },
+                caret_moved,
+            ];
+        }
+    };
+
+    let mut events = Vec::new();
+    if previous.text != current.text {
+        let (range, removed, inserted) = text_change(&previous.text, &current.text);
+        if !removed.is_empty() {
+            events.push(AtSpiEvent {
+                any_data: removed,
+                ..AtSpiEvent::object("TextChanged", "delete", range.start, range.len())
+            });
+        }
+        if !inserted.is_empty() {
+            let len = inserted.chars().count();
+            events.push(AtSpiEvent {
+                any_data: inserted,
+                ..AtSpiEvent::object("TextChanged", "insert", range.start, len)
+            });
+        }
+    }
+    if previous.text != current.text
+        || previous.selected_range_utf16 != current.selected_range_utf16
+    {
+        events.push(caret_moved);
+    }
+    if previous.selected_range_utf16 != current.selected_range_utf16
+        && !(previous.selected_range_utf16.is_empty() && current.selected_range_utf16.is_empty())
+    {
+        events.push(AtSpiEvent::object("TextSelectionChanged", "", 0, 0));
+    }
+    events
+}
+
+/// The characters of the previous text that were replaced, with the text that
+/// was removed and the text that was inserted in their place.
+fn text_change(previous: &str, current: &str) -> (Range<usize>, String, String) {
+    let previous = previous.chars().collect::<Vec<_>>();
+    let current = current.chars().collect::<Vec<_>>();
+    let prefix_len = previous
+        .iter()
+        .zip(&current)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix_len = previous[prefix_len..]
+        .iter()
+        .rev()
+        .zip(current[prefix_len..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let removed = &previous[prefix_len..previous.len() - suffix_len];
+    let inserted = &current[prefix_len..current.len() - suffix_len];
+    (
+        prefix_len..prefix_len + removed.len(),
+        removed.iter().collect(),
+        inserted.iter().collect(),
+    )
+}
+
+/// AT-SPI counts offsets in characters, where the accessible text counts them
+/// in UTF-16 code units.
+fn char_offset(text: &str, offset_utf16: usize) -> usize {
+    let mut utf16_count = 0;
+    text.chars()
+        .take_while(|c| {
+            utf16_count += c.len_utf16();
+            utf16_count <= offset_utf16
+        })
+        .count()
+}
+
+fn caret_offset(text: &AccessibleText) -> usize {
+    char_offset(&text.text, text.selected_range_utf16.end)
+}
+
+/// How much text around an offset is read, as AT-SPI's text granularities.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Granularity {
+    Char,
+    Word,
+    Line,
+}
+
+impl Granularity {
+    fn from_granularity(granularity: u32) -> Self {
+        match granularity {
+            0 => Self::Char,
+            1 => Self::Word,
+            // Sentences and paragraphs are read as lines.
+            _ => Self::Line,
+        }
+    }
+
+    fn from_boundary_type(boundary_type: u32) -> Self {
+        match boundary_type {
+            0 => Self::Char,
+            1 | 2 => Self::Word,
+            _ => Self::Line,
+        }
+    }
+}
+
+/// The range of the characters of the given granularity around an offset, with
+/// their text. Lines include their newline.
+fn text_at_offset(text: &str, offset: usize, granularity: Granularity) -> (String, Range<usize>) {
+    let chars = text.chars().collect::<Vec<_>>();
+    let offset = offset.min(chars.len());
+    let is_word_char = |c: &char| c.is_alphanumeric() || *c == '_';
+    let range = match granularity {
+        Granularity::Char => offset..(offset + 1).min(chars.len()),
+        Granularity::Word if chars.get(offset).map_or(false, is_word_char) => {
+            let start = chars[..offset]
+                .iter()
+                .rposition(|c| !is_word_char(c))
+                .map_or(0, |ix| ix + 1);
+            let end = chars[offset..]
+                .iter()
+                .position(|c| !is_word_char(c))
+                .map_or(chars.len(), |ix| offset + ix);
+            start..end
+        }
+        Granularity::Word => offset..(offset + 1).min(chars.len()),
+        Granularity::Line => {
+            let start = chars[..offset]
+                .iter()
+                .rposition(|c| *c == '\n')
+                .map_or(0, |ix| ix + 1);
+            let end = chars[offset..]
+                .iter()
+                .position(|c| *c == '\n')
+                .map_or(chars.len(), |ix| offset + ix + 1);
+            start..end
+        }
+    };
+    (chars[range.clone()].iter().collect(), range)
+}
+
+fn object_path(path: &'static str) -> OwnedObjectPath {
+    ObjectPath::from_static_str_unchecked(path).into()
+}
+
+/// The state set of an object, as two 32-bit bitfields.
+fn state_set(states: &[u32]) -> Vec<u32> {
+    let mut set = vec![0; 2];
+    for state in states {
+        set[(state / 32) as usize] |= 1 << (state % 32);
+    }
+    set
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Node {
+    Application,
+    Text,
+}
+
+struct AccessibleNode {
+    node: Node,
+    state: Arc<Mutex<AtSpiState>>,
+    app_name: String,
+}
+
+impl AccessibleNode {
+    fn new(node: Node, state: &Arc<Mutex<AtSpiState>>) -> Self {
+        let app_name = std::env::current_exe()
+            .ok()
+            .and_then(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+            .unwrap_or_default();
+        Self {
+            node,
+            state: state.clone(),
+            app_name,
+        }
+    }
+
+    fn children(&self) -> Vec<(String, OwnedObjectPath)> {
+        let state = self.state.lock();
+        match self.node {
+            Node::Application if state.focused_text().is_some() => {
+                vec![state.object_ref(TEXT_PATH)]
+            }
+            Node::Application | Node::Text => Vec::new(),
+        }
+    }
+}
+
+#[zbus::interface(name = "org.a11y.atspi.Accessible")]
+impl AccessibleNode {
+    #[zbus(property)]
+    fn name(&self) -> String {
+        match self.node {
+            Node::Application => self.app_name.clone(),
+            Node::Text => self
+                .state
+                .lock()
+                .focused_text()
+                .map(|text| text.label.to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    #[zbus(property)]
+    fn description(&self) -> String {
+        String::new()
+    }
+
+    #[zbus(property)]
+    fn parent(&self) -> (String, OwnedObjectPath) {
+        let state = self.state.lock();
+        match self.node {
+            Node::Application => state
+                .desktop
+                .clone()
+                .unwrap_or_else(|| (String::new(), object_path(NULL_PATH))),
+            Node::Text => state.object_ref(ROOT_PATH),
+        }
+    }
+
+    #[zbus(property)]
+    fn child_count(&self) -> i32 {
+        self.children().len() as i32
+    }
+
+    #[zbus(property)]
+    fn locale(&self) -> String {
+        String::new()
+    }
+
+    #[zbus(property)]
+    fn accessible_id(&self) -> String {
+        String::new()
+    }
+
+    fn get_child_at_index(&self, index: i32) -> (String, OwnedObjectPath) {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.children().into_iter().nth(index))
+            .unwrap_or_else(|| (String::new(), object_path(NULL_PATH)))
+    }
+
+    fn get_children(&self) -> Vec<(String, OwnedObjectPath)> {
+        self.children()
+    }
+
+    fn get_index_in_parent(&self) -> i32 {
+        match self.node {
+            Node::Application => -1,
+            Node::Text => 0,
+        }
+    }
+
+    fn get_relation_set(&self) -> Vec<(u32, Vec<(String, OwnedObjectPath)>)> {
+        Vec::new()
+    }
+
+    fn get_role(&self) -> u32 {
+        match self.node {
+            Node::Application => ROLE_APPLICATION,
+            Node::Text => match self.state.lock().focused_text().map(|text| text.role) {
+                Some(AccessibilityRole::TextField) => ROLE_ENTRY,
+                Some(AccessibilityRole::TextArea) | None => ROLE_TEXT,
+            },
+        }
+    }
+
+    fn get_role_name(&self) -> String {
+        match self.get_role() {
+            ROLE_APPLICATION => "application",
+            ROLE_ENTRY => "entry",
+            _ => "text",
+        }
+        .to_string()
+    }
+
+    fn get_localized_role_name(&self) -> String {
+        self.get_role_name()
+    }
+
+    fn get_state(&self) -> Vec<u32> {
+        let state = self.state.lock();
+        match (self.node, state.focused_text()) {
+            (Node::Application, _) | (Node::Text, None) => state_set(&[]),
+            (Node::Text, Some(text)) => state_set(&[
+                STATE_EDITABLE,
+                STATE_ENABLED,
+                STATE_FOCUSABLE,
+                STATE_FOCUSED,
+                if text.role == AccessibilityRole::TextField {
+                    STATE_SINGLE_LINE
+                } else {
+                    STATE_MULTI_LINE
+                },
+                STATE_SENSITIVE,
+                STATE_SHOWING,
+                STATE_VISIBLE,
+                STATE_SELECTABLE_TEXT,
+            ]),
+        }
+    }
+
+    fn get_attributes(&self) -> HashMap<String, String> {
+        HashMap::default()
+    }
+
+    fn get_application(&self) -> (String, OwnedObjectPath) {
+        self.state.lock().object_ref(ROOT_PATH)
+    }
+
+    fn get_interfaces(&self) -> Vec<String> {
+        let interface = match self.node {
+            Node::Application => APPLICATION_INTERFACE,
+            Node::Text => TEXT_INTERFACE,
+        };
+        vec![ACCESSIBLE_INTERFACE.to_string(), interface.to_string()]
+    }
+}
+
+struct ApplicationNode {
+    id: i32,
+}
+
+#[zbus::interface(name = "org.a11y.atspi.Application")]
+impl ApplicationNode {
+    #[zbus(property)]
+    fn toolkit_name(&self) -> String {
+        "GPUI".to_string()
+    }
+
+    #[zbus(property)]
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    #[zbus(property)]
+    fn atspi_version(&self) -> String {
+        "2.1".to_string()
+    }
+
+    #[zbus(property)]
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    #[zbus(property)]
+    fn set_id(&mut self, id: i32) {
+        self.id = id;
+    }
+
+    fn get_locale(&self, _lctype: u32) -> String {
+        String::new()
+    }
+}
+
+struct TextNode(Arc<Mutex<AtSpiState>>);
+
+impl TextNode {
+    fn with_text<R: Default>(&self, f: impl FnOnce(&AccessibleText) -> R) -> R {
+        self.0.lock().focused_text().map(f).unwrap_or_default()
+    }
+}
+
+#[zbus::interface(name = "org.a11y.atspi.Text")]
+impl TextNode {
+    #[zbus(property)]
+    fn character_count(&self) -> i32 {
+        self.with_text(|text| text.text.chars().count() as i32)
+    }
+
+    #[zbus(property)]
+    fn caret_offset(&self) -> i32 {
+        self.with_text(|text| caret_offset(text) as i32)
+    }
+
+    fn get_text(&self, start_offset: i32, end_offset: i32) -> String {
+        self.with_text(|text| {
+            let start = start_offset.max(0) as usize;
+            // An end offset of -1 is the end of the text.
+            let end = usize::try_from(end_offset).unwrap_or(usize::MAX);
+            text.text
+                .chars()
+                .skip(start)
+                .take(end.saturating_sub(start))
+                .collect()
+        })
+    }
+
+    fn set_caret_offset(&self, _offset: i32) -> bool {
+        false
+    }
+
+    fn get_string_at_offset(&self, offset: i32, granularity: u32) -> (String, i32, i32) {
+        self.text_at_offset(offset, Granularity::from_granularity(granularity))
+    }
+
+    fn get_text_at_offset(&self, offset: i32, boundary_type: u32) -> (String, i32, i32) {
+        self.text_at_offset(offset, Granularity::from_boundary_type(boundary_type))
+    }
+
+    fn get_character_at_offset(&self, offset: i32) -> i32 {
+        self.with_text(|text| {
+            text.text
+                .chars()
+                .nth(offset.max(0) as usize)
+                .map_or(0, |c| c as i32)
+        })
+    }
+
+    fn get_n_selections(&self) -> i32 {
+        self.with_text(|text| !text.selected_range_utf16.is_empty() as i32)
+    }
+
+    fn get_selection(&self, _selection_num: i32) -> (i32, i32) {
+        self.with_text(|text| {
+            let range = &text.selected_range_utf16;
+            (
+                char_offset(&text.text, range.start) as i32,
+                char_offset(&text.text, range.end) as i32,
+            )
+        })
+    }
+
+    fn add_selection(&self, _start_offset: i32, _end_offset: i32) -> bool {
+        false
+    }
+
+    fn remove_selection(&self, _selection_num: i32) -> bool {
+        false
+    }
+
+    fn set_selection(&self, _selection_num: i32, _start_offset: i32, _end_offset: i32) -> bool {
+        false
+    }
+
+    fn get_attribute_run(
+        &self,
+        _offset: i32,
+        _include_defaults: bool,
+    ) -> (HashMap<String, String>, i32, i32) {
+        let len = self.character_count();
+        (HashMap::default(), 0, len)
+    }
+
+    fn get_default_attributes(&self) -> HashMap<String, String> {
+        HashMap::default()
+    }
+}
+
+impl TextNode {
+    fn text_at_offset(&self, offset: i32, granularity: Granularity) -> (String, i32, i32) {
+        self.with_text(|text| {
+            let (text, range) = text_at_offset(&text.text, offset.max(0) as usize, granularity);
+            (text, range.start as i32, range.end as i32)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accessible_text(text: &'static str, selected_range_utf16: Range<usize>) -> AccessibleText {
+        AccessibleText {
+            role: AccessibilityRole::TextArea,
+            label: "main.rs".into(),
+            text: text.into(),
+            selected_range_utf16,
+        }
+    }
+
+    #[test]
+    fn test_text_at_offset() {
+        let text = "fn main() {\n    \"π😀\";\n}\n";
+        assert_eq!(char_offset(text, 20), 19);
+        assert_eq!(
+            text_at_offset(text, 14, Granularity::Line),
+            ("    \"π😀\";\n".to_string(), 12..22)
+        );
+        assert_eq!(
+            text_at_offset(text, 4, Granularity::Word),
+            ("main".to_string(), 3..7)
+        );
+        assert_eq!(
+            text_at_offset(text, 17, Granularity::Char),
+            ("π".to_string(), 17..18)
+        );
+        assert_eq!(
+            text_at_offset(text, 24, Granularity::Line),
+            (String::new(), 24..24)
+        );
+    }
+
+    #[test]
+    fn test_events_between() {
+        let before = accessible_text("let a = 1;\n", 4..4);
+        let focused = events_between(None, Some(&before));
+        assert_eq!(
+            focused.iter().map(|event| event.member).collect::<Vec<_>>(),
+            ["StateChanged", "Focus", "TextCaretMoved"]
+        );
+
+        let after = accessible_text("let ab = 1;\n", 5..5);
+        assert_eq!(
+            events_between(Some(&before), Some(&after)),
+            [
+                AtSpiEvent {
+                    any_data: "b".to_string(),
+                    ..AtSpiEvent::object("TextChanged", "insert", 5, 1)
+                },
+                AtSpiEvent::object("TextCaretMoved", "", 5, 0),
+            ]
+        );
+
+        let selected = accessible_text("let ab = 1;\n", 4..6);
+        assert_eq!(
+            events_between(Some(&after), Some(&selected)),
+            [
+                AtSpiEvent::object("TextCaretMoved", "", 6, 0),
+                AtSpiEvent::object("TextSelectionChanged", "", 0, 0),
+            ]
+        );
+
+        assert_eq!(
+            events_between(Some(&selected), None),
+            [AtSpiEvent::object("StateChanged", "focused", 0, 0)]
+        );
+    }
+}
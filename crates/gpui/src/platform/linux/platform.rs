@@ -34,6 +34,7 @@ use wayland_client::Connection;
 use wayland_protocols::wp::cursor_shape::v1::client::wp_cursor_shape_device_v1::Shape;
 use xkbcommon::xkb::{self, Keycode, Keysym, State};
 
+use crate::platform::linux::atspi::AtSpiBridge;
 use crate::platform::linux::wayland::WaylandClient;
 use crate::{
     px, Action, AnyWindowHandle, BackgroundExecutor, ClipboardItem, CosmicTextSystem, CursorStyle,
@@ -99,6 +100,7 @@ pub(crate) struct LinuxCommon {
     pub(crate) callbacks: PlatformHandlers,
     pub(crate) signal: LoopSignal,
     pub(crate) menus: Vec<OwnedMenu>,
+    pub(crate) accessibility: AtSpiBridge,
 }
 
 impl LinuxCommon {
@@ -112,6 +114,7 @@ impl LinuxCommon {
         let background_executor = BackgroundExecutor::new(dispatcher.clone());
 
         let common = LinuxCommon {
+            accessibility: AtSpiBridge::new(background_executor.clone()),
             background_executor,
             foreground_executor: ForegroundExecutor::new(dispatcher.clone()),
             text_system,
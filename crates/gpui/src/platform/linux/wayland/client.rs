@@ -626,6 +626,7 @@ impl LinuxClient for WaylandClient {
             WaylandClientStatePtr(Rc::downgrade(&self.0)),
             params,
             state.common.appearance,
+            state.common.accessibility.clone(),
         )?;
         state.windows.insert(surface_id, window.0.clone());
 
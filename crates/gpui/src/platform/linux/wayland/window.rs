@@ -20,13 +20,14 @@ use wayland_protocols::xdg::shell::client::xdg_toplevel::{self};
 use wayland_protocols_plasma::blur::client::org_kde_kwin_blur;
 
 use crate::platform::blade::{BladeRenderer, BladeSurfaceConfig};
+use crate::platform::linux::atspi::AtSpiBridge;
 use crate::platform::linux::wayland::display::WaylandDisplay;
 use crate::platform::linux::wayland::serial::SerialKind;
 use crate::platform::{PlatformAtlas, PlatformInputHandler, PlatformWindow};
 use crate::scene::Scene;
 use crate::{
-    px, size, AnyWindowHandle, Bounds, Decorations, GPUSpecs, Globals, Modifiers, Output, Pixels,
-    PlatformDisplay, PlatformInput, Point, PromptLevel, ResizeEdge, Size, Tiling,
+    px, size, AccessibleText, AnyWindowHandle, Bounds, Decorations, GPUSpecs, Globals, Modifiers,
+    Output, Pixels, PlatformDisplay, PlatformInput, Point, PromptLevel, ResizeEdge, Size, Tiling,
     WaylandClientStatePtr, WindowAppearance, WindowBackgroundAppearance, WindowBounds,
     WindowControls, WindowDecorations, WindowParams,
 };
@@ -103,6 +104,7 @@ pub struct WaylandWindowState {
     in_progress_window_controls: Option<WindowControls>,
     window_controls: WindowControls,
     inset: Option<Pixels>,
+    accessibility: AtSpiBridge,
 }
 
 #[derive(Clone)]
@@ -124,6 +126,7 @@ impl WaylandWindowState {
         client: WaylandClientStatePtr,
         globals: Globals,
         options: WindowParams,
+        accessibility: AtSpiBridge,
     ) -> anyhow::Result<Self> {
         let raw = RawWindow {
             window: surface.id().as_ptr().cast::<c_void>(),
@@ -187,6 +190,7 @@ impl WaylandWindowState {
             in_progress_window_controls: None,
             window_controls: WindowControls::default(),
             inset: None,
+            accessibility,
         })
     }
 
@@ -211,6 +215,9 @@ impl Drop for WaylandWindow {
         let client = state.client.clone();
 
         state.renderer.destroy();
+        state
+            .accessibility
+            .update_text(state.handle.window_id(), None);
         if let Some(decoration) = &state.decoration {
             decoration.destroy();
         }
@@ -252,6 +259,7 @@ impl WaylandWindow {
         client: WaylandClientStatePtr,
         params: WindowParams,
         appearance: WindowAppearance,
+        accessibility: AtSpiBridge,
     ) -> anyhow::Result<(Self, ObjectId)> {
         let surface = globals.compositor.create_surface(&globals.qh, ());
         let xdg_surface = globals
@@ -292,6 +300,7 @@ impl WaylandWindow {
                 client,
                 globals,
                 params,
+                accessibility,
             )?)),
             callbacks: Rc::new(RefCell::new(Callbacks::default())),
         });
@@ -1017,6 +1026,13 @@ impl PlatformWindow for WaylandWindow {
         client.update_ime_position(bounds);
     }
 
+    fn update_accessible_text(&self, text: Option<AccessibleText>) {
+        let state = self.borrow();
+        state
+            .accessibility
+            .update_text(state.handle.window_id(), text);
+    }
+
     fn gpu_specs(&self) -> Option<GPUSpecs> {
         self.borrow().renderer.gpu_specs().into()
     }
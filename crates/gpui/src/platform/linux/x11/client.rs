@@ -1178,6 +1178,7 @@ impl LinuxClient for X11Client {
             &state.atoms,
             state.scale_factor,
             state.common.appearance,
+            state.common.accessibility.clone(),
         )?;
 
         let screen_resources = state
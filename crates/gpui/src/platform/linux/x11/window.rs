@@ -1,10 +1,13 @@
 use crate::{
-    platform::blade::{BladeRenderer, BladeSurfaceConfig},
-    px, size, AnyWindowHandle, Bounds, Decorations, DevicePixels, ForegroundExecutor, GPUSpecs,
-    Modifiers, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler,
-    PlatformWindow, Point, PromptLevel, ResizeEdge, Scene, Size, Tiling, WindowAppearance,
-    WindowBackgroundAppearance, WindowBounds, WindowDecorations, WindowKind, WindowParams,
-    X11ClientStatePtr,
+    platform::{
+        blade::{BladeRenderer, BladeSurfaceConfig},
+        linux::atspi::AtSpiBridge,
+    },
+    px, size, AccessibleText, AnyWindowHandle, Bounds, Decorations, DevicePixels,
+    ForegroundExecutor, GPUSpecs, Modifiers, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
+    PlatformInputHandler, PlatformWindow, Point, PromptLevel, ResizeEdge, Scene, Size, Tiling,
+    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowDecorations, WindowKind,
+    WindowParams, X11ClientStatePtr,
 };
 
 use anyhow::Context;
@@ -248,6 +251,7 @@ pub struct X11WindowState {
     edge_constraints: Option<EdgeConstraints>,
     pub handle: AnyWindowHandle,
     last_insets: [u32; 4],
+    accessibility: AtSpiBridge,
 }
 
 impl X11WindowState {
@@ -305,6 +309,7 @@ impl X11WindowState {
         atoms: &XcbAtoms,
         scale_factor: f32,
         appearance: WindowAppearance,
+        accessibility: AtSpiBridge,
     ) -> anyhow::Result<Self> {
         let x_screen_index = params
             .display_id
@@ -535,6 +540,7 @@ impl X11WindowState {
             edge_constraints: None,
             counter_id: sync_request_counter,
             last_sync_counter: None,
+            accessibility,
         })
     }
 
@@ -553,6 +559,9 @@ impl Drop for X11Window {
     fn drop(&mut self) {
         let mut state = self.0.state.borrow_mut();
         state.renderer.destroy();
+        state
+            .accessibility
+            .update_text(state.handle.window_id(), None);
 
         let destroy_x_window = maybe!({
             self.0.xcb_connection.unmap_window(self.0.x_window)?;
@@ -604,6 +613,7 @@ impl X11Window {
         atoms: &XcbAtoms,
         scale_factor: f32,
         appearance: WindowAppearance,
+        accessibility: AtSpiBridge,
     ) -> anyhow::Result<Self> {
         let ptr = X11WindowStatePtr {
             state: Rc::new(RefCell::new(X11WindowState::new(
@@ -618,6 +628,7 @@ impl X11Window {
                 atoms,
                 scale_factor,
                 appearance,
+                accessibility,
             )?)),
             callbacks: Rc::new(RefCell::new(Callbacks::default())),
             xcb_connection: xcb_connection.clone(),
@@ -1403,6 +1414,13 @@ impl PlatformWindow for X11Window {
         client.update_ime_position(bounds);
     }
 
+    fn update_accessible_text(&self, text: Option<AccessibleText>) {
+        let state = self.0.state.borrow();
+        state
+            .accessibility
+            .update_text(state.handle.window_id(), text);
+    }
+
     fn gpu_specs(&self) -> Option<GPUSpecs> {
         self.0.state.borrow().renderer.gpu_specs().into()
     }
@@ -1,10 +1,11 @@
 use super::{ns_string, renderer, MacDisplay, NSRange, NSStringExt};
 use crate::{
-    platform::PlatformInputHandler, point, px, size, AnyWindowHandle, Bounds, DisplayLink,
-    ExternalPaths, FileDropEvent, ForegroundExecutor, KeyDownEvent, Keystroke, Modifiers,
-    ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels,
-    PlatformAtlas, PlatformDisplay, PlatformInput, PlatformWindow, Point, PromptLevel, Size, Timer,
-    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowKind, WindowParams,
+    platform::PlatformInputHandler, point, px, size, AccessibilityRole, AccessibleText,
+    AnyWindowHandle, Bounds, DisplayLink, ExternalPaths, FileDropEvent, ForegroundExecutor,
+    KeyDownEvent, Keystroke, Modifiers, ModifiersChangedEvent, MouseButton, MouseDownEvent,
+    MouseMoveEvent, MouseUpEvent, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
+    PlatformWindow, Point, PromptLevel, Size, Timer, WindowAppearance, WindowBackgroundAppearance,
+    WindowBounds, WindowKind, WindowParams,
 };
 use block::ConcreteBlock;
 use cocoa::{
@@ -226,6 +227,55 @@ unsafe fn build_classes() {
             accepts_first_mouse as extern "C" fn(&Object, Sel, id) -> BOOL,
         );
 
+        decl.add_method(
+            sel!(isAccessibilityElement),
+            is_accessibility_element as extern "C" fn(&Object, Sel) -> BOOL,
+        );
+        decl.add_method(
+            sel!(accessibilityRole),
+            accessibility_role as extern "C" fn(&Object, Sel) -> id,
+        );
+        decl.add_method(
+            sel!(accessibilityLabel),
+            accessibility_label as extern "C" fn(&Object, Sel) -> id,
+        );
+        decl.add_method(
+            sel!(accessibilityValue),
+            accessibility_value as extern "C" fn(&Object, Sel) -> id,
+        );
+        decl.add_method(
+            sel!(accessibilityNumberOfCharacters),
+            accessibility_number_of_characters as extern "C" fn(&Object, Sel) -> NSInteger,
+        );
+        decl.add_method(
+            sel!(accessibilitySelectedTextRange),
+            accessibility_selected_text_range as extern "C" fn(&Object, Sel) -> NSRange,
+        );
+        decl.add_method(
+            sel!(accessibilitySelectedText),
+            accessibility_selected_text as extern "C" fn(&Object, Sel) -> id,
+        );
+        decl.add_method(
+            sel!(accessibilityVisibleCharacterRange),
+            accessibility_visible_character_range as extern "C" fn(&Object, Sel) -> NSRange,
+        );
+        decl.add_method(
+            sel!(accessibilityInsertionPointLineNumber),
+            accessibility_insertion_point_line_number as extern "C" fn(&Object, Sel) -> NSInteger,
+        );
+        decl.add_method(
+            sel!(accessibilityLineForIndex:),
+            accessibility_line_for_index as extern "C" fn(&Object, Sel, NSInteger) -> NSInteger,
+        );
+        decl.add_method(
+            sel!(accessibilityRangeForLine:),
+            accessibility_range_for_line as extern "C" fn(&Object, Sel, NSInteger) -> NSRange,
+        );
+        decl.add_method(
+            sel!(accessibilityStringForRange:),
+            accessibility_string_for_range as extern "C" fn(&Object, Sel, NSRange) -> id,
+        );
+
         decl.register()
     };
 }
@@ -345,6 +395,8 @@ struct MacWindowState {
     first_mouse: bool,
     fullscreen_restore_bounds: Bounds<Pixels>,
     ime_composing: bool,
+    // The focused text that is exposed to assistive technologies.
+    accessible_text: Option<AccessibleText>,
 }
 
 impl MacWindowState {
@@ -625,6 +677,7 @@ impl MacWindow {
                 first_mouse: false,
                 fullscreen_restore_bounds: Bounds::default(),
                 ime_composing: false,
+                accessible_text: None,
             })));
 
             (*native_window).set_ivar(
@@ -1130,6 +1183,22 @@ impl PlatformWindow for MacWindow {
     fn fps(&self) -> Option<f32> {
         Some(self.0.lock().renderer.fps())
     }
+
+    fn update_accessible_text(&self, text: Option<AccessibleText>) {
+        let mut this = self.0.lock();
+        let value_changed = this.accessible_text.as_ref().map(|text| &text.text)
+            != text.as_ref().map(|text| &text.text);
+        this.accessible_text = text;
+        let native_view = this.native_view.as_ptr() as id;
+        drop(this);
+
+        unsafe {
+            if value_changed {
+                NSAccessibilityPostNotification(native_view, ns_string("AXValueChanged"));
+            }
+            NSAccessibilityPostNotification(native_view, ns_string("AXSelectedTextChanged"));
+        }
+    }
 }
 
 impl rwh::HasWindowHandle for MacWindow {
@@ -1805,6 +1874,90 @@ extern "C" fn attributed_substring_for_proposed_range(
 
 extern "C" fn do_command_by_selector(_: &Object, _: Sel, _: Sel) {}
 
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {
+    fn NSAccessibilityPostNotification(element: id, notification: id);
+}
+
+fn with_accessible_text<R>(view: &Object, f: impl FnOnce(&AccessibleText) -> R) -> Option<R> {
+    let state = unsafe { get_window_state(view) };
+    let lock = state.lock();
+    lock.accessible_text.as_ref().map(f)
+}
+
+extern "C" fn is_accessibility_element(this: &Object, _: Sel) -> BOOL {
+    if with_accessible_text(this, |_| ()).is_some() {
+        YES
+    } else {
+        NO
+    }
+}
+
+extern "C" fn accessibility_role(this: &Object, _: Sel) -> id {
+    let role = with_accessible_text(this, |text| match text.role {
+        AccessibilityRole::TextArea => "AXTextArea",
+        AccessibilityRole::TextField => "AXTextField",
+    });
+    unsafe { ns_string(role.unwrap_or("AXGroup")) }
+}
+
+extern "C" fn accessibility_label(this: &Object, _: Sel) -> id {
+    with_accessible_text(this, |text| unsafe { ns_string(&text.label) }).unwrap_or(nil)
+}
+
+extern "C" fn accessibility_value(this: &Object, _: Sel) -> id {
+    with_accessible_text(this, |text| unsafe { ns_string(&text.text) }).unwrap_or(nil)
+}
+
+extern "C" fn accessibility_number_of_characters(this: &Object, _: Sel) -> NSInteger {
+    with_accessible_text(this, |text| text.len_utf16() as NSInteger).unwrap_or(0)
+}
+
+extern "C" fn accessibility_selected_text_range(this: &Object, _: Sel) -> NSRange {
+    with_accessible_text(this, |text| text.selected_range_utf16.clone().into())
+        .unwrap_or(NSRange::invalid())
+}
+
+extern "C" fn accessibility_selected_text(this: &Object, _: Sel) -> id {
+    with_accessible_text(this, |text| unsafe { ns_string(text.selected_text()) }).unwrap_or(nil)
+}
+
+extern "C" fn accessibility_visible_character_range(this: &Object, _: Sel) -> NSRange {
+    with_accessible_text(this, |text| (0..text.len_utf16()).into()).unwrap_or(NSRange::invalid())
+}
+
+extern "C" fn accessibility_insertion_point_line_number(this: &Object, _: Sel) -> NSInteger {
+    with_accessible_text(this, |text| {
+        text.line_for_offset_utf16(text.selected_range_utf16.end) as NSInteger
+    })
+    .unwrap_or(0)
+}
+
+extern "C" fn accessibility_line_for_index(this: &Object, _: Sel, index: NSInteger) -> NSInteger {
+    with_accessible_text(this, |text| {
+        text.line_for_offset_utf16(index.max(0) as usize) as NSInteger
+    })
+    .unwrap_or(0)
+}
+
+extern "C" fn accessibility_range_for_line(this: &Object, _: Sel, line: NSInteger) -> NSRange {
+    with_accessible_text(this, |text| {
+        text.range_for_line_utf16(line.max(0) as usize)
+            .map_or(NSRange::invalid(), Into::into)
+    })
+    .unwrap_or(NSRange::invalid())
+}
+
+extern "C" fn accessibility_string_for_range(this: &Object, _: Sel, range: NSRange) -> id {
+    with_accessible_text(this, |text| {
+        let range = range.to_range()?;
+        let string = text.text_for_range_utf16(range)?;
+        Some(unsafe { ns_string(string) })
+    })
+    .flatten()
+    .unwrap_or(nil)
+}
+
 extern "C" fn view_did_change_effective_appearance(this: &Object, _: Sel) {
     unsafe {
         let state = get_window_state(this);
@@ -3,12 +3,14 @@ mod font_features;
 mod line;
 mod line_layout;
 mod line_wrapper;
+mod offscreen;
 
 pub use font_fallbacks::*;
 pub use font_features::*;
 pub use line::*;
 pub use line_layout::*;
 pub use line_wrapper::*;
+pub use offscreen::*;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -475,7 +477,7 @@ impl WindowTextSystem {
         Ok(lines)
     }
 
-    pub(crate) fn finish_frame(&self) {
+    pub(crate) fn finish_frame(&self) -> LineLayoutStats {
         self.line_layout_cache.finish_frame()
     }
 
@@ -6,7 +6,10 @@ use std::{
     borrow::Borrow,
     hash::{Hash, Hasher},
     ops::Range,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering::SeqCst},
+        Arc,
+    },
 };
 
 /// A laid out and styled line of text
@@ -336,9 +339,21 @@ impl WrappedLineLayout {
 pub(crate) struct LineLayoutCache {
     previous_frame: Mutex<FrameCache>,
     current_frame: RwLock<FrameCache>,
+    shaped_lines: AtomicUsize,
+    reused_lines: AtomicUsize,
     platform_text_system: Arc<dyn PlatformTextSystem>,
 }
 
+/// How many lines of text a frame laid out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LineLayoutStats {
+    /// The lines that were shaped by the platform's text system.
+    pub shaped_lines: usize,
+    /// The lines whose layout was taken from the cache, because they were laid out
+    /// in the previous frame or earlier in the same one.
+    pub reused_lines: usize,
+}
+
 #[derive(Default)]
 struct FrameCache {
     lines: FxHashMap<Arc<CacheKey>, Arc<LineLayout>>,
@@ -358,6 +373,8 @@ impl LineLayoutCache {
         Self {
             previous_frame: Mutex::default(),
             current_frame: RwLock::default(),
+            shaped_lines: AtomicUsize::new(0),
+            reused_lines: AtomicUsize::new(0),
             platform_text_system,
         }
     }
@@ -373,6 +390,11 @@ impl LineLayoutCache {
     pub fn reuse_layouts(&self, range: Range<LineLayoutIndex>) {
         let mut previous_frame = &mut *self.previous_frame.lock();
         let mut current_frame = &mut *self.current_frame.write();
+        self.reused_lines.fetch_add(
+            (range.end.lines_index - range.start.lines_index)
+                + (range.end.wrapped_lines_index - range.start.wrapped_lines_index),
+            SeqCst,
+        );
 
         for key in &previous_frame.used_lines[range.start.lines_index..range.end.lines_index] {
             if let Some((key, line)) = previous_frame.lines.remove_entry(key) {
@@ -399,7 +421,9 @@ impl LineLayoutCache {
             .truncate(index.wrapped_lines_index);
     }
 
-    pub fn finish_frame(&self) {
+    /// Moves the current frame's layouts into the previous frame, where they can be
+    /// reused, and returns how many lines the frame laid out.
+    pub fn finish_frame(&self) -> LineLayoutStats {
         let mut prev_frame = self.previous_frame.lock();
         let mut curr_frame = self.current_frame.write();
         std::mem::swap(&mut *prev_frame, &mut *curr_frame);
@@ -407,6 +431,10 @@ impl LineLayoutCache {
         curr_frame.wrapped_lines.clear();
         curr_frame.used_lines.clear();
         curr_frame.used_wrapped_lines.clear();
+        LineLayoutStats {
+            shaped_lines: self.shaped_lines.swap(0, SeqCst),
+            reused_lines: self.reused_lines.swap(0, SeqCst),
+        }
     }
 
     pub fn layout_wrapped_line(
@@ -425,11 +453,13 @@ impl LineLayoutCache {
 
         let current_frame = self.current_frame.upgradable_read();
         if let Some(layout) = current_frame.wrapped_lines.get(key) {
+            self.reused_lines.fetch_add(1, SeqCst);
             return layout.clone();
         }
 
         let previous_frame_entry = self.previous_frame.lock().wrapped_lines.remove_entry(key);
         if let Some((key, layout)) = previous_frame_entry {
+            self.reused_lines.fetch_add(1, SeqCst);
             let mut current_frame = RwLockUpgradableReadGuard::upgrade(current_frame);
             current_frame
                 .wrapped_lines
@@ -477,15 +507,18 @@ impl LineLayoutCache {
 
         let current_frame = self.current_frame.upgradable_read();
         if let Some(layout) = current_frame.lines.get(key) {
+            self.reused_lines.fetch_add(1, SeqCst);
             return layout.clone();
         }
 
         let mut current_frame = RwLockUpgradableReadGuard::upgrade(current_frame);
         if let Some((key, layout)) = self.previous_frame.lock().lines.remove_entry(key) {
+            self.reused_lines.fetch_add(1, SeqCst);
             current_frame.lines.insert(key.clone(), layout.clone());
             current_frame.used_lines.push(key);
             layout
         } else {
+            self.shaped_lines.fetch_add(1, SeqCst);
             let layout = Arc::new(self.platform_text_system.layout_line(text, font_size, runs));
             let key = Arc::new(CacheKey {
                 text: text.into(),
@@ -575,3 +608,73 @@ impl<'a> AsCacheKeyRef for CacheKeyRef<'a> {
         *self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{font, px, TestAppContext, TestDispatcher};
+    use rand::prelude::*;
+
+    #[test]
+    fn test_line_layout_stats() {
+        let dispatcher = TestDispatcher::new(StdRng::seed_from_u64(0));
+        let cx = TestAppContext::new(dispatcher, None);
+        cx.text_system()
+            .add_fonts(vec![std::fs::read(
+                "../../assets/fonts/plex-mono/ZedPlexMono-Regular.ttf",
+            )
+            .unwrap()
+            .into()])
+            .unwrap();
+        let font_id = cx.text_system().font_id(&font("Zed Plex Mono")).unwrap();
+        let cache = LineLayoutCache::new(cx.text_system().platform_text_system.clone());
+        let layout_line = |text: &str| {
+            let runs = [FontRun {
+                len: text.len(),
+                font_id,
+            }];
+            cache.layout_line(text, px(16.), &runs);
+        };
+
+        // Lines laid out again in the same frame are taken from the cache.
+        let frame_start = cache.layout_index();
+        layout_line("one");
+        layout_line("two");
+        layout_line("one");
+        let frame_end = cache.layout_index();
+        assert_eq!(
+            cache.finish_frame(),
+            LineLayoutStats {
+                shaped_lines: 2,
+                reused_lines: 1,
+            }
+        );
+
+        // So are lines laid out in the previous frame.
+        layout_line("one");
+        layout_line("three");
+        assert_eq!(
+            cache.finish_frame(),
+            LineLayoutStats {
+                shaped_lines: 1,
+                reused_lines: 1,
+            }
+        );
+
+        // The stats are reset for each frame.
+        assert_eq!(cache.finish_frame(), LineLayoutStats::default());
+
+        // Reusing the layouts of a range of lines counts each of them.
+        layout_line("one");
+        layout_line("two");
+        cache.finish_frame();
+        cache.reuse_layouts(frame_start..frame_end);
+        assert_eq!(
+            cache.finish_frame(),
+            LineLayoutStats {
+                shaped_lines: 0,
+                reused_lines: 2,
+            }
+        );
+    }
+}
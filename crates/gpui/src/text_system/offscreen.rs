@@ -0,0 +1,250 @@
+use std::io::Cursor;
+
+use anyhow::Context as _;
+use image::{ImageFormat, RgbaImage};
+
+use crate::{
+    point, px, Bounds, DevicePixels, Hsla, Pixels, Point, RenderGlyphParams, Result, Rgba,
+    ShapedLine, Size, TextSystem, SUBPIXEL_VARIANTS,
+};
+
+/// A bitmap that shapes and text are drawn into on the CPU rather than in a window,
+/// for making images of them, such as to copy to the clipboard.
+///
+/// Positions and sizes are given in logical pixels, and scaled by the scale factor
+/// the canvas is created with.
+pub struct OffscreenCanvas {
+    image: RgbaImage,
+    scale_factor: f32,
+}
+
+impl OffscreenCanvas {
+    /// Creates a transparent canvas of the given size.
+    pub fn new(size: Size<Pixels>, scale_factor: f32) -> Self {
+        let width = (size.width.0 * scale_factor).ceil().max(1.) as u32;
+        let height = (size.height.0 * scale_factor).ceil().max(1.) as u32;
+        Self {
+            image: RgbaImage::new(width, height),
+            scale_factor,
+        }
+    }
+
+    /// The size of the canvas in device pixels.
+    pub fn size(&self) -> Size<DevicePixels> {
+        Size {
+            width: DevicePixels(self.image.width() as i32),
+            height: DevicePixels(self.image.height() as i32),
+        }
+    }
+
+    /// Fills a rectangle with rounded corners, with its edges anti-aliased.
+    pub fn fill(&mut self, bounds: Bounds<Pixels>, corner_radius: Pixels, color: Hsla) {
+        let scale = self.scale_factor;
+        let left = bounds.origin.x.0 * scale;
+        let top = bounds.origin.y.0 * scale;
+        let right = left + bounds.size.width.0 * scale;
+        let bottom = top + bounds.size.height.0 * scale;
+        let radius = (corner_radius.0 * scale)
+            .min((right - left) / 2.)
+            .min((bottom - top) / 2.)
+            .max(0.);
+
+        let x_range = left.floor().max(0.) as u32..(right.ceil() as u32).min(self.image.width());
+        let y_range = top.floor().max(0.) as u32..(bottom.ceil() as u32).min(self.image.height());
+        for y in y_range {
+            for x in x_range.clone() {
+                let (center_x, center_y) = (x as f32 + 0.5, y as f32 + 0.5);
+                // The distance outside the rectangle, shrunk by the radius so that the
+                // corners are rounded.
+                let dx = (left + radius - center_x).max(center_x - (right - radius));
+                let dy = (top + radius - center_y).max(center_y - (bottom - radius));
+                let outside = dx.max(0.).hypot(dy.max(0.)) + dx.max(dy).min(0.) - radius;
+                let coverage = (0.5 - outside).clamp(0., 1.);
+                if coverage > 0. {
+                    self.blend(x, y, color, coverage);
+                }
+            }
+        }
+    }
+
+    /// Draws a shaped line of text with its top left corner at the origin, centered
+    /// vertically in the line height, as [`ShapedLine::paint`] would.
+    pub fn paint_line(
+        &mut self,
+        line: &ShapedLine,
+        origin: Point<Pixels>,
+        line_height: Pixels,
+        text_system: &TextSystem,
+    ) -> Result<()> {
+        let layout = &line.layout;
+        let padding_top = (line_height - layout.ascent - layout.descent) / 2.;
+        let baseline = point(origin.x, origin.y + padding_top + layout.ascent);
+
+        let mut decoration_runs = line.decoration_runs.iter();
+        let mut run_end = 0;
+        let mut color = crate::black();
+        for run in &layout.runs {
+            for glyph in &run.glyphs {
+                if glyph.index >= run_end {
+                    if let Some(decoration_run) = decoration_runs.next() {
+                        if let Some(background) = decoration_run.background_color {
+                            let start = layout.x_for_index(glyph.index);
+                            let end = layout.x_for_index(glyph.index + decoration_run.len as usize);
+                            self.fill(
+                                Bounds::new(
+                                    point(origin.x + start, origin.y),
+                                    crate::size(end - start, line_height),
+                                ),
+                                px(0.),
+                                background,
+                            );
+                        }
+                        run_end += decoration_run.len as usize;
+                        color = decoration_run.color;
+                    } else {
+                        run_end = layout.len;
+                    }
+                }
+
+                let glyph_origin = (baseline + glyph.position).scale(self.scale_factor);
+                let params = RenderGlyphParams {
+                    font_id: run.font_id,
+                    glyph_id: glyph.id,
+                    font_size: layout.font_size,
+                    subpixel_variant: Point {
+                        x: (glyph_origin.x.0.fract() * SUBPIXEL_VARIANTS as f32).floor() as u8,
+                        y: (glyph_origin.y.0.fract() * SUBPIXEL_VARIANTS as f32).floor() as u8,
+                    },
+                    scale_factor: self.scale_factor,
+                    is_emoji: glyph.is_emoji,
+                };
+                let raster_bounds = text_system.raster_bounds(&params)?;
+                if raster_bounds.is_zero() {
+                    continue;
+                }
+                let (size, bytes) = text_system.rasterize_glyph(&params)?;
+                let origin = point(
+                    glyph_origin.x.0.floor() as i32 + raster_bounds.origin.x.0,
+                    glyph_origin.y.0.floor() as i32 + raster_bounds.origin.y.0,
+                );
+                self.draw_glyph(origin, size, &bytes, glyph.is_emoji, color);
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes the canvas as a PNG image.
+    pub fn to_png(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.image
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .context("failed to encode PNG")?;
+        Ok(bytes)
+    }
+
+    /// Draws a rasterized glyph, which is a mask of alpha values to fill with the
+    /// color, or BGRA pixels if it's an emoji.
+    fn draw_glyph(
+        &mut self,
+        origin: Point<i32>,
+        size: Size<DevicePixels>,
+        bytes: &[u8],
+        is_emoji: bool,
+        color: Hsla,
+    ) {
+        let (width, height) = (size.width.0.max(0), size.height.0.max(0));
+        for row in 0..height {
+            for column in 0..width {
+                let (x, y) = (origin.x + column, origin.y + row);
+                if x < 0
+                    || y < 0
+                    || x >= self.image.width() as i32
+                    || y >= self.image.height() as i32
+                {
+                    continue;
+                }
+                let ix = (row * width + column) as usize;
+                if is_emoji {
+                    let Some(&[b, g, r, a]) = bytes.get(ix * 4..ix * 4 + 4) else {
+                        continue;
+                    };
+                    if a > 0 {
+                        // Emoji are rasterized with premultiplied alpha.
+                        let alpha = a as f32 / 255.;
+                        let color = Rgba {
+                            r: r as f32 / 255. / alpha,
+                            g: g as f32 / 255. / alpha,
+                            b: b as f32 / 255. / alpha,
+                            a: 1.,
+                        };
+                        self.blend(x as u32, y as u32, color.into(), alpha);
+                    }
+                } else if let Some(&coverage) = bytes.get(ix) {
+                    if coverage > 0 {
+                        self.blend(x as u32, y as u32, color, coverage as f32 / 255.);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Composites a color over a pixel, with the given coverage of the pixel.
+    fn blend(&mut self, x: u32, y: u32, color: Hsla, coverage: f32) {
+        let source = Rgba::from(color);
+        let source_alpha = source.a * coverage;
+        let pixel = self.image.get_pixel_mut(x, y);
+        let destination_alpha = pixel[3] as f32 / 255.;
+        let alpha = source_alpha + destination_alpha * (1. - source_alpha);
+        if alpha <= 0. {
+            return;
+        }
+        let channel = |source: f32, destination: u8| {
+            let destination = destination as f32 / 255.;
+            let value = (source * source_alpha
+                + destination * destination_alpha * (1. - source_alpha))
+                / alpha;
+            (value.clamp(0., 1.) * 255.).round() as u8
+        };
+        pixel.0 = [
+            channel(source.r, pixel[0]),
+            channel(source.g, pixel[1]),
+            channel(source.b, pixel[2]),
+            (alpha * 255.).round() as u8,
+        ];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{hsla, size};
+
+    use super::*;
+
+    #[test]
+    fn test_fill() {
+        let mut canvas = OffscreenCanvas::new(size(px(10.), px(10.)), 2.);
+        assert_eq!(canvas.size(), size(DevicePixels(20), DevicePixels(20)));
+
+        let red = hsla(0., 1., 0.5, 1.);
+        canvas.fill(
+            Bounds::new(point(px(0.), px(0.)), size(px(10.), px(10.))),
+            px(4.),
+            red,
+        );
+        // The corners are rounded off, and the rest is filled.
+        assert_eq!(canvas.image.get_pixel(0, 0).0, [0, 0, 0, 0]);
+        assert_eq!(canvas.image.get_pixel(10, 10).0, [255, 0, 0, 255]);
+        assert_eq!(canvas.image.get_pixel(0, 10).0, [255, 0, 0, 255]);
+
+        // Half transparent colors are blended with what's under them.
+        canvas.fill(
+            Bounds::new(point(px(0.), px(0.)), size(px(10.), px(10.))),
+            px(0.),
+            hsla(0., 0., 1., 0.5),
+        );
+        assert_eq!(canvas.image.get_pixel(10, 10).0, [255, 128, 128, 255]);
+        assert_eq!(canvas.image.get_pixel(0, 0).0, [255, 255, 255, 128]);
+
+        assert!(canvas.to_png().unwrap().starts_with(b"\x89PNG"));
+    }
+}
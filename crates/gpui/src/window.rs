@@ -1,19 +1,20 @@
 use crate::{
-    point, prelude::*, px, size, transparent_black, Action, AnyDrag, AnyElement, AnyTooltip,
-    AnyView, AppContext, Arena, Asset, AsyncWindowContext, AvailableSpace, Bounds, BoxShadow,
-    Context, Corners, CursorStyle, Decorations, DevicePixels, DispatchActionListener,
+    point, prelude::*, px, size, transparent_black, AccessibleText, Action, AnyDrag, AnyElement,
+    AnyTooltip, AnyView, AppContext, Arena, Asset, AsyncWindowContext, AvailableSpace, Bounds,
+    BoxShadow, Context, Corners, CursorStyle, Decorations, DevicePixels, DispatchActionListener,
     DispatchNodeId, DispatchTree, DisplayId, Edges, Effect, Entity, EntityId, EventEmitter,
     FileDropEvent, Flatten, FontId, GPUSpecs, Global, GlobalElementId, GlyphId, Hsla, InputHandler,
     IsZero, KeyBinding, KeyContext, KeyDownEvent, KeyEvent, Keystroke, KeystrokeEvent, LayoutId,
-    LineLayoutIndex, Model, ModelContext, Modifiers, ModifiersChangedEvent, MonochromeSprite,
-    MouseButton, MouseEvent, MouseMoveEvent, MouseUpEvent, Path, Pixels, PlatformAtlas,
-    PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point, PolychromeSprite,
-    PromptLevel, Quad, Render, RenderGlyphParams, RenderImage, RenderImageParams, RenderSvgParams,
-    Replay, ResizeEdge, ScaledPixels, Scene, Shadow, SharedString, Size, StrikethroughStyle, Style,
-    SubscriberSet, Subscription, TaffyLayoutEngine, Task, TextStyle, TextStyleRefinement,
-    TimeToFirstWindowDraw, TransformationMatrix, Underline, UnderlineStyle, View, VisualContext,
-    WeakView, WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControls,
-    WindowDecorations, WindowOptions, WindowParams, WindowTextSystem, SUBPIXEL_VARIANTS,
+    LineLayoutIndex, LineLayoutStats, Model, ModelContext, Modifiers, ModifiersChangedEvent,
+    MonochromeSprite, MouseButton, MouseEvent, MouseMoveEvent, MouseUpEvent, Path, Pixels,
+    PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point,
+    PolychromeSprite, PromptLevel, Quad, Render, RenderGlyphParams, RenderImage, RenderImageParams,
+    RenderSvgParams, Replay, ResizeEdge, ScaledPixels, Scene, Shadow, SharedString, Size,
+    StrikethroughStyle, Style, SubscriberSet, Subscription, TaffyLayoutEngine, Task, TextStyle,
+    TextStyleRefinement, TimeToFirstWindowDraw, TransformationMatrix, Underline, UnderlineStyle,
+    View, VisualContext, WeakView, WindowAppearance, WindowBackgroundAppearance, WindowBounds,
+    WindowControls, WindowDecorations, WindowOptions, WindowParams, WindowTextSystem,
+    SUBPIXEL_VARIANTS,
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::{FxHashMap, FxHashSet};
@@ -405,6 +406,7 @@ pub(crate) struct Frame {
     pub(crate) hitboxes: Vec<Hitbox>,
     pub(crate) deferred_draws: Vec<DeferredDraw>,
     pub(crate) input_handlers: Vec<Option<PlatformInputHandler>>,
+    pub(crate) accessible_texts: Vec<AccessibleText>,
     pub(crate) tooltip_requests: Vec<Option<TooltipRequest>>,
     pub(crate) cursor_styles: Vec<CursorStyleRequest>,
     #[cfg(any(test, feature = "test-support"))]
@@ -426,6 +428,7 @@ pub(crate) struct PaintIndex {
     scene_index: usize,
     mouse_listeners_index: usize,
     input_handlers_index: usize,
+    accessible_texts_index: usize,
     cursor_styles_index: usize,
     accessed_element_states_index: usize,
     line_layout_index: LineLayoutIndex,
@@ -444,6 +447,7 @@ impl Frame {
             hitboxes: Vec::new(),
             deferred_draws: Vec::new(),
             input_handlers: Vec::new(),
+            accessible_texts: Vec::new(),
             tooltip_requests: Vec::new(),
             cursor_styles: Vec::new(),
 
@@ -459,6 +463,7 @@ impl Frame {
         self.dispatch_tree.clear();
         self.scene.clear();
         self.input_handlers.clear();
+        self.accessible_texts.clear();
         self.tooltip_requests.clear();
         self.cursor_styles.clear();
         self.hitboxes.clear();
@@ -547,6 +552,7 @@ pub struct Window {
     /// We assign this to be notified when the platform graphics backend fires the next completion callback for drawing the window.
     present_completed: RefCell<Option<oneshot::Sender<()>>>,
     pub(crate) last_input_timestamp: Rc<Cell<Instant>>,
+    frame_stats: Rc<Cell<FrameStats>>,
     pub(crate) refreshing: bool,
     pub(crate) draw_phase: DrawPhase,
     activation_observers: SubscriberSet<(), AnyObserver>,
@@ -558,6 +564,32 @@ pub struct Window {
     prompt: Option<RenderablePromptHandle>,
 }
 
+/// How long a frame can take to draw before it's dropped, at 60 frames per second.
+const FRAME_BUDGET: Duration = Duration::from_micros(16_667);
+
+/// Statistics about the frames that a window draws, for diagnosing slow rendering.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// How long the last frame took to draw and present.
+    pub frame_duration: Duration,
+    /// How many frames took longer to draw than a refresh at 60 frames per second,
+    /// since the window was opened.
+    pub dropped_frames: usize,
+    /// How many lines of text the last frame laid out.
+    pub line_layouts: LineLayoutStats,
+}
+
+impl FrameStats {
+    /// Records how long a frame took to draw and present, counting it as dropped
+    /// when it took longer than the frame budget.
+    fn record_frame(&mut self, duration: Duration) {
+        self.frame_duration = duration;
+        if duration > FRAME_BUDGET {
+            self.dropped_frames += 1;
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 struct ModifierState {
     modifiers: Modifiers,
@@ -655,6 +687,7 @@ impl Window {
         let needs_present = Rc::new(Cell::new(false));
         let next_frame_callbacks: Rc<RefCell<Vec<FrameCallback>>> = Default::default();
         let last_input_timestamp = Rc::new(Cell::new(Instant::now()));
+        let frame_stats = Rc::new(Cell::new(FrameStats::default()));
 
         platform_window
             .request_decorations(window_decorations.unwrap_or(WindowDecorations::Server));
@@ -681,6 +714,7 @@ impl Window {
             let needs_present = needs_present.clone();
             let next_frame_callbacks = next_frame_callbacks.clone();
             let last_input_timestamp = last_input_timestamp.clone();
+            let frame_stats = frame_stats.clone();
             move || {
                 let next_frame_callbacks = next_frame_callbacks.take();
                 if !next_frame_callbacks.is_empty() {
@@ -700,6 +734,7 @@ impl Window {
                         && last_input_timestamp.get().elapsed() < Duration::from_secs(1));
 
                 if dirty.get() {
+                    let frame_start = Instant::now();
                     measure("frame duration", || {
                         handle
                             .update(&mut cx, |_, cx| {
@@ -707,7 +742,10 @@ impl Window {
                                 cx.present();
                             })
                             .log_err();
-                    })
+                    });
+                    let mut stats = frame_stats.get();
+                    stats.record_frame(frame_start.elapsed());
+                    frame_stats.set(stats);
                 } else if needs_present {
                     handle.update(&mut cx, |_, cx| cx.present()).log_err();
                 }
@@ -824,6 +862,7 @@ impl Window {
             needs_present,
             present_completed: RefCell::default(),
             last_input_timestamp,
+            frame_stats,
             refreshing: false,
             draw_phase: DrawPhase::None,
             activation_observers: SubscriberSet::new(),
@@ -1413,6 +1452,13 @@ impl<'a> WindowContext<'a> {
         self.window.platform_window.completed_frame();
     }
 
+    /// Statistics about the frames that this window has drawn. The last frame's
+    /// duration is only known once it's presented, so while a frame is drawn, it's
+    /// the duration of the one before it.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.window.frame_stats.get()
+    }
+
     /// Produces a new frame and assigns it to `rendered_frame`. To actually show
     /// the contents of the new [Scene], use [present].
     #[profiling::function]
@@ -1439,8 +1485,20 @@ impl<'a> WindowContext<'a> {
                 .set_input_handler(input_handler.unwrap());
         }
 
+        // Tell assistive technologies about the focused text when it changes.
+        let accessible_text = self.window.next_frame.accessible_texts.last();
+        if accessible_text != self.window.rendered_frame.accessible_texts.last() {
+            self.window
+                .platform_window
+                .update_accessible_text(accessible_text.cloned());
+        }
+
         self.window.layout_engine.as_mut().unwrap().clear();
-        self.text_system().finish_frame();
+        let line_layouts = self.text_system().finish_frame();
+        self.window.frame_stats.set(FrameStats {
+            line_layouts,
+            ..self.window.frame_stats.get()
+        });
         self.window
             .next_frame
             .finish(&mut self.window.rendered_frame);
@@ -1744,6 +1802,7 @@ impl<'a> WindowContext<'a> {
             scene_index: self.window.next_frame.scene.len(),
             mouse_listeners_index: self.window.next_frame.mouse_listeners.len(),
             input_handlers_index: self.window.next_frame.input_handlers.len(),
+            accessible_texts_index: self.window.next_frame.accessible_texts.len(),
             cursor_styles_index: self.window.next_frame.cursor_styles.len(),
             accessed_element_states_index: self.window.next_frame.accessed_element_states.len(),
             line_layout_index: self.window.text_system.layout_index(),
@@ -1765,6 +1824,12 @@ impl<'a> WindowContext<'a> {
                 .iter_mut()
                 .map(|handler| handler.take()),
         );
+        window.next_frame.accessible_texts.extend(
+            window.rendered_frame.accessible_texts
+                [range.start.accessible_texts_index..range.end.accessible_texts_index]
+                .iter()
+                .cloned(),
+        );
         window.next_frame.mouse_listeners.extend(
             window.rendered_frame.mouse_listeners
                 [range.start.mouse_listeners_index..range.end.mouse_listeners_index]
@@ -2862,6 +2927,23 @@ impl<'a> WindowContext<'a> {
         }
     }
 
+    /// Exposes the text of an element and its selection to assistive technologies, such
+    /// as screen readers, while the element is focused. The text is exposed until the
+    /// following frame is rendered.
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub fn set_accessible_text(&mut self, focus_handle: &FocusHandle, text: AccessibleText) {
+        debug_assert_eq!(
+            self.window.draw_phase,
+            DrawPhase::Paint,
+            "this method can only be called during paint"
+        );
+
+        if focus_handle.is_focused(self) {
+            self.window.next_frame.accessible_texts.push(text);
+        }
+    }
+
     /// Register a mouse event listener on the window for the next frame. The type of event
     /// is determined by the first parameter of the given listener. When the next frame is rendered
     /// the listener will be cleared.
@@ -4961,3 +5043,26 @@ pub fn outline(bounds: impl Into<Bounds<Pixels>>, border_color: impl Into<Hsla>)
         border_color: border_color.into(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_frame() {
+        let mut stats = FrameStats::default();
+        stats.record_frame(Duration::from_millis(5));
+        stats.record_frame(Duration::from_millis(40));
+        stats.record_frame(FRAME_BUDGET);
+        stats.record_frame(Duration::from_millis(20));
+        assert_eq!(stats.frame_duration, Duration::from_millis(20));
+        assert_eq!(
+            stats.dropped_frames, 2,
+            "Only the frames over the budget are dropped"
+        );
+
+        stats.record_frame(Duration::from_millis(1));
+        assert_eq!(stats.frame_duration, Duration::from_millis(1));
+        assert_eq!(stats.dropped_frames, 2);
+    }
+}
@@ -0,0 +1,246 @@
+//! A client for grammar and style checkers speaking the LanguageTool HTTP API.
+use anyhow::{anyhow, Context as _, Result};
+use futures::AsyncReadExt as _;
+use http_client::{AsyncBody, HttpClient, Method, Request as HttpRequest};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// The URL of the API of a LanguageTool server running locally, on its default port.
+pub const LANGUAGE_TOOL_API_URL: &str = "http://localhost:8081/v2";
+
+/// Text to check, made of prose and of markup that's skipped by the checker,
+/// such as code and comment delimiters.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct AnnotatedText {
+    pub annotation: Vec<Annotation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Annotation {
+    Text {
+        text: String,
+    },
+    Markup {
+        markup: String,
+        /// The text that the markup stands for, like a space or a paragraph break.
+        #[serde(rename = "interpretAs", skip_serializing_if = "Option::is_none")]
+        interpret_as: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckResponse {
+    pub matches: Vec<Match>,
+}
+
+/// A problem found by the checker.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Match {
+    pub message: String,
+    #[serde(default)]
+    pub short_message: String,
+    /// The offset of the problem in the checked text, in UTF-16 code units.
+    pub offset: usize,
+    /// The length of the problem, in UTF-16 code units.
+    pub length: usize,
+    #[serde(default)]
+    pub replacements: Vec<Replacement>,
+    pub rule: Rule,
+}
+
+impl Match {
+    /// The range of the problem in the checked text, in UTF-16 code units.
+    pub fn range_utf16(&self) -> Range<usize> {
+        self.offset..self.offset + self.length
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Replacement {
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    pub id: String,
+    #[serde(default)]
+    pub description: String,
+    /// The kind of problem found by the rule, like `misspelling`, `grammar` or `style`.
+    #[serde(default)]
+    pub issue_type: String,
+}
+
+/// Checks the text with the checker serving the API at `api_url`, in the given
+/// language, or `auto` to detect it.
+pub async fn check(
+    client: &dyn HttpClient,
+    api_url: &str,
+    language: &str,
+    text: &AnnotatedText,
+) -> Result<CheckResponse> {
+    let uri = format!("{}/check", api_url.trim_end_matches('/'));
+    let body = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("language", language)
+        .append_pair("data", &serde_json::to_string(text)?)
+        .finish();
+    let request = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Accept", "application/json")
+        .body(AsyncBody::from(body))?;
+
+    let mut response = client.send(request).await?;
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+    if response.status().is_success() {
+        serde_json::from_str(&body).context("failed to parse the grammar check response")
+    } else {
+        Err(anyhow!(
+            "failed to check grammar: {} {}",
+            response.status(),
+            body
+        ))
+    }
+}
+
+/// Returns the ranges of prose in `text`, given whether the word at an offset is
+/// part of the prose, like the words of comments.
+///
+/// The words and the whitespace between them are grouped in ranges that don't span
+/// lines. Punctuation is part of the prose when it follows prose on the same line,
+/// so that comment delimiters and list markers at the start of lines aren't.
+pub fn prose_ranges(text: &str, mut is_prose: impl FnMut(usize) -> bool) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        let mut previous_is_prose = false;
+        let tokens = line.split_whitespace().map(|token| {
+            let start = line_start + token.as_ptr() as usize - line.as_ptr() as usize;
+            start..start + token.len()
+        });
+        for token in tokens {
+            let word_start = text[token.clone()]
+                .find(char::is_alphanumeric)
+                .map(|ix| token.start + ix);
+            let is_token_prose = match word_start {
+                Some(word_start) => is_prose(word_start),
+                None => previous_is_prose,
+            };
+            if is_token_prose {
+                match ranges.last_mut() {
+                    Some(last) if previous_is_prose => last.end = token.end,
+                    _ => ranges.push(token),
+                }
+            }
+            previous_is_prose = is_token_prose;
+        }
+        line_start += line.len();
+    }
+    ranges
+}
+
+/// Builds the text to check out of the prose ranges of `text`, marking up the
+/// text between them.
+pub fn annotate(text: &str, prose_ranges: &[Range<usize>]) -> AnnotatedText {
+    let mut annotation = Vec::new();
+    let mut offset = 0;
+    for range in prose_ranges {
+        if offset < range.start {
+            annotation.push(markup(&text[offset..range.start]));
+        }
+        annotation.push(Annotation::Text {
+            text: text[range.clone()].to_string(),
+        });
+        offset = range.end;
+    }
+    if offset < text.len() {
+        annotation.push(markup(&text[offset..]));
+    }
+    AnnotatedText { annotation }
+}
+
+fn markup(text: &str) -> Annotation {
+    let interpret_as = if text.matches('\n').count() > 1 {
+        Some("\n\n")
+    } else if text.contains(char::is_whitespace) {
+        Some(" ")
+    } else {
+        None
+    };
+    Annotation::Markup {
+        markup: text.to_string(),
+        interpret_as: interpret_as.map(ToString::to_string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prose_ranges() {
+        let text =
+            "fn main() {\n    // Returns the value, or -- if\n    // not found -- none.\n}\n";
+        let comment = text.find("//").unwrap()..text.rfind('}').unwrap();
+        let ranges = prose_ranges(text, |offset| comment.contains(&offset));
+        assert_eq!(
+            ranges
+                .into_iter()
+                .map(|range| &text[range])
+                .collect::<Vec<_>>(),
+            vec!["Returns the value, or -- if", "not found -- none."]
+        );
+    }
+
+    #[test]
+    fn test_annotate() {
+        let text = "# Title\n\nSome `code` here.\n";
+        let ranges = prose_ranges(text, |offset| !text[offset..].starts_with("code"));
+        let annotated = annotate(text, &ranges);
+        assert_eq!(
+            serde_json::to_value(&annotated).unwrap(),
+            serde_json::json!({
+                "annotation": [
+                    {"markup": "# ", "interpretAs": " "},
+                    {"text": "Title"},
+                    {"markup": "\n\n", "interpretAs": "\n\n"},
+                    {"text": "Some"},
+                    {"markup": " `code` ", "interpretAs": " "},
+                    {"text": "here."},
+                    {"markup": "\n", "interpretAs": " "},
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let response: CheckResponse = serde_json::from_str(
+            r#"{
+                "software": {"name": "LanguageTool"},
+                "matches": [{
+                    "message": "Possible spelling mistake found.",
+                    "shortMessage": "Spelling mistake",
+                    "replacements": [{"value": "the"}, {"value": "then"}],
+                    "offset": 8,
+                    "length": 3,
+                    "rule": {
+                        "id": "MORFOLOGIK_RULE_EN_US",
+                        "description": "Possible spelling mistake",
+                        "issueType": "misspelling",
+                        "category": {"id": "TYPOS", "name": "Possible Typo"}
+                    }
+                }]
+            }"#,
+        )
+        .unwrap();
+        let found = &response.matches[0];
+        assert_eq!(found.range_utf16(), 8..11);
+        assert_eq!(found.replacements[1].value, "then");
+        assert_eq!(found.rule.issue_type, "misspelling");
+    }
+}
@@ -1,21 +1,40 @@
+mod loaded_image;
+
 use gpui::{
-    canvas, div, fill, img, opaque_grey, point, size, AnyElement, AppContext, Bounds, Context,
-    EventEmitter, FocusHandle, FocusableView, Img, InteractiveElement, IntoElement, Model,
-    ObjectFit, ParentElement, Render, Styled, Task, View, ViewContext, VisualContext, WeakView,
+    actions, canvas, div, fill, img, opaque_grey, point, size, AnyElement, AppContext, Bounds,
+    Context, EventEmitter, FocusHandle, FocusableView, Img, InteractiveElement, IntoElement, Model,
+    MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, ObjectFit, ParentElement, Point,
+    Render, ScrollWheelEvent, Size, Styled, Task, View, ViewContext, VisualContext, WeakView,
     WindowContext,
 };
+use loaded_image::{format_file_size, onion_skin, render_image, LoadedImage};
 use persistence::IMAGE_VIEWER;
-use ui::prelude::*;
+use ui::{prelude::*, ToggleButton, Tooltip};
 
 use file_icons::FileIcons;
 use project::{Project, ProjectEntryId, ProjectPath};
 use settings::Settings;
-use std::{ffi::OsStr, path::PathBuf};
+use std::{cell::RefCell, ffi::OsStr, path::PathBuf, rc::Rc, sync::Arc};
+use util::ResultExt;
 use workspace::{
     item::{Item, ProjectItem, SerializableItem, TabContentParams},
     ItemId, ItemSettings, Pane, Workspace, WorkspaceId,
 };
 
+actions!(
+    image_viewer,
+    [
+        ZoomIn,
+        ZoomOut,
+        ZoomToFit,
+        ActualSize,
+        ToggleSideBySideDiff,
+        ToggleOnionSkinDiff,
+        IncreaseOnionSkinOpacity,
+        DecreaseOnionSkinOpacity
+    ]
+);
+
 const IMAGE_VIEWER_KIND: &str = "ImageView";
 
 pub struct ImageItem {
@@ -65,9 +84,695 @@ impl project::Item for ImageItem {
     }
 }
 
+/// How much each zoom step scales the image by.
+const ZOOM_STEP: f32 = 1.25;
+const MIN_ZOOM: f32 = 0.05;
+const MAX_ZOOM: f32 = 64.;
+
+/// How much each step changes the opacity of the onion skin.
+const OPACITY_STEP: f32 = 0.1;
+
+/// How far a scroll of the mouse wheel changes the zoom by, per pixel.
+const SCROLL_ZOOM_SENSITIVITY: f32 = 0.005;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DiffMode {
+    /// The version in the HEAD commit next to the working copy.
+    SideBySide,
+    /// The working copy faded over the version in the HEAD commit.
+    OnionSkin,
+}
+
+/// Which version of the image a pane of the view shows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ImageVersion {
+    WorkingCopy,
+    Head,
+    OnionSkin,
+}
+
+impl ImageVersion {
+    fn label(self) -> &'static str {
+        match self {
+            ImageVersion::WorkingCopy => "Working Copy",
+            ImageVersion::Head => "HEAD",
+            ImageVersion::OnionSkin => "Onion Skin",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct HoveredPixel {
+    version: ImageVersion,
+    x: u32,
+    y: u32,
+    color: [u8; 4],
+}
+
 pub struct ImageView {
     path: PathBuf,
+    project_path: Option<ProjectPath>,
+    project: Model<Project>,
     focus_handle: FocusHandle,
+    image: Option<Arc<LoadedImage>>,
+    /// The version of the image in the HEAD commit of its repository, if it's
+    /// in one.
+    head_image: Option<Arc<LoadedImage>>,
+    loading: bool,
+    /// The scale the image is shown at, or `None` to fit it in the view.
+    zoom: Option<f32>,
+    /// How far the image is moved from the center of the view.
+    pan: Point<Pixels>,
+    /// Where the mouse was pressed to drag the image, and the pan then.
+    drag_start: Option<(Point<Pixels>, Point<Pixels>)>,
+    hovered_pixel: Option<HoveredPixel>,
+    diff_mode: Option<DiffMode>,
+    onion_skin_opacity: f32,
+    onion_skin: Option<Arc<LoadedImage>>,
+    /// The bounds of the panes of the view, as of the last time they were
+    /// painted, by the version of the image they show.
+    pane_bounds: Rc<RefCell<Vec<(ImageVersion, Bounds<Pixels>)>>>,
+    load_task: Option<Task<()>>,
+    onion_skin_task: Option<Task<()>>,
+}
+
+impl ImageView {
+    fn new(
+        path: PathBuf,
+        project_path: Option<ProjectPath>,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let mut this = Self {
+            path,
+            project_path,
+            project,
+            focus_handle: cx.focus_handle(),
+            image: None,
+            head_image: None,
+            loading: true,
+            zoom: None,
+            pan: Point::default(),
+            drag_start: None,
+            hovered_pixel: None,
+            diff_mode: None,
+            onion_skin_opacity: 0.5,
+            onion_skin: None,
+            pane_bounds: Rc::default(),
+            load_task: None,
+            onion_skin_task: None,
+        };
+        this.load(cx);
+        this
+    }
+
+    /// Decodes the image and the version of it in the HEAD commit.
+    fn load(&mut self, cx: &mut ViewContext<Self>) {
+        let fs = self.project.read(cx).fs().clone();
+        let path = self.path.clone();
+        let head_bytes = self
+            .project_path
+            .as_ref()
+            .map(|project_path| self.project.read(cx).load_head_bytes(project_path, cx));
+        self.load_task = Some(cx.spawn(|this, mut cx| async move {
+            let image = cx
+                .background_executor()
+                .spawn(async move { LoadedImage::decode(&fs.load_bytes(&path).await?) })
+                .await
+                .log_err()
+                .map(Arc::new);
+            // Images that aren't in a repository have no version to compare
+            // with.
+            let head_bytes = match head_bytes {
+                Some(head_bytes) => head_bytes.await.ok().flatten(),
+                None => None,
+            };
+            let head_image = match head_bytes {
+                Some(bytes) => cx
+                    .background_executor()
+                    .spawn(async move { LoadedImage::decode(&bytes) })
+                    .await
+                    .log_err()
+                    .map(Arc::new),
+                None => None,
+            };
+            this.update(&mut cx, |this, cx| {
+                this.image = image;
+                this.head_image = head_image;
+                this.loading = false;
+                if this.head_image.is_none() {
+                    this.diff_mode = None;
+                }
+                this.update_onion_skin(cx);
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn image_for_version(&self, version: ImageVersion) -> Option<&Arc<LoadedImage>> {
+        match version {
+            ImageVersion::WorkingCopy => self.image.as_ref(),
+            ImageVersion::Head => self.head_image.as_ref(),
+            ImageVersion::OnionSkin => self.onion_skin.as_ref(),
+        }
+    }
+
+    /// Returns the versions of the image shown side by side.
+    fn shown_versions(&self) -> &'static [ImageVersion] {
+        match self.diff_mode {
+            None => &[ImageVersion::WorkingCopy],
+            Some(DiffMode::SideBySide) => &[ImageVersion::Head, ImageVersion::WorkingCopy],
+            Some(DiffMode::OnionSkin) => &[ImageVersion::OnionSkin],
+        }
+    }
+
+    /// Returns the scale the image is shown at in a pane of the given size.
+    fn scale(&self, image: &LoadedImage, pane_size: Size<Pixels>) -> f32 {
+        self.zoom.unwrap_or_else(|| {
+            let (width, height) = (image.width() as f32, image.height() as f32);
+            if width == 0. || height == 0. {
+                return 1.;
+            }
+            // Images are scaled down to fit, but not up.
+            (pane_size.width.0 / width)
+                .min(pane_size.height.0 / height)
+                .min(1.)
+        })
+    }
+
+    /// Returns the pane under a position, and the scale its image is shown at.
+    fn pane_at(&self, position: Point<Pixels>) -> Option<(ImageVersion, Bounds<Pixels>, f32)> {
+        let pane_bounds = self.pane_bounds.borrow();
+        let (version, bounds) = pane_bounds
+            .iter()
+            .find(|(_, bounds)| bounds.contains(&position))?;
+        let image = self.image_for_version(*version)?;
+        Some((*version, *bounds, self.scale(image, bounds.size)))
+    }
+
+    /// Returns the pixel of the image under a position, in a pane with the
+    /// given bounds.
+    fn pixel_at(
+        &self,
+        image: &LoadedImage,
+        bounds: Bounds<Pixels>,
+        scale: f32,
+        position: Point<Pixels>,
+    ) -> Option<(u32, u32)> {
+        let pan = if self.zoom.is_some() {
+            self.pan
+        } else {
+            Point::default()
+        };
+        let image_size = size(
+            px(image.width() as f32 * scale),
+            px(image.height() as f32 * scale),
+        );
+        let origin = bounds.center() + pan - point(image_size.width / 2., image_size.height / 2.);
+        let x = ((position.x - origin.x).0 / scale).floor();
+        let y = ((position.y - origin.y).0 / scale).floor();
+        (x >= 0. && y >= 0. && x < image.width() as f32 && y < image.height() as f32)
+            .then_some((x as u32, y as u32))
+    }
+
+    fn set_zoom(&mut self, zoom: Option<f32>, cx: &mut ViewContext<Self>) {
+        self.zoom = zoom.map(|zoom| zoom.clamp(MIN_ZOOM, MAX_ZOOM));
+        if self.zoom.is_none() {
+            self.pan = Point::default();
+        }
+        cx.notify();
+    }
+
+    /// Returns the scale the image is currently shown at, in its first pane.
+    fn current_scale(&self) -> f32 {
+        self.zoom.unwrap_or_else(|| {
+            let pane_bounds = self.pane_bounds.borrow();
+            let Some((version, bounds)) = pane_bounds.first() else {
+                return 1.;
+            };
+            self.image_for_version(*version)
+                .map_or(1., |image| self.scale(image, bounds.size))
+        })
+    }
+
+    fn zoom_in(&mut self, _: &ZoomIn, cx: &mut ViewContext<Self>) {
+        self.set_zoom(Some(self.current_scale() * ZOOM_STEP), cx);
+    }
+
+    fn zoom_out(&mut self, _: &ZoomOut, cx: &mut ViewContext<Self>) {
+        self.set_zoom(Some(self.current_scale() / ZOOM_STEP), cx);
+    }
+
+    fn zoom_to_fit(&mut self, _: &ZoomToFit, cx: &mut ViewContext<Self>) {
+        self.set_zoom(None, cx);
+    }
+
+    fn actual_size(&mut self, _: &ActualSize, cx: &mut ViewContext<Self>) {
+        self.set_zoom(Some(1.), cx);
+    }
+
+    fn toggle_diff_mode(&mut self, mode: DiffMode, cx: &mut ViewContext<Self>) {
+        if self.head_image.is_none() {
+            return;
+        }
+        self.diff_mode = if self.diff_mode == Some(mode) {
+            None
+        } else {
+            Some(mode)
+        };
+        self.hovered_pixel = None;
+        self.update_onion_skin(cx);
+        cx.notify();
+    }
+
+    fn toggle_side_by_side_diff(&mut self, _: &ToggleSideBySideDiff, cx: &mut ViewContext<Self>) {
+        self.toggle_diff_mode(DiffMode::SideBySide, cx);
+    }
+
+    fn toggle_onion_skin_diff(&mut self, _: &ToggleOnionSkinDiff, cx: &mut ViewContext<Self>) {
+        self.toggle_diff_mode(DiffMode::OnionSkin, cx);
+    }
+
+    fn set_onion_skin_opacity(&mut self, opacity: f32, cx: &mut ViewContext<Self>) {
+        // Rounded so that stepping the opacity doesn't accumulate errors.
+        let opacity = (opacity.clamp(0., 1.) * 10.).round() / 10.;
+        if opacity != self.onion_skin_opacity {
+            self.onion_skin_opacity = opacity;
+            self.update_onion_skin(cx);
+            cx.notify();
+        }
+    }
+
+    fn increase_onion_skin_opacity(
+        &mut self,
+        _: &IncreaseOnionSkinOpacity,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.set_onion_skin_opacity(self.onion_skin_opacity + OPACITY_STEP, cx);
+    }
+
+    fn decrease_onion_skin_opacity(
+        &mut self,
+        _: &DecreaseOnionSkinOpacity,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.set_onion_skin_opacity(self.onion_skin_opacity - OPACITY_STEP, cx);
+    }
+
+    /// Blends the working copy over the version in the HEAD commit, when
+    /// they're shown as an onion skin.
+    fn update_onion_skin(&mut self, cx: &mut ViewContext<Self>) {
+        if self.diff_mode != Some(DiffMode::OnionSkin) {
+            self.onion_skin = None;
+            self.onion_skin_task = None;
+            return;
+        }
+        let (Some(image), Some(head_image)) = (self.image.clone(), self.head_image.clone()) else {
+            return;
+        };
+        let opacity = self.onion_skin_opacity;
+        self.onion_skin_task = Some(cx.spawn(|this, mut cx| async move {
+            let onion_skin = cx
+                .background_executor()
+                .spawn(async move {
+                    let pixels = onion_skin(&head_image.pixels, &image.pixels, opacity);
+                    LoadedImage {
+                        render_image: render_image(pixels.clone()),
+                        pixels,
+                        file_size: 0,
+                    }
+                })
+                .await;
+            this.update(&mut cx, |this, cx| {
+                this.onion_skin = Some(Arc::new(onion_skin));
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn handle_scroll_wheel(&mut self, event: &ScrollWheelEvent, cx: &mut ViewContext<Self>) {
+        let delta = event.delta.pixel_delta(px(20.));
+        if event.modifiers.secondary() {
+            let Some((_, bounds, scale)) = self.pane_at(event.position) else {
+                return;
+            };
+            let new_scale =
+                (scale * (-delta.y.0 * SCROLL_ZOOM_SENSITIVITY).exp()).clamp(MIN_ZOOM, MAX_ZOOM);
+            // Keep the point under the mouse where it is.
+            let pan = if self.zoom.is_some() {
+                self.pan
+            } else {
+                Point::default()
+            };
+            let from_center = event.position - bounds.center();
+            self.pan = from_center - (from_center - pan) * (new_scale / scale);
+            self.zoom = Some(new_scale);
+            cx.notify();
+        } else if self.zoom.is_some() {
+            self.pan = self.pan + delta;
+            cx.notify();
+        }
+    }
+
+    fn handle_mouse_down(&mut self, event: &MouseDownEvent, cx: &mut ViewContext<Self>) {
+        cx.focus(&self.focus_handle);
+        if self.zoom.is_none() {
+            // Dragging a fitted image starts panning it at its current scale.
+            self.zoom = Some(self.current_scale());
+            self.pan = Point::default();
+        }
+        self.drag_start = Some((event.position, self.pan));
+    }
+
+    fn handle_mouse_up(&mut self, _: &MouseUpEvent, _: &mut ViewContext<Self>) {
+        self.drag_start = None;
+    }
+
+    fn handle_mouse_move(&mut self, event: &MouseMoveEvent, cx: &mut ViewContext<Self>) {
+        if let Some((start_position, start_pan)) = self.drag_start {
+            if event.pressed_button == Some(MouseButton::Left) {
+                self.pan = start_pan + (event.position - start_position);
+            } else {
+                self.drag_start = None;
+            }
+        }
+
+        let hovered_pixel = self
+            .pane_at(event.position)
+            .and_then(|(version, bounds, scale)| {
+                let image = self.image_for_version(version)?;
+                let (x, y) = self.pixel_at(image, bounds, scale, event.position)?;
+                Some(HoveredPixel {
+                    version,
+                    x,
+                    y,
+                    color: image.pixel(x, y)?,
+                })
+            });
+        if hovered_pixel != self.hovered_pixel || self.drag_start.is_some() {
+            self.hovered_pixel = hovered_pixel;
+            cx.notify();
+        }
+    }
+
+    fn render_pane(&self, version: ImageVersion, cx: &mut ViewContext<Self>) -> AnyElement {
+        let pane_bounds = self.pane_bounds.clone();
+        let checkered_background = move |bounds: Bounds<Pixels>, _, cx: &mut WindowContext| {
+            pane_bounds.borrow_mut().push((version, bounds));
+            paint_checkerboard(bounds, cx);
+        };
+        let checkered_background = canvas(|_, _| (), checkered_background)
+            .size_full()
+            .absolute()
+            .top_0()
+            .left_0();
+
+        let image = match version {
+            // The working copy is loaded from its file, so that animated
+            // images play.
+            ImageVersion::WorkingCopy => Some(img(self.path.clone())),
+            version => self
+                .image_for_version(version)
+                .map(|image| img(image.render_image.clone())),
+        };
+        let image = image.map(|image| match (self.zoom, self.image_for_version(version)) {
+            (Some(zoom), Some(loaded)) => image
+                .flex_none()
+                .w(px(loaded.width() as f32 * zoom))
+                .h(px(loaded.height() as f32 * zoom))
+                .relative()
+                .left(self.pan.x)
+                .top(self.pan.y),
+            _ => image
+                .object_fit(ObjectFit::ScaleDown)
+                .max_w_full()
+                .max_h_full(),
+        });
+
+        v_flex()
+            .flex_1()
+            .h_full()
+            .min_w_0()
+            .when(self.diff_mode == Some(DiffMode::SideBySide), |pane| {
+                pane.child(
+                    div()
+                        .px_2()
+                        .py_1()
+                        .border_b_1()
+                        .border_color(cx.theme().colors().border_variant)
+                        .child(Label::new(version.label()).size(LabelSize::Small)),
+                )
+            })
+            .child(
+                div()
+                    .relative()
+                    .flex_1()
+                    .w_full()
+                    .overflow_hidden()
+                    .child(checkered_background)
+                    .child(
+                        div()
+                            .flex()
+                            .justify_center()
+                            .items_center()
+                            .size_full()
+                            .children(image),
+                    ),
+            )
+            .into_any_element()
+    }
+
+    /// Describes the position and color of a pixel, such as
+    /// `12, 34  #FF8000FF  rgba(255, 128, 0, 1.00)`.
+    fn describe_pixel(&self, pixel: HoveredPixel) -> String {
+        let [r, g, b, a] = pixel.color;
+        let version = match self.diff_mode {
+            Some(DiffMode::SideBySide) => format!("{}: ", pixel.version.label()),
+            _ => String::new(),
+        };
+        let alpha = a as f32 / 255.;
+        format!(
+            "{version}{}, {}  #{r:02X}{g:02X}{b:02X}{a:02X}  rgba({r}, {g}, {b}, {alpha:.2})",
+            pixel.x, pixel.y
+        )
+    }
+
+    fn render_toolbar(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let mut details = Vec::new();
+        if let Some(image) = &self.image {
+            details.push(format!("{} × {}", image.width(), image.height()));
+            details.push(format_file_size(image.file_size));
+        }
+        if let Some(extension) = self.path.extension() {
+            details.push(extension.to_string_lossy().to_uppercase());
+        }
+        let zoom_label = match self.zoom {
+            Some(zoom) => format!("{:.0}%", zoom * 100.),
+            None => format!("Fit ({:.0}%)", self.current_scale() * 100.),
+        };
+        let focus_handle = self.focus_handle.clone();
+        let diff_tooltip = if self.loading {
+            "Loading the version in the HEAD commit"
+        } else if self.head_image.is_none() {
+            "The image isn't in the HEAD commit"
+        } else {
+            "Compare with the version in the HEAD commit"
+        };
+
+        h_flex()
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                h_flex()
+                    .gap_3()
+                    .overflow_hidden()
+                    .child(Label::new(details.join(" · ")).size(LabelSize::Small))
+                    .children(self.hovered_pixel.map(|pixel| {
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                div()
+                                    .size_3()
+                                    .border_1()
+                                    .border_color(cx.theme().colors().border)
+                                    .bg(gpui::rgba(u32::from_be_bytes(pixel.color))),
+                            )
+                            .child(
+                                Label::new(self.describe_pixel(pixel))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                    })),
+            )
+            .child(
+                h_flex()
+                    .flex_none()
+                    .gap_1()
+                    .when(self.diff_mode == Some(DiffMode::OnionSkin), |toolbar| {
+                        toolbar
+                            .child(
+                                IconButton::new("decrease-opacity", IconName::Dash)
+                                    .icon_size(IconSize::Small)
+                                    .tooltip(|cx| {
+                                        Tooltip::for_action(
+                                            "Show More of HEAD",
+                                            &DecreaseOnionSkinOpacity,
+                                            cx,
+                                        )
+                                    })
+                                    .on_click(cx.listener(|this, _, cx| {
+                                        this.decrease_onion_skin_opacity(
+                                            &DecreaseOnionSkinOpacity,
+                                            cx,
+                                        )
+                                    })),
+                            )
+                            .child(
+                                Label::new(format!(
+                                    "{:.0}% Working Copy",
+                                    self.onion_skin_opacity * 100.
+                                ))
+                                .size(LabelSize::Small),
+                            )
+                            .child(
+                                IconButton::new("increase-opacity", IconName::Plus)
+                                    .icon_size(IconSize::Small)
+                                    .tooltip(|cx| {
+                                        Tooltip::for_action(
+                                            "Show More of the Working Copy",
+                                            &IncreaseOnionSkinOpacity,
+                                            cx,
+                                        )
+                                    })
+                                    .on_click(cx.listener(|this, _, cx| {
+                                        this.increase_onion_skin_opacity(
+                                            &IncreaseOnionSkinOpacity,
+                                            cx,
+                                        )
+                                    })),
+                            )
+                    })
+                    .child(
+                        ToggleButton::new("side-by-side", "Side by Side")
+                            .first()
+                            .size(ButtonSize::Compact)
+                            .disabled(self.head_image.is_none())
+                            .selected(self.diff_mode == Some(DiffMode::SideBySide))
+                            .tooltip(move |cx| Tooltip::text(diff_tooltip, cx))
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.toggle_diff_mode(DiffMode::SideBySide, cx)
+                            })),
+                    )
+                    .child(
+                        ToggleButton::new("onion-skin", "Onion Skin")
+                            .last()
+                            .size(ButtonSize::Compact)
+                            .disabled(self.head_image.is_none())
+                            .selected(self.diff_mode == Some(DiffMode::OnionSkin))
+                            .tooltip(move |cx| Tooltip::text(diff_tooltip, cx))
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.toggle_diff_mode(DiffMode::OnionSkin, cx)
+                            })),
+                    )
+                    .child(
+                        IconButton::new("zoom-out", IconName::Dash)
+                            .icon_size(IconSize::Small)
+                            .tooltip({
+                                let focus_handle = focus_handle.clone();
+                                move |cx| {
+                                    Tooltip::for_action_in("Zoom Out", &ZoomOut, &focus_handle, cx)
+                                }
+                            })
+                            .on_click(cx.listener(|this, _, cx| this.zoom_out(&ZoomOut, cx))),
+                    )
+                    .child(Label::new(zoom_label).size(LabelSize::Small))
+                    .child(
+                        IconButton::new("zoom-in", IconName::Plus)
+                            .icon_size(IconSize::Small)
+                            .tooltip({
+                                let focus_handle = focus_handle.clone();
+                                move |cx| {
+                                    Tooltip::for_action_in("Zoom In", &ZoomIn, &focus_handle, cx)
+                                }
+                            })
+                            .on_click(cx.listener(|this, _, cx| this.zoom_in(&ZoomIn, cx))),
+                    )
+                    .child(
+                        IconButton::new("zoom-to-fit", IconName::Minimize)
+                            .icon_size(IconSize::Small)
+                            .selected(self.zoom.is_none())
+                            .tooltip({
+                                let focus_handle = focus_handle.clone();
+                                move |cx| {
+                                    Tooltip::for_action_in(
+                                        "Zoom to Fit",
+                                        &ZoomToFit,
+                                        &focus_handle,
+                                        cx,
+                                    )
+                                }
+                            })
+                            .on_click(cx.listener(|this, _, cx| this.zoom_to_fit(&ZoomToFit, cx))),
+                    )
+                    .child(
+                        IconButton::new("actual-size", IconName::Maximize)
+                            .icon_size(IconSize::Small)
+                            .selected(self.zoom == Some(1.))
+                            .tooltip(move |cx| {
+                                Tooltip::for_action_in(
+                                    "Actual Size",
+                                    &ActualSize,
+                                    &focus_handle,
+                                    cx,
+                                )
+                            })
+                            .on_click(cx.listener(|this, _, cx| this.actual_size(&ActualSize, cx))),
+                    ),
+            )
+    }
+}
+
+/// Paints a checkerboard behind images, to show which of their pixels are
+/// transparent.
+fn paint_checkerboard(bounds: Bounds<Pixels>, cx: &mut WindowContext) {
+    let square_size = 32.0;
+
+    let start_y = bounds.origin.y.0;
+    let height = bounds.size.height.0;
+    let start_x = bounds.origin.x.0;
+    let width = bounds.size.width.0;
+
+    let mut y = start_y;
+    let mut x = start_x;
+    let mut color_swapper = true;
+    // draw checkerboard pattern
+    while y <= start_y + height {
+        // Keeping track of the grid in order to be resilient to resizing
+        let start_swap = color_swapper;
+        while x <= start_x + width {
+            let rect = Bounds::new(point(px(x), px(y)), size(px(square_size), px(square_size)));
+
+            let color = if color_swapper {
+                opaque_grey(0.6, 0.4)
+            } else {
+                opaque_grey(0.7, 0.4)
+            };
+
+            cx.paint_quad(fill(rect.intersect(&bounds), color));
+            color_swapper = !color_swapper;
+            x += square_size;
+        }
+        x = start_x;
+        color_swapper = !start_swap;
+        y += square_size;
+    }
 }
 
 impl Item for ImageView {
@@ -105,7 +810,22 @@ impl Item for ImageView {
     {
         Some(cx.new_view(|cx| Self {
             path: self.path.clone(),
+            project_path: self.project_path.clone(),
+            project: self.project.clone(),
             focus_handle: cx.focus_handle(),
+            image: self.image.clone(),
+            head_image: self.head_image.clone(),
+            loading: self.loading,
+            zoom: self.zoom,
+            pan: self.pan,
+            drag_start: None,
+            hovered_pixel: None,
+            diff_mode: self.diff_mode,
+            onion_skin_opacity: self.onion_skin_opacity,
+            onion_skin: self.onion_skin.clone(),
+            pane_bounds: Rc::default(),
+            load_task: None,
+            onion_skin_task: None,
         }))
     }
 }
@@ -116,7 +836,7 @@ impl SerializableItem for ImageView {
     }
 
     fn deserialize(
-        _project: Model<Project>,
+        project: Model<Project>,
         _workspace: WeakView<Workspace>,
         workspace_id: WorkspaceId,
         item_id: ItemId,
@@ -127,9 +847,16 @@ impl SerializableItem for ImageView {
                 .get_image_path(item_id, workspace_id)?
                 .ok_or_else(|| anyhow::anyhow!("No image path found"))?;
 
-            cx.new_view(|cx| ImageView {
-                path: image_path,
-                focus_handle: cx.focus_handle(),
+            cx.new_view(|cx| {
+                let project_path =
+                    project
+                        .read(cx)
+                        .find_worktree(&image_path, cx)
+                        .map(|(worktree, path)| ProjectPath {
+                            worktree_id: worktree.read(cx).id(),
+                            path: path.into(),
+                        });
+                ImageView::new(image_path, project_path, project, cx)
             })
         })
     }
@@ -175,67 +902,39 @@ impl FocusableView for ImageView {
 
 impl Render for ImageView {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
-        let checkered_background = |bounds: Bounds<Pixels>, _, cx: &mut WindowContext| {
-            let square_size = 32.0;
-
-            let start_y = bounds.origin.y.0;
-            let height = bounds.size.height.0;
-            let start_x = bounds.origin.x.0;
-            let width = bounds.size.width.0;
-
-            let mut y = start_y;
-            let mut x = start_x;
-            let mut color_swapper = true;
-            // draw checkerboard pattern
-            while y <= start_y + height {
-                // Keeping track of the grid in order to be resilient to resizing
-                let start_swap = color_swapper;
-                while x <= start_x + width {
-                    let rect =
-                        Bounds::new(point(px(x), px(y)), size(px(square_size), px(square_size)));
-
-                    let color = if color_swapper {
-                        opaque_grey(0.6, 0.4)
-                    } else {
-                        opaque_grey(0.7, 0.4)
-                    };
-
-                    cx.paint_quad(fill(rect, color));
-                    color_swapper = !color_swapper;
-                    x += square_size;
-                }
-                x = start_x;
-                color_swapper = !start_swap;
-                y += square_size;
-            }
-        };
-
-        let checkered_background = canvas(|_, _| (), checkered_background)
-            .border_2()
-            .border_color(cx.theme().styles.colors.border)
-            .size_full()
-            .absolute()
-            .top_0()
-            .left_0();
+        // The panes record their bounds as they're painted.
+        self.pane_bounds.borrow_mut().clear();
+        let panes = self
+            .shown_versions()
+            .iter()
+            .map(|version| self.render_pane(*version, cx))
+            .collect::<Vec<_>>();
 
-        div()
+        v_flex()
+            .key_context("ImageView")
             .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::zoom_in))
+            .on_action(cx.listener(Self::zoom_out))
+            .on_action(cx.listener(Self::zoom_to_fit))
+            .on_action(cx.listener(Self::actual_size))
+            .on_action(cx.listener(Self::toggle_side_by_side_diff))
+            .on_action(cx.listener(Self::toggle_onion_skin_diff))
+            .on_action(cx.listener(Self::increase_onion_skin_opacity))
+            .on_action(cx.listener(Self::decrease_onion_skin_opacity))
             .size_full()
-            .child(checkered_background)
+            .child(self.render_toolbar(cx))
             .child(
-                div()
-                    .flex()
-                    .justify_center()
-                    .items_center()
+                h_flex()
+                    .flex_1()
                     .w_full()
-                    // TODO: In browser based Tailwind & Flex this would be h-screen and we'd use w-full
-                    .h_full()
-                    .child(
-                        img(self.path.clone())
-                            .object_fit(ObjectFit::ScaleDown)
-                            .max_w_full()
-                            .max_h_full(),
-                    ),
+                    .border_2()
+                    .border_color(cx.theme().styles.colors.border)
+                    .on_scroll_wheel(cx.listener(Self::handle_scroll_wheel))
+                    .on_mouse_down(MouseButton::Left, cx.listener(Self::handle_mouse_down))
+                    .on_mouse_up(MouseButton::Left, cx.listener(Self::handle_mouse_up))
+                    .on_mouse_up_out(MouseButton::Left, cx.listener(Self::handle_mouse_up))
+                    .on_mouse_move(cx.listener(Self::handle_mouse_move))
+                    .children(panes),
             )
     }
 }
@@ -244,17 +943,16 @@ impl ProjectItem for ImageView {
     type Item = ImageItem;
 
     fn for_project_item(
-        _project: Model<Project>,
+        project: Model<Project>,
         item: Model<Self::Item>,
         cx: &mut ViewContext<Self>,
     ) -> Self
     where
         Self: Sized,
     {
-        Self {
-            path: item.read(cx).path.clone(),
-            focus_handle: cx.focus_handle(),
-        }
+        let item = item.read(cx);
+        let (path, project_path) = (item.path.clone(), item.project_path.clone());
+        Self::new(path, Some(project_path), project, cx)
     }
 }
 
@@ -343,3 +1041,256 @@ mod persistence {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{Modifiers, ScrollDelta, TestAppContext, VisualTestContext};
+    use image::RgbaImage;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+
+    /// A 2×1 image with a red and a blue pixel, in the plain text PPM format.
+    const RED_AND_BLUE: &str = "P3\n2 1\n255\n255 0 0  0 0 255\n";
+    const GREEN_AND_BLUE: &str = "P3\n2 1\n255\n0 255 0  0 0 255\n";
+
+    #[gpui::test]
+    async fn test_zoom_clamping(cx: &mut TestAppContext) {
+        let (view, cx) = blank_image_view(cx).await;
+        view.update(cx, |view, cx| {
+            view.set_zoom(Some(1000.), cx);
+            assert_eq!(view.zoom, Some(MAX_ZOOM));
+            view.set_zoom(Some(0.), cx);
+            assert_eq!(view.zoom, Some(MIN_ZOOM));
+
+            view.actual_size(&ActualSize, cx);
+            assert_eq!(view.zoom, Some(1.));
+            view.zoom_in(&ZoomIn, cx);
+            assert_eq!(view.zoom, Some(ZOOM_STEP));
+            view.zoom_out(&ZoomOut, cx);
+            view.zoom_out(&ZoomOut, cx);
+            assert_eq!(view.zoom, Some(1. / ZOOM_STEP));
+
+            for _ in 0..100 {
+                view.zoom_in(&ZoomIn, cx);
+            }
+            assert_eq!(view.zoom, Some(MAX_ZOOM));
+            for _ in 0..100 {
+                view.zoom_out(&ZoomOut, cx);
+            }
+            assert_eq!(view.zoom, Some(MIN_ZOOM));
+
+            view.pan = point(px(10.), px(20.));
+            view.zoom_to_fit(&ZoomToFit, cx);
+            assert_eq!(view.zoom, None);
+            assert_eq!(view.pan, Point::default(), "Fitting the image centers it");
+
+            // Zooming with the mouse wheel is clamped too.
+            let bounds = set_pane_bounds(view);
+            view.handle_scroll_wheel(
+                &ScrollWheelEvent {
+                    position: bounds.center(),
+                    delta: ScrollDelta::Pixels(point(px(0.), px(-100_000.))),
+                    modifiers: Modifiers::secondary_key(),
+                    ..Default::default()
+                },
+                cx,
+            );
+            assert_eq!(view.zoom, Some(MAX_ZOOM));
+            view.handle_scroll_wheel(
+                &ScrollWheelEvent {
+                    position: bounds.center(),
+                    delta: ScrollDelta::Pixels(point(px(0.), px(100_000.))),
+                    modifiers: Modifiers::secondary_key(),
+                    ..Default::default()
+                },
+                cx,
+            );
+            assert_eq!(view.zoom, Some(MIN_ZOOM));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_pan(cx: &mut TestAppContext) {
+        let (view, cx) = blank_image_view(cx).await;
+        view.update(cx, |view, cx| {
+            let bounds = set_pane_bounds(view);
+            let image = view.image.clone().unwrap();
+
+            // The 100×100 image is centered in the 200×200 pane.
+            view.set_zoom(Some(1.), cx);
+            let position = point(px(60.5), px(70.5));
+            assert_eq!(view.pixel_at(&image, bounds, 1., position), Some((10, 20)));
+            assert_eq!(
+                view.pixel_at(&image, bounds, 1., point(px(49.), px(70.))),
+                None
+            );
+            view.pan = point(px(-10.), px(5.));
+            assert_eq!(view.pixel_at(&image, bounds, 1., position), Some((20, 15)));
+
+            // Zooming with the mouse wheel keeps the pixel under the mouse in place.
+            view.handle_scroll_wheel(
+                &ScrollWheelEvent {
+                    position,
+                    delta: ScrollDelta::Pixels(point(px(0.), px(-100.))),
+                    modifiers: Modifiers::secondary_key(),
+                    ..Default::default()
+                },
+                cx,
+            );
+            let scale = view.zoom.unwrap();
+            assert!(scale > 1.);
+            assert_eq!(
+                view.pixel_at(&image, bounds, scale, position),
+                Some((20, 15))
+            );
+
+            // Scrolling without the modifier pans the zoomed image.
+            let pan = view.pan;
+            view.handle_scroll_wheel(
+                &ScrollWheelEvent {
+                    position,
+                    delta: ScrollDelta::Pixels(point(px(3.), px(-4.))),
+                    ..Default::default()
+                },
+                cx,
+            );
+            assert_eq!(view.pan, pan + point(px(3.), px(-4.)));
+
+            // Dragging a fitted image pans it from its fitted scale.
+            view.zoom_to_fit(&ZoomToFit, cx);
+            view.handle_mouse_down(
+                &MouseDownEvent {
+                    button: MouseButton::Left,
+                    position,
+                    ..Default::default()
+                },
+                cx,
+            );
+            assert_eq!(view.zoom, Some(1.));
+            view.handle_mouse_move(
+                &MouseMoveEvent {
+                    position: position + point(px(30.), px(-20.)),
+                    pressed_button: Some(MouseButton::Left),
+                    ..Default::default()
+                },
+                cx,
+            );
+            assert_eq!(view.pan, point(px(30.), px(-20.)));
+            view.handle_mouse_up(&MouseUpEvent::default(), cx);
+            view.handle_mouse_move(
+                &MouseMoveEvent {
+                    position,
+                    ..Default::default()
+                },
+                cx,
+            );
+            assert_eq!(view.pan, point(px(30.), px(-20.)));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_head_image(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "image.ppm": RED_AND_BLUE,
+                "new.ppm": RED_AND_BLUE,
+            }),
+        )
+        .await;
+        fs.set_head_for_repo(
+            Path::new("/dir/.git"),
+            &[(Path::new("image.ppm"), GREEN_AND_BLUE.to_string())],
+        );
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let cx = cx.add_empty_window();
+        cx.run_until_parked();
+
+        let view = open_image_view(&project, "image.ppm", cx);
+        cx.run_until_parked();
+        view.update(cx, |view, cx| {
+            assert!(!view.loading);
+            let image = view.image.clone().unwrap();
+            let head_image = view.head_image.clone().unwrap();
+            assert_eq!(image.pixel(0, 0), Some([255, 0, 0, 255]));
+            assert_eq!(head_image.pixel(0, 0), Some([0, 255, 0, 255]));
+            assert_eq!(head_image.pixel(1, 0), Some([0, 0, 255, 255]));
+
+            view.toggle_diff_mode(DiffMode::SideBySide, cx);
+            assert_eq!(
+                view.shown_versions(),
+                &[ImageVersion::Head, ImageVersion::WorkingCopy]
+            );
+        });
+
+        // Images that aren't in the HEAD commit can't be compared with it.
+        let view = open_image_view(&project, "new.ppm", cx);
+        cx.run_until_parked();
+        view.update(cx, |view, cx| {
+            assert!(view.image.is_some());
+            assert!(view.head_image.is_none());
+            view.toggle_diff_mode(DiffMode::SideBySide, cx);
+            assert_eq!(view.diff_mode, None);
+        });
+    }
+
+    fn open_image_view(
+        project: &Model<Project>,
+        path: &str,
+        cx: &mut VisualTestContext,
+    ) -> View<ImageView> {
+        let project_path = project.update(cx, |project, cx| ProjectPath {
+            worktree_id: project.worktrees(cx).next().unwrap().read(cx).id(),
+            path: Path::new(path).into(),
+        });
+        let abs_path = Path::new("/dir").join(path);
+        let project = project.clone();
+        cx.new_view(|cx| ImageView::new(abs_path, Some(project_path), project, cx))
+    }
+
+    /// Opens a view of a missing file, with a blank 100×100 image in its place.
+    async fn blank_image_view(
+        cx: &mut TestAppContext,
+    ) -> (View<ImageView>, &mut VisualTestContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({})).await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let cx = cx.add_empty_window();
+        let view =
+            cx.new_view(|cx| ImageView::new(PathBuf::from("/dir/missing.png"), None, project, cx));
+        cx.run_until_parked();
+        view.update(cx, |view, _| {
+            let pixels = RgbaImage::new(100, 100);
+            view.image = Some(Arc::new(LoadedImage {
+                render_image: render_image(pixels.clone()),
+                pixels,
+                file_size: 0,
+            }));
+        });
+        (view, cx)
+    }
+
+    /// Lays the view out as a single 200×200 pane.
+    fn set_pane_bounds(view: &mut ImageView) -> Bounds<Pixels> {
+        let bounds = Bounds::new(point(px(0.), px(0.)), size(px(200.), px(200.)));
+        *view.pane_bounds.borrow_mut() = vec![(ImageVersion::WorkingCopy, bounds)];
+        bounds
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use gpui::RenderImage;
+use image::{Rgba, RgbaImage};
+
+/// An image decoded into its pixels, so that their colors can be inspected
+/// and the image compared with other versions of itself.
+pub struct LoadedImage {
+    pub pixels: RgbaImage,
+    pub render_image: Arc<RenderImage>,
+    /// The size of the image's file, in bytes.
+    pub file_size: u64,
+}
+
+impl LoadedImage {
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let pixels = image::load_from_memory(bytes)?.into_rgba8();
+        Ok(Self {
+            render_image: render_image(pixels.clone()),
+            pixels,
+            file_size: bytes.len() as u64,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.pixels.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.pixels.height()
+    }
+
+    /// Returns the color of a pixel, as RGBA.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        self.pixels.get_pixel_checked(x, y).map(|pixel| pixel.0)
+    }
+}
+
+/// Makes an image that GPUI can render from RGBA pixels, which it expects in
+/// BGRA order.
+pub fn render_image(mut pixels: RgbaImage) -> Arc<RenderImage> {
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+    Arc::new(RenderImage::new(vec![image::Frame::new(pixels)]))
+}
+
+/// Fades from one image to another, by the opacity of the second, with both
+/// aligned at their top-left corners. The result is as large as both.
+pub fn onion_skin(below: &RgbaImage, above: &RgbaImage, opacity: f32) -> RgbaImage {
+    let width = below.width().max(above.width());
+    let height = below.height().max(above.height());
+    let transparent = Rgba([0, 0, 0, 0]);
+    RgbaImage::from_fn(width, height, |x, y| {
+        let below = below.get_pixel_checked(x, y).unwrap_or(&transparent);
+        let above = above.get_pixel_checked(x, y).unwrap_or(&transparent);
+        let mut blended = [0; 4];
+        for (channel, value) in blended.iter_mut().enumerate() {
+            let (below, above) = (below[channel] as f32, above[channel] as f32);
+            *value = (below + (above - below) * opacity).round() as u8;
+        }
+        Rgba(blended)
+    })
+}
+
+/// Writes a size in bytes with the largest unit it's at least one of, such as
+/// `12.3 KB`.
+pub fn format_file_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.;
+    let mut unit = UNITS[0];
+    for next_unit in &UNITS[1..] {
+        if size < 1024. {
+            break;
+        }
+        size /= 1024.;
+        unit = next_unit;
+    }
+    format!("{size:.1} {unit}")
+}
@@ -0,0 +1,700 @@
+//! Reads JSON and YAML documents into [`Value`]s by way of their syntax trees,
+//! and writes values back out in either format.
+use std::ops::Range;
+
+use anyhow::{anyhow, bail, Context as _, Result};
+use collections::HashMap;
+use language::Node;
+use serde::Serialize;
+use serde_json::{ser::PrettyFormatter, Map, Value};
+
+/// Reads the value of a document from its syntax tree in the JSON grammar.
+/// Comments are left out.
+pub fn json_value(root: Node, text_for_range: &dyn Fn(Range<usize>) -> String) -> Result<Value> {
+    if root.has_error() {
+        bail!("the document has syntax errors");
+    }
+    json_node_value(root, text_for_range)
+}
+
+fn json_node_value(node: Node, text_for_range: &dyn Fn(Range<usize>) -> String) -> Result<Value> {
+    let mut cursor = node.walk();
+    let mut children = node
+        .named_children(&mut cursor)
+        .filter(|child| child.kind() != "comment");
+    Ok(match node.kind() {
+        "document" => match children.next() {
+            Some(child) => json_node_value(child, text_for_range)?,
+            None => bail!("the document is empty"),
+        },
+        "object" => {
+            let mut map = Map::new();
+            for pair in children {
+                let key = pair.child_by_field_name("key").context("missing key")?;
+                let key = serde_json::from_str(&text_for_range(key.byte_range()))?;
+                let value = pair.child_by_field_name("value").context("missing value")?;
+                map.insert(key, json_node_value(value, text_for_range)?);
+            }
+            Value::Object(map)
+        }
+        "array" => Value::Array(
+            children
+                .map(|child| json_node_value(child, text_for_range))
+                .collect::<Result<_>>()?,
+        ),
+        "string" | "number" => serde_json::from_str(&text_for_range(node.byte_range()))?,
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        "null" => Value::Null,
+        kind => bail!("unexpected {kind} at offset {}", node.start_byte()),
+    })
+}
+
+/// Reads the value of a document from its syntax tree in the YAML grammar.
+/// Aliases are replaced with the values of their anchors, and a stream of
+/// several documents is read as an array of them.
+pub fn yaml_value(root: Node, text_for_range: &dyn Fn(Range<usize>) -> String) -> Result<Value> {
+    if root.has_error() {
+        bail!("the document has syntax errors");
+    }
+    YamlReader {
+        text_for_range,
+        anchors: HashMap::default(),
+    }
+    .value(root)
+}
+
+struct YamlReader<'a> {
+    text_for_range: &'a dyn Fn(Range<usize>) -> String,
+    anchors: HashMap<String, Value>,
+}
+
+impl YamlReader<'_> {
+    fn text(&self, node: Node) -> String {
+        (self.text_for_range)(node.byte_range())
+    }
+
+    fn value(&mut self, node: Node) -> Result<Value> {
+        let mut cursor = node.walk();
+        let children = node
+            .named_children(&mut cursor)
+            .filter(|child| child.kind() != "comment")
+            .collect::<Vec<_>>();
+        Ok(match node.kind() {
+            "stream" => {
+                let mut documents = children
+                    .into_iter()
+                    .filter(|child| child.kind() == "document")
+                    .map(|document| self.value(document))
+                    .collect::<Result<Vec<_>>>()?;
+                match documents.len() {
+                    0 => Value::Null,
+                    1 => documents.pop().unwrap(),
+                    _ => Value::Array(documents),
+                }
+            }
+            "document" => match children
+                .into_iter()
+                .find(|child| matches!(child.kind(), "block_node" | "flow_node"))
+            {
+                Some(child) => self.value(child)?,
+                None => Value::Null,
+            },
+            "block_node" | "flow_node" => {
+                let mut anchor = None;
+                let mut content = None;
+                for child in children {
+                    match child.kind() {
+                        "anchor" => anchor = Some(self.text(child).trim_start_matches('&').into()),
+                        "tag" => {}
+                        _ => content = Some(child),
+                    }
+                }
+                let value = match content {
+                    Some(content) => self.value(content)?,
+                    None => Value::Null,
+                };
+                if let Some(anchor) = anchor {
+                    self.anchors.insert(anchor, value.clone());
+                }
+                value
+            }
+            "alias" => {
+                let text = self.text(node);
+                let name = text.trim_start_matches('*');
+                self.anchors
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("undefined alias *{name}"))?
+            }
+            "block_mapping" | "flow_mapping" => {
+                let mut map = Map::new();
+                let mut merged = Vec::new();
+                for pair in children {
+                    let (key, value) = match pair.kind() {
+                        "block_mapping_pair" | "flow_pair" => (
+                            pair.child_by_field_name("key"),
+                            pair.child_by_field_name("value"),
+                        ),
+                        // A key without a value in a flow mapping.
+                        _ => (Some(pair), None),
+                    };
+                    let key = match key {
+                        Some(key) => yaml_scalar_text(key, self.text_for_range),
+                        None => String::new(),
+                    };
+                    let value = match value {
+                        Some(value) => self.value(value)?,
+                        None => Value::Null,
+                    };
+                    if key == "<<" {
+                        merged.push(value);
+                    } else {
+                        map.insert(key, value);
+                    }
+                }
+                // Merged keys don't replace the mapping's own.
+                for value in merged {
+                    let maps = match value {
+                        Value::Array(values) => values,
+                        value => vec![value],
+                    };
+                    for merged_map in maps {
+                        if let Value::Object(merged_map) = merged_map {
+                            for (key, value) in merged_map {
+                                map.entry(key).or_insert(value);
+                            }
+                        }
+                    }
+                }
+                Value::Object(map)
+            }
+            "block_sequence" => Value::Array(
+                children
+                    .into_iter()
+                    .map(|item| {
+                        let mut cursor = item.walk();
+                        let value = item
+                            .named_children(&mut cursor)
+                            .find(|child| child.kind() != "comment");
+                        match value {
+                            Some(value) => self.value(value),
+                            None => Ok(Value::Null),
+                        }
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            "flow_sequence" => Value::Array(
+                children
+                    .into_iter()
+                    .map(|item| {
+                        if item.kind() == "flow_pair" {
+                            let mut map = Map::new();
+                            let key = item
+                                .child_by_field_name("key")
+                                .map(|key| yaml_scalar_text(key, self.text_for_range))
+                                .unwrap_or_default();
+                            let value = match item.child_by_field_name("value") {
+                                Some(value) => self.value(value)?,
+                                None => Value::Null,
+                            };
+                            map.insert(key, value);
+                            Ok(Value::Object(map))
+                        } else {
+                            self.value(item)
+                        }
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            "plain_scalar" => plain_scalar_value(&fold_lines(&self.text(node), false)),
+            "double_quote_scalar" | "single_quote_scalar" | "block_scalar" => {
+                Value::String(yaml_scalar_text(node, self.text_for_range))
+            }
+            kind => bail!("unexpected {kind} at offset {}", node.start_byte()),
+        })
+    }
+}
+
+/// Returns the text of a YAML scalar, such as a key, without its quotes or
+/// escapes. Other nodes are returned as they're written.
+pub fn yaml_scalar_text(node: Node, text_for_range: &dyn Fn(Range<usize>) -> String) -> String {
+    let text = text_for_range(node.byte_range());
+    match node.kind() {
+        "block_node" | "flow_node" => {
+            let mut cursor = node.walk();
+            let content = node
+                .named_children(&mut cursor)
+                .find(|child| !matches!(child.kind(), "anchor" | "tag" | "comment"));
+            match content {
+                Some(content) => yaml_scalar_text(content, text_for_range),
+                None => String::new(),
+            }
+        }
+        "plain_scalar" => fold_lines(&text, false),
+        "single_quote_scalar" => {
+            let inner = text
+                .strip_prefix('\'')
+                .and_then(|text| text.strip_suffix('\''))
+                .unwrap_or(&text);
+            fold_lines(inner, false).replace("''", "'")
+        }
+        "double_quote_scalar" => {
+            let inner = text
+                .strip_prefix('"')
+                .and_then(|text| text.strip_suffix('"'))
+                .unwrap_or(&text);
+            unescape_double_quoted(&fold_lines(inner, true))
+        }
+        "block_scalar" => block_scalar_text(&text),
+        _ => text.trim().to_string(),
+    }
+}
+
+/// Joins the lines of a multi-line flow scalar with spaces, keeping a line
+/// break for each blank line. In double-quoted scalars, escaped line breaks
+/// are joined without a space.
+fn fold_lines(text: &str, escapes: bool) -> String {
+    let mut result = String::new();
+    let mut blank_lines = 0;
+    for (ix, line) in text.split('\n').enumerate() {
+        let line = if ix == 0 {
+            line.trim_end_matches(['\r', ' ', '\t'])
+        } else {
+            line.trim_matches(['\r', ' ', '\t'])
+        };
+        if ix > 0 && line.is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        if ix > 0 {
+            let escaped_break = escapes
+                && blank_lines == 0
+                && result.ends_with('\\')
+                && (result.len() - result.trim_end_matches('\\').len()) % 2 == 1;
+            if escaped_break {
+                result.pop();
+            } else if blank_lines > 0 {
+                result.extend(std::iter::repeat('\n').take(blank_lines));
+            } else {
+                result.push(' ');
+            }
+        }
+        blank_lines = 0;
+        result.push_str(line);
+    }
+    result
+}
+
+fn unescape_double_quoted(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+        let Some(escape) = chars.next() else {
+            result.push(ch);
+            break;
+        };
+        let hex_digits = match escape {
+            'x' => 2,
+            'u' => 4,
+            'U' => 8,
+            _ => 0,
+        };
+        if hex_digits > 0 {
+            let digits = chars.by_ref().take(hex_digits).collect::<String>();
+            match u32::from_str_radix(&digits, 16)
+                .ok()
+                .and_then(char::from_u32)
+            {
+                Some(ch) => result.push(ch),
+                None => {
+                    result.push('\\');
+                    result.push(escape);
+                    result.push_str(&digits);
+                }
+            }
+            continue;
+        }
+        result.push(match escape {
+            '0' => '\0',
+            'a' => '\x07',
+            'b' => '\x08',
+            't' | '\t' => '\t',
+            'n' => '\n',
+            'v' => '\x0b',
+            'f' => '\x0c',
+            'r' => '\r',
+            'e' => '\x1b',
+            'N' => '\u{85}',
+            '_' => '\u{a0}',
+            'L' => '\u{2028}',
+            'P' => '\u{2029}',
+            ' ' | '"' | '/' | '\\' => escape,
+            _ => {
+                result.push('\\');
+                escape
+            }
+        });
+    }
+    result
+}
+
+/// Returns the text of a literal (`|`) or folded (`>`) block scalar, with its
+/// indentation removed and its final line breaks chomped as its header says.
+fn block_scalar_text(text: &str) -> String {
+    let (header, body) = text.split_once('\n').unwrap_or((text, ""));
+    let header = header.split('#').next().unwrap_or_default().trim();
+    let folded = header.starts_with('>');
+
+    let lines = body
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect::<Vec<_>>();
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start_matches(' ').len())
+        .min()
+        .unwrap_or(0);
+    let lines = lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or_default())
+        .collect::<Vec<_>>();
+    let content_len = lines
+        .iter()
+        .rposition(|line| !line.is_empty())
+        .map_or(0, |ix| ix + 1);
+    let trailing_blank_lines = lines.len().saturating_sub(content_len + 1);
+    let content = &lines[..content_len];
+
+    let mut result = String::new();
+    if folded {
+        let mut blank_lines = 0;
+        let mut previous_more_indented = false;
+        for (ix, line) in content.iter().enumerate() {
+            if line.is_empty() {
+                blank_lines += 1;
+                continue;
+            }
+            let more_indented = line.starts_with([' ', '\t']);
+            if ix > 0 {
+                if more_indented || previous_more_indented {
+                    result.extend(std::iter::repeat('\n').take(blank_lines + 1));
+                } else if blank_lines > 0 {
+                    result.extend(std::iter::repeat('\n').take(blank_lines));
+                } else {
+                    result.push(' ');
+                }
+            }
+            blank_lines = 0;
+            previous_more_indented = more_indented;
+            result.push_str(line);
+        }
+    } else {
+        result = content.join("\n");
+    }
+
+    if header.contains('-') {
+        // Strip all final line breaks.
+    } else if header.contains('+') {
+        result.extend(std::iter::repeat('\n').take(trailing_blank_lines + 1));
+    } else if !content.is_empty() {
+        result.push('\n');
+    }
+    result
+}
+
+/// Returns the value of an unquoted scalar under YAML's core schema.
+fn plain_scalar_value(text: &str) -> Value {
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => return Value::Null,
+        "true" | "True" | "TRUE" => return Value::Bool(true),
+        "false" | "False" | "FALSE" => return Value::Bool(false),
+        _ => {}
+    }
+
+    let (sign, digits) = match text.strip_prefix(['-', '+']) {
+        Some(digits) => (&text[..1], digits),
+        None => ("", text),
+    };
+    let integer = if let Some(hex) = digits.strip_prefix("0x").filter(|_| sign.is_empty()) {
+        i64::from_str_radix(hex, 16).ok()
+    } else if let Some(octal) = digits.strip_prefix("0o").filter(|_| sign.is_empty()) {
+        i64::from_str_radix(octal, 8).ok()
+    } else if !digits.is_empty() && digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        text.parse::<i64>().ok()
+    } else {
+        None
+    };
+    if let Some(integer) = integer {
+        return Value::from(integer);
+    }
+
+    let is_decimal = digits.bytes().any(|byte| byte.is_ascii_digit())
+        && digits
+            .bytes()
+            .all(|byte| byte.is_ascii_digit() || matches!(byte, b'.' | b'e' | b'E' | b'-' | b'+'));
+    if is_decimal {
+        if let Some(number) = text
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+        {
+            return Value::Number(number);
+        }
+    }
+
+    Value::String(text.to_string())
+}
+
+/// Sorts the keys of every object in the value.
+pub fn sort_keys(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            let mut entries = std::mem::take(map).into_iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (key, mut value) in entries {
+                sort_keys(&mut value);
+                map.insert(key, value);
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(sort_keys),
+        _ => {}
+    }
+}
+
+/// Writes the value as JSON, indenting nested values by the given indent.
+pub fn to_json(value: &Value, indent: &str) -> String {
+    let mut json = Vec::new();
+    let mut serializer = serde_json::Serializer::with_formatter(
+        &mut json,
+        PrettyFormatter::with_indent(indent.as_bytes()),
+    );
+    value
+        .serialize(&mut serializer)
+        .expect("JSON values can always be serialized");
+    let mut json = String::from_utf8(json).expect("serialized JSON is UTF-8");
+    json.push('\n');
+    json
+}
+
+/// Writes the value as block-style YAML, indenting nested values by the given
+/// indent.
+pub fn to_yaml(value: &Value, indent: &str) -> String {
+    let mut yaml = String::new();
+    write_yaml_block(value, "", indent, &mut yaml);
+    yaml
+}
+
+fn write_yaml_block(value: &Value, prefix: &str, indent: &str, yaml: &mut String) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                yaml.push_str(prefix);
+                yaml.push_str(&yaml_string(key));
+                yaml.push(':');
+                if is_block(value) {
+                    yaml.push('\n');
+                    write_yaml_block(value, &format!("{prefix}{indent}"), indent, yaml);
+                } else {
+                    yaml.push(' ');
+                    yaml.push_str(&yaml_flow_value(value));
+                    yaml.push('\n');
+                }
+            }
+        }
+        Value::Array(values) if !values.is_empty() => {
+            for value in values {
+                yaml.push_str(prefix);
+                yaml.push_str("- ");
+                if is_block(value) {
+                    // The first line of a nested block follows the dash, and
+                    // the rest are aligned with it.
+                    let nested_prefix = format!("{prefix}  ");
+                    let mut nested = String::new();
+                    write_yaml_block(value, &nested_prefix, indent, &mut nested);
+                    yaml.push_str(&nested[nested_prefix.len()..]);
+                } else {
+                    yaml.push_str(&yaml_flow_value(value));
+                    yaml.push('\n');
+                }
+            }
+        }
+        value => {
+            yaml.push_str(prefix);
+            yaml.push_str(&yaml_flow_value(value));
+            yaml.push('\n');
+        }
+    }
+}
+
+fn is_block(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => !map.is_empty(),
+        Value::Array(values) => !values.is_empty(),
+        _ => false,
+    }
+}
+
+fn yaml_flow_value(value: &Value) -> String {
+    match value {
+        Value::String(string) => yaml_string(string),
+        Value::Object(_) => "{}".to_string(),
+        Value::Array(_) => "[]".to_string(),
+        value => value.to_string(),
+    }
+}
+
+/// Writes a string as a plain scalar, or as a double-quoted one when it would
+/// be read as something else unquoted.
+fn yaml_string(string: &str) -> String {
+    const INDICATORS: &[char] = &[
+        '-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%', '@',
+        '`',
+    ];
+    // Words that YAML 1.1 reads as booleans, which some tools still follow.
+    const YAML_1_1_BOOLEANS: &[&str] = &["y", "n", "yes", "no", "on", "off"];
+
+    let needs_quotes = string.trim() != string
+        || string.starts_with(INDICATORS)
+        || string.contains(": ")
+        || string.contains(" #")
+        || string.ends_with(':')
+        || string.chars().any(char::is_control)
+        || YAML_1_1_BOOLEANS.contains(&string.to_lowercase().as_str())
+        || string
+            .to_lowercase()
+            .trim_start_matches(['-', '+'])
+            .starts_with(".inf")
+        || string.to_lowercase() == ".nan"
+        || plain_scalar_value(string) != Value::String(string.to_string());
+    if needs_quotes {
+        Value::from(string).to_string()
+    } else {
+        string.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language::Parser;
+    use serde_json::json;
+
+    fn parse(language: &tree_sitter::Language, text: &str) -> language::Tree {
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        parser.parse(text, None).unwrap()
+    }
+
+    fn read_json(text: &str) -> Result<Value> {
+        let tree = parse(&tree_sitter_json::language(), text);
+        json_value(tree.root_node(), &|range| text[range].to_string())
+    }
+
+    fn read_yaml(text: &str) -> Result<Value> {
+        let tree = parse(&tree_sitter_yaml::language(), text);
+        yaml_value(tree.root_node(), &|range| text[range].to_string())
+    }
+
+    #[test]
+    fn test_read_json() {
+        let value = read_json(
+            r#"{
+                // The name.
+                "name": "a \"b\"\n",
+                "values": [1, 2.5, true, null, {}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            json!({ "name": "a \"b\"\n", "values": [1, 2.5, true, null, {}] })
+        );
+        assert!(read_json("{ \"a\": }").is_err());
+    }
+
+    #[test]
+    fn test_read_yaml() {
+        let text = r#"
+defaults: &defaults
+  retries: 3
+  timeout: 1.5
+service:
+  <<: *defaults
+  retries: 5
+  name: "web\tserver"
+  quoted: 'it''s'
+  enabled: yes
+  disabled: false
+  empty:
+  ports: [80, 0x1bb]
+  hosts:
+    - a.example.com # primary
+    - name: b
+      weight: 2
+  script: |
+    echo one
+    echo two
+  summary: >-
+    folded
+    text
+"#;
+        assert_eq!(
+            read_yaml(text).unwrap(),
+            json!({
+                "defaults": { "retries": 3, "timeout": 1.5 },
+                "service": {
+                    "retries": 5,
+                    "name": "web\tserver",
+                    "quoted": "it's",
+                    "enabled": "yes",
+                    "disabled": false,
+                    "empty": null,
+                    "ports": [80, 443],
+                    "hosts": ["a.example.com", { "name": "b", "weight": 2 }],
+                    "script": "echo one\necho two\n",
+                    "summary": "folded text",
+                    "timeout": 1.5,
+                }
+            })
+        );
+        assert_eq!(
+            read_yaml("---\na: 1\n---\nb: 2\n").unwrap(),
+            json!([{ "a": 1 }, { "b": 2 }])
+        );
+    }
+
+    #[test]
+    fn test_write_and_sort() {
+        let mut value = json!({
+            "zeta": [1, { "b": "yes", "a": "" }, [], [2, 3]],
+            "alpha": { "key: value": "line\nbreak", "n": "12", "x": null },
+        });
+        sort_keys(&mut value);
+        assert_eq!(
+            to_yaml(&value, "  "),
+            concat!(
+                "alpha:\n",
+                "  \"key: value\": \"line\\nbreak\"\n",
+                "  n: \"12\"\n",
+                "  x: null\n",
+                "zeta:\n",
+                "  - 1\n",
+                "  - a: \"\"\n",
+                "    b: \"yes\"\n",
+                "  - []\n",
+                "  - - 2\n",
+                "    - 3\n",
+            )
+        );
+        assert_eq!(read_yaml(&to_yaml(&value, "    ")).unwrap(), value);
+        assert_eq!(
+            to_json(&json!({ "b": [1], "a": {} }), "\t"),
+            "{\n\t\"b\": [\n\t\t1\n\t],\n\t\"a\": {}\n}\n"
+        );
+    }
+}
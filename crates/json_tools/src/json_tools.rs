@@ -0,0 +1,188 @@
+//! Commands for JSON and YAML documents: sorting the keys of their objects and
+//! converting between the two formats.
+mod conversion;
+mod node_path;
+mod node_path_indicator;
+
+use std::ops::Range;
+
+use anyhow::{Context as _, Result};
+use editor::Editor;
+use gpui::{actions, AppContext, Model, View, ViewContext, VisualContext, WindowContext};
+use language::{language_settings::AllLanguageSettings, Buffer};
+use serde_json::Value;
+use settings::Settings;
+use util::ResultExt;
+use workspace::{notifications::NotificationId, Toast};
+
+pub use conversion::*;
+pub use node_path::*;
+pub use node_path_indicator::NodePathIndicator;
+
+actions!(json_tools, [SortObjectKeys, ToJson, ToYaml]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(register).detach();
+}
+
+fn register(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let handle = cx.view().downgrade();
+    editor
+        .register_action(move |_: &SortObjectKeys, cx| {
+            if let Some(editor) = handle.upgrade() {
+                sort_object_keys(editor, cx);
+            }
+        })
+        .detach();
+
+    let handle = cx.view().downgrade();
+    editor
+        .register_action(move |_: &ToJson, cx| {
+            if let Some(editor) = handle.upgrade() {
+                convert(editor, DocumentFormat::Json, cx);
+            }
+        })
+        .detach();
+
+    let handle = cx.view().downgrade();
+    editor
+        .register_action(move |_: &ToYaml, cx| {
+            if let Some(editor) = handle.upgrade() {
+                convert(editor, DocumentFormat::Yaml, cx);
+            }
+        })
+        .detach();
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DocumentFormat {
+    Json,
+    Yaml,
+}
+
+impl DocumentFormat {
+    fn for_language(language_name: &str) -> Option<Self> {
+        match language_name {
+            "JSON" | "JSONC" => Some(Self::Json),
+            "YAML" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    fn language_name(self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::Yaml => "YAML",
+        }
+    }
+
+    /// Writes the value in this format, indented as the language's settings
+    /// say.
+    fn write(self, value: &Value, cx: &AppContext) -> String {
+        let settings = AllLanguageSettings::get_global(cx).language(Some(self.language_name()));
+        let indent = match self {
+            // YAML doesn't allow tabs for indentation.
+            Self::Json if settings.hard_tabs => "\t".to_string(),
+            _ => " ".repeat(settings.tab_size.get() as usize),
+        };
+        match self {
+            Self::Json => to_json(value, &indent),
+            Self::Yaml => to_yaml(value, &indent),
+        }
+    }
+}
+
+/// Reads the value of the JSON or YAML document in the editor.
+fn read_document(
+    editor: &View<Editor>,
+    cx: &AppContext,
+) -> Result<(Model<Buffer>, DocumentFormat, Value)> {
+    let buffer = editor
+        .read(cx)
+        .buffer()
+        .read(cx)
+        .as_singleton()
+        .context("only a single JSON or YAML buffer can be converted")?;
+    let snapshot = buffer.read(cx).snapshot();
+    let (layer, format) = snapshot
+        .syntax_layers()
+        .find_map(|layer| Some((layer, DocumentFormat::for_language(&layer.language.name())?)))
+        .context("the buffer isn't a JSON or YAML document")?;
+
+    let text_for_range = |range: Range<usize>| snapshot.text_for_range(range).collect::<String>();
+    let value = match format {
+        DocumentFormat::Json => json_value(layer.node(), &text_for_range)?,
+        DocumentFormat::Yaml => yaml_value(layer.node(), &text_for_range)?,
+    };
+    Ok((buffer, format, value))
+}
+
+fn sort_object_keys(editor: View<Editor>, cx: &mut WindowContext) {
+    let (buffer, format, mut value) = match read_document(&editor, cx) {
+        Ok(document) => document,
+        Err(error) => return show_error(&editor, "Couldn't sort the keys", error, cx),
+    };
+    sort_keys(&mut value);
+    let diff = buffer.read(cx).diff(format.write(&value, cx), cx);
+    cx.spawn(|mut cx| async move {
+        let diff = diff.await;
+        buffer
+            .update(&mut cx, |buffer, cx| buffer.apply_diff(diff, cx))
+            .log_err();
+    })
+    .detach();
+}
+
+/// Opens the document in the editor, written in the given format, in a new
+/// buffer.
+fn convert(editor: View<Editor>, format: DocumentFormat, cx: &mut WindowContext) {
+    let Some(workspace) = editor.read(cx).workspace() else {
+        return;
+    };
+    let text = match read_document(&editor, cx) {
+        Ok((_, _, value)) => format.write(&value, cx),
+        Err(error) => {
+            let message = format!("Couldn't convert to {}", format.language_name());
+            return show_error(&editor, &message, error, cx);
+        }
+    };
+
+    workspace.update(cx, |workspace, cx| {
+        let project = workspace.project().clone();
+        let language = project
+            .read(cx)
+            .languages()
+            .language_for_name(format.language_name());
+        let create_buffer = project.update(cx, |project, cx| project.create_buffer(cx));
+        cx.spawn(|workspace, mut cx| async move {
+            let language = language.await.log_err();
+            let buffer = create_buffer.await?;
+            buffer.update(&mut cx, |buffer, cx| {
+                buffer.edit([(0..0, text)], None, cx);
+                buffer.set_language(language, cx);
+            })?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let editor = cx.new_view(|cx| Editor::for_buffer(buffer, Some(project), cx));
+                workspace.add_item_to_active_pane(Box::new(editor), None, true, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    });
+}
+
+fn show_error(editor: &View<Editor>, message: &str, error: anyhow::Error, cx: &mut WindowContext) {
+    struct JsonToolsError;
+
+    let Some(workspace) = editor.read(cx).workspace() else {
+        return;
+    };
+    workspace.update(cx, |workspace, cx| {
+        workspace.show_toast(
+            Toast::new(
+                NotificationId::unique::<JsonToolsError>(),
+                format!("{message}: {error:#}"),
+            ),
+            cx,
+        );
+    });
+}
@@ -0,0 +1,142 @@
+//! Describes where a position is within a JSON or YAML document, as the keys
+//! and indices leading to the value that contains it, such as
+//! `services.web.ports[0]`.
+use std::ops::Range;
+
+use language::Node;
+
+use crate::conversion::yaml_scalar_text;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Returns the keys and indices of the values containing the offset, from the
+/// outermost, in a syntax tree of the JSON or YAML grammar.
+pub fn node_path(
+    root: Node,
+    offset: usize,
+    text_for_range: &dyn Fn(Range<usize>) -> String,
+) -> Vec<PathSegment> {
+    let Some(mut node) = root.descendant_for_byte_range(offset, offset) else {
+        return Vec::new();
+    };
+
+    let mut segments = Vec::new();
+    loop {
+        match node.kind() {
+            // JSON
+            "pair" => {
+                if let Some(key) = node.child_by_field_name("key") {
+                    let key_text = text_for_range(key.byte_range());
+                    let key = serde_json::from_str::<String>(&key_text)
+                        .unwrap_or_else(|_| key_text.trim_matches('"').to_string());
+                    segments.push(PathSegment::Key(key));
+                }
+            }
+            // YAML
+            "block_mapping_pair" | "flow_pair" => {
+                if let Some(key) = node.child_by_field_name("key") {
+                    segments.push(PathSegment::Key(yaml_scalar_text(key, text_for_range)));
+                }
+            }
+            _ => {}
+        }
+
+        let Some(parent) = node.parent() else {
+            break;
+        };
+        if matches!(parent.kind(), "array" | "block_sequence" | "flow_sequence") {
+            let mut cursor = parent.walk();
+            let index = parent
+                .named_children(&mut cursor)
+                .filter(|child| child.kind() != "comment")
+                .position(|child| child.id() == node.id());
+            if let Some(index) = index {
+                segments.push(PathSegment::Index(index));
+            }
+        }
+        node = parent;
+    }
+
+    segments.reverse();
+    segments
+}
+
+/// Writes a path as keys separated by dots and indices in brackets. Keys that
+/// aren't identifiers are quoted in brackets.
+pub fn format_node_path(segments: &[PathSegment]) -> String {
+    let mut path = String::new();
+    for segment in segments {
+        match segment {
+            PathSegment::Key(key) if is_identifier(key) => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+            }
+            PathSegment::Key(key) => {
+                path.push('[');
+                path.push_str(&serde_json::Value::from(key.as_str()).to_string());
+                path.push(']');
+            }
+            PathSegment::Index(index) => {
+                path.push_str(&format!("[{index}]"));
+            }
+        }
+    }
+    path
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars.next().map_or(false, |first| {
+        first.is_alphabetic() || first == '_' || first == '$'
+    }) && chars.all(|ch| ch.is_alphanumeric() || ch == '_' || ch == '$' || ch == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language::Parser;
+
+    fn path_at(language: &tree_sitter::Language, text: &str, marker: &str) -> String {
+        let offset = text.find(marker).unwrap();
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(text, None).unwrap();
+        let segments = node_path(tree.root_node(), offset, &|range| text[range].to_string());
+        format_node_path(&segments)
+    }
+
+    #[test]
+    fn test_json_node_path() {
+        let language = tree_sitter_json::language();
+        let text = r#"{
+            "services": {
+                "web": { "ports": [8080, /* admin */ 9090] },
+                "the db": { "image": "postgres" }
+            }
+        }"#;
+        assert_eq!(path_at(&language, text, "9090"), "services.web.ports[1]");
+        assert_eq!(
+            path_at(&language, text, "\"postgres"),
+            "services[\"the db\"].image"
+        );
+        assert_eq!(path_at(&language, text, "\"web"), "services.web");
+        assert_eq!(path_at(&language, text, "{"), "");
+    }
+
+    #[test]
+    fn test_yaml_node_path() {
+        let language = tree_sitter_yaml::language();
+        let text = "services:\n  web:\n    ports:\n      - 8080\n      # admin\n      - 9090\n    \
+                    env: {DEBUG: 'yes', LIST: [a, b]}\n";
+        assert_eq!(path_at(&language, text, "9090"), "services.web.ports[1]");
+        assert_eq!(path_at(&language, text, "'yes'"), "services.web.env.DEBUG");
+        assert_eq!(path_at(&language, text, "b]"), "services.web.env.LIST[1]");
+        assert_eq!(path_at(&language, text, "web"), "services.web");
+    }
+}
@@ -0,0 +1,68 @@
+use editor::Editor;
+use gpui::{ClipboardItem, SharedString, Subscription, View};
+use ui::{
+    div, Button, ButtonCommon, Clickable, FluentBuilder, IntoElement, LabelSize, Render, Tooltip,
+    ViewContext,
+};
+use workspace::{item::ItemHandle, StatusItemView};
+
+use crate::{format_node_path, node_path, DocumentFormat};
+
+/// Shows the path of the value under the cursor in JSON and YAML documents,
+/// such as `services.web.ports[0]`.
+#[derive(Default)]
+pub struct NodePathIndicator {
+    path: Option<SharedString>,
+    _observe_active_editor: Option<Subscription>,
+}
+
+impl NodePathIndicator {
+    fn update_path(&mut self, editor: View<Editor>, cx: &mut ViewContext<Self>) {
+        let editor = editor.read(cx);
+        let buffer = editor.buffer().read(cx).snapshot(cx);
+        let offset = editor.selections.newest::<usize>(cx).head();
+        // The offsets of a singleton multibuffer are those of its buffer.
+        self.path = buffer.as_singleton().and_then(|(_, _, snapshot)| {
+            let layer = snapshot.syntax_layer_at(offset)?;
+            DocumentFormat::for_language(&layer.language.name())?;
+            let segments = node_path(layer.node(), offset, &|range| {
+                snapshot.text_for_range(range).collect()
+            });
+            (!segments.is_empty()).then(|| format_node_path(&segments).into())
+        });
+        cx.notify();
+    }
+}
+
+impl Render for NodePathIndicator {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        div().when_some(self.path.clone(), |el, path| {
+            el.child(
+                Button::new("json-node-path", path.clone())
+                    .label_size(LabelSize::Small)
+                    .on_click(move |_, cx| {
+                        cx.write_to_clipboard(ClipboardItem::new_string(path.to_string()))
+                    })
+                    .tooltip(|cx| Tooltip::text("Copy Path", cx)),
+            )
+        })
+    }
+}
+
+impl StatusItemView for NodePathIndicator {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(editor) = active_pane_item.and_then(|item| item.act_as::<Editor>(cx)) {
+            self._observe_active_editor = Some(cx.observe(&editor, Self::update_path));
+            self.update_path(editor, cx);
+        } else {
+            self.path = None;
+            self._observe_active_editor = None;
+        }
+
+        cx.notify();
+    }
+}
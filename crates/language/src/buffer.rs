@@ -6,19 +6,23 @@ pub use crate::{
 };
 use crate::{
     diagnostic_set::{DiagnosticEntry, DiagnosticGroup},
-    language_settings::{language_settings, IndentGuideSettings, LanguageSettings},
+    language_settings::{
+        language_settings, ExternalChangesSettings, IndentGuideSettings, LanguageSettings,
+        SyntaxParsingSettings,
+    },
     markdown::parse_markdown,
     outline::OutlineItem,
     syntax_map::{
-        SyntaxLayer, SyntaxMap, SyntaxMapCapture, SyntaxMapCaptures, SyntaxMapMatch,
-        SyntaxMapMatches, SyntaxSnapshot, ToTreeSitterPoint,
+        SyntaxLayer, SyntaxMap, SyntaxMapCaptures, SyntaxMapMatch, SyntaxMapMatches,
+        SyntaxSnapshot, ToTreeSitterPoint,
     },
-    task_context::RunnableRange,
+    task_context::{RunnableKind, RunnableRange},
     LanguageScope, Outline, OutlineConfig, RunnableCapture, RunnableTag,
 };
 use anyhow::{anyhow, Context, Result};
 use async_watch as watch;
 pub use clock::ReplicaId;
+use collections::HashMap;
 use futures::channel::oneshot;
 use gpui::{
     AnyElement, AppContext, EventEmitter, HighlightStyle, ModelContext, Pixels, Task, TaskLabel,
@@ -26,6 +30,8 @@ use gpui::{
 };
 use lsp::LanguageServerId;
 use parking_lot::Mutex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use similar::{ChangeTag, TextDiff};
 use smallvec::SmallVec;
@@ -101,10 +107,16 @@ pub struct Buffer {
     language: Option<Arc<Language>>,
     autoindent_requests: Vec<Arc<AutoindentRequest>>,
     pending_autoindent: Option<Task<()>>,
-    sync_parse_timeout: Duration,
+    sync_parse_timeout: Option<Duration>,
     syntax_map: Mutex<SyntaxMap>,
     parsing_in_background: bool,
     parse_status: (watch::Sender<ParseStatus>, watch::Receiver<ParseStatus>),
+    /// The syntax highlights of the rows being shown, for buffers whose
+    /// highlight queries run in the background.
+    background_highlights: Option<Arc<BackgroundHighlights>>,
+    /// The range last asked to be highlighted in the background.
+    background_highlights_range: Option<Range<Anchor>>,
+    background_highlights_task: Option<Task<()>>,
     non_text_state_update_count: usize,
     diagnostics: SmallVec<[(LanguageServerId, DiagnosticSet); 2]>,
     remote_selections: TreeMap<ReplicaId, SelectionSet>,
@@ -126,12 +138,76 @@ pub enum ParseStatus {
     Parsing,
 }
 
+/// The syntax highlights of a range of a buffer, found by running the highlight
+/// queries on a background thread rather than as the rows are rendered.
+#[derive(Clone, Debug)]
+struct BackgroundHighlights {
+    /// The version of the buffer the highlights were found at.
+    found_version: clock::Global,
+    /// The version of the buffer the highlights are positioned for. Edits made
+    /// after they were found shift them, leaving the new text unhighlighted.
+    version: clock::Global,
+    /// The range the highlights were found for.
+    range: Range<usize>,
+    /// The highlighted ranges, in the order the highlight queries capture them.
+    captures: Vec<(Range<usize>, HighlightId)>,
+}
+
+impl BackgroundHighlights {
+    fn empty(version: &clock::Global) -> Self {
+        Self {
+            found_version: version.clone(),
+            version: version.clone(),
+            range: 0..0,
+            captures: Vec::new(),
+        }
+    }
+
+    /// Shifts the highlights by the edits made to the buffer since they were
+    /// positioned, dropping the ones whose text was deleted.
+    fn interpolate(&mut self, text: &text::BufferSnapshot) {
+        if self.version == *text.version() {
+            return;
+        }
+        let edits = text.edits_since::<usize>(&self.version).collect::<Vec<_>>();
+        self.version = text.version().clone();
+        let shift = |offset: usize, bias: Bias| {
+            let ix = edits.partition_point(|edit| edit.old.start <= offset);
+            let Some(edit) = ix.checked_sub(1).map(|ix| &edits[ix]) else {
+                return offset;
+            };
+            if offset >= edit.old.end {
+                edit.new.end + (offset - edit.old.end)
+            } else if bias == Bias::Left {
+                edit.new.start
+            } else {
+                edit.new.end
+            }
+        };
+        self.range = shift(self.range.start, Bias::Left)..shift(self.range.end, Bias::Right);
+        self.captures.retain_mut(|(range, _)| {
+            *range = shift(range.start, Bias::Right)..shift(range.end, Bias::Left);
+            range.start < range.end
+        });
+    }
+}
+
+/// An estimate of the memory that a buffer uses, in bytes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BufferMemoryUsage {
+    /// The buffer's text, including deleted text kept for undo.
+    pub text: usize,
+    /// The syntax trees of every language in the buffer.
+    pub syntax_trees: usize,
+}
+
 /// An immutable, cheaply cloneable representation of a fixed
 /// state of a buffer.
 pub struct BufferSnapshot {
     text: text::BufferSnapshot,
     git_diff: git::diff::BufferDiff,
     pub(crate) syntax: SyntaxSnapshot,
+    background_highlights: Option<Arc<BackgroundHighlights>>,
     file: Option<Arc<dyn File>>,
     diagnostics: SmallVec<[(LanguageServerId, DiagnosticSet); 2]>,
     remote_selections: TreeMap<ReplicaId, SelectionSet>,
@@ -160,7 +236,8 @@ pub enum IndentKind {
 }
 
 /// The shape of a selection cursor.
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
 pub enum CursorShape {
     /// A vertical bar
     #[default]
@@ -168,6 +245,7 @@ pub enum CursorShape {
     /// A block that surrounds the following character
     Block,
     /// An underline that runs along the following character
+    #[serde(rename = "underline", alias = "underscore")]
     Underscore,
     /// A box drawn around the following character
     Hollow,
@@ -212,6 +290,8 @@ pub struct Diagnostic {
     pub is_disk_based: bool,
     /// Whether this diagnostic marks unnecessary code.
     pub is_unnecessary: bool,
+    /// Whether this diagnostic marks deprecated code.
+    pub is_deprecated: bool,
     /// Data from language server that produced this diagnostic. Passed back to the LS when we request code actions for this diagnostic.
     pub data: Option<Value>,
 }
@@ -440,10 +520,61 @@ struct IndentSuggestion {
 }
 
 struct BufferChunkHighlights<'a> {
-    captures: SyntaxMapCaptures<'a>,
-    next_capture: Option<SyntaxMapCapture<'a>>,
+    captures: HighlightCaptures<'a>,
+    next_capture: Option<(Range<usize>, HighlightId)>,
     stack: Vec<(usize, HighlightId)>,
-    highlight_maps: Vec<HighlightMap>,
+}
+
+/// The syntax highlights of the chunks of a buffer, in the order the highlight
+/// queries capture them.
+pub(crate) enum HighlightCaptures<'a> {
+    /// The captures of the highlight queries, run as the chunks are read.
+    Query {
+        captures: SyntaxMapCaptures<'a>,
+        highlight_maps: Vec<HighlightMap>,
+    },
+    /// Highlights found in the background, of which those intersecting the range
+    /// are read.
+    Background {
+        captures: &'a [(Range<usize>, HighlightId)],
+        range: Range<usize>,
+    },
+}
+
+impl<'a> HighlightCaptures<'a> {
+    fn next(&mut self) -> Option<(Range<usize>, HighlightId)> {
+        match self {
+            HighlightCaptures::Query {
+                captures,
+                highlight_maps,
+            } => {
+                let capture = captures.next()?;
+                Some((
+                    capture.node.byte_range(),
+                    highlight_maps[capture.grammar_index].get(capture.index),
+                ))
+            }
+            HighlightCaptures::Background { captures, range } => {
+                while let Some(((capture_range, highlight_id), rest)) = captures.split_first() {
+                    if capture_range.start >= range.end {
+                        break;
+                    }
+                    *captures = rest;
+                    if capture_range.end > range.start {
+                        return Some((capture_range.clone(), *highlight_id));
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    fn set_byte_range(&mut self, byte_range: Range<usize>) {
+        match self {
+            HighlightCaptures::Query { captures, .. } => captures.set_byte_range(byte_range),
+            HighlightCaptures::Background { range, .. } => *range = byte_range,
+        }
+    }
 }
 
 /// An iterator that yields chunks of a buffer's text, along with their
@@ -458,6 +589,7 @@ pub struct BufferChunks<'a> {
     information_depth: usize,
     hint_depth: usize,
     unnecessary_depth: usize,
+    deprecated_depth: usize,
     highlights: Option<BufferChunkHighlights<'a>>,
 }
 
@@ -476,6 +608,8 @@ pub struct Chunk<'a> {
     pub diagnostic_severity: Option<DiagnosticSeverity>,
     /// Whether this chunk of text is marked as unnecessary.
     pub is_unnecessary: bool,
+    /// Whether this chunk of text is marked as deprecated.
+    pub is_deprecated: bool,
     /// Whether this chunk of text was originally a tab character.
     pub is_tab: bool,
     /// An optional recipe for how the chunk should be presented.
@@ -532,6 +666,7 @@ pub(crate) struct DiagnosticEndpoint {
     is_start: bool,
     severity: DiagnosticSeverity,
     is_unnecessary: bool,
+    is_deprecated: bool,
 }
 
 /// A class of characters, used for characterizing a run of text.
@@ -548,6 +683,7 @@ pub enum CharKind {
 /// A runnable is a set of data about a region that could be resolved into a task
 pub struct Runnable {
     pub tags: SmallVec<[RunnableTag; 1]>,
+    pub kind: RunnableKind,
     pub language: Arc<Language>,
     pub buffer: BufferId,
 }
@@ -568,6 +704,17 @@ impl IndentGuide {
     }
 }
 
+/// An occurrence of a local variable or parameter, as found by a language's
+/// locals query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalOccurrence {
+    /// The range of the occurrence.
+    pub range: Range<usize>,
+    /// The range of the definition the occurrence refers to, which is the
+    /// occurrence's own range if it's a definition.
+    pub definition_range: Range<usize>,
+}
+
 impl Buffer {
     /// Create a new buffer with the given base text.
     pub fn local<T: Into<String>>(base_text: T, cx: &mut ModelContext<Self>) -> Self {
@@ -740,8 +887,11 @@ impl Buffer {
             capability,
             syntax_map: Mutex::new(SyntaxMap::new()),
             parsing_in_background: false,
+            background_highlights: None,
+            background_highlights_range: None,
+            background_highlights_task: None,
             non_text_state_update_count: 0,
-            sync_parse_timeout: Duration::from_millis(1),
+            sync_parse_timeout: None,
             parse_status: async_watch::channel(ParseStatus::Idle),
             autoindent_requests: Default::default(),
             pending_autoindent: Default::default(),
@@ -763,10 +913,21 @@ impl Buffer {
         let mut syntax_map = self.syntax_map.lock();
         syntax_map.interpolate(&text);
         let syntax = syntax_map.snapshot();
+        // Edits made in a transaction haven't shifted the highlights yet.
+        let background_highlights = self.background_highlights.as_ref().map(|highlights| {
+            if highlights.version == *text.version() {
+                highlights.clone()
+            } else {
+                let mut highlights = highlights.as_ref().clone();
+                highlights.interpolate(&text);
+                Arc::new(highlights)
+            }
+        });
 
         BufferSnapshot {
             text,
             syntax,
+            background_highlights,
             git_diff: self.git_diff.clone(),
             file: self.file.clone(),
             remote_selections: self.remote_selections.clone(),
@@ -914,6 +1075,29 @@ impl Buffer {
         cx.notify();
     }
 
+    /// Marks the changes made to the buffer's file on disk as merged into the buffer,
+    /// so that it doesn't conflict with the file anymore. `matches_file` is whether
+    /// the buffer's text is now the same as the file's, which was last modified at
+    /// `mtime`.
+    pub fn did_merge_file_changes(
+        &mut self,
+        matches_file: bool,
+        mtime: Option<SystemTime>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let was_dirty = self.is_dirty();
+        let version = if matches_file {
+            self.version()
+        } else {
+            self.saved_version.clone()
+        };
+        self.has_conflict = false;
+        self.did_reload(version, self.line_ending(), mtime, cx);
+        if was_dirty != self.is_dirty() {
+            cx.emit(Event::DirtyChanged);
+        }
+    }
+
     /// Updates the [File] backing this buffer. This should be called when
     /// the file has changed or has been deleted.
     pub fn file_updated(&mut self, new_file: Arc<dyn File>, cx: &mut ModelContext<Self>) {
@@ -937,7 +1121,12 @@ impl Buffer {
                     file_changed = true;
 
                     if !self.is_dirty() {
-                        self.reload(cx).close();
+                        if ExternalChangesSettings::get_or_default(cx).auto_reload {
+                            self.reload(cx).close();
+                        } else {
+                            self.has_conflict = true;
+                            cx.emit(Event::DirtyChanged);
+                        }
                     }
                 }
             }
@@ -1044,7 +1233,7 @@ impl Buffer {
 
     #[cfg(test)]
     pub fn set_sync_parse_timeout(&mut self, timeout: Duration) {
-        self.sync_parse_timeout = timeout;
+        self.sync_parse_timeout = Some(timeout);
     }
 
     /// Called after an edit to synchronize the buffer's main parse tree with
@@ -1057,14 +1246,20 @@ impl Buffer {
     /// The snapshot with the interpolated edits is sent to a background thread,
     /// where we ask Tree-sitter to perform an incremental parse.
     ///
-    /// Meanwhile, in the foreground, we block the main thread for up to 1ms
-    /// waiting on the parse to complete. As soon as it completes, we proceed
-    /// synchronously, unless a 1ms timeout elapses.
+    /// Meanwhile, in the foreground, we block the main thread for up to
+    /// `syntax_parsing.sync_parse_budget_ms` (1ms by default) waiting on the
+    /// parse to complete. As soon as it completes, we proceed synchronously,
+    /// unless the budget elapses. Buffers larger than
+    /// `syntax_parsing.large_file_size_kb` have no budget, so that typing in
+    /// them never waits on Tree-sitter.
     ///
     /// If we time out waiting on the parse, we spawn a second task waiting
     /// until the parse does complete and return with the interpolated tree still
-    /// in the foreground. When the background parse completes, call back into
-    /// the main thread and assign the foreground parse state.
+    /// in the foreground, so the previous highlights are shown in the meantime.
+    /// When the background parse completes, call back into the main thread and
+    /// assign the foreground parse state. The highlight queries aren't run
+    /// here, but for the rows being rendered: on the main thread, or, for large
+    /// buffers, on a background thread (see [`Buffer::highlight_in_background`]).
     ///
     /// If the buffer or grammar changed since the start of the background parse,
     /// initiate an additional reparse recursively. To avoid concurrent parses
@@ -1082,6 +1277,12 @@ impl Buffer {
 
         let text = self.text_snapshot();
         let parsed_version = self.version();
+        let settings = SyntaxParsingSettings::get_or_default(cx);
+        let sync_parse_timeout = self
+            .sync_parse_timeout
+            .unwrap_or_else(|| settings.sync_parse_budget(text.len()));
+        let log_parse_latency = settings.log_parse_latency;
+        let started_at = Instant::now();
 
         let mut syntax_map = self.syntax_map.lock();
         syntax_map.interpolate(&text);
@@ -1093,25 +1294,33 @@ impl Buffer {
             let language = language.clone();
             let language_registry = language_registry.clone();
             async move {
+                let parse_started_at = Instant::now();
                 syntax_snapshot.reparse(&text, language_registry, language);
-                syntax_snapshot
+                (syntax_snapshot, parse_started_at.elapsed())
             }
         });
 
         self.parse_status.0.send(ParseStatus::Parsing).unwrap();
         match cx
             .background_executor()
-            .block_with_timeout(self.sync_parse_timeout, parse_task)
+            .block_with_timeout(sync_parse_timeout, parse_task)
         {
-            Ok(new_syntax_snapshot) => {
+            Ok((new_syntax_snapshot, parse_duration)) => {
+                if log_parse_latency {
+                    self.log_parse_latency(parse_duration, started_at.elapsed());
+                }
                 self.did_finish_parsing(new_syntax_snapshot, cx);
                 return;
             }
             Err(parse_task) => {
                 self.parsing_in_background = true;
+                let blocked_duration = started_at.elapsed();
                 cx.spawn(move |this, mut cx| async move {
-                    let new_syntax_map = parse_task.await;
+                    let (new_syntax_map, parse_duration) = parse_task.await;
                     this.update(&mut cx, move |this, cx| {
+                        if log_parse_latency {
+                            this.log_parse_latency(parse_duration, blocked_duration);
+                        }
                         let grammar_changed =
                             this.language.as_ref().map_or(true, |current_language| {
                                 !Arc::ptr_eq(&language, current_language)
@@ -1137,15 +1346,102 @@ impl Buffer {
         }
     }
 
+    fn log_parse_latency(&self, parse_duration: Duration, blocked_duration: Duration) {
+        let path = self
+            .file
+            .as_ref()
+            .map_or("untitled".into(), |file| file.path().to_string_lossy());
+        log::info!(
+            "reparsed {path} in {parse_duration:?}, blocking the main thread for {:?}",
+            blocked_duration,
+        );
+    }
+
     fn did_finish_parsing(&mut self, syntax_snapshot: SyntaxSnapshot, cx: &mut ModelContext<Self>) {
         self.non_text_state_update_count += 1;
         self.syntax_map.lock().did_parse(syntax_snapshot);
         self.request_autoindent(cx);
+        self.refresh_background_highlights(true, cx);
         self.parse_status.0.send(ParseStatus::Idle).unwrap();
         cx.emit(Event::Reparsed);
         cx.notify();
     }
 
+    /// Whether the highlight queries of the buffer run in the background, which
+    /// they do for buffers larger than `syntax_parsing.large_file_size_kb`.
+    pub fn highlights_in_background(&self, cx: &AppContext) -> bool {
+        self.language.is_some()
+            && SyntaxParsingSettings::get_or_default(cx).is_large_buffer(self.text.len())
+    }
+
+    /// Finds the syntax highlights of the range on a background thread, if the
+    /// highlight queries of the buffer run in the background. Until they're found,
+    /// the range shows the previous highlights, shifted by the edits made since.
+    pub fn highlight_in_background(&mut self, range: Range<usize>, cx: &mut ModelContext<Self>) {
+        let range = self.anchor_before(range.start)..self.anchor_after(range.end);
+        self.background_highlights_range = Some(range);
+        self.refresh_background_highlights(false, cx);
+    }
+
+    /// Finds the syntax highlights of the range last asked for again, unless they
+    /// were found at the current version of the buffer and cover the range, and
+    /// the buffer wasn't reparsed since.
+    fn refresh_background_highlights(&mut self, reparsed: bool, cx: &mut ModelContext<Self>) {
+        if !self.highlights_in_background(cx) {
+            self.background_highlights_task = None;
+            if self.background_highlights.take().is_some() {
+                self.non_text_state_update_count += 1;
+                cx.notify();
+            }
+            return;
+        }
+        let Some(range) = self.background_highlights_range.clone() else {
+            return;
+        };
+
+        let range = range.to_offset(&self.text);
+        let version = self.version();
+        let is_current = self
+            .background_highlights
+            .as_ref()
+            .map_or(false, |highlights| {
+                highlights.found_version == version
+                    && highlights.range.start <= range.start
+                    && highlights.range.end >= range.end
+            });
+        if is_current && !reparsed {
+            return;
+        }
+        if self.background_highlights.is_none() {
+            // Rows aren't highlighted on the main thread while their first highlights
+            // are found.
+            self.background_highlights = Some(Arc::new(BackgroundHighlights::empty(&version)));
+            self.non_text_state_update_count += 1;
+        }
+
+        let snapshot = self.snapshot();
+        let captures = cx.background_executor().spawn(async move {
+            let mut captures = snapshot.query_highlights(range.clone());
+            let captures = iter::from_fn(|| captures.next()).collect::<Vec<_>>();
+            BackgroundHighlights {
+                found_version: version.clone(),
+                version,
+                range,
+                captures,
+            }
+        });
+        self.background_highlights_task = Some(cx.spawn(|this, mut cx| async move {
+            let mut highlights = captures.await;
+            this.update(&mut cx, |this, cx| {
+                highlights.interpolate(&this.text);
+                this.background_highlights = Some(Arc::new(highlights));
+                this.non_text_state_update_count += 1;
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
     pub fn parse_status(&self) -> watch::Receiver<ParseStatus> {
         self.parse_status.1.clone()
     }
@@ -1898,6 +2194,9 @@ impl Buffer {
             return;
         }
 
+        if let Some(highlights) = self.background_highlights.as_mut() {
+            Arc::make_mut(highlights).interpolate(&self.text);
+        }
         self.reparse(cx);
 
         cx.emit(Event::Edited);
@@ -2576,7 +2875,19 @@ impl BufferSnapshot {
         None
     }
 
-    fn get_highlights(&self, range: Range<usize>) -> (SyntaxMapCaptures, Vec<HighlightMap>) {
+    /// The syntax highlights of the range, found in the background for buffers
+    /// whose highlight queries run there.
+    fn get_highlights(&self, range: Range<usize>) -> HighlightCaptures {
+        if let Some(highlights) = self.background_highlights.as_ref() {
+            return HighlightCaptures::Background {
+                captures: &highlights.captures,
+                range,
+            };
+        }
+        self.query_highlights(range)
+    }
+
+    fn query_highlights(&self, range: Range<usize>) -> HighlightCaptures {
         let captures = self.syntax.captures(range, &self.text, |grammar| {
             grammar.highlights_query.as_ref()
         });
@@ -2585,8 +2896,12 @@ impl BufferSnapshot {
             .into_iter()
             .map(|grammar| grammar.highlight_map())
             .collect();
-        (captures, highlight_maps)
+        HighlightCaptures::Query {
+            captures,
+            highlight_maps,
+        }
     }
+
     /// Iterates over chunks of text in the given range of the buffer. Text is chunked
     /// in an arbitrary way due to being stored in a [`Rope`](text::Rope). The text is also
     /// returned in chunks where each chunk has a single syntax highlighting style and
@@ -2624,6 +2939,14 @@ impl BufferSnapshot {
         }
     }
 
+    /// An estimate of the memory that the buffer uses.
+    pub fn memory_usage(&self) -> BufferMemoryUsage {
+        BufferMemoryUsage {
+            text: self.text.estimated_memory_usage(),
+            syntax_trees: self.syntax.estimated_memory_usage(),
+        }
+    }
+
     /// Iterates over every [`SyntaxLayer`] in the buffer.
     pub fn syntax_layers(&self) -> impl Iterator<Item = SyntaxLayer> + '_ {
         self.syntax
@@ -2856,47 +3179,14 @@ impl BufferSnapshot {
         theme: Option<&SyntaxTheme>,
     ) -> Option<Vec<OutlineItem<Anchor>>> {
         let range = range.to_offset(self);
-        let mut matches = self.syntax.matches(range.clone(), &self.text, |grammar| {
-            grammar.outline_config.as_ref().map(|c| &c.query)
-        });
-        let configs = matches
-            .grammars()
-            .iter()
-            .map(|g| g.outline_config.as_ref().unwrap())
-            .collect::<Vec<_>>();
-
-        let mut items = Vec::new();
-        let mut annotation_row_ranges: Vec<Range<u32>> = Vec::new();
-        while let Some(mat) = matches.peek() {
-            let config = &configs[mat.grammar_index];
-            if let Some(item) =
-                self.next_outline_item(config, &mat, &range, include_extra_context, theme)
-            {
-                items.push(item);
-            } else if let Some(capture) = mat
-                .captures
-                .iter()
-                .find(|capture| Some(capture.index) == config.annotation_capture_ix)
-            {
-                let capture_range = capture.node.start_position()..capture.node.end_position();
-                let mut capture_row_range =
-                    capture_range.start.row as u32..capture_range.end.row as u32;
-                if capture_range.end.row > capture_range.start.row && capture_range.end.column == 0
-                {
-                    capture_row_range.end -= 1;
-                }
-                if let Some(last_row_range) = annotation_row_ranges.last_mut() {
-                    if last_row_range.end >= capture_row_range.start.saturating_sub(1) {
-                        last_row_range.end = capture_row_range.end;
-                    } else {
-                        annotation_row_ranges.push(capture_row_range);
-                    }
-                } else {
-                    annotation_row_ranges.push(capture_row_range);
-                }
-            }
-            matches.advance();
-        }
+        let provided_items = self
+            .language()
+            .and_then(|language| language.context_provider())
+            .and_then(|provider| provider.outline_items(self, range.clone()));
+        let (mut items, annotation_row_ranges) = match provided_items {
+            Some(items) => (items, Vec::new()),
+            None => self.query_outline_items(range, include_extra_context, theme),
+        };
 
         items.sort_by_key(|item| (item.range.start, Reverse(item.range.end)));
 
@@ -2950,6 +3240,59 @@ impl BufferSnapshot {
         Some(anchor_items)
     }
 
+    /// Runs the outline query of the buffer's grammars over the range, returning the items
+    /// and the row ranges of their annotations.
+    fn query_outline_items(
+        &self,
+        range: Range<usize>,
+        include_extra_context: bool,
+        theme: Option<&SyntaxTheme>,
+    ) -> (Vec<OutlineItem<Point>>, Vec<Range<u32>>) {
+        let mut matches = self.syntax.matches(range.clone(), &self.text, |grammar| {
+            grammar.outline_config.as_ref().map(|c| &c.query)
+        });
+        let configs = matches
+            .grammars()
+            .iter()
+            .map(|g| g.outline_config.as_ref().unwrap())
+            .collect::<Vec<_>>();
+
+        let mut items = Vec::new();
+        let mut annotation_row_ranges: Vec<Range<u32>> = Vec::new();
+        while let Some(mat) = matches.peek() {
+            let config = &configs[mat.grammar_index];
+            if let Some(item) =
+                self.next_outline_item(config, &mat, &range, include_extra_context, theme)
+            {
+                items.push(item);
+            } else if let Some(capture) = mat
+                .captures
+                .iter()
+                .find(|capture| Some(capture.index) == config.annotation_capture_ix)
+            {
+                let capture_range = capture.node.start_position()..capture.node.end_position();
+                let mut capture_row_range =
+                    capture_range.start.row as u32..capture_range.end.row as u32;
+                if capture_range.end.row > capture_range.start.row && capture_range.end.column == 0
+                {
+                    capture_row_range.end -= 1;
+                }
+                if let Some(last_row_range) = annotation_row_ranges.last_mut() {
+                    if last_row_range.end >= capture_row_range.start.saturating_sub(1) {
+                        last_row_range.end = capture_row_range.end;
+                    } else {
+                        annotation_row_ranges.push(capture_row_range);
+                    }
+                } else {
+                    annotation_row_ranges.push(capture_row_range);
+                }
+            }
+            matches.advance();
+        }
+
+        (items, annotation_row_ranges)
+    }
+
     fn next_outline_item(
         &self,
         config: &OutlineConfig,
@@ -3210,6 +3553,114 @@ impl BufferSnapshot {
         })
     }
 
+    /// Returns the occurrences of local variables and parameters that intersect
+    /// the given range, in order.
+    ///
+    /// Definitions are only recognized within the scopes captured by the
+    /// locals query, and references are resolved to the definition of the same
+    /// name in the innermost scope containing them.
+    pub fn local_occurrences<T: ToOffset>(&self, range: Range<T>) -> Vec<LocalOccurrence> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let locals_query =
+            |grammar: &Grammar| grammar.locals_config.as_ref().map(|config| &config.query);
+
+        // Occurrences in the range may refer to definitions before it, so
+        // start from the outermost scope intersecting the range.
+        let mut scanned_range = range.clone();
+        let mut syntax_matches = self.syntax.matches(range.clone(), self, locals_query);
+        let configs = syntax_matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.locals_config.as_ref())
+            .collect::<Vec<_>>();
+        while let Some(mat) = syntax_matches.peek() {
+            if let Some(config) = configs[mat.grammar_index] {
+                for capture in mat.captures {
+                    if capture.index == config.scope_capture_ix {
+                        let scope_range = capture.node.byte_range();
+                        scanned_range.start = scanned_range.start.min(scope_range.start);
+                        scanned_range.end = scanned_range.end.max(scope_range.end);
+                    }
+                }
+            }
+            syntax_matches.advance();
+        }
+
+        struct Scope {
+            range: Range<usize>,
+            definitions: HashMap<String, Range<usize>>,
+        }
+
+        let mut scopes = Vec::<Scope>::new();
+        let mut occurrences = BTreeMap::<usize, LocalOccurrence>::new();
+        let mut syntax_matches = self.syntax.matches(scanned_range, self, locals_query);
+        let configs = syntax_matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.locals_config.as_ref())
+            .collect::<Vec<_>>();
+        while let Some(mat) = syntax_matches.peek() {
+            let Some(config) = configs[mat.grammar_index] else {
+                syntax_matches.advance();
+                continue;
+            };
+            for capture in mat.captures {
+                let node_range = capture.node.byte_range();
+                while scopes
+                    .last()
+                    .map_or(false, |scope| scope.range.end <= node_range.start)
+                {
+                    scopes.pop();
+                }
+
+                if capture.index == config.scope_capture_ix {
+                    scopes.push(Scope {
+                        range: node_range,
+                        definitions: HashMap::default(),
+                    });
+                } else if capture.index == config.definition_capture_ix {
+                    // Definitions outside of any scope aren't local.
+                    let Some(scope) = scopes.last_mut() else {
+                        continue;
+                    };
+                    let name = self.text_for_range(node_range.clone()).collect::<String>();
+                    scope.definitions.insert(name, node_range.clone());
+                    // A node may be captured as a reference too, in which case
+                    // it's a definition.
+                    occurrences.insert(
+                        node_range.start,
+                        LocalOccurrence {
+                            range: node_range.clone(),
+                            definition_range: node_range,
+                        },
+                    );
+                } else if capture.index == config.reference_capture_ix {
+                    let name = self.text_for_range(node_range.clone()).collect::<String>();
+                    let definition_range = scopes
+                        .iter()
+                        .rev()
+                        .find_map(|scope| scope.definitions.get(&name));
+                    if let Some(definition_range) = definition_range {
+                        occurrences
+                            .entry(node_range.start)
+                            .or_insert_with(|| LocalOccurrence {
+                                range: node_range,
+                                definition_range: definition_range.clone(),
+                            });
+                    }
+                }
+            }
+            syntax_matches.advance();
+        }
+
+        occurrences
+            .into_values()
+            .filter(|occurrence| {
+                occurrence.range.start < range.end && occurrence.range.end > range.start
+            })
+            .collect()
+    }
+
     pub fn injections_intersecting_range<T: ToOffset>(
         &self,
         range: Range<T>,
@@ -3253,7 +3704,7 @@ impl BufferSnapshot {
     ) -> impl Iterator<Item = RunnableRange> + '_ {
         let offset_range = range.start.to_offset(self)..range.end.to_offset(self);
 
-        let mut syntax_matches = self.syntax.matches(offset_range, self, |grammar| {
+        let mut syntax_matches = self.syntax.matches(offset_range.clone(), self, |grammar| {
             grammar.runnable_config.as_ref().map(|config| &config.query)
         });
 
@@ -3263,6 +3714,29 @@ impl BufferSnapshot {
             .map(|grammar| grammar.runnable_config.as_ref())
             .collect::<Vec<_>>();
 
+        let provided_runnables = self
+            .language()
+            .and_then(|language| Some((language.clone(), language.context_provider()?)))
+            .map(|(language, provider)| {
+                provider
+                    .runnables(self, offset_range.clone())
+                    .into_iter()
+                    .map(|runnable| RunnableRange {
+                        run_range: runnable.run_range,
+                        full_range: runnable.full_range,
+                        runnable: Runnable {
+                            tags: runnable.tags,
+                            kind: runnable.kind,
+                            language: language.clone(),
+                            buffer: self.remote_id(),
+                        },
+                        extra_captures: runnable.extra_captures,
+                        buffer_id: self.remote_id(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
         iter::from_fn(move || loop {
             let mat = syntax_matches.peek()?;
 
@@ -3305,9 +3779,8 @@ impl BufferSnapshot {
                             })
                     }));
                 let run_range = run_range?;
-                let tags = test_configs
-                    .query
-                    .property_settings(mat.pattern_index)
+                let properties = test_configs.query.property_settings(mat.pattern_index);
+                let tags = properties
                     .iter()
                     .filter_map(|property| {
                         if *property.key == *"tag" {
@@ -3320,6 +3793,11 @@ impl BufferSnapshot {
                         }
                     })
                     .collect();
+                let kind = properties
+                    .iter()
+                    .find(|property| *property.key == *"kind")
+                    .and_then(|property| RunnableKind::from_name(property.value.as_deref()?))
+                    .unwrap_or_default();
                 let extra_captures = extra_captures
                     .into_iter()
                     .map(|(range, name)| {
@@ -3335,6 +3813,7 @@ impl BufferSnapshot {
                     full_range,
                     runnable: Runnable {
                         tags,
+                        kind,
                         language: mat.language,
                         buffer: self.remote_id(),
                     },
@@ -3350,6 +3829,7 @@ impl BufferSnapshot {
                 return test_range;
             }
         })
+        .chain(provided_runnables)
     }
 
     pub fn indent_guides_in_range(
@@ -3805,6 +4285,7 @@ impl Clone for BufferSnapshot {
             text: self.text.clone(),
             git_diff: self.git_diff.clone(),
             syntax: self.syntax.clone(),
+            background_highlights: self.background_highlights.clone(),
             file: self.file.clone(),
             remote_selections: self.remote_selections.clone(),
             diagnostics: self.diagnostics.clone(),
@@ -3828,17 +4309,16 @@ impl<'a> BufferChunks<'a> {
     pub(crate) fn new(
         text: &'a Rope,
         range: Range<usize>,
-        syntax: Option<(SyntaxMapCaptures<'a>, Vec<HighlightMap>)>,
+        syntax: Option<HighlightCaptures<'a>>,
         diagnostics: bool,
         buffer_snapshot: Option<&'a BufferSnapshot>,
     ) -> Self {
         let mut highlights = None;
-        if let Some((captures, highlight_maps)) = syntax {
+        if let Some(captures) = syntax {
             highlights = Some(BufferChunkHighlights {
                 captures,
                 next_capture: None,
                 stack: Default::default(),
-                highlight_maps,
             })
         }
 
@@ -3855,6 +4335,7 @@ impl<'a> BufferChunks<'a> {
             information_depth: 0,
             hint_depth: 0,
             unnecessary_depth: 0,
+            deprecated_depth: 0,
             highlights,
         };
         this.initialize_diagnostic_endpoints();
@@ -3871,25 +4352,19 @@ impl<'a> BufferChunks<'a> {
                 highlights
                     .stack
                     .retain(|(end_offset, _)| *end_offset > range.start);
-                if let Some(capture) = &highlights.next_capture {
-                    if range.start >= capture.node.start_byte() {
-                        let next_capture_end = capture.node.end_byte();
-                        if range.start < next_capture_end {
-                            highlights.stack.push((
-                                next_capture_end,
-                                highlights.highlight_maps[capture.grammar_index].get(capture.index),
-                            ));
+                if let Some((capture_range, highlight_id)) = &highlights.next_capture {
+                    if range.start >= capture_range.start {
+                        if range.start < capture_range.end {
+                            highlights.stack.push((capture_range.end, *highlight_id));
                         }
                         highlights.next_capture.take();
                     }
                 }
             } else if let Some(snapshot) = self.buffer_snapshot {
-                let (captures, highlight_maps) = snapshot.get_highlights(self.range.clone());
                 *highlights = BufferChunkHighlights {
-                    captures,
+                    captures: snapshot.get_highlights(self.range.clone()),
                     next_capture: None,
                     stack: Default::default(),
-                    highlight_maps,
                 };
             } else {
                 // We cannot obtain new highlights for a language-aware buffer iterator, as we don't have a buffer snapshot.
@@ -3912,12 +4387,14 @@ impl<'a> BufferChunks<'a> {
                         is_start: true,
                         severity: entry.diagnostic.severity,
                         is_unnecessary: entry.diagnostic.is_unnecessary,
+                        is_deprecated: entry.diagnostic.is_deprecated,
                     });
                     diagnostic_endpoints.push(DiagnosticEndpoint {
                         offset: entry.range.end,
                         is_start: false,
                         severity: entry.diagnostic.severity,
                         is_unnecessary: entry.diagnostic.is_unnecessary,
+                        is_deprecated: entry.diagnostic.is_deprecated,
                     });
                 }
                 diagnostic_endpoints
@@ -3953,6 +4430,14 @@ impl<'a> BufferChunks<'a> {
                 self.unnecessary_depth -= 1;
             }
         }
+
+        if endpoint.is_deprecated {
+            if endpoint.is_start {
+                self.deprecated_depth += 1;
+            } else {
+                self.deprecated_depth -= 1;
+            }
+        }
     }
 
     fn current_diagnostic_severity(&self) -> Option<DiagnosticSeverity> {
@@ -3972,6 +4457,10 @@ impl<'a> BufferChunks<'a> {
     fn current_code_is_unnecessary(&self) -> bool {
         self.unnecessary_depth > 0
     }
+
+    fn current_code_is_deprecated(&self) -> bool {
+        self.deprecated_depth > 0
+    }
 }
 
 impl<'a> Iterator for BufferChunks<'a> {
@@ -3994,16 +4483,12 @@ impl<'a> Iterator for BufferChunks<'a> {
                 highlights.next_capture = highlights.captures.next();
             }
 
-            while let Some(capture) = highlights.next_capture.as_ref() {
-                if self.range.start < capture.node.start_byte() {
-                    next_capture_start = capture.node.start_byte();
+            while let Some((capture_range, highlight_id)) = highlights.next_capture.as_ref() {
+                if self.range.start < capture_range.start {
+                    next_capture_start = capture_range.start;
                     break;
                 } else {
-                    let highlight_id =
-                        highlights.highlight_maps[capture.grammar_index].get(capture.index);
-                    highlights
-                        .stack
-                        .push((capture.node.end_byte(), highlight_id));
+                    highlights.stack.push((capture_range.end, *highlight_id));
                     highlights.next_capture = highlights.captures.next();
                 }
             }
@@ -4048,6 +4533,7 @@ impl<'a> Iterator for BufferChunks<'a> {
                 syntax_highlight_id: highlight_id,
                 diagnostic_severity: self.current_diagnostic_severity(),
                 is_unnecessary: self.current_code_is_unnecessary(),
+                is_deprecated: self.current_code_is_deprecated(),
                 ..Default::default()
             })
         } else {
@@ -4086,6 +4572,7 @@ impl Default for Diagnostic {
             is_primary: false,
             is_disk_based: false,
             is_unnecessary: false,
+            is_deprecated: false,
             data: None,
         }
     }
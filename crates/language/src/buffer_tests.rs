@@ -1,12 +1,13 @@
 use super::*;
 use crate::language_settings::{
-    AllLanguageSettings, AllLanguageSettingsContent, LanguageSettingsContent,
+    AllLanguageSettings, AllLanguageSettingsContent, FileTypeMatcher, LanguageSettingsContent,
+    SyntaxParsingSettings,
 };
 use crate::Buffer;
 use clock::ReplicaId;
 use collections::BTreeMap;
 use futures::FutureExt as _;
-use gpui::{AppContext, BorrowAppContext, Model};
+use gpui::{AppContext, BorrowAppContext, Hsla, Model};
 use gpui::{Context, TestAppContext};
 use indoc::indoc;
 use proto::deserialize_operation;
@@ -22,6 +23,7 @@ use std::{
 use text::network::Network;
 use text::{BufferId, LineEnding, LineIndent};
 use text::{Point, ToPoint};
+use theme::SyntaxTheme;
 use unindent::Unindent as _;
 use util::{assert_set_eq, post_inc, test::marked_text_ranges, RandomCharIter};
 
@@ -257,6 +259,114 @@ async fn test_language_for_file_with_custom_file_types(cx: &mut TestAppContext)
     assert_eq!(language.name().as_ref(), "Dockerfile");
 }
 
+#[gpui::test]
+async fn test_language_for_file_by_content(cx: &mut TestAppContext) {
+    cx.update(|cx| {
+        init_settings(cx, |settings| {
+            settings.file_types.insert(
+                "Ruby".into(),
+                vec![FileTypeMatcher::FirstLine {
+                    first_line: "^# frozen_string_literal".into(),
+                }],
+            );
+        })
+    });
+
+    let languages = Arc::new(LanguageRegistry::test(cx.executor()));
+    for (name, suffix) in [
+        ("Python", "py"),
+        ("Shell Script", "sh"),
+        ("Ruby", "rb"),
+        ("Dockerfile", "Dockerfile"),
+    ] {
+        languages.register_test_language(LanguageConfig {
+            name: name.into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec![suffix.to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+    }
+
+    for (path, content, expected_language, expected_source) in [
+        // The shebang line tells the language of extensionless files.
+        (
+            "bin/serve",
+            "#!/usr/bin/env python3\n",
+            "Python",
+            LanguageDetectionSource::Shebang,
+        ),
+        (
+            "bin/setup",
+            "#!/bin/bash -e\n",
+            "Shell Script",
+            LanguageDetectionSource::Shebang,
+        ),
+        // But not the language of the files with an extension.
+        (
+            "lib/tasks.rb",
+            "#!/bin/sh\n",
+            "Ruby",
+            LanguageDetectionSource::Extension,
+        ),
+        // Modelines do.
+        (
+            "lib/tasks.txt",
+            "echo hi\n# vim: set ft=sh:\n",
+            "Shell Script",
+            LanguageDetectionSource::Modeline,
+        ),
+        (
+            "Containerfile",
+            "FROM alpine\nRUN apk add git\n",
+            "Dockerfile",
+            LanguageDetectionSource::Content,
+        ),
+        (
+            "lib/task",
+            "# frozen_string_literal: true\n",
+            "Ruby",
+            LanguageDetectionSource::FileTypes,
+        ),
+    ] {
+        let content = content.into();
+        let language = cx
+            .read(|cx| languages.language_for_file(&file(path), Some(&content), cx))
+            .await
+            .unwrap();
+        assert_eq!(language.name().as_ref(), expected_language, "{path}");
+        let source = cx.read(|cx| {
+            languages.language_detection_source(&file(path), Some(&content), &language, cx)
+        });
+        assert_eq!(source, Some(expected_source), "{path}");
+    }
+
+    cx.read(|cx| languages.language_for_file(&file("notes"), Some(&"hello".into()), cx))
+        .await
+        .unwrap_err();
+
+    // A language chosen for a file is used whatever its name and content.
+    languages.set_language_override(PathBuf::from("zed/lib/tasks.rb"), Some("Python".into()));
+    let language = cx
+        .read(|cx| languages.language_for_file(&file("lib/tasks.rb"), Some(&"".into()), cx))
+        .await
+        .unwrap();
+    assert_eq!(language.name().as_ref(), "Python");
+    assert_eq!(
+        cx.read(|cx| {
+            languages.language_detection_source(&file("lib/tasks.rb"), None, &language, cx)
+        }),
+        Some(LanguageDetectionSource::Override)
+    );
+    languages.set_language_override(PathBuf::from("zed/lib/tasks.rb"), None);
+    let language = cx
+        .read(|cx| languages.language_for_file(&file("lib/tasks.rb"), Some(&"".into()), cx))
+        .await
+        .unwrap();
+    assert_eq!(language.name().as_ref(), "Ruby");
+}
+
 fn file(path: &str) -> Arc<dyn File> {
     Arc::new(TestFile {
         path: Path::new(path).into(),
@@ -450,6 +560,94 @@ async fn test_normalize_whitespace(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_buffer_memory_usage(cx: &mut gpui::TestAppContext) {
+    let text = "fn a() {}\n".repeat(100);
+    let buffer =
+        cx.new_model(|cx| Buffer::local(&text, cx).with_language(Arc::new(rust_lang()), cx));
+    cx.executor().run_until_parked();
+
+    let usage = buffer.read_with(cx, |buffer, _| buffer.snapshot().memory_usage());
+    assert!(usage.text >= text.len());
+    assert!(usage.syntax_trees > 0);
+
+    // Deleted text is kept for undo.
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(0..text.len(), "")], None, cx)
+    });
+    cx.executor().run_until_parked();
+    let usage = buffer.read_with(cx, |buffer, _| buffer.snapshot().memory_usage());
+    assert!(usage.text >= text.len());
+}
+
+#[gpui::test]
+async fn test_highlights_in_background(cx: &mut gpui::TestAppContext) {
+    cx.update(|cx| {
+        let settings_store = SettingsStore::test(cx);
+        cx.set_global(settings_store);
+        crate::init(cx);
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings::<SyntaxParsingSettings>(cx, |settings| {
+                settings.large_file_size_kb = Some(1);
+            });
+        });
+    });
+
+    let language = rust_lang()
+        .with_highlights_query("(function_item name: (identifier) @fn.name)")
+        .unwrap();
+    language.set_theme(&SyntaxTheme::new_test([("fn.name", Hsla::blue())]));
+    let text = (0..100)
+        .map(|ix| format!("fn f{ix}() {{}}\n"))
+        .collect::<String>();
+    let buffer = cx.new_model(|cx| Buffer::local(&text, cx).with_language(Arc::new(language), cx));
+    cx.executor().run_until_parked();
+    assert!(buffer.read_with(cx, |buffer, cx| buffer.highlights_in_background(cx)));
+
+    // Nothing is highlighted until the highlights of the first rows are found.
+    buffer.update(cx, |buffer, cx| {
+        let end = buffer.text().find("fn f3").unwrap();
+        buffer.highlight_in_background(0..end, cx);
+    });
+    assert_eq!(highlighted_text(&buffer, cx), Vec::<String>::new());
+    cx.executor().run_until_parked();
+    assert_eq!(highlighted_text(&buffer, cx), ["f0", "f1", "f2"]);
+
+    // Edits shift the previous highlights until the buffer is reparsed.
+    buffer.update(cx, |buffer, cx| {
+        let offset = buffer.text().find("f1").unwrap();
+        buffer.edit([(offset..offset, "g")], None, cx);
+        assert!(buffer.is_parsing());
+    });
+    assert_eq!(highlighted_text(&buffer, cx), ["f0", "f1", "f2"]);
+    cx.executor().run_until_parked();
+    assert_eq!(highlighted_text(&buffer, cx), ["f0", "gf1", "f2"]);
+
+    // Rows scrolled into view are highlighted once their highlights are found.
+    buffer.update(cx, |buffer, cx| {
+        let start = buffer.text().find("fn f50").unwrap();
+        let end = buffer.text().find("fn f52").unwrap();
+        buffer.highlight_in_background(start..end, cx);
+    });
+    cx.executor().run_until_parked();
+    assert_eq!(highlighted_text(&buffer, cx), ["f50", "f51"]);
+}
+
+fn highlighted_text(buffer: &Model<Buffer>, cx: &mut gpui::TestAppContext) -> Vec<String> {
+    buffer.read_with(cx, |buffer, _| {
+        let snapshot = buffer.snapshot();
+        snapshot
+            .chunks(0..snapshot.len(), true)
+            .filter(|chunk| {
+                chunk
+                    .syntax_highlight_id
+                    .map_or(false, |highlight_id| !highlight_id.is_default())
+            })
+            .map(|chunk| chunk.text.to_string())
+            .collect()
+    })
+}
+
 #[gpui::test]
 async fn test_reparse(cx: &mut gpui::TestAppContext) {
     let text = "fn a() {}";
@@ -1104,6 +1302,77 @@ fn test_range_for_syntax_ancestor(cx: &mut AppContext) {
     }
 }
 
+#[gpui::test]
+fn test_local_occurrences(cx: &mut AppContext) {
+    let language = rust_lang()
+        .with_locals_query(
+            r#"
+            [(function_item) (closure_expression) (block)] @local.scope
+            (parameter pattern: (identifier) @local.definition)
+            (closure_parameters (identifier) @local.definition)
+            (let_declaration pattern: (identifier) @local.definition)
+            (identifier) @local.reference
+            "#,
+        )
+        .unwrap();
+
+    cx.new_model(|cx| {
+        let text = r#"
+            fn a(x: u32) -> u32 {
+                let y = x + 1;
+                let f = |x| x * y;
+                f(B)
+            }
+        "#
+        .unindent();
+        let buffer = Buffer::local(text.clone(), cx).with_language(Arc::new(language), cx);
+        let snapshot = buffer.snapshot();
+
+        let occurrences = |range: Range<usize>| {
+            snapshot
+                .local_occurrences(range)
+                .into_iter()
+                .map(|occurrence| {
+                    (
+                        &text[occurrence.range.clone()],
+                        occurrence.range.start,
+                        occurrence.definition_range.start,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let param_x = text.find("x:").unwrap();
+        let y = text.find("y =").unwrap();
+        let x_plus = text.find("x +").unwrap();
+        let f = text.find("f =").unwrap();
+        let closure_x = text.find("|x|").unwrap() + 1;
+        let x_times = text.find("x *").unwrap();
+        let y_ref = text.find("y;").unwrap();
+        let f_call = text.find("f(").unwrap();
+
+        // The function's name and `B` aren't defined locally.
+        assert_eq!(
+            occurrences(0..text.len()),
+            [
+                ("x", param_x, param_x),
+                ("y", y, y),
+                ("x", x_plus, param_x),
+                ("f", f, f),
+                ("x", closure_x, closure_x),
+                ("x", x_times, closure_x),
+                ("y", y_ref, y),
+                ("f", f_call, f),
+            ]
+        );
+
+        // Definitions before the range are found for references within it.
+        let row_3 = text.find("    f(").unwrap();
+        assert_eq!(occurrences(row_3..text.len()), [("f", f_call, f)]);
+
+        buffer
+    });
+}
+
 #[gpui::test]
 fn test_autoindent_with_soft_tabs(cx: &mut AppContext) {
     init_settings(cx, |_| {});
@@ -11,10 +11,11 @@ pub struct HighlightId(pub u32);
 const DEFAULT_SYNTAX_HIGHLIGHT_ID: HighlightId = HighlightId(u32::MAX);
 
 impl HighlightMap {
-    pub(crate) fn new(capture_names: &[&str], theme: &SyntaxTheme) -> Self {
+    pub(crate) fn new(capture_names: &[&str], theme: &SyntaxTheme, language: &str) -> Self {
         // For each capture name in the highlight query, find the longest
         // key in the theme's syntax styles that matches all of the
-        // dot-separated components of the capture name.
+        // dot-separated components of the capture name. Of keys that match
+        // equally well, prefer the ones specific to the language.
         HighlightMap(
             capture_names
                 .iter()
@@ -23,6 +24,7 @@ impl HighlightMap {
                         .highlights
                         .iter()
                         .enumerate()
+                        .filter(|(i, _)| theme.applies_to_language(*i, language))
                         .filter_map(|(i, (key, _))| {
                             let mut len = 0;
                             let capture_parts = capture_name.split('.');
@@ -33,9 +35,9 @@ impl HighlightMap {
                                     return None;
                                 }
                             }
-                            Some((i, len))
+                            Some((i, (len, theme.is_language_specific(i))))
                         })
-                        .max_by_key(|(_, len)| *len)
+                        .max_by_key(|(_, specificity)| *specificity)
                         .map_or(DEFAULT_SYNTAX_HIGHLIGHT_ID, |(i, _)| HighlightId(i as u32))
                 })
                 .collect(),
@@ -95,6 +97,7 @@ mod tests {
             .iter()
             .map(|(name, color)| (name.to_string(), (*color).into()))
             .collect(),
+            language_highlights: Default::default(),
         };
 
         let capture_names = &[
@@ -103,9 +106,41 @@ mod tests {
             "variable.builtin.self",
         ];
 
-        let map = HighlightMap::new(capture_names, &theme);
+        let map = HighlightMap::new(capture_names, &theme, "Rust");
         assert_eq!(map.get(0).name(&theme), Some("function"));
         assert_eq!(map.get(1).name(&theme), Some("function.async"));
         assert_eq!(map.get(2).name(&theme), Some("variable.builtin"));
     }
+
+    #[test]
+    fn test_highlight_map_with_language_specific_styles() {
+        let theme = SyntaxTheme {
+            highlights: [
+                ("comment", rgba(0x100000ff)),
+                ("comment.doc", rgba(0x200000ff)),
+                ("keyword", rgba(0x300000ff)),
+            ]
+            .iter()
+            .map(|(name, color)| (name.to_string(), (*color).into()))
+            .collect(),
+            language_highlights: Default::default(),
+        };
+        let theme = SyntaxTheme::merge_language(
+            Arc::new(theme),
+            "Rust",
+            vec![("comment".to_string(), rgba(0x400000ff).into())],
+        );
+
+        let capture_names = &["comment", "comment.doc", "keyword"];
+
+        let rust_map = HighlightMap::new(capture_names, &theme, "Rust");
+        assert_eq!(rust_map.get(0), HighlightId(3));
+        assert_eq!(rust_map.get(1).name(&theme), Some("comment.doc"));
+        assert_eq!(rust_map.get(2).name(&theme), Some("keyword"));
+
+        let python_map = HighlightMap::new(capture_names, &theme, "Python");
+        assert_eq!(python_map.get(0), HighlightId(0));
+        assert_eq!(python_map.get(1), HighlightId(1));
+        assert_eq!(python_map.get(2), HighlightId(2));
+    }
 }
@@ -9,6 +9,7 @@
 mod buffer;
 mod diagnostic_set;
 mod highlight_map;
+mod language_detection;
 mod language_registry;
 pub mod language_settings;
 mod outline;
@@ -57,17 +58,18 @@ use std::{
 };
 use syntax_map::{QueryCursorHandle, SyntaxSnapshot};
 use task::RunnableTag;
-pub use task_context::{ContextProvider, RunnableRange};
+pub use task_context::{ContextProvider, ProvidedRunnable, RunnableKind, RunnableRange};
 use theme::SyntaxTheme;
 use tree_sitter::{self, wasmtime, Query, QueryCursor, WasmStore};
 use util::serde::default_true;
 
 pub use buffer::Operation;
 pub use buffer::*;
-pub use diagnostic_set::DiagnosticEntry;
+pub use diagnostic_set::{DiagnosticEntry, DiagnosticGroup};
+pub use language_detection::LanguageDetectionSource;
 pub use language_registry::{
-    LanguageNotFound, LanguageQueries, LanguageRegistry, LanguageServerBinaryStatus,
-    PendingLanguageServer, QUERY_FILENAME_PREFIXES,
+    language_override_path, LanguageNotFound, LanguageQueries, LanguageRegistry,
+    LanguageServerBinaryStatus, PendingLanguageServer, QUERY_FILENAME_PREFIXES,
 };
 pub use lsp::LanguageServerId;
 pub use outline::*;
@@ -631,6 +633,14 @@ pub struct LanguageConfig {
     /// languages, but should not appear to the user as a distinct language.
     #[serde(default)]
     pub hidden: bool,
+    /// If true, the editor continues lists and block quotes on newline, formats pipe tables
+    /// and turns URLs pasted over a selection into links, as in Markdown.
+    #[serde(default)]
+    pub markdown_editing: bool,
+    /// If true, outline items like the headings of documents can be folded, along with the
+    /// lines up to the next item at the same or a lower depth.
+    #[serde(default)]
+    pub outline_folding: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default, JsonSchema)]
@@ -718,6 +728,8 @@ impl Default for LanguageConfig {
             soft_wrap: None,
             prettier_parser_name: None,
             hidden: false,
+            markdown_editing: false,
+            outline_folding: false,
         }
     }
 }
@@ -862,6 +874,7 @@ pub struct Grammar {
     pub(crate) brackets_config: Option<BracketConfig>,
     pub(crate) redactions_config: Option<RedactionConfig>,
     pub(crate) runnable_config: Option<RunnableConfig>,
+    pub(crate) locals_config: Option<LocalsConfig>,
     pub(crate) indents_config: Option<IndentConfig>,
     pub outline_config: Option<OutlineConfig>,
     pub embedding_config: Option<EmbeddingConfig>,
@@ -911,6 +924,13 @@ struct RedactionConfig {
     pub redaction_capture_ix: u32,
 }
 
+struct LocalsConfig {
+    pub query: Query,
+    pub scope_capture_ix: u32,
+    pub definition_capture_ix: u32,
+    pub reference_capture_ix: u32,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 enum RunnableCapture {
     Named(SharedString),
@@ -965,6 +985,7 @@ impl Language {
                     override_config: None,
                     redactions_config: None,
                     runnable_config: None,
+                    locals_config: None,
                     error_query: Query::new(&ts_language, "(ERROR) @error").unwrap(),
                     ts_language,
                     highlight_map: Default::default(),
@@ -1025,6 +1046,11 @@ impl Language {
                 .with_runnable_query(query.as_ref())
                 .context("Error loading tests query")?;
         }
+        if let Some(query) = queries.locals {
+            self = self
+                .with_locals_query(query.as_ref())
+                .context("Error loading locals query")?;
+        }
         Ok(self)
     }
 
@@ -1330,6 +1356,40 @@ impl Language {
         Ok(self)
     }
 
+    pub fn with_locals_query(mut self, source: &str) -> anyhow::Result<Self> {
+        let grammar = self
+            .grammar_mut()
+            .ok_or_else(|| anyhow!("cannot mutate grammar"))?;
+
+        let query = Query::new(&grammar.ts_language, source)?;
+        let mut scope_capture_ix = None;
+        let mut definition_capture_ix = None;
+        let mut reference_capture_ix = None;
+        get_capture_indices(
+            &query,
+            &mut [
+                ("local.scope", &mut scope_capture_ix),
+                ("local.definition", &mut definition_capture_ix),
+                ("local.reference", &mut reference_capture_ix),
+            ],
+        );
+
+        if let Some(((scope_capture_ix, definition_capture_ix), reference_capture_ix)) =
+            scope_capture_ix
+                .zip(definition_capture_ix)
+                .zip(reference_capture_ix)
+        {
+            grammar.locals_config = Some(LocalsConfig {
+                query,
+                scope_capture_ix,
+                definition_capture_ix,
+                reference_capture_ix,
+            });
+        }
+
+        Ok(self)
+    }
+
     fn grammar_mut(&mut self) -> Option<&mut Grammar> {
         Arc::get_mut(self.grammar.as_mut()?)
     }
@@ -1363,9 +1423,11 @@ impl Language {
                 });
             let highlight_maps = vec![grammar.highlight_map()];
             let mut offset = 0;
-            for chunk in
-                BufferChunks::new(text, range, Some((captures, highlight_maps)), false, None)
-            {
+            let captures = HighlightCaptures::Query {
+                captures,
+                highlight_maps,
+            };
+            for chunk in BufferChunks::new(text, range, Some(captures), false, None) {
                 let end_offset = offset + chunk.text.len();
                 if let Some(highlight_id) = chunk.syntax_highlight_id {
                     if !highlight_id.is_default() {
@@ -1390,7 +1452,7 @@ impl Language {
         if let Some(grammar) = self.grammar.as_ref() {
             if let Some(highlights_query) = &grammar.highlights_query {
                 *grammar.highlight_map.lock() =
-                    HighlightMap::new(highlights_query.capture_names(), theme);
+                    HighlightMap::new(highlights_query.capture_names(), theme, &self.name());
             }
         }
     }
@@ -1427,6 +1489,30 @@ impl LanguageScope {
         self.language.config.collapsed_placeholder.as_ref()
     }
 
+    /// Returns whether Markdown editing aids, like list continuation, are enabled in this scope.
+    pub fn markdown_editing(&self) -> bool {
+        self.language.config.markdown_editing
+    }
+
+    /// Returns whether outline items, like headings, can be folded in this scope.
+    pub fn outline_folding(&self) -> bool {
+        self.language.config.outline_folding
+    }
+
+    /// Returns the name of the override this scope is in, like `comment` or `string`.
+    pub fn override_name(&self) -> Option<&str> {
+        let id = self.override_id?;
+        let grammar = self.language.grammar.as_ref()?;
+        let override_config = grammar.override_config.as_ref()?;
+        override_config.values.get(&id).map(|e| e.0.as_str())
+    }
+
+    /// Returns whether this scope's language is hidden, only being injected
+    /// into other languages, like Markdown-Inline.
+    pub fn is_hidden_language(&self) -> bool {
+        self.language.config.hidden
+    }
+
     /// Returns line prefix that is inserted in e.g. line continuations or
     /// in `toggle comments` action.
     pub fn line_comment_prefixes(&self) -> &[Arc<str>] {
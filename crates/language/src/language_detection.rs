@@ -0,0 +1,341 @@
+//! Detection of the language of a file from its content, for the files whose
+//! name doesn't tell it: shebang lines, Vim and Emacs modelines, and the look of
+//! some files that usually have no extension, like Dockerfiles and Makefiles.
+
+use regex::Regex;
+use std::{ops::Range, sync::LazyLock};
+use sum_tree::Bias;
+use text::{Point, Rope};
+
+/// The length of the first line matched against first line patterns.
+const FIRST_LINE_LEN: u32 = 256;
+/// The number of lines at the start and at the end of a file where modelines
+/// are looked for, like Vim does.
+const MODELINE_LINES: u32 = 5;
+/// The number of lines at the start of a file the content heuristics look at.
+const HEURISTIC_LINES: u32 = 50;
+/// The most bytes of a file looked at, so that long lines don't slow the detection.
+const MAX_SCANNED_LEN: usize = 16 * 1024;
+
+static VIM_MODELINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|\s)(?:vi|vim|Vim|ex)(?:[<=>]?\d+)?:\s*(.*)$").unwrap());
+static EMACS_MODELINE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"-\*-(.*?)-\*-").unwrap());
+static MAKE_RULE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[\w./%-]+(?:[ \t]+[\w./%-]+)*[ \t]*:(?:[^=:]|$)").unwrap());
+static DOCKERFILE_INSTRUCTION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?:ADD|ARG|CMD|COPY|ENTRYPOINT|ENV|EXPOSE|HEALTHCHECK|LABEL|ONBUILD|RUN|SHELL|STOPSIGNAL|USER|VOLUME|WORKDIR)\s",
+    )
+    .unwrap()
+});
+
+/// The languages usually run by interpreters, by the suffixes of their files.
+const INTERPRETER_LANGUAGES: &[(&str, &str)] = &[
+    ("awk", "awk"),
+    ("bash", "sh"),
+    ("bun", "ts"),
+    ("dash", "sh"),
+    ("deno", "ts"),
+    ("elixir", "exs"),
+    ("escript", "erl"),
+    ("fish", "fish"),
+    ("ksh", "sh"),
+    ("lua", "lua"),
+    ("luajit", "lua"),
+    ("make", "Makefile"),
+    ("node", "js"),
+    ("nodejs", "js"),
+    ("perl", "pl"),
+    ("php", "php"),
+    ("pwsh", "ps1"),
+    ("pypy", "py"),
+    ("python", "py"),
+    ("Rscript", "r"),
+    ("ruby", "rb"),
+    ("runghc", "hs"),
+    ("runhaskell", "hs"),
+    ("sh", "sh"),
+    ("tclsh", "tcl"),
+    ("ts-node", "ts"),
+    ("zsh", "sh"),
+];
+
+/// How the language of a file was detected, from the weakest to the strongest
+/// reason.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LanguageDetectionSource {
+    /// The look of the file's content, like the instructions of a Dockerfile.
+    Content,
+    /// The interpreter of the file's shebang line.
+    Shebang,
+    /// The file's extension or name.
+    Extension,
+    /// The file's first line, matched by the language's `first_line_pattern`.
+    FirstLine,
+    /// A Vim or Emacs modeline in the file.
+    Modeline,
+    /// The `file_types` setting.
+    FileTypes,
+    /// The language chosen for the file.
+    Override,
+}
+
+impl LanguageDetectionSource {
+    /// Returns how strongly the source tells the language of a file. The
+    /// language of a file is the one with the highest score.
+    pub(crate) fn score(self) -> usize {
+        match self {
+            Self::Content | Self::Shebang => 1,
+            Self::Extension | Self::FirstLine => 2,
+            Self::Modeline => 3,
+            Self::FileTypes => 4,
+            Self::Override => 5,
+        }
+    }
+
+    /// Describes the source, like `detected from shebang`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Content => "detected from content",
+            Self::Shebang => "detected from shebang",
+            Self::Extension => "detected from extension",
+            Self::FirstLine => "detected from first line",
+            Self::Modeline => "set by modeline",
+            Self::FileTypes => "set by file types setting",
+            Self::Override => "chosen for this file",
+        }
+    }
+}
+
+/// What the content of a file tells about its language.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct ContentLanguageHints {
+    /// The start of the first line of the file.
+    pub first_line: String,
+    /// The language named by a Vim or Emacs modeline.
+    pub modeline: Option<String>,
+    /// The names or file suffixes of the language suggested by the shebang line
+    /// or by the look of the content.
+    pub detected: Vec<String>,
+    /// Whether the detected language is the one of the shebang line's interpreter.
+    pub from_shebang: bool,
+}
+
+impl ContentLanguageHints {
+    pub fn new(content: &Rope) -> Self {
+        let first_line = text_in_range(content, 0..offset_of_row(content, 0, FIRST_LINE_LEN));
+        let head = text_in_range(content, 0..offset_of_row(content, HEURISTIC_LINES, 0));
+        let tail_start = offset_of_row(
+            content,
+            content.max_point().row.saturating_sub(MODELINE_LINES - 1),
+            0,
+        )
+        .max(content.len().saturating_sub(MAX_SCANNED_LEN));
+        let tail = text_in_range(content, tail_start..content.len());
+        Self::from_text(first_line, &head, &tail)
+    }
+
+    fn from_text(first_line: String, head: &str, tail: &str) -> Self {
+        let modeline = head
+            .lines()
+            .take(MODELINE_LINES as usize)
+            .chain(tail.lines())
+            .find_map(modeline_language)
+            .map(str::to_string);
+
+        let mut detected = Vec::new();
+        let mut from_shebang = false;
+        if let Some(interpreter) = shebang_interpreter(&first_line) {
+            from_shebang = true;
+            detected.push(interpreter.to_string());
+            detected.extend(
+                INTERPRETER_LANGUAGES
+                    .iter()
+                    .filter(|(name, _)| *name == interpreter)
+                    .map(|(_, suffix)| suffix.to_string()),
+            );
+        } else if let Some(names) = heuristic_language(head) {
+            detected.extend(names.iter().map(|name| name.to_string()));
+        }
+
+        Self {
+            first_line,
+            modeline,
+            detected,
+            from_shebang,
+        }
+    }
+}
+
+fn offset_of_row(content: &Rope, row: u32, column: u32) -> usize {
+    let point = content.clip_point(Point::new(row, column), Bias::Left);
+    content.point_to_offset(point)
+}
+
+fn text_in_range(content: &Rope, range: Range<usize>) -> String {
+    let end = range.end.min(range.start + MAX_SCANNED_LEN);
+    let start = content.clip_offset(range.start, Bias::Right);
+    let end = content.clip_offset(end, Bias::Left).max(start);
+    content.chunks_in_range(start..end).collect()
+}
+
+/// Returns the name of the interpreter the shebang line runs the file with,
+/// without its version, like `python` for `#!/usr/bin/env python3.12`.
+fn shebang_interpreter(first_line: &str) -> Option<&str> {
+    let mut words = first_line.strip_prefix("#!")?.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        // Skip the options of `env` and the variables it sets.
+        program = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+    }
+    let interpreter = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    (!interpreter.is_empty()).then_some(interpreter)
+}
+
+/// Returns the language set by a Vim modeline, like `# vim: ft=python`, or an
+/// Emacs one, like `# -*- mode: python -*-`, in the line.
+fn modeline_language(line: &str) -> Option<&str> {
+    if let Some(captures) = VIM_MODELINE.captures(line) {
+        let options = captures.get(1)?.as_str();
+        let options = options
+            .strip_prefix("set ")
+            .or_else(|| options.strip_prefix("se "))
+            .unwrap_or(options);
+        let language = options
+            .split(|c: char| c == ':' || c.is_whitespace())
+            .find_map(|option| {
+                let (name, value) = option.split_once('=')?;
+                matches!(name, "ft" | "filetype" | "syn" | "syntax").then_some(value)
+            });
+        if language.is_some() {
+            return language;
+        }
+    }
+
+    let variables = EMACS_MODELINE.captures(line)?.get(1)?.as_str();
+    if variables.contains(':') {
+        variables.split(';').find_map(|variable| {
+            let (name, value) = variable.split_once(':')?;
+            name.trim()
+                .eq_ignore_ascii_case("mode")
+                .then(|| value.trim())
+                .filter(|value| !value.is_empty())
+        })
+    } else {
+        Some(variables.trim()).filter(|mode| !mode.is_empty())
+    }
+}
+
+/// Recognizes the kinds of files that usually have no extension by the look of
+/// their first lines.
+fn heuristic_language(head: &str) -> Option<&'static [&'static str]> {
+    let lines = head.lines().collect::<Vec<_>>();
+
+    // A Dockerfile starts with a `FROM` instruction, maybe after some `ARG` ones.
+    let mut instructions = lines
+        .iter()
+        .map(|line| line.trim_start())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .skip_while(|line| line.starts_with("ARG "));
+    if instructions
+        .next()
+        .map_or(false, |line| line.starts_with("FROM "))
+        && instructions.any(|line| DOCKERFILE_INSTRUCTION.is_match(line))
+    {
+        return Some(&["Dockerfile"]);
+    }
+
+    // A Makefile has rules, whose recipe lines are indented with tabs.
+    let has_rule = lines.windows(2).any(|lines| {
+        MAKE_RULE.is_match(lines[0]) && lines[1].starts_with('\t') && !lines[1].trim().is_empty()
+    });
+    if has_rule || lines.iter().any(|line| line.starts_with(".PHONY:")) {
+        return Some(&["Makefile", "Make"]);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shebang_interpreter() {
+        assert_eq!(shebang_interpreter("#!/bin/bash"), Some("bash"));
+        assert_eq!(
+            shebang_interpreter("#! /usr/bin/python3.12 -u"),
+            Some("python")
+        );
+        assert_eq!(shebang_interpreter("#!/usr/bin/env node"), Some("node"));
+        assert_eq!(
+            shebang_interpreter("#!/usr/bin/env -S FOO=bar deno run"),
+            Some("deno")
+        );
+        assert_eq!(shebang_interpreter("#!/usr/bin/env"), None);
+        assert_eq!(shebang_interpreter("# not a shebang"), None);
+    }
+
+    #[test]
+    fn test_modeline_language() {
+        assert_eq!(modeline_language("# vim: ft=python"), Some("python"));
+        assert_eq!(
+            modeline_language("/* vim: set ts=4 filetype=c: */"),
+            Some("c")
+        );
+        assert_eq!(
+            modeline_language("# vi:noai:sw=3 syntax=ruby"),
+            Some("ruby")
+        );
+        assert_eq!(
+            modeline_language("# -*- mode: sh; tab-width: 4 -*-"),
+            Some("sh")
+        );
+        assert_eq!(modeline_language(";; -*- lisp -*-"), Some("lisp"));
+        assert_eq!(modeline_language("# vim: ts=4"), None);
+        assert_eq!(modeline_language("avim: ft=python"), None);
+        assert_eq!(modeline_language("nothing to see"), None);
+    }
+
+    #[test]
+    fn test_heuristic_language() {
+        assert_eq!(
+            heuristic_language("# syntax=docker/dockerfile:1\nARG VERSION\nFROM rust:${VERSION}\nRUN cargo build\n"),
+            Some(&["Dockerfile"][..])
+        );
+        assert_eq!(
+            heuristic_language("CC = gcc\n\nall: main.o\n\t$(CC) -o main main.o\n"),
+            Some(&["Makefile", "Make"][..])
+        );
+        assert_eq!(
+            heuristic_language(".PHONY: test\n"),
+            Some(&["Makefile", "Make"][..])
+        );
+        assert_eq!(
+            heuristic_language("From: someone\nTo: someone else\n\nHello"),
+            None
+        );
+        assert_eq!(heuristic_language("key: value\n  nested: value\n"), None);
+    }
+
+    #[test]
+    fn test_content_language_hints() {
+        let hints = ContentLanguageHints::new(&Rope::from(
+            "#!/usr/bin/env python3\nprint('hi')\n# vim: ft=python3\n",
+        ));
+        assert_eq!(
+            hints,
+            ContentLanguageHints {
+                first_line: "#!/usr/bin/env python3".into(),
+                modeline: Some("python3".into()),
+                detected: vec!["python".into(), "py".into()],
+                from_shebang: true,
+            }
+        );
+
+        let hints = ContentLanguageHints::new(&Rope::from("FROM alpine\nCMD [\"sh\"]\n"));
+        assert_eq!(hints.modeline, None);
+        assert_eq!(hints.detected, vec!["Dockerfile".to_string()]);
+        assert!(!hints.from_shebang);
+    }
+}
@@ -1,6 +1,8 @@
 use crate::{
+    language_detection::{ContentLanguageHints, LanguageDetectionSource},
     language_settings::{
-        all_language_settings, AllLanguageSettingsContent, LanguageSettingsContent,
+        all_language_settings, AllLanguageSettings, AllLanguageSettingsContent,
+        LanguageSettingsContent,
     },
     task_context::ContextProvider,
     with_parser, CachedLspAdapter, File, Language, LanguageConfig, LanguageId, LanguageMatcher,
@@ -14,7 +16,6 @@ use futures::{
     future::Shared,
     Future, FutureExt as _,
 };
-use globset::GlobSet;
 use gpui::{AppContext, BackgroundExecutor, Task};
 use lsp::LanguageServerId;
 use parking_lot::{Mutex, RwLock};
@@ -26,8 +27,7 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
-use sum_tree::Bias;
-use text::{Point, Rope};
+use text::Rope;
 use theme::Theme;
 use unicase::UniCase;
 use util::{maybe, paths::PathExt, post_inc, ResultExt};
@@ -47,10 +47,15 @@ struct LanguageRegistryState {
     available_languages: Vec<AvailableLanguage>,
     grammars: HashMap<Arc<str>, AvailableGrammar>,
     lsp_adapters: HashMap<Arc<str>, Vec<Arc<CachedLspAdapter>>>,
+    /// The context providers of languages registered without one, by their name.
+    context_providers: HashMap<Arc<str>, Arc<dyn ContextProvider>>,
     available_lsp_adapters:
         HashMap<LanguageServerName, Arc<dyn Fn() -> Arc<CachedLspAdapter> + 'static + Send + Sync>>,
     loading_languages: HashMap<LanguageId, Vec<oneshot::Sender<Result<Arc<Language>>>>>,
     subscription: (watch::Sender<()>, watch::Receiver<()>),
+    /// The languages chosen for files, by their path, whatever their name and
+    /// content.
+    language_overrides: HashMap<PathBuf, Arc<str>>,
     theme: Option<Arc<Theme>>,
     version: usize,
     reload_count: usize,
@@ -126,6 +131,7 @@ pub const QUERY_FILENAME_PREFIXES: &[(
     ("overrides", |q| &mut q.overrides),
     ("redactions", |q| &mut q.redactions),
     ("runnables", |q| &mut q.runnables),
+    ("locals", |q| &mut q.locals),
 ];
 
 /// Tree-sitter language queries for a given language.
@@ -140,6 +146,7 @@ pub struct LanguageQueries {
     pub overrides: Option<Cow<'static, str>>,
     pub redactions: Option<Cow<'static, str>>,
     pub runnables: Option<Cow<'static, str>>,
+    pub locals: Option<Cow<'static, str>>,
 }
 
 #[derive(Clone, Default)]
@@ -158,8 +165,10 @@ impl LanguageRegistry {
                 language_settings: Default::default(),
                 loading_languages: Default::default(),
                 lsp_adapters: Default::default(),
+                context_providers: Default::default(),
                 available_lsp_adapters: HashMap::default(),
                 subscription: watch::channel(),
+                language_overrides: HashMap::default(),
                 theme: Default::default(),
                 version: 0,
                 reload_count: 0,
@@ -276,6 +285,19 @@ impl LanguageRegistry {
             .push(CachedLspAdapter::new(adapter));
     }
 
+    /// Adds a context provider to the language of the given name, used when the
+    /// language, such as one from an extension, is registered without its own.
+    pub fn register_context_provider(
+        &self,
+        language_name: Arc<str>,
+        provider: Arc<dyn ContextProvider>,
+    ) {
+        self.state
+            .write()
+            .context_providers
+            .insert(language_name, provider);
+    }
+
     #[cfg(any(feature = "test-support", test))]
     pub fn register_fake_lsp_adapter(
         &self,
@@ -481,18 +503,39 @@ impl LanguageRegistry {
         cx: &AppContext,
     ) -> impl Future<Output = Result<Arc<Language>>> {
         let user_file_types = all_language_settings(Some(file), cx);
+        let language_override = self.language_override(&language_override_path(file.as_ref(), cx));
         self.language_for_file_internal(
             &file.full_path(cx),
             content,
-            Some(&user_file_types.file_types),
+            Some(user_file_types),
+            language_override,
         )
     }
 
+    /// Returns why the file would use the language, or `None` when nothing about
+    /// the file tells that language.
+    pub fn language_detection_source(
+        &self,
+        file: &Arc<dyn File>,
+        content: Option<&Rope>,
+        language: &Language,
+        cx: &AppContext,
+    ) -> Option<LanguageDetectionSource> {
+        let path = file.full_path(cx);
+        let query = FileLanguageQuery::new(
+            &path,
+            content,
+            Some(all_language_settings(Some(file), cx)),
+            self.language_override(&language_override_path(file.as_ref(), cx)),
+        );
+        query.detection_source(&language.config.name, &language.config.matcher)
+    }
+
     pub fn language_for_file_path<'a>(
         self: &Arc<Self>,
         path: &'a Path,
     ) -> impl Future<Output = Result<Arc<Language>>> + 'a {
-        self.language_for_file_internal(path, None, None)
+        self.language_for_file_internal(path, None, None, None)
             .map_err(|error| error.context(format!("language for file path {}", path.display())))
     }
 
@@ -500,45 +543,43 @@ impl LanguageRegistry {
         self: &Arc<Self>,
         path: &Path,
         content: Option<&Rope>,
-        user_file_types: Option<&HashMap<Arc<str>, GlobSet>>,
+        user_file_types: Option<&AllLanguageSettings>,
+        language_override: Option<Arc<str>>,
     ) -> impl Future<Output = Result<Arc<Language>>> {
-        let filename = path.file_name().and_then(|name| name.to_str());
-        let extension = path.extension_or_hidden_file_name();
-        let path_suffixes = [extension, filename, path.to_str()];
-        let empty = GlobSet::empty();
-
-        let rx = self.get_or_load_language(move |language_name, config| {
-            let path_matches_default_suffix = config
-                .path_suffixes
-                .iter()
-                .any(|suffix| path_suffixes.contains(&Some(suffix.as_str())));
-            let custom_suffixes = user_file_types
-                .and_then(|types| types.get(language_name))
-                .unwrap_or(&empty);
-            let path_matches_custom_suffix = path_suffixes
-                .iter()
-                .map(|suffix| suffix.unwrap_or(""))
-                .any(|suffix| custom_suffixes.is_match(suffix));
-            let content_matches = content.zip(config.first_line_pattern.as_ref()).map_or(
-                false,
-                |(content, pattern)| {
-                    let end = content.clip_point(Point::new(0, 256), Bias::Left);
-                    let end = content.point_to_offset(end);
-                    let text = content.chunks_in_range(0..end).collect::<String>();
-                    pattern.is_match(&text)
-                },
-            );
-            if path_matches_custom_suffix {
-                2
-            } else if path_matches_default_suffix || content_matches {
-                1
-            } else {
-                0
-            }
+        let query = FileLanguageQuery::new(path, content, user_file_types, language_override);
+        let rx = self.get_or_load_language(|language_name, config| {
+            query
+                .detection_source(language_name, config)
+                .map_or(0, LanguageDetectionSource::score)
         });
         async move { rx.await? }
     }
 
+    /// Makes the file at the path use the language, whatever its name and
+    /// content, or detect its language again when no language is given.
+    pub fn set_language_override(&self, path: PathBuf, language_name: Option<Arc<str>>) {
+        let mut state = self.state.write();
+        match language_name {
+            Some(language_name) => state.language_overrides.insert(path, language_name),
+            None => state.language_overrides.remove(&path),
+        };
+    }
+
+    /// Returns the language chosen for the file at the path, if any.
+    pub fn language_override(&self, path: &Path) -> Option<Arc<str>> {
+        self.state.read().language_overrides.get(path).cloned()
+    }
+
+    /// Returns the languages chosen for files, by their path.
+    pub fn language_overrides(&self) -> Vec<(PathBuf, Arc<str>)> {
+        let state = self.state.read();
+        state
+            .language_overrides
+            .iter()
+            .map(|(path, language_name)| (path.clone(), language_name.clone()))
+            .collect()
+    }
+
     fn get_or_load_language(
         self: &Arc<Self>,
         callback: impl Fn(&str, &LanguageMatcher) -> usize,
@@ -586,6 +627,9 @@ impl LanguageRegistry {
                         let name = language.name.clone();
                         let language = async {
                             let (config, queries, provider) = (language.load)()?;
+                            let provider = provider.or_else(|| {
+                                this.state.read().context_providers.get(&name).cloned()
+                            });
 
                             if let Some(grammar) = config.grammar.clone() {
                                 let grammar = Some(this.get_or_load_grammar(grammar).await?);
@@ -893,6 +937,118 @@ impl LanguageRegistry {
     }
 }
 
+/// What tells the language of a file.
+struct FileLanguageQuery<'a> {
+    path_suffixes: [Option<&'a str>; 3],
+    has_extension: bool,
+    content_hints: Option<ContentLanguageHints>,
+    user_file_types: Option<&'a AllLanguageSettings>,
+    language_override: Option<Arc<str>>,
+}
+
+impl<'a> FileLanguageQuery<'a> {
+    fn new(
+        path: &'a Path,
+        content: Option<&Rope>,
+        user_file_types: Option<&'a AllLanguageSettings>,
+        language_override: Option<Arc<str>>,
+    ) -> Self {
+        let filename = path.file_name().and_then(|name| name.to_str());
+        let extension = path.extension_or_hidden_file_name();
+        Self {
+            path_suffixes: [extension, filename, path.to_str()],
+            has_extension: path.extension().is_some(),
+            content_hints: content.map(ContentLanguageHints::new),
+            user_file_types,
+            language_override,
+        }
+    }
+
+    /// Returns the strongest reason for the file to use the language, if any.
+    fn detection_source(
+        &self,
+        language_name: &str,
+        config: &LanguageMatcher,
+    ) -> Option<LanguageDetectionSource> {
+        let is_language = |name: &str| {
+            UniCase::new(name) == UniCase::new(language_name)
+                || config
+                    .path_suffixes
+                    .iter()
+                    .any(|suffix| UniCase::new(suffix.as_str()) == UniCase::new(name))
+        };
+        if self.language_override.as_deref() == Some(language_name) {
+            return Some(LanguageDetectionSource::Override);
+        }
+
+        let path_matches_default_suffix = config
+            .path_suffixes
+            .iter()
+            .any(|suffix| self.path_suffixes.contains(&Some(suffix.as_str())));
+        let path_matches_custom_suffix = self
+            .user_file_types
+            .and_then(|types| types.file_types.get(language_name))
+            .map_or(false, |custom_suffixes| {
+                self.path_suffixes
+                    .iter()
+                    .map(|suffix| suffix.unwrap_or(""))
+                    .any(|suffix| custom_suffixes.is_match(suffix))
+            });
+        let Some(content_hints) = self.content_hints.as_ref() else {
+            return if path_matches_custom_suffix {
+                Some(LanguageDetectionSource::FileTypes)
+            } else if path_matches_default_suffix {
+                Some(LanguageDetectionSource::Extension)
+            } else {
+                None
+            };
+        };
+
+        let first_line = content_hints.first_line.as_str();
+        let content_matches_custom_pattern = self
+            .user_file_types
+            .and_then(|types| types.file_type_first_lines.get(language_name))
+            .map_or(false, |patterns| {
+                patterns.iter().any(|pattern| pattern.is_match(first_line))
+            });
+        let content_matches = config
+            .first_line_pattern
+            .as_ref()
+            .map_or(false, |pattern| pattern.is_match(first_line));
+        let modeline_matches = content_hints
+            .modeline
+            .as_deref()
+            .map_or(false, |modeline| is_language(modeline));
+        // The shebang and the look of the content are only used for the
+        // files whose extension doesn't tell their language.
+        let content_detected =
+            !self.has_extension && content_hints.detected.iter().any(|name| is_language(name));
+        if path_matches_custom_suffix || content_matches_custom_pattern {
+            Some(LanguageDetectionSource::FileTypes)
+        } else if modeline_matches {
+            Some(LanguageDetectionSource::Modeline)
+        } else if path_matches_default_suffix {
+            Some(LanguageDetectionSource::Extension)
+        } else if content_matches {
+            Some(LanguageDetectionSource::FirstLine)
+        } else if content_detected && content_hints.from_shebang {
+            Some(LanguageDetectionSource::Shebang)
+        } else if content_detected {
+            Some(LanguageDetectionSource::Content)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the path the language chosen for the file is stored under: its
+/// absolute path for local files.
+pub fn language_override_path(file: &dyn File, cx: &AppContext) -> PathBuf {
+    file.as_local()
+        .map(|file| file.abs_path(cx))
+        .unwrap_or_else(|| file.full_path(cx))
+}
+
 impl LanguageRegistryState {
     fn next_language_server_id(&mut self) -> LanguageServerId {
         LanguageServerId(post_inc(&mut self.next_language_server_id))
@@ -7,6 +7,7 @@ use core::slice;
 use globset::{Glob, GlobMatcher, GlobSet, GlobSetBuilder};
 use gpui::AppContext;
 use itertools::{Either, Itertools};
+use regex::Regex;
 use schemars::{
     schema::{InstanceType, ObjectValidation, Schema, SchemaObject, SingleOrVec},
     JsonSchema,
@@ -16,8 +17,10 @@ use serde::{
     Deserialize, Deserializer, Serialize,
 };
 use serde_json::Value;
-use settings::{add_references_to_properties, Settings, SettingsLocation, SettingsSources};
-use std::{num::NonZeroU32, path::Path, sync::Arc};
+use settings::{
+    add_references_to_properties, Settings, SettingsLocation, SettingsSources, SettingsStore,
+};
+use std::{num::NonZeroU32, path::Path, sync::Arc, time::Duration};
 use util::serde::default_true;
 
 impl<'a> Into<SettingsLocation<'a>> for &'a dyn File {
@@ -32,6 +35,8 @@ impl<'a> Into<SettingsLocation<'a>> for &'a dyn File {
 /// Initializes the language settings.
 pub fn init(cx: &mut AppContext) {
     AllLanguageSettings::register(cx);
+    SyntaxParsingSettings::register(cx);
+    ExternalChangesSettings::register(cx);
 }
 
 /// Returns the settings for the specified language from the provided file.
@@ -61,6 +66,7 @@ pub struct AllLanguageSettings {
     defaults: LanguageSettings,
     languages: HashMap<Arc<str>, LanguageSettings>,
     pub(crate) file_types: HashMap<Arc<str>, GlobSet>,
+    pub(crate) file_type_first_lines: HashMap<Arc<str>, Vec<Regex>>,
 }
 
 /// The settings for a particular language.
@@ -106,6 +112,8 @@ pub struct LanguageSettings {
     /// - `"!<language_server_id>"` - A language server ID prefixed with a `!` will be disabled.
     /// - `"..."` - A placeholder to refer to the **rest** of the registered language servers for this language.
     pub language_servers: Vec<Arc<str>>,
+    /// How requests are routed between the language servers of this language.
+    pub language_server_routing: LanguageServerRouting,
     /// Controls whether inline completions are shown immediately (true)
     /// or manually by triggering `editor::ShowInlineCompletion` (false).
     pub show_inline_completions: bool,
@@ -125,8 +133,18 @@ pub struct LanguageSettings {
     pub code_actions_on_format: HashMap<String, bool>,
     /// Whether to perform linked edits
     pub linked_edits: bool,
+    /// Whether to show code lenses provided by language servers.
+    pub code_lens: bool,
+    /// Whether to color local variables and parameters by name.
+    pub semantic_rainbow: bool,
     /// Task configuration for this language.
     pub tasks: LanguageTaskConfig,
+    /// How code is sent to the terminal with `editor::SendSelectionToTerminal`.
+    pub send_to_terminal: SendToTerminalSettings,
+    /// Spell checking settings for this language.
+    pub spell_check: SpellCheckSettings,
+    /// Grammar and style checking settings for this language.
+    pub grammar_check: GrammarCheckSettings,
 }
 
 impl LanguageSettings {
@@ -176,6 +194,68 @@ impl LanguageSettings {
     }
 }
 
+/// Controls how requests are dispatched when several language servers
+/// are running for the same language.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct LanguageServerRouting {
+    /// How completions from several language servers are combined.
+    ///
+    /// Default: merge
+    #[serde(default)]
+    pub completions: CompletionsRouting,
+    /// How hover responses from several language servers are combined.
+    ///
+    /// Default: all
+    #[serde(default)]
+    pub hover: HoverRouting,
+    /// The language servers to prefer for formatting, in order of priority.
+    /// The first running server from this list is used; when none of them
+    /// are running, the primary language server is used.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub formatting: Vec<Arc<str>>,
+}
+
+impl LanguageServerRouting {
+    /// Returns the position, within `running_language_servers`, of the
+    /// language server that should be used for formatting.
+    pub fn formatting_server_index(
+        &self,
+        running_language_servers: &[LanguageServerName],
+    ) -> Option<usize> {
+        self.formatting.iter().find_map(|preferred| {
+            running_language_servers
+                .iter()
+                .position(|running| running.0.as_ref() == preferred.as_ref())
+        })
+    }
+}
+
+/// How completions from several language servers are combined.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CompletionsRouting {
+    /// Query every capable language server and merge the results.
+    #[default]
+    Merge,
+    /// Only query the primary capable language server.
+    Primary,
+}
+
+/// How hover responses from several language servers are combined.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HoverRouting {
+    /// Show the hover contents of every language server.
+    #[default]
+    All,
+    /// Show the first non-empty hover, in language server order.
+    FirstNonEmpty,
+    /// Only query the primary language server.
+    Primary,
+}
+
 /// The provider that supplies inline completions.
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -211,10 +291,27 @@ pub struct AllLanguageSettingsContent {
     /// The settings for individual languages.
     #[serde(default)]
     pub languages: HashMap<Arc<str>, LanguageSettingsContent>,
-    /// Settings for associating file extensions and filenames
-    /// with languages.
+    /// Settings for associating file extensions, filenames and
+    /// first lines with languages.
     #[serde(default)]
-    pub file_types: HashMap<Arc<str>, Vec<String>>,
+    pub file_types: HashMap<Arc<str>, Vec<FileTypeMatcher>>,
+}
+
+/// A way of recognizing the files of a language.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum FileTypeMatcher {
+    /// A glob matched against the extension, the name or the path of files.
+    Glob(String),
+    /// A regex matched against the first line of files, for files whose
+    /// name doesn't tell their language.
+    FirstLine { first_line: String },
+}
+
+impl From<&str> for FileTypeMatcher {
+    fn from(glob: &str) -> Self {
+        Self::Glob(glob.to_string())
+    }
 }
 
 /// The settings for a particular language.
@@ -302,6 +399,10 @@ pub struct LanguageSettingsContent {
     /// Default: ["..."]
     #[serde(default)]
     pub language_servers: Option<Vec<Arc<str>>>,
+    /// How requests are routed when several language servers are running
+    /// for this language.
+    #[serde(default)]
+    pub language_server_routing: Option<LanguageServerRouting>,
     /// Controls whether inline completions are shown immediately (true)
     /// or manually by triggering `editor::ShowInlineCompletion` (false).
     ///
@@ -347,10 +448,26 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: true
     pub linked_edits: Option<bool>,
+    /// Whether to show code lenses, such as reference counts or run and debug
+    /// actions, above the lines they apply to, if the language server provides them.
+    ///
+    /// Default: false
+    pub code_lens: Option<bool>,
+    /// Whether to color each local variable and parameter with a distinct color,
+    /// derived from its name, in all of its occurrences within a function.
+    ///
+    /// Default: false
+    pub semantic_rainbow: Option<bool>,
     /// Task configuration for this language.
     ///
     /// Default: {}
     pub tasks: Option<LanguageTaskConfig>,
+    /// How code is sent to the terminal with `editor::SendSelectionToTerminal`.
+    pub send_to_terminal: Option<SendToTerminalSettings>,
+    /// Spell checking settings for this language.
+    pub spell_check: Option<SpellCheckSettingsContent>,
+    /// Grammar and style checking settings for this language.
+    pub grammar_check: Option<GrammarCheckSettingsContent>,
 }
 
 /// The contents of the inline completion settings.
@@ -657,6 +774,112 @@ pub enum Formatter {
     CodeActions(HashMap<String, bool>),
 }
 
+/// How code is sent to the terminal with `editor::SendSelectionToTerminal`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SendToTerminalSettings {
+    /// Whether to paste multiple lines at once, as a bracketed paste, when the program
+    /// running in the terminal supports it. Otherwise, the lines are typed in one by one.
+    ///
+    /// Default: true
+    #[serde(default = "default_true")]
+    pub bracketed_paste: bool,
+    /// Whether to end the code with an empty line, which REPLs like Python's need
+    /// to run an indented block.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub end_with_empty_line: bool,
+}
+
+/// The settings for spell checking.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SpellCheckSettings {
+    /// Whether to check the spelling of words.
+    pub enabled: bool,
+    /// The parts of the code in which spelling is checked: the names of the
+    /// scopes of the language, like `comment` and `string`, or `text` for any
+    /// text outside of them, like the prose of Markdown files.
+    pub scopes: Vec<String>,
+    /// The names of the Hunspell dictionaries used to check spelling, like `en_US`.
+    pub dictionaries: Vec<Arc<str>>,
+}
+
+/// The settings for spell checking.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SpellCheckSettingsContent {
+    /// Whether to check the spelling of words.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The parts of the code in which spelling is checked: the names of the
+    /// scopes of the language, like `comment` and `string`, or `text` for any
+    /// text outside of them, like the prose of Markdown files.
+    ///
+    /// Default: ["comment", "string"]
+    pub scopes: Option<Vec<String>>,
+    /// The names of the Hunspell dictionaries used to check spelling, like `en_US`.
+    /// Dictionaries are looked up in the `dictionaries` directory of the Zed
+    /// configuration directory, then in the system's dictionary directories.
+    ///
+    /// Default: ["en_US"]
+    pub dictionaries: Option<Vec<Arc<str>>>,
+}
+
+/// The settings for grammar and style checking, with a checker speaking the
+/// LanguageTool HTTP API.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct GrammarCheckSettings {
+    /// Whether to check grammar and style.
+    pub enabled: bool,
+    /// The URL of the checker's API.
+    pub api_url: String,
+    /// The language of the text, like `en-US`, or `auto` to detect it.
+    pub language: String,
+    /// The parts of the code that are checked: the names of the scopes of the
+    /// language, like `comment`, or `text` for any text outside of them.
+    pub scopes: Vec<String>,
+    /// When the text is checked.
+    pub check_on: GrammarCheckTrigger,
+}
+
+/// The settings for grammar and style checking, with a checker speaking the
+/// LanguageTool HTTP API.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct GrammarCheckSettingsContent {
+    /// Whether to check grammar and style.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The URL of the checker's API, like the one of a LanguageTool server.
+    ///
+    /// Default: "http://localhost:8081/v2"
+    pub api_url: Option<String>,
+    /// The language of the text, like `en-US`, or `auto` to detect it.
+    ///
+    /// Default: "auto"
+    pub language: Option<String>,
+    /// The parts of the code that are checked: the names of the scopes of the
+    /// language, like `comment`, or `text` for any text outside of them, like
+    /// the prose of Markdown files.
+    ///
+    /// Default: ["comment"]
+    pub scopes: Option<Vec<String>>,
+    /// When the text is checked.
+    ///
+    /// Default: save
+    pub check_on: Option<GrammarCheckTrigger>,
+}
+
+/// When grammar and style are checked.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GrammarCheckTrigger {
+    /// Check the text once it stops changing.
+    Edit,
+    /// Check the text when it's opened and saved.
+    Save,
+}
+
 /// The settings for indent guides.
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct IndentGuideSettings {
@@ -889,15 +1112,12 @@ impl settings::Settings for AllLanguageSettings {
             .ok_or_else(Self::missing_default)?;
 
         let mut file_types: HashMap<Arc<str>, GlobSet> = HashMap::default();
+        let mut file_type_first_lines: HashMap<Arc<str>, Vec<Regex>> = HashMap::default();
 
-        for (language, suffixes) in &default_value.file_types {
-            let mut builder = GlobSetBuilder::new();
-
-            for suffix in suffixes {
-                builder.add(Glob::new(suffix)?);
-            }
-
-            file_types.insert(language.clone(), builder.build()?);
+        for (language, matchers) in &default_value.file_types {
+            let (globs, first_lines) = build_file_type_matchers(matchers)?;
+            file_types.insert(language.clone(), globs);
+            file_type_first_lines.insert(language.clone(), first_lines);
         }
 
         for user_settings in sources.customizations() {
@@ -936,23 +1156,13 @@ impl settings::Settings for AllLanguageSettings {
                 );
             }
 
-            for (language, suffixes) in &user_settings.file_types {
-                let mut builder = GlobSetBuilder::new();
-
-                let default_value = default_value.file_types.get(&language.clone());
-
+            for (language, matchers) in &user_settings.file_types {
                 // Merge the default value with the user's value.
-                if let Some(suffixes) = default_value {
-                    for suffix in suffixes {
-                        builder.add(Glob::new(suffix)?);
-                    }
-                }
-
-                for suffix in suffixes {
-                    builder.add(Glob::new(suffix)?);
-                }
-
-                file_types.insert(language.clone(), builder.build()?);
+                let default_matchers = default_value.file_types.get(language).into_iter().flatten();
+                let (globs, first_lines) =
+                    build_file_type_matchers(default_matchers.chain(matchers))?;
+                file_types.insert(language.clone(), globs);
+                file_type_first_lines.insert(language.clone(), first_lines);
             }
         }
 
@@ -973,6 +1183,7 @@ impl settings::Settings for AllLanguageSettings {
             defaults,
             languages,
             file_types,
+            file_type_first_lines,
         })
     }
 
@@ -1020,6 +1231,22 @@ impl settings::Settings for AllLanguageSettings {
     }
 }
 
+fn build_file_type_matchers<'a>(
+    matchers: impl IntoIterator<Item = &'a FileTypeMatcher>,
+) -> Result<(GlobSet, Vec<Regex>)> {
+    let mut globs = GlobSetBuilder::new();
+    let mut first_lines = Vec::new();
+    for matcher in matchers {
+        match matcher {
+            FileTypeMatcher::Glob(glob) => {
+                globs.add(Glob::new(glob)?);
+            }
+            FileTypeMatcher::FirstLine { first_line } => first_lines.push(Regex::new(first_line)?),
+        }
+    }
+    Ok((globs.build()?, first_lines))
+}
+
 fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent) {
     fn merge<T>(target: &mut T, value: Option<T>) {
         if let Some(value) = value {
@@ -1044,7 +1271,34 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.code_actions_on_format.clone(),
     );
     merge(&mut settings.linked_edits, src.linked_edits);
+    merge(&mut settings.code_lens, src.code_lens);
+    merge(&mut settings.semantic_rainbow, src.semantic_rainbow);
     merge(&mut settings.tasks, src.tasks.clone());
+    merge(&mut settings.send_to_terminal, src.send_to_terminal);
+    if let Some(spell_check) = &src.spell_check {
+        merge(&mut settings.spell_check.enabled, spell_check.enabled);
+        merge(&mut settings.spell_check.scopes, spell_check.scopes.clone());
+        merge(
+            &mut settings.spell_check.dictionaries,
+            spell_check.dictionaries.clone(),
+        );
+    }
+    if let Some(grammar_check) = &src.grammar_check {
+        merge(&mut settings.grammar_check.enabled, grammar_check.enabled);
+        merge(
+            &mut settings.grammar_check.api_url,
+            grammar_check.api_url.clone(),
+        );
+        merge(
+            &mut settings.grammar_check.language,
+            grammar_check.language.clone(),
+        );
+        merge(
+            &mut settings.grammar_check.scopes,
+            grammar_check.scopes.clone(),
+        );
+        merge(&mut settings.grammar_check.check_on, grammar_check.check_on);
+    }
 
     merge(
         &mut settings.preferred_line_length,
@@ -1066,6 +1320,10 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.enable_language_server,
     );
     merge(&mut settings.language_servers, src.language_servers.clone());
+    merge(
+        &mut settings.language_server_routing,
+        src.language_server_routing.clone(),
+    );
     merge(
         &mut settings.show_inline_completions,
         src.show_inline_completions,
@@ -1102,10 +1360,189 @@ pub struct PrettierSettings {
     pub options: HashMap<String, serde_json::Value>,
 }
 
+/// How buffers are parsed for syntax highlighting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SyntaxParsingSettings {
+    /// How long an edit may block the main thread waiting for its reparse, before the
+    /// reparse continues in the background and the previous highlights are shown.
+    pub sync_parse_budget_ms: u64,
+    /// The size of a buffer, in kilobytes, above which edits never wait for their
+    /// reparse. 0 means no size limit.
+    pub large_file_size_kb: u64,
+    /// Whether to log how long each reparse takes.
+    pub log_parse_latency: bool,
+}
+
+impl Default for SyntaxParsingSettings {
+    fn default() -> Self {
+        Self {
+            sync_parse_budget_ms: 1,
+            large_file_size_kb: 1024,
+            log_parse_latency: false,
+        }
+    }
+}
+
+impl SyntaxParsingSettings {
+    /// The settings, or their defaults if they haven't been registered.
+    pub fn get_or_default(cx: &AppContext) -> Self {
+        cx.try_global::<SettingsStore>()
+            .and_then(|store| store.try_get::<Self>(None))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// How long an edit to a buffer of the given length may block the main thread
+    /// waiting for its reparse.
+    pub fn sync_parse_budget(&self, buffer_len: usize) -> Duration {
+        if self.is_large_buffer(buffer_len) {
+            Duration::ZERO
+        } else {
+            Duration::from_millis(self.sync_parse_budget_ms)
+        }
+    }
+
+    /// Whether a buffer of the given length is parsed, and highlighted, in the
+    /// background.
+    pub fn is_large_buffer(&self, buffer_len: usize) -> bool {
+        self.large_file_size_kb > 0
+            && buffer_len as u64 > self.large_file_size_kb.saturating_mul(1024)
+    }
+}
+
+/// The `syntax_parsing` settings.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SyntaxParsingSettingsContent {
+    /// How long, in milliseconds, an edit may block the main thread waiting for its
+    /// reparse. Once it elapses, the reparse continues in the background and the
+    /// previous highlights are shown until it's done.
+    ///
+    /// Default: 1
+    pub sync_parse_budget_ms: Option<u64>,
+    /// The size of a buffer, in kilobytes, above which edits never wait for their
+    /// reparse, which then always happens in the background, and the highlight
+    /// queries of the visible rows run in the background too. 0 means no size limit.
+    ///
+    /// Default: 1024
+    pub large_file_size_kb: Option<u64>,
+    /// Whether to log how long each reparse takes, and how much of it blocked the
+    /// main thread.
+    ///
+    /// Default: false
+    pub log_parse_latency: Option<bool>,
+}
+
+impl settings::Settings for SyntaxParsingSettings {
+    const KEY: Option<&'static str> = Some("syntax_parsing");
+
+    type FileContent = SyntaxParsingSettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        let mut settings = Self::default();
+        for content in sources.defaults_and_customizations() {
+            if let Some(sync_parse_budget_ms) = content.sync_parse_budget_ms {
+                settings.sync_parse_budget_ms = sync_parse_budget_ms;
+            }
+            if let Some(large_file_size_kb) = content.large_file_size_kb {
+                settings.large_file_size_kb = large_file_size_kb;
+            }
+            if let Some(log_parse_latency) = content.log_parse_latency {
+                settings.log_parse_latency = log_parse_latency;
+            }
+        }
+        Ok(settings)
+    }
+}
+
+/// What happens to buffers when their files are changed on disk by another program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExternalChangesSettings {
+    /// Whether buffers without unsaved changes are reloaded.
+    pub auto_reload: bool,
+    /// Whether the changes to the file are merged into buffers with unsaved changes,
+    /// when they don't conflict with them.
+    pub auto_merge: bool,
+}
+
+impl Default for ExternalChangesSettings {
+    fn default() -> Self {
+        Self {
+            auto_reload: true,
+            auto_merge: true,
+        }
+    }
+}
+
+impl ExternalChangesSettings {
+    /// The settings, or their defaults if they haven't been registered.
+    pub fn get_or_default(cx: &AppContext) -> Self {
+        cx.try_global::<SettingsStore>()
+            .and_then(|store| store.try_get::<Self>(None))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// The `external_changes` settings.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ExternalChangesSettingsContent {
+    /// Whether buffers without unsaved changes are reloaded when their files are
+    /// changed on disk. When they aren't, they're marked as conflicting with their
+    /// files, until they're reloaded or saved.
+    ///
+    /// Default: true
+    pub auto_reload: Option<bool>,
+    /// Whether the changes made to a file on disk are merged into its buffer when it
+    /// has unsaved changes that they don't conflict with. When they conflict, or
+    /// aren't merged, a three-way merge view resolves them.
+    ///
+    /// Default: true
+    pub auto_merge: Option<bool>,
+}
+
+impl settings::Settings for ExternalChangesSettings {
+    const KEY: Option<&'static str> = Some("external_changes");
+
+    type FileContent = ExternalChangesSettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        let mut settings = Self::default();
+        for content in sources.defaults_and_customizations() {
+            if let Some(auto_reload) = content.auto_reload {
+                settings.auto_reload = auto_reload;
+            }
+            if let Some(auto_merge) = content.auto_merge {
+                settings.auto_merge = auto_merge;
+            }
+        }
+        Ok(settings)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_sync_parse_budget() {
+        let settings = SyntaxParsingSettings::default();
+        assert_eq!(settings.sync_parse_budget(1024), Duration::from_millis(1));
+        assert_eq!(
+            settings.sync_parse_budget(1024 * 1024),
+            Duration::from_millis(1)
+        );
+        assert_eq!(settings.sync_parse_budget(1024 * 1024 + 1), Duration::ZERO);
+
+        let settings = SyntaxParsingSettings {
+            large_file_size_kb: 0,
+            ..settings
+        };
+        assert_eq!(
+            settings.sync_parse_budget(usize::MAX),
+            Duration::from_millis(1)
+        );
+    }
+
     #[test]
     fn test_formatter_deserialization() {
         let raw_auto = "{\"formatter\": \"auto\"}";
@@ -1220,4 +1657,37 @@ mod tests {
             ])
         );
     }
+
+    #[test]
+    fn test_language_server_routing() {
+        fn language_server_names(names: &[&str]) -> Vec<LanguageServerName> {
+            names
+                .iter()
+                .map(|name| LanguageServerName((*name).into()))
+                .collect()
+        }
+
+        let settings: LanguageSettingsContent = serde_json::from_str(
+            r#"{"language_server_routing": {"hover": "first_non_empty", "formatting": ["biome", "vtsls"]}}"#,
+        )
+        .unwrap();
+        let routing = settings.language_server_routing.unwrap();
+        assert_eq!(routing.completions, CompletionsRouting::Merge);
+        assert_eq!(routing.hover, HoverRouting::FirstNonEmpty);
+
+        let running = language_server_names(&["vtsls", "eslint", "biome"]);
+        assert_eq!(routing.formatting_server_index(&running), Some(2));
+        assert_eq!(
+            routing.formatting_server_index(&language_server_names(&["vtsls", "eslint"])),
+            Some(0)
+        );
+        assert_eq!(
+            routing.formatting_server_index(&language_server_names(&["eslint"])),
+            None
+        );
+        assert_eq!(
+            LanguageServerRouting::default().formatting_server_index(&running),
+            None
+        );
+    }
 }
@@ -18,6 +18,9 @@ use sum_tree::{Bias, SeekTarget, SumTree};
 use text::{Anchor, BufferSnapshot, OffsetRangeExt, Point, Rope, ToOffset, ToPoint};
 use tree_sitter::{Node, Query, QueryCapture, QueryCaptures, QueryCursor, QueryMatches, Tree};
 
+/// A rough estimate of the bytes that tree-sitter uses for each node of a syntax tree.
+const ESTIMATED_SYNTAX_NODE_SIZE: usize = 48;
+
 #[derive(Default)]
 pub struct SyntaxMap {
     snapshot: SyntaxSnapshot,
@@ -252,6 +255,19 @@ impl SyntaxSnapshot {
         self.layers.is_empty()
     }
 
+    /// An estimate of the bytes used by the syntax trees of every layer.
+    pub fn estimated_memory_usage(&self) -> usize {
+        self.layers
+            .iter()
+            .map(|layer| match &layer.content {
+                SyntaxLayerContent::Parsed { tree, .. } => {
+                    tree.root_node().descendant_count() * ESTIMATED_SYNTAX_NODE_SIZE
+                }
+                SyntaxLayerContent::Pending { .. } => 0,
+            })
+            .sum()
+    }
+
     fn interpolate(&mut self, text: &BufferSnapshot) {
         let edits = text
             .anchored_edits_since::<(usize, Point)>(&self.interpolated_version)
@@ -1,12 +1,13 @@
 use std::{ops::Range, sync::Arc};
 
-use crate::{Location, Runnable};
+use crate::{BufferSnapshot, Location, OutlineItem, Runnable};
 
 use anyhow::Result;
 use collections::HashMap;
 use gpui::AppContext;
-use task::{TaskTemplates, TaskVariables};
-use text::BufferId;
+use smallvec::SmallVec;
+use task::{RunnableTag, TaskTemplates, TaskVariables};
+use text::{BufferId, Point};
 
 pub struct RunnableRange {
     pub buffer_id: BufferId,
@@ -15,6 +16,50 @@ pub struct RunnableRange {
     pub runnable: Runnable,
     pub extra_captures: HashMap<String, String>,
 }
+
+/// What a runnable does, which determines its run indicator.
+/// Set with a `kind` property in `runnables.scm`, such as `(#set! kind bench)`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RunnableKind {
+    #[default]
+    Test,
+    Bench,
+    DocTest,
+    Main,
+    Script,
+    Target,
+}
+
+impl RunnableKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "test" => Some(Self::Test),
+            "bench" => Some(Self::Bench),
+            "doc-test" => Some(Self::DocTest),
+            "main" => Some(Self::Main),
+            "script" => Some(Self::Script),
+            "target" => Some(Self::Target),
+            _ => None,
+        }
+    }
+
+    /// Whether the runnable runs a program, that can be started in a debugger.
+    pub fn is_debuggable(&self) -> bool {
+        matches!(self, Self::Test | Self::Bench | Self::Main)
+    }
+}
+
+/// A runnable found by a [`ContextProvider`], rather than by the `runnables.scm` query of its language.
+pub struct ProvidedRunnable {
+    /// The range showing the run indicator, on its first row.
+    pub run_range: Range<usize>,
+    /// The range of the whole runnable.
+    pub full_range: Range<usize>,
+    pub tags: SmallVec<[RunnableTag; 1]>,
+    pub kind: RunnableKind,
+    /// Values available to the tasks as `ZED_CUSTOM_<name>` variables, like the named captures of a query.
+    pub extra_captures: HashMap<String, String>,
+}
 /// Language Contexts are used by Zed tasks to extract information about the source file where the tasks are supposed to be scheduled from.
 /// Multiple context providers may be used together: by default, Zed provides a base [`BasicContextProvider`] context that fills all non-custom [`VariableName`] variants.
 ///
@@ -30,6 +75,22 @@ pub trait ContextProvider: Send + Sync {
         Ok(TaskVariables::default())
     }
 
+    /// Finds runnables in the given range of the buffer, that the runnables query of the language can't express,
+    /// or for languages without a grammar.
+    fn runnables(&self, _buffer: &BufferSnapshot, _range: Range<usize>) -> Vec<ProvidedRunnable> {
+        Vec::new()
+    }
+
+    /// Finds the outline items intersecting the given range of the buffer, for languages without a grammar,
+    /// like the headings of documents. Their depths are assigned from how they contain each other.
+    fn outline_items(
+        &self,
+        _buffer: &BufferSnapshot,
+        _range: Range<usize>,
+    ) -> Option<Vec<OutlineItem<Point>>> {
+        None
+    }
+
     /// Provides all tasks, associated with the current language.
     fn associated_tasks(
         &self,
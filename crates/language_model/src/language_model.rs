@@ -8,7 +8,10 @@ pub mod settings;
 
 use anyhow::Result;
 use client::{Client, UserStore};
-use futures::{future::BoxFuture, stream::BoxStream, TryStreamExt as _};
+use futures::{
+    channel::oneshot, future::BoxFuture, stream::BoxStream, FutureExt as _, StreamExt as _,
+    TryStreamExt as _,
+};
 use gpui::{
     AnyElement, AnyView, AppContext, AsyncAppContext, Model, SharedString, Task, WindowContext,
 };
@@ -21,8 +24,9 @@ pub use request::*;
 pub use role::*;
 use schemars::JsonSchema;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use std::{future::Future, sync::Arc};
+use std::{future::Future, mem, sync::Arc};
 use ui::IconName;
+use util::ResultExt as _;
 
 pub fn init(
     user_store: Model<UserStore>,
@@ -104,6 +108,77 @@ pub trait LanguageModel: Send + Sync {
 }
 
 impl dyn LanguageModel {
+    /// Streams a completion like [`LanguageModel::stream_completion`], and records
+    /// the tokens of the request and of the response in the [`LanguageModelRegistry`]
+    /// once the stream is done or dropped.
+    pub fn stream_completion_with_usage(
+        self: Arc<Self>,
+        request: LanguageModelRequest,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        let (response_tx, response_rx) = oneshot::channel();
+        let stream = self.stream_completion(request.clone(), cx);
+        cx.spawn(|cx| async move {
+            // The sender is dropped without sending if the request fails.
+            let Ok(response) = response_rx.await else {
+                return;
+            };
+            let response_request = LanguageModelRequest {
+                messages: vec![LanguageModelRequestMessage {
+                    role: Role::Assistant,
+                    content: vec![response.into()],
+                    cache: false,
+                }],
+                ..Default::default()
+            };
+            let Some((input_tokens, output_tokens)) = cx
+                .update(|cx| {
+                    (
+                        self.count_tokens(request, cx),
+                        self.count_tokens(response_request, cx),
+                    )
+                })
+                .log_err()
+            else {
+                return;
+            };
+            let (Some(input_tokens), Some(output_tokens)) =
+                (input_tokens.await.log_err(), output_tokens.await.log_err())
+            else {
+                return;
+            };
+            let usage = TokenUsage {
+                requests: 1,
+                input_tokens,
+                output_tokens,
+            };
+            cx.update(|cx| {
+                LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
+                    registry.record_token_usage(self.as_ref(), usage, cx)
+                })
+            })
+            .log_err();
+        })
+        .detach();
+
+        async move {
+            let stream = stream.await?;
+            let mut recorder = ResponseRecorder {
+                response: String::new(),
+                response_tx: Some(response_tx),
+            };
+            Ok(stream
+                .map(move |chunk| {
+                    if let Ok(chunk) = &chunk {
+                        recorder.response.push_str(chunk);
+                    }
+                    chunk
+                })
+                .boxed())
+        }
+        .boxed()
+    }
+
     pub fn use_tool<T: LanguageModelTool>(
         &self,
         request: LanguageModelRequest,
@@ -130,6 +205,21 @@ impl dyn LanguageModel {
     }
 }
 
+/// Collects the text of a streamed response, and sends it when the stream is
+/// dropped, so that responses that are canceled are counted too.
+struct ResponseRecorder {
+    response: String,
+    response_tx: Option<oneshot::Sender<String>>,
+}
+
+impl Drop for ResponseRecorder {
+    fn drop(&mut self) {
+        if let Some(response_tx) = self.response_tx.take() {
+            response_tx.send(mem::take(&mut self.response)).ok();
+        }
+    }
+}
+
 pub trait LanguageModelTool: 'static + DeserializeOwned + JsonSchema {
     fn name() -> String;
     fn description() -> String;
@@ -4,13 +4,13 @@ use crate::{
         copilot_chat::CopilotChatLanguageModelProvider, google::GoogleLanguageModelProvider,
         ollama::OllamaLanguageModelProvider, open_ai::OpenAiLanguageModelProvider,
     },
-    LanguageModel, LanguageModelId, LanguageModelProvider, LanguageModelProviderId,
-    LanguageModelProviderState,
+    LanguageModel, LanguageModelId, LanguageModelName, LanguageModelProvider,
+    LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
 };
 use client::{Client, UserStore};
 use collections::BTreeMap;
 use gpui::{AppContext, EventEmitter, Global, Model, ModelContext};
-use std::sync::Arc;
+use std::{ops::AddAssign, sync::Arc};
 use ui::Context;
 
 pub fn init(user_store: Model<UserStore>, client: Arc<Client>, cx: &mut AppContext) {
@@ -75,7 +75,32 @@ impl Global for GlobalLanguageModelRegistry {}
 #[derive(Default)]
 pub struct LanguageModelRegistry {
     active_model: Option<ActiveModel>,
+    inline_assistant_model: Option<Arc<dyn LanguageModel>>,
     providers: BTreeMap<LanguageModelProviderId, Arc<dyn LanguageModelProvider>>,
+    token_usage: BTreeMap<(LanguageModelProviderName, LanguageModelName), TokenUsage>,
+}
+
+/// The tokens of the requests sent to a model and of its responses, counted with
+/// the model's tokenizer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub requests: usize,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+}
+
+impl TokenUsage {
+    pub fn total_tokens(&self) -> usize {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+impl AddAssign for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.requests += other.requests;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+    }
 }
 
 pub struct ActiveModel {
@@ -85,6 +110,8 @@ pub struct ActiveModel {
 
 pub enum Event {
     ActiveModelChanged,
+    InlineAssistantModelChanged,
+    TokenUsageChanged,
     ProviderStateChanged,
     AddedProvider(LanguageModelProviderId),
     RemovedProvider(LanguageModelProviderId),
@@ -227,6 +254,63 @@ impl LanguageModelRegistry {
     pub fn active_model(&self) -> Option<Arc<dyn LanguageModel>> {
         self.active_model.as_ref()?.model.clone()
     }
+
+    /// Selects the model used by the inline assistant, or `None` to use the
+    /// active model.
+    pub fn select_inline_assistant_model(
+        &mut self,
+        selection: Option<(&LanguageModelProviderId, &LanguageModelId)>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let model = selection.and_then(|(provider, model_id)| {
+            self.provider(provider)?
+                .provided_models(cx)
+                .into_iter()
+                .find(|model| &model.id() == model_id)
+        });
+        let changed = match (&self.inline_assistant_model, &model) {
+            (Some(old), Some(new)) => {
+                old.provider_id() != new.provider_id() || old.id() != new.id()
+            }
+            (None, None) => false,
+            _ => true,
+        };
+        if changed {
+            self.inline_assistant_model = model;
+            cx.emit(Event::InlineAssistantModelChanged);
+        }
+    }
+
+    /// The model used by the inline assistant, which is the active model unless
+    /// another one is selected for it.
+    pub fn inline_assistant_model(&self) -> Option<Arc<dyn LanguageModel>> {
+        self.inline_assistant_model
+            .clone()
+            .or_else(|| self.active_model())
+    }
+
+    pub fn record_token_usage(
+        &mut self,
+        model: &dyn LanguageModel,
+        usage: TokenUsage,
+        cx: &mut ModelContext<Self>,
+    ) {
+        *self
+            .token_usage
+            .entry((model.provider_name(), model.name()))
+            .or_default() += usage;
+        cx.emit(Event::TokenUsageChanged);
+    }
+
+    /// The tokens used by each model since Zed was started, by the names of their
+    /// providers and of the models.
+    pub fn token_usage(
+        &self,
+    ) -> impl Iterator<Item = (&LanguageModelProviderName, &LanguageModelName, TokenUsage)> {
+        self.token_usage
+            .iter()
+            .map(|((provider, model), usage)| (provider, model, *usage))
+    }
 }
 
 #[cfg(test)]
@@ -253,4 +337,45 @@ mod tests {
         let providers = registry.read(cx).providers();
         assert!(providers.is_empty());
     }
+
+    #[gpui::test]
+    fn test_inline_assistant_model_and_token_usage(cx: &mut AppContext) {
+        let provider = LanguageModelRegistry::test(cx);
+        let registry = LanguageModelRegistry::global(cx);
+        let model = provider.provided_models(cx)[0].clone();
+
+        // The inline assistant uses the active model unless another is selected.
+        registry.update(cx, |registry, cx| {
+            assert_eq!(registry.inline_assistant_model().unwrap().id(), model.id());
+            registry.set_active_model(None, cx);
+            assert!(registry.inline_assistant_model().is_none());
+
+            registry.select_inline_assistant_model(Some((&provider.id(), &model.id())), cx);
+            assert_eq!(registry.inline_assistant_model().unwrap().id(), model.id());
+            registry.select_inline_assistant_model(None, cx);
+            assert!(registry.inline_assistant_model().is_none());
+        });
+
+        registry.update(cx, |registry, cx| {
+            let usage = TokenUsage {
+                requests: 1,
+                input_tokens: 10,
+                output_tokens: 5,
+            };
+            registry.record_token_usage(model.as_ref(), usage, cx);
+            registry.record_token_usage(model.as_ref(), usage, cx);
+        });
+        let usage = registry.read(cx).token_usage().collect::<Vec<_>>();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].1, &model.name());
+        assert_eq!(
+            usage[0].2,
+            TokenUsage {
+                requests: 2,
+                input_tokens: 20,
+                output_tokens: 10,
+            }
+        );
+        assert_eq!(usage[0].2.total_tokens(), 30);
+    }
 }
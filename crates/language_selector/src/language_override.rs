@@ -0,0 +1,201 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context as _, Result};
+use db::kvp::KEY_VALUE_STORE;
+use fs::Fs as _;
+use futures::FutureExt as _;
+use gpui::{AppContext, Model, Task, WeakModel};
+use language::{
+    language_override_path,
+    language_settings::{AllLanguageSettings, FileTypeMatcher},
+    Buffer, LanguageRegistry, PLAIN_TEXT,
+};
+use paths::local_settings_file_relative_path;
+use project::Project;
+use settings::{initial_local_settings_content, SettingsStore};
+use util::ResultExt as _;
+
+const LANGUAGE_OVERRIDES_KEY: &str = "language_overrides";
+
+/// Restores the languages chosen for files in the previous sessions.
+pub fn restore_language_overrides(language_registry: &LanguageRegistry) {
+    let Some(overrides) = KEY_VALUE_STORE
+        .read_kvp(LANGUAGE_OVERRIDES_KEY)
+        .log_err()
+        .flatten()
+        .and_then(|overrides| {
+            serde_json::from_str::<BTreeMap<PathBuf, String>>(&overrides).log_err()
+        })
+    else {
+        return;
+    };
+    for (path, language_name) in overrides {
+        language_registry.set_language_override(path, Some(language_name.into()));
+    }
+}
+
+fn save_language_overrides(language_registry: &LanguageRegistry, cx: &AppContext) {
+    let overrides = language_registry
+        .language_overrides()
+        .into_iter()
+        .map(|(path, language_name)| (path, language_name.to_string()))
+        .collect::<BTreeMap<_, _>>();
+    cx.background_executor()
+        .spawn(async move {
+            KEY_VALUE_STORE
+                .write_kvp(
+                    LANGUAGE_OVERRIDES_KEY.to_string(),
+                    serde_json::to_string(&overrides)?,
+                )
+                .await
+        })
+        .detach_and_log_err(cx);
+}
+
+/// Makes the buffer use the language, and remembers it for the buffer's file so
+/// that it's used whenever the file is opened. Without a language, the language
+/// of the buffer is detected again from its file name and content.
+pub(crate) fn set_buffer_language(
+    buffer: Model<Buffer>,
+    project: WeakModel<Project>,
+    language_registry: Arc<LanguageRegistry>,
+    language_name: Option<Arc<str>>,
+    cx: &mut AppContext,
+) -> Task<Result<()>> {
+    let file = buffer.read(cx).file().cloned();
+    if let Some(file) = file.as_ref() {
+        let path = language_override_path(file.as_ref(), cx);
+        if language_registry.language_override(&path) != language_name {
+            language_registry.set_language_override(path, language_name.clone());
+            save_language_overrides(&language_registry, cx);
+        }
+    }
+
+    let language = match (&language_name, &file) {
+        (Some(language_name), _) => language_registry.language_for_name(language_name).boxed(),
+        (None, Some(file)) => language_registry
+            .language_for_file(file, Some(buffer.read(cx).as_rope()), cx)
+            .boxed(),
+        (None, None) => return Task::ready(Ok(())),
+    };
+    cx.spawn(|mut cx| async move {
+        let language = match language.await {
+            Ok(language) => language,
+            // Files whose language isn't detected are plain text.
+            Err(_) if language_name.is_none() => PLAIN_TEXT.clone(),
+            Err(error) => return Err(error),
+        };
+        project.update(&mut cx, |project, cx| {
+            project.set_language_for_buffer(&buffer, language, cx);
+        })
+    })
+}
+
+/// The files a language can be associated with in the `file_types` setting.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum FileType {
+    /// The files with the extension.
+    Extension(String),
+    /// The files with the name, for the files without an extension.
+    Name(String),
+}
+
+impl FileType {
+    pub fn for_path(path: &Path) -> Option<Self> {
+        if let Some(extension) = path.extension() {
+            return Some(Self::Extension(extension.to_str()?.to_string()));
+        }
+        Some(Self::Name(path.file_name()?.to_str()?.to_string()))
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Self::Extension(extension) => format!("all .{extension} files"),
+            Self::Name(name) => format!("all files named {name}"),
+        }
+    }
+
+    fn matcher(&self) -> FileTypeMatcher {
+        match self {
+            Self::Extension(glob) | Self::Name(glob) => FileTypeMatcher::Glob(glob.clone()),
+        }
+    }
+}
+
+/// Makes the files of the buffer's file type use the language in the project, by
+/// associating them in the `file_types` setting of the project's
+/// `.zed/settings.json` file.
+pub(crate) fn set_file_type_language(
+    buffer: Model<Buffer>,
+    project: Model<Project>,
+    language_registry: Arc<LanguageRegistry>,
+    language_name: Arc<str>,
+    cx: &mut AppContext,
+) -> Task<Result<()>> {
+    let Some(file) = buffer.read(cx).file().cloned() else {
+        return Task::ready(Err(anyhow!("The buffer has no file")));
+    };
+    let Some(file_type) = FileType::for_path(Path::new(file.file_name(cx))) else {
+        return Task::ready(Err(anyhow!("The file has no name")));
+    };
+    let Some(worktree) = project::File::from_dyn(Some(&file))
+        .map(|file| file.worktree.read(cx))
+        .filter(|worktree| worktree.is_local())
+    else {
+        return Task::ready(Err(anyhow!(
+            "Languages can only be set for the files of local projects"
+        )));
+    };
+    if !worktree.root_entry().map_or(false, |entry| entry.is_dir()) {
+        return Task::ready(Err(anyhow!("The file isn't in a project folder")));
+    }
+    let settings_path = worktree
+        .abs_path()
+        .join(local_settings_file_relative_path());
+    let fs = project.read(cx).fs().clone();
+
+    // The project's setting tells the language of the file from now on.
+    let path = language_override_path(file.as_ref(), cx);
+    if language_registry.language_override(&path).is_some() {
+        language_registry.set_language_override(path, None);
+        save_language_overrides(&language_registry, cx);
+    }
+
+    let language = language_registry.language_for_name(&language_name);
+    cx.spawn(|mut cx| async move {
+        let old_text = if fs.is_file(&settings_path).await {
+            fs.load(&settings_path).await?
+        } else {
+            initial_local_settings_content().to_string()
+        };
+        let new_text = cx.read_global(|store: &SettingsStore, _| {
+            store.new_text_for_update::<AllLanguageSettings>(old_text, |settings| {
+                // A file type is associated with one language at most.
+                let matcher = file_type.matcher();
+                for matchers in settings.file_types.values_mut() {
+                    matchers.retain(|other| *other != matcher);
+                }
+                settings
+                    .file_types
+                    .entry(language_name)
+                    .or_default()
+                    .push(matcher);
+            })
+        })?;
+        if let Some(settings_dir) = settings_path.parent() {
+            fs.create_dir(settings_dir).await?;
+        }
+        fs.atomic_write(settings_path.clone(), new_text)
+            .await
+            .with_context(|| format!("Failed to write settings to {settings_path:?}"))?;
+
+        let language = language.await?;
+        project.update(&mut cx, |project, cx| {
+            project.set_language_for_buffer(&buffer, language, cx);
+        })
+    })
+}
@@ -1,24 +1,36 @@
 mod active_buffer_language;
+mod language_override;
 
 pub use active_buffer_language::ActiveBufferLanguage;
-use anyhow::anyhow;
 use editor::Editor;
 use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
 use gpui::{
-    actions, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model,
-    ParentElement, Render, Styled, View, ViewContext, VisualContext, WeakView,
+    actions, impl_actions, AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle,
+    FocusableView, Model, ParentElement, Render, Styled, View, ViewContext, VisualContext,
+    WeakView,
 };
-use language::{Buffer, LanguageRegistry};
+use language::{Buffer, LanguageDetectionSource, LanguageRegistry};
+use language_override::FileType;
 use picker::{Picker, PickerDelegate};
 use project::Project;
-use std::sync::Arc;
-use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use serde::Deserialize;
+use std::{path::Path, sync::Arc};
+use ui::{prelude::*, ButtonLike, HighlightedLabel, KeyBinding, ListItem, ListItemSpacing};
 use util::ResultExt;
-use workspace::{ModalView, Workspace};
+use workspace::{notifications::DetachAndPromptErr, ModalView, NewScratchBuffer, Workspace};
 
-actions!(language_selector, [Toggle]);
+actions!(language_selector, [Toggle, ResetLanguage]);
 
-pub fn init(cx: &mut AppContext) {
+/// Sets the language of the active buffer, which is remembered for its file.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct SetLanguage {
+    pub language: String,
+}
+
+impl_actions!(language_selector, [SetLanguage]);
+
+pub fn init(language_registry: Arc<LanguageRegistry>, cx: &mut AppContext) {
+    language_override::restore_language_overrides(&language_registry);
     cx.observe_new_views(LanguageSelector::register).detach();
 }
 
@@ -31,6 +43,48 @@ impl LanguageSelector {
         workspace.register_action(move |workspace, _: &Toggle, cx| {
             Self::toggle(workspace, cx);
         });
+        workspace.register_action(Self::new_scratch_buffer);
+        workspace.register_action(|workspace, action: &SetLanguage, cx| {
+            Self::set_active_buffer_language(workspace, Some(action.language.as_str().into()), cx);
+        });
+        workspace.register_action(|workspace, _: &ResetLanguage, cx| {
+            Self::set_active_buffer_language(workspace, None, cx);
+        });
+    }
+
+    fn set_active_buffer_language(
+        workspace: &mut Workspace,
+        language_name: Option<Arc<str>>,
+        cx: &mut ViewContext<Workspace>,
+    ) -> Option<()> {
+        let (_, buffer, _) = workspace
+            .active_item(cx)?
+            .act_as::<Editor>(cx)?
+            .read(cx)
+            .active_excerpt(cx)?;
+        let project = workspace.project().downgrade();
+        let registry = workspace.app_state().languages.clone();
+        language_override::set_buffer_language(buffer, project, registry, language_name, cx)
+            .detach_and_log_err(cx);
+        Some(())
+    }
+
+    /// Creates a scratch buffer, then picks its language.
+    fn new_scratch_buffer(
+        workspace: &mut Workspace,
+        _: &NewScratchBuffer,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let new_scratch_buffer = editor::new_scratch_buffer(workspace, cx);
+        cx.spawn(|workspace, mut cx| async move {
+            new_scratch_buffer.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                Self::toggle(workspace, cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to create scratch buffer", cx, |error, _| {
+            Some(error.to_string())
+        });
     }
 
     fn toggle(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> Option<()> {
@@ -59,6 +113,7 @@ impl LanguageSelector {
             buffer,
             project,
             language_registry,
+            cx,
         );
 
         let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
@@ -89,6 +144,10 @@ pub struct LanguageSelectorDelegate {
     candidates: Vec<StringMatchCandidate>,
     matches: Vec<StringMatch>,
     selected_index: usize,
+    /// Why the buffer uses its current language.
+    detection_source: Option<LanguageDetectionSource>,
+    /// The files of the project the picked language can be used for.
+    file_type: Option<FileType>,
 }
 
 impl LanguageSelectorDelegate {
@@ -97,7 +156,28 @@ impl LanguageSelectorDelegate {
         buffer: Model<Buffer>,
         project: Model<Project>,
         language_registry: Arc<LanguageRegistry>,
+        cx: &AppContext,
     ) -> Self {
+        let (detection_source, file_type) = {
+            let buffer = buffer.read(cx);
+            let detection_source =
+                buffer
+                    .file()
+                    .zip(buffer.language())
+                    .and_then(|(file, language)| {
+                        language_registry.language_detection_source(
+                            file,
+                            Some(buffer.as_rope()),
+                            language,
+                            cx,
+                        )
+                    });
+            let file_type = buffer
+                .file()
+                .filter(|_| project.read(cx).is_local())
+                .and_then(|file| FileType::for_path(Path::new(file.file_name(cx))));
+            (detection_source, file_type)
+        };
         let candidates = language_registry
             .language_names()
             .into_iter()
@@ -113,6 +193,8 @@ impl LanguageSelectorDelegate {
             candidates,
             matches: vec![],
             selected_index: 0,
+            detection_source,
+            file_type,
         }
     }
 }
@@ -128,25 +210,32 @@ impl PickerDelegate for LanguageSelectorDelegate {
         self.matches.len()
     }
 
-    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
         if let Some(mat) = self.matches.get(self.selected_index) {
-            let language_name = &self.candidates[mat.candidate_id].string;
-            let language = self.language_registry.language_for_name(language_name);
-            let project = self.project.downgrade();
-            let buffer = self.buffer.downgrade();
-            cx.spawn(|_, mut cx| async move {
-                let language = language.await?;
-                let project = project
-                    .upgrade()
-                    .ok_or_else(|| anyhow!("project was dropped"))?;
-                let buffer = buffer
-                    .upgrade()
-                    .ok_or_else(|| anyhow!("buffer was dropped"))?;
-                project.update(&mut cx, |project, cx| {
-                    project.set_language_for_buffer(&buffer, language, cx);
-                })
-            })
-            .detach_and_log_err(cx);
+            let language_name = self.candidates[mat.candidate_id].string.as_str();
+            if secondary && self.file_type.is_some() {
+                language_override::set_file_type_language(
+                    self.buffer.clone(),
+                    self.project.clone(),
+                    self.language_registry.clone(),
+                    language_name.into(),
+                    cx,
+                )
+                .detach_and_prompt_err(
+                    "Failed to set the language",
+                    cx,
+                    |error, _| Some(error.to_string()),
+                );
+            } else {
+                language_override::set_buffer_language(
+                    self.buffer.clone(),
+                    self.project.downgrade(),
+                    self.language_registry.clone(),
+                    Some(language_name.into()),
+                    cx,
+                )
+                .detach_and_log_err(cx);
+            }
         }
         self.dismissed(cx);
     }
@@ -218,7 +307,10 @@ impl PickerDelegate for LanguageSelectorDelegate {
         let buffer_language_name = self.buffer.read(cx).language().map(|l| l.name());
         let mut label = mat.string.clone();
         if buffer_language_name.as_deref() == Some(mat.string.as_str()) {
-            label.push_str(" (current)");
+            match self.detection_source {
+                Some(source) => label.push_str(&format!(" (current, {})", source.label())),
+                None => label.push_str(" (current)"),
+            }
         }
 
         Some(
@@ -229,4 +321,155 @@ impl PickerDelegate for LanguageSelectorDelegate {
                 .child(HighlightedLabel::new(label, mat.positions.clone())),
         )
     }
+
+    fn render_footer(&self, cx: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
+        let file_type = self.file_type.as_ref()?;
+        Some(
+            h_flex()
+                .border_t_1()
+                .py_2()
+                .pr_2()
+                .border_color(cx.theme().colors().border)
+                .justify_end()
+                .child(
+                    ButtonLike::new("use-for-file-type")
+                        .when_some(
+                            KeyBinding::for_action(&menu::SecondaryConfirm, cx),
+                            |button, key| button.child(key),
+                        )
+                        .child(
+                            Label::new(format!("Use for {} in this project", file_type.label()))
+                                .color(Color::Muted),
+                        )
+                        .on_click(cx.listener(|picker, _, cx| picker.delegate.confirm(true, cx))),
+                )
+                .into_any(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::Fs as _;
+    use gpui::{TestAppContext, VisualTestContext};
+    use language::{Language, LanguageConfig, LanguageMatcher};
+    use project::FakeFs;
+    use serde_json::json;
+    use std::path::PathBuf;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_use_language_for_file_type(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ "a.foo": "a", "b.foo": "b" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(Arc::new(Language::new(
+            LanguageConfig {
+                name: "Foo Script".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["fs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        )));
+        cx.update(|cx| init(language_registry.clone(), cx));
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        let editor = open_editor("a.foo", &workspace, cx).await;
+        assert_ne!(
+            buffer_language_name(&editor, cx).as_deref(),
+            Some("Foo Script")
+        );
+
+        let picker = open_language_selector(&workspace, cx);
+        cx.simulate_input("Foo Script");
+        picker.update(cx, |picker, cx| {
+            let footer = picker.delegate.file_type.as_ref().map(FileType::label);
+            assert_eq!(footer.as_deref(), Some("all .foo files"));
+            picker.delegate.set_selected_index(0, cx);
+        });
+        cx.dispatch_action(menu::SecondaryConfirm);
+        cx.run_until_parked();
+
+        let settings = fs.load(Path::new("/dir/.zed/settings.json")).await.unwrap();
+        let settings: serde_json::Value = settings::parse_json_with_comments(&settings).unwrap();
+        assert_eq!(settings["file_types"], json!({ "Foo Script": ["foo"] }));
+        assert_eq!(
+            buffer_language_name(&editor, cx).as_deref(),
+            Some("Foo Script")
+        );
+
+        // Other files of the type use the language too, which is shown to be
+        // set by the user.
+        let editor = open_editor("b.foo", &workspace, cx).await;
+        assert_eq!(
+            buffer_language_name(&editor, cx).as_deref(),
+            Some("Foo Script")
+        );
+        let picker = open_language_selector(&workspace, cx);
+        picker.update(cx, |picker, _| {
+            assert_eq!(
+                picker.delegate.detection_source,
+                Some(LanguageDetectionSource::FileTypes)
+            );
+        });
+    }
+
+    async fn open_editor(
+        path: &str,
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> View<Editor> {
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir").join(path), true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        cx.run_until_parked();
+        editor
+    }
+
+    fn open_language_selector(
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> View<Picker<LanguageSelectorDelegate>> {
+        cx.dispatch_action(Toggle);
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<LanguageSelector>(cx)
+                .unwrap()
+                .read(cx)
+                .picker
+                .clone()
+        })
+    }
+
+    fn buffer_language_name(editor: &View<Editor>, cx: &mut VisualTestContext) -> Option<String> {
+        editor.update(cx, |editor, cx| {
+            let buffer = editor.buffer().read(cx).as_singleton()?;
+            let language = buffer.read(cx).language()?;
+            Some(language.name().to_string())
+        })
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            state
+        })
+    }
 }
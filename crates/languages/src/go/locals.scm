@@ -0,0 +1,25 @@
+; Scopes
+[
+  (function_declaration)
+  (method_declaration)
+  (func_literal)
+  (block)
+] @local.scope
+
+; Definitions
+(parameter_declaration
+  name: (identifier) @local.definition)
+
+(short_var_declaration
+  left: (expression_list
+    (identifier) @local.definition))
+
+(var_spec
+  name: (identifier) @local.definition)
+
+(range_clause
+  left: (expression_list
+    (identifier) @local.definition))
+
+; References
+(identifier) @local.reference
@@ -50,6 +50,7 @@
       (#match? @_name "^Benchmark.+"))
   ) @_
   (#set! tag go-benchmark)
+  (#set! kind bench)
 )
 
 ; go run
@@ -59,4 +60,5 @@
       (#eq? @run "main"))
   ) @_
   (#set! tag go-main)
+  (#set! kind main)
 )
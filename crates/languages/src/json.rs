@@ -10,9 +10,9 @@ use http_client::github::{latest_github_release, GitHubLspBinaryVersion};
 use language::{LanguageRegistry, LanguageServerName, LspAdapter, LspAdapterDelegate};
 use lsp::LanguageServerBinary;
 use node_runtime::NodeRuntime;
-use project::ContextProviderWithTasks;
+use project::{project_settings::ProjectSettings, ContextProviderWithTasks};
 use serde_json::{json, Value};
-use settings::{KeymapFile, SettingsJsonSchemaParams, SettingsStore};
+use settings::{KeymapFile, Settings, SettingsJsonSchemaParams, SettingsLocation, SettingsStore};
 use smol::{
     fs::{self},
     io::BufReader,
@@ -204,13 +204,36 @@ impl LspAdapter for JsonLspAdapter {
 
     async fn workspace_configuration(
         self: Arc<Self>,
-        _: &Arc<dyn LspAdapterDelegate>,
+        delegate: &Arc<dyn LspAdapterDelegate>,
         cx: &mut AsyncAppContext,
     ) -> Result<Value> {
+        let worktree_root = delegate.worktree_root_path();
+        let location = SettingsLocation {
+            worktree_id: delegate.worktree_id() as usize,
+            path: worktree_root,
+        };
+
         cx.update(|cx| {
-            self.workspace_config
+            let mut config = self
+                .workspace_config
                 .get_or_init(|| Self::get_workspace_config(self.languages.language_names(), cx))
-                .clone()
+                .clone();
+            let user_schemas = ProjectSettings::get(Some(location), cx)
+                .json_schemas
+                .iter()
+                .map(|schema| {
+                    json!({
+                        "fileMatch": schema.file_match,
+                        "url": schema.resolved_url(worktree_root),
+                    })
+                });
+            if let Some(schemas) = config
+                .pointer_mut("/json/schemas")
+                .and_then(Value::as_array_mut)
+            {
+                schemas.extend(user_schemas);
+            }
+            config
         })
     }
 
@@ -18,4 +18,5 @@
     )
     (#set! tag package-script)
     (#set! tag composer-script)
+    (#set! kind script)
 )
@@ -11,13 +11,21 @@ use std::{str, sync::Arc};
 use typescript::typescript_task_context;
 use util::{asset_str, ResultExt};
 
-use crate::{bash::bash_task_context, go::GoContextProvider, rust::RustContextProvider};
+use crate::{
+    bash::bash_task_context,
+    go::GoContextProvider,
+    make::{MakeContextProvider, MAKE_LANGUAGE_NAMES},
+    markup::{OrgContextProvider, RstContextProvider},
+    rust::RustContextProvider,
+};
 
 mod bash;
 mod c;
 mod css;
 mod go;
 mod json;
+mod make;
+mod markup;
 mod python;
 mod rust;
 mod tailwind;
@@ -116,6 +124,7 @@ pub fn init(
         vec![Arc::new(go::GoLspAdapter)],
         GoContextProvider
     );
+    language!("http");
 
     language!(
         "json",
@@ -136,8 +145,10 @@ pub fn init(
         ))],
         json_task_context()
     );
+    language!("log");
     language!("markdown");
     language!("markdown-inline");
+    language!("org", Vec::new(), OrgContextProvider);
     language!(
         "python",
         vec![Arc::new(python::PythonLspAdapter::new(
@@ -182,12 +193,19 @@ pub fn init(
         ]
     );
     language!("regex");
+    language!("rst", Vec::new(), RstContextProvider);
     language!(
         "yaml",
         vec![Arc::new(yaml::YamlLspAdapter::new(node_runtime.clone()))]
     );
     language!("proto");
 
+    // Makefiles have no language built in, but their targets are runnable with the
+    // language of any extension providing one.
+    for language_name in MAKE_LANGUAGE_NAMES {
+        languages.register_context_provider(language_name.into(), Arc::new(MakeContextProvider));
+    }
+
     // Register globally available language servers.
     //
     // This will allow users to add support for a built-in language server (e.g., Tailwind)
@@ -0,0 +1,178 @@
+use std::{borrow::Cow, ops::Range, sync::Arc};
+
+use collections::HashMap;
+use gpui::AppContext;
+use language::{BufferSnapshot, ContextProvider, Point, ProvidedRunnable, RunnableKind};
+use task::{RunnableTag, TaskTemplate, TaskTemplates, VariableName};
+
+/// Marks the targets of makefile rules as runnables, whichever language is registered
+/// for makefiles.
+pub(super) struct MakeContextProvider;
+
+/// The names extensions give to the language of makefiles.
+pub(super) const MAKE_LANGUAGE_NAMES: [&str; 2] = ["Makefile", "Make"];
+
+const MAKE_TARGET_NAME: &str = "MAKE_TARGET";
+const MAKE_TARGET_TASK_VARIABLE: VariableName =
+    VariableName::Custom(Cow::Borrowed(MAKE_TARGET_NAME));
+
+impl ContextProvider for MakeContextProvider {
+    fn runnables(&self, buffer: &BufferSnapshot, range: Range<usize>) -> Vec<ProvidedRunnable> {
+        // Rules are found line by line, so only the lines of the range are scanned.
+        let start_row = buffer.offset_to_point(range.start).row;
+        let end_row = buffer.offset_to_point(range.end).row;
+        let lines_start = buffer.point_to_offset(Point::new(start_row, 0));
+        let lines_end = buffer.point_to_offset(Point::new(end_row, buffer.line_len(end_row)));
+        let text = buffer
+            .text_for_range(lines_start..lines_end)
+            .collect::<String>();
+        make_targets(&text)
+            .into_iter()
+            .map(|(target_range, target)| {
+                (
+                    lines_start + target_range.start..lines_start + target_range.end,
+                    target,
+                )
+            })
+            .filter(|(target_range, _)| range.contains(&target_range.start))
+            .map(|(target_range, target)| ProvidedRunnable {
+                run_range: target_range.clone(),
+                full_range: target_range,
+                tags: vec![RunnableTag("make-target".into())].into(),
+                kind: RunnableKind::Target,
+                extra_captures: HashMap::from_iter([(MAKE_TARGET_NAME.to_owned(), target)]),
+            })
+            .collect()
+    }
+
+    fn associated_tasks(
+        &self,
+        _: Option<Arc<dyn language::File>>,
+        _: &AppContext,
+    ) -> Option<TaskTemplates> {
+        Some(TaskTemplates(vec![
+            TaskTemplate {
+                label: format!("make {}", MAKE_TARGET_TASK_VARIABLE.template_value()),
+                command: "make".into(),
+                args: vec![MAKE_TARGET_TASK_VARIABLE.template_value()],
+                tags: vec!["make-target".to_owned()],
+                cwd: Some(VariableName::Dirname.template_value()),
+                ..TaskTemplate::default()
+            },
+            TaskTemplate {
+                label: "make".into(),
+                command: "make".into(),
+                cwd: Some(VariableName::Dirname.template_value()),
+                ..TaskTemplate::default()
+            },
+        ]))
+    }
+}
+
+/// Finds the explicit targets of the rules in a makefile, skipping special targets
+/// like `.PHONY`, pattern rules and variable assignments.
+fn make_targets(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut targets = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        // Recipe lines start with a tab.
+        if line.starts_with(['\t', ' ', '#']) {
+            continue;
+        }
+        let Some(colon) = line.find(':') else {
+            continue;
+        };
+        if line[colon + 1..].starts_with([':', '=']) || line[..colon].contains('=') {
+            continue;
+        }
+        // Only the first of several targets of a rule gets a run indicator.
+        let Some(target) = line[..colon].split_whitespace().next() else {
+            continue;
+        };
+        if target.starts_with('.') || target.contains(['%', '$']) {
+            continue;
+        }
+        let target_start = line_start + line.find(target).unwrap_or(0);
+        targets.push((target_start..target_start + target.len(), target.to_owned()));
+    }
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{Context, TestAppContext};
+    use language::{Buffer, LanguageConfig, LanguageMatcher, LanguageRegistry};
+    use settings::SettingsStore;
+
+    #[test]
+    fn test_make_targets() {
+        let text = "\
+CC := gcc
+.PHONY: all clean
+
+all: build test
+\t$(CC) -o main main.c
+
+build test: deps
+%.o: %.c
+\t$(CC) -c $<
+# clean: in a comment
+clean:
+\trm -f *.o
+";
+        assert_eq!(
+            make_targets(text)
+                .into_iter()
+                .map(|(range, target)| {
+                    assert_eq!(text[range].to_string(), target);
+                    target
+                })
+                .collect::<Vec<_>>(),
+            vec!["all", "build", "clean"]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_make_runnables(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let test_settings = SettingsStore::test(cx);
+            cx.set_global(test_settings);
+            language::init(cx);
+        });
+        let languages = Arc::new(LanguageRegistry::test(cx.executor()));
+        languages.register_context_provider("Make".into(), Arc::new(MakeContextProvider));
+        // Registered without a context provider, like the language of an extension.
+        languages.register_test_language(LanguageConfig {
+            name: "Make".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["mk".into()],
+                ..LanguageMatcher::default()
+            },
+            ..LanguageConfig::default()
+        });
+        let language = languages.language_for_name("Make").await.unwrap();
+        let provider = language.context_provider().unwrap();
+
+        let text = "all: build\n\techo done\nbuild:\n\tcc main.c\n";
+        let buffer = cx.new_model(|cx| Buffer::local(text, cx));
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+        let targets = |range: Range<usize>| {
+            provider
+                .runnables(&snapshot, range)
+                .into_iter()
+                .map(|runnable| {
+                    (
+                        runnable.run_range,
+                        runnable.extra_captures[MAKE_TARGET_NAME].clone(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(targets(0..5), vec![(0..3, "all".to_owned())]);
+        assert_eq!(targets(20..text.len()), vec![(22..27, "build".to_owned())]);
+        assert_eq!(targets(24..text.len()), Vec::new());
+    }
+}
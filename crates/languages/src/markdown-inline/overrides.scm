@@ -0,0 +1,4 @@
+[
+  (code_span)
+  (link_destination)
+] @code
@@ -0,0 +1,4 @@
+[
+  (fenced_code_block)
+  (indented_code_block)
+] @code
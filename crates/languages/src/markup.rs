@@ -0,0 +1,304 @@
+use std::ops::Range;
+
+use language::{BufferSnapshot, ContextProvider, OutlineItem, Point, ToOffset as _};
+
+/// Outlines reStructuredText documents, which have no grammar, by the section titles
+/// found in their lines.
+pub(super) struct RstContextProvider;
+
+impl ContextProvider for RstContextProvider {
+    fn outline_items(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<Vec<OutlineItem<Point>>> {
+        Some(section_outline_items(
+            buffer,
+            rst_headings(&buffer.text()),
+            range,
+        ))
+    }
+}
+
+/// Outlines Org documents, which have no grammar, by the headlines found in their lines.
+pub(super) struct OrgContextProvider;
+
+impl ContextProvider for OrgContextProvider {
+    fn outline_items(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<Vec<OutlineItem<Point>>> {
+        Some(section_outline_items(
+            buffer,
+            org_headings(&buffer.text()),
+            range,
+        ))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Heading {
+    /// The first row of the heading, which is its overline in reStructuredText.
+    start_row: u32,
+    /// The row of the title.
+    title_row: u32,
+    /// The last row of the heading, which is its underline in reStructuredText.
+    end_row: u32,
+    /// The depth of the heading, starting at 1 for the top-level sections.
+    level: usize,
+    title: String,
+}
+
+/// Turns the headings of a document into outline items spanning their sections, which
+/// end before the next heading at the same or a higher level, so that they contain the
+/// items of their subsections.
+fn section_outline_items(
+    buffer: &BufferSnapshot,
+    headings: Vec<Heading>,
+    range: Range<usize>,
+) -> Vec<OutlineItem<Point>> {
+    let max_row = buffer.max_point().row;
+    let mut items = Vec::new();
+    for (ix, heading) in headings.iter().enumerate() {
+        let mut end_row = headings[ix + 1..]
+            .iter()
+            .find(|next| next.level <= heading.level)
+            .map_or(max_row, |next| next.start_row - 1);
+        while end_row > heading.end_row && buffer.is_line_blank(end_row) {
+            end_row -= 1;
+        }
+
+        let start = Point::new(heading.title_row, 0);
+        let end = Point::new(end_row, buffer.line_len(end_row));
+        let item_range = start.to_offset(buffer)..end.to_offset(buffer);
+        if item_range.end < range.start || item_range.start > range.end {
+            continue;
+        }
+
+        let heading_end = Point::new(heading.end_row, buffer.line_len(heading.end_row));
+        items.push(OutlineItem {
+            depth: 0,
+            range: start..end,
+            text: heading.title.clone(),
+            highlight_ranges: Vec::new(),
+            name_ranges: vec![0..heading.title.len()],
+            body_range: (end > heading_end).then(|| heading_end..end),
+            annotation_range: None,
+        });
+    }
+    items
+}
+
+/// Finds the section titles of a reStructuredText document: lines underlined, and
+/// optionally overlined, by a repeated punctuation character. Their levels follow the
+/// order in which each adornment style first appears.
+fn rst_headings(text: &str) -> Vec<Heading> {
+    let lines = text.lines().collect::<Vec<_>>();
+    let mut styles = Vec::new();
+    let mut level_of = |style: (char, bool)| {
+        1 + styles
+            .iter()
+            .position(|other| *other == style)
+            .unwrap_or_else(|| {
+                styles.push(style);
+                styles.len() - 1
+            })
+    };
+
+    let mut headings = Vec::new();
+    let mut ix = 0;
+    while ix < lines.len() {
+        let line = lines[ix];
+        let next_line = lines.get(ix + 1).copied();
+        if let Some(adornment) = rst_adornment(line) {
+            let title = next_line.filter(|title| !title.trim().is_empty());
+            let underline = lines.get(ix + 2).copied();
+            if let (Some(title), Some(underline)) = (title, underline) {
+                if rst_adornment(underline) == Some(adornment) {
+                    headings.push(Heading {
+                        start_row: ix as u32,
+                        title_row: ix as u32 + 1,
+                        end_row: ix as u32 + 2,
+                        level: level_of((adornment, true)),
+                        title: title.trim().to_owned(),
+                    });
+                    ix += 3;
+                    continue;
+                }
+            }
+        }
+        if let Some(adornment) = next_line.and_then(rst_adornment) {
+            let title = line.trim_end();
+            let underline = lines[ix + 1].trim_end();
+            if !title.is_empty()
+                && !title.starts_with(char::is_whitespace)
+                && rst_adornment(line).is_none()
+                && underline.chars().count() >= title.chars().count()
+            {
+                headings.push(Heading {
+                    start_row: ix as u32,
+                    title_row: ix as u32,
+                    end_row: ix as u32 + 1,
+                    level: level_of((adornment, false)),
+                    title: title.to_owned(),
+                });
+                ix += 2;
+                continue;
+            }
+        }
+        ix += 1;
+    }
+    headings
+}
+
+fn rst_adornment(line: &str) -> Option<char> {
+    let text = line.trim_end();
+    let c = text.chars().next()?;
+    (c.is_ascii_punctuation() && text.len() >= 2 && text.chars().all(|other| other == c))
+        .then_some(c)
+}
+
+/// Finds the headlines of an Org document: lines starting with stars, as many as their
+/// level, followed by a space. The tags at their end, like `:work:urgent:`, aren't part
+/// of their titles.
+fn org_headings(text: &str) -> Vec<Heading> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(row, line)| {
+            let level = line.bytes().take_while(|&byte| byte == b'*').count();
+            let title = line[level..]
+                .strip_prefix(' ')
+                .filter(|_| level > 0)?
+                .trim();
+            let title = match title.rsplit_once(char::is_whitespace) {
+                Some((title, tags))
+                    if tags.len() > 2 && tags.starts_with(':') && tags.ends_with(':') =>
+                {
+                    title.trim_end()
+                }
+                _ => title,
+            };
+            Some(Heading {
+                start_row: row as u32,
+                title_row: row as u32,
+                end_row: row as u32,
+                level,
+                title: title.to_owned(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{Context, TestAppContext};
+    use language::{AnchorRangeExt as _, Buffer, Language, LanguageConfig};
+    use settings::SettingsStore;
+    use std::sync::Arc;
+
+    fn titles(headings: Vec<Heading>) -> Vec<(usize, String)> {
+        headings
+            .into_iter()
+            .map(|heading| (heading.level, heading.title))
+            .collect()
+    }
+
+    #[test]
+    fn test_rst_headings() {
+        let text = "\
+=====
+Title
+=====
+
+Intro
+-----
+
+Some text, followed by a transition.
+
+----
+
+Usage
+-----
+
+Details
+~~~~~~~
+";
+        assert_eq!(
+            titles(rst_headings(text)),
+            vec![
+                (1, "Title".to_owned()),
+                (2, "Intro".to_owned()),
+                (2, "Usage".to_owned()),
+                (3, "Details".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_org_headings() {
+        let text = "\
+#+TITLE: Notes
+* Projects :work:
+Some text.
+** TODO Write the docs
+*bold* isn't a headline
+*** Details
+";
+        assert_eq!(
+            titles(org_headings(text)),
+            vec![
+                (1, "Projects".to_owned()),
+                (2, "TODO Write the docs".to_owned()),
+                (3, "Details".to_owned()),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn test_org_outline(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let test_settings = SettingsStore::test(cx);
+            cx.set_global(test_settings);
+            language::init(cx);
+        });
+        let language = Language::new(
+            LanguageConfig {
+                name: "Org".into(),
+                ..LanguageConfig::default()
+            },
+            None,
+        )
+        .with_context_provider(Some(Arc::new(OrgContextProvider)));
+        let text = "\
+* One
+Text.
+** Two
+
+* Three
+";
+        let buffer =
+            cx.new_model(|cx| Buffer::local(text, cx).with_language(Arc::new(language), cx));
+        buffer.read_with(cx, |buffer, _| {
+            let snapshot = buffer.snapshot();
+            let outline = snapshot.outline(None).unwrap();
+            assert_eq!(
+                outline
+                    .items
+                    .iter()
+                    .map(|item| (
+                        item.depth,
+                        item.text.as_str(),
+                        item.range.to_point(&snapshot)
+                    ))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (0, "One", Point::new(0, 0)..Point::new(2, 6)),
+                    (1, "Two", Point::new(2, 0)..Point::new(2, 6)),
+                    (0, "Three", Point::new(4, 0)..Point::new(4, 7)),
+                ]
+            );
+        });
+    }
+}
@@ -287,6 +287,25 @@ impl ContextProvider for PythonContextProvider {
                 ],
                 ..TaskTemplate::default()
             },
+            TaskTemplate {
+                label: format!(
+                    "pytest '{}::{}'",
+                    VariableName::RelativeFile.template_value(),
+                    VariableName::Symbol.template_value(),
+                ),
+                command: "python3".to_owned(),
+                args: vec![
+                    "-m".to_owned(),
+                    "pytest".to_owned(),
+                    format!(
+                        "{}::{}",
+                        VariableName::RelativeFile.template_value(),
+                        VariableName::Symbol.template_value(),
+                    ),
+                ],
+                tags: vec!["python-pytest".to_owned()],
+                ..TaskTemplate::default()
+            },
         ]))
     }
 }
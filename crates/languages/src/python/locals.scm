@@ -0,0 +1,30 @@
+; Scopes
+[
+  (function_definition)
+  (lambda)
+] @local.scope
+
+; Definitions
+(parameters
+  (identifier) @local.definition)
+
+(default_parameter
+  name: (identifier) @local.definition)
+
+(typed_parameter
+  (identifier) @local.definition)
+
+(typed_default_parameter
+  name: (identifier) @local.definition)
+
+(lambda_parameters
+  (identifier) @local.definition)
+
+(assignment
+  left: (identifier) @local.definition)
+
+(for_statement
+  left: (identifier) @local.definition)
+
+; References
+(identifier) @local.reference
@@ -29,3 +29,14 @@
             )
         )
 )
+
+; pytest test functions whose names start with `test`
+(
+    (module
+        (function_definition
+            name: (identifier) @run @_pytest_function_name
+            (#match? @_pytest_function_name "^test.*")
+        ) @python-pytest
+    )
+    (#set! tag python-pytest)
+)
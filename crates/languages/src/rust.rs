@@ -15,11 +15,12 @@ use std::{
     any::Any,
     borrow::Cow,
     env::consts,
+    ops::Range,
     path::{Path, PathBuf},
     sync::Arc,
     sync::LazyLock,
 };
-use task::{TaskTemplate, TaskTemplates, TaskVariables, VariableName};
+use task::{RunnableTag, TaskTemplate, TaskTemplates, TaskVariables, VariableName};
 use util::{fs::remove_matching, maybe, ResultExt};
 
 pub struct RustLspAdapter;
@@ -384,6 +385,11 @@ const RUST_BIN_NAME_TASK_VARIABLE: VariableName =
 const RUST_MAIN_FUNCTION_TASK_VARIABLE: VariableName =
     VariableName::Custom(Cow::Borrowed("_rust_main_function_end"));
 
+/// The name of a documentation test, as filtered by `cargo test --doc`
+const RUST_DOC_TEST_NAME: &str = "RUST_DOC_TEST";
+const RUST_DOC_TEST_TASK_VARIABLE: VariableName =
+    VariableName::Custom(Cow::Borrowed(RUST_DOC_TEST_NAME));
+
 impl ContextProvider for RustContextProvider {
     fn build_context(
         &self,
@@ -427,6 +433,23 @@ impl ContextProvider for RustContextProvider {
         Ok(TaskVariables::default())
     }
 
+    fn runnables(&self, buffer: &BufferSnapshot, range: Range<usize>) -> Vec<ProvidedRunnable> {
+        doc_tests(&buffer.text())
+            .into_iter()
+            .filter(|doc_test| range.contains(&doc_test.fence_range.start))
+            .map(|doc_test| ProvidedRunnable {
+                run_range: doc_test.fence_range,
+                full_range: doc_test.full_range,
+                tags: vec![RunnableTag("rust-doc-test".into())].into(),
+                kind: RunnableKind::DocTest,
+                extra_captures: collections::HashMap::from_iter([(
+                    RUST_DOC_TEST_NAME.to_owned(),
+                    doc_test.name,
+                )]),
+            })
+            .collect()
+    }
+
     fn associated_tasks(
         &self,
         file: Option<Arc<dyn language::File>>,
@@ -501,6 +524,42 @@ impl ContextProvider for RustContextProvider {
                 cwd: Some("$ZED_DIRNAME".to_owned()),
                 ..TaskTemplate::default()
             },
+            TaskTemplate {
+                label: format!(
+                    "cargo bench -p {} {}",
+                    RUST_PACKAGE_TASK_VARIABLE.template_value(),
+                    VariableName::Symbol.template_value(),
+                ),
+                command: "cargo".into(),
+                args: vec![
+                    "bench".into(),
+                    "-p".into(),
+                    RUST_PACKAGE_TASK_VARIABLE.template_value(),
+                    VariableName::Symbol.template_value(),
+                ],
+                tags: vec!["rust-bench".to_owned()],
+                cwd: Some("$ZED_DIRNAME".to_owned()),
+                ..TaskTemplate::default()
+            },
+            TaskTemplate {
+                label: format!(
+                    "cargo test --doc -p {} {}",
+                    RUST_PACKAGE_TASK_VARIABLE.template_value(),
+                    RUST_DOC_TEST_TASK_VARIABLE.template_value(),
+                ),
+                command: "cargo".into(),
+                args: vec![
+                    "test".into(),
+                    "--doc".into(),
+                    "-p".into(),
+                    RUST_PACKAGE_TASK_VARIABLE.template_value(),
+                    "--".into(),
+                    RUST_DOC_TEST_TASK_VARIABLE.template_value(),
+                ],
+                tags: vec!["rust-doc-test".to_owned()],
+                cwd: Some("$ZED_DIRNAME".to_owned()),
+                ..TaskTemplate::default()
+            },
             TaskTemplate {
                 label: format!(
                     "cargo run -p {} --bin {}",
@@ -551,6 +610,84 @@ impl ContextProvider for RustContextProvider {
     }
 }
 
+#[derive(Debug, PartialEq)]
+struct DocTest {
+    /// The opening fence of the code block.
+    fence_range: Range<usize>,
+    /// The documentation comment and the first line of the documented item.
+    full_range: Range<usize>,
+    /// Filters the test in `cargo test --doc`, whose tests are named like
+    /// `src/lib.rs - module::Item::method (line 10)`.
+    name: String,
+}
+
+/// Finds the Rust code blocks in the `///` comments of the text, documenting an item.
+fn doc_tests(text: &str) -> Vec<DocTest> {
+    static ITEM_NAME: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(
+            r#"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:(?:const|async|unsafe|extern\s+"[^"]*")\s+)*(?:fn|struct|enum|union|trait|type|mod|static|const|macro_rules!)\s+(?:r#)?(\w+)"#,
+        )
+        .unwrap()
+    });
+
+    let mut doc_tests = Vec::new();
+    let mut doc_start = None;
+    let mut fences = Vec::new();
+    let mut in_code_block = false;
+    let mut offset = 0;
+    for (row, line) in text.split_inclusive('\n').enumerate() {
+        let line_start = offset;
+        offset += line.len();
+        let trimmed = line.trim_start();
+        if let Some(doc) = trimmed.strip_prefix("///") {
+            doc_start.get_or_insert(line_start);
+            if let Some(info) = doc.trim_start().strip_prefix("```") {
+                if !in_code_block && is_rust_code_block(info.trim()) {
+                    let fence_start = line_start + line.len() - doc.trim_start().len();
+                    fences.push((fence_start..line_start + line.trim_end().len(), row));
+                }
+                in_code_block = !in_code_block;
+            }
+            continue;
+        }
+        let Some(start) = doc_start else {
+            continue;
+        };
+        // Attributes may come between the documentation and the item.
+        if trimmed.starts_with("#[") {
+            continue;
+        }
+        if let Some(item) = ITEM_NAME
+            .captures(line)
+            .and_then(|captures| captures.get(1))
+        {
+            for (fence_range, row) in fences.drain(..) {
+                doc_tests.push(DocTest {
+                    fence_range,
+                    full_range: start..line_start + line.trim_end().len(),
+                    name: format!("{} (line {})", item.as_str(), row + 1),
+                });
+            }
+        }
+        doc_start = None;
+        fences.clear();
+        in_code_block = false;
+    }
+    doc_tests
+}
+
+/// Whether rustdoc runs the code block with the given info string, as a test.
+fn is_rust_code_block(info: &str) -> bool {
+    info.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|attribute| !attribute.is_empty())
+        .all(|attribute| {
+            matches!(
+                attribute,
+                "rust" | "should_panic" | "no_run" | "compile_fail" | "test_harness"
+            ) || attribute.starts_with("edition")
+        })
+}
+
 /// Part of the data structure of Cargo metadata
 #[derive(serde::Deserialize)]
 struct CargoMetadata {
@@ -965,6 +1102,52 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_doc_tests() {
+        let text = r#"
+/// Adds one.
+///
+/// ```
+/// assert_eq!(add_one(1), 2);
+/// ```
+///
+/// ```text
+/// not a test
+/// ```
+///
+/// ```should_panic
+/// add_one(u32::MAX);
+/// ```
+#[inline]
+pub const fn add_one(value: u32) -> u32 {
+    value + 1
+}
+
+/// ```ignore
+/// ignored();
+/// ```
+fn ignored() {}
+
+/// ```
+/// let _ = Point::default();
+/// ```
+// Not an item.
+"#;
+        let doc_tests = doc_tests(text);
+        assert_eq!(
+            doc_tests
+                .iter()
+                .map(|doc_test| (doc_test.name.as_str(), &text[doc_test.fence_range.clone()]))
+                .collect::<Vec<_>>(),
+            vec![
+                ("add_one (line 4)", "```"),
+                ("add_one (line 12)", "```should_panic"),
+            ]
+        );
+        assert!(text[doc_tests[0].full_range.clone()].starts_with("/// Adds one."));
+        assert!(text[doc_tests[0].full_range.clone()].ends_with("-> u32 {"));
+    }
+
     #[test]
     fn test_package_name_from_pkgid() {
         for (input, expected) in [
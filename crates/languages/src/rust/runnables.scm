@@ -26,6 +26,27 @@
     (#set! tag rust-test)
 )
 
+; Rust benchmark
+(
+    (
+        (attribute_item (attribute
+            [((identifier) @_attribute)
+                (scoped_identifier (identifier) @_attribute)
+                ])
+            (#eq? @_attribute "bench")
+        ) @start
+        .
+        (attribute_item) *
+        .
+        (function_item
+            name: (_) @run
+            body: _
+        ) @end
+    )
+    (#set! tag rust-bench)
+    (#set! kind bench)
+)
+
 ; Rust main function
 (
     (
@@ -36,4 +57,5 @@
         (#eq? @run "main")
     )
     (#set! tag rust-main)
+    (#set! kind main)
 )
@@ -7,7 +7,8 @@ use language::{
 };
 use lsp::LanguageServerBinary;
 use node_runtime::NodeRuntime;
-use serde_json::Value;
+use project::project_settings::ProjectSettings;
+use serde_json::{Map, Value};
 use settings::{Settings, SettingsLocation};
 use smol::fs;
 use std::{
@@ -99,20 +100,36 @@ impl LspAdapter for YamlLspAdapter {
         delegate: &Arc<dyn LspAdapterDelegate>,
         cx: &mut AsyncAppContext,
     ) -> Result<Value> {
+        let worktree_root = delegate.worktree_root_path();
         let location = SettingsLocation {
             worktree_id: delegate.worktree_id() as usize,
-            path: delegate.worktree_root_path(),
+            path: worktree_root,
         };
 
-        let tab_size = cx.update(|cx| {
-            AllLanguageSettings::get(Some(location), cx)
+        let (tab_size, schemas) = cx.update(|cx| {
+            let tab_size = AllLanguageSettings::get(Some(location), cx)
                 .language(Some("YAML"))
-                .tab_size
+                .tab_size;
+            // The YAML language server maps the URL of each schema to the
+            // globs of the files it applies to.
+            let schemas = ProjectSettings::get(Some(location), cx)
+                .json_schemas
+                .iter()
+                .map(|schema| {
+                    (
+                        schema.resolved_url(worktree_root),
+                        Value::from(schema.file_match.clone()),
+                    )
+                })
+                .collect::<Map<_, _>>();
+            (tab_size, schemas)
         })?;
 
         Ok(serde_json::json!({
             "yaml": {
-                "keyOrdering": false
+                "keyOrdering": false,
+                "validate": true,
+                "schemas": schemas
             },
             "[yaml]": {
                 "editor.tabSize": tab_size
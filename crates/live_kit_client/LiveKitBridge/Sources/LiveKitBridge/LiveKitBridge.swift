@@ -261,6 +261,98 @@ public func LKCreateScreenShareTrackForDisplay(display: UnsafeMutableRawPointer)
     return Unmanaged.passRetained(track).toOpaque()
 }
 
+@_cdecl("LKCreateScreenShareTrackForWindow")
+public func LKCreateScreenShareTrackForWindow(window: UnsafeMutableRawPointer) -> UnsafeMutableRawPointer {
+    let window = Unmanaged<MacOSWindow>.fromOpaque(window).takeUnretainedValue()
+    let track = LocalVideoTrack.createMacOSScreenShareTrack(source: window, preferredMethod: .legacy)
+    return Unmanaged.passRetained(track).toOpaque()
+}
+
+/// Captures a rectangle of a display into a buffer track, until the track is released.
+class LKRegionCapturer {
+    weak var track: LocalVideoTrack?
+    let displayID: CGDirectDisplayID
+    let region: CGRect
+    var timer: Timer?
+
+    init(track: LocalVideoTrack, displayID: CGDirectDisplayID, region: CGRect) {
+        self.track = track
+        self.displayID = displayID
+        self.region = region
+    }
+
+    func start(fps: Double) {
+        timer = Timer.scheduledTimer(withTimeInterval: 1.0 / fps, repeats: true) { [self] timer in
+            guard let track = self.track else {
+                timer.invalidate()
+                return
+            }
+            guard let capturer = track.capturer as? BufferCapturer,
+                  let image = CGDisplayCreateImage(self.displayID, rect: self.region),
+                  let pixelBuffer = self.pixelBuffer(for: image) else {
+                return
+            }
+            capturer.capture(pixelBuffer)
+        }
+    }
+
+    func pixelBuffer(for image: CGImage) -> CVPixelBuffer? {
+        var pixelBuffer: CVPixelBuffer?
+        let attributes = [kCVPixelBufferIOSurfacePropertiesKey: [:]] as CFDictionary
+        guard CVPixelBufferCreate(kCFAllocatorDefault, image.width, image.height, kCVPixelFormatType_32BGRA, attributes, &pixelBuffer) == kCVReturnSuccess,
+              let pixelBuffer = pixelBuffer else {
+            return nil
+        }
+
+        CVPixelBufferLockBaseAddress(pixelBuffer, [])
+        defer { CVPixelBufferUnlockBaseAddress(pixelBuffer, []) }
+        let context = CGContext(
+            data: CVPixelBufferGetBaseAddress(pixelBuffer),
+            width: image.width,
+            height: image.height,
+            bitsPerComponent: 8,
+            bytesPerRow: CVPixelBufferGetBytesPerRow(pixelBuffer),
+            space: CGColorSpaceCreateDeviceRGB(),
+            bitmapInfo: CGImageAlphaInfo.premultipliedFirst.rawValue | CGBitmapInfo.byteOrder32Little.rawValue
+        )
+        context?.draw(image, in: CGRect(x: 0, y: 0, width: image.width, height: image.height))
+        return pixelBuffer
+    }
+}
+
+@_cdecl("LKCreateScreenShareTrackForRegion")
+public func LKCreateScreenShareTrackForRegion(display: UnsafeMutableRawPointer, x: Double, y: Double, width: Double, height: Double) -> UnsafeMutableRawPointer {
+    let display = Unmanaged<MacOSDisplay>.fromOpaque(display).takeUnretainedValue()
+    let track = LocalVideoTrack.createBufferTrack()
+    let capturer = LKRegionCapturer(track: track, displayID: display.displayID, region: CGRect(x: x, y: y, width: width, height: height))
+    capturer.start(fps: 15)
+    return Unmanaged.passRetained(track).toOpaque()
+}
+
+@_cdecl("LKMacOSDisplayGetWidth")
+public func LKMacOSDisplayGetWidth(display: UnsafeRawPointer) -> Int {
+    let display = Unmanaged<MacOSDisplay>.fromOpaque(display).takeUnretainedValue()
+    return display.width
+}
+
+@_cdecl("LKMacOSDisplayGetHeight")
+public func LKMacOSDisplayGetHeight(display: UnsafeRawPointer) -> Int {
+    let display = Unmanaged<MacOSDisplay>.fromOpaque(display).takeUnretainedValue()
+    return display.height
+}
+
+@_cdecl("LKMacOSWindowGetTitle")
+public func LKMacOSWindowGetTitle(window: UnsafeRawPointer) -> CFString? {
+    let window = Unmanaged<MacOSWindow>.fromOpaque(window).takeUnretainedValue()
+    return window.title as CFString?
+}
+
+@_cdecl("LKMacOSWindowGetApplicationName")
+public func LKMacOSWindowGetApplicationName(window: UnsafeRawPointer) -> CFString? {
+    let window = Unmanaged<MacOSWindow>.fromOpaque(window).takeUnretainedValue()
+    return window.owningApplication?.applicationName as CFString?
+}
+
 @_cdecl("LKVideoRendererCreate")
 public func LKVideoRendererCreate(data: UnsafeRawPointer, onFrame: @escaping @convention(c) (UnsafeRawPointer, CVPixelBuffer) -> Bool, onDrop: @escaping @convention(c) (UnsafeRawPointer) -> Void) -> UnsafeMutableRawPointer {
     Unmanaged.passRetained(LKVideoRenderer(data: data, onFrame: onFrame, onDrop: onDrop)).toOpaque()
@@ -307,6 +399,15 @@ public func LKDisplaySources(data: UnsafeRawPointer, callback: @escaping @conven
     }
 }
 
+@_cdecl("LKWindowSources")
+public func LKWindowSources(data: UnsafeRawPointer, callback: @escaping @convention(c) (UnsafeRawPointer, CFArray?, CFString?) -> Void) {
+    MacOSScreenCapturer.sources(for: .window, includeCurrentApplication: false, preferredMethod: .legacy).then { windowSources in
+        callback(data, windowSources as CFArray, nil)
+    }.catch { error in
+        callback(data, nil, error.localizedDescription as CFString)
+    }
+}
+
 @_cdecl("LKLocalTrackPublicationSetMute")
 public func LKLocalTrackPublicationSetMute(
     publication: UnsafeRawPointer,
@@ -22,6 +22,15 @@ pub enum ConnectionState {
     Connected { url: String, token: String },
 }
 
+/// A rectangle of a display to share, in points from its top-left corner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScreenRegion {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
 #[derive(Clone)]
 pub enum RoomUpdate {
     ActiveSpeakersChanged { speakers: Vec<Sid> },
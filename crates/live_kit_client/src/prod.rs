@@ -1,4 +1,4 @@
-use crate::{ConnectionState, RoomUpdate, Sid};
+use crate::{ConnectionState, RoomUpdate, ScreenRegion, Sid};
 use anyhow::{anyhow, Context, Result};
 use core_foundation::{
     array::{CFArray, CFArrayRef},
@@ -36,6 +36,7 @@ mod swift {
     pointer_type!(LocalTrackPublication);
     pointer_type!(RemoteTrackPublication);
     pointer_type!(MacOSDisplay);
+    pointer_type!(MacOSWindow);
     pointer_type!(RoomDelegate);
 }
 
@@ -145,7 +146,27 @@ extern "C" {
             error: CFStringRef,
         ),
     );
+    fn LKWindowSources(
+        callback_data: *mut c_void,
+        callback: extern "C" fn(
+            callback_data: *mut c_void,
+            sources: CFArrayRef,
+            error: CFStringRef,
+        ),
+    );
     fn LKCreateScreenShareTrackForDisplay(display: swift::MacOSDisplay) -> swift::LocalVideoTrack;
+    fn LKCreateScreenShareTrackForWindow(window: swift::MacOSWindow) -> swift::LocalVideoTrack;
+    fn LKCreateScreenShareTrackForRegion(
+        display: swift::MacOSDisplay,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) -> swift::LocalVideoTrack;
+    fn LKMacOSDisplayGetWidth(display: swift::MacOSDisplay) -> isize;
+    fn LKMacOSDisplayGetHeight(display: swift::MacOSDisplay) -> isize;
+    fn LKMacOSWindowGetTitle(window: swift::MacOSWindow) -> CFStringRef;
+    fn LKMacOSWindowGetApplicationName(window: swift::MacOSWindow) -> CFStringRef;
     fn LKLocalAudioTrackCreateTrack() -> swift::LocalAudioTrack;
 
     fn LKLocalTrackPublicationSetMute(
@@ -250,6 +271,33 @@ impl Room {
         async move { rx.await.unwrap() }
     }
 
+    pub fn window_sources(self: &Arc<Self>) -> impl Future<Output = Result<Vec<MacOSWindow>>> {
+        extern "C" fn callback(tx: *mut c_void, sources: CFArrayRef, error: CFStringRef) {
+            unsafe {
+                let tx = Box::from_raw(tx as *mut oneshot::Sender<Result<Vec<MacOSWindow>>>);
+
+                if sources.is_null() {
+                    let _ = tx.send(Err(anyhow!("{}", CFString::wrap_under_get_rule(error))));
+                } else {
+                    let sources = CFArray::wrap_under_get_rule(sources)
+                        .into_iter()
+                        .map(|source| MacOSWindow::new(swift::MacOSWindow(*source)))
+                        .collect();
+
+                    let _ = tx.send(Ok(sources));
+                }
+            }
+        }
+
+        let (tx, rx) = oneshot::channel();
+
+        unsafe {
+            LKWindowSources(Box::into_raw(Box::new(tx)) as *mut _, callback);
+        }
+
+        async move { rx.await.unwrap() }
+    }
+
     pub fn publish_video_track(
         self: &Arc<Self>,
         track: LocalVideoTrack,
@@ -709,6 +757,22 @@ impl LocalVideoTrack {
     pub fn screen_share_for_display(display: &MacOSDisplay) -> Self {
         Self(unsafe { LKCreateScreenShareTrackForDisplay(display.0) })
     }
+
+    pub fn screen_share_for_window(window: &MacOSWindow) -> Self {
+        Self(unsafe { LKCreateScreenShareTrackForWindow(window.0) })
+    }
+
+    pub fn screen_share_for_region(display: &MacOSDisplay, region: ScreenRegion) -> Self {
+        Self(unsafe {
+            LKCreateScreenShareTrackForRegion(
+                display.0,
+                region.x,
+                region.y,
+                region.width,
+                region.height,
+            )
+        })
+    }
 }
 
 impl Drop for LocalVideoTrack {
@@ -955,6 +1019,22 @@ impl MacOSDisplay {
         }
         Self(ptr)
     }
+
+    /// The width of the display, in points.
+    pub fn width(&self) -> usize {
+        unsafe { LKMacOSDisplayGetWidth(self.0) as usize }
+    }
+
+    /// The height of the display, in points.
+    pub fn height(&self) -> usize {
+        unsafe { LKMacOSDisplayGetHeight(self.0) as usize }
+    }
+}
+
+impl Clone for MacOSDisplay {
+    fn clone(&self) -> Self {
+        Self::new(self.0)
+    }
 }
 
 impl Drop for MacOSDisplay {
@@ -963,6 +1043,45 @@ impl Drop for MacOSDisplay {
     }
 }
 
+pub struct MacOSWindow(swift::MacOSWindow);
+
+impl MacOSWindow {
+    fn new(ptr: swift::MacOSWindow) -> Self {
+        unsafe {
+            CFRetain(ptr.0);
+        }
+        Self(ptr)
+    }
+
+    pub fn title(&self) -> Option<String> {
+        unsafe { optional_string(LKMacOSWindowGetTitle(self.0)) }
+    }
+
+    pub fn application_name(&self) -> Option<String> {
+        unsafe { optional_string(LKMacOSWindowGetApplicationName(self.0)) }
+    }
+}
+
+impl Clone for MacOSWindow {
+    fn clone(&self) -> Self {
+        Self::new(self.0)
+    }
+}
+
+impl Drop for MacOSWindow {
+    fn drop(&mut self) {
+        unsafe { CFRelease(self.0 .0) }
+    }
+}
+
+unsafe fn optional_string(string: CFStringRef) -> Option<String> {
+    if string.is_null() {
+        None
+    } else {
+        Some(CFString::wrap_under_get_rule(string).to_string())
+    }
+}
+
 #[derive(Clone)]
 pub struct Frame(CVImageBuffer);
 
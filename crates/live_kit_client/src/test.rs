@@ -1,4 +1,4 @@
-use crate::{ConnectionState, RoomUpdate, Sid};
+use crate::{ConnectionState, RoomUpdate, ScreenRegion, Sid};
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use collections::{btree_map::Entry as BTreeEntry, hash_map::Entry, BTreeMap, HashMap, HashSet};
@@ -216,6 +216,25 @@ impl TestServer {
         }
     }
 
+    /// Tells the clients of every room which of their participants are speaking.
+    pub fn set_active_speakers(&self, speakers: &[&str]) {
+        let server_rooms = self.rooms.lock();
+        for room in server_rooms.values() {
+            let room_speakers = speakers
+                .iter()
+                .filter(|speaker| room.client_rooms.contains_key(**speaker))
+                .map(|speaker| speaker.to_string())
+                .collect::<Vec<_>>();
+            for client_room in room.client_rooms.values() {
+                let _ = client_room.0.lock().updates_tx.try_broadcast(
+                    RoomUpdate::ActiveSpeakersChanged {
+                        speakers: room_speakers.clone(),
+                    },
+                );
+            }
+        }
+    }
+
     async fn publish_video_track(
         &self,
         token: String,
@@ -518,6 +537,7 @@ struct RoomState {
         watch::Receiver<ConnectionState>,
     ),
     display_sources: Vec<MacOSDisplay>,
+    window_sources: Vec<MacOSWindow>,
     paused_audio_tracks: HashSet<Sid>,
     updates_tx: async_broadcast::Sender<RoomUpdate>,
     updates_rx: async_broadcast::Receiver<RoomUpdate>,
@@ -531,6 +551,7 @@ impl Room {
         Arc::new(Self(Mutex::new(RoomState {
             connection: watch::channel_with(ConnectionState::Disconnected),
             display_sources: Default::default(),
+            window_sources: Default::default(),
             paused_audio_tracks: Default::default(),
             updates_tx,
             updates_rx,
@@ -570,6 +591,20 @@ impl Room {
         }
     }
 
+    pub fn window_sources(self: &Arc<Self>) -> impl Future<Output = Result<Vec<MacOSWindow>>> {
+        let this = self.clone();
+        async move {
+            // todo(linux): Remove this once the cross-platform LiveKit implementation is merged
+            #[cfg(any(test, feature = "test-support"))]
+            {
+                let server = this.test_server();
+                server.executor.simulate_random_delay().await;
+            }
+
+            Ok(this.0.lock().window_sources.clone())
+        }
+    }
+
     pub fn publish_video_track(
         self: &Arc<Self>,
         track: LocalVideoTrack,
@@ -659,6 +694,10 @@ impl Room {
         self.0.lock().display_sources = sources;
     }
 
+    pub fn set_window_sources(&self, sources: Vec<MacOSWindow>) {
+        self.0.lock().window_sources = sources;
+    }
+
     fn test_server(&self) -> Arc<TestServer> {
         match self.0.lock().connection.1.borrow().clone() {
             ConnectionState::Disconnected => panic!("must be connected to call this method"),
@@ -759,6 +798,17 @@ impl LocalVideoTrack {
             frames_rx: display.frames.1.clone(),
         }
     }
+
+    pub fn screen_share_for_window(window: &MacOSWindow) -> Self {
+        Self {
+            frames_rx: window.frames.1.clone(),
+        }
+    }
+
+    /// Frames of the fake display aren't cropped to the region.
+    pub fn screen_share_for_region(display: &MacOSDisplay, _region: ScreenRegion) -> Self {
+        Self::screen_share_for_display(display)
+    }
 }
 
 #[derive(Clone)]
@@ -849,6 +899,46 @@ impl MacOSDisplay {
         }
     }
 
+    pub fn width(&self) -> usize {
+        1920
+    }
+
+    pub fn height(&self) -> usize {
+        1080
+    }
+
+    pub fn send_frame(&self, frame: Frame) {
+        self.frames.0.try_broadcast(frame).unwrap();
+    }
+}
+
+#[derive(Clone)]
+pub struct MacOSWindow {
+    title: Option<String>,
+    application_name: Option<String>,
+    frames: (
+        async_broadcast::Sender<Frame>,
+        async_broadcast::Receiver<Frame>,
+    ),
+}
+
+impl MacOSWindow {
+    pub fn new(title: &str, application_name: &str) -> Self {
+        Self {
+            title: Some(title.to_string()),
+            application_name: Some(application_name.to_string()),
+            frames: async_broadcast::broadcast(128),
+        }
+    }
+
+    pub fn title(&self) -> Option<String> {
+        self.title.clone()
+    }
+
+    pub fn application_name(&self) -> Option<String> {
+        self.application_name.clone()
+    }
+
     pub fn send_frame(&self, frame: Frame) {
         self.frames.0.try_broadcast(frame).unwrap();
     }
@@ -0,0 +1,189 @@
+//! Snapshots of files taken when they are saved, as a safety net independent of
+//! version control, and a timeline to compare and restore them.
+mod local_history_settings;
+mod store;
+mod timeline;
+
+use anyhow::Result;
+use fs::Fs;
+use gpui::{actions, AppContext, Context as _, Global, Model, ModelContext, Task, ViewContext};
+use language::Buffer;
+use project::{buffer_store::BufferStoreEvent, Project};
+use settings::Settings as _;
+use std::{path::PathBuf, sync::Arc, time::SystemTime};
+use store::{Limits, Store};
+use util::ResultExt as _;
+use workspace::Workspace;
+
+pub use local_history_settings::{LocalHistorySettings, LocalHistorySettingsContent};
+pub use store::Snapshot;
+pub use timeline::Timeline;
+
+actions!(local_history, [OpenTimeline]);
+
+pub fn init(fs: Arc<dyn Fs>, cx: &mut AppContext) {
+    LocalHistorySettings::register(cx);
+
+    let local_history = cx.new_model(|_| LocalHistory::new(fs, paths::local_history_dir().clone()));
+    cx.set_global(GlobalLocalHistory(local_history.clone()));
+
+    cx.observe_new_views(
+        move |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
+            local_history.update(cx, |local_history, cx| {
+                local_history.track_project(workspace.project(), cx)
+            });
+            Timeline::register(workspace, cx);
+        },
+    )
+    .detach();
+}
+
+struct GlobalLocalHistory(Model<LocalHistory>);
+
+impl Global for GlobalLocalHistory {}
+
+/// Keeps snapshots of the local files of projects when they are saved.
+pub struct LocalHistory {
+    store: Arc<Store>,
+}
+
+impl LocalHistory {
+    fn new(fs: Arc<dyn Fs>, root: PathBuf) -> Self {
+        Self {
+            store: Arc::new(Store::new(fs, root)),
+        }
+    }
+
+    pub fn global(cx: &AppContext) -> Option<Model<Self>> {
+        cx.try_global::<GlobalLocalHistory>()
+            .map(|local_history| local_history.0.clone())
+    }
+
+    fn track_project(&mut self, project: &Model<Project>, cx: &mut ModelContext<Self>) {
+        for buffer in project.read(cx).opened_buffers(cx) {
+            self.track_buffer(&buffer, cx);
+        }
+        let buffer_store = project.read(cx).buffer_store();
+        cx.subscribe(&buffer_store, |this, _, event, cx| {
+            if let BufferStoreEvent::BufferAdded(buffer) = event {
+                this.track_buffer(buffer, cx);
+            }
+        })
+        .detach();
+    }
+
+    fn track_buffer(&mut self, buffer: &Model<Buffer>, cx: &mut ModelContext<Self>) {
+        cx.subscribe(buffer, |this, buffer, event, cx| {
+            if let language::Event::Saved = event {
+                this.record(&buffer, cx);
+            }
+        })
+        .detach();
+    }
+
+    /// Records a snapshot of the saved buffer.
+    fn record(&mut self, buffer: &Model<Buffer>, cx: &mut ModelContext<Self>) {
+        let settings = *LocalHistorySettings::get_global(cx);
+        if !settings.enabled {
+            return;
+        }
+        let buffer = buffer.read(cx);
+        // The history is stored in plain text, so the contents of private files,
+        // like `.env` files and keys, aren't copied to it.
+        let Some(file) = buffer
+            .file()
+            .filter(|file| !file.is_private())
+            .and_then(|file| file.as_local())
+        else {
+            return;
+        };
+        if buffer.len() as u64 > settings.max_file_size_kb * 1024 {
+            return;
+        }
+
+        let abs_path = file.abs_path(cx);
+        let text = buffer.as_rope().clone();
+        let limits = Limits {
+            max_snapshots_per_file: settings.max_snapshots_per_file,
+            max_size: settings.max_size_mb * 1024 * 1024,
+        };
+        let store = self.store.clone();
+        cx.background_executor()
+            .spawn(async move {
+                store
+                    .record(&abs_path, &text.to_string(), SystemTime::now(), limits)
+                    .await
+                    .log_err();
+            })
+            .detach();
+    }
+
+    /// Returns the snapshots of the file, from the newest to the oldest.
+    pub fn snapshots(&self, abs_path: PathBuf, cx: &AppContext) -> Task<Result<Vec<Snapshot>>> {
+        let store = self.store.clone();
+        cx.background_executor()
+            .spawn(async move { store.snapshots(&abs_path).await })
+    }
+
+    /// Returns the content of the file in the snapshot.
+    pub fn load(&self, snapshot: Snapshot, cx: &AppContext) -> Task<Result<String>> {
+        let store = self.store.clone();
+        cx.background_executor()
+            .spawn(async move { store.load(&snapshot).await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::{SemanticVersion, TestAppContext};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+
+    #[gpui::test]
+    async fn test_private_files_are_not_recorded(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            release_channel::init(SemanticVersion::default(), cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            LocalHistorySettings::register(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/project", json!({ "a.txt": "one", ".env": "SECRET=one" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/project".as_ref()], cx).await;
+        let local_history =
+            cx.new_model(|_| LocalHistory::new(fs.clone(), PathBuf::from("/history")));
+        local_history.update(cx, |local_history, cx| {
+            local_history.track_project(&project, cx)
+        });
+
+        for (path, text) in [("a.txt", "two"), (".env", "SECRET=two")] {
+            let buffer = project
+                .update(cx, |project, cx| {
+                    project.open_local_buffer(Path::new("/project").join(path), cx)
+                })
+                .await
+                .unwrap();
+            buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
+            project
+                .update(cx, |project, cx| project.save_buffer(buffer, cx))
+                .await
+                .unwrap();
+        }
+        cx.run_until_parked();
+
+        let snapshots = |path: &str| {
+            local_history.read_with(cx, |local_history, cx| {
+                local_history.snapshots(Path::new("/project").join(path), cx)
+            })
+        };
+        assert_eq!(snapshots("a.txt").await.unwrap().len(), 1);
+        assert!(snapshots(".env").await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,47 @@
+use anyhow;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct LocalHistorySettings {
+    pub enabled: bool,
+    pub max_snapshots_per_file: usize,
+    pub max_size_mb: u64,
+    pub max_file_size_kb: u64,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct LocalHistorySettingsContent {
+    /// Whether to keep snapshots of files when they are saved.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// The maximum number of snapshots kept for each file, past which the
+    /// oldest snapshots are removed.
+    ///
+    /// Default: 50
+    pub max_snapshots_per_file: Option<usize>,
+    /// The maximum total size of the local history, in megabytes, past which
+    /// the oldest snapshots are removed.
+    ///
+    /// Default: 256
+    pub max_size_mb: Option<u64>,
+    /// The maximum size of the files that are snapshotted, in kilobytes.
+    ///
+    /// Default: 1024
+    pub max_file_size_kb: Option<u64>,
+}
+
+impl Settings for LocalHistorySettings {
+    const KEY: Option<&'static str> = Some("local_history");
+
+    type FileContent = LocalHistorySettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
@@ -0,0 +1,293 @@
+use anyhow::{Context as _, Result};
+use collections::HashSet;
+use fs::{Fs, RemoveOptions};
+use futures::lock::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use util::ResultExt as _;
+
+const INDEX_FILE_NAME: &str = "index.json";
+const BLOBS_DIR_NAME: &str = "blobs";
+
+/// A saved version of a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// The SHA-256 hash of the file's content, under which the content is stored.
+    pub hash: String,
+    /// When the file was saved, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The length of the file's content, in bytes.
+    pub len: u64,
+}
+
+impl Snapshot {
+    pub fn time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.timestamp)
+    }
+}
+
+/// The snapshots of each file, from the oldest to the newest.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Index {
+    files: BTreeMap<PathBuf, Vec<Snapshot>>,
+}
+
+/// The limits on the size of the store, past which the oldest snapshots are
+/// removed.
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub max_snapshots_per_file: usize,
+    /// The maximum total size of the stored contents, in bytes.
+    pub max_size: u64,
+}
+
+/// A content-addressed store of the snapshots of saved files.
+///
+/// The contents are stored once per distinct content, in files named after
+/// their hashes, and an index lists the snapshots of each file.
+pub struct Store {
+    fs: Arc<dyn Fs>,
+    root: PathBuf,
+    /// The index, loaded on first use. The lock serializes the changes to the store.
+    index: Mutex<Option<Index>>,
+}
+
+impl Store {
+    pub fn new(fs: Arc<dyn Fs>, root: PathBuf) -> Self {
+        Self {
+            fs,
+            root,
+            index: Mutex::new(None),
+        }
+    }
+
+    /// Records a snapshot of the file's content, unless it's the same as the
+    /// latest snapshot of the file. Returns whether a snapshot was recorded.
+    pub async fn record(
+        &self,
+        path: &Path,
+        text: &str,
+        time: SystemTime,
+        limits: Limits,
+    ) -> Result<bool> {
+        let hash = hex::encode(Sha256::digest(text.as_bytes()));
+        let mut index = self.index.lock().await;
+        let index = self.loaded_index(&mut index).await;
+        let snapshots = index.files.entry(path.to_path_buf()).or_default();
+        if snapshots.last().map_or(false, |latest| latest.hash == hash) {
+            return Ok(false);
+        }
+
+        let blob_path = self.blob_path(&hash);
+        if !self.fs.is_file(&blob_path).await {
+            self.fs.create_dir(&self.root.join(BLOBS_DIR_NAME)).await?;
+            self.fs
+                .atomic_write(blob_path, text.to_string())
+                .await
+                .context("writing snapshot")?;
+        }
+        snapshots.push(Snapshot {
+            hash,
+            timestamp: time
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            len: text.len() as u64,
+        });
+
+        let removed_hashes = prune(index, path, limits);
+        self.save_index(index).await?;
+        for hash in removed_hashes {
+            self.fs
+                .remove_file(
+                    &self.blob_path(&hash),
+                    RemoveOptions {
+                        recursive: false,
+                        ignore_if_not_exists: true,
+                    },
+                )
+                .await?;
+        }
+        Ok(true)
+    }
+
+    /// Returns the snapshots of the file, from the newest to the oldest.
+    pub async fn snapshots(&self, path: &Path) -> Result<Vec<Snapshot>> {
+        let mut index = self.index.lock().await;
+        let index = self.loaded_index(&mut index).await;
+        Ok(index
+            .files
+            .get(path)
+            .map(|snapshots| snapshots.iter().rev().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    /// Returns the content of the file in the snapshot.
+    pub async fn load(&self, snapshot: &Snapshot) -> Result<String> {
+        self.fs
+            .load(&self.blob_path(&snapshot.hash))
+            .await
+            .context("loading snapshot")
+    }
+
+    async fn loaded_index<'a>(&self, index: &'a mut Option<Index>) -> &'a mut Index {
+        if index.is_none() {
+            let path = self.root.join(INDEX_FILE_NAME);
+            let loaded = match self.fs.load(&path).await {
+                Ok(json) => serde_json::from_str(&json)
+                    .with_context(|| format!("parsing {path:?}"))
+                    .log_err()
+                    .unwrap_or_default(),
+                Err(_) => Index::default(),
+            };
+            *index = Some(loaded);
+        }
+        index.get_or_insert_with(Index::default)
+    }
+
+    async fn save_index(&self, index: &Index) -> Result<()> {
+        self.fs.create_dir(&self.root).await?;
+        self.fs
+            .atomic_write(
+                self.root.join(INDEX_FILE_NAME),
+                serde_json::to_string(index)?,
+            )
+            .await
+            .context("writing local history index")
+    }
+
+    fn blob_path(&self, hash: &str) -> PathBuf {
+        self.root.join(BLOBS_DIR_NAME).join(hash)
+    }
+}
+
+/// Removes the oldest snapshots past the limits, keeping the latest snapshot of
+/// `path`, and returns the hashes of the contents that aren't used anymore.
+fn prune(index: &mut Index, path: &Path, limits: Limits) -> Vec<String> {
+    let mut removed = Vec::new();
+    for snapshots in index.files.values_mut() {
+        let excess = snapshots
+            .len()
+            .saturating_sub(limits.max_snapshots_per_file.max(1));
+        removed.extend(snapshots.drain(..excess));
+    }
+
+    let mut size = used_size(index);
+    while size > limits.max_size {
+        let oldest = index
+            .files
+            .iter()
+            .filter_map(|(file_path, snapshots)| {
+                let oldest = snapshots.first()?;
+                let is_kept = file_path == path && snapshots.len() == 1;
+                (!is_kept).then_some((oldest.timestamp, file_path.clone()))
+            })
+            .min();
+        let Some((_, oldest_path)) = oldest else {
+            break;
+        };
+        if let Some(snapshots) = index.files.get_mut(&oldest_path) {
+            removed.push(snapshots.remove(0));
+        }
+        size = used_size(index);
+    }
+    index.files.retain(|_, snapshots| !snapshots.is_empty());
+
+    let used_hashes = index
+        .files
+        .values()
+        .flatten()
+        .map(|snapshot| snapshot.hash.as_str())
+        .collect::<HashSet<_>>();
+    let mut removed_hashes = removed
+        .into_iter()
+        .map(|snapshot| snapshot.hash)
+        .filter(|hash| !used_hashes.contains(hash.as_str()))
+        .collect::<Vec<_>>();
+    removed_hashes.sort();
+    removed_hashes.dedup();
+    removed_hashes
+}
+
+/// Returns the total size of the stored contents, counting each distinct content once.
+fn used_size(index: &Index) -> u64 {
+    let mut hashes = HashSet::default();
+    index
+        .files
+        .values()
+        .flatten()
+        .filter(|snapshot| hashes.insert(snapshot.hash.as_str()))
+        .map(|snapshot| snapshot.len)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_store(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let root = PathBuf::from("/history");
+        let store = Store::new(fs.clone(), root.clone());
+        let limits = Limits {
+            max_snapshots_per_file: 3,
+            max_size: 1024,
+        };
+        let a = Path::new("/project/a.txt");
+        let b = Path::new("/project/b.txt");
+        let at = |seconds| UNIX_EPOCH + Duration::from_secs(seconds);
+
+        assert!(store.record(a, "one", at(1), limits).await.unwrap());
+        assert!(!store.record(a, "one", at(2), limits).await.unwrap());
+        assert!(store.record(a, "two", at(3), limits).await.unwrap());
+        assert!(store.record(b, "one", at(4), limits).await.unwrap());
+        let snapshots = store.snapshots(a).await.unwrap();
+        assert_eq!(
+            snapshots.iter().map(|s| s.timestamp).collect::<Vec<_>>(),
+            vec![3000, 1000]
+        );
+        assert_eq!(store.load(&snapshots[1]).await.unwrap(), "one");
+        // Identical contents are stored once.
+        assert_eq!(fs.files().len(), 3);
+
+        // The oldest snapshots of a file are removed past the limit, along with
+        // the contents that aren't used anymore.
+        store.record(a, "three", at(5), limits).await.unwrap();
+        store.record(a, "four", at(6), limits).await.unwrap();
+        store.record(a, "five", at(7), limits).await.unwrap();
+        assert_eq!(store.snapshots(a).await.unwrap().len(), 3);
+        assert_eq!(store.snapshots(b).await.unwrap().len(), 1);
+        assert!(fs.is_file(&store.blob_path(&snapshots[1].hash)).await);
+        assert!(!fs.is_file(&store.blob_path(&snapshots[0].hash)).await);
+
+        // The index is persisted.
+        let store = Store::new(fs.clone(), root.clone());
+        assert_eq!(store.snapshots(a).await.unwrap().len(), 3);
+
+        // The oldest snapshots of all files are removed past the maximum size,
+        // keeping the latest snapshot of the file that was saved.
+        let limits = Limits {
+            max_snapshots_per_file: 3,
+            max_size: 10,
+        };
+        store.record(b, "0123456789", at(8), limits).await.unwrap();
+        assert!(store.snapshots(a).await.unwrap().is_empty());
+        assert_eq!(
+            store.snapshots(b).await.unwrap(),
+            vec![Snapshot {
+                hash: hex::encode(Sha256::digest(b"0123456789")),
+                timestamp: 8000,
+                len: 10,
+            }]
+        );
+    }
+}
@@ -0,0 +1,313 @@
+use crate::{LocalHistory, OpenTimeline, Snapshot};
+use anyhow::anyhow;
+use chrono::Local;
+use editor::{Editor, MultiBuffer};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, ParentElement,
+    Render, Styled, Task, View, ViewContext, VisualContext, WeakView,
+};
+use language::Buffer;
+use picker::{Picker, PickerDelegate};
+use std::{path::PathBuf, sync::Arc};
+use time::{OffsetDateTime, UtcOffset};
+use time_format::TimestampFormat;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt as _;
+use workspace::{ModalView, Workspace};
+
+/// A modal listing the snapshots of the active file, from which they can be
+/// compared with the file and restored.
+pub struct Timeline {
+    picker: View<Picker<TimelineDelegate>>,
+}
+
+impl Timeline {
+    pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &OpenTimeline, cx| {
+            Self::toggle(workspace, cx);
+        });
+    }
+
+    fn toggle(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> Option<()> {
+        let local_history = LocalHistory::global(cx)?;
+        let (_, buffer, _) = workspace
+            .active_item(cx)?
+            .act_as::<Editor>(cx)?
+            .read(cx)
+            .active_excerpt(cx)?;
+        let abs_path = buffer.read(cx).file()?.as_local()?.abs_path(cx);
+        let snapshots = local_history.read(cx).snapshots(abs_path.clone(), cx);
+        cx.spawn(|workspace, mut cx| async move {
+            let snapshots = snapshots.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let weak_workspace = cx.view().downgrade();
+                workspace.toggle_modal(cx, |cx| {
+                    Timeline::new(
+                        weak_workspace,
+                        local_history,
+                        buffer,
+                        abs_path,
+                        snapshots,
+                        cx,
+                    )
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+        Some(())
+    }
+
+    fn new(
+        workspace: WeakView<Workspace>,
+        local_history: Model<LocalHistory>,
+        buffer: Model<Buffer>,
+        abs_path: PathBuf,
+        snapshots: Vec<Snapshot>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let local = Local::now().offset().local_minus_utc();
+        let timezone = UtcOffset::from_whole_seconds(local).unwrap_or(UtcOffset::UTC);
+        let now = OffsetDateTime::now_utc();
+        let candidates = snapshots
+            .iter()
+            .enumerate()
+            .map(|(ix, snapshot)| {
+                let time = OffsetDateTime::from(snapshot.time());
+                let label = time_format::format_localized_timestamp(
+                    time,
+                    now,
+                    timezone,
+                    TimestampFormat::EnhancedAbsolute,
+                );
+                StringMatchCandidate::new(ix, label)
+            })
+            .collect();
+        let delegate = TimelineDelegate {
+            timeline: cx.view().downgrade(),
+            workspace,
+            local_history,
+            buffer,
+            abs_path,
+            snapshots,
+            candidates,
+            timezone,
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for Timeline {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for Timeline {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for Timeline {}
+impl ModalView for Timeline {}
+
+pub struct TimelineDelegate {
+    timeline: WeakView<Timeline>,
+    workspace: WeakView<Workspace>,
+    local_history: Model<LocalHistory>,
+    buffer: Model<Buffer>,
+    abs_path: PathBuf,
+    /// The snapshots of the file, from the newest to the oldest.
+    snapshots: Vec<Snapshot>,
+    candidates: Vec<StringMatchCandidate>,
+    timezone: UtcOffset,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl TimelineDelegate {
+    /// Opens the snapshot in a read-only editor, showing how it differs from
+    /// the file's current content.
+    fn open_snapshot(&self, snapshot: Snapshot, label: String, cx: &mut ViewContext<Picker<Self>>) {
+        let load = self.local_history.read(cx).load(snapshot, cx);
+        let workspace = self.workspace.clone();
+        let buffer = self.buffer.clone();
+        let file_name = self
+            .abs_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        cx.spawn(|_, mut cx| async move {
+            let text = load.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let project = workspace.project().clone();
+                let current = buffer.read(cx);
+                let language = current.language().cloned();
+                let current_text = current.text();
+                let snapshot_buffer = cx.new_model(|cx| {
+                    let mut snapshot_buffer = Buffer::local(text, cx);
+                    snapshot_buffer.set_language_registry(project.read(cx).languages().clone());
+                    snapshot_buffer.set_language(language, cx);
+                    snapshot_buffer.set_diff_base(Some(current_text), cx);
+                    snapshot_buffer
+                });
+                let multibuffer = cx.new_model(|cx| {
+                    MultiBuffer::singleton(snapshot_buffer, cx)
+                        .with_title(format!("{file_name} ({label})"))
+                });
+                let editor = cx.new_view(|cx| {
+                    let mut editor = Editor::for_multibuffer(multibuffer, Some(project), true, cx);
+                    editor.set_read_only(true);
+                    editor
+                });
+                workspace.add_item_to_active_pane(Box::new(editor), None, true, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Replaces the content of the file's buffer with the snapshot's, as an edit
+    /// that can be undone.
+    fn restore_snapshot(&self, snapshot: Snapshot, cx: &mut ViewContext<Picker<Self>>) {
+        let load = self.local_history.read(cx).load(snapshot, cx);
+        let buffer = self.buffer.downgrade();
+        cx.spawn(|_, mut cx| async move {
+            let text = load.await?;
+            let buffer = buffer
+                .upgrade()
+                .ok_or_else(|| anyhow!("buffer was dropped"))?;
+            let diff = buffer
+                .update(&mut cx, |buffer, cx| buffer.diff(text, cx))?
+                .await;
+            buffer.update(&mut cx, |buffer, cx| {
+                buffer.finalize_last_transaction();
+                buffer.apply_diff(diff, cx);
+                buffer.finalize_last_transaction();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+impl PickerDelegate for TimelineDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Search saved versions...".into()
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        if self.snapshots.is_empty() {
+            "No saved versions of this file".into()
+        } else {
+            "No matches".into()
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(mat) = self.matches.get(self.selected_index) {
+            let snapshot = self.snapshots[mat.candidate_id].clone();
+            if secondary {
+                self.restore_snapshot(snapshot, cx);
+            } else {
+                self.open_snapshot(snapshot, mat.string.clone(), cx);
+            }
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.timeline
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                let mut matches = match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await;
+                // Keep the snapshots ordered from the newest to the oldest.
+                matches.sort_unstable_by_key(|mat| mat.candidate_id);
+                matches
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let snapshot = &self.snapshots[mat.candidate_id];
+        let relative_time = time_format::format_localized_timestamp(
+            OffsetDateTime::from(snapshot.time()),
+            OffsetDateTime::now_utc(),
+            self.timezone,
+            TimestampFormat::Relative,
+        );
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                ))
+                .end_slot(
+                    Label::new(relative_time)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+        )
+    }
+}
@@ -0,0 +1,396 @@
+//! Recognizes the timestamps and levels of the lines of common log formats.
+use std::{cmp::Ordering, fmt, ops::Range, sync::LazyLock};
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use regex::{Captures, Regex};
+use serde::Deserialize;
+
+/// How many of the first lines of a buffer are looked at to decide whether
+/// it's a log.
+const DETECTION_LINE_COUNT: usize = 20;
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// `2024-05-01T12:00:00.123Z`, `2024-05-01 12:00:00,123 +02:00`, and the like.
+static ISO_TIMESTAMP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"\b(\d{4})-(\d{2})-(\d{2})[T ](\d{2}):(\d{2}):(\d{2})",
+        r"(?:[.,](\d{1,9}))?(?:Z|[+-]\d{2}:?\d{2})?",
+    ))
+    .unwrap()
+});
+
+/// `[01/May/2024:12:00:00 +0000]`, as written by web servers.
+static COMMON_LOG_TIMESTAMP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[(\d{2})/([A-Z][a-z]{2})/(\d{4}):(\d{2}):(\d{2}):(\d{2})(?: [+-]\d{4})?\]")
+        .unwrap()
+});
+
+/// `May  1 12:00:00`, as written by syslog, which leaves out the year.
+static SYSLOG_TIMESTAMP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b([A-Z][a-z]{2}) +(\d{1,2}) (\d{2}):(\d{2}):(\d{2})\b").unwrap()
+});
+
+/// `12:00:00.123` at the start of a line, for logs that leave out the date.
+static TIME_TIMESTAMP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\[?(\d{2}):(\d{2}):(\d{2})(?:[.,](\d{1,9}))?\b").unwrap());
+
+/// Levels written in capitals anywhere in a line, or in any case when they're
+/// bracketed or given as `level=`, so that words in messages aren't mistaken
+/// for levels.
+static LEVEL: LazyLock<Regex> = LazyLock::new(|| {
+    const LEVELS: &str = "trace|debug|info|notice|warn|warning|error|err|fatal|critical|crit|panic";
+    Regex::new(&format!(
+        r"\b({upper})\b|(?i:\[({LEVELS})\]|\blevel=({LEVELS})\b)",
+        upper = LEVELS.to_uppercase()
+    ))
+    .unwrap()
+});
+
+/// The severity of a line of a log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 6] = [
+        LogLevel::Trace,
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+        LogLevel::Fatal,
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_lowercase().as_str() {
+            "trace" => Self::Trace,
+            "debug" => Self::Debug,
+            "info" | "notice" => Self::Info,
+            "warn" | "warning" => Self::Warn,
+            "error" | "err" => Self::Error,
+            "fatal" | "critical" | "crit" | "panic" => Self::Fatal,
+            _ => return None,
+        })
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Trace => "TRACE",
+            Self::Debug => "DEBUG",
+            Self::Info => "INFO",
+            Self::Warn => "WARN",
+            Self::Error => "ERROR",
+            Self::Fatal => "FATAL",
+        }
+    }
+}
+
+/// When a line of a log was written. Some formats leave out the date, in
+/// which case only times of day are compared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timestamp {
+    pub date: Option<NaiveDate>,
+    pub time: NaiveTime,
+}
+
+impl Timestamp {
+    /// Compares the timestamps by their times of day if either leaves out the
+    /// date.
+    pub fn compare(&self, other: &Timestamp) -> Ordering {
+        match (self.date, other.date) {
+            (Some(date), Some(other_date)) => {
+                NaiveDateTime::new(date, self.time).cmp(&NaiveDateTime::new(other_date, other.time))
+            }
+            _ => self.time.cmp(&other.time),
+        }
+    }
+
+    /// Parses a timestamp typed by the user, such as `2024-05-01 12:00`,
+    /// `2024-05-01T12:00:30`, or `12:00`.
+    pub fn parse_query(query: &str) -> Option<Self> {
+        let query = query.trim();
+        for format in [
+            "%Y-%m-%d %H:%M:%S%.f",
+            "%Y-%m-%dT%H:%M:%S%.f",
+            "%Y-%m-%d %H:%M",
+        ] {
+            if let Ok(date_time) = NaiveDateTime::parse_from_str(query, format) {
+                return Some(Self {
+                    date: Some(date_time.date()),
+                    time: date_time.time(),
+                });
+            }
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(query, "%Y-%m-%d") {
+            return Some(Self {
+                date: Some(date),
+                time: NaiveTime::MIN,
+            });
+        }
+        ["%H:%M:%S%.f", "%H:%M"].into_iter().find_map(|format| {
+            let time = NaiveTime::parse_from_str(query, format).ok()?;
+            Some(Self { date: None, time })
+        })
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(date) = self.date {
+            write!(f, "{} ", date.format("%Y-%m-%d"))?;
+        }
+        write!(f, "{}", self.time.format("%H:%M:%S"))
+    }
+}
+
+/// What was recognized in a line of a log, with ranges that are offsets into
+/// the line.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LogLine {
+    pub timestamp: Option<(Timestamp, Range<usize>)>,
+    pub level: Option<(LogLevel, Range<usize>)>,
+}
+
+/// Finds the timestamp and level of a line of a log, preferring the first of
+/// each in the line.
+pub fn parse_line(line: &str) -> LogLine {
+    let timestamp = [
+        parse_iso_timestamp(line),
+        parse_common_log_timestamp(line),
+        parse_syslog_timestamp(line),
+        parse_time_timestamp(line),
+    ]
+    .into_iter()
+    .flatten()
+    .min_by_key(|(_, range)| range.start);
+
+    let level = LEVEL
+        .captures_iter(line)
+        .filter(|captures| {
+            // A level inside the timestamp, as in `[01/May/...]`, isn't one.
+            let start = captures.get(0).unwrap().start();
+            timestamp
+                .as_ref()
+                .map_or(true, |(_, range)| !range.contains(&start))
+        })
+        .find_map(|captures| {
+            let name = (1..=3).find_map(|group| captures.get(group))?;
+            Some((LogLevel::from_name(name.as_str())?, name.range()))
+        });
+
+    LogLine { timestamp, level }
+}
+
+/// Returns whether most of the first lines of the text start with a
+/// timestamp, as the lines of logs do.
+pub fn looks_like_log(text: &str) -> bool {
+    let lines = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(DETECTION_LINE_COUNT)
+        .collect::<Vec<_>>();
+    let timestamped_lines = lines
+        .iter()
+        .filter(|line| {
+            parse_line(line)
+                .timestamp
+                .map_or(false, |(_, range)| range.start <= 1)
+        })
+        .count();
+    timestamped_lines > 0 && timestamped_lines * 2 >= lines.len()
+}
+
+fn parse_iso_timestamp(line: &str) -> Option<(Timestamp, Range<usize>)> {
+    let captures = ISO_TIMESTAMP.captures(line)?;
+    let date = NaiveDate::from_ymd_opt(
+        number(&captures, 1)?,
+        number(&captures, 2)?,
+        number(&captures, 3)?,
+    )?;
+    let time = time(&captures, 4, Some(7))?;
+    Some((
+        Timestamp {
+            date: Some(date),
+            time,
+        },
+        captures.get(0)?.range(),
+    ))
+}
+
+fn parse_common_log_timestamp(line: &str) -> Option<(Timestamp, Range<usize>)> {
+    let captures = COMMON_LOG_TIMESTAMP.captures(line)?;
+    let date = NaiveDate::from_ymd_opt(
+        number(&captures, 3)?,
+        month(&captures[2])?,
+        number(&captures, 1)?,
+    )?;
+    let time = time(&captures, 4, None)?;
+    Some((
+        Timestamp {
+            date: Some(date),
+            time,
+        },
+        captures.get(0)?.range(),
+    ))
+}
+
+fn parse_syslog_timestamp(line: &str) -> Option<(Timestamp, Range<usize>)> {
+    let captures = SYSLOG_TIMESTAMP.captures(line)?;
+    month(&captures[1])?;
+    let time = time(&captures, 3, None)?;
+    Some((Timestamp { date: None, time }, captures.get(0)?.range()))
+}
+
+fn parse_time_timestamp(line: &str) -> Option<(Timestamp, Range<usize>)> {
+    let captures = TIME_TIMESTAMP.captures(line)?;
+    let time = time(&captures, 1, Some(4))?;
+    Some((Timestamp { date: None, time }, captures.get(0)?.range()))
+}
+
+/// Parses the hours, minutes, and seconds in the three groups starting at
+/// `first_group`, and the fraction of a second in `fraction_group`.
+fn time(
+    captures: &Captures,
+    first_group: usize,
+    fraction_group: Option<usize>,
+) -> Option<NaiveTime> {
+    let nanoseconds = match fraction_group.and_then(|group| captures.get(group)) {
+        Some(fraction) => format!("{:0<9}", fraction.as_str()).parse().ok()?,
+        None => 0,
+    };
+    NaiveTime::from_hms_nano_opt(
+        number(captures, first_group)?,
+        number(captures, first_group + 1)?,
+        number(captures, first_group + 2)?,
+        nanoseconds,
+    )
+}
+
+fn number<T: std::str::FromStr>(captures: &Captures, group: usize) -> Option<T> {
+    captures.get(group)?.as_str().parse().ok()
+}
+
+fn month(name: &str) -> Option<u32> {
+    let index = MONTHS.iter().position(|month| *month == name)?;
+    Some(index as u32 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestamp(date: Option<(i32, u32, u32)>, (h, m, s, ms): (u32, u32, u32, u32)) -> Timestamp {
+        Timestamp {
+            date: date.map(|(y, mo, d)| NaiveDate::from_ymd_opt(y, mo, d).unwrap()),
+            time: NaiveTime::from_hms_milli_opt(h, m, s, ms).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let line = "2024-05-01T12:00:00.123Z ERROR disk full";
+        assert_eq!(
+            parse_line(line),
+            LogLine {
+                timestamp: Some((timestamp(Some((2024, 5, 1)), (12, 0, 0, 123)), 0..24)),
+                level: Some((LogLevel::Error, 25..30)),
+            }
+        );
+
+        let line = "2024-05-01 12:00:00,5 [warning] retrying";
+        let parsed = parse_line(line);
+        assert_eq!(
+            parsed.timestamp.unwrap().0,
+            timestamp(Some((2024, 5, 1)), (12, 0, 0, 500))
+        );
+        assert_eq!(parsed.level, Some((LogLevel::Warn, 23..30)));
+
+        let line = r#"127.0.0.1 - - [01/May/2024:12:00:00 +0000] "GET / HTTP/1.1" 200"#;
+        let parsed = parse_line(line);
+        assert_eq!(
+            parsed.timestamp,
+            Some((timestamp(Some((2024, 5, 1)), (12, 0, 0, 0)), 14..42))
+        );
+        assert_eq!(parsed.level, None);
+
+        let line = "May  1 12:00:00 host sshd[42]: level=debug accepted key";
+        let parsed = parse_line(line);
+        assert_eq!(
+            parsed.timestamp,
+            Some((timestamp(None, (12, 0, 0, 0)), 0..15))
+        );
+        assert_eq!(parsed.level, Some((LogLevel::Debug, 37..42)));
+
+        let line = "12:00:01.250 FATAL out of memory";
+        let parsed = parse_line(line);
+        assert_eq!(
+            parsed.timestamp,
+            Some((timestamp(None, (12, 0, 1, 250)), 0..12))
+        );
+        assert_eq!(parsed.level, Some((LogLevel::Fatal, 13..18)));
+
+        // Levels in messages are only recognized when they stand out.
+        assert_eq!(
+            parse_line("for more info, see the error log"),
+            LogLine::default()
+        );
+        assert_eq!(
+            parse_line("    at main (main.js:10) INFO").level,
+            Some((LogLevel::Info, 25..29))
+        );
+    }
+
+    #[test]
+    fn test_looks_like_log() {
+        assert!(looks_like_log(
+            "2024-05-01 12:00:00 INFO start\n\
+             Traceback (most recent call last):\n\
+             2024-05-01 12:00:01 ERROR crashed\n"
+        ));
+        assert!(looks_like_log("[12:00:00] ready\n[12:00:05] done\n"));
+        assert!(!looks_like_log(
+            "fn main() {\n    // 2024-05-01 12:00:00\n}\n"
+        ));
+        assert!(!looks_like_log(""));
+    }
+
+    #[test]
+    fn test_timestamp_queries() {
+        assert_eq!(
+            Timestamp::parse_query("2024-05-01 12:30"),
+            Some(timestamp(Some((2024, 5, 1)), (12, 30, 0, 0)))
+        );
+        assert_eq!(
+            Timestamp::parse_query(" 2024-05-01T12:30:15.5 "),
+            Some(timestamp(Some((2024, 5, 1)), (12, 30, 15, 500)))
+        );
+        assert_eq!(
+            Timestamp::parse_query("2024-05-01"),
+            Some(timestamp(Some((2024, 5, 1)), (0, 0, 0, 0)))
+        );
+        assert_eq!(
+            Timestamp::parse_query("12:30"),
+            Some(timestamp(None, (12, 30, 0, 0)))
+        );
+        assert_eq!(Timestamp::parse_query("noon"), None);
+
+        // Timestamps without dates are compared by their times of day.
+        let morning = timestamp(Some((2024, 5, 2)), (9, 0, 0, 0));
+        let evening = timestamp(Some((2024, 5, 1)), (18, 0, 0, 0));
+        assert_eq!(morning.compare(&evening), Ordering::Greater);
+        assert_eq!(
+            morning.compare(&timestamp(None, (18, 0, 0, 0))),
+            Ordering::Less
+        );
+    }
+}
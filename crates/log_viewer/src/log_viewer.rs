@@ -0,0 +1,521 @@
+//! A mode for viewing logs in the editors of buffers whose language is Log, or
+//! whose text looks like a log. Levels and timestamps are colored, the lines
+//! can be filtered by level or time, and the editor can follow the end of the
+//! file as it grows.
+mod log_format;
+mod timestamp_modal;
+
+use std::{
+    ops::{Range, RangeInclusive},
+    sync::Arc,
+    time::Duration,
+};
+
+use editor::{
+    display_map::DisplayRow, scroll::Autoscroll, Addon, Bias, DisplayPoint, Editor, EditorEvent,
+    EditorMode,
+};
+use filter_lines::LinePredicate;
+use gpui::{
+    actions, impl_actions, point, AppContext, FontWeight, HighlightStyle, KeyContext, Model,
+    Render, Task, View, ViewContext, WeakView,
+};
+use language::{Buffer, BufferSnapshot, Point};
+use serde::Deserialize;
+use theme::ActiveTheme;
+use ui::IntoElement;
+
+pub use log_format::{looks_like_log, parse_line, LogLevel, LogLine, Timestamp};
+use timestamp_modal::{TimestampModal, TimestampModalKind};
+
+/// How long to wait after a log is edited or scrolled before coloring its
+/// visible lines again.
+const HIGHLIGHT_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// How much of the start of a plain text buffer is looked at to decide
+/// whether it's a log.
+const DETECTION_LEN: usize = 4096;
+
+/// The kinds of text colored in logs: each level, followed by timestamps.
+const TIMESTAMP_HIGHLIGHT: usize = LogLevel::ALL.len();
+const HIGHLIGHT_KINDS: usize = TIMESTAMP_HIGHLIGHT + 1;
+
+enum LogHighlight<const KIND: usize> {}
+
+actions!(
+    log_viewer,
+    [ToggleFollowTail, GoToTimestamp, FilterByTimeRange]
+);
+
+/// Shows the lines of the log at the given level or more severe.
+#[derive(PartialEq, Clone, Deserialize)]
+pub struct FilterByLevel {
+    #[serde(default = "default_filter_level")]
+    pub level: LogLevel,
+}
+
+fn default_filter_level() -> LogLevel {
+    LogLevel::Warn
+}
+
+impl_actions!(log_viewer, [FilterByLevel]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(LogView::register).detach();
+}
+
+struct LogViewAddon {
+    view: View<LogView>,
+}
+
+impl Addon for LogViewAddon {
+    fn extend_key_context(&self, key_context: &mut KeyContext, cx: &AppContext) {
+        key_context.add("log_view");
+        if self.view.read(cx).is_following() {
+            key_context.add("following_tail");
+        }
+    }
+
+    fn to_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// The state of the log mode of an editor.
+struct LogView {
+    editor: WeakView<Editor>,
+    /// Whether to keep the end of the log in view as lines are appended.
+    follow_tail: bool,
+    /// Whether the user scrolled away from the end of the log while following
+    /// it, which stops following it until they scroll back.
+    follow_paused: bool,
+    highlight_task: Option<Task<()>>,
+    go_to_task: Option<Task<()>>,
+}
+
+impl Render for LogView {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        gpui::Empty
+    }
+}
+
+impl LogView {
+    fn register(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        if editor.mode() != EditorMode::Full {
+            return;
+        }
+        let multibuffer = editor.buffer().clone();
+        if !multibuffer.read(cx).is_singleton() {
+            return;
+        }
+
+        cx.subscribe(&multibuffer, |editor, _, event, cx| {
+            if let multi_buffer::Event::LanguageChanged(_)
+            | multi_buffer::Event::Reloaded
+            | multi_buffer::Event::Saved = event
+            {
+                Self::update_log_mode(editor, cx);
+            }
+        })
+        .detach();
+        Self::update_log_mode(editor, cx);
+    }
+
+    /// Turns the log mode on or off, as the editor's buffer is or isn't a log.
+    fn update_log_mode(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        let is_log = editor
+            .buffer()
+            .read(cx)
+            .as_singleton()
+            .map_or(false, |buffer| is_log_buffer(buffer.read(cx)));
+        let is_active = editor.addon::<LogViewAddon>().is_some();
+        if is_log && !is_active {
+            Self::activate(editor, cx);
+        } else if !is_log && is_active {
+            Self::deactivate(editor, cx);
+        }
+    }
+
+    fn activate(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        let view = Self::new(cx);
+        editor.register_addon(LogViewAddon { view: view.clone() });
+
+        view.update(cx, |view, cx| {
+            Self::action(editor, cx, |view, _: &ToggleFollowTail, cx| {
+                view.toggle_follow_tail(cx)
+            });
+            Self::action(editor, cx, |view, action: &FilterByLevel, cx| {
+                view.filter_by_level(action.level, cx)
+            });
+            Self::action(editor, cx, |view, _: &FilterByTimeRange, cx| {
+                view.toggle_timestamp_modal(TimestampModalKind::FilterByTimeRange, cx)
+            });
+            Self::action(editor, cx, |view, _: &GoToTimestamp, cx| {
+                view.toggle_timestamp_modal(TimestampModalKind::GoToTimestamp, cx)
+            });
+            view.refresh_highlights(cx);
+        });
+    }
+
+    fn deactivate(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        editor.unregister_addon::<LogViewAddon>();
+        set_highlights(editor, vec![Vec::new(); HIGHLIGHT_KINDS], cx);
+    }
+
+    fn new(cx: &mut ViewContext<Editor>) -> View<Self> {
+        let editor = cx.view().clone();
+        cx.new_view(|cx: &mut ViewContext<Self>| {
+            cx.subscribe(&editor, |view, _, event, cx| {
+                view.handle_editor_event(event, cx)
+            })
+            .detach();
+
+            Self {
+                editor: editor.downgrade(),
+                follow_tail: false,
+                follow_paused: false,
+                highlight_task: None,
+                go_to_task: None,
+            }
+        })
+    }
+
+    /// Registers an action on the editor for as long as it's in log mode.
+    fn action<A: gpui::Action>(
+        editor: &mut Editor,
+        cx: &mut ViewContext<Self>,
+        f: impl Fn(&mut Self, &A, &mut ViewContext<Self>) + 'static,
+    ) {
+        let subscription = editor.register_action(cx.listener(f));
+        cx.on_release(|_, _, _| drop(subscription)).detach();
+    }
+
+    fn is_following(&self) -> bool {
+        self.follow_tail && !self.follow_paused
+    }
+
+    fn handle_editor_event(&mut self, event: &EditorEvent, cx: &mut ViewContext<Self>) {
+        match event {
+            EditorEvent::BufferEdited => {
+                if self.is_following() {
+                    self.scroll_to_end(cx);
+                }
+                self.refresh_highlights(cx);
+            }
+            EditorEvent::ScrollPositionChanged { local, .. } => {
+                // Scrolling up stops following the log, and scrolling back
+                // to its end follows it again.
+                if *local && self.follow_tail {
+                    let paused = self
+                        .editor
+                        .update(cx, |editor, cx| !is_scrolled_to_end(editor, cx))
+                        .unwrap_or(false);
+                    if paused != self.follow_paused {
+                        self.follow_paused = paused;
+                        cx.notify();
+                    }
+                }
+                self.refresh_highlights(cx);
+            }
+            _ => {}
+        }
+    }
+
+    fn toggle_follow_tail(&mut self, cx: &mut ViewContext<Self>) {
+        self.follow_tail = !self.follow_tail;
+        self.follow_paused = false;
+        if self.follow_tail {
+            self.scroll_to_end(cx);
+        }
+        cx.notify();
+    }
+
+    fn scroll_to_end(&self, cx: &mut ViewContext<Self>) {
+        self.editor
+            .update(cx, |editor, cx| {
+                let Some(visible_line_count) = editor.visible_line_count() else {
+                    return;
+                };
+                let last_row = editor.snapshot(cx).max_point().row().0 as f32;
+                let scroll_x = editor.scroll_position(cx).x;
+                let scroll_y = (last_row + 1. - visible_line_count).max(0.);
+                editor.set_scroll_position(point(scroll_x, scroll_y), cx);
+            })
+            .ok();
+    }
+
+    fn buffer(&self, cx: &AppContext) -> Option<Model<Buffer>> {
+        let editor = self.editor.upgrade()?;
+        let buffer = editor.read(cx).buffer().read(cx).as_singleton()?;
+        Some(buffer)
+    }
+
+    fn filter_by_level(&mut self, level: LogLevel, cx: &mut ViewContext<Self>) {
+        let title = format!("Log: {} and above", level.label());
+        let predicate: LinePredicate = Arc::new(move |line| {
+            parse_line(line)
+                .level
+                .map_or(false, |(line_level, _)| line_level >= level)
+        });
+        self.open_filtered_lines(title, predicate, cx);
+    }
+
+    /// Shows the lines of the log with timestamps in the range, where either
+    /// end may be left out.
+    fn filter_by_time_range(
+        &mut self,
+        start: Option<Timestamp>,
+        end: Option<Timestamp>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let title = format!(
+            "Log: {} to {}",
+            start.map_or("start".to_string(), |start| start.to_string()),
+            end.map_or("end".to_string(), |end| end.to_string()),
+        );
+        let predicate: LinePredicate = Arc::new(move |line| {
+            parse_line(line).timestamp.map_or(false, |(timestamp, _)| {
+                start.map_or(true, |start| timestamp.compare(&start).is_ge())
+                    && end.map_or(true, |end| timestamp.compare(&end).is_le())
+            })
+        });
+        self.open_filtered_lines(title, predicate, cx);
+    }
+
+    fn open_filtered_lines(
+        &self,
+        title: String,
+        predicate: LinePredicate,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(buffer) = self.buffer(cx) else {
+            return;
+        };
+        let Some(workspace) = self
+            .editor
+            .upgrade()
+            .and_then(|editor| editor.read(cx).workspace())
+        else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| {
+            filter_lines::open_filtered_lines(workspace, buffer, title, predicate, cx);
+        });
+    }
+
+    /// Moves the cursor to the first line of the log written at or after the
+    /// timestamp.
+    fn go_to_timestamp(&mut self, timestamp: Timestamp, cx: &mut ViewContext<Self>) {
+        let Some(buffer) = self.buffer(cx) else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let editor = self.editor.clone();
+        self.go_to_task = Some(cx.spawn(|_, mut cx| async move {
+            let row = cx
+                .background_executor()
+                .spawn(async move { first_row_at_or_after(&snapshot, &timestamp) })
+                .await;
+            let Some(row) = row else {
+                return;
+            };
+            editor
+                .update(&mut cx, |editor, cx| {
+                    let position = Point::new(row, 0);
+                    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                        s.select_ranges([position..position])
+                    });
+                })
+                .ok();
+        }));
+    }
+
+    fn toggle_timestamp_modal(&mut self, kind: TimestampModalKind, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self
+            .editor
+            .upgrade()
+            .and_then(|editor| editor.read(cx).workspace())
+        else {
+            return;
+        };
+        let log_view = cx.view().clone();
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(cx, move |cx| TimestampModal::new(log_view, kind, cx));
+        });
+    }
+
+    /// Colors the levels and timestamps of the visible lines of the log.
+    fn refresh_highlights(&mut self, cx: &mut ViewContext<Self>) {
+        let editor = self.editor.clone();
+        self.highlight_task = Some(cx.spawn(|_, mut cx| async move {
+            cx.background_executor().timer(HIGHLIGHT_DEBOUNCE).await;
+            let Some(Some((snapshot, rows))) = editor
+                .update(&mut cx, |editor, cx| visible_rows(editor, cx))
+                .ok()
+            else {
+                return;
+            };
+            let highlights = cx
+                .background_executor()
+                .spawn(async move { highlight_ranges(&snapshot, rows) })
+                .await;
+            editor
+                .update(&mut cx, |editor, cx| {
+                    let multibuffer = editor.buffer().read(cx).snapshot(cx);
+                    // Offsets into a singleton multibuffer are offsets into
+                    // its buffer.
+                    let ranges = highlights
+                        .into_iter()
+                        .map(|ranges| {
+                            ranges
+                                .into_iter()
+                                .map(|range| {
+                                    multibuffer.anchor_after(range.start)
+                                        ..multibuffer.anchor_before(range.end)
+                                })
+                                .collect()
+                        })
+                        .collect();
+                    set_highlights(editor, ranges, cx);
+                })
+                .ok();
+        }));
+    }
+}
+
+fn is_log_buffer(buffer: &Buffer) -> bool {
+    match buffer.language() {
+        Some(language) if language.name().as_ref() == "Log" => true,
+        Some(language) if language.name().as_ref() != "Plain Text" => false,
+        _ => {
+            let end = buffer.clip_offset(buffer.len().min(DETECTION_LEN), Bias::Left);
+            looks_like_log(&buffer.text_for_range(0..end).collect::<String>())
+        }
+    }
+}
+
+fn is_scrolled_to_end(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> bool {
+    let Some(visible_line_count) = editor.visible_line_count() else {
+        return true;
+    };
+    let last_row = editor.snapshot(cx).max_point().row().0 as f32;
+    // The last line counts as visible once at least half of it is.
+    editor.scroll_position(cx).y + visible_line_count >= last_row + 0.5
+}
+
+/// Returns the buffer shown by the editor and the rows of it that are visible.
+fn visible_rows(
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+) -> Option<(BufferSnapshot, RangeInclusive<u32>)> {
+    let buffer = editor.buffer().read(cx).as_singleton()?.read(cx).snapshot();
+    let visible_line_count = editor.visible_line_count()?;
+    let top = editor.scroll_position(cx).y as u32;
+    let bottom = top + visible_line_count.ceil() as u32;
+    let snapshot = editor.snapshot(cx);
+    let row = |display_row| {
+        snapshot
+            .clip_point(DisplayPoint::new(DisplayRow(display_row), 0), Bias::Left)
+            .to_point(&snapshot)
+            .row
+    };
+    Some((buffer, row(top)..=row(bottom)))
+}
+
+/// Returns the offsets of the levels and timestamps in the rows of the buffer,
+/// grouped by the kind of highlight they get.
+fn highlight_ranges(
+    snapshot: &BufferSnapshot,
+    rows: RangeInclusive<u32>,
+) -> Vec<Vec<Range<usize>>> {
+    let mut ranges = vec![Vec::new(); HIGHLIGHT_KINDS];
+    for row in rows {
+        let start = Point::new(row, 0);
+        let end = Point::new(row, snapshot.line_len(row));
+        let line = snapshot.text_for_range(start..end).collect::<String>();
+        let line_offset = snapshot.point_to_offset(start);
+        let parsed = parse_line(&line);
+        if let Some((level, range)) = parsed.level {
+            let kind = LogLevel::ALL.iter().position(|l| *l == level).unwrap();
+            ranges[kind].push(line_offset + range.start..line_offset + range.end);
+        }
+        if let Some((_, range)) = parsed.timestamp {
+            ranges[TIMESTAMP_HIGHLIGHT].push(line_offset + range.start..line_offset + range.end);
+        }
+    }
+    ranges
+}
+
+fn first_row_at_or_after(snapshot: &BufferSnapshot, timestamp: &Timestamp) -> Option<u32> {
+    (0..=snapshot.max_point().row).find(|row| {
+        let start = Point::new(*row, 0);
+        let end = Point::new(*row, snapshot.line_len(*row));
+        let line = snapshot.text_for_range(start..end).collect::<String>();
+        parse_line(&line)
+            .timestamp
+            .map_or(false, |(line_timestamp, _)| {
+                line_timestamp.compare(timestamp).is_ge()
+            })
+    })
+}
+
+fn set_highlights(
+    editor: &mut Editor,
+    mut ranges: Vec<Vec<Range<editor::Anchor>>>,
+    cx: &mut ViewContext<Editor>,
+) {
+    set_kind_highlights::<0>(editor, &mut ranges, cx);
+    set_kind_highlights::<1>(editor, &mut ranges, cx);
+    set_kind_highlights::<2>(editor, &mut ranges, cx);
+    set_kind_highlights::<3>(editor, &mut ranges, cx);
+    set_kind_highlights::<4>(editor, &mut ranges, cx);
+    set_kind_highlights::<5>(editor, &mut ranges, cx);
+    set_kind_highlights::<TIMESTAMP_HIGHLIGHT>(editor, &mut ranges, cx);
+}
+
+fn set_kind_highlights<const KIND: usize>(
+    editor: &mut Editor,
+    ranges: &mut [Vec<Range<editor::Anchor>>],
+    cx: &mut ViewContext<Editor>,
+) {
+    let ranges = std::mem::take(&mut ranges[KIND]);
+    editor.clear_highlights::<LogHighlight<KIND>>(cx);
+    if ranges.is_empty() {
+        return;
+    }
+
+    let theme = cx.theme();
+    let status = theme.status();
+    let style = match LogLevel::ALL.get(KIND) {
+        None => HighlightStyle {
+            color: Some(theme.colors().text_muted),
+            ..Default::default()
+        },
+        Some(LogLevel::Trace) => HighlightStyle {
+            color: Some(theme.colors().text_muted),
+            ..Default::default()
+        },
+        Some(LogLevel::Debug) => HighlightStyle {
+            color: Some(status.hint),
+            ..Default::default()
+        },
+        Some(LogLevel::Info) => HighlightStyle {
+            color: Some(status.info),
+            ..Default::default()
+        },
+        Some(LogLevel::Warn) => HighlightStyle {
+            color: Some(status.warning),
+            ..Default::default()
+        },
+        Some(LogLevel::Error) => HighlightStyle {
+            color: Some(status.error),
+            ..Default::default()
+        },
+        Some(LogLevel::Fatal) => HighlightStyle {
+            color: Some(status.error),
+            font_weight: Some(FontWeight::BOLD),
+            ..Default::default()
+        },
+    };
+    editor.highlight_text::<LogHighlight<KIND>>(ranges, style, cx);
+}
@@ -0,0 +1,188 @@
+use editor::{Editor, EditorEvent};
+use gpui::{
+    div, prelude::*, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render,
+    SharedString, Styled, Subscription, View, ViewContext,
+};
+use theme::ActiveTheme;
+use ui::{h_flex, prelude::*, v_flex, Label};
+use workspace::ModalView;
+
+use crate::{LogView, Timestamp};
+
+/// What the timestamps typed into a [`TimestampModal`] are for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum TimestampModalKind {
+    GoToTimestamp,
+    FilterByTimeRange,
+}
+
+/// Prompts for a timestamp to go to, or a range of them to filter a log by.
+pub(crate) struct TimestampModal {
+    query_editor: View<Editor>,
+    log_view: View<LogView>,
+    kind: TimestampModalKind,
+    status: SharedString,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ModalView for TimestampModal {}
+
+impl FocusableView for TimestampModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.query_editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for TimestampModal {}
+
+impl TimestampModal {
+    pub(crate) fn new(
+        log_view: View<LogView>,
+        kind: TimestampModalKind,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let query_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            let placeholder = match kind {
+                TimestampModalKind::GoToTimestamp => "Go to timestamp…",
+                TimestampModalKind::FilterByTimeRange => "Filter by time range, as start..end",
+            };
+            editor.set_placeholder_text(placeholder, cx);
+            editor
+        });
+        let query_editor_change = cx.subscribe(&query_editor, Self::on_query_editor_event);
+        let mut this = Self {
+            query_editor,
+            log_view,
+            kind,
+            status: SharedString::default(),
+            _subscriptions: vec![query_editor_change],
+        };
+        this.update_status(cx);
+        this
+    }
+
+    fn on_query_editor_event(
+        &mut self,
+        _: View<Editor>,
+        event: &EditorEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match event {
+            EditorEvent::Blurred => cx.emit(DismissEvent),
+            EditorEvent::BufferEdited { .. } => self.update_status(cx),
+            _ => {}
+        }
+    }
+
+    fn update_status(&mut self, cx: &mut ViewContext<Self>) {
+        let query = self.query_editor.read(cx).text(cx);
+        self.status = if query.trim().is_empty() {
+            match self.kind {
+                TimestampModalKind::GoToTimestamp => {
+                    "Type a timestamp, such as 2024-05-01 12:00 or 12:00:30".into()
+                }
+                TimestampModalKind::FilterByTimeRange => {
+                    "Type a range, such as 12:00..12:30, leaving out either end".into()
+                }
+            }
+        } else {
+            match self.kind {
+                TimestampModalKind::GoToTimestamp => match Timestamp::parse_query(&query) {
+                    Some(timestamp) => {
+                        format!("Go to the first line at or after {timestamp}").into()
+                    }
+                    None => "Invalid timestamp".into(),
+                },
+                TimestampModalKind::FilterByTimeRange => match parse_time_range(&query) {
+                    Some((start, end)) => format!(
+                        "Show the lines from {} to {}",
+                        start.map_or("the start".to_string(), |start| start.to_string()),
+                        end.map_or("the end".to_string(), |end| end.to_string()),
+                    )
+                    .into(),
+                    None => "Invalid time range".into(),
+                },
+            }
+        };
+        cx.notify();
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let query = self.query_editor.read(cx).text(cx);
+        match self.kind {
+            TimestampModalKind::GoToTimestamp => {
+                let Some(timestamp) = Timestamp::parse_query(&query) else {
+                    return;
+                };
+                self.log_view
+                    .update(cx, |log_view, cx| log_view.go_to_timestamp(timestamp, cx));
+            }
+            TimestampModalKind::FilterByTimeRange => {
+                let Some((start, end)) = parse_time_range(&query) else {
+                    return;
+                };
+                self.log_view.update(cx, |log_view, cx| {
+                    log_view.filter_by_time_range(start, end, cx)
+                });
+            }
+        }
+        cx.emit(DismissEvent);
+    }
+}
+
+/// Parses a range of timestamps written as `start..end`, where either end may
+/// be left out, or a single timestamp to start the range at.
+fn parse_time_range(query: &str) -> Option<(Option<Timestamp>, Option<Timestamp>)> {
+    let parse_end = |end: &str| {
+        if end.trim().is_empty() {
+            Some(None)
+        } else {
+            Timestamp::parse_query(end).map(Some)
+        }
+    };
+    match query.split_once("..") {
+        Some((start, end)) => Some((parse_end(start)?, parse_end(end)?)),
+        None => Some((Some(Timestamp::parse_query(query)?), None)),
+    }
+}
+
+impl Render for TimestampModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        div()
+            .elevation_2(cx)
+            .key_context("TimestampModal")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .w_96()
+            .child(
+                v_flex()
+                    .px_1()
+                    .pt_0p5()
+                    .gap_px()
+                    .child(
+                        v_flex()
+                            .py_0p5()
+                            .px_1()
+                            .child(div().px_1().py_0p5().child(self.query_editor.clone())),
+                    )
+                    .child(
+                        div()
+                            .h_px()
+                            .w_full()
+                            .bg(cx.theme().colors().element_background),
+                    )
+                    .child(
+                        h_flex()
+                            .justify_between()
+                            .px_2()
+                            .py_1()
+                            .child(Label::new(self.status.clone()).color(Color::Muted)),
+                    ),
+            )
+    }
+}
@@ -4,6 +4,8 @@ use gpui::{
 use language::HighlightId;
 use std::{fmt::Display, ops::Range, path::PathBuf};
 
+use crate::markdown_mermaid::{Flowchart, FlowchartLayout};
+
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub enum ParsedMarkdownElement {
@@ -12,6 +14,10 @@ pub enum ParsedMarkdownElement {
     Table(ParsedMarkdownTable),
     BlockQuote(ParsedMarkdownBlockQuote),
     CodeBlock(ParsedMarkdownCodeBlock),
+    /// A formula, displayed on its own lines.
+    Math(ParsedMarkdownMath),
+    MermaidDiagram(ParsedMarkdownMermaidDiagram),
+    FootnoteDefinition(ParsedMarkdownFootnoteDefinition),
     /// A paragraph of text and other inline elements.
     Paragraph(ParsedMarkdownText),
     HorizontalRule(Range<usize>),
@@ -25,6 +31,9 @@ impl ParsedMarkdownElement {
             Self::Table(table) => table.source_range.clone(),
             Self::BlockQuote(block_quote) => block_quote.source_range.clone(),
             Self::CodeBlock(code_block) => code_block.source_range.clone(),
+            Self::Math(math) => math.source_range.clone(),
+            Self::MermaidDiagram(diagram) => diagram.source_range.clone(),
+            Self::FootnoteDefinition(footnote) => footnote.source_range.clone(),
             Self::Paragraph(text) => text.source_range.clone(),
             Self::HorizontalRule(range) => range.clone(),
         }
@@ -68,6 +77,31 @@ pub struct ParsedMarkdownCodeBlock {
     pub highlights: Option<Vec<(Range<usize>, HighlightId)>>,
 }
 
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownMath {
+    pub source_range: Range<usize>,
+    /// The formula laid out as text, one line for each of its rows.
+    pub contents: SharedString,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownMermaidDiagram {
+    pub source_range: Range<usize>,
+    pub flowchart: Flowchart,
+    pub layout: FlowchartLayout,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownFootnoteDefinition {
+    pub source_range: Range<usize>,
+    /// The number of the footnote, in the order footnotes are first referenced.
+    pub number: usize,
+    pub children: Vec<ParsedMarkdownElement>,
+}
+
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct ParsedMarkdownHeading {
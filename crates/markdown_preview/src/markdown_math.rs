@@ -0,0 +1,505 @@
+//! Math in Markdown, written in TeX between `$` (inline) or `$$` (display) delimiters.
+//!
+//! Formulas are shown the way KaTeX would lay them out on a single line: commands are
+//! replaced by their Unicode symbols, and scripts by superscript and subscript
+//! characters when there are some for all of their characters.
+
+use std::{iter::Peekable, ops::Range, str::Chars};
+
+/// Finds the formulas between single `$` in the text, following the rules of Pandoc:
+/// the opening `$` can't be followed by a space, and the closing one can't be preceded
+/// by a space, nor followed by a digit, so that prices aren't formulas.
+pub fn find_inline_math(text: &str) -> Vec<Range<usize>> {
+    let bytes = text.as_bytes();
+    let mut ranges = Vec::new();
+    let mut ix = 0;
+    while ix < bytes.len() {
+        if bytes[ix] != b'$' || is_escaped(bytes, ix) {
+            ix += 1;
+            continue;
+        }
+        // `$$` delimits display math, which isn't inline.
+        if bytes.get(ix + 1) == Some(&b'$') {
+            ix += 2;
+            continue;
+        }
+        let start = ix + 1;
+        if bytes.get(start).map_or(true, u8::is_ascii_whitespace) {
+            ix += 1;
+            continue;
+        }
+
+        let end = (start..bytes.len()).find(|&end| bytes[end] == b'$' && !is_escaped(bytes, end));
+        match end {
+            Some(end)
+                if !bytes[end - 1].is_ascii_whitespace()
+                    && bytes
+                        .get(end + 1)
+                        .map_or(true, |next| *next != b'$' && !next.is_ascii_digit()) =>
+            {
+                ranges.push(start..end);
+                ix = end + 1;
+            }
+            _ => ix += 1,
+        }
+    }
+    ranges
+}
+
+/// Renders the inline formulas of the text, returning the text with its formulas laid
+/// out and their ranges in it.
+pub fn render_inline_math(text: &str) -> (String, Vec<Range<usize>>) {
+    let mut rendered = String::new();
+    let mut ranges = Vec::new();
+    let mut last_end = 0;
+    for range in find_inline_math(text) {
+        // Formulas are rendered without their `$` delimiters.
+        rendered.push_str(&text[last_end..range.start - 1]);
+        let start = rendered.len();
+        rendered.push_str(&render_math(&text[range.clone()]));
+        ranges.push(start..rendered.len());
+        last_end = range.end + 1;
+    }
+    rendered.push_str(&text[last_end..]);
+    (rendered, ranges)
+}
+
+fn is_escaped(bytes: &[u8], ix: usize) -> bool {
+    ix > 0 && bytes[ix - 1] == b'\\'
+}
+
+/// The contents of a display formula, if the text is one.
+pub fn display_math(text: &str) -> Option<&str> {
+    let text = text.trim();
+    let tex = text.strip_prefix("$$")?.strip_suffix("$$")?;
+    (!tex.trim().is_empty()).then_some(tex.trim())
+}
+
+/// Lays out a formula as text, with the symbols of its commands and its scripts.
+pub fn render_math(tex: &str) -> String {
+    let mut chars = tex.chars().peekable();
+    let rendered = render_group(&mut chars, None);
+    // Spaces are only significant in TeX when they're commands.
+    let mut text = String::new();
+    for line in rendered.lines() {
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&collapse_spaces(line));
+    }
+    text
+}
+
+fn collapse_spaces(line: &str) -> String {
+    let mut result = String::new();
+    for word in line.split(' ').filter(|word| !word.is_empty()) {
+        if !result.is_empty() {
+            result.push(' ');
+        }
+        result.push_str(word);
+    }
+    result
+}
+
+/// Renders the formula until the closing delimiter, if any.
+fn render_group(chars: &mut Peekable<Chars>, closing: Option<char>) -> String {
+    let mut output = String::new();
+    while let Some(char) = chars.next() {
+        match char {
+            _ if Some(char) == closing => break,
+            '{' => output.push_str(&render_group(chars, Some('}'))),
+            '\\' => output.push_str(&render_command(chars)),
+            '^' => output.push_str(&script(&render_argument(chars), superscript, "^")),
+            '_' => output.push_str(&script(&render_argument(chars), subscript, "_")),
+            // Alignment points of environments like `aligned`.
+            '&' => {}
+            '~' => output.push(' '),
+            '-' => output.push('−'),
+            '*' => output.push('∗'),
+            '\n' | '\t' => output.push(' '),
+            '+' | '=' | '<' | '>' => {
+                output.push(' ');
+                output.push(char);
+                output.push(' ');
+            }
+            char => output.push(char),
+        }
+    }
+    output
+}
+
+/// Renders the argument of a command or script: a group, a command, or a single character.
+fn render_argument(chars: &mut Peekable<Chars>) -> String {
+    while chars.next_if(|char| char.is_whitespace()).is_some() {}
+    match chars.next() {
+        Some('{') => render_group(chars, Some('}')),
+        Some('\\') => render_command(chars),
+        Some(char) => char.to_string(),
+        None => String::new(),
+    }
+}
+
+fn render_command(chars: &mut Peekable<Chars>) -> String {
+    let mut name = String::new();
+    while let Some(char) = chars.next_if(|char| char.is_ascii_alphabetic()) {
+        name.push(char);
+    }
+    if name.is_empty() {
+        return match chars.next() {
+            Some('\\') => "\n".to_string(),
+            Some(',' | ':' | ';' | ' ') => " ".to_string(),
+            Some('!') | None => String::new(),
+            Some(char) => char.to_string(),
+        };
+    }
+
+    match name.as_str() {
+        "frac" | "dfrac" | "tfrac" => {
+            let numerator = render_argument(chars);
+            let denominator = render_argument(chars);
+            format!(
+                "{}/{}",
+                parenthesize(&numerator),
+                parenthesize(&denominator)
+            )
+        }
+        "sqrt" => {
+            let index = if chars.next_if_eq(&'[').is_some() {
+                render_group(chars, Some(']'))
+            } else {
+                String::new()
+            };
+            let radicand = render_argument(chars);
+            let root = match index.as_str() {
+                "" => "√".to_string(),
+                "3" => "∛".to_string(),
+                "4" => "∜".to_string(),
+                index => format!("{}√", script(index, superscript, "")),
+            };
+            format!("{root}{}", parenthesize(&radicand))
+        }
+        "text" | "textrm" | "mathrm" | "mathit" | "mathbf" | "mathsf" | "mathtt" | "boldsymbol"
+        | "operatorname" | "mbox" => render_argument(chars),
+        "mathbb" => render_argument(chars)
+            .chars()
+            .map(|char| double_struck(char).unwrap_or(char))
+            .collect(),
+        "begin" | "end" => {
+            render_argument(chars);
+            String::new()
+        }
+        "left" | "right" | "big" | "Big" | "bigg" | "Bigg" | "displaystyle" | "limits" => {
+            String::new()
+        }
+        "quad" => "  ".to_string(),
+        "qquad" => "    ".to_string(),
+        name => match symbol(name) {
+            Some(symbol) => symbol.to_string(),
+            // Functions like `\sin`, and the commands we don't know about.
+            None => name.to_string(),
+        },
+    }
+}
+
+fn parenthesize(text: &str) -> String {
+    if text.chars().count() <= 1 || text.chars().all(|char| char.is_alphanumeric()) {
+        text.to_string()
+    } else {
+        format!("({text})")
+    }
+}
+
+/// Writes the script with the given characters if it has them all, or with a caret or
+/// an underscore otherwise.
+fn script(text: &str, map: fn(char) -> Option<char>, fallback: &str) -> String {
+    let text = text
+        .chars()
+        .filter(|char| !char.is_whitespace())
+        .collect::<String>();
+    match text.chars().map(map).collect::<Option<String>>() {
+        Some(script) => script,
+        None if text.chars().count() == 1 => format!("{fallback}{text}"),
+        None => format!("{fallback}({text})"),
+    }
+}
+
+pub fn superscript(char: char) -> Option<char> {
+    Some(match char {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '-' | '−' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'a' => 'ᵃ',
+        'b' => 'ᵇ',
+        'c' => 'ᶜ',
+        'd' => 'ᵈ',
+        'e' => 'ᵉ',
+        'f' => 'ᶠ',
+        'g' => 'ᵍ',
+        'h' => 'ʰ',
+        'i' => 'ⁱ',
+        'j' => 'ʲ',
+        'k' => 'ᵏ',
+        'l' => 'ˡ',
+        'm' => 'ᵐ',
+        'n' => 'ⁿ',
+        'o' => 'ᵒ',
+        'p' => 'ᵖ',
+        'r' => 'ʳ',
+        's' => 'ˢ',
+        't' => 'ᵗ',
+        'u' => 'ᵘ',
+        'v' => 'ᵛ',
+        'w' => 'ʷ',
+        'x' => 'ˣ',
+        'y' => 'ʸ',
+        'z' => 'ᶻ',
+        'T' => 'ᵀ',
+        '′' | '\'' => '′',
+        '∗' | '*' => '*',
+        _ => return None,
+    })
+}
+
+fn subscript(char: char) -> Option<char> {
+    Some(match char {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '-' | '−' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'h' => 'ₕ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'l' => 'ₗ',
+        'm' => 'ₘ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        'p' => 'ₚ',
+        'r' => 'ᵣ',
+        's' => 'ₛ',
+        't' => 'ₜ',
+        'u' => 'ᵤ',
+        'v' => 'ᵥ',
+        'x' => 'ₓ',
+        _ => return None,
+    })
+}
+
+fn double_struck(char: char) -> Option<char> {
+    Some(match char {
+        'C' => 'ℂ',
+        'H' => 'ℍ',
+        'N' => 'ℕ',
+        'P' => 'ℙ',
+        'Q' => 'ℚ',
+        'R' => 'ℝ',
+        'Z' => 'ℤ',
+        _ => return None,
+    })
+}
+
+fn symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "rho" => "ρ",
+        "sigma" => "σ",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        "times" => "×",
+        "cdot" => "·",
+        "div" => "÷",
+        "pm" => "±",
+        "mp" => "∓",
+        "ast" => "∗",
+        "circ" => "∘",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "ll" => "≪",
+        "gg" => "≫",
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "sum" => "∑",
+        "prod" => "∏",
+        "coprod" => "∐",
+        "int" => "∫",
+        "iint" => "∬",
+        "iiint" => "∭",
+        "oint" => "∮",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "supset" => "⊃",
+        "subseteq" => "⊆",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "emptyset" | "varnothing" => "∅",
+        "forall" => "∀",
+        "exists" => "∃",
+        "neg" | "lnot" => "¬",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "to" | "rightarrow" => "→",
+        "leftarrow" | "gets" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" | "implies" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" | "iff" => "⇔",
+        "mapsto" => "↦",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "lbrace" => "{",
+        "rbrace" => "}",
+        "vert" | "mid" => "|",
+        "Vert" => "‖",
+        "perp" => "⊥",
+        "parallel" => "∥",
+        "angle" => "∠",
+        "degree" => "°",
+        "prime" => "′",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "hbar" => "ℏ",
+        "ell" => "ℓ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "aleph" => "ℵ",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_inline_math() {
+        let text = "Euler: $e^{i\\pi} + 1 = 0$, costs $5 and $10, and $$x$$ or $ y$.";
+        let ranges = find_inline_math(text);
+        assert_eq!(
+            ranges
+                .iter()
+                .map(|range| &text[range.clone()])
+                .collect::<Vec<_>>(),
+            vec!["e^{i\\pi} + 1 = 0"]
+        );
+        assert_eq!(find_inline_math("\\$a$ and $b$"), vec![10..11]);
+    }
+
+    #[test]
+    fn test_render_inline_math() {
+        let (text, ranges) = render_inline_math("Area is $\\pi r^2$, or $x$.");
+        assert_eq!(text, "Area is π r², or x.");
+        assert_eq!(
+            ranges
+                .iter()
+                .map(|range| &text[range.clone()])
+                .collect::<Vec<_>>(),
+            vec!["π r²", "x"]
+        );
+    }
+
+    #[test]
+    fn test_display_math() {
+        assert_eq!(display_math("$$\nx^2\n$$"), Some("x^2"));
+        assert_eq!(display_math("$$ $$"), None);
+        assert_eq!(display_math("$x$"), None);
+    }
+
+    #[test]
+    fn test_render_math() {
+        assert_eq!(render_math("e^{i\\pi} + 1 = 0"), "e^(iπ) + 1 = 0");
+        assert_eq!(render_math("x^2 + y_1^{n+1}"), "x² + y₁ⁿ⁺¹");
+        assert_eq!(render_math("\\frac{a+b}{2}"), "(a + b)/2");
+        assert_eq!(render_math("\\sqrt{x} \\leq \\sqrt[3]{y}"), "√x ≤ ∛y");
+        assert_eq!(
+            render_math("\\sum_{i=1}^{n} \\alpha_i \\in \\mathbb{R}"),
+            "∑ᵢ₌₁ⁿ αᵢ ∈ ℝ"
+        );
+        assert_eq!(
+            render_math("\\sin\\theta \\cdot \\text{rate}"),
+            "sinθ · rate"
+        );
+        assert_eq!(
+            render_math("\\begin{aligned} a &= b \\\\ c &= d \\end{aligned}"),
+            "a = b\nc = d"
+        );
+    }
+}
@@ -0,0 +1,538 @@
+//! Mermaid diagrams, from ` ```mermaid ` code blocks.
+//!
+//! Only flowcharts are drawn: their nodes are placed in ranks, along the direction of
+//! the chart, so that edges go from a rank to the ones after it.
+
+use collections::HashSet;
+
+pub const NODE_HEIGHT: f32 = 36.;
+const CHAR_WIDTH: f32 = 8.;
+const NODE_PADDING: f32 = 16.;
+const NODE_GAP: f32 = 24.;
+const RANK_GAP: f32 = 48.;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlowchartDirection {
+    TopDown,
+    BottomUp,
+    LeftRight,
+    RightLeft,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeShape {
+    Rectangle,
+    Rounded,
+    Circle,
+    Diamond,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowchartNode {
+    pub id: String,
+    pub label: String,
+    pub shape: NodeShape,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeStyle {
+    Solid,
+    Dotted,
+    Thick,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowchartEdge {
+    /// The index of the node the edge starts from.
+    pub from: usize,
+    /// The index of the node the edge goes to.
+    pub to: usize,
+    pub label: Option<String>,
+    pub style: EdgeStyle,
+    /// Whether the edge ends with an arrow head.
+    pub arrow: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flowchart {
+    pub direction: FlowchartDirection,
+    pub nodes: Vec<FlowchartNode>,
+    pub edges: Vec<FlowchartEdge>,
+}
+
+/// Parses a flowchart, returning `None` for other diagrams.
+pub fn parse_flowchart(source: &str) -> Option<Flowchart> {
+    let mut lines = source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("%%"));
+
+    let mut header = lines.next()?.trim_end_matches(';').split_whitespace();
+    if !matches!(header.next()?, "graph" | "flowchart") {
+        return None;
+    }
+    let direction = match header.next() {
+        None | Some("TD" | "TB") => FlowchartDirection::TopDown,
+        Some("BT") => FlowchartDirection::BottomUp,
+        Some("LR") => FlowchartDirection::LeftRight,
+        Some("RL") => FlowchartDirection::RightLeft,
+        Some(_) => return None,
+    };
+
+    let mut flowchart = Flowchart {
+        direction,
+        nodes: Vec::new(),
+        edges: Vec::new(),
+    };
+    for line in lines {
+        for statement in line.split(';') {
+            flowchart.parse_statement(statement.trim());
+        }
+    }
+    (!flowchart.nodes.is_empty()).then_some(flowchart)
+}
+
+impl Flowchart {
+    /// Parses a node, or a chain of nodes linked by edges, like `A[Start] --> B{Ok?}`.
+    fn parse_statement(&mut self, statement: &str) {
+        let keyword = statement.split_whitespace().next().unwrap_or_default();
+        if matches!(
+            keyword,
+            "classDef"
+                | "class"
+                | "style"
+                | "linkStyle"
+                | "click"
+                | "subgraph"
+                | "end"
+                | "direction"
+        ) {
+            return;
+        }
+
+        let Some((mut previous, mut rest)) = self.parse_node(statement) else {
+            return;
+        };
+        while let Some((edge, tail)) = parse_edge(rest.trim_start()) {
+            let Some((next, tail)) = self.parse_node(tail.trim_start()) else {
+                break;
+            };
+            self.edges.push(FlowchartEdge {
+                from: previous,
+                to: next,
+                ..edge
+            });
+            previous = next;
+            rest = tail;
+        }
+    }
+
+    /// Parses a node id with its optional shape and label, returning the index of the
+    /// node and the rest of the text.
+    fn parse_node<'a>(&mut self, text: &'a str) -> Option<(usize, &'a str)> {
+        let id_len = text
+            .find(|char: char| !(char.is_alphanumeric() || char == '_'))
+            .unwrap_or(text.len());
+        if id_len == 0 {
+            return None;
+        }
+        let (id, rest) = text.split_at(id_len);
+
+        const SHAPES: [(&str, &str, NodeShape); 9] = [
+            ("((", "))", NodeShape::Circle),
+            ("([", "])", NodeShape::Rounded),
+            ("[[", "]]", NodeShape::Rectangle),
+            ("[(", ")]", NodeShape::Rectangle),
+            ("{{", "}}", NodeShape::Diamond),
+            ("[", "]", NodeShape::Rectangle),
+            ("(", ")", NodeShape::Rounded),
+            ("{", "}", NodeShape::Diamond),
+            (">", "]", NodeShape::Rectangle),
+        ];
+        let (label, shape, rest) = SHAPES
+            .iter()
+            .find_map(|(open, close, shape)| {
+                let inner = rest.strip_prefix(open)?;
+                let end = inner.find(close)?;
+                let label = unquote(inner[..end].trim()).to_string();
+                Some((Some(label), *shape, &inner[end + close.len()..]))
+            })
+            .unwrap_or((None, NodeShape::Rectangle, rest));
+
+        let ix = match self.nodes.iter().position(|node| node.id == id) {
+            Some(ix) => ix,
+            None => {
+                self.nodes.push(FlowchartNode {
+                    id: id.to_string(),
+                    label: id.to_string(),
+                    shape,
+                });
+                self.nodes.len() - 1
+            }
+        };
+        // Nodes can be declared before they're given a label, like in `A --> B[Label]`.
+        if let Some(label) = label {
+            let node = &mut self.nodes[ix];
+            node.label = label;
+            node.shape = shape;
+        }
+        Some((ix, rest))
+    }
+}
+
+/// Parses an edge, like `-->`, `-.->|label|` or `-- label ---`, returning it without
+/// its nodes, and the rest of the text.
+fn parse_edge(text: &str) -> Option<(FlowchartEdge, &str)> {
+    let (mut arrow, mut rest) = split_arrow(text)?;
+    let mut label = None;
+
+    // Labels in the middle of edges, like `A -- label --> B`.
+    if matches!(arrow, "--" | "==" | "-.") {
+        let end_marker = if arrow == "-." { ".-" } else { arrow };
+        let end = rest.find(end_marker)?;
+        label = Some(unquote(rest[..end].trim()).to_string());
+        let (end_arrow, tail) = split_arrow(&rest[end..])?;
+        arrow = end_arrow;
+        rest = tail;
+    }
+
+    let trimmed = rest.trim_start();
+    if let Some(inner) = trimmed.strip_prefix('|') {
+        if let Some(end) = inner.find('|') {
+            label = Some(unquote(inner[..end].trim()).to_string());
+            rest = &inner[end + 1..];
+        }
+    }
+
+    let style = if arrow.contains('.') {
+        EdgeStyle::Dotted
+    } else if arrow.contains('=') {
+        EdgeStyle::Thick
+    } else {
+        EdgeStyle::Solid
+    };
+    let edge = FlowchartEdge {
+        from: 0,
+        to: 0,
+        label: label.filter(|label| !label.is_empty()),
+        style,
+        arrow: arrow.ends_with('>'),
+    };
+    Some((edge, rest))
+}
+
+fn split_arrow(text: &str) -> Option<(&str, &str)> {
+    let len = text
+        .find(|char| !matches!(char, '-' | '=' | '.' | '<' | '>'))
+        .unwrap_or(text.len());
+    let (arrow, rest) = text.split_at(len);
+    (arrow.len() >= 2 && (arrow.contains('-') || arrow.contains('='))).then_some((arrow, rest))
+}
+
+fn unquote(text: &str) -> &str {
+    text.strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .unwrap_or(text)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeBounds {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl NodeBounds {
+    pub fn center(&self) -> (f32, f32) {
+        (self.x + self.width / 2., self.y + self.height / 2.)
+    }
+
+    /// The point of the border of the node on the line from its center to the given point.
+    fn border_point(&self, toward: (f32, f32)) -> (f32, f32) {
+        let (center_x, center_y) = self.center();
+        let (dx, dy) = (toward.0 - center_x, toward.1 - center_y);
+        let t = f32::min(
+            if dx == 0. {
+                f32::INFINITY
+            } else {
+                self.width / 2. / dx.abs()
+            },
+            if dy == 0. {
+                f32::INFINITY
+            } else {
+                self.height / 2. / dy.abs()
+            },
+        );
+        if t.is_finite() {
+            (center_x + dx * t, center_y + dy * t)
+        } else {
+            (center_x, center_y)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowchartLayout {
+    /// The bounds of each node of the flowchart, in pixels.
+    pub nodes: Vec<NodeBounds>,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl FlowchartLayout {
+    /// The start and end of the edge, on the borders of its nodes.
+    pub fn edge_endpoints(&self, edge: &FlowchartEdge) -> ((f32, f32), (f32, f32)) {
+        let from = self.nodes[edge.from];
+        let to = self.nodes[edge.to];
+        (
+            from.border_point(to.center()),
+            to.border_point(from.center()),
+        )
+    }
+}
+
+pub fn layout_flowchart(flowchart: &Flowchart) -> FlowchartLayout {
+    let sizes = flowchart
+        .nodes
+        .iter()
+        .map(|node| {
+            let width = node.label.chars().count() as f32 * CHAR_WIDTH + NODE_PADDING * 2.;
+            match node.shape {
+                NodeShape::Rectangle | NodeShape::Rounded => (width, NODE_HEIGHT),
+                NodeShape::Circle => (width.max(NODE_HEIGHT), width.max(NODE_HEIGHT)),
+                NodeShape::Diamond => (width * 1.5, NODE_HEIGHT * 1.5),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let ranks = ranks(flowchart);
+    let rank_count = ranks.iter().max().map_or(0, |rank| rank + 1);
+    let mut rank_nodes = vec![Vec::new(); rank_count];
+    for (node, rank) in ranks.iter().enumerate() {
+        rank_nodes[*rank].push(node);
+    }
+
+    // The main axis goes along the direction of the chart, from a rank to the next.
+    let horizontal = matches!(
+        flowchart.direction,
+        FlowchartDirection::LeftRight | FlowchartDirection::RightLeft
+    );
+    let main_size = |node: usize| {
+        if horizontal {
+            sizes[node].0
+        } else {
+            sizes[node].1
+        }
+    };
+    let cross_size = |node: usize| {
+        if horizontal {
+            sizes[node].1
+        } else {
+            sizes[node].0
+        }
+    };
+
+    let rank_depths = rank_nodes
+        .iter()
+        .map(|nodes| nodes.iter().map(|node| main_size(*node)).fold(0., f32::max))
+        .collect::<Vec<_>>();
+    let rank_breadths = rank_nodes
+        .iter()
+        .map(|nodes| {
+            nodes.iter().map(|node| cross_size(*node)).sum::<f32>()
+                + NODE_GAP * nodes.len().saturating_sub(1) as f32
+        })
+        .collect::<Vec<_>>();
+    let breadth = rank_breadths.iter().copied().fold(0., f32::max);
+    let depth = rank_depths.iter().sum::<f32>() + RANK_GAP * rank_count.saturating_sub(1) as f32;
+
+    let mut nodes = vec![
+        NodeBounds {
+            x: 0.,
+            y: 0.,
+            width: 0.,
+            height: 0.,
+        };
+        flowchart.nodes.len()
+    ];
+    let mut main = 0.;
+    for (rank, rank_nodes) in rank_nodes.iter().enumerate() {
+        // Ranks are centered on the cross axis, and nodes on the main axis of their rank.
+        let mut cross = (breadth - rank_breadths[rank]) / 2.;
+        for node in rank_nodes {
+            let main_offset = main + (rank_depths[rank] - main_size(*node)) / 2.;
+            let (width, height) = sizes[*node];
+            let (x, y) = if horizontal {
+                (main_offset, cross)
+            } else {
+                (cross, main_offset)
+            };
+            nodes[*node] = NodeBounds {
+                x,
+                y,
+                width,
+                height,
+            };
+            cross += cross_size(*node) + NODE_GAP;
+        }
+        main += rank_depths[rank] + RANK_GAP;
+    }
+
+    for node in &mut nodes {
+        match flowchart.direction {
+            FlowchartDirection::BottomUp => node.y = depth - node.y - node.height,
+            FlowchartDirection::RightLeft => node.x = depth - node.x - node.width,
+            FlowchartDirection::TopDown | FlowchartDirection::LeftRight => {}
+        }
+    }
+
+    let (width, height) = if horizontal {
+        (depth, breadth)
+    } else {
+        (breadth, depth)
+    };
+    FlowchartLayout {
+        nodes,
+        width,
+        height,
+    }
+}
+
+/// The rank of each node: the length of the longest path to it, ignoring the edges
+/// that close cycles.
+fn ranks(flowchart: &Flowchart) -> Vec<usize> {
+    fn visit(
+        node: usize,
+        flowchart: &Flowchart,
+        on_stack: &mut Vec<bool>,
+        visited: &mut Vec<bool>,
+        back_edges: &mut HashSet<usize>,
+    ) {
+        visited[node] = true;
+        on_stack[node] = true;
+        for (ix, edge) in flowchart.edges.iter().enumerate() {
+            if edge.from != node {
+                continue;
+            }
+            if on_stack[edge.to] {
+                back_edges.insert(ix);
+            } else if !visited[edge.to] {
+                visit(edge.to, flowchart, on_stack, visited, back_edges);
+            }
+        }
+        on_stack[node] = false;
+    }
+
+    let node_count = flowchart.nodes.len();
+    let mut on_stack = vec![false; node_count];
+    let mut visited = vec![false; node_count];
+    let mut back_edges = HashSet::default();
+    for node in 0..node_count {
+        if !visited[node] {
+            visit(
+                node,
+                flowchart,
+                &mut on_stack,
+                &mut visited,
+                &mut back_edges,
+            );
+        }
+    }
+
+    let mut ranks = vec![0; node_count];
+    for _ in 0..node_count {
+        let mut changed = false;
+        for (ix, edge) in flowchart.edges.iter().enumerate() {
+            if !back_edges.contains(&ix) && ranks[edge.to] < ranks[edge.from] + 1 {
+                ranks[edge.to] = ranks[edge.from] + 1;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_flowchart() {
+        let flowchart = parse_flowchart(
+            "flowchart LR\n  %% A comment\n  A[Start] --> B{\"Is it?\"}\n  B -->|Yes| C(Done); B -. no .-> A\n  style A fill:#f9f\n  C === D((End))",
+        )
+        .unwrap();
+
+        assert_eq!(flowchart.direction, FlowchartDirection::LeftRight);
+        assert_eq!(
+            flowchart
+                .nodes
+                .iter()
+                .map(|node| (node.id.as_str(), node.label.as_str(), node.shape))
+                .collect::<Vec<_>>(),
+            vec![
+                ("A", "Start", NodeShape::Rectangle),
+                ("B", "Is it?", NodeShape::Diamond),
+                ("C", "Done", NodeShape::Rounded),
+                ("D", "End", NodeShape::Circle),
+            ]
+        );
+        assert_eq!(
+            flowchart
+                .edges
+                .iter()
+                .map(|edge| (
+                    edge.from,
+                    edge.to,
+                    edge.label.as_deref(),
+                    edge.style,
+                    edge.arrow
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, 1, None, EdgeStyle::Solid, true),
+                (1, 2, Some("Yes"), EdgeStyle::Solid, true),
+                (1, 0, Some("no"), EdgeStyle::Dotted, true),
+                (2, 3, None, EdgeStyle::Thick, false),
+            ]
+        );
+
+        assert!(parse_flowchart("sequenceDiagram\n  A->>B: Hi").is_none());
+        assert!(parse_flowchart("graph XY\n  A --> B").is_none());
+    }
+
+    #[test]
+    fn test_layout_flowchart() {
+        let flowchart =
+            parse_flowchart("graph TD\n  A --> B\n  A --> C\n  C --> D\n  B --> D\n  D --> A")
+                .unwrap();
+        let layout = layout_flowchart(&flowchart);
+
+        // The edge from D back to A closes a cycle, and doesn't push A down.
+        let ranks = layout
+            .nodes
+            .iter()
+            .map(|bounds| (bounds.y / (NODE_HEIGHT + RANK_GAP)).round() as usize)
+            .collect::<Vec<_>>();
+        assert_eq!(ranks, vec![0, 1, 1, 2]);
+        assert_eq!(layout.height, NODE_HEIGHT * 3. + RANK_GAP * 2.);
+
+        // B and C are side by side, with A and D centered above and below them.
+        let (b, c) = (layout.nodes[1], layout.nodes[2]);
+        assert_eq!(c.x, b.x + b.width + NODE_GAP);
+        assert_eq!(layout.nodes[0].center().0, layout.width / 2.);
+
+        let ((start_x, start_y), (end_x, end_y)) = layout.edge_endpoints(&flowchart.edges[0]);
+        assert!((start_y - (layout.nodes[0].y + NODE_HEIGHT)).abs() < 0.01);
+        assert!((end_y - b.y).abs() < 0.01);
+        assert!(start_x > end_x);
+
+        let flowchart = parse_flowchart("graph BT\n  A --> B").unwrap();
+        let layout = layout_flowchart(&flowchart);
+        assert!(layout.nodes[0].y > layout.nodes[1].y);
+    }
+}
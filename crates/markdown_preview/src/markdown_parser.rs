@@ -1,9 +1,11 @@
 use crate::markdown_elements::*;
+use crate::markdown_math::{display_math, render_inline_math, render_math, superscript};
+use crate::markdown_mermaid::{layout_flowchart, parse_flowchart};
 use async_recursion::async_recursion;
 use collections::FxHashMap;
 use gpui::FontWeight;
 use language::LanguageRegistry;
-use pulldown_cmark::{Alignment, Event, Options, Parser, Tag, TagEnd};
+use pulldown_cmark::{Alignment, CowStr, Event, Options, Parser, Tag, TagEnd};
 use std::{ops::Range, path::PathBuf, sync::Arc};
 
 pub async fn parse_markdown(
@@ -14,7 +16,8 @@ pub async fn parse_markdown(
     let options = Options::all();
     let parser = Parser::new_ext(markdown_input, options);
     let parser = MarkdownParser::new(
-        parser.into_offset_iter().collect(),
+        markdown_input,
+        merge_text_events(parser.into_offset_iter()),
         file_location_directory,
         language_registry,
     );
@@ -24,7 +27,29 @@ pub async fn parse_markdown(
     }
 }
 
+/// Merges the consecutive text events, which the parser splits around characters like
+/// unmatched emphasis delimiters, so that formulas and links can be found in them.
+fn merge_text_events<'a>(
+    events: impl Iterator<Item = (Event<'a>, Range<usize>)>,
+) -> Vec<(Event<'a>, Range<usize>)> {
+    let mut tokens: Vec<(Event<'a>, Range<usize>)> = Vec::new();
+    for (event, range) in events {
+        if let (Event::Text(text), Some((Event::Text(previous_text), previous_range))) =
+            (&event, tokens.last_mut())
+        {
+            if previous_range.end == range.start {
+                *previous_text = CowStr::from(format!("{previous_text}{text}"));
+                previous_range.end = range.end;
+                continue;
+            }
+        }
+        tokens.push((event, range));
+    }
+    tokens
+}
+
 struct MarkdownParser<'a> {
+    markdown_input: &'a str,
     tokens: Vec<(Event<'a>, Range<usize>)>,
     /// The current index in the tokens array
     cursor: usize,
@@ -32,23 +57,37 @@ struct MarkdownParser<'a> {
     parsed: Vec<ParsedMarkdownElement>,
     file_location_directory: Option<PathBuf>,
     language_registry: Option<Arc<LanguageRegistry>>,
+    /// The numbers of the footnotes, by label.
+    footnote_numbers: FxHashMap<String, usize>,
 }
 
 impl<'a> MarkdownParser<'a> {
     fn new(
+        markdown_input: &'a str,
         tokens: Vec<(Event<'a>, Range<usize>)>,
         file_location_directory: Option<PathBuf>,
         language_registry: Option<Arc<LanguageRegistry>>,
     ) -> Self {
         Self {
+            markdown_input,
             tokens,
             file_location_directory,
             language_registry,
             cursor: 0,
             parsed: vec![],
+            footnote_numbers: FxHashMap::default(),
         }
     }
 
+    /// Footnotes are numbered in the order they're first referenced or defined in.
+    fn footnote_number(&mut self, label: &str) -> usize {
+        let next_number = self.footnote_numbers.len() + 1;
+        *self
+            .footnote_numbers
+            .entry(label.to_string())
+            .or_insert(next_number)
+    }
+
     fn eof(&self) -> bool {
         if self.tokens.is_empty() {
             return true;
@@ -113,6 +152,15 @@ impl<'a> MarkdownParser<'a> {
             Event::Start(tag) => match tag {
                 Tag::Paragraph => {
                     self.cursor += 1;
+                    let markdown_input = self.markdown_input;
+                    if let Some(tex) = display_math(&markdown_input[source_range.clone()]) {
+                        let math = ParsedMarkdownMath {
+                            source_range,
+                            contents: render_math(tex).into(),
+                        };
+                        self.skip_past(TagEnd::Paragraph);
+                        return Some(vec![ParsedMarkdownElement::Math(math)]);
+                    }
                     let text = self.parse_text(false, Some(source_range));
                     Some(vec![ParsedMarkdownElement::Paragraph(text)])
                 }
@@ -159,7 +207,13 @@ impl<'a> MarkdownParser<'a> {
                     self.cursor += 1;
 
                     let code_block = self.parse_code_block(language).await;
-                    Some(vec![ParsedMarkdownElement::CodeBlock(code_block)])
+                    Some(vec![code_block_element(code_block)])
+                }
+                Tag::FootnoteDefinition(label) => {
+                    let label = label.to_string();
+                    self.cursor += 1;
+                    let footnote = self.parse_footnote_definition(label).await;
+                    Some(vec![ParsedMarkdownElement::FootnoteDefinition(footnote)])
                 }
                 _ => {
                     self.cursor += 1;
@@ -223,7 +277,8 @@ impl<'a> MarkdownParser<'a> {
                 }
 
                 Event::Text(t) => {
-                    text.push_str(t.as_ref());
+                    let (t, math_ranges) = render_inline_math(t);
+                    text.push_str(&t);
 
                     let mut style = MarkdownHighlightStyle::default();
 
@@ -304,6 +359,22 @@ impl<'a> MarkdownParser<'a> {
                                 .push((last_run_len..text.len(), MarkdownHighlight::Style(style)));
                         }
                     }
+
+                    for range in math_ranges {
+                        highlights.push((
+                            prev_len + range.start..prev_len + range.end,
+                            MarkdownHighlight::Style(MarkdownHighlightStyle {
+                                italic: true,
+                                ..Default::default()
+                            }),
+                        ));
+                    }
+                }
+
+                Event::FootnoteReference(label) => {
+                    let label = label.to_string();
+                    let number = self.footnote_number(&label);
+                    text.extend(number.to_string().chars().filter_map(superscript));
                 }
 
                 // Note: This event means "inline code" and not "code block"
@@ -674,6 +745,43 @@ impl<'a> MarkdownParser<'a> {
         }
     }
 
+    async fn parse_footnote_definition(
+        &mut self,
+        label: String,
+    ) -> ParsedMarkdownFootnoteDefinition {
+        let (_event, source_range) = self.previous().unwrap();
+        let source_range = source_range.clone();
+        let number = self.footnote_number(&label);
+        let mut children = vec![];
+
+        while !self.eof() {
+            if let Some(Event::End(TagEnd::FootnoteDefinition)) = self.current_event() {
+                self.cursor += 1;
+                break;
+            }
+            if let Some(block) = self.parse_block().await {
+                children.extend(block);
+            }
+        }
+
+        ParsedMarkdownFootnoteDefinition {
+            source_range,
+            number,
+            children,
+        }
+    }
+
+    /// Moves the cursor past the next end of the given tag.
+    fn skip_past(&mut self, tag_end: TagEnd) {
+        while !self.eof() {
+            let is_end = self.current_event() == Some(&Event::End(tag_end));
+            self.cursor += 1;
+            if is_end {
+                break;
+            }
+        }
+    }
+
     async fn parse_code_block(&mut self, language: Option<String>) -> ParsedMarkdownCodeBlock {
         let (_event, source_range) = self.previous().unwrap();
         let source_range = source_range.clone();
@@ -720,6 +828,30 @@ impl<'a> MarkdownParser<'a> {
     }
 }
 
+/// Code blocks of formulas and diagrams are rendered, rather than highlighted.
+fn code_block_element(code_block: ParsedMarkdownCodeBlock) -> ParsedMarkdownElement {
+    match code_block.language.as_deref() {
+        Some("math") => {
+            return ParsedMarkdownElement::Math(ParsedMarkdownMath {
+                source_range: code_block.source_range,
+                contents: render_math(&code_block.contents).into(),
+            })
+        }
+        Some("mermaid") => {
+            if let Some(flowchart) = parse_flowchart(&code_block.contents) {
+                let layout = layout_flowchart(&flowchart);
+                return ParsedMarkdownElement::MermaidDiagram(ParsedMarkdownMermaidDiagram {
+                    source_range: code_block.source_range,
+                    flowchart,
+                    layout,
+                });
+            }
+        }
+        _ => {}
+    }
+    ParsedMarkdownElement::CodeBlock(code_block)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1224,6 +1356,60 @@ fn main() {
         );
     }
 
+    #[gpui::test]
+    async fn test_math() {
+        let parsed =
+            parse("The area is $\\pi r^2$.\n\n$$\nE = mc^2\n$$\n\n```math\nx_1\n```\n").await;
+
+        assert_eq!(
+            parsed.children,
+            vec![
+                p("The area is π r².", 0..23),
+                math("E = mc²", 24..39),
+                math("x₁", 40..55),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_footnotes() {
+        let parsed =
+            parse("Text[^note] and more[^other].\n\n[^other]: Other.\n\n[^note]: The note.\n")
+                .await;
+
+        let [paragraph, ParsedMarkdownElement::FootnoteDefinition(other), ParsedMarkdownElement::FootnoteDefinition(note)] =
+            parsed.children.as_slice()
+        else {
+            panic!("unexpected elements {:?}", parsed.children);
+        };
+        assert_eq!(paragraph, &p("Text¹ and more².", 0..30));
+
+        let contents =
+            |footnote: &ParsedMarkdownFootnoteDefinition| match footnote.children.as_slice() {
+                [ParsedMarkdownElement::Paragraph(text)] => text.contents.clone(),
+                children => panic!("unexpected footnote contents {children:?}"),
+            };
+        assert_eq!(other.number, 2);
+        assert_eq!(contents(other), "Other.");
+        assert_eq!(note.number, 1);
+        assert_eq!(contents(note), "The note.");
+    }
+
+    #[gpui::test]
+    async fn test_mermaid_diagram() {
+        let parsed = parse("```mermaid\ngraph TD\n  A --> B\n```\n\n```mermaid\npie\n```\n").await;
+
+        let [ParsedMarkdownElement::MermaidDiagram(diagram), ParsedMarkdownElement::CodeBlock(code_block)] =
+            parsed.children.as_slice()
+        else {
+            panic!("unexpected elements {:?}", parsed.children);
+        };
+        assert_eq!(diagram.flowchart.nodes.len(), 2);
+        assert_eq!(diagram.flowchart.edges.len(), 1);
+        assert_eq!(diagram.layout.nodes.len(), 2);
+        assert_eq!(code_block.contents.as_ref(), "pie");
+    }
+
     fn rust_lang() -> Arc<Language> {
         Arc::new(Language::new(
             LanguageConfig {
@@ -1277,6 +1463,13 @@ fn main() {
         }
     }
 
+    fn math(contents: &str, source_range: Range<usize>) -> ParsedMarkdownElement {
+        ParsedMarkdownElement::Math(ParsedMarkdownMath {
+            source_range,
+            contents: contents.to_string().into(),
+        })
+    }
+
     fn block_quote(
         children: Vec<ParsedMarkdownElement>,
         source_range: Range<usize>,
@@ -3,19 +3,20 @@ use std::time::Duration;
 use std::{ops::Range, path::PathBuf};
 
 use anyhow::Result;
-use editor::scroll::{Autoscroll, AutoscrollStrategy};
+use editor::scroll::{Autoscroll, AutoscrollStrategy, ScrollAnchor};
 use editor::{Editor, EditorEvent};
 use gpui::{
     list, AppContext, ClickEvent, EventEmitter, FocusHandle, FocusableView, InteractiveElement,
-    IntoElement, ListState, ParentElement, Render, Styled, Subscription, Task, View, ViewContext,
-    WeakView,
+    IntoElement, ListOffset, ListState, ParentElement, Render, Styled, Subscription, Task, View,
+    ViewContext, WeakView,
 };
-use language::LanguageRegistry;
+use language::{LanguageRegistry, Point};
 use ui::prelude::*;
 use workspace::item::{Item, ItemHandle};
 use workspace::{Pane, Workspace};
 
 use crate::markdown_elements::ParsedMarkdownElement;
+use crate::markup_parser::{parse_org, parse_rst, PreviewFormat};
 use crate::OpenPreviewToTheSide;
 use crate::{
     markdown_elements::ParsedMarkdown,
@@ -32,6 +33,9 @@ pub struct MarkdownPreviewView {
     focus_handle: FocusHandle,
     contents: Option<ParsedMarkdown>,
     selected_block: usize,
+    /// The block shown at the top of both the preview and the editor, used to keep their
+    /// scroll positions in sync.
+    top_block: usize,
     list_state: ListState,
     tab_description: Option<String>,
     fallback_tab_description: SharedString,
@@ -161,9 +165,15 @@ impl MarkdownPreviewView {
                                                     .as_ref()
                                                     .map(|s| s.editor.clone())
                                                 {
+                                                    let is_org = Self::preview_format(&editor, cx)
+                                                        == Some(PreviewFormat::Org);
                                                     editor.update(cx, |editor, cx| {
-                                                        let task_marker =
-                                                            if checked { "[x]" } else { "[ ]" };
+                                                        // Org only recognizes upper case checked boxes.
+                                                        let task_marker = match (checked, is_org) {
+                                                            (true, true) => "[X]",
+                                                            (true, false) => "[x]",
+                                                            (false, _) => "[ ]",
+                                                        };
 
                                                         editor.edit(
                                                             vec![(source_range, task_marker)],
@@ -230,8 +240,20 @@ impl MarkdownPreviewView {
                     }
                 });
 
+            list_state.set_scroll_handler({
+                let view = cx.view().downgrade();
+                move |event, cx| {
+                    if let Some(view) = view.upgrade() {
+                        view.update(cx, |this, cx| {
+                            this.preview_scrolled(event.visible_range.start, cx)
+                        });
+                    }
+                }
+            });
+
             let mut this = Self {
                 selected_block: 0,
+                top_block: 0,
                 active_editor: None,
                 focus_handle: cx.focus_handle(),
                 workspace: workspace.clone(),
@@ -279,13 +301,19 @@ impl MarkdownPreviewView {
     }
 
     pub fn is_markdown_file<V>(editor: &View<Editor>, cx: &mut ViewContext<V>) -> bool {
-        let buffer = editor.read(cx).buffer().read(cx);
-        if let Some(buffer) = buffer.as_singleton() {
-            if let Some(language) = buffer.read(cx).language() {
-                return language.name().as_ref() == "Markdown";
-            }
-        }
-        false
+        Self::preview_format(editor, cx).is_some()
+    }
+
+    /// The format of the document in the editor, if the preview can render it.
+    pub fn preview_format(editor: &View<Editor>, cx: &AppContext) -> Option<PreviewFormat> {
+        let buffer = editor.read(cx).buffer().read(cx).as_singleton()?;
+        let buffer = buffer.read(cx);
+        let language_name = buffer.language().map(|language| language.name());
+        let extension = buffer
+            .file()
+            .and_then(|file| file.path().extension())
+            .and_then(|extension| extension.to_str());
+        PreviewFormat::detect(language_name.as_deref(), extension)
     }
 
     fn set_editor(&mut self, editor: View<Editor>, cx: &mut ViewContext<Self>) {
@@ -307,6 +335,13 @@ impl MarkdownPreviewView {
                     this.list_state.scroll_to_reveal_item(this.selected_block);
                     cx.notify();
                 }
+                EditorEvent::ScrollPositionChanged { .. } => {
+                    let editor = editor.read(cx);
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let top_row = editor.scroll_manager.anchor().top_row(&snapshot);
+                    let offset = snapshot.point_to_offset(Point::new(top_row, 0));
+                    this.editor_scrolled(offset, cx);
+                }
                 _ => {}
             };
         });
@@ -352,15 +387,22 @@ impl MarkdownPreviewView {
                 cx.background_executor().timer(REPARSE_DEBOUNCE).await;
             }
 
-            let (contents, file_location) = view.update(&mut cx, |_, cx| {
+            let (contents, file_location, format) = view.update(&mut cx, |_, cx| {
+                let format = MarkdownPreviewView::preview_format(&editor, cx);
                 let editor = editor.read(cx);
                 let contents = editor.buffer().read(cx).snapshot(cx).text();
                 let file_location = MarkdownPreviewView::get_folder_for_active_editor(editor, cx);
-                (contents, file_location)
+                (contents, file_location, format)
             })?;
 
             let parsing_task = cx.background_executor().spawn(async move {
-                parse_markdown(&contents, file_location, Some(language_registry)).await
+                match format {
+                    Some(PreviewFormat::ReStructuredText) => parse_rst(&contents, file_location),
+                    Some(PreviewFormat::Org) => parse_org(&contents, file_location),
+                    Some(PreviewFormat::Markdown) | None => {
+                        parse_markdown(&contents, file_location, Some(language_registry)).await
+                    }
+                }
             });
             let contents = parsing_task.await;
             view.update(&mut cx, move |view, cx| {
@@ -374,6 +416,49 @@ impl MarkdownPreviewView {
         })
     }
 
+    /// Scrolls the preview to the block shown at the top of the editor.
+    fn editor_scrolled(&mut self, offset: usize, cx: &mut ViewContext<Self>) {
+        let top_block = self.get_block_index_under_cursor(offset..offset);
+        if top_block != self.top_block {
+            self.top_block = top_block;
+            self.list_state.scroll_to(ListOffset {
+                item_ix: top_block,
+                offset_in_item: px(0.),
+            });
+            cx.notify();
+        }
+    }
+
+    /// Scrolls the editor to the block shown at the top of the preview.
+    fn preview_scrolled(&mut self, top_block: usize, cx: &mut ViewContext<Self>) {
+        if top_block == self.top_block {
+            return;
+        }
+        self.top_block = top_block;
+
+        let Some(block) = self
+            .contents
+            .as_ref()
+            .and_then(|contents| contents.children.get(top_block))
+        else {
+            return;
+        };
+        let start = block.source_range().start;
+        if let Some(state) = &self.active_editor {
+            state.editor.update(cx, |editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let anchor = snapshot.anchor_before(start);
+                editor.set_scroll_anchor(
+                    ScrollAnchor {
+                        offset: gpui::Point::default(),
+                        anchor,
+                    },
+                    cx,
+                );
+            });
+        }
+    }
+
     fn move_cursor_to_block(&self, cx: &mut ViewContext<Self>, selection: Range<usize>) {
         if let Some(state) = &self.active_editor {
             state.editor.update(cx, |editor, cx| {
@@ -1,13 +1,16 @@
 use crate::markdown_elements::{
     HeadingLevel, Link, ParsedMarkdown, ParsedMarkdownBlockQuote, ParsedMarkdownCodeBlock,
-    ParsedMarkdownElement, ParsedMarkdownHeading, ParsedMarkdownListItem,
-    ParsedMarkdownListItemType, ParsedMarkdownTable, ParsedMarkdownTableAlignment,
+    ParsedMarkdownElement, ParsedMarkdownFootnoteDefinition, ParsedMarkdownHeading,
+    ParsedMarkdownListItem, ParsedMarkdownListItemType, ParsedMarkdownMath,
+    ParsedMarkdownMermaidDiagram, ParsedMarkdownTable, ParsedMarkdownTableAlignment,
     ParsedMarkdownTableRow, ParsedMarkdownText,
 };
+use crate::markdown_mermaid::{EdgeStyle, NodeShape};
 use gpui::{
-    div, px, rems, AbsoluteLength, AnyElement, DefiniteLength, Div, Element, ElementId,
-    HighlightStyle, Hsla, InteractiveText, IntoElement, Keystroke, Modifiers, ParentElement,
-    SharedString, Styled, StyledText, TextStyle, WeakView, WindowContext,
+    canvas, div, point, px, rems, AbsoluteLength, AnyElement, DefiniteLength, Div, Element,
+    ElementId, HighlightStyle, Hsla, InteractiveText, IntoElement, Keystroke, Modifiers,
+    ParentElement, Path, Pixels, Point, SharedString, Styled, StyledText, TextStyle, WeakView,
+    WindowContext,
 };
 use std::{
     ops::{Mul, Range},
@@ -115,6 +118,9 @@ pub fn render_markdown_block(block: &ParsedMarkdownElement, cx: &mut RenderConte
         Table(table) => render_markdown_table(table, cx),
         BlockQuote(block_quote) => render_markdown_block_quote(block_quote, cx),
         CodeBlock(code_block) => render_markdown_code_block(code_block, cx),
+        Math(math) => render_markdown_math(math, cx),
+        MermaidDiagram(diagram) => render_mermaid_diagram(diagram, cx),
+        FootnoteDefinition(footnote) => render_markdown_footnote_definition(footnote, cx),
         HorizontalRule(_) => render_markdown_rule(cx),
     }
 }
@@ -328,6 +334,259 @@ fn render_markdown_code_block(
         .into_any()
 }
 
+fn render_markdown_math(parsed: &ParsedMarkdownMath, cx: &mut RenderContext) -> AnyElement {
+    let lines = parsed
+        .contents
+        .lines()
+        .map(|line| div().child(SharedString::from(line.to_string())));
+
+    cx.with_common_p(v_flex())
+        .w_full()
+        .py_2()
+        .items_center()
+        .italic()
+        .text_color(cx.text_color)
+        .children(lines)
+        .into_any()
+}
+
+fn render_markdown_footnote_definition(
+    parsed: &ParsedMarkdownFootnoteDefinition,
+    cx: &mut RenderContext,
+) -> AnyElement {
+    let children: Vec<AnyElement> = parsed
+        .children
+        .iter()
+        .map(|child| render_markdown_block(child, cx))
+        .collect();
+
+    cx.with_common_p(h_flex())
+        .items_start()
+        .gap_2()
+        .child(
+            div()
+                .text_color(cx.text_muted_color)
+                .child(format!("{}.", parsed.number)),
+        )
+        .child(div().w_full().children(children))
+        .into_any()
+}
+
+/// Draws a flowchart, with its nodes laid out in boxes and its edges on a canvas below them.
+fn render_mermaid_diagram(
+    parsed: &ParsedMarkdownMermaidDiagram,
+    cx: &mut RenderContext,
+) -> AnyElement {
+    let flowchart = &parsed.flowchart;
+    let layout = &parsed.layout;
+    let edge_color = cx.text_muted_color;
+    let border_color = cx.border_color;
+    let background_color = cx.code_block_background_color;
+
+    let edges = flowchart
+        .edges
+        .iter()
+        .map(|edge| {
+            let ((start_x, start_y), (end_x, end_y)) = layout.edge_endpoints(edge);
+            (
+                point(px(start_x), px(start_y)),
+                point(px(end_x), px(end_y)),
+                edge.style,
+                edge.arrow,
+            )
+        })
+        .collect::<Vec<_>>();
+    let diamonds = flowchart
+        .nodes
+        .iter()
+        .zip(&layout.nodes)
+        .filter(|(node, _)| node.shape == NodeShape::Diamond)
+        .map(|(_, bounds)| *bounds)
+        .collect::<Vec<_>>();
+
+    let edges_canvas = canvas(
+        |_, _| {},
+        move |bounds, _, cx| {
+            for (start, end, style, arrow) in edges {
+                let (start, end) = (bounds.origin + start, bounds.origin + end);
+                let width = if style == EdgeStyle::Thick {
+                    px(3.)
+                } else {
+                    px(1.)
+                };
+                if style == EdgeStyle::Dotted {
+                    paint_dashed_line(start, end, width, edge_color, cx);
+                } else {
+                    paint_line(start, end, width, edge_color, cx);
+                }
+                if arrow {
+                    paint_arrow_head(start, end, edge_color, cx);
+                }
+            }
+
+            for diamond in diamonds {
+                let (center_x, center_y) = diamond.center();
+                let center = bounds.origin + point(px(center_x), px(center_y));
+                let (width, height) = (px(diamond.width / 2.), px(diamond.height / 2.));
+                paint_diamond(center, width, height, border_color, cx);
+                paint_diamond(
+                    center,
+                    width - px(1.5),
+                    height - px(1.),
+                    background_color,
+                    cx,
+                );
+            }
+        },
+    )
+    .absolute()
+    .size_full();
+
+    let nodes = flowchart
+        .nodes
+        .iter()
+        .zip(&layout.nodes)
+        .map(|(node, bounds)| {
+            div()
+                .absolute()
+                .left(px(bounds.x))
+                .top(px(bounds.y))
+                .w(px(bounds.width))
+                .h(px(bounds.height))
+                .flex()
+                .items_center()
+                .justify_center()
+                .text_color(cx.text_color)
+                .when(node.shape != NodeShape::Diamond, |this| {
+                    this.border_1()
+                        .border_color(cx.border_color)
+                        .bg(cx.code_block_background_color)
+                })
+                .map(|this| match node.shape {
+                    NodeShape::Rectangle | NodeShape::Diamond => this.rounded_sm(),
+                    NodeShape::Rounded => this.rounded_lg(),
+                    NodeShape::Circle => this.rounded_full(),
+                })
+                .child(SharedString::from(node.label.clone()))
+        })
+        .collect::<Vec<_>>();
+
+    let edge_labels = flowchart
+        .edges
+        .iter()
+        .filter_map(|edge| {
+            let label = edge.label.clone()?;
+            let ((start_x, start_y), (end_x, end_y)) = layout.edge_endpoints(edge);
+            Some(
+                div()
+                    .absolute()
+                    .left(px((start_x + end_x) / 2.))
+                    .top(px((start_y + end_y) / 2.))
+                    .px_1()
+                    .text_size(rems(0.75))
+                    .text_color(cx.text_muted_color)
+                    .bg(cx.code_block_background_color)
+                    .child(SharedString::from(label)),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    cx.with_common_p(div())
+        .w_full()
+        .flex()
+        .justify_center()
+        .child(
+            div()
+                .relative()
+                .flex_none()
+                .w(px(layout.width))
+                .h(px(layout.height))
+                .child(edges_canvas)
+                .children(nodes)
+                .children(edge_labels),
+        )
+        .into_any()
+}
+
+/// Paints a line as a thin quad along it.
+fn paint_line(
+    start: Point<Pixels>,
+    end: Point<Pixels>,
+    width: Pixels,
+    color: Hsla,
+    cx: &mut WindowContext,
+) {
+    let (dx, dy) = ((end.x - start.x).0, (end.y - start.y).0);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length == 0. {
+        return;
+    }
+    let normal = point(px(-dy / length), px(dx / length)) * (width.0 / 2.);
+
+    let mut path = Path::new(start + normal);
+    path.line_to(end + normal);
+    path.line_to(end - normal);
+    path.line_to(start - normal);
+    cx.paint_path(path, color);
+}
+
+fn paint_dashed_line(
+    start: Point<Pixels>,
+    end: Point<Pixels>,
+    width: Pixels,
+    color: Hsla,
+    cx: &mut WindowContext,
+) {
+    const DASH_LENGTH: f32 = 4.;
+
+    let (dx, dy) = ((end.x - start.x).0, (end.y - start.y).0);
+    let length = (dx * dx + dy * dy).sqrt();
+    let dash_count = (length / DASH_LENGTH) as usize;
+    for dash in (0..dash_count).step_by(2) {
+        let from = dash as f32 / dash_count as f32;
+        let to = (dash + 1) as f32 / dash_count as f32;
+        paint_line(
+            start + point(px(dx * from), px(dy * from)),
+            start + point(px(dx * to), px(dy * to)),
+            width,
+            color,
+            cx,
+        );
+    }
+}
+
+fn paint_arrow_head(start: Point<Pixels>, end: Point<Pixels>, color: Hsla, cx: &mut WindowContext) {
+    const ARROW_LENGTH: f32 = 8.;
+
+    let (dx, dy) = ((end.x - start.x).0, (end.y - start.y).0);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length == 0. {
+        return;
+    }
+    let direction = point(px(dx / length), px(dy / length));
+    let normal = point(px(-dy / length), px(dx / length));
+    let base = end - direction * ARROW_LENGTH;
+
+    let mut path = Path::new(end);
+    path.line_to(base + normal * (ARROW_LENGTH / 2.));
+    path.line_to(base - normal * (ARROW_LENGTH / 2.));
+    cx.paint_path(path, color);
+}
+
+fn paint_diamond(
+    center: Point<Pixels>,
+    half_width: Pixels,
+    half_height: Pixels,
+    color: Hsla,
+    cx: &mut WindowContext,
+) {
+    let mut path = Path::new(center - point(px(0.), half_height));
+    path.line_to(center + point(half_width, px(0.)));
+    path.line_to(center + point(px(0.), half_height));
+    path.line_to(center - point(half_width, px(0.)));
+    cx.paint_path(path, color);
+}
+
 fn render_markdown_paragraph(parsed: &ParsedMarkdownText, cx: &mut RenderContext) -> AnyElement {
     cx.with_common_p(div())
         .child(render_markdown_text(parsed, cx))
@@ -0,0 +1,1121 @@
+//! Parsers for the markup languages other than Markdown that the preview can render:
+//! reStructuredText and Org. They cover the common constructs, like headings, lists, code
+//! blocks, quotes and paragraphs with inline markup, and produce the same elements as the
+//! Markdown parser.
+
+use std::{ops::Range, path::PathBuf};
+
+use gpui::FontWeight;
+
+use crate::markdown_elements::{
+    HeadingLevel, Link, MarkdownHighlight, MarkdownHighlightStyle, ParsedMarkdown,
+    ParsedMarkdownBlockQuote, ParsedMarkdownCodeBlock, ParsedMarkdownElement,
+    ParsedMarkdownHeading, ParsedMarkdownListItem, ParsedMarkdownListItemType, ParsedMarkdownText,
+    ParsedRegion,
+};
+
+/// The formats of the documents the preview can render.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewFormat {
+    Markdown,
+    ReStructuredText,
+    Org,
+}
+
+impl PreviewFormat {
+    /// Returns the format of a file from its language, or from its extension for the
+    /// languages provided by extensions.
+    pub fn detect(language_name: Option<&str>, extension: Option<&str>) -> Option<Self> {
+        match language_name {
+            Some("Markdown") => return Some(Self::Markdown),
+            Some("reStructuredText" | "reST" | "RST") => return Some(Self::ReStructuredText),
+            Some("Org") => return Some(Self::Org),
+            _ => {}
+        }
+        match extension? {
+            "rst" => Some(Self::ReStructuredText),
+            "org" => Some(Self::Org),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Line<'a> {
+    text: &'a str,
+    /// The range of the line in the document, without its line ending.
+    range: Range<usize>,
+}
+
+impl<'a> Line<'a> {
+    fn indent(&self) -> usize {
+        self.text.len() - self.text.trim_start().len()
+    }
+
+    fn is_blank(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+
+    /// Removes the given number of leading bytes, which must be whitespace.
+    fn dedent(&self, len: usize) -> Line<'a> {
+        let len = len.min(self.indent());
+        Line {
+            text: &self.text[len..],
+            range: self.range.start + len..self.range.end,
+        }
+    }
+
+    /// Returns the part of the line starting at the given byte offset.
+    fn slice(&self, start: usize) -> Line<'a> {
+        Line {
+            text: &self.text[start..],
+            range: self.range.start + start..self.range.end,
+        }
+    }
+}
+
+fn split_lines(source: &str) -> Vec<Line<'_>> {
+    let mut offset = 0;
+    source
+        .split_inclusive('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len();
+            let text = line.trim_end_matches(['\n', '\r']);
+            Line {
+                text,
+                range: start..start + text.len(),
+            }
+        })
+        .collect()
+}
+
+fn heading_level(level: usize) -> HeadingLevel {
+    match level {
+        0 | 1 => HeadingLevel::H1,
+        2 => HeadingLevel::H2,
+        3 => HeadingLevel::H3,
+        4 => HeadingLevel::H4,
+        5 => HeadingLevel::H5,
+        _ => HeadingLevel::H6,
+    }
+}
+
+/// Tracks the indentation of nested list items to compute their depth.
+#[derive(Default)]
+struct ListDepth {
+    indents: Vec<usize>,
+}
+
+impl ListDepth {
+    fn depth_for_indent(&mut self, indent: usize) -> u16 {
+        while self.indents.last().map_or(false, |&last| last > indent) {
+            self.indents.pop();
+        }
+        if self.indents.last() != Some(&indent) {
+            self.indents.push(indent);
+        }
+        self.indents.len() as u16
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InlineStyle {
+    Bold,
+    Italic,
+    Underline,
+    Strikethrough,
+    Code,
+}
+
+struct InlineLink {
+    /// The length of the link markup in bytes.
+    len: usize,
+    text: String,
+    url: Option<String>,
+}
+
+/// The inline markup of a format.
+struct InlineSyntax {
+    /// The delimiters around styled text, longest first.
+    delimiters: &'static [(&'static str, InlineStyle)],
+    parse_link: fn(&str) -> Option<InlineLink>,
+}
+
+fn is_word_boundary(c: Option<char>) -> bool {
+    c.map_or(true, |c| !c.is_alphanumeric())
+}
+
+/// Returns the length of the text between the delimiter at the start of `text` and its
+/// closing delimiter.
+fn delimited_len(text: &str, delimiter: &str) -> Option<usize> {
+    let inner = text.strip_prefix(delimiter)?;
+    if inner.starts_with(char::is_whitespace) || inner.starts_with(delimiter) {
+        return None;
+    }
+    inner.match_indices(delimiter).find_map(|(ix, _)| {
+        let before = inner[..ix].chars().next_back()?;
+        let after = inner[ix + delimiter.len()..].chars().next();
+        (!before.is_whitespace() && is_word_boundary(after)).then_some(ix)
+    })
+}
+
+fn bare_url_len(text: &str) -> Option<usize> {
+    if !text.starts_with("http://") && !text.starts_with("https://") {
+        return None;
+    }
+    let len = text
+        .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"'))
+        .unwrap_or(text.len());
+    Some(
+        text[..len]
+            .trim_end_matches(['.', ',', ';', ':', ')', ']'])
+            .len(),
+    )
+}
+
+fn push_link(
+    parsed: &mut ParsedMarkdownText,
+    link_text: &str,
+    url: Option<String>,
+    file_location_directory: &Option<PathBuf>,
+) {
+    let start = parsed.contents.len();
+    parsed.contents.push_str(link_text);
+    let Some(link) = url.and_then(|url| Link::identify(file_location_directory.clone(), url))
+    else {
+        return;
+    };
+    let range = start..parsed.contents.len();
+    parsed.highlights.push((
+        range.clone(),
+        MarkdownHighlight::Style(MarkdownHighlightStyle {
+            underline: true,
+            ..Default::default()
+        }),
+    ));
+    parsed.region_ranges.push(range);
+    parsed.regions.push(ParsedRegion {
+        code: false,
+        link: Some(link),
+    });
+}
+
+fn parse_inline(
+    text: &str,
+    source_range: Range<usize>,
+    syntax: &InlineSyntax,
+    file_location_directory: &Option<PathBuf>,
+) -> ParsedMarkdownText {
+    let mut parsed = ParsedMarkdownText {
+        source_range,
+        contents: String::new(),
+        highlights: Vec::new(),
+        region_ranges: Vec::new(),
+        regions: Vec::new(),
+    };
+
+    let mut ix = 0;
+    'outer: while let Some(c) = text[ix..].chars().next() {
+        let rest = &text[ix..];
+        if let Some(link) = (syntax.parse_link)(rest) {
+            push_link(&mut parsed, &link.text, link.url, file_location_directory);
+            ix += link.len;
+            continue;
+        }
+
+        if is_word_boundary(text[..ix].chars().next_back()) {
+            if let Some(len) = bare_url_len(rest) {
+                let url = &rest[..len];
+                push_link(
+                    &mut parsed,
+                    url,
+                    Some(url.to_string()),
+                    file_location_directory,
+                );
+                ix += len;
+                continue;
+            }
+
+            for &(delimiter, style) in syntax.delimiters {
+                let Some(inner_len) = delimited_len(rest, delimiter) else {
+                    continue;
+                };
+                let start = parsed.contents.len();
+                parsed
+                    .contents
+                    .push_str(&rest[delimiter.len()..delimiter.len() + inner_len]);
+                let range = start..parsed.contents.len();
+                ix += inner_len + 2 * delimiter.len();
+
+                let mut highlight = MarkdownHighlightStyle::default();
+                match style {
+                    InlineStyle::Bold => highlight.weight = FontWeight::BOLD,
+                    InlineStyle::Italic => highlight.italic = true,
+                    InlineStyle::Underline => highlight.underline = true,
+                    InlineStyle::Strikethrough => highlight.strikethrough = true,
+                    InlineStyle::Code => {
+                        parsed.region_ranges.push(range);
+                        parsed.regions.push(ParsedRegion {
+                            code: true,
+                            link: None,
+                        });
+                        continue 'outer;
+                    }
+                }
+                parsed
+                    .highlights
+                    .push((range, MarkdownHighlight::Style(highlight)));
+                continue 'outer;
+            }
+        }
+
+        parsed.contents.push(c);
+        ix += c.len_utf8();
+    }
+    parsed
+}
+
+/// Joins the text of consecutive lines into a single paragraph.
+fn join_lines(lines: &[Line]) -> (String, Range<usize>) {
+    let text = lines
+        .iter()
+        .map(|line| line.text.trim())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let range = match (lines.first(), lines.last()) {
+        (Some(first), Some(last)) => first.range.start..last.range.end,
+        _ => 0..0,
+    };
+    (text, range)
+}
+
+fn code_block(
+    lines: &[Line],
+    language: Option<String>,
+    source_range: Range<usize>,
+) -> ParsedMarkdownElement {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.is_blank())
+        .map(Line::indent)
+        .min()
+        .unwrap_or(0);
+    let mut code = lines
+        .iter()
+        .map(|line| line.dedent(indent).text)
+        .skip_while(|line| line.trim().is_empty())
+        .collect::<Vec<_>>();
+    while code.last().map_or(false, |line| line.trim().is_empty()) {
+        code.pop();
+    }
+
+    ParsedMarkdownElement::CodeBlock(ParsedMarkdownCodeBlock {
+        source_range,
+        language,
+        contents: code.join("\n").into(),
+        highlights: None,
+    })
+}
+
+/// A list item marker, like `- ` or `2. `, followed by an optional task checkbox.
+struct ListItemMarker {
+    item_type: ParsedMarkdownListItemType,
+    /// The length of the marker and the whitespace after it.
+    len: usize,
+}
+
+fn parse_list_item_marker(
+    line: &Line,
+    bullets: &[char],
+    previous_number: Option<u64>,
+) -> Option<ListItemMarker> {
+    let indent = line.indent();
+    let text = &line.text[indent..];
+
+    let (mut item_type, marker_len) = if text.starts_with(bullets) {
+        (ParsedMarkdownListItemType::Unordered, 1)
+    } else {
+        let digits = text.bytes().take_while(u8::is_ascii_digit).count();
+        let (number, digits) = if digits > 0 && digits <= 9 {
+            (text[..digits].parse().ok()?, digits)
+        } else if text.starts_with('#') {
+            // reStructuredText numbers the items starting with `#.` automatically.
+            (previous_number.map_or(1, |number| number + 1), 1)
+        } else {
+            return None;
+        };
+        if !text[digits..].starts_with(['.', ')']) {
+            return None;
+        }
+        (ParsedMarkdownListItemType::Ordered(number), digits + 1)
+    };
+
+    let rest = &text[marker_len..];
+    let whitespace_len = rest.len() - rest.trim_start().len();
+    if whitespace_len == 0 && !rest.is_empty() {
+        return None;
+    }
+    let mut len = marker_len + whitespace_len;
+
+    let rest = rest.trim_start();
+    for (checkbox, checked) in [("[ ]", false), ("[x]", true), ("[X]", true)] {
+        if rest.starts_with(checkbox) {
+            let start = line.range.start + indent + len;
+            item_type = ParsedMarkdownListItemType::Task(checked, start..start + checkbox.len());
+            len += checkbox.len() + rest[checkbox.len()..].len()
+                - rest[checkbox.len()..].trim_start().len();
+            break;
+        }
+    }
+
+    Some(ListItemMarker {
+        item_type,
+        len: indent + len,
+    })
+}
+
+/// Parses the list item starting at the first line, returning it with the number of lines
+/// it spans.
+fn parse_list_item(
+    lines: &[Line],
+    marker: ListItemMarker,
+    depth: u16,
+    is_item_start: impl Fn(&Line) -> bool,
+    syntax: &InlineSyntax,
+    file_location_directory: &Option<PathBuf>,
+) -> (ParsedMarkdownElement, usize) {
+    let first_line = lines[0].slice(marker.len);
+    let mut item_lines = vec![first_line];
+    let mut len = 1;
+    for line in &lines[1..] {
+        if line.is_blank() || line.indent() < marker.len.min(lines[0].indent() + 1) {
+            break;
+        }
+        if is_item_start(line) {
+            break;
+        }
+        item_lines.push(line.clone());
+        len += 1;
+    }
+
+    let (text, text_range) = join_lines(&item_lines);
+    let content = if text.is_empty() {
+        Vec::new()
+    } else {
+        vec![ParsedMarkdownElement::Paragraph(parse_inline(
+            &text,
+            text_range,
+            syntax,
+            file_location_directory,
+        ))]
+    };
+    let item = ParsedMarkdownElement::ListItem(ParsedMarkdownListItem {
+        source_range: lines[0].range.start..lines[len - 1].range.end,
+        depth,
+        item_type: marker.item_type,
+        content,
+    });
+    (item, len)
+}
+
+const ORG_SYNTAX: InlineSyntax = InlineSyntax {
+    delimiters: &[
+        ("*", InlineStyle::Bold),
+        ("/", InlineStyle::Italic),
+        ("_", InlineStyle::Underline),
+        ("+", InlineStyle::Strikethrough),
+        ("=", InlineStyle::Code),
+        ("~", InlineStyle::Code),
+    ],
+    parse_link: parse_org_link,
+};
+
+/// Parses links like `[[https://orgmode.org][Org]]` and `[[https://orgmode.org]]`.
+fn parse_org_link(text: &str) -> Option<InlineLink> {
+    let inner = text.strip_prefix("[[")?;
+    let end = inner.find("]]")?;
+    let inner = &inner[..end];
+    let (url, description) = inner.split_once("][").unwrap_or((inner, inner));
+    Some(InlineLink {
+        len: end + 4,
+        text: description.to_string(),
+        url: Some(url.to_string()),
+    })
+}
+
+/// Returns the keyword and the argument of a line like `#+BEGIN_SRC rust`.
+fn org_keyword<'a>(line: &Line<'a>) -> Option<(String, &'a str)> {
+    let text = line.text.trim_start().strip_prefix("#+")?;
+    let (keyword, argument) = text
+        .split_once(|c: char| c.is_whitespace())
+        .unwrap_or((text, ""));
+    Some((
+        keyword.trim_end_matches(':').to_ascii_uppercase(),
+        argument.trim(),
+    ))
+}
+
+fn org_heading_level(line: &Line) -> Option<usize> {
+    let stars = line.text.bytes().take_while(|&byte| byte == b'*').count();
+    (stars > 0 && line.text[stars..].starts_with(' ')).then_some(stars)
+}
+
+fn org_list_item_marker(line: &Line, previous_number: Option<u64>) -> Option<ListItemMarker> {
+    // Stars only start list items when indented, otherwise they start headings.
+    let bullets: &[char] = if line.indent() > 0 {
+        &['-', '+', '*']
+    } else {
+        &['-', '+']
+    };
+    parse_list_item_marker(line, bullets, previous_number)
+}
+
+fn is_org_block_start(line: &Line) -> bool {
+    line.is_blank()
+        || org_heading_level(line).is_some()
+        || org_keyword(line).is_some()
+        || org_list_item_marker(line, None).is_some()
+}
+
+/// Parses an Org document into the elements of the preview.
+pub fn parse_org(source: &str, file_location_directory: Option<PathBuf>) -> ParsedMarkdown {
+    let lines = split_lines(source);
+    ParsedMarkdown {
+        children: parse_org_blocks(&lines, &file_location_directory),
+    }
+}
+
+fn parse_org_blocks(
+    lines: &[Line],
+    file_location_directory: &Option<PathBuf>,
+) -> Vec<ParsedMarkdownElement> {
+    let mut elements = Vec::new();
+    let mut list_depth = ListDepth::default();
+    let mut previous_number = None;
+
+    let mut ix = 0;
+    while ix < lines.len() {
+        let line = &lines[ix];
+        if line.is_blank() {
+            ix += 1;
+            continue;
+        }
+
+        if let Some(marker) = org_list_item_marker(line, previous_number) {
+            previous_number = match marker.item_type {
+                ParsedMarkdownListItemType::Ordered(number) => Some(number),
+                _ => None,
+            };
+            let depth = list_depth.depth_for_indent(line.indent());
+            let (item, len) = parse_list_item(
+                &lines[ix..],
+                marker,
+                depth,
+                |line| org_list_item_marker(line, None).is_some(),
+                &ORG_SYNTAX,
+                file_location_directory,
+            );
+            elements.push(item);
+            ix += len;
+            continue;
+        }
+        list_depth = ListDepth::default();
+        previous_number = None;
+
+        if let Some(level) = org_heading_level(line) {
+            let title = line.slice(level + 1);
+            // Drop the tags at the end of the heading, like `:work:urgent:`.
+            let text = match title.text.trim_end().rsplit_once(char::is_whitespace) {
+                Some((text, tags))
+                    if tags.len() > 2 && tags.starts_with(':') && tags.ends_with(':') =>
+                {
+                    text.trim_end()
+                }
+                _ => title.text.trim(),
+            };
+            elements.push(ParsedMarkdownElement::Heading(ParsedMarkdownHeading {
+                source_range: line.range.clone(),
+                level: heading_level(level),
+                contents: parse_inline(
+                    text,
+                    title.range.clone(),
+                    &ORG_SYNTAX,
+                    file_location_directory,
+                ),
+            }));
+            ix += 1;
+            continue;
+        }
+
+        if let Some((keyword, argument)) = org_keyword(line) {
+            if let Some(block_type) = keyword.strip_prefix("BEGIN_") {
+                let end_keyword = format!("END_{block_type}");
+                let end = lines[ix + 1..]
+                    .iter()
+                    .position(|line| {
+                        org_keyword(line).map_or(false, |(keyword, _)| keyword == end_keyword)
+                    })
+                    .map_or(lines.len(), |position| ix + 1 + position);
+                let body = &lines[ix + 1..end];
+                let source_range =
+                    line.range.start..lines.get(end).unwrap_or(&lines[end - 1]).range.end;
+
+                elements.push(match block_type {
+                    "QUOTE" | "CENTER" | "VERSE" => {
+                        ParsedMarkdownElement::BlockQuote(ParsedMarkdownBlockQuote {
+                            source_range,
+                            children: parse_org_blocks(body, file_location_directory),
+                        })
+                    }
+                    _ => {
+                        let language = (block_type == "SRC")
+                            .then(|| argument.split_whitespace().next())
+                            .flatten()
+                            .map(str::to_string);
+                        code_block(body, language, source_range)
+                    }
+                });
+                ix = end + 1;
+                continue;
+            }
+
+            if keyword == "TITLE" && !argument.is_empty() {
+                let start = line.range.end - argument.len();
+                elements.push(ParsedMarkdownElement::Heading(ParsedMarkdownHeading {
+                    source_range: line.range.clone(),
+                    level: HeadingLevel::H1,
+                    contents: parse_inline(
+                        argument,
+                        start..line.range.end,
+                        &ORG_SYNTAX,
+                        file_location_directory,
+                    ),
+                }));
+            }
+            // Other keywords are settings of the document.
+            ix += 1;
+            continue;
+        }
+
+        let text = line.text.trim();
+        if text.starts_with("# ") || text == "#" {
+            ix += 1;
+            continue;
+        }
+        if text.len() >= 5 && text.bytes().all(|byte| byte == b'-') {
+            elements.push(ParsedMarkdownElement::HorizontalRule(line.range.clone()));
+            ix += 1;
+            continue;
+        }
+
+        let len = 1 + lines[ix + 1..]
+            .iter()
+            .take_while(|line| !is_org_block_start(line))
+            .count();
+        let (text, range) = join_lines(&lines[ix..ix + len]);
+        elements.push(ParsedMarkdownElement::Paragraph(parse_inline(
+            &text,
+            range,
+            &ORG_SYNTAX,
+            file_location_directory,
+        )));
+        ix += len;
+    }
+    elements
+}
+
+const RST_SYNTAX: InlineSyntax = InlineSyntax {
+    delimiters: &[
+        ("``", InlineStyle::Code),
+        ("**", InlineStyle::Bold),
+        ("*", InlineStyle::Italic),
+    ],
+    parse_link: parse_rst_link,
+};
+
+/// Parses hyperlink references like `` `Docutils <https://docutils.sourceforge.io>`_ ``.
+fn parse_rst_link(text: &str) -> Option<InlineLink> {
+    let inner = text.strip_prefix('`')?;
+    let end = inner.find('`')?;
+    let suffix_len = if inner[end..].starts_with("`__") {
+        3
+    } else if inner[end..].starts_with("`_") {
+        2
+    } else {
+        return None;
+    };
+    let inner = &inner[..end];
+    let (text, url) = match inner
+        .strip_suffix('>')
+        .and_then(|inner| inner.rsplit_once('<'))
+    {
+        Some((text, url)) if !text.trim().is_empty() => (text.trim(), Some(url.to_string())),
+        Some((_, url)) => (url, Some(url.to_string())),
+        None => (inner, None),
+    };
+    Some(InlineLink {
+        len: 1 + end + suffix_len,
+        text: text.to_string(),
+        url,
+    })
+}
+
+/// Returns the character of a line made of a single repeated punctuation character, like
+/// the `====` under section titles.
+fn rst_adornment(line: &Line) -> Option<char> {
+    let text = line.text.trim_end();
+    let c = text.chars().next()?;
+    (c.is_ascii_punctuation() && text.len() >= 2 && text.chars().all(|other| other == c))
+        .then_some(c)
+}
+
+fn rst_list_item_marker(line: &Line, previous_number: Option<u64>) -> Option<ListItemMarker> {
+    parse_list_item_marker(line, &['-', '*', '+', '•'], previous_number)
+}
+
+/// Returns the name and the argument of a directive like `.. code-block:: python`.
+fn rst_directive<'a>(line: &Line<'a>) -> Option<(&'a str, &'a str)> {
+    let text = line.text.trim_start().strip_prefix(".. ")?;
+    let (name, argument) = text.split_once("::")?;
+    (!name.contains(char::is_whitespace)).then(|| (name, argument.trim()))
+}
+
+/// Returns the number of lines of the block indented under the first line.
+fn rst_indented_block_len(lines: &[Line], indent: usize) -> usize {
+    let len = lines[1..]
+        .iter()
+        .take_while(|line| line.is_blank() || line.indent() > indent)
+        .count();
+    let trailing_blank_lines = lines[1..=len]
+        .iter()
+        .rev()
+        .take_while(|line| line.is_blank())
+        .count();
+    1 + len - trailing_blank_lines
+}
+
+/// Parses a reStructuredText document into the elements of the preview.
+pub fn parse_rst(source: &str, file_location_directory: Option<PathBuf>) -> ParsedMarkdown {
+    let lines = split_lines(source);
+    let mut section_styles = Vec::new();
+    ParsedMarkdown {
+        children: parse_rst_blocks(&lines, &mut section_styles, &file_location_directory),
+    }
+}
+
+/// Returns the level of the section titles with the given adornment character and overline,
+/// which is the order in which the style first appears in the document.
+fn rst_heading_level(section_styles: &mut Vec<(char, bool)>, style: (char, bool)) -> HeadingLevel {
+    let level = section_styles
+        .iter()
+        .position(|other| *other == style)
+        .unwrap_or_else(|| {
+            section_styles.push(style);
+            section_styles.len() - 1
+        });
+    heading_level(level + 1)
+}
+
+fn parse_rst_blocks(
+    lines: &[Line],
+    section_styles: &mut Vec<(char, bool)>,
+    file_location_directory: &Option<PathBuf>,
+) -> Vec<ParsedMarkdownElement> {
+    let mut elements = Vec::new();
+    let mut list_depth = ListDepth::default();
+    let mut previous_number = None;
+
+    let mut ix = 0;
+    while ix < lines.len() {
+        let line = &lines[ix];
+        if line.is_blank() {
+            ix += 1;
+            continue;
+        }
+        let next_line = lines.get(ix + 1);
+        let previous_line_is_blank = ix == 0 || lines[ix - 1].is_blank();
+
+        // Section titles, underlined and optionally overlined.
+        if let Some(adornment) = rst_adornment(line) {
+            let title = next_line.filter(|line| !line.is_blank());
+            let underline = lines.get(ix + 2);
+            if let (Some(title), Some(underline)) = (title, underline) {
+                if rst_adornment(underline) == Some(adornment) {
+                    elements.push(ParsedMarkdownElement::Heading(ParsedMarkdownHeading {
+                        source_range: line.range.start..underline.range.end,
+                        level: rst_heading_level(section_styles, (adornment, true)),
+                        contents: parse_inline(
+                            title.text.trim(),
+                            title.range.clone(),
+                            &RST_SYNTAX,
+                            file_location_directory,
+                        ),
+                    }));
+                    ix += 3;
+                    continue;
+                }
+            }
+            if previous_line_is_blank
+                && next_line.map_or(true, Line::is_blank)
+                && line.text.trim_end().len() >= 4
+            {
+                elements.push(ParsedMarkdownElement::HorizontalRule(line.range.clone()));
+                ix += 1;
+                continue;
+            }
+        }
+        if let Some(adornment) = next_line.and_then(rst_adornment) {
+            let underline = &lines[ix + 1];
+            if line.indent() == 0
+                && underline.text.trim_end().chars().count() >= line.text.trim_end().chars().count()
+            {
+                elements.push(ParsedMarkdownElement::Heading(ParsedMarkdownHeading {
+                    source_range: line.range.start..underline.range.end,
+                    level: rst_heading_level(section_styles, (adornment, false)),
+                    contents: parse_inline(
+                        line.text.trim(),
+                        line.range.clone(),
+                        &RST_SYNTAX,
+                        file_location_directory,
+                    ),
+                }));
+                ix += 2;
+                continue;
+            }
+        }
+
+        if let Some(marker) = rst_list_item_marker(line, previous_number) {
+            previous_number = match marker.item_type {
+                ParsedMarkdownListItemType::Ordered(number) => Some(number),
+                _ => None,
+            };
+            let depth = list_depth.depth_for_indent(line.indent());
+            let (item, len) = parse_list_item(
+                &lines[ix..],
+                marker,
+                depth,
+                |line| rst_list_item_marker(line, None).is_some(),
+                &RST_SYNTAX,
+                file_location_directory,
+            );
+            elements.push(item);
+            ix += len;
+            continue;
+        }
+        list_depth = ListDepth::default();
+        previous_number = None;
+
+        if line.text.trim_start().starts_with("..") {
+            let len = rst_indented_block_len(&lines[ix..], line.indent());
+            let source_range = line.range.start..lines[ix + len - 1].range.end;
+            let body = &lines[ix + 1..ix + len];
+            match rst_directive(line) {
+                Some(("code" | "code-block" | "sourcecode", argument)) => {
+                    // Skip the options of the directive, like `:linenos:`.
+                    let options_len = body
+                        .iter()
+                        .take_while(|line| line.text.trim_start().starts_with(':'))
+                        .count();
+                    let language = (!argument.is_empty()).then(|| argument.to_string());
+                    elements.push(code_block(&body[options_len..], language, source_range));
+                }
+                Some((
+                    name @ ("note" | "tip" | "hint" | "important" | "warning" | "caution"
+                    | "danger" | "error" | "attention" | "admonition"),
+                    argument,
+                )) => {
+                    let mut title = name.to_string();
+                    title[..1].make_ascii_uppercase();
+                    let mut children = vec![ParsedMarkdownElement::Paragraph(parse_inline(
+                        &format!("**{title}** {argument}"),
+                        line.range.clone(),
+                        &RST_SYNTAX,
+                        file_location_directory,
+                    ))];
+                    let indent = body
+                        .iter()
+                        .filter(|line| !line.is_blank())
+                        .map(Line::indent)
+                        .min()
+                        .unwrap_or(0);
+                    let body = body
+                        .iter()
+                        .map(|line| line.dedent(indent))
+                        .collect::<Vec<_>>();
+                    children.extend(parse_rst_blocks(
+                        &body,
+                        section_styles,
+                        file_location_directory,
+                    ));
+                    elements.push(ParsedMarkdownElement::BlockQuote(
+                        ParsedMarkdownBlockQuote {
+                            source_range,
+                            children,
+                        },
+                    ));
+                }
+                // Comments, link targets and unsupported directives aren't rendered.
+                _ => {}
+            }
+            ix += len;
+            continue;
+        }
+
+        if line.indent() > 0 {
+            let len = rst_indented_block_len(&lines[ix..], 0);
+            let source_range = line.range.start..lines[ix + len - 1].range.end;
+            let indent = lines[ix..ix + len]
+                .iter()
+                .filter(|line| !line.is_blank())
+                .map(Line::indent)
+                .min()
+                .unwrap_or(0);
+            let body = lines[ix..ix + len]
+                .iter()
+                .map(|line| line.dedent(indent))
+                .collect::<Vec<_>>();
+            elements.push(ParsedMarkdownElement::BlockQuote(
+                ParsedMarkdownBlockQuote {
+                    source_range,
+                    children: parse_rst_blocks(&body, section_styles, file_location_directory),
+                },
+            ));
+            ix += len;
+            continue;
+        }
+
+        let len = 1 + lines[ix + 1..]
+            .iter()
+            .take_while(|line| !line.is_blank() && line.indent() == 0)
+            .count();
+        let paragraph = &lines[ix..ix + len];
+        ix += len;
+
+        // A paragraph ending with `::` introduces the indented literal block after it.
+        let (mut text, range) = join_lines(paragraph);
+        let literal_block = text.ends_with("::");
+        if literal_block {
+            text.truncate(text.len() - 1);
+            if text == ":" {
+                text.clear();
+            } else if text.ends_with(" :") {
+                text.truncate(text.len() - 2);
+            }
+        }
+        if !text.is_empty() {
+            elements.push(ParsedMarkdownElement::Paragraph(parse_inline(
+                &text,
+                range,
+                &RST_SYNTAX,
+                file_location_directory,
+            )));
+        }
+
+        if literal_block {
+            let Some(start) = lines[ix..]
+                .iter()
+                .position(|line| !line.is_blank())
+                .map(|position| ix + position)
+            else {
+                continue;
+            };
+            if lines[start].indent() == 0 {
+                continue;
+            }
+            let len = rst_indented_block_len(&lines[start..], 0);
+            let source_range = lines[start].range.start..lines[start + len - 1].range.end;
+            elements.push(code_block(&lines[start..start + len], None, source_range));
+            ix = start + len;
+        }
+    }
+    elements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_contents(element: &ParsedMarkdownElement) -> &str {
+        match element {
+            ParsedMarkdownElement::Heading(heading) => &heading.contents.contents,
+            ParsedMarkdownElement::Paragraph(text) => &text.contents,
+            ParsedMarkdownElement::ListItem(item) => match item.content.first() {
+                Some(ParsedMarkdownElement::Paragraph(text)) => &text.contents,
+                _ => "",
+            },
+            ParsedMarkdownElement::CodeBlock(code_block) => code_block.contents.as_ref(),
+            _ => "",
+        }
+    }
+
+    fn heading_levels(elements: &[ParsedMarkdownElement]) -> Vec<(&HeadingLevel, &str)> {
+        elements
+            .iter()
+            .filter_map(|element| match element {
+                ParsedMarkdownElement::Heading(heading) => {
+                    Some((&heading.level, heading.contents.contents.as_str()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_preview_format() {
+        assert_eq!(
+            PreviewFormat::detect(Some("Markdown"), Some("md")),
+            Some(PreviewFormat::Markdown)
+        );
+        assert_eq!(
+            PreviewFormat::detect(None, Some("rst")),
+            Some(PreviewFormat::ReStructuredText)
+        );
+        assert_eq!(
+            PreviewFormat::detect(Some("Plain Text"), Some("org")),
+            Some(PreviewFormat::Org)
+        );
+        assert_eq!(PreviewFormat::detect(Some("Rust"), Some("rs")), None);
+    }
+
+    #[test]
+    fn test_parse_org() {
+        let source = "\
+#+TITLE: Notes
+* Tasks :work:
+- [ ] Write *docs*
+  for the preview
+- [X] Ship it
+  1. nested
+** Code
+#+BEGIN_SRC rust
+fn main() {}
+#+END_SRC
+Some =code= and a [[https://orgmode.org][link]].
+";
+        let parsed = parse_org(source, None);
+        let children = &parsed.children;
+        assert_eq!(children.len(), 8);
+
+        assert_eq!(
+            heading_levels(children),
+            vec![
+                (&HeadingLevel::H1, "Notes"),
+                (&HeadingLevel::H1, "Tasks"),
+                (&HeadingLevel::H2, "Code"),
+            ]
+        );
+
+        let ParsedMarkdownElement::ListItem(item) = &children[2] else {
+            panic!("expected a list item, got {:?}", children[2]);
+        };
+        assert_eq!(item.depth, 1);
+        assert_eq!(
+            item.item_type,
+            ParsedMarkdownListItemType::Task(false, 32..35)
+        );
+        assert_eq!(&source[32..35], "[ ]");
+        assert_eq!(text_contents(&children[2]), "Write docs for the preview");
+
+        let ParsedMarkdownElement::ListItem(item) = &children[4] else {
+            panic!("expected a list item, got {:?}", children[4]);
+        };
+        assert_eq!(item.depth, 2);
+        assert_eq!(item.item_type, ParsedMarkdownListItemType::Ordered(1));
+
+        let ParsedMarkdownElement::CodeBlock(code_block) = &children[6] else {
+            panic!("expected a code block, got {:?}", children[6]);
+        };
+        assert_eq!(code_block.language.as_deref(), Some("rust"));
+        assert_eq!(code_block.contents.as_ref(), "fn main() {}");
+
+        let ParsedMarkdownElement::Paragraph(paragraph) = &children[7] else {
+            panic!("expected a paragraph, got {:?}", children[7]);
+        };
+        assert_eq!(paragraph.contents, "Some code and a link.");
+        assert_eq!(paragraph.region_ranges, vec![5..9, 16..20]);
+    }
+
+    #[test]
+    fn test_parse_rst() {
+        let source = "\
+=====
+Title
+=====
+
+Section
+-------
+
+Some **bold** text with a `link <https://docutils.sourceforge.io>`_.
+
+* One
+* Two
+
+  #. Nested
+
+Example::
+
+    print(\"hello\")
+
+.. code-block:: rust
+
+   fn main() {}
+
+Other section
+-------------
+
+.. note:: Be careful.
+
+----
+
+.. a comment
+";
+        let parsed = parse_rst(source, None);
+        let children = &parsed.children;
+
+        assert_eq!(
+            heading_levels(children),
+            vec![
+                (&HeadingLevel::H1, "Title"),
+                (&HeadingLevel::H2, "Section"),
+                (&HeadingLevel::H2, "Other section"),
+            ]
+        );
+
+        let contents = children.iter().map(text_contents).collect::<Vec<_>>();
+        assert_eq!(
+            contents,
+            vec![
+                "Title",
+                "Section",
+                "Some bold text with a link.",
+                "One",
+                "Two",
+                "Nested",
+                "Example:",
+                "print(\"hello\")",
+                "fn main() {}",
+                "Other section",
+                "",
+                "",
+            ]
+        );
+
+        let ParsedMarkdownElement::ListItem(item) = &children[5] else {
+            panic!("expected a list item, got {:?}", children[5]);
+        };
+        assert_eq!(item.depth, 2);
+        assert_eq!(item.item_type, ParsedMarkdownListItemType::Ordered(1));
+
+        let ParsedMarkdownElement::CodeBlock(code_block) = &children[8] else {
+            panic!("expected a code block, got {:?}", children[8]);
+        };
+        assert_eq!(code_block.language.as_deref(), Some("rust"));
+
+        assert!(matches!(children[10], ParsedMarkdownElement::BlockQuote(_)));
+        assert!(matches!(
+            children[11],
+            ParsedMarkdownElement::HorizontalRule(_)
+        ));
+    }
+}
@@ -134,12 +134,68 @@ pub fn keymap_file() -> &'static PathBuf {
     KEYMAP_FILE.get_or_init(|| config_dir().join("keymap.json"))
 }
 
+/// Returns the path to the snippets directory.
+pub fn snippets_dir() -> &'static PathBuf {
+    static SNIPPETS_DIR: OnceLock<PathBuf> = OnceLock::new();
+    SNIPPETS_DIR.get_or_init(|| config_dir().join("snippets"))
+}
+
 /// Returns the path to the `tasks.json` file.
 pub fn tasks_file() -> &'static PathBuf {
     static TASKS_FILE: OnceLock<PathBuf> = OnceLock::new();
     TASKS_FILE.get_or_init(|| config_dir().join("tasks.json"))
 }
 
+/// Returns the path to the personal dictionary file, listing the words that
+/// aren't reported as misspelled.
+pub fn personal_dictionary_file() -> &'static PathBuf {
+    static PERSONAL_DICTIONARY_FILE: OnceLock<PathBuf> = OnceLock::new();
+    PERSONAL_DICTIONARY_FILE.get_or_init(|| config_dir().join("personal_dictionary.txt"))
+}
+
+/// Returns the path to the dictionaries directory.
+///
+/// This is where the Hunspell dictionaries used for spell checking can be installed.
+pub fn dictionaries_dir() -> &'static PathBuf {
+    static DICTIONARIES_DIR: OnceLock<PathBuf> = OnceLock::new();
+    DICTIONARIES_DIR.get_or_init(|| config_dir().join("dictionaries"))
+}
+
+/// Returns the path to the local history directory.
+///
+/// This is where the snapshots of saved files are stored.
+pub fn local_history_dir() -> &'static PathBuf {
+    static LOCAL_HISTORY_DIR: OnceLock<PathBuf> = OnceLock::new();
+    LOCAL_HISTORY_DIR.get_or_init(|| support_dir().join("local_history"))
+}
+
+/// Returns the path to the buffer recovery directory.
+///
+/// This is where the journals of unsaved changes are stored, so that they can be
+/// recovered after a crash.
+pub fn recovery_dir() -> &'static PathBuf {
+    static RECOVERY_DIR: OnceLock<PathBuf> = OnceLock::new();
+    RECOVERY_DIR.get_or_init(|| support_dir().join("recovery"))
+}
+
+/// Returns the path to the file storing which folders are trusted.
+///
+/// Folders that aren't trusted are opened in restricted mode, without running the
+/// tasks, language servers and environment they configure.
+pub fn trusted_folders_file() -> &'static PathBuf {
+    static TRUSTED_FOLDERS_FILE: OnceLock<PathBuf> = OnceLock::new();
+    TRUSTED_FOLDERS_FILE.get_or_init(|| support_dir().join("trusted_folders.json"))
+}
+
+/// Returns the path to the settings sync directory.
+///
+/// This is where the state of the last settings sync, the clone of the sync repository,
+/// and the files replaced by synced files are stored.
+pub fn settings_sync_dir() -> &'static PathBuf {
+    static SETTINGS_SYNC_DIR: OnceLock<PathBuf> = OnceLock::new();
+    SETTINGS_SYNC_DIR.get_or_init(|| support_dir().join("settings_sync"))
+}
+
 /// Returns the path to the extensions directory.
 ///
 /// This is where installed extensions are stored.
@@ -278,6 +334,11 @@ pub fn local_tasks_file_relative_path() -> &'static Path {
     Path::new(".zed/tasks.json")
 }
 
+/// Returns the relative path to a `debug.json` file within a project.
+pub fn local_debug_file_relative_path() -> &'static Path {
+    Path::new(".zed/debug.json")
+}
+
 /// Returns the relative path to a `.vscode/tasks.json` file within a project.
 pub fn local_vscode_tasks_file_relative_path() -> &'static Path {
     Path::new(".vscode/tasks.json")
@@ -0,0 +1,493 @@
+//! Reading the objects and pages of PDF files.
+//!
+//! Objects are found by scanning the file for their definitions rather than by
+//! reading its cross-reference tables, which makes damaged files readable too, and
+//! handles incremental updates by letting later definitions replace earlier ones.
+
+use std::io::Read as _;
+
+use anyhow::{anyhow, bail, Context as _, Result};
+use collections::{HashMap, HashSet};
+
+use crate::pdf_object::{find, is_whitespace, Dictionary, Object, ObjectId, Parser, Stream};
+
+/// The depth of the page tree, and of chains of references, beyond which files are
+/// assumed to be malformed.
+const MAX_DEPTH: usize = 32;
+
+pub struct PdfDocument {
+    objects: HashMap<ObjectId, Object>,
+    pub pages: Vec<Page>,
+}
+
+pub struct Page {
+    /// The visible area of the page, as `[left, bottom, right, top]`.
+    pub bounds: [f32; 4],
+    pub resources: Dictionary,
+    /// The references to the content streams of the page.
+    pub contents: Vec<Object>,
+}
+
+impl Page {
+    pub fn width(&self) -> f32 {
+        (self.bounds[2] - self.bounds[0]).abs()
+    }
+
+    pub fn height(&self) -> f32 {
+        (self.bounds[3] - self.bounds[1]).abs()
+    }
+}
+
+impl PdfDocument {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        if find(&bytes[..bytes.len().min(1024)], b"%PDF-").is_none() {
+            bail!("not a PDF file");
+        }
+
+        let mut objects = HashMap::default();
+        let mut trailers = Vec::new();
+        let mut position = 0;
+        while let Some(offset) = find(&bytes[position..], b"obj") {
+            let keyword = position + offset;
+            position = keyword + 3;
+            let Some(id) = object_id_before(bytes, keyword) else {
+                continue;
+            };
+            let mut parser = Parser::new(bytes, position);
+            if let Ok(object) = parser.parse_object() {
+                position = parser.position;
+                if is_type(&object, "XRef") {
+                    trailers.push(object.as_dict().cloned().unwrap_or_default());
+                }
+                objects.insert(id, object);
+            }
+        }
+        let mut position = 0;
+        while let Some(offset) = find(&bytes[position..], b"trailer") {
+            position += offset + b"trailer".len();
+            let mut parser = Parser::new(bytes, position);
+            if let Ok(Object::Dictionary(dict)) = parser.parse_object() {
+                trailers.push(dict);
+            }
+        }
+
+        // Files with object streams keep most of their objects compressed in them.
+        let object_streams = objects
+            .values()
+            .filter(|object| is_type(object, "ObjStm"))
+            .filter_map(Object::as_stream)
+            .cloned()
+            .collect::<Vec<_>>();
+        for stream in object_streams {
+            if let Ok(contained) = parse_object_stream(&stream) {
+                for (id, object) in contained {
+                    objects.entry(id).or_insert(object);
+                }
+            }
+        }
+
+        if trailers
+            .iter()
+            .any(|trailer| trailer.contains_key("Encrypt"))
+        {
+            bail!("encrypted PDF files aren't supported");
+        }
+
+        let mut document = Self {
+            objects,
+            pages: Vec::new(),
+        };
+        let catalog = trailers
+            .iter()
+            .rev()
+            .find_map(|trailer| document.resolve(trailer.get("Root")?).as_dict())
+            .or_else(|| {
+                document
+                    .objects
+                    .values()
+                    .find(|object| is_type(object, "Catalog"))
+                    .and_then(Object::as_dict)
+            })
+            .ok_or_else(|| anyhow!("the file has no document catalog"))?;
+        let root = catalog
+            .get("Pages")
+            .ok_or_else(|| anyhow!("the file has no pages"))?;
+
+        let mut pages = Vec::new();
+        let mut visited = HashSet::default();
+        document.collect_pages(root, &Inherited::default(), &mut visited, &mut pages, 0)?;
+        document.pages = pages;
+        Ok(document)
+    }
+
+    /// Follows references to the object they refer to. References to objects that
+    /// don't exist refer to null.
+    pub fn resolve<'a>(&'a self, mut object: &'a Object) -> &'a Object {
+        for _ in 0..MAX_DEPTH {
+            match object {
+                Object::Reference(id) => {
+                    object = self.objects.get(id).unwrap_or(&Object::Null);
+                }
+                _ => return object,
+            }
+        }
+        &Object::Null
+    }
+
+    /// Returns an entry of a dictionary, with references followed.
+    pub fn get<'a>(&'a self, dict: &'a Dictionary, key: &str) -> Option<&'a Object> {
+        match self.resolve(dict.get(key)?) {
+            Object::Null => None,
+            object => Some(object),
+        }
+    }
+
+    /// Returns the decoded data of the content streams of a page, joined together.
+    pub fn page_contents(&self, page: &Page) -> Vec<u8> {
+        let mut contents = Vec::new();
+        for stream in &page.contents {
+            if let Some(stream) = self.resolve(stream).as_stream() {
+                match self.decode_stream(stream) {
+                    Ok(data) => contents.extend(data),
+                    Err(error) => log::warn!("failed to decode page contents: {error:#}"),
+                }
+                // The streams are joined as if they were one, so operators can't be
+                // split across them.
+                contents.push(b'\n');
+            }
+        }
+        contents
+    }
+
+    pub fn decode_stream(&self, stream: &Stream) -> Result<Vec<u8>> {
+        let filters = match self.get(&stream.dict, "Filter") {
+            Some(Object::Name(name)) => vec![name.as_str()],
+            Some(Object::Array(filters)) => filters
+                .iter()
+                .filter_map(|filter| self.resolve(filter).as_name())
+                .collect(),
+            _ => Vec::new(),
+        };
+        let mut data = stream.data.clone();
+        for filter in filters {
+            data = decode_filter(filter, &data)?;
+        }
+        Ok(data)
+    }
+
+    fn collect_pages(
+        &self,
+        node: &Object,
+        inherited: &Inherited,
+        visited: &mut HashSet<ObjectId>,
+        pages: &mut Vec<Page>,
+        depth: usize,
+    ) -> Result<()> {
+        if depth > MAX_DEPTH {
+            bail!("the page tree is too deep");
+        }
+        if let Object::Reference(id) = node {
+            if !visited.insert(*id) {
+                return Ok(());
+            }
+        }
+        let Some(dict) = self.resolve(node).as_dict() else {
+            return Ok(());
+        };
+
+        let inherited = Inherited {
+            resources: self
+                .get(dict, "Resources")
+                .and_then(Object::as_dict)
+                .cloned()
+                .or_else(|| inherited.resources.clone()),
+            bounds: self
+                .rectangle(dict, "CropBox")
+                .or_else(|| self.rectangle(dict, "MediaBox"))
+                .or(inherited.bounds),
+        };
+
+        if let Some(kids) = self.get(dict, "Kids").and_then(Object::as_array) {
+            for kid in kids {
+                self.collect_pages(kid, &inherited, visited, pages, depth + 1)?;
+            }
+        } else {
+            let contents = match dict.get("Contents").map(|contents| self.resolve(contents)) {
+                Some(Object::Array(streams)) => streams.clone(),
+                Some(Object::Stream(_)) => vec![dict["Contents"].clone()],
+                _ => Vec::new(),
+            };
+            pages.push(Page {
+                // Pages are US Letter sized when their size is missing.
+                bounds: inherited.bounds.unwrap_or([0., 0., 612., 792.]),
+                resources: inherited.resources.unwrap_or_default(),
+                contents,
+            });
+        }
+        Ok(())
+    }
+
+    fn rectangle(&self, dict: &Dictionary, key: &str) -> Option<[f32; 4]> {
+        let values = self.get(dict, key)?.as_array()?;
+        let mut rectangle = [0.; 4];
+        if values.len() != 4 {
+            return None;
+        }
+        for (value, component) in values.iter().zip(&mut rectangle) {
+            *component = self.resolve(value).as_f32()?;
+        }
+        Some([
+            rectangle[0].min(rectangle[2]),
+            rectangle[1].min(rectangle[3]),
+            rectangle[0].max(rectangle[2]),
+            rectangle[1].max(rectangle[3]),
+        ])
+    }
+}
+
+/// The attributes of pages that can be given by the nodes of the page tree above
+/// them.
+#[derive(Default)]
+struct Inherited {
+    resources: Option<Dictionary>,
+    bounds: Option<[f32; 4]>,
+}
+
+fn is_type(object: &Object, type_name: &str) -> bool {
+    object
+        .as_dict()
+        .and_then(|dict| dict.get("Type"))
+        .and_then(Object::as_name)
+        == Some(type_name)
+}
+
+/// Reads the `12 0` before an `obj` keyword.
+fn object_id_before(bytes: &[u8], keyword: usize) -> Option<ObjectId> {
+    let mut position = keyword;
+    let read_number = |position: &mut usize| -> Option<u64> {
+        let end = *position;
+        while *position > 0 && bytes[*position - 1].is_ascii_digit() {
+            *position -= 1;
+        }
+        std::str::from_utf8(&bytes[*position..end])
+            .ok()?
+            .parse()
+            .ok()
+    };
+    let skip_whitespace = |position: &mut usize| -> bool {
+        let end = *position;
+        while *position > 0 && is_whitespace(bytes[*position - 1]) {
+            *position -= 1;
+        }
+        *position < end
+    };
+
+    if !skip_whitespace(&mut position) {
+        return None;
+    }
+    let generation = read_number(&mut position)?;
+    if !skip_whitespace(&mut position) {
+        return None;
+    }
+    let number = read_number(&mut position)?;
+    // The number must start a token, rather than end a longer one.
+    if position > 0 && bytes[position - 1].is_ascii_alphanumeric() {
+        return None;
+    }
+    Some((u32::try_from(number).ok()?, u16::try_from(generation).ok()?))
+}
+
+fn parse_object_stream(stream: &Stream) -> Result<Vec<(ObjectId, Object)>> {
+    let count = stream
+        .dict
+        .get("N")
+        .and_then(Object::as_i64)
+        .context("object stream has no count")?;
+    let first = stream
+        .dict
+        .get("First")
+        .and_then(Object::as_i64)
+        .and_then(|first| usize::try_from(first).ok())
+        .context("object stream has no offset")?;
+    let mut data = stream.data.clone();
+    if let Some(Object::Name(filter)) = stream.dict.get("Filter") {
+        data = decode_filter(filter, &data)?;
+    }
+
+    let mut header = Parser::new(&data, 0);
+    let mut objects = Vec::new();
+    for _ in 0..count {
+        let (Ok(Object::Integer(number)), Ok(Object::Integer(offset))) =
+            (header.parse_object(), header.parse_object())
+        else {
+            break;
+        };
+        let mut parser = Parser::new(&data, first + offset as usize);
+        if let Ok(object) = parser.parse_object() {
+            objects.push(((number as u32, 0), object));
+        }
+    }
+    Ok(objects)
+}
+
+fn decode_filter(filter: &str, data: &[u8]) -> Result<Vec<u8>> {
+    match filter {
+        "FlateDecode" | "Fl" => {
+            let mut decoded = Vec::new();
+            let result = flate2::read::ZlibDecoder::new(data).read_to_end(&mut decoded);
+            // Streams are often truncated, and what could be decoded of them is
+            // still worth showing.
+            match result {
+                Err(error) if decoded.is_empty() => Err(error.into()),
+                _ => Ok(decoded),
+            }
+        }
+        "ASCIIHexDecode" | "AHx" => {
+            // The data is parsed as the body of a hexadecimal string.
+            let mut bytes = Vec::with_capacity(data.len() + 1);
+            bytes.push(b'<');
+            bytes.extend_from_slice(data);
+            match Parser::new(&bytes, 0).parse_object()? {
+                Object::String(decoded) => Ok(decoded),
+                _ => bail!("invalid hexadecimal data"),
+            }
+        }
+        "ASCII85Decode" | "A85" => decode_ascii85(data),
+        _ => bail!("unsupported filter {filter}"),
+    }
+}
+
+fn decode_ascii85(data: &[u8]) -> Result<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(data.len() * 4 / 5);
+    let mut group = Vec::with_capacity(5);
+    let data = data.strip_prefix(b"<~").unwrap_or(data);
+    for &byte in data {
+        match byte {
+            b'~' => break,
+            b'z' if group.is_empty() => decoded.extend([0; 4]),
+            b'!'..=b'u' => {
+                group.push(byte - b'!');
+                if group.len() == 5 {
+                    let value = group
+                        .iter()
+                        .fold(0u64, |value, &digit| value * 85 + digit as u64);
+                    decoded.extend_from_slice(&(value as u32).to_be_bytes());
+                    group.clear();
+                }
+            }
+            byte if is_whitespace(byte) => {}
+            _ => bail!("invalid ASCII85 data"),
+        }
+    }
+    // A final partial group is padded with the highest digit.
+    if !group.is_empty() {
+        let length = group.len();
+        group.resize(5, 84);
+        let value = group
+            .iter()
+            .fold(0u64, |value, &digit| value * 85 + digit as u64);
+        decoded.extend_from_slice(&(value as u32).to_be_bytes()[..length - 1]);
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::Write as _;
+
+    use super::*;
+
+    /// Builds a PDF file whose pages have the given content streams, compressing
+    /// the content of every other page.
+    pub(crate) fn build_pdf(pages: &[&str]) -> Vec<u8> {
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} /MediaBox [0 0 200 100] \
+                 /Resources << /Font << /F1 << /Type /Font /Subtype /Type1 \
+                 /BaseFont /Helvetica >> >> >> >>",
+                (0..pages.len())
+                    .map(|index| format!("{} 0 R", 3 + index * 2))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                pages.len()
+            ),
+        ];
+        let mut bodies = Vec::new();
+        for (index, content) in pages.iter().enumerate() {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /Contents {} 0 R >>",
+                4 + index * 2
+            ));
+            if index % 2 == 1 {
+                let mut encoder =
+                    flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(content.as_bytes()).unwrap();
+                bodies.push((objects.len(), encoder.finish().unwrap(), true));
+            } else {
+                bodies.push((objects.len(), content.as_bytes().to_vec(), false));
+            }
+            objects.push(String::new());
+        }
+
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        for (index, object) in objects.iter().enumerate() {
+            let id = index + 1;
+            if let Some((_, data, compressed)) = bodies.iter().find(|(ix, _, _)| *ix == index) {
+                let filter = if *compressed {
+                    " /Filter /FlateDecode"
+                } else {
+                    ""
+                };
+                pdf.extend(
+                    format!("{id} 0 obj\n<< /Length {}{filter} >>\nstream\n", data.len()).bytes(),
+                );
+                pdf.extend(data);
+                pdf.extend(b"\nendstream\nendobj\n");
+            } else {
+                pdf.extend(format!("{id} 0 obj\n{object}\nendobj\n").bytes());
+            }
+        }
+        pdf.extend(b"trailer\n<< /Root 1 0 R >>\n%%EOF\n");
+        pdf
+    }
+
+    #[test]
+    fn test_parse_pages() {
+        let pdf = build_pdf(&["BT (one) Tj ET", "BT (two) Tj ET"]);
+        let document = PdfDocument::parse(&pdf).unwrap();
+        assert_eq!(document.pages.len(), 2);
+        assert_eq!(document.pages[0].width(), 200.);
+        assert_eq!(document.pages[0].height(), 100.);
+        assert!(document.pages[0].resources.contains_key("Font"));
+        assert_eq!(
+            String::from_utf8(document.page_contents(&document.pages[1])).unwrap(),
+            "BT (two) Tj ET\n"
+        );
+    }
+
+    #[test]
+    fn test_incremental_updates_replace_objects() {
+        let mut pdf = build_pdf(&["BT (old) Tj ET"]);
+        pdf.extend(b"4 0 obj\n<< /Length 14 >>\nstream\nBT (new) Tj ET\nendstream\nendobj\n");
+        let document = PdfDocument::parse(&pdf).unwrap();
+        assert_eq!(
+            document.page_contents(&document.pages[0]),
+            b"BT (new) Tj ET\n"
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_files() {
+        assert!(PdfDocument::parse(b"GIF89a").is_err());
+    }
+
+    #[test]
+    fn test_decode_ascii85() {
+        assert_eq!(
+            decode_ascii85(b"<~87cURD]i,\"Ebo7~>").unwrap(),
+            b"Hello World"
+        );
+        assert_eq!(decode_ascii85(b"z~>").unwrap(), [0; 4]);
+    }
+}
@@ -0,0 +1,579 @@
+//! The objects that PDF files are made of, and a parser for their syntax, which is
+//! shared by the bodies of files and the content streams of their pages.
+
+use anyhow::{anyhow, bail, Result};
+use collections::HashMap;
+
+pub type ObjectId = (u32, u16);
+pub type Dictionary = HashMap<String, Object>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Object {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Real(f64),
+    String(Vec<u8>),
+    Name(String),
+    Array(Vec<Object>),
+    Dictionary(Dictionary),
+    Stream(Stream),
+    Reference(ObjectId),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stream {
+    pub dict: Dictionary,
+    /// The data of the stream, before its filters are decoded.
+    pub data: Vec<u8>,
+}
+
+impl Object {
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Object::Integer(value) => Some(*value as f32),
+            Object::Real(value) => Some(*value as f32),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Object::Integer(value) => Some(*value),
+            Object::Real(value) => Some(*value as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_name(&self) -> Option<&str> {
+        match self {
+            Object::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Object::String(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Object]> {
+        match self {
+            Object::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// Returns the dictionary of a dictionary or a stream.
+    pub fn as_dict(&self) -> Option<&Dictionary> {
+        match self {
+            Object::Dictionary(dict) => Some(dict),
+            Object::Stream(stream) => Some(&stream.dict),
+            _ => None,
+        }
+    }
+
+    pub fn as_stream(&self) -> Option<&Stream> {
+        match self {
+            Object::Stream(stream) => Some(stream),
+            _ => None,
+        }
+    }
+}
+
+/// A token of PDF syntax: either an object, or a keyword such as `obj` or, in content
+/// streams, an operator such as `Tj`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    Object(Object),
+    Keyword(String),
+}
+
+pub struct Parser<'a> {
+    bytes: &'a [u8],
+    pub position: usize,
+}
+
+/// The nesting of arrays and dictionaries beyond which files are assumed to be
+/// malformed, so that they can't overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+impl<'a> Parser<'a> {
+    pub fn new(bytes: &'a [u8], position: usize) -> Self {
+        Self { bytes, position }
+    }
+
+    pub fn is_at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        self.position >= self.bytes.len()
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        while let Some(&byte) = self.bytes.get(self.position) {
+            if is_whitespace(byte) {
+                self.position += 1;
+            } else if byte == b'%' {
+                while let Some(&byte) = self.bytes.get(self.position) {
+                    if byte == b'\n' || byte == b'\r' {
+                        break;
+                    }
+                    self.position += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Parses the next object, failing if the next token is a keyword.
+    pub fn parse_object(&mut self) -> Result<Object> {
+        match self.next_token()? {
+            Token::Object(object) => Ok(object),
+            Token::Keyword(keyword) => bail!("expected an object, found {keyword:?}"),
+        }
+    }
+
+    pub fn next_token(&mut self) -> Result<Token> {
+        self.next_token_at_depth(0)
+    }
+
+    fn next_token_at_depth(&mut self, depth: usize) -> Result<Token> {
+        if depth > MAX_DEPTH {
+            bail!("objects are nested too deeply");
+        }
+        self.skip_whitespace();
+        let Some(&byte) = self.bytes.get(self.position) else {
+            bail!("unexpected end of data");
+        };
+        let object = match byte {
+            b'/' => {
+                self.position += 1;
+                Object::Name(self.parse_name())
+            }
+            b'(' => {
+                self.position += 1;
+                Object::String(self.parse_literal_string())
+            }
+            b'<' if self.bytes.get(self.position + 1) == Some(&b'<') => {
+                self.position += 2;
+                let dict = self.parse_dictionary(depth)?;
+                return Ok(Token::Object(self.parse_stream_after(dict)?));
+            }
+            b'<' => {
+                self.position += 1;
+                Object::String(self.parse_hex_string())
+            }
+            b'[' => {
+                self.position += 1;
+                let mut array = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.bytes.get(self.position) {
+                        Some(b']') => {
+                            self.position += 1;
+                            break;
+                        }
+                        Some(_) => match self.next_token_at_depth(depth + 1)? {
+                            Token::Object(object) => array.push(object),
+                            Token::Keyword(keyword) => bail!("unexpected {keyword:?} in an array"),
+                        },
+                        None => bail!("unterminated array"),
+                    }
+                }
+                Object::Array(array)
+            }
+            b'+' | b'-' | b'.' | b'0'..=b'9' => self.parse_number_or_reference()?,
+            b')' | b'>' | b']' | b'{' | b'}' => {
+                self.position += 1;
+                return Ok(Token::Keyword((byte as char).to_string()));
+            }
+            _ => {
+                let start = self.position;
+                while let Some(&byte) = self.bytes.get(self.position) {
+                    if is_whitespace(byte) || is_delimiter(byte) {
+                        break;
+                    }
+                    self.position += 1;
+                }
+                let keyword = String::from_utf8_lossy(&self.bytes[start..self.position]);
+                match keyword.as_ref() {
+                    "true" => Object::Bool(true),
+                    "false" => Object::Bool(false),
+                    "null" => Object::Null,
+                    _ => return Ok(Token::Keyword(keyword.into_owned())),
+                }
+            }
+        };
+        Ok(Token::Object(object))
+    }
+
+    fn parse_name(&mut self) -> String {
+        let mut name = Vec::new();
+        while let Some(&byte) = self.bytes.get(self.position) {
+            if is_whitespace(byte) || is_delimiter(byte) {
+                break;
+            }
+            self.position += 1;
+            if byte == b'#' {
+                let hex = self.bytes.get(self.position..self.position + 2);
+                if let Some(value) =
+                    hex.and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok())
+                {
+                    name.push(value);
+                    self.position += 2;
+                    continue;
+                }
+            }
+            name.push(byte);
+        }
+        String::from_utf8_lossy(&name).into_owned()
+    }
+
+    fn parse_literal_string(&mut self) -> Vec<u8> {
+        let mut string = Vec::new();
+        let mut nesting = 0;
+        while let Some(&byte) = self.bytes.get(self.position) {
+            self.position += 1;
+            match byte {
+                b'(' => {
+                    nesting += 1;
+                    string.push(byte);
+                }
+                b')' if nesting == 0 => break,
+                b')' => {
+                    nesting -= 1;
+                    string.push(byte);
+                }
+                b'\\' => {
+                    let Some(&escaped) = self.bytes.get(self.position) else {
+                        break;
+                    };
+                    self.position += 1;
+                    match escaped {
+                        b'n' => string.push(b'\n'),
+                        b'r' => string.push(b'\r'),
+                        b't' => string.push(b'\t'),
+                        b'b' => string.push(0x08),
+                        b'f' => string.push(0x0c),
+                        b'0'..=b'7' => {
+                            let mut value = (escaped - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.bytes.get(self.position) {
+                                    Some(&digit @ b'0'..=b'7') => {
+                                        value = value * 8 + (digit - b'0') as u32;
+                                        self.position += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            string.push(value as u8);
+                        }
+                        // A backslash at the end of a line continues the string on
+                        // the next one.
+                        b'\r' => {
+                            if self.bytes.get(self.position) == Some(&b'\n') {
+                                self.position += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        _ => string.push(escaped),
+                    }
+                }
+                _ => string.push(byte),
+            }
+        }
+        string
+    }
+
+    fn parse_hex_string(&mut self) -> Vec<u8> {
+        let mut digits = Vec::new();
+        while let Some(&byte) = self.bytes.get(self.position) {
+            self.position += 1;
+            if byte == b'>' {
+                break;
+            }
+            if let Some(digit) = (byte as char).to_digit(16) {
+                digits.push(digit as u8);
+            }
+        }
+        // An odd final digit is followed by an implied zero.
+        if digits.len() % 2 == 1 {
+            digits.push(0);
+        }
+        digits
+            .chunks(2)
+            .map(|pair| pair[0] * 16 + pair[1])
+            .collect()
+    }
+
+    fn parse_dictionary(&mut self, depth: usize) -> Result<Dictionary> {
+        let mut dict = Dictionary::default();
+        loop {
+            self.skip_whitespace();
+            if self.bytes[self.position..].starts_with(b">>") {
+                self.position += 2;
+                return Ok(dict);
+            }
+            let key = match self.next_token_at_depth(depth + 1)? {
+                Token::Object(Object::Name(key)) => key,
+                token => bail!("expected a name as a dictionary key, found {token:?}"),
+            };
+            self.skip_whitespace();
+            if self.bytes[self.position..].starts_with(b">>") {
+                // Some writers leave out the value of the last key.
+                continue;
+            }
+            match self.next_token_at_depth(depth + 1)? {
+                Token::Object(value) => {
+                    dict.insert(key, value);
+                }
+                Token::Keyword(keyword) => bail!("unexpected {keyword:?} in a dictionary"),
+            }
+        }
+    }
+
+    /// Parses the data of a stream, if the dictionary just parsed is followed by one.
+    fn parse_stream_after(&mut self, dict: Dictionary) -> Result<Object> {
+        let after_dict = self.position;
+        self.skip_whitespace();
+        if !self.bytes[self.position..].starts_with(b"stream") {
+            self.position = after_dict;
+            return Ok(Object::Dictionary(dict));
+        }
+        self.position += b"stream".len();
+        if self.bytes[self.position..].starts_with(b"\r\n") {
+            self.position += 2;
+        } else if self.bytes[self.position..].starts_with(b"\n") {
+            self.position += 1;
+        }
+        let start = self.position;
+
+        // The length may be given by a reference, which can't be resolved while
+        // parsing, so it's only trusted if the stream ends where it says.
+        let length = dict
+            .get("Length")
+            .and_then(Object::as_i64)
+            .and_then(|length| usize::try_from(length).ok());
+        let end = length
+            .filter(|length| {
+                let mut parser = Parser::new(self.bytes, start.saturating_add(*length));
+                parser.skip_whitespace();
+                parser
+                    .bytes
+                    .get(parser.position..)
+                    .map_or(false, |rest| rest.starts_with(b"endstream"))
+            })
+            .map(|length| start + length)
+            .or_else(|| {
+                let end = find(&self.bytes[start..], b"endstream")? + start;
+                // The end of line before `endstream` isn't part of the data.
+                let data = &self.bytes[start..end];
+                let data = data.strip_suffix(b"\n").unwrap_or(data);
+                let data = data.strip_suffix(b"\r").unwrap_or(data);
+                Some(start + data.len())
+            })
+            .ok_or_else(|| anyhow!("unterminated stream"))?;
+
+        let data = self.bytes[start..end].to_vec();
+        self.position = end;
+        self.skip_whitespace();
+        if self.bytes[self.position..].starts_with(b"endstream") {
+            self.position += b"endstream".len();
+        }
+        Ok(Object::Stream(Stream { dict, data }))
+    }
+
+    fn parse_number_or_reference(&mut self) -> Result<Object> {
+        let number = self.parse_number()?;
+        let Object::Integer(id) = number else {
+            return Ok(number);
+        };
+        // `12 0 R` is a reference, which needs two more tokens to tell apart from a
+        // number.
+        let after_number = self.position;
+        let reference = (|| {
+            self.skip_whitespace();
+            let generation = self.parse_unsigned()?;
+            self.skip_whitespace();
+            let is_r = self.bytes.get(self.position) == Some(&b'R')
+                && self
+                    .bytes
+                    .get(self.position + 1)
+                    .map_or(true, |&byte| is_whitespace(byte) || is_delimiter(byte));
+            if !is_r {
+                return None;
+            }
+            self.position += 1;
+            Some(Object::Reference((
+                u32::try_from(id).ok()?,
+                u16::try_from(generation).ok()?,
+            )))
+        })();
+        match reference {
+            Some(reference) => Ok(reference),
+            None => {
+                self.position = after_number;
+                Ok(number)
+            }
+        }
+    }
+
+    fn parse_unsigned(&mut self) -> Option<u64> {
+        let start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .map_or(false, u8::is_ascii_digit)
+        {
+            self.position += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.position])
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    fn parse_number(&mut self) -> Result<Object> {
+        let start = self.position;
+        if matches!(self.bytes.get(self.position), Some(b'+' | b'-')) {
+            self.position += 1;
+        }
+        let mut is_real = false;
+        while let Some(&byte) = self.bytes.get(self.position) {
+            match byte {
+                b'0'..=b'9' => {}
+                b'.' => is_real = true,
+                _ => break,
+            }
+            self.position += 1;
+        }
+        let text = std::str::from_utf8(&self.bytes[start..self.position])?;
+        // Some writers produce numbers like `-.` or `--5`, which readers treat as zero.
+        if is_real {
+            Ok(Object::Real(text.parse().unwrap_or(0.)))
+        } else {
+            Ok(text
+                .parse()
+                .map(Object::Integer)
+                .unwrap_or(Object::Integer(0)))
+        }
+    }
+
+    /// Skips the data of an inline image, which follows an `ID` operator and ends
+    /// with `EI`.
+    pub fn skip_inline_image_data(&mut self) {
+        self.position += 1;
+        while self.position < self.bytes.len() {
+            if self.bytes[self.position..].starts_with(b"EI")
+                && self.position > 0
+                && is_whitespace(self.bytes[self.position - 1])
+                && self
+                    .bytes
+                    .get(self.position + 2)
+                    .map_or(true, |&byte| is_whitespace(byte))
+            {
+                self.position += 2;
+                return;
+            }
+            self.position += 1;
+        }
+    }
+}
+
+pub fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b'\0' | b'\t' | b'\n' | 0x0c | b'\r' | b' ')
+}
+
+fn is_delimiter(byte: u8) -> bool {
+    matches!(
+        byte,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+pub fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Object {
+        Parser::new(source.as_bytes(), 0).parse_object().unwrap()
+    }
+
+    #[test]
+    fn test_parse_objects() {
+        assert_eq!(parse("42"), Object::Integer(42));
+        assert_eq!(parse("-.5"), Object::Real(-0.5));
+        assert_eq!(parse("/A#20B"), Object::Name("A B".into()));
+        assert_eq!(parse("12 0 R"), Object::Reference((12, 0)));
+        assert_eq!(
+            parse("[1 2 R 3 /R]"),
+            Object::Array(vec![
+                Object::Reference((1, 2)),
+                Object::Integer(3),
+                Object::Name("R".into())
+            ])
+        );
+        assert_eq!(
+            parse(
+                r"(a (nested) \(string\)\n\101\
+b)"
+            ),
+            Object::String(b"a (nested) (string)\nAb".to_vec())
+        );
+        assert_eq!(
+            parse("<48 65 6C6C 6F7>"),
+            Object::String(b"Hellop".to_vec())
+        );
+
+        let Object::Dictionary(dict) = parse("<< /Type /Page /Count 3 % comment\n /Kids [] >>")
+        else {
+            panic!("expected a dictionary");
+        };
+        assert_eq!(dict["Type"], Object::Name("Page".into()));
+        assert_eq!(dict["Count"], Object::Integer(3));
+        assert_eq!(dict["Kids"], Object::Array(Vec::new()));
+    }
+
+    #[test]
+    fn test_parse_streams() {
+        let Object::Stream(stream) = parse("<< /Length 5 >>\nstream\r\nhello\nendstream") else {
+            panic!("expected a stream");
+        };
+        assert_eq!(stream.data, b"hello");
+
+        // A wrong or indirect length falls back to finding the end of the stream.
+        let Object::Stream(stream) = parse("<< /Length 9 0 R >>\nstream\nhello\nendstream") else {
+            panic!("expected a stream");
+        };
+        assert_eq!(stream.data, b"hello");
+    }
+
+    #[test]
+    fn test_content_stream_tokens() {
+        let mut parser = Parser::new(b"BT /F1 12 Tf (Hi) Tj ET", 0);
+        let mut tokens = Vec::new();
+        while !parser.is_at_end() {
+            tokens.push(parser.next_token().unwrap());
+        }
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Keyword("BT".into()),
+                Token::Object(Object::Name("F1".into())),
+                Token::Object(Object::Integer(12)),
+                Token::Keyword("Tf".into()),
+                Token::Object(Object::String(b"Hi".to_vec())),
+                Token::Keyword("Tj".into()),
+                Token::Keyword("ET".into()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,894 @@
+//! Extracting the text of pages, with where it's drawn, from their content streams.
+//!
+//! Only text is extracted: it's laid out at the positions, and in the sizes, the page
+//! draws it at, which is enough to read and search documents but not to show their
+//! graphics.
+
+use std::{ops::Range, sync::Arc};
+
+use collections::HashMap;
+
+use crate::pdf_document::{Page, PdfDocument};
+use crate::pdf_object::{Dictionary, Object, Parser, Token};
+
+/// The depth of nested form XObjects beyond which they aren't drawn, so that forms
+/// that draw themselves can't recurse forever.
+const MAX_FORM_DEPTH: usize = 8;
+
+/// The gap between two pieces of text on a line, as a fraction of the size of their
+/// font, beyond which they're separated by a space.
+const SPACE_THRESHOLD: f32 = 0.15;
+
+/// The gap beyond which two pieces of text on a line are kept as separate runs.
+const RUN_GAP_THRESHOLD: f32 = 1.5;
+
+/// The text of a page.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PageText {
+    pub width: f32,
+    pub height: f32,
+    pub runs: Vec<TextRun>,
+}
+
+/// A run of text on a line of a page, in the coordinates of the page, measured from
+/// its top-left corner.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextRun {
+    pub text: String,
+    pub x: f32,
+    /// The position of the baseline of the text.
+    pub baseline: f32,
+    pub font_size: f32,
+    pub width: f32,
+}
+
+/// A match for a search, in the text of a page.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextMatch {
+    pub page: usize,
+    pub run: usize,
+    pub range: Range<usize>,
+}
+
+/// Returns the matches for a query in the text of pages, ignoring case. Matches
+/// don't span runs.
+pub fn search(pages: &[PageText], query: &str) -> Vec<TextMatch> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut matches = Vec::new();
+    for (page_index, page) in pages.iter().enumerate() {
+        for (run_index, run) in page.runs.iter().enumerate() {
+            // Lowercasing can change the lengths of characters, so matches are
+            // found in the original text, character by character.
+            let lowercase_chars = run
+                .text
+                .char_indices()
+                .map(|(index, c)| (index, c.to_lowercase().collect::<String>()))
+                .collect::<Vec<_>>();
+            let mut start = 0;
+            while start < lowercase_chars.len() {
+                let mut candidate = String::new();
+                let mut end = start;
+                while end < lowercase_chars.len() && candidate.len() < query.len() {
+                    candidate.push_str(&lowercase_chars[end].1);
+                    end += 1;
+                }
+                if candidate == query {
+                    let range_end = lowercase_chars
+                        .get(end)
+                        .map_or(run.text.len(), |(index, _)| *index);
+                    matches.push(TextMatch {
+                        page: page_index,
+                        run: run_index,
+                        range: lowercase_chars[start].0..range_end,
+                    });
+                    start = end;
+                } else {
+                    start += 1;
+                }
+            }
+        }
+    }
+    matches
+}
+
+/// Extracts the text of a page.
+pub fn page_text(document: &PdfDocument, page: &Page) -> PageText {
+    let mut extractor = TextExtractor {
+        document,
+        fonts: HashMap::default(),
+        runs: Vec::new(),
+        page_top: page.bounds[3],
+        page_left: page.bounds[0],
+    };
+    let contents = document.page_contents(page);
+    extractor.run(&contents, &page.resources, Matrix::IDENTITY, 0);
+    PageText {
+        width: page.width(),
+        height: page.height(),
+        runs: extractor.runs,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Matrix([f32; 6]);
+
+impl Matrix {
+    const IDENTITY: Self = Self([1., 0., 0., 1., 0., 0.]);
+
+    fn translation(x: f32, y: f32) -> Self {
+        Self([1., 0., 0., 1., x, y])
+    }
+
+    /// Returns the matrix that applies this one, then another.
+    fn then(&self, other: &Self) -> Self {
+        let [a, b, c, d, e, f] = self.0;
+        let [a2, b2, c2, d2, e2, f2] = other.0;
+        Self([
+            a * a2 + b * c2,
+            a * b2 + b * d2,
+            c * a2 + d * c2,
+            c * b2 + d * d2,
+            e * a2 + f * c2 + e2,
+            e * b2 + f * d2 + f2,
+        ])
+    }
+
+    fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        let [a, b, c, d, e, f] = self.0;
+        (a * x + c * y + e, b * x + d * y + f)
+    }
+
+    fn horizontal_scale(&self) -> f32 {
+        self.0[0].hypot(self.0[1])
+    }
+
+    fn vertical_scale(&self) -> f32 {
+        self.0[2].hypot(self.0[3])
+    }
+
+    fn from_operands(operands: &[Object]) -> Option<Self> {
+        let mut matrix = [0.; 6];
+        if operands.len() < 6 {
+            return None;
+        }
+        for (value, operand) in matrix.iter_mut().zip(&operands[operands.len() - 6..]) {
+            *value = operand.as_f32()?;
+        }
+        Some(Self(matrix))
+    }
+}
+
+#[derive(Clone)]
+struct GraphicsState {
+    ctm: Matrix,
+    font: Option<Arc<Font>>,
+    font_size: f32,
+    char_spacing: f32,
+    word_spacing: f32,
+    horizontal_scaling: f32,
+    leading: f32,
+    rise: f32,
+}
+
+struct TextExtractor<'a> {
+    document: &'a PdfDocument,
+    /// The fonts already read, by the ids of their dictionaries.
+    fonts: HashMap<usize, Arc<Font>>,
+    runs: Vec<TextRun>,
+    page_top: f32,
+    page_left: f32,
+}
+
+impl<'a> TextExtractor<'a> {
+    fn run(&mut self, contents: &[u8], resources: &Dictionary, ctm: Matrix, depth: usize) {
+        let mut state = GraphicsState {
+            ctm,
+            font: None,
+            font_size: 0.,
+            char_spacing: 0.,
+            word_spacing: 0.,
+            horizontal_scaling: 1.,
+            leading: 0.,
+            rise: 0.,
+        };
+        let mut saved_states = Vec::new();
+        let mut text_matrix = Matrix::IDENTITY;
+        let mut line_matrix = Matrix::IDENTITY;
+        let mut operands = Vec::new();
+        let mut parser = Parser::new(contents, 0);
+
+        while !parser.is_at_end() {
+            let operator = match parser.next_token() {
+                Ok(Token::Object(object)) => {
+                    operands.push(object);
+                    continue;
+                }
+                Ok(Token::Keyword(operator)) => operator,
+                Err(_) => break,
+            };
+            let number = |index: usize| {
+                operands
+                    .get(index)
+                    .and_then(Object::as_f32)
+                    .unwrap_or_default()
+            };
+            match operator.as_str() {
+                "q" => saved_states.push(state.clone()),
+                "Q" => {
+                    if let Some(saved) = saved_states.pop() {
+                        state = saved;
+                    }
+                }
+                "cm" => {
+                    if let Some(matrix) = Matrix::from_operands(&operands) {
+                        state.ctm = matrix.then(&state.ctm);
+                    }
+                }
+                "BT" => {
+                    text_matrix = Matrix::IDENTITY;
+                    line_matrix = Matrix::IDENTITY;
+                }
+                "Tf" => {
+                    state.font = operands
+                        .first()
+                        .and_then(Object::as_name)
+                        .and_then(|name| self.font(resources, name));
+                    state.font_size = number(1);
+                }
+                "Tc" => state.char_spacing = number(0),
+                "Tw" => state.word_spacing = number(0),
+                "Tz" => state.horizontal_scaling = number(0) / 100.,
+                "TL" => state.leading = number(0),
+                "Ts" => state.rise = number(0),
+                "Td" | "TD" => {
+                    if operator == "TD" {
+                        state.leading = -number(1);
+                    }
+                    line_matrix = Matrix::translation(number(0), number(1)).then(&line_matrix);
+                    text_matrix = line_matrix;
+                }
+                "Tm" => {
+                    if let Some(matrix) = Matrix::from_operands(&operands) {
+                        line_matrix = matrix;
+                        text_matrix = matrix;
+                    }
+                }
+                "T*" => {
+                    line_matrix = Matrix::translation(0., -state.leading).then(&line_matrix);
+                    text_matrix = line_matrix;
+                }
+                "Tj" | "'" | "\"" => {
+                    if operator != "Tj" {
+                        if operator == "\"" {
+                            state.word_spacing = number(0);
+                            state.char_spacing = number(1);
+                        }
+                        line_matrix = Matrix::translation(0., -state.leading).then(&line_matrix);
+                        text_matrix = line_matrix;
+                    }
+                    if let Some(string) = operands.last().and_then(Object::as_bytes) {
+                        self.show_text(&state, &mut text_matrix, string);
+                    }
+                }
+                "TJ" => {
+                    let elements = operands.last().and_then(Object::as_array).unwrap_or(&[]);
+                    for element in elements {
+                        match element {
+                            Object::String(string) => {
+                                self.show_text(&state, &mut text_matrix, string)
+                            }
+                            element => {
+                                let adjustment = element.as_f32().unwrap_or_default();
+                                let offset = -adjustment / 1000.
+                                    * state.font_size
+                                    * state.horizontal_scaling;
+                                text_matrix = Matrix::translation(offset, 0.).then(&text_matrix);
+                            }
+                        }
+                    }
+                }
+                "Do" => {
+                    if let Some(name) = operands.first().and_then(Object::as_name) {
+                        self.draw_form(resources, name, &state, depth);
+                    }
+                }
+                "BI" => {
+                    // Inline images are skipped, up to the end of their data.
+                    while let Ok(token) = parser.next_token() {
+                        if token == Token::Keyword("ID".into()) {
+                            parser.skip_inline_image_data();
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            }
+            operands.clear();
+        }
+    }
+
+    /// Draws a form XObject, which is a content stream with its own resources that
+    /// can be drawn by other ones.
+    fn draw_form(
+        &mut self,
+        resources: &Dictionary,
+        name: &str,
+        state: &GraphicsState,
+        depth: usize,
+    ) {
+        if depth >= MAX_FORM_DEPTH {
+            return;
+        }
+        let document = self.document;
+        let Some(stream) = document
+            .get(resources, "XObject")
+            .and_then(Object::as_dict)
+            .and_then(|xobjects| document.get(xobjects, name))
+            .and_then(Object::as_stream)
+        else {
+            return;
+        };
+        if document
+            .get(&stream.dict, "Subtype")
+            .and_then(Object::as_name)
+            != Some("Form")
+        {
+            return;
+        }
+        let Ok(contents) = document.decode_stream(stream) else {
+            return;
+        };
+        let matrix = document
+            .get(&stream.dict, "Matrix")
+            .and_then(Object::as_array)
+            .and_then(Matrix::from_operands)
+            .unwrap_or(Matrix::IDENTITY);
+        let form_resources = document
+            .get(&stream.dict, "Resources")
+            .and_then(Object::as_dict)
+            .unwrap_or(resources);
+        self.run(
+            &contents,
+            form_resources,
+            matrix.then(&state.ctm),
+            depth + 1,
+        );
+    }
+
+    fn font(&mut self, resources: &Dictionary, name: &str) -> Option<Arc<Font>> {
+        let document = self.document;
+        let dict = document
+            .get(resources, "Font")
+            .and_then(Object::as_dict)
+            .and_then(|fonts| document.get(fonts, name))
+            .and_then(Object::as_dict)?;
+        let key = dict as *const Dictionary as usize;
+        Some(
+            self.fonts
+                .entry(key)
+                .or_insert_with(|| Arc::new(Font::new(document, dict)))
+                .clone(),
+        )
+    }
+
+    fn show_text(&mut self, state: &GraphicsState, text_matrix: &mut Matrix, string: &[u8]) {
+        let Some(font) = state.font.clone() else {
+            return;
+        };
+        let start_matrix = Matrix::translation(0., state.rise)
+            .then(text_matrix)
+            .then(&state.ctm);
+
+        let mut text = String::new();
+        let mut advance = 0.;
+        for code in font.codes(string) {
+            text.push_str(&font.text_for_code(code));
+            let word_spacing = if code == 32 && !font.two_byte {
+                state.word_spacing
+            } else {
+                0.
+            };
+            advance += (font.width(code) * state.font_size + state.char_spacing + word_spacing)
+                * state.horizontal_scaling;
+        }
+        *text_matrix = Matrix::translation(advance, 0.).then(text_matrix);
+
+        let (x, y) = start_matrix.apply(0., 0.);
+        let font_size = state.font_size * start_matrix.vertical_scale();
+        let width = advance * start_matrix.horizontal_scale();
+        if text.trim().is_empty() || font_size <= 0. {
+            return;
+        }
+        self.add_run(TextRun {
+            text,
+            x: x - self.page_left,
+            baseline: self.page_top - y,
+            font_size,
+            width,
+        });
+    }
+
+    /// Adds a run of text, joining it to the last one if it continues the same line.
+    fn add_run(&mut self, run: TextRun) {
+        if let Some(last) = self.runs.last_mut() {
+            let same_line = (last.baseline - run.baseline).abs() < last.font_size * 0.2;
+            let gap = run.x - (last.x + last.width);
+            if same_line && gap > -last.font_size * 0.5 && gap < last.font_size * RUN_GAP_THRESHOLD
+            {
+                if gap > last.font_size * SPACE_THRESHOLD
+                    && !last.text.ends_with(' ')
+                    && !run.text.starts_with(' ')
+                {
+                    last.text.push(' ');
+                }
+                last.text.push_str(&run.text);
+                last.width = run.x + run.width - last.x;
+                return;
+            }
+        }
+        self.runs.push(run);
+    }
+}
+
+/// What's needed of a font to turn the codes in strings into text, and to measure
+/// them.
+struct Font {
+    /// Whether the font's strings are made of two-byte codes.
+    two_byte: bool,
+    to_unicode: HashMap<u32, String>,
+    /// The characters of the codes of a simple font, from its encoding.
+    encoding: Vec<Option<char>>,
+    /// The widths of the codes, in thousandths of the size of the font.
+    widths: HashMap<u32, f32>,
+    default_width: f32,
+}
+
+impl Font {
+    fn new(document: &PdfDocument, dict: &Dictionary) -> Self {
+        let subtype = document.get(dict, "Subtype").and_then(Object::as_name);
+        let two_byte = subtype == Some("Type0");
+        let to_unicode = document
+            .get(dict, "ToUnicode")
+            .and_then(Object::as_stream)
+            .and_then(|stream| document.decode_stream(stream).ok())
+            .map(|data| parse_to_unicode(&data))
+            .unwrap_or_default();
+
+        let mut widths = HashMap::default();
+        let default_width;
+        if two_byte {
+            let descendant = document
+                .get(dict, "DescendantFonts")
+                .and_then(Object::as_array)
+                .and_then(|fonts| fonts.first())
+                .and_then(|font| document.resolve(font).as_dict());
+            default_width = descendant
+                .and_then(|font| document.get(font, "DW"))
+                .and_then(Object::as_f32)
+                .unwrap_or(1000.);
+            if let Some(w) = descendant
+                .and_then(|font| document.get(font, "W"))
+                .and_then(Object::as_array)
+            {
+                read_cid_widths(document, w, &mut widths);
+            }
+        } else {
+            let base_font = document
+                .get(dict, "BaseFont")
+                .and_then(Object::as_name)
+                .unwrap_or_default();
+            // The standard fonts don't need to give their widths, so they're
+            // approximated.
+            default_width = if base_font.contains("Courier") {
+                600.
+            } else {
+                500.
+            };
+            let first_char = document
+                .get(dict, "FirstChar")
+                .and_then(Object::as_i64)
+                .unwrap_or(0);
+            if let Some(values) = document.get(dict, "Widths").and_then(Object::as_array) {
+                for (index, value) in values.iter().enumerate() {
+                    if let Some(width) = document.resolve(value).as_f32() {
+                        widths.insert((first_char + index as i64) as u32, width);
+                    }
+                }
+            }
+        }
+
+        Self {
+            two_byte,
+            to_unicode,
+            encoding: if two_byte {
+                Vec::new()
+            } else {
+                simple_encoding(document, dict)
+            },
+            widths,
+            default_width,
+        }
+    }
+
+    fn codes<'b>(&self, string: &'b [u8]) -> impl Iterator<Item = u32> + 'b {
+        let step = if self.two_byte { 2 } else { 1 };
+        string.chunks(step).map(|chunk| {
+            chunk
+                .iter()
+                .fold(0u32, |code, &byte| (code << 8) | byte as u32)
+        })
+    }
+
+    fn text_for_code(&self, code: u32) -> String {
+        if let Some(text) = self.to_unicode.get(&code) {
+            return text.clone();
+        }
+        if let Some(Some(c)) = self.encoding.get(code as usize) {
+            return c.to_string();
+        }
+        char::from_u32(code)
+            .filter(|c| !c.is_control())
+            .map_or_else(|| "\u{fffd}".to_string(), |c| c.to_string())
+    }
+
+    /// Returns the width of a code, as a fraction of the size of the font.
+    fn width(&self, code: u32) -> f32 {
+        self.widths
+            .get(&code)
+            .copied()
+            .unwrap_or(self.default_width)
+            / 1000.
+    }
+}
+
+/// Reads the widths of a CID font, given as `first [w1 w2 ...]` or
+/// `first last w`.
+fn read_cid_widths(document: &PdfDocument, values: &[Object], widths: &mut HashMap<u32, f32>) {
+    let mut index = 0;
+    while index + 1 < values.len() {
+        let Some(first) = document.resolve(&values[index]).as_i64() else {
+            break;
+        };
+        match document.resolve(&values[index + 1]) {
+            Object::Array(list) => {
+                for (offset, width) in list.iter().enumerate() {
+                    if let Some(width) = document.resolve(width).as_f32() {
+                        widths.insert(first as u32 + offset as u32, width);
+                    }
+                }
+                index += 2;
+            }
+            last => {
+                let (Some(last), Some(width)) = (
+                    last.as_i64(),
+                    values
+                        .get(index + 2)
+                        .and_then(|width| document.resolve(width).as_f32()),
+                ) else {
+                    break;
+                };
+                for code in first..=last.min(first + 0xffff) {
+                    widths.insert(code as u32, width);
+                }
+                index += 3;
+            }
+        }
+    }
+}
+
+/// Parses the `bfchar` and `bfrange` mappings of a `ToUnicode` CMap.
+fn parse_to_unicode(data: &[u8]) -> HashMap<u32, String> {
+    let mut map = HashMap::default();
+    let mut parser = Parser::new(data, 0);
+    let mut operands = Vec::new();
+    let mut section = None;
+    while !parser.is_at_end() {
+        match parser.next_token() {
+            Ok(Token::Keyword(keyword)) => match keyword.as_str() {
+                "beginbfchar" | "beginbfrange" => {
+                    section = Some(keyword);
+                    operands.clear();
+                }
+                "endbfchar" => {
+                    for pair in operands.chunks_exact(2) {
+                        if let (Some(code), Some(text)) = (pair[0].as_bytes(), pair[1].as_bytes()) {
+                            map.insert(code_for_bytes(code), utf16_text(text));
+                        }
+                    }
+                    section = None;
+                }
+                "endbfrange" => {
+                    for range in operands.chunks_exact(3) {
+                        let (Some(start), Some(end)) = (range[0].as_bytes(), range[1].as_bytes())
+                        else {
+                            continue;
+                        };
+                        let (start, end) = (code_for_bytes(start), code_for_bytes(end));
+                        for (offset, code) in (start..=end.min(start + 0xffff)).enumerate() {
+                            let text = match &range[2] {
+                                Object::String(first) => {
+                                    let mut units = utf16_units(first);
+                                    if let Some(last) = units.last_mut() {
+                                        *last = last.wrapping_add(offset as u16);
+                                    }
+                                    String::from_utf16_lossy(&units)
+                                }
+                                Object::Array(texts) => match texts.get(offset) {
+                                    Some(Object::String(text)) => utf16_text(text),
+                                    _ => continue,
+                                },
+                                _ => continue,
+                            };
+                            map.insert(code, text);
+                        }
+                    }
+                    section = None;
+                }
+                _ => operands.clear(),
+            },
+            Ok(Token::Object(object)) => {
+                if section.is_some() {
+                    operands.push(object);
+                }
+            }
+            Err(_) => break,
+        }
+    }
+    map
+}
+
+fn code_for_bytes(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0, |code, &byte| (code << 8) | byte as u32)
+}
+
+fn utf16_units(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair.get(1).copied().unwrap_or(0)]))
+        .collect()
+}
+
+fn utf16_text(bytes: &[u8]) -> String {
+    String::from_utf16_lossy(&utf16_units(bytes))
+}
+
+/// Returns the characters of the codes of a simple font, from its base encoding and
+/// the differences from it.
+fn simple_encoding(document: &PdfDocument, dict: &Dictionary) -> Vec<Option<char>> {
+    let encoding = document.get(dict, "Encoding");
+    let base = match encoding {
+        Some(Object::Name(name)) => Some(name.as_str()),
+        Some(Object::Dictionary(encoding)) => document
+            .get(encoding, "BaseEncoding")
+            .and_then(Object::as_name),
+        _ => None,
+    };
+    let mut chars = (0..256u32)
+        .map(|code| match base {
+            Some("MacRomanEncoding") if code >= 128 => None,
+            _ => base_encoding_char(code as u8),
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(differences) = encoding
+        .and_then(Object::as_dict)
+        .and_then(|encoding| document.get(encoding, "Differences"))
+        .and_then(Object::as_array)
+    {
+        let mut code = 0;
+        for difference in differences {
+            match difference {
+                Object::Integer(start) => code = *start as usize,
+                Object::Name(name) => {
+                    if let Some(slot) = chars.get_mut(code) {
+                        *slot = char_for_glyph_name(name);
+                    }
+                    code += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+    chars
+}
+
+/// Returns the character of a code in the WinAnsi encoding, which is what most
+/// simple fonts use, and matches the standard encoding for letters and digits.
+fn base_encoding_char(code: u8) -> Option<char> {
+    const HIGH: [char; 32] = [
+        '€', '\u{fffd}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{fffd}', 'Ž',
+        '\u{fffd}', '\u{fffd}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ',
+        '\u{fffd}', 'ž', 'Ÿ',
+    ];
+    match code {
+        0..=31 => None,
+        128..=159 => Some(HIGH[code as usize - 128]),
+        _ => Some(code as char),
+    }
+}
+
+/// Returns the character of a glyph name from the Adobe Glyph List, for the names
+/// fonts commonly use.
+fn char_for_glyph_name(name: &str) -> Option<char> {
+    if let Some(hex) = name
+        .strip_prefix("uni")
+        .or_else(|| name.strip_prefix('u'))
+        .filter(|hex| (4..=6).contains(&hex.len()))
+    {
+        if let Some(c) = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32) {
+            return Some(c);
+        }
+    }
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(c);
+    }
+    let c = match name {
+        "space" => ' ',
+        "exclam" => '!',
+        "quotedbl" => '"',
+        "numbersign" => '#',
+        "dollar" => '$',
+        "percent" => '%',
+        "ampersand" => '&',
+        "quotesingle" | "quoteright" => '\'',
+        "parenleft" => '(',
+        "parenright" => ')',
+        "asterisk" => '*',
+        "plus" => '+',
+        "comma" => ',',
+        "hyphen" | "minus" => '-',
+        "period" => '.',
+        "slash" => '/',
+        "zero" => '0',
+        "one" => '1',
+        "two" => '2',
+        "three" => '3',
+        "four" => '4',
+        "five" => '5',
+        "six" => '6',
+        "seven" => '7',
+        "eight" => '8',
+        "nine" => '9',
+        "colon" => ':',
+        "semicolon" => ';',
+        "less" => '<',
+        "equal" => '=',
+        "greater" => '>',
+        "question" => '?',
+        "at" => '@',
+        "bracketleft" => '[',
+        "backslash" => '\\',
+        "bracketright" => ']',
+        "asciicircum" => '^',
+        "underscore" => '_',
+        "grave" | "quoteleft" => '`',
+        "braceleft" => '{',
+        "bar" => '|',
+        "braceright" => '}',
+        "asciitilde" => '~',
+        "bullet" => '•',
+        "endash" => '–',
+        "emdash" => '—',
+        "ellipsis" => '…',
+        "quotedblleft" => '“',
+        "quotedblright" => '”',
+        "fi" => 'ﬁ',
+        "fl" => 'ﬂ',
+        "ff" => 'ﬀ',
+        "ffi" => 'ﬃ',
+        "ffl" => 'ﬄ',
+        "copyright" => '©',
+        "registered" => '®',
+        "trademark" => '™',
+        "degree" => '°',
+        "section" => '§',
+        "paragraph" => '¶',
+        _ => return None,
+    };
+    Some(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf_document::tests::build_pdf;
+
+    fn extract(content: &str) -> PageText {
+        let pdf = build_pdf(&[content]);
+        let document = PdfDocument::parse(&pdf).unwrap();
+        page_text(&document, &document.pages[0])
+    }
+
+    #[test]
+    fn test_page_text_positions() {
+        let page = extract("BT /F1 10 Tf 20 80 Td (Hello) Tj 0 -20 Td (World) Tj ET");
+        assert_eq!((page.width, page.height), (200., 100.));
+        assert_eq!(page.runs.len(), 2);
+        assert_eq!(page.runs[0].text, "Hello");
+        assert_eq!((page.runs[0].x, page.runs[0].baseline), (20., 20.));
+        assert_eq!(page.runs[0].font_size, 10.);
+        // Helvetica's widths are approximated as half of the font size.
+        assert_eq!(page.runs[0].width, 25.);
+        assert_eq!(page.runs[1].text, "World");
+        assert_eq!(page.runs[1].baseline, 40.);
+    }
+
+    #[test]
+    fn test_page_text_joins_runs() {
+        let page = extract(
+            "BT /F1 10 Tf 1 0 0 1 20 80 Tm [(Hel) -10 (lo) -400 (there)] TJ (!) Tj ET \
+             q 2 0 0 2 0 0 cm BT /F1 10 Tf 10 10 Td (Big) Tj ET Q",
+        );
+        assert_eq!(page.runs.len(), 2);
+        assert_eq!(page.runs[0].text, "Hello there!");
+        assert_eq!(page.runs[1].text, "Big");
+        assert_eq!(page.runs[1].font_size, 20.);
+        assert_eq!((page.runs[1].x, page.runs[1].baseline), (20., 80.));
+    }
+
+    #[test]
+    fn test_to_unicode() {
+        let cmap = b"1 begincodespacerange <0000> <FFFF> endcodespacerange
+            2 beginbfchar <0003> <0020> <0011> <00660069> endbfchar
+            1 beginbfrange <0024> <0026> <0041> endbfrange";
+        let map = parse_to_unicode(cmap);
+        assert_eq!(map[&0x03], " ");
+        assert_eq!(map[&0x11], "fi");
+        assert_eq!(map[&0x24], "A");
+        assert_eq!(map[&0x26], "C");
+    }
+
+    #[test]
+    fn test_search() {
+        let run = |text: &str| TextRun {
+            text: text.into(),
+            x: 0.,
+            baseline: 0.,
+            font_size: 10.,
+            width: 0.,
+        };
+        let pages = vec![
+            PageText {
+                runs: vec![run("Zed is fast"), run("ZED")],
+                ..Default::default()
+            },
+            PageText {
+                runs: vec![run("İzed zed")],
+                ..Default::default()
+            },
+        ];
+        let matches = search(&pages, "zed");
+        assert_eq!(
+            matches,
+            vec![
+                TextMatch {
+                    page: 0,
+                    run: 0,
+                    range: 0..3
+                },
+                TextMatch {
+                    page: 0,
+                    run: 1,
+                    range: 0..3
+                },
+                TextMatch {
+                    page: 1,
+                    run: 0,
+                    range: 2..5
+                },
+                TextMatch {
+                    page: 1,
+                    run: 0,
+                    range: 6..9
+                },
+            ]
+        );
+        assert!(search(&pages, "").is_empty());
+    }
+}
@@ -0,0 +1,755 @@
+//! A read-only viewer for PDF files.
+//!
+//! Pages are shown with their text laid out where the file draws it, so that
+//! documentation can be read and searched without leaving Zed. Images and other
+//! graphics aren't shown.
+
+mod pdf_document;
+mod pdf_object;
+mod pdf_text;
+
+use std::{ffi::OsStr, ops::Range, path::PathBuf, sync::Arc};
+
+use editor::{Editor, EditorEvent};
+use file_icons::FileIcons;
+use gpui::{
+    actions, div, list, AnyElement, AppContext, EventEmitter, FocusHandle, FocusableView, Hsla,
+    InteractiveElement, IntoElement, ListAlignment, ListOffset, ListState, Model, MouseButton,
+    ParentElement, Render, Styled, Subscription, Task, View, ViewContext, VisualContext, WeakView,
+    WindowContext,
+};
+use pdf_document::PdfDocument;
+use pdf_text::{page_text, search, PageText, TextMatch, TextRun};
+use persistence::PDF_VIEWER;
+use project::{Project, ProjectEntryId, ProjectPath};
+use settings::Settings;
+use ui::{prelude::*, Tooltip};
+use util::ResultExt;
+use workspace::{
+    item::{Item, ProjectItem, SerializableItem, TabContentParams},
+    ItemId, ItemSettings, Pane, Workspace, WorkspaceId,
+};
+
+actions!(
+    pdf_viewer,
+    [
+        NextPage,
+        PreviousPage,
+        FirstPage,
+        LastPage,
+        ZoomIn,
+        ZoomOut,
+        ResetZoom,
+        FocusSearch,
+        NextMatch,
+        PreviousMatch
+    ]
+);
+
+const PDF_VIEWER_KIND: &str = "PdfView";
+
+/// The number of pixels in a point, the unit PDF files are measured in, at a zoom
+/// of 100%.
+const PIXELS_PER_POINT: f32 = 96. / 72.;
+const ZOOM_STEP: f32 = 1.2;
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 5.;
+
+/// The height of the tallest letters of fonts, as a fraction of their size, used to
+/// place text by its baseline.
+const ASCENT: f32 = 0.8;
+
+pub struct PdfItem {
+    path: PathBuf,
+    project_path: ProjectPath,
+}
+
+impl project::Item for PdfItem {
+    fn try_open(
+        project: &Model<Project>,
+        path: &ProjectPath,
+        cx: &mut AppContext,
+    ) -> Option<Task<gpui::Result<Model<Self>>>> {
+        let path = path.clone();
+        let project = project.clone();
+
+        let ext = path
+            .path
+            .extension()
+            .and_then(OsStr::to_str)
+            .unwrap_or_default();
+        if !ext.eq_ignore_ascii_case("pdf") {
+            return None;
+        }
+
+        Some(cx.spawn(|mut cx| async move {
+            let abs_path = project
+                .read_with(&cx, |project, cx| project.absolute_path(&path, cx))?
+                .ok_or_else(|| anyhow::anyhow!("Failed to find the absolute path"))?;
+
+            cx.new_model(|_| PdfItem {
+                path: abs_path,
+                project_path: path,
+            })
+        }))
+    }
+
+    fn entry_id(&self, _: &AppContext) -> Option<ProjectEntryId> {
+        None
+    }
+
+    fn project_path(&self, _: &AppContext) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+}
+
+enum LoadState {
+    Loading,
+    Loaded(Arc<Vec<PageText>>),
+    Failed(SharedString),
+}
+
+pub struct PdfView {
+    path: PathBuf,
+    project: Model<Project>,
+    focus_handle: FocusHandle,
+    state: LoadState,
+    zoom: f32,
+    list_state: ListState,
+    query_editor: View<Editor>,
+    matches: Vec<TextMatch>,
+    active_match: Option<usize>,
+    _load_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl PdfView {
+    fn new(path: PathBuf, project: Model<Project>, cx: &mut ViewContext<Self>) -> Self {
+        let view = cx.view().downgrade();
+        let list_state = ListState::new(0, ListAlignment::Top, px(1000.), move |ix, cx| {
+            view.upgrade()
+                .map(|view| view.update(cx, |this, cx| this.render_page(ix, cx)))
+                .unwrap_or_else(|| div().into_any())
+        });
+        let query_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Search…", cx);
+            editor
+        });
+        let query_subscription = cx.subscribe(&query_editor, |this, _, event, cx| {
+            if let EditorEvent::BufferEdited = event {
+                this.update_matches(cx);
+            }
+        });
+
+        let fs = project.read(cx).fs().clone();
+        let load_path = path.clone();
+        let load_task = cx.spawn(|this, mut cx| async move {
+            let pages = async {
+                let bytes = fs.load_bytes(&load_path).await?;
+                cx.background_executor()
+                    .spawn(async move {
+                        let document = PdfDocument::parse(&bytes)?;
+                        anyhow::Ok(
+                            document
+                                .pages
+                                .iter()
+                                .map(|page| page_text(&document, page))
+                                .collect::<Vec<_>>(),
+                        )
+                    })
+                    .await
+            }
+            .await;
+            this.update(&mut cx, |this, cx| {
+                this.state = match pages {
+                    Ok(pages) => {
+                        this.list_state.reset(pages.len());
+                        LoadState::Loaded(Arc::new(pages))
+                    }
+                    Err(error) => LoadState::Failed(format!("{error:#}").into()),
+                };
+                this.update_matches(cx);
+                cx.notify();
+            })
+            .log_err();
+        });
+
+        Self {
+            path,
+            project,
+            focus_handle: cx.focus_handle(),
+            state: LoadState::Loading,
+            zoom: 1.,
+            list_state,
+            query_editor,
+            matches: Vec::new(),
+            active_match: None,
+            _load_task: load_task,
+            _subscriptions: vec![query_subscription],
+        }
+    }
+
+    fn pages(&self) -> &[PageText] {
+        match &self.state {
+            LoadState::Loaded(pages) => pages,
+            _ => &[],
+        }
+    }
+
+    fn scale(&self) -> f32 {
+        self.zoom * PIXELS_PER_POINT
+    }
+
+    fn current_page(&self) -> usize {
+        self.list_state.logical_scroll_top().item_ix
+    }
+
+    fn go_to_page(&mut self, page: usize, cx: &mut ViewContext<Self>) {
+        let page_count = self.pages().len();
+        if page_count == 0 {
+            return;
+        }
+        self.list_state.scroll_to(ListOffset {
+            item_ix: page.min(page_count - 1),
+            offset_in_item: px(0.),
+        });
+        cx.notify();
+    }
+
+    fn next_page(&mut self, _: &NextPage, cx: &mut ViewContext<Self>) {
+        self.go_to_page(self.current_page() + 1, cx);
+    }
+
+    fn previous_page(&mut self, _: &PreviousPage, cx: &mut ViewContext<Self>) {
+        self.go_to_page(self.current_page().saturating_sub(1), cx);
+    }
+
+    fn first_page(&mut self, _: &FirstPage, cx: &mut ViewContext<Self>) {
+        self.go_to_page(0, cx);
+    }
+
+    fn last_page(&mut self, _: &LastPage, cx: &mut ViewContext<Self>) {
+        self.go_to_page(usize::MAX, cx);
+    }
+
+    fn set_zoom(&mut self, zoom: f32, cx: &mut ViewContext<Self>) {
+        let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        // Keep the same part of the page at the top of the view.
+        let scroll_top = self.list_state.logical_scroll_top();
+        let offset_in_item = scroll_top.offset_in_item * (zoom / self.zoom);
+        self.zoom = zoom;
+        self.list_state.reset(self.pages().len());
+        self.list_state.scroll_to(ListOffset {
+            item_ix: scroll_top.item_ix,
+            offset_in_item,
+        });
+        cx.notify();
+    }
+
+    fn zoom_in(&mut self, _: &ZoomIn, cx: &mut ViewContext<Self>) {
+        self.set_zoom(self.zoom * ZOOM_STEP, cx);
+    }
+
+    fn zoom_out(&mut self, _: &ZoomOut, cx: &mut ViewContext<Self>) {
+        self.set_zoom(self.zoom / ZOOM_STEP, cx);
+    }
+
+    fn reset_zoom(&mut self, _: &ResetZoom, cx: &mut ViewContext<Self>) {
+        self.set_zoom(1., cx);
+    }
+
+    fn focus_search(&mut self, _: &FocusSearch, cx: &mut ViewContext<Self>) {
+        self.query_editor.update(cx, |editor, cx| {
+            editor.select_all(&editor::actions::SelectAll, cx);
+            editor.focus(cx);
+        });
+    }
+
+    fn update_matches(&mut self, cx: &mut ViewContext<Self>) {
+        let query = self.query_editor.read(cx).text(cx);
+        self.matches = search(self.pages(), &query);
+        // Start from the first match on the page being read.
+        let current_page = self.current_page();
+        self.active_match = (!self.matches.is_empty()).then(|| {
+            self.matches
+                .iter()
+                .position(|text_match| text_match.page >= current_page)
+                .unwrap_or(0)
+        });
+        self.reveal_active_match(cx);
+        cx.notify();
+    }
+
+    fn next_match(&mut self, _: &NextMatch, cx: &mut ViewContext<Self>) {
+        if let Some(active_match) = self.active_match {
+            self.active_match = Some((active_match + 1) % self.matches.len());
+            self.reveal_active_match(cx);
+        }
+    }
+
+    fn previous_match(&mut self, _: &PreviousMatch, cx: &mut ViewContext<Self>) {
+        if let Some(active_match) = self.active_match {
+            let count = self.matches.len();
+            self.active_match = Some((active_match + count - 1) % count);
+            self.reveal_active_match(cx);
+        }
+    }
+
+    /// Scrolls the active match into view, with some of the text above it.
+    fn reveal_active_match(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(text_match) = self
+            .active_match
+            .and_then(|active_match| self.matches.get(active_match))
+        else {
+            return;
+        };
+        let Some(run) = self
+            .pages()
+            .get(text_match.page)
+            .and_then(|page| page.runs.get(text_match.run))
+        else {
+            return;
+        };
+        let offset_in_item = px(((run.baseline - run.font_size * 4.) * self.scale()).max(0.));
+        self.list_state.scroll_to(ListOffset {
+            item_ix: text_match.page,
+            offset_in_item,
+        });
+        cx.notify();
+    }
+
+    fn render_page(&self, ix: usize, cx: &mut ViewContext<Self>) -> AnyElement {
+        let Some(page) = self.pages().get(ix) else {
+            return div().into_any();
+        };
+        let scale = self.scale();
+        let match_background = cx.theme().colors().search_match_background;
+        let active_match_background = cx.theme().status().warning_background;
+
+        let runs = page.runs.iter().enumerate().map(|(run_ix, run)| {
+            let highlights = self
+                .matches
+                .iter()
+                .enumerate()
+                .filter(|(_, text_match)| text_match.page == ix && text_match.run == run_ix)
+                .map(|(match_ix, text_match)| {
+                    let background = if Some(match_ix) == self.active_match {
+                        active_match_background
+                    } else {
+                        match_background
+                    };
+                    (text_match.range.clone(), background)
+                })
+                .collect::<Vec<_>>();
+            render_run(run, &highlights, scale)
+        });
+
+        div()
+            .w_full()
+            .flex()
+            .justify_center()
+            .py_2()
+            .child(
+                div()
+                    .relative()
+                    .flex_none()
+                    .w(px(page.width * scale))
+                    .h(px(page.height * scale))
+                    .overflow_hidden()
+                    .bg(gpui::white())
+                    .text_color(gpui::black())
+                    .shadow_md()
+                    .children(runs)
+                    .when(page.runs.is_empty(), |element| {
+                        element.flex().items_center().justify_center().child(
+                            Label::new("This page has no text")
+                                .color(Color::Muted)
+                                .size(LabelSize::Small),
+                        )
+                    }),
+            )
+            .into_any()
+    }
+
+    fn render_toolbar(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let page_count = self.pages().len();
+        let page_label = if page_count == 0 {
+            String::new()
+        } else {
+            format!("Page {} of {page_count}", self.current_page() + 1)
+        };
+        let query_is_empty = self.query_editor.read(cx).text(cx).is_empty();
+        let match_label = match self.active_match {
+            Some(active_match) => format!("{} of {}", active_match + 1, self.matches.len()),
+            None if query_is_empty => String::new(),
+            None => "No matches".to_string(),
+        };
+        let has_matches = !self.matches.is_empty();
+
+        h_flex()
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_4()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        IconButton::new("previous-page", IconName::ChevronUp)
+                            .icon_size(IconSize::Small)
+                            .disabled(page_count == 0)
+                            .tooltip(|cx| Tooltip::for_action("Previous Page", &PreviousPage, cx))
+                            .on_click(
+                                cx.listener(|this, _, cx| this.previous_page(&PreviousPage, cx)),
+                            ),
+                    )
+                    .child(
+                        IconButton::new("next-page", IconName::ChevronDown)
+                            .icon_size(IconSize::Small)
+                            .disabled(page_count == 0)
+                            .tooltip(|cx| Tooltip::for_action("Next Page", &NextPage, cx))
+                            .on_click(cx.listener(|this, _, cx| this.next_page(&NextPage, cx))),
+                    )
+                    .child(Label::new(page_label).size(LabelSize::Small))
+                    .child(div().w_2())
+                    .child(
+                        IconButton::new("zoom-out", IconName::Dash)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::for_action("Zoom Out", &ZoomOut, cx))
+                            .on_click(cx.listener(|this, _, cx| this.zoom_out(&ZoomOut, cx))),
+                    )
+                    .child(
+                        Button::new("reset-zoom", format!("{:.0}%", self.zoom * 100.))
+                            .label_size(LabelSize::Small)
+                            .tooltip(|cx| Tooltip::for_action("Reset Zoom", &ResetZoom, cx))
+                            .on_click(cx.listener(|this, _, cx| this.reset_zoom(&ResetZoom, cx))),
+                    )
+                    .child(
+                        IconButton::new("zoom-in", IconName::Plus)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::for_action("Zoom In", &ZoomIn, cx))
+                            .on_click(cx.listener(|this, _, cx| this.zoom_in(&ZoomIn, cx))),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        h_flex()
+                            .w(rems(16.))
+                            .px_2()
+                            .py_0p5()
+                            .gap_1()
+                            .rounded_md()
+                            .border_1()
+                            .border_color(cx.theme().colors().border)
+                            .bg(cx.theme().colors().editor_background)
+                            .child(
+                                Icon::new(IconName::MagnifyingGlass)
+                                    .size(IconSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .child(div().flex_1().child(self.query_editor.clone())),
+                    )
+                    .child(
+                        Label::new(match_label)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        IconButton::new("previous-match", IconName::ChevronUp)
+                            .icon_size(IconSize::Small)
+                            .disabled(!has_matches)
+                            .tooltip(|cx| Tooltip::for_action("Previous Match", &PreviousMatch, cx))
+                            .on_click(
+                                cx.listener(|this, _, cx| this.previous_match(&PreviousMatch, cx)),
+                            ),
+                    )
+                    .child(
+                        IconButton::new("next-match", IconName::ChevronDown)
+                            .icon_size(IconSize::Small)
+                            .disabled(!has_matches)
+                            .tooltip(|cx| Tooltip::for_action("Next Match", &NextMatch, cx))
+                            .on_click(cx.listener(|this, _, cx| this.next_match(&NextMatch, cx))),
+                    ),
+            )
+    }
+}
+
+/// Renders a run of text at its position on the page, with the ranges of it that
+/// match the search highlighted.
+fn render_run(run: &TextRun, highlights: &[(Range<usize>, Hsla)], scale: f32) -> AnyElement {
+    let font_size = px(run.font_size * scale);
+    let mut segments = Vec::new();
+    let mut position = 0;
+    for (range, background) in highlights {
+        if range.start > position {
+            segments.push((run.text[position..range.start].to_string(), None));
+        }
+        segments.push((run.text[range.clone()].to_string(), Some(*background)));
+        position = range.end;
+    }
+    if position < run.text.len() {
+        segments.push((run.text[position..].to_string(), None));
+    }
+
+    h_flex()
+        .absolute()
+        .left(px(run.x * scale))
+        .top(px((run.baseline - run.font_size * ASCENT) * scale))
+        .text_size(font_size)
+        .line_height(font_size)
+        .whitespace_nowrap()
+        .children(segments.into_iter().map(|(text, background)| {
+            div()
+                .when_some(background, |segment, background| segment.bg(background))
+                .child(text)
+        }))
+        .into_any()
+}
+
+impl Item for PdfView {
+    type Event = ();
+
+    fn tab_content(&self, params: TabContentParams, _cx: &WindowContext) -> AnyElement {
+        let title = self
+            .path
+            .file_name()
+            .unwrap_or_else(|| self.path.as_os_str())
+            .to_string_lossy()
+            .to_string();
+        Label::new(title)
+            .single_line()
+            .color(params.text_color())
+            .italic(params.preview)
+            .into_any_element()
+    }
+
+    fn tab_icon(&self, cx: &WindowContext) -> Option<Icon> {
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(self.path.as_path(), cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("pdf viewer")
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<WorkspaceId>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Self>>
+    where
+        Self: Sized,
+    {
+        Some(cx.new_view(|cx| Self::new(self.path.clone(), self.project.clone(), cx)))
+    }
+}
+
+impl SerializableItem for PdfView {
+    fn serialized_item_kind() -> &'static str {
+        PDF_VIEWER_KIND
+    }
+
+    fn deserialize(
+        project: Model<Project>,
+        _workspace: WeakView<Workspace>,
+        workspace_id: WorkspaceId,
+        item_id: ItemId,
+        cx: &mut ViewContext<Pane>,
+    ) -> Task<gpui::Result<View<Self>>> {
+        cx.spawn(|_pane, mut cx| async move {
+            let pdf_path = PDF_VIEWER
+                .get_pdf_path(item_id, workspace_id)?
+                .ok_or_else(|| anyhow::anyhow!("No PDF path found"))?;
+
+            cx.new_view(|cx| PdfView::new(pdf_path, project, cx))
+        })
+    }
+
+    fn cleanup(
+        workspace_id: WorkspaceId,
+        alive_items: Vec<ItemId>,
+        cx: &mut WindowContext,
+    ) -> Task<gpui::Result<()>> {
+        cx.spawn(|_| PDF_VIEWER.delete_unloaded_items(workspace_id, alive_items))
+    }
+
+    fn serialize(
+        &mut self,
+        workspace: &mut Workspace,
+        item_id: ItemId,
+        _closing: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Task<gpui::Result<()>>> {
+        let workspace_id = workspace.database_id()?;
+
+        Some(cx.background_executor().spawn({
+            let pdf_path = self.path.clone();
+            async move {
+                PDF_VIEWER
+                    .save_pdf_path(item_id, workspace_id, pdf_path)
+                    .await
+            }
+        }))
+    }
+
+    fn should_serialize(&self, _event: &Self::Event) -> bool {
+        false
+    }
+}
+
+impl EventEmitter<()> for PdfView {}
+
+impl FocusableView for PdfView {
+    fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for PdfView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let content = match &self.state {
+            LoadState::Loading => v_flex()
+                .size_full()
+                .items_center()
+                .justify_center()
+                .child(Label::new("Loading…").color(Color::Muted))
+                .into_any_element(),
+            LoadState::Failed(error) => v_flex()
+                .size_full()
+                .items_center()
+                .justify_center()
+                .gap_1()
+                .child(Label::new("Failed to open the PDF file"))
+                .child(Label::new(error.clone()).color(Color::Muted))
+                .into_any_element(),
+            LoadState::Loaded(_) => list(self.list_state.clone()).size_full().into_any_element(),
+        };
+
+        v_flex()
+            .key_context("PdfView")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::next_page))
+            .on_action(cx.listener(Self::previous_page))
+            .on_action(cx.listener(Self::first_page))
+            .on_action(cx.listener(Self::last_page))
+            .on_action(cx.listener(Self::zoom_in))
+            .on_action(cx.listener(Self::zoom_out))
+            .on_action(cx.listener(Self::reset_zoom))
+            .on_action(cx.listener(Self::focus_search))
+            .on_action(cx.listener(Self::next_match))
+            .on_action(cx.listener(Self::previous_match))
+            .size_full()
+            .child(self.render_toolbar(cx))
+            .child(
+                div()
+                    .flex_1()
+                    .w_full()
+                    .bg(cx.theme().colors().editor_background)
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|this, _, cx| cx.focus(&this.focus_handle)),
+                    )
+                    .child(content),
+            )
+    }
+}
+
+impl ProjectItem for PdfView {
+    type Item = PdfItem;
+
+    fn for_project_item(
+        project: Model<Project>,
+        item: Model<Self::Item>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(item.read(cx).path.clone(), project, cx)
+    }
+}
+
+pub fn init(cx: &mut AppContext) {
+    workspace::register_project_item::<PdfView>(cx);
+    workspace::register_serializable_item::<PdfView>(cx)
+}
+
+mod persistence {
+    use anyhow::Result;
+    use std::path::PathBuf;
+
+    use db::{define_connection, query, sqlez::statement::Statement, sqlez_macros::sql};
+    use workspace::{ItemId, WorkspaceDb, WorkspaceId};
+
+    define_connection! {
+        pub static ref PDF_VIEWER: PdfViewerDb<WorkspaceDb> =
+            &[sql!(
+                CREATE TABLE pdf_viewers (
+                    workspace_id INTEGER,
+                    item_id INTEGER UNIQUE,
+
+                    pdf_path BLOB,
+
+                    PRIMARY KEY(workspace_id, item_id),
+                    FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                    ON DELETE CASCADE
+                ) STRICT;
+            )];
+    }
+
+    impl PdfViewerDb {
+        query! {
+            pub async fn save_pdf_path(
+                item_id: ItemId,
+                workspace_id: WorkspaceId,
+                pdf_path: PathBuf
+            ) -> Result<()> {
+                INSERT OR REPLACE INTO pdf_viewers(item_id, workspace_id, pdf_path)
+                VALUES (?, ?, ?)
+            }
+        }
+
+        query! {
+            pub fn get_pdf_path(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<PathBuf>> {
+                SELECT pdf_path
+                FROM pdf_viewers
+                WHERE item_id = ? AND workspace_id = ?
+            }
+        }
+
+        pub async fn delete_unloaded_items(
+            &self,
+            workspace: WorkspaceId,
+            alive_items: Vec<ItemId>,
+        ) -> Result<()> {
+            let placeholders = alive_items
+                .iter()
+                .map(|_| "?")
+                .collect::<Vec<&str>>()
+                .join(", ");
+
+            let query = format!(
+                "DELETE FROM pdf_viewers WHERE workspace_id = ? AND item_id NOT IN ({placeholders})"
+            );
+
+            self.write(move |conn| {
+                let mut statement = Statement::prepare(conn, query)?;
+                let mut next_index = statement.bind(&workspace, 1)?;
+                for id in alive_items {
+                    next_index = statement.bind(&id, next_index)?;
+                }
+                statement.exec()
+            })
+            .await
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use gpui::{Model, Subscription};
+use ui::{prelude::*, Tooltip};
+use workspace::{item::ItemHandle, StatusItemView};
+
+use crate::{Ports, ToggleFocus};
+
+/// Shows how many ports of a remote project are forwarded in the status bar.
+pub struct PortForwardingIndicator {
+    ports: Model<Ports>,
+    _observe_ports: Subscription,
+}
+
+impl PortForwardingIndicator {
+    pub fn new(ports: Model<Ports>, cx: &mut ViewContext<Self>) -> Self {
+        let observe_ports = cx.observe(&ports, |_, _, cx| cx.notify());
+        Self {
+            ports,
+            _observe_ports: observe_ports,
+        }
+    }
+}
+
+impl Render for PortForwardingIndicator {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let forwards = self.ports.read(cx).forwards();
+        if forwards.is_empty() {
+            return div().into_any_element();
+        }
+
+        let label = if forwards.len() == 1 {
+            format!("localhost:{}", forwards[0].local_port)
+        } else {
+            format!("{} ports", forwards.len())
+        };
+        let tooltip = forwards
+            .iter()
+            .map(|forward| {
+                format!(
+                    "localhost:{} → {}:{}",
+                    forward.local_port, forward.remote_host, forward.remote_port
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Button::new("port-forwarding-indicator", label)
+            .icon(IconName::Link)
+            .icon_position(IconPosition::Start)
+            .icon_size(IconSize::Small)
+            .label_size(LabelSize::Small)
+            .tooltip(move |cx| Tooltip::with_meta("Forwarded Ports", None, tooltip.clone(), cx))
+            .on_click(|_, cx| cx.dispatch_action(Box::new(ToggleFocus)))
+            .into_any_element()
+    }
+}
+
+impl StatusItemView for PortForwardingIndicator {
+    fn set_active_pane_item(&mut self, _: Option<&dyn ItemHandle>, _: &mut ViewContext<Self>) {}
+}
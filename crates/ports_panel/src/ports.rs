@@ -0,0 +1,349 @@
+use std::{
+    net::TcpListener,
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+use anyhow::Result;
+use collections::{BTreeSet, HashMap, HashSet};
+use db::kvp::KEY_VALUE_STORE;
+use gpui::{AppContext, ModelContext, Task, WeakModel};
+use project::Project;
+use regex::Regex;
+use remote::{SshPortForward, SshSession};
+use settings::Settings;
+use terminal::TaskStatus;
+use util::ResultExt;
+
+use crate::PortsPanelSettings;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const PORT_LABELS_KEY_PREFIX: &str = "port_labels";
+const FORWARDED_HOST: &str = "localhost";
+
+/// How many of the last lines of the output of running tasks are searched for
+/// the ports they listen on.
+const TASK_OUTPUT_LINES: usize = 100;
+
+/// Matches the ports in messages like `Listening on port 3000` or
+/// `Serving at http://localhost:8080/`.
+static PORT_IN_OUTPUT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(?:\blocalhost|\b127\.0\.0\.1|\b0\.0\.0\.0|\[::1?\]):(\d+)\b|\bport:?\s+(\d+)\b",
+    )
+    .unwrap()
+});
+
+/// The ports listening on the remote host of an ssh project, and which of
+/// them are forwarded to this machine.
+pub struct Ports {
+    session: Arc<SshSession>,
+    project: WeakModel<Project>,
+    listening: Vec<u16>,
+    labels: HashMap<u16, String>,
+    auto_forwarded: HashSet<u16>,
+    _poll_listening_ports: Task<()>,
+}
+
+impl Ports {
+    pub fn new(
+        session: Arc<SshSession>,
+        project: WeakModel<Project>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let poll_session = session.clone();
+        let poll_listening_ports = cx.spawn(|this, mut cx| async move {
+            loop {
+                // Hosts without procfs or lsof still have the ports that tasks
+                // say they listen on.
+                let listening = poll_session.listening_ports().await.log_err();
+                let updated = this.update(&mut cx, |this, cx| {
+                    let mut ports = listening.unwrap_or_default();
+                    ports.extend(this.ports_in_task_output(cx));
+                    this.set_listening_ports(ports, cx);
+                });
+                if updated.is_err() {
+                    break;
+                }
+                cx.background_executor().timer(POLL_INTERVAL).await;
+            }
+        });
+
+        let mut this = Self {
+            session,
+            project,
+            listening: Vec::new(),
+            labels: HashMap::default(),
+            auto_forwarded: HashSet::default(),
+            _poll_listening_ports: poll_listening_ports,
+        };
+        this.restore_labels(cx);
+        this
+    }
+
+    pub fn connection_string(&self) -> String {
+        self.session.connection_string().unwrap_or_default()
+    }
+
+    /// The ports that are either listening on the remote host or forwarded,
+    /// in ascending order.
+    pub fn ports(&self) -> Vec<u16> {
+        let mut ports = self.listening.iter().copied().collect::<BTreeSet<_>>();
+        ports.extend(
+            self.session
+                .port_forwards()
+                .into_iter()
+                .map(|forward| forward.remote_port),
+        );
+        ports.into_iter().collect()
+    }
+
+    pub fn is_listening(&self, remote_port: u16) -> bool {
+        self.listening.contains(&remote_port)
+    }
+
+    pub fn forwards(&self) -> Vec<SshPortForward> {
+        self.session.port_forwards()
+    }
+
+    pub fn forward_for(&self, remote_port: u16) -> Option<SshPortForward> {
+        self.session
+            .port_forwards()
+            .into_iter()
+            .find(|forward| forward.remote_port == remote_port)
+    }
+
+    pub fn label(&self, remote_port: u16) -> Option<&str> {
+        self.labels.get(&remote_port).map(String::as_str)
+    }
+
+    pub fn set_label(&mut self, remote_port: u16, label: String, cx: &mut ModelContext<Self>) {
+        let label = label.trim();
+        if label.is_empty() {
+            self.labels.remove(&remote_port);
+        } else {
+            self.labels.insert(remote_port, label.to_string());
+        }
+        self.serialize_labels(cx);
+        cx.notify();
+    }
+
+    /// Forwards a remote port to the same port on this machine, or to another
+    /// free port if that one is taken.
+    pub fn forward(&mut self, remote_port: u16, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let session = self.session.clone();
+        cx.spawn(|this, mut cx| async move {
+            let forward = SshPortForward {
+                local_port: available_local_port(remote_port)?,
+                remote_host: FORWARDED_HOST.to_string(),
+                remote_port,
+            };
+            session.add_port_forward(forward).await?;
+            this.update(&mut cx, |_, cx| cx.notify())
+        })
+    }
+
+    pub fn stop_forwarding(
+        &mut self,
+        local_port: u16,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let session = self.session.clone();
+        cx.spawn(|this, mut cx| async move {
+            session.remove_port_forward(local_port).await?;
+            this.update(&mut cx, |_, cx| cx.notify())
+        })
+    }
+
+    fn set_listening_ports(&mut self, mut ports: Vec<u16>, cx: &mut ModelContext<Self>) {
+        ports.sort_unstable();
+        ports.dedup();
+        for port in self.ports_to_auto_forward(&ports, cx) {
+            self.forward(port, cx).detach_and_log_err(cx);
+        }
+
+        if ports != self.listening {
+            self.listening = ports;
+            cx.notify();
+        }
+    }
+
+    /// The configured ports to forward now that they're listening. They're only
+    /// forwarded once, so that a forward the user stopped stays stopped.
+    fn ports_to_auto_forward(&mut self, listening: &[u16], cx: &AppContext) -> Vec<u16> {
+        self.auto_forwarded.retain(|port| listening.contains(port));
+        let auto_forward = &PortsPanelSettings::get_global(cx).auto_forward;
+        let mut ports = Vec::new();
+        for port in listening {
+            if auto_forward.contains(port)
+                && self.auto_forwarded.insert(*port)
+                && self.forward_for(*port).is_none()
+            {
+                ports.push(*port);
+            }
+        }
+        ports
+    }
+
+    /// The ports mentioned in the recent output of the tasks still running.
+    fn ports_in_task_output(&self, cx: &AppContext) -> Vec<u16> {
+        let Some(project) = self.project.upgrade() else {
+            return Vec::new();
+        };
+        project
+            .read(cx)
+            .local_terminal_handles()
+            .iter()
+            .filter_map(|terminal| terminal.upgrade())
+            .filter(|terminal| {
+                terminal
+                    .read(cx)
+                    .task()
+                    .map_or(false, |task| task.status == TaskStatus::Running)
+            })
+            .flat_map(|terminal| terminal.read(cx).last_n_non_empty_lines(TASK_OUTPUT_LINES))
+            .flat_map(|line| ports_in_output(&line))
+            .collect()
+    }
+
+    fn labels_key(&self) -> String {
+        format!("{PORT_LABELS_KEY_PREFIX}:{}", self.connection_string())
+    }
+
+    fn restore_labels(&mut self, cx: &mut ModelContext<Self>) {
+        let key = self.labels_key();
+        cx.spawn(|this, mut cx| async move {
+            let serialized = cx
+                .background_executor()
+                .spawn(async move { KEY_VALUE_STORE.read_kvp(&key) })
+                .await
+                .log_err()
+                .flatten();
+            let Some(labels) = serialized.and_then(|serialized| {
+                serde_json::from_str::<HashMap<u16, String>>(&serialized).log_err()
+            }) else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                for (port, label) in labels {
+                    this.labels.entry(port).or_insert(label);
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn serialize_labels(&self, cx: &mut ModelContext<Self>) {
+        let key = self.labels_key();
+        let labels = serde_json::to_string(&self.labels);
+        cx.background_executor()
+            .spawn(async move { KEY_VALUE_STORE.write_kvp(key, labels?).await })
+            .detach_and_log_err(cx);
+    }
+}
+
+fn ports_in_output(line: &str) -> Vec<u16> {
+    PORT_IN_OUTPUT_REGEX
+        .captures_iter(line)
+        .filter_map(|captures| captures.get(1).or_else(|| captures.get(2)))
+        .filter_map(|port| port.as_str().parse().ok())
+        .filter(|port| *port != 0)
+        .collect()
+}
+
+fn available_local_port(preferred: u16) -> Result<u16> {
+    if TcpListener::bind((FORWARDED_HOST, preferred)).is_ok() {
+        return Ok(preferred);
+    }
+    Ok(TcpListener::bind((FORWARDED_HOST, 0))?.local_addr()?.port())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::{Context as _, Model, SemanticVersion, TestAppContext};
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[test]
+    fn test_ports_in_output() {
+        assert_eq!(ports_in_output("Listening on port 3000"), [3000]);
+        assert_eq!(ports_in_output("  Local:   http://localhost:5173/"), [5173]);
+        assert_eq!(
+            ports_in_output("Uvicorn running on http://127.0.0.1:8000 (Press CTRL+C to quit)"),
+            [8000]
+        );
+        assert_eq!(
+            ports_in_output("bound to [::]:8080 and [::1]:8081"),
+            [8080, 8081]
+        );
+        assert!(ports_in_output("src/main.rs:1234:5: error").is_empty());
+        assert!(ports_in_output("fetching https://example.com:443/").is_empty());
+        assert!(ports_in_output("port 99999").is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_auto_forward(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+        let ports = init_test(cx, server_cx).await;
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<PortsPanelSettings>(cx, |settings| {
+                    settings.auto_forward = Some(vec![3000]);
+                });
+            });
+        });
+
+        ports.update(cx, |ports, cx| {
+            assert_eq!(ports.ports_to_auto_forward(&[3000, 4000], cx), [3000]);
+            // A forward the user stopped isn't started again while the port
+            // is still listening.
+            assert!(ports.ports_to_auto_forward(&[3000, 4000], cx).is_empty());
+            assert!(ports.ports_to_auto_forward(&[4000], cx).is_empty());
+            assert_eq!(ports.ports_to_auto_forward(&[3000], cx), [3000]);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_port_labels(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+        let ports = init_test(cx, server_cx).await;
+        ports.update(cx, |ports, cx| {
+            ports.set_label(3000, "  web  ".to_string(), cx);
+            ports.set_label(4000, "api".to_string(), cx);
+            ports.set_label(4000, " ".to_string(), cx);
+            assert_eq!(ports.label(3000), Some("web"));
+            assert_eq!(ports.label(4000), None);
+        });
+        cx.run_until_parked();
+
+        // The labels are restored for the same host.
+        let (session, project) = ports.read_with(cx, |ports, _| {
+            (ports.session.clone(), ports.project.clone())
+        });
+        let restored_ports = cx.new_model(|cx| Ports::new(session, project, cx));
+        cx.run_until_parked();
+        restored_ports.read_with(cx, |ports, _| {
+            assert_eq!(ports.label(3000), Some("web"));
+            assert_eq!(ports.label(4000), None);
+        });
+    }
+
+    async fn init_test(cx: &mut TestAppContext, server_cx: &mut TestAppContext) -> Model<Ports> {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            release_channel::init(SemanticVersion::default(), cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            PortsPanelSettings::register(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/project", json!({ "a.txt": "" })).await;
+        let project = Project::test(fs, ["/project".as_ref()], cx).await;
+        let (session, _) = SshSession::fake(cx, server_cx);
+        cx.new_model(|cx| Ports::new(session, project.downgrade(), cx))
+    }
+}
@@ -0,0 +1,362 @@
+//! A panel listing the ports listening on the host of a remote project, which
+//! forwards them to this machine.
+mod port_forwarding_indicator;
+mod ports;
+mod ports_panel_settings;
+
+use std::sync::Arc;
+
+use editor::Editor;
+use gpui::{
+    actions, Action, AppContext, AsyncWindowContext, EventEmitter, FocusHandle, FocusableView,
+    Model, Subscription, View, WeakView,
+};
+use menu::{Cancel, Confirm};
+use project::Fs;
+use settings::Settings;
+use ui::{prelude::*, IconButtonShape, Indicator, ListItem, Tooltip};
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    notifications::NotifyTaskExt,
+    Workspace,
+};
+
+pub use port_forwarding_indicator::PortForwardingIndicator;
+pub use ports::Ports;
+pub use ports_panel_settings::{
+    PortsPanelDockPosition, PortsPanelSettings, PortsPanelSettingsContent,
+};
+
+actions!(ports_panel, [ToggleFocus]);
+
+pub fn init(cx: &mut AppContext) {
+    PortsPanelSettings::register(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, cx| {
+            workspace.toggle_panel_focus::<PortsPanel>(cx);
+        });
+    })
+    .detach();
+}
+
+pub struct PortsPanel {
+    ports: Option<Model<Ports>>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    size: Option<Pixels>,
+    label_editor: Option<(u16, View<Editor>)>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl PortsPanel {
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<View<Self>> {
+        workspace.update(&mut cx, |workspace, cx| Self::new(workspace, cx))
+    }
+
+    fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let fs = workspace.app_state().fs.clone();
+        let project = workspace.project().clone();
+        let ports = project
+            .read(cx)
+            .ssh_session()
+            .cloned()
+            .map(|session| cx.new_model(|cx| Ports::new(session, project.downgrade(), cx)));
+
+        if let Some(ports) = &ports {
+            let indicator = cx.new_view(|cx| PortForwardingIndicator::new(ports.clone(), cx));
+            workspace.status_bar().update(cx, |status_bar, cx| {
+                status_bar.add_right_item(indicator, cx);
+            });
+        }
+
+        cx.new_view(|cx| {
+            let subscriptions = ports
+                .iter()
+                .map(|ports| cx.observe(ports, |_, _, cx| cx.notify()))
+                .collect();
+            Self {
+                ports,
+                fs,
+                focus_handle: cx.focus_handle(),
+                size: None,
+                label_editor: None,
+                _subscriptions: subscriptions,
+            }
+        })
+    }
+
+    fn forward(&mut self, remote_port: u16, cx: &mut ViewContext<Self>) {
+        if let Some(ports) = &self.ports {
+            ports
+                .update(cx, |ports, cx| ports.forward(remote_port, cx))
+                .detach_and_notify_err(cx);
+        }
+    }
+
+    fn stop_forwarding(&mut self, local_port: u16, cx: &mut ViewContext<Self>) {
+        if let Some(ports) = &self.ports {
+            ports
+                .update(cx, |ports, cx| ports.stop_forwarding(local_port, cx))
+                .detach_and_notify_err(cx);
+        }
+    }
+
+    fn edit_label(&mut self, remote_port: u16, cx: &mut ViewContext<Self>) {
+        let Some(ports) = &self.ports else {
+            return;
+        };
+        let label = ports
+            .read(cx)
+            .label(remote_port)
+            .unwrap_or_default()
+            .to_string();
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Label", cx);
+            editor.set_text(label, cx);
+            editor
+        });
+        cx.focus_view(&editor);
+        self.label_editor = Some((remote_port, editor));
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
+        let Some((remote_port, editor)) = self.label_editor.take() else {
+            return;
+        };
+        let label = editor.read(cx).text(cx);
+        if let Some(ports) = &self.ports {
+            ports.update(cx, |ports, cx| ports.set_label(remote_port, label, cx));
+        }
+        cx.focus(&self.focus_handle);
+        cx.notify();
+    }
+
+    fn cancel(&mut self, _: &Cancel, cx: &mut ViewContext<Self>) {
+        if self.label_editor.take().is_some() {
+            cx.focus(&self.focus_handle);
+            cx.notify();
+        }
+    }
+
+    fn render_port(&self, ports: &Ports, remote_port: u16, cx: &ViewContext<Self>) -> ListItem {
+        let forward = ports.forward_for(remote_port);
+        let listening = ports.is_listening(remote_port);
+        let title = match &self.label_editor {
+            Some((port, editor)) if *port == remote_port => {
+                div().w_full().child(editor.clone()).into_any_element()
+            }
+            _ => Label::new(
+                ports
+                    .label(remote_port)
+                    .map_or_else(|| remote_port.to_string(), ToString::to_string),
+            )
+            .single_line()
+            .into_any_element(),
+        };
+
+        ListItem::new(("port", remote_port as usize))
+            .start_slot(Indicator::dot().color(if forward.is_some() {
+                Color::Success
+            } else if listening {
+                Color::Muted
+            } else {
+                Color::Disabled
+            }))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(title)
+                    .when(ports.label(remote_port).is_some(), |this| {
+                        this.child(
+                            Label::new(remote_port.to_string())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    }),
+            )
+            .end_slot(
+                h_flex()
+                    .gap_1()
+                    .map(|this| match forward {
+                        Some(forward) => {
+                            let local_port = forward.local_port;
+                            this.child(
+                                Label::new(format!("localhost:{local_port}"))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .child(
+                                IconButton::new(
+                                    ("open-port", local_port as usize),
+                                    IconName::ArrowUpRight,
+                                )
+                                .shape(IconButtonShape::Square)
+                                .icon_size(IconSize::XSmall)
+                                .tooltip(|cx| Tooltip::text("Open in Browser", cx))
+                                .on_click(move |_, cx| {
+                                    cx.open_url(&format!("http://localhost:{local_port}"))
+                                }),
+                            )
+                            .child(
+                                IconButton::new(
+                                    ("stop-forwarding", local_port as usize),
+                                    IconName::Close,
+                                )
+                                .shape(IconButtonShape::Square)
+                                .icon_size(IconSize::XSmall)
+                                .tooltip(|cx| Tooltip::text("Stop Forwarding", cx))
+                                .on_click(cx.listener(
+                                    move |this, _, cx| this.stop_forwarding(local_port, cx),
+                                )),
+                            )
+                        }
+                        None => this.child(
+                            IconButton::new(("forward-port", remote_port as usize), IconName::Link)
+                                .shape(IconButtonShape::Square)
+                                .icon_size(IconSize::XSmall)
+                                .tooltip(|cx| Tooltip::text("Forward Port", cx))
+                                .on_click(
+                                    cx.listener(move |this, _, cx| this.forward(remote_port, cx)),
+                                ),
+                        ),
+                    })
+                    .child(
+                        IconButton::new(
+                            ("edit-port-label", remote_port as usize),
+                            IconName::Pencil,
+                        )
+                        .shape(IconButtonShape::Square)
+                        .icon_size(IconSize::XSmall)
+                        .tooltip(|cx| Tooltip::text("Edit Label", cx))
+                        .on_click(cx.listener(move |this, _, cx| this.edit_label(remote_port, cx))),
+                    ),
+            )
+    }
+}
+
+impl Panel for PortsPanel {
+    fn persistent_name() -> &'static str {
+        "Ports Panel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        match PortsPanelSettings::get_global(cx).dock {
+            PortsPanelDockPosition::Left => DockPosition::Left,
+            PortsPanelDockPosition::Right => DockPosition::Right,
+            PortsPanelDockPosition::Bottom => DockPosition::Bottom,
+        }
+    }
+
+    fn position_is_valid(&self, _: DockPosition) -> bool {
+        true
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<PortsPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| {
+                let dock = match position {
+                    DockPosition::Left => PortsPanelDockPosition::Left,
+                    DockPosition::Right => PortsPanelDockPosition::Right,
+                    DockPosition::Bottom => PortsPanelDockPosition::Bottom,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.size
+            .unwrap_or_else(|| PortsPanelSettings::get_global(cx).default_size)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.size = size;
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        (self.ports.is_some() && PortsPanelSettings::get_global(cx).button)
+            .then_some(IconName::Link)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Ports Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+}
+
+impl FocusableView for PortsPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for PortsPanel {}
+
+impl Render for PortsPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let content = match &self.ports {
+            None => div()
+                .p_2()
+                .child(
+                    Label::new("Port forwarding is available in projects opened over SSH")
+                        .color(Color::Muted),
+                )
+                .into_any_element(),
+            Some(ports) => {
+                let ports = ports.read(cx);
+                let remote_ports = ports.ports();
+                v_flex()
+                    .child(
+                        h_flex()
+                            .px_2()
+                            .py_1()
+                            .border_b_1()
+                            .border_color(cx.theme().colors().border)
+                            .child(Label::new(ports.connection_string()).color(Color::Muted)),
+                    )
+                    .child(if remote_ports.is_empty() {
+                        div()
+                            .p_2()
+                            .child(
+                                Label::new("No ports are listening on the remote host")
+                                    .color(Color::Muted),
+                            )
+                            .into_any_element()
+                    } else {
+                        v_flex()
+                            .id("ports")
+                            .flex_1()
+                            .overflow_y_scroll()
+                            .children(
+                                remote_ports
+                                    .into_iter()
+                                    .map(|remote_port| self.render_port(ports, remote_port, cx)),
+                            )
+                            .into_any_element()
+                    })
+                    .into_any_element()
+            }
+        };
+
+        v_flex()
+            .id("ports-panel")
+            .size_full()
+            .key_context("PortsPanel")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .child(content)
+    }
+}
@@ -0,0 +1,53 @@
+use gpui::Pixels;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PortsPanelDockPosition {
+    Left,
+    Right,
+    Bottom,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct PortsPanelSettings {
+    pub button: bool,
+    pub default_size: Pixels,
+    pub dock: PortsPanelDockPosition,
+    pub auto_forward: Vec<u16>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct PortsPanelSettingsContent {
+    /// Whether to show the ports panel button in the status bar of remote projects.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Customize default width or height (in pixels) taken by the ports panel
+    ///
+    /// Default: 240
+    pub default_size: Option<f32>,
+    /// The position of the ports panel
+    ///
+    /// Default: bottom
+    pub dock: Option<PortsPanelDockPosition>,
+    /// Remote ports to forward as soon as something starts listening on them.
+    ///
+    /// Default: []
+    pub auto_forward: Option<Vec<u16>>,
+}
+
+impl Settings for PortsPanelSettings {
+    const KEY: Option<&'static str> = Some("ports_panel");
+
+    type FileContent = PortsPanelSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
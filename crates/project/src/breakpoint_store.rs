@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+
+use gpui::{AppContext, EventEmitter, Model, ModelContext, Subscription, WeakModel};
+use language::{Buffer, Point};
+
+use crate::{
+    buffer_store::{BufferStore, BufferStoreEvent},
+    Item as _, ProjectPath,
+};
+
+/// The line breakpoints set in the project's files.
+///
+/// Breakpoints are stored as rows, which are kept up to date through edits
+/// while the file is open.
+pub struct BreakpointStore {
+    files: BTreeMap<ProjectPath, FileBreakpoints>,
+    _subscription: Subscription,
+}
+
+#[derive(Default)]
+struct FileBreakpoints {
+    /// Sorted, zero-based rows.
+    rows: Vec<u32>,
+    buffer: Option<OpenBuffer>,
+}
+
+struct OpenBuffer {
+    buffer: WeakModel<Buffer>,
+    anchors: Vec<text::Anchor>,
+    _subscription: Subscription,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BreakpointStoreEvent {
+    BreakpointsChanged(ProjectPath),
+}
+
+impl EventEmitter<BreakpointStoreEvent> for BreakpointStore {}
+
+impl BreakpointStore {
+    pub fn new(buffer_store: &Model<BufferStore>, cx: &mut ModelContext<Self>) -> Self {
+        Self {
+            files: BTreeMap::default(),
+            _subscription: cx.subscribe(buffer_store, |this, _, event, cx| {
+                if let BufferStoreEvent::BufferAdded(buffer) = event {
+                    if let Some(path) = buffer.read(cx).project_path(cx) {
+                        if this.files.contains_key(&path) {
+                            this.track_buffer(path, buffer, cx);
+                        }
+                    }
+                }
+            }),
+        }
+    }
+
+    /// The files containing breakpoints, and the rows of their breakpoints.
+    pub fn breakpoints(&self) -> impl Iterator<Item = (&ProjectPath, &[u32])> {
+        self.files
+            .iter()
+            .filter(|(_, file)| !file.rows.is_empty())
+            .map(|(path, file)| (path, file.rows.as_slice()))
+    }
+
+    pub fn rows(&self, path: &ProjectPath) -> &[u32] {
+        self.files
+            .get(path)
+            .map_or(&[], |file| file.rows.as_slice())
+    }
+
+    pub fn rows_for_buffer(&self, buffer: &Model<Buffer>, cx: &AppContext) -> &[u32] {
+        match buffer.read(cx).project_path(cx) {
+            Some(path) => self.rows(&path),
+            None => &[],
+        }
+    }
+
+    /// Adds a breakpoint on the given row of the buffer, or removes the one
+    /// already there.
+    pub fn toggle(&mut self, buffer: &Model<Buffer>, row: u32, cx: &mut ModelContext<Self>) {
+        let Some(path) = buffer.read(cx).project_path(cx) else {
+            return;
+        };
+        let mut rows = self.rows(&path).to_vec();
+        match rows.binary_search(&row) {
+            Ok(ix) => {
+                rows.remove(ix);
+            }
+            Err(ix) => rows.insert(ix, row),
+        }
+        self.set_rows(path.clone(), rows, cx);
+        self.track_buffer(path, buffer, cx);
+    }
+
+    pub fn remove(&mut self, path: &ProjectPath, row: u32, cx: &mut ModelContext<Self>) {
+        let mut rows = self.rows(path).to_vec();
+        if let Ok(ix) = rows.binary_search(&row) {
+            rows.remove(ix);
+            self.set_rows(path.clone(), rows, cx);
+        }
+    }
+
+    pub fn clear(&mut self, cx: &mut ModelContext<Self>) {
+        for (path, file) in std::mem::take(&mut self.files) {
+            if !file.rows.is_empty() {
+                cx.emit(BreakpointStoreEvent::BreakpointsChanged(path));
+            }
+        }
+    }
+
+    /// Replaces the breakpoints of a file, e.g. when restoring them from a
+    /// previous session.
+    pub fn set_rows(&mut self, path: ProjectPath, mut rows: Vec<u32>, cx: &mut ModelContext<Self>) {
+        rows.sort_unstable();
+        rows.dedup();
+        let file = self.files.entry(path.clone()).or_default();
+        if file.rows == rows {
+            return;
+        }
+        file.rows = rows;
+        if let Some(open_buffer) = file.buffer.as_mut() {
+            if let Some(buffer) = open_buffer.buffer.upgrade() {
+                let snapshot = buffer.read(cx).snapshot();
+                open_buffer.anchors = file
+                    .rows
+                    .iter()
+                    .map(|row| snapshot.anchor_after(Point::new(*row, 0)))
+                    .collect();
+            }
+        }
+        cx.emit(BreakpointStoreEvent::BreakpointsChanged(path));
+    }
+
+    fn track_buffer(
+        &mut self,
+        path: ProjectPath,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(file) = self.files.get_mut(&path) else {
+            return;
+        };
+        if file.buffer.as_ref().map_or(false, |open_buffer| {
+            open_buffer.buffer == buffer.downgrade()
+        }) {
+            return;
+        }
+
+        let snapshot = buffer.read(cx).snapshot();
+        let max_row = snapshot.max_point().row;
+        file.rows.retain(|row| *row <= max_row);
+        let anchors = file
+            .rows
+            .iter()
+            .map(|row| snapshot.anchor_after(Point::new(*row, 0)))
+            .collect();
+        let subscription = cx.subscribe(buffer, move |this, buffer, event, cx| {
+            if matches!(event, language::Event::Edited | language::Event::Reloaded) {
+                this.buffer_edited(&path, &buffer, cx);
+            }
+        });
+        file.buffer = Some(OpenBuffer {
+            buffer: buffer.downgrade(),
+            anchors,
+            _subscription: subscription,
+        });
+    }
+
+    fn buffer_edited(
+        &mut self,
+        path: &ProjectPath,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(file) = self.files.get_mut(path) else {
+            return;
+        };
+        let Some(open_buffer) = file.buffer.as_mut() else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let mut rows = open_buffer
+            .anchors
+            .iter()
+            .map(|anchor| snapshot.summary_for_anchor::<Point>(anchor).row)
+            .collect::<Vec<_>>();
+        rows.dedup();
+        if rows != file.rows {
+            // Breakpoints on deleted lines collapse into one.
+            open_buffer.anchors = rows
+                .iter()
+                .map(|row| snapshot.anchor_after(Point::new(*row, 0)))
+                .collect();
+            file.rows = rows;
+            cx.emit(BreakpointStoreEvent::BreakpointsChanged(path.clone()));
+        }
+    }
+}
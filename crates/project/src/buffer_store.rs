@@ -8,7 +8,10 @@ use client::Client;
 use collections::{hash_map, HashMap, HashSet};
 use fs::Fs;
 use futures::{channel::oneshot, stream::FuturesUnordered, StreamExt};
-use git::blame::Blame;
+use git::{
+    blame::Blame,
+    repository::{GitRepository, RepoPath},
+};
 use gpui::{
     AppContext, AsyncAppContext, Context as _, EventEmitter, Model, ModelContext, Task, WeakModel,
 };
@@ -76,7 +79,6 @@ impl BufferStore {
         client.add_model_message_handler(Self::handle_buffer_saved);
         client.add_model_message_handler(Self::handle_update_buffer_file);
         client.add_model_message_handler(Self::handle_update_diff_base);
-        client.add_model_request_handler(Self::handle_save_buffer);
         client.add_model_request_handler(Self::handle_blame_buffer);
     }
 
@@ -436,13 +438,42 @@ impl BufferStore {
             return Task::ready(Err(anyhow!("buffer doesn't have a file")));
         };
         match file.worktree.read(cx) {
-            Worktree::Local(_) => {
-                self.save_local_buffer(file.worktree.clone(), buffer, file.path.clone(), false, cx)
-            }
+            Worktree::Local(_) => self.save_local_buffer(
+                file.worktree.clone(),
+                buffer,
+                file.path.clone(),
+                false,
+                false,
+                cx,
+            ),
             Worktree::Remote(tree) => self.save_remote_buffer(buffer, None, tree, cx),
         }
     }
 
+    /// Saves a buffer to a file the user isn't allowed to write, as an administrator.
+    pub fn save_buffer_elevated(
+        &mut self,
+        buffer: Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(file) = File::from_dyn(buffer.read(cx).file()) else {
+            return Task::ready(Err(anyhow!("buffer doesn't have a file")));
+        };
+        match file.worktree.read(cx) {
+            Worktree::Local(_) => self.save_local_buffer(
+                file.worktree.clone(),
+                buffer,
+                file.path.clone(),
+                false,
+                true,
+                cx,
+            ),
+            Worktree::Remote(_) => Task::ready(Err(anyhow!(
+                "remote buffers can't be saved as an administrator"
+            ))),
+        }
+    }
+
     pub fn save_buffer_as(
         &mut self,
         buffer: Model<Buffer>,
@@ -461,7 +492,7 @@ impl BufferStore {
 
         let task = match worktree.read(cx) {
             Worktree::Local(_) => {
-                self.save_local_buffer(worktree, buffer.clone(), path.path, true, cx)
+                self.save_local_buffer(worktree, buffer.clone(), path.path, true, false, cx)
             }
             Worktree::Remote(tree) => {
                 self.save_remote_buffer(buffer.clone(), Some(path.to_proto()), tree, cx)
@@ -481,6 +512,7 @@ impl BufferStore {
         buffer_handle: Model<Buffer>,
         path: Arc<Path>,
         mut has_changed_file: bool,
+        elevated: bool,
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<()>> {
         let buffer = buffer_handle.read(cx);
@@ -493,7 +525,11 @@ impl BufferStore {
         }
 
         let save = worktree.update(cx, |worktree, cx| {
-            worktree.write_file(path.as_ref(), text, line_ending, cx)
+            if elevated {
+                worktree.write_file_elevated(path.as_ref(), text, line_ending, cx)
+            } else {
+                worktree.write_file(path.as_ref(), text, line_ending, cx)
+            }
         });
 
         cx.spawn(move |this, mut cx| async move {
@@ -621,6 +657,56 @@ impl BufferStore {
         }
     }
 
+    /// Loads the content of the buffer's file in the HEAD commit of its repository.
+    pub fn load_head_text(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Task<Result<Option<String>>> {
+        let repository = Self::local_repository_for_buffer(buffer, cx);
+        cx.background_executor().spawn(async move {
+            let (repo, relative_path) = repository?;
+            Ok(repo.load_head_text(&relative_path))
+        })
+    }
+
+    /// Writes the content of the buffer's file to the index of its repository,
+    /// staging it.
+    pub fn set_index_text(
+        &self,
+        buffer: &Model<Buffer>,
+        text: String,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        let repository = Self::local_repository_for_buffer(buffer, cx);
+        cx.background_executor().spawn(async move {
+            let (repo, relative_path) = repository?;
+            repo.set_index_text(&relative_path, text)
+                .with_context(|| format!("Failed to stage {:?}", relative_path.0))
+        })
+    }
+
+    /// Returns the repository containing the buffer's file and the path of the
+    /// file in it. Repositories are only available in local projects.
+    pub fn local_repository_for_buffer(
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Result<(Arc<dyn GitRepository>, RepoPath)> {
+        let file = File::from_dyn(buffer.read(cx).file()).context("buffer has no file")?;
+        let Worktree::Local(worktree) = file.worktree.read(cx) else {
+            anyhow::bail!("git repositories are only available in local projects");
+        };
+        let worktree = worktree.snapshot();
+        let (repo_entry, local_repo_entry) = match worktree.repo_for_path(&file.path) {
+            Some(repo_for_path) => repo_for_path,
+            None => anyhow::bail!(NoRepositoryError {}),
+        };
+        let relative_path = repo_entry
+            .relativize(&worktree, &file.path)
+            .context("failed to relativize buffer path")?;
+        Ok((local_repo_entry.repo().clone(), relative_path))
+    }
+
     fn add_buffer(&mut self, buffer: Model<Buffer>, cx: &mut ModelContext<Self>) -> Result<()> {
         let remote_id = buffer.read(cx).remote_id();
         let is_remote = buffer.read(cx).replica_id() != 0;
@@ -1075,15 +1161,13 @@ impl BufferStore {
         &mut self,
         envelope: TypedEnvelope<proto::SynchronizeBuffers>,
         cx: &mut ModelContext<Self>,
-        client: Arc<Client>,
+        client: AnyProtoClient,
     ) -> Result<proto::SynchronizeBuffersResponse> {
         let project_id = envelope.payload.project_id;
         let mut response = proto::SynchronizeBuffersResponse {
             buffers: Default::default(),
         };
-        let Some(guest_id) = envelope.original_sender_id else {
-            anyhow::bail!("missing original_sender_id on SynchronizeBuffers request");
-        };
+        let guest_id = envelope.original_sender_id.unwrap_or(envelope.sender_id);
 
         self.shared_buffers.entry(guest_id).or_default().clear();
         for buffer in envelope.payload.buffers {
@@ -0,0 +1,222 @@
+use crate::project_settings::CustomLanguageServerSettings;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use collections::HashMap;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use gpui::AsyncAppContext;
+use language::{LanguageRegistry, LanguageServerName, LspAdapter, LspAdapterDelegate};
+use lsp::LanguageServerBinary;
+use std::{
+    any::Any,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use util::ResultExt;
+use worktree::Worktree;
+
+/// A language server adapter for a language server defined in the
+/// `custom_language_servers` settings, rather than by Zed or an extension.
+///
+/// The language server binary is never downloaded: it is expected to be
+/// installed by the user.
+pub(crate) struct CustomLspAdapter {
+    name: LanguageServerName,
+    settings: CustomLanguageServerSettings,
+}
+
+#[async_trait(?Send)]
+impl LspAdapter for CustomLspAdapter {
+    fn name(&self) -> LanguageServerName {
+        self.name.clone()
+    }
+
+    async fn check_if_user_installed(
+        &self,
+        delegate: &dyn LspAdapterDelegate,
+        _: &AsyncAppContext,
+    ) -> Option<LanguageServerBinary> {
+        let command = Path::new(&self.settings.command);
+        let path = if command.is_absolute() {
+            command.to_path_buf()
+        } else {
+            delegate.which(command.as_os_str()).await?
+        };
+
+        let mut env = delegate.shell_env().await;
+        env.extend(self.settings.env.clone());
+
+        Some(LanguageServerBinary {
+            path,
+            arguments: self
+                .settings
+                .arguments
+                .iter()
+                .map(|arg| arg.into())
+                .collect(),
+            env: Some(env),
+        })
+    }
+
+    async fn fetch_latest_server_version(
+        &self,
+        _: &dyn LspAdapterDelegate,
+    ) -> Result<Box<dyn 'static + Send + Any>> {
+        Err(anyhow!(
+            "could not find `{}` for language server {}",
+            self.settings.command,
+            self.name.0
+        ))
+    }
+
+    async fn fetch_server_binary(
+        &self,
+        _: Box<dyn 'static + Send + Any>,
+        _: PathBuf,
+        _: &dyn LspAdapterDelegate,
+    ) -> Result<LanguageServerBinary> {
+        Err(anyhow!(
+            "custom language server {} cannot be downloaded",
+            self.name.0
+        ))
+    }
+
+    async fn cached_server_binary(
+        &self,
+        _: PathBuf,
+        _: &dyn LspAdapterDelegate,
+    ) -> Option<LanguageServerBinary> {
+        None
+    }
+
+    fn can_be_reinstalled(&self) -> bool {
+        false
+    }
+
+    async fn installation_test_binary(&self, _: PathBuf) -> Option<LanguageServerBinary> {
+        None
+    }
+}
+
+/// The language servers defined in the `custom_language_servers` settings,
+/// kept in sync with the [`LanguageRegistry`].
+#[derive(Default)]
+pub(crate) struct CustomLanguageServers {
+    definitions: HashMap<Arc<str>, CustomLanguageServerSettings>,
+    file_globs: HashMap<LanguageServerName, GlobSet>,
+}
+
+impl CustomLanguageServers {
+    /// Registers new and changed language server definitions with the language
+    /// registry, and removes the ones that are no longer defined.
+    ///
+    /// Returns the names of the language servers whose definition changed.
+    pub fn update(
+        &mut self,
+        definitions: &HashMap<Arc<str>, CustomLanguageServerSettings>,
+        languages: &LanguageRegistry,
+    ) -> Vec<LanguageServerName> {
+        let mut changed = Vec::new();
+
+        for (name, old_definition) in &self.definitions {
+            if definitions.get(name) == Some(old_definition) {
+                continue;
+            }
+            let server_name = LanguageServerName(name.clone());
+            for language in &old_definition.languages {
+                languages.remove_lsp_adapter(language, &server_name);
+            }
+            self.file_globs.remove(&server_name);
+            changed.push(server_name);
+        }
+
+        for (name, definition) in definitions {
+            if self.definitions.get(name) == Some(definition) {
+                continue;
+            }
+            let server_name = LanguageServerName(name.clone());
+            for language in &definition.languages {
+                languages.register_lsp_adapter(
+                    language.clone(),
+                    Arc::new(CustomLspAdapter {
+                        name: server_name.clone(),
+                        settings: definition.clone(),
+                    }),
+                );
+            }
+            if !definition.file_globs.is_empty() {
+                if let Some(globs) = build_glob_set(&definition.file_globs).log_err() {
+                    self.file_globs.insert(server_name.clone(), globs);
+                }
+            }
+            if !changed.contains(&server_name) {
+                changed.push(server_name);
+            }
+        }
+
+        self.definitions = definitions.clone();
+        changed
+    }
+
+    /// Returns whether the language server should be attached to the file at
+    /// the given worktree-relative path.
+    pub fn handles_path(&self, server_name: &LanguageServerName, path: &Path) -> bool {
+        self.file_globs
+            .get(server_name)
+            .map_or(true, |globs| globs.is_match(path))
+    }
+
+    /// Returns whether the language server should be started in the given worktree.
+    pub fn should_start(&self, server_name: &LanguageServerName, worktree: &Worktree) -> bool {
+        self.definitions
+            .get(&server_name.0)
+            .map_or(true, |definition| {
+                has_root_marker(&definition.root_markers, |marker| {
+                    worktree.entry_for_path(marker).is_some()
+                })
+            })
+    }
+}
+
+fn build_glob_set(globs: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob)?);
+    }
+    Ok(builder.build()?)
+}
+
+fn has_root_marker(root_markers: &[String], exists: impl Fn(&str) -> bool) -> bool {
+    root_markers.is_empty() || root_markers.iter().any(|marker| exists(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_root_marker() {
+        assert!(has_root_marker(&[], |_| false));
+        assert!(has_root_marker(
+            &["build.zig".into(), "flake.nix".into()],
+            |marker| marker == "flake.nix"
+        ));
+        assert!(!has_root_marker(&["build.zig".into()], |_| false));
+    }
+
+    #[test]
+    fn test_file_globs() {
+        let mut servers = CustomLanguageServers::default();
+        let name = LanguageServerName("nim-ls".into());
+        servers.file_globs.insert(
+            name.clone(),
+            build_glob_set(&["src/**/*.nim".into()]).unwrap(),
+        );
+
+        assert!(servers.handles_path(&name, Path::new("src/foo/bar.nim")));
+        assert!(!servers.handles_path(&name, Path::new("tests/bar.nim")));
+        assert!(servers.handles_path(
+            &LanguageServerName("other".into()),
+            Path::new("tests/bar.nim")
+        ));
+    }
+}
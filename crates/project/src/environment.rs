@@ -4,14 +4,17 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
-use util::{parse_env_output, ResultExt};
+use util::{parse_env_output, paths::home_dir, ResultExt};
 
 use collections::HashMap;
 use gpui::{AppContext, Context, Model, ModelContext, Task};
 use settings::Settings as _;
 use worktree::WorktreeId;
 
-use crate::project_settings::{DirenvSettings, ProjectSettings};
+use crate::{
+    project_settings::{DirenvSettings, ProjectSettings},
+    worktree_trust::WorktreeTrust,
+};
 
 pub(crate) struct ProjectEnvironment {
     cli_environment: Option<HashMap<String, String>>,
@@ -50,6 +53,13 @@ impl ProjectEnvironment {
         self.cached_shell_environments.remove(&worktree_id);
     }
 
+    /// Loads the worktree's environment again the next time it's needed, as it's
+    /// loaded differently once the worktree is trusted or restricted.
+    pub(crate) fn reload_worktree_environment(&mut self, worktree_id: WorktreeId) {
+        self.remove_worktree_environment(worktree_id);
+        self.get_environment_task = None;
+    }
+
     /// Returns the inherited CLI environment, if this project was opened from the Zed CLI.
     pub(crate) fn get_cli_environment(&self) -> Option<HashMap<String, String>> {
         if let Some(mut env) = self.cli_environment.clone() {
@@ -111,15 +121,19 @@ impl ProjectEnvironment {
         if let Some(env) = cached_env {
             Task::ready(Some(env))
         } else {
-            let load_direnv = ProjectSettings::get_global(cx).load_direnv.clone();
+            let mut load_direnv = ProjectSettings::get_global(cx).load_direnv.clone();
+            let mut cwd = worktree_abs_path.clone();
+            // The shell hooks of restricted worktrees would run the scripts they contain,
+            // so the environment is loaded from the home directory instead.
+            if WorktreeTrust::is_restricted(worktree_id, cx) {
+                load_direnv = DirenvSettings::ShellHook;
+                cwd = home_dir().as_path().into();
+            }
 
             cx.spawn(|this, mut cx| async move {
                 let mut shell_env = cx
                     .background_executor()
-                    .spawn({
-                        let cwd = worktree_abs_path.clone();
-                        async move { load_shell_environment(&cwd, &load_direnv).await }
-                    })
+                    .spawn(async move { load_shell_environment(&cwd, &load_direnv).await })
                     .await
                     .ok();
 
@@ -0,0 +1,286 @@
+use crate::{
+    spell_checking::{is_checked_scope, replace_text},
+    CodeAction, File, Project, ProjectTransaction,
+};
+use anyhow::{anyhow, Context as _, Result};
+use collections::{HashMap, HashSet};
+use gpui::{Model, ModelContext, Task};
+use language::{
+    language_settings::{language_settings, GrammarCheckTrigger},
+    Anchor, Buffer, BufferSnapshot, Diagnostic, DiagnosticEntry, DiagnosticSeverity, PointUtf16,
+    ToOffset as _, Unclipped,
+};
+use lsp::LanguageServerId;
+use serde::{Deserialize, Serialize};
+use std::{ops::Range, time::Duration};
+use text::{BufferId, OffsetUtf16};
+use util::ResultExt as _;
+
+/// The id under which grammar and style problems are reported as diagnostics, as
+/// they don't come from a language server.
+pub const GRAMMAR_CHECK_SERVER_ID: LanguageServerId = LanguageServerId(usize::MAX - 2);
+
+/// The source of the diagnostics reported for grammar and style problems.
+pub const GRAMMAR_CHECK_SOURCE: &str = "grammar check";
+
+/// The maximum number of replacements offered for a problem.
+const MAX_REPLACEMENTS: usize = 5;
+
+pub(crate) const GRAMMAR_CHECK_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(1000);
+
+#[derive(Default)]
+pub(crate) struct GrammarChecking {
+    pending_checks: HashMap<BufferId, Task<()>>,
+    /// The buffers in which problems were reported.
+    checked_buffers: HashSet<BufferId>,
+}
+
+/// The replacements suggested by the checker, stored in the data of diagnostics.
+#[derive(Debug, Serialize, Deserialize)]
+struct Replacements {
+    replacements: Vec<String>,
+}
+
+/// What a grammar checking code action does, stored in its data.
+#[derive(Debug, Serialize, Deserialize)]
+struct Replace {
+    replacement: String,
+}
+
+impl Project {
+    /// Checks the grammar and style of the buffer with the configured checker, and
+    /// reports the problems as diagnostics.
+    ///
+    /// `trigger` is the reason for the check, if it's an edit or a save, which
+    /// only trigger checks when the buffer's settings say so.
+    pub(crate) fn check_grammar(
+        &mut self,
+        buffer_handle: &Model<Buffer>,
+        trigger: Option<GrammarCheckTrigger>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if !self.is_local() {
+            return;
+        }
+        let buffer = buffer_handle.read(cx);
+        let buffer_id = buffer.remote_id();
+        let Some(abs_path) = File::from_dyn(buffer.file()).map(|file| file.abs_path(cx)) else {
+            return;
+        };
+        let settings = language_settings(buffer.language(), buffer.file(), cx)
+            .grammar_check
+            .clone();
+        if !settings.enabled {
+            self.grammar_checking.pending_checks.remove(&buffer_id);
+            if self.grammar_checking.checked_buffers.remove(&buffer_id) {
+                self.update_diagnostic_entries(
+                    GRAMMAR_CHECK_SERVER_ID,
+                    abs_path,
+                    None,
+                    Vec::new(),
+                    cx,
+                )
+                .log_err();
+            }
+            return;
+        }
+        if trigger.map_or(false, |trigger| trigger != settings.check_on) {
+            return;
+        }
+
+        let snapshot = buffer.snapshot();
+        let http_client = self.client.http_client();
+        let buffer = buffer_handle.downgrade();
+        let debounce = trigger == Some(GrammarCheckTrigger::Edit);
+        let check = cx.spawn(|project, mut cx| async move {
+            if debounce {
+                cx.background_executor()
+                    .timer(GRAMMAR_CHECK_DEBOUNCE_TIMEOUT)
+                    .await;
+            }
+            let version = snapshot.version().clone();
+            let text = cx
+                .background_executor()
+                .spawn({
+                    let snapshot = snapshot.clone();
+                    let scopes = settings.scopes.clone();
+                    async move { prose(&snapshot, &scopes) }
+                })
+                .await;
+            let diagnostics = if text.annotation.is_empty() {
+                Vec::new()
+            } else {
+                let Some(response) = grammar_check::check(
+                    http_client.as_ref(),
+                    &settings.api_url,
+                    &settings.language,
+                    &text,
+                )
+                .await
+                .with_context(|| format!("checking grammar with {}", settings.api_url))
+                .log_err() else {
+                    return;
+                };
+                problems(&snapshot, response.matches)
+            };
+
+            project
+                .update(&mut cx, |project, cx| {
+                    let buffer = buffer.upgrade()?;
+                    // The buffer changed while it was checked, so the problems'
+                    // positions are out of date.
+                    if buffer.read(cx).version().changed_since(&version) {
+                        return None;
+                    }
+                    project.grammar_checking.checked_buffers.insert(buffer_id);
+                    project
+                        .update_diagnostic_entries(
+                            GRAMMAR_CHECK_SERVER_ID,
+                            abs_path,
+                            None,
+                            diagnostics,
+                            cx,
+                        )
+                        .log_err()
+                })
+                .ok();
+        });
+        self.grammar_checking
+            .pending_checks
+            .insert(buffer_id, check);
+    }
+
+    /// Checks the grammar of all open buffers again, for example after their
+    /// settings changed.
+    pub(crate) fn recheck_grammar(&mut self, cx: &mut ModelContext<Self>) {
+        let buffers = self.buffer_store.read(cx).buffers().collect::<Vec<_>>();
+        for buffer in buffers {
+            self.check_grammar(&buffer, None, cx);
+        }
+    }
+
+    pub(crate) fn forget_grammar_checked_buffer(&mut self, buffer_id: BufferId) {
+        self.grammar_checking.pending_checks.remove(&buffer_id);
+        self.grammar_checking.checked_buffers.remove(&buffer_id);
+    }
+
+    /// Returns the code actions applying the replacements suggested for the
+    /// grammar and style problems in the range.
+    pub(crate) fn grammar_check_actions(
+        &self,
+        buffer: &Model<Buffer>,
+        range: Range<Anchor>,
+        cx: &mut ModelContext<Self>,
+    ) -> Vec<CodeAction> {
+        let snapshot = buffer.read(cx).snapshot();
+        let range = range.start.to_offset(&snapshot)..range.end.to_offset(&snapshot);
+        snapshot
+            .diagnostic_groups(Some(GRAMMAR_CHECK_SERVER_ID))
+            .into_iter()
+            .filter_map(|(_, group)| {
+                let entry = group.entries.get(group.primary_ix)?;
+                let problem_range =
+                    entry.range.start.to_offset(&snapshot)..entry.range.end.to_offset(&snapshot);
+                if problem_range.start > range.end || problem_range.end < range.start {
+                    return None;
+                }
+                let Replacements { replacements } =
+                    serde_json::from_value(entry.diagnostic.data.clone()?).log_err()?;
+                let text = snapshot.text_for_range(problem_range).collect::<String>();
+                Some((entry.range.clone(), text, replacements))
+            })
+            .flat_map(|(range, text, replacements)| {
+                replacements.into_iter().map(move |replacement| {
+                    let title = if replacement.is_empty() {
+                        format!("Remove \"{text}\"")
+                    } else {
+                        format!("Change to \"{replacement}\"")
+                    };
+                    CodeAction {
+                        server_id: GRAMMAR_CHECK_SERVER_ID,
+                        range: range.clone(),
+                        lsp_action: lsp::CodeAction {
+                            title,
+                            kind: Some(lsp::CodeActionKind::QUICKFIX),
+                            data: serde_json::to_value(Replace { replacement }).log_err(),
+                            ..Default::default()
+                        },
+                    }
+                })
+            })
+            .collect()
+    }
+
+    pub(crate) fn apply_grammar_check_action(
+        &self,
+        buffer: Model<Buffer>,
+        action: CodeAction,
+        push_to_history: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        let replace = action
+            .lsp_action
+            .data
+            .ok_or_else(|| anyhow!("grammar check action without data"))
+            .and_then(|data| Ok(serde_json::from_value::<Replace>(data)?));
+        Task::ready(replace.map(|Replace { replacement }| {
+            replace_text(buffer, action.range, replacement, push_to_history, cx)
+        }))
+    }
+}
+
+/// Returns the text to check: the prose in the scopes that are checked, with the
+/// rest of the buffer marked up.
+fn prose(snapshot: &BufferSnapshot, scopes: &[String]) -> grammar_check::AnnotatedText {
+    let text = snapshot.text();
+    let language_name = snapshot.language().map(|language| language.name());
+    let ranges = grammar_check::prose_ranges(&text, |offset| {
+        is_checked_scope(snapshot, offset, scopes, language_name.as_ref())
+    });
+    if ranges.is_empty() {
+        return grammar_check::AnnotatedText::default();
+    }
+    grammar_check::annotate(&text, &ranges)
+}
+
+/// Returns the diagnostics for the problems found by the checker.
+fn problems(
+    snapshot: &BufferSnapshot,
+    matches: Vec<grammar_check::Match>,
+) -> Vec<DiagnosticEntry<Unclipped<PointUtf16>>> {
+    matches
+        .into_iter()
+        .enumerate()
+        .map(|(group_id, problem)| {
+            let range = problem.range_utf16();
+            let start = snapshot.offset_utf16_to_offset(OffsetUtf16(range.start));
+            let end = snapshot.offset_utf16_to_offset(OffsetUtf16(range.end));
+            let replacements = problem
+                .replacements
+                .into_iter()
+                .take(MAX_REPLACEMENTS)
+                .map(|replacement| replacement.value)
+                .collect();
+            let severity = match problem.rule.issue_type.as_str() {
+                "misspelling" | "grammar" => DiagnosticSeverity::INFORMATION,
+                _ => DiagnosticSeverity::HINT,
+            };
+            DiagnosticEntry {
+                range: Unclipped(snapshot.offset_to_point_utf16(start))
+                    ..Unclipped(snapshot.offset_to_point_utf16(end)),
+                diagnostic: Diagnostic {
+                    source: Some(GRAMMAR_CHECK_SOURCE.to_string()),
+                    code: Some(problem.rule.id),
+                    severity,
+                    message: problem.message,
+                    group_id,
+                    is_primary: true,
+                    is_disk_based: false,
+                    is_unnecessary: false,
+                    is_deprecated: false,
+                    data: serde_json::to_value(Replacements { replacements }).log_err(),
+                },
+            }
+        })
+        .collect()
+}
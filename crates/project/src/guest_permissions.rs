@@ -0,0 +1,131 @@
+use std::{collections::BTreeMap, path::Path};
+
+use rpc::proto;
+use util::{paths::PathMatcher, ResultExt};
+
+/// The permissions the host of a shared project grants to its guests.
+///
+/// Guests whose channel role doesn't allow editing can never edit, whatever
+/// the host grants them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GuestPermissions {
+    guests_can_edit: bool,
+    /// The guests the host explicitly allowed or forbade to edit, by user id.
+    overrides: BTreeMap<u64, bool>,
+    read_only_paths: PathMatcher,
+}
+
+impl Default for GuestPermissions {
+    fn default() -> Self {
+        Self {
+            guests_can_edit: true,
+            overrides: BTreeMap::default(),
+            read_only_paths: PathMatcher::default(),
+        }
+    }
+}
+
+impl GuestPermissions {
+    /// Creates the permissions of guests the host hasn't singled out.
+    pub fn new(guests_can_edit: bool, read_only_paths: &[String]) -> Self {
+        let mut this = Self::default();
+        this.set_defaults(guests_can_edit, read_only_paths);
+        this
+    }
+
+    /// Updates the permissions of guests the host hasn't singled out, keeping
+    /// the ones of guests it has. Returns whether the permissions changed.
+    pub fn set_defaults(&mut self, guests_can_edit: bool, read_only_paths: &[String]) -> bool {
+        let read_only_paths = PathMatcher::new(read_only_paths)
+            .log_err()
+            .unwrap_or_default();
+        let changed =
+            self.guests_can_edit != guests_can_edit || self.read_only_paths != read_only_paths;
+        self.guests_can_edit = guests_can_edit;
+        self.read_only_paths = read_only_paths;
+        changed
+    }
+
+    pub fn can_edit(&self, user_id: u64) -> bool {
+        self.overrides
+            .get(&user_id)
+            .copied()
+            .unwrap_or(self.guests_can_edit)
+    }
+
+    /// Allows or forbids a guest to edit. Returns whether the permissions
+    /// changed.
+    pub fn set_can_edit(&mut self, user_id: u64, can_edit: bool) -> bool {
+        let changed = self.can_edit(user_id) != can_edit;
+        self.overrides.insert(user_id, can_edit);
+        changed
+    }
+
+    /// Whether guests can't write to the path, relative to its worktree,
+    /// whoever they are.
+    pub fn is_read_only_path(&self, path: &Path) -> bool {
+        self.read_only_paths.is_match(path)
+    }
+
+    pub fn can_write_path(&self, user_id: u64, path: &Path) -> bool {
+        self.can_edit(user_id) && !self.is_read_only_path(path)
+    }
+
+    pub fn to_proto(&self, project_id: u64) -> proto::UpdateGuestPermissions {
+        proto::UpdateGuestPermissions {
+            project_id,
+            guests_can_edit: self.guests_can_edit,
+            guests: self
+                .overrides
+                .iter()
+                .map(|(user_id, can_edit)| proto::GuestEditPermission {
+                    user_id: *user_id,
+                    can_edit: *can_edit,
+                })
+                .collect(),
+            read_only_paths: self.read_only_paths.sources().to_vec(),
+        }
+    }
+
+    pub fn from_proto(message: proto::UpdateGuestPermissions) -> Self {
+        let mut this = Self::new(message.guests_can_edit, &message.read_only_paths);
+        this.overrides = message
+            .guests
+            .into_iter()
+            .map(|guest| (guest.user_id, guest.can_edit))
+            .collect();
+        this
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guest_permissions() {
+        let mut permissions = GuestPermissions::new(false, &["**/.env".to_string()]);
+        assert!(!permissions.can_edit(1));
+        assert!(permissions.set_can_edit(1, true));
+        assert!(!permissions.set_can_edit(1, true));
+        assert!(permissions.can_edit(1));
+        assert!(!permissions.can_edit(2));
+
+        assert!(permissions.can_write_path(1, Path::new("src/main.rs")));
+        assert!(!permissions.can_write_path(1, Path::new("config/.env")));
+        assert!(!permissions.can_write_path(2, Path::new("src/main.rs")));
+
+        // Explicit permissions survive changes of the defaults.
+        assert!(permissions.set_defaults(true, &[]));
+        assert!(!permissions.set_defaults(true, &[]));
+        assert!(permissions.set_can_edit(3, false));
+        assert!(permissions.can_edit(2));
+        assert!(!permissions.can_edit(3));
+        assert!(permissions.can_write_path(1, Path::new("config/.env")));
+
+        assert_eq!(
+            GuestPermissions::from_proto(permissions.to_proto(1)),
+            permissions
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use std::{future::Future, sync::Arc};
+
+use collections::{BTreeMap, HashMap};
+use futures::channel::oneshot;
+use parking_lot::Mutex;
+use text::BufferId;
+
+/// How many scheduled requests are sent to a language server at once. Requests beyond
+/// this wait their turn, visible ones first.
+pub const MAX_CONCURRENT_SCHEDULED_LSP_REQUESTS: usize = 4;
+
+/// How soon the response to an LSP request is needed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LspRequestPriority {
+    /// The response is shown in the visible part of an editor.
+    #[default]
+    Visible,
+    /// The response is for a part of the buffer that isn't visible yet, such as the
+    /// lines just above and below the viewport.
+    Background,
+}
+
+/// How an LSP request is scheduled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LspRequestSchedule {
+    pub priority: LspRequestPriority,
+    /// Requests with the same key, for the same buffer and method, supersede each
+    /// other: scheduling one cancels those scheduled before it, whether they were
+    /// sent already or are still waiting their turn. Editors use this to drop the
+    /// requests for the parts of a buffer they've scrolled away from.
+    pub supersede_key: Option<u64>,
+}
+
+/// An LSP request that was cancelled, because a newer one superseded it.
+#[derive(Debug)]
+pub struct LspRequestSuperseded;
+
+impl std::fmt::Display for LspRequestSuperseded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the LSP request was superseded by a newer one")
+    }
+}
+
+impl std::error::Error for LspRequestSuperseded {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct RequestKey {
+    buffer_id: BufferId,
+    method: &'static str,
+    supersede_key: u64,
+}
+
+/// Limits how many requests are sent to a language server at once, sending the ones
+/// for the viewport first, and cancels the ones that newer requests supersede.
+#[derive(Clone)]
+pub struct LspRequestScheduler {
+    state: Arc<Mutex<SchedulerState>>,
+}
+
+struct SchedulerState {
+    max_concurrent_requests: usize,
+    next_request_id: usize,
+    pending: BTreeMap<(LspRequestPriority, usize), PendingRequest>,
+    running: HashMap<usize, RunningRequest>,
+}
+
+struct PendingRequest {
+    key: Option<RequestKey>,
+    start_tx: oneshot::Sender<LspRequestPermit>,
+    superseded_tx: oneshot::Sender<()>,
+    superseded_rx: oneshot::Receiver<()>,
+}
+
+struct RunningRequest {
+    key: Option<RequestKey>,
+    superseded_tx: Option<oneshot::Sender<()>>,
+}
+
+/// A turn to send a request to a language server. The next request is started when
+/// it's dropped.
+pub struct LspRequestPermit {
+    request_id: usize,
+    state: Arc<Mutex<SchedulerState>>,
+    superseded_rx: oneshot::Receiver<()>,
+}
+
+impl LspRequestScheduler {
+    pub fn new(max_concurrent_requests: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SchedulerState {
+                max_concurrent_requests: max_concurrent_requests.max(1),
+                next_request_id: 0,
+                pending: BTreeMap::default(),
+                running: HashMap::default(),
+            })),
+        }
+    }
+
+    /// Waits for a turn to send a request. Resolves to `None` when a newer request
+    /// superseded this one before its turn came.
+    pub fn schedule(
+        &self,
+        buffer_id: BufferId,
+        method: &'static str,
+        schedule: LspRequestSchedule,
+    ) -> impl Future<Output = Option<LspRequestPermit>> {
+        let key = schedule.supersede_key.map(|supersede_key| RequestKey {
+            buffer_id,
+            method,
+            supersede_key,
+        });
+        let (start_tx, start_rx) = oneshot::channel();
+        let (superseded_tx, superseded_rx) = oneshot::channel();
+
+        let mut state = self.state.lock();
+        if let Some(key) = key.as_ref() {
+            state.supersede(key);
+        }
+        let request_id = state.next_request_id;
+        state.next_request_id += 1;
+        state.pending.insert(
+            (schedule.priority, request_id),
+            PendingRequest {
+                key,
+                start_tx,
+                superseded_tx,
+                superseded_rx,
+            },
+        );
+        let unclaimed_permits = state.start_pending(&self.state);
+        drop(state);
+        drop(unclaimed_permits);
+
+        async move { start_rx.await.ok() }
+    }
+}
+
+impl SchedulerState {
+    fn supersede(&mut self, key: &RequestKey) {
+        // Dropping a pending request's sender resolves its turn to `None`.
+        self.pending
+            .retain(|_, request| request.key.as_ref() != Some(key));
+        for request in self.running.values_mut() {
+            if request.key.as_ref() == Some(key) {
+                if let Some(superseded_tx) = request.superseded_tx.take() {
+                    superseded_tx.send(()).ok();
+                }
+            }
+        }
+    }
+
+    /// Starts pending requests, highest priority first, while there's room. Returns
+    /// the permits of requests whose callers stopped waiting, which must be dropped
+    /// after the state is unlocked.
+    #[must_use]
+    fn start_pending(&mut self, state: &Arc<Mutex<SchedulerState>>) -> Vec<LspRequestPermit> {
+        let mut unclaimed_permits = Vec::new();
+        while self.running.len() < self.max_concurrent_requests {
+            let Some(((_, request_id), request)) = self.pending.pop_first() else {
+                break;
+            };
+            self.running.insert(
+                request_id,
+                RunningRequest {
+                    key: request.key,
+                    superseded_tx: Some(request.superseded_tx),
+                },
+            );
+            let permit = LspRequestPermit {
+                request_id,
+                state: state.clone(),
+                superseded_rx: request.superseded_rx,
+            };
+            if let Err(permit) = request.start_tx.send(permit) {
+                unclaimed_permits.push(permit);
+            }
+        }
+        unclaimed_permits
+    }
+}
+
+impl LspRequestPermit {
+    /// Resolves when a newer request supersedes this one.
+    pub async fn superseded(&mut self) {
+        if (&mut self.superseded_rx).await.is_err() {
+            futures::future::pending::<()>().await;
+        }
+    }
+}
+
+impl Drop for LspRequestPermit {
+    fn drop(&mut self) {
+        let mut state = self.state.lock();
+        state.running.remove(&self.request_id);
+        let unclaimed_permits = state.start_pending(&self.state);
+        drop(state);
+        drop(unclaimed_permits);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt as _;
+
+    fn schedule(
+        scheduler: &LspRequestScheduler,
+        priority: LspRequestPriority,
+        supersede_key: Option<u64>,
+    ) -> futures::future::BoxFuture<'static, Option<LspRequestPermit>> {
+        scheduler
+            .schedule(
+                BufferId::new(1).unwrap(),
+                "textDocument/inlayHint",
+                LspRequestSchedule {
+                    priority,
+                    supersede_key,
+                },
+            )
+            .boxed()
+    }
+
+    #[test]
+    fn test_visible_requests_go_first() {
+        let scheduler = LspRequestScheduler::new(1);
+        let first = schedule(&scheduler, LspRequestPriority::Background, None)
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        let mut background = schedule(&scheduler, LspRequestPriority::Background, None);
+        let mut visible = schedule(&scheduler, LspRequestPriority::Visible, None);
+        assert!((&mut background).now_or_never().is_none());
+        assert!((&mut visible).now_or_never().is_none());
+
+        drop(first);
+        assert!((&mut background).now_or_never().is_none());
+        let visible = (&mut visible).now_or_never().unwrap().unwrap();
+
+        drop(visible);
+        assert!(background.now_or_never().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_superseding_requests() {
+        let scheduler = LspRequestScheduler::new(1);
+        let mut running = schedule(&scheduler, LspRequestPriority::Visible, Some(1))
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert!(running.superseded().boxed().now_or_never().is_none());
+
+        // A newer request with the same key cancels the running one, and replaces the
+        // pending one.
+        let pending = schedule(&scheduler, LspRequestPriority::Visible, Some(1));
+        assert!(running.superseded().boxed().now_or_never().is_some());
+        let mut newest = schedule(&scheduler, LspRequestPriority::Visible, Some(1));
+        let mut other = schedule(&scheduler, LspRequestPriority::Visible, Some(2));
+        assert_eq!(
+            pending.now_or_never().map(|permit| permit.is_none()),
+            Some(true)
+        );
+
+        drop(running);
+        assert!((&mut newest).now_or_never().unwrap().is_some());
+        assert!((&mut other).now_or_never().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_abandoned_requests_free_their_turn() {
+        let scheduler = LspRequestScheduler::new(1);
+        let first = schedule(&scheduler, LspRequestPriority::Visible, None)
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        drop(schedule(&scheduler, LspRequestPriority::Visible, None));
+        let mut next = schedule(&scheduler, LspRequestPriority::Visible, None);
+
+        drop(first);
+        assert!((&mut next).now_or_never().unwrap().is_some());
+    }
+}
@@ -1,24 +1,27 @@
 use crate::{
     buffer_store::BufferStore,
+    custom_language_servers::CustomLanguageServers,
     environment::ProjectEnvironment,
     lsp_command::{self, *},
     lsp_ext_command,
+    lsp_request_scheduler::{
+        LspRequestSchedule, LspRequestScheduler, LspRequestSuperseded,
+        MAX_CONCURRENT_SCHEDULED_LSP_REQUESTS,
+    },
     project_settings::ProjectSettings,
     relativize_path, resolve_path,
     worktree_store::WorktreeStore,
     yarn::YarnPathStore,
-    CodeAction, Completion, CoreCompletion, Hover, InlayHint, Item as _, ProjectPath,
+    CodeAction, CodeLens, Completion, CoreCompletion, Hover, InlayHint, Item as _, ProjectPath,
     ProjectTransaction, ResolveState, Symbol,
 };
 use anyhow::{anyhow, Context as _, Result};
 use async_trait::async_trait;
 use client::{proto, Client, TypedEnvelope};
-use collections::{btree_map, BTreeMap, HashMap, HashSet};
+use collections::{btree_map, BTreeMap, HashMap, HashSet, VecDeque};
 use futures::{
-    future::{join_all, Shared},
-    select,
-    stream::FuturesUnordered,
-    Future, FutureExt, StreamExt,
+    future::{self, join_all, Either, Shared},
+    select, Future, FutureExt, StreamExt,
 };
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use gpui::{
@@ -28,7 +31,9 @@ use gpui::{
 use http_client::HttpClient;
 use itertools::Itertools;
 use language::{
-    language_settings::{language_settings, AllLanguageSettings, LanguageSettings},
+    language_settings::{
+        language_settings, AllLanguageSettings, CompletionsRouting, HoverRouting, LanguageSettings,
+    },
     markdown, point_to_lsp, prepare_completion_documentation,
     proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
     range_from_lsp, Bias, Buffer, BufferSnapshot, CachedLspAdapter, CodeLabel, Diagnostic,
@@ -45,8 +50,9 @@ use lsp::{
 use parking_lot::{Mutex, RwLock};
 use postage::watch;
 use rand::prelude::*;
+use remote::SshSession;
 use rpc::proto::AnyProtoClient;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsLocation, SettingsStore};
 use sha2::{Digest, Sha256};
 use similar::{ChangeTag, TextDiff};
@@ -59,6 +65,7 @@ use std::{
     iter, mem,
     ops::{ControlFlow, Range},
     path::{self, Path, PathBuf},
+    pin::pin,
     process::Stdio,
     str,
     sync::{atomic::Ordering::SeqCst, Arc},
@@ -83,6 +90,8 @@ const MAX_SERVER_REINSTALL_ATTEMPT_COUNT: u64 = 4;
 const SERVER_REINSTALL_DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(1);
 const SERVER_LAUNCHING_BEFORE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 pub const SERVER_PROGRESS_THROTTLE_TIMEOUT: Duration = Duration::from_millis(100);
+const MAX_FINISHED_LANGUAGE_SERVER_WORK: usize = 20;
+const PULL_DIAGNOSTICS_DEBOUNCE: Duration = Duration::from_millis(125);
 
 #[derive(Clone, Debug)]
 pub(crate) struct CoreSymbol {
@@ -106,6 +115,7 @@ pub enum LspStoreEvent {
     LanguageServerPrompt(LanguageServerPromptRequest),
     Notification(String),
     RefreshInlayHints,
+    RefreshCodeLens,
     DiagnosticsUpdated {
         language_server_id: LanguageServerId,
         path: ProjectPath,
@@ -142,6 +152,7 @@ pub struct LspStore {
     supplementary_language_servers:
         HashMap<LanguageServerId, (LanguageServerName, Arc<LanguageServer>)>,
     languages: Arc<LanguageRegistry>,
+    custom_language_servers: CustomLanguageServers,
     language_servers: HashMap<LanguageServerId, LanguageServerState>,
     language_server_ids: HashMap<(WorktreeId, LanguageServerName), LanguageServerId>,
     language_server_statuses: BTreeMap<LanguageServerId, LanguageServerStatus>,
@@ -165,6 +176,18 @@ pub struct LspStore {
         >,
     >,
     yarn: Model<YarnPathStore>,
+    pulled_diagnostics_result_ids: HashMap<LanguageServerId, HashMap<lsp::Url, String>>,
+    pull_diagnostics_tasks: HashMap<BufferId, Task<()>>,
+    pull_workspace_diagnostics_tasks: HashMap<LanguageServerId, Task<()>>,
+    pull_diagnostics_partial_result_tokens: HashMap<String, (LanguageServerId, PulledDiagnostics)>,
+    next_pull_diagnostics_token: usize,
+}
+
+/// What a pull diagnostics request, and its partial results, report on.
+#[derive(Clone, Debug)]
+enum PulledDiagnostics {
+    Document(lsp::Url),
+    Workspace,
 }
 
 impl LspStore {
@@ -176,6 +199,9 @@ impl LspStore {
         client.add_model_message_handler(Self::handle_update_diagnostic_summary);
         client.add_model_request_handler(Self::handle_resolve_completion_documentation);
         client.add_model_request_handler(Self::handle_apply_code_action);
+        client.add_model_request_handler(Self::handle_get_code_lens);
+        client.add_model_request_handler(Self::handle_resolve_code_lens);
+        client.add_model_request_handler(Self::handle_execute_code_lens);
         client.add_model_request_handler(Self::handle_inlay_hints);
         client.add_model_request_handler(Self::handle_get_project_symbols);
         client.add_model_request_handler(Self::handle_resolve_inlay_hint);
@@ -198,6 +224,64 @@ impl LspStore {
         client.add_model_request_handler(Self::handle_apply_additional_edits_for_completion);
     }
 
+    /// Handles the language server requests of the client of an SSH project,
+    /// on the server the project's files are on.
+    pub fn init_ssh_session(session: &SshSession, lsp_store: &Model<Self>) {
+        let lsp_store = lsp_store.downgrade();
+        session.add_request_handler(lsp_store.clone(), Self::handle_multi_lsp_query);
+        session.add_request_handler(lsp_store.clone(), Self::handle_restart_language_servers);
+        session.add_request_handler(
+            lsp_store.clone(),
+            Self::handle_resolve_completion_documentation,
+        );
+        session.add_request_handler(lsp_store.clone(), Self::handle_apply_code_action);
+        session.add_request_handler(lsp_store.clone(), Self::handle_get_code_lens);
+        session.add_request_handler(lsp_store.clone(), Self::handle_resolve_code_lens);
+        session.add_request_handler(lsp_store.clone(), Self::handle_execute_code_lens);
+        session.add_request_handler(lsp_store.clone(), Self::handle_inlay_hints);
+        session.add_request_handler(lsp_store.clone(), Self::handle_get_project_symbols);
+        session.add_request_handler(lsp_store.clone(), Self::handle_resolve_inlay_hint);
+        session.add_request_handler(lsp_store.clone(), Self::handle_open_buffer_for_symbol);
+        session.add_request_handler(
+            lsp_store.clone(),
+            Self::handle_lsp_command::<GetCodeActions>,
+        );
+        session.add_request_handler(
+            lsp_store.clone(),
+            Self::handle_lsp_command::<GetCompletions>,
+        );
+        session.add_request_handler(lsp_store.clone(), Self::handle_lsp_command::<GetHover>);
+        session.add_request_handler(lsp_store.clone(), Self::handle_lsp_command::<GetDefinition>);
+        session.add_request_handler(
+            lsp_store.clone(),
+            Self::handle_lsp_command::<GetDeclaration>,
+        );
+        session.add_request_handler(
+            lsp_store.clone(),
+            Self::handle_lsp_command::<GetTypeDefinition>,
+        );
+        session.add_request_handler(
+            lsp_store.clone(),
+            Self::handle_lsp_command::<GetDocumentHighlights>,
+        );
+        session.add_request_handler(lsp_store.clone(), Self::handle_lsp_command::<GetReferences>);
+        session.add_request_handler(lsp_store.clone(), Self::handle_lsp_command::<PrepareRename>);
+        session.add_request_handler(lsp_store.clone(), Self::handle_lsp_command::<PerformRename>);
+        session.add_request_handler(
+            lsp_store.clone(),
+            Self::handle_lsp_command::<lsp_ext_command::ExpandMacro>,
+        );
+        session.add_request_handler(
+            lsp_store.clone(),
+            Self::handle_lsp_command::<LinkedEditingRange>,
+        );
+        session.add_request_handler(lsp_store.clone(), Self::handle_on_type_formatting);
+        session.add_request_handler(
+            lsp_store,
+            Self::handle_apply_additional_edits_for_completion,
+        );
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         buffer_store: Model<BufferStore>,
@@ -213,6 +297,14 @@ impl LspStore {
     ) -> Self {
         let yarn = YarnPathStore::new(fs.clone(), cx);
 
+        let mut custom_language_servers = CustomLanguageServers::default();
+        if upstream_client.is_none() {
+            custom_language_servers.update(
+                &ProjectSettings::get_global(cx).custom_language_servers,
+                &languages,
+            );
+        }
+
         Self {
             downstream_client,
             upstream_client,
@@ -222,6 +314,7 @@ impl LspStore {
             buffer_store,
             worktree_store,
             languages,
+            custom_language_servers,
             environment,
             nonce: StdRng::from_entropy().gen(),
             buffer_snapshots: Default::default(),
@@ -237,11 +330,41 @@ impl LspStore {
             diagnostics: Default::default(),
             active_entry: None,
             yarn,
+            pulled_diagnostics_result_ids: Default::default(),
+            pull_diagnostics_tasks: Default::default(),
+            pull_workspace_diagnostics_tasks: Default::default(),
+            pull_diagnostics_partial_result_tokens: Default::default(),
+            next_pull_diagnostics_token: 0,
             _maintain_workspace_config: Self::maintain_workspace_config(cx),
             _subscription: cx.on_app_quit(Self::shutdown_language_servers),
         }
     }
 
+    /// Creates the language servers of a project edited from another machine,
+    /// such as the one the headless server of an SSH project runs.
+    pub fn new_local(
+        buffer_store: Model<BufferStore>,
+        worktree_store: Model<WorktreeStore>,
+        languages: Arc<LanguageRegistry>,
+        http_client: Arc<dyn HttpClient>,
+        fs: Arc<dyn Fs>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let environment = ProjectEnvironment::new(None, cx);
+        Self::new(
+            buffer_store,
+            worktree_store,
+            Some(environment),
+            languages,
+            http_client,
+            fs,
+            None,
+            None,
+            None,
+            cx,
+        )
+    }
+
     pub fn buffer_store(&self) -> Model<BufferStore> {
         self.buffer_store.clone()
     }
@@ -323,6 +446,25 @@ impl LspStore {
         request: R,
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<R::Response>>
+    where
+        <R::LspRequest as lsp::request::Request>::Result: Send,
+        <R::LspRequest as lsp::request::Request>::Params: Send,
+    {
+        self.request_lsp_with_schedule(buffer_handle, server, request, None, cx)
+    }
+
+    /// Like [`Self::request_lsp`], but when given a schedule, waits for the request's
+    /// turn to be sent to a local language server, and cancels it with `$/cancelRequest`
+    /// once a newer request supersedes it. Superseded requests fail with
+    /// [`LspRequestSuperseded`].
+    pub fn request_lsp_with_schedule<R: LspCommand>(
+        &self,
+        buffer_handle: Model<Buffer>,
+        server: LanguageServerToQuery,
+        request: R,
+        schedule: Option<LspRequestSchedule>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<R::Response>>
     where
         <R::LspRequest as lsp::request::Request>::Result: Send,
         <R::LspRequest as lsp::request::Request>::Params: Send,
@@ -346,11 +488,28 @@ impl LspStore {
         if let (Some(file), Some(language_server)) = (file, language_server) {
             let lsp_params = request.to_lsp(&file.abs_path(cx), buffer, &language_server, cx);
             let status = request.status();
+            let turn = schedule.and_then(|schedule| {
+                let Some(LanguageServerState::Running {
+                    request_scheduler, ..
+                }) = self.language_servers.get(&language_server.server_id())
+                else {
+                    return None;
+                };
+                Some(request_scheduler.schedule(
+                    buffer.remote_id(),
+                    <R::LspRequest as lsp::request::Request>::METHOD,
+                    schedule,
+                ))
+            });
             return cx.spawn(move |this, cx| async move {
                 if !request.check_capabilities(language_server.adapter_server_capabilities()) {
                     return Ok(Default::default());
                 }
 
+                let mut permit = match turn {
+                    Some(turn) => Some(turn.await.ok_or(LspRequestSuperseded)?),
+                    None => None,
+                };
                 let lsp_request = language_server.request::<R::LspRequest>(lsp_params);
 
                 let id = lsp_request.id();
@@ -367,6 +526,7 @@ impl LspStore {
                                     message: status.clone(),
                                     percentage: None,
                                     last_update_at: cx.background_executor().now(),
+                                    started_at: cx.background_executor().now(),
                                 },
                                 cx,
                             );
@@ -390,7 +550,17 @@ impl LspStore {
                     None
                 };
 
-                let result = lsp_request.await;
+                let result = match permit.as_mut() {
+                    Some(permit) => {
+                        match future::select(pin!(lsp_request), pin!(permit.superseded())).await {
+                            Either::Left((result, _)) => result,
+                            // Dropping the request cancels it.
+                            Either::Right(_) => return Err(LspRequestSuperseded.into()),
+                        }
+                    }
+                    None => lsp_request.await,
+                };
+                drop(permit);
 
                 let response = result.map_err(|err| {
                     log::warn!(
@@ -564,6 +734,32 @@ impl LspStore {
         })
     }
 
+    pub fn serialize_code_lens(code_lens: &CodeLens) -> proto::CodeLens {
+        proto::CodeLens {
+            server_id: code_lens.server_id.0 as u64,
+            start: Some(serialize_anchor(&code_lens.range.start)),
+            end: Some(serialize_anchor(&code_lens.range.end)),
+            lsp_lens: serde_json::to_vec(&code_lens.lsp_lens).unwrap(),
+        }
+    }
+
+    pub fn deserialize_code_lens(code_lens: proto::CodeLens) -> Result<CodeLens> {
+        let start = code_lens
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = code_lens
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        let lsp_lens = serde_json::from_slice(&code_lens.lsp_lens)?;
+        Ok(CodeLens {
+            server_id: LanguageServerId(code_lens.server_id as usize),
+            range: start..end,
+            lsp_lens,
+        })
+    }
+
     pub fn apply_code_action(
         &self,
         buffer_handle: Model<Buffer>,
@@ -649,6 +845,195 @@ impl LspStore {
         }
     }
 
+    pub fn code_lens(
+        &self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CodeLens>>> {
+        let buffer = buffer_handle.read(cx);
+        if let Some(upstream_client) = self.upstream_client.clone() {
+            let request = proto::GetCodeLens {
+                project_id: self.project_id,
+                buffer_id: buffer.remote_id().into(),
+                version: serialize_version(&buffer.version()),
+            };
+            let buffer_handle = buffer_handle.clone();
+            return cx.spawn(move |_, mut cx| async move {
+                let response = upstream_client.request(request).await?;
+                buffer_handle
+                    .update(&mut cx, |buffer, _| {
+                        buffer.wait_for_version(deserialize_version(&response.version))
+                    })?
+                    .await?;
+                response
+                    .lenses
+                    .into_iter()
+                    .map(Self::deserialize_code_lens)
+                    .collect()
+            });
+        }
+        let Some(file) = File::from_dyn(buffer.file()).and_then(|file| file.as_local()) else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let Ok(uri) = lsp::Url::from_file_path(file.abs_path(cx)) else {
+            return Task::ready(Err(anyhow!("invalid file path for code lens request")));
+        };
+        let snapshot = buffer.snapshot();
+        let requests = self
+            .language_servers_for_buffer(buffer, cx)
+            .filter(|(_, server)| server.capabilities().code_lens_provider.is_some())
+            .map(|(_, server)| {
+                let server = server.clone();
+                let params = lsp::CodeLensParams {
+                    text_document: lsp::TextDocumentIdentifier::new(uri.clone()),
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                };
+                async move {
+                    let lenses = server
+                        .request::<lsp::request::CodeLensRequest>(params)
+                        .await?;
+                    anyhow::Ok((server.server_id(), lenses.unwrap_or_default()))
+                }
+            })
+            .collect::<Vec<_>>();
+
+        cx.background_executor().spawn(async move {
+            let mut code_lens = Vec::new();
+            for response in join_all(requests).await {
+                let Some((server_id, lenses)) = response.log_err() else {
+                    continue;
+                };
+                code_lens.extend(lenses.into_iter().map(|lsp_lens| {
+                    let range = range_from_lsp(lsp_lens.range);
+                    let start = snapshot.clip_point_utf16(range.start, Bias::Left);
+                    let end = snapshot.clip_point_utf16(range.end, Bias::Left);
+                    CodeLens {
+                        server_id,
+                        range: snapshot.anchor_before(start)..snapshot.anchor_after(end),
+                        lsp_lens,
+                    }
+                }));
+            }
+            Ok(code_lens)
+        })
+    }
+
+    pub fn resolve_code_lens(
+        &self,
+        buffer_handle: &Model<Buffer>,
+        mut code_lens: CodeLens,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<CodeLens>> {
+        if code_lens.lsp_lens.command.is_some() {
+            return Task::ready(Ok(code_lens));
+        }
+        if let Some(upstream_client) = self.upstream_client.clone() {
+            let request = proto::ResolveCodeLens {
+                project_id: self.project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                lens: Some(Self::serialize_code_lens(&code_lens)),
+            };
+            return cx.background_executor().spawn(async move {
+                let response = upstream_client.request(request).await?;
+                Self::deserialize_code_lens(response.lens.context("missing code lens")?)
+            });
+        }
+        let Some((_, server)) =
+            self.language_server_for_buffer(buffer_handle.read(cx), code_lens.server_id, cx)
+        else {
+            return Task::ready(Ok(code_lens));
+        };
+        let can_resolve = server
+            .capabilities()
+            .code_lens_provider
+            .as_ref()
+            .and_then(|options| options.resolve_provider)
+            .unwrap_or(false);
+        if !can_resolve {
+            return Task::ready(Ok(code_lens));
+        }
+
+        let server = server.clone();
+        cx.background_executor().spawn(async move {
+            code_lens.lsp_lens = server
+                .request::<lsp::request::CodeLensResolve>(code_lens.lsp_lens.clone())
+                .await?;
+            Ok(code_lens)
+        })
+    }
+
+    /// Runs the command of a resolved code lens on the language server that
+    /// provided it, returning the edits the language server applied meanwhile.
+    pub fn execute_code_lens(
+        &self,
+        buffer_handle: &Model<Buffer>,
+        code_lens: CodeLens,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        if let Some(upstream_client) = self.upstream_client.clone() {
+            let request = proto::ExecuteCodeLens {
+                project_id: self.project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                lens: Some(Self::serialize_code_lens(&code_lens)),
+            };
+            return cx.spawn(move |this, cx| async move {
+                let response = upstream_client
+                    .request(request)
+                    .await?
+                    .transaction
+                    .context("missing transaction")?;
+                BufferStore::deserialize_project_transaction(
+                    this.read_with(&cx, |this, _| this.buffer_store.downgrade())?,
+                    response,
+                    true,
+                    cx,
+                )
+                .await
+            });
+        }
+        let Some(command) = code_lens.lsp_lens.command else {
+            return Task::ready(Ok(ProjectTransaction::default()));
+        };
+        let Some((_, server)) =
+            self.language_server_for_buffer(buffer_handle.read(cx), code_lens.server_id, cx)
+        else {
+            return Task::ready(Ok(ProjectTransaction::default()));
+        };
+        let is_server_command = server
+            .capabilities()
+            .execute_command_provider
+            .as_ref()
+            .map_or(false, |options| options.commands.contains(&command.command));
+        if !is_server_command {
+            return Task::ready(Err(anyhow!(
+                "command {} is not supported by language server {}",
+                command.command,
+                server.name()
+            )));
+        }
+
+        let server = server.clone();
+        cx.spawn(move |this, mut cx| async move {
+            this.update(&mut cx, |this, _| {
+                this.last_workspace_edits_by_language_server
+                    .remove(&server.server_id());
+            })?;
+            server
+                .request::<lsp::request::ExecuteCommand>(lsp::ExecuteCommandParams {
+                    command: command.command,
+                    arguments: command.arguments.unwrap_or_default(),
+                    ..Default::default()
+                })
+                .await?;
+            this.update(&mut cx, |this, _| {
+                this.last_workspace_edits_by_language_server
+                    .remove(&server.server_id())
+                    .unwrap_or_default()
+            })
+        })
+    }
+
     pub(crate) fn linked_edit(
         &self,
         buffer: &Model<Buffer>,
@@ -971,8 +1356,11 @@ impl LspStore {
             let offset = position.to_offset(&snapshot);
             let scope = snapshot.language_scope_at(offset);
             let language = snapshot.language().cloned();
+            let routing = language_settings(snapshot.language(), snapshot.file(), cx)
+                .language_server_routing
+                .completions;
 
-            let server_ids: Vec<_> = self
+            let mut server_ids: Vec<_> = self
                 .language_servers_for_buffer(buffer.read(cx), cx)
                 .filter(|(_, server)| server.capabilities().completion_provider.is_some())
                 .filter(|(adapter, _)| {
@@ -983,6 +1371,9 @@ impl LspStore {
                 })
                 .map(|(_, server)| server.server_id())
                 .collect();
+            if routing == CompletionsRouting::Primary {
+                server_ids.truncate(1);
+            }
 
             let buffer = buffer.clone();
             cx.spawn(move |this, mut cx| async move {
@@ -1348,6 +1739,7 @@ impl LspStore {
         &mut self,
         buffer_handle: Model<Buffer>,
         range: Range<Anchor>,
+        schedule: LspRequestSchedule,
         cx: &mut ModelContext<Self>,
     ) -> Task<anyhow::Result<Vec<InlayHint>>> {
         let buffer = buffer_handle.read(cx);
@@ -1380,10 +1772,11 @@ impl LspStore {
                 .context("inlay hints proto response conversion")
             })
         } else {
-            let lsp_request_task = self.request_lsp(
+            let lsp_request_task = self.request_lsp_with_schedule(
                 buffer_handle.clone(),
                 LanguageServerToQuery::Primary,
                 lsp_request,
+                Some(schedule),
                 cx,
             );
             cx.spawn(move |_, mut cx| async move {
@@ -1490,12 +1883,13 @@ impl LspStore {
                     GetHover { position }.to_proto(self.project_id, buffer.read(cx)),
                 )),
             });
+            let routing = hover_routing(buffer, cx);
             let buffer = buffer.clone();
             cx.spawn(|weak_project, cx| async move {
                 let Some(project) = weak_project.upgrade() else {
                     return Vec::new();
                 };
-                join_all(
+                let hovers = join_all(
                     request_task
                         .await
                         .log_err()
@@ -1530,21 +1924,34 @@ impl LspStore {
                 .await
                 .into_iter()
                 .flatten()
-                .collect()
+                .collect();
+                route_hovers(routing, hovers)
             })
         } else {
-            let all_actions_task = self.request_multiple_lsp_locally(
-                &buffer,
-                Some(position),
-                GetHover { position },
-                cx,
-            );
+            let routing = hover_routing(buffer, cx);
+            let hover_task = if routing == HoverRouting::Primary {
+                let task = self.request_lsp(
+                    buffer.clone(),
+                    LanguageServerToQuery::Primary,
+                    GetHover { position },
+                    cx,
+                );
+                cx.spawn(|_, _| async move { task.await.log_err().into_iter().collect::<Vec<_>>() })
+            } else {
+                self.request_multiple_lsp_locally(
+                    &buffer,
+                    Some(position),
+                    GetHover { position },
+                    cx,
+                )
+            };
             cx.spawn(|_, _| async move {
-                all_actions_task
+                let hovers = hover_task
                     .await
                     .into_iter()
                     .filter_map(|hover| remove_empty_hover_blocks(hover?))
-                    .collect::<Vec<Hover>>()
+                    .collect::<Vec<Hover>>();
+                route_hovers(routing, hovers)
             })
         }
     }
@@ -1788,10 +2195,10 @@ impl LspStore {
 
     pub fn on_buffer_edited(
         &mut self,
-        buffer: Model<Buffer>,
+        buffer_handle: Model<Buffer>,
         cx: &mut ModelContext<Self>,
     ) -> Option<()> {
-        let buffer = buffer.read(cx);
+        let buffer = buffer_handle.read(cx);
         let file = File::from_dyn(buffer.file())?;
         let abs_path = file.as_local()?.abs_path(cx);
         let uri = lsp::Url::from_file_path(abs_path).unwrap();
@@ -1882,6 +2289,7 @@ impl LspStore {
                 .log_err();
         }
 
+        self.pull_diagnostics_for_buffer(&buffer_handle, cx);
         None
     }
 
@@ -1919,6 +2327,7 @@ impl LspStore {
             self.simulate_disk_based_diagnostics_events_if_needed(language_server_id, cx);
         }
 
+        self.pull_diagnostics_for_buffer(&buffer, cx);
         None
     }
 
@@ -2035,6 +2444,10 @@ impl LspStore {
             self.language_server_statuses.remove(&server_id_to_remove);
             self.language_server_watched_paths
                 .remove(&server_id_to_remove);
+            self.pulled_diagnostics_result_ids
+                .remove(&server_id_to_remove);
+            self.pull_workspace_diagnostics_tasks
+                .remove(&server_id_to_remove);
             self.last_workspace_edits_by_language_server
                 .remove(&server_id_to_remove);
             self.language_servers.remove(&server_id_to_remove);
@@ -2064,6 +2477,12 @@ impl LspStore {
         }
     }
 
+    /// Sends the language server requests to the machine the project's files
+    /// are on, rather than to language servers running on this one.
+    pub(crate) fn set_upstream_client(&mut self, upstream_client: AnyProtoClient) {
+        self.upstream_client = Some(upstream_client);
+    }
+
     pub fn disconnected_from_host(&mut self) {
         self.downstream_client.take();
     }
@@ -2080,8 +2499,10 @@ impl LspStore {
                     LanguageServerStatus {
                         name: server.name,
                         pending_work: Default::default(),
+                        finished_work: Default::default(),
                         has_pending_diagnostic_updates: false,
                         progress_tokens: Default::default(),
+                        cancelled_tokens: Default::default(),
                     },
                 )
             })
@@ -2098,6 +2519,68 @@ impl LspStore {
             .insert((worktree_id, language_server_name), language_server_id);
     }
 
+    /// Detects the language of a buffer opened on behalf of another machine,
+    /// starts the language servers of that language and registers the buffer
+    /// with them.
+    pub fn register_remotely_opened_buffer(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        buffer.update(cx, |buffer, _| {
+            buffer.set_language_registry(self.languages.clone())
+        });
+        self.register_buffer_with_language_servers(buffer, cx);
+        self.detect_language_for_remotely_opened_buffer(buffer, cx);
+    }
+
+    /// Sets the language of a buffer opened by a remote client, and starts its
+    /// language servers, once the language is loaded. Buffers whose language isn't
+    /// registered yet are detected again when the registry changes.
+    pub fn detect_language_for_remotely_opened_buffer(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let language = {
+            let buffer = buffer.read(cx);
+            let Some(file) = buffer.file() else {
+                return;
+            };
+            self.languages
+                .language_for_file(file, Some(buffer.as_rope()), cx)
+        };
+        let buffer = buffer.downgrade();
+        cx.spawn(move |this, mut cx| async move {
+            let Ok(language) = language.await else {
+                return Ok(());
+            };
+            let Some(buffer) = buffer.upgrade() else {
+                return Ok(());
+            };
+            this.update(&mut cx, |this, cx| {
+                // The buffer may have been detected again while the language loaded.
+                let has_language = buffer
+                    .read(cx)
+                    .language()
+                    .map_or(false, |current| Arc::ptr_eq(current, &language));
+                if has_language {
+                    return;
+                }
+                buffer.update(cx, |buffer, cx| {
+                    buffer.set_language(Some(language.clone()), cx)
+                });
+                let worktree =
+                    File::from_dyn(buffer.read(cx).file()).map(|file| file.worktree.clone());
+                if let Some(worktree) = worktree {
+                    this.start_language_servers(&worktree, language, cx);
+                }
+                this.register_buffer_with_language_servers(&buffer, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     pub(crate) fn register_buffer_with_language_servers(
         &mut self,
         buffer_handle: &Model<Buffer>,
@@ -2181,10 +2664,12 @@ impl LspStore {
                         .insert(server.server_id(), vec![snapshot]);
                 }
             }
+
+            self.pull_diagnostics_for_buffer(buffer_handle, cx);
         }
     }
 
-    pub(crate) fn unregister_buffer_from_language_servers(
+    pub fn unregister_buffer_from_language_servers(
         &mut self,
         buffer: &Model<Buffer>,
         old_file: &File,
@@ -2210,6 +2695,7 @@ impl LspStore {
 
             self.buffer_snapshots.remove(&buffer.remote_id());
             let file_url = lsp::Url::from_file_path(old_path).unwrap();
+            self.forget_pulled_diagnostics(buffer.remote_id(), Some(&file_url));
             for (_, language_server) in self.language_servers_for_buffer(buffer, cx) {
                 language_server
                     .notify::<lsp::notification::DidCloseTextDocument>(
@@ -2564,7 +3050,9 @@ impl LspStore {
             })
             .map(|(_, server)| server.server_id())
             .collect::<Vec<_>>();
-        let mut response_results = server_ids
+        // Responses are kept in the order of the language servers, so that
+        // routing policies relying on server priority can be applied to them.
+        let response_results = server_ids
             .into_iter()
             .map(|server_id| {
                 self.request_lsp(
@@ -2574,16 +3062,14 @@ impl LspStore {
                     cx,
                 )
             })
-            .collect::<FuturesUnordered<_>>();
+            .collect::<Vec<_>>();
 
         return cx.spawn(|_, _| async move {
-            let mut responses = Vec::with_capacity(response_results.len());
-            while let Some(response_result) = response_results.next().await {
-                if let Some(response) = response_result.log_err() {
-                    responses.push(response);
-                }
-            }
-            responses
+            join_all(response_results)
+                .await
+                .into_iter()
+                .filter_map(|response_result| response_result.log_err())
+                .collect()
         });
     }
 
@@ -2596,7 +3082,7 @@ impl LspStore {
         <T::LspRequest as lsp::request::Request>::Params: Send,
         <T::LspRequest as lsp::request::Request>::Result: Send,
     {
-        let sender_id = envelope.original_sender_id()?;
+        let sender_id = envelope.original_sender_id().unwrap_or(envelope.sender_id);
         let buffer_id = T::buffer_id_from_proto(&envelope.payload)?;
         let buffer_handle = this.update(&mut cx, |this, cx| {
             this.buffer_store.read(cx).get_existing(buffer_id)
@@ -2634,7 +3120,7 @@ impl LspStore {
         envelope: TypedEnvelope<proto::MultiLspQuery>,
         mut cx: AsyncAppContext,
     ) -> Result<proto::MultiLspQueryResponse> {
-        let sender_id = envelope.original_sender_id()?;
+        let sender_id = envelope.original_sender_id().unwrap_or(envelope.sender_id);
         let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
         let version = deserialize_version(&envelope.payload.version);
         let buffer = this.update(&mut cx, |this, cx| {
@@ -2769,25 +3255,107 @@ impl LspStore {
         }
     }
 
-    pub async fn handle_apply_code_action(
+    pub async fn handle_apply_code_action(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ApplyCodeAction>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ApplyCodeActionResponse> {
+        let sender_id = envelope.original_sender_id().unwrap_or(envelope.sender_id);
+        let action = Self::deserialize_code_action(
+            envelope
+                .payload
+                .action
+                .ok_or_else(|| anyhow!("invalid action"))?,
+        )?;
+        let apply_code_action = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            let buffer = this.buffer_store.read(cx).get_existing(buffer_id)?;
+            anyhow::Ok(this.apply_code_action(buffer, action, false, cx))
+        })??;
+
+        let project_transaction = apply_code_action.await?;
+        let project_transaction = this.update(&mut cx, |this, cx| {
+            this.buffer_store.update(cx, |buffer_store, cx| {
+                buffer_store.serialize_project_transaction_for_peer(
+                    project_transaction,
+                    sender_id,
+                    cx,
+                )
+            })
+        })?;
+        Ok(proto::ApplyCodeActionResponse {
+            transaction: Some(project_transaction),
+        })
+    }
+
+    pub async fn handle_get_code_lens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetCodeLens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetCodeLensResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let buffer = this.update(&mut cx, |this, cx| {
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&envelope.payload.version))
+            })?
+            .await
+            .with_context(|| format!("waiting for version for buffer {}", buffer.entity_id()))?;
+
+        let version = buffer.update(&mut cx, |buffer, _| buffer.version())?;
+        let code_lens = this
+            .update(&mut cx, |this, cx| this.code_lens(&buffer, cx))?
+            .await?;
+        Ok(proto::GetCodeLensResponse {
+            lenses: code_lens.iter().map(Self::serialize_code_lens).collect(),
+            version: serialize_version(&version),
+        })
+    }
+
+    pub async fn handle_resolve_code_lens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ResolveCodeLens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ResolveCodeLensResponse> {
+        let code_lens = Self::deserialize_code_lens(
+            envelope
+                .payload
+                .lens
+                .ok_or_else(|| anyhow!("invalid code lens"))?,
+        )?;
+        let resolve_code_lens = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            let buffer = this.buffer_store.read(cx).get_existing(buffer_id)?;
+            anyhow::Ok(this.resolve_code_lens(&buffer, code_lens, cx))
+        })??;
+
+        let code_lens = resolve_code_lens.await?;
+        Ok(proto::ResolveCodeLensResponse {
+            lens: Some(Self::serialize_code_lens(&code_lens)),
+        })
+    }
+
+    pub async fn handle_execute_code_lens(
         this: Model<Self>,
-        envelope: TypedEnvelope<proto::ApplyCodeAction>,
+        envelope: TypedEnvelope<proto::ExecuteCodeLens>,
         mut cx: AsyncAppContext,
-    ) -> Result<proto::ApplyCodeActionResponse> {
-        let sender_id = envelope.original_sender_id()?;
-        let action = Self::deserialize_code_action(
+    ) -> Result<proto::ExecuteCodeLensResponse> {
+        let sender_id = envelope.original_sender_id().unwrap_or(envelope.sender_id);
+        let code_lens = Self::deserialize_code_lens(
             envelope
                 .payload
-                .action
-                .ok_or_else(|| anyhow!("invalid action"))?,
+                .lens
+                .ok_or_else(|| anyhow!("invalid code lens"))?,
         )?;
-        let apply_code_action = this.update(&mut cx, |this, cx| {
+        let execute_code_lens = this.update(&mut cx, |this, cx| {
             let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
             let buffer = this.buffer_store.read(cx).get_existing(buffer_id)?;
-            anyhow::Ok(this.apply_code_action(buffer, action, false, cx))
+            anyhow::Ok(this.execute_code_lens(&buffer, code_lens, cx))
         })??;
 
-        let project_transaction = apply_code_action.await?;
+        let project_transaction = execute_code_lens.await?;
         let project_transaction = this.update(&mut cx, |this, cx| {
             this.buffer_store.update(cx, |buffer_store, cx| {
                 buffer_store.serialize_project_transaction_for_peer(
@@ -2797,7 +3365,7 @@ impl LspStore {
                 )
             })
         })?;
-        Ok(proto::ApplyCodeActionResponse {
+        Ok(proto::ExecuteCodeLensResponse {
             transaction: Some(project_transaction),
         })
     }
@@ -2864,8 +3432,10 @@ impl LspStore {
                 LanguageServerStatus {
                     name: server.name,
                     pending_work: Default::default(),
+                    finished_work: Default::default(),
                     has_pending_diagnostic_updates: false,
                     progress_tokens: Default::default(),
+                    cancelled_tokens: Default::default(),
                 },
             );
             cx.notify();
@@ -2897,6 +3467,7 @@ impl LspStore {
                             message: payload.message,
                             percentage: payload.percentage.map(|p| p as usize),
                             last_update_at: cx.background_executor().now(),
+                            started_at: cx.background_executor().now(),
                         },
                         cx,
                     );
@@ -2913,6 +3484,7 @@ impl LspStore {
                             message: payload.message,
                             percentage: payload.percentage.map(|p| p as usize),
                             last_update_at: cx.background_executor().now(),
+                            started_at: cx.background_executor().now(),
                         },
                         cx,
                     );
@@ -3542,6 +4114,41 @@ impl LspStore {
         })
     }
 
+    fn on_raw_lsp_progress(
+        &mut self,
+        params: RawProgressParams,
+        language_server_id: LanguageServerId,
+        disk_based_diagnostics_progress_token: Option<String>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if let lsp::NumberOrString::String(token) = &params.token {
+            if let Some((server_id, pulled)) = self
+                .pull_diagnostics_partial_result_tokens
+                .get(token)
+                .cloned()
+            {
+                if server_id == language_server_id {
+                    self.on_pulled_diagnostics_partial_result(server_id, pulled, params.value, cx)
+                        .log_err();
+                }
+                return;
+            }
+        }
+
+        match serde_json::from_value(params.value) {
+            Ok(progress) => self.on_lsp_progress(
+                lsp::ProgressParams {
+                    token: params.token,
+                    value: lsp::ProgressParamsValue::WorkDone(progress),
+                },
+                language_server_id,
+                disk_based_diagnostics_progress_token,
+                cx,
+            ),
+            Err(error) => log::warn!("unrecognized progress notification: {error}"),
+        }
+    }
+
     fn on_lsp_progress(
         &mut self,
         progress: lsp::ProgressParams,
@@ -3590,6 +4197,7 @@ impl LspStore {
                         message: report.message.clone(),
                         percentage: report.percentage.map(|p| p as usize),
                         last_update_at: cx.background_executor().now(),
+                        started_at: cx.background_executor().now(),
                     },
                     cx,
                 );
@@ -3605,6 +4213,7 @@ impl LspStore {
                         message: report.message.clone(),
                         percentage: report.percentage.map(|p| p as usize),
                         last_update_at: cx.background_executor().now(),
+                        started_at: cx.background_executor().now(),
                     },
                     cx,
                 ) {
@@ -3699,6 +4308,17 @@ impl LspStore {
                 if !work.is_disk_based_diagnostics_progress {
                     cx.emit(LspStoreEvent::RefreshInlayHints);
                 }
+                if status.finished_work.len() == MAX_FINISHED_LANGUAGE_SERVER_WORK {
+                    status.finished_work.pop_front();
+                }
+                status.finished_work.push_back(LanguageServerFinishedWork {
+                    was_cancelled: status.cancelled_tokens.remove(&token),
+                    token: token.clone(),
+                    title: work.title,
+                    message: work.message,
+                    started_at: work.started_at,
+                    finished_at: cx.background_executor().now(),
+                });
             }
             cx.notify();
         }
@@ -3922,7 +4542,7 @@ impl LspStore {
         })
     }
 
-    async fn handle_on_type_formatting(
+    pub async fn handle_on_type_formatting(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::OnTypeFormatting>,
         mut cx: AsyncAppContext,
@@ -3950,7 +4570,7 @@ impl LspStore {
         Ok(proto::OnTypeFormattingResponse { transaction })
     }
 
-    async fn handle_refresh_inlay_hints(
+    pub async fn handle_refresh_inlay_hints(
         this: Model<Self>,
         _: TypedEnvelope<proto::RefreshInlayHints>,
         mut cx: AsyncAppContext,
@@ -3966,7 +4586,7 @@ impl LspStore {
         envelope: TypedEnvelope<proto::InlayHints>,
         mut cx: AsyncAppContext,
     ) -> Result<proto::InlayHintsResponse> {
-        let sender_id = envelope.original_sender_id()?;
+        let sender_id = envelope.original_sender_id().unwrap_or(envelope.sender_id);
         let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
         let buffer = this.update(&mut cx, |this, cx| {
             this.buffer_store.read(cx).get_existing(buffer_id)
@@ -3990,7 +4610,12 @@ impl LspStore {
             .context("missing range end")?;
         let buffer_hints = this
             .update(&mut cx, |lsp_store, cx| {
-                lsp_store.inlay_hints(buffer.clone(), start..end, cx)
+                lsp_store.inlay_hints(
+                    buffer.clone(),
+                    start..end,
+                    LspRequestSchedule::default(),
+                    cx,
+                )
             })?
             .await
             .context("inlay hints fetch")?;
@@ -4097,12 +4722,12 @@ impl LspStore {
         }
     }
 
-    async fn handle_open_buffer_for_symbol(
+    pub async fn handle_open_buffer_for_symbol(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::OpenBufferForSymbol>,
         mut cx: AsyncAppContext,
     ) -> Result<proto::OpenBufferForSymbolResponse> {
-        let peer_id = envelope.original_sender_id()?;
+        let peer_id = envelope.original_sender_id().unwrap_or(envelope.sender_id);
         let symbol = envelope
             .payload
             .symbol
@@ -4204,7 +4829,7 @@ impl LspStore {
         Ok(proto::Ack {})
     }
 
-    async fn handle_apply_additional_edits_for_completion(
+    pub async fn handle_apply_additional_edits_for_completion(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::ApplyCompletionAdditionalEdits>,
         mut cx: AsyncAppContext,
@@ -4250,6 +4875,23 @@ impl LspStore {
         })
     }
 
+    /// Synchronizes the language servers defined in the `custom_language_servers`
+    /// settings with the language registry.
+    ///
+    /// Returns the names of the language servers whose definition changed.
+    pub fn update_custom_language_servers(
+        &mut self,
+        cx: &mut ModelContext<Self>,
+    ) -> Vec<LanguageServerName> {
+        if self.upstream_client.is_some() {
+            return Vec::new();
+        }
+        self.custom_language_servers.update(
+            &ProjectSettings::get_global(cx).custom_language_servers,
+            &self.languages,
+        )
+    }
+
     pub fn start_language_servers(
         &mut self,
         worktree: &Model<Worktree>,
@@ -4308,6 +4950,12 @@ impl LspStore {
         );
 
         for adapter in &enabled_lsp_adapters {
+            if !self
+                .custom_language_servers
+                .should_start(&adapter.name, worktree.read(cx))
+            {
+                continue;
+            }
             self.start_language_server(worktree, adapter.clone(), language.clone(), cx);
         }
 
@@ -4642,6 +5290,10 @@ impl LspStore {
 
             self.language_server_watched_paths.remove(&server_id);
             self.language_server_statuses.remove(&server_id);
+            self.pulled_diagnostics_result_ids.remove(&server_id);
+            self.pull_workspace_diagnostics_tasks.remove(&server_id);
+            self.pull_diagnostics_partial_result_tokens
+                .retain(|_, (id, _)| *id != server_id);
             cx.notify();
 
             let server_state = self.language_servers.remove(&server_id);
@@ -5106,6 +5758,36 @@ impl LspStore {
             })
             .detach();
 
+        language_server
+            .on_request::<lsp::request::CodeLensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |_, cx| {
+                            cx.emit(LspStoreEvent::RefreshCodeLens);
+                        })?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::WorkspaceDiagnosticRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            this.refresh_pulled_diagnostics(server_id, cx);
+                        })?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
         language_server
             .on_request::<lsp::request::ShowMessageRequest, _, _>({
                 let this = this.clone();
@@ -5216,12 +5898,12 @@ impl LspStore {
             })
             .detach();
         language_server
-            .on_notification::<lsp::notification::Progress, _>({
+            .on_notification::<RawProgress, _>({
                 let this = this.clone();
                 move |params, mut cx| {
                     if let Some(this) = this.upgrade() {
                         this.update(&mut cx, |this, cx| {
-                            this.on_lsp_progress(
+                            this.on_raw_lsp_progress(
                                 params,
                                 server_id,
                                 disk_based_diagnostics_progress_token.clone(),
@@ -5301,6 +5983,376 @@ impl LspStore {
         Ok(language_server)
     }
 
+    /// Requests diagnostics for the buffer from each of its language servers
+    /// that supports pull diagnostics, replacing any pending request for it.
+    fn pull_diagnostics_for_buffer(&mut self, buffer: &Model<Buffer>, cx: &mut ModelContext<Self>) {
+        if self.upstream_client.is_some() {
+            return;
+        }
+        let Some(file) = File::from_dyn(buffer.read(cx).file()).and_then(|file| file.as_local())
+        else {
+            return;
+        };
+        let Some(uri) = lsp::Url::from_file_path(file.abs_path(cx)).log_err() else {
+            return;
+        };
+        let has_pull_diagnostics_server = self
+            .language_servers_for_buffer(buffer.read(cx), cx)
+            .any(|(_, server)| server.capabilities().diagnostic_provider.is_some());
+        if !has_pull_diagnostics_server {
+            return;
+        }
+
+        self.pull_diagnostics_partial_result_tokens
+            .retain(|_, (_, pulled)| !matches!(pulled, PulledDiagnostics::Document(document) if *document == uri));
+        let buffer = buffer.clone();
+        let buffer_id = buffer.read(cx).remote_id();
+        let task = cx.spawn(move |this, mut cx| async move {
+            cx.background_executor()
+                .timer(PULL_DIAGNOSTICS_DEBOUNCE)
+                .await;
+            let Ok(requests) = this.update(&mut cx, |this, cx| {
+                this.language_servers_for_buffer(buffer.read(cx), cx)
+                    .filter_map(|(adapter, server)| {
+                        let options = diagnostic_options(&server.capabilities())?;
+                        Some((adapter.clone(), server.clone(), options.identifier))
+                    })
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .map(|(adapter, server, identifier)| {
+                        let server_id = server.server_id();
+                        let token = this.next_pull_diagnostics_token(
+                            server_id,
+                            PulledDiagnostics::Document(uri.clone()),
+                        );
+                        let params = lsp::DocumentDiagnosticParams {
+                            text_document: lsp::TextDocumentIdentifier::new(uri.clone()),
+                            identifier,
+                            previous_result_id: this.pulled_diagnostics_result_id(server_id, &uri),
+                            work_done_progress_params: Default::default(),
+                            partial_result_params: lsp::PartialResultParams {
+                                partial_result_token: Some(lsp::NumberOrString::String(
+                                    token.clone(),
+                                )),
+                            },
+                        };
+                        (adapter, server, token, params)
+                    })
+                    .collect::<Vec<_>>()
+            }) else {
+                return;
+            };
+
+            let responses = join_all(requests.into_iter().map(
+                |(adapter, server, token, params)| async move {
+                    let response = server
+                        .request::<lsp::request::DocumentDiagnosticRequest>(params)
+                        .await;
+                    (adapter, server.server_id(), token, response)
+                },
+            ))
+            .await;
+
+            this.update(&mut cx, |this, cx| {
+                for (adapter, server_id, token, response) in responses {
+                    this.pull_diagnostics_partial_result_tokens.remove(&token);
+                    match response {
+                        Ok(report) => this
+                            .on_document_diagnostic_report(
+                                server_id,
+                                &adapter,
+                                uri.clone(),
+                                report,
+                                cx,
+                            )
+                            .log_err(),
+                        Err(error) => {
+                            log::warn!(
+                                "failed to pull diagnostics for {uri} from {}: {error}",
+                                adapter.name.0
+                            );
+                            None
+                        }
+                    };
+                }
+            })
+            .ok();
+        });
+        self.pull_diagnostics_tasks.insert(buffer_id, task);
+    }
+
+    /// Requests diagnostics for the whole workspace from a language server that
+    /// supports workspace pull diagnostics.
+    fn pull_workspace_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(LanguageServerState::Running {
+            adapter, server, ..
+        }) = self.language_servers.get(&server_id)
+        else {
+            return;
+        };
+        let Some(options) = diagnostic_options(&server.capabilities()) else {
+            return;
+        };
+        if !options.workspace_diagnostics {
+            return;
+        }
+        let adapter = adapter.clone();
+        let server = server.clone();
+
+        self.pull_diagnostics_partial_result_tokens
+            .retain(|_, (id, pulled)| {
+                *id != server_id || !matches!(pulled, PulledDiagnostics::Workspace)
+            });
+        let token = self.next_pull_diagnostics_token(server_id, PulledDiagnostics::Workspace);
+        let params = lsp::WorkspaceDiagnosticParams {
+            identifier: options.identifier,
+            previous_result_ids: self
+                .pulled_diagnostics_result_ids
+                .get(&server_id)
+                .into_iter()
+                .flatten()
+                .map(|(uri, result_id)| lsp::PreviousResultId {
+                    uri: uri.clone(),
+                    value: result_id.clone(),
+                })
+                .collect(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: lsp::PartialResultParams {
+                partial_result_token: Some(lsp::NumberOrString::String(token.clone())),
+            },
+        };
+
+        let task = cx.spawn(move |this, mut cx| async move {
+            let response = server
+                .request::<lsp::request::WorkspaceDiagnosticRequest>(params)
+                .await;
+            this.update(&mut cx, |this, cx| {
+                this.pull_diagnostics_partial_result_tokens.remove(&token);
+                match response {
+                    Ok(report) => this
+                        .on_workspace_diagnostic_report(server_id, &adapter, report, cx)
+                        .log_err(),
+                    Err(error) => {
+                        log::warn!(
+                            "failed to pull workspace diagnostics from {}: {error}",
+                            adapter.name.0
+                        );
+                        None
+                    }
+                };
+            })
+            .ok();
+        });
+        self.pull_workspace_diagnostics_tasks
+            .insert(server_id, task);
+    }
+
+    /// Pulls diagnostics again for every open buffer of the language server, and
+    /// for its workspace, in response to a `workspace/diagnostic/refresh` request.
+    fn refresh_pulled_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let buffers = self
+            .buffer_store
+            .read(cx)
+            .buffers()
+            .filter(|buffer| {
+                self.language_server_ids_for_buffer(buffer.read(cx), cx)
+                    .contains(&server_id)
+            })
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.pull_diagnostics_for_buffer(&buffer, cx);
+        }
+        self.pull_workspace_diagnostics(server_id, cx);
+    }
+
+    fn next_pull_diagnostics_token(
+        &mut self,
+        server_id: LanguageServerId,
+        pulled: PulledDiagnostics,
+    ) -> String {
+        let token = format!("zed-pull-diagnostics-{}", self.next_pull_diagnostics_token);
+        self.next_pull_diagnostics_token += 1;
+        self.pull_diagnostics_partial_result_tokens
+            .insert(token.clone(), (server_id, pulled));
+        token
+    }
+
+    /// Forgets the diagnostics pulled for a buffer that was released or moved
+    /// away from `uri`, dropping any pending request for them.
+    pub(crate) fn forget_pulled_diagnostics(
+        &mut self,
+        buffer_id: BufferId,
+        uri: Option<&lsp::Url>,
+    ) {
+        self.pull_diagnostics_tasks.remove(&buffer_id);
+        let Some(uri) = uri else {
+            return;
+        };
+        for result_ids in self.pulled_diagnostics_result_ids.values_mut() {
+            result_ids.remove(uri);
+        }
+        self.pull_diagnostics_partial_result_tokens.retain(
+            |_, (_, pulled)| !matches!(pulled, PulledDiagnostics::Document(document) if document == uri),
+        );
+    }
+
+    fn pulled_diagnostics_result_id(
+        &self,
+        server_id: LanguageServerId,
+        uri: &lsp::Url,
+    ) -> Option<String> {
+        self.pulled_diagnostics_result_ids
+            .get(&server_id)?
+            .get(uri)
+            .cloned()
+    }
+
+    fn on_pulled_diagnostics_partial_result(
+        &mut self,
+        server_id: LanguageServerId,
+        pulled: PulledDiagnostics,
+        value: serde_json::Value,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let Some(LanguageServerState::Running { adapter, .. }) =
+            self.language_servers.get(&server_id)
+        else {
+            return Ok(());
+        };
+        let adapter = adapter.clone();
+        match pulled {
+            PulledDiagnostics::Document(uri) => {
+                let report = serde_json::from_value(value)?;
+                self.on_document_diagnostic_report(server_id, &adapter, uri, report, cx)
+            }
+            PulledDiagnostics::Workspace => {
+                let report = serde_json::from_value(value)?;
+                self.on_workspace_diagnostic_report(server_id, &adapter, report, cx)
+            }
+        }
+    }
+
+    fn on_document_diagnostic_report(
+        &mut self,
+        server_id: LanguageServerId,
+        adapter: &CachedLspAdapter,
+        uri: lsp::Url,
+        report: lsp::DocumentDiagnosticReportResult,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let related_documents = match report {
+            lsp::DocumentDiagnosticReportResult::Report(lsp::DocumentDiagnosticReport::Full(
+                report,
+            )) => {
+                self.on_diagnostic_report(
+                    server_id,
+                    adapter,
+                    uri,
+                    lsp::DocumentDiagnosticReportKind::Full(report.full_document_diagnostic_report),
+                    cx,
+                )?;
+                report.related_documents
+            }
+            lsp::DocumentDiagnosticReportResult::Report(
+                lsp::DocumentDiagnosticReport::Unchanged(report),
+            ) => {
+                self.on_diagnostic_report(
+                    server_id,
+                    adapter,
+                    uri,
+                    lsp::DocumentDiagnosticReportKind::Unchanged(
+                        report.unchanged_document_diagnostic_report,
+                    ),
+                    cx,
+                )?;
+                report.related_documents
+            }
+            lsp::DocumentDiagnosticReportResult::Partial(report) => report.related_documents,
+        };
+
+        for (uri, report) in related_documents.into_iter().flatten() {
+            self.on_diagnostic_report(server_id, adapter, uri, report, cx)
+                .log_err();
+        }
+        Ok(())
+    }
+
+    fn on_workspace_diagnostic_report(
+        &mut self,
+        server_id: LanguageServerId,
+        adapter: &CachedLspAdapter,
+        report: lsp::WorkspaceDiagnosticReportResult,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let items = match report {
+            lsp::WorkspaceDiagnosticReportResult::Report(report) => report.items,
+            lsp::WorkspaceDiagnosticReportResult::Partial(report) => report.items,
+        };
+        for item in items {
+            let (uri, report) = match item {
+                lsp::WorkspaceDocumentDiagnosticReport::Full(report) => (
+                    report.uri,
+                    lsp::DocumentDiagnosticReportKind::Full(report.full_document_diagnostic_report),
+                ),
+                lsp::WorkspaceDocumentDiagnosticReport::Unchanged(report) => (
+                    report.uri,
+                    lsp::DocumentDiagnosticReportKind::Unchanged(
+                        report.unchanged_document_diagnostic_report,
+                    ),
+                ),
+            };
+            self.on_diagnostic_report(server_id, adapter, uri, report, cx)
+                .log_err();
+        }
+        Ok(())
+    }
+
+    fn on_diagnostic_report(
+        &mut self,
+        server_id: LanguageServerId,
+        adapter: &CachedLspAdapter,
+        uri: lsp::Url,
+        report: lsp::DocumentDiagnosticReportKind,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let result_ids = self
+            .pulled_diagnostics_result_ids
+            .entry(server_id)
+            .or_default();
+        match report {
+            lsp::DocumentDiagnosticReportKind::Full(report) => {
+                match report.result_id {
+                    Some(result_id) => result_ids.insert(uri.clone(), result_id),
+                    None => result_ids.remove(&uri),
+                };
+                let mut params = lsp::PublishDiagnosticsParams {
+                    uri,
+                    diagnostics: report.items,
+                    version: None,
+                };
+                adapter.process_diagnostics(&mut params);
+                self.update_diagnostics(
+                    server_id,
+                    params,
+                    &adapter.disk_based_diagnostic_sources,
+                    cx,
+                )
+            }
+            lsp::DocumentDiagnosticReportKind::Unchanged(report) => {
+                result_ids.insert(uri, report.result_id);
+                Ok(())
+            }
+        }
+    }
+
     pub fn update_diagnostics(
         &mut self,
         language_server_id: LanguageServerId,
@@ -5343,11 +6395,14 @@ impl LspStore {
             let is_unnecessary = diagnostic.tags.as_ref().map_or(false, |tags| {
                 tags.iter().any(|tag| *tag == DiagnosticTag::UNNECESSARY)
             });
+            let is_deprecated = diagnostic.tags.as_ref().map_or(false, |tags| {
+                tags.iter().any(|tag| *tag == DiagnosticTag::DEPRECATED)
+            });
 
             if is_supporting {
                 supporting_diagnostics.insert(
                     (source, code.clone(), range),
-                    (diagnostic.severity, is_unnecessary),
+                    (diagnostic.severity, is_unnecessary, is_deprecated),
                 );
             } else {
                 let group_id = post_inc(&mut self.next_diagnostic_group_id);
@@ -5369,6 +6424,7 @@ impl LspStore {
                         is_primary: true,
                         is_disk_based,
                         is_unnecessary,
+                        is_deprecated,
                         data: diagnostic.data.clone(),
                     },
                 });
@@ -5387,6 +6443,7 @@ impl LspStore {
                                     is_primary: false,
                                     is_disk_based,
                                     is_unnecessary: false,
+                                    is_deprecated: false,
                                     data: diagnostic.data.clone(),
                                 },
                             });
@@ -5400,15 +6457,14 @@ impl LspStore {
             let diagnostic = &mut entry.diagnostic;
             if !diagnostic.is_primary {
                 let source = *sources_by_group_id.get(&diagnostic.group_id).unwrap();
-                if let Some(&(severity, is_unnecessary)) = supporting_diagnostics.get(&(
-                    source,
-                    diagnostic.code.clone(),
-                    entry.range.clone(),
-                )) {
+                if let Some(&(severity, is_unnecessary, is_deprecated)) = supporting_diagnostics
+                    .get(&(source, diagnostic.code.clone(), entry.range.clone()))
+                {
                     if let Some(severity) = severity {
                         diagnostic.severity = severity;
                     }
                     diagnostic.is_unnecessary = is_unnecessary;
+                    diagnostic.is_deprecated = is_deprecated;
                 }
             }
         }
@@ -5452,6 +6508,7 @@ impl LspStore {
                 language: language.clone(),
                 server: language_server.clone(),
                 simulate_disk_based_diagnostics_completion: None,
+                request_scheduler: LspRequestScheduler::new(MAX_CONCURRENT_SCHEDULED_LSP_REQUESTS),
             },
         );
 
@@ -5460,8 +6517,10 @@ impl LspStore {
             LanguageServerStatus {
                 name: language_server.name().to_string(),
                 pending_work: Default::default(),
+                finished_work: Default::default(),
                 has_pending_diagnostic_updates: false,
                 progress_tokens: Default::default(),
+                cancelled_tokens: Default::default(),
             },
         );
 
@@ -5547,6 +6606,7 @@ impl LspStore {
             anyhow::Ok(())
         })?;
 
+        self.refresh_pulled_diagnostics(server_id, cx);
         cx.notify();
         Ok(())
     }
@@ -5749,7 +6809,7 @@ impl LspStore {
         token_to_cancel: Option<String>,
         _cx: &mut ModelContext<Self>,
     ) {
-        let status = self.language_server_statuses.get(&server_id);
+        let status = self.language_server_statuses.get_mut(&server_id);
         let server = self.language_servers.get(&server_id);
         if let Some((server, status)) = server.zip(status) {
             if let LanguageServerState::Running { server, .. } = server {
@@ -5767,6 +6827,7 @@ impl LspStore {
                                 },
                             )
                             .ok();
+                        status.cancelled_tokens.insert(token.clone());
                     }
                 }
             }
@@ -5793,6 +6854,10 @@ impl LspStore {
             self.languages
                 .lsp_adapters(&language)
                 .iter()
+                .filter(|adapter| {
+                    self.custom_language_servers
+                        .handles_path(&adapter.name, &file.path)
+                })
                 .flat_map(|adapter| {
                     let key = (worktree_id, adapter.name.clone());
                     self.language_server_ids.get(&key).copied()
@@ -5804,6 +6869,20 @@ impl LspStore {
     }
 }
 
+fn hover_routing(buffer: &Model<Buffer>, cx: &AppContext) -> HoverRouting {
+    let buffer = buffer.read(cx);
+    language_settings(buffer.language(), buffer.file(), cx)
+        .language_server_routing
+        .hover
+}
+
+fn route_hovers(routing: HoverRouting, hovers: Vec<Hover>) -> Vec<Hover> {
+    match routing {
+        HoverRouting::All => hovers,
+        HoverRouting::FirstNonEmpty | HoverRouting::Primary => hovers.into_iter().take(1).collect(),
+    }
+}
+
 fn remove_empty_hover_blocks(mut hover: Hover) -> Option<Hover> {
     hover
         .contents
@@ -6011,6 +7090,7 @@ pub enum LanguageServerState {
         adapter: Arc<CachedLspAdapter>,
         server: Arc<LanguageServer>,
         simulate_disk_based_diagnostics_completion: Option<Task<()>>,
+        request_scheduler: LspRequestScheduler,
     },
 }
 
@@ -6018,8 +7098,11 @@ pub enum LanguageServerState {
 pub struct LanguageServerStatus {
     pub name: String,
     pub pending_work: BTreeMap<String, LanguageServerProgress>,
+    /// The most recently finished work, oldest first.
+    pub finished_work: VecDeque<LanguageServerFinishedWork>,
     pub has_pending_diagnostic_updates: bool,
     progress_tokens: HashSet<String>,
+    cancelled_tokens: HashSet<String>,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -6031,6 +7114,21 @@ pub struct LanguageServerProgress {
     pub percentage: Option<usize>,
     #[serde(skip_serializing)]
     pub last_update_at: Instant,
+    #[serde(skip_serializing)]
+    pub started_at: Instant,
+}
+
+/// A unit of work-done progress that a language server has finished.
+#[derive(Clone, Debug, Serialize)]
+pub struct LanguageServerFinishedWork {
+    pub token: String,
+    pub title: Option<String>,
+    pub message: Option<String>,
+    pub was_cancelled: bool,
+    #[serde(skip_serializing)]
+    pub started_at: Instant,
+    #[serde(skip_serializing)]
+    pub finished_at: Instant,
 }
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, Serialize)]
@@ -6291,6 +7389,30 @@ fn include_text(server: &lsp::LanguageServer) -> Option<bool> {
     }
 }
 
+fn diagnostic_options(capabilities: &lsp::ServerCapabilities) -> Option<lsp::DiagnosticOptions> {
+    match capabilities.diagnostic_provider.as_ref()? {
+        lsp::DiagnosticServerCapabilities::Options(options) => Some(options.clone()),
+        lsp::DiagnosticServerCapabilities::RegistrationOptions(options) => {
+            Some(options.diagnostic_options.clone())
+        }
+    }
+}
+
+/// A `$/progress` notification, which either reports work done progress or
+/// carries partial results for a request, depending on its token.
+enum RawProgress {}
+
+impl lsp::notification::Notification for RawProgress {
+    type Params = RawProgressParams;
+    const METHOD: &'static str = "$/progress";
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RawProgressParams {
+    token: lsp::NumberOrString,
+    value: serde_json::Value,
+}
+
 fn serialize_symbol(symbol: &Symbol) -> proto::Symbol {
     proto::Symbol {
         language_server_name: symbol.language_server_name.0.to_string(),
@@ -0,0 +1,358 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+    time::SystemTime,
+};
+
+use anyhow::{anyhow, Result};
+use clock::ReplicaId;
+use collections::HashMap;
+use rpc::proto;
+use sha2::{Digest, Sha256};
+use text::{BufferId, Operation};
+
+/// The extension of patch bundle files.
+pub const PATCH_BUNDLE_EXTENSION: &str = "zedpatch";
+
+/// Bumped when bundles can't be read by older versions anymore.
+const FORMAT_VERSION: u32 = 1;
+
+/// The replica ids given to the authors of imported edits start here, above
+/// the ones the collab server gives to the participants of a project.
+const FIRST_IMPORTED_REPLICA_ID: ReplicaId = 256;
+
+/// The timestamp of the insertion of the text a buffer was opened with.
+const BASE_TEXT_TIMESTAMP: clock::Lamport = clock::Lamport {
+    replica_id: 0,
+    value: 1,
+};
+
+/// Where a replica of a buffer wrote its edits first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ReplicaOrigin {
+    session_id: u64,
+    replica_id: ReplicaId,
+}
+
+#[derive(Clone, Debug)]
+struct ImportedReplica {
+    origin: ReplicaOrigin,
+    author: Option<String>,
+}
+
+/// The edits of a patch bundle that a buffer doesn't contain yet.
+#[derive(Debug)]
+pub struct ImportedPatch {
+    pub operations: Vec<Operation>,
+    /// The authors of the new edits, when known.
+    pub authors: Vec<String>,
+}
+
+/// Exports the edits made to buffers since they were opened as patch
+/// bundles, and merges bundles exported elsewhere into buffers.
+///
+/// Edits keep their CRDT identity through bundles, so merging is
+/// conflict-free and a bundle's edits are never applied twice, even if it's
+/// imported again or sent back to where its edits were made.
+pub struct PatchBundles {
+    /// Identifies the edits written here, as opposed to imported ones.
+    session_id: u64,
+    imported_replicas: HashMap<BufferId, BTreeMap<ReplicaId, ImportedReplica>>,
+}
+
+impl Default for PatchBundles {
+    fn default() -> Self {
+        Self::with_session_id(rand::random())
+    }
+}
+
+impl PatchBundles {
+    fn with_session_id(session_id: u64) -> Self {
+        Self {
+            session_id,
+            imported_replicas: HashMap::default(),
+        }
+    }
+
+    /// The author of the edits of an imported replica of the buffer, if known.
+    pub fn author(&self, buffer_id: BufferId, replica_id: ReplicaId) -> Option<&str> {
+        self.imported_replicas
+            .get(&buffer_id)?
+            .get(&replica_id)?
+            .author
+            .as_deref()
+    }
+
+    /// Bundles the edits made to the buffer since it was opened.
+    ///
+    /// `authors` are the logins of the users who made the edits of each
+    /// replica, for those that weren't imported.
+    pub fn export(
+        &self,
+        buffer: &text::Buffer,
+        path: &Path,
+        authors: &HashMap<ReplicaId, String>,
+    ) -> proto::PatchBundle {
+        let has_base_text = !buffer.base_text().is_empty();
+        let imported_replicas = self.imported_replicas.get(&buffer.remote_id());
+        let mut replica_ids = BTreeSet::new();
+        let mut operations = Vec::new();
+        for (_, operation) in buffer.operations().iter() {
+            referenced_replicas(operation, has_base_text, &mut replica_ids);
+            operations.push(language::proto::serialize_operation(
+                &language::Operation::Buffer(operation.clone()),
+            ));
+        }
+
+        let replicas = replica_ids
+            .into_iter()
+            .map(|replica_id| {
+                let imported = imported_replicas.and_then(|replicas| replicas.get(&replica_id));
+                let origin = imported.map_or(
+                    ReplicaOrigin {
+                        session_id: self.session_id,
+                        replica_id,
+                    },
+                    |imported| imported.origin,
+                );
+                proto::PatchBundleReplica {
+                    replica_id: replica_id as u32,
+                    origin_session_id: origin.session_id,
+                    origin_replica_id: origin.replica_id as u32,
+                    author: imported
+                        .and_then(|imported| imported.author.clone())
+                        .or_else(|| authors.get(&replica_id).cloned()),
+                }
+            })
+            .collect();
+
+        proto::PatchBundle {
+            format_version: FORMAT_VERSION,
+            path: path.to_string_lossy().into_owned(),
+            base_text_digest: base_text_digest(buffer),
+            replicas,
+            operations,
+            exported_at: Some(SystemTime::now().into()),
+        }
+    }
+
+    /// Translates the edits of a bundle into operations on the buffer,
+    /// leaving out the ones it already contains.
+    pub fn import(
+        &mut self,
+        buffer: &text::Buffer,
+        bundle: proto::PatchBundle,
+    ) -> Result<ImportedPatch> {
+        if bundle.format_version > FORMAT_VERSION {
+            return Err(anyhow!(
+                "the patch bundle was exported by a newer version of Zed"
+            ));
+        }
+        if bundle.base_text_digest != base_text_digest(buffer) {
+            return Err(anyhow!(
+                "the patch bundle was exported from another version of {}",
+                bundle.path
+            ));
+        }
+
+        let has_base_text = !buffer.base_text().is_empty();
+        let version = buffer.version();
+        let imported_replicas = self
+            .imported_replicas
+            .entry(buffer.remote_id())
+            .or_default();
+        let mut replica_ids = HashMap::default();
+        for replica in bundle.replicas {
+            let origin = ReplicaOrigin {
+                session_id: replica.origin_session_id,
+                replica_id: replica.origin_replica_id as ReplicaId,
+            };
+            let replica_id = if origin.session_id == self.session_id {
+                origin.replica_id
+            } else if let Some(replica_id) =
+                imported_replicas.iter().find_map(|(replica_id, imported)| {
+                    (imported.origin == origin).then_some(*replica_id)
+                })
+            {
+                replica_id
+            } else {
+                let replica_id = (FIRST_IMPORTED_REPLICA_ID..=ReplicaId::MAX)
+                    .find(|replica_id| {
+                        !imported_replicas.contains_key(replica_id) && version.get(*replica_id) == 0
+                    })
+                    .ok_or_else(|| anyhow!("too many patch bundles were imported"))?;
+                imported_replicas.insert(
+                    replica_id,
+                    ImportedReplica {
+                        origin,
+                        author: replica.author,
+                    },
+                );
+                replica_id
+            };
+            replica_ids.insert(replica.replica_id as ReplicaId, replica_id);
+        }
+
+        let mut operations = Vec::new();
+        let mut authors = Vec::new();
+        for operation in bundle.operations {
+            let language::Operation::Buffer(operation) =
+                language::proto::deserialize_operation(operation)?
+            else {
+                continue;
+            };
+            let operation = remap_operation(operation, &replica_ids, has_base_text)
+                .ok_or_else(|| anyhow!("the patch bundle is corrupted"))?;
+            if version.observed(operation.timestamp()) {
+                continue;
+            }
+            if let Some(author) = imported_replicas
+                .get(&operation.replica_id())
+                .and_then(|imported| imported.author.as_ref())
+            {
+                if !authors.contains(author) {
+                    authors.push(author.clone());
+                }
+            }
+            operations.push(operation);
+        }
+
+        Ok(ImportedPatch {
+            operations,
+            authors,
+        })
+    }
+}
+
+fn base_text_digest(buffer: &text::Buffer) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    for chunk in buffer.base_text().chunks() {
+        hasher.update(chunk.as_bytes());
+    }
+    hasher.finalize().to_vec()
+}
+
+fn referenced_replicas(
+    operation: &Operation,
+    has_base_text: bool,
+    replica_ids: &mut BTreeSet<ReplicaId>,
+) {
+    let (timestamp, version) = match operation {
+        Operation::Edit(edit) => (edit.timestamp, &edit.version),
+        Operation::Undo(undo) => {
+            for edit_id in undo.counts.keys() {
+                replica_ids.insert(edit_id.replica_id);
+            }
+            (undo.timestamp, &undo.version)
+        }
+    };
+    replica_ids.insert(timestamp.replica_id);
+    for timestamp in version.iter() {
+        let only_base_text = has_base_text && timestamp == BASE_TEXT_TIMESTAMP;
+        if timestamp.value > 0 && !only_base_text {
+            replica_ids.insert(timestamp.replica_id);
+        }
+    }
+}
+
+/// Rewrites the timestamps of an operation exported elsewhere into the
+/// replica ids of the local buffer. The insertion of the base text is the
+/// same everywhere, so it's kept as is.
+fn remap_operation(
+    operation: Operation,
+    replica_ids: &HashMap<ReplicaId, ReplicaId>,
+    has_base_text: bool,
+) -> Option<Operation> {
+    let remap_timestamp = |timestamp: clock::Lamport| {
+        if has_base_text && timestamp == BASE_TEXT_TIMESTAMP {
+            Some(timestamp)
+        } else {
+            Some(clock::Lamport {
+                replica_id: *replica_ids.get(&timestamp.replica_id)?,
+                value: timestamp.value,
+            })
+        }
+    };
+    let remap_version = |version: &clock::Global| {
+        let mut remapped = clock::Global::new();
+        for timestamp in version.iter().filter(|timestamp| timestamp.value > 0) {
+            if has_base_text && timestamp.replica_id == BASE_TEXT_TIMESTAMP.replica_id {
+                remapped.observe(BASE_TEXT_TIMESTAMP);
+                if timestamp == BASE_TEXT_TIMESTAMP {
+                    continue;
+                }
+            }
+            remapped.observe(clock::Lamport {
+                replica_id: *replica_ids.get(&timestamp.replica_id)?,
+                value: timestamp.value,
+            });
+        }
+        Some(remapped)
+    };
+
+    Some(match operation {
+        Operation::Edit(mut edit) => {
+            edit.timestamp = remap_timestamp(edit.timestamp)?;
+            edit.version = remap_version(&edit.version)?;
+            Operation::Edit(edit)
+        }
+        Operation::Undo(mut undo) => {
+            undo.timestamp = remap_timestamp(undo.timestamp)?;
+            undo.version = remap_version(&undo.version)?;
+            undo.counts = undo
+                .counts
+                .into_iter()
+                .map(|(edit_id, count)| Some((remap_timestamp(edit_id)?, count)))
+                .collect::<Option<_>>()?;
+            Operation::Undo(undo)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patch_bundle_round_trip() {
+        let base_text = "one\ntwo\nthree\n";
+        let mut buffer_a = text::Buffer::new(0, BufferId::new(1).unwrap(), base_text.into());
+        let mut buffer_b = text::Buffer::new(0, BufferId::new(1).unwrap(), base_text.into());
+        let mut bundles_a = PatchBundles::with_session_id(1);
+        let mut bundles_b = PatchBundles::with_session_id(2);
+        let authors_a = HashMap::from_iter([(0, "alice".to_string())]);
+        let authors_b = HashMap::from_iter([(0, "bob".to_string())]);
+
+        // Both sides edit the same file concurrently.
+        buffer_a.edit([(0..3, "ONE")]);
+        buffer_a.edit([(14..14, "four\n")]);
+        buffer_b.edit([(4..7, "TWO")]);
+
+        let bundle = bundles_a.export(&buffer_a, Path::new("numbers.txt"), &authors_a);
+        let patch = bundles_b.import(&buffer_b, bundle.clone()).unwrap();
+        assert_eq!(patch.operations.len(), 2);
+        assert_eq!(patch.authors, ["alice"]);
+        buffer_b.apply_ops(patch.operations).unwrap();
+        assert_eq!(buffer_b.text(), "ONE\nTWO\nthree\nfour\n");
+
+        // Importing the same bundle again doesn't apply its edits twice.
+        let patch = bundles_b.import(&buffer_b, bundle).unwrap();
+        assert!(patch.operations.is_empty());
+
+        // The merged bundle only brings B's edits back to A.
+        let bundle = bundles_b.export(&buffer_b, Path::new("numbers.txt"), &authors_b);
+        let patch = bundles_a.import(&buffer_a, bundle).unwrap();
+        assert_eq!(patch.operations.len(), 1);
+        assert_eq!(patch.authors, ["bob"]);
+        buffer_a.apply_ops(patch.operations).unwrap();
+        assert_eq!(buffer_a.text(), buffer_b.text());
+        assert_eq!(bundles_b.author(buffer_b.remote_id(), 256), Some("alice"));
+
+        // Bundles can't be merged into another version of the file.
+        let buffer_c = text::Buffer::new(0, BufferId::new(1).unwrap(), "one\n".into());
+        let bundle = bundles_a.export(&buffer_a, Path::new("numbers.txt"), &authors_a);
+        assert!(PatchBundles::with_session_id(3)
+            .import(&buffer_c, bundle)
+            .is_err());
+    }
+}
@@ -1,15 +1,26 @@
+pub mod breakpoint_store;
 pub mod buffer_store;
 pub mod connection_manager;
+mod custom_language_servers;
 pub mod debounced_delay;
+pub mod grammar_checking;
+pub mod guest_permissions;
 pub mod lsp_command;
 pub mod lsp_ext_command;
+pub mod lsp_request_scheduler;
 pub mod lsp_store;
+pub mod patch_bundles;
 mod prettier_support;
 pub mod project_settings;
+pub mod review_store;
 pub mod search;
+pub mod search_index;
+pub mod settings_validation;
+pub mod spell_checking;
 mod task_inventory;
 pub mod terminals;
 pub mod worktree_store;
+pub mod worktree_trust;
 
 #[cfg(test)]
 mod project_tests;
@@ -35,16 +46,20 @@ use futures::{
     AsyncWriteExt, FutureExt, StreamExt,
 };
 
-use git::{blame::Blame, repository::GitRepository};
+use git::{
+    blame::Blame,
+    repository::{GitRepository, RepoPath},
+};
 use gpui::{
     AnyModel, AppContext, AsyncAppContext, BorrowAppContext, Context, Entity, EventEmitter, Model,
     ModelContext, SharedString, Task, WeakModel, WindowContext,
 };
+use grammar_checking::{GrammarChecking, GRAMMAR_CHECK_SERVER_ID};
 use itertools::Itertools;
 use language::{
     language_settings::{
-        language_settings, FormatOnSave, Formatter, InlayHintKind, LanguageSettings,
-        SelectedFormatter,
+        language_settings, FormatOnSave, Formatter, GrammarCheckTrigger, InlayHintKind,
+        LanguageSettings, SelectedFormatter,
     },
     proto::{
         deserialize_anchor, serialize_anchor, serialize_line_ending, serialize_version,
@@ -69,9 +84,11 @@ use rpc::{proto::AnyProtoClient, ErrorCode};
 use search::{SearchQuery, SearchResult};
 use search_history::SearchHistory;
 use settings::{watch_config_file, Settings, SettingsLocation, SettingsStore};
+use settings_validation::{SettingsValidation, SETTINGS_VALIDATION_SERVER_ID};
 use smol::channel::Receiver;
 use snippet::Snippet;
 use snippet_provider::SnippetProvider;
+use spell_checking::{SpellChecking, SPELL_CHECK_SERVER_ID};
 use std::{
     borrow::Cow,
     ops::Range,
@@ -82,32 +99,42 @@ use std::{
 };
 use task::{
     static_source::{StaticSource, TrackedFile},
-    HideStrategy, RevealStrategy, Shell, TaskContext, TaskTemplate, TaskVariables, VariableName,
+    DependsOrder, HideStrategy, RevealStrategy, Shell, TaskContext, TaskInput, TaskTemplate,
+    TaskVariables, VariableName,
 };
 use terminals::Terminals;
 use text::{Anchor, BufferId};
 use util::{defer, paths::compare_paths, ResultExt as _};
 use worktree::{CreatedEntry, Snapshot, Traversal};
 use worktree_store::{WorktreeStore, WorktreeStoreEvent};
+use worktree_trust::{WorktreeTrust, WorktreeTrustEvent, WorktreeTrustSettings};
 
+pub use breakpoint_store::{BreakpointStore, BreakpointStoreEvent};
 pub use fs::*;
+pub use guest_permissions::GuestPermissions;
 pub use language::Location;
+pub use patch_bundles::{PatchBundles, PATCH_BUNDLE_EXTENSION};
 #[cfg(any(test, feature = "test-support"))]
 pub use prettier::FORMAT_SUFFIX as TEST_PRETTIER_FORMAT_SUFFIX;
+pub use review_store::{
+    ReviewComment, ReviewStore, ReviewStoreEvent, ReviewThread, ReviewThreadId,
+    SerializedReviewThread,
+};
 pub use task_inventory::{
     BasicContextProvider, ContextProviderWithTasks, Inventory, TaskSourceKind,
 };
 pub use worktree::{
     Entry, EntryKind, File, LocalWorktree, PathChange, ProjectEntryId, RepositoryEntry,
-    UpdatedEntriesSet, UpdatedGitRepositoriesSet, Worktree, WorktreeId, WorktreeSettings,
-    FS_WATCH_LATENCY,
+    SaveSettings, UpdatedEntriesSet, UpdatedGitRepositoriesSet, Worktree, WorktreeId,
+    WorktreeSettings, FS_WATCH_LATENCY,
 };
 
 pub use buffer_store::ProjectTransaction;
+pub use lsp_request_scheduler::{LspRequestPriority, LspRequestSchedule, LspRequestSuperseded};
 pub use lsp_store::{
-    DiagnosticSummary, LanguageServerLogType, LanguageServerProgress, LanguageServerPromptRequest,
-    LanguageServerStatus, LanguageServerToQuery, LspStore, LspStoreEvent,
-    ProjectLspAdapterDelegate, SERVER_PROGRESS_THROTTLE_TIMEOUT,
+    DiagnosticSummary, LanguageServerFinishedWork, LanguageServerLogType, LanguageServerProgress,
+    LanguageServerPromptRequest, LanguageServerStatus, LanguageServerToQuery, LspStore,
+    LspStoreEvent, ProjectLspAdapterDelegate, SERVER_PROGRESS_THROTTLE_TIMEOUT,
 };
 
 const MAX_PROJECT_SEARCH_HISTORY_SIZE: usize = 500;
@@ -160,11 +187,19 @@ pub struct Project {
     remotely_created_buffers: Arc<Mutex<RemotelyCreatedBuffers>>,
     _maintain_buffer_languages: Task<()>,
     terminals: Terminals,
+    spell_checking: SpellChecking,
+    grammar_checking: GrammarChecking,
+    settings_validation: SettingsValidation,
     node: Option<Arc<dyn NodeRuntime>>,
     default_prettier: DefaultPrettier,
     prettiers_per_worktree: HashMap<WorktreeId, HashSet<Option<PathBuf>>>,
     prettier_instances: HashMap<PathBuf, PrettierInstance>,
     tasks: Model<Inventory>,
+    breakpoint_store: Model<BreakpointStore>,
+    review_store: Model<ReviewStore>,
+    patch_bundles: PatchBundles,
+    guest_permissions: GuestPermissions,
+    edit_access_requested: bool,
     hosted_project_id: Option<ProjectId>,
     dev_server_project_id: Option<client::DevServerProjectId>,
     search_history: SearchHistory,
@@ -199,6 +234,15 @@ impl Drop for RemotelyCreatedBufferGuard {
         }
     }
 }
+/// The result of merging a patch bundle into the file it was exported from.
+pub struct PatchBundleImport {
+    pub buffer: Model<Buffer>,
+    /// The number of edits of the bundle the file didn't contain yet.
+    pub edit_count: usize,
+    /// The authors of those edits, when known.
+    pub authors: Vec<String>,
+}
+
 /// Message ordered with respect to buffer operations
 #[derive(Debug)]
 enum BufferOrderedMessage {
@@ -263,10 +307,15 @@ pub enum Event {
     },
     CollaboratorJoined(proto::PeerId),
     CollaboratorLeft(proto::PeerId),
+    /// A guest asked the host for permission to edit the project.
+    EditAccessRequested {
+        user_id: u64,
+    },
     HostReshared,
     Reshared,
     Rejoined,
     RefreshInlayHints,
+    RefreshCodeLens,
     RevealInProjectPanel(ProjectEntryId),
     SnippetEdit(BufferId, Vec<(lsp::Range, Snippet)>),
 }
@@ -381,6 +430,17 @@ pub struct CodeAction {
     pub lsp_action: lsp::CodeAction,
 }
 
+/// A code lens provided by a language server.
+#[derive(Clone, Debug)]
+pub struct CodeLens {
+    /// The id of the language server that produced this code lens.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer this code lens applies to.
+    pub range: Range<Anchor>,
+    /// The raw code lens provided by the language server.
+    pub lsp_lens: lsp::CodeLens,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResolveState {
     Resolved,
@@ -567,7 +627,9 @@ pub const DEFAULT_COMPLETION_CONTEXT: CompletionContext = CompletionContext {
 impl Project {
     pub fn init_settings(cx: &mut AppContext) {
         WorktreeSettings::register(cx);
+        SaveSettings::register(cx);
         ProjectSettings::register(cx);
+        WorktreeTrustSettings::register(cx);
     }
 
     pub fn init(client: &Arc<Client>, cx: &mut AppContext) {
@@ -593,6 +655,13 @@ impl Project {
         client.add_model_request_handler(Self::handle_task_context_for_location);
         client.add_model_request_handler(Self::handle_task_templates);
         client.add_model_message_handler(Self::handle_create_buffer_for_peer);
+        client.add_model_message_handler(Self::handle_update_guest_permissions);
+        client.add_model_request_handler(Self::handle_request_edit_access);
+        client.add_model_request_handler(Self::handle_save_buffer);
+        client.add_model_request_handler(Self::handle_create_project_entry);
+        client.add_model_request_handler(Self::handle_rename_project_entry);
+        client.add_model_request_handler(Self::handle_copy_project_entry);
+        client.add_model_request_handler(Self::handle_delete_project_entry);
 
         WorktreeStore::init(client);
         BufferStore::init(client);
@@ -613,7 +682,7 @@ impl Project {
             cx.spawn(move |this, cx| Self::send_buffer_ordered_messages(this, rx, cx))
                 .detach();
             let tasks = Inventory::new(cx);
-            let global_snippets_dir = paths::config_dir().join("snippets");
+            let global_snippets_dir = paths::snippets_dir().to_owned();
             let snippets =
                 SnippetProvider::new(fs.clone(), BTreeSet::from_iter([global_snippets_dir]), cx);
 
@@ -625,6 +694,8 @@ impl Project {
                 cx.new_model(|cx| BufferStore::new(worktree_store.clone(), None, cx));
             cx.subscribe(&buffer_store, Self::on_buffer_store_event)
                 .detach();
+            let breakpoint_store = cx.new_model(|cx| BreakpointStore::new(&buffer_store, cx));
+            let review_store = cx.new_model(|cx| ReviewStore::new(&buffer_store, cx));
 
             let environment = ProjectEnvironment::new(env, cx);
             let lsp_store = cx.new_model(|cx| {
@@ -653,10 +724,16 @@ impl Project {
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
                 client_subscriptions: Vec::new(),
-                _subscriptions: vec![
-                    cx.observe_global::<SettingsStore>(Self::on_settings_changed),
-                    cx.on_release(Self::release),
-                ],
+                _subscriptions: WorktreeTrust::global(cx)
+                    .map(|worktree_trust| {
+                        cx.subscribe(&worktree_trust, Self::on_worktree_trust_event)
+                    })
+                    .into_iter()
+                    .chain([
+                        cx.observe_global::<SettingsStore>(Self::on_settings_changed),
+                        cx.on_release(Self::release),
+                    ])
+                    .collect(),
                 _maintain_buffer_languages: Self::maintain_buffer_languages(languages.clone(), cx),
                 active_entry: None,
                 snippets,
@@ -669,12 +746,25 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_diagnostics: HashMap::default(),
+                    task_waiters: HashMap::default(),
                 },
+                spell_checking: SpellChecking::default(),
+                grammar_checking: GrammarChecking::default(),
+                settings_validation: SettingsValidation::default(),
                 node: Some(node),
                 default_prettier: DefaultPrettier::default(),
                 prettiers_per_worktree: HashMap::default(),
                 prettier_instances: HashMap::default(),
                 tasks,
+                breakpoint_store,
+                review_store,
+                patch_bundles: PatchBundles::default(),
+                guest_permissions: {
+                    let settings = &ProjectSettings::get_global(cx).collaboration;
+                    GuestPermissions::new(settings.guests_can_edit, &settings.read_only_paths)
+                },
+                edit_access_requested: false,
                 hosted_project_id: None,
                 dev_server_project_id: None,
                 search_history: Self::new_search_history(),
@@ -706,6 +796,21 @@ impl Project {
             ssh.add_message_handler(cx.weak_model(), Self::handle_create_buffer_for_peer);
             ssh.add_message_handler(buffer_store.clone(), BufferStore::handle_update_buffer_file);
             ssh.add_message_handler(buffer_store.clone(), BufferStore::handle_update_diff_base);
+            ssh.add_request_handler(buffer_store, BufferStore::handle_update_buffer);
+            ssh.add_request_handler(cx.weak_model(), Self::handle_synchronize_buffers);
+
+            // Language servers run on the server, next to the project's files.
+            let lsp_store = this.lsp_store.downgrade();
+            this.lsp_store.update(cx, |lsp_store, _| {
+                lsp_store.set_upstream_client(ssh.clone().into());
+            });
+            ssh.add_message_handler(lsp_store.clone(), LspStore::handle_start_language_server);
+            ssh.add_message_handler(lsp_store.clone(), LspStore::handle_update_language_server);
+            ssh.add_message_handler(
+                lsp_store.clone(),
+                LspStore::handle_update_diagnostic_summary,
+            );
+            ssh.add_request_handler(lsp_store, LspStore::handle_refresh_inlay_hints);
 
             this.ssh_session = Some(ssh);
         });
@@ -811,7 +916,7 @@ impl Project {
         let this = cx.new_model(|cx| {
             let replica_id = response.payload.replica_id as ReplicaId;
             let tasks = Inventory::new(cx);
-            let global_snippets_dir = paths::config_dir().join("snippets");
+            let global_snippets_dir = paths::snippets_dir().to_owned();
             let snippets =
                 SnippetProvider::new(fs.clone(), BTreeSet::from_iter([global_snippets_dir]), cx);
 
@@ -829,6 +934,8 @@ impl Project {
             cx.subscribe(&buffer_store, Self::on_buffer_store_event)
                 .detach();
             cx.subscribe(&lsp_store, Self::on_lsp_store_event).detach();
+            let breakpoint_store = cx.new_model(|cx| BreakpointStore::new(&buffer_store, cx));
+            let review_store = cx.new_model(|cx| ReviewStore::new(&buffer_store, cx));
 
             let mut this = Self {
                 buffer_ordered_messages_tx: tx,
@@ -859,12 +966,22 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_diagnostics: HashMap::default(),
+                    task_waiters: HashMap::default(),
                 },
+                spell_checking: SpellChecking::default(),
+                grammar_checking: GrammarChecking::default(),
+                settings_validation: SettingsValidation::default(),
                 node: None,
                 default_prettier: DefaultPrettier::default(),
                 prettiers_per_worktree: HashMap::default(),
                 prettier_instances: HashMap::default(),
                 tasks,
+                breakpoint_store,
+                review_store,
+                patch_bundles: PatchBundles::default(),
+                guest_permissions: GuestPermissions::default(),
+                edit_access_requested: false,
                 hosted_project_id: None,
                 dev_server_project_id: response
                     .payload
@@ -1061,7 +1178,25 @@ impl Project {
         self.lsp_store.clone()
     }
 
+    pub fn buffer_store(&self) -> Model<BufferStore> {
+        self.buffer_store.clone()
+    }
+
     fn on_settings_changed(&mut self, cx: &mut ModelContext<Self>) {
+        if !self.is_via_collab() {
+            let settings = &ProjectSettings::get_global(cx).collaboration;
+            if self
+                .guest_permissions
+                .set_defaults(settings.guests_can_edit, &settings.read_only_paths)
+            {
+                self.send_guest_permissions();
+            }
+        }
+
+        let changed_custom_language_servers = self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.update_custom_language_servers(cx)
+        });
+
         let mut language_servers_to_start = Vec::new();
         let mut language_formatters_to_check = Vec::new();
         for buffer in self.buffer_store.read(cx).buffers() {
@@ -1097,7 +1232,14 @@ impl Project {
                     .clone();
                 Some((l, adapter))
             });
-            if let Some((language, adapter)) = language {
+            if changed_custom_language_servers.contains(&started_lsp_name) {
+                match (language, self.worktree_for_id(worktree_id, cx)) {
+                    (Some((language, _)), Some(worktree)) => {
+                        language_servers_to_restart.push((worktree, Arc::clone(language)));
+                    }
+                    _ => language_servers_to_stop.push((worktree_id, started_lsp_name.clone())),
+                }
+            } else if let Some((language, adapter)) = language {
                 let worktree = self.worktree_for_id(worktree_id, cx);
                 let file = worktree.as_ref().and_then(|tree| {
                     tree.update(cx, |tree, cx| tree.root_file(cx).map(|f| f as _))
@@ -1164,6 +1306,9 @@ impl Project {
                 lsp_store.restart_language_servers(worktree, language, cx);
             }
         });
+        self.recheck_spelling(cx);
+        self.recheck_grammar(cx);
+        self.revalidate_settings_files(cx);
 
         cx.notify();
     }
@@ -1196,6 +1341,22 @@ impl Project {
         self.environment.read(cx).get_cli_environment()
     }
 
+    /// Returns the environment to run the processes of a worktree with: the
+    /// inherited CLI environment, or the one of a shell started in the worktree.
+    pub fn worktree_environment(
+        &mut self,
+        worktree_id: WorktreeId,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Option<HashMap<String, String>>> {
+        let worktree_abs_path = self
+            .worktree_for_id(worktree_id, cx)
+            .map(|worktree| worktree.read(cx).abs_path());
+        let environment = self.environment.update(cx, |environment, cx| {
+            environment.get_environment(Some(worktree_id), worktree_abs_path, cx)
+        });
+        cx.background_executor().spawn(environment)
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn has_open_buffer(&self, path: impl Into<ProjectPath>, cx: &AppContext) -> bool {
         self.buffer_store
@@ -1250,6 +1411,11 @@ impl Project {
             .clone()
     }
 
+    /// The session with the remote server, when this project was opened over ssh.
+    pub fn ssh_session(&self) -> Option<&Arc<SshSession>> {
+        self.ssh_session.as_ref()
+    }
+
     pub fn replica_id(&self) -> ReplicaId {
         match self.client_state {
             ProjectClientState::Remote { replica_id, .. } => replica_id,
@@ -1298,6 +1464,14 @@ impl Project {
         &self.tasks
     }
 
+    pub fn breakpoint_store(&self) -> &Model<BreakpointStore> {
+        &self.breakpoint_store
+    }
+
+    pub fn review_store(&self) -> &Model<ReviewStore> {
+        &self.review_store
+    }
+
     pub fn snippets(&self) -> &Model<SnippetProvider> {
         &self.snippets
     }
@@ -1524,6 +1698,7 @@ impl Project {
         self.client_state = ProjectClientState::Shared {
             remote_id: project_id,
         };
+        self.send_guest_permissions();
 
         self.metadata_changed(cx);
         cx.emit(Event::RemoteIdChanged(Some(project_id)));
@@ -1540,6 +1715,7 @@ impl Project {
             .update(cx, |buffer_store, _| buffer_store.forget_shared_buffers());
         self.set_collaborators_from_proto(message.collaborators, cx)?;
         self.metadata_changed(cx);
+        self.send_guest_permissions();
         cx.emit(Event::Reshared);
         Ok(())
     }
@@ -1636,12 +1812,33 @@ impl Project {
             }
 
             *capability = new_capability;
-            for buffer in self.opened_buffers(cx) {
-                buffer.update(cx, |buffer, cx| buffer.set_capability(new_capability, cx));
-            }
+            self.update_buffer_capabilities(cx);
         }
     }
 
+    fn update_buffer_capabilities(&self, cx: &mut ModelContext<Self>) {
+        for buffer in self.opened_buffers(cx) {
+            self.update_buffer_capability(&buffer, cx);
+        }
+    }
+
+    /// Makes a guest's buffer read-only when the guest can't edit the project
+    /// or its path is read-only for guests.
+    fn update_buffer_capability(&self, buffer: &Model<Buffer>, cx: &mut AppContext) {
+        if !self.is_via_collab() {
+            return;
+        }
+        let is_read_only_path = File::from_dyn(buffer.read(cx).file()).map_or(false, |file| {
+            self.guest_permissions.is_read_only_path(&file.path)
+        });
+        let capability = if is_read_only_path {
+            Capability::ReadOnly
+        } else {
+            self.capability()
+        };
+        buffer.update(cx, |buffer, cx| buffer.set_capability(capability, cx));
+    }
+
     fn disconnected_from_host_internal(&mut self, cx: &mut AppContext) {
         if let ProjectClientState::Remote {
             sharing_has_stopped,
@@ -1677,11 +1874,125 @@ impl Project {
 
     pub fn capability(&self) -> Capability {
         match &self.client_state {
-            ProjectClientState::Remote { capability, .. } => *capability,
+            ProjectClientState::Remote { capability, .. } => {
+                if *capability == Capability::ReadWrite && self.host_allows_editing() {
+                    Capability::ReadWrite
+                } else {
+                    Capability::ReadOnly
+                }
+            }
             ProjectClientState::Shared { .. } | ProjectClientState::Local => Capability::ReadWrite,
         }
     }
 
+    /// The permissions the host grants to the guests of the project.
+    pub fn guest_permissions(&self) -> &GuestPermissions {
+        &self.guest_permissions
+    }
+
+    /// Allows or forbids a guest of the shared project to edit it.
+    pub fn set_guest_can_edit(
+        &mut self,
+        user_id: u64,
+        can_edit: bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if self.is_via_collab() {
+            return;
+        }
+        if self.guest_permissions.set_can_edit(user_id, can_edit) {
+            self.send_guest_permissions();
+            cx.notify();
+        }
+    }
+
+    fn send_guest_permissions(&self) {
+        if let ProjectClientState::Shared { remote_id } = &self.client_state {
+            self.client
+                .send(self.guest_permissions.to_proto(*remote_id))
+                .log_err();
+        }
+    }
+
+    fn host_allows_editing(&self) -> bool {
+        self.client
+            .user_id()
+            .map_or(true, |user_id| self.guest_permissions.can_edit(user_id))
+    }
+
+    /// Whether the guest's role allows editing the project but the host
+    /// doesn't, in which case the guest can ask the host for permission.
+    pub fn can_request_edit_access(&self) -> bool {
+        match &self.client_state {
+            ProjectClientState::Remote {
+                capability,
+                sharing_has_stopped,
+                ..
+            } => {
+                !sharing_has_stopped
+                    && *capability == Capability::ReadWrite
+                    && !self.host_allows_editing()
+            }
+            _ => false,
+        }
+    }
+
+    pub fn edit_access_requested(&self) -> bool {
+        self.edit_access_requested
+    }
+
+    /// Asks the host of the project for permission to edit it.
+    pub fn request_edit_access(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let Some(project_id) = self.remote_id() else {
+            return Task::ready(Err(anyhow!("project was not shared")));
+        };
+        if !self.can_request_edit_access() {
+            return Task::ready(Ok(()));
+        }
+        self.edit_access_requested = true;
+        cx.notify();
+        let request = self.client.request(proto::RequestEditAccess { project_id });
+        cx.spawn(|this, mut cx| async move {
+            let result = request.await;
+            if result.is_err() {
+                this.update(&mut cx, |this, cx| {
+                    this.edit_access_requested = false;
+                    cx.notify();
+                })?;
+            }
+            result.map(|_| ())
+        })
+    }
+
+    /// Fails unless the collaborator who sent a request to the host may write
+    /// to the paths, relative to their worktree.
+    fn authorize_guest_write<'a>(
+        &self,
+        sender_id: Option<proto::PeerId>,
+        paths: impl IntoIterator<Item = &'a Path>,
+    ) -> Result<()> {
+        let Some(sender_id) = sender_id else {
+            return Ok(());
+        };
+        if !matches!(self.client_state, ProjectClientState::Shared { .. }) {
+            return Ok(());
+        }
+        let user_id = self
+            .collaborators
+            .get(&sender_id)
+            .map(|collaborator| collaborator.user_id)
+            .context("unknown collaborator")?;
+        if !self.guest_permissions.can_edit(user_id) {
+            return Err(anyhow!("not allowed to edit the project"));
+        }
+        for path in paths {
+            if self.guest_permissions.is_read_only_path(path) {
+                return Err(anyhow!("{path:?} is read-only for guests"));
+            }
+        }
+        Ok(())
+    }
+
     pub fn is_read_only(&self) -> bool {
         self.is_disconnected() || self.capability() == Capability::ReadOnly
     }
@@ -1805,6 +2116,103 @@ impl Project {
         }
     }
 
+    /// Writes the edits made to the buffer since it was opened to a patch
+    /// bundle, which can be merged into another copy of the file later.
+    pub fn export_patch_bundle(
+        &self,
+        buffer: &Model<Buffer>,
+        abs_path: PathBuf,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let buffer = buffer.read(cx);
+        let Some(file) = buffer.file() else {
+            return Task::ready(Err(anyhow!("the buffer isn't saved to a file")));
+        };
+        if buffer.operations().is_empty() {
+            return Task::ready(Err(anyhow!(
+                "{} has no edits to export",
+                file.path().display()
+            )));
+        }
+        let bundle = self
+            .patch_bundles
+            .export(buffer, file.path(), &self.replica_authors(cx));
+        let fs = self.fs.clone();
+        cx.background_executor()
+            .spawn(async move { fs.atomic_write_bytes(abs_path, bundle.to_bytes()).await })
+    }
+
+    /// Merges the edits of a patch bundle into the file it was exported
+    /// from, leaving out the ones the file already contains.
+    pub fn import_patch_bundle(
+        &mut self,
+        abs_path: PathBuf,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<PatchBundleImport>> {
+        let fs = self.fs.clone();
+        cx.spawn(move |this, mut cx| async move {
+            let bytes = fs.load_bytes(&abs_path).await?;
+            let bundle = proto::PatchBundle::from_bytes(&bytes)
+                .with_context(|| format!("{} isn't a patch bundle", abs_path.display()))?;
+            let path: Arc<Path> = Path::new(&bundle.path).into();
+            let project_path = this
+                .update(&mut cx, |this, cx| {
+                    this.visible_worktrees(cx).find_map(|worktree| {
+                        let worktree = worktree.read(cx);
+                        worktree.entry_for_path(&path)?;
+                        Some(ProjectPath {
+                            worktree_id: worktree.id(),
+                            path: path.clone(),
+                        })
+                    })
+                })?
+                .ok_or_else(|| anyhow!("{} isn't part of the project", bundle.path))?;
+            let buffer = this
+                .update(&mut cx, |this, cx| this.open_buffer(project_path, cx))?
+                .await?;
+
+            this.update(&mut cx, |this, cx| {
+                if buffer.read(cx).read_only() {
+                    return Err(anyhow!("{} is read-only", bundle.path));
+                }
+                let patch = this.patch_bundles.import(buffer.read(cx), bundle)?;
+                let buffer_id = buffer.read(cx).remote_id();
+                let operations = patch
+                    .operations
+                    .into_iter()
+                    .map(language::Operation::Buffer)
+                    .collect::<Vec<_>>();
+                // Operations that are applied aren't emitted by the buffer,
+                // so they're sent to the collaborators here.
+                for operation in &operations {
+                    this.send_buffer_operation(buffer_id, operation);
+                }
+                let edit_count = operations.len();
+                buffer.update(cx, |buffer, cx| buffer.apply_ops(operations, cx))?;
+                Ok(PatchBundleImport {
+                    buffer,
+                    edit_count,
+                    authors: patch.authors,
+                })
+            })?
+        })
+    }
+
+    /// The logins of the users who edit the project's buffers, by replica.
+    fn replica_authors(&self, cx: &AppContext) -> HashMap<ReplicaId, String> {
+        let user_store = self.user_store.read(cx);
+        let mut authors = HashMap::default();
+        if let Some(user) = user_store.current_user() {
+            authors.insert(self.replica_id(), user.github_login.clone());
+        }
+        for collaborator in self.collaborators.values() {
+            if let Some(user) = user_store.get_cached_user(collaborator.user_id) {
+                authors.insert(collaborator.replica_id, user.github_login.clone());
+            }
+        }
+        authors
+    }
+
     pub fn save_buffers(
         &self,
         buffers: HashSet<Model<Buffer>>,
@@ -1829,6 +2237,16 @@ impl Project {
             .update(cx, |buffer_store, cx| buffer_store.save_buffer(buffer, cx))
     }
 
+    pub fn save_buffer_elevated(
+        &self,
+        buffer: Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        self.buffer_store.update(cx, |buffer_store, cx| {
+            buffer_store.save_buffer_elevated(buffer, cx)
+        })
+    }
+
     pub fn save_buffer_as(
         &mut self,
         buffer: Model<Buffer>,
@@ -1872,10 +2290,18 @@ impl Project {
 
         self.detect_language_for_buffer(buffer, cx);
         self.register_buffer_with_language_servers(buffer, cx);
+        self.check_spelling(buffer, cx);
+        self.check_grammar(buffer, None, cx);
+        self.validate_settings_file(buffer, cx);
         cx.observe_release(buffer, |this, buffer, cx| {
+            this.forget_spell_checked_buffer(buffer.remote_id());
+            this.forget_grammar_checked_buffer(buffer.remote_id());
+            this.forget_validated_settings_buffer(buffer.remote_id());
+            let mut uri = None;
             if let Some(file) = File::from_dyn(buffer.file()) {
                 if file.is_local() {
-                    let uri = lsp::Url::from_file_path(file.abs_path(cx)).unwrap();
+                    let file_uri = lsp::Url::from_file_path(file.abs_path(cx)).unwrap();
+                    let uri = uri.insert(file_uri);
                     for server in this.language_servers_for_buffer(buffer, cx) {
                         server
                             .1
@@ -1888,6 +2314,9 @@ impl Project {
                     }
                 }
             }
+            this.lsp_store.update(cx, |lsp_store, _| {
+                lsp_store.forget_pulled_diagnostics(buffer.remote_id(), uri.as_ref())
+            });
         })
         .detach();
 
@@ -2032,6 +2461,7 @@ impl Project {
         match event {
             BufferStoreEvent::BufferAdded(buffer) => {
                 self.register_buffer(buffer, cx).log_err();
+                self.update_buffer_capability(buffer, cx);
             }
             BufferStoreEvent::BufferChangedFilePath { buffer, old_file } => {
                 if let Some(old_file) = File::from_dyn(old_file.as_ref()) {
@@ -2078,6 +2508,7 @@ impl Project {
                 Event::LanguageServerLog(*server_id, log_type.clone(), string.clone()),
             ),
             LspStoreEvent::RefreshInlayHints => cx.emit(Event::RefreshInlayHints),
+            LspStoreEvent::RefreshCodeLens => cx.emit(Event::RefreshCodeLens),
             LspStoreEvent::LanguageServerPrompt(prompt) => {
                 cx.emit(Event::LanguageServerPrompt(prompt.clone()))
             }
@@ -2164,7 +2595,68 @@ impl Project {
         }
     }
 
+    /// Reloads the environment, and restarts the language servers, of the worktrees
+    /// that became trusted or restricted.
+    fn on_worktree_trust_event(
+        &mut self,
+        _: Model<WorktreeTrust>,
+        event: &WorktreeTrustEvent,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let WorktreeTrustEvent::TrustChanged(worktree_ids) = event else {
+            return;
+        };
+        let worktrees = worktree_ids
+            .iter()
+            .filter_map(|worktree_id| self.worktree_for_id(*worktree_id, cx))
+            .collect::<Vec<_>>();
+        if worktrees.is_empty() {
+            return;
+        }
+        self.environment.update(cx, |environment, _| {
+            for worktree_id in worktree_ids {
+                environment.reload_worktree_environment(*worktree_id);
+            }
+        });
+
+        let languages = self.languages.to_vec();
+        let mut language_servers_to_restart = Vec::new();
+        for (worktree_id, started_lsp_name) in self.lsp_store.read(cx).started_language_servers() {
+            let Some(worktree) = worktrees
+                .iter()
+                .find(|worktree| worktree.read(cx).id() == worktree_id)
+            else {
+                continue;
+            };
+            let language = languages.iter().find(|language| {
+                self.languages
+                    .lsp_adapters(language)
+                    .iter()
+                    .any(|adapter| adapter.name == started_lsp_name)
+            });
+            if let Some(language) = language {
+                language_servers_to_restart.push((worktree.clone(), Arc::clone(language)));
+            }
+        }
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            for (worktree, language) in language_servers_to_restart {
+                lsp_store.restart_language_servers(worktree, language, cx);
+            }
+        });
+        cx.notify();
+    }
+
     fn on_worktree_added(&mut self, worktree: &Model<Worktree>, cx: &mut ModelContext<Self>) {
+        if let Some(worktree_trust) = WorktreeTrust::global(cx) {
+            let worktree = worktree.read(cx);
+            if worktree.is_local() && !self.is_via_collab() {
+                let (worktree_id, abs_path) = (worktree.id(), worktree.abs_path());
+                let is_visible = worktree.is_visible();
+                worktree_trust.update(cx, |worktree_trust, cx| {
+                    worktree_trust.worktree_added(worktree_id, abs_path, is_visible, cx)
+                });
+            }
+        }
         cx.observe(worktree, |_, _, cx| cx.notify()).detach();
         cx.subscribe(worktree, |this, worktree, event, cx| {
             let is_local = worktree.read(cx).is_local();
@@ -2200,6 +2692,11 @@ impl Project {
     }
 
     fn on_worktree_removed(&mut self, id_to_remove: WorktreeId, cx: &mut ModelContext<Self>) {
+        if let Some(worktree_trust) = WorktreeTrust::global(cx) {
+            worktree_trust.update(cx, |worktree_trust, cx| {
+                worktree_trust.worktree_removed(id_to_remove, cx)
+            });
+        }
         if let Some(dev_server_project_id) = self.dev_server_project_id {
             let paths: Vec<String> = self
                 .visible_worktrees(cx)
@@ -2267,6 +2764,25 @@ impl Project {
         self.metadata_changed(cx);
     }
 
+    fn send_buffer_operation(&mut self, buffer_id: BufferId, operation: &language::Operation) {
+        let operation = language::proto::serialize_operation(operation);
+
+        if let Some(ssh) = &self.ssh_session {
+            ssh.send(proto::UpdateBuffer {
+                project_id: 0,
+                buffer_id: buffer_id.to_proto(),
+                operations: vec![operation.clone()],
+            })
+            .ok();
+        }
+
+        self.enqueue_buffer_ordered_message(BufferOrderedMessage::Operation {
+            buffer_id,
+            operation,
+        })
+        .ok();
+    }
+
     fn on_buffer_event(
         &mut self,
         buffer: Model<Buffer>,
@@ -2280,25 +2796,36 @@ impl Project {
             self.request_buffer_diff_recalculation(&buffer, cx);
         }
 
+        if matches!(
+            event,
+            BufferEvent::Edited { .. }
+                | BufferEvent::Reloaded
+                | BufferEvent::Reparsed
+                | BufferEvent::LanguageChanged
+        ) {
+            self.check_spelling(&buffer, cx);
+        }
+        match event {
+            BufferEvent::Edited { .. } => {
+                self.check_grammar(&buffer, Some(GrammarCheckTrigger::Edit), cx)
+            }
+            BufferEvent::Saved => self.check_grammar(&buffer, Some(GrammarCheckTrigger::Save), cx),
+            BufferEvent::Reloaded | BufferEvent::LanguageChanged => {
+                self.check_grammar(&buffer, None, cx)
+            }
+            _ => {}
+        }
+        if matches!(
+            event,
+            BufferEvent::Edited { .. } | BufferEvent::Reloaded | BufferEvent::FileHandleChanged
+        ) {
+            self.validate_settings_file(&buffer, cx);
+        }
+
         let buffer_id = buffer.read(cx).remote_id();
         match event {
             BufferEvent::Operation(operation) => {
-                let operation = language::proto::serialize_operation(operation);
-
-                if let Some(ssh) = &self.ssh_session {
-                    ssh.send(proto::UpdateBuffer {
-                        project_id: 0,
-                        buffer_id: buffer_id.to_proto(),
-                        operations: vec![operation.clone()],
-                    })
-                    .ok();
-                }
-
-                self.enqueue_buffer_ordered_message(BufferOrderedMessage::Operation {
-                    buffer_id,
-                    operation,
-                })
-                .ok();
+                self.send_buffer_operation(buffer_id, operation);
             }
 
             BufferEvent::Reloaded => {
@@ -2790,11 +3317,23 @@ impl Project {
                         .map(|(adapter, lsp)| (adapter.clone(), lsp.clone()))
                         .collect::<Vec<_>>();
 
-                    let primary_adapter = project
-                        .lsp_store
-                        .read(cx)
-                        .primary_language_server_for_buffer(buffer, cx)
-                        .map(|(adapter, lsp)| (adapter.clone(), lsp.clone()));
+                    // Prefer the formatting servers configured in the routing
+                    // settings, falling back to the primary language server.
+                    let running_servers = adapters_and_servers
+                        .iter()
+                        .map(|(adapter, _)| adapter.name.clone())
+                        .collect::<Vec<_>>();
+                    let primary_adapter = language_settings(buffer.language(), buffer.file(), cx)
+                        .language_server_routing
+                        .formatting_server_index(&running_servers)
+                        .map(|ix| adapters_and_servers[ix].clone())
+                        .or_else(|| {
+                            project
+                                .lsp_store
+                                .read(cx)
+                                .primary_language_server_for_buffer(buffer, cx)
+                                .map(|(adapter, lsp)| (adapter.clone(), lsp.clone()))
+                        });
 
                     (primary_adapter, adapters_and_servers)
                 })?;
@@ -3341,12 +3880,15 @@ impl Project {
         position: PointUtf16,
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<Vec<DocumentHighlight>>> {
-        self.request_lsp(
-            buffer.clone(),
-            LanguageServerToQuery::Primary,
-            GetDocumentHighlights { position },
-            cx,
-        )
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.request_lsp_with_schedule(
+                buffer.clone(),
+                LanguageServerToQuery::Primary,
+                GetDocumentHighlights { position },
+                Some(LspRequestSchedule::default()),
+                cx,
+            )
+        })
     }
 
     pub fn document_highlights<T: ToPointUtf16>(
@@ -3409,8 +3951,18 @@ impl Project {
         cx: &mut ModelContext<Self>,
     ) -> Task<Vec<Hover>> {
         let position = position.to_point_utf16(buffer.read(cx));
-        self.lsp_store
-            .update(cx, |lsp_store, cx| lsp_store.hover(buffer, position, cx))
+        let settings_hover = self.settings_hover(buffer, position, cx);
+        let hovers = self
+            .lsp_store
+            .update(cx, |lsp_store, cx| lsp_store.hover(buffer, position, cx));
+        if settings_hover.is_none() {
+            return hovers;
+        }
+        cx.background_executor().spawn(async move {
+            let mut hovers = hovers.await;
+            hovers.splice(0..0, settings_hover);
+            hovers
+        })
     }
 
     pub fn linked_edit(
@@ -3474,8 +4026,19 @@ impl Project {
     ) -> Task<Vec<CodeAction>> {
         let buffer = buffer_handle.read(cx);
         let range = buffer.anchor_before(range.start)..buffer.anchor_before(range.end);
-        self.lsp_store.update(cx, |lsp_store, cx| {
+        let spell_check_actions = self.spell_check_actions(buffer_handle, range.clone(), cx);
+        let grammar_check_actions = self.grammar_check_actions(buffer_handle, range.clone(), cx);
+        let settings_validation_actions =
+            self.settings_validation_actions(buffer_handle, range.clone(), cx);
+        let code_actions = self.lsp_store.update(cx, |lsp_store, cx| {
             lsp_store.code_actions(buffer_handle, range, cx)
+        });
+        cx.background_executor().spawn(async move {
+            let mut actions = code_actions.await;
+            actions.extend(grammar_check_actions);
+            actions.extend(settings_validation_actions);
+            actions.extend(spell_check_actions.await);
+            actions
         })
     }
 
@@ -3486,11 +4049,56 @@ impl Project {
         push_to_history: bool,
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<ProjectTransaction>> {
+        if action.server_id == SPELL_CHECK_SERVER_ID {
+            return self.apply_spell_check_action(buffer_handle, action, push_to_history, cx);
+        }
+        if action.server_id == GRAMMAR_CHECK_SERVER_ID {
+            return self.apply_grammar_check_action(buffer_handle, action, push_to_history, cx);
+        }
+        if action.server_id == SETTINGS_VALIDATION_SERVER_ID {
+            return self.apply_settings_validation_action(
+                buffer_handle,
+                action,
+                push_to_history,
+                cx,
+            );
+        }
         self.lsp_store.update(cx, |lsp_store, cx| {
             lsp_store.apply_code_action(buffer_handle, action, push_to_history, cx)
         })
     }
 
+    pub fn code_lens(
+        &self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CodeLens>>> {
+        self.lsp_store
+            .update(cx, |lsp_store, cx| lsp_store.code_lens(buffer_handle, cx))
+    }
+
+    pub fn resolve_code_lens(
+        &self,
+        buffer_handle: &Model<Buffer>,
+        code_lens: CodeLens,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<CodeLens>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.resolve_code_lens(buffer_handle, code_lens, cx)
+        })
+    }
+
+    pub fn execute_code_lens(
+        &self,
+        buffer_handle: &Model<Buffer>,
+        code_lens: CodeLens,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.execute_code_lens(buffer_handle, code_lens, cx)
+        })
+    }
+
     fn prepare_rename_impl(
         &mut self,
         buffer: Model<Buffer>,
@@ -3563,12 +4171,13 @@ impl Project {
         &mut self,
         buffer_handle: Model<Buffer>,
         range: Range<T>,
+        schedule: LspRequestSchedule,
         cx: &mut ModelContext<Self>,
     ) -> Task<anyhow::Result<Vec<InlayHint>>> {
         let buffer = buffer_handle.read(cx);
         let range = buffer.anchor_before(range.start)..buffer.anchor_before(range.end);
         self.lsp_store.update(cx, |lsp_store, cx| {
-            lsp_store.inlay_hints(buffer_handle, range, cx)
+            lsp_store.inlay_hints(buffer_handle, range, schedule, cx)
         })
     }
 
@@ -4248,6 +4857,58 @@ impl Project {
         self.buffer_store.read(cx).blame_buffer(buffer, version, cx)
     }
 
+    pub fn load_head_text(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Task<Result<Option<String>>> {
+        self.buffer_store.read(cx).load_head_text(buffer, cx)
+    }
+
+    /// Loads the content of a file in the HEAD commit of its repository, which
+    /// may not be text, such as that of an image.
+    pub fn load_head_bytes(
+        &self,
+        project_path: &ProjectPath,
+        cx: &AppContext,
+    ) -> Task<Result<Option<Vec<u8>>>> {
+        let repository = self
+            .worktree_for_id(project_path.worktree_id, cx)
+            .context("worktree not found")
+            .and_then(|worktree| {
+                let Worktree::Local(worktree) = worktree.read(cx) else {
+                    anyhow::bail!("git repositories are only available in local projects");
+                };
+                let snapshot = worktree.snapshot();
+                let (repo_entry, local_repo_entry) = snapshot
+                    .repo_for_path(&project_path.path)
+                    .ok_or(NoRepositoryError {})?;
+                let relative_path = repo_entry.relativize(&snapshot, &project_path.path)?;
+                Ok((local_repo_entry.repo().clone(), relative_path))
+            });
+        cx.background_executor().spawn(async move {
+            let (repo, relative_path) = repository?;
+            Ok(repo.load_head_bytes(&relative_path))
+        })
+    }
+
+    pub fn set_index_text(
+        &self,
+        buffer: &Model<Buffer>,
+        text: String,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.buffer_store.read(cx).set_index_text(buffer, text, cx)
+    }
+
+    pub fn local_repository_for_buffer(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Result<(Arc<dyn GitRepository>, RepoPath)> {
+        BufferStore::local_repository_for_buffer(buffer, cx)
+    }
+
     // RPC message handlers
 
     async fn handle_unshare_project(
@@ -4284,6 +4945,7 @@ impl Project {
             cx.emit(Event::CollaboratorJoined(collaborator.peer_id));
             this.collaborators
                 .insert(collaborator.peer_id, collaborator);
+            this.send_guest_permissions();
             cx.notify();
         })?;
 
@@ -4421,6 +5083,21 @@ impl Project {
         cx: AsyncAppContext,
     ) -> Result<proto::Ack> {
         let buffer_store = this.read_with(&cx, |this, cx| {
+            let is_edit = envelope.payload.operations.iter().any(|operation| {
+                !matches!(
+                    operation.variant,
+                    None | Some(proto::operation::Variant::UpdateSelections(_))
+                )
+            });
+            if is_edit {
+                let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+                let path = this
+                    .buffer_store
+                    .read(cx)
+                    .get(buffer_id)
+                    .and_then(|buffer| Some(File::from_dyn(buffer.read(cx).file())?.path.clone()));
+                this.authorize_guest_write(envelope.original_sender_id, path.as_deref())?;
+            }
             if let Some(ssh) = &this.ssh_session {
                 let mut payload = envelope.payload.clone();
                 payload.project_id = 0;
@@ -4428,11 +5105,133 @@ impl Project {
                     .spawn(ssh.request(payload))
                     .detach_and_log_err(cx);
             }
-            this.buffer_store.clone()
-        })?;
+            anyhow::Ok(this.buffer_store.clone())
+        })??;
         BufferStore::handle_update_buffer(buffer_store, envelope, cx).await
     }
 
+    async fn handle_save_buffer(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::SaveBuffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::BufferSaved> {
+        let buffer_store = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            let path = this
+                .buffer_store
+                .read(cx)
+                .get(buffer_id)
+                .and_then(|buffer| Some(File::from_dyn(buffer.read(cx).file())?.path.clone()));
+            let new_path = envelope
+                .payload
+                .new_path
+                .as_ref()
+                .map(|new_path| PathBuf::from(&new_path.path));
+            this.authorize_guest_write(
+                envelope.original_sender_id,
+                path.as_deref().into_iter().chain(new_path.as_deref()),
+            )?;
+            anyhow::Ok(this.buffer_store.clone())
+        })??;
+        BufferStore::handle_save_buffer(buffer_store, envelope, cx).await
+    }
+
+    async fn handle_create_project_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::CreateProjectEntry>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let worktree_store = this.update(&mut cx, |this, _| {
+            let path = PathBuf::from(&envelope.payload.path);
+            this.authorize_guest_write(envelope.original_sender_id, [path.as_path()])?;
+            anyhow::Ok(this.worktree_store.clone())
+        })??;
+        WorktreeStore::handle_create_project_entry(worktree_store, envelope, cx).await
+    }
+
+    async fn handle_rename_project_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::RenameProjectEntry>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let worktree_store = this.update(&mut cx, |this, cx| {
+            let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
+            let path = this
+                .path_for_entry(entry_id, cx)
+                .context("entry not found")?;
+            let new_path = PathBuf::from(&envelope.payload.new_path);
+            this.authorize_guest_write(
+                envelope.original_sender_id,
+                [path.path.as_ref(), new_path.as_path()],
+            )?;
+            anyhow::Ok(this.worktree_store.clone())
+        })??;
+        WorktreeStore::handle_rename_project_entry(worktree_store, envelope, cx).await
+    }
+
+    async fn handle_copy_project_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::CopyProjectEntry>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let worktree_store = this.update(&mut cx, |this, _| {
+            let new_path = PathBuf::from(&envelope.payload.new_path);
+            this.authorize_guest_write(envelope.original_sender_id, [new_path.as_path()])?;
+            anyhow::Ok(this.worktree_store.clone())
+        })??;
+        WorktreeStore::handle_copy_project_entry(worktree_store, envelope, cx).await
+    }
+
+    async fn handle_delete_project_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::DeleteProjectEntry>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let worktree_store = this.update(&mut cx, |this, cx| {
+            let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
+            let path = this
+                .path_for_entry(entry_id, cx)
+                .context("entry not found")?;
+            this.authorize_guest_write(envelope.original_sender_id, [path.path.as_ref()])?;
+            anyhow::Ok(this.worktree_store.clone())
+        })??;
+        WorktreeStore::handle_delete_project_entry(worktree_store, envelope, cx).await
+    }
+
+    async fn handle_update_guest_permissions(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UpdateGuestPermissions>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            this.guest_permissions = GuestPermissions::from_proto(envelope.payload);
+            if !this.can_request_edit_access() {
+                this.edit_access_requested = false;
+            }
+            this.update_buffer_capabilities(cx);
+            cx.notify();
+        })
+    }
+
+    async fn handle_request_edit_access(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::RequestEditAccess>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let sender_id = envelope.original_sender_id()?;
+        this.update(&mut cx, |this, cx| {
+            let user_id = this
+                .collaborators
+                .get(&sender_id)
+                .map(|collaborator| collaborator.user_id)
+                .context("unknown collaborator")?;
+            if !this.guest_permissions.can_edit(user_id) {
+                cx.emit(Event::EditAccessRequested { user_id });
+            }
+            Ok(proto::Ack {})
+        })?
+    }
+
     fn retain_remotely_created_buffers(
         &mut self,
         cx: &mut ModelContext<Self>,
@@ -4496,7 +5295,10 @@ impl Project {
         mut cx: AsyncAppContext,
     ) -> Result<proto::SynchronizeBuffersResponse> {
         let response = this.update(&mut cx, |this, cx| {
-            let client = this.client.clone();
+            let client: AnyProtoClient = match &this.ssh_session {
+                Some(ssh) => ssh.clone().into(),
+                None => this.client.clone().into(),
+            };
             this.buffer_store.update(cx, |this, cx| {
                 this.handle_synchronize_buffers(envelope, cx, client)
             })
@@ -4653,6 +5455,23 @@ impl Project {
                         }),
                     }),
                     tags: template.tags,
+                    problem_matcher: template
+                        .problem_matcher
+                        .and_then(|matcher| serde_json::to_string(&matcher).log_err()),
+                    depends_on: template.depends_on,
+                    depends_in_parallel: template.depends_order == DependsOrder::Parallel,
+                    is_background: template.is_background,
+                    ready_pattern: template.ready_pattern,
+                    inputs: template
+                        .inputs
+                        .into_iter()
+                        .map(|input| proto::TaskInput {
+                            id: input.id,
+                            description: input.description,
+                            options: input.options,
+                            default: input.default,
+                        })
+                        .collect(),
                 });
                 proto::TemplatePair { kind, template }
             })
@@ -5223,6 +6042,27 @@ impl Project {
                         hide,
                         shell,
                         tags: proto_template.tags,
+                        problem_matcher: proto_template
+                            .problem_matcher
+                            .and_then(|matcher| serde_json::from_str(&matcher).log_err()),
+                        depends_on: proto_template.depends_on,
+                        depends_order: if proto_template.depends_in_parallel {
+                            DependsOrder::Parallel
+                        } else {
+                            DependsOrder::Sequence
+                        },
+                        is_background: proto_template.is_background,
+                        ready_pattern: proto_template.ready_pattern,
+                        inputs: proto_template
+                            .inputs
+                            .into_iter()
+                            .map(|input| TaskInput {
+                                id: input.id,
+                                description: input.description,
+                                options: input.options,
+                                default: input.default,
+                            })
+                            .collect(),
                     };
                     Some((task_source_kind, task_template))
                 })
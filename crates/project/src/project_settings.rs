@@ -3,7 +3,7 @@ use gpui::AppContext;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
-use std::{sync::Arc, time::Duration};
+use std::{path::Path, sync::Arc, time::Duration};
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ProjectSettings {
@@ -17,6 +17,22 @@ pub struct ProjectSettings {
     #[serde(default)]
     pub lsp: HashMap<Arc<str>, LspSettings>,
 
+    /// Language servers defined directly in settings, keyed by the name of the
+    /// language server.
+    ///
+    /// These servers are started like any other language server for the
+    /// languages they list, and can be configured via the `lsp` settings.
+    /// Default: {}
+    #[serde(default)]
+    pub custom_language_servers: HashMap<Arc<str>, CustomLanguageServerSettings>,
+
+    /// JSON schemas to validate JSON and YAML files against, in addition to
+    /// those that files declare with `$schema`.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub json_schemas: Vec<JsonSchemaMapping>,
+
     /// Configuration for Git-related features
     #[serde(default)]
     pub git: GitSettings,
@@ -28,6 +44,14 @@ pub struct ProjectSettings {
     /// Configuration for session-related features
     #[serde(default)]
     pub session: SessionSettings,
+
+    /// Configuration for what guests can do in the projects you share
+    #[serde(default)]
+    pub collaboration: CollaborationSettings,
+
+    /// Configuration for the index that speeds up project search
+    #[serde(default)]
+    pub search_index: SearchIndexSettings,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -126,6 +150,62 @@ pub struct LspSettings {
     pub settings: Option<serde_json::Value>,
 }
 
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct CustomLanguageServerSettings {
+    /// The command used to start the language server.
+    ///
+    /// Relative commands are looked up in the worktree's `$PATH`.
+    pub command: String,
+    /// The arguments to pass to the language server.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub arguments: Vec<String>,
+    /// Extra environment variables to set for the language server.
+    ///
+    /// Default: {}
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// The names of the languages this language server handles.
+    pub languages: Vec<Arc<str>>,
+    /// Globs of the paths, relative to the worktree root, that the language
+    /// server should be attached to. All files of the listed languages are
+    /// attached when empty.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub file_globs: Vec<String>,
+    /// File or directory names, one of which must exist at the root of a
+    /// worktree for the language server to be started in it. The language
+    /// server is started in every worktree when empty.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub root_markers: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct JsonSchemaMapping {
+    /// Globs of the paths, relative to the worktree root, of the files that
+    /// are validated against the schema.
+    pub file_match: Vec<String>,
+    /// The URL of the schema, or its path relative to the worktree root.
+    pub url: String,
+}
+
+impl JsonSchemaMapping {
+    /// Returns the URL of the schema, resolving paths against the worktree
+    /// root.
+    pub fn resolved_url(&self, worktree_root: &Path) -> String {
+        if self.url.contains("://") {
+            return self.url.clone();
+        }
+        lsp::Url::from_file_path(worktree_root.join(&self.url))
+            .map(|url| url.to_string())
+            .unwrap_or_else(|_| self.url.clone())
+    }
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SessionSettings {
     /// Whether or not to restore unsaved buffers on restart.
@@ -145,6 +225,60 @@ impl Default for SessionSettings {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CollaborationSettings {
+    /// Whether guests can edit the projects you share, unless you allow or
+    /// forbid them individually.
+    ///
+    /// Default: true
+    #[serde(default = "true_value")]
+    pub guests_can_edit: bool,
+    /// Globs of the paths, relative to the worktree root, that guests can't
+    /// write to even when they can edit the project.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub read_only_paths: Vec<String>,
+}
+
+impl Default for CollaborationSettings {
+    fn default() -> Self {
+        Self {
+            guests_can_edit: true,
+            read_only_paths: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SearchIndexSettings {
+    /// Whether to index the trigrams of the files in local worktrees, so that
+    /// project search only reads the files that may contain the query's text.
+    ///
+    /// Default: true
+    #[serde(default = "true_value")]
+    pub enabled: bool,
+    /// The most memory, in megabytes, that the index of a project can use. Files
+    /// that don't fit are searched without the index.
+    ///
+    /// Default: 256
+    #[serde(default = "default_search_index_max_memory_mb")]
+    pub max_memory_mb: usize,
+}
+
+fn default_search_index_max_memory_mb() -> usize {
+    256
+}
+
+impl Default for SearchIndexSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_memory_mb: default_search_index_max_memory_mb(),
+        }
+    }
+}
+
 impl Settings for ProjectSettings {
     const KEY: Option<&'static str> = None;
 
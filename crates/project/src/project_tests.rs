@@ -1,10 +1,16 @@
-use crate::{Event, *};
+use crate::{
+    worktree_trust::{Trust, WorktreeTrust},
+    Event, *,
+};
 use fs::FakeFs;
 use futures::{future, StreamExt};
 use gpui::{AppContext, SemanticVersion, UpdateGlobal};
 use http_client::Url;
 use language::{
-    language_settings::{AllLanguageSettings, LanguageSettingsContent},
+    language_settings::{
+        AllLanguageSettings, ExternalChangesSettings, LanguageSettingsContent,
+        SpellCheckSettingsContent,
+    },
     tree_sitter_rust, tree_sitter_typescript, Diagnostic, DiagnosticSet, FakeLspAdapter,
     LanguageConfig, LanguageMatcher, LineEnding, OffsetRangeExt, Point, ToPoint,
 };
@@ -16,7 +22,7 @@ use serde_json::json;
 use std::os;
 
 use std::{mem, ops::Range, task::Poll};
-use task::{ResolvedTask, TaskContext, TaskTemplate, TaskTemplates};
+use task::{ProblemMatcher, ResolvedTask, TaskContext, TaskId, TaskTemplate, TaskTemplates};
 use unindent::Unindent as _;
 use util::{assert_set_eq, paths::PathMatcher, test::temp_tree, TryFutureExt as _};
 
@@ -296,6 +302,64 @@ async fn test_managing_project_specific_settings(cx: &mut gpui::TestAppContext)
     );
 }
 
+#[gpui::test]
+async fn test_restricted_worktree_tasks(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-root",
+        json!({
+            ".zed": {
+                "tasks.json": r#"[{ "label": "build", "command": "make" }]"#,
+            },
+            "a.rs": "",
+        }),
+    )
+    .await;
+    cx.update(|cx| WorktreeTrust::set_global(fs.clone(), Default::default(), cx));
+    let worktree_trust = cx.update(|cx| WorktreeTrust::global(cx).unwrap());
+
+    let project = Project::test(fs.clone(), ["/the-root".as_ref()], cx).await;
+    cx.executor().run_until_parked();
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+    let task_labels = |cx: &mut gpui::TestAppContext| {
+        let tasks =
+            cx.update(|cx| get_all_tasks(&project, Some(worktree_id), &TaskContext::default(), cx));
+        async move {
+            tasks
+                .await
+                .into_iter()
+                .map(|(_, task)| task.resolved_label)
+                .collect::<Vec<_>>()
+        }
+    };
+
+    // Folders whose trust wasn't decided are restricted.
+    assert_eq!(
+        worktree_trust.read_with(cx, |worktree_trust, _| worktree_trust
+            .worktree_trust(worktree_id)),
+        None
+    );
+    assert!(cx.update(|cx| WorktreeTrust::is_restricted(worktree_id, cx)));
+    assert_eq!(task_labels(cx).await, Vec::<String>::new());
+
+    worktree_trust.update(cx, |worktree_trust, cx| {
+        worktree_trust.set_trust(Path::new("/the-root"), Trust::Trusted, cx)
+    });
+    assert!(!cx.update(|cx| WorktreeTrust::is_restricted(worktree_id, cx)));
+    assert_eq!(task_labels(cx).await, vec!["build".to_string()]);
+
+    // Restricting a folder containing the worktree restricts it again.
+    worktree_trust.update(cx, |worktree_trust, cx| {
+        worktree_trust.set_trust(Path::new("/"), Trust::Restricted, cx)
+    });
+    assert!(cx.update(|cx| WorktreeTrust::is_restricted(worktree_id, cx)));
+    assert_eq!(task_labels(cx).await, Vec::<String>::new());
+}
+
 #[gpui::test]
 async fn test_managing_language_servers(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -965,6 +1029,95 @@ async fn test_single_file_worktrees_diagnostics(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_diagnostic_tags(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "let a = old(b);",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir/a.rs".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    project.update(cx, |project, cx| {
+        project
+            .update_diagnostics(
+                LanguageServerId(0),
+                lsp::PublishDiagnosticsParams {
+                    uri: Url::from_file_path("/dir/a.rs").unwrap(),
+                    version: None,
+                    diagnostics: vec![
+                        lsp::Diagnostic {
+                            range: lsp::Range::new(
+                                lsp::Position::new(0, 4),
+                                lsp::Position::new(0, 5),
+                            ),
+                            severity: Some(DiagnosticSeverity::HINT),
+                            message: "unused variable".to_string(),
+                            tags: Some(vec![lsp::DiagnosticTag::UNNECESSARY]),
+                            ..Default::default()
+                        },
+                        lsp::Diagnostic {
+                            range: lsp::Range::new(
+                                lsp::Position::new(0, 8),
+                                lsp::Position::new(0, 11),
+                            ),
+                            severity: Some(DiagnosticSeverity::HINT),
+                            message: "deprecated function".to_string(),
+                            tags: Some(vec![lsp::DiagnosticTag::DEPRECATED]),
+                            ..Default::default()
+                        },
+                    ],
+                },
+                &[],
+                cx,
+            )
+            .unwrap();
+    });
+
+    buffer.update(cx, |buffer, _| {
+        let mut chunks: Vec<(String, bool, bool)> = Vec::new();
+        for chunk in buffer.snapshot().chunks(0..buffer.len(), true) {
+            match chunks.last_mut() {
+                Some(last) if (last.1, last.2) == (chunk.is_unnecessary, chunk.is_deprecated) => {
+                    last.0.push_str(chunk.text)
+                }
+                _ => chunks.push((
+                    chunk.text.to_string(),
+                    chunk.is_unnecessary,
+                    chunk.is_deprecated,
+                )),
+            }
+        }
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|(text, is_unnecessary, is_deprecated)| (
+                    text.as_str(),
+                    *is_unnecessary,
+                    *is_deprecated
+                ))
+                .collect::<Vec<_>>(),
+            &[
+                ("let ", false, false),
+                ("a", true, false),
+                (" = ", false, false),
+                ("old", false, true),
+                ("(b);", false, false),
+            ]
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_omitted_diagnostics(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -1495,6 +1648,128 @@ async fn test_cancel_language_server_work(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_language_server_work_history(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "" })).await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            name: "the-language-server",
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server
+        .start_progress_with(
+            "indexing",
+            lsp::WorkDoneProgressBegin {
+                title: "Indexing".into(),
+                cancellable: Some(true),
+                ..Default::default()
+            },
+        )
+        .await;
+    fake_server
+        .start_progress_with(
+            "building",
+            lsp::WorkDoneProgressBegin {
+                title: "Building".into(),
+                message: Some("1/3".into()),
+                ..Default::default()
+            },
+        )
+        .await;
+    cx.executor().run_until_parked();
+
+    // Each token's work is pending on its own.
+    project.read_with(cx, |project, cx| {
+        let (_, status) = project.language_server_statuses(cx).next().unwrap();
+        let pending_work = status
+            .pending_work
+            .iter()
+            .map(|(token, progress)| {
+                (
+                    token.as_str(),
+                    progress.title.as_deref(),
+                    progress.message.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pending_work,
+            [
+                ("building", Some("Building"), Some("1/3")),
+                ("indexing", Some("Indexing"), None),
+            ]
+        );
+        assert!(status.finished_work.is_empty());
+    });
+
+    fake_server.end_progress("building");
+    cx.executor().advance_clock(Duration::from_secs(1));
+    project.update(cx, |project, cx| {
+        project.cancel_language_server_work_for_buffers([buffer.clone()], cx)
+    });
+    fake_server
+        .receive_notification::<lsp::notification::WorkDoneProgressCancel>()
+        .await;
+    fake_server.end_progress("indexing");
+    cx.executor().run_until_parked();
+
+    // Finished work is kept, oldest first, remembering whether it was cancelled.
+    project.read_with(cx, |project, cx| {
+        let (_, status) = project.language_server_statuses(cx).next().unwrap();
+        assert!(status.pending_work.is_empty());
+        let finished_work = status
+            .finished_work
+            .iter()
+            .map(|work| {
+                (
+                    work.token.as_str(),
+                    work.title.as_deref(),
+                    work.was_cancelled,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            finished_work,
+            [
+                ("building", Some("Building"), false),
+                ("indexing", Some("Indexing"), true),
+            ]
+        );
+        assert!(status.finished_work[0].finished_at < status.finished_work[1].finished_at);
+    });
+
+    // Only the most recently finished work is kept.
+    for ix in 0..30 {
+        let token = format!("task-{ix}");
+        fake_server.start_progress(token.clone()).await;
+        fake_server.end_progress(token);
+    }
+    cx.executor().run_until_parked();
+    project.read_with(cx, |project, cx| {
+        let (_, status) = project.language_server_statuses(cx).next().unwrap();
+        assert_eq!(status.finished_work.len(), 20);
+        assert_eq!(status.finished_work.front().unwrap().token, "task-10");
+        assert_eq!(status.finished_work.back().unwrap().token, "task-29");
+    });
+}
+
 #[gpui::test]
 async fn test_toggling_enable_language_server(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2018,6 +2293,139 @@ async fn test_diagnostics_from_multiple_language_servers(cx: &mut gpui::TestAppC
     });
 }
 
+#[gpui::test]
+async fn test_pull_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "fn a() { A }" }))
+        .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions::default(),
+                )),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    let previous_result_ids = Arc::new(Mutex::new(Vec::new()));
+    fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>({
+        let previous_result_ids = previous_result_ids.clone();
+        move |params, _| {
+            previous_result_ids
+                .lock()
+                .push(params.previous_result_id.clone());
+            async move {
+                let report = match params.previous_result_id {
+                    None => lsp::DocumentDiagnosticReport::Full(
+                        lsp::RelatedFullDocumentDiagnosticReport {
+                            related_documents: None,
+                            full_document_diagnostic_report: lsp::FullDocumentDiagnosticReport {
+                                result_id: Some("1".into()),
+                                items: vec![lsp::Diagnostic {
+                                    range: lsp::Range::new(
+                                        lsp::Position::new(0, 9),
+                                        lsp::Position::new(0, 10),
+                                    ),
+                                    severity: Some(lsp::DiagnosticSeverity::ERROR),
+                                    message: "undefined variable 'A'".to_string(),
+                                    ..Default::default()
+                                }],
+                            },
+                        },
+                    ),
+                    Some(result_id) => lsp::DocumentDiagnosticReport::Unchanged(
+                        lsp::RelatedUnchangedDocumentDiagnosticReport {
+                            related_documents: None,
+                            unchanged_document_diagnostic_report:
+                                lsp::UnchangedDocumentDiagnosticReport { result_id },
+                        },
+                    ),
+                };
+                Ok(lsp::DocumentDiagnosticReportResult::Report(report))
+            }
+        }
+    });
+
+    cx.executor()
+        .advance_clock(std::time::Duration::from_millis(500));
+    cx.executor().run_until_parked();
+
+    let expected_diagnostics = vec![DiagnosticEntry {
+        range: Point::new(0, 9)..Point::new(0, 10),
+        diagnostic: Diagnostic {
+            severity: lsp::DiagnosticSeverity::ERROR,
+            message: "undefined variable 'A'".to_string(),
+            group_id: 0,
+            is_primary: true,
+            ..Default::default()
+        },
+    }];
+    buffer.update(cx, |buffer, _| {
+        let diagnostics = buffer
+            .snapshot()
+            .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+            .collect::<Vec<_>>();
+        assert_eq!(diagnostics, expected_diagnostics);
+    });
+
+    // Edits cause diagnostics to be pulled again, and an unchanged report
+    // leaves the previously pulled diagnostics in place.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    cx.executor()
+        .advance_clock(std::time::Duration::from_millis(500));
+    cx.executor().run_until_parked();
+
+    assert_eq!(
+        *previous_result_ids.lock(),
+        vec![None, Some("1".to_string())]
+    );
+    buffer.update(cx, |buffer, _| {
+        let diagnostics = buffer
+            .snapshot()
+            .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            &[DiagnosticEntry {
+                range: Point::new(1, 9)..Point::new(1, 10),
+                ..expected_diagnostics[0].clone()
+            }]
+        );
+    });
+
+    // Releasing the buffer forgets its result id, so that reopening it pulls
+    // its full diagnostics again.
+    drop(buffer);
+    cx.run_until_parked();
+    let _buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    cx.executor()
+        .advance_clock(std::time::Duration::from_millis(500));
+    cx.executor().run_until_parked();
+    assert_eq!(
+        *previous_result_ids.lock(),
+        vec![None, Some("1".to_string()), None]
+    );
+}
+
 #[gpui::test]
 async fn test_edits_from_lsp2_with_past_version(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2784,22 +3192,129 @@ async fn test_apply_code_actions_with_commands(cx: &mut gpui::TestAppContext) {
     });
 }
 
-#[gpui::test(iterations = 10)]
-async fn test_save_file(cx: &mut gpui::TestAppContext) {
+#[gpui::test]
+async fn test_code_lens(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         "/dir",
         json!({
-            "file1": "the old contents",
+            "a.ts": "function a() {}\nfunction b() {}",
         }),
     )
     .await;
 
-    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                execute_command_provider: Some(lsp::ExecuteCommandOptions {
+                    commands: vec!["_the/command".into()],
+                    ..Default::default()
+                }),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
     let buffer = project
-        .update(cx, |p, cx| p.open_local_buffer("/dir/file1", cx))
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.ts", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_language_servers.next().await.unwrap();
+
+    let code_lens = project.update(cx, |project, cx| project.code_lens(&buffer, cx));
+    fake_server
+        .handle_request::<lsp::request::CodeLensRequest, _, _>(|params, _| async move {
+            assert_eq!(
+                params.text_document.uri,
+                lsp::Url::from_file_path("/dir/a.ts").unwrap()
+            );
+            Ok(Some(vec![
+                lsp::CodeLens {
+                    range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
+                    command: Some(lsp::Command {
+                        title: "2 references".into(),
+                        command: "_the/command".into(),
+                        arguments: None,
+                    }),
+                    data: None,
+                },
+                lsp::CodeLens {
+                    range: lsp::Range::new(lsp::Position::new(1, 9), lsp::Position::new(1, 10)),
+                    command: None,
+                    data: Some(json!({ "function": "b" })),
+                },
+            ]))
+        })
+        .next()
+        .await;
+    let code_lens = code_lens.await.unwrap();
+    assert_eq!(code_lens.len(), 2);
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            code_lens[1].range.to_point(buffer),
+            Point::new(1, 9)..Point::new(1, 10)
+        );
+    });
+
+    // Only code lenses without a command are resolved.
+    let resolved = project.update(cx, |project, cx| {
+        project.resolve_code_lens(&buffer, code_lens[1].clone(), cx)
+    });
+    fake_server
+        .handle_request::<lsp::request::CodeLensResolve, _, _>(|mut lens, _| async move {
+            assert_eq!(lens.data, Some(json!({ "function": "b" })));
+            lens.command = Some(lsp::Command {
+                title: "0 references".into(),
+                command: "_the/command".into(),
+                arguments: None,
+            });
+            Ok(lens)
+        })
+        .next()
+        .await;
+    let resolved = resolved.await.unwrap();
+    assert_eq!(resolved.lsp_lens.command.unwrap().title, "0 references");
+
+    let executed = project.update(cx, |project, cx| {
+        project.execute_code_lens(&buffer, code_lens[0].clone(), cx)
+    });
+    fake_server
+        .handle_request::<lsp::request::ExecuteCommand, _, _>(|params, _| async move {
+            assert_eq!(params.command, "_the/command");
+            Ok(None)
+        })
+        .next()
+        .await;
+    executed.await.unwrap();
+}
+
+#[gpui::test(iterations = 10)]
+async fn test_save_file(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "file1": "the old contents",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/file1", cx))
         .await
         .unwrap();
     buffer.update(cx, |buffer, cx| {
@@ -3264,6 +3779,361 @@ async fn test_buffer_deduping(cx: &mut gpui::TestAppContext) {
     assert_eq!(buffer_a_3.entity_id(), buffer_a_id);
 }
 
+#[gpui::test]
+async fn test_task_problems_as_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "src": {
+                "main.rs": "fn main() {\n    let y = x;\n}\n",
+            },
+        }),
+    )
+    .await;
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+
+    let mut scanner = ProblemMatcher::Preset("$rustc".to_string())
+        .scanner()
+        .unwrap();
+    let problems = scanner.scan([
+        "error[E0425]: cannot find value `x` in this scope",
+        " --> src/main.rs:2:13",
+        "warning: unused variable: `y`",
+        " --> src/main.rs:2:9",
+    ]);
+    project.update(cx, |project, cx| {
+        let diagnostics = terminals::task_diagnostics(problems, Path::new("/dir"), "rustc");
+        project.set_task_diagnostics(TaskId("cargo build".to_string()), diagnostics, cx);
+        assert_eq!(
+            project.diagnostic_summaries(false, cx).collect::<Vec<_>>(),
+            vec![(
+                ProjectPath {
+                    worktree_id,
+                    path: Arc::from(Path::new("src/main.rs")),
+                },
+                terminals::TASK_DIAGNOSTICS_SERVER_ID,
+                DiagnosticSummary {
+                    error_count: 1,
+                    warning_count: 1,
+                }
+            )]
+        );
+
+        // Rerunning the task without problems clears its diagnostics.
+        project.set_task_diagnostics(TaskId("cargo build".to_string()), HashMap::default(), cx);
+        assert_eq!(project.diagnostic_summaries(false, cx).next(), None);
+    });
+}
+
+#[gpui::test]
+async fn test_spell_checking(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.defaults.spell_check = Some(SpellCheckSettingsContent {
+                    enabled: Some(true),
+                    scopes: Some(vec!["text".to_string()]),
+                    dictionaries: None,
+                });
+            });
+        })
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        paths::dictionaries_dir(),
+        json!({
+            "en_US.aff": "SET UTF-8\nSFX S Y 1\nSFX S 0 s .\n",
+            "en_US.dic": "3\nhello\nword/S\nwide\n",
+        }),
+    )
+    .await;
+    fs.insert_tree("/dir", json!({ "notes.txt": "Helo wide words\n" }))
+        .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/notes.txt", cx)
+        })
+        .await
+        .unwrap();
+    cx.executor()
+        .advance_clock(spell_checking::SPELL_CHECK_DEBOUNCE_TIMEOUT);
+    cx.executor().run_until_parked();
+
+    let misspellings = |cx: &mut gpui::TestAppContext| {
+        buffer.read_with(cx, |buffer, _| {
+            buffer
+                .snapshot()
+                .diagnostics_in_range::<_, usize>(0..buffer.len(), false)
+                .map(|entry| (entry.range, entry.diagnostic.message))
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(
+        misspellings(cx),
+        vec![(0..4, "Unknown word \"Helo\"".to_string())]
+    );
+
+    let actions = project
+        .update(cx, |project, cx| project.code_actions(&buffer, 0..0, cx))
+        .await;
+    assert_eq!(
+        actions
+            .iter()
+            .map(|action| action.lsp_action.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Change to \"Hello\"", "Add \"Helo\" to dictionary"]
+    );
+    project
+        .update(cx, |project, cx| {
+            project.apply_code_action(buffer.clone(), actions[0].clone(), true, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| buffer.text()),
+        "Hello wide words\n"
+    );
+    cx.executor()
+        .advance_clock(spell_checking::SPELL_CHECK_DEBOUNCE_TIMEOUT);
+    cx.executor().run_until_parked();
+    assert_eq!(misspellings(cx), Vec::new());
+
+    // Words added to the personal dictionary aren't reported anymore.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..5, "Zed")], None, cx));
+    cx.executor()
+        .advance_clock(spell_checking::SPELL_CHECK_DEBOUNCE_TIMEOUT);
+    cx.executor().run_until_parked();
+    assert_eq!(
+        misspellings(cx),
+        vec![(0..3, "Unknown word \"Zed\"".to_string())]
+    );
+    let actions = project
+        .update(cx, |project, cx| project.code_actions(&buffer, 0..0, cx))
+        .await;
+    let add_to_dictionary = actions.last().unwrap().clone();
+    project
+        .update(cx, |project, cx| {
+            project.apply_code_action(buffer.clone(), add_to_dictionary, true, cx)
+        })
+        .await
+        .unwrap();
+    cx.executor()
+        .advance_clock(spell_checking::SPELL_CHECK_DEBOUNCE_TIMEOUT);
+    cx.executor().run_until_parked();
+    assert_eq!(misspellings(cx), Vec::new());
+    assert_eq!(
+        fs.load(paths::personal_dictionary_file()).await.unwrap(),
+        "Zed\n"
+    );
+}
+
+#[gpui::test]
+async fn test_settings_validation(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        paths::config_dir(),
+        json!({ "settings.json": r#"{ "tab_sise": 4, "hard_tabs": "true" }"# }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [paths::config_dir().as_path()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(paths::settings_file(), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor()
+        .advance_clock(settings_validation::SETTINGS_VALIDATION_DEBOUNCE_TIMEOUT);
+    cx.executor().run_until_parked();
+
+    let problems = |cx: &mut gpui::TestAppContext| {
+        buffer.read_with(cx, |buffer, _| {
+            buffer
+                .snapshot()
+                .diagnostics_in_range::<_, usize>(0..buffer.len(), false)
+                .map(|entry| (entry.range, entry.diagnostic.message))
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(
+        problems(cx),
+        vec![
+            (2..12, "Unknown setting \"tab_sise\"".to_string()),
+            (30..36, "Expected a boolean, found a string".to_string()),
+        ]
+    );
+
+    let actions = project
+        .update(cx, |project, cx| project.code_actions(&buffer, 2..2, cx))
+        .await;
+    assert_eq!(
+        actions
+            .iter()
+            .map(|action| action.lsp_action.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Rename to \"tab_size\""]
+    );
+    project
+        .update(cx, |project, cx| {
+            project.apply_code_action(buffer.clone(), actions[0].clone(), true, cx)
+        })
+        .await
+        .unwrap();
+    cx.executor()
+        .advance_clock(settings_validation::SETTINGS_VALIDATION_DEBOUNCE_TIMEOUT);
+    cx.executor().run_until_parked();
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| buffer.text()),
+        r#"{ "tab_size": 4, "hard_tabs": "true" }"#
+    );
+    assert_eq!(
+        problems(cx),
+        vec![(30..36, "Expected a boolean, found a string".to_string())]
+    );
+}
+
+#[gpui::test]
+async fn test_breakpoints_follow_edits(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "one\ntwo\nthree\nfour\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let breakpoint_store = project.update(cx, |project, _| project.breakpoint_store().clone());
+    let path = buffer.update(cx, |buffer, cx| buffer.project_path(cx).unwrap());
+
+    breakpoint_store.update(cx, |store, cx| {
+        store.toggle(&buffer, 1, cx);
+        store.toggle(&buffer, 3, cx);
+        store.toggle(&buffer, 2, cx);
+        store.toggle(&buffer, 2, cx);
+    });
+    breakpoint_store.update(cx, |store, _| assert_eq!(store.rows(&path), &[1, 3]));
+
+    // Breakpoints move with the lines they were set on.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+    breakpoint_store.update(cx, |store, _| assert_eq!(store.rows(&path), &[2, 4]));
+
+    // Breakpoints on deleted lines are merged.
+    buffer.update(cx, |buffer, cx| {
+        let range =
+            buffer.point_to_offset(Point::new(2, 0))..buffer.point_to_offset(Point::new(4, 0));
+        buffer.edit([(range, "")], None, cx)
+    });
+    breakpoint_store.update(cx, |store, _| assert_eq!(store.rows(&path), &[2]));
+
+    // Breakpoints restored for a closed file are applied once it's opened.
+    drop(buffer);
+    cx.run_until_parked();
+    let other_path = ProjectPath {
+        worktree_id: path.worktree_id,
+        path: Path::new("b.rs").into(),
+    };
+    breakpoint_store.update(cx, |store, cx| {
+        store.set_rows(other_path.clone(), vec![5, 0], cx)
+    });
+    assert_eq!(
+        breakpoint_store.update(cx, |store, _| store
+            .breakpoints()
+            .map(|(path, rows)| (path.path.to_path_buf(), rows.to_vec()))
+            .collect::<Vec<_>>()),
+        vec![
+            (PathBuf::from("a.rs"), vec![2]),
+            (PathBuf::from("b.rs"), vec![0, 5]),
+        ]
+    );
+}
+
+#[gpui::test]
+async fn test_review_threads_follow_edits(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "one\ntwo\nthree\nfour\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let review_store = project.update(cx, |project, _| project.review_store().clone());
+    let path = buffer.update(cx, |buffer, cx| buffer.project_path(cx).unwrap());
+    let comment = |body: &str| ReviewComment {
+        author: Some("user".to_string()),
+        body: body.to_string(),
+        created_at: 0,
+    };
+
+    let thread_id = review_store
+        .update(cx, |store, cx| {
+            store.create_thread(
+                &buffer,
+                Point::new(1, 0)..Point::new(2, 5),
+                comment("Rename these"),
+                cx,
+            )
+        })
+        .unwrap();
+    review_store.update(cx, |store, cx| {
+        store.add_comment(thread_id, comment("Done"), cx);
+        store.set_resolved(thread_id, true, cx);
+    });
+
+    // Threads move with the text they were created on.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+    review_store.update(cx, |store, _| {
+        let thread = store.thread(thread_id).unwrap();
+        assert_eq!(thread.range, Point::new(2, 0)..Point::new(3, 5));
+        assert_eq!(thread.comments.len(), 2);
+        assert!(thread.resolved);
+    });
+
+    // Threads restored for a file are anchored once it's open.
+    let serialized = review_store.update(cx, |store, cx| {
+        let serialized = store.serialize_threads(&path);
+        store.delete_thread(thread_id, cx);
+        store.restore_threads(path.clone(), serialized.clone(), cx);
+        serialized
+    });
+    assert_eq!(serialized.len(), 1);
+    assert_eq!(serialized[0].start, (2, 0));
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    review_store.update(cx, |store, _| {
+        let threads = store.threads();
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].range, Point::new(3, 0)..Point::new(4, 5));
+        assert_eq!(threads[0].comments, serialized[0].comments);
+    });
+}
+
 #[gpui::test]
 async fn test_buffer_is_dirty(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -3486,6 +4356,52 @@ async fn test_buffer_file_changes_on_disk(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_buffer_file_changes_on_disk_without_auto_reload(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<ExternalChangesSettings>(cx, |settings| {
+                settings.auto_reload = Some(false);
+            });
+        })
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "the-file": "one\ntwo\n" }))
+        .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/the-file", cx))
+        .await
+        .unwrap();
+
+    fs.save(
+        "/dir/the-file".as_ref(),
+        &"one\nTWO\n".into(),
+        LineEnding::Unix,
+    )
+    .await
+    .unwrap();
+
+    // The buffer isn't reloaded, but is marked as conflicting with its file.
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "one\ntwo\n");
+        assert!(buffer.is_dirty());
+        assert!(buffer.has_conflict());
+    });
+
+    // Merging the file's changes resolves the conflict.
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(4..7, "TWO")], None, cx);
+        let mtime = buffer.file().unwrap().mtime();
+        buffer.did_merge_file_changes(true, mtime, cx);
+        assert!(!buffer.is_dirty());
+        assert!(!buffer.has_conflict());
+    });
+}
+
 #[gpui::test]
 async fn test_buffer_line_endings(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -5242,6 +6158,83 @@ async fn test_reordering_worktrees(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_patch_bundles(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    for dir in ["/one", "/two"] {
+        fs.insert_tree(dir, json!({ "numbers.txt": "one\ntwo\nthree\n" }))
+            .await;
+    }
+    let project_a = Project::test(fs.clone(), ["/one".as_ref()], cx).await;
+    let project_b = Project::test(fs.clone(), ["/two".as_ref()], cx).await;
+    let buffer_a = project_a
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/one/numbers.txt", cx)
+        })
+        .await
+        .unwrap();
+    let buffer_b = project_b
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/two/numbers.txt", cx)
+        })
+        .await
+        .unwrap();
+
+    // Files without edits have nothing to export.
+    project_a
+        .update(cx, |project, cx| {
+            project.export_patch_bundle(&buffer_a, "/a.zedpatch".into(), cx)
+        })
+        .await
+        .unwrap_err();
+
+    buffer_a.update(cx, |buffer, cx| buffer.edit([(0..3, "ONE")], None, cx));
+    buffer_b.update(cx, |buffer, cx| buffer.edit([(4..7, "TWO")], None, cx));
+    project_a
+        .update(cx, |project, cx| {
+            project.export_patch_bundle(&buffer_a, "/a.zedpatch".into(), cx)
+        })
+        .await
+        .unwrap();
+
+    let import = project_b
+        .update(cx, |project, cx| {
+            project.import_patch_bundle("/a.zedpatch".into(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(import.buffer, buffer_b);
+    assert_eq!(import.edit_count, 1);
+    buffer_b.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "ONE\nTWO\nthree\n");
+    });
+
+    // Merging the same bundle twice doesn't duplicate its edits.
+    let import = project_b
+        .update(cx, |project, cx| {
+            project.import_patch_bundle("/a.zedpatch".into(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(import.edit_count, 0);
+    buffer_b.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "ONE\nTWO\nthree\n");
+    });
+
+    // Bundles can only be merged into the file they were exported from.
+    fs.insert_tree("/three", json!({ "numbers.txt": "four\n" }))
+        .await;
+    let project_c = Project::test(fs.clone(), ["/three".as_ref()], cx).await;
+    project_c
+        .update(cx, |project, cx| {
+            project.import_patch_bundle("/a.zedpatch".into(), cx)
+        })
+        .await
+        .unwrap_err();
+}
+
 async fn search(
     project: &Model<Project>,
     query: SearchQuery,
@@ -0,0 +1,320 @@
+use std::{collections::BTreeMap, ops::Range};
+
+use collections::HashMap;
+use gpui::{AppContext, EventEmitter, Model, ModelContext, Subscription, WeakModel};
+use language::{Buffer, Point};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    buffer_store::{BufferStore, BufferStoreEvent},
+    Item as _, ProjectPath,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ReviewThreadId(pub usize);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReviewComment {
+    /// The login of the user who wrote the comment, if known.
+    pub author: Option<String>,
+    pub body: String,
+    /// The time the comment was written, as a Unix timestamp in seconds.
+    pub created_at: i64,
+}
+
+/// A discussion about a range of a file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReviewThread {
+    pub id: ReviewThreadId,
+    pub path: ProjectPath,
+    pub range: Range<Point>,
+    pub comments: Vec<ReviewComment>,
+    pub resolved: bool,
+}
+
+/// A review thread, in the form it's stored in between sessions.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SerializedReviewThread {
+    /// The start and end of the range, as zero-based rows and columns.
+    pub start: (u32, u32),
+    pub end: (u32, u32),
+    pub comments: Vec<ReviewComment>,
+    #[serde(default)]
+    pub resolved: bool,
+}
+
+/// The code review comment threads of the project's files.
+///
+/// Threads are anchored to ranges, which are kept up to date through edits
+/// while the file is open.
+pub struct ReviewStore {
+    threads: BTreeMap<ReviewThreadId, ReviewThread>,
+    open_buffers: HashMap<ProjectPath, OpenBuffer>,
+    buffer_store: WeakModel<BufferStore>,
+    next_thread_id: usize,
+    _subscription: Subscription,
+}
+
+struct OpenBuffer {
+    buffer: WeakModel<Buffer>,
+    anchors: BTreeMap<ReviewThreadId, Range<text::Anchor>>,
+    _subscription: Subscription,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReviewStoreEvent {
+    ThreadsChanged,
+}
+
+impl EventEmitter<ReviewStoreEvent> for ReviewStore {}
+
+impl ReviewStore {
+    pub fn new(buffer_store: &Model<BufferStore>, cx: &mut ModelContext<Self>) -> Self {
+        Self {
+            threads: BTreeMap::default(),
+            open_buffers: HashMap::default(),
+            buffer_store: buffer_store.downgrade(),
+            next_thread_id: 0,
+            _subscription: cx.subscribe(buffer_store, |this, _, event, cx| {
+                if let BufferStoreEvent::BufferAdded(buffer) = event {
+                    if let Some(path) = buffer.read(cx).project_path(cx) {
+                        if this.threads.values().any(|thread| thread.path == path) {
+                            this.track_buffer(path, buffer, cx);
+                        }
+                    }
+                }
+            }),
+        }
+    }
+
+    /// All threads, ordered by file and position.
+    pub fn threads(&self) -> Vec<&ReviewThread> {
+        let mut threads = self.threads.values().collect::<Vec<_>>();
+        threads.sort_by(|a, b| {
+            a.path
+                .cmp(&b.path)
+                .then(a.range.start.cmp(&b.range.start))
+                .then(a.id.cmp(&b.id))
+        });
+        threads
+    }
+
+    pub fn thread(&self, id: ReviewThreadId) -> Option<&ReviewThread> {
+        self.threads.get(&id)
+    }
+
+    pub fn threads_for_path<'a>(
+        &'a self,
+        path: &'a ProjectPath,
+    ) -> impl Iterator<Item = &'a ReviewThread> + 'a {
+        self.threads
+            .values()
+            .filter(move |thread| &thread.path == path)
+    }
+
+    pub fn threads_for_buffer(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Vec<&ReviewThread> {
+        match buffer.read(cx).project_path(cx) {
+            Some(path) => self
+                .threads
+                .values()
+                .filter(|thread| thread.path == path)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Starts a thread on a range of the buffer with its first comment.
+    pub fn create_thread(
+        &mut self,
+        buffer: &Model<Buffer>,
+        range: Range<Point>,
+        comment: ReviewComment,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<ReviewThreadId> {
+        let path = buffer.read(cx).project_path(cx)?;
+        let id = self.insert_thread(path.clone(), range, vec![comment], false);
+        self.track_buffer(path, buffer, cx);
+        cx.emit(ReviewStoreEvent::ThreadsChanged);
+        Some(id)
+    }
+
+    pub fn add_comment(
+        &mut self,
+        id: ReviewThreadId,
+        comment: ReviewComment,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if let Some(thread) = self.threads.get_mut(&id) {
+            thread.comments.push(comment);
+            cx.emit(ReviewStoreEvent::ThreadsChanged);
+        }
+    }
+
+    pub fn set_resolved(
+        &mut self,
+        id: ReviewThreadId,
+        resolved: bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if let Some(thread) = self.threads.get_mut(&id) {
+            if thread.resolved != resolved {
+                thread.resolved = resolved;
+                cx.emit(ReviewStoreEvent::ThreadsChanged);
+            }
+        }
+    }
+
+    pub fn delete_thread(&mut self, id: ReviewThreadId, cx: &mut ModelContext<Self>) {
+        if let Some(thread) = self.threads.remove(&id) {
+            if let Some(open_buffer) = self.open_buffers.get_mut(&thread.path) {
+                open_buffer.anchors.remove(&id);
+            }
+            cx.emit(ReviewStoreEvent::ThreadsChanged);
+        }
+    }
+
+    /// The threads of a file, in the form they're stored in between sessions.
+    pub fn serialize_threads(&self, path: &ProjectPath) -> Vec<SerializedReviewThread> {
+        self.threads_for_path(path)
+            .map(|thread| SerializedReviewThread {
+                start: (thread.range.start.row, thread.range.start.column),
+                end: (thread.range.end.row, thread.range.end.column),
+                comments: thread.comments.clone(),
+                resolved: thread.resolved,
+            })
+            .collect()
+    }
+
+    /// Adds threads stored in a previous session to a file.
+    pub fn restore_threads(
+        &mut self,
+        path: ProjectPath,
+        threads: Vec<SerializedReviewThread>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if threads.is_empty() {
+            return;
+        }
+        for thread in threads {
+            let range =
+                Point::new(thread.start.0, thread.start.1)..Point::new(thread.end.0, thread.end.1);
+            self.insert_thread(path.clone(), range, thread.comments, thread.resolved);
+        }
+        let buffer = self
+            .buffer_store
+            .upgrade()
+            .and_then(|buffer_store| buffer_store.read(cx).get_by_path(&path, cx));
+        if let Some(buffer) = buffer {
+            self.track_buffer(path, &buffer, cx);
+        }
+        cx.emit(ReviewStoreEvent::ThreadsChanged);
+    }
+
+    fn insert_thread(
+        &mut self,
+        path: ProjectPath,
+        range: Range<Point>,
+        comments: Vec<ReviewComment>,
+        resolved: bool,
+    ) -> ReviewThreadId {
+        let id = ReviewThreadId(self.next_thread_id);
+        self.next_thread_id += 1;
+        self.threads.insert(
+            id,
+            ReviewThread {
+                id,
+                path,
+                range,
+                comments,
+                resolved,
+            },
+        );
+        id
+    }
+
+    fn track_buffer(
+        &mut self,
+        path: ProjectPath,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let is_tracked = self.open_buffers.get(&path).map_or(false, |open_buffer| {
+            open_buffer.buffer == buffer.downgrade()
+        });
+        if !is_tracked {
+            let subscription = cx.subscribe(buffer, {
+                let path = path.clone();
+                move |this, buffer, event, cx| {
+                    if matches!(event, language::Event::Edited | language::Event::Reloaded) {
+                        this.buffer_edited(&path, &buffer, cx);
+                    }
+                }
+            });
+            self.open_buffers.insert(
+                path.clone(),
+                OpenBuffer {
+                    buffer: buffer.downgrade(),
+                    anchors: BTreeMap::default(),
+                    _subscription: subscription,
+                },
+            );
+        }
+
+        let Some(open_buffer) = self.open_buffers.get_mut(&path) else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        for thread in self
+            .threads
+            .values_mut()
+            .filter(|thread| thread.path == path)
+        {
+            if open_buffer.anchors.contains_key(&thread.id) {
+                continue;
+            }
+            // The file may have changed since the thread was stored.
+            thread.range = snapshot.clip_point(thread.range.start, text::Bias::Left)
+                ..snapshot.clip_point(thread.range.end, text::Bias::Left);
+            open_buffer
+                .anchors
+                .insert(thread.id, anchor_range(&snapshot, &thread.range));
+        }
+    }
+
+    fn buffer_edited(
+        &mut self,
+        path: &ProjectPath,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(open_buffer) = self.open_buffers.get(path) else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let mut changed = false;
+        for (id, anchors) in &open_buffer.anchors {
+            let Some(thread) = self.threads.get_mut(id) else {
+                continue;
+            };
+            let range = snapshot.summary_for_anchor::<Point>(&anchors.start)
+                ..snapshot.summary_for_anchor::<Point>(&anchors.end);
+            // The end stays after the start when the range is deleted.
+            let range = range.start..range.end.max(range.start);
+            if range != thread.range {
+                thread.range = range;
+                changed = true;
+            }
+        }
+        if changed {
+            cx.emit(ReviewStoreEvent::ThreadsChanged);
+        }
+    }
+}
+
+fn anchor_range(snapshot: &text::BufferSnapshot, range: &Range<Point>) -> Range<text::Anchor> {
+    snapshot.anchor_after(range.start)..snapshot.anchor_before(range.end)
+}
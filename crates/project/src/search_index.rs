@@ -0,0 +1,211 @@
+use std::{mem, path::Path, sync::Arc, time::SystemTime};
+
+use collections::HashMap;
+use worktree::WorktreeId;
+
+use crate::{search::SearchQuery, ProjectPath};
+
+/// Files larger than this aren't indexed, and are always scanned when searching.
+pub const MAX_INDEXED_FILE_SIZE: usize = 1024 * 1024;
+
+/// How many bytes at the start of a file are checked for NUL bytes, which mark a
+/// binary file that isn't indexed.
+const BINARY_DETECTION_LENGTH: usize = 8 * 1024;
+
+/// An estimate of the memory each indexed file uses besides its trigrams.
+const FILE_OVERHEAD: usize = 64;
+
+/// The trigrams of the files in a project's local worktrees, with which project
+/// search skips files that can't contain a query's text without reading them.
+///
+/// Trigrams are taken from the ASCII-lowercased bytes of a file, so that they
+/// match both case-sensitive and case-insensitive queries. A file that isn't
+/// indexed, or that changed since it was, is always treated as a candidate.
+pub struct SearchIndex {
+    files: HashMap<ProjectPath, IndexedFile>,
+    memory_usage: usize,
+    max_memory_usage: usize,
+}
+
+struct IndexedFile {
+    mtime: SystemTime,
+    trigrams: Box<[u32]>,
+}
+
+impl IndexedFile {
+    fn memory_usage(&self, path: &Path) -> usize {
+        self.trigrams.len() * mem::size_of::<u32>() + path.as_os_str().len() + FILE_OVERHEAD
+    }
+}
+
+impl SearchIndex {
+    pub fn new(max_memory_usage: usize) -> Self {
+        Self {
+            files: HashMap::default(),
+            memory_usage: 0,
+            max_memory_usage,
+        }
+    }
+
+    /// How many bytes the index uses, roughly.
+    pub fn memory_usage(&self) -> usize {
+        self.memory_usage
+    }
+
+    /// Indexes the contents of a file, which was last modified at `mtime`. Returns
+    /// false if the file isn't indexed, because it's binary or too large, or because
+    /// the index is full.
+    pub fn insert(&mut self, path: ProjectPath, mtime: SystemTime, content: &[u8]) -> bool {
+        self.remove(&path);
+
+        let is_binary = content[..content.len().min(BINARY_DETECTION_LENGTH)].contains(&0);
+        if is_binary || content.len() > MAX_INDEXED_FILE_SIZE {
+            return false;
+        }
+
+        let file = IndexedFile {
+            mtime,
+            trigrams: trigrams(content),
+        };
+        let memory_usage = file.memory_usage(&path.path);
+        if self.memory_usage + memory_usage > self.max_memory_usage {
+            return false;
+        }
+        self.memory_usage += memory_usage;
+        self.files.insert(path, file);
+        true
+    }
+
+    pub fn remove(&mut self, path: &ProjectPath) {
+        if let Some(file) = self.files.remove(path) {
+            self.memory_usage -= file.memory_usage(&path.path);
+        }
+    }
+
+    pub fn remove_worktree(&mut self, worktree_id: WorktreeId) {
+        let mut memory_usage = self.memory_usage;
+        self.files.retain(|path, file| {
+            let retain = path.worktree_id != worktree_id;
+            if !retain {
+                memory_usage -= file.memory_usage(&path.path);
+            }
+            retain
+        });
+        self.memory_usage = memory_usage;
+    }
+
+    /// Whether the file at `path`, last modified at `mtime`, may contain text with
+    /// the given trigrams.
+    pub fn may_contain(
+        &self,
+        path: &ProjectPath,
+        mtime: Option<SystemTime>,
+        trigrams: &QueryTrigrams,
+    ) -> bool {
+        let Some(file) = self.files.get(path) else {
+            return true;
+        };
+        if mtime != Some(file.mtime) {
+            return true;
+        }
+        trigrams
+            .0
+            .iter()
+            .all(|trigram| file.trigrams.binary_search(trigram).is_ok())
+    }
+}
+
+/// The trigrams that a file must contain to match a search query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryTrigrams(Arc<[u32]>);
+
+impl QueryTrigrams {
+    /// The trigrams of a query's text, or `None` if the index can't narrow down the
+    /// files that match the query, as with regexes and queries of fewer than three
+    /// bytes.
+    pub fn new(query: &SearchQuery) -> Option<Self> {
+        if query.is_regex() || query.as_str().len() < 3 {
+            return None;
+        }
+        Some(Self(trigrams(query.as_str().as_bytes()).into()))
+    }
+}
+
+/// The sorted, distinct trigrams of the ASCII-lowercased text.
+fn trigrams(text: &[u8]) -> Box<[u32]> {
+    let mut trigrams = text
+        .windows(3)
+        .map(|window| {
+            u32::from_le_bytes([
+                window[0].to_ascii_lowercase(),
+                window[1].to_ascii_lowercase(),
+                window[2].to_ascii_lowercase(),
+                0,
+            ])
+        })
+        .collect::<Vec<_>>();
+    trigrams.sort_unstable();
+    trigrams.dedup();
+    trigrams.into_boxed_slice()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use util::paths::PathMatcher;
+
+    fn project_path(path: &str) -> ProjectPath {
+        ProjectPath {
+            worktree_id: WorktreeId::from_usize(1),
+            path: Path::new(path).into(),
+        }
+    }
+
+    fn query_trigrams(query: &str) -> QueryTrigrams {
+        let query = SearchQuery::text(
+            query,
+            false,
+            true,
+            false,
+            PathMatcher::default(),
+            PathMatcher::default(),
+            None,
+        )
+        .unwrap();
+        QueryTrigrams::new(&query).unwrap()
+    }
+
+    #[test]
+    fn test_search_index() {
+        let mut index = SearchIndex::new(usize::MAX);
+        let mtime = SystemTime::UNIX_EPOCH;
+        assert!(index.insert(project_path("a.rs"), mtime, b"fn Main() {}"));
+        assert!(index.insert(project_path("b.rs"), mtime, b"struct Point;"));
+        assert!(!index.insert(project_path("c.bin"), mtime, b"ma\0in"));
+
+        let main = query_trigrams("main");
+        assert!(index.may_contain(&project_path("a.rs"), Some(mtime), &main));
+        assert!(!index.may_contain(&project_path("b.rs"), Some(mtime), &main));
+        // Files that aren't indexed, or changed since they were, may contain anything.
+        assert!(index.may_contain(&project_path("c.bin"), Some(mtime), &main));
+        let later = mtime + Duration::from_secs(1);
+        assert!(index.may_contain(&project_path("b.rs"), Some(later), &main));
+
+        let memory_usage = index.memory_usage();
+        index.remove(&project_path("a.rs"));
+        assert!(index.memory_usage() < memory_usage);
+        assert!(index.may_contain(&project_path("a.rs"), Some(mtime), &main));
+        index.remove_worktree(WorktreeId::from_usize(1));
+        assert_eq!(index.memory_usage(), 0);
+    }
+
+    #[test]
+    fn test_search_index_memory_limit() {
+        let mut index = SearchIndex::new(100);
+        let mtime = SystemTime::UNIX_EPOCH;
+        assert!(index.insert(project_path("a.rs"), mtime, b"abcd"));
+        assert!(!index.insert(project_path("b.rs"), mtime, b"abcdefghijklmnop"));
+        assert!(index.may_contain(&project_path("b.rs"), Some(mtime), &query_trigrams("xyz")));
+    }
+}
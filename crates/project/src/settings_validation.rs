@@ -0,0 +1,327 @@
+use crate::{
+    spell_checking::replace_text, CodeAction, File, Hover, HoverBlock, HoverBlockKind, Project,
+    ProjectTransaction,
+};
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use feature_flags::FeatureFlagAppExt as _;
+use gpui::{AppContext, Model, ModelContext, Task};
+use language::{
+    Anchor, Buffer, BufferSnapshot, Diagnostic, DiagnosticEntry, DiagnosticSeverity, PointUtf16,
+    ToOffset as _, Unclipped,
+};
+use lsp::LanguageServerId;
+use serde_json::Value;
+use settings::{
+    KeymapFile, SettingsFix, SettingsJsonSchemaParams, SettingsProblem, SettingsProblemSeverity,
+    SettingsStore,
+};
+use std::{ops::Range, path::Path, sync::Arc, time::Duration};
+use text::BufferId;
+use util::ResultExt as _;
+
+/// The id under which the problems of settings files are reported as diagnostics, as
+/// they don't come from a language server.
+pub const SETTINGS_VALIDATION_SERVER_ID: LanguageServerId = LanguageServerId(usize::MAX - 3);
+
+/// The source of the diagnostics reported for the problems of settings files.
+pub const SETTINGS_VALIDATION_SOURCE: &str = "settings";
+
+pub(crate) const SETTINGS_VALIDATION_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// The keys of the user settings whose values hold settings themselves.
+const RELEASE_CHANNEL_KEYS: &[&str] = &["dev", "nightly", "preview", "stable"];
+const PROFILES_KEY: &str = "profiles";
+
+#[derive(Default)]
+pub(crate) struct SettingsValidation {
+    pending_validations: HashMap<BufferId, Task<()>>,
+    /// The schemas, which are generated when a settings file is first validated.
+    settings_schema: Option<Arc<Value>>,
+    keymap_schema: Option<Arc<Value>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SettingsFileKind {
+    Settings,
+    Keymap,
+}
+
+fn settings_file_kind(abs_path: &Path) -> Option<SettingsFileKind> {
+    if abs_path == paths::settings_file().as_path() {
+        Some(SettingsFileKind::Settings)
+    } else if abs_path == paths::keymap_file().as_path() {
+        Some(SettingsFileKind::Keymap)
+    } else {
+        None
+    }
+}
+
+impl Project {
+    /// Validates the user settings or keymap file once it stops changing, reporting
+    /// the problems found against their schema as diagnostics.
+    pub(crate) fn validate_settings_file(
+        &mut self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if !self.is_local() {
+            return;
+        }
+        let buffer = buffer_handle.read(cx);
+        let buffer_id = buffer.remote_id();
+        let Some(abs_path) = File::from_dyn(buffer.file()).map(|file| file.abs_path(cx)) else {
+            return;
+        };
+        let Some(kind) = settings_file_kind(&abs_path) else {
+            return;
+        };
+
+        let schema = self.settings_file_schema(kind, cx);
+        let snapshot = buffer.snapshot();
+        let buffer = buffer_handle.downgrade();
+        let validation = cx.spawn(|project, mut cx| async move {
+            cx.background_executor()
+                .timer(SETTINGS_VALIDATION_DEBOUNCE_TIMEOUT)
+                .await;
+            let version = snapshot.version().clone();
+            let diagnostics = cx
+                .background_executor()
+                .spawn(async move {
+                    let problems = settings::validate_settings_text(&snapshot.text(), &schema);
+                    diagnostics(&snapshot, problems)
+                })
+                .await;
+
+            project
+                .update(&mut cx, |project, cx| {
+                    let buffer = buffer.upgrade()?;
+                    // The buffer changed while it was validated, and is validated again.
+                    if buffer.read(cx).version().changed_since(&version) {
+                        return None;
+                    }
+                    project
+                        .update_diagnostic_entries(
+                            SETTINGS_VALIDATION_SERVER_ID,
+                            abs_path,
+                            None,
+                            diagnostics,
+                            cx,
+                        )
+                        .log_err()
+                })
+                .ok();
+        });
+        self.settings_validation
+            .pending_validations
+            .insert(buffer_id, validation);
+    }
+
+    /// Validates the open settings files again, as the settings schema changes when
+    /// settings are registered or languages are added.
+    pub(crate) fn revalidate_settings_files(&mut self, cx: &mut ModelContext<Self>) {
+        self.settings_validation.settings_schema = None;
+        let buffers = self.buffer_store.read(cx).buffers().collect::<Vec<_>>();
+        for buffer in buffers {
+            self.validate_settings_file(&buffer, cx);
+        }
+    }
+
+    pub(crate) fn forget_validated_settings_buffer(&mut self, buffer_id: BufferId) {
+        self.settings_validation
+            .pending_validations
+            .remove(&buffer_id);
+    }
+
+    /// Returns the code actions applying the fixes suggested for the problems of a
+    /// settings file in the range.
+    pub(crate) fn settings_validation_actions(
+        &self,
+        buffer: &Model<Buffer>,
+        range: Range<Anchor>,
+        cx: &mut ModelContext<Self>,
+    ) -> Vec<CodeAction> {
+        let snapshot = buffer.read(cx).snapshot();
+        let range = range.start.to_offset(&snapshot)..range.end.to_offset(&snapshot);
+        snapshot
+            .diagnostic_groups(Some(SETTINGS_VALIDATION_SERVER_ID))
+            .into_iter()
+            .filter_map(|(_, group)| {
+                let entry = group.entries.get(group.primary_ix)?;
+                let problem_range =
+                    entry.range.start.to_offset(&snapshot)..entry.range.end.to_offset(&snapshot);
+                if problem_range.start > range.end || problem_range.end < range.start {
+                    return None;
+                }
+                let fix: SettingsFix =
+                    serde_json::from_value(entry.diagnostic.data.clone()?).log_err()?;
+                Some(CodeAction {
+                    server_id: SETTINGS_VALIDATION_SERVER_ID,
+                    range: entry.range.clone(),
+                    lsp_action: lsp::CodeAction {
+                        title: fix.title.clone(),
+                        kind: Some(lsp::CodeActionKind::QUICKFIX),
+                        data: serde_json::to_value(fix).log_err(),
+                        ..Default::default()
+                    },
+                })
+            })
+            .collect()
+    }
+
+    pub(crate) fn apply_settings_validation_action(
+        &self,
+        buffer: Model<Buffer>,
+        action: CodeAction,
+        push_to_history: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        let fix = action
+            .lsp_action
+            .data
+            .ok_or_else(|| anyhow!("settings fix without data"))
+            .and_then(|data| Ok(serde_json::from_value::<SettingsFix>(data)?));
+        Task::ready(
+            fix.map(|fix| replace_text(buffer, action.range, fix.replacement, push_to_history, cx)),
+        )
+    }
+
+    /// Describes the setting whose key is at the position of the user settings file,
+    /// with its value once all of the user settings are merged into the defaults.
+    pub(crate) fn settings_hover(
+        &self,
+        buffer: &Model<Buffer>,
+        position: PointUtf16,
+        cx: &AppContext,
+    ) -> Option<Hover> {
+        let buffer = buffer.read(cx);
+        let abs_path = File::from_dyn(buffer.file())?.abs_path(cx);
+        if settings_file_kind(&abs_path)? != SettingsFileKind::Settings {
+            return None;
+        }
+        let snapshot = buffer.snapshot();
+        let offset = snapshot.point_utf16_to_offset(position);
+        let (key_path, key_range) = settings::key_path_at_offset(&snapshot.text(), offset)?;
+
+        // The settings of release channels and profiles are described like the others.
+        let setting_path = match key_path.first().map(String::as_str) {
+            Some(key) if RELEASE_CHANNEL_KEYS.contains(&key) => &key_path[1..],
+            Some(PROFILES_KEY) => key_path.get(2..).unwrap_or_default(),
+            _ => &key_path[..],
+        };
+        if setting_path.is_empty() {
+            return None;
+        }
+        let setting_keys = setting_path.iter().map(String::as_str).collect::<Vec<_>>();
+        let store = cx.global::<SettingsStore>();
+        let effective_value = store.effective_value(&setting_keys)?;
+        let default_value = setting_keys
+            .iter()
+            .try_fold(store.raw_default_settings(), |value, key| value.get(key));
+
+        let mut text = format!("**`{}`**", setting_keys.join("."));
+        let description = self
+            .settings_validation
+            .settings_schema
+            .as_ref()
+            .and_then(|schema| settings::setting_description(schema, setting_path));
+        if let Some(description) = description {
+            text.push_str("\n\n");
+            text.push_str(&description);
+        }
+        text.push_str("\n\nEffective value:");
+        push_json(&mut text, &effective_value);
+        if let Some(default_value) = default_value.filter(|value| **value != effective_value) {
+            text.push_str("\n\nDefault value:");
+            push_json(&mut text, default_value);
+        }
+
+        Some(Hover {
+            contents: vec![HoverBlock {
+                text,
+                kind: HoverBlockKind::Markdown,
+            }],
+            range: Some(
+                snapshot.anchor_before(key_range.start)..snapshot.anchor_after(key_range.end),
+            ),
+            language: buffer.language().cloned(),
+        })
+    }
+
+    fn settings_file_schema(
+        &mut self,
+        kind: SettingsFileKind,
+        cx: &mut ModelContext<Self>,
+    ) -> Arc<Value> {
+        let validation = &mut self.settings_validation;
+        let schema = match kind {
+            SettingsFileKind::Settings => &mut validation.settings_schema,
+            SettingsFileKind::Keymap => &mut validation.keymap_schema,
+        };
+        schema
+            .get_or_insert_with(|| {
+                Arc::new(match kind {
+                    SettingsFileKind::Settings => {
+                        let language_names = self.languages.language_names();
+                        let font_names = cx.text_system().all_font_names();
+                        cx.global::<SettingsStore>().json_schema(
+                            &SettingsJsonSchemaParams {
+                                staff_mode: cx.is_staff(),
+                                language_names: &language_names,
+                                font_names: &font_names,
+                            },
+                            cx,
+                        )
+                    }
+                    SettingsFileKind::Keymap => {
+                        KeymapFile::generate_json_schema(&cx.all_action_names())
+                    }
+                })
+            })
+            .clone()
+    }
+}
+
+/// Appends a JSON value to a Markdown text, inline when it's short.
+fn push_json(text: &mut String, value: &Value) {
+    let json = serde_json::to_string_pretty(value).unwrap_or_default();
+    if json.contains('\n') {
+        text.push_str("\n```json\n");
+        text.push_str(&json);
+        text.push_str("\n```");
+    } else {
+        text.push_str(&format!(" `{json}`"));
+    }
+}
+
+/// Returns the diagnostics for the problems found in a settings file.
+fn diagnostics(
+    snapshot: &BufferSnapshot,
+    problems: Vec<SettingsProblem>,
+) -> Vec<DiagnosticEntry<Unclipped<PointUtf16>>> {
+    problems
+        .into_iter()
+        .enumerate()
+        .map(|(group_id, problem)| DiagnosticEntry {
+            range: Unclipped(snapshot.offset_to_point_utf16(problem.range.start))
+                ..Unclipped(snapshot.offset_to_point_utf16(problem.range.end)),
+            diagnostic: Diagnostic {
+                source: Some(SETTINGS_VALIDATION_SOURCE.to_string()),
+                code: None,
+                severity: match problem.severity {
+                    SettingsProblemSeverity::Error => DiagnosticSeverity::ERROR,
+                    SettingsProblemSeverity::Warning => DiagnosticSeverity::WARNING,
+                },
+                message: problem.message,
+                group_id,
+                is_primary: true,
+                is_disk_based: false,
+                is_unnecessary: false,
+                is_deprecated: false,
+                data: problem
+                    .fix
+                    .and_then(|fix| serde_json::to_value(fix).log_err()),
+            },
+        })
+        .collect()
+}
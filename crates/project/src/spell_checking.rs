@@ -0,0 +1,420 @@
+use crate::{CodeAction, File, Project, ProjectTransaction};
+use anyhow::{anyhow, Context as _, Result};
+use collections::{HashMap, HashSet};
+use fs::Fs;
+use futures::{
+    future::{join_all, Shared},
+    FutureExt as _,
+};
+use gpui::{Model, ModelContext, Task};
+use language::{
+    language_settings::{language_settings, SpellCheckSettings},
+    Anchor, Buffer, BufferSnapshot, Diagnostic, DiagnosticEntry, DiagnosticSeverity, PointUtf16,
+    ToOffset as _, Unclipped,
+};
+use lsp::LanguageServerId;
+use serde::{Deserialize, Serialize};
+use spell_check::Dictionary;
+use std::{iter, ops::Range, path::PathBuf, sync::Arc, time::Duration};
+use text::BufferId;
+use util::ResultExt as _;
+
+/// The id under which misspelled words are reported as diagnostics, as they
+/// don't come from a language server.
+pub const SPELL_CHECK_SERVER_ID: LanguageServerId = LanguageServerId(usize::MAX - 1);
+
+/// The source of the diagnostics reported for misspelled words.
+pub const SPELL_CHECK_SOURCE: &str = "spell check";
+
+/// The scope that stands for the text outside of any of the language's scopes.
+const TEXT_SCOPE: &str = "text";
+
+pub(crate) const SPELL_CHECK_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(300);
+
+#[derive(Default)]
+pub(crate) struct SpellChecking {
+    /// The dictionaries loaded so far, by name.
+    dictionaries: HashMap<Arc<str>, Shared<Task<Option<Arc<Dictionary>>>>>,
+    pending_checks: HashMap<BufferId, Task<()>>,
+    /// The buffers in which misspelled words were reported.
+    checked_buffers: HashSet<BufferId>,
+}
+
+/// What a spell checking code action does, stored in its data.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum SpellCheckAction {
+    Replace { replacement: String },
+    AddToDictionary { word: String },
+}
+
+impl Project {
+    /// Checks the spelling of the buffer once it stops changing, and reports the
+    /// misspelled words as diagnostics.
+    pub(crate) fn check_spelling(
+        &mut self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if !self.is_local() {
+            return;
+        }
+        let buffer = buffer_handle.read(cx);
+        let buffer_id = buffer.remote_id();
+        let Some(abs_path) = File::from_dyn(buffer.file()).map(|file| file.abs_path(cx)) else {
+            return;
+        };
+        let settings = language_settings(buffer.language(), buffer.file(), cx)
+            .spell_check
+            .clone();
+        if !settings.enabled {
+            self.spell_checking.pending_checks.remove(&buffer_id);
+            if self.spell_checking.checked_buffers.remove(&buffer_id) {
+                self.update_diagnostic_entries(
+                    SPELL_CHECK_SERVER_ID,
+                    abs_path,
+                    None,
+                    Vec::new(),
+                    cx,
+                )
+                .log_err();
+            }
+            return;
+        }
+
+        let snapshot = buffer.snapshot();
+        let dictionaries = self.load_dictionaries(&settings, cx);
+        let fs = self.fs.clone();
+        let buffer = buffer_handle.downgrade();
+        let check = cx.spawn(|project, mut cx| async move {
+            cx.background_executor()
+                .timer(SPELL_CHECK_DEBOUNCE_TIMEOUT)
+                .await;
+            let dictionaries = join_all(dictionaries)
+                .await
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+            let personal_dictionary = load_personal_dictionary(fs.as_ref()).await;
+            let version = snapshot.version().clone();
+            let diagnostics = cx
+                .background_executor()
+                .spawn(async move {
+                    misspellings(&snapshot, &settings, &dictionaries, &personal_dictionary)
+                })
+                .await;
+
+            project
+                .update(&mut cx, |project, cx| {
+                    let buffer = buffer.upgrade()?;
+                    // The buffer changed while it was checked, and is checked again.
+                    if buffer.read(cx).version().changed_since(&version) {
+                        return None;
+                    }
+                    project.spell_checking.checked_buffers.insert(buffer_id);
+                    project
+                        .update_diagnostic_entries(
+                            SPELL_CHECK_SERVER_ID,
+                            abs_path,
+                            None,
+                            diagnostics,
+                            cx,
+                        )
+                        .log_err()
+                })
+                .ok();
+        });
+        self.spell_checking.pending_checks.insert(buffer_id, check);
+    }
+
+    /// Checks the spelling of all open buffers again, for example after their
+    /// settings changed.
+    pub(crate) fn recheck_spelling(&mut self, cx: &mut ModelContext<Self>) {
+        let buffers = self.buffer_store.read(cx).buffers().collect::<Vec<_>>();
+        for buffer in buffers {
+            self.check_spelling(&buffer, cx);
+        }
+    }
+
+    pub(crate) fn forget_spell_checked_buffer(&mut self, buffer_id: BufferId) {
+        self.spell_checking.pending_checks.remove(&buffer_id);
+        self.spell_checking.checked_buffers.remove(&buffer_id);
+    }
+
+    /// Returns the code actions fixing the misspelled words in the range: replacing
+    /// them with suggested words, or adding them to the personal dictionary.
+    pub(crate) fn spell_check_actions(
+        &mut self,
+        buffer: &Model<Buffer>,
+        range: Range<Anchor>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Vec<CodeAction>> {
+        let buffer = buffer.read(cx);
+        let snapshot = buffer.snapshot();
+        let range = range.start.to_offset(&snapshot)..range.end.to_offset(&snapshot);
+        let misspellings = snapshot
+            .diagnostic_groups(Some(SPELL_CHECK_SERVER_ID))
+            .into_iter()
+            .filter_map(|(_, group)| {
+                let entry = group.entries.get(group.primary_ix)?;
+                let word_range =
+                    entry.range.start.to_offset(&snapshot)..entry.range.end.to_offset(&snapshot);
+                if word_range.start > range.end || word_range.end < range.start {
+                    return None;
+                }
+                let word = snapshot.text_for_range(word_range).collect::<String>();
+                Some((entry.range.clone(), word))
+            })
+            .collect::<Vec<_>>();
+        if misspellings.is_empty() {
+            return Task::ready(Vec::new());
+        }
+
+        let settings = language_settings(buffer.language(), buffer.file(), cx)
+            .spell_check
+            .clone();
+        let dictionaries = self.load_dictionaries(&settings, cx);
+        cx.background_executor().spawn(async move {
+            let dictionaries = join_all(dictionaries)
+                .await
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+            misspellings
+                .into_iter()
+                .flat_map(|(range, word)| {
+                    let replacements = spell_check::suggestions(&word, &dictionaries)
+                        .into_iter()
+                        .map(|replacement| {
+                            (
+                                format!("Change to \"{replacement}\""),
+                                SpellCheckAction::Replace { replacement },
+                            )
+                        });
+                    let add_to_dictionary = (
+                        format!("Add \"{word}\" to dictionary"),
+                        SpellCheckAction::AddToDictionary { word },
+                    );
+                    replacements
+                        .chain(iter::once(add_to_dictionary))
+                        .map(move |(title, action)| CodeAction {
+                            server_id: SPELL_CHECK_SERVER_ID,
+                            range: range.clone(),
+                            lsp_action: lsp::CodeAction {
+                                title,
+                                kind: Some(lsp::CodeActionKind::QUICKFIX),
+                                data: serde_json::to_value(action).log_err(),
+                                ..Default::default()
+                            },
+                        })
+                })
+                .collect()
+        })
+    }
+
+    pub(crate) fn apply_spell_check_action(
+        &self,
+        buffer: Model<Buffer>,
+        action: CodeAction,
+        push_to_history: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        let spell_check_action = action
+            .lsp_action
+            .data
+            .ok_or_else(|| anyhow!("spell check action without data"))
+            .and_then(|data| Ok(serde_json::from_value::<SpellCheckAction>(data)?));
+        match spell_check_action {
+            Ok(SpellCheckAction::Replace { replacement }) => Task::ready(Ok(replace_text(
+                buffer,
+                action.range,
+                replacement,
+                push_to_history,
+                cx,
+            ))),
+            Ok(SpellCheckAction::AddToDictionary { word }) => {
+                let fs = self.fs.clone();
+                cx.spawn(|project, mut cx| async move {
+                    add_to_personal_dictionary(fs.as_ref(), &word).await?;
+                    project.update(&mut cx, |project, cx| project.recheck_spelling(cx))?;
+                    Ok(ProjectTransaction::default())
+                })
+            }
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+
+    fn load_dictionaries(
+        &mut self,
+        settings: &SpellCheckSettings,
+        cx: &mut ModelContext<Self>,
+    ) -> Vec<Shared<Task<Option<Arc<Dictionary>>>>> {
+        settings
+            .dictionaries
+            .iter()
+            .map(|name| {
+                let fs = self.fs.clone();
+                let name = name.clone();
+                self.spell_checking
+                    .dictionaries
+                    .entry(name.clone())
+                    .or_insert_with(|| {
+                        cx.background_executor()
+                            .spawn(async move {
+                                load_dictionary(fs.as_ref(), &name)
+                                    .await
+                                    .with_context(|| format!("loading the {name:?} dictionary"))
+                                    .log_err()
+                                    .map(Arc::new)
+                            })
+                            .shared()
+                    })
+                    .clone()
+            })
+            .collect()
+    }
+}
+
+/// Returns the misspelled words in the buffer, in the scopes in which spelling is checked.
+fn misspellings(
+    snapshot: &BufferSnapshot,
+    settings: &SpellCheckSettings,
+    dictionaries: &[Arc<Dictionary>],
+    personal_dictionary: &HashSet<String>,
+) -> Vec<DiagnosticEntry<Unclipped<PointUtf16>>> {
+    let text = snapshot.text();
+    let language_name = snapshot.language().map(|language| language.name());
+    spell_check::words(&text)
+        .into_iter()
+        .filter(|(range, word)| {
+            is_checked_scope(
+                snapshot,
+                range.start,
+                &settings.scopes,
+                language_name.as_ref(),
+            ) && !spell_check::is_correct(word, dictionaries, personal_dictionary)
+        })
+        .enumerate()
+        .map(|(group_id, (range, word))| {
+            let start = snapshot.offset_to_point_utf16(range.start);
+            let end = snapshot.offset_to_point_utf16(range.end);
+            DiagnosticEntry {
+                range: Unclipped(start)..Unclipped(end),
+                diagnostic: Diagnostic {
+                    source: Some(SPELL_CHECK_SOURCE.to_string()),
+                    code: None,
+                    severity: DiagnosticSeverity::HINT,
+                    message: format!("Unknown word \"{word}\""),
+                    group_id,
+                    is_primary: true,
+                    is_disk_based: false,
+                    is_unnecessary: false,
+                    is_deprecated: false,
+                    data: None,
+                },
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn is_checked_scope(
+    snapshot: &BufferSnapshot,
+    offset: usize,
+    scopes: &[String],
+    language_name: Option<&Arc<str>>,
+) -> bool {
+    let checks_text = scopes.iter().any(|scope| scope == TEXT_SCOPE);
+    let Some(scope) = snapshot.language_scope_at(offset) else {
+        return checks_text;
+    };
+    match scope.override_name() {
+        Some(name) => scopes.iter().any(|scope| scope == name),
+        // The text of other languages, like code blocks in Markdown, is only
+        // checked in the listed scopes.
+        None => {
+            checks_text
+                && (scope.is_hidden_language() || Some(&scope.language_name()) == language_name)
+        }
+    }
+}
+
+/// Replaces the text in the range, for code actions that don't come from
+/// language servers.
+pub(crate) fn replace_text(
+    buffer: Model<Buffer>,
+    range: Range<Anchor>,
+    text: String,
+    push_to_history: bool,
+    cx: &mut ModelContext<Project>,
+) -> ProjectTransaction {
+    let transaction = buffer.update(cx, |buffer, cx| {
+        buffer.finalize_last_transaction();
+        buffer.start_transaction();
+        buffer.edit([(range, text)], None, cx);
+        buffer.end_transaction(cx)?;
+        let transaction = buffer.finalize_last_transaction()?.clone();
+        if !push_to_history {
+            buffer.forget_transaction(transaction.id);
+        }
+        Some(transaction)
+    });
+    let mut project_transaction = ProjectTransaction::default();
+    if let Some(transaction) = transaction {
+        project_transaction.0.insert(buffer, transaction);
+    }
+    project_transaction
+}
+
+/// Returns the directories in which dictionaries are looked up, in order.
+fn dictionary_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![paths::dictionaries_dir().clone()];
+    if cfg!(target_os = "macos") {
+        dirs.push(paths::home_dir().join("Library/Spelling"));
+        dirs.push(PathBuf::from("/Library/Spelling"));
+        dirs.push(PathBuf::from("/opt/homebrew/share/hunspell"));
+    } else if cfg!(any(target_os = "linux", target_os = "freebsd")) {
+        dirs.extend(
+            [
+                "/usr/share/hunspell",
+                "/usr/share/myspell",
+                "/usr/share/myspell/dicts",
+                "/usr/local/share/hunspell",
+            ]
+            .map(PathBuf::from),
+        );
+    }
+    dirs
+}
+
+async fn load_dictionary(fs: &dyn Fs, name: &str) -> Result<Dictionary> {
+    let dirs = dictionary_dirs();
+    for dir in &dirs {
+        let aff_path = dir.join(format!("{name}.aff"));
+        if !fs.is_file(&aff_path).await {
+            continue;
+        }
+        let aff = fs.load_bytes(&aff_path).await?;
+        let dic = fs.load_bytes(&dir.join(format!("{name}.dic"))).await?;
+        return Dictionary::new(&aff, &dic);
+    }
+    Err(anyhow!("dictionary not found in {dirs:?}"))
+}
+
+async fn load_personal_dictionary(fs: &dyn Fs) -> HashSet<String> {
+    fs.load(paths::personal_dictionary_file())
+        .await
+        .map(|text| spell_check::parse_personal_dictionary(&text))
+        .unwrap_or_default()
+}
+
+async fn add_to_personal_dictionary(fs: &dyn Fs, word: &str) -> Result<()> {
+    let path = paths::personal_dictionary_file();
+    let mut text = fs.load(path).await.unwrap_or_default();
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(word);
+    text.push('\n');
+    fs.create_dir(paths::config_dir()).await?;
+    fs.atomic_write(path.clone(), text).await
+}
@@ -24,7 +24,7 @@ use text::{Point, ToPoint};
 use util::{post_inc, NumericPrefixWithSuffix, ResultExt};
 use worktree::WorktreeId;
 
-use crate::Project;
+use crate::{worktree_trust::WorktreeTrust, Project};
 
 /// Inventory tracks available tasks for a given project.
 pub struct Inventory {
@@ -175,6 +175,7 @@ impl Inventory {
                 let source_worktree = source.kind.worktree();
                 worktree.is_none() || source_worktree.is_none() || source_worktree == worktree
             })
+            .filter(|source| !is_restricted(&source.kind, cx))
             .flat_map(|source| {
                 source
                     .source
@@ -223,6 +224,7 @@ impl Inventory {
             .last_scheduled_tasks
             .iter()
             .rev()
+            .filter(|(task_kind, _)| !is_restricted(task_kind, cx))
             .filter(|(task_kind, _)| {
                 if matches!(task_kind, TaskSourceKind::Language { .. }) {
                     Some(task_kind) == task_source_kind.as_ref()
@@ -247,6 +249,7 @@ impl Inventory {
                 let source_worktree = source.kind.worktree();
                 worktree.is_none() || source_worktree.is_none() || source_worktree == worktree
             })
+            .filter(|source| !is_restricted(&source.kind, cx))
             .flat_map(|source| {
                 source
                     .source
@@ -389,6 +392,13 @@ impl Inventory {
     }
 }
 
+/// Whether the tasks come from a worktree in restricted mode, which aren't offered
+/// until the worktree is trusted.
+fn is_restricted(kind: &TaskSourceKind, cx: &AppContext) -> bool {
+    kind.worktree()
+        .is_some_and(|worktree_id| WorktreeTrust::is_restricted(worktree_id, cx))
+}
+
 fn task_lru_comparator(
     (kind_a, task_a, lru_score_a): &(TaskSourceKind, ResolvedTask, u32),
     (kind_b, task_b, lru_score_b): &(TaskSourceKind, ResolvedTask, u32),
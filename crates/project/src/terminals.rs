@@ -1,16 +1,21 @@
-use crate::Project;
+use crate::{Event, Project};
 use anyhow::Context as _;
-use collections::HashMap;
-use gpui::{AnyWindowHandle, AppContext, Context, Entity, Model, ModelContext, WeakModel};
+use collections::{HashMap, HashSet};
+use futures::channel::oneshot;
+use gpui::{AnyWindowHandle, AppContext, Context, Entity, Model, ModelContext, Task, WeakModel};
 use itertools::Itertools;
+use language::{Diagnostic, DiagnosticEntry, DiagnosticSeverity, PointUtf16, Unclipped};
+use lsp::LanguageServerId;
+use regex::Regex;
 use settings::{Settings, SettingsLocation};
 use smol::channel::bounded;
 use std::{
     env::{self},
     iter,
     path::{Path, PathBuf},
+    time::Duration,
 };
-use task::{Shell, SpawnInTerminal};
+use task::{Problem, ProblemMatcher, ProblemSeverity, Shell, SpawnInTerminal, TaskId};
 use terminal::{
     terminal_settings::{self, TerminalSettings},
     TaskState, TaskStatus, Terminal, TerminalBuilder,
@@ -20,8 +25,22 @@ use util::ResultExt;
 // #[cfg(target_os = "macos")]
 // use std::os::unix::ffi::OsStrExt;
 
+/// The id under which the diagnostics parsed from the output of tasks are reported,
+/// as they don't come from a language server.
+pub const TASK_DIAGNOSTICS_SERVER_ID: LanguageServerId = LanguageServerId(usize::MAX);
+
+/// How often the output of a background task is checked for its ready pattern.
+const READY_PATTERN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// The number of last lines of output of a background task checked for its ready pattern.
+const READY_PATTERN_LINES: usize = 100;
+
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakModel<terminal::Terminal>>,
+    /// The diagnostics found in the output of the last run of each task, by path.
+    pub(crate) task_diagnostics:
+        HashMap<TaskId, HashMap<PathBuf, Vec<DiagnosticEntry<Unclipped<PointUtf16>>>>>,
+    /// Notified with the terminal of a task, once it's created.
+    pub(crate) task_waiters: HashMap<TaskId, Vec<oneshot::Sender<Model<Terminal>>>>,
 }
 
 /// Terminals are opened either for the users shell, or to run a task.
@@ -41,6 +60,10 @@ pub enum SshCommand {
     DevServer(String),
     /// Direct ssh has a list of arguments to pass to ssh
     Direct(Vec<String>),
+    /// Dev containers have a list of arguments to pass to docker
+    Container(Vec<String>),
+    /// WSL distros have a list of arguments to pass to wsl.exe
+    Wsl(Vec<String>),
 }
 
 impl Project {
@@ -68,6 +91,12 @@ impl Project {
 
     fn ssh_command(&self, cx: &AppContext) -> Option<SshCommand> {
         if let Some(ssh_session) = self.ssh_session.as_ref() {
+            if let Some(container) = ssh_session.dev_container() {
+                return Some(SshCommand::Container(container.exec_args()));
+            }
+            if let Some(distro) = ssh_session.wsl_distro() {
+                return Some(SshCommand::Wsl(distro.shell_args()));
+            }
             return Some(SshCommand::Direct(ssh_session.ssh_args()));
         }
 
@@ -111,6 +140,8 @@ impl Project {
         let settings = TerminalSettings::get(settings_location, cx);
 
         let (completion_tx, completion_rx) = bounded(1);
+        let mut problem_matcher = None;
+        let mut task_id = None;
 
         // Start with the environment that we might have inherited from the Zed CLI.
         let mut env = self
@@ -159,6 +190,16 @@ impl Project {
                 }
             }
             TerminalKind::Task(spawn_task) => {
+                task_id = Some(spawn_task.id.clone());
+                if let Some(matcher) = spawn_task.problem_matcher.clone() {
+                    let cwd = path.clone().unwrap_or_default();
+                    problem_matcher = Some((
+                        spawn_task.id.clone(),
+                        spawn_task.label.clone(),
+                        cwd,
+                        matcher,
+                    ));
+                }
                 let task_state = Some(TaskState {
                     id: spawn_task.id,
                     full_label: spawn_task.full_label,
@@ -246,6 +287,14 @@ impl Project {
             if let Some(activate_command) = python_venv_activate_command {
                 self.activate_python_virtual_environment(activate_command, &terminal_handle, cx);
             }
+            if let Some((task_id, label, cwd, matcher)) = problem_matcher {
+                self.scan_task_output(&terminal_handle, task_id, label, cwd, matcher, cx);
+            }
+            if let Some(waiters) = task_id.and_then(|id| self.terminals.task_waiters.remove(&id)) {
+                for waiter in waiters {
+                    waiter.send(terminal_handle.clone()).ok();
+                }
+            }
             terminal_handle
         });
 
@@ -309,6 +358,200 @@ impl Project {
     pub fn local_terminal_handles(&self) -> &Vec<WeakModel<terminal::Terminal>> {
         &self.terminals.local_handles
     }
+
+    /// Returns whether a terminal is still running the task with the given id.
+    pub fn is_task_running(&self, task_id: &TaskId, cx: &AppContext) -> bool {
+        self.terminals
+            .local_handles
+            .iter()
+            .filter_map(|terminal| terminal.upgrade())
+            .any(|terminal| {
+                terminal.read(cx).task().map_or(false, |task| {
+                    task.id == *task_id && task.status == TaskStatus::Running
+                })
+            })
+    }
+
+    /// Waits for the terminal of the task with the given id to be created, and then
+    /// for the task to finish successfully, or for background tasks, to be ready.
+    ///
+    /// A background task is ready once a line of its output matches its ready pattern,
+    /// or right away without one.
+    pub fn wait_for_task(
+        &mut self,
+        task_id: TaskId,
+        label: String,
+        is_background: bool,
+        ready_pattern: Option<String>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        let ready_pattern = match ready_pattern.as_deref().map(Regex::new).transpose() {
+            Ok(ready_pattern) => ready_pattern,
+            Err(error) => {
+                return Task::ready(
+                    Err(error).with_context(|| format!("invalid ready pattern of task `{label}`")),
+                )
+            }
+        };
+        let (tx, rx) = oneshot::channel();
+        self.terminals
+            .task_waiters
+            .entry(task_id)
+            .or_default()
+            .push(tx);
+
+        cx.spawn(|_, mut cx| async move {
+            let terminal = rx
+                .await
+                .with_context(|| format!("task `{label}` was not started"))?;
+            if !is_background {
+                terminal
+                    .update(&mut cx, |terminal, cx| terminal.wait_for_completed_task(cx))?
+                    .await;
+                let succeeded = terminal.update(&mut cx, |terminal, _| {
+                    terminal.task().map_or(false, |task| {
+                        task.status == TaskStatus::Completed { success: true }
+                    })
+                })?;
+                anyhow::ensure!(succeeded, "task `{label}` failed");
+                return Ok(());
+            }
+
+            let Some(ready_pattern) = ready_pattern else {
+                return Ok(());
+            };
+            loop {
+                let (is_ready, is_running) = terminal.update(&mut cx, |terminal, _| {
+                    let is_ready = terminal
+                        .last_n_non_empty_lines(READY_PATTERN_LINES)
+                        .iter()
+                        .any(|line| ready_pattern.is_match(line));
+                    let is_running = terminal
+                        .task()
+                        .map_or(false, |task| task.status == TaskStatus::Running);
+                    (is_ready, is_running)
+                })?;
+                if is_ready {
+                    return Ok(());
+                }
+                anyhow::ensure!(is_running, "task `{label}` stopped before being ready");
+                cx.background_executor()
+                    .timer(READY_PATTERN_POLL_INTERVAL)
+                    .await;
+            }
+        })
+    }
+
+    /// Once the task running in the terminal finishes, parses its output with the
+    /// problem matcher and reports the problems found as diagnostics.
+    fn scan_task_output(
+        &self,
+        terminal: &Model<Terminal>,
+        task_id: TaskId,
+        label: String,
+        cwd: PathBuf,
+        matcher: ProblemMatcher,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let mut scanner = match matcher.scanner() {
+            Ok(scanner) => scanner,
+            Err(error) => {
+                log::error!("invalid problem matcher for task {label:?}: {error:#}");
+                return;
+            }
+        };
+        let source = scanner.source().map_or(label, ToString::to_string);
+        let completed = terminal.update(cx, |terminal, cx| terminal.wait_for_completed_task(cx));
+        let terminal = terminal.downgrade();
+        cx.spawn(|project, mut cx| async move {
+            completed.await;
+            let output = terminal.update(&mut cx, |terminal, _| terminal.output_lines())?;
+            let problems = scanner.scan(output.iter().map(String::as_str));
+            project.update(&mut cx, |project, cx| {
+                let diagnostics = task_diagnostics(problems, &cwd, &source);
+                project.set_task_diagnostics(task_id, diagnostics, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    pub(crate) fn set_task_diagnostics(
+        &mut self,
+        task_id: TaskId,
+        diagnostics: HashMap<PathBuf, Vec<DiagnosticEntry<Unclipped<PointUtf16>>>>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let task_diagnostics = &mut self.terminals.task_diagnostics;
+        let mut changed_paths = diagnostics.keys().cloned().collect::<HashSet<_>>();
+        if let Some(previous) = task_diagnostics.remove(&task_id) {
+            changed_paths.extend(previous.into_keys());
+        }
+        if !diagnostics.is_empty() {
+            task_diagnostics.insert(task_id, diagnostics);
+        }
+
+        for path in changed_paths {
+            // Several tasks may report problems in the same file, and each update
+            // replaces all of the file's task diagnostics.
+            let mut group_id = 0;
+            let entries = self
+                .terminals
+                .task_diagnostics
+                .values()
+                .filter_map(|diagnostics| diagnostics.get(&path))
+                .flatten()
+                .map(|entry| {
+                    let mut entry = entry.clone();
+                    entry.diagnostic.group_id = group_id;
+                    group_id += 1;
+                    entry
+                })
+                .collect();
+            self.update_diagnostic_entries(TASK_DIAGNOSTICS_SERVER_ID, path, None, entries, cx)
+                .log_err();
+        }
+        cx.emit(Event::DiskBasedDiagnosticsFinished {
+            language_server_id: TASK_DIAGNOSTICS_SERVER_ID,
+        });
+    }
+}
+
+/// Converts the problems found in the output of a task into diagnostics, by absolute path.
+pub(crate) fn task_diagnostics(
+    problems: Vec<Problem>,
+    cwd: &Path,
+    source: &str,
+) -> HashMap<PathBuf, Vec<DiagnosticEntry<Unclipped<PointUtf16>>>> {
+    let mut diagnostics = HashMap::<PathBuf, Vec<_>>::default();
+    for problem in problems {
+        let path = cwd.join(&problem.path);
+        let point = PointUtf16::new(
+            problem.line.saturating_sub(1),
+            problem.column.unwrap_or(1).saturating_sub(1),
+        );
+        let severity = match problem.severity {
+            ProblemSeverity::Error => DiagnosticSeverity::ERROR,
+            ProblemSeverity::Warning => DiagnosticSeverity::WARNING,
+            ProblemSeverity::Info => DiagnosticSeverity::INFORMATION,
+            ProblemSeverity::Hint => DiagnosticSeverity::HINT,
+        };
+        diagnostics.entry(path).or_default().push(DiagnosticEntry {
+            range: Unclipped(point)..Unclipped(point),
+            diagnostic: Diagnostic {
+                source: Some(source.to_string()),
+                code: problem.code,
+                severity,
+                message: problem.message,
+                group_id: 0,
+                is_primary: true,
+                is_disk_based: true,
+                is_unnecessary: false,
+                is_deprecated: false,
+                data: None,
+            },
+        });
+    }
+    diagnostics
 }
 
 pub fn wrap_for_ssh(
@@ -353,6 +596,18 @@ pub fn wrap_for_ssh(
             (program, args)
         }
         SshCommand::Direct(ssh_args) => ("ssh".to_string(), ssh_args.clone()),
+        // These already allocate a terminal, and run the shell invocation
+        // with `sh -c`.
+        SshCommand::Container(exec_args) => {
+            let mut args = exec_args.clone();
+            args.push(shell_invocation);
+            return ("docker".to_string(), args);
+        }
+        SshCommand::Wsl(shell_args) => {
+            let mut args = shell_args.clone();
+            args.push(shell_invocation);
+            return ("wsl.exe".to_string(), args);
+        }
     };
 
     if command.is_none() {
@@ -2,6 +2,7 @@ use std::{
     cell::RefCell,
     path::{Path, PathBuf},
     sync::{atomic::AtomicUsize, Arc},
+    time::SystemTime,
 };
 
 use anyhow::{anyhow, Context as _, Result};
@@ -13,22 +14,30 @@ use futures::{
     FutureExt, SinkExt,
 };
 use gpui::{
-    AppContext, AsyncAppContext, EntityId, EventEmitter, Model, ModelContext, Task, WeakModel,
+    AppContext, AsyncAppContext, EntityId, EventEmitter, Model, ModelContext, Subscription, Task,
+    WeakModel,
 };
+use parking_lot::RwLock;
 use postage::oneshot;
 use rpc::{
     proto::{self, AnyProtoClient},
     TypedEnvelope,
 };
+use settings::Settings as _;
 use smol::{
     channel::{Receiver, Sender},
     stream::StreamExt,
 };
 use text::ReplicaId;
 use util::{paths::compare_paths, ResultExt};
-use worktree::{Entry, ProjectEntryId, Worktree, WorktreeId, WorktreeSettings};
+use worktree::{Entry, PathChange, ProjectEntryId, Worktree, WorktreeId, WorktreeSettings};
 
-use crate::{search::SearchQuery, ProjectPath};
+use crate::{
+    project_settings::ProjectSettings,
+    search::SearchQuery,
+    search_index::{QueryTrigrams, SearchIndex},
+    ProjectPath,
+};
 
 struct MatchingEntry {
     worktree_path: Arc<Path>,
@@ -47,6 +56,24 @@ pub struct WorktreeStore {
     loading_worktrees:
         HashMap<Arc<Path>, Shared<Task<Result<Model<Worktree>, Arc<anyhow::Error>>>>>,
     fs: Arc<dyn Fs>,
+    search_index: Option<Arc<RwLock<SearchIndex>>>,
+    search_indexers: HashMap<WorktreeId, SearchIndexer>,
+}
+
+/// Keeps the search index up to date with the files of a local worktree.
+struct SearchIndexer {
+    updates_tx: Sender<SearchIndexUpdate>,
+    _task: Task<()>,
+    _subscription: Subscription,
+}
+
+enum SearchIndexUpdate {
+    Index {
+        path: ProjectPath,
+        abs_path: PathBuf,
+        mtime: SystemTime,
+    },
+    Remove(ProjectPath),
 }
 
 pub enum WorktreeStoreEvent {
@@ -59,10 +86,6 @@ impl EventEmitter<WorktreeStoreEvent> for WorktreeStore {}
 
 impl WorktreeStore {
     pub fn init(client: &Arc<Client>) {
-        client.add_model_request_handler(WorktreeStore::handle_create_project_entry);
-        client.add_model_request_handler(WorktreeStore::handle_rename_project_entry);
-        client.add_model_request_handler(WorktreeStore::handle_copy_project_entry);
-        client.add_model_request_handler(WorktreeStore::handle_delete_project_entry);
         client.add_model_request_handler(WorktreeStore::handle_expand_project_entry);
     }
 
@@ -76,6 +99,8 @@ impl WorktreeStore {
             worktrees: Vec::new(),
             worktrees_reordered: false,
             fs,
+            search_index: None,
+            search_indexers: HashMap::default(),
         }
     }
 
@@ -311,9 +336,11 @@ impl WorktreeStore {
         }
 
         cx.emit(WorktreeStoreEvent::WorktreeAdded(worktree.clone()));
+        self.start_indexing(worktree, cx);
 
         let handle_id = worktree.entity_id();
-        cx.observe_release(worktree, move |_, worktree, cx| {
+        cx.observe_release(worktree, move |this, worktree, cx| {
+            this.stop_indexing(worktree.id());
             cx.emit(WorktreeStoreEvent::WorktreeRemoved(
                 handle_id,
                 worktree.id(),
@@ -322,6 +349,92 @@ impl WorktreeStore {
         .detach();
     }
 
+    /// Indexes the files of a local worktree for project search, and keeps them indexed
+    /// as they change, unless the index is disabled.
+    fn start_indexing(&mut self, worktree: &Model<Worktree>, cx: &mut ModelContext<Self>) {
+        let settings = &ProjectSettings::get_global(cx).search_index;
+        let Some(local_worktree) = worktree.read(cx).as_local() else {
+            return;
+        };
+        if !settings.enabled {
+            return;
+        }
+        let max_memory_usage = settings.max_memory_mb.saturating_mul(1024 * 1024);
+        let search_index = self
+            .search_index
+            .get_or_insert_with(|| Arc::new(RwLock::new(SearchIndex::new(max_memory_usage))))
+            .clone();
+
+        let (updates_tx, updates_rx) = smol::channel::unbounded();
+        let snapshot = local_worktree.snapshot();
+        for entry in snapshot.files(false, 0) {
+            if let Some(update) = index_update(&snapshot, entry) {
+                updates_tx.try_send(update).ok();
+            }
+        }
+
+        let fs = self.fs.clone();
+        let task = cx.background_executor().spawn(async move {
+            while let Ok(update) = updates_rx.recv().await {
+                match update {
+                    SearchIndexUpdate::Index {
+                        path,
+                        abs_path,
+                        mtime,
+                    } => {
+                        if let Some(content) = fs.load_bytes(&abs_path).await.log_err() {
+                            search_index.write().insert(path, mtime, &content);
+                        }
+                    }
+                    SearchIndexUpdate::Remove(path) => search_index.write().remove(&path),
+                }
+            }
+        });
+
+        let subscription = cx.subscribe(worktree, |this, worktree, event, cx| {
+            let worktree::Event::UpdatedEntries(changes) = event else {
+                return;
+            };
+            let worktree = worktree.read(cx);
+            let Some(indexer) = this.search_indexers.get(&worktree.id()) else {
+                return;
+            };
+            let snapshot = worktree.snapshot();
+            for (path, _, change) in changes.iter() {
+                let update = if *change == PathChange::Removed {
+                    Some(SearchIndexUpdate::Remove(ProjectPath {
+                        worktree_id: snapshot.id(),
+                        path: path.clone(),
+                    }))
+                } else {
+                    snapshot
+                        .entry_for_path(path)
+                        .and_then(|entry| index_update(&snapshot, entry))
+                };
+                if let Some(update) = update {
+                    indexer.updates_tx.try_send(update).ok();
+                }
+            }
+        });
+
+        self.search_indexers.insert(
+            worktree.read(cx).id(),
+            SearchIndexer {
+                updates_tx,
+                _task: task,
+                _subscription: subscription,
+            },
+        );
+    }
+
+    fn stop_indexing(&mut self, worktree_id: WorktreeId) {
+        if self.search_indexers.remove(&worktree_id).is_some() {
+            if let Some(search_index) = self.search_index.as_ref() {
+                search_index.write().remove_worktree(worktree_id);
+            }
+        }
+    }
+
     pub fn remove_worktree(&mut self, id_to_remove: WorktreeId, cx: &mut ModelContext<Self>) {
         self.worktrees.retain(|worktree| {
             if let Some(worktree) = worktree.upgrade() {
@@ -338,6 +451,7 @@ impl WorktreeStore {
                 false
             }
         });
+        self.stop_indexing(id_to_remove);
     }
 
     pub fn set_worktrees_reordered(&mut self, worktrees_reordered: bool) {
@@ -483,6 +597,12 @@ impl WorktreeStore {
                 Some((tree.snapshot(), tree.as_local()?.settings()))
             })
             .collect::<Vec<_>>();
+        let search_index = ProjectSettings::get_global(cx)
+            .search_index
+            .enabled
+            .then(|| self.search_index.clone())
+            .flatten()
+            .zip(QueryTrigrams::new(&query));
 
         let executor = cx.background_executor().clone();
 
@@ -504,6 +624,7 @@ impl WorktreeStore {
                     fs,
                     snapshots,
                     open_entries,
+                    search_index,
                     query,
                     filter_tx,
                     output_tx,
@@ -640,6 +761,7 @@ impl WorktreeStore {
         fs: Arc<dyn Fs>,
         snapshots: Vec<(worktree::Snapshot, WorktreeSettings)>,
         open_entries: HashSet<ProjectEntryId>,
+        search_index: Option<(Arc<RwLock<SearchIndex>>, QueryTrigrams)>,
         query: SearchQuery,
         filter_tx: Sender<MatchingEntry>,
         output_tx: Sender<oneshot::Receiver<ProjectPath>>,
@@ -649,7 +771,9 @@ impl WorktreeStore {
             let mut entries: Vec<_> = snapshot.entries(query.include_ignored(), 0).collect();
             entries.sort_by(|a, b| compare_paths((&a.path, a.is_file()), (&b.path, b.is_file())));
             for entry in entries {
-                if entry.is_dir() && entry.is_ignored {
+                // Ignored directories, and those that weren't scanned yet, have no
+                // entries for their contents, so they're read from the file system.
+                if entry.is_dir() && (entry.is_ignored || entry.kind.is_unloaded()) {
                     if !settings.is_path_excluded(&entry.path) {
                         Self::scan_ignored_dir(
                             &fs,
@@ -682,23 +806,32 @@ impl WorktreeStore {
                     }
                 }
 
+                let path = ProjectPath {
+                    worktree_id: snapshot.id(),
+                    path: entry.path.clone(),
+                };
+                let is_open = open_entries.contains(&entry.id);
+                // Open buffers may have unsaved changes, so they're always searched.
+                if let Some((search_index, trigrams)) = search_index.as_ref() {
+                    if !is_open
+                        && !search_index
+                            .read()
+                            .may_contain(&path, entry.mtime, trigrams)
+                    {
+                        continue;
+                    }
+                }
+
                 let (mut tx, rx) = oneshot::channel();
 
-                if open_entries.contains(&entry.id) {
-                    tx.send(ProjectPath {
-                        worktree_id: snapshot.id(),
-                        path: entry.path.clone(),
-                    })
-                    .await?;
+                if is_open {
+                    tx.send(path).await?;
                 } else {
                     filter_tx
                         .send(MatchingEntry {
                             respond: tx,
                             worktree_path: snapshot.abs_path().clone(),
-                            path: ProjectPath {
-                                worktree_id: snapshot.id(),
-                                path: entry.path.clone(),
-                            },
+                            path,
                         })
                         .await?;
                 }
@@ -792,6 +925,20 @@ impl WorktreeStore {
     }
 }
 
+fn index_update(snapshot: &worktree::Snapshot, entry: &Entry) -> Option<SearchIndexUpdate> {
+    if !entry.is_file() || entry.is_ignored || entry.is_fifo {
+        return None;
+    }
+    Some(SearchIndexUpdate::Index {
+        path: ProjectPath {
+            worktree_id: snapshot.id(),
+            path: entry.path.clone(),
+        },
+        abs_path: snapshot.abs_path().join(&entry.path),
+        mtime: entry.mtime?,
+    })
+}
+
 #[derive(Clone)]
 enum WorktreeHandle {
     Strong(Model<Worktree>),
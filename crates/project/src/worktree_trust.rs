@@ -0,0 +1,223 @@
+//! Whether the user trusts the authors of the folders opened as worktrees.
+//!
+//! Worktrees of folders that aren't trusted are opened in restricted mode: the tasks
+//! defined in the folder aren't offered, the language server binaries configured in
+//! its settings are ignored, and the environment isn't loaded from the folder, as
+//! shell hooks such as direnv's would run the folder's scripts.
+use anyhow::Result;
+use collections::{BTreeMap, HashMap};
+use fs::Fs;
+use gpui::{AppContext, Context as _, EventEmitter, Global, Model, ModelContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use util::ResultExt as _;
+use worktree::WorktreeId;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trust {
+    Trusted,
+    Restricted,
+}
+
+pub enum WorktreeTrustEvent {
+    /// A worktree was opened for a folder whose trust wasn't decided yet.
+    TrustRequested(WorktreeId),
+    /// The worktrees became trusted or restricted.
+    TrustChanged(Vec<WorktreeId>),
+}
+
+struct GlobalWorktreeTrust(Model<WorktreeTrust>);
+
+impl Global for GlobalWorktreeTrust {}
+
+/// The trust decided for folders, and the local worktrees open for them.
+pub struct WorktreeTrust {
+    fs: Arc<dyn Fs>,
+    /// The trust decided for folders, which applies to the folders inside them that
+    /// have no trust decided.
+    folders: BTreeMap<PathBuf, Trust>,
+    worktrees: HashMap<WorktreeId, OpenWorktree>,
+}
+
+struct OpenWorktree {
+    abs_path: Arc<Path>,
+    is_visible: bool,
+}
+
+impl EventEmitter<WorktreeTrustEvent> for WorktreeTrust {}
+
+impl WorktreeTrust {
+    /// Loads the trust decided for folders. Without it, every worktree is trusted.
+    pub fn init(fs: Arc<dyn Fs>, cx: &mut AppContext) {
+        // The trust is needed as soon as the first worktree is opened.
+        let folders = std::fs::read_to_string(paths::trusted_folders_file())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).log_err())
+            .unwrap_or_default();
+        Self::set_global(fs, folders, cx);
+    }
+
+    pub(crate) fn set_global(
+        fs: Arc<dyn Fs>,
+        folders: BTreeMap<PathBuf, Trust>,
+        cx: &mut AppContext,
+    ) {
+        let worktree_trust = cx.new_model(|_| Self {
+            fs,
+            folders,
+            worktrees: HashMap::default(),
+        });
+        cx.set_global(GlobalWorktreeTrust(worktree_trust));
+    }
+
+    pub fn global(cx: &AppContext) -> Option<Model<Self>> {
+        cx.try_global::<GlobalWorktreeTrust>()
+            .map(|global| global.0.clone())
+    }
+
+    /// Whether the worktree is open in restricted mode.
+    pub fn is_restricted(worktree_id: WorktreeId, cx: &AppContext) -> bool {
+        Self::global(cx).map_or(false, |worktree_trust| {
+            WorktreeTrustSettings::get_global(cx).enabled
+                && worktree_trust.read(cx).worktree_trust(worktree_id) != Some(Trust::Trusted)
+        })
+    }
+
+    /// The trust decided for the folder, or for the closest folder containing it.
+    pub fn trust(&self, abs_path: &Path) -> Option<Trust> {
+        abs_path
+            .ancestors()
+            .find_map(|ancestor| self.folders.get(ancestor).copied())
+    }
+
+    /// The trust decided for the folder of a local worktree. Worktrees that aren't
+    /// local are trusted.
+    pub fn worktree_trust(&self, worktree_id: WorktreeId) -> Option<Trust> {
+        match self.worktrees.get(&worktree_id) {
+            Some(worktree) => self.trust(&worktree.abs_path),
+            None => Some(Trust::Trusted),
+        }
+    }
+
+    /// The folders of the visible worktrees that are restricted, as their trust isn't
+    /// decided yet, or they aren't trusted.
+    pub fn restricted_folders(&self, worktree_ids: &[WorktreeId]) -> Vec<Arc<Path>> {
+        worktree_ids
+            .iter()
+            .filter_map(|worktree_id| {
+                let worktree = self.worktrees.get(worktree_id)?;
+                (worktree.is_visible && self.trust(&worktree.abs_path) != Some(Trust::Trusted))
+                    .then(|| worktree.abs_path.clone())
+            })
+            .collect()
+    }
+
+    pub(crate) fn worktree_added(
+        &mut self,
+        worktree_id: WorktreeId,
+        abs_path: Arc<Path>,
+        is_visible: bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let is_undecided = self.trust(&abs_path).is_none();
+        self.worktrees.insert(
+            worktree_id,
+            OpenWorktree {
+                abs_path,
+                is_visible,
+            },
+        );
+        if is_undecided && is_visible {
+            cx.emit(WorktreeTrustEvent::TrustRequested(worktree_id));
+        }
+        cx.notify();
+    }
+
+    pub(crate) fn worktree_removed(
+        &mut self,
+        worktree_id: WorktreeId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if self.worktrees.remove(&worktree_id).is_some() {
+            cx.notify();
+        }
+    }
+
+    /// Decides the trust of a folder, and of the folders inside it, and persists it.
+    pub fn set_trust(&mut self, abs_path: &Path, trust: Trust, cx: &mut ModelContext<Self>) {
+        let was_trusted = self
+            .worktrees
+            .keys()
+            .map(|worktree_id| (*worktree_id, self.worktree_trust(*worktree_id)))
+            .collect::<HashMap<_, _>>();
+
+        self.folders
+            .retain(|folder, _| !folder.starts_with(abs_path));
+        self.folders.insert(abs_path.to_path_buf(), trust);
+
+        let changed_worktrees = was_trusted
+            .into_iter()
+            .filter(|(worktree_id, was_trusted)| {
+                (*was_trusted == Some(Trust::Trusted))
+                    != (self.worktree_trust(*worktree_id) == Some(Trust::Trusted))
+            })
+            .map(|(worktree_id, _)| worktree_id)
+            .collect::<Vec<_>>();
+        if !changed_worktrees.is_empty() {
+            cx.emit(WorktreeTrustEvent::TrustChanged(changed_worktrees));
+        }
+        cx.notify();
+        self.save(cx);
+    }
+
+    fn save(&self, cx: &mut ModelContext<Self>) {
+        let fs = self.fs.clone();
+        let json = serde_json::to_string_pretty(&self.folders);
+        cx.background_executor()
+            .spawn(async move {
+                fs.atomic_write(paths::trusted_folders_file().clone(), json?)
+                    .await
+            })
+            .detach_and_log_err(cx);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorktreeTrustSettings {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct WorktreeTrustSettingsContent {
+    /// Whether to ask to trust the folders opened for the first time, and to open
+    /// the folders that aren't trusted in restricted mode, without running the tasks,
+    /// language servers and environment they configure.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+}
+
+impl Settings for WorktreeTrustSettings {
+    const KEY: Option<&'static str> = Some("workspace_trust");
+
+    type FileContent = WorktreeTrustSettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        // Folders can't trust themselves with their own settings.
+        let content: WorktreeTrustSettingsContent =
+            SettingsSources::<Self::FileContent>::json_merge_with(
+                [sources.default]
+                    .into_iter()
+                    .chain(sources.user_customizations()),
+            )?;
+        Ok(Self {
+            enabled: content.enabled.unwrap_or(true),
+        })
+    }
+}
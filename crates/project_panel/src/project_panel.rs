@@ -71,6 +71,8 @@ pub struct ProjectPanel {
     edit_state: Option<EditState>,
     filename_editor: View<Editor>,
     clipboard: Option<ClipboardEntry>,
+    /// The file selected to be compared with another one.
+    compare_selection: Option<SelectedEntry>,
     _dragged_entry_destination: Option<Arc<Path>>,
     workspace: WeakView<Workspace>,
     width: Option<Pixels>,
@@ -152,6 +154,8 @@ actions!(
         UnfoldDirectory,
         FoldDirectory,
         SelectParent,
+        SelectForCompare,
+        CompareWithSelected,
     ]
 );
 
@@ -287,6 +291,7 @@ impl ProjectPanel {
                 context_menu: None,
                 filename_editor,
                 clipboard: None,
+                compare_selection: None,
                 _dragged_entry_destination: None,
                 workspace: workspace.weak_handle(),
                 width: None,
@@ -466,6 +471,9 @@ impl ProjectPanel {
             let worktree_id = worktree.id();
             let is_read_only = project.is_read_only();
             let is_remote = project.is_via_collab() && project.dev_server_project_id().is_none();
+            let can_compare = self
+                .compare_selection
+                .is_some_and(|compared| compared.entry_id != entry_id);
 
             let context_menu = ContextMenu::build(cx, |menu, cx| {
                 menu.context(self.focus_handle.clone()).map(|menu| {
@@ -484,6 +492,16 @@ impl ProjectPanel {
                                 menu.action("Reveal in File Manager", Box::new(RevealInFileManager))
                             })
                             .action("Open in Terminal", Box::new(OpenInTerminal))
+                            .when(!is_dir, |menu| {
+                                menu.separator()
+                                    .action("Select for Compare", Box::new(SelectForCompare))
+                                    .when(can_compare, |menu| {
+                                        menu.action(
+                                            "Compare with Selected",
+                                            Box::new(CompareWithSelected),
+                                        )
+                                    })
+                            })
                             .when(is_dir, |menu| {
                                 menu.separator()
                                     .action("Find in Folder…", Box::new(NewSearchInDirectory))
@@ -1442,6 +1460,62 @@ impl ProjectPanel {
         }
     }
 
+    fn select_for_compare(&mut self, _: &SelectForCompare, cx: &mut ViewContext<Self>) {
+        if let Some((worktree, entry)) = self.selected_entry(cx) {
+            if entry.is_file() {
+                self.compare_selection = Some(SelectedEntry {
+                    worktree_id: worktree.id(),
+                    entry_id: entry.id,
+                });
+            }
+        }
+    }
+
+    /// Opens the selected file in a read-only editor, showing how it differs from
+    /// the file selected for compare.
+    fn compare_with_selected(&mut self, _: &CompareWithSelected, cx: &mut ViewContext<Self>) {
+        let Some(compared) = self.compare_selection else {
+            return;
+        };
+        let Some((_, entry)) = self
+            .selected_entry(cx)
+            .filter(|(_, entry)| entry.is_file() && entry.id != compared.entry_id)
+        else {
+            return;
+        };
+        let project = self.project.read(cx);
+        let (Some(old_path), Some(new_path)) = (
+            project.path_for_entry(compared.entry_id, cx),
+            project.path_for_entry(entry.id, cx),
+        ) else {
+            return;
+        };
+        let title = format!(
+            "{} ↔ {}",
+            old_path.path.to_string_lossy(),
+            new_path.path.to_string_lossy()
+        );
+        let (old_buffer, new_buffer) = self.project.update(cx, |project, cx| {
+            (
+                project.open_buffer(old_path, cx),
+                project.open_buffer(new_path, cx),
+            )
+        });
+        let workspace = self.workspace.clone();
+        cx.spawn(|_, mut cx| async move {
+            let old_buffer = old_buffer.await?;
+            let new_buffer = new_buffer.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let old_text = old_buffer.read(cx).text();
+                let new_buffer = new_buffer.read(cx);
+                let new_text = new_buffer.text();
+                let language = new_buffer.language().cloned();
+                editor::open_comparison(workspace, title, old_text, new_text, language, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn reveal_in_finder(&mut self, _: &RevealInFileManager, cx: &mut ViewContext<Self>) {
         if let Some((worktree, entry)) = self.selected_entry(cx) {
             cx.reveal_path(&worktree.abs_path().join(&entry.path));
@@ -2498,6 +2572,8 @@ impl Render for ProjectPanel {
                 .on_action(cx.listener(Self::cancel))
                 .on_action(cx.listener(Self::copy_path))
                 .on_action(cx.listener(Self::copy_relative_path))
+                .on_action(cx.listener(Self::select_for_compare))
+                .on_action(cx.listener(Self::compare_with_selected))
                 .on_action(cx.listener(Self::new_search_in_directory))
                 .on_action(cx.listener(Self::unfold_directory))
                 .on_action(cx.listener(Self::fold_directory))
@@ -4137,6 +4213,61 @@ mod tests {
         ensure_no_open_items_and_panes(&workspace, cx);
     }
 
+    #[gpui::test]
+    async fn test_compare_with_selected(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/src",
+            json!({
+                "first.rs": "// First Rust file",
+                "second.rs": "// Second Rust file",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/src".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        select_path(&panel, "src/first.rs", cx);
+        panel.update(cx, |panel, cx| {
+            panel.compare_with_selected(&CompareWithSelected, cx);
+            panel.select_for_compare(&SelectForCompare, cx);
+            // A file isn't compared with itself.
+            panel.compare_with_selected(&CompareWithSelected, cx);
+        });
+        cx.executor().run_until_parked();
+        ensure_no_open_items_and_panes(&workspace, cx);
+
+        select_path(&panel, "src/second.rs", cx);
+        panel.update(cx, |panel, cx| {
+            panel.compare_with_selected(&CompareWithSelected, cx)
+        });
+        cx.executor().run_until_parked();
+        workspace
+            .update(cx, |workspace, cx| {
+                let editor = workspace
+                    .active_item_as::<Editor>(cx)
+                    .expect("the comparison should be open in an editor");
+                let editor = editor.read(cx);
+                assert_eq!(editor.title(cx), "first.rs ↔ second.rs");
+                assert!(editor.read_only(cx));
+                let buffer = editor.buffer().read(cx).as_singleton().unwrap();
+                let buffer = buffer.read(cx);
+                assert_eq!(buffer.text(), "// Second Rust file");
+                assert_eq!(
+                    buffer.diff_base().map(|diff_base| diff_base.to_string()),
+                    Some("// First Rust file".to_string())
+                );
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     async fn test_create_duplicate_items(cx: &mut gpui::TestAppContext) {
         init_test_with_editor(cx);
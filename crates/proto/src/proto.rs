@@ -408,7 +408,23 @@ messages!(
     (AddWorktreeResponse, Foreground),
     (FindSearchCandidates, Background),
     (FindSearchCandidatesResponse, Background),
-    (CloseBuffer, Foreground)
+    (CloseBuffer, Foreground),
+    (AdvertiseSharedTerminals, Foreground),
+    (OpenSharedTerminal, Foreground),
+    (OpenSharedTerminalResponse, Foreground),
+    (UpdateSharedTerminal, Foreground),
+    (SharedTerminalInput, Foreground),
+    (UpdateGuestPermissions, Foreground),
+    (RequestEditAccess, Foreground),
+    (GetSyncedSettings, Foreground),
+    (GetSyncedSettingsResponse, Foreground),
+    (UpdateSyncedSettings, Foreground),
+    (GetCodeLens, Background),
+    (GetCodeLensResponse, Background),
+    (ResolveCodeLens, Background),
+    (ResolveCodeLensResponse, Background),
+    (ExecuteCodeLens, Background),
+    (ExecuteCodeLensResponse, Background)
 );
 
 request_messages!(
@@ -532,6 +548,14 @@ request_messages!(
     (SynchronizeContexts, SynchronizeContextsResponse),
     (LspExtSwitchSourceHeader, LspExtSwitchSourceHeaderResponse),
     (AddWorktree, AddWorktreeResponse),
+    (OpenSharedTerminal, OpenSharedTerminalResponse),
+    (SharedTerminalInput, Ack),
+    (RequestEditAccess, Ack),
+    (GetSyncedSettings, GetSyncedSettingsResponse),
+    (UpdateSyncedSettings, Ack),
+    (GetCodeLens, GetCodeLensResponse),
+    (ResolveCodeLens, ResolveCodeLensResponse),
+    (ExecuteCodeLens, ExecuteCodeLensResponse),
 );
 
 entity_messages!(
@@ -601,7 +625,16 @@ entity_messages!(
     CreateContext,
     UpdateContext,
     SynchronizeContexts,
-    LspExtSwitchSourceHeader
+    LspExtSwitchSourceHeader,
+    AdvertiseSharedTerminals,
+    OpenSharedTerminal,
+    UpdateSharedTerminal,
+    SharedTerminalInput,
+    UpdateGuestPermissions,
+    RequestEditAccess,
+    GetCodeLens,
+    ResolveCodeLens,
+    ExecuteCodeLens
 );
 
 entity_messages!(
@@ -651,6 +684,17 @@ impl From<Nonce> for u128 {
     }
 }
 
+impl PatchBundle {
+    /// Patch bundles are stored in files, rather than sent over the wire.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.encode_to_vec()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::decode(bytes)
+    }
+}
+
 pub fn split_worktree_update(
     mut message: UpdateWorktree,
     max_chunk_size: usize,
@@ -24,6 +24,7 @@ use zed_actions::InlineAssist;
 
 mod repl_menu;
 mod toggle_markdown_preview;
+mod toggle_svg_preview;
 
 pub struct QuickActionBar {
     _inlay_hints_enabled_subscription: Option<Subscription>,
@@ -302,6 +303,7 @@ impl Render for QuickActionBar {
             .gap(Spacing::Medium.rems(cx))
             .children(self.render_repl_menu(cx))
             .children(self.render_toggle_markdown_preview(self.workspace.clone(), cx))
+            .children(self.render_toggle_svg_preview(self.workspace.clone(), cx))
             .children(search_button)
             .when(
                 AssistantSettings::get_global(cx).enabled
@@ -68,12 +68,22 @@ impl QuickActionBar {
         };
 
         let menu_state = session_state(session.clone(), cx);
+        let is_busy = matches!(menu_state.status, KernelStatus::Busy);
 
         let id = "repl-menu".to_string();
 
         let element_id = |suffix| ElementId::Name(format!("{}-{}", id, suffix).into());
 
         let editor = editor.downgrade();
+        let interrupt_button = is_busy.then(|| {
+            let editor = editor.clone();
+            IconButton::new(element_id("interrupt"), IconName::Stop)
+                .size(ButtonSize::Compact)
+                .icon_color(Color::Error)
+                .style(ButtonStyle::Subtle)
+                .tooltip(move |cx| Tooltip::for_action("Interrupt Kernel", &repl::Interrupt, cx))
+                .on_click(move |_, cx| repl::interrupt(editor.clone(), cx))
+        });
         let dropdown_menu = PopoverMenu::new(element_id("menu"))
             .menu(move |cx| {
                 let editor = editor.clone();
@@ -167,11 +177,18 @@ impl QuickActionBar {
                         },
                     )
                     .separator()
-                    .link(
-                        "Change Kernel",
-                        Box::new(zed_actions::OpenBrowser {
-                            url: format!("{}#change-kernel", ZED_REPL_DOCUMENTATION),
-                        }),
+                    .custom_entry(
+                        move |_cx| {
+                            Label::new("Change Kernel")
+                                .size(LabelSize::Small)
+                                .into_any_element()
+                        },
+                        {
+                            let editor = editor.clone();
+                            move |cx| {
+                                repl::toggle_kernel_picker(editor.clone(), cx);
+                            }
+                        },
                     )
                     .custom_entry(
                         move |_cx| {
@@ -203,6 +220,7 @@ impl QuickActionBar {
                     )
                     .separator()
                     .action("View Sessions", Box::new(repl::Sessions))
+                    .action("View Variables", Box::new(repl::Variables))
                     // TODO: Add shut down all kernels action
                     // .action("Shut Down all Kernels", Box::new(gpui::NoAction))
                 })
@@ -246,6 +264,7 @@ impl QuickActionBar {
 
         Some(
             h_flex()
+                .children(interrupt_button)
                 .child(button)
                 .child(dropdown_menu)
                 .into_any_element(),
@@ -0,0 +1,299 @@
+use std::path::{Path, PathBuf};
+
+use editor::Editor;
+use futures::{channel::mpsc, StreamExt as _};
+use git::{
+    clone::{clone_directory_name, clone_repository, CloneOptions},
+    command::{AuthenticationRequired, Credentials},
+};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, PathPromptOptions, Render,
+    SharedString, Task, View, ViewContext, WeakView,
+};
+use ui::{prelude::*, CheckboxWithLabel};
+use workspace::{DismissDecision, ModalView, Workspace};
+
+use crate::CloneRepository;
+
+pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(|workspace, _: &CloneRepository, cx| {
+        let weak_workspace = workspace.weak_handle();
+        workspace.toggle_modal(cx, |cx| CloneRepositoryPrompt::new(weak_workspace, cx));
+    });
+}
+
+enum CloneState {
+    Editing,
+    Cloning { progress: Option<SharedString> },
+    AuthenticationRequired,
+    Failed(SharedString),
+}
+
+/// Asks for the URL of a Git repository and the directory to clone it into, then
+/// clones it, showing the progress, and opens it. When the remote requires
+/// credentials, they are asked for and the clone is retried with them.
+pub struct CloneRepositoryPrompt {
+    workspace: WeakView<Workspace>,
+    url_editor: View<Editor>,
+    username_editor: View<Editor>,
+    password_editor: View<Editor>,
+    options: CloneOptions,
+    /// The directory the repository is cloned into, once it's chosen.
+    directory: Option<PathBuf>,
+    state: CloneState,
+    clone_task: Option<Task<()>>,
+}
+
+impl CloneRepositoryPrompt {
+    pub fn new(workspace: WeakView<Workspace>, cx: &mut ViewContext<Self>) -> Self {
+        let url_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("https://github.com/zed-industries/zed.git", cx);
+            editor
+        });
+        let username_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Username", cx);
+            editor
+        });
+        let password_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Password or access token", cx);
+            editor.set_masked(true, cx);
+            editor
+        });
+        Self {
+            workspace,
+            url_editor,
+            username_editor,
+            password_editor,
+            options: CloneOptions::default(),
+            directory: None,
+            state: CloneState::Editing,
+            clone_task: None,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        match self.state {
+            CloneState::Cloning { .. } => {}
+            CloneState::AuthenticationRequired => {
+                if self.username_editor.focus_handle(cx).is_focused(cx) {
+                    self.password_editor.focus_handle(cx).focus(cx);
+                    return;
+                }
+                let credentials = Credentials {
+                    username: self.username_editor.read(cx).text(cx),
+                    password: self.password_editor.read(cx).text(cx),
+                };
+                self.start_clone(Some(credentials), cx);
+            }
+            CloneState::Editing | CloneState::Failed(_) => self.choose_directory(cx),
+        }
+    }
+
+    /// Asks for the directory the repository's directory is created in.
+    fn choose_directory(&mut self, cx: &mut ViewContext<Self>) {
+        let url = self.url_editor.read(cx).text(cx);
+        let Some(name) = clone_directory_name(&url).map(str::to_string) else {
+            self.state = CloneState::Failed("Enter the URL of a Git repository".into());
+            cx.notify();
+            return;
+        };
+        let parent = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+        });
+        cx.spawn(|this, mut cx| async move {
+            let Ok(Ok(Some(paths))) = parent.await else {
+                return;
+            };
+            let Some(parent) = paths.into_iter().next() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                this.directory = Some(parent.join(name));
+                this.start_clone(None, cx);
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn start_clone(&mut self, credentials: Option<Credentials>, cx: &mut ViewContext<Self>) {
+        let Some(directory) = self.directory.clone() else {
+            return;
+        };
+        let url = self.url_editor.read(cx).text(cx).trim().to_string();
+        let options = self.options;
+
+        let (progress_tx, mut progress_rx) = mpsc::unbounded::<String>();
+        let clone = cx.background_executor().spawn({
+            let directory = directory.clone();
+            async move {
+                clone_repository(
+                    Path::new("git"),
+                    &url,
+                    &directory,
+                    options,
+                    credentials.as_ref(),
+                    &|line: &str| {
+                        progress_tx.unbounded_send(line.to_string()).ok();
+                    },
+                )
+            }
+        });
+        self.state = CloneState::Cloning { progress: None };
+        cx.notify();
+
+        self.clone_task = Some(cx.spawn(|this, mut cx| async move {
+            while let Some(line) = progress_rx.next().await {
+                this.update(&mut cx, |this, cx| {
+                    this.state = CloneState::Cloning {
+                        progress: Some(line.into()),
+                    };
+                    cx.notify();
+                })
+                .ok();
+            }
+            let result = clone.await;
+            this.update(&mut cx, |this, cx| {
+                this.clone_task = None;
+                match result {
+                    Ok(()) => {
+                        this.open_clone(directory, cx);
+                        cx.emit(DismissEvent);
+                    }
+                    Err(error) if error.is::<AuthenticationRequired>() => {
+                        this.state = CloneState::AuthenticationRequired;
+                        this.username_editor.focus_handle(cx).focus(cx);
+                    }
+                    Err(error) => this.state = CloneState::Failed(error.to_string().into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn open_clone(&self, directory: PathBuf, cx: &mut ViewContext<Self>) {
+        self.workspace
+            .update(cx, |workspace, cx| {
+                // Replace the window when it has no project, as when Zed starts.
+                let replace_current_window = workspace
+                    .project()
+                    .read(cx)
+                    .visible_worktrees(cx)
+                    .next()
+                    .is_none();
+                workspace
+                    .open_workspace_for_paths(replace_current_window, vec![directory], cx)
+                    .detach_and_log_err(cx);
+            })
+            .ok();
+    }
+
+    fn render_field(&self, editor: &View<Editor>, cx: &ViewContext<Self>) -> impl IntoElement {
+        div()
+            .px_2()
+            .py_1()
+            .rounded_md()
+            .border_1()
+            .border_color(cx.theme().colors().border)
+            .child(editor.clone())
+    }
+}
+
+impl ModalView for CloneRepositoryPrompt {
+    fn on_before_dismiss(&mut self, _: &mut ViewContext<Self>) -> DismissDecision {
+        // The clone can't be interrupted once it started.
+        DismissDecision::Dismiss(self.clone_task.is_none())
+    }
+}
+
+impl EventEmitter<DismissEvent> for CloneRepositoryPrompt {}
+
+impl FocusableView for CloneRepositoryPrompt {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        match self.state {
+            CloneState::AuthenticationRequired => self.username_editor.focus_handle(cx),
+            _ => self.url_editor.focus_handle(cx),
+        }
+    }
+}
+
+impl Render for CloneRepositoryPrompt {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let is_cloning = self.clone_task.is_some();
+        let status = match &self.state {
+            CloneState::Editing => {
+                Label::new("You'll then choose the directory to clone it into.").color(Color::Muted)
+            }
+            CloneState::Cloning { progress } => {
+                Label::new(progress.clone().unwrap_or_else(|| "Cloning…".into()))
+                    .color(Color::Muted)
+            }
+            CloneState::AuthenticationRequired => {
+                Label::new("The repository requires authentication.").color(Color::Warning)
+            }
+            CloneState::Failed(error) => Label::new(error.clone()).color(Color::Error),
+        };
+        let checkbox_selection = |enabled| {
+            if enabled {
+                Selection::Selected
+            } else {
+                Selection::Unselected
+            }
+        };
+
+        v_flex()
+            .key_context("CloneRepositoryPrompt")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .elevation_2(cx)
+            .w_96()
+            .p_2()
+            .gap_2()
+            .child(Label::new("Enter the URL of the Git repository to clone."))
+            .child(self.render_field(&self.url_editor, cx))
+            .child(
+                h_flex()
+                    .gap_4()
+                    .child(CheckboxWithLabel::new(
+                        "clone-shallow",
+                        Label::new("Latest commit only"),
+                        checkbox_selection(self.options.shallow),
+                        cx.listener(|this, selection, cx| {
+                            this.options.shallow = *selection == Selection::Selected;
+                            cx.notify();
+                        }),
+                    ))
+                    .child(CheckboxWithLabel::new(
+                        "clone-submodules",
+                        Label::new("Submodules"),
+                        checkbox_selection(self.options.recurse_submodules),
+                        cx.listener(|this, selection, cx| {
+                            this.options.recurse_submodules = *selection == Selection::Selected;
+                            cx.notify();
+                        }),
+                    )),
+            )
+            .when(
+                matches!(self.state, CloneState::AuthenticationRequired),
+                |this| {
+                    this.child(self.render_field(&self.username_editor, cx))
+                        .child(self.render_field(&self.password_editor, cx))
+                },
+            )
+            .child(
+                status
+                    .size(LabelSize::Small)
+                    .when(is_cloning, |label| label.single_line()),
+            )
+    }
+}
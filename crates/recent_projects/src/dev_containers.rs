@@ -0,0 +1,91 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use gpui::AsyncAppContext;
+use remote::DevContainer;
+use ui::ViewContext;
+use workspace::{notifications::DetachAndPromptErr, AppState, Workspace};
+
+use crate::{
+    ssh_connections::{
+        connect_to_dev_container, open_connecting_window, open_remote_workspace, SshConnectionModal,
+    },
+    OpenDevContainer,
+};
+
+pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(open_dev_container);
+}
+
+fn open_dev_container(
+    workspace: &mut Workspace,
+    _: &OpenDevContainer,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let project = workspace.project().read(cx);
+    let project_dir = if project.is_local() {
+        project
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+            .find(|dir| DevContainer::config_path(dir).is_some())
+    } else {
+        None
+    };
+    let Some(project_dir) = project_dir else {
+        workspace.show_error(
+            &anyhow::anyhow!("this project has no .devcontainer/devcontainer.json"),
+            cx,
+        );
+        return;
+    };
+
+    let app_state = workspace.app_state().clone();
+    cx.spawn(|_, mut cx| async move {
+        open_dev_container_project(project_dir, app_state, &mut cx).await
+    })
+    .detach_and_prompt_err("Failed to open dev container", cx, |_, _| None);
+}
+
+/// Opens the project in the given directory in a new window, connected to a
+/// remote server running in the project's dev container.
+pub async fn open_dev_container_project(
+    project_dir: PathBuf,
+    app_state: Arc<AppState>,
+    cx: &mut AsyncAppContext,
+) -> Result<()> {
+    let window = open_connecting_window(&app_state, cx)?;
+    let title = format!(
+        "dev container {}",
+        project_dir
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+    );
+    let result = window
+        .update(cx, |workspace, cx| {
+            cx.activate_window();
+            workspace.toggle_modal(cx, |cx| SshConnectionModal::with_title(title.into(), cx));
+            let ui = workspace
+                .active_modal::<SshConnectionModal>(cx)
+                .unwrap()
+                .read(cx)
+                .prompt
+                .clone();
+            connect_to_dev_container(project_dir, ui, cx)
+        })?
+        .await;
+
+    if result.is_err() {
+        window.update(cx, |_, cx| cx.remove_window()).ok();
+    }
+
+    let (session, container) = result?;
+    open_remote_workspace(
+        window,
+        session,
+        &[container.workspace_folder],
+        app_state,
+        cx,
+    )
+    .await
+}
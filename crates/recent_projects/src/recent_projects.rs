@@ -1,8 +1,13 @@
+mod clone_repository;
+mod dev_containers;
 mod dev_servers;
 pub mod disconnected_overlay;
 mod ssh_connections;
 mod ssh_remotes;
+mod wsl;
+pub use dev_containers::open_dev_container_project;
 pub use ssh_connections::open_ssh_project;
+pub use wsl::open_wsl_project;
 
 use client::{DevServerProjectId, ProjectId};
 use dev_servers::reconnect_to_dev_server_project;
@@ -47,13 +52,15 @@ fn default_create_new_window() -> bool {
 }
 
 gpui::impl_actions!(projects, [OpenRecent]);
-gpui::actions!(projects, [OpenRemote]);
+gpui::actions!(projects, [OpenRemote, OpenDevContainer, CloneRepository]);
 
 pub fn init(cx: &mut AppContext) {
     SshSettings::register(cx);
     cx.observe_new_views(RecentProjects::register).detach();
     cx.observe_new_views(DevServerProjects::register).detach();
     cx.observe_new_views(DisconnectedOverlay::register).detach();
+    cx.observe_new_views(dev_containers::register).detach();
+    cx.observe_new_views(clone_repository::register).detach();
 }
 
 pub struct RecentProjects {
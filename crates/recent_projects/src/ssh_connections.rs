@@ -8,10 +8,12 @@ use gpui::AppContext;
 use gpui::{
     percentage, px, Animation, AnimationExt, AnyWindowHandle, AsyncAppContext, DismissEvent,
     EventEmitter, FocusableView, ParentElement as _, Render, SemanticVersion, SharedString, Task,
-    Transformation, View,
+    Transformation, View, WindowHandle,
 };
 use release_channel::{AppVersion, ReleaseChannel};
-use remote::{SshConnectionOptions, SshPlatform, SshSession};
+use remote::{
+    DevContainer, SshConnectionOptions, SshPlatform, SshPortForward, SshSession, WslDistro,
+};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
@@ -45,6 +47,9 @@ pub struct SshConnection {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub port: Option<u16>,
     pub projects: Vec<SshProject>,
+    /// Local ports to forward to the remote host while connected.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub port_forwards: Vec<SshPortForwardSettings>,
 }
 impl From<SshConnection> for SshConnectionOptions {
     fn from(val: SshConnection) -> Self {
@@ -53,6 +58,26 @@ impl From<SshConnection> for SshConnectionOptions {
             username: val.username,
             port: val.port,
             password: None,
+            port_forwards: val.port_forwards.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SshPortForwardSettings {
+    pub local_port: u16,
+    /// The host to forward to, as seen from the remote host. Defaults to "localhost".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_host: Option<String>,
+    pub remote_port: u16,
+}
+
+impl From<SshPortForwardSettings> for SshPortForward {
+    fn from(val: SshPortForwardSettings) -> Self {
+        SshPortForward {
+            local_port: val.local_port,
+            remote_host: val.remote_host.unwrap_or_else(|| "localhost".to_string()),
+            remote_port: val.remote_port,
         }
     }
 }
@@ -78,7 +103,7 @@ impl Settings for SshSettings {
 }
 
 pub struct SshPrompt {
-    connection_string: SharedString,
+    title: SharedString,
     status_message: Option<SharedString>,
     prompt: Option<(SharedString, oneshot::Sender<Result<String>>)>,
     editor: View<Editor>,
@@ -89,9 +114,13 @@ pub struct SshConnectionModal {
 }
 impl SshPrompt {
     pub fn new(connection_options: &SshConnectionOptions, cx: &mut ViewContext<Self>) -> Self {
-        let connection_string = connection_options.connection_string().into();
+        let title = format!("ssh {}", connection_options.connection_string()).into();
+        Self::with_title(title, cx)
+    }
+
+    pub fn with_title(title: SharedString, cx: &mut ViewContext<Self>) -> Self {
         Self {
-            connection_string,
+            title,
             status_message: None,
             prompt: None,
             editor: cx.new_view(|cx| Editor::single_line(cx)),
@@ -152,10 +181,7 @@ impl Render for SshPrompt {
                                 },
                             ),
                     )
-                    .child(
-                        Label::new(format!("ssh {}…", self.connection_string))
-                            .size(ui::LabelSize::Large),
-                    ),
+                    .child(Label::new(format!("{}…", self.title)).size(ui::LabelSize::Large)),
             )
             .when_some(self.status_message.as_ref(), |el, status| {
                 el.child(Label::new(status.clone()))
@@ -174,6 +200,12 @@ impl SshConnectionModal {
         }
     }
 
+    pub fn with_title(title: SharedString, cx: &mut ViewContext<Self>) -> Self {
+        Self {
+            prompt: cx.new_view(|cx| SshPrompt::with_title(title, cx)),
+        }
+    }
+
     fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
         self.prompt.update(cx, |prompt, cx| prompt.confirm(cx))
     }
@@ -343,6 +375,49 @@ pub fn connect_over_ssh(
     })
 }
 
+/// Starts the dev container of the project in the given directory, and
+/// connects to a remote server running in it.
+pub fn connect_to_dev_container(
+    project_dir: PathBuf,
+    ui: View<SshPrompt>,
+    cx: &mut WindowContext,
+) -> Task<Result<(Arc<SshSession>, DevContainer)>> {
+    let window = cx.window_handle();
+
+    cx.spawn(|mut cx| async move {
+        let delegate: Arc<dyn remote::SshClientDelegate> = Arc::new(SshClientDelegate {
+            window,
+            ui,
+            known_password: None,
+        });
+        let container = DevContainer::start(&project_dir, &delegate, &mut cx).await?;
+        let session = SshSession::container(container.clone(), delegate, &mut cx).await?;
+        Ok((session, container))
+    })
+}
+
+/// Connects to a remote server running in the given WSL distro.
+pub fn connect_to_wsl(
+    distro: WslDistro,
+    ui: View<SshPrompt>,
+    cx: &mut WindowContext,
+) -> Task<Result<Arc<SshSession>>> {
+    let window = cx.window_handle();
+
+    cx.spawn(|mut cx| async move {
+        SshSession::wsl(
+            distro,
+            Arc::new(SshClientDelegate {
+                window,
+                ui,
+                known_password: None,
+            }),
+            &mut cx,
+        )
+        .await
+    })
+}
+
 pub async fn open_ssh_project(
     connection_options: SshConnectionOptions,
     paths: Vec<PathWithPosition>,
@@ -350,20 +425,7 @@ pub async fn open_ssh_project(
     _open_options: workspace::OpenOptions,
     cx: &mut AsyncAppContext,
 ) -> Result<()> {
-    let options = cx.update(|cx| (app_state.build_window_options)(None, cx))?;
-    let window = cx.open_window(options, |cx| {
-        let project = project::Project::local(
-            app_state.client.clone(),
-            app_state.node_runtime.clone(),
-            app_state.user_store.clone(),
-            app_state.languages.clone(),
-            app_state.fs.clone(),
-            None,
-            cx,
-        );
-        cx.new_view(|cx| Workspace::new(None, project, app_state.clone(), cx))
-    })?;
-
+    let window = open_connecting_window(&app_state, cx)?;
     let result = window
         .update(cx, |workspace, cx| {
             cx.activate_window();
@@ -383,7 +445,40 @@ pub async fn open_ssh_project(
     }
 
     let session = result?;
+    let paths = paths.into_iter().map(|path| path.path).collect::<Vec<_>>();
+    open_remote_workspace(window, session, &paths, app_state, cx).await
+}
 
+/// Opens a window with an empty workspace, to show the progress of
+/// connecting to a remote server in.
+pub(crate) fn open_connecting_window(
+    app_state: &Arc<AppState>,
+    cx: &mut AsyncAppContext,
+) -> Result<WindowHandle<Workspace>> {
+    let options = cx.update(|cx| (app_state.build_window_options)(None, cx))?;
+    cx.open_window(options, |cx| {
+        let project = project::Project::local(
+            app_state.client.clone(),
+            app_state.node_runtime.clone(),
+            app_state.user_store.clone(),
+            app_state.languages.clone(),
+            app_state.fs.clone(),
+            None,
+            cx,
+        );
+        cx.new_view(|cx| Workspace::new(None, project, app_state.clone(), cx))
+    })
+}
+
+/// Replaces the workspace of a connecting window with one for the given
+/// paths on the remote server.
+pub(crate) async fn open_remote_workspace(
+    window: WindowHandle<Workspace>,
+    session: Arc<SshSession>,
+    paths: &[PathBuf],
+    app_state: Arc<AppState>,
+    cx: &mut AsyncAppContext,
+) -> Result<()> {
     let project = cx.update(|cx| {
         project::Project::ssh(
             session,
@@ -399,7 +494,7 @@ pub async fn open_ssh_project(
     for path in paths {
         project
             .update(cx, |project, cx| {
-                project.find_or_create_worktree(&path.path, true, cx)
+                project.find_or_create_worktree(path, true, cx)
             })?
             .await?;
     }
@@ -0,0 +1,47 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use gpui::AsyncAppContext;
+use remote::WslDistro;
+use util::paths::PathWithPosition;
+use workspace::AppState;
+
+use crate::ssh_connections::{
+    connect_to_wsl, open_connecting_window, open_remote_workspace, SshConnectionModal,
+};
+
+/// Opens the given paths inside a WSL distro in a new window, connected to a
+/// remote server running in the distro.
+pub async fn open_wsl_project(
+    distro: WslDistro,
+    paths: Vec<PathWithPosition>,
+    app_state: Arc<AppState>,
+    cx: &mut AsyncAppContext,
+) -> Result<()> {
+    let window = open_connecting_window(&app_state, cx)?;
+    let title = format!("wsl {}", distro.name);
+    let result = window
+        .update(cx, |workspace, cx| {
+            cx.activate_window();
+            workspace.toggle_modal(cx, |cx| SshConnectionModal::with_title(title.into(), cx));
+            let ui = workspace
+                .active_modal::<SshConnectionModal>(cx)
+                .unwrap()
+                .read(cx)
+                .prompt
+                .clone();
+            connect_to_wsl(distro, ui, cx)
+        })?
+        .await;
+
+    if result.is_err() {
+        window.update(cx, |_, cx| cx.remove_window()).ok();
+    }
+
+    let session = result?;
+    let paths = paths
+        .into_iter()
+        .map(|path| path.path)
+        .collect::<Vec<PathBuf>>();
+    open_remote_workspace(window, session, &paths, app_state, cx).await
+}
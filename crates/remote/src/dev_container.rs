@@ -0,0 +1,424 @@
+//! Dev containers: a project describes the container it's developed in with a
+//! `.devcontainer/devcontainer.json`, and the remote server runs inside it.
+
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, bail, Context as _, Result};
+use futures::future;
+use gpui::{AsyncAppContext, BackgroundExecutor, Task};
+use serde::Deserialize;
+use smol::{
+    fs,
+    io::{self, AsyncWriteExt as _},
+    net::{TcpListener, TcpStream},
+    process::{self, Stdio},
+};
+use util::ResultExt as _;
+
+use crate::{ssh_session::run_cmd, SshClientDelegate, SshPortForward};
+
+/// Where a project may keep its dev container configuration, in order of precedence.
+const CONFIG_PATHS: [&str; 2] = [".devcontainer/devcontainer.json", ".devcontainer.json"];
+
+/// Labels the containers we create with the project they were created for,
+/// so that reopening the project reuses its container.
+const LOCAL_FOLDER_LABEL: &str = "dev.zed.devcontainer.local_folder";
+
+/// The subset of the `devcontainer.json` format that we support.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevContainerConfig {
+    pub name: Option<String>,
+    pub image: Option<String>,
+    pub build: Option<DevContainerBuild>,
+    /// Where the project is mounted in the container. Defaults to
+    /// `/workspaces/<project directory name>`.
+    pub workspace_folder: Option<String>,
+    #[serde(default)]
+    pub forward_ports: Vec<u16>,
+    #[serde(default)]
+    pub container_env: BTreeMap<String, String>,
+    pub remote_user: Option<String>,
+    #[serde(default)]
+    pub run_args: Vec<String>,
+    /// Runs once, after the container is created.
+    pub post_create_command: Option<DevContainerCommand>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevContainerBuild {
+    /// Relative to the `devcontainer.json`.
+    #[serde(alias = "dockerFile")]
+    pub dockerfile: Option<String>,
+    /// Relative to the `devcontainer.json`. Defaults to its directory.
+    pub context: Option<String>,
+    #[serde(default)]
+    pub args: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum DevContainerCommand {
+    /// Run by a shell.
+    Shell(String),
+    /// Run directly, without a shell.
+    Args(Vec<String>),
+}
+
+impl DevContainerConfig {
+    /// Parses a `devcontainer.json`, which may contain comments and trailing commas.
+    pub fn parse(contents: &str) -> Result<Self> {
+        serde_json_lenient::from_str(contents).context("invalid devcontainer.json")
+    }
+
+    fn workspace_folder(&self, project_dir: &Path) -> PathBuf {
+        match &self.workspace_folder {
+            Some(workspace_folder) => PathBuf::from(workspace_folder),
+            None => Path::new("/workspaces").join(project_dir.file_name().unwrap_or_default()),
+        }
+    }
+}
+
+/// A running dev container, which commands are run in with `docker exec`.
+#[derive(Debug, Clone)]
+pub struct DevContainer {
+    pub container_id: String,
+    pub name: String,
+    pub workspace_folder: PathBuf,
+    pub remote_user: Option<String>,
+}
+
+impl DevContainer {
+    /// The `devcontainer.json` of the project in the given directory, if it has one.
+    pub fn config_path(project_dir: &Path) -> Option<PathBuf> {
+        CONFIG_PATHS
+            .iter()
+            .map(|path| project_dir.join(path))
+            .find(|path| path.is_file())
+    }
+
+    /// Starts the dev container of the project in the given directory,
+    /// building and creating it first if this project doesn't have one yet.
+    pub async fn start(
+        project_dir: &Path,
+        delegate: &Arc<dyn SshClientDelegate>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<Self> {
+        let config_path = Self::config_path(project_dir)
+            .with_context(|| format!("no devcontainer.json in {}", project_dir.display()))?;
+        let config = DevContainerConfig::parse(&fs::read_to_string(&config_path).await?)?;
+        let config_dir = config_path.parent().unwrap_or(project_dir);
+        let workspace_folder = config.workspace_folder(project_dir);
+        let label = format!("{LOCAL_FOLDER_LABEL}={}", project_dir.display());
+
+        let existing_container = run_cmd(
+            process::Command::new("docker")
+                .args(["ps", "--all", "--quiet", "--filter"])
+                .arg(format!("label={label}")),
+        )
+        .await
+        .context("failed to run docker")?;
+
+        let container_id = if let Some(container_id) = existing_container.lines().next() {
+            delegate.set_status(Some("starting dev container"), cx);
+            run_cmd(process::Command::new("docker").args(["start", container_id])).await?;
+            container_id.to_string()
+        } else {
+            let image = match (&config.image, &config.build) {
+                (_, Some(build)) => {
+                    delegate.set_status(Some("building dev container image"), cx);
+                    build_image(project_dir, config_dir, build).await?
+                }
+                (Some(image), None) => image.clone(),
+                (None, None) => {
+                    bail!("devcontainer.json must specify either an \"image\" or a \"build\"")
+                }
+            };
+
+            delegate.set_status(Some("creating dev container"), cx);
+            let mut command = process::Command::new("docker");
+            command
+                .args(["run", "--detach", "--label", &label, "--mount"])
+                .arg(format!(
+                    "type=bind,source={},target={}",
+                    project_dir.display(),
+                    workspace_folder.display()
+                ))
+                .arg("--workdir")
+                .arg(&workspace_folder);
+            for (key, value) in &config.container_env {
+                command.arg("--env").arg(format!("{key}={value}"));
+            }
+            for port in &config.forward_ports {
+                command.arg("--publish").arg(format!("{port}:{port}"));
+            }
+            // Keep the container running regardless of what the image would run.
+            command
+                .args(&config.run_args)
+                .args(["--entrypoint", "/bin/sh"])
+                .arg(&image)
+                .args([
+                    "-c",
+                    "trap 'exit 0' TERM; while sleep 1000 & wait $!; do :; done",
+                ]);
+            let container_id = run_cmd(&mut command)
+                .await
+                .context("failed to create dev container")?
+                .trim()
+                .to_string();
+
+            if let Some(post_create_command) = &config.post_create_command {
+                delegate.set_status(Some("running postCreateCommand"), cx);
+                let mut command = process::Command::new("docker");
+                command.arg("exec").arg("--workdir").arg(&workspace_folder);
+                if let Some(user) = &config.remote_user {
+                    command.args(["--user", user]);
+                }
+                command.arg(&container_id);
+                match post_create_command {
+                    DevContainerCommand::Shell(script) => command.args(["sh", "-c", script]),
+                    DevContainerCommand::Args(args) => command.args(args),
+                };
+                run_cmd(&mut command)
+                    .await
+                    .context("postCreateCommand failed")?;
+            }
+
+            container_id
+        };
+
+        let name = config.name.unwrap_or_else(|| {
+            project_dir
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string()
+        });
+        Ok(Self {
+            container_id,
+            name,
+            workspace_folder,
+            remote_user: config.remote_user,
+        })
+    }
+
+    /// The arguments to `docker` that run an interactive shell command in the container.
+    pub fn exec_args(&self) -> Vec<String> {
+        let mut args = vec!["exec".to_string(), "-it".to_string()];
+        if let Some(user) = &self.remote_user {
+            args.extend(["--user".to_string(), user.clone()]);
+        }
+        args.extend([
+            self.container_id.clone(),
+            "sh".to_string(),
+            "-c".to_string(),
+        ]);
+        args
+    }
+
+    /// Runs a shell command in the container, from the home directory of the
+    /// remote user, like ssh does. Arguments added to the returned command are
+    /// passed to the shell command.
+    pub(crate) fn command<S: AsRef<OsStr>>(&self, program: S) -> process::Command {
+        let mut command = process::Command::new("docker");
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .args(["exec", "--interactive"]);
+        if let Some(user) = &self.remote_user {
+            command.args(["--user", user]);
+        }
+        command
+            .arg(&self.container_id)
+            .args(["sh", "-c"])
+            .arg(format!(
+                "cd && {} \"$@\"",
+                program.as_ref().to_string_lossy()
+            ))
+            .arg("sh");
+        command
+    }
+
+    /// Forwards a local port to a port in the container until the returned task
+    /// is dropped. Ports can't be published once the container is running, so
+    /// each connection is relayed by the remote server binary, run with
+    /// `docker exec`.
+    pub(crate) async fn forward_port(
+        &self,
+        forward: &SshPortForward,
+        remote_binary_path: PathBuf,
+        executor: &BackgroundExecutor,
+    ) -> Result<Task<()>> {
+        let listener = TcpListener::bind(("127.0.0.1", forward.local_port))
+            .await
+            .with_context(|| format!("failed to listen on port {}", forward.local_port))?;
+        let address = format!("{}:{}", forward.remote_host, forward.remote_port);
+        let container = self.clone();
+        let relay_executor = executor.clone();
+        Ok(executor.spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let relay = container
+                    .command(&remote_binary_path)
+                    .arg("forward-port")
+                    .arg(&address)
+                    .stderr(Stdio::null())
+                    .kill_on_drop(true)
+                    .spawn();
+                match relay {
+                    Ok(relay) => relay_executor
+                        .spawn(async move { relay_connection(stream, relay).await.log_err() })
+                        .detach(),
+                    Err(error) => log::error!("failed to relay connection to {address}: {error}"),
+                }
+            }
+        }))
+    }
+
+    pub(crate) async fn upload_file(&self, src_path: &Path, dest_path: &Path) -> Result<()> {
+        let file = std::fs::File::open(src_path)?;
+        let output = self
+            .command("cat >")
+            .arg(dest_path)
+            .stdin(Stdio::from(file))
+            .output()
+            .await?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "failed to upload file {} -> {}: {}",
+                src_path.display(),
+                dest_path.display(),
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+}
+
+async fn build_image(
+    project_dir: &Path,
+    config_dir: &Path,
+    build: &DevContainerBuild,
+) -> Result<String> {
+    let mut hasher = DefaultHasher::new();
+    project_dir.hash(&mut hasher);
+    let tag = format!("zed-devcontainer-{:x}", hasher.finish());
+
+    let dockerfile = config_dir.join(build.dockerfile.as_deref().unwrap_or("Dockerfile"));
+    let context = config_dir.join(build.context.as_deref().unwrap_or("."));
+    let mut command = process::Command::new("docker");
+    command
+        .args(["build", "--tag", &tag, "--file"])
+        .arg(&dockerfile);
+    for (key, value) in &build.args {
+        command.arg("--build-arg").arg(format!("{key}={value}"));
+    }
+    command.arg(&context);
+    run_cmd(&mut command)
+        .await
+        .context("failed to build dev container image")?;
+    Ok(tag)
+}
+
+/// Relays a connection to a forwarded port through the process that's
+/// connected to the port in the container.
+async fn relay_connection(stream: TcpStream, mut relay: process::Child) -> Result<()> {
+    let mut relay_stdin = relay.stdin.take().context("no stdin for relay process")?;
+    let mut relay_stdout = relay.stdout.take().context("no stdout for relay process")?;
+    let mut reader = stream.clone();
+    let mut writer = stream;
+
+    let client_to_container = async move {
+        io::copy(&mut reader, &mut relay_stdin).await?;
+        // Closing stdin lets the relay close its half of the connection.
+        drop(relay_stdin);
+        io::Result::Ok(())
+    };
+    let container_to_client = async move {
+        io::copy(&mut relay_stdout, &mut writer).await?;
+        writer.close().await
+    };
+    future::try_join(client_to_container, container_to_client).await?;
+    relay.status().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = DevContainerConfig::parse(
+            r#"
+            // Comments and trailing commas are allowed.
+            {
+                "name": "Rust",
+                "build": {
+                    "dockerfile": "Dockerfile",
+                    "args": { "VARIANT": "bookworm" },
+                },
+                "forwardPorts": [3000, 8080],
+                "containerEnv": { "RUST_LOG": "info" },
+                "remoteUser": "vscode",
+                "postCreateCommand": ["cargo", "fetch"],
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.name.as_deref(), Some("Rust"));
+        assert_eq!(config.image, None);
+        assert_eq!(
+            config.build,
+            Some(DevContainerBuild {
+                dockerfile: Some("Dockerfile".into()),
+                context: None,
+                args: BTreeMap::from_iter([("VARIANT".into(), "bookworm".into())]),
+            })
+        );
+        assert_eq!(config.forward_ports, vec![3000, 8080]);
+        assert_eq!(config.remote_user.as_deref(), Some("vscode"));
+        assert_eq!(
+            config.post_create_command,
+            Some(DevContainerCommand::Args(vec![
+                "cargo".into(),
+                "fetch".into()
+            ]))
+        );
+        assert_eq!(
+            config.workspace_folder(Path::new("/home/me/project")),
+            Path::new("/workspaces/project")
+        );
+
+        let config = DevContainerConfig::parse(
+            r#"{
+                "image": "mcr.microsoft.com/devcontainers/base:ubuntu",
+                "workspaceFolder": "/src",
+                "postCreateCommand": "npm install"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.image.as_deref(),
+            Some("mcr.microsoft.com/devcontainers/base:ubuntu")
+        );
+        assert_eq!(
+            config.post_create_command,
+            Some(DevContainerCommand::Shell("npm install".into()))
+        );
+        assert_eq!(
+            config.workspace_folder(Path::new("/home/me/project")),
+            Path::new("/src")
+        );
+    }
+}
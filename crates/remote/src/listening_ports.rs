@@ -0,0 +1,68 @@
+//! Parsing of the listening TCP ports reported by a remote host.
+use std::collections::BTreeSet;
+
+const TCP_LISTEN_STATE: &str = "0A";
+
+/// Parses the contents of `/proc/net/tcp` and `/proc/net/tcp6`.
+pub fn parse_proc_net_tcp(contents: &str) -> Vec<u16> {
+    let mut ports = BTreeSet::new();
+    for line in contents.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(_), Some(local_address), Some(_), Some(state)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        if state != TCP_LISTEN_STATE {
+            continue;
+        }
+        if let Some(port) = local_address
+            .rsplit_once(':')
+            .and_then(|(_, port)| u16::from_str_radix(port, 16).ok())
+        {
+            ports.insert(port);
+        }
+    }
+    ports.into_iter().collect()
+}
+
+/// Parses the output of `lsof -iTCP -sTCP:LISTEN -Fn`, in which each
+/// listening address is on a line starting with `n`.
+pub fn parse_lsof(output: &str) -> Vec<u16> {
+    let mut ports = BTreeSet::new();
+    for line in output.lines() {
+        if let Some(port) = line
+            .strip_prefix('n')
+            .and_then(|address| address.rsplit_once(':'))
+            .and_then(|(_, port)| port.parse().ok())
+        {
+            ports.insert(port);
+        }
+    }
+    ports.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_net_tcp() {
+        let contents = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 1 1
+   1: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 2 1
+   2: 0100007F:1F90 0100007F:D2A4 01 00000000:00000000 00:00000000 00000000  1000        0 3 1
+  sl  local_address                         remote_address                        st
+   0: 00000000000000000000000001000000:0BB8 00000000000000000000000000000000:0000 0A
+   1: 00000000000000000000000001000000:1F90 00000000000000000000000000000000:0000 0A
+";
+        assert_eq!(parse_proc_net_tcp(contents), vec![22, 3000, 8080]);
+    }
+
+    #[test]
+    fn test_parse_lsof() {
+        let output = "p123\nf5\nn*:3000\nf6\nn[::1]:5432\np456\nf7\nn127.0.0.1:3000\n";
+        assert_eq!(parse_lsof(output), vec![3000, 5432]);
+    }
+}
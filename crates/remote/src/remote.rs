@@ -1,5 +1,12 @@
+pub mod dev_container;
 pub mod json_log;
+pub mod listening_ports;
 pub mod protocol;
 pub mod ssh_session;
+pub mod wsl;
 
-pub use ssh_session::{SshClientDelegate, SshConnectionOptions, SshPlatform, SshSession};
+pub use dev_container::DevContainer;
+pub use ssh_session::{
+    SshClientDelegate, SshConnectionOptions, SshPlatform, SshPortForward, SshSession,
+};
+pub use wsl::WslDistro;
@@ -1,8 +1,11 @@
 use crate::{
+    dev_container::DevContainer,
     json_log::LogRecord,
+    listening_ports,
     protocol::{
         message_len_from_buffer, read_message_with_len, write_message, MessageId, MESSAGE_LEN_SIZE,
     },
+    wsl::WslDistro,
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::HashMap;
@@ -11,7 +14,9 @@ use futures::{
     future::{BoxFuture, LocalBoxFuture},
     select_biased, AsyncReadExt as _, AsyncWriteExt as _, Future, FutureExt as _, StreamExt as _,
 };
-use gpui::{AppContext, AsyncAppContext, Model, SemanticVersion, WeakModel};
+use gpui::{
+    AppContext, AsyncAppContext, BackgroundExecutor, Model, SemanticVersion, Task, WeakModel,
+};
 use parking_lot::Mutex;
 use rpc::{
     proto::{
@@ -30,11 +35,12 @@ use std::{
     path::{Path, PathBuf},
     sync::{
         atomic::{AtomicU32, Ordering::SeqCst},
-        Arc,
+        Arc, Weak,
     },
-    time::Instant,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use tempfile::TempDir;
+use util::ResultExt as _;
 
 #[derive(Clone)]
 pub struct SshSocket {
@@ -47,7 +53,15 @@ pub struct SshSession {
     response_channels: ResponseChannels,
     outgoing_tx: mpsc::UnboundedSender<Envelope>,
     spawn_process_tx: mpsc::UnboundedSender<SpawnRequest>,
-    client_socket: Option<SshSocket>,
+    client_socket: Mutex<Option<RemoteSocket>>,
+    /// Where the remote server binary is on the remote host, which relays the
+    /// connections to forwarded ports of dev containers.
+    remote_binary_path: Option<PathBuf>,
+    added_port_forwards: Mutex<Vec<SshPortForward>>,
+    /// Dev containers can't publish ports once they're running, so their ports
+    /// are forwarded by listening on the local port while these tasks run.
+    container_port_forwards: Mutex<HashMap<u16, Task<()>>>,
+    executor: BackgroundExecutor,
     message_handlers: Mutex<
         HashMap<
             TypeId,
@@ -70,12 +84,75 @@ struct SshClientState {
     _temp_dir: TempDir,
 }
 
+/// How commands are run on the host of the remote server.
+#[derive(Clone)]
+enum RemoteSocket {
+    Ssh(SshSocket),
+    /// Commands run in a dev container on this machine, with `docker exec`.
+    Container(DevContainer),
+    /// Commands run in a WSL distro on this machine, with `wsl.exe`.
+    Wsl(WslDistro),
+}
+
+enum RemoteHost {
+    Ssh(SshConnectionOptions),
+    Container(DevContainer),
+    Wsl(WslDistro),
+}
+
+/// Everything needed to (re)establish a connection to the remote server.
+struct SshServerTarget {
+    host: RemoteHost,
+    delegate: Arc<dyn SshClientDelegate>,
+    remote_binary_path: PathBuf,
+    server_socket_path: PathBuf,
+}
+
+/// A live connection to the remote server, made through a `proxy` process
+/// that relays messages to the long-lived server daemon.
+struct RemoteServerConnection {
+    _client_state: Option<SshClientState>,
+    socket: RemoteSocket,
+    proxy_process: process::Child,
+}
+
+/// The channels that outlive any single connection, so that a session can
+/// survive reconnects without its users noticing.
+struct IoChannels {
+    outgoing_rx: mpsc::UnboundedReceiver<Envelope>,
+    spawn_process_rx: mpsc::UnboundedReceiver<SpawnRequest>,
+    incoming_tx: mpsc::UnboundedSender<Envelope>,
+}
+
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+const MAX_RECONNECT_ATTEMPTS: usize = 10;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SshConnectionOptions {
     pub host: String,
     pub username: Option<String>,
     pub port: Option<u16>,
     pub password: Option<String>,
+    pub port_forwards: Vec<SshPortForward>,
+}
+
+/// Forwards `local_port` on this machine to `remote_host:remote_port`, as
+/// seen from the ssh server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshPortForward {
+    pub local_port: u16,
+    pub remote_host: String,
+    pub remote_port: u16,
+}
+
+impl SshPortForward {
+    fn ssh_arg(&self) -> String {
+        format!(
+            "{}:{}:{}",
+            self.local_port, self.remote_host, self.remote_port
+        )
+    }
 }
 
 impl SshConnectionOptions {
@@ -149,133 +226,141 @@ impl SshSession {
         delegate: Arc<dyn SshClientDelegate>,
         cx: &mut AsyncAppContext,
     ) -> Result<Arc<Self>> {
-        let client_state = SshClientState::new(connection_options, delegate.clone(), cx).await?;
+        Self::connect(RemoteHost::Ssh(connection_options), delegate, cx).await
+    }
+
+    /// Connects to a remote server running in the given dev container.
+    pub async fn container(
+        container: DevContainer,
+        delegate: Arc<dyn SshClientDelegate>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<Arc<Self>> {
+        Self::connect(RemoteHost::Container(container), delegate, cx).await
+    }
+
+    /// Connects to a remote server running in the given WSL distro.
+    pub async fn wsl(
+        distro: WslDistro,
+        delegate: Arc<dyn SshClientDelegate>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<Arc<Self>> {
+        Self::connect(RemoteHost::Wsl(distro), delegate, cx).await
+    }
 
-        let platform = client_state.query_platform().await?;
-        let (local_binary_path, version) = delegate.get_server_binary(platform, cx).await??;
+    async fn connect(
+        host: RemoteHost,
+        delegate: Arc<dyn SshClientDelegate>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<Arc<Self>> {
         let remote_binary_path = delegate.remote_server_binary_path(cx)?;
-        client_state
-            .ensure_server_binary(
-                &delegate,
-                &local_binary_path,
-                &remote_binary_path,
-                version,
-                cx,
-            )
-            .await?;
+        let server_socket_path = server_socket_path(&remote_binary_path);
+        let target = SshServerTarget {
+            host,
+            delegate,
+            remote_binary_path,
+            server_socket_path,
+        };
+        let connection = target.connect(cx).await?;
 
-        let (spawn_process_tx, mut spawn_process_rx) = mpsc::unbounded::<SpawnRequest>();
-        let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded::<Envelope>();
+        let (spawn_process_tx, spawn_process_rx) = mpsc::unbounded::<SpawnRequest>();
+        let (outgoing_tx, outgoing_rx) = mpsc::unbounded::<Envelope>();
         let (incoming_tx, incoming_rx) = mpsc::unbounded::<Envelope>();
 
-        let socket = client_state.socket.clone();
-        run_cmd(socket.ssh_command(&remote_binary_path).arg("version")).await?;
+        let socket = connection.socket.clone();
+        let remote_binary_path = target.remote_binary_path.clone();
+        let this = cx.update(|cx| {
+            Self::new(
+                incoming_rx,
+                outgoing_tx,
+                spawn_process_tx,
+                Some(socket),
+                Some(remote_binary_path),
+                cx,
+            )
+        })?;
 
-        let mut remote_server_child = socket
-            .ssh_command(&format!(
-                "RUST_LOG={} {:?} run",
-                std::env::var("RUST_LOG").unwrap_or(String::new()),
-                remote_binary_path,
-            ))
-            .spawn()
-            .context("failed to spawn remote server")?;
-        let mut child_stderr = remote_server_child.stderr.take().unwrap();
-        let mut child_stdout = remote_server_child.stdout.take().unwrap();
-        let mut child_stdin = remote_server_child.stdin.take().unwrap();
-
-        let executor = cx.background_executor().clone();
-        executor.clone().spawn(async move {
-            let mut stdin_buffer = Vec::new();
-            let mut stdout_buffer = Vec::new();
-            let mut stderr_buffer = Vec::new();
-            let mut stderr_offset = 0;
-
-            loop {
-                stdout_buffer.resize(MESSAGE_LEN_SIZE, 0);
-                stderr_buffer.resize(stderr_offset + 1024, 0);
-
-                select_biased! {
-                    outgoing = outgoing_rx.next().fuse() => {
-                        let Some(outgoing) = outgoing else {
-                            return anyhow::Ok(());
-                        };
-
-                        write_message(&mut child_stdin, &mut stdin_buffer, outgoing).await?;
-                    }
+        let channels = IoChannels {
+            outgoing_rx,
+            spawn_process_rx,
+            incoming_tx,
+        };
+        cx.spawn(|cx| {
+            Self::maintain_connection(Arc::downgrade(&this), connection, channels, target, cx)
+        })
+        .detach();
 
-                    request = spawn_process_rx.next().fuse() => {
-                        let Some(request) = request else {
-                            return Ok(());
-                        };
-
-                        log::info!("spawn process: {:?}", request.command);
-                        let child = client_state.socket
-                            .ssh_command(&request.command)
-                            .spawn()
-                            .context("failed to create channel")?;
-                        request.process_tx.send(child).ok();
-                    }
+        Ok(this)
+    }
 
-                    result = child_stdout.read(&mut stdout_buffer).fuse() => {
-                        match result {
-                            Ok(len) => {
-                                if len == 0 {
-                                    child_stdin.close().await?;
-                                    let status = remote_server_child.status().await?;
-                                    if !status.success() {
-                                        log::info!("channel exited with status: {status:?}");
-                                    }
-                                    return Ok(());
-                                }
+    async fn maintain_connection(
+        this: Weak<Self>,
+        mut connection: RemoteServerConnection,
+        mut channels: IoChannels,
+        target: SshServerTarget,
+        mut cx: AsyncAppContext,
+    ) {
+        loop {
+            let (returned_channels, result) = cx
+                .background_executor()
+                .spawn(connection.run(channels))
+                .await;
+            channels = returned_channels;
+
+            let Some(session) = this.upgrade() else {
+                return;
+            };
+            match result {
+                Ok(()) => log::warn!("connection to remote server closed"),
+                Err(error) => log::error!("lost connection to remote server: {error:?}"),
+            }
 
-                                if len < stdout_buffer.len() {
-                                    child_stdout.read_exact(&mut stdout_buffer[len..]).await?;
-                                }
+            // Responses to in-flight requests are lost along with the connection.
+            // Messages queued from now on are delivered once we reconnect, and the
+            // server resynchronizes buffers and worktrees when it sees us again.
+            session.response_channels.lock().clear();
+            drop(session);
+
+            let mut delay = INITIAL_RECONNECT_DELAY;
+            let mut attempt = 0;
+            connection = loop {
+                attempt += 1;
+                if attempt > MAX_RECONNECT_ATTEMPTS {
+                    log::error!("giving up on reconnecting to remote server");
+                    return;
+                }
 
-                                let message_len = message_len_from_buffer(&stdout_buffer);
-                                match read_message_with_len(&mut child_stdout, &mut stdout_buffer, message_len).await {
-                                    Ok(envelope) => {
-                                        incoming_tx.unbounded_send(envelope).ok();
-                                    }
-                                    Err(error) => {
-                                        log::error!("error decoding message {error:?}");
-                                    }
-                                }
-                            }
-                            Err(error) => {
-                                Err(anyhow!("error reading stdout: {error:?}"))?;
-                            }
-                        }
-                    }
+                cx.background_executor().timer(delay).await;
+                if this.upgrade().is_none() {
+                    return;
+                }
 
-                    result = child_stderr.read(&mut stderr_buffer[stderr_offset..]).fuse() => {
-                        match result {
-                            Ok(len) => {
-                                stderr_offset += len;
-                                let mut start_ix = 0;
-                                while let Some(ix) = stderr_buffer[start_ix..stderr_offset].iter().position(|b| b == &b'\n') {
-                                    let line_ix = start_ix + ix;
-                                    let content = &stderr_buffer[start_ix..line_ix];
-                                    start_ix = line_ix + 1;
-                                    if let Ok(record) = serde_json::from_slice::<LogRecord>(&content) {
-                                        record.log(log::logger())
-                                    } else {
-                                        eprintln!("(remote) {}", String::from_utf8_lossy(content));
-                                    }
-                                }
-                                stderr_buffer.drain(0..start_ix);
-                                stderr_offset -= start_ix;
-                            }
-                            Err(error) => {
-                                Err(anyhow!("error reading stderr: {error:?}"))?;
-                            }
-                        }
-                    }
+                log::info!("reconnecting to remote server (attempt {attempt})");
+                match target.connect(&mut cx).await {
+                    Ok(connection) => break connection,
+                    Err(error) => log::error!("failed to reconnect: {error:?}"),
+                }
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+            };
+
+            let Some(session) = this.upgrade() else {
+                return;
+            };
+            let socket = connection.socket.clone();
+            *session.client_socket.lock() = Some(socket.clone());
+            let added_port_forwards = session.added_port_forwards.lock().clone();
+            drop(session);
+            // Dev containers relay each connection to a forwarded port on its
+            // own, so their forwards outlive the connection to the server.
+            if let RemoteSocket::Ssh(socket) = socket {
+                for forward in added_port_forwards {
+                    socket
+                        .control_port_forward("forward", &forward)
+                        .await
+                        .log_err();
                 }
             }
-        }).detach();
-
-        cx.update(|cx| Self::new(incoming_rx, outgoing_tx, spawn_process_tx, Some(socket), cx))
+            target.delegate.set_status(None, &mut cx);
+        }
     }
 
     pub fn server(
@@ -284,7 +369,7 @@ impl SshSession {
         cx: &AppContext,
     ) -> Arc<SshSession> {
         let (tx, _rx) = mpsc::unbounded();
-        Self::new(incoming_rx, outgoing_tx, tx, None, cx)
+        Self::new(incoming_rx, outgoing_tx, tx, None, None, cx)
     }
 
     #[cfg(any(test, feature = "test-support"))]
@@ -302,6 +387,7 @@ impl SshSession {
                     client_to_server_tx,
                     tx.clone(),
                     None, // todo()
+                    None,
                     cx,
                 )
             }),
@@ -311,6 +397,7 @@ impl SshSession {
                     server_to_client_tx,
                     tx.clone(),
                     None,
+                    None,
                     cx,
                 )
             }),
@@ -321,7 +408,8 @@ impl SshSession {
         mut incoming_rx: mpsc::UnboundedReceiver<Envelope>,
         outgoing_tx: mpsc::UnboundedSender<Envelope>,
         spawn_process_tx: mpsc::UnboundedSender<SpawnRequest>,
-        client_socket: Option<SshSocket>,
+        client_socket: Option<RemoteSocket>,
+        remote_binary_path: Option<PathBuf>,
         cx: &AppContext,
     ) -> Arc<SshSession> {
         let this = Arc::new(Self {
@@ -329,7 +417,11 @@ impl SshSession {
             response_channels: ResponseChannels::default(),
             outgoing_tx,
             spawn_process_tx,
-            client_socket,
+            client_socket: Mutex::new(client_socket),
+            remote_binary_path,
+            added_port_forwards: Default::default(),
+            container_port_forwards: Default::default(),
+            executor: cx.background_executor().clone(),
             message_handlers: Default::default(),
         });
 
@@ -398,11 +490,16 @@ impl SshSession {
         _request_type: &'static str,
     ) -> impl 'static + Future<Output = Result<proto::Envelope>> {
         envelope.id = self.next_message_id.fetch_add(1, SeqCst);
+        let envelope_id = envelope.id;
         let (tx, rx) = oneshot::channel();
         self.response_channels
             .lock()
-            .insert(MessageId(envelope.id), tx);
-        self.outgoing_tx.unbounded_send(envelope).ok();
+            .insert(MessageId(envelope_id), tx);
+        if self.outgoing_tx.unbounded_send(envelope).is_err() {
+            self.response_channels
+                .lock()
+                .remove(&MessageId(envelope_id));
+        }
         async move { Ok(rx.await.context("connection lost")?.0) }
     }
 
@@ -424,7 +521,121 @@ impl SshSession {
     }
 
     pub fn ssh_args(&self) -> Vec<String> {
-        self.client_socket.as_ref().unwrap().ssh_args()
+        match self.client_socket.lock().as_ref().unwrap() {
+            RemoteSocket::Ssh(socket) => socket.ssh_args(),
+            RemoteSocket::Container(_) | RemoteSocket::Wsl(_) => Vec::new(),
+        }
+    }
+
+    /// The dev container this session's server runs in, if any.
+    pub fn dev_container(&self) -> Option<DevContainer> {
+        match self.client_socket.lock().as_ref()? {
+            RemoteSocket::Container(container) => Some(container.clone()),
+            RemoteSocket::Ssh(_) | RemoteSocket::Wsl(_) => None,
+        }
+    }
+
+    /// The WSL distro this session's server runs in, if any.
+    pub fn wsl_distro(&self) -> Option<WslDistro> {
+        match self.client_socket.lock().as_ref()? {
+            RemoteSocket::Wsl(distro) => Some(distro.clone()),
+            RemoteSocket::Ssh(_) | RemoteSocket::Container(_) => None,
+        }
+    }
+
+    pub fn connection_string(&self) -> Option<String> {
+        self.client_socket
+            .lock()
+            .as_ref()
+            .map(|socket| match socket {
+                RemoteSocket::Ssh(socket) => socket.connection_options.connection_string(),
+                RemoteSocket::Container(container) => container.name.clone(),
+                RemoteSocket::Wsl(distro) => format!("wsl {}", distro.name),
+            })
+    }
+
+    /// The ports forwarded over this session, both those configured for the
+    /// connection and those added since.
+    pub fn port_forwards(&self) -> Vec<SshPortForward> {
+        let mut forwards = match self.client_socket.lock().as_ref() {
+            Some(RemoteSocket::Ssh(socket)) => socket.connection_options.port_forwards.clone(),
+            _ => Vec::new(),
+        };
+        forwards.extend(self.added_port_forwards.lock().iter().cloned());
+        forwards
+    }
+
+    /// Starts forwarding a local port over the existing connection. The
+    /// forward is restored whenever the session reconnects.
+    pub async fn add_port_forward(&self, forward: SshPortForward) -> Result<()> {
+        match self.socket()? {
+            RemoteSocket::Ssh(socket) => socket.control_port_forward("forward", &forward).await?,
+            RemoteSocket::Container(container) => {
+                let remote_binary_path = self
+                    .remote_binary_path
+                    .clone()
+                    .context("the remote server binary isn't known")?;
+                let task = container
+                    .forward_port(&forward, remote_binary_path, &self.executor)
+                    .await?;
+                self.container_port_forwards
+                    .lock()
+                    .insert(forward.local_port, task);
+            }
+            RemoteSocket::Wsl(_) => {
+                return Err(anyhow!(
+                    "ports of WSL distros are forwarded to localhost by Windows"
+                ))
+            }
+        }
+        self.added_port_forwards.lock().push(forward);
+        Ok(())
+    }
+
+    pub async fn remove_port_forward(&self, local_port: u16) -> Result<()> {
+        let forward = {
+            let mut forwards = self.added_port_forwards.lock();
+            let ix = forwards
+                .iter()
+                .position(|forward| forward.local_port == local_port)
+                .context("port is not forwarded")?;
+            forwards.remove(ix)
+        };
+        match self.socket()? {
+            RemoteSocket::Ssh(socket) => socket.control_port_forward("cancel", &forward).await,
+            RemoteSocket::Container(_) => {
+                self.container_port_forwards.lock().remove(&local_port);
+                Ok(())
+            }
+            RemoteSocket::Wsl(_) => Ok(()),
+        }
+    }
+
+    /// Lists the TCP ports that processes on the remote host are listening on.
+    pub async fn listening_ports(&self) -> Result<Vec<u16>> {
+        let socket = self.socket()?;
+        let proc_net_tcp =
+            run_cmd(&mut socket.command("cat /proc/net/tcp /proc/net/tcp6 2>/dev/null || true"))
+                .await?;
+        if !proc_net_tcp.trim().is_empty() {
+            return Ok(listening_ports::parse_proc_net_tcp(&proc_net_tcp));
+        }
+
+        // There's no procfs on macOS.
+        let lsof = run_cmd(
+            socket
+                .command("lsof")
+                .args(["-nP", "-iTCP", "-sTCP:LISTEN", "-Fn"]),
+        )
+        .await?;
+        Ok(listening_ports::parse_lsof(&lsof))
+    }
+
+    fn socket(&self) -> Result<RemoteSocket> {
+        self.client_socket
+            .lock()
+            .clone()
+            .context("not connected to an ssh host")
     }
 
     pub fn add_message_handler<M, E, H, F>(&self, entity: WeakModel<E>, handler: H)
@@ -508,7 +719,6 @@ impl SshClientState {
     ) -> Result<Self> {
         use futures::{io::BufReader, AsyncBufReadExt as _};
         use smol::{fs::unix::PermissionsExt as _, net::unix::UnixListener};
-        use util::ResultExt as _;
 
         delegate.set_status(Some("connecting"), cx);
 
@@ -565,6 +775,12 @@ impl SshClientState {
             .env("SSH_ASKPASS", &askpass_script_path)
             .args(["-N", "-o", "ControlMaster=yes", "-o"])
             .arg(format!("ControlPath={}", socket_path.display()))
+            .args(
+                connection_options
+                    .port_forwards
+                    .iter()
+                    .flat_map(|forward| ["-L".to_string(), forward.ssh_arg()]),
+            )
             .arg(&url)
             .spawn()?;
 
@@ -594,6 +810,16 @@ impl SshClientState {
             _temp_dir: temp_dir,
         })
     }
+}
+
+impl RemoteSocket {
+    fn command<S: AsRef<OsStr>>(&self, program: S) -> process::Command {
+        match self {
+            RemoteSocket::Ssh(socket) => socket.ssh_command(program),
+            RemoteSocket::Container(container) => container.command(program),
+            RemoteSocket::Wsl(distro) => distro.command(program),
+        }
+    }
 
     async fn ensure_server_binary(
         &self,
@@ -607,14 +833,12 @@ impl SshClientState {
         dst_path_gz.set_extension("gz");
 
         if let Some(parent) = dst_path.parent() {
-            run_cmd(self.socket.ssh_command("mkdir").arg("-p").arg(parent)).await?;
+            run_cmd(self.command("mkdir").arg("-p").arg(parent)).await?;
         }
 
         let mut server_binary_exists = false;
         if cfg!(not(debug_assertions)) {
-            if let Ok(installed_version) =
-                run_cmd(self.socket.ssh_command(&dst_path).arg("version")).await
-            {
+            if let Ok(installed_version) = run_cmd(self.command(&dst_path).arg("version")).await {
                 if installed_version.trim() == version.to_string() {
                     server_binary_exists = true;
                 }
@@ -639,18 +863,11 @@ impl SshClientState {
         log::info!("uploaded remote development server in {:?}", t0.elapsed());
 
         delegate.set_status(Some("extracting remote development server"), cx);
-        run_cmd(
-            self.socket
-                .ssh_command("gunzip")
-                .arg("--force")
-                .arg(&dst_path_gz),
-        )
-        .await?;
+        run_cmd(self.command("gunzip").arg("--force").arg(&dst_path_gz)).await?;
 
         delegate.set_status(Some("unzipping remote development server"), cx);
         run_cmd(
-            self.socket
-                .ssh_command("chmod")
+            self.command("chmod")
                 .arg(format!("{:o}", server_mode))
                 .arg(&dst_path),
         )
@@ -660,8 +877,8 @@ impl SshClientState {
     }
 
     async fn query_platform(&self) -> Result<SshPlatform> {
-        let os = run_cmd(self.socket.ssh_command("uname").arg("-s")).await?;
-        let arch = run_cmd(self.socket.ssh_command("uname").arg("-m")).await?;
+        let os = run_cmd(self.command("uname").arg("-s")).await?;
+        let arch = run_cmd(self.command("uname").arg("-m")).await?;
 
         let os = match os.trim() {
             "Darwin" => "macos",
@@ -679,14 +896,202 @@ impl SshClientState {
         Ok(SshPlatform { os, arch })
     }
 
+    async fn upload_file(&self, src_path: &Path, dest_path: &Path) -> Result<()> {
+        match self {
+            RemoteSocket::Ssh(socket) => socket.upload_file(src_path, dest_path).await,
+            RemoteSocket::Container(container) => container.upload_file(src_path, dest_path).await,
+            RemoteSocket::Wsl(distro) => distro.upload_file(src_path, dest_path).await,
+        }
+    }
+}
+
+impl SshServerTarget {
+    /// Connects to the remote host, uploads or upgrades the server binary if
+    /// needed, and starts a proxy to the server daemon, spawning the daemon
+    /// if it isn't already running.
+    async fn connect(&self, cx: &mut AsyncAppContext) -> Result<RemoteServerConnection> {
+        let (client_state, socket) = match &self.host {
+            RemoteHost::Ssh(connection_options) => {
+                let client_state =
+                    SshClientState::new(connection_options.clone(), self.delegate.clone(), cx)
+                        .await?;
+                let socket = RemoteSocket::Ssh(client_state.socket.clone());
+                (Some(client_state), socket)
+            }
+            RemoteHost::Container(container) => (None, RemoteSocket::Container(container.clone())),
+            RemoteHost::Wsl(distro) => (None, RemoteSocket::Wsl(distro.clone())),
+        };
+
+        let platform = socket.query_platform().await?;
+        let (local_binary_path, version) = self.delegate.get_server_binary(platform, cx).await??;
+        socket
+            .ensure_server_binary(
+                &self.delegate,
+                &local_binary_path,
+                &self.remote_binary_path,
+                version,
+                cx,
+            )
+            .await?;
+
+        run_cmd(socket.command(&self.remote_binary_path).arg("version")).await?;
+
+        let proxy_process = socket
+            .command(&format!(
+                "RUST_LOG={} {:?} proxy {:?}",
+                std::env::var("RUST_LOG").unwrap_or(String::new()),
+                self.remote_binary_path,
+                self.server_socket_path,
+            ))
+            .spawn()
+            .context("failed to spawn remote server")?;
+
+        Ok(RemoteServerConnection {
+            _client_state: client_state,
+            socket,
+            proxy_process,
+        })
+    }
+}
+
+impl RemoteServerConnection {
+    /// Relays messages until the connection is lost or the session is dropped,
+    /// handing the channels back so that they can be used by the next connection.
+    async fn run(mut self, mut channels: IoChannels) -> (IoChannels, Result<()>) {
+        let result = self.relay_messages(&mut channels).await;
+        (channels, result)
+    }
+
+    async fn relay_messages(&mut self, channels: &mut IoChannels) -> Result<()> {
+        let mut child_stderr = self.proxy_process.stderr.take().unwrap();
+        let mut child_stdout = self.proxy_process.stdout.take().unwrap();
+        let mut child_stdin = self.proxy_process.stdin.take().unwrap();
+
+        let mut stdin_buffer = Vec::new();
+        let mut stdout_buffer = Vec::new();
+        let mut stderr_buffer = Vec::new();
+        let mut stderr_offset = 0;
+
+        loop {
+            stdout_buffer.resize(MESSAGE_LEN_SIZE, 0);
+            stderr_buffer.resize(stderr_offset + 1024, 0);
+
+            select_biased! {
+                outgoing = channels.outgoing_rx.next().fuse() => {
+                    let Some(outgoing) = outgoing else {
+                        return Ok(());
+                    };
+
+                    write_message(&mut child_stdin, &mut stdin_buffer, outgoing).await?;
+                }
+
+                request = channels.spawn_process_rx.next().fuse() => {
+                    let Some(request) = request else {
+                        return Ok(());
+                    };
+
+                    log::info!("spawn process: {:?}", request.command);
+                    let child = self.socket
+                        .command(&request.command)
+                        .spawn()
+                        .context("failed to create channel")?;
+                    request.process_tx.send(child).ok();
+                }
+
+                result = child_stdout.read(&mut stdout_buffer).fuse() => {
+                    match result {
+                        Ok(len) => {
+                            if len == 0 {
+                                child_stdin.close().await?;
+                                let status = self.proxy_process.status().await?;
+                                if !status.success() {
+                                    log::info!("channel exited with status: {status:?}");
+                                }
+                                return Ok(());
+                            }
+
+                            if len < stdout_buffer.len() {
+                                child_stdout.read_exact(&mut stdout_buffer[len..]).await?;
+                            }
+
+                            let message_len = message_len_from_buffer(&stdout_buffer);
+                            match read_message_with_len(&mut child_stdout, &mut stdout_buffer, message_len).await {
+                                Ok(envelope) => {
+                                    channels.incoming_tx.unbounded_send(envelope).ok();
+                                }
+                                Err(error) => {
+                                    log::error!("error decoding message {error:?}");
+                                }
+                            }
+                        }
+                        Err(error) => {
+                            Err(anyhow!("error reading stdout: {error:?}"))?;
+                        }
+                    }
+                }
+
+                result = child_stderr.read(&mut stderr_buffer[stderr_offset..]).fuse() => {
+                    match result {
+                        Ok(len) => {
+                            stderr_offset += len;
+                            let mut start_ix = 0;
+                            while let Some(ix) = stderr_buffer[start_ix..stderr_offset].iter().position(|b| b == &b'\n') {
+                                let line_ix = start_ix + ix;
+                                let content = &stderr_buffer[start_ix..line_ix];
+                                start_ix = line_ix + 1;
+                                if let Ok(record) = serde_json::from_slice::<LogRecord>(&content) {
+                                    record.log(log::logger())
+                                } else {
+                                    eprintln!("(remote) {}", String::from_utf8_lossy(content));
+                                }
+                            }
+                            stderr_buffer.drain(0..start_ix);
+                            stderr_offset -= start_ix;
+                        }
+                        Err(error) => {
+                            Err(anyhow!("error reading stderr: {error:?}"))?;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl SshSocket {
+    fn ssh_command<S: AsRef<OsStr>>(&self, program: S) -> process::Command {
+        let mut command = process::Command::new("ssh");
+        self.ssh_options(&mut command)
+            .arg(self.connection_options.ssh_url())
+            .arg(program);
+        command
+    }
+
+    fn ssh_options<'a>(&self, command: &'a mut process::Command) -> &'a mut process::Command {
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .args(["-o", "ControlMaster=no", "-o"])
+            .arg(format!("ControlPath={}", self.socket_path.display()))
+    }
+
+    async fn control_port_forward(&self, operation: &str, forward: &SshPortForward) -> Result<()> {
+        let mut command = process::Command::new("ssh");
+        self.ssh_options(&mut command)
+            .args(["-O", operation, "-L"])
+            .arg(forward.ssh_arg())
+            .arg(self.connection_options.ssh_url());
+        run_cmd(&mut command).await?;
+        Ok(())
+    }
+
     async fn upload_file(&self, src_path: &Path, dest_path: &Path) -> Result<()> {
         let mut command = process::Command::new("scp");
         let output = self
-            .socket
             .ssh_options(&mut command)
             .args(
-                self.socket
-                    .connection_options
+                self.connection_options
                     .port
                     .map(|port| vec!["-P".to_string(), port.to_string()])
                     .unwrap_or_default(),
@@ -694,7 +1099,7 @@ impl SshClientState {
             .arg(&src_path)
             .arg(&format!(
                 "{}:{}",
-                self.socket.connection_options.scp_url(),
+                self.connection_options.scp_url(),
                 dest_path.display()
             ))
             .output()
@@ -711,25 +1116,6 @@ impl SshClientState {
             ))
         }
     }
-}
-
-impl SshSocket {
-    fn ssh_command<S: AsRef<OsStr>>(&self, program: S) -> process::Command {
-        let mut command = process::Command::new("ssh");
-        self.ssh_options(&mut command)
-            .arg(self.connection_options.ssh_url())
-            .arg(program);
-        command
-    }
-
-    fn ssh_options<'a>(&self, command: &'a mut process::Command) -> &'a mut process::Command {
-        command
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .args(["-o", "ControlMaster=no", "-o"])
-            .arg(format!("ControlPath={}", self.socket_path.display()))
-    }
 
     fn ssh_args(&self) -> Vec<String> {
         vec![
@@ -742,7 +1128,24 @@ impl SshSocket {
     }
 }
 
-async fn run_cmd(command: &mut process::Command) -> Result<String> {
+/// Each client session gets its own server daemon, reachable through a unix
+/// socket next to the server binary, so that it can be reconnected to.
+fn server_socket_path(remote_binary_path: &Path) -> PathBuf {
+    let session_id = format!(
+        "{}-{}",
+        std::process::id(),
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis())
+    );
+    remote_binary_path
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join("sessions")
+        .join(format!("{session_id}.sock"))
+}
+
+pub(crate) async fn run_cmd(command: &mut process::Command) -> Result<String> {
     let output = command.output().await?;
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
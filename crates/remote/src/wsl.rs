@@ -0,0 +1,130 @@
+//! WSL: projects in a Linux distro on Windows, reached through `\\wsl$` paths,
+//! with the remote server running inside the distro.
+
+use std::{ffi::OsStr, path::Path};
+
+use anyhow::{anyhow, Result};
+use smol::process::{self, Stdio};
+
+/// The hosts Windows serves the file systems of WSL distros from.
+const WSL_HOSTS: [&str; 2] = ["wsl$", "wsl.localhost"];
+
+/// A WSL distro, which commands are run in with `wsl.exe`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WslDistro {
+    pub name: String,
+}
+
+impl WslDistro {
+    /// Splits a Windows path into a distro and its path inside the distro, if
+    /// it points into a WSL distro, such as `\\wsl$\Ubuntu\home\me\project`.
+    pub fn parse_path(path: &str) -> Option<(Self, String)> {
+        let path = path.replace('/', "\\");
+        let path = path
+            .strip_prefix(r"\\?\UNC\")
+            .or_else(|| path.strip_prefix(r"UNC\"))
+            .or_else(|| path.strip_prefix(r"\\"))?;
+        let (host, path) = path.split_once('\\')?;
+        if !WSL_HOSTS
+            .iter()
+            .any(|wsl_host| host.eq_ignore_ascii_case(wsl_host))
+        {
+            return None;
+        }
+
+        let (name, path) = path.split_once('\\').unwrap_or((path, ""));
+        if name.is_empty() {
+            return None;
+        }
+        let path = format!("/{}", path.trim_end_matches('\\').replace('\\', "/"));
+        Some((
+            Self {
+                name: name.to_string(),
+            },
+            path,
+        ))
+    }
+
+    /// The arguments to `wsl.exe` that run a shell command in the distro.
+    pub fn shell_args(&self) -> Vec<String> {
+        vec![
+            "--distribution".to_string(),
+            self.name.clone(),
+            "--".to_string(),
+            "sh".to_string(),
+            "-c".to_string(),
+        ]
+    }
+
+    /// Runs a shell command in the distro, from the home directory of the
+    /// default user, like ssh does. Arguments added to the returned command
+    /// are passed to the shell command.
+    pub(crate) fn command<S: AsRef<OsStr>>(&self, program: S) -> process::Command {
+        let mut command = process::Command::new("wsl.exe");
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .args(self.shell_args())
+            .arg(format!(
+                "cd && {} \"$@\"",
+                program.as_ref().to_string_lossy()
+            ))
+            .arg("sh");
+        command
+    }
+
+    pub(crate) async fn upload_file(&self, src_path: &Path, dest_path: &Path) -> Result<()> {
+        let file = std::fs::File::open(src_path)?;
+        let output = self
+            .command("cat >")
+            .arg(dest_path)
+            .stdin(Stdio::from(file))
+            .output()
+            .await?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "failed to upload file {} -> {}: {}",
+                src_path.display(),
+                dest_path.display(),
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_path() {
+        let ubuntu = WslDistro {
+            name: "Ubuntu".to_string(),
+        };
+        assert_eq!(
+            WslDistro::parse_path(r"\\wsl$\Ubuntu\home\me\project"),
+            Some((ubuntu.clone(), "/home/me/project".to_string()))
+        );
+        assert_eq!(
+            WslDistro::parse_path(r"\\wsl.localhost\Ubuntu\home\me\project\"),
+            Some((ubuntu.clone(), "/home/me/project".to_string()))
+        );
+        assert_eq!(
+            WslDistro::parse_path(r"\\?\UNC\WSL$\Ubuntu\home\me\src\main.rs:10:5"),
+            Some((ubuntu.clone(), "/home/me/src/main.rs:10:5".to_string()))
+        );
+        assert_eq!(
+            WslDistro::parse_path("//wsl$/Ubuntu"),
+            Some((ubuntu, "/".to_string()))
+        );
+
+        assert_eq!(WslDistro::parse_path(r"\\wsl$\"), None);
+        assert_eq!(WslDistro::parse_path(r"\\server\share\file.txt"), None);
+        assert_eq!(WslDistro::parse_path(r"C:\Users\me\project"), None);
+        assert_eq!(WslDistro::parse_path("/home/me/project"), None);
+    }
+}
@@ -1,22 +1,31 @@
 use anyhow::{anyhow, Result};
 use fs::Fs;
-use gpui::{AppContext, AsyncAppContext, Context, Model, ModelContext};
+use gpui::{AppContext, AsyncAppContext, Context, Model, ModelContext, Task};
+use http_client::HttpClient;
+use language::{
+    proto::{deserialize_version, serialize_operation, split_operations},
+    Buffer, BufferId, Event as BufferEvent, LanguageRegistry,
+};
 use project::{
-    buffer_store::BufferStore, search::SearchQuery, worktree_store::WorktreeStore, ProjectPath,
-    WorktreeId, WorktreeSettings,
+    buffer_store::{BufferStore, BufferStoreEvent},
+    lsp_store::LspStore,
+    search::SearchQuery,
+    worktree_store::WorktreeStore,
+    Project, ProjectPath, WorktreeId,
 };
 use remote::SshSession;
 use rpc::{
     proto::{self, AnyProtoClient, PeerId},
     TypedEnvelope,
 };
-use settings::{Settings as _, SettingsStore};
+use settings::SettingsStore;
 use smol::stream::StreamExt;
 use std::{
     path::{Path, PathBuf},
     sync::{atomic::AtomicUsize, Arc},
 };
-use worktree::Worktree;
+use util::ResultExt as _;
+use worktree::{File, Worktree};
 
 const PEER_ID: PeerId = PeerId { owner_id: 0, id: 0 };
 const PROJECT_ID: u64 = 0;
@@ -26,16 +35,25 @@ pub struct HeadlessProject {
     pub session: AnyProtoClient,
     pub worktree_store: Model<WorktreeStore>,
     pub buffer_store: Model<BufferStore>,
+    pub lsp_store: Model<LspStore>,
     pub next_entry_id: Arc<AtomicUsize>,
+    _maintain_buffer_languages: Task<()>,
 }
 
 impl HeadlessProject {
     pub fn init(cx: &mut AppContext) {
         cx.set_global(SettingsStore::new(cx));
-        WorktreeSettings::register(cx);
+        Project::init_settings(cx);
+        language::init(cx);
     }
 
-    pub fn new(session: Arc<SshSession>, fs: Arc<dyn Fs>, cx: &mut ModelContext<Self>) -> Self {
+    pub fn new(
+        session: Arc<SshSession>,
+        fs: Arc<dyn Fs>,
+        languages: Arc<LanguageRegistry>,
+        http_client: Arc<dyn HttpClient>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
         let this = cx.weak_model();
 
         let worktree_store = cx.new_model(|_| WorktreeStore::new(true, fs.clone()));
@@ -44,6 +62,22 @@ impl HeadlessProject {
             buffer_store.shared(PROJECT_ID, session.clone().into(), cx);
             buffer_store
         });
+        cx.subscribe(&buffer_store, Self::on_buffer_store_event)
+            .detach();
+        let maintain_buffer_languages = Self::maintain_buffer_languages(languages.clone(), cx);
+        let lsp_store = cx.new_model(|cx| {
+            let mut lsp_store = LspStore::new_local(
+                buffer_store.clone(),
+                worktree_store.clone(),
+                languages,
+                http_client,
+                fs.clone(),
+                cx,
+            );
+            lsp_store.shared(PROJECT_ID, session.clone().into(), cx);
+            lsp_store
+        });
+        LspStore::init_ssh_session(&session, &lsp_store);
 
         session.add_request_handler(this.clone(), Self::handle_list_remote_directory);
         session.add_request_handler(this.clone(), Self::handle_add_worktree);
@@ -81,7 +115,150 @@ impl HeadlessProject {
             fs,
             worktree_store,
             buffer_store,
+            lsp_store,
             next_entry_id: Default::default(),
+            _maintain_buffer_languages: maintain_buffer_languages,
+        }
+    }
+
+    /// Detects the languages of the buffers that have none when languages are
+    /// added to the registry, like those of extensions installed later.
+    fn maintain_buffer_languages(
+        languages: Arc<LanguageRegistry>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<()> {
+        let mut subscription = languages.subscribe();
+        cx.spawn(|this, mut cx| async move {
+            while let Some(()) = subscription.next().await {
+                let result = this.update(&mut cx, |this, cx| {
+                    let buffers = this
+                        .buffer_store
+                        .read(cx)
+                        .buffers()
+                        .filter(|buffer| {
+                            let language = buffer.read(cx).language();
+                            language.is_none() || language == Some(&*language::PLAIN_TEXT)
+                        })
+                        .collect::<Vec<_>>();
+                    this.lsp_store.update(cx, |lsp_store, cx| {
+                        for buffer in &buffers {
+                            lsp_store.detect_language_for_remotely_opened_buffer(buffer, cx);
+                        }
+                    });
+                });
+                if result.is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    /// Brings the client up to date after it reconnected, as the messages
+    /// sent while the connection was lost may not have reached it.
+    pub fn resync(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let worktrees = self.worktree_store.read(cx).worktrees().collect::<Vec<_>>();
+        for worktree in worktrees {
+            let session = self.session.clone();
+            worktree.update(cx, |worktree, cx| {
+                worktree.stop_observing_updates();
+                observe_worktree_updates(worktree, session, cx);
+            });
+        }
+
+        let session = self.session.clone();
+        let (buffers, _) = self.buffer_store.read(cx).buffer_version_info(cx);
+        cx.spawn(|this, mut cx| async move {
+            let response = session
+                .request(proto::SynchronizeBuffers {
+                    project_id: PROJECT_ID,
+                    buffers,
+                })
+                .await?;
+            let operations = this.update(&mut cx, |this, cx| {
+                let buffer_store = this.buffer_store.read(cx);
+                response
+                    .buffers
+                    .into_iter()
+                    .filter_map(|buffer| {
+                        let buffer_id = BufferId::new(buffer.id).ok()?;
+                        let client_version = deserialize_version(&buffer.version);
+                        let operations = buffer_store
+                            .get(buffer_id)?
+                            .read(cx)
+                            .serialize_ops(Some(client_version), cx);
+                        Some((buffer_id, operations))
+                    })
+                    .collect::<Vec<_>>()
+            })?;
+            for (buffer_id, operations) in operations {
+                for chunk in split_operations(operations.await) {
+                    session
+                        .request(proto::UpdateBuffer {
+                            project_id: PROJECT_ID,
+                            buffer_id: buffer_id.to_proto(),
+                            operations: chunk,
+                        })
+                        .await?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn on_buffer_store_event(
+        &mut self,
+        _: Model<BufferStore>,
+        event: &BufferStoreEvent,
+        cx: &mut ModelContext<Self>,
+    ) {
+        match event {
+            BufferStoreEvent::BufferAdded(buffer) => {
+                self.lsp_store.update(cx, |lsp_store, cx| {
+                    lsp_store.register_remotely_opened_buffer(buffer, cx)
+                });
+                cx.subscribe(buffer, Self::on_buffer_event).detach();
+            }
+            BufferStoreEvent::BufferChangedFilePath { buffer, old_file } => {
+                self.lsp_store.update(cx, |lsp_store, cx| {
+                    if let Some(old_file) = File::from_dyn(old_file.as_ref()) {
+                        lsp_store.unregister_buffer_from_language_servers(buffer, old_file, cx);
+                    }
+                    lsp_store.register_remotely_opened_buffer(buffer, cx);
+                });
+            }
+            BufferStoreEvent::BufferDropped(_) => {}
+        }
+    }
+
+    fn on_buffer_event(
+        &mut self,
+        buffer: Model<Buffer>,
+        event: &BufferEvent,
+        cx: &mut ModelContext<Self>,
+    ) {
+        match event {
+            // Edits made by the language servers, and the diagnostics they
+            // report, are sent to the client.
+            BufferEvent::Operation(operation) => {
+                self.session
+                    .send(proto::UpdateBuffer {
+                        project_id: PROJECT_ID,
+                        buffer_id: buffer.read(cx).remote_id().to_proto(),
+                        operations: vec![serialize_operation(operation)],
+                    })
+                    .log_err();
+            }
+            BufferEvent::Edited { .. } => {
+                self.lsp_store.update(cx, |lsp_store, cx| {
+                    lsp_store.on_buffer_edited(buffer, cx);
+                });
+            }
+            BufferEvent::Saved => {
+                self.lsp_store.update(cx, |lsp_store, cx| {
+                    lsp_store.on_buffer_saved(buffer, cx);
+                });
+            }
+            _ => {}
         }
     }
 
@@ -109,10 +286,7 @@ impl HeadlessProject {
                 worktree_store.add(&worktree, cx);
             });
             worktree.update(cx, |worktree, cx| {
-                worktree.observe_updates(0, cx, move |update| {
-                    session.send(update).ok();
-                    futures::future::ready(true)
-                });
+                observe_worktree_updates(worktree, session, cx);
                 proto::AddWorktreeResponse {
                     worktree_id: worktree.id().to_proto(),
                 }
@@ -207,3 +381,14 @@ impl HeadlessProject {
         Ok(proto::ListRemoteDirectoryResponse { entries })
     }
 }
+
+fn observe_worktree_updates(
+    worktree: &mut Worktree,
+    session: AnyProtoClient,
+    cx: &mut ModelContext<Worktree>,
+) {
+    worktree.observe_updates(PROJECT_ID, cx, move |update| {
+        session.send(update).ok();
+        futures::future::ready(true)
+    });
+}
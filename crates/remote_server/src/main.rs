@@ -1,22 +1,44 @@
 #![cfg_attr(target_os = "windows", allow(unused, dead_code))]
 
+use anyhow::{Context as _, Result};
 use fs::RealFs;
-use futures::channel::mpsc;
-use gpui::Context as _;
+use futures::{channel::mpsc, select_biased, FutureExt as _};
+use gpui::{BackgroundExecutor, Context as _, Task};
+use http_client::{HttpClient, HttpClientWithProxy};
+use language::LanguageRegistry;
+use node_runtime::RealNodeRuntime;
 use remote::{
     json_log::LogRecord,
     protocol::{read_message, write_message},
     SshSession,
 };
 use remote_server::HeadlessProject;
-use smol::{io::AsyncWriteExt, stream::StreamExt as _, Async};
+use rpc::proto::Envelope;
+use smol::{
+    io::{AsyncReadExt as _, AsyncWriteExt},
+    net::{
+        unix::{UnixListener, UnixStream},
+        TcpStream,
+    },
+    stream::StreamExt as _,
+    Async,
+};
 use std::{
     env,
+    fs::File,
     io::{self, Write},
-    mem, process,
+    path::{Path, PathBuf},
+    process,
     sync::Arc,
+    time::Duration,
 };
 
+/// How long the server keeps running without a client, waiting for it to reconnect.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// How long the proxy waits for a newly spawned server to start listening.
+const SERVER_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[cfg(windows)]
 fn main() {
     unimplemented!()
@@ -25,72 +47,278 @@ fn main() {
 #[cfg(not(windows))]
 fn main() {
     env::set_var("RUST_BACKTRACE", "1");
+
+    let mut args = std::env::args().skip(1);
+    let subcommand = args.next();
+    let argument = args.next();
+    match (subcommand.as_deref(), argument) {
+        (Some("run"), Some(socket_path)) => run_server(PathBuf::from(socket_path)),
+        (Some("proxy"), Some(socket_path)) => {
+            init_logging(Box::new(io::stderr()));
+            if let Err(error) = run_proxy(Path::new(&socket_path)) {
+                log::error!("proxy failed: {error:?}");
+                process::exit(1);
+            }
+        }
+        (Some("forward-port"), Some(address)) => {
+            init_logging(Box::new(io::stderr()));
+            if let Err(error) = run_port_relay(&address) {
+                log::error!("port relay failed: {error:?}");
+                process::exit(1);
+            }
+        }
+        (Some("version"), _) => {
+            println!("{}", env!("ZED_PKG_VERSION"));
+        }
+        _ => {
+            eprintln!("usage: remote <run SOCKET|proxy SOCKET|forward-port HOST:PORT|version>");
+            process::exit(1);
+        }
+    }
+}
+
+fn init_logging(target: Box<dyn Write + Send>) {
     env_logger::builder()
         .format(|buf, record| {
             serde_json::to_writer(&mut *buf, &LogRecord::new(&record))?;
             buf.write_all(b"\n")?;
             Ok(())
         })
+        .target(env_logger::Target::Pipe(target))
         .init();
+}
 
-    let subcommand = std::env::args().nth(1);
-    match subcommand.as_deref() {
-        Some("run") => {}
-        Some("version") => {
-            println!("{}", env!("ZED_PKG_VERSION"));
-            return;
-        }
-        _ => {
-            eprintln!("usage: remote <run|version>");
-            process::exit(1);
-        }
+/// Runs the server as a daemon listening on the given socket. Clients reach it
+/// through `proxy`, and may disconnect and reconnect without losing the state
+/// of the project.
+fn run_server(socket_path: PathBuf) {
+    match File::create(socket_path.with_extension("log")) {
+        Ok(log_file) => init_logging(Box::new(log_file)),
+        Err(_) => init_logging(Box::new(io::stderr())),
     }
 
     gpui::App::headless().run(move |cx| {
         HeadlessProject::init(cx);
 
         let (incoming_tx, incoming_rx) = mpsc::unbounded();
-        let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded();
+        let (outgoing_tx, outgoing_rx) = mpsc::unbounded();
 
-        let mut stdin = Async::new(io::stdin()).unwrap();
-        let mut stdout = Async::new(io::stdout()).unwrap();
+        let http_client: Arc<dyn HttpClient> = Arc::new(HttpClientWithProxy::new(None, None));
+        let node_runtime = RealNodeRuntime::new(http_client.clone());
+        let mut languages =
+            LanguageRegistry::new(Task::ready(()), cx.background_executor().clone());
+        languages.set_language_server_download_dir(paths::languages_dir().clone());
+        let languages = Arc::new(languages);
+        languages::init(languages.clone(), node_runtime, cx);
 
         let session = SshSession::server(incoming_rx, outgoing_tx, cx);
         let project = cx.new_model(|cx| {
             HeadlessProject::new(
-                session.clone(),
+                session,
                 Arc::new(RealFs::new(Default::default(), None)),
+                languages,
+                http_client,
                 cx,
             )
         });
 
-        cx.background_executor()
-            .spawn(async move {
-                let mut output_buffer = Vec::new();
-                while let Some(message) = outgoing_rx.next().await {
-                    write_message(&mut stdout, &mut output_buffer, message).await?;
-                    stdout.flush().await?;
-                }
-                anyhow::Ok(())
-            })
-            .detach();
-
-        cx.background_executor()
-            .spawn(async move {
-                let mut input_buffer = Vec::new();
-                loop {
-                    let message = match read_message(&mut stdin, &mut input_buffer).await {
-                        Ok(message) => message,
+        std::fs::remove_file(&socket_path).ok();
+        let listener = match UnixListener::bind(&socket_path) {
+            Ok(listener) => listener,
+            Err(error) => {
+                log::error!("failed to listen on {socket_path:?}: {error:?}");
+                process::exit(1);
+            }
+        };
+
+        cx.spawn(|mut cx| async move {
+            let mut outgoing_rx = outgoing_rx;
+            let mut is_first_connection = true;
+            loop {
+                let stream = select_biased! {
+                    connection = listener.accept().fuse() => match connection {
+                        Ok((stream, _)) => stream,
                         Err(error) => {
-                            log::warn!("error reading message: {:?}", error);
-                            process::exit(0);
+                            log::error!("failed to accept connection: {error:?}");
+                            continue;
                         }
-                    };
-                    incoming_tx.unbounded_send(message).ok();
+                    },
+                    _ = cx.background_executor().timer(IDLE_TIMEOUT).fuse() => {
+                        log::info!("no client connected for {IDLE_TIMEOUT:?}, exiting");
+                        std::fs::remove_file(&socket_path).ok();
+                        process::exit(0);
+                    }
+                };
+
+                log::info!("client connected");
+                if !is_first_connection {
+                    project
+                        .update(&mut cx, |project, cx| {
+                            project.resync(cx).detach_and_log_err(cx)
+                        })
+                        .ok();
                 }
-            })
-            .detach();
+                is_first_connection = false;
 
-        mem::forget(project);
+                serve_client(
+                    stream,
+                    &incoming_tx,
+                    &mut outgoing_rx,
+                    cx.background_executor().clone(),
+                )
+                .await;
+                log::info!("client disconnected");
+            }
+        })
+        .detach();
     });
 }
+
+/// Relays messages between a connected client and the session until either
+/// side of the connection fails.
+async fn serve_client(
+    stream: UnixStream,
+    incoming_tx: &mpsc::UnboundedSender<Envelope>,
+    outgoing_rx: &mut mpsc::UnboundedReceiver<Envelope>,
+    executor: BackgroundExecutor,
+) {
+    let mut reader = stream.clone();
+    let mut writer = stream;
+
+    let incoming_tx = incoming_tx.clone();
+    let mut read_task = executor
+        .spawn(async move {
+            let mut input_buffer = Vec::new();
+            loop {
+                match read_message(&mut reader, &mut input_buffer).await {
+                    Ok(message) => incoming_tx.unbounded_send(message).ok(),
+                    Err(error) => return error,
+                };
+            }
+        })
+        .fuse();
+
+    let mut output_buffer = Vec::new();
+    loop {
+        select_biased! {
+            error = read_task => {
+                log::warn!("error reading message: {error:?}");
+                return;
+            }
+            message = outgoing_rx.next().fuse() => {
+                let Some(message) = message else {
+                    process::exit(0);
+                };
+                if let Err(error) = write_message(&mut writer, &mut output_buffer, message).await {
+                    log::warn!("error writing message: {error:?}");
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Connects stdin and stdout to the server listening on the given socket,
+/// starting the server if it isn't running yet.
+fn run_proxy(socket_path: &Path) -> Result<()> {
+    smol::block_on(async {
+        let stream = match UnixStream::connect(socket_path).await {
+            Ok(stream) => stream,
+            Err(_) => {
+                if let Some(parent) = socket_path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                spawn_server(socket_path)?;
+                connect_to_new_server(socket_path).await?
+            }
+        };
+
+        let mut stdin = Async::new(io::stdin())?;
+        let mut stdout = Async::new(io::stdout())?;
+        let mut reader = stream.clone();
+        let mut writer = stream;
+
+        let client_to_server = async {
+            futures::io::copy(&mut stdin, &mut writer).await?;
+            writer.close().await
+        };
+        let server_to_client = async {
+            futures::io::copy(&mut reader, &mut stdout).await?;
+            stdout.flush().await
+        };
+        futures::future::select(Box::pin(client_to_server), Box::pin(server_to_client))
+            .await
+            .factor_first()
+            .0?;
+        Ok(())
+    })
+}
+
+/// Connects stdin and stdout to the given TCP address, which lets the client
+/// forward ports of hosts that can't forward them on demand, like dev containers.
+fn run_port_relay(address: &str) -> Result<()> {
+    smol::block_on(async {
+        let stream = TcpStream::connect(address)
+            .await
+            .with_context(|| format!("failed to connect to {address}"))?;
+
+        let mut stdin = Async::new(io::stdin())?;
+        let mut stdout = Async::new(io::stdout())?;
+        let mut reader = stream.clone();
+        let mut writer = stream;
+
+        // Once stdin ends, the response to what was sent may still be read.
+        let client_to_server = async {
+            futures::io::copy(&mut stdin, &mut writer).await?;
+            writer.close().await?;
+            futures::future::pending::<io::Result<()>>().await
+        };
+        let server_to_client = async {
+            let mut buffer = [0; 8192];
+            loop {
+                let len = reader.read(&mut buffer).await?;
+                if len == 0 {
+                    return io::Result::Ok(());
+                }
+                // Stdout is line buffered, and what's relayed is rarely lines.
+                stdout.write_all(&buffer[..len]).await?;
+                stdout.flush().await?;
+            }
+        };
+        futures::future::select(Box::pin(client_to_server), Box::pin(server_to_client))
+            .await
+            .factor_first()
+            .0?;
+        Ok(())
+    })
+}
+
+fn spawn_server(socket_path: &Path) -> Result<()> {
+    use std::os::unix::process::CommandExt as _;
+
+    // Start the server in its own process group, so that it outlives the ssh
+    // connection that started it.
+    process::Command::new(env::current_exe()?)
+        .arg("run")
+        .arg(socket_path)
+        .stdin(process::Stdio::null())
+        .stdout(process::Stdio::null())
+        .stderr(process::Stdio::null())
+        .process_group(0)
+        .spawn()
+        .context("failed to spawn server")?;
+    Ok(())
+}
+
+async fn connect_to_new_server(socket_path: &Path) -> Result<UnixStream> {
+    let start = std::time::Instant::now();
+    loop {
+        match UnixStream::connect(socket_path).await {
+            Ok(stream) => return Ok(stream),
+            Err(error) if start.elapsed() > SERVER_STARTUP_TIMEOUT => {
+                return Err(error).context("server did not start");
+            }
+            Err(_) => smol::Timer::after(Duration::from_millis(50)).await,
+        };
+    }
+}
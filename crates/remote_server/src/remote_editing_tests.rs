@@ -4,7 +4,7 @@ use clock::FakeSystemClock;
 use fs::{FakeFs, Fs};
 use gpui::{Context, Model, TestAppContext};
 use http_client::FakeHttpClient;
-use language::{Buffer, LanguageRegistry};
+use language::{Buffer, FakeLspAdapter, LanguageConfig, LanguageMatcher, LanguageRegistry, Point};
 use node_runtime::FakeNodeRuntime;
 use project::{
     search::{SearchQuery, SearchResult},
@@ -184,6 +184,78 @@ async fn test_remote_project_search(cx: &mut TestAppContext, server_cx: &mut Tes
     do_search(&project, cx.clone()).await;
 }
 
+#[gpui::test]
+async fn test_remote_language_server(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    // The language is loaded when the first buffer using it is opened, like the
+    // built-in languages are.
+    let languages = Arc::new(LanguageRegistry::test(server_cx.executor()));
+    languages.register_test_language(LanguageConfig {
+        name: "Rust".into(),
+        matcher: LanguageMatcher {
+            path_suffixes: vec!["rs".into()],
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    let mut fake_servers = languages.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    let (project, _headless, _) = init_test_with_languages(languages, cx, server_cx).await;
+
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree("/code/project1", true, cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+
+    // Opening a buffer starts the language server on the remote host.
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, Path::new("src/lib.rs")), cx)
+        })
+        .await
+        .unwrap();
+    server_cx.executor().run_until_parked();
+    let fake_server = fake_servers.next().await.unwrap();
+    let mut hover_requests =
+        fake_server.handle_request::<lsp::request::HoverRequest, _, _>(|params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.text_document.uri,
+                lsp::Url::from_file_path("/code/project1/src/lib.rs").unwrap()
+            );
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Scalar(lsp::MarkedString::String(
+                    "remote hover".to_string(),
+                )),
+                range: None,
+            }))
+        });
+
+    // Requests made by the client are answered by the remote language server.
+    let hover_task = project.update(cx, |project, cx| {
+        project.hover(&buffer, Point::new(0, 3), cx)
+    });
+    hover_requests.next().await.unwrap();
+    let hovers = hover_task.await;
+    assert_eq!(
+        hovers
+            .iter()
+            .flat_map(|hover| hover.contents.iter().map(|block| block.text.as_str()))
+            .collect::<Vec<_>>(),
+        vec!["remote hover"]
+    );
+}
+
 fn init_logger() {
     if std::env::var("RUST_LOG").is_ok() {
         env_logger::try_init().ok();
@@ -193,6 +265,15 @@ fn init_logger() {
 async fn init_test(
     cx: &mut TestAppContext,
     server_cx: &mut TestAppContext,
+) -> (Model<Project>, Model<HeadlessProject>, Arc<FakeFs>) {
+    let languages = Arc::new(LanguageRegistry::test(server_cx.executor()));
+    init_test_with_languages(languages, cx, server_cx).await
+}
+
+async fn init_test_with_languages(
+    languages: Arc<LanguageRegistry>,
+    cx: &mut TestAppContext,
+    server_cx: &mut TestAppContext,
 ) -> (Model<Project>, Model<HeadlessProject>, Arc<FakeFs>) {
     let (client_ssh, server_ssh) = SshSession::fake(cx, server_cx);
     init_logger();
@@ -220,7 +301,15 @@ async fn init_test(
     );
 
     server_cx.update(HeadlessProject::init);
-    let headless = server_cx.new_model(|cx| HeadlessProject::new(server_ssh, fs.clone(), cx));
+    let headless = server_cx.new_model(|cx| {
+        HeadlessProject::new(
+            server_ssh,
+            fs.clone(),
+            languages,
+            FakeHttpClient::with_404_response(),
+            cx,
+        )
+    });
     let project = build_project(client_ssh, cx);
 
     project
@@ -9,6 +9,8 @@ use settings::{Settings, SettingsSources};
 #[derive(Debug, Default)]
 pub struct JupyterSettings {
     pub kernel_selections: HashMap<String, String>,
+    pub kernel_gateways: Vec<KernelGateway>,
+    pub strip_notebook_outputs: bool,
 }
 
 impl JupyterSettings {
@@ -26,12 +28,33 @@ pub struct JupyterSettingsContent {
     ///
     /// Default: `{}`
     pub kernel_selections: Option<HashMap<String, String>>,
+    /// Jupyter servers to start kernels on, in addition to the kernels installed locally.
+    ///
+    /// Default: `[]`
+    pub kernel_gateways: Option<Vec<KernelGateway>>,
+    /// Whether to leave the outputs and execution counts of the cells out when saving
+    /// notebooks, unless their metadata has `"keep_output": true`.
+    ///
+    /// Default: `false`
+    pub strip_notebook_outputs: Option<bool>,
+}
+
+/// A Jupyter server (or kernel gateway) running kernels for Zed.
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq, Hash)]
+pub struct KernelGateway {
+    /// The URL of the server, as in `http://localhost:8888`.
+    pub url: String,
+    /// The token to authenticate with, if the server requires one.
+    #[serde(default)]
+    pub token: Option<String>,
 }
 
 impl Default for JupyterSettingsContent {
     fn default() -> Self {
         JupyterSettingsContent {
             kernel_selections: Some(HashMap::new()),
+            kernel_gateways: Some(Vec::new()),
+            strip_notebook_outputs: Some(false),
         }
     }
 }
@@ -56,6 +79,12 @@ impl Settings for JupyterSettings {
                     settings.kernel_selections.insert(k.clone(), v.clone());
                 }
             }
+            if let Some(kernel_gateways) = &value.kernel_gateways {
+                settings.kernel_gateways = kernel_gateways.clone();
+            }
+            if let Some(strip_notebook_outputs) = value.strip_notebook_outputs {
+                settings.strip_notebook_outputs = strip_notebook_outputs;
+            }
         }
 
         Ok(settings)
@@ -0,0 +1,179 @@
+use std::sync::Arc;
+
+use editor::Editor;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{rems, DismissEvent, Task, ViewContext, WeakView};
+use picker::{Picker, PickerDelegate};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt as _;
+
+use crate::repl_editor::get_language;
+use crate::repl_store::ReplStore;
+use crate::KernelSpecification;
+
+/// Picks the kernel to run the code of the editor in, among the local and remote
+/// kernels for its language.
+pub fn toggle_kernel_picker(editor: WeakView<Editor>, cx: &mut WindowContext) {
+    let Some(workspace) = editor
+        .upgrade()
+        .and_then(|editor| editor.read(cx).workspace())
+    else {
+        return;
+    };
+
+    let language = get_language(editor.clone(), cx);
+    let kernel_specifications = ReplStore::global(cx)
+        .read(cx)
+        .kernel_specifications()
+        .filter(|spec| match &language {
+            Some(language) => spec
+                .kernelspec
+                .language
+                .eq_ignore_ascii_case(language.code_fence_block_name().as_ref()),
+            None => true,
+        })
+        .cloned()
+        .collect();
+
+    workspace.update(cx, |workspace, cx| {
+        workspace.toggle_modal(cx, |cx| {
+            Picker::uniform_list(KernelPickerDelegate::new(editor, kernel_specifications), cx)
+                .width(rems(34.))
+        })
+    });
+}
+
+struct KernelPickerDelegate {
+    editor: WeakView<Editor>,
+    kernel_specifications: Vec<KernelSpecification>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl KernelPickerDelegate {
+    fn new(editor: WeakView<Editor>, kernel_specifications: Vec<KernelSpecification>) -> Self {
+        Self {
+            editor,
+            kernel_specifications,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for KernelPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        "Select a kernel...".into()
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        "No kernels found".into()
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let candidates = self
+            .kernel_specifications
+            .iter()
+            .enumerate()
+            .map(|(id, spec)| StringMatchCandidate::new(id, spec.name.clone()))
+            .collect::<Vec<_>>();
+        let background = cx.background_executor().clone();
+        cx.spawn(|picker, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+            picker
+                .update(&mut cx, |picker, cx| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    delegate.selected_index = delegate
+                        .selected_index
+                        .min(delegate.matches.len().saturating_sub(1));
+                    cx.notify();
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(spec) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|mat| self.kernel_specifications.get(mat.candidate_id))
+        else {
+            return;
+        };
+        crate::change_kernel(self.editor.clone(), spec.clone(), cx).log_err();
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut ViewContext<Picker<Self>>) {}
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let spec = self.kernel_specifications.get(mat.candidate_id)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(HighlightedLabel::new(
+                            mat.string.clone(),
+                            mat.positions.clone(),
+                        ))
+                        .child(
+                            Label::new(spec.kernelspec.display_name.clone())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .when_some(spec.gateway.as_ref(), |this, gateway| {
+                            this.child(
+                                Label::new(gateway.url.clone())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                        }),
+                ),
+        )
+    }
+}
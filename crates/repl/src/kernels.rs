@@ -6,6 +6,7 @@ use futures::{
     SinkExt as _,
 };
 use gpui::{AppContext, EntityId, Task};
+use http_client::HttpClient;
 use project::Fs;
 use runtimelib::{
     dirs, ConnectionInfo, ExecutionState, JupyterKernelspec, JupyterMessage, JupyterMessageContent,
@@ -19,13 +20,20 @@ use std::{
     path::PathBuf,
     sync::Arc,
 };
+use util::ResultExt as _;
 use uuid::Uuid;
 
+use crate::remote_kernels::{remote_kernel_specifications, RemoteKernel};
+use crate::KernelGateway;
+
 #[derive(Debug, Clone)]
 pub struct KernelSpecification {
     pub name: String,
+    /// The directory of the kernelspec, or the URL of the server for remote kernels.
     pub path: PathBuf,
     pub kernelspec: JupyterKernelspec,
+    /// The server to start the kernel on, `None` for kernels launched locally.
+    pub gateway: Option<KernelGateway>,
 }
 
 impl KernelSpecification {
@@ -174,12 +182,11 @@ impl Kernel {
 }
 
 pub struct RunningKernel {
-    pub process: smol::process::Child,
-    _shell_task: Task<Result<()>>,
-    _iopub_task: Task<Result<()>>,
-    _control_task: Task<Result<()>>,
-    _routing_task: Task<Result<()>>,
-    connection_path: PathBuf,
+    /// The kernel process, `None` for kernels running on a server.
+    pub process: Option<smol::process::Child>,
+    remote_kernel: Option<RemoteKernel>,
+    _tasks: Vec<Task<Result<()>>>,
+    connection_path: Option<PathBuf>,
     pub working_directory: PathBuf,
     pub request_tx: mpsc::Sender<JupyterMessage>,
     pub execution_state: ExecutionState,
@@ -192,6 +199,7 @@ impl Debug for RunningKernel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RunningKernel")
             .field("process", &self.process)
+            .field("remote", &self.remote_kernel.is_some())
             .finish()
     }
 }
@@ -204,6 +212,10 @@ impl RunningKernel {
         fs: Arc<dyn Fs>,
         cx: &mut AppContext,
     ) -> Task<Result<(Self, JupyterMessageChannel)>> {
+        if let Some(gateway) = kernel_specification.gateway.clone() {
+            return Self::new_remote(kernel_specification, gateway, working_directory, cx);
+        }
+
         cx.spawn(|cx| async move {
             let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
             let ports = peek_ports(ip).await?;
@@ -320,14 +332,12 @@ impl RunningKernel {
 
             anyhow::Ok((
                 Self {
-                    process,
+                    process: Some(process),
+                    remote_kernel: None,
                     request_tx,
                     working_directory,
-                    _shell_task: shell_task,
-                    _iopub_task: iopub_task,
-                    _control_task: control_task,
-                    _routing_task: routing_task,
-                    connection_path,
+                    _tasks: vec![shell_task, iopub_task, control_task, routing_task],
+                    connection_path: Some(connection_path),
                     execution_state: ExecutionState::Idle,
                     kernel_info: None,
                 },
@@ -335,13 +345,55 @@ impl RunningKernel {
             ))
         })
     }
+
+    fn new_remote(
+        kernel_specification: KernelSpecification,
+        gateway: KernelGateway,
+        working_directory: PathBuf,
+        cx: &mut AppContext,
+    ) -> Task<Result<(Self, JupyterMessageChannel)>> {
+        let http_client = cx.http_client();
+        let executor = cx.background_executor().clone();
+        cx.spawn(|_| async move {
+            let remote_kernel =
+                RemoteKernel::start(&kernel_specification, gateway, http_client, executor).await?;
+            let (request_tx, messages_rx, tasks) = remote_kernel.connect().await?;
+
+            let mut messages = SelectAll::new();
+            messages.push(messages_rx);
+
+            anyhow::Ok((
+                Self {
+                    process: None,
+                    remote_kernel: Some(remote_kernel),
+                    request_tx,
+                    working_directory,
+                    _tasks: tasks,
+                    connection_path: None,
+                    execution_state: ExecutionState::Idle,
+                    kernel_info: None,
+                },
+                messages,
+            ))
+        })
+    }
+
+    /// Stops the kernel process, or shuts the kernel down on its server.
+    pub fn kill(&mut self) {
+        if let Some(process) = self.process.as_mut() {
+            process.kill().ok();
+        }
+        self.remote_kernel.take();
+    }
 }
 
 impl Drop for RunningKernel {
     fn drop(&mut self) {
-        std::fs::remove_file(&self.connection_path).ok();
+        if let Some(connection_path) = &self.connection_path {
+            std::fs::remove_file(connection_path).ok();
+        }
         self.request_tx.close_channel();
-        self.process.kill().ok();
+        self.kill();
     }
 }
 
@@ -370,6 +422,7 @@ async fn read_kernelspec_at(
         name: kernel_name,
         path,
         kernelspec: spec,
+        gateway: None,
     })
 }
 
@@ -444,6 +497,22 @@ pub async fn kernel_specifications(fs: Arc<dyn Fs>) -> Result<Vec<KernelSpecific
     Ok(kernel_dirs)
 }
 
+/// Lists the kernels of the servers, skipping the servers that can't be reached.
+pub async fn gateway_kernel_specifications(
+    gateways: Vec<KernelGateway>,
+    http_client: Arc<dyn HttpClient>,
+) -> Vec<KernelSpecification> {
+    let kernel_specifications = gateways
+        .into_iter()
+        .map(|gateway| remote_kernel_specifications(gateway, http_client.clone()));
+    futures::future::join_all(kernel_specifications)
+        .await
+        .into_iter()
+        .filter_map(|kernel_specifications| kernel_specifications.log_err())
+        .flatten()
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -0,0 +1,453 @@
+//! Jupyter notebooks (`.ipynb`), read from and written back to the
+//! [nbformat](https://nbformat.readthedocs.io/en/latest/format_description.html) JSON.
+//!
+//! Only the sources, outputs and execution counts of the cells are edited, everything
+//! else in the notebook, like its metadata, is written back as it was read.
+
+use anyhow::{anyhow, Context as _, Result};
+use runtimelib::JupyterMessageContent;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellType {
+    Code,
+    Markdown,
+    Raw,
+}
+
+impl CellType {
+    fn parse(cell_type: &str) -> Option<Self> {
+        match cell_type {
+            "code" => Some(Self::Code),
+            "markdown" => Some(Self::Markdown),
+            "raw" => Some(Self::Raw),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Code => "code",
+            Self::Markdown => "markdown",
+            Self::Raw => "raw",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cell {
+    pub cell_type: CellType,
+    pub source: String,
+    /// The outputs of code cells, in the format of notebooks.
+    pub outputs: Vec<Value>,
+    pub execution_count: Option<u64>,
+    /// The other fields of the cell, like its id and metadata.
+    fields: Map<String, Value>,
+}
+
+impl Cell {
+    fn new(cell_type: CellType, source: String, id: Option<String>) -> Self {
+        let mut fields = Map::new();
+        fields.insert("metadata".into(), json!({}));
+        if let Some(id) = id {
+            fields.insert("id".into(), id.into());
+        }
+        Self {
+            cell_type,
+            source,
+            outputs: Vec::new(),
+            execution_count: None,
+            fields,
+        }
+    }
+
+    /// Whether the outputs of the cell are kept when stripping outputs, with the
+    /// `keep_output` metadata of nbstripout.
+    fn keeps_output(&self) -> bool {
+        self.fields
+            .get("metadata")
+            .and_then(|metadata| metadata.get("keep_output"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Notebook {
+    pub cells: Vec<Cell>,
+    /// The other fields of the notebook, like its metadata and version.
+    fields: Map<String, Value>,
+}
+
+impl Notebook {
+    pub fn parse(text: &str) -> Result<Self> {
+        let Value::Object(mut fields) = serde_json::from_str(text)? else {
+            return Err(anyhow!("notebook is not a JSON object"));
+        };
+        let nbformat = fields.get("nbformat").and_then(Value::as_u64);
+        if nbformat != Some(4) {
+            return Err(anyhow!(
+                "unsupported notebook format {}, only version 4 is supported",
+                nbformat.map_or("unknown".to_string(), |nbformat| nbformat.to_string())
+            ));
+        }
+
+        let cells = match fields.remove("cells") {
+            Some(Value::Array(cells)) => cells,
+            Some(_) => return Err(anyhow!("cells of notebook are not an array")),
+            None => Vec::new(),
+        };
+        let cells = cells
+            .into_iter()
+            .enumerate()
+            .map(|(ix, cell)| parse_cell(cell).with_context(|| format!("invalid cell {ix}")))
+            .collect::<Result<_>>()?;
+
+        Ok(Self { cells, fields })
+    }
+
+    /// The name of the kernel the notebook was last run with.
+    pub fn kernel_name(&self) -> Option<&str> {
+        self.fields
+            .get("metadata")?
+            .get("kernelspec")?
+            .get("name")?
+            .as_str()
+    }
+
+    /// The language of the code cells, Python unless the metadata says otherwise.
+    pub fn language_name(&self) -> &str {
+        let metadata = self.fields.get("metadata");
+        metadata
+            .and_then(|metadata| metadata.get("kernelspec")?.get("language")?.as_str())
+            .or_else(|| {
+                metadata.and_then(|metadata| metadata.get("language_info")?.get("name")?.as_str())
+            })
+            .unwrap_or("python")
+    }
+
+    /// Creates a cell, with an id if the version of the notebook has them.
+    pub fn new_cell(&self, cell_type: CellType, source: String) -> Cell {
+        let has_ids = self
+            .fields
+            .get("nbformat_minor")
+            .and_then(Value::as_u64)
+            .map_or(false, |minor| minor >= 5);
+        let id = has_ids.then(|| Uuid::new_v4().simple().to_string()[..8].to_string());
+        Cell::new(cell_type, source, id)
+    }
+
+    /// Writes the notebook the way Jupyter does, with sorted keys and an indent of
+    /// one space. When stripping outputs, the outputs and execution counts of the
+    /// cells are left out, unless the notebook or the cell has the `keep_output`
+    /// metadata.
+    pub fn to_json(&self, strip_outputs: bool) -> Result<String> {
+        let keep_all_outputs = self
+            .fields
+            .get("metadata")
+            .and_then(|metadata| metadata.get("keep_output"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
+        let mut notebook = self.fields.clone();
+        let cells = self
+            .cells
+            .iter()
+            .map(|cell| {
+                let strip_outputs = strip_outputs && !keep_all_outputs && !cell.keeps_output();
+                let mut fields = cell.fields.clone();
+                fields.insert("cell_type".into(), cell.cell_type.as_str().into());
+                fields.insert("source".into(), multiline_string(&cell.source));
+                fields.entry("metadata").or_insert_with(|| json!({}));
+                if cell.cell_type == CellType::Code {
+                    let (outputs, execution_count) = if strip_outputs {
+                        (Vec::new(), None)
+                    } else {
+                        (cell.outputs.clone(), cell.execution_count)
+                    };
+                    fields.insert("outputs".into(), Value::Array(outputs));
+                    fields.insert("execution_count".into(), execution_count.into());
+                }
+                Value::Object(fields)
+            })
+            .collect();
+        notebook.insert("cells".into(), Value::Array(cells));
+
+        let mut json = Vec::new();
+        let mut serializer = serde_json::Serializer::with_formatter(
+            &mut json,
+            serde_json::ser::PrettyFormatter::with_indent(b" "),
+        );
+        sort_keys(Value::Object(notebook)).serialize(&mut serializer)?;
+        json.push(b'\n');
+        Ok(String::from_utf8(json)?)
+    }
+}
+
+/// Jupyter writes the keys of objects in order, while ours keep the order they were
+/// inserted in.
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries = map.into_iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_keys(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+        value => value,
+    }
+}
+
+fn parse_cell(cell: Value) -> Result<Cell> {
+    let Value::Object(mut fields) = cell else {
+        return Err(anyhow!("cell is not a JSON object"));
+    };
+    let cell_type = fields
+        .remove("cell_type")
+        .and_then(|cell_type| CellType::parse(cell_type.as_str()?))
+        .context("unknown cell type")?;
+    let source = fields
+        .remove("source")
+        .map(|source| parse_multiline_string(&source))
+        .unwrap_or_default();
+    let outputs = match fields.remove("outputs") {
+        Some(Value::Array(outputs)) => outputs,
+        _ => Vec::new(),
+    };
+    let execution_count = fields
+        .remove("execution_count")
+        .and_then(|count| count.as_u64());
+    Ok(Cell {
+        cell_type,
+        source,
+        outputs,
+        execution_count,
+        fields,
+    })
+}
+
+/// Strings in notebooks can be split in lines, each ending with its line break.
+fn parse_multiline_string(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+fn multiline_string(string: &str) -> Value {
+    Value::Array(
+        string
+            .split_inclusive('\n')
+            .map(|line| Value::String(line.to_string()))
+            .collect(),
+    )
+}
+
+/// The output of a notebook cell for a message of the kernel, if the message is an
+/// output.
+pub fn notebook_output(message: &JupyterMessageContent) -> Option<Value> {
+    let output_type = match message {
+        JupyterMessageContent::ExecuteResult(_) => "execute_result",
+        JupyterMessageContent::DisplayData(_) => "display_data",
+        JupyterMessageContent::StreamContent(_) => "stream",
+        JupyterMessageContent::ErrorOutput(_) => "error",
+        _ => return None,
+    };
+    let Ok(Value::Object(mut output)) = serde_json::to_value(message) else {
+        return None;
+    };
+    // Transient data, like display ids, is never saved.
+    output.remove("transient");
+    if output_type != "stream" && output_type != "error" {
+        output.entry("metadata").or_insert_with(|| json!({}));
+    }
+    output.insert("output_type".into(), output_type.into());
+    Some(Value::Object(output))
+}
+
+/// The message of the kernel an output of a notebook cell was saved from.
+pub fn notebook_output_message(output: &Value) -> Option<JupyterMessageContent> {
+    let mut content = output.as_object()?.clone();
+    let output_type = content.remove("output_type")?.as_str()?.to_string();
+    match output_type.as_str() {
+        "stream" => {
+            let text = parse_multiline_string(content.get("text")?);
+            content.insert("text".into(), text.into());
+        }
+        "display_data" | "execute_result" => {
+            if let Some(Value::Object(data)) = content.get_mut("data") {
+                for (mime_type, value) in data.iter_mut() {
+                    // JSON media types are the only ones that aren't strings.
+                    if !mime_type.ends_with("json") && value.is_array() {
+                        *value = parse_multiline_string(value).into();
+                    }
+                }
+            }
+            content.entry("metadata").or_insert_with(|| json!({}));
+            content.insert("transient".into(), json!({}));
+        }
+        "error" => {}
+        _ => return None,
+    }
+    JupyterMessageContent::from_type_and_content(&output_type, Value::Object(content)).ok()
+}
+
+/// The execution count of the messages that have one, like execution replies.
+pub fn execution_count(message: &JupyterMessageContent) -> Option<u64> {
+    match message {
+        JupyterMessageContent::ExecuteInput(_)
+        | JupyterMessageContent::ExecuteReply(_)
+        | JupyterMessageContent::ExecuteResult(_) => serde_json::to_value(message)
+            .ok()?
+            .get("execution_count")?
+            .as_u64(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "id": "9a8b7c6d",
+   "metadata": {},
+   "source": [
+    "# Title\n",
+    "\n",
+    "Some text"
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 2,
+   "id": "1a2b3c4d",
+   "metadata": {
+    "tags": [
+     "parameters"
+    ]
+   },
+   "outputs": [
+    {
+     "name": "stdout",
+     "output_type": "stream",
+     "text": [
+      "hello\n"
+     ]
+    }
+   ],
+   "source": [
+    "print(\"hello\")"
+   ]
+  }
+ ],
+ "metadata": {
+  "kernelspec": {
+   "display_name": "Python 3 (ipykernel)",
+   "language": "python",
+   "name": "python3"
+  }
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##;
+
+    #[test]
+    fn test_notebook_round_trip() {
+        let notebook = Notebook::parse(NOTEBOOK).unwrap();
+        assert_eq!(notebook.cells.len(), 2);
+        assert_eq!(notebook.cells[0].cell_type, CellType::Markdown);
+        assert_eq!(notebook.cells[0].source, "# Title\n\nSome text");
+        assert_eq!(notebook.cells[1].cell_type, CellType::Code);
+        assert_eq!(notebook.cells[1].execution_count, Some(2));
+        assert_eq!(notebook.kernel_name(), Some("python3"));
+        assert_eq!(notebook.language_name(), "python");
+
+        assert_eq!(notebook.to_json(false).unwrap(), NOTEBOOK);
+
+        let stripped: Value = serde_json::from_str(&notebook.to_json(true).unwrap()).unwrap();
+        assert_eq!(stripped["cells"][1]["outputs"], json!([]));
+        assert_eq!(stripped["cells"][1]["execution_count"], Value::Null);
+        assert_eq!(
+            stripped["cells"][1]["metadata"]["tags"],
+            json!(["parameters"])
+        );
+        assert_eq!(stripped["metadata"]["kernelspec"]["name"], "python3");
+    }
+
+    #[test]
+    fn test_notebook_keep_output() {
+        let mut notebook = Notebook::parse(NOTEBOOK).unwrap();
+        notebook.cells[1]
+            .fields
+            .insert("metadata".into(), json!({ "keep_output": true }));
+        let stripped: Value = serde_json::from_str(&notebook.to_json(true).unwrap()).unwrap();
+        assert_eq!(stripped["cells"][1]["execution_count"], 2);
+        assert_eq!(
+            stripped["cells"][1]["outputs"][0]["text"],
+            json!(["hello\n"])
+        );
+    }
+
+    #[test]
+    fn test_new_cell() {
+        let notebook = Notebook::parse(NOTEBOOK).unwrap();
+        let cell = notebook.new_cell(CellType::Code, "1 + 1".into());
+        assert_eq!(cell.fields["id"].as_str().unwrap().len(), 8);
+
+        let mut notebook = notebook;
+        notebook.cells = vec![cell];
+        let json: Value = serde_json::from_str(&notebook.to_json(false).unwrap()).unwrap();
+        assert_eq!(json["cells"][0]["source"], json!(["1 + 1"]));
+        assert_eq!(json["cells"][0]["outputs"], json!([]));
+        assert_eq!(json["cells"][0]["execution_count"], Value::Null);
+        assert_eq!(json["cells"][0]["metadata"], json!({}));
+
+        assert!(Notebook::parse(r#"{"nbformat": 3, "worksheets": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_notebook_output_message() {
+        let output = json!({
+            "output_type": "stream",
+            "name": "stdout",
+            "text": ["a\n", "b\n"]
+        });
+        let message = notebook_output_message(&output).unwrap();
+        match &message {
+            JupyterMessageContent::StreamContent(stream) => assert_eq!(stream.text, "a\nb\n"),
+            message => panic!("unexpected message {message:?}"),
+        }
+        assert_eq!(
+            notebook_output(&message).unwrap(),
+            json!({ "output_type": "stream", "name": "stdout", "text": "a\nb\n" })
+        );
+
+        let output = json!({
+            "output_type": "display_data",
+            "data": { "text/plain": ["<Figure>"], "image/png": "iVBORw0KGgo=" },
+            "metadata": {}
+        });
+        let message = notebook_output_message(&output).unwrap();
+        assert!(matches!(message, JupyterMessageContent::DisplayData(_)));
+        let saved = notebook_output(&message).unwrap();
+        assert_eq!(saved["output_type"], "display_data");
+        assert_eq!(saved["data"]["text/plain"], "<Figure>");
+        assert_eq!(saved["data"]["image/png"], "iVBORw0KGgo=");
+        assert!(saved.get("transient").is_none());
+
+        assert!(notebook_output_message(&json!({ "output_type": "unknown" })).is_none());
+    }
+}
@@ -0,0 +1,832 @@
+//! Jupyter notebooks opened as a multibuffer of their cells, each cell being an excerpt
+//! of its own buffer. Code cells run in the REPL session of the notebook, and their
+//! outputs are saved back with them.
+
+use std::{ffi::OsStr, ops::Range, path::PathBuf, sync::Arc};
+
+use anyhow::{anyhow, Context as _, Result};
+use collections::HashSet;
+use editor::{
+    display_map::{BlockDisposition, BlockProperties, BlockStyle, CustomBlockId},
+    scroll::Autoscroll,
+    Anchor, Editor, ExcerptId, ExcerptRange, MultiBuffer,
+};
+use gpui::{
+    actions, AnyElement, AppContext, EventEmitter, FocusHandle, FocusableView, Model, Subscription,
+    Task, View, ViewContext, WeakView,
+};
+use language::{Buffer, Capability, Language};
+use project::{Project, ProjectEntryId, ProjectPath};
+use settings::Settings as _;
+use ui::{prelude::*, Tooltip};
+use util::ResultExt as _;
+use workspace::item::{Item, ItemEvent, ProjectItem};
+use workspace::{Workspace, WorkspaceId};
+
+use crate::notebook::{notebook_output_message, Cell, CellType, Notebook};
+use crate::outputs::{ExecutionStatus, ExecutionView};
+use crate::repl_store::ReplStore;
+use crate::session::{output_area_renderer, CloseBlockFn};
+use crate::{JupyterSettings, Session};
+
+actions!(
+    notebook,
+    [
+        RunCell,
+        AddCodeCell,
+        AddMarkdownCell,
+        DeleteCell,
+        MoveCellUp,
+        MoveCellDown
+    ]
+);
+
+pub fn init(cx: &mut AppContext) {
+    // The last registered project item opens files first, so this has to come after
+    // the editor, which opens any file.
+    workspace::register_project_item::<NotebookEditor>(cx);
+}
+
+pub struct NotebookItem {
+    abs_path: PathBuf,
+    project_path: ProjectPath,
+    entry_id: Option<ProjectEntryId>,
+    /// The notebook as it was last read or saved.
+    notebook: Notebook,
+}
+
+impl project::Item for NotebookItem {
+    fn try_open(
+        project: &Model<Project>,
+        path: &ProjectPath,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Model<Self>>>> {
+        let is_notebook = path.path.extension().and_then(OsStr::to_str) == Some("ipynb");
+        if !is_notebook || !JupyterSettings::enabled(cx) || !project.read(cx).is_local() {
+            return None;
+        }
+
+        let path = path.clone();
+        let project = project.clone();
+        Some(cx.spawn(|mut cx| async move {
+            let (abs_path, entry_id, fs) = project.read_with(&cx, |project, cx| {
+                (
+                    project.absolute_path(&path, cx),
+                    project.entry_for_path(&path, cx).map(|entry| entry.id),
+                    project.fs().clone(),
+                )
+            })?;
+            let abs_path = abs_path.context("failed to find the absolute path of the notebook")?;
+            let text = fs.load(&abs_path).await?;
+            let notebook = Notebook::parse(&text)
+                .with_context(|| format!("failed to read notebook {}", abs_path.display()))?;
+
+            cx.new_model(|_| NotebookItem {
+                abs_path,
+                project_path: path,
+                entry_id,
+                notebook,
+            })
+        }))
+    }
+
+    fn entry_id(&self, _: &AppContext) -> Option<ProjectEntryId> {
+        self.entry_id
+    }
+
+    fn project_path(&self, _: &AppContext) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+}
+
+struct NotebookCell {
+    /// The cell as it was read, for its metadata.
+    cell: Cell,
+    buffer: Model<Buffer>,
+    excerpt_id: ExcerptId,
+    /// The outputs shown below the cell, unless it ran in the session since.
+    outputs: Option<CellOutputs>,
+}
+
+struct CellOutputs {
+    block_id: CustomBlockId,
+    execution_view: View<ExecutionView>,
+}
+
+pub struct NotebookEditor {
+    notebook_item: Model<NotebookItem>,
+    editor: View<Editor>,
+    multibuffer: Model<MultiBuffer>,
+    cells: Vec<NotebookCell>,
+    workspace: Option<WeakView<Workspace>>,
+    code_language: Option<Arc<Language>>,
+    markdown_language: Option<Arc<Language>>,
+    /// Whether cells were added, removed, moved or run since the notebook was saved.
+    cells_changed: bool,
+    _load_languages: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl NotebookEditor {
+    fn new(
+        project: Model<Project>,
+        notebook_item: Model<NotebookItem>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let multibuffer = cx.new_model(|_| MultiBuffer::new(0, Capability::ReadWrite));
+        let editor = cx.new_view(|cx| {
+            Editor::for_multibuffer(multibuffer.clone(), Some(project.clone()), false, cx)
+        });
+
+        let this = cx.view().downgrade();
+        editor.update(cx, |editor, _| {
+            editor.set_custom_buffer_header(move |buffer, cx| {
+                let buffer_id = buffer.remote_id();
+                this.update(cx, |this, cx| this.render_cell_header(buffer_id, cx))
+                    .unwrap_or_else(|_| div().into_any_element())
+            });
+        });
+
+        let subscriptions = vec![cx.subscribe(&multibuffer, |_, _, event, cx| {
+            if let multi_buffer::Event::DirtyChanged = event {
+                cx.emit(ItemEvent::UpdateTab);
+            }
+        })];
+
+        let notebook = notebook_item.read(cx).notebook.clone();
+        let languages = project.read(cx).languages().clone();
+        let language_name = notebook.language_name().to_string();
+        let load_languages = cx.spawn(|this, mut cx| async move {
+            let code_language = languages.language_for_name(&language_name).await.log_err();
+            let markdown_language = languages.language_for_name("Markdown").await.log_err();
+            this.update(&mut cx, |this, cx| {
+                this.code_language = code_language;
+                this.markdown_language = markdown_language;
+                for cell in &this.cells {
+                    this.set_cell_language(cell, cx);
+                }
+            })
+            .ok();
+        });
+
+        let mut this = Self {
+            notebook_item,
+            editor,
+            multibuffer,
+            cells: Vec::new(),
+            workspace: None,
+            code_language: None,
+            markdown_language: None,
+            cells_changed: false,
+            _load_languages: load_languages,
+            _subscriptions: subscriptions,
+        };
+        this.set_notebook(&notebook, cx);
+        this
+    }
+
+    /// Replaces the cells with the ones of the notebook.
+    fn set_notebook(&mut self, notebook: &Notebook, cx: &mut ViewContext<Self>) {
+        let block_ids = self
+            .cells
+            .drain(..)
+            .filter_map(|cell| Some(cell.outputs?.block_id))
+            .collect::<HashSet<_>>();
+        self.editor.update(cx, |editor, cx| {
+            editor.remove_blocks(block_ids, None, cx);
+        });
+        if let Some(session) = self.session(cx) {
+            session.update(cx, |session, cx| session.clear_outputs(cx));
+        }
+        self.multibuffer
+            .update(cx, |multibuffer, cx| multibuffer.clear(cx));
+
+        for cell in &notebook.cells {
+            self.insert_cell(self.cells.len(), cell.clone(), cx);
+        }
+        self.show_saved_outputs(cx);
+        self.cells_changed = false;
+    }
+
+    fn insert_cell(&mut self, ix: usize, cell: Cell, cx: &mut ViewContext<Self>) {
+        let buffer = cx.new_model(|cx| Buffer::local(cell.source.clone(), cx));
+        self.insert_excerpt(ix, cell, buffer, None, cx);
+        self.set_cell_language(&self.cells[ix], cx);
+    }
+
+    fn insert_excerpt(
+        &mut self,
+        ix: usize,
+        cell: Cell,
+        buffer: Model<Buffer>,
+        outputs: Option<View<ExecutionView>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let previous_excerpt_id = match ix.checked_sub(1) {
+            Some(previous_ix) => self.cells[previous_ix].excerpt_id,
+            None => ExcerptId::min(),
+        };
+        let excerpt_id = self.multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.insert_excerpts_after(
+                previous_excerpt_id,
+                buffer.clone(),
+                [ExcerptRange {
+                    context: language::Anchor::MIN..language::Anchor::MAX,
+                    primary: None,
+                }],
+                cx,
+            )[0]
+        });
+        self.cells.insert(
+            ix,
+            NotebookCell {
+                cell,
+                buffer,
+                excerpt_id,
+                outputs: None,
+            },
+        );
+        if let Some(execution_view) = outputs {
+            self.insert_outputs(ix, execution_view, cx);
+        }
+    }
+
+    fn set_cell_language(&self, cell: &NotebookCell, cx: &mut ViewContext<Self>) {
+        let language = match cell.cell.cell_type {
+            CellType::Code => self.code_language.clone(),
+            CellType::Markdown => self.markdown_language.clone(),
+            CellType::Raw => None,
+        };
+        if language.is_some() {
+            cell.buffer
+                .update(cx, |buffer, cx| buffer.set_language(language, cx));
+        }
+    }
+
+    /// Shows the outputs the code cells were saved with, once the editor is in a workspace.
+    fn show_saved_outputs(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.clone() else {
+            return;
+        };
+        for ix in 0..self.cells.len() {
+            let cell = &self.cells[ix];
+            if cell.outputs.is_some() || cell.cell.outputs.is_empty() {
+                continue;
+            }
+            let outputs = cell.cell.outputs.clone();
+            let execution_count = cell.cell.execution_count;
+            let execution_view = cx.new_view(|cx| {
+                let mut execution_view =
+                    ExecutionView::new(ExecutionStatus::Finished, workspace.clone(), cx);
+                for output in &outputs {
+                    if let Some(message) = notebook_output_message(output) {
+                        execution_view.push_message(&message, cx);
+                    }
+                }
+                // Keep the outputs as they were saved, rather than as they're shown.
+                execution_view.notebook_outputs = outputs;
+                execution_view.execution_count = execution_count;
+                execution_view
+            });
+            self.insert_outputs(ix, execution_view, cx);
+        }
+    }
+
+    fn insert_outputs(
+        &mut self,
+        ix: usize,
+        execution_view: View<ExecutionView>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(range) = self.cell_range(ix, cx) else {
+            return;
+        };
+
+        let this = cx.view().downgrade();
+        let on_close: CloseBlockFn = Arc::new(move |block_id, cx| {
+            this.update(cx, |this, cx| {
+                let Some(cell) = this.cells.iter_mut().find(|cell| {
+                    cell.outputs
+                        .as_ref()
+                        .map_or(false, |outputs| outputs.block_id == block_id)
+                }) else {
+                    return;
+                };
+                cell.outputs = None;
+                this.editor.update(cx, |editor, cx| {
+                    editor.remove_blocks(HashSet::from_iter([block_id]), None, cx);
+                });
+                this.cells_changed(cx);
+            })
+            .ok();
+        });
+
+        let block = BlockProperties {
+            position: range.end,
+            height: 1,
+            style: BlockStyle::Sticky,
+            render: output_area_renderer(execution_view.clone(), on_close),
+            disposition: BlockDisposition::Below,
+            priority: 0,
+        };
+        let block_id = self
+            .editor
+            .update(cx, |editor, cx| editor.insert_blocks([block], None, cx)[0]);
+        self.cells[ix].outputs = Some(CellOutputs {
+            block_id,
+            execution_view,
+        });
+    }
+
+    /// Removes the outputs of the cell, returning the last ones it had.
+    fn take_outputs(
+        &mut self,
+        ix: usize,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<ExecutionView>> {
+        let range = self.cell_range(ix, cx)?;
+        let saved_outputs = self.cells[ix].outputs.take();
+        if let Some(outputs) = &saved_outputs {
+            self.editor.update(cx, |editor, cx| {
+                editor.remove_blocks(HashSet::from_iter([outputs.block_id]), None, cx);
+            });
+        }
+
+        let session_outputs = self.session(cx).and_then(|session| {
+            let execution_view = session.read(cx).execution_view(&range, cx);
+            session.update(cx, |session, cx| session.remove_outputs(&range, cx));
+            execution_view
+        });
+        session_outputs.or(saved_outputs.map(|outputs| outputs.execution_view))
+    }
+
+    fn cell_range(&self, ix: usize, cx: &AppContext) -> Option<Range<Anchor>> {
+        let excerpt_id = self.cells.get(ix)?.excerpt_id;
+        let snapshot = self.multibuffer.read(cx).snapshot(cx);
+        let start = snapshot.anchor_in_excerpt(excerpt_id, language::Anchor::MIN)?;
+        let end = snapshot.anchor_in_excerpt(excerpt_id, language::Anchor::MAX)?;
+        Some(start..end)
+    }
+
+    fn active_cell(&self, cx: &AppContext) -> Option<usize> {
+        let head = self.editor.read(cx).selections.newest_anchor().head();
+        self.cells
+            .iter()
+            .position(|cell| cell.excerpt_id == head.excerpt_id)
+    }
+
+    fn select_cell(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(range) = self.cell_range(ix, cx) else {
+            return;
+        };
+        self.editor.update(cx, |editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |selections| {
+                selections.select_anchor_ranges([range.start..range.start]);
+            });
+            editor.focus(cx);
+        });
+    }
+
+    fn cells_changed(&mut self, cx: &mut ViewContext<Self>) {
+        self.cells_changed = true;
+        cx.emit(ItemEvent::UpdateTab);
+        cx.notify();
+    }
+
+    fn session(&self, cx: &AppContext) -> Option<View<Session>> {
+        ReplStore::global(cx)
+            .read(cx)
+            .get_session(self.editor.entity_id())
+            .cloned()
+    }
+
+    /// Starts the session of the notebook with the kernel it was last run with, when
+    /// it's installed. Otherwise the kernel is picked for the language of the notebook.
+    fn start_session(&self, cx: &mut ViewContext<Self>) {
+        if self.session(cx).is_some() {
+            return;
+        }
+        let notebook_item = self.notebook_item.read(cx);
+        let Some(kernel_name) = notebook_item.notebook.kernel_name() else {
+            return;
+        };
+        let kernel_specification = ReplStore::global(cx)
+            .read(cx)
+            .kernel_specifications()
+            .find(|spec| spec.name == kernel_name)
+            .cloned();
+        if let Some(kernel_specification) = kernel_specification {
+            crate::change_kernel(self.editor.downgrade(), kernel_specification, cx).log_err();
+        }
+    }
+
+    fn run_cell(&mut self, _: &RunCell, cx: &mut ViewContext<Self>) {
+        let Some(ix) = self.active_cell(cx) else {
+            return;
+        };
+        self.run_cell_at(ix, cx);
+    }
+
+    /// Runs the cell and moves to the next one, adding a code cell after the last one.
+    fn run_cell_at(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        if ix + 1 == self.cells.len() {
+            let cell = self
+                .notebook_item
+                .read(cx)
+                .notebook
+                .new_cell(CellType::Code, String::new());
+            self.insert_cell(ix + 1, cell, cx);
+        }
+
+        if self.cells[ix].cell.cell_type != CellType::Code {
+            self.select_cell(ix + 1, cx);
+            return;
+        }
+
+        self.take_outputs(ix, cx);
+        self.start_session(cx);
+        self.select_cell(ix, cx);
+        crate::run(self.editor.downgrade(), true, cx).log_err();
+        self.cells_changed(cx);
+    }
+
+    fn add_code_cell(&mut self, _: &AddCodeCell, cx: &mut ViewContext<Self>) {
+        let ix = self.active_cell(cx).map_or(self.cells.len(), |ix| ix + 1);
+        self.add_cell(ix, CellType::Code, cx);
+    }
+
+    fn add_markdown_cell(&mut self, _: &AddMarkdownCell, cx: &mut ViewContext<Self>) {
+        let ix = self.active_cell(cx).map_or(self.cells.len(), |ix| ix + 1);
+        self.add_cell(ix, CellType::Markdown, cx);
+    }
+
+    fn add_cell(&mut self, ix: usize, cell_type: CellType, cx: &mut ViewContext<Self>) {
+        let cell = self
+            .notebook_item
+            .read(cx)
+            .notebook
+            .new_cell(cell_type, String::new());
+        self.insert_cell(ix, cell, cx);
+        self.select_cell(ix, cx);
+        self.cells_changed(cx);
+    }
+
+    fn delete_cell(&mut self, _: &DeleteCell, cx: &mut ViewContext<Self>) {
+        if let Some(ix) = self.active_cell(cx) {
+            self.delete_cell_at(ix, cx);
+        }
+    }
+
+    fn delete_cell_at(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        self.take_outputs(ix, cx);
+        self.remove_cell(ix, cx);
+        if !self.cells.is_empty() {
+            self.select_cell(ix.min(self.cells.len() - 1), cx);
+        }
+        self.cells_changed(cx);
+    }
+
+    fn remove_cell(&mut self, ix: usize, cx: &mut ViewContext<Self>) -> NotebookCell {
+        let cell = self.cells.remove(ix);
+        self.multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.remove_excerpts([cell.excerpt_id], cx);
+        });
+        cell
+    }
+
+    fn move_cell_up(&mut self, _: &MoveCellUp, cx: &mut ViewContext<Self>) {
+        if let Some(ix) = self.active_cell(cx) {
+            self.move_cell(ix, true, cx);
+        }
+    }
+
+    fn move_cell_down(&mut self, _: &MoveCellDown, cx: &mut ViewContext<Self>) {
+        if let Some(ix) = self.active_cell(cx) {
+            self.move_cell(ix, false, cx);
+        }
+    }
+
+    fn move_cell(&mut self, ix: usize, up: bool, cx: &mut ViewContext<Self>) {
+        let new_ix = if up {
+            ix.checked_sub(1)
+        } else {
+            Some(ix + 1).filter(|new_ix| *new_ix < self.cells.len())
+        };
+        let Some(new_ix) = new_ix else {
+            return;
+        };
+
+        // Excerpts can't be moved, so the cell is removed and inserted again, along
+        // with its outputs.
+        let outputs = self.take_outputs(ix, cx);
+        let cell = self.remove_cell(ix, cx);
+        self.insert_excerpt(new_ix, cell.cell, cell.buffer, outputs, cx);
+        self.select_cell(new_ix, cx);
+        self.cells_changed(cx);
+    }
+
+    /// The notebook with the sources of the cells and their last outputs.
+    fn notebook(&self, cx: &AppContext) -> Notebook {
+        let mut notebook = self.notebook_item.read(cx).notebook.clone();
+        let session = self.session(cx);
+        notebook.cells = self
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(ix, notebook_cell)| {
+                let mut cell = notebook_cell.cell.clone();
+                cell.source = notebook_cell.buffer.read(cx).text();
+                if cell.cell_type != CellType::Code {
+                    return cell;
+                }
+
+                let execution_view = session
+                    .as_ref()
+                    .zip(self.cell_range(ix, cx))
+                    .and_then(|(session, range)| session.read(cx).execution_view(&range, cx))
+                    .or_else(|| {
+                        let outputs = notebook_cell.outputs.as_ref()?;
+                        Some(outputs.execution_view.clone())
+                    });
+                match execution_view {
+                    Some(execution_view) => {
+                        let execution_view = execution_view.read(cx);
+                        cell.outputs = execution_view.notebook_outputs.clone();
+                        cell.execution_count = execution_view.execution_count;
+                    }
+                    None => {
+                        cell.outputs.clear();
+                        cell.execution_count = None;
+                    }
+                }
+                cell
+            })
+            .collect();
+        notebook
+    }
+
+    fn save_notebook(
+        &mut self,
+        abs_path: PathBuf,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let notebook = self.notebook(cx);
+        let strip_outputs = JupyterSettings::get_global(cx).strip_notebook_outputs;
+        let text = match notebook.to_json(strip_outputs) {
+            Ok(text) => text,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let fs = project.read(cx).fs().clone();
+        let versions = self
+            .cells
+            .iter()
+            .map(|cell| (cell.buffer.clone(), cell.buffer.read(cx).version()))
+            .collect::<Vec<_>>();
+
+        cx.spawn(|this, mut cx| async move {
+            fs.atomic_write(abs_path, text).await?;
+            this.update(&mut cx, |this, cx| {
+                for (buffer, version) in versions {
+                    buffer.update(cx, |buffer, cx| buffer.did_save(version, None, cx));
+                }
+                this.notebook_item
+                    .update(cx, |notebook_item, _| notebook_item.notebook = notebook);
+                this.cells_changed = false;
+                cx.emit(ItemEvent::UpdateTab);
+                cx.notify();
+            })
+        })
+    }
+
+    fn render_cell_header(
+        &mut self,
+        buffer_id: language::BufferId,
+        cx: &mut ViewContext<Self>,
+    ) -> AnyElement {
+        let Some(ix) = self
+            .cells
+            .iter()
+            .position(|cell| cell.buffer.read(cx).remote_id() == buffer_id)
+        else {
+            return div().into_any_element();
+        };
+        let cell = &self.cells[ix];
+        let execution_count = cell
+            .outputs
+            .as_ref()
+            .and_then(|outputs| outputs.execution_view.read(cx).execution_count);
+        let label = match cell.cell.cell_type {
+            CellType::Code => match execution_count {
+                Some(execution_count) => format!("In [{execution_count}]"),
+                None => "In [ ]".to_string(),
+            },
+            CellType::Markdown => "Markdown".to_string(),
+            CellType::Raw => "Raw".to_string(),
+        };
+        let is_code = cell.cell.cell_type == CellType::Code;
+        let is_first = ix == 0;
+        let is_last = ix + 1 == self.cells.len();
+
+        h_flex()
+            .id(("notebook-cell-header", ix))
+            .size_full()
+            .px_2()
+            .justify_between()
+            .border_t_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(Label::new(label).size(LabelSize::Small).color(Color::Muted))
+            .child(
+                h_flex()
+                    .gap_1()
+                    .when(is_code, |this| {
+                        this.child(
+                            IconButton::new(("run-cell", ix), IconName::Play)
+                                .icon_size(IconSize::Small)
+                                .tooltip(|cx| Tooltip::text("Run Cell", cx))
+                                .on_click(cx.listener(move |this, _, cx| this.run_cell_at(ix, cx))),
+                        )
+                    })
+                    .child(
+                        IconButton::new(("move-cell-up", ix), IconName::ArrowUp)
+                            .icon_size(IconSize::Small)
+                            .disabled(is_first)
+                            .tooltip(|cx| Tooltip::text("Move Cell Up", cx))
+                            .on_click(cx.listener(move |this, _, cx| this.move_cell(ix, true, cx))),
+                    )
+                    .child(
+                        IconButton::new(("move-cell-down", ix), IconName::ArrowDown)
+                            .icon_size(IconSize::Small)
+                            .disabled(is_last)
+                            .tooltip(|cx| Tooltip::text("Move Cell Down", cx))
+                            .on_click(
+                                cx.listener(move |this, _, cx| this.move_cell(ix, false, cx)),
+                            ),
+                    )
+                    .child(
+                        IconButton::new(("add-cell", ix), IconName::Plus)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::text("Add Code Cell Below", cx))
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.add_cell(ix + 1, CellType::Code, cx)
+                            })),
+                    )
+                    .child(
+                        IconButton::new(("delete-cell", ix), IconName::Trash)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::text("Delete Cell", cx))
+                            .on_click(cx.listener(move |this, _, cx| this.delete_cell_at(ix, cx))),
+                    ),
+            )
+            .into_any_element()
+    }
+}
+
+impl EventEmitter<ItemEvent> for NotebookEditor {}
+
+impl FocusableView for NotebookEditor {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Item for NotebookEditor {
+    type Event = ItemEvent;
+
+    fn tab_content_text(&self, cx: &WindowContext) -> Option<SharedString> {
+        let path = &self.notebook_item.read(cx).project_path.path;
+        Some(path.file_name()?.to_string_lossy().to_string().into())
+    }
+
+    fn tab_tooltip_text(&self, cx: &AppContext) -> Option<SharedString> {
+        let abs_path = &self.notebook_item.read(cx).abs_path;
+        Some(abs_path.to_string_lossy().to_string().into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("notebook")
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn added_to_workspace(&mut self, workspace: &mut Workspace, cx: &mut ViewContext<Self>) {
+        // The outputs of the cells are shown in the workspace of the editor.
+        self.editor
+            .update(cx, |editor, cx| editor.added_to_workspace(workspace, cx));
+        self.workspace = Some(workspace.weak_handle());
+        self.show_saved_outputs(cx);
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::Item),
+    ) {
+        f(self.notebook_item.entity_id(), self.notebook_item.read(cx))
+    }
+
+    fn is_singleton(&self, _cx: &AppContext) -> bool {
+        true
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<WorkspaceId>,
+        _: &mut ViewContext<Self>,
+    ) -> Option<View<Self>> {
+        None
+    }
+
+    fn is_dirty(&self, cx: &AppContext) -> bool {
+        self.cells_changed
+            || self
+                .cells
+                .iter()
+                .any(|cell| cell.buffer.read(cx).is_dirty())
+    }
+
+    fn can_save(&self, _cx: &AppContext) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        _format: bool,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let abs_path = self.notebook_item.read(cx).abs_path.clone();
+        self.save_notebook(abs_path, project, cx)
+    }
+
+    fn save_as(
+        &mut self,
+        project: Model<Project>,
+        path: ProjectPath,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(abs_path) = project.read(cx).absolute_path(&path, cx) else {
+            return Task::ready(Err(anyhow!("failed to find the absolute path of {path:?}")));
+        };
+        let save = self.save_notebook(abs_path.clone(), project, cx);
+        cx.spawn(|this, mut cx| async move {
+            save.await?;
+            this.update(&mut cx, |this, cx| {
+                this.notebook_item.update(cx, |notebook_item, _| {
+                    notebook_item.abs_path = abs_path;
+                    notebook_item.project_path = path;
+                    notebook_item.entry_id = None;
+                });
+                cx.emit(ItemEvent::UpdateTab);
+            })
+        })
+    }
+
+    fn reload(&mut self, project: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let abs_path = self.notebook_item.read(cx).abs_path.clone();
+        let fs = project.read(cx).fs().clone();
+        cx.spawn(|this, mut cx| async move {
+            let notebook = Notebook::parse(&fs.load(&abs_path).await?)?;
+            this.update(&mut cx, |this, cx| {
+                this.notebook_item.update(cx, |notebook_item, _| {
+                    notebook_item.notebook = notebook.clone()
+                });
+                this.set_notebook(&notebook, cx);
+                cx.emit(ItemEvent::UpdateTab);
+                cx.notify();
+            })
+        })
+    }
+}
+
+impl ProjectItem for NotebookEditor {
+    type Item = NotebookItem;
+
+    fn for_project_item(
+        project: Model<Project>,
+        item: Model<Self::Item>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(project, item, cx)
+    }
+}
+
+impl Render for NotebookEditor {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        div()
+            .key_context("NotebookEditor")
+            .size_full()
+            .on_action(cx.listener(Self::run_cell))
+            .on_action(cx.listener(Self::add_code_cell))
+            .on_action(cx.listener(Self::add_markdown_cell))
+            .on_action(cx.listener(Self::delete_cell))
+            .on_action(cx.listener(Self::move_cell_up))
+            .on_action(cx.listener(Self::move_cell_down))
+            .child(self.editor.clone())
+    }
+}
@@ -14,10 +14,13 @@
 //! The module supports several output types, including:
 //! - Plain text
 //! - Markdown
-//! - Images (PNG and JPEG)
-//! - Tables
+//! - Images (PNG, JPEG and SVG), which can be zoomed
+//! - Tables, from table schemas or HTML tables, sortable by column
 //! - Error messages
 //!
+//! Each media type is rendered by an `OutputRenderer`, picked by its rank when the
+//! kernel sends several representations of an output.
+//!
 //! ## Clipboard Support
 //!
 //! Most output types implement the `SupportsClipboard` trait, allowing
@@ -35,6 +38,7 @@
 
 use std::time::Duration;
 
+use anyhow::{anyhow, Result};
 use editor::{Editor, MultiBuffer};
 use gpui::{
     percentage, Animation, AnimationExt, AnyElement, ClipboardItem, Model, Render, Transformation,
@@ -44,6 +48,8 @@ use language::Buffer;
 use runtimelib::{ExecutionState, JupyterMessageContent, MimeBundle, MimeType};
 use ui::{div, prelude::*, v_flex, IntoElement, Styled, Tooltip, ViewContext};
 
+use crate::notebook::{execution_count, notebook_output};
+
 mod image;
 use image::ImageView;
 
@@ -60,17 +66,116 @@ mod user_error;
 use user_error::ErrorView;
 use workspace::Workspace;
 
+/// Renders the media types of one kind of output.
+struct OutputRenderer {
+    /// What the output is shown as, for errors.
+    name: &'static str,
+    /// Richer outputs rank higher, and are rendered over the other media types of an output.
+    rank: usize,
+    supports: fn(&MimeType) -> bool,
+    render: fn(&MimeType, Option<String>, &mut WindowContext) -> Result<Output>,
+}
+
+/// The renderers of the supported media types, all other media types are not supported in Zed at this time.
+const OUTPUT_RENDERERS: &[OutputRenderer] = &[
+    OutputRenderer {
+        name: "table",
+        rank: 6,
+        supports: |mimetype| matches!(mimetype, MimeType::DataTable(_)),
+        render: |mimetype, display_id, cx| match mimetype {
+            MimeType::DataTable(data) => Ok(Output::Table {
+                content: cx.new_view(|cx| TableView::new(data, cx)),
+                display_id,
+            }),
+            _ => Err(anyhow!("not a table")),
+        },
+    },
+    // HTML is only rendered for tables, like the ones of DataFrames.
+    OutputRenderer {
+        name: "table",
+        rank: 5,
+        supports: |mimetype| match mimetype {
+            MimeType::Html(html) => html.to_ascii_lowercase().contains("<table"),
+            _ => false,
+        },
+        render: |mimetype, display_id, cx| match mimetype {
+            MimeType::Html(html) => {
+                let view = TableView::from_html(html, cx).ok_or_else(|| anyhow!("no table"))?;
+                Ok(Output::Table {
+                    content: cx.new_view(|_| view),
+                    display_id,
+                })
+            }
+            _ => Err(anyhow!("not HTML")),
+        },
+    },
+    OutputRenderer {
+        name: "image",
+        rank: 4,
+        supports: |mimetype| matches!(mimetype, MimeType::Png(_) | MimeType::Svg(_)),
+        render: |mimetype, display_id, cx| {
+            let view = match mimetype {
+                MimeType::Png(data) => ImageView::from(data)?,
+                MimeType::Svg(svg) => ImageView::from_svg(svg, cx)?,
+                _ => return Err(anyhow!("not an image")),
+            };
+            Ok(Output::Image {
+                content: cx.new_view(|_| view),
+                display_id,
+            })
+        },
+    },
+    OutputRenderer {
+        name: "image",
+        rank: 3,
+        supports: |mimetype| matches!(mimetype, MimeType::Jpeg(_)),
+        render: |mimetype, display_id, cx| match mimetype {
+            MimeType::Jpeg(data) => {
+                let view = ImageView::from(data)?;
+                Ok(Output::Image {
+                    content: cx.new_view(|_| view),
+                    display_id,
+                })
+            }
+            _ => Err(anyhow!("not an image")),
+        },
+    },
+    OutputRenderer {
+        name: "markdown",
+        rank: 2,
+        supports: |mimetype| matches!(mimetype, MimeType::Markdown(_)),
+        render: |mimetype, display_id, cx| match mimetype {
+            MimeType::Markdown(text) => Ok(Output::Markdown {
+                content: cx.new_view(|cx| MarkdownView::from(text.clone(), cx)),
+                display_id,
+            }),
+            _ => Err(anyhow!("not markdown")),
+        },
+    },
+    OutputRenderer {
+        name: "text",
+        rank: 1,
+        supports: |mimetype| matches!(mimetype, MimeType::Plain(_)),
+        render: |mimetype, display_id, cx| match mimetype {
+            MimeType::Plain(text) => Ok(Output::Plain {
+                content: cx.new_view(|cx| TerminalOutput::from(text, cx)),
+                display_id,
+            }),
+            _ => Err(anyhow!("not text")),
+        },
+    },
+];
+
+fn output_renderer(mimetype: &MimeType) -> Option<&'static OutputRenderer> {
+    OUTPUT_RENDERERS
+        .iter()
+        .filter(|renderer| (renderer.supports)(mimetype))
+        .max_by_key(|renderer| renderer.rank)
+}
+
 /// When deciding what to render from a collection of mediatypes, we need to rank them in order of importance
 fn rank_mime_type(mimetype: &MimeType) -> usize {
-    match mimetype {
-        MimeType::DataTable(_) => 6,
-        MimeType::Png(_) => 4,
-        MimeType::Jpeg(_) => 3,
-        MimeType::Markdown(_) => 2,
-        MimeType::Plain(_) => 1,
-        // All other media types are not supported in Zed at this time
-        _ => 0,
-    }
+    output_renderer(mimetype).map_or(0, |renderer| renderer.rank)
 }
 
 pub(crate) trait OutputContent {
@@ -263,31 +368,16 @@ impl Output {
     }
 
     pub fn new(data: &MimeBundle, display_id: Option<String>, cx: &mut WindowContext) -> Self {
-        match data.richest(rank_mime_type) {
-            Some(MimeType::Plain(text)) => Output::Plain {
-                content: cx.new_view(|cx| TerminalOutput::from(text, cx)),
-                display_id,
-            },
-            Some(MimeType::Markdown(text)) => {
-                let view = cx.new_view(|cx| MarkdownView::from(text.clone(), cx));
-                Output::Markdown {
-                    content: view,
-                    display_id,
-                }
-            }
-            Some(MimeType::Png(data)) | Some(MimeType::Jpeg(data)) => match ImageView::from(data) {
-                Ok(view) => Output::Image {
-                    content: cx.new_view(|_| view),
-                    display_id,
-                },
-                Err(error) => Output::Message(format!("Failed to load image: {}", error)),
-            },
-            Some(MimeType::DataTable(data)) => Output::Table {
-                content: cx.new_view(|cx| TableView::new(data, cx)),
-                display_id,
-            },
+        let Some((mimetype, renderer)) = data
+            .richest(rank_mime_type)
+            .and_then(|mimetype| Some((mimetype, output_renderer(mimetype)?)))
+        else {
             // Any other media types are not supported
-            _ => Output::Message("Unsupported media type".to_string()),
+            return Output::Message("Unsupported media type".to_string());
+        };
+        match (renderer.render)(mimetype, display_id, cx) {
+            Ok(output) => output,
+            Err(error) => Output::Message(format!("Failed to load {}: {}", renderer.name, error)),
         }
     }
 }
@@ -314,6 +404,9 @@ pub struct ExecutionView {
     workspace: WeakView<Workspace>,
     pub outputs: Vec<Output>,
     pub status: ExecutionStatus,
+    /// The outputs in the format of notebooks, to save them with the cell they ran from.
+    pub notebook_outputs: Vec<serde_json::Value>,
+    pub execution_count: Option<u64>,
 }
 
 impl ExecutionView {
@@ -326,11 +419,23 @@ impl ExecutionView {
             workspace,
             outputs: Default::default(),
             status,
+            notebook_outputs: Vec::new(),
+            execution_count: None,
         }
     }
 
     /// Accept a Jupyter message belonging to this execution
     pub fn push_message(&mut self, message: &JupyterMessageContent, cx: &mut ViewContext<Self>) {
+        if let Some(output) = notebook_output(message) {
+            if let Some(Output::ClearOutputWaitMarker) = self.outputs.last() {
+                self.notebook_outputs.clear();
+            }
+            self.notebook_outputs.push(output);
+        }
+        if let Some(execution_count) = execution_count(message) {
+            self.execution_count = Some(execution_count);
+        }
+
         let output: Output = match message {
             JupyterMessageContent::ExecuteResult(result) => Output::new(
                 &result.data,
@@ -392,6 +497,7 @@ impl ExecutionView {
             JupyterMessageContent::ClearOutput(options) => {
                 if !options.wait {
                     self.outputs.clear();
+                    self.notebook_outputs.clear();
                     cx.notify();
                     return;
                 }
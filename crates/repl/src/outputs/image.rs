@@ -2,16 +2,22 @@ use anyhow::Result;
 use base64::prelude::*;
 use gpui::{img, ClipboardItem, Image, ImageFormat, Pixels, RenderImage, WindowContext};
 use std::sync::Arc;
-use ui::{div, prelude::*, IntoElement, Styled};
+use ui::{div, prelude::*, IntoElement, Styled, Tooltip};
 
 use crate::outputs::OutputContent;
 
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 4.;
+const ZOOM_STEP: f32 = 1.25;
+
 /// ImageView renders an image inline in an editor, adapting to the line height to fit the image.
+/// The image can be zoomed in and out, like plots that are too small to read.
 pub struct ImageView {
     clipboard_image: Arc<Image>,
     height: u32,
     width: u32,
     image: Arc<RenderImage>,
+    zoom: f32,
 }
 
 impl ImageView {
@@ -26,9 +32,6 @@ impl ImageView {
             pixel.swap(0, 2);
         }
 
-        let height = data.height();
-        let width = data.width();
-
         let gpui_image_data = RenderImage::new(vec![image::Frame::new(data)]);
 
         let format = match format {
@@ -43,19 +46,70 @@ impl ImageView {
             }
         };
 
+        Ok(Self::new(format, bytes, Arc::new(gpui_image_data)))
+    }
+
+    /// Rasterizes an SVG, like the plots of matplotlib with the `svg` format.
+    pub fn from_svg(svg: &str, cx: &WindowContext) -> Result<Self> {
+        let bytes = svg.as_bytes().to_vec();
+        let gpui_image_data = Image {
+            format: ImageFormat::Svg,
+            bytes: bytes.clone(),
+            id: 0,
+        }
+        .to_image_data(cx)?;
+
+        Ok(Self::new(ImageFormat::Svg, bytes, gpui_image_data))
+    }
+
+    fn new(format: ImageFormat, bytes: Vec<u8>, image: Arc<RenderImage>) -> Self {
+        let size = image.size(0);
+
         // Convert back to a GPUI image for use with the clipboard
         let clipboard_image = Arc::new(Image {
             format,
             bytes,
-            id: gpui_image_data.id.0 as u64,
+            id: image.id.0 as u64,
         });
 
-        return Ok(ImageView {
+        ImageView {
             clipboard_image,
-            height,
-            width,
-            image: Arc::new(gpui_image_data),
-        });
+            height: size.height.0 as u32,
+            width: size.width.0 as u32,
+            image,
+            zoom: 1.,
+        }
+    }
+
+    fn set_zoom(&mut self, zoom: f32, cx: &mut ViewContext<Self>) {
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        cx.notify();
+    }
+
+    fn render_zoom_controls(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        h_flex()
+            .gap_1()
+            .child(
+                IconButton::new("zoom-out", IconName::Dash)
+                    .icon_size(IconSize::XSmall)
+                    .disabled(self.zoom <= MIN_ZOOM)
+                    .tooltip(|cx| Tooltip::text("Zoom Out", cx))
+                    .on_click(cx.listener(|this, _, cx| this.set_zoom(this.zoom / ZOOM_STEP, cx))),
+            )
+            .child(
+                Button::new("reset-zoom", format!("{:.0}%", self.zoom * 100.))
+                    .label_size(LabelSize::XSmall)
+                    .color(Color::Muted)
+                    .tooltip(|cx| Tooltip::text("Reset Zoom", cx))
+                    .on_click(cx.listener(|this, _, cx| this.set_zoom(1., cx))),
+            )
+            .child(
+                IconButton::new("zoom-in", IconName::Plus)
+                    .icon_size(IconSize::XSmall)
+                    .disabled(self.zoom >= MAX_ZOOM)
+                    .tooltip(|cx| Tooltip::text("Zoom In", cx))
+                    .on_click(cx.listener(|this, _, cx| this.set_zoom(this.zoom * ZOOM_STEP, cx))),
+            )
     }
 }
 
@@ -73,7 +127,18 @@ impl Render for ImageView {
 
         let image = self.image.clone();
 
-        div().h(Pixels(height)).w(Pixels(width)).child(img(image))
+        v_flex()
+            .id("image-output")
+            .max_w_full()
+            .overflow_x_scroll()
+            .child(self.render_zoom_controls(cx))
+            .child(
+                div()
+                    .flex_none()
+                    .h(Pixels(height * self.zoom))
+                    .w(Pixels(width * self.zoom))
+                    .child(img(image).size_full()),
+            )
     }
 }
 
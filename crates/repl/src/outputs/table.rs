@@ -54,8 +54,18 @@
 //! # Display the DataFrame
 //! display(df)
 //! ```
+//!
+//! ## HTML Tables
+//!
+//! Outputs without a table schema, like DataFrames displayed with the default options
+//! of Pandas, are shown from the first table of their `text/html` representation.
+//!
+//! Clicking on the heading of a column sorts the rows by it, in ascending then
+//! descending order, then back to the order of the output.
+use std::cmp::Ordering;
+
 use gpui::{AnyElement, ClipboardItem, TextRun};
-use runtimelib::datatable::TableSchema;
+use runtimelib::datatable::FieldType;
 use runtimelib::media::datatable::TabularDataResource;
 use serde_json::Value;
 use settings::Settings;
@@ -64,14 +74,31 @@ use ui::{div, prelude::*, v_flex, IntoElement, Styled};
 
 use crate::outputs::OutputContent;
 
-/// TableView renders a static table inline in a buffer.
-/// It uses the https://specs.frictionlessdata.io/tabular-data-resource/ specification for data interchange.
+/// TableView renders a table inline in a buffer, sortable by column.
+/// It uses the https://specs.frictionlessdata.io/tabular-data-resource/ specification for data interchange,
+/// or reads the table from HTML.
 pub struct TableView {
-    pub table: TabularDataResource,
+    columns: Vec<TableColumn>,
+    rows: Vec<Vec<String>>,
+    /// The indices of the rows, in the order they're shown in.
+    row_order: Vec<usize>,
+    sort: Option<TableSort>,
     pub widths: Vec<Pixels>,
     cached_clipboard_content: ClipboardItem,
 }
 
+struct TableColumn {
+    name: String,
+    /// Numeric columns are aligned to the right, and sorted by value.
+    numeric: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TableSort {
+    column: usize,
+    ascending: bool,
+}
+
 fn cell_content(row: &Value, field: &str) -> String {
     match row.get(&field) {
         Some(Value::String(s)) => s.clone(),
@@ -88,7 +115,62 @@ const TABLE_Y_PADDING_MULTIPLE: f32 = 0.5;
 
 impl TableView {
     pub fn new(table: &TabularDataResource, cx: &mut WindowContext) -> Self {
-        let mut widths = Vec::with_capacity(table.schema.fields.len());
+        let columns = table
+            .schema
+            .fields
+            .iter()
+            .map(|field| TableColumn {
+                name: field.name.clone(),
+                numeric: match field.field_type {
+                    FieldType::Number
+                    | FieldType::Integer
+                    | FieldType::Date
+                    | FieldType::Time
+                    | FieldType::Datetime
+                    | FieldType::Year
+                    | FieldType::Duration
+                    | FieldType::Yearmonth => true,
+                    _ => false,
+                },
+            })
+            .collect::<Vec<_>>();
+        let rows = table
+            .data
+            .iter()
+            .flatten()
+            .map(|row| {
+                columns
+                    .iter()
+                    .map(|column| cell_content(row, &column.name))
+                    .collect()
+            })
+            .collect();
+        Self::from_rows(columns, rows, cx)
+    }
+
+    /// Shows the first table of an HTML output, `None` if it has no table.
+    pub fn from_html(html: &str, cx: &mut WindowContext) -> Option<Self> {
+        let (header, rows) = parse_html_table(html)?;
+        let columns = header
+            .into_iter()
+            .enumerate()
+            .map(|(ix, name)| TableColumn {
+                name,
+                numeric: rows.iter().all(|row| {
+                    row.get(ix)
+                        .map_or(true, |cell| cell.is_empty() || cell.parse::<f64>().is_ok())
+                }),
+            })
+            .collect();
+        Some(Self::from_rows(columns, rows, cx))
+    }
+
+    fn from_rows(
+        columns: Vec<TableColumn>,
+        rows: Vec<Vec<String>>,
+        cx: &mut WindowContext,
+    ) -> Self {
+        let mut widths = Vec::with_capacity(columns.len());
 
         let text_system = cx.text_system();
         let text_style = cx.text_style();
@@ -103,24 +185,20 @@ impl TableView {
             strikethrough: None,
         }];
 
-        for field in table.schema.fields.iter() {
-            runs[0].len = field.name.len();
+        for (ix, column) in columns.iter().enumerate() {
+            runs[0].len = column.name.len();
+            // Leave room for the sort indicator in the heading.
             let mut width = text_system
-                .layout_line(&field.name, font_size, &runs)
+                .layout_line(&column.name, font_size, &runs)
                 .map(|layout| layout.width)
-                .unwrap_or(px(0.));
-
-            let Some(data) = table.data.as_ref() else {
-                widths.push(width);
-                continue;
-            };
+                .unwrap_or(px(0.))
+                + px(16.);
 
-            for row in data {
-                let content = cell_content(&row, &field.name);
+            for row in &rows {
+                let content = row.get(ix).map(String::as_str).unwrap_or_default();
                 runs[0].len = content.len();
-                let cell_width = cx
-                    .text_system()
-                    .layout_line(&content, font_size, &runs)
+                let cell_width = text_system
+                    .layout_line(content, font_size, &runs)
                     .map(|layout| layout.width)
                     .unwrap_or(px(0.));
 
@@ -130,10 +208,13 @@ impl TableView {
             widths.push(width)
         }
 
-        let cached_clipboard_content = Self::create_clipboard_content(&table);
+        let cached_clipboard_content = Self::create_clipboard_content(&columns, &rows);
 
         Self {
-            table: table.clone(),
+            row_order: (0..rows.len()).collect(),
+            columns,
+            rows,
+            sort: None,
             widths,
             cached_clipboard_content: ClipboardItem::new_string(cached_clipboard_content),
         }
@@ -150,129 +231,161 @@ impl TableView {
             .replace('>', "&gt;")
     }
 
-    fn create_clipboard_content(table: &TabularDataResource) -> String {
-        let data = match table.data.as_ref() {
-            Some(data) => data,
-            None => &Vec::new(),
-        };
-        let schema = table.schema.clone();
-
+    fn create_clipboard_content(columns: &[TableColumn], rows: &[Vec<String>]) -> String {
         let mut markdown = format!(
             "| {} |\n",
-            table
-                .schema
-                .fields
+            columns
                 .iter()
-                .map(|field| field.name.clone())
+                .map(|column| column.name.clone())
                 .collect::<Vec<_>>()
                 .join(" | ")
         );
 
         markdown.push_str("|---");
-        for _ in 1..table.schema.fields.len() {
+        for _ in 1..columns.len() {
             markdown.push_str("|---");
         }
         markdown.push_str("|\n");
 
-        let body = data
-            .iter()
-            .map(|record: &Value| {
-                let row_content = schema
-                    .fields
-                    .iter()
-                    .map(|field| Self::escape_markdown(&cell_content(record, &field.name)))
-                    .collect::<Vec<_>>();
-
-                row_content.join(" | ")
-            })
-            .collect::<Vec<String>>();
-
-        for row in body {
-            markdown.push_str(&format!("| {} |\n", row));
+        for row in rows {
+            let row_content = row
+                .iter()
+                .map(|cell| Self::escape_markdown(cell))
+                .collect::<Vec<_>>();
+            markdown.push_str(&format!("| {} |\n", row_content.join(" | ")));
         }
 
         markdown
     }
 
-    pub fn render_row(
+    /// Sorts by the column in ascending, then descending order, then goes back to the
+    /// order of the output.
+    fn toggle_sort(&mut self, column: usize, cx: &mut ViewContext<Self>) {
+        self.sort = match self.sort {
+            Some(sort) if sort.column == column && sort.ascending => Some(TableSort {
+                column,
+                ascending: false,
+            }),
+            Some(sort) if sort.column == column => None,
+            _ => Some(TableSort {
+                column,
+                ascending: true,
+            }),
+        };
+        self.row_order = match self.sort {
+            Some(sort) => sorted_row_order(
+                &self.rows,
+                sort.column,
+                self.columns[sort.column].numeric,
+                sort.ascending,
+            ),
+            None => (0..self.rows.len()).collect(),
+        };
+        cx.notify();
+    }
+
+    fn render_cell(
         &self,
-        schema: &TableSchema,
+        ix: usize,
+        content: impl IntoElement,
         is_header: bool,
-        row: &Value,
         cx: &WindowContext,
-    ) -> AnyElement {
+    ) -> Div {
         let theme = cx.theme();
-
         let line_height = cx.line_height();
+        let width = self.widths[ix] + px(22.);
 
-        let row_cells = schema
-            .fields
-            .iter()
-            .zip(self.widths.iter())
-            .map(|(field, width)| {
-                let container = match field.field_type {
-                    runtimelib::datatable::FieldType::String => div(),
-
-                    runtimelib::datatable::FieldType::Number
-                    | runtimelib::datatable::FieldType::Integer
-                    | runtimelib::datatable::FieldType::Date
-                    | runtimelib::datatable::FieldType::Time
-                    | runtimelib::datatable::FieldType::Datetime
-                    | runtimelib::datatable::FieldType::Year
-                    | runtimelib::datatable::FieldType::Duration
-                    | runtimelib::datatable::FieldType::Yearmonth => v_flex().items_end(),
-
-                    _ => div(),
-                };
-
-                let value = cell_content(row, &field.name);
-
-                let mut cell = container
-                    .min_w(*width + px(22.))
-                    .w(*width + px(22.))
-                    .child(value)
-                    .px_2()
-                    .py((TABLE_Y_PADDING_MULTIPLE / 2.0) * line_height)
-                    .border_color(theme.colors().border);
-
-                if is_header {
-                    cell = cell.border_1().bg(theme.colors().border_focused)
-                } else {
-                    cell = cell.border_1()
-                }
-                cell
-            })
-            .collect::<Vec<_>>();
+        let container = if self.columns[ix].numeric {
+            v_flex().items_end()
+        } else {
+            div()
+        };
 
+        let cell = container
+            .min_w(width)
+            .w(width)
+            .child(content)
+            .px_2()
+            .py((TABLE_Y_PADDING_MULTIPLE / 2.0) * line_height)
+            .border_1()
+            .border_color(theme.colors().border);
+
+        if is_header {
+            cell.bg(theme.colors().border_focused)
+        } else {
+            cell
+        }
+    }
+
+    fn total_width(&self) -> Pixels {
         let mut total_width = px(0.);
         for width in self.widths.iter() {
             // Width fudge factor: border + 2 (heading), padding
             total_width += *width + px(22.);
         }
+        total_width
+    }
+
+    fn render_header(&self, cx: &mut ViewContext<Self>) -> AnyElement {
+        let cells = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(ix, column)| {
+                let sort_icon = self.sort.filter(|sort| sort.column == ix).map(|sort| {
+                    Icon::new(if sort.ascending {
+                        IconName::ArrowUp
+                    } else {
+                        IconName::ArrowDown
+                    })
+                    .size(IconSize::XSmall)
+                    .color(Color::Muted)
+                });
+                let heading = h_flex()
+                    .id(("table-heading", ix))
+                    .gap_1()
+                    .cursor_pointer()
+                    .child(column.name.clone())
+                    .children(sort_icon)
+                    .on_click(cx.listener(move |this, _, cx| this.toggle_sort(ix, cx)));
+                self.render_cell(ix, heading, true, cx)
+            })
+            .collect::<Vec<_>>();
+
+        h_flex()
+            .w(self.total_width())
+            .children(cells)
+            .into_any_element()
+    }
+
+    pub fn render_row(&self, row: &[String], cx: &WindowContext) -> AnyElement {
+        let cells = (0..self.columns.len())
+            .map(|ix| {
+                let value = row.get(ix).cloned().unwrap_or_default();
+                self.render_cell(ix, value, false, cx)
+            })
+            .collect::<Vec<_>>();
 
         h_flex()
-            .w(total_width)
-            .children(row_cells)
+            .w(self.total_width())
+            .children(cells)
             .into_any_element()
     }
 }
 
 impl Render for TableView {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
-        let data = match &self.table.data {
-            Some(data) => data,
-            None => return div().into_any_element(),
-        };
-
-        let mut headings = serde_json::Map::new();
-        for field in &self.table.schema.fields {
-            headings.insert(field.name.clone(), Value::String(field.name.clone()));
+        if self.columns.is_empty() {
+            return div().into_any_element();
         }
-        let header = self.render_row(&self.table.schema, true, &Value::Object(headings), cx);
 
-        let body = data
+        let header = self.render_header(cx);
+
+        let body = self
+            .row_order
             .iter()
-            .map(|row| self.render_row(&self.table.schema, false, &row, cx));
+            .map(|ix| self.render_row(&self.rows[*ix], cx))
+            .collect::<Vec<_>>();
 
         v_flex()
             .id("table")
@@ -293,3 +406,223 @@ impl OutputContent for TableView {
         true
     }
 }
+
+/// The order of the rows sorted by a column. Numeric columns are sorted by value, with
+/// empty cells and cells that aren't numbers last.
+fn sorted_row_order(
+    rows: &[Vec<String>],
+    column: usize,
+    numeric: bool,
+    ascending: bool,
+) -> Vec<usize> {
+    let cell = |ix: usize| rows[ix].get(column).map(String::as_str).unwrap_or_default();
+    let mut order = (0..rows.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| {
+        let (a, b) = (cell(*a), cell(*b));
+        let ordering = if numeric {
+            match (a.parse::<f64>().ok(), b.parse::<f64>().ok()) {
+                (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+                (Some(_), None) => return Ordering::Less,
+                (None, Some(_)) => return Ordering::Greater,
+                (None, None) => a.cmp(b),
+            }
+        } else {
+            a.cmp(b)
+        };
+        if ascending {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    });
+    order
+}
+
+/// Reads the first table of an HTML document, as the cells of its heading and of its
+/// rows. The heading is the first row of the `thead`, or the first row of the table.
+fn parse_html_table(html: &str) -> Option<(Vec<String>, Vec<Vec<String>>)> {
+    // Lowercasing ASCII keeps the offsets of the tags the same as in the HTML.
+    let lowercase = html.to_ascii_lowercase();
+    let table_start = lowercase.find("<table")?;
+    let table_end = lowercase[table_start..]
+        .find("</table")
+        .map_or(html.len(), |end| table_start + end);
+    let head_end = lowercase[table_start..table_end]
+        .find("</thead")
+        .map(|end| table_start + end);
+
+    let mut header = None;
+    let mut rows = Vec::new();
+    let mut offset = table_start;
+    while let Some(row_start) = find_tag(&lowercase[offset..table_end], "tr") {
+        let row_start = offset + row_start;
+        let row_end = lowercase[row_start..table_end]
+            .find("</tr")
+            .map_or(table_end, |end| row_start + end);
+        let cells = parse_html_row(&html[row_start..row_end], &lowercase[row_start..row_end]);
+        offset = row_end;
+
+        let in_head = head_end.map_or(false, |head_end| row_start < head_end);
+        if header.is_none() && (in_head || head_end.is_none()) {
+            header = Some(cells);
+        } else if !in_head {
+            rows.push(cells);
+        }
+    }
+
+    let header = header?;
+    let column_count = rows
+        .iter()
+        .map(Vec::len)
+        .max()
+        .unwrap_or_default()
+        .max(header.len());
+    let pad = |mut cells: Vec<String>| {
+        cells.resize(column_count, String::new());
+        cells
+    };
+    Some((pad(header), rows.into_iter().map(pad).collect()))
+}
+
+fn parse_html_row(html: &str, lowercase: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut offset = 0;
+    loop {
+        let next_cell = [
+            find_tag(&lowercase[offset..], "td"),
+            find_tag(&lowercase[offset..], "th"),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        let Some(cell_start) = next_cell else {
+            break;
+        };
+        let cell_start = offset + cell_start;
+        let Some(content_start) = lowercase[cell_start..].find('>') else {
+            break;
+        };
+        let content_start = cell_start + content_start + 1;
+        let content_end = [
+            lowercase[content_start..].find("</td"),
+            lowercase[content_start..].find("</th"),
+            find_tag(&lowercase[content_start..], "td"),
+            find_tag(&lowercase[content_start..], "th"),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+        .map_or(html.len(), |end| content_start + end);
+        cells.push(html_text(&html[content_start..content_end]));
+        offset = content_end;
+    }
+    cells
+}
+
+/// Finds an opening tag, without matching longer tag names starting the same way.
+fn find_tag(lowercase: &str, tag: &str) -> Option<usize> {
+    let pattern = format!("<{tag}");
+    let mut offset = 0;
+    while let Some(start) = lowercase[offset..].find(&pattern) {
+        let start = offset + start;
+        let end = start + pattern.len();
+        match lowercase[end..].chars().next() {
+            Some('>' | '/') | Some(' ' | '\t' | '\n' | '\r') => return Some(start),
+            _ => offset = end,
+        }
+    }
+    None
+}
+
+/// The text of an HTML fragment, without its tags and with its whitespace collapsed.
+fn html_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_html_table() {
+        let html = r#"<div>
+<style scoped>
+    .dataframe tbody tr th { vertical-align: top; }
+</style>
+<table border="1" class="dataframe">
+  <thead>
+    <tr style="text-align: right;">
+      <th></th>
+      <th>name</th>
+      <th>age</th>
+    </tr>
+  </thead>
+  <tbody>
+    <tr>
+      <th>0</th>
+      <td>Alice &amp; <b>Bob</b></td>
+      <td>30</td>
+    </tr>
+    <tr>
+      <th>1</th>
+      <td>&lt;NA&gt;</td>
+    </tr>
+  </tbody>
+</table>
+<p>2 rows × 2 columns</p>
+</div>"#;
+        assert_eq!(
+            parse_html_table(html).unwrap(),
+            (
+                vec!["".to_string(), "name".to_string(), "age".to_string()],
+                vec![
+                    vec!["0".to_string(), "Alice & Bob".to_string(), "30".to_string()],
+                    vec!["1".to_string(), "<NA>".to_string(), "".to_string()],
+                ]
+            )
+        );
+
+        // Without a `thead`, the first row is the heading.
+        assert_eq!(
+            parse_html_table("<TABLE><TR><TH>a<TH>b</TR><TR><TD>1<TD>2</TR></TABLE>").unwrap(),
+            (
+                vec!["a".to_string(), "b".to_string()],
+                vec![vec!["1".to_string(), "2".to_string()]]
+            )
+        );
+
+        assert_eq!(parse_html_table("<p>no table</p>"), None);
+    }
+
+    #[test]
+    fn test_sorted_row_order() {
+        let rows = [
+            vec!["b".to_string(), "10".to_string()],
+            vec!["a".to_string(), "9.5".to_string()],
+            vec!["c".to_string(), "".to_string()],
+            vec!["d".to_string(), "-1".to_string()],
+        ]
+        .to_vec();
+        assert_eq!(sorted_row_order(&rows, 0, false, true), vec![1, 0, 2, 3]);
+        assert_eq!(sorted_row_order(&rows, 0, false, false), vec![3, 2, 0, 1]);
+        assert_eq!(sorted_row_order(&rows, 1, true, true), vec![3, 1, 0, 2]);
+        assert_eq!(sorted_row_order(&rows, 1, true, false), vec![2, 0, 1, 3]);
+    }
+}
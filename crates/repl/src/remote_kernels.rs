@@ -0,0 +1,341 @@
+//! Kernels running on a Jupyter server, started over its REST API and talked to
+//! over websockets, rather than launched locally and connected to with ZeroMQ.
+
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use anyhow::{anyhow, Context as _, Result};
+use async_tungstenite::tungstenite::{client::IntoClientRequest, http::HeaderValue, Message};
+use futures::{channel::mpsc, AsyncReadExt as _, SinkExt as _, StreamExt as _};
+use gpui::{BackgroundExecutor, Task};
+use http_client::{AsyncBody, HttpClient, Method, Request as HttpRequest};
+use runtimelib::{JupyterKernelspec, JupyterMessage, JupyterMessageContent};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use util::ResultExt as _;
+use uuid::Uuid;
+
+use crate::{KernelGateway, KernelSpecification};
+
+#[derive(Deserialize)]
+struct KernelspecsResponse {
+    kernelspecs: HashMap<String, RemoteKernelspec>,
+}
+
+#[derive(Deserialize)]
+struct RemoteKernelspec {
+    name: String,
+    spec: JupyterKernelspec,
+}
+
+#[derive(Deserialize)]
+struct KernelResponse {
+    id: String,
+}
+
+/// Lists the kernels the server can start.
+pub async fn remote_kernel_specifications(
+    gateway: KernelGateway,
+    http_client: Arc<dyn HttpClient>,
+) -> Result<Vec<KernelSpecification>> {
+    let body = send(
+        http_client.as_ref(),
+        &gateway,
+        Method::GET,
+        "api/kernelspecs",
+        None,
+    )
+    .await
+    .with_context(|| format!("failed to list the kernels of {}", gateway.url))?;
+    parse_kernelspecs(&gateway, &body)
+}
+
+fn parse_kernelspecs(gateway: &KernelGateway, body: &str) -> Result<Vec<KernelSpecification>> {
+    let response: KernelspecsResponse = serde_json::from_str(body)?;
+    let mut kernel_specifications = response
+        .kernelspecs
+        .into_values()
+        .map(|kernelspec| KernelSpecification {
+            name: kernelspec.name,
+            path: PathBuf::from(&gateway.url),
+            kernelspec: kernelspec.spec,
+            gateway: Some(gateway.clone()),
+        })
+        .collect::<Vec<_>>();
+    kernel_specifications.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(kernel_specifications)
+}
+
+/// A kernel started on a Jupyter server, shut down when dropped.
+pub struct RemoteKernel {
+    gateway: KernelGateway,
+    id: String,
+    http_client: Arc<dyn HttpClient>,
+    executor: BackgroundExecutor,
+}
+
+impl RemoteKernel {
+    pub async fn start(
+        kernel_specification: &KernelSpecification,
+        gateway: KernelGateway,
+        http_client: Arc<dyn HttpClient>,
+        executor: BackgroundExecutor,
+    ) -> Result<Self> {
+        let body = send(
+            http_client.as_ref(),
+            &gateway,
+            Method::POST,
+            "api/kernels",
+            Some(json!({ "name": kernel_specification.name })),
+        )
+        .await
+        .with_context(|| {
+            format!(
+                "failed to start kernel {} on {}",
+                kernel_specification.name, gateway.url
+            )
+        })?;
+        let response: KernelResponse = serde_json::from_str(&body)?;
+        Ok(Self {
+            gateway,
+            id: response.id,
+            http_client,
+            executor,
+        })
+    }
+
+    /// Connects to the channels of the kernel, returning the sender of the requests
+    /// to the kernel, the receiver of its messages, and the tasks forwarding them.
+    pub async fn connect(
+        &self,
+    ) -> Result<(
+        mpsc::Sender<JupyterMessage>,
+        mpsc::Receiver<JupyterMessage>,
+        Vec<Task<Result<()>>>,
+    )> {
+        let url = format!(
+            "{}?session_id={}",
+            websocket_url(
+                &self.gateway.url,
+                &format!("api/kernels/{}/channels", self.id)
+            ),
+            Uuid::new_v4()
+        );
+        let mut request = url.into_client_request()?;
+        if let Some(token) = &self.gateway.token {
+            request.headers_mut().insert(
+                "Authorization",
+                HeaderValue::from_str(&format!("token {token}"))?,
+            );
+        }
+        let (stream, _) = async_tungstenite::async_std::connect_async(request)
+            .await
+            .with_context(|| format!("failed to connect to kernel {}", self.id))?;
+        let (mut websocket_tx, mut websocket_rx) = stream.split();
+
+        let (request_tx, mut request_rx) = mpsc::channel::<JupyterMessage>(100);
+        let (mut messages_tx, messages_rx) = mpsc::channel(100);
+
+        let send_task = self.executor.spawn(async move {
+            while let Some(message) = request_rx.next().await {
+                websocket_tx
+                    .send(Message::Text(websocket_message(&message)?))
+                    .await?;
+            }
+            anyhow::Ok(())
+        });
+
+        let receive_task = self.executor.spawn(async move {
+            while let Some(message) = websocket_rx.next().await {
+                match message? {
+                    Message::Text(text) => {
+                        if let Some(message) = parse_websocket_message(&text).log_err() {
+                            messages_tx.send(message).await?;
+                        }
+                    }
+                    Message::Close(_) => break,
+                    _ => {}
+                }
+            }
+            anyhow::Ok(())
+        });
+
+        Ok((request_tx, messages_rx, vec![send_task, receive_task]))
+    }
+}
+
+impl Drop for RemoteKernel {
+    fn drop(&mut self) {
+        let http_client = self.http_client.clone();
+        let gateway = self.gateway.clone();
+        let path = format!("api/kernels/{}", self.id);
+        self.executor
+            .spawn(async move {
+                send(http_client.as_ref(), &gateway, Method::DELETE, &path, None)
+                    .await
+                    .log_err();
+            })
+            .detach();
+    }
+}
+
+async fn send(
+    http_client: &dyn HttpClient,
+    gateway: &KernelGateway,
+    method: Method,
+    path: &str,
+    body: Option<Value>,
+) -> Result<String> {
+    let mut request_builder = HttpRequest::builder()
+        .method(method)
+        .uri(format!("{}/{path}", gateway.url.trim_end_matches('/')))
+        .header("Accept", "application/json");
+    if let Some(token) = &gateway.token {
+        request_builder = request_builder.header("Authorization", format!("token {token}"));
+    }
+    let request = match body {
+        Some(body) => request_builder
+            .header("Content-Type", "application/json")
+            .body(AsyncBody::from(serde_json::to_string(&body)?))?,
+        None => request_builder.body(AsyncBody::default())?,
+    };
+
+    let mut response = http_client.send(request).await?;
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+    if response.status().is_success() {
+        Ok(body)
+    } else {
+        Err(anyhow!("{} {}", response.status(), body))
+    }
+}
+
+fn websocket_url(url: &str, path: &str) -> String {
+    let url = url.trim_end_matches('/');
+    let url = if let Some(url) = url.strip_prefix("https://") {
+        format!("wss://{url}")
+    } else if let Some(url) = url.strip_prefix("http://") {
+        format!("ws://{url}")
+    } else {
+        url.to_string()
+    };
+    format!("{url}/{path}")
+}
+
+/// Encodes a message in the JSON format of the websocket protocol of Jupyter servers.
+fn websocket_message(message: &JupyterMessage) -> Result<String> {
+    let channel = match message.content {
+        JupyterMessageContent::DebugRequest(_)
+        | JupyterMessageContent::InterruptRequest(_)
+        | JupyterMessageContent::ShutdownRequest(_) => "control",
+        _ => "shell",
+    };
+    let parent_header = match &message.parent_header {
+        Some(parent_header) => serde_json::to_value(parent_header)?,
+        None => json!({}),
+    };
+    Ok(serde_json::to_string(&json!({
+        "header": message.header,
+        "parent_header": parent_header,
+        "metadata": {},
+        "content": message.content,
+        "buffers": [],
+        "channel": channel,
+    }))?)
+}
+
+fn parse_websocket_message(text: &str) -> Result<JupyterMessage> {
+    let value: Value = serde_json::from_str(text)?;
+    let msg_type = value["header"]["msg_type"]
+        .as_str()
+        .context("message without a type")?;
+    let content = JupyterMessageContent::from_type_and_content(msg_type, value["content"].clone())?;
+    let mut message = JupyterMessage::new(content, None);
+    message.header = serde_json::from_value(value["header"].clone())?;
+    // Messages without a parent have an empty parent header.
+    message.parent_header = serde_json::from_value(value["parent_header"].clone())
+        .ok()
+        .flatten();
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use runtimelib::ExecuteRequest;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_kernelspecs() {
+        let gateway = KernelGateway {
+            url: "http://localhost:8888".into(),
+            token: None,
+        };
+        let body = r#"{
+            "default": "python3",
+            "kernelspecs": {
+                "python3": {
+                    "name": "python3",
+                    "spec": {
+                        "argv": ["python", "-m", "ipykernel_launcher", "-f", "{connection_file}"],
+                        "display_name": "Python 3 (ipykernel)",
+                        "language": "python"
+                    },
+                    "resources": {}
+                },
+                "ir": {
+                    "name": "ir",
+                    "spec": {
+                        "argv": ["R", "--slave", "-e", "IRkernel::main()", "--args", "{connection_file}"],
+                        "display_name": "R",
+                        "language": "R"
+                    },
+                    "resources": {}
+                }
+            }
+        }"#;
+        let kernel_specifications = parse_kernelspecs(&gateway, body).unwrap();
+        assert_eq!(
+            kernel_specifications
+                .iter()
+                .map(|spec| (spec.name.as_str(), spec.kernelspec.language.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("ir", "R"), ("python3", "python")]
+        );
+        assert!(kernel_specifications
+            .iter()
+            .all(|spec| spec.gateway.as_ref() == Some(&gateway)));
+    }
+
+    #[test]
+    fn test_websocket_url() {
+        assert_eq!(
+            websocket_url("http://localhost:8888/", "api/kernels/a/channels"),
+            "ws://localhost:8888/api/kernels/a/channels"
+        );
+        assert_eq!(
+            websocket_url("https://example.com/jupyter", "api/kernels/a/channels"),
+            "wss://example.com/jupyter/api/kernels/a/channels"
+        );
+    }
+
+    #[test]
+    fn test_websocket_message() {
+        let message: JupyterMessage = ExecuteRequest {
+            code: "1 + 1".into(),
+            ..ExecuteRequest::default()
+        }
+        .into();
+        let text = websocket_message(&message).unwrap();
+        let value: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(value["channel"], "shell");
+        assert_eq!(value["parent_header"], json!({}));
+
+        let parsed = parse_websocket_message(&text).unwrap();
+        assert_eq!(parsed.header.msg_id, message.header.msg_id);
+        assert!(parsed.parent_header.is_none());
+        match parsed.content {
+            JupyterMessageContent::ExecuteRequest(request) => assert_eq!(request.code, "1 + 1"),
+            content => panic!("unexpected content {content:?}"),
+        }
+    }
+}
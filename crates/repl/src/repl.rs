@@ -1,11 +1,17 @@
 mod components;
 mod jupyter_settings;
+mod kernel_picker;
 mod kernels;
+mod notebook;
+pub mod notebook_editor;
 mod outputs;
+mod remote_kernels;
 mod repl_editor;
 mod repl_sessions_ui;
 mod repl_store;
+mod repl_variables_ui;
 mod session;
+mod variables;
 
 use std::{sync::Arc, time::Duration};
 
@@ -15,13 +21,16 @@ use project::Fs;
 pub use runtimelib::ExecutionState;
 use settings::Settings as _;
 
-pub use crate::jupyter_settings::JupyterSettings;
+pub use crate::jupyter_settings::{JupyterSettings, KernelGateway};
+pub use crate::kernel_picker::toggle_kernel_picker;
 pub use crate::kernels::{Kernel, KernelSpecification, KernelStatus};
 pub use crate::repl_editor::*;
 pub use crate::repl_sessions_ui::{
-    ClearOutputs, Interrupt, ReplSessionsPage, Restart, Run, Sessions, Shutdown,
+    ChangeKernel, ClearOutputs, Interrupt, ReplSessionsPage, Restart, Run, Sessions, Shutdown,
+    Variables,
 };
 use crate::repl_store::ReplStore;
+pub use crate::repl_variables_ui::ReplVariablesPage;
 pub use crate::session::Session;
 use client::telemetry::Telemetry;
 
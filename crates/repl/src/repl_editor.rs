@@ -4,9 +4,10 @@ use std::ops::Range;
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
-use editor::Editor;
-use gpui::{prelude::*, AppContext, Entity, View, WeakView, WindowContext};
+use editor::{Editor, MultiBufferSnapshot, ToPoint as _};
+use gpui::{prelude::*, AppContext, Entity, Model, View, WeakView, WindowContext};
 use language::{BufferSnapshot, Language, Point};
+use multi_buffer::MultiBufferRow;
 
 use crate::repl_store::ReplStore;
 use crate::session::SessionEvent;
@@ -23,13 +24,11 @@ pub fn run(editor: WeakView<Editor>, move_down: bool, cx: &mut WindowContext) ->
         .update(cx, |editor, cx| editor.selections.newest_adjusted(cx))
         .range();
     let multibuffer = editor.read(cx).buffer().clone();
-    let Some(buffer) = multibuffer.read(cx).as_singleton() else {
-        return Ok(());
+    let (runnable_ranges, next_cell_point) = match multibuffer.read(cx).as_singleton() {
+        Some(buffer) => runnable_ranges(&buffer.read(cx).snapshot(), selected_range),
+        None => excerpt_runnable_ranges(&multibuffer.read(cx).snapshot(cx), selected_range),
     };
 
-    let (runnable_ranges, next_cell_point) =
-        runnable_ranges(&buffer.read(cx).snapshot(), selected_range);
-
     for runnable_range in runnable_ranges {
         let Some(language) = multibuffer.read(cx).language_at(runnable_range.start, cx) else {
             continue;
@@ -41,38 +40,11 @@ pub fn run(editor: WeakView<Editor>, move_down: bool, cx: &mut WindowContext) ->
                 .with_context(|| format!("No kernel found for language: {}", language.name()))
         })?;
 
-        let fs = store.read(cx).fs().clone();
-        let telemetry = store.read(cx).telemetry().clone();
-
         let session = if let Some(session) = store.read(cx).get_session(editor.entity_id()).cloned()
         {
             session
         } else {
-            let weak_editor = editor.downgrade();
-            let session = cx
-                .new_view(|cx| Session::new(weak_editor, fs, telemetry, kernel_specification, cx));
-
-            editor.update(cx, |_editor, cx| {
-                cx.notify();
-
-                cx.subscribe(&session, {
-                    let store = store.clone();
-                    move |_this, _session, event, cx| match event {
-                        SessionEvent::Shutdown(shutdown_event) => {
-                            store.update(cx, |store, _cx| {
-                                store.remove_session(shutdown_event.entity_id());
-                            });
-                        }
-                    }
-                })
-                .detach();
-            });
-
-            store.update(cx, |store, _cx| {
-                store.insert_session(editor.entity_id(), session.clone());
-            });
-
-            session
+            start_session(&editor, kernel_specification, &store, cx)
         };
 
         let selected_text;
@@ -96,6 +68,65 @@ pub fn run(editor: WeakView<Editor>, move_down: bool, cx: &mut WindowContext) ->
     anyhow::Ok(())
 }
 
+fn start_session(
+    editor: &View<Editor>,
+    kernel_specification: KernelSpecification,
+    store: &Model<ReplStore>,
+    cx: &mut WindowContext,
+) -> View<Session> {
+    let fs = store.read(cx).fs().clone();
+    let telemetry = store.read(cx).telemetry().clone();
+
+    let weak_editor = editor.downgrade();
+    let session =
+        cx.new_view(|cx| Session::new(weak_editor, fs, telemetry, kernel_specification, cx));
+
+    editor.update(cx, |_editor, cx| {
+        cx.notify();
+
+        cx.subscribe(&session, {
+            let store = store.clone();
+            move |_this, _session, event, cx| match event {
+                SessionEvent::Shutdown(shutdown_event) => {
+                    store.update(cx, |store, cx| {
+                        store.remove_session(shutdown_event.entity_id(), cx);
+                    });
+                }
+            }
+        })
+        .detach();
+    });
+
+    store.update(cx, |store, cx| {
+        store.insert_session(editor.entity_id(), session.clone(), cx);
+    });
+
+    session
+}
+
+/// Runs the code of the editor in a kernel started from the given specification,
+/// replacing the kernel of its session if it has one.
+pub fn change_kernel(
+    editor: WeakView<Editor>,
+    kernel_specification: KernelSpecification,
+    cx: &mut WindowContext,
+) -> Result<()> {
+    let store = ReplStore::global(cx);
+    let editor = editor.upgrade().context("editor was dropped")?;
+
+    match store.read(cx).get_session(editor.entity_id()).cloned() {
+        Some(session) => session.update(cx, |session, cx| {
+            session.change_kernel(kernel_specification, cx);
+            cx.notify();
+        }),
+        None => {
+            start_session(&editor, kernel_specification, &store, cx);
+        }
+    }
+
+    anyhow::Ok(())
+}
+
 pub enum SessionSupport {
     ActiveSession(View<Session>),
     Inactive(Box<KernelSpecification>),
@@ -263,6 +294,29 @@ fn jupytext_cells(
     (snippets, None)
 }
 
+/// In multibuffers, like the cells of a notebook, the excerpt under the cursor is run as a
+/// cell.
+fn excerpt_runnable_ranges(
+    snapshot: &MultiBufferSnapshot,
+    range: Range<Point>,
+) -> (Vec<Range<Point>>, Option<Point>) {
+    let Some(excerpt) = snapshot.excerpt_containing(range) else {
+        return (Vec::new(), None);
+    };
+    let excerpt_range = excerpt.map_range_from_buffer(0..excerpt.buffer().len());
+    let next_excerpt_start = excerpt_range.end + 1;
+
+    let start = excerpt_range.start.to_point(snapshot);
+    let mut end = excerpt_range.end.to_point(snapshot);
+    while end.row > start.row && snapshot.is_line_blank(MultiBufferRow(end.row)) {
+        end = Point::new(end.row - 1, snapshot.line_len(MultiBufferRow(end.row - 1)));
+    }
+
+    let next_cell =
+        (next_excerpt_start < snapshot.len()).then(|| next_excerpt_start.to_point(snapshot));
+    (vec![start..end], next_cell)
+}
+
 fn runnable_ranges(
     buffer: &BufferSnapshot,
     range: Range<Point>,
@@ -311,7 +365,7 @@ fn language_supported(language: &Arc<Language>) -> bool {
     }
 }
 
-fn get_language(editor: WeakView<Editor>, cx: &mut AppContext) -> Option<Arc<Language>> {
+pub(crate) fn get_language(editor: WeakView<Editor>, cx: &mut AppContext) -> Option<Arc<Language>> {
     let editor = editor.upgrade()?;
     let selection = editor.read(cx).selections.newest::<usize>(cx);
     let buffer = editor.read(cx).buffer().read(cx).snapshot(cx);
@@ -12,6 +12,7 @@ use workspace::{item::Item, Workspace};
 
 use crate::jupyter_settings::JupyterSettings;
 use crate::repl_store::ReplStore;
+use crate::repl_variables_ui::ReplVariablesPage;
 use crate::KernelSpecification;
 
 actions!(
@@ -24,6 +25,8 @@ actions!(
         Interrupt,
         Shutdown,
         Restart,
+        ChangeKernel,
+        Variables,
         RefreshKernelspecs
     ]
 );
@@ -46,6 +49,21 @@ pub fn init(cx: &mut AppContext) {
                 }
             });
 
+            workspace.register_action(|workspace, _: &Variables, cx| {
+                let existing = workspace
+                    .active_pane()
+                    .read(cx)
+                    .items()
+                    .find_map(|item| item.downcast::<ReplVariablesPage>());
+
+                if let Some(existing) = existing {
+                    workspace.activate_item(&existing, true, true, cx);
+                } else {
+                    let repl_variables_page = ReplVariablesPage::new(cx);
+                    workspace.add_item_to_active_pane(Box::new(repl_variables_page), None, true, cx)
+                }
+            });
+
             workspace.register_action(|_workspace, _: &RefreshKernelspecs, cx| {
                 let store = ReplStore::global(cx);
                 store.update(cx, |store, cx| {
@@ -140,6 +158,19 @@ pub fn init(cx: &mut AppContext) {
                 }
             })
             .detach();
+
+        editor
+            .register_action({
+                let editor_handle = editor_handle.clone();
+                move |_: &ChangeKernel, cx| {
+                    if !JupyterSettings::enabled(cx) {
+                        return;
+                    }
+
+                    crate::toggle_kernel_picker(editor_handle.clone(), cx);
+                }
+            })
+            .detach();
     })
     .detach();
 }
@@ -10,8 +10,8 @@ use gpui::{
 use project::Fs;
 use settings::{Settings, SettingsStore};
 
-use crate::kernels::kernel_specifications;
-use crate::{JupyterSettings, KernelSpecification, Session};
+use crate::kernels::{gateway_kernel_specifications, kernel_specifications};
+use crate::{JupyterSettings, KernelGateway, KernelSpecification, Session};
 
 struct GlobalReplStore(Model<ReplStore>);
 
@@ -22,6 +22,7 @@ pub struct ReplStore {
     enabled: bool,
     sessions: HashMap<EntityId, View<Session>>,
     kernel_specifications: Vec<KernelSpecification>,
+    kernel_gateways: Vec<KernelGateway>,
     telemetry: Arc<Telemetry>,
     _subscriptions: Vec<Subscription>,
 }
@@ -46,6 +47,12 @@ impl ReplStore {
     pub fn new(fs: Arc<dyn Fs>, telemetry: Arc<Telemetry>, cx: &mut ModelContext<Self>) -> Self {
         let subscriptions = vec![cx.observe_global::<SettingsStore>(move |this, cx| {
             this.set_enabled(JupyterSettings::enabled(cx), cx);
+
+            let kernel_gateways = &JupyterSettings::get_global(cx).kernel_gateways;
+            if this.kernel_gateways != *kernel_gateways {
+                this.kernel_gateways = kernel_gateways.clone();
+                this.refresh_kernelspecs(cx).detach_and_log_err(cx);
+            }
         })];
 
         let this = Self {
@@ -54,6 +61,7 @@ impl ReplStore {
             enabled: JupyterSettings::enabled(cx),
             sessions: HashMap::default(),
             kernel_specifications: Vec::new(),
+            kernel_gateways: JupyterSettings::get_global(cx).kernel_gateways.clone(),
             _subscriptions: subscriptions,
         };
         this.on_enabled_changed(cx);
@@ -107,8 +115,11 @@ impl ReplStore {
 
     pub fn refresh_kernelspecs(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
         let kernel_specifications = kernel_specifications(self.fs.clone());
+        let remote_kernel_specifications =
+            gateway_kernel_specifications(self.kernel_gateways.clone(), cx.http_client());
         cx.spawn(|this, mut cx| async move {
-            let kernel_specifications = kernel_specifications.await?;
+            let mut kernel_specifications = kernel_specifications.await?;
+            kernel_specifications.extend(remote_kernel_specifications.await);
 
             this.update(&mut cx, |this, cx| {
                 this.kernel_specifications = kernel_specifications;
@@ -150,11 +161,18 @@ impl ReplStore {
         self.sessions.get(&entity_id)
     }
 
-    pub fn insert_session(&mut self, entity_id: EntityId, session: View<Session>) {
+    pub fn insert_session(
+        &mut self,
+        entity_id: EntityId,
+        session: View<Session>,
+        cx: &mut ModelContext<Self>,
+    ) {
         self.sessions.insert(entity_id, session);
+        cx.notify();
     }
 
-    pub fn remove_session(&mut self, entity_id: EntityId) {
+    pub fn remove_session(&mut self, entity_id: EntityId, cx: &mut ModelContext<Self>) {
         self.sessions.remove(&entity_id);
+        cx.notify();
     }
 }
@@ -0,0 +1,226 @@
+use collections::HashMap;
+use gpui::{
+    prelude::*, AnyElement, AppContext, EntityId, EventEmitter, FocusHandle, FocusableView,
+    FontWeight, Subscription, View,
+};
+use ui::{prelude::*, KeyBinding, Tooltip};
+use workspace::item::{Item, ItemEvent};
+use workspace::{Workspace, WorkspaceId};
+
+use crate::repl_store::ReplStore;
+use crate::variables::KernelVariable;
+use crate::{Run, Session};
+
+/// Lists the globals of the kernel of each session.
+pub struct ReplVariablesPage {
+    focus_handle: FocusHandle,
+    session_subscriptions: HashMap<EntityId, Subscription>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ReplVariablesPage {
+    pub fn new(cx: &mut ViewContext<Workspace>) -> View<Self> {
+        cx.new_view(|cx: &mut ViewContext<Self>| {
+            let focus_handle = cx.focus_handle();
+            let store = ReplStore::global(cx);
+
+            let subscriptions = vec![
+                cx.on_focus_in(&focus_handle, |_this, cx| cx.notify()),
+                cx.on_focus_out(&focus_handle, |_this, _event, cx| cx.notify()),
+                cx.observe(&store, |this, _store, cx| {
+                    this.observe_sessions(cx);
+                    cx.notify();
+                }),
+            ];
+
+            let mut this = Self {
+                focus_handle,
+                session_subscriptions: HashMap::default(),
+                _subscriptions: subscriptions,
+            };
+            this.observe_sessions(cx);
+            this
+        })
+    }
+
+    fn observe_sessions(&mut self, cx: &mut ViewContext<Self>) {
+        let sessions = ReplStore::global(cx)
+            .read(cx)
+            .sessions()
+            .cloned()
+            .collect::<Vec<_>>();
+        self.session_subscriptions.retain(|entity_id, _| {
+            sessions
+                .iter()
+                .any(|session| session.entity_id() == *entity_id)
+        });
+        for session in sessions {
+            self.session_subscriptions
+                .entry(session.entity_id())
+                .or_insert_with(|| cx.observe(&session, |_this, _session, cx| cx.notify()));
+        }
+    }
+
+    fn render_session(&self, session: &View<Session>, cx: &mut ViewContext<Self>) -> AnyElement {
+        let session_id = session.entity_id();
+        let session_handle = session.clone();
+        let session = session.read(cx);
+        let title = match session.title(cx) {
+            Some(title) => format!("{} ({title})", session.kernel_specification.name),
+            None => session.kernel_specification.name.clone(),
+        };
+
+        let content = if !session.supports_variables() {
+            Label::new(format!(
+                "Variables aren't available for {} kernels",
+                session.kernel_specification.kernelspec.language
+            ))
+            .color(Color::Muted)
+            .into_any_element()
+        } else if let Some(error) = &session.variables_error {
+            Label::new(error.clone())
+                .color(Color::Error)
+                .into_any_element()
+        } else if session.variables.is_empty() {
+            Label::new("No variables")
+                .color(Color::Muted)
+                .into_any_element()
+        } else {
+            v_flex()
+                .child(variable_row(
+                    Label::new("Name").color(Color::Muted),
+                    Label::new("Type").color(Color::Muted),
+                    Label::new("Size").color(Color::Muted),
+                    Label::new("Value").color(Color::Muted),
+                ))
+                .children(session.variables.iter().map(render_variable))
+                .into_any_element()
+        };
+
+        v_flex()
+            .gap_1()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(title).weight(FontWeight::BOLD))
+                    .child(
+                        IconButton::new(("refresh-variables", session_id), IconName::RotateCw)
+                            .icon_size(IconSize::Small)
+                            .disabled(!session.supports_variables())
+                            .tooltip(|cx| Tooltip::text("Refresh Variables", cx))
+                            .on_click(move |_, cx| {
+                                session_handle.update(cx, |session, cx| {
+                                    session.refresh_variables(cx);
+                                });
+                            }),
+                    ),
+            )
+            .child(content)
+            .into_any_element()
+    }
+}
+
+fn render_variable(variable: &KernelVariable) -> impl IntoElement {
+    // Previews of multiline values, like tables, are cut to their first line.
+    let preview = variable
+        .preview
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    variable_row(
+        Label::new(variable.name.clone()),
+        Label::new(variable.type_name.clone()),
+        Label::new(variable.size.clone().unwrap_or_default()),
+        Label::new(preview).single_line(),
+    )
+}
+
+fn variable_row(
+    name: impl IntoElement,
+    type_name: impl IntoElement,
+    size: impl IntoElement,
+    preview: impl IntoElement,
+) -> impl IntoElement {
+    h_flex()
+        .gap_4()
+        .child(div().w(rems(12.)).overflow_hidden().child(name))
+        .child(div().w(rems(8.)).overflow_hidden().child(type_name))
+        .child(div().w(rems(6.)).overflow_hidden().child(size))
+        .child(div().flex_1().overflow_hidden().child(preview))
+}
+
+impl EventEmitter<ItemEvent> for ReplVariablesPage {}
+
+impl FocusableView for ReplVariablesPage {
+    fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for ReplVariablesPage {
+    type Event = ItemEvent;
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some("REPL Variables".into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("repl variables")
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<WorkspaceId>,
+        _: &mut ViewContext<Self>,
+    ) -> Option<View<Self>> {
+        None
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(workspace::item::ItemEvent)) {
+        f(*event)
+    }
+}
+
+impl Render for ReplVariablesPage {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let sessions = ReplStore::global(cx)
+            .read(cx)
+            .sessions()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let container = v_flex()
+            .id("repl-variables")
+            .p_4()
+            .gap_4()
+            .size_full()
+            .overflow_y_scroll()
+            .track_focus(&self.focus_handle);
+
+        if sessions.is_empty() {
+            let instructions = "To see the variables of a Jupyter kernel, select some code and use the 'repl::Run' command.";
+
+            return container
+                .child(Label::new("No Jupyter Kernel Sessions").size(LabelSize::Large))
+                .child(
+                    v_flex()
+                        .child(Label::new(instructions))
+                        .children(KeyBinding::for_action(&Run, cx)),
+                );
+        }
+
+        container
+            .child(Label::new("Jupyter Kernel Variables").size(LabelSize::Large))
+            .children(
+                sessions
+                    .iter()
+                    .map(|session| self.render_session(session, cx))
+                    .collect::<Vec<_>>(),
+            )
+    }
+}
@@ -3,6 +3,7 @@ use crate::KernelStatus;
 use crate::{
     kernels::{Kernel, KernelSpecification, RunningKernel},
     outputs::{ExecutionStatus, ExecutionView},
+    variables::{parse_variables, supports_variables, variables_request, KernelVariable},
 };
 use client::telemetry::Telemetry;
 use collections::{HashMap, HashSet};
@@ -17,8 +18,8 @@ use editor::{
 use futures::io::BufReader;
 use futures::{AsyncBufReadExt as _, FutureExt as _, StreamExt as _};
 use gpui::{
-    div, prelude::*, EntityId, EventEmitter, Model, Render, Subscription, Task, View, ViewContext,
-    WeakView,
+    div, prelude::*, AppContext, EntityId, EventEmitter, Model, Render, Subscription, Task, View,
+    ViewContext, WeakView,
 };
 use language::Point;
 use project::Fs;
@@ -29,6 +30,7 @@ use runtimelib::{
 use std::{env::temp_dir, ops::Range, sync::Arc, time::Duration};
 use theme::ActiveTheme;
 use ui::{prelude::*, IconButtonShape, Tooltip};
+use util::ResultExt as _;
 
 pub struct Session {
     fs: Arc<dyn Fs>,
@@ -38,6 +40,10 @@ pub struct Session {
     messaging_task: Option<Task<()>>,
     process_status_task: Option<Task<()>>,
     pub kernel_specification: KernelSpecification,
+    /// The globals of the kernel, refreshed after each execution.
+    pub variables: Vec<KernelVariable>,
+    pub variables_error: Option<String>,
+    variables_request_id: Option<String>,
     telemetry: Arc<Telemetry>,
     _buffer_subscription: Subscription,
 }
@@ -49,7 +55,7 @@ struct EditorBlock {
     execution_view: View<ExecutionView>,
 }
 
-type CloseBlockFn =
+pub(crate) type CloseBlockFn =
     Arc<dyn for<'a> Fn(CustomBlockId, &'a mut WindowContext) + Send + Sync + 'static>;
 
 impl EditorBlock {
@@ -95,7 +101,7 @@ impl EditorBlock {
                 // Take up at least one height for status, allow the editor to determine the real height based on the content from render
                 height: 1,
                 style: BlockStyle::Sticky,
-                render: Self::create_output_area_renderer(execution_view.clone(), on_close.clone()),
+                render: output_area_renderer(execution_view.clone(), on_close.clone()),
                 disposition: BlockDisposition::Below,
                 priority: 0,
             };
@@ -117,82 +123,83 @@ impl EditorBlock {
             execution_view.push_message(&message.content, cx);
         });
     }
+}
 
-    fn create_output_area_renderer(
-        execution_view: View<ExecutionView>,
-        on_close: CloseBlockFn,
-    ) -> RenderBlock {
-        let render = move |cx: &mut BlockContext| {
-            let execution_view = execution_view.clone();
-            let text_style = crate::outputs::plain::text_style(cx);
-
-            let gutter = cx.gutter_dimensions;
-
-            let block_id = cx.block_id;
-            let on_close = on_close.clone();
-
-            let rem_size = cx.rem_size();
-
-            let text_line_height = text_style.line_height_in_pixels(rem_size);
-
-            let close_button = h_flex()
-                .flex_none()
-                .items_center()
-                .justify_center()
-                .absolute()
-                .top(text_line_height / 2.)
-                .right(
-                    // 2px is a magic number to nudge the button just a bit closer to
-                    // the line number start
-                    gutter.full_width() / 2.0 - text_line_height / 2.0 - px(2.),
-                )
-                .w(text_line_height)
-                .h(text_line_height)
-                .child(
-                    IconButton::new(
-                        ("close_output_area", EntityId::from(cx.block_id)),
-                        IconName::Close,
-                    )
-                    .icon_size(IconSize::Small)
-                    .icon_color(Color::Muted)
-                    .size(ButtonSize::Compact)
-                    .shape(IconButtonShape::Square)
-                    .tooltip(|cx| Tooltip::text("Close output area", cx))
-                    .on_click(move |_, cx| {
-                        if let BlockId::Custom(block_id) = block_id {
-                            (on_close)(block_id, cx)
-                        }
-                    }),
-                );
-
-            div()
-                .flex()
-                .items_start()
-                .min_h(text_line_height)
-                .w_full()
-                .border_y_1()
-                .border_color(cx.theme().colors().border)
-                .bg(cx.theme().colors().background)
-                .child(
-                    div()
-                        .relative()
-                        .w(gutter.full_width())
-                        .h(text_line_height * 2)
-                        .child(close_button),
-                )
-                .child(
-                    div()
-                        .flex_1()
-                        .size_full()
-                        .py(text_line_height / 2.)
-                        .mr(gutter.width)
-                        .child(execution_view),
+/// Renders the outputs of an execution below the code it ran.
+pub(crate) fn output_area_renderer(
+    execution_view: View<ExecutionView>,
+    on_close: CloseBlockFn,
+) -> RenderBlock {
+    let render = move |cx: &mut BlockContext| {
+        let execution_view = execution_view.clone();
+        let text_style = crate::outputs::plain::text_style(cx);
+
+        let gutter = cx.gutter_dimensions;
+
+        let block_id = cx.block_id;
+        let on_close = on_close.clone();
+
+        let rem_size = cx.rem_size();
+
+        let text_line_height = text_style.line_height_in_pixels(rem_size);
+
+        let close_button = h_flex()
+            .flex_none()
+            .items_center()
+            .justify_center()
+            .absolute()
+            .top(text_line_height / 2.)
+            .right(
+                // 2px is a magic number to nudge the button just a bit closer to
+                // the line number start
+                gutter.full_width() / 2.0 - text_line_height / 2.0 - px(2.),
+            )
+            .w(text_line_height)
+            .h(text_line_height)
+            .child(
+                IconButton::new(
+                    ("close_output_area", EntityId::from(cx.block_id)),
+                    IconName::Close,
                 )
-                .into_any_element()
-        };
+                .icon_size(IconSize::Small)
+                .icon_color(Color::Muted)
+                .size(ButtonSize::Compact)
+                .shape(IconButtonShape::Square)
+                .tooltip(|cx| Tooltip::text("Close output area", cx))
+                .on_click(move |_, cx| {
+                    if let BlockId::Custom(block_id) = block_id {
+                        (on_close)(block_id, cx)
+                    }
+                }),
+            );
+
+        div()
+            .flex()
+            .items_start()
+            .min_h(text_line_height)
+            .w_full()
+            .border_y_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().background)
+            .child(
+                div()
+                    .relative()
+                    .w(gutter.full_width())
+                    .h(text_line_height * 2)
+                    .child(close_button),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .size_full()
+                    .py(text_line_height / 2.)
+                    .mr(gutter.width)
+                    .child(execution_view),
+            )
+            .into_any_element()
+    };
 
-        Box::new(render)
-    }
+    Box::new(render)
 }
 
 impl Session {
@@ -219,6 +226,9 @@ impl Session {
             process_status_task: None,
             blocks: HashMap::default(),
             kernel_specification,
+            variables: Vec::new(),
+            variables_error: None,
+            variables_request_id: None,
             _buffer_subscription: subscription,
             telemetry,
         };
@@ -242,6 +252,10 @@ impl Session {
             cx.entity_id().to_string(),
         );
 
+        self.variables.clear();
+        self.variables_error = None;
+        self.variables_request_id = None;
+
         let kernel = RunningKernel::new(
             self.kernel_specification.clone(),
             entity_id,
@@ -257,7 +271,10 @@ impl Session {
                 match kernel {
                     Ok((mut kernel, mut messages_rx)) => {
                         this.update(&mut cx, |session, cx| {
-                            let stderr = kernel.process.stderr.take();
+                            let stderr = kernel
+                                .process
+                                .as_mut()
+                                .and_then(|process| process.stderr.take());
 
                             cx.spawn(|_session, mut _cx| async move {
                                 if let None = stderr {
@@ -272,7 +289,10 @@ impl Session {
                             })
                             .detach();
 
-                            let stdout = kernel.process.stdout.take();
+                            let stdout = kernel
+                                .process
+                                .as_mut()
+                                .and_then(|process| process.stdout.take());
 
                             cx.spawn(|_session, mut _cx| async move {
                                 if let None = stdout {
@@ -286,10 +306,14 @@ impl Session {
                             })
                             .detach();
 
-                            let status = kernel.process.status();
+                            let status = kernel.process.as_mut().map(|process| process.status());
                             session.kernel(Kernel::RunningKernel(kernel), cx);
 
                             let process_status_task = cx.spawn(|session, mut cx| async move {
+                                // Kernels running on a server have no process to watch.
+                                let Some(status) = status else {
+                                    return;
+                                };
                                 let error_message = match status.await {
                                     Ok(status) => {
                                         if status.success() {
@@ -434,6 +458,30 @@ impl Session {
         self.blocks.clear();
     }
 
+    /// Removes the outputs of the code run in the range.
+    pub fn remove_outputs(&mut self, range: &Range<Anchor>, cx: &mut ViewContext<Self>) {
+        let Some(editor) = self.editor.upgrade() else {
+            return;
+        };
+
+        let mut blocks_to_remove: HashSet<CustomBlockId> = HashSet::default();
+
+        let buffer = editor.read(cx).buffer().read(cx).snapshot(cx);
+
+        self.blocks.retain(|_key, block| {
+            if range.overlaps(&block.code_range, &buffer) {
+                blocks_to_remove.insert(block.block_id);
+                false
+            } else {
+                true
+            }
+        });
+
+        editor.update(cx, |editor, cx| {
+            editor.remove_blocks(blocks_to_remove, None, cx);
+        });
+    }
+
     pub fn execute(
         &mut self,
         code: String,
@@ -457,24 +505,7 @@ impl Session {
 
         let message: JupyterMessage = execute_request.into();
 
-        let mut blocks_to_remove: HashSet<CustomBlockId> = HashSet::default();
-
-        let buffer = editor.read(cx).buffer().read(cx).snapshot(cx);
-
-        self.blocks.retain(|_key, block| {
-            if anchor_range.overlaps(&block.code_range, &buffer) {
-                blocks_to_remove.insert(block.block_id);
-                false
-            } else {
-                true
-            }
-        });
-
-        self.editor
-            .update(cx, |editor, cx| {
-                editor.remove_blocks(blocks_to_remove, None, cx);
-            })
-            .ok();
+        self.remove_outputs(&anchor_range, cx);
 
         let status = match &self.kernel {
             Kernel::Restarting => ExecutionStatus::Restarting,
@@ -562,6 +593,12 @@ impl Session {
             JupyterMessageContent::Status(status) => {
                 self.kernel.set_execution_state(&status.execution_state);
 
+                if matches!(status.execution_state, ExecutionState::Idle)
+                    && self.blocks.contains_key(parent_message_id)
+                {
+                    self.refresh_variables(cx);
+                }
+
                 self.telemetry.report_repl_event(
                     self.kernel_specification.kernelspec.language.clone(),
                     KernelStatus::from(&self.kernel).to_string(),
@@ -574,6 +611,20 @@ impl Session {
                 self.kernel.set_kernel_info(&reply);
                 cx.notify();
             }
+            JupyterMessageContent::ExecuteReply(reply)
+                if self.variables_request_id.as_ref() == Some(parent_message_id) =>
+            {
+                self.variables_request_id = None;
+                match parse_variables(reply) {
+                    Ok(variables) => {
+                        self.variables = variables;
+                        self.variables_error = None;
+                    }
+                    Err(error) => self.variables_error = Some(error.to_string()),
+                }
+                cx.notify();
+                return;
+            }
             JupyterMessageContent::UpdateDisplayData(update) => {
                 let display_id = if let Some(display_id) = update.transient.display_id.clone() {
                     display_id
@@ -597,6 +648,46 @@ impl Session {
         }
     }
 
+    /// The outputs of the last execution of code in the range, if they're still shown.
+    pub fn execution_view(
+        &self,
+        range: &Range<Anchor>,
+        cx: &AppContext,
+    ) -> Option<View<ExecutionView>> {
+        let editor = self.editor.upgrade()?;
+        let buffer = editor.read(cx).buffer().read(cx).snapshot(cx);
+        self.blocks
+            .values()
+            .find(|block| range.overlaps(&block.code_range, &buffer))
+            .map(|block| block.execution_view.clone())
+    }
+
+    /// The title of the buffer the code is run from.
+    pub fn title(&self, cx: &AppContext) -> Option<String> {
+        let editor = self.editor.upgrade()?;
+        let title = editor.read(cx).buffer().read(cx).title(cx).to_string();
+        Some(title)
+    }
+
+    pub fn supports_variables(&self) -> bool {
+        supports_variables(&self.kernel_specification.kernelspec.language)
+    }
+
+    /// Asks the kernel for its globals, unless it's already been asked.
+    pub fn refresh_variables(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.supports_variables()
+            || self.variables_request_id.is_some()
+            || !matches!(self.kernel, Kernel::RunningKernel(_))
+        {
+            return;
+        }
+        let Some(message) = variables_request().log_err() else {
+            return;
+        };
+        self.variables_request_id = Some(message.header.msg_id.clone());
+        self.send(message, cx).ok();
+    }
+
     pub fn interrupt(&mut self, cx: &mut ViewContext<Self>) {
         match &mut self.kernel {
             Kernel::RunningKernel(_kernel) => {
@@ -646,7 +737,7 @@ impl Session {
                     })
                     .ok();
 
-                    kernel.process.kill().ok();
+                    kernel.kill();
 
                     this.update(&mut cx, |session, cx| {
                         session.clear_outputs(cx);
@@ -666,6 +757,16 @@ impl Session {
         cx.notify();
     }
 
+    /// Replaces the kernel with a new one, started from another specification.
+    pub fn change_kernel(
+        &mut self,
+        kernel_specification: KernelSpecification,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.kernel_specification = kernel_specification;
+        self.restart(cx);
+    }
+
     pub fn restart(&mut self, cx: &mut ViewContext<Self>) {
         let kernel = std::mem::replace(&mut self.kernel, Kernel::Restarting);
 
@@ -692,7 +793,7 @@ impl Session {
                     cx.background_executor().timer(Duration::from_secs(1)).await;
 
                     // Force kill the kernel if it hasn't shut down
-                    kernel.process.kill().ok();
+                    kernel.kill();
 
                     // Start a new kernel
                     this.update(&mut cx, |session, cx| {
@@ -759,6 +860,14 @@ impl Render for Session {
             })
             .child(Label::new(self.kernel_specification.name.clone()))
             .children(status_text.map(|status_text| Label::new(format!("({status_text})"))))
+            .button(
+                Button::new("restart", "Restart")
+                    .style(ButtonStyle::Subtle)
+                    .disabled(self.kernel.is_shutting_down())
+                    .on_click(cx.listener(move |session, _, cx| {
+                        session.restart(cx);
+                    })),
+            )
             .button(
                 Button::new("shutdown", "Shutdown")
                     .style(ButtonStyle::Subtle)
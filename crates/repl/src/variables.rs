@@ -0,0 +1,148 @@
+//! The global variables of a kernel, listed by evaluating an expression in the
+//! kernel alongside an empty, silent execution.
+
+use anyhow::{anyhow, Context as _, Result};
+use runtimelib::{ExecuteReply, ExecuteRequest, JupyterMessage};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+const VARIABLES_EXPRESSION_NAME: &str = "zed_variables";
+
+/// Lists the globals of an IPython kernel as JSON, leaving out modules, functions,
+/// classes, private names, and the names IPython defines itself.
+const PYTHON_VARIABLES_EXPRESSION: &str = "__import__('json').dumps([\
+{'name': k, 'type': type(v).__name__, \
+'size': str(getattr(v, 'shape', '')) or (str(len(v)) if hasattr(v, '__len__') else None), \
+'preview': __import__('reprlib').repr(v)} \
+for k, v in list(globals().items()) \
+if not k.startswith('_') and k not in ('In', 'Out', 'exit', 'quit', 'get_ipython') \
+and not callable(v) and type(v).__name__ != 'module'])";
+
+/// A global variable of a kernel.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct KernelVariable {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    /// The shape or the length of the value, for values having one.
+    pub size: Option<String>,
+    pub preview: String,
+}
+
+pub fn supports_variables(language: &str) -> bool {
+    language.eq_ignore_ascii_case("python")
+}
+
+/// The request listing the variables, answered with an [`ExecuteReply`].
+pub fn variables_request() -> Result<JupyterMessage> {
+    let request: ExecuteRequest = serde_json::from_value(json!({
+        "code": "",
+        "silent": true,
+        "store_history": false,
+        "user_expressions": { VARIABLES_EXPRESSION_NAME: PYTHON_VARIABLES_EXPRESSION },
+        "allow_stdin": false,
+        "stop_on_error": false,
+    }))?;
+    Ok(request.into())
+}
+
+pub fn parse_variables(reply: &ExecuteReply) -> Result<Vec<KernelVariable>> {
+    variables_from_reply(&serde_json::to_value(reply)?)
+}
+
+fn variables_from_reply(reply: &Value) -> Result<Vec<KernelVariable>> {
+    let expression = &reply["user_expressions"][VARIABLES_EXPRESSION_NAME];
+    if expression["status"] != "ok" {
+        return Err(anyhow!(
+            "failed to list the variables: {} {}",
+            expression["ename"].as_str().unwrap_or_default(),
+            expression["evalue"].as_str().unwrap_or_default()
+        ));
+    }
+    let repr = expression["data"]["text/plain"]
+        .as_str()
+        .context("variables without a plain text representation")?;
+    let json = parse_python_str_repr(repr).context("unexpected representation of variables")?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Reads back a string from its `repr`, as long as it's printable ASCII, like the
+/// output of `json.dumps`.
+fn parse_python_str_repr(repr: &str) -> Option<String> {
+    let quote = repr
+        .chars()
+        .next()
+        .filter(|quote| *quote == '\'' || *quote == '"')?;
+    let contents = repr.strip_prefix(quote)?.strip_suffix(quote)?;
+    let mut string = String::with_capacity(contents.len());
+    let mut chars = contents.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            string.push(chars.next()?);
+        } else {
+            string.push(c);
+        }
+    }
+    Some(string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variables_from_reply() {
+        let reply = json!({
+            "status": "ok",
+            "execution_count": 3,
+            "user_expressions": {
+                "zed_variables": {
+                    "status": "ok",
+                    "data": {
+                        "text/plain": r#"'[{"name": "df", "type": "DataFrame", "size": "(3, 2)", "preview": "   a  b\\n0  1  4"}, {"name": "s", "type": "str", "size": "4", "preview": "\\"it\'s\\""}, {"name": "n", "type": "int", "size": null, "preview": "1"}]'"#
+                    },
+                    "metadata": {}
+                }
+            }
+        });
+        assert_eq!(
+            variables_from_reply(&reply).unwrap(),
+            vec![
+                KernelVariable {
+                    name: "df".into(),
+                    type_name: "DataFrame".into(),
+                    size: Some("(3, 2)".into()),
+                    preview: "   a  b\n0  1  4".into(),
+                },
+                KernelVariable {
+                    name: "s".into(),
+                    type_name: "str".into(),
+                    size: Some("4".into()),
+                    preview: "\"it's\"".into(),
+                },
+                KernelVariable {
+                    name: "n".into(),
+                    type_name: "int".into(),
+                    size: None,
+                    preview: "1".into(),
+                },
+            ]
+        );
+
+        let reply = json!({
+            "status": "ok",
+            "user_expressions": {
+                "zed_variables": {
+                    "status": "error",
+                    "ename": "NameError",
+                    "evalue": "name 'json' is not defined",
+                    "traceback": []
+                }
+            }
+        });
+        assert_eq!(
+            variables_from_reply(&reply).unwrap_err().to_string(),
+            "failed to list the variables: NameError name 'json' is not defined"
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Environments of variables for `.http` files, defined in
+//! `http-client.env.json` files next to them:
+//!
+//! ```json
+//! {
+//!   "$shared": { "version": "v1" },
+//!   "dev": { "host": "http://localhost:8080" },
+//!   "prod": { "host": "https://example.com" }
+//! }
+//! ```
+//!
+//! The variables of `$shared` are in every environment. Secrets can be kept
+//! in an `http-client.private.env.json` file of the same shape, whose
+//! variables take precedence.
+use collections::HashMap;
+use serde_json::Value;
+
+pub const ENVIRONMENT_FILE_NAME: &str = "http-client.env.json";
+pub const PRIVATE_ENVIRONMENT_FILE_NAME: &str = "http-client.private.env.json";
+
+/// The environment whose variables every other environment has.
+const SHARED_ENVIRONMENT: &str = "$shared";
+
+/// Returns the variables of the environment, from the contents of environment
+/// files in increasing order of precedence.
+pub fn environment_variables(
+    files: &[Value],
+    environment: Option<&str>,
+) -> HashMap<String, String> {
+    let mut variables = HashMap::default();
+    let environments = std::iter::once(SHARED_ENVIRONMENT).chain(environment);
+    for environment in environments {
+        for file in files {
+            let Some(Value::Object(values)) = file.get(environment) else {
+                continue;
+            };
+            for (name, value) in values {
+                let value = match value {
+                    Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                variables.insert(name.clone(), value);
+            }
+        }
+    }
+    variables
+}
+
+/// Returns the names of the environments defined by the files, other than the
+/// shared one.
+pub fn environment_names(files: &[Value]) -> Vec<String> {
+    let mut names = files
+        .iter()
+        .filter_map(Value::as_object)
+        .flat_map(|environments| environments.keys())
+        .filter(|name| name.as_str() != SHARED_ENVIRONMENT)
+        .cloned()
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_environment_variables() {
+        let public = json!({
+            "$shared": { "host": "https://example.com", "retries": 3 },
+            "dev": { "host": "http://localhost:8080", "user": "dev" },
+            "prod": { "user": "admin" },
+        });
+        let private = json!({
+            "dev": { "token": "secret" },
+            "$shared": { "token": "none" },
+        });
+        let files = [public, private];
+
+        let dev = environment_variables(&files, Some("dev"));
+        assert_eq!(dev["host"], "http://localhost:8080");
+        assert_eq!(dev["retries"], "3");
+        assert_eq!(dev["token"], "secret");
+        assert_eq!(dev["user"], "dev");
+
+        let shared = environment_variables(&files, None);
+        assert_eq!(shared["host"], "https://example.com");
+        assert_eq!(shared["token"], "none");
+        assert!(!shared.contains_key("user"));
+
+        assert_eq!(environment_names(&files), ["dev", "prod"]);
+    }
+}
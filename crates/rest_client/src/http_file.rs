@@ -0,0 +1,507 @@
+//! Reads the requests of `.http` files, in the format shared by the REST
+//! clients of other editors:
+//!
+//! ```http
+//! @host = https://example.com
+//!
+//! # @name login
+//! POST {{host}}/login HTTP/1.1
+//! Content-Type: application/json
+//!
+//! { "user": "{{user}}" }
+//!
+//! ###
+//!
+//! GET {{host}}/items?page=1
+//! ```
+//!
+//! Requests are separated by lines starting with `###`. Each has a request
+//! line, headers up to the first blank line, and a body after it. Variables
+//! are defined with `@name = value` lines and used as `{{name}}`.
+use std::ops::Range;
+
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+
+pub const METHODS: &[&str] = &[
+    "GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS", "TRACE", "CONNECT",
+];
+
+/// How deeply variables whose values use other variables are substituted.
+const MAX_VARIABLE_DEPTH: usize = 8;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HttpFile {
+    /// The variables defined with `@name = value` lines.
+    pub variables: HashMap<String, String>,
+    pub requests: Vec<HttpRequest>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpRequest {
+    /// The offsets of the request line.
+    pub request_line: Range<usize>,
+    /// The offsets of the request, from its request line to the end of its
+    /// body.
+    pub range: Range<usize>,
+    /// The name given to the request with a `# @name` comment.
+    pub name: Option<String>,
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+impl HttpRequest {
+    /// Returns the request with its variables substituted.
+    pub fn resolve(&self, variables: &HashMap<String, String>) -> Result<HttpRequest> {
+        Ok(HttpRequest {
+            url: substitute_variables(&self.url, variables)?,
+            headers: self
+                .headers
+                .iter()
+                .map(|(name, value)| Ok((name.clone(), substitute_variables(value, variables)?)))
+                .collect::<Result<_>>()?,
+            body: self
+                .body
+                .as_deref()
+                .map(|body| substitute_variables(body, variables))
+                .transpose()?,
+            ..self.clone()
+        })
+    }
+
+    /// Returns a short description of the request, such as `GET /items`.
+    pub fn title(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{name}: {} {}", self.method, self.url),
+            None => format!("{} {}", self.method, self.url),
+        }
+    }
+}
+
+/// The kinds of text highlighted in `.http` files and responses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HttpHighlight {
+    Comment,
+    Method,
+    Url,
+    HeaderName,
+    Variable,
+    SuccessStatus,
+    ErrorStatus,
+}
+
+impl HttpHighlight {
+    pub const ALL: [HttpHighlight; 7] = [
+        HttpHighlight::Comment,
+        HttpHighlight::Method,
+        HttpHighlight::Url,
+        HttpHighlight::HeaderName,
+        HttpHighlight::Variable,
+        HttpHighlight::SuccessStatus,
+        HttpHighlight::ErrorStatus,
+    ];
+}
+
+/// The part of a request that a line of it is in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Section {
+    BeforeRequestLine,
+    Headers,
+    Body,
+}
+
+fn lines_with_offsets(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start, line.trim_end_matches(['\n', '\r'])))
+    })
+}
+
+fn is_separator(line: &str) -> bool {
+    line.starts_with("###")
+}
+
+fn is_comment(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with('#') || line.starts_with("//")
+}
+
+/// Returns the name of a `# @name login` comment.
+fn request_name(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    let comment = line
+        .strip_prefix('#')
+        .or_else(|| line.strip_prefix("//"))?
+        .trim_start();
+    let name = comment.strip_prefix("@name")?;
+    name.starts_with(char::is_whitespace)
+        .then(|| name.trim())
+        .filter(|name| !name.is_empty())
+}
+
+/// Returns the name and value of a `@name = value` line.
+fn variable_definition(line: &str) -> Option<(&str, &str)> {
+    let (name, value) = line.trim_start().strip_prefix('@')?.split_once('=')?;
+    let name = name.trim();
+    (!name.is_empty() && !name.contains(char::is_whitespace)).then(|| (name, value.trim()))
+}
+
+/// Returns the method and URL of a request line, whose method may be left out.
+/// Lines that start with neither a method nor something like a URL aren't
+/// request lines.
+fn parse_request_line(line: &str) -> Option<(Option<Range<usize>>, Range<usize>)> {
+    let start = line.len() - line.trim_start().len();
+    let trimmed = line.trim();
+    let (method, rest_start) = match trimmed.split_once(char::is_whitespace) {
+        Some((method, _)) if METHODS.contains(&method) => {
+            let rest = &line[start + method.len()..];
+            let rest_start = start + method.len() + rest.len() - rest.trim_start().len();
+            (Some(start..start + method.len()), rest_start)
+        }
+        _ if METHODS.contains(&trimmed) => return None,
+        _ => {
+            let looks_like_url = ["http://", "https://", "{{", "/"]
+                .iter()
+                .any(|prefix| trimmed.starts_with(prefix));
+            if !looks_like_url {
+                return None;
+            }
+            (None, start)
+        }
+    };
+
+    // A trailing HTTP version isn't part of the URL.
+    let rest = line[rest_start..].trim_end();
+    let url = match rest.rsplit_once(char::is_whitespace) {
+        Some((url, version)) if version.starts_with("HTTP/") => url.trim_end(),
+        _ => rest,
+    };
+    (!url.is_empty()).then(|| (method, rest_start..rest_start + url.len()))
+}
+
+/// Reads the variables and requests of an `.http` file.
+pub fn parse_http_file(text: &str) -> HttpFile {
+    let mut file = HttpFile::default();
+    let mut section = Section::BeforeRequestLine;
+    let mut name = None;
+    let mut body_lines: Vec<(usize, &str)> = Vec::new();
+
+    fn finish_request(file: &mut HttpFile, body_lines: &mut Vec<(usize, &str)>) {
+        let Some(request) = file.requests.last_mut() else {
+            return;
+        };
+        while body_lines
+            .last()
+            .map_or(false, |(_, line)| line.trim().is_empty())
+        {
+            body_lines.pop();
+        }
+        if let Some((offset, line)) = body_lines.last() {
+            request.range.end = offset + line.len();
+            let body = body_lines
+                .iter()
+                .map(|(_, line)| *line)
+                .collect::<Vec<_>>()
+                .join("\n");
+            request.body = Some(body);
+        }
+        body_lines.clear();
+    }
+
+    for (offset, line) in lines_with_offsets(text) {
+        if is_separator(line) {
+            if section == Section::Body {
+                finish_request(&mut file, &mut body_lines);
+            }
+            section = Section::BeforeRequestLine;
+            name = None;
+            continue;
+        }
+
+        match section {
+            Section::BeforeRequestLine => {
+                if let Some((variable, value)) = variable_definition(line) {
+                    file.variables.insert(variable.into(), value.into());
+                } else if let Some(request_name) = request_name(line) {
+                    name = Some(request_name.to_string());
+                } else if is_comment(line) || line.trim().is_empty() {
+                    continue;
+                } else if let Some((method, url)) = parse_request_line(line) {
+                    file.requests.push(HttpRequest {
+                        request_line: offset..offset + line.len(),
+                        range: offset..offset + line.len(),
+                        name: name.take(),
+                        method: method.map_or("GET".into(), |method| line[method].into()),
+                        url: line[url].to_string(),
+                        headers: Vec::new(),
+                        body: None,
+                    });
+                    section = Section::Headers;
+                }
+            }
+            Section::Headers => {
+                let request = file.requests.last_mut().unwrap();
+                let trimmed = line.trim();
+                if trimmed.is_empty() {
+                    section = Section::Body;
+                } else if is_comment(line) {
+                    continue;
+                } else if request.headers.is_empty()
+                    && (trimmed.starts_with('?') || trimmed.starts_with('&'))
+                {
+                    // The query of a long URL may continue on the lines after
+                    // the request line.
+                    request.url.push_str(trimmed);
+                    request.range.end = offset + line.len();
+                } else if let Some((header, value)) = line.split_once(':') {
+                    request
+                        .headers
+                        .push((header.trim().to_string(), value.trim().to_string()));
+                    request.range.end = offset + line.len();
+                }
+            }
+            Section::Body => body_lines.push((offset, line)),
+        }
+    }
+    if section == Section::Body {
+        finish_request(&mut file, &mut body_lines);
+    }
+    file
+}
+
+/// Replaces the `{{name}}` variables of the text with their values. System
+/// variables start with `$`: `{{$timestamp}}` is the current Unix time, and
+/// `{{$processEnv NAME}}` is the value of an environment variable of Zed.
+pub fn substitute_variables(text: &str, variables: &HashMap<String, String>) -> Result<String> {
+    substitute_variables_at_depth(text, variables, 0)
+}
+
+fn substitute_variables_at_depth(
+    text: &str,
+    variables: &HashMap<String, String>,
+    depth: usize,
+) -> Result<String> {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        let name = rest[start + 2..start + end].trim();
+        let value = if let Some(system_variable) = name.strip_prefix('$') {
+            system_variable_value(system_variable)?
+        } else {
+            let value = variables
+                .get(name)
+                .ok_or_else(|| anyhow!("undefined variable {{{{{name}}}}}"))?;
+            if depth < MAX_VARIABLE_DEPTH {
+                substitute_variables_at_depth(value, variables, depth + 1)?
+            } else {
+                value.clone()
+            }
+        };
+        result.push_str(&value);
+        rest = &rest[start + end + 2..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+fn system_variable_value(variable: &str) -> Result<String> {
+    let (name, argument) = variable
+        .split_once(char::is_whitespace)
+        .map_or((variable, ""), |(name, argument)| (name, argument.trim()));
+    match name {
+        "timestamp" => Ok(std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs()
+            .to_string()),
+        "guid" => Ok(uuid::Uuid::new_v4().to_string()),
+        "processEnv" => std::env::var(argument)
+            .map_err(|_| anyhow!("environment variable {argument} isn't set")),
+        _ => Err(anyhow!("unknown system variable ${name}")),
+    }
+}
+
+/// Returns the ranges of the text to highlight in an `.http` file, or in a
+/// response shown in one.
+pub fn highlights(text: &str) -> Vec<(HttpHighlight, Range<usize>)> {
+    let mut highlights = Vec::new();
+    let mut section = Section::BeforeRequestLine;
+    for (offset, line) in lines_with_offsets(text) {
+        if is_separator(line) {
+            highlights.push((HttpHighlight::Comment, offset..offset + line.len()));
+            section = Section::BeforeRequestLine;
+            continue;
+        }
+        if section != Section::Body && is_comment(line) {
+            highlights.push((HttpHighlight::Comment, offset..offset + line.len()));
+            continue;
+        }
+
+        match section {
+            Section::BeforeRequestLine => {
+                if let Some(status) = response_status(line) {
+                    let kind = if (200..400).contains(&status.1) {
+                        HttpHighlight::SuccessStatus
+                    } else {
+                        HttpHighlight::ErrorStatus
+                    };
+                    highlights.push((kind, offset + status.0.start..offset + status.0.end));
+                    section = Section::Headers;
+                } else if let Some((method, url)) = parse_request_line(line) {
+                    if let Some(method) = method {
+                        highlights.push((
+                            HttpHighlight::Method,
+                            offset + method.start..offset + method.end,
+                        ));
+                    }
+                    highlights.push((HttpHighlight::Url, offset + url.start..offset + url.end));
+                    section = Section::Headers;
+                }
+            }
+            Section::Headers => {
+                if line.trim().is_empty() {
+                    section = Section::Body;
+                } else if let Some((header, _)) = line.split_once(':') {
+                    highlights.push((HttpHighlight::HeaderName, offset..offset + header.len()));
+                }
+            }
+            Section::Body => {}
+        }
+
+        let mut rest = line;
+        let mut rest_offset = offset;
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}") else {
+                break;
+            };
+            let range = rest_offset + start..rest_offset + start + end + 2;
+            highlights.push((HttpHighlight::Variable, range));
+            rest_offset += start + end + 2;
+            rest = &rest[start + end + 2..];
+        }
+    }
+    highlights
+}
+
+/// Returns the range and value of the status code of a response's status line,
+/// such as `HTTP/1.1 200 OK`.
+fn response_status(line: &str) -> Option<(Range<usize>, u16)> {
+    let rest = line.strip_prefix("HTTP/")?;
+    let version_len = rest.find(' ')?;
+    let start = "HTTP/".len() + version_len + 1;
+    let code = line[start..].split(' ').next()?;
+    let status = code.parse().ok()?;
+    Some((start..start + code.len(), status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_http_file() {
+        let text = "@host = https://example.com\n\
+                    @user = ada\n\
+                    \n\
+                    # @name login\n\
+                    POST {{host}}/login HTTP/1.1\n\
+                    Content-Type: application/json\n\
+                    # A comment among the headers.\n\
+                    \n\
+                    {\n  \"user\": \"{{user}}\"\n}\n\
+                    \n\
+                    ###\n\
+                    {{host}}/items\n  ?page=1\n  &size=10\n\
+                    ### Last\n\
+                    // Not a request\n\
+                    DELETE /items/1\n";
+        let file = parse_http_file(text);
+        assert_eq!(file.variables.get("host").unwrap(), "https://example.com");
+        assert_eq!(file.requests.len(), 3);
+
+        let login = &file.requests[0];
+        assert_eq!(login.name.as_deref(), Some("login"));
+        assert_eq!(login.method, "POST");
+        assert_eq!(login.url, "{{host}}/login");
+        assert_eq!(
+            login.headers,
+            [("Content-Type".to_string(), "application/json".to_string())]
+        );
+        assert_eq!(
+            login.body.as_deref(),
+            Some("{\n  \"user\": \"{{user}}\"\n}")
+        );
+        assert_eq!(
+            &text[login.request_line.clone()],
+            "POST {{host}}/login HTTP/1.1"
+        );
+        assert!(text[login.range.clone()].ends_with("}"));
+
+        let items = &file.requests[1];
+        assert_eq!(items.method, "GET");
+        assert_eq!(items.url, "{{host}}/items?page=1&size=10");
+        assert_eq!(items.body, None);
+
+        let delete = &file.requests[2];
+        assert_eq!(
+            (delete.method.as_str(), delete.url.as_str()),
+            ("DELETE", "/items/1")
+        );
+        assert_eq!(delete.name, None);
+
+        let resolved = login.resolve(&file.variables).unwrap();
+        assert_eq!(resolved.url, "https://example.com/login");
+        assert_eq!(resolved.body.as_deref(), Some("{\n  \"user\": \"ada\"\n}"));
+    }
+
+    #[test]
+    fn test_substitute_variables() {
+        let variables = HashMap::from_iter([
+            ("base".to_string(), "{{scheme}}://example.com".to_string()),
+            ("scheme".to_string(), "https".to_string()),
+            ("loop".to_string(), "{{loop}}".to_string()),
+        ]);
+        assert_eq!(
+            substitute_variables("{{ base }}/a?b={{scheme}}", &variables).unwrap(),
+            "https://example.com/a?b=https"
+        );
+        assert!(substitute_variables("{{missing}}", &variables).is_err());
+        assert!(substitute_variables("{{loop}}", &variables).is_ok());
+        assert_eq!(
+            substitute_variables("{{ unclosed", &variables).unwrap(),
+            "{{ unclosed"
+        );
+        assert!(substitute_variables("{{$timestamp}}", &variables)
+            .unwrap()
+            .parse::<u64>()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_highlights() {
+        let text =
+            "# Items\nGET {{host}}/items\nAccept: */*\n\n{\"a\": 1}\n###\nHTTP/1.1 404 Not Found";
+        let highlighted = highlights(text)
+            .into_iter()
+            .map(|(kind, range)| (kind, &text[range]))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            highlighted,
+            [
+                (HttpHighlight::Comment, "# Items"),
+                (HttpHighlight::Method, "GET"),
+                (HttpHighlight::Url, "{{host}}/items"),
+                (HttpHighlight::Variable, "{{host}}"),
+                (HttpHighlight::HeaderName, "Accept"),
+                (HttpHighlight::Comment, "###"),
+                (HttpHighlight::ErrorStatus, "404"),
+            ]
+        );
+    }
+}
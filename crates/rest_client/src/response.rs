@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+/// Writes out a response as its status line, headers, and body, with JSON
+/// bodies pretty-printed.
+pub fn format_response(status_line: &str, headers: &[(String, String)], body: &[u8]) -> String {
+    let mut text = String::new();
+    text.push_str(status_line);
+    text.push('\n');
+    for (name, value) in headers {
+        text.push_str(&format!("{name}: {value}\n"));
+    }
+
+    if body.is_empty() {
+        return text;
+    }
+    text.push('\n');
+    let is_json = headers.iter().any(|(name, value)| {
+        name.eq_ignore_ascii_case("content-type") && value.to_ascii_lowercase().contains("json")
+    });
+    match std::str::from_utf8(body) {
+        Ok(body) => {
+            let pretty_body = is_json
+                .then(|| serde_json::from_str::<serde_json::Value>(body).ok())
+                .flatten()
+                .and_then(|value| serde_json::to_string_pretty(&value).ok());
+            match pretty_body {
+                Some(pretty_body) => {
+                    text.push_str(&pretty_body);
+                    text.push('\n');
+                }
+                None => text.push_str(body),
+            }
+        }
+        Err(_) => text.push_str(&format!("({} bytes of binary data)\n", body.len())),
+    }
+    text
+}
+
+/// Returns a short summary of a response, such as `200 OK, 12 ms`.
+pub fn response_summary(status: u16, reason: Option<&str>, elapsed: Duration) -> String {
+    match reason {
+        Some(reason) => format!("{status} {reason}, {} ms", elapsed.as_millis()),
+        None => format!("{status}, {} ms", elapsed.as_millis()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_response() {
+        let headers = vec![
+            (
+                "Content-Type".to_string(),
+                "application/json; charset=utf-8".to_string(),
+            ),
+            ("Content-Length".to_string(), "16".to_string()),
+        ];
+        assert_eq!(
+            format_response("HTTP/1.1 200 OK", &headers, br#"{"a":[1,2],"b":{}}"#),
+            "HTTP/1.1 200 OK\n\
+             Content-Type: application/json; charset=utf-8\n\
+             Content-Length: 16\n\
+             \n\
+             {\n  \"a\": [\n    1,\n    2\n  ],\n  \"b\": {}\n}\n"
+        );
+
+        let headers = vec![("Content-Type".to_string(), "text/plain".to_string())];
+        assert_eq!(
+            format_response("HTTP/1.1 404 Not Found", &headers, b"{\"a\":1}"),
+            "HTTP/1.1 404 Not Found\nContent-Type: text/plain\n\n{\"a\":1}"
+        );
+        assert_eq!(
+            format_response("HTTP/2.0 204 No Content", &[], b""),
+            "HTTP/2.0 204 No Content\n"
+        );
+        assert_eq!(
+            format_response("HTTP/1.1 200 OK", &[], &[0xff, 0xfe]),
+            "HTTP/1.1 200 OK\n\n(2 bytes of binary data)\n"
+        );
+    }
+}
@@ -0,0 +1,609 @@
+//! A REST client for `.http` files. Each request of a file gets a "Send
+//! Request" button above it, and its response is shown in an editor split off
+//! to the side, with variables substituted from the file and from the
+//! environment files next to it.
+mod environment;
+mod http_file;
+mod response;
+
+use std::{
+    ops::Range,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context as _, Result};
+use collections::HashSet;
+use editor::{
+    display_map::{BlockContext, BlockDisposition, BlockProperties, BlockStyle, CustomBlockId},
+    Addon, Editor, EditorEvent, EditorMode,
+};
+use futures::AsyncReadExt as _;
+use gpui::{
+    actions, AppContext, HighlightStyle, KeyContext, Model, Render, Task, View, ViewContext,
+    WeakView,
+};
+use http_client::{AsyncBody, HttpClient, Method, Request};
+use language::{Buffer, Capability, Point};
+use multi_buffer::MultiBuffer;
+use project::Fs;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use ui::prelude::*;
+use util::ResultExt;
+use workspace::SplitDirection;
+
+pub use environment::*;
+pub use http_file::*;
+pub use response::*;
+
+/// How long to wait after an `.http` file is edited before reading its
+/// requests again.
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+enum RestClientHighlight<const KIND: usize> {}
+
+actions!(rest_client, [SendRequest]);
+
+/// Settings for sending the requests of `.http` files.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct RestClientSettings {
+    /// The environment of the `http-client.env.json` files to take variables
+    /// from, in addition to the shared one.
+    ///
+    /// Default: null
+    pub environment: Option<String>,
+}
+
+impl Settings for RestClientSettings {
+    const KEY: Option<&'static str> = Some("rest_client");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+pub fn init(cx: &mut AppContext) {
+    RestClientSettings::register(cx);
+    cx.observe_new_views(RestClientView::register).detach();
+}
+
+struct RestClientAddon {
+    view: View<RestClientView>,
+}
+
+impl Addon for RestClientAddon {
+    fn extend_key_context(&self, key_context: &mut KeyContext, _: &AppContext) {
+        key_context.add("http_file");
+    }
+
+    fn to_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// The state of an editor of an `.http` file, or of a response shown in one.
+struct RestClientView {
+    editor: WeakView<Editor>,
+    /// The rows of the request lines that have "Send Request" buttons.
+    request_rows: Vec<u32>,
+    blocks: Vec<CustomBlockId>,
+    /// The row of the request line of the request being sent.
+    sending_row: Option<u32>,
+    response_editor: Option<WeakView<Editor>>,
+    refresh_task: Option<Task<()>>,
+    send_task: Option<Task<()>>,
+}
+
+impl Render for RestClientView {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        gpui::Empty
+    }
+}
+
+impl RestClientView {
+    fn register(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        if editor.mode() != EditorMode::Full {
+            return;
+        }
+        let multibuffer = editor.buffer().clone();
+        if !multibuffer.read(cx).is_singleton() {
+            return;
+        }
+
+        cx.subscribe(&multibuffer, |editor, _, event, cx| {
+            if let multi_buffer::Event::LanguageChanged(_) = event {
+                Self::update_http_mode(editor, cx);
+            }
+        })
+        .detach();
+        Self::update_http_mode(editor, cx);
+    }
+
+    /// Turns the REST client on or off, as the editor's buffer is or isn't an
+    /// `.http` file.
+    fn update_http_mode(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        let is_http = editor
+            .buffer()
+            .read(cx)
+            .as_singleton()
+            .and_then(|buffer| buffer.read(cx).language().cloned())
+            .map_or(false, |language| language.name().as_ref() == "HTTP");
+        let is_active = editor.addon::<RestClientAddon>().is_some();
+        if is_http && !is_active {
+            Self::activate(editor, cx);
+        } else if !is_http && is_active {
+            Self::deactivate(editor, cx);
+        }
+    }
+
+    fn activate(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        let view = Self::new(cx);
+        editor.register_addon(RestClientAddon { view: view.clone() });
+
+        view.update(cx, |view, cx| {
+            let subscription = editor.register_action(
+                cx.listener(|view, _: &SendRequest, cx| view.send_request_at_cursor(cx)),
+            );
+            cx.on_release(|_, _, _| drop(subscription)).detach();
+            view.refresh(cx);
+        });
+    }
+
+    fn deactivate(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        if let Some(addon) = editor.addon::<RestClientAddon>() {
+            let blocks = addon
+                .view
+                .update(cx, |view, _| std::mem::take(&mut view.blocks));
+            editor.remove_blocks(blocks.into_iter().collect(), None, cx);
+        }
+        editor.unregister_addon::<RestClientAddon>();
+        set_highlights(editor, vec![Vec::new(); HttpHighlight::ALL.len()], cx);
+    }
+
+    fn new(cx: &mut ViewContext<Editor>) -> View<Self> {
+        let editor = cx.view().clone();
+        cx.new_view(|cx: &mut ViewContext<Self>| {
+            cx.subscribe(&editor, |view, _, event, cx| {
+                if let EditorEvent::BufferEdited = event {
+                    view.refresh(cx);
+                }
+            })
+            .detach();
+
+            Self {
+                editor: editor.downgrade(),
+                request_rows: Vec::new(),
+                blocks: Vec::new(),
+                sending_row: None,
+                response_editor: None,
+                refresh_task: None,
+                send_task: None,
+            }
+        })
+    }
+
+    fn buffer(&self, cx: &AppContext) -> Option<Model<Buffer>> {
+        let editor = self.editor.upgrade()?;
+        let buffer = editor.read(cx).buffer().read(cx).as_singleton()?;
+        Some(buffer)
+    }
+
+    /// Reads the requests of the file again, to update their buttons and
+    /// highlights.
+    fn refresh(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(buffer) = self.buffer(cx) else {
+            return;
+        };
+        self.refresh_task = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(REFRESH_DEBOUNCE).await;
+            let Some((snapshot, capability)) = buffer
+                .update(&mut cx, |buffer, _| {
+                    (buffer.snapshot(), buffer.capability())
+                })
+                .ok()
+            else {
+                return;
+            };
+            let (file, highlights, snapshot) = cx
+                .background_executor()
+                .spawn(async move {
+                    let text = snapshot.text();
+                    (parse_http_file(&text), highlights(&text), snapshot)
+                })
+                .await;
+            this.update(&mut cx, |this, cx| {
+                // Responses are shown read-only, and have no requests to send.
+                let request_rows = if capability == Capability::ReadOnly {
+                    Vec::new()
+                } else {
+                    file.requests
+                        .iter()
+                        .map(|request| snapshot.offset_to_point(request.request_line.start).row)
+                        .collect()
+                };
+                if request_rows != this.request_rows {
+                    this.request_rows = request_rows;
+                    this.update_blocks(cx);
+                }
+                this.update_highlights(highlights, cx);
+            })
+            .ok();
+        }));
+    }
+
+    fn update_blocks(&mut self, cx: &mut ViewContext<Self>) {
+        let old_blocks = self.blocks.drain(..).collect::<HashSet<_>>();
+        let view = cx.view().downgrade();
+        let request_rows = self.request_rows.clone();
+        let sending_row = self.sending_row;
+        self.blocks = self
+            .editor
+            .update(cx, |editor, cx| {
+                editor.remove_blocks(old_blocks, None, cx);
+                // The points of a singleton multibuffer are those of its
+                // buffer.
+                let multibuffer = editor.buffer().read(cx).snapshot(cx);
+                let blocks = request_rows.into_iter().map(|row| {
+                    let sending = sending_row == Some(row);
+                    let view = view.clone();
+                    BlockProperties {
+                        position: multibuffer.anchor_before(Point::new(row, 0)),
+                        height: 1,
+                        style: BlockStyle::Flex,
+                        render: Box::new(move |cx: &mut BlockContext| {
+                            render_send_button(view.clone(), row, sending, cx)
+                        }),
+                        disposition: BlockDisposition::Above,
+                        priority: 0,
+                    }
+                });
+                editor.insert_blocks(blocks, None, cx)
+            })
+            .unwrap_or_default();
+    }
+
+    fn update_highlights(
+        &mut self,
+        highlights: Vec<(HttpHighlight, Range<usize>)>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.editor
+            .update(cx, |editor, cx| {
+                let multibuffer = editor.buffer().read(cx).snapshot(cx);
+                let mut ranges = vec![Vec::new(); HttpHighlight::ALL.len()];
+                for (kind, range) in highlights {
+                    let kind = HttpHighlight::ALL.iter().position(|k| *k == kind).unwrap();
+                    ranges[kind].push(
+                        multibuffer.anchor_after(range.start)..multibuffer.anchor_before(range.end),
+                    );
+                }
+                set_highlights(editor, ranges, cx);
+            })
+            .ok();
+    }
+
+    fn send_request_at_cursor(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(offset) = self
+            .editor
+            .update(cx, |editor, cx| {
+                editor.selections.newest::<usize>(cx).head()
+            })
+            .ok()
+        else {
+            return;
+        };
+        self.send_request_at(offset, cx);
+    }
+
+    fn send_request_at_row(&mut self, row: u32, cx: &mut ViewContext<Self>) {
+        let Some(buffer) = self.buffer(cx) else {
+            return;
+        };
+        let offset = buffer.read(cx).point_to_offset(Point::new(row, 0));
+        self.send_request_at(offset, cx);
+    }
+
+    /// Sends the request at the offset, or the one after it, and shows its
+    /// response.
+    fn send_request_at(&mut self, offset: usize, cx: &mut ViewContext<Self>) {
+        let Some(buffer) = self.buffer(cx) else {
+            return;
+        };
+        let buffer = buffer.read(cx);
+        if buffer.capability() == Capability::ReadOnly {
+            return;
+        }
+        let file = parse_http_file(&buffer.text());
+        let Some(request) = file
+            .requests
+            .iter()
+            .find(|request| request.range.end >= offset)
+            .or(file.requests.last())
+            .cloned()
+        else {
+            return;
+        };
+        let Some(fs) = self
+            .editor
+            .upgrade()
+            .and_then(|editor| editor.read(cx).workspace())
+            .map(|workspace| workspace.read(cx).app_state().fs.clone())
+        else {
+            return;
+        };
+
+        let environment_directories = environment_directories(buffer, cx);
+        let environment = RestClientSettings::get_global(cx).environment.clone();
+        let http_client = cx.http_client();
+        let language = buffer.language().cloned();
+        let title = request.title();
+        let file_variables = file.variables;
+
+        self.sending_row = Some(buffer.offset_to_point(request.request_line.start).row);
+        self.update_blocks(cx);
+
+        self.send_task = Some(cx.spawn(|this, mut cx| async move {
+            let started_at = Instant::now();
+            let response = async {
+                let environment_files =
+                    load_environment_files(fs.as_ref(), environment_directories).await;
+                if let Some(environment) = &environment {
+                    if !environment_names(&environment_files).contains(environment) {
+                        bail!("{ENVIRONMENT_FILE_NAME} doesn't define {environment}");
+                    }
+                }
+                let mut variables =
+                    environment_variables(&environment_files, environment.as_deref());
+                variables.extend(file_variables);
+                let request = request.resolve(&variables)?;
+                send_request(http_client, &request).await
+            }
+            .await;
+
+            this.update(&mut cx, |this, cx| {
+                this.sending_row = None;
+                let (title, text) = match response {
+                    Ok((summary, text)) => (format!("{title} ({summary})"), text),
+                    Err(error) => {
+                        let elapsed = started_at.elapsed().as_millis();
+                        (
+                            format!("{title} (failed)"),
+                            format!("# {title} failed after {elapsed} ms\n# {error:#}\n"),
+                        )
+                    }
+                };
+                this.show_response(title, text, language, cx);
+                this.update_blocks(cx);
+            })
+            .ok();
+        }));
+    }
+
+    /// Shows a response in the editor split off for this file's responses,
+    /// opening it if it isn't open.
+    fn show_response(
+        &mut self,
+        title: String,
+        text: String,
+        language: Option<Arc<language::Language>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(workspace) = self
+            .editor
+            .upgrade()
+            .and_then(|editor| editor.read(cx).workspace())
+        else {
+            return;
+        };
+        let title = format!("Response: {title}");
+
+        if let Some(response_editor) = self.response_editor.as_ref().and_then(|e| e.upgrade()) {
+            response_editor.update(cx, |response_editor, cx| {
+                response_editor.buffer().update(cx, |multibuffer, cx| {
+                    multibuffer.set_title(title, cx);
+                    if let Some(buffer) = multibuffer.as_singleton() {
+                        buffer.update(cx, |buffer, cx| {
+                            buffer.set_text(text, cx);
+                        });
+                    }
+                });
+            });
+            workspace.update(cx, |workspace, cx| {
+                if !workspace.activate_item(&response_editor, true, false, cx) {
+                    workspace.split_item(SplitDirection::Right, Box::new(response_editor), cx);
+                }
+            });
+            return;
+        }
+
+        let project = workspace.read(cx).project().clone();
+        let buffer = cx.new_model(|cx| {
+            let mut buffer = Buffer::local(text, cx);
+            buffer.set_language(language, cx);
+            buffer.set_capability(Capability::ReadOnly, cx);
+            buffer
+        });
+        let multibuffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx).with_title(title));
+        let response_editor =
+            cx.new_view(|cx| Editor::for_multibuffer(multibuffer, Some(project), true, cx));
+        self.response_editor = Some(response_editor.downgrade());
+        workspace.update(cx, |workspace, cx| {
+            workspace.split_item(SplitDirection::Right, Box::new(response_editor), cx);
+        });
+    }
+}
+
+fn render_send_button(
+    view: WeakView<RestClientView>,
+    row: u32,
+    sending: bool,
+    cx: &mut BlockContext,
+) -> gpui::AnyElement {
+    let label = if sending {
+        "Sending…"
+    } else {
+        "Send Request"
+    };
+    h_flex()
+        .id(("send-request-row", row as usize))
+        .h(cx.line_height)
+        .child(
+            Button::new(("send-request", row as usize), label)
+                .label_size(LabelSize::Small)
+                .color(Color::Muted)
+                .style(ButtonStyle::Transparent)
+                .disabled(sending)
+                .on_click(move |_, cx| {
+                    view.update(cx, |view, cx| view.send_request_at_row(row, cx))
+                        .ok();
+                }),
+        )
+        .into_any_element()
+}
+
+/// Returns the directories to look for environment files in: that of the file
+/// and those above it, up to the root of its worktree.
+fn environment_directories(buffer: &Buffer, cx: &AppContext) -> Vec<PathBuf> {
+    let Some(file) = buffer.file().and_then(|file| file.as_local()) else {
+        return Vec::new();
+    };
+    let abs_path = file.abs_path(cx);
+    let depth = file.path().components().count();
+    let Some(worktree_root) = abs_path.ancestors().nth(depth) else {
+        return Vec::new();
+    };
+    abs_path
+        .ancestors()
+        .skip(1)
+        .take_while(|directory| directory.starts_with(worktree_root))
+        .map(|directory| directory.to_path_buf())
+        .collect()
+}
+
+/// Reads the environment files in the nearest of the directories that has
+/// any, in increasing order of precedence.
+async fn load_environment_files(fs: &dyn Fs, directories: Vec<PathBuf>) -> Vec<serde_json::Value> {
+    for directory in directories {
+        let mut files = Vec::new();
+        for file_name in [ENVIRONMENT_FILE_NAME, PRIVATE_ENVIRONMENT_FILE_NAME] {
+            let path = directory.join(file_name);
+            let Ok(text) = fs.load(&path).await else {
+                continue;
+            };
+            if let Some(file) = serde_json::from_str(&text)
+                .with_context(|| format!("parsing {path:?}"))
+                .log_err()
+            {
+                files.push(file);
+            }
+        }
+        if !files.is_empty() {
+            return files;
+        }
+    }
+    Vec::new()
+}
+
+/// Sends the request, returning a summary of its response and the response
+/// written out.
+async fn send_request(
+    http_client: Arc<dyn HttpClient>,
+    request: &HttpRequest,
+) -> Result<(String, String)> {
+    let method = Method::from_bytes(request.method.as_bytes())?;
+    let mut builder = Request::builder().method(method).uri(request.url.as_str());
+    for (name, value) in &request.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    let body = match &request.body {
+        Some(body) => AsyncBody::from(body.clone()),
+        None => AsyncBody::default(),
+    };
+    let request = builder
+        .body(body)
+        .map_err(|error| anyhow!("invalid request: {error}"))?;
+
+    let started_at = Instant::now();
+    let mut response = http_client.send(request).await?;
+    let mut body = Vec::new();
+    response.body_mut().read_to_end(&mut body).await?;
+    let elapsed = started_at.elapsed();
+
+    let status = response.status();
+    let status_line = format!(
+        "{:?} {} {}",
+        response.version(),
+        status.as_u16(),
+        status.canonical_reason().unwrap_or_default()
+    );
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+            (name.to_string(), value)
+        })
+        .collect::<Vec<_>>();
+    Ok((
+        response_summary(status.as_u16(), status.canonical_reason(), elapsed),
+        format_response(status_line.trim_end(), &headers, &body),
+    ))
+}
+
+fn set_highlights(
+    editor: &mut Editor,
+    mut ranges: Vec<Vec<Range<editor::Anchor>>>,
+    cx: &mut ViewContext<Editor>,
+) {
+    set_kind_highlights::<0>(editor, &mut ranges, cx);
+    set_kind_highlights::<1>(editor, &mut ranges, cx);
+    set_kind_highlights::<2>(editor, &mut ranges, cx);
+    set_kind_highlights::<3>(editor, &mut ranges, cx);
+    set_kind_highlights::<4>(editor, &mut ranges, cx);
+    set_kind_highlights::<5>(editor, &mut ranges, cx);
+    set_kind_highlights::<6>(editor, &mut ranges, cx);
+}
+
+fn set_kind_highlights<const KIND: usize>(
+    editor: &mut Editor,
+    ranges: &mut [Vec<Range<editor::Anchor>>],
+    cx: &mut ViewContext<Editor>,
+) {
+    let ranges = std::mem::take(&mut ranges[KIND]);
+    editor.clear_highlights::<RestClientHighlight<KIND>>(cx);
+    if ranges.is_empty() {
+        return;
+    }
+
+    let theme = cx.theme();
+    let syntax = theme.syntax();
+    // Themes may not style every capture, so each kind falls back to another.
+    let syntax_style = |names: &[&str]| {
+        names
+            .iter()
+            .map(|name| syntax.get(name))
+            .find(|style| *style != HighlightStyle::default())
+            .unwrap_or_default()
+    };
+    let style = match HttpHighlight::ALL[KIND] {
+        HttpHighlight::Comment => syntax_style(&["comment"]),
+        HttpHighlight::Method => syntax_style(&["keyword"]),
+        HttpHighlight::Url => syntax_style(&["link_uri", "string"]),
+        HttpHighlight::HeaderName => syntax_style(&["property", "attribute"]),
+        HttpHighlight::Variable => syntax_style(&["variable.special", "constant"]),
+        HttpHighlight::SuccessStatus => HighlightStyle {
+            color: Some(theme.status().success),
+            ..Default::default()
+        },
+        HttpHighlight::ErrorStatus => HighlightStyle {
+            color: Some(theme.status().error),
+            ..Default::default()
+        },
+    };
+    editor.highlight_text::<RestClientHighlight<KIND>>(ranges, style, cx);
+}
@@ -0,0 +1,416 @@
+//! A panel listing the code review comment threads of the project's files.
+mod review_panel_settings;
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use collections::HashSet;
+use db::kvp::KEY_VALUE_STORE;
+use editor::Editor;
+use gpui::{
+    actions, Action, AppContext, AsyncWindowContext, EventEmitter, FocusHandle, FocusableView,
+    Model, Subscription, Task, View, WeakView,
+};
+use project::{
+    Fs, Project, ProjectPath, ReviewThread, ReviewThreadId, SerializedReviewThread, WorktreeId,
+};
+use settings::Settings;
+use ui::{prelude::*, IconButtonShape, ListItem, Tooltip};
+use util::ResultExt;
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    Workspace,
+};
+
+pub use review_panel_settings::{
+    ReviewPanelDockPosition, ReviewPanelSettings, ReviewPanelSettingsContent,
+};
+
+const REVIEW_THREADS_KEY_PREFIX: &str = "review_threads";
+
+actions!(review_panel, [ToggleFocus]);
+
+pub fn init(cx: &mut AppContext) {
+    ReviewPanelSettings::register(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, cx| {
+            workspace.toggle_panel_focus::<ReviewPanel>(cx);
+        });
+    })
+    .detach();
+}
+
+pub struct ReviewPanel {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    width: Option<Pixels>,
+    show_resolved: bool,
+    restored_worktrees: HashSet<WorktreeId>,
+    pending_serialization: Task<Option<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ReviewPanel {
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<View<Self>> {
+        workspace.update(&mut cx, |workspace, cx| Self::new(workspace, cx))
+    }
+
+    fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let project = workspace.project().clone();
+        let fs = workspace.app_state().fs.clone();
+        let workspace_handle = cx.view().downgrade();
+        cx.new_view(|cx| {
+            let project_subscription = cx.subscribe(&project, |this, _, event, cx| {
+                if let project::Event::WorktreeAdded = event {
+                    this.restore_threads(cx);
+                }
+            });
+            let review_store = project.read(cx).review_store().clone();
+            let review_subscription = cx.subscribe(&review_store, |this, _, _, cx| {
+                this.serialize_threads(cx);
+                cx.notify();
+            });
+
+            let mut this = Self {
+                workspace: workspace_handle,
+                project,
+                fs,
+                focus_handle: cx.focus_handle(),
+                width: None,
+                show_resolved: ReviewPanelSettings::get_global(cx).show_resolved,
+                restored_worktrees: HashSet::default(),
+                pending_serialization: Task::ready(None),
+                _subscriptions: vec![project_subscription, review_subscription],
+            };
+            this.restore_threads(cx);
+            this
+        })
+    }
+
+    fn open_thread(&mut self, thread: &ReviewThread, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let id = thread.id;
+        let open_task = workspace.update(cx, |workspace, cx| {
+            workspace.open_path(thread.path.clone(), None, true, cx)
+        });
+        cx.spawn(|_, mut cx| async move {
+            let item = open_task.await?;
+            if let Some(editor) = item.downcast::<Editor>() {
+                editor.update(&mut cx, |editor, cx| editor.show_review_thread(id, cx))?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn set_resolved(&mut self, id: ReviewThreadId, resolved: bool, cx: &mut ViewContext<Self>) {
+        let store = self.project.read(cx).review_store().clone();
+        store.update(cx, |store, cx| store.set_resolved(id, resolved, cx));
+    }
+
+    fn restore_threads(&mut self, cx: &mut ViewContext<Self>) {
+        let project = self.project.read(cx);
+        let worktrees = project
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx))
+            .filter(|worktree| !self.restored_worktrees.contains(&worktree.id()))
+            .map(|worktree| (worktree.id(), review_threads_key(&worktree.abs_path())))
+            .collect::<Vec<_>>();
+        if worktrees.is_empty() {
+            return;
+        }
+        let review_store = project.review_store().clone();
+        cx.spawn(|this, mut cx| async move {
+            let mut restored = Vec::new();
+            for (worktree_id, key) in &worktrees {
+                let key = key.clone();
+                let serialized = cx
+                    .background_executor()
+                    .spawn(async move { KEY_VALUE_STORE.read_kvp(&key) })
+                    .await
+                    .log_err()
+                    .flatten();
+                let threads = serialized
+                    .and_then(|serialized| {
+                        serde_json::from_str::<BTreeMap<PathBuf, Vec<SerializedReviewThread>>>(
+                            &serialized,
+                        )
+                        .log_err()
+                    })
+                    .unwrap_or_default();
+                restored.extend(threads.into_iter().map(|(path, threads)| {
+                    let path = ProjectPath {
+                        worktree_id: *worktree_id,
+                        path: path.into(),
+                    };
+                    (path, threads)
+                }));
+            }
+            this.update(&mut cx, |this, cx| {
+                this.restored_worktrees
+                    .extend(worktrees.into_iter().map(|(worktree_id, _)| worktree_id));
+                review_store.update(cx, |store, cx| {
+                    for (path, threads) in restored {
+                        if store.threads_for_path(&path).next().is_none() {
+                            store.restore_threads(path, threads, cx);
+                        }
+                    }
+                });
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Stores the threads of each worktree, so they're restored when the
+    /// worktree is opened again.
+    fn serialize_threads(&mut self, cx: &mut ViewContext<Self>) {
+        let project = self.project.read(cx);
+        let store = project.review_store().read(cx);
+        let entries = project
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx))
+            .filter(|worktree| self.restored_worktrees.contains(&worktree.id()))
+            .map(|worktree| {
+                let paths = store
+                    .threads()
+                    .into_iter()
+                    .filter(|thread| thread.path.worktree_id == worktree.id())
+                    .map(|thread| thread.path.clone())
+                    .collect::<HashSet<_>>();
+                let threads = paths
+                    .into_iter()
+                    .map(|path| (path.path.to_path_buf(), store.serialize_threads(&path)))
+                    .collect::<BTreeMap<_, _>>();
+                (review_threads_key(&worktree.abs_path()), threads)
+            })
+            .collect::<Vec<_>>();
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                for (key, threads) in entries {
+                    if threads.is_empty() {
+                        KEY_VALUE_STORE.delete_kvp(key).await?;
+                    } else {
+                        KEY_VALUE_STORE
+                            .write_kvp(key, serde_json::to_string(&threads)?)
+                            .await?;
+                    }
+                }
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn render_header(&self, open_count: usize, cx: &ViewContext<Self>) -> impl IntoElement {
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(Label::new(format!("{open_count} open")).color(Color::Muted))
+            .child(div().flex_1())
+            .child(
+                IconButton::new("toggle-show-resolved", IconName::Check)
+                    .shape(IconButtonShape::Square)
+                    .icon_size(IconSize::Small)
+                    .selected(self.show_resolved)
+                    .tooltip(|cx| Tooltip::text("Show Resolved Threads", cx))
+                    .on_click(cx.listener(|this, _, cx| {
+                        this.show_resolved = !this.show_resolved;
+                        cx.notify();
+                    })),
+            )
+    }
+
+    fn render_thread(&self, thread: &ReviewThread, cx: &ViewContext<Self>) -> impl IntoElement {
+        let id = thread.id;
+        let resolved = thread.resolved;
+        let summary = thread
+            .comments
+            .first()
+            .and_then(|comment| comment.body.lines().next())
+            .unwrap_or_default()
+            .to_string();
+        let replies = thread.comments.len().saturating_sub(1);
+        let open_thread = thread.clone();
+        ListItem::new(("review-thread", id.0))
+            .start_slot(
+                Label::new(format!("L{}", thread.range.start.row + 1))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(Label::new(summary).single_line().color(if resolved {
+                Color::Muted
+            } else {
+                Color::Default
+            }))
+            .end_slot(
+                h_flex()
+                    .gap_1()
+                    .when(replies > 0, |this| {
+                        this.child(
+                            Label::new(format!(
+                                "{replies} {}",
+                                if replies == 1 { "reply" } else { "replies" }
+                            ))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                        )
+                    })
+                    .child(
+                        IconButton::new(
+                            ("toggle-resolved", id.0),
+                            if resolved {
+                                IconName::Undo
+                            } else {
+                                IconName::Check
+                            },
+                        )
+                        .shape(IconButtonShape::Square)
+                        .icon_size(IconSize::XSmall)
+                        .tooltip(move |cx| {
+                            Tooltip::text(if resolved { "Reopen" } else { "Resolve" }, cx)
+                        })
+                        .on_click(
+                            cx.listener(move |this, _, cx| this.set_resolved(id, !resolved, cx)),
+                        ),
+                    ),
+            )
+            .on_click(cx.listener(move |this, _, cx| this.open_thread(&open_thread, cx)))
+    }
+}
+
+fn review_threads_key(worktree_root: &Path) -> String {
+    format!("{REVIEW_THREADS_KEY_PREFIX}:{}", worktree_root.display())
+}
+
+impl Panel for ReviewPanel {
+    fn persistent_name() -> &'static str {
+        "Review Panel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        match ReviewPanelSettings::get_global(cx).dock {
+            ReviewPanelDockPosition::Left => DockPosition::Left,
+            ReviewPanelDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<ReviewPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| {
+                let dock = match position {
+                    DockPosition::Left | DockPosition::Bottom => ReviewPanelDockPosition::Left,
+                    DockPosition::Right => ReviewPanelDockPosition::Right,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.width
+            .unwrap_or_else(|| ReviewPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        ReviewPanelSettings::get_global(cx)
+            .button
+            .then(|| IconName::MessageBubbles)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Review Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+}
+
+impl FocusableView for ReviewPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for ReviewPanel {}
+
+impl Render for ReviewPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let store = self.project.read(cx).review_store().read(cx);
+        let threads = store.threads();
+        let open_count = threads.iter().filter(|thread| !thread.resolved).count();
+        let mut files: Vec<(ProjectPath, Vec<ReviewThread>)> = Vec::new();
+        for thread in threads {
+            if thread.resolved && !self.show_resolved {
+                continue;
+            }
+            match files.last_mut() {
+                Some((path, threads)) if *path == thread.path => threads.push(thread.clone()),
+                _ => files.push((thread.path.clone(), vec![thread.clone()])),
+            }
+        }
+
+        let content = if files.is_empty() {
+            div()
+                .p_2()
+                .child(
+                    Label::new(
+                        "Select code and run \"editor: add review comment\" to start a thread",
+                    )
+                    .color(Color::Muted),
+                )
+                .into_any_element()
+        } else {
+            v_flex()
+                .id("review-threads")
+                .flex_1()
+                .overflow_y_scroll()
+                .children(files.into_iter().enumerate().map(|(ix, (path, threads))| {
+                    v_flex()
+                        .child(ListItem::new(("review-file", ix)).child(
+                            Label::new(path.path.to_string_lossy().to_string()).single_line(),
+                        ))
+                        .children(
+                            threads
+                                .iter()
+                                .map(|thread| div().pl_4().child(self.render_thread(thread, cx))),
+                        )
+                }))
+                .into_any_element()
+        };
+
+        v_flex()
+            .id("review-panel")
+            .size_full()
+            .key_context("ReviewPanel")
+            .track_focus(&self.focus_handle)
+            .child(self.render_header(open_count, cx))
+            .child(content)
+    }
+}
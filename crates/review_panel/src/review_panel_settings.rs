@@ -0,0 +1,52 @@
+use gpui::Pixels;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewPanelDockPosition {
+    Left,
+    Right,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ReviewPanelSettings {
+    pub button: bool,
+    pub default_width: Pixels,
+    pub dock: ReviewPanelDockPosition,
+    pub show_resolved: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct ReviewPanelSettingsContent {
+    /// Whether to show the review panel button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Customize default width (in pixels) taken by the review panel
+    ///
+    /// Default: 240
+    pub default_width: Option<f32>,
+    /// The position of the review panel
+    ///
+    /// Default: right
+    pub dock: Option<ReviewPanelDockPosition>,
+    /// Whether to list resolved threads.
+    ///
+    /// Default: true
+    pub show_resolved: Option<bool>,
+}
+
+impl Settings for ReviewPanelSettings {
+    const KEY: Option<&'static str> = Some("review_panel");
+
+    type FileContent = ReviewPanelSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
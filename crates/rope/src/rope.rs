@@ -196,6 +196,12 @@ impl Rope {
         self.len() == 0
     }
 
+    /// An estimate of the bytes that the rope's chunks take up, including any that
+    /// it shares with other ropes.
+    pub fn estimated_memory_usage(&self) -> usize {
+        self.chunks.iter().count() * (mem::size_of::<Chunk>() + mem::size_of::<ChunkSummary>())
+    }
+
     pub fn max_point(&self) -> Point {
         self.chunks.extent(&())
     }
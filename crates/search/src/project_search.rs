@@ -1,3 +1,5 @@
+mod replace_preview;
+
 use crate::{
     FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext, SearchOptions,
     SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive, ToggleIncludeIgnored, ToggleRegex,
@@ -21,6 +23,7 @@ use gpui::{
 use language::Buffer;
 use menu::Confirm;
 use project::{search::SearchQuery, search_history::SearchHistoryCursor, Project, ProjectPath};
+use replace_preview::ReplacePreview;
 use settings::Settings;
 use std::{
     any::{Any, TypeId},
@@ -46,7 +49,14 @@ const MAX_INPUT_WIDTH_REMS: f32 = 30.;
 
 actions!(
     project_search,
-    [SearchInNew, ToggleFocus, NextField, ToggleFilters]
+    [
+        SearchInNew,
+        ToggleFocus,
+        NextField,
+        ToggleFilters,
+        ToggleReplacement,
+        ApplyReplacements
+    ]
 );
 
 #[derive(Default)]
@@ -586,30 +596,32 @@ impl ProjectSearchView {
     pub fn replacement(&self, cx: &AppContext) -> String {
         self.replacement_editor.read(cx).text(cx)
     }
+    /// Opens a preview of the replacement of all the matches, from which they
+    /// are applied.
     fn replace_all(&mut self, _: &ReplaceAll, cx: &mut ViewContext<Self>) {
         if self.active_match_index.is_none() {
             return;
         }
 
-        let Some(query) = self.model.read(cx).active_query.as_ref() else {
+        let model = self.model.read(cx);
+        let Some(query) = model.active_query.as_ref() else {
             return;
         };
         let query = query.clone().with_replacement(self.replacement(cx));
-
-        let match_ranges = self
-            .model
-            .update(cx, |model, _| mem::take(&mut model.match_ranges));
+        let match_ranges = model.match_ranges.clone();
         if match_ranges.is_empty() {
             return;
         }
 
-        self.results_editor.update(cx, |editor, cx| {
-            editor.replace_all(&mut match_ranges.iter(), &query, cx);
-        });
-
-        self.model.update(cx, |model, _cx| {
-            model.match_ranges = match_ranges;
-        });
+        let search = cx.view().downgrade();
+        let results_editor = self.results_editor.clone();
+        let preview = cx
+            .new_view(|cx| ReplacePreview::new(search, &results_editor, &match_ranges, query, cx));
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace.add_item_to_active_pane(Box::new(preview), None, true, cx);
+            })
+            .ok();
     }
 
     fn new(
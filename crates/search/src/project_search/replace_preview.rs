@@ -0,0 +1,647 @@
+use super::{ApplyReplacements, ProjectSearchView, ToggleReplacement};
+use collections::HashMap;
+use editor::{
+    actions::ExpandAllHunkDiffs, Anchor, Editor, ExcerptId, MultiBuffer, MAX_TAB_TITLE_LEN,
+};
+use gpui::{
+    AppContext, EventEmitter, FocusHandle, FocusableView, Model, Render, SharedString,
+    Subscription, View, ViewContext, WeakView, WindowContext,
+};
+use language::{Buffer, Capability, TextBuffer, ToOffset as _};
+use project::search::SearchQuery;
+use std::{ops::Range, sync::Arc};
+use ui::{prelude::*, Checkbox, Tooltip};
+use workspace::{
+    item::{Item, ItemEvent},
+    searchable::SearchableItem,
+};
+
+/// Excluded replacements, which are highlighted in the preview as matches left
+/// as they are.
+enum ExcludedReplacements {}
+
+/// The replacement of a search match, which is applied unless it's excluded.
+struct Replacement {
+    /// The match in the search results.
+    range: Range<Anchor>,
+    /// The match in the text of the file.
+    offsets: Range<usize>,
+    original: Arc<str>,
+    text: Arc<str>,
+    /// The excerpt of the preview showing the replacement.
+    excerpt_id: ExcerptId,
+    included: bool,
+}
+
+/// A file with matches, previewed with its included replacements applied.
+struct PreviewFile {
+    path: SharedString,
+    preview_buffer: Model<Buffer>,
+    replacements: Vec<Replacement>,
+}
+
+pub enum ReplacePreviewEvent {
+    Dismiss,
+}
+
+/// Previews replacing all the matches of a project search, showing the changes
+/// to each file as a diff. Replacements can be excluded individually or per
+/// file before the remaining ones are applied, in a single transaction of the
+/// search results.
+pub struct ReplacePreview {
+    search: WeakView<ProjectSearchView>,
+    query: SearchQuery,
+    files: Vec<PreviewFile>,
+    editor: View<Editor>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ReplacePreview {
+    pub(super) fn new(
+        search: WeakView<ProjectSearchView>,
+        results_editor: &View<Editor>,
+        match_ranges: &[Range<Anchor>],
+        query: SearchQuery,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let results = results_editor.read(cx).buffer().clone();
+        let mut matches_by_buffer = HashMap::<_, Vec<_>>::default();
+        let mut buffers = Vec::new();
+        for range in match_ranges {
+            let Some(buffer) = range
+                .start
+                .buffer_id
+                .and_then(|buffer_id| results.read(cx).buffer(buffer_id))
+            else {
+                continue;
+            };
+            let buffer_id = buffer.entity_id();
+            if !matches_by_buffer.contains_key(&buffer_id) {
+                buffers.push(buffer);
+            }
+            matches_by_buffer
+                .entry(buffer_id)
+                .or_default()
+                .push(range.clone());
+        }
+
+        let title = match query.replacement() {
+            Some(replacement) => format!("Replace {} with {}", query.as_str(), replacement),
+            None => format!("Replace {}", query.as_str()),
+        };
+        let multibuffer =
+            cx.new_model(|_| MultiBuffer::new(0, Capability::ReadOnly).with_title(title));
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer.clone(), None, true, cx);
+            editor.set_read_only(true);
+            editor
+        });
+
+        let mut files = Vec::new();
+        let mut subscriptions = Vec::new();
+        for buffer in buffers {
+            let buffer_id = buffer.entity_id();
+            let buffer = buffer.read(cx);
+            let snapshot = buffer.snapshot();
+            let mut replacements = Vec::new();
+            for range in &matches_by_buffer[&buffer_id] {
+                let offsets = range.start.text_anchor.to_offset(&snapshot)
+                    ..range.end.text_anchor.to_offset(&snapshot);
+                let original = snapshot.text_for_range(offsets.clone()).collect::<String>();
+                let Some(text) = query.replacement_for(&original).map(Arc::from) else {
+                    continue;
+                };
+                replacements.push(Replacement {
+                    range: range.clone(),
+                    offsets,
+                    original: original.into(),
+                    text,
+                    excerpt_id: ExcerptId::min(),
+                    included: true,
+                });
+            }
+            if replacements.is_empty() {
+                continue;
+            }
+            replacements.sort_by_key(|replacement| replacement.offsets.start);
+
+            let path = buffer
+                .file()
+                .map(|file| file.full_path(cx).to_string_lossy().to_string())
+                .unwrap_or_else(|| "untitled".to_string());
+            let file = buffer.file().cloned();
+            let language = buffer.language().cloned();
+            let language_registry = buffer.language_registry();
+            let text = snapshot.text();
+            let preview_buffer = cx.new_model(|cx| {
+                let mut preview_buffer = Buffer::build(
+                    TextBuffer::new(0, cx.entity_id().as_non_zero_u64().into(), text.clone()),
+                    None,
+                    file,
+                    Capability::ReadWrite,
+                );
+                if let Some(language_registry) = language_registry {
+                    preview_buffer.set_language_registry(language_registry);
+                }
+                preview_buffer.set_language(language, cx);
+                preview_buffer.edit(
+                    replacements
+                        .iter()
+                        .map(|replacement| (replacement.offsets.clone(), replacement.text.clone())),
+                    None,
+                    cx,
+                );
+                preview_buffer.set_diff_base(Some(text), cx);
+                preview_buffer
+            });
+
+            let preview_ranges = preview_ranges(&replacements);
+            let excerpt_ranges = multibuffer.update(cx, |multibuffer, cx| {
+                multibuffer.push_excerpts_with_context_lines(
+                    preview_buffer.clone(),
+                    preview_ranges,
+                    editor::DEFAULT_MULTIBUFFER_CONTEXT,
+                    cx,
+                )
+            });
+            for (replacement, range) in replacements.iter_mut().zip(excerpt_ranges) {
+                replacement.excerpt_id = range.start.excerpt_id;
+            }
+
+            // The hunks are known, and expanded, once the diff with the file is computed.
+            subscriptions.push(cx.subscribe(&preview_buffer, |this, _, event, cx| {
+                if let language::Event::DiffBaseChanged | language::Event::DiffUpdated = event {
+                    this.editor.update(cx, |editor, cx| {
+                        editor.expand_all_hunk_diffs(&ExpandAllHunkDiffs, cx)
+                    });
+                }
+            }));
+            files.push(PreviewFile {
+                path: path.into(),
+                preview_buffer,
+                replacements,
+            });
+        }
+
+        Self {
+            search,
+            query,
+            files,
+            editor,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn replacement_count(&self) -> (usize, usize) {
+        self.files
+            .iter()
+            .flat_map(|file| &file.replacements)
+            .fold((0, 0), |(included, total), replacement| {
+                (included + replacement.included as usize, total + 1)
+            })
+    }
+
+    /// Includes the replacements of the file if some of them are excluded, or
+    /// excludes them all otherwise.
+    fn toggle_file(&mut self, file_ix: usize, cx: &mut ViewContext<Self>) {
+        let replacements = &self.files[file_ix].replacements;
+        let include = replacements.iter().any(|replacement| !replacement.included);
+        let toggled = (0..replacements.len())
+            .filter(|ix| replacements[*ix].included != include)
+            .collect();
+        self.toggle_replacements(file_ix, toggled, cx);
+    }
+
+    /// Toggles the replacements touching the selections in the preview.
+    fn toggle_replacement(&mut self, _: &ToggleReplacement, cx: &mut ViewContext<Self>) {
+        let editor = self.editor.read(cx);
+        let multibuffer = editor.buffer().read(cx);
+        let mut toggled = HashMap::<usize, Vec<usize>>::default();
+        for selection in editor.selections.all::<usize>(cx) {
+            for (buffer, range, _) in
+                multibuffer.range_to_buffer_ranges(selection.start..selection.end, cx)
+            {
+                let Some(file_ix) = self
+                    .files
+                    .iter()
+                    .position(|file| file.preview_buffer == buffer)
+                else {
+                    continue;
+                };
+                let preview_ranges = preview_ranges(&self.files[file_ix].replacements);
+                let file_toggled = toggled.entry(file_ix).or_default();
+                for (ix, preview_range) in preview_ranges.into_iter().enumerate() {
+                    if preview_range.start <= range.end
+                        && range.start <= preview_range.end
+                        && !file_toggled.contains(&ix)
+                    {
+                        file_toggled.push(ix);
+                    }
+                }
+            }
+        }
+        for (file_ix, replacements) in toggled {
+            self.toggle_replacements(file_ix, replacements, cx);
+        }
+    }
+
+    fn toggle_replacements(
+        &mut self,
+        file_ix: usize,
+        replacement_ixs: Vec<usize>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if replacement_ixs.is_empty() {
+            return;
+        }
+        let file = &mut self.files[file_ix];
+        let preview_ranges = preview_ranges(&file.replacements);
+        let edits = replacement_ixs
+            .iter()
+            .map(|ix| {
+                let replacement = &file.replacements[*ix];
+                let text = if replacement.included {
+                    replacement.original.clone()
+                } else {
+                    replacement.text.clone()
+                };
+                (preview_ranges[*ix].clone(), text)
+            })
+            .collect::<Vec<_>>();
+        for ix in replacement_ixs {
+            let replacement = &mut file.replacements[ix];
+            replacement.included = !replacement.included;
+        }
+        file.preview_buffer.update(cx, |preview_buffer, cx| {
+            preview_buffer.edit(edits, None, cx);
+            if let Some(diff) = preview_buffer.git_diff_recalc(cx) {
+                diff.detach();
+            }
+        });
+        self.highlight_excluded_replacements(cx);
+        cx.notify();
+    }
+
+    fn highlight_excluded_replacements(&mut self, cx: &mut ViewContext<Self>) {
+        let snapshot = self.editor.read(cx).buffer().read(cx).snapshot(cx);
+        let mut ranges = Vec::new();
+        for file in &self.files {
+            let preview_buffer = file.preview_buffer.read(cx);
+            let preview_ranges = preview_ranges(&file.replacements);
+            for (replacement, range) in file.replacements.iter().zip(preview_ranges) {
+                if replacement.included {
+                    continue;
+                }
+                let start = snapshot.anchor_in_excerpt(
+                    replacement.excerpt_id,
+                    preview_buffer.anchor_before(range.start),
+                );
+                let end = snapshot.anchor_in_excerpt(
+                    replacement.excerpt_id,
+                    preview_buffer.anchor_after(range.end),
+                );
+                ranges.extend(start.zip(end).map(|(start, end)| start..end));
+            }
+        }
+        self.editor.update(cx, |editor, cx| {
+            editor.highlight_background::<ExcludedReplacements>(
+                &ranges,
+                |colors| colors.search_match_background,
+                cx,
+            );
+        });
+    }
+
+    /// Applies the included replacements to the search results, so that
+    /// undoing in them reverts all the replacements at once.
+    fn apply(&mut self, _: &ApplyReplacements, cx: &mut ViewContext<Self>) {
+        let Some(search) = self.search.upgrade() else {
+            return;
+        };
+        let ranges = self
+            .files
+            .iter()
+            .flat_map(|file| &file.replacements)
+            .filter(|replacement| replacement.included)
+            .map(|replacement| replacement.range.clone())
+            .collect::<Vec<_>>();
+        let (results_editor, workspace) = {
+            let search = search.read(cx);
+            (search.results_editor.clone(), search.workspace.clone())
+        };
+        results_editor.update(cx, |editor, cx| {
+            editor.replace_all(&mut ranges.iter(), &self.query, cx);
+        });
+
+        cx.emit(ReplacePreviewEvent::Dismiss);
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.activate_item(&search, true, true, cx);
+            })
+            .ok();
+    }
+
+    fn render_file(&self, file_ix: usize, cx: &ViewContext<Self>) -> impl IntoElement {
+        let file = &self.files[file_ix];
+        let included = file
+            .replacements
+            .iter()
+            .filter(|replacement| replacement.included)
+            .count();
+        let selection = if included == file.replacements.len() {
+            Selection::Selected
+        } else if included == 0 {
+            Selection::Unselected
+        } else {
+            Selection::Indeterminate
+        };
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .child(
+                Checkbox::new(("replace-preview-file", file_ix), selection)
+                    .on_click(cx.listener(move |this, _, cx| this.toggle_file(file_ix, cx))),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .overflow_hidden()
+                    .child(Label::new(file.path.clone()).size(LabelSize::Small)),
+            )
+            .child(
+                Label::new(format!("{included}/{}", file.replacements.len()))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+    }
+}
+
+/// Returns the range of each replacement in the preview of its file, where
+/// only the included replacements are applied.
+fn preview_ranges(replacements: &[Replacement]) -> Vec<Range<usize>> {
+    let mut delta = 0_isize;
+    replacements
+        .iter()
+        .map(|replacement| {
+            let start = (replacement.offsets.start as isize + delta) as usize;
+            let len = if replacement.included {
+                delta += replacement.text.len() as isize - replacement.original.len() as isize;
+                replacement.text.len()
+            } else {
+                replacement.original.len()
+            };
+            start..start + len
+        })
+        .collect()
+}
+
+impl EventEmitter<ReplacePreviewEvent> for ReplacePreview {}
+
+impl FocusableView for ReplacePreview {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Item for ReplacePreview {
+    type Event = ReplacePreviewEvent;
+
+    fn tab_icon(&self, _: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::Replace))
+    }
+
+    fn tab_content_text(&self, _: &WindowContext) -> Option<SharedString> {
+        let query = self.query.as_str().replace('\n', "");
+        Some(
+            format!(
+                "Replace {}",
+                util::truncate_and_trailoff(&query, MAX_TAB_TITLE_LEN)
+            )
+            .into(),
+        )
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("replace preview")
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        match event {
+            ReplacePreviewEvent::Dismiss => f(ItemEvent::CloseItem),
+        }
+    }
+}
+
+impl Render for ReplacePreview {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let (included, total) = self.replacement_count();
+        v_flex()
+            .key_context("ReplacePreview")
+            .on_action(cx.listener(Self::toggle_replacement))
+            .on_action(cx.listener(Self::apply))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(format!(
+                        "{included} of {total} replacements in {} files",
+                        self.files.len()
+                    )))
+                    .child(
+                        Button::new("apply-replacements", "Replace")
+                            .disabled(included == 0)
+                            .tooltip(|cx| {
+                                Tooltip::for_action("Apply replacements", &ApplyReplacements, cx)
+                            })
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.apply(&ApplyReplacements, cx);
+                            })),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .flex_1()
+                    .items_start()
+                    .overflow_hidden()
+                    .child(
+                        v_flex()
+                            .id("replace-preview-files")
+                            .w_64()
+                            .h_full()
+                            .overflow_y_scroll()
+                            .border_r_1()
+                            .border_color(cx.theme().colors().border_variant)
+                            .children(
+                                (0..self.files.len()).map(|file_ix| self.render_file(file_ix, cx)),
+                            ),
+                    )
+                    .child(div().flex_1().h_full().child(self.editor.clone())),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_search::ProjectSearch;
+    use gpui::{Context as _, TestAppContext};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use workspace::Workspace;
+
+    #[test]
+    fn test_preview_ranges() {
+        let replacement =
+            |offsets: Range<usize>, original: &str, text: &str, included| Replacement {
+                range: Anchor::min()..Anchor::min(),
+                offsets,
+                original: original.into(),
+                text: text.into(),
+                excerpt_id: ExcerptId::min(),
+                included,
+            };
+        let replacements = [
+            replacement(2..5, "one", "three", true),
+            replacement(10..13, "one", "1", false),
+            replacement(20..23, "one", "1", true),
+        ];
+        assert_eq!(preview_ranges(&replacements), [2..7, 12..15, 22..23]);
+    }
+
+    #[gpui::test]
+    async fn test_replace_preview(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings = SettingsStore::test(cx);
+            cx.set_global(settings);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            client::init_settings(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+        });
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;\nconst TWO: usize = ONE + ONE;",
+                "two.rs": "const THREE: usize = ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace = window.root(cx).unwrap();
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|cx| {
+            ProjectSearchView::new(workspace.downgrade(), search.clone(), cx, None)
+        });
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view
+                    .query_editor
+                    .update(cx, |query_editor, cx| query_editor.set_text("ONE", cx));
+                search_view.search(cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        let (handle, results_editor, match_ranges, query) = search_view
+            .update(cx, |search_view, cx| {
+                let model = search_view.model.read(cx);
+                let query = model
+                    .active_query
+                    .clone()
+                    .unwrap()
+                    .with_replacement("UNO".to_string());
+                (
+                    cx.view().downgrade(),
+                    search_view.results_editor.clone(),
+                    model.match_ranges.clone(),
+                    query,
+                )
+            })
+            .unwrap();
+        let preview = cx.add_window(|cx| {
+            ReplacePreview::new(handle, &results_editor, &match_ranges, query, cx)
+        });
+        cx.background_executor.run_until_parked();
+
+        preview
+            .update(cx, |preview, cx| {
+                assert_eq!(preview.replacement_count(), (4, 4));
+                let one_ix = preview
+                    .files
+                    .iter()
+                    .position(|file| file.path.ends_with("one.rs"))
+                    .unwrap();
+                assert_eq!(
+                    preview.files[one_ix].preview_buffer.read(cx).text(),
+                    "const UNO: usize = 1;\nconst TWO: usize = UNO + UNO;"
+                );
+
+                // Excluding a replacement restores the match in the preview.
+                preview.toggle_replacements(one_ix, vec![1], cx);
+                assert_eq!(
+                    preview.files[one_ix].preview_buffer.read(cx).text(),
+                    "const UNO: usize = 1;\nconst TWO: usize = ONE + UNO;"
+                );
+
+                // Excluding a file excludes all its replacements.
+                preview.toggle_file(1 - one_ix, cx);
+                assert_eq!(preview.replacement_count(), (2, 4));
+
+                preview.apply(&ApplyReplacements, cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        let buffer_texts = |cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, cx| {
+                    let results = search_view.results_editor.read(cx).buffer().read(cx);
+                    let mut texts = results
+                        .all_buffers()
+                        .into_iter()
+                        .map(|buffer| buffer.read(cx).text())
+                        .collect::<Vec<_>>();
+                    texts.sort();
+                    texts
+                })
+                .unwrap()
+        };
+        assert_eq!(
+            buffer_texts(cx),
+            [
+                "const THREE: usize = ONE;",
+                "const UNO: usize = 1;\nconst TWO: usize = ONE + UNO;",
+            ]
+        );
+
+        // The replacements are undone at once.
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.undo(&editor::actions::Undo, cx));
+            })
+            .unwrap();
+        assert_eq!(
+            buffer_texts(cx),
+            [
+                "const ONE: usize = 1;\nconst TWO: usize = ONE + ONE;",
+                "const THREE: usize = ONE;",
+            ]
+        );
+    }
+}
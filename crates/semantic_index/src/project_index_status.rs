@@ -0,0 +1,116 @@
+use crate::{ProjectIndex, ProjectIndexDebugView, SemanticIndex, Status};
+use gpui::{
+    percentage, Animation, AnimationExt, Model, Render, Subscription, Transformation, ViewContext,
+    WeakModel, WeakView,
+};
+use project::Project;
+use std::time::Duration;
+use ui::{prelude::*, ButtonLike, Tooltip};
+use workspace::{item::ItemHandle, StatusItemView, Workspace};
+
+/// Shows whether the files of the project are being indexed, once its index has
+/// been created by something that uses it, such as the assistant.
+pub struct ProjectIndexStatus {
+    workspace: WeakView<Workspace>,
+    project: WeakModel<Project>,
+    project_index: Option<Model<ProjectIndex>>,
+    _observe_semantic_index: Subscription,
+    _observe_project_index: Option<Subscription>,
+}
+
+impl ProjectIndexStatus {
+    pub fn new(workspace: &Workspace, cx: &mut ViewContext<Self>) -> Self {
+        let mut this = Self {
+            workspace: workspace.weak_handle(),
+            project: workspace.project().downgrade(),
+            project_index: None,
+            _observe_semantic_index: cx
+                .observe_global::<SemanticIndex>(|this, cx| this.update_project_index(cx)),
+            _observe_project_index: None,
+        };
+        this.update_project_index(cx);
+        this
+    }
+
+    fn update_project_index(&mut self, cx: &mut ViewContext<Self>) {
+        if self.project_index.is_some() || !cx.has_global::<SemanticIndex>() {
+            return;
+        }
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        if let Some(project_index) = cx
+            .global::<SemanticIndex>()
+            .existing_project_index(&project)
+        {
+            self._observe_project_index =
+                Some(cx.subscribe(&project_index, |_, _, _: &Status, cx| cx.notify()));
+            self.project_index = Some(project_index);
+            cx.notify();
+        }
+    }
+
+    fn open_debug_view(&mut self, cx: &mut ViewContext<Self>) {
+        let (Some(workspace), Some(project_index)) =
+            (self.workspace.upgrade(), self.project_index.clone())
+        else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| {
+            let debug_view = cx.new_view(|cx| ProjectIndexDebugView::new(project_index, cx));
+            workspace.add_item_to_active_pane(Box::new(debug_view), None, true, cx);
+        });
+    }
+}
+
+impl Render for ProjectIndexStatus {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let Some(project_index) = self.project_index.as_ref() else {
+            return div().into_any_element();
+        };
+
+        let (label, tooltip): (Option<SharedString>, SharedString) =
+            match project_index.read(cx).status() {
+                Status::Idle => (None, "The project index is up to date".into()),
+                Status::Loading => (
+                    Some("Loading index…".into()),
+                    "Loading the project index".into(),
+                ),
+                Status::Scanning { remaining_count } => (
+                    Some(format!("Indexing {remaining_count} files…").into()),
+                    "Indexing the project's files for the assistant".into(),
+                ),
+            };
+        let icon = if label.is_some() {
+            Icon::new(IconName::ArrowCircle)
+                .size(IconSize::Small)
+                .color(Color::Muted)
+                .with_animation(
+                    "indexing",
+                    Animation::new(Duration::from_secs(2)).repeat(),
+                    |icon, delta| icon.transform(Transformation::rotate(percentage(delta))),
+                )
+                .into_any_element()
+        } else {
+            Icon::new(IconName::DatabaseZap)
+                .size(IconSize::Small)
+                .color(Color::Muted)
+                .into_any_element()
+        };
+
+        ButtonLike::new("project-index-status")
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(icon)
+                    .children(label.map(|label| Label::new(label).size(LabelSize::Small))),
+            )
+            .tooltip(move |cx| Tooltip::text(tooltip.clone(), cx))
+            .on_click(cx.listener(|this, _, cx| this.open_debug_view(cx)))
+            .into_any_element()
+    }
+}
+
+impl StatusItemView for ProjectIndexStatus {
+    fn set_active_pane_item(&mut self, _: Option<&dyn ItemHandle>, _: &mut ViewContext<Self>) {}
+}
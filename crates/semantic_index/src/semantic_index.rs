@@ -1,6 +1,8 @@
 mod chunking;
 mod embedding;
 mod project_index_debug_view;
+mod project_index_status;
+mod semantic_index_settings;
 
 use anyhow::{anyhow, Context as _, Result};
 use chunking::{chunk_text, Chunk};
@@ -18,6 +20,7 @@ use language::LanguageRegistry;
 use parking_lot::Mutex;
 use project::{Entry, Project, ProjectEntryId, UpdatedEntriesSet, Worktree, WorktreeId};
 use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsStore};
 use smol::channel;
 use std::{
     cmp::Ordering,
@@ -29,10 +32,16 @@ use std::{
     sync::{Arc, Weak},
     time::{Duration, SystemTime},
 };
-use util::ResultExt;
+use util::{paths::PathMatcher, ResultExt};
 use worktree::Snapshot;
 
 pub use project_index_debug_view::ProjectIndexDebugView;
+pub use project_index_status::ProjectIndexStatus;
+pub use semantic_index_settings::*;
+
+pub fn init(cx: &mut AppContext) {
+    SemanticIndexSettings::register(cx);
+}
 
 pub struct SemanticIndex {
     embedding_provider: Arc<dyn EmbeddingProvider>,
@@ -100,6 +109,11 @@ impl SemanticIndex {
             })
             .clone()
     }
+
+    /// Returns the index of a project, if it has been created by using it.
+    pub fn existing_project_index(&self, project: &Model<Project>) -> Option<Model<ProjectIndex>> {
+        self.project_indices.get(&project.downgrade()).cloned()
+    }
 }
 
 pub struct ProjectIndex {
@@ -111,8 +125,9 @@ pub struct ProjectIndex {
     last_status: Status,
     status_tx: channel::Sender<()>,
     embedding_provider: Arc<dyn EmbeddingProvider>,
+    exclusions: Arc<PathMatcher>,
     _maintain_status: Task<()>,
-    _subscription: Subscription,
+    _subscriptions: Vec<Subscription>,
 }
 
 #[derive(Clone)]
@@ -144,7 +159,11 @@ impl ProjectIndex {
             status_tx,
             last_status: Status::Idle,
             embedding_provider,
-            _subscription: cx.subscribe(&project, Self::handle_project_event),
+            exclusions: Arc::new(SemanticIndexSettings::get_global(cx).exclusions()),
+            _subscriptions: vec![
+                cx.subscribe(&project, Self::handle_project_event),
+                cx.observe_global::<SettingsStore>(Self::handle_settings_changed),
+            ],
             _maintain_status: cx.spawn(|this, mut cx| async move {
                 while status_rx.next().await.is_some() {
                     if this
@@ -186,6 +205,17 @@ impl ProjectIndex {
         }
     }
 
+    /// Reindexes the project when the excluded paths change, which removes the
+    /// files that are now excluded from the index, and adds those that aren't.
+    fn handle_settings_changed(&mut self, cx: &mut ModelContext<Self>) {
+        let exclusions = SemanticIndexSettings::get_global(cx).exclusions();
+        if exclusions != *self.exclusions {
+            self.exclusions = Arc::new(exclusions);
+            self.worktree_indices.clear();
+            self.update_worktree_indices(cx);
+        }
+    }
+
     fn update_worktree_indices(&mut self, cx: &mut ModelContext<Self>) {
         let Some(project) = self.project.upgrade() else {
             return;
@@ -214,6 +244,7 @@ impl ProjectIndex {
                     self.fs.clone(),
                     self.status_tx.clone(),
                     self.embedding_provider.clone(),
+                    self.exclusions.clone(),
                     cx,
                 );
 
@@ -488,12 +519,14 @@ struct WorktreeIndex {
     language_registry: Arc<LanguageRegistry>,
     fs: Arc<dyn Fs>,
     embedding_provider: Arc<dyn EmbeddingProvider>,
+    exclusions: Arc<PathMatcher>,
     entry_ids_being_indexed: Arc<IndexingEntrySet>,
     _index_entries: Task<Result<()>>,
     _subscription: Subscription,
 }
 
 impl WorktreeIndex {
+    #[allow(clippy::too_many_arguments)]
     pub fn load(
         worktree: Model<Worktree>,
         db_connection: heed::Env,
@@ -501,6 +534,7 @@ impl WorktreeIndex {
         fs: Arc<dyn Fs>,
         status_tx: channel::Sender<()>,
         embedding_provider: Arc<dyn EmbeddingProvider>,
+        exclusions: Arc<PathMatcher>,
         cx: &mut AppContext,
     ) -> Task<Result<Model<Self>>> {
         let worktree_abs_path = worktree.read(cx).abs_path();
@@ -527,6 +561,7 @@ impl WorktreeIndex {
                     language_registry,
                     fs,
                     embedding_provider,
+                    exclusions,
                     cx,
                 )
             })
@@ -542,6 +577,7 @@ impl WorktreeIndex {
         language_registry: Arc<LanguageRegistry>,
         fs: Arc<dyn Fs>,
         embedding_provider: Arc<dyn EmbeddingProvider>,
+        exclusions: Arc<PathMatcher>,
         cx: &mut ModelContext<Self>,
     ) -> Self {
         let (updated_entries_tx, updated_entries_rx) = channel::unbounded();
@@ -558,6 +594,7 @@ impl WorktreeIndex {
             language_registry,
             fs,
             embedding_provider,
+            exclusions,
             entry_ids_being_indexed: Arc::new(IndexingEntrySet::new(status)),
             _index_entries: cx.spawn(|this, cx| Self::index_entries(this, updated_entries_rx, cx)),
             _subscription,
@@ -618,6 +655,7 @@ impl WorktreeIndex {
         let db_connection = self.db_connection.clone();
         let db = self.db;
         let entries_being_indexed = self.entry_ids_being_indexed.clone();
+        let exclusions = self.exclusions.clone();
         let task = cx.background_executor().spawn(async move {
             let txn = db_connection
                 .read_txn()
@@ -628,8 +666,13 @@ impl WorktreeIndex {
                 .move_between_keys()
                 .peekable();
 
+            // Excluded files are skipped as if they didn't exist, so that they're
+            // removed from the index if they were indexed before.
             let mut deletion_range: Option<(Bound<&str>, Bound<&str>)> = None;
-            for entry in worktree.files(false, 0) {
+            for entry in worktree
+                .files(false, 0)
+                .filter(|entry| !exclusions.is_match(&entry.path))
+            {
                 let entry_db_key = db_key_for_path(&entry.path);
 
                 let mut saved_mtime = None;
@@ -699,8 +742,13 @@ impl WorktreeIndex {
         let (updated_entries_tx, updated_entries_rx) = channel::bounded(512);
         let (deleted_entry_ranges_tx, deleted_entry_ranges_rx) = channel::bounded(128);
         let entries_being_indexed = self.entry_ids_being_indexed.clone();
+        let exclusions = self.exclusions.clone();
         let task = cx.background_executor().spawn(async move {
             for (path, entry_id, status) in updated_entries.iter() {
+                if exclusions.is_match(path) {
+                    continue;
+                }
+
                 match status {
                     project::PathChange::Added
                     | project::PathChange::Updated
@@ -1052,6 +1100,7 @@ mod tests {
             cx.set_global(store);
             language::init(cx);
             Project::init_settings(cx);
+            crate::init(cx);
             SettingsStore::update(cx, |store, cx| {
                 store.update_user_settings::<AllLanguageSettings>(cx, |_| {});
             });
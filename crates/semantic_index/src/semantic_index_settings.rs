@@ -0,0 +1,149 @@
+use crate::{
+    CloudEmbeddingProvider, EmbeddingProvider, OllamaEmbeddingModel, OllamaEmbeddingProvider,
+    OpenAiEmbeddingModel, OpenAiEmbeddingProvider,
+};
+use anyhow::{anyhow, Result};
+use client::Client;
+use gpui::{AppContext, AsyncAppContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use std::sync::Arc;
+use util::{paths::PathMatcher, ResultExt};
+
+const OPENAI_API_KEY_VAR: &str = "OPENAI_API_KEY";
+
+/// Settings for the index of a project's files used to find code relevant to
+/// prompts.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct SemanticIndexSettings {
+    /// The service that embeds chunks of files as vectors. Changing it takes
+    /// effect once Zed is restarted.
+    ///
+    /// Default: zed
+    pub provider: EmbeddingProviderName,
+    /// The URL of the OpenAI API, when `provider` is `open_ai`.
+    ///
+    /// Default: null, for https://api.openai.com/v1
+    pub api_url: Option<String>,
+    /// Globs of paths, relative to the roots of worktrees, that aren't indexed.
+    ///
+    /// Default: []
+    pub exclude: Vec<String>,
+}
+
+/// A service that embeds chunks of files as vectors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingProviderName {
+    /// Zed's servers, for those who are signed in.
+    #[default]
+    Zed,
+    /// A local Ollama server, with the `nomic-embed-text` model.
+    Ollama,
+    /// OpenAI's `text-embedding-3-small` model, with the API key of the OpenAI
+    /// language model provider.
+    OpenAi,
+}
+
+impl EmbeddingProviderName {
+    /// The name of the directory that embeddings from the provider are stored in,
+    /// as embeddings from different providers can't be compared.
+    pub fn db_dir_name(&self) -> &'static str {
+        match self {
+            Self::Zed => "semantic-index-db.0.mdb",
+            Self::Ollama => "semantic-index-db.0.ollama.mdb",
+            Self::OpenAi => "semantic-index-db.0.open-ai.mdb",
+        }
+    }
+}
+
+impl SemanticIndexSettings {
+    /// Creates the embedding provider chosen in the settings. The API key of
+    /// OpenAI is read from the `OPENAI_API_KEY` environment variable, or else
+    /// from where the OpenAI language model provider keeps it.
+    pub async fn embedding_provider(
+        &self,
+        client: Arc<Client>,
+        cx: &AsyncAppContext,
+    ) -> Result<Arc<dyn EmbeddingProvider>> {
+        Ok(match self.provider {
+            EmbeddingProviderName::Zed => Arc::new(CloudEmbeddingProvider::new(client)),
+            EmbeddingProviderName::Ollama => Arc::new(OllamaEmbeddingProvider::new(
+                client.http_client(),
+                OllamaEmbeddingModel::NomicEmbedText,
+            )),
+            EmbeddingProviderName::OpenAi => {
+                let api_url = self
+                    .api_url
+                    .clone()
+                    .unwrap_or_else(|| open_ai::OPEN_AI_API_URL.to_string());
+                let api_key = if let Ok(api_key) = std::env::var(OPENAI_API_KEY_VAR) {
+                    api_key
+                } else {
+                    let (_, api_key) = cx
+                        .update(|cx| cx.read_credentials(&api_url))?
+                        .await?
+                        .ok_or_else(|| anyhow!("no OpenAI API key found for {api_url}"))?;
+                    String::from_utf8(api_key)?
+                };
+                Arc::new(OpenAiEmbeddingProvider::new(
+                    client.http_client(),
+                    OpenAiEmbeddingModel::TextEmbedding3Small,
+                    api_url,
+                    api_key,
+                ))
+            }
+        })
+    }
+
+    /// Returns the matcher of the excluded paths, leaving out invalid globs.
+    pub fn exclusions(&self) -> PathMatcher {
+        let globs = self
+            .exclude
+            .iter()
+            .filter(|glob| {
+                PathMatcher::new(std::slice::from_ref(glob))
+                    .log_err()
+                    .is_some()
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        PathMatcher::new(&globs).unwrap_or_default()
+    }
+}
+
+impl Settings for SemanticIndexSettings {
+    const KEY: Option<&'static str> = Some("semantic_index");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn test_exclusions() {
+        let settings = SemanticIndexSettings {
+            exclude: vec![
+                "**/*.min.js".to_string(),
+                "vendor/**".to_string(),
+                "[".to_string(),
+            ],
+            ..Default::default()
+        };
+        let exclusions = settings.exclusions();
+        assert!(exclusions.is_match(Path::new("vendor/lib/a.rs")));
+        assert!(exclusions.is_match(Path::new("web/app.min.js")));
+        assert!(!exclusions.is_match(Path::new("src/vendor.rs")));
+        assert!(!exclusions.is_match(Path::new("web/app.js")));
+    }
+}
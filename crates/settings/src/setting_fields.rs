@@ -0,0 +1,324 @@
+use serde_json::Value;
+
+/// How deep the objects of the settings schema are flattened into fields. Deeper
+/// objects are edited as a whole in the settings file.
+const MAX_DEPTH: usize = 3;
+
+/// The keys of the settings schema that repeat the whole schema for a release channel.
+const RELEASE_CHANNEL_KEYS: &[&str] = &["dev", "nightly", "preview", "stable"];
+
+/// A setting that can be edited on its own, found in the settings JSON schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettingField {
+    /// The keys leading to the setting in a settings file.
+    pub key_path: Vec<String>,
+    pub description: Option<String>,
+    pub kind: SettingFieldKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SettingFieldKind {
+    Boolean,
+    /// A string that is one of the given values.
+    Enum(Vec<String>),
+    Integer {
+        minimum: Option<f64>,
+        maximum: Option<f64>,
+    },
+    Number {
+        minimum: Option<f64>,
+        maximum: Option<f64>,
+    },
+    String,
+    /// A value, such as an array or a map, that is edited in the settings file.
+    Other,
+}
+
+impl SettingField {
+    /// The top-level key of the object containing the setting, or `None` for the
+    /// settings at the root of the settings file.
+    pub fn section(&self) -> Option<&str> {
+        if self.key_path.len() > 1 {
+            self.key_path.first().map(String::as_str)
+        } else {
+            None
+        }
+    }
+
+    /// The setting's value in the given settings.
+    pub fn value_in<'a>(&self, settings: &'a Value) -> Option<&'a Value> {
+        self.key_path
+            .iter()
+            .try_fold(settings, |value, key| value.get(key))
+            .filter(|value| !value.is_null())
+    }
+}
+
+/// Flattens the settings JSON schema, as generated by the settings store, into the
+/// settings it describes.
+pub fn setting_fields(schema: &Value) -> Vec<SettingField> {
+    let mut fields = Vec::new();
+    collect_fields(
+        schema,
+        schema.get("definitions"),
+        None,
+        &mut Vec::new(),
+        &mut fields,
+    );
+    fields
+}
+
+fn collect_fields(
+    schema: &Value,
+    definitions: Option<&Value>,
+    description: Option<&str>,
+    key_path: &mut Vec<String>,
+    fields: &mut Vec<SettingField>,
+) {
+    let (schema, resolved_description) = resolve(schema, definitions);
+    let description = description.or(resolved_description);
+
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        if key_path.len() < MAX_DEPTH {
+            for (key, property) in properties {
+                if key_path.is_empty() && RELEASE_CHANNEL_KEYS.contains(&key.as_str()) {
+                    continue;
+                }
+                key_path.push(key.clone());
+                let description = property.get("description").and_then(Value::as_str);
+                collect_fields(property, definitions, description, key_path, fields);
+                key_path.pop();
+            }
+            return;
+        }
+    }
+
+    if !key_path.is_empty() {
+        fields.push(SettingField {
+            key_path: key_path.clone(),
+            description: description.map(ToString::to_string),
+            kind: field_kind(schema, definitions),
+        });
+    }
+}
+
+/// Follows the references of a schema, and unwraps the single schemas it's made of,
+/// returning the schema and the first description found along the way.
+pub(crate) fn resolve<'a>(
+    mut schema: &'a Value,
+    definitions: Option<&'a Value>,
+) -> (&'a Value, Option<&'a str>) {
+    let mut description = None;
+    // References can be recursive.
+    for _ in 0..16 {
+        if description.is_none() {
+            description = schema.get("description").and_then(Value::as_str);
+        }
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let Some(definition) = reference
+                .strip_prefix("#/definitions/")
+                .and_then(|name| definitions?.get(name))
+            else {
+                break;
+            };
+            schema = definition;
+            continue;
+        }
+        let subschemas = ["allOf", "anyOf", "oneOf"]
+            .into_iter()
+            .find_map(|keyword| schema.get(keyword).and_then(Value::as_array));
+        if let Some(subschemas) = subschemas {
+            let mut subschemas = subschemas.iter().filter(|subschema| !is_null(subschema));
+            if let (Some(subschema), None) = (subschemas.next(), subschemas.next()) {
+                schema = subschema;
+                continue;
+            }
+        }
+        break;
+    }
+    (schema, description)
+}
+
+fn field_kind(schema: &Value, definitions: Option<&Value>) -> SettingFieldKind {
+    if let Some(options) = enum_options(schema, definitions) {
+        return SettingFieldKind::Enum(options);
+    }
+
+    let minimum = schema.get("minimum").and_then(Value::as_f64);
+    let maximum = schema.get("maximum").and_then(Value::as_f64);
+    match instance_types(schema).as_slice() {
+        ["boolean"] => SettingFieldKind::Boolean,
+        ["integer"] => SettingFieldKind::Integer { minimum, maximum },
+        ["number"] => SettingFieldKind::Number { minimum, maximum },
+        ["string"] => SettingFieldKind::String,
+        _ => SettingFieldKind::Other,
+    }
+}
+
+/// The values of a schema that only allows some strings, listed either as its enum
+/// values, or as subschemas with a single value each, as documented enum variants are.
+pub(crate) fn enum_options(schema: &Value, definitions: Option<&Value>) -> Option<Vec<String>> {
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        return values
+            .iter()
+            .filter(|value| !value.is_null())
+            .map(|value| value.as_str().map(ToString::to_string))
+            .collect();
+    }
+    if let Some(value) = schema.get("const") {
+        return value.as_str().map(|value| vec![value.to_string()]);
+    }
+
+    let subschemas = ["anyOf", "oneOf"]
+        .into_iter()
+        .find_map(|keyword| schema.get(keyword).and_then(Value::as_array))?;
+    let mut options = Vec::new();
+    for subschema in subschemas.iter().filter(|subschema| !is_null(subschema)) {
+        let (subschema, _) = resolve(subschema, definitions);
+        options.extend(enum_options(subschema, definitions)?);
+    }
+    (!options.is_empty()).then_some(options)
+}
+
+pub(crate) fn instance_types(schema: &Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(instance_type)) => vec![instance_type.as_str()],
+        Some(Value::Array(instance_types)) => instance_types
+            .iter()
+            .filter_map(Value::as_str)
+            .filter(|instance_type| *instance_type != "null")
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+pub(crate) fn is_null(schema: &Value) -> bool {
+    schema.get("type").and_then(Value::as_str) == Some("null")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_setting_fields() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "tab_size": {
+                    "description": "How many columns a tab occupies.",
+                    "type": ["integer", "null"],
+                    "minimum": 1.0
+                },
+                "cursor_blink": {
+                    "type": "boolean"
+                },
+                "theme": {
+                    "type": "string"
+                },
+                "soft_wrap": {
+                    "description": "How to soft-wrap long lines of text.",
+                    "allOf": [{ "$ref": "#/definitions/SoftWrap" }]
+                },
+                "terminal": {
+                    "$ref": "#/definitions/TerminalSettings"
+                },
+                "file_types": {
+                    "type": "object",
+                    "additionalProperties": { "type": "array" }
+                },
+                "stable": {
+                    "type": "object",
+                    "properties": { "tab_size": { "type": "integer" } }
+                }
+            },
+            "definitions": {
+                "SoftWrap": {
+                    "oneOf": [
+                        {
+                            "description": "Don't soft-wrap.",
+                            "type": "string",
+                            "enum": ["none"]
+                        },
+                        {
+                            "description": "Soft-wrap at the editor width.",
+                            "type": "string",
+                            "enum": ["editor_width"]
+                        }
+                    ]
+                },
+                "TerminalSettings": {
+                    "type": "object",
+                    "properties": {
+                        "blinking": {
+                            "anyOf": [
+                                { "type": "string", "enum": ["off", "on"] },
+                                { "type": "null" }
+                            ]
+                        },
+                        "line_height": {
+                            "type": "number",
+                            "minimum": 1.0,
+                            "maximum": 2.0
+                        }
+                    }
+                }
+            }
+        });
+
+        let fields = setting_fields(&schema);
+        pretty_assertions::assert_eq!(
+            fields,
+            vec![
+                SettingField {
+                    key_path: vec!["tab_size".into()],
+                    description: Some("How many columns a tab occupies.".into()),
+                    kind: SettingFieldKind::Integer {
+                        minimum: Some(1.0),
+                        maximum: None
+                    },
+                },
+                SettingField {
+                    key_path: vec!["cursor_blink".into()],
+                    description: None,
+                    kind: SettingFieldKind::Boolean,
+                },
+                SettingField {
+                    key_path: vec!["theme".into()],
+                    description: None,
+                    kind: SettingFieldKind::String,
+                },
+                SettingField {
+                    key_path: vec!["soft_wrap".into()],
+                    description: Some("How to soft-wrap long lines of text.".into()),
+                    kind: SettingFieldKind::Enum(vec!["none".into(), "editor_width".into()]),
+                },
+                SettingField {
+                    key_path: vec!["terminal".into(), "blinking".into()],
+                    description: None,
+                    kind: SettingFieldKind::Enum(vec!["off".into(), "on".into()]),
+                },
+                SettingField {
+                    key_path: vec!["terminal".into(), "line_height".into()],
+                    description: None,
+                    kind: SettingFieldKind::Number {
+                        minimum: Some(1.0),
+                        maximum: Some(2.0)
+                    },
+                },
+                SettingField {
+                    key_path: vec!["file_types".into()],
+                    description: None,
+                    kind: SettingFieldKind::Other,
+                },
+            ]
+        );
+        assert_eq!(fields[0].section(), None);
+        assert_eq!(fields[4].section(), Some("terminal"));
+
+        let settings = json!({ "terminal": { "blinking": "on" }, "tab_size": null });
+        assert_eq!(fields[4].value_in(&settings), Some(&json!("on")));
+        assert_eq!(fields[0].value_in(&settings), None);
+    }
+}
@@ -1,8 +1,10 @@
 mod editable_setting_control;
 mod json_schema;
 mod keymap_file;
+mod setting_fields;
 mod settings_file;
 mod settings_store;
+mod settings_validation;
 
 use gpui::AppContext;
 use rust_embed::RustEmbed;
@@ -12,8 +14,13 @@ use util::asset_str;
 pub use editable_setting_control::*;
 pub use json_schema::*;
 pub use keymap_file::KeymapFile;
+pub use setting_fields::{setting_fields, SettingField, SettingFieldKind};
 pub use settings_file::*;
 pub use settings_store::{Settings, SettingsLocation, SettingsSources, SettingsStore};
+pub use settings_validation::{
+    key_path_at_offset, setting_description, validate_settings_text, SettingsFix, SettingsProblem,
+    SettingsProblemSeverity,
+};
 
 #[derive(RustEmbed)]
 #[folder = "../../assets"]
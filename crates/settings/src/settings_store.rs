@@ -110,6 +110,11 @@ pub struct SettingsSources<'a, T> {
     pub user: Option<&'a T>,
     /// The user settings for the current release channel.
     pub release_channel: Option<&'a T>,
+    /// The user settings for this machine, from the conditional settings whose
+    /// conditions, such as the OS or the host name, it matches.
+    pub machine: &'a [&'a T],
+    /// The user settings of the active settings profile.
+    pub profile: Option<&'a T>,
     /// The project settings, ordered from least specific to most specific.
     pub project: &'a [&'a T],
 }
@@ -124,11 +129,21 @@ impl<'a, T: Serialize> SettingsSources<'a, T> {
     pub fn customizations(&self) -> impl Iterator<Item = &T> {
         self.extensions
             .into_iter()
-            .chain(self.user)
-            .chain(self.release_channel)
+            .chain(self.user_customizations())
             .chain(self.project.iter().copied())
     }
 
+    /// Returns an iterator over the user's settings customizations: the user settings,
+    /// followed by those for the current release channel, for this machine, and for
+    /// the active profile.
+    pub fn user_customizations(&self) -> impl Iterator<Item = &T> {
+        self.user
+            .into_iter()
+            .chain(self.release_channel)
+            .chain(self.machine.iter().copied())
+            .chain(self.profile)
+    }
+
     /// Returns the settings after performing a JSON merge of the provided customizations.
     ///
     /// Customizations later in the iterator win out over the earlier ones.
@@ -164,6 +179,8 @@ pub struct SettingsStore {
     raw_user_settings: serde_json::Value,
     raw_extension_settings: serde_json::Value,
     raw_local_settings: BTreeMap<(usize, Arc<Path>), serde_json::Value>,
+    active_profile: Option<String>,
+    host_name: Option<String>,
     tab_size_callback: Option<(
         TypeId,
         Box<dyn Fn(&dyn Any) -> Option<usize> + Send + Sync + 'static>,
@@ -213,6 +230,8 @@ impl SettingsStore {
             raw_user_settings: serde_json::json!({}),
             raw_extension_settings: serde_json::json!({}),
             raw_local_settings: Default::default(),
+            active_profile: None,
+            host_name: sysinfo::System::host_name(),
             tab_size_callback: Default::default(),
             setting_file_updates_tx,
             _setting_file_updates: cx.spawn(|cx| async move {
@@ -265,6 +284,18 @@ impl SettingsStore {
                 .deserialize_setting(&self.raw_extension_settings)
                 .log_err();
 
+            let (machine_settings, profile_settings) = user_overrides(
+                &self.raw_user_settings,
+                self.active_profile.as_deref(),
+                self.host_name.as_deref(),
+            );
+            let machine_values = machine_settings
+                .into_iter()
+                .filter_map(|settings| setting_value.deserialize_setting(settings).log_err())
+                .collect::<Vec<_>>();
+            let profile_value = profile_settings
+                .and_then(|settings| setting_value.deserialize_setting(settings).log_err());
+
             if let Some(setting) = setting_value
                 .load_setting(
                     SettingsSources {
@@ -272,6 +303,8 @@ impl SettingsStore {
                         release_channel: release_channel_value.as_ref(),
                         extensions: extension_value.as_ref(),
                         user: user_value.as_ref(),
+                        machine: &machine_values.iter().collect::<Vec<_>>(),
+                        profile: profile_value.as_ref(),
                         project: &[],
                     },
                     cx,
@@ -297,6 +330,15 @@ impl SettingsStore {
             .expect("no default value for setting type")
     }
 
+    /// Get the value of a setting, or `None` if the given setting type has not been
+    /// registered.
+    pub fn try_get<T: Settings>(&self, path: Option<SettingsLocation>) -> Option<&T> {
+        self.setting_values
+            .get(&TypeId::of::<T>())?
+            .value_for_path(path)
+            .downcast_ref::<T>()
+    }
+
     /// Override the global value for a setting.
     ///
     /// The given value will be overwritten if the user settings file changes.
@@ -315,6 +357,80 @@ impl SettingsStore {
         &self.raw_user_settings
     }
 
+    /// Get the default settings as a raw JSON value.
+    pub fn raw_default_settings(&self) -> &serde_json::Value {
+        &self.raw_default_settings
+    }
+
+    /// The value of a setting once the user settings, and the release channel, conditional
+    /// and profile settings in them, are merged into the defaults.
+    pub fn effective_value(&self, key_path: &[&str]) -> Option<serde_json::Value> {
+        key_path
+            .iter()
+            .try_fold(&self.effective_user_settings(), |value, key| value.get(key))
+            .cloned()
+    }
+
+    /// The local settings of a worktree that differ from the effective user settings,
+    /// for each directory of the worktree whose settings override any of them.
+    pub fn local_settings_deltas(&self, root_id: usize) -> Vec<(Arc<Path>, serde_json::Value)> {
+        let user_settings = self.effective_user_settings();
+        self.raw_local_settings
+            .range((root_id, Path::new("").into())..(root_id + 1, Path::new("").into()))
+            .filter_map(|((_, path), content)| {
+                Some((path.clone(), json_delta(content, &user_settings)?))
+            })
+            .collect()
+    }
+
+    fn effective_user_settings(&self) -> serde_json::Value {
+        let mut merged = self.raw_default_settings.clone();
+        merge_non_null_json_value_into(self.raw_extension_settings.clone(), &mut merged);
+        merge_non_null_json_value_into(self.raw_user_settings.clone(), &mut merged);
+        if let Some(release_settings) = self
+            .raw_user_settings
+            .get(release_channel::RELEASE_CHANNEL.dev_name())
+        {
+            merge_non_null_json_value_into(release_settings.clone(), &mut merged);
+        }
+        let (machine_settings, profile_settings) = user_overrides(
+            &self.raw_user_settings,
+            self.active_profile.as_deref(),
+            self.host_name.as_deref(),
+        );
+        for settings in machine_settings.into_iter().chain(profile_settings) {
+            merge_non_null_json_value_into(settings.clone(), &mut merged);
+        }
+        merged
+    }
+
+    /// The names of the settings profiles defined in the user settings.
+    pub fn profile_names(&self) -> Vec<String> {
+        self.raw_user_settings
+            .get(PROFILES_KEY)
+            .and_then(serde_json::Value::as_object)
+            .map(|profiles| profiles.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn active_profile(&self) -> Option<&str> {
+        self.active_profile.as_deref()
+    }
+
+    /// Activates a settings profile, whose settings apply over the user settings, or
+    /// deactivates the active profile when the profile is `None`.
+    pub fn set_active_profile(
+        &mut self,
+        profile: Option<String>,
+        cx: &mut AppContext,
+    ) -> Result<()> {
+        if self.active_profile != profile {
+            self.active_profile = profile;
+            self.recompute_values(None, cx)?;
+        }
+        Ok(())
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn test(cx: &mut AppContext) -> Self {
         let mut this = Self::new(cx);
@@ -365,33 +481,55 @@ impl SettingsStore {
                     let new_text = cx.read_global(|store: &SettingsStore, cx| {
                         store.new_text_for_update::<T>(old_text, |content| update(content, cx))
                     })?;
-                    let initial_path = paths::settings_file().as_path();
-                    if fs.is_file(initial_path).await {
-                        let resolved_path =
-                            fs.canonicalize(initial_path).await.with_context(|| {
-                                format!("Failed to canonicalize settings path {:?}", initial_path)
-                            })?;
-
-                        fs.atomic_write(resolved_path.clone(), new_text)
-                            .await
-                            .with_context(|| {
-                                format!("Failed to write settings to file {:?}", resolved_path)
-                            })?;
-                    } else {
-                        fs.atomic_write(initial_path.to_path_buf(), new_text)
-                            .await
-                            .with_context(|| {
-                                format!("Failed to write settings to file {:?}", initial_path)
-                            })?;
-                    }
+                    Self::write_settings(&fs, new_text).await
+                }
+                .boxed_local()
+            }))
+            .ok();
+    }
 
-                    anyhow::Ok(())
+    /// Sets the value at a key path of the user's settings file, or removes it when
+    /// the value is `None`, without any knowledge of the setting's type.
+    pub fn update_settings_file_value(
+        &self,
+        fs: Arc<dyn Fs>,
+        key_path: Vec<String>,
+        value: Option<serde_json::Value>,
+    ) {
+        self.setting_file_updates_tx
+            .unbounded_send(Box::new(move |cx: AsyncAppContext| {
+                async move {
+                    let old_text = Self::load_settings(&fs).await?;
+                    let new_text = cx.read_global(|store: &SettingsStore, _| {
+                        let key_path = key_path.iter().map(String::as_str).collect::<Vec<_>>();
+                        store.new_text_for_value_update(old_text, &key_path, value)
+                    })?;
+                    Self::write_settings(&fs, new_text).await
                 }
                 .boxed_local()
             }))
             .ok();
     }
 
+    async fn write_settings(fs: &Arc<dyn Fs>, new_text: String) -> Result<()> {
+        let initial_path = paths::settings_file().as_path();
+        if fs.is_file(initial_path).await {
+            let resolved_path = fs.canonicalize(initial_path).await.with_context(|| {
+                format!("Failed to canonicalize settings path {:?}", initial_path)
+            })?;
+
+            fs.atomic_write(resolved_path.clone(), new_text)
+                .await
+                .with_context(|| format!("Failed to write settings to file {:?}", resolved_path))?;
+        } else {
+            fs.atomic_write(initial_path.to_path_buf(), new_text)
+                .await
+                .with_context(|| format!("Failed to write settings to file {:?}", initial_path))?;
+        }
+
+        anyhow::Ok(())
+    }
+
     /// Updates the value of a setting in a JSON file, returning the new text
     /// for that JSON file.
     pub fn new_text_for_update<T: Settings>(
@@ -407,6 +545,45 @@ impl SettingsStore {
         new_text
     }
 
+    /// Sets the value at a key path in a JSON file, or removes its key when the value
+    /// is `None`, returning the new text for that JSON file. The comments and the
+    /// order of the keys of the rest of the file are preserved.
+    pub fn new_text_for_value_update(
+        &self,
+        old_text: String,
+        key_path: &[&str],
+        new_value: Option<serde_json::Value>,
+    ) -> String {
+        let Some(new_value) = new_value else {
+            let mut text = old_text;
+            if let Some(range) = remove_value_in_json_text(&text, key_path) {
+                text.replace_range(range, "");
+            }
+            return text;
+        };
+        let raw_settings =
+            parse_json_with_comments::<serde_json::Value>(&old_text).unwrap_or_default();
+        let old_value = key_path
+            .iter()
+            .try_fold(&raw_settings, |value, key| value.get(key))
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+
+        let mut key_path = key_path.to_vec();
+        let mut edits = Vec::new();
+        let mut text = old_text;
+        update_value_in_json_text(
+            &mut text,
+            &mut key_path,
+            self.json_tab_size(),
+            &old_value,
+            &new_value,
+            &[],
+            &mut edits,
+        );
+        text
+    }
+
     /// Updates the value of a setting in a JSON file, returning a list
     /// of edits to apply to the JSON file.
     pub fn edits_for_update<T: Settings>(
@@ -668,6 +845,55 @@ impl SettingsStore {
                 .insert(release_stage.to_string(), schema.into());
         }
 
+        let settings_schema = serde_json::to_value(&combined_schema.schema).unwrap();
+        let profiles_schema = serde_json::json!({
+            "description": "Sets of settings that apply over the user settings, \
+                while their profile is active.",
+            "type": "object",
+            "additionalProperties": settings_schema,
+        });
+        let os_schema = serde_json::json!({
+            "type": "string",
+            "enum": ["linux", "macos", "windows"],
+        });
+        let host_name_schema = serde_json::json!({ "type": "string" });
+        let conditional_settings_schema = serde_json::json!({
+            "description": "Settings that only apply to the machines matching their conditions.",
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": {
+                    "when": {
+                        "description": "The conditions the machine must match, \
+                            each of which is either a value or a list of values.",
+                        "type": "object",
+                        "properties": {
+                            "os": {
+                                "anyOf": [os_schema, { "type": "array", "items": os_schema }]
+                            },
+                            "hostname": {
+                                "anyOf": [
+                                    host_name_schema,
+                                    { "type": "array", "items": host_name_schema }
+                                ]
+                            }
+                        },
+                        "additionalProperties": false
+                    },
+                    "settings": settings_schema,
+                }
+            }
+        });
+        let properties = &mut combined_schema.schema.object().properties;
+        for (key, schema) in [
+            (PROFILES_KEY, profiles_schema),
+            (CONDITIONAL_SETTINGS_KEY, conditional_settings_schema),
+        ] {
+            if let Some(schema) = serde_json::from_value::<Schema>(schema).log_err() {
+                properties.insert(key.to_string(), schema);
+            }
+        }
+
         serde_json::to_value(&combined_schema).unwrap()
     }
 
@@ -679,6 +905,11 @@ impl SettingsStore {
         // Reload the global and local values for every setting.
         let mut project_settings_stack = Vec::<DeserializedSetting>::new();
         let mut paths_stack = Vec::<Option<(usize, &Path)>>::new();
+        let (machine_overrides, profile_overrides) = user_overrides(
+            &self.raw_user_settings,
+            self.active_profile.as_deref(),
+            self.host_name.as_deref(),
+        );
         for setting_value in self.setting_values.values_mut() {
             let default_settings = setting_value.deserialize_setting(&self.raw_default_settings)?;
 
@@ -703,6 +934,14 @@ impl SettingsStore {
                 }
             }
 
+            let machine_settings = machine_overrides
+                .iter()
+                .filter_map(|settings| setting_value.deserialize_setting(settings).log_err())
+                .collect::<Vec<_>>();
+            let machine_settings = machine_settings.iter().collect::<Vec<_>>();
+            let profile_settings = profile_overrides
+                .and_then(|settings| setting_value.deserialize_setting(settings).log_err());
+
             // If the global settings file changed, reload the global value for the field.
             if changed_local_path.is_none() {
                 if let Some(value) = setting_value
@@ -712,6 +951,8 @@ impl SettingsStore {
                             extensions: extension_settings.as_ref(),
                             user: user_settings.as_ref(),
                             release_channel: release_channel_settings.as_ref(),
+                            machine: &machine_settings,
+                            profile: profile_settings.as_ref(),
                             project: &[],
                         },
                         cx,
@@ -759,6 +1000,8 @@ impl SettingsStore {
                                 extensions: extension_settings.as_ref(),
                                 user: user_settings.as_ref(),
                                 release_channel: release_channel_settings.as_ref(),
+                                machine: &machine_settings,
+                                profile: profile_settings.as_ref(),
                                 project: &project_settings_stack.iter().collect::<Vec<_>>(),
                             },
                             cx,
@@ -818,6 +1061,15 @@ impl<T: Settings> AnySettingValue for SettingValue<T> {
                 release_channel: values
                     .release_channel
                     .map(|value| value.0.downcast_ref::<T::FileContent>().unwrap()),
+                machine: values
+                    .machine
+                    .iter()
+                    .map(|value| value.0.downcast_ref().unwrap())
+                    .collect::<SmallVec<[_; 2]>>()
+                    .as_slice(),
+                profile: values
+                    .profile
+                    .map(|value| value.0.downcast_ref::<T::FileContent>().unwrap()),
                 project: values
                     .project
                     .iter()
@@ -880,6 +1132,86 @@ impl<T: Settings> AnySettingValue for SettingValue<T> {
     }
 }
 
+/// The key of the user settings whose named objects are the settings profiles.
+const PROFILES_KEY: &str = "profiles";
+/// The key of the user settings whose list holds the settings that only apply to the
+/// machines matching their conditions.
+const CONDITIONAL_SETTINGS_KEY: &str = "conditional_settings";
+
+/// The conditional user settings that apply to this machine, and the settings of the
+/// active profile.
+fn user_overrides<'a>(
+    raw_user_settings: &'a serde_json::Value,
+    active_profile: Option<&str>,
+    host_name: Option<&str>,
+) -> (Vec<&'a serde_json::Value>, Option<&'a serde_json::Value>) {
+    let machine = raw_user_settings
+        .get(CONDITIONAL_SETTINGS_KEY)
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|conditional| {
+            conditional.get("when").map_or(true, |when| {
+                conditions_match(when, std::env::consts::OS, host_name)
+            })
+        })
+        .filter_map(|conditional| conditional.get("settings"))
+        .collect();
+    let profile =
+        active_profile.and_then(|profile| raw_user_settings.get(PROFILES_KEY)?.get(profile));
+    (machine, profile)
+}
+
+/// The parts of a JSON value that differ from a base value, ignoring nulls as they
+/// don't override anything when settings are merged.
+fn json_delta(value: &serde_json::Value, base: &serde_json::Value) -> Option<serde_json::Value> {
+    match (value, base) {
+        (serde_json::Value::Null, _) => None,
+        (serde_json::Value::Object(object), serde_json::Value::Object(base)) => {
+            let delta = object
+                .iter()
+                .filter_map(|(key, value)| {
+                    let delta = match base.get(key) {
+                        Some(base) => json_delta(value, base)?,
+                        None if value.is_null() => return None,
+                        None => value.clone(),
+                    };
+                    Some((key.clone(), delta))
+                })
+                .collect::<serde_json::Map<_, _>>();
+            (!delta.is_empty()).then_some(serde_json::Value::Object(delta))
+        }
+        _ => (value != base).then(|| value.clone()),
+    }
+}
+
+/// Whether the machine matches all of the conditions, each of which is either a value
+/// or a list of values, one of which must match.
+fn conditions_match(when: &serde_json::Value, os: &str, host_name: Option<&str>) -> bool {
+    let Some(conditions) = when.as_object() else {
+        return false;
+    };
+    conditions.iter().all(|(condition, expected)| {
+        let actual = match condition.as_str() {
+            "os" => Some(os),
+            "hostname" => host_name,
+            _ => None,
+        };
+        let Some(actual) = actual else {
+            return false;
+        };
+        let matches = |expected: &serde_json::Value| {
+            expected
+                .as_str()
+                .map_or(false, |expected| expected.eq_ignore_ascii_case(actual))
+        };
+        match expected {
+            serde_json::Value::Array(values) => values.iter().any(matches),
+            value => matches(value),
+        }
+    })
+}
+
 fn update_value_in_json_text<'a>(
     text: &mut String,
     key_path: &mut Vec<&'a str>,
@@ -1056,6 +1388,55 @@ fn replace_value_in_json_text(
     }
 }
 
+/// The range of the text to remove to remove the key at the key path, along with its
+/// value and the comma separating it from its siblings.
+fn remove_value_in_json_text(text: &str, key_path: &[&str]) -> Option<Range<usize>> {
+    fn find_pair<'a>(
+        object: tree_sitter::Node<'a>,
+        key: &str,
+        text: &str,
+    ) -> Option<tree_sitter::Node<'a>> {
+        if object.kind() != "object" {
+            return None;
+        }
+        let key = format!("\"{key}\"");
+        let mut cursor = object.walk();
+        let pair = object.named_children(&mut cursor).find(|child| {
+            child.kind() == "pair"
+                && child
+                    .child_by_field_name("key")
+                    .and_then(|key_node| text.get(key_node.byte_range()))
+                    == Some(key.as_str())
+        });
+        pair
+    }
+
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_json::language()).unwrap();
+    let syntax_tree = parser.parse(text, None)?;
+
+    let (last_key, parent_keys) = key_path.split_last()?;
+    let mut object = syntax_tree.root_node().named_child(0)?;
+    for key in parent_keys {
+        object = find_pair(object, key, text)?.child_by_field_name("value")?;
+    }
+    let pair = find_pair(object, last_key, text)?;
+
+    let mut range = pair.byte_range();
+    if let Some(comma) = pair.next_sibling().filter(|sibling| sibling.kind() == ",") {
+        range.end = comma
+            .next_sibling()
+            .map_or(comma.end_byte(), |sibling| sibling.start_byte());
+    } else if let Some(previous) = pair.prev_sibling() {
+        range.start = if previous.kind() == "," {
+            previous.start_byte()
+        } else {
+            previous.end_byte()
+        };
+    }
+    Some(range)
+}
+
 fn to_pretty_json(value: &impl Serialize, indent_size: usize, indent_prefix_len: usize) -> String {
     const SPACES: [u8; 32] = [b' '; 32];
 
@@ -1091,6 +1472,7 @@ pub fn parse_json_with_comments<T: DeserializeOwned>(content: &str) -> Result<T>
 mod tests {
     use super::*;
     use serde_derive::Deserialize;
+    use serde_json::json;
     use unindent::Unindent;
 
     #[gpui::test]
@@ -1385,6 +1767,271 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_conditional_settings_and_profiles(cx: &mut AppContext) {
+        let mut store = SettingsStore::new(cx);
+        store.register_setting::<UserSettings>(cx);
+        store
+            .set_default_settings(
+                r#"{
+                    "user": {
+                        "name": "John Doe",
+                        "age": 30,
+                        "staff": false
+                    }
+                }"#,
+                cx,
+            )
+            .unwrap();
+        store
+            .set_user_settings(
+                &format!(
+                    r#"{{
+                        "user": {{ "age": 31 }},
+                        "conditional_settings": [
+                            {{
+                                "when": {{ "os": "{}" }},
+                                "settings": {{ "user": {{ "age": 32 }} }}
+                            }},
+                            {{
+                                "when": {{ "os": "plan9" }},
+                                "settings": {{ "user": {{ "age": 9 }} }}
+                            }},
+                            {{
+                                "when": {{ "cpu": "z80" }},
+                                "settings": {{ "user": {{ "age": 8 }} }}
+                            }}
+                        ],
+                        "profiles": {{
+                            "presentation": {{ "user": {{ "name": "Presenter" }} }},
+                            "staff": {{ "user": {{ "staff": true, "age": 40 }} }}
+                        }}
+                    }}"#,
+                    std::env::consts::OS
+                ),
+                cx,
+            )
+            .unwrap();
+
+        // only the conditions matching this machine apply
+        assert_eq!(
+            store.get::<UserSettings>(None),
+            &UserSettings {
+                name: "John Doe".to_string(),
+                age: 32,
+                staff: false,
+            }
+        );
+        assert_eq!(store.profile_names(), vec!["presentation", "staff"]);
+
+        // the active profile applies over the conditional settings
+        store.set_active_profile(Some("staff".into()), cx).unwrap();
+        assert_eq!(store.active_profile(), Some("staff"));
+        assert_eq!(
+            store.get::<UserSettings>(None),
+            &UserSettings {
+                name: "John Doe".to_string(),
+                age: 40,
+                staff: true,
+            }
+        );
+
+        assert_eq!(store.effective_value(&["user", "age"]), Some(json!(40)));
+        assert_eq!(
+            store.effective_value(&["user", "name"]),
+            Some(json!("John Doe"))
+        );
+        assert_eq!(store.effective_value(&["user", "height"]), None);
+
+        store
+            .set_active_profile(Some("presentation".into()), cx)
+            .unwrap();
+        assert_eq!(
+            store.get::<UserSettings>(None),
+            &UserSettings {
+                name: "Presenter".to_string(),
+                age: 32,
+                staff: false,
+            }
+        );
+
+        store.set_active_profile(None, cx).unwrap();
+        assert_eq!(store.get::<UserSettings>(None).name, "John Doe");
+    }
+
+    #[gpui::test]
+    fn test_local_settings_deltas(cx: &mut AppContext) {
+        let mut store = SettingsStore::new(cx);
+        store.register_setting::<UserSettings>(cx);
+        store.register_setting::<TurboSetting>(cx);
+        store
+            .set_default_settings(
+                r#"{
+                    "turbo": false,
+                    "user": { "name": "John Doe", "age": 30, "staff": false }
+                }"#,
+                cx,
+            )
+            .unwrap();
+        store.set_user_settings(r#"{ "turbo": true }"#, cx).unwrap();
+        store
+            .set_local_settings(
+                1,
+                Path::new("/root1").into(),
+                Some(r#"{ "turbo": true, "user": { "age": 30, "staff": true, "name": null } }"#),
+                cx,
+            )
+            .unwrap();
+        store
+            .set_local_settings(
+                1,
+                Path::new("/root1/subdir").into(),
+                Some(r#"{ "turbo": true }"#),
+                cx,
+            )
+            .unwrap();
+        store
+            .set_local_settings(
+                2,
+                Path::new("/root2").into(),
+                Some(r#"{ "turbo": false }"#),
+                cx,
+            )
+            .unwrap();
+
+        // only the settings differing from the user settings are deltas
+        assert_eq!(
+            store.local_settings_deltas(1),
+            vec![(
+                Path::new("/root1").into(),
+                json!({ "user": { "staff": true } })
+            )]
+        );
+        assert_eq!(
+            store.local_settings_deltas(2),
+            vec![(Path::new("/root2").into(), json!({ "turbo": false }))]
+        );
+    }
+
+    #[test]
+    fn test_conditions_match() {
+        let host_name = Some("Studio");
+        assert!(conditions_match(&json!({}), "linux", host_name));
+        assert!(conditions_match(
+            &json!({ "os": "linux" }),
+            "linux",
+            host_name
+        ));
+        assert!(conditions_match(
+            &json!({ "os": ["macos", "linux"] }),
+            "linux",
+            host_name
+        ));
+        assert!(conditions_match(
+            &json!({ "os": "linux", "hostname": "studio" }),
+            "linux",
+            host_name
+        ));
+        assert!(!conditions_match(
+            &json!({ "os": "linux", "hostname": "laptop" }),
+            "linux",
+            host_name
+        ));
+        assert!(!conditions_match(
+            &json!({ "hostname": "studio" }),
+            "linux",
+            None
+        ));
+        assert!(!conditions_match(
+            &json!({ "os": "windows" }),
+            "linux",
+            host_name
+        ));
+        assert!(!conditions_match(
+            &json!({ "arch": "x86_64" }),
+            "linux",
+            host_name
+        ));
+        assert!(!conditions_match(&json!("linux"), "linux", host_name));
+    }
+
+    #[gpui::test]
+    fn test_setting_store_update_value(cx: &mut AppContext) {
+        let store = SettingsStore::new(cx);
+        let old_text = r#"{
+            // The theme.
+            "theme": "One Dark",
+            "tab_size": 4,
+            "terminal": {
+                "font_size": 15 // Large.
+            },
+            "vim_mode": true
+        }"#
+        .unindent();
+
+        // existing values are replaced in place
+        let new_text =
+            store.new_text_for_value_update(old_text.clone(), &["tab_size"], Some(json!(2)));
+        pretty_assertions::assert_eq!(
+            new_text,
+            old_text.replace("\"tab_size\": 4", "\"tab_size\": 2")
+        );
+
+        // missing values are added, along with the objects containing them
+        let new_text = store.new_text_for_value_update(
+            old_text.clone(),
+            &["terminal", "blinking"],
+            Some(json!("off")),
+        );
+        pretty_assertions::assert_eq!(
+            new_text,
+            r#"{
+                // The theme.
+                "theme": "One Dark",
+                "tab_size": 4,
+                "terminal": {
+                    "blinking": "off",
+                    "font_size": 15 // Large.
+                },
+                "vim_mode": true
+            }"#
+            .unindent()
+        );
+
+        // removed values take their comma with them
+        let new_text = store.new_text_for_value_update(old_text.clone(), &["tab_size"], None);
+        pretty_assertions::assert_eq!(
+            new_text,
+            r#"{
+                // The theme.
+                "theme": "One Dark",
+                "terminal": {
+                    "font_size": 15 // Large.
+                },
+                "vim_mode": true
+            }"#
+            .unindent()
+        );
+        let new_text = store.new_text_for_value_update(old_text.clone(), &["vim_mode"], None);
+        pretty_assertions::assert_eq!(
+            new_text,
+            r#"{
+                // The theme.
+                "theme": "One Dark",
+                "tab_size": 4,
+                "terminal": {
+                    "font_size": 15 // Large.
+                }
+            }"#
+            .unindent()
+        );
+
+        // removing a missing value leaves the text as it is
+        let new_text =
+            store.new_text_for_value_update(old_text.clone(), &["terminal", "shell"], None);
+        pretty_assertions::assert_eq!(new_text, old_text);
+    }
+
     fn check_settings_update<T: Settings>(
         store: &mut SettingsStore,
         old_json: String,
@@ -0,0 +1,604 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tree_sitter::Node;
+
+use crate::setting_fields::{enum_options, instance_types, is_null, resolve};
+
+/// A problem found in a settings file, which doesn't match the settings schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettingsProblem {
+    /// The byte range of the key or value with the problem.
+    pub range: Range<usize>,
+    pub message: String,
+    pub severity: SettingsProblemSeverity,
+    pub fix: Option<SettingsFix>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingsProblemSeverity {
+    /// The value can't be deserialized, so the setting is ignored.
+    Error,
+    /// The key or value is ignored, or is probably a mistake.
+    Warning,
+}
+
+/// A suggested fix for a problem, which replaces the text of its range.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettingsFix {
+    pub title: String,
+    pub replacement: String,
+}
+
+/// Validates the text of a settings file, such as `settings.json` or `keymap.json`,
+/// against its JSON schema, reporting the values of the wrong type and the keys the
+/// schema doesn't know about.
+pub fn validate_settings_text(text: &str, schema: &Value) -> Vec<SettingsProblem> {
+    let Some(tree) = parse(text) else {
+        return Vec::new();
+    };
+    let Some(root) = value_node(tree.root_node()) else {
+        return Vec::new();
+    };
+    let mut validator = Validator {
+        text,
+        definitions: schema.get("definitions"),
+        problems: Vec::new(),
+    };
+    validator.validate(root, schema);
+    validator.problems
+}
+
+/// Returns the keys leading to the key at the given offset of a settings file, and
+/// the byte range of that key.
+pub fn key_path_at_offset(text: &str, offset: usize) -> Option<(Vec<String>, Range<usize>)> {
+    let tree = parse(text)?;
+    let mut node = value_node(tree.root_node())?;
+    let mut key_path = Vec::new();
+    loop {
+        if node.kind() != "object" {
+            return None;
+        }
+        let mut cursor = node.walk();
+        let pair = node
+            .named_children(&mut cursor)
+            .find(|child| child.kind() == "pair" && child.byte_range().contains(&offset))?;
+        let key = pair.child_by_field_name("key")?;
+        key_path.push(string_value(text, key)?);
+        if key.byte_range().contains(&offset) {
+            return Some((key_path, key.byte_range()));
+        }
+        node = pair.child_by_field_name("value")?;
+    }
+}
+
+/// Returns the description of the setting at the given key path, found in the schema.
+pub fn setting_description(schema: &Value, key_path: &[String]) -> Option<String> {
+    let definitions = schema.get("definitions");
+    let mut schema = schema;
+    let mut description = None;
+    for key in key_path {
+        let (resolved, _) = resolve(schema, definitions);
+        let property = resolved
+            .get("properties")
+            .and_then(|properties| properties.get(key))
+            .or_else(|| {
+                resolved
+                    .get("additionalProperties")
+                    .filter(|p| p.is_object())
+            })?;
+        description = property.get("description").and_then(Value::as_str);
+        schema = property;
+    }
+    let (_, resolved_description) = resolve(schema, definitions);
+    description
+        .or(resolved_description)
+        .map(ToString::to_string)
+}
+
+fn parse(text: &str) -> Option<tree_sitter::Tree> {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_json::language()).ok()?;
+    parser.parse(text, None)
+}
+
+/// The first value of the document, skipping its comments.
+fn value_node(document: Node) -> Option<Node> {
+    let mut cursor = document.walk();
+    let node = document
+        .named_children(&mut cursor)
+        .find(|child| child.kind() != "comment");
+    node
+}
+
+fn string_value(text: &str, node: Node) -> Option<String> {
+    serde_json::from_str(text.get(node.byte_range())?).ok()
+}
+
+struct Validator<'a> {
+    text: &'a str,
+    definitions: Option<&'a Value>,
+    problems: Vec<SettingsProblem>,
+}
+
+impl<'a> Validator<'a> {
+    fn validate(&mut self, node: Node, schema: &'a Value) {
+        let (schema, _) = resolve(schema, self.definitions);
+        // Settings are unset with `null`, whatever their type.
+        if node.kind() == "null" || node.is_error() {
+            return;
+        }
+
+        let alternatives = ["anyOf", "oneOf"]
+            .into_iter()
+            .find_map(|keyword| schema.get(keyword).and_then(Value::as_array))
+            .map(|subschemas| {
+                subschemas
+                    .iter()
+                    .filter(|subschema| !is_null(subschema))
+                    .map(|subschema| resolve(subschema, self.definitions).0)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if alternatives.len() > 1 {
+            if let Some(options) = enum_options(schema, self.definitions) {
+                self.validate_enum(node, &options);
+                return;
+            }
+            let matching = alternatives
+                .iter()
+                .filter(|alternative| self.type_matches(node, alternative))
+                .collect::<Vec<_>>();
+            match matching.as_slice() {
+                [] => {
+                    let expected = alternatives
+                        .iter()
+                        .flat_map(|alternative| instance_types(alternative))
+                        .collect::<Vec<_>>();
+                    if !expected.is_empty() && expected.len() == alternatives.len() {
+                        self.report_type_mismatch(node, &expected);
+                    }
+                }
+                [alternative] => self.validate(node, alternative),
+                _ => {}
+            }
+            return;
+        }
+
+        let types = instance_types(schema);
+        if !types.is_empty() && !self.type_matches(node, schema) {
+            self.report_type_mismatch(node, &types);
+            return;
+        }
+
+        if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            let options = values
+                .iter()
+                .filter_map(|value| value.as_str().map(ToString::to_string))
+                .collect::<Vec<_>>();
+            if options.len() == values.len() {
+                self.validate_enum(node, &options);
+            }
+            return;
+        }
+
+        match node.kind() {
+            "object" => self.validate_object(node, schema),
+            "array" => {
+                let Some(items) = schema.get("items") else {
+                    return;
+                };
+                let mut cursor = node.walk();
+                let elements = node
+                    .named_children(&mut cursor)
+                    .filter(|child| child.kind() != "comment" && !child.is_error())
+                    .collect::<Vec<_>>();
+                for (ix, element) in elements.into_iter().enumerate() {
+                    match items {
+                        Value::Array(items) => {
+                            if let Some(item) = items.get(ix) {
+                                self.validate(element, item);
+                            }
+                        }
+                        item => self.validate(element, item),
+                    }
+                }
+            }
+            "number" => self.validate_number(node, schema),
+            _ => {}
+        }
+    }
+
+    fn validate_object(&mut self, node: Node, schema: &'a Value) {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let additional_properties = schema.get("additionalProperties");
+        let has_pattern_properties = schema.get("patternProperties").is_some();
+
+        let mut cursor = node.walk();
+        let pairs = node
+            .named_children(&mut cursor)
+            .filter(|child| child.kind() == "pair")
+            .collect::<Vec<_>>();
+        for pair in pairs {
+            let (Some(key_node), Some(value_node)) = (
+                pair.child_by_field_name("key"),
+                pair.child_by_field_name("value"),
+            ) else {
+                continue;
+            };
+            let Some(key) = string_value(self.text, key_node) else {
+                continue;
+            };
+
+            if let Some(property) = properties.and_then(|properties| properties.get(&key)) {
+                self.validate(value_node, property);
+                continue;
+            }
+            match additional_properties {
+                Some(Value::Bool(false)) => {}
+                Some(schema @ Value::Object(_)) => {
+                    self.validate(value_node, schema);
+                    continue;
+                }
+                // Objects without properties, such as maps, allow any key.
+                _ if properties.is_none() || has_pattern_properties => continue,
+                _ => {}
+            }
+
+            let suggestion = properties
+                .and_then(|properties| closest_match(&key, properties.keys().map(String::as_str)));
+            self.problems.push(SettingsProblem {
+                range: key_node.byte_range(),
+                message: format!("Unknown setting \"{key}\""),
+                severity: SettingsProblemSeverity::Warning,
+                fix: suggestion.map(|suggestion| SettingsFix {
+                    title: format!("Rename to \"{suggestion}\""),
+                    replacement: Value::String(suggestion.to_string()).to_string(),
+                }),
+            });
+        }
+    }
+
+    fn validate_enum(&mut self, node: Node, options: &[String]) {
+        let Some(value) = (node.kind() == "string")
+            .then(|| string_value(self.text, node))
+            .flatten()
+        else {
+            self.report_type_mismatch(node, &["string"]);
+            return;
+        };
+        if options.contains(&value) {
+            return;
+        }
+
+        let suggestion = closest_match(&value, options.iter().map(String::as_str));
+        let expected = if options.len() <= 8 {
+            format!(
+                ", expected one of {}",
+                options
+                    .iter()
+                    .map(|option| format!("\"{option}\""))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        } else {
+            String::new()
+        };
+        self.problems.push(SettingsProblem {
+            range: node.byte_range(),
+            message: format!("Unknown value \"{value}\"{expected}"),
+            severity: SettingsProblemSeverity::Error,
+            fix: suggestion.map(|suggestion| SettingsFix {
+                title: format!("Change to \"{suggestion}\""),
+                replacement: Value::String(suggestion.to_string()).to_string(),
+            }),
+        });
+    }
+
+    fn validate_number(&mut self, node: Node, schema: &Value) {
+        let Some(value) = self
+            .text
+            .get(node.byte_range())
+            .and_then(|text| text.parse::<f64>().ok())
+        else {
+            return;
+        };
+        let minimum = schema.get("minimum").and_then(Value::as_f64);
+        let maximum = schema.get("maximum").and_then(Value::as_f64);
+        let message = match (minimum, maximum) {
+            (Some(minimum), _) if value < minimum => format!("Must be at least {minimum}"),
+            (_, Some(maximum)) if value > maximum => format!("Must be at most {maximum}"),
+            _ => return,
+        };
+        self.problems.push(SettingsProblem {
+            range: node.byte_range(),
+            message,
+            severity: SettingsProblemSeverity::Warning,
+            fix: None,
+        });
+    }
+
+    fn type_matches(&self, node: Node, schema: &Value) -> bool {
+        let types = instance_types(schema);
+        if types.is_empty() {
+            // Schemas without a type, such as enums and constants, are checked by value.
+            return match schema.get("enum").or_else(|| schema.get("const")) {
+                Some(Value::Array(values)) => values
+                    .iter()
+                    .any(|value| json_kind(value) == Some(node.kind())),
+                Some(value) => json_kind(value) == Some(node.kind()),
+                None => true,
+            };
+        }
+        let text = self.text.get(node.byte_range()).unwrap_or_default();
+        types.iter().any(|instance_type| match *instance_type {
+            "object" => node.kind() == "object",
+            "array" => node.kind() == "array",
+            "string" => node.kind() == "string",
+            "boolean" => matches!(node.kind(), "true" | "false"),
+            "number" => node.kind() == "number",
+            "integer" => node.kind() == "number" && !text.contains(['.', 'e', 'E']),
+            "null" => node.kind() == "null",
+            _ => true,
+        })
+    }
+
+    fn report_type_mismatch(&mut self, node: Node, expected: &[&str]) {
+        let text = self.text.get(node.byte_range()).unwrap_or_default();
+        let string = (node.kind() == "string")
+            .then(|| string_value(self.text, node))
+            .flatten();
+
+        // Suggest the value the user probably meant, when it's written as the wrong type.
+        let replacement = expected.iter().find_map(|expected| match *expected {
+            "boolean" => string
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| matches!(*value, "true" | "false"))
+                .map(ToString::to_string),
+            "number" => string
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| value.parse::<f64>().is_ok())
+                .map(ToString::to_string),
+            "integer" => string
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| value.parse::<i64>().is_ok())
+                .map(ToString::to_string)
+                .or_else(|| {
+                    let value = text.parse::<f64>().ok()?;
+                    (node.kind() == "number" && value.fract() == 0.0 && value.abs() < 1e15)
+                        .then(|| (value as i64).to_string())
+                }),
+            "string" => matches!(node.kind(), "number" | "true" | "false")
+                .then(|| Value::String(text.to_string()).to_string()),
+            "array" => (node.kind() != "object").then(|| format!("[{text}]")),
+            _ => None,
+        });
+
+        let mut expected = expected
+            .iter()
+            .map(|instance_type| type_description(instance_type))
+            .collect::<Vec<_>>();
+        expected.dedup();
+        self.problems.push(SettingsProblem {
+            range: node.byte_range(),
+            message: format!(
+                "Expected {}, found {}",
+                expected.join(" or "),
+                node_description(node.kind())
+            ),
+            severity: SettingsProblemSeverity::Error,
+            fix: replacement.map(|replacement| SettingsFix {
+                title: format!("Change to {replacement}"),
+                replacement,
+            }),
+        });
+    }
+}
+
+/// The kind of the tree-sitter node for a JSON value.
+fn json_kind(value: &Value) -> Option<&'static str> {
+    match value {
+        Value::Null => Some("null"),
+        Value::Bool(true) => Some("true"),
+        Value::Bool(false) => Some("false"),
+        Value::Number(_) => Some("number"),
+        Value::String(_) => Some("string"),
+        Value::Array(_) => Some("array"),
+        Value::Object(_) => Some("object"),
+    }
+}
+
+fn type_description(instance_type: &str) -> &str {
+    match instance_type {
+        "object" => "an object",
+        "array" => "an array",
+        "string" => "a string",
+        "boolean" => "a boolean",
+        "number" => "a number",
+        "integer" => "an integer",
+        "null" => "null",
+        instance_type => instance_type,
+    }
+}
+
+fn node_description(kind: &str) -> &str {
+    match kind {
+        "true" | "false" => "a boolean",
+        kind => type_description(kind),
+    }
+}
+
+/// The candidate that's closest to a misspelled key or value, if any is close enough.
+fn closest_match<'b>(value: &str, candidates: impl Iterator<Item = &'b str>) -> Option<&'b str> {
+    let value = value.to_lowercase();
+    let max_distance = (value.chars().count() / 3).max(1);
+    candidates
+        .map(|candidate| {
+            (
+                strsim::levenshtein(&value, &candidate.to_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "tab_size": { "type": "integer", "minimum": 1.0 },
+                "format_on_save": { "type": "boolean" },
+                "theme": { "type": "string" },
+                "file_scan_exclusions": { "type": "array", "items": { "type": "string" } },
+                "soft_wrap": { "$ref": "#/definitions/SoftWrap" },
+                "languages": {
+                    "type": "object",
+                    "additionalProperties": { "$ref": "#/definitions/LanguageSettings" }
+                },
+                "lsp": { "type": "object" }
+            },
+            "definitions": {
+                "SoftWrap": {
+                    "oneOf": [
+                        { "type": "string", "enum": ["none"] },
+                        { "type": "string", "enum": ["editor_width"] }
+                    ]
+                },
+                "LanguageSettings": {
+                    "description": "The settings of a language.",
+                    "type": "object",
+                    "properties": {
+                        "tab_size": {
+                            "description": "How many columns a tab occupies.",
+                            "type": "integer"
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    fn problems(text: &str) -> Vec<(&str, String, Option<String>)> {
+        validate_settings_text(text, &schema())
+            .into_iter()
+            .map(|problem| {
+                (
+                    &text[problem.range],
+                    problem.message,
+                    problem.fix.map(|fix| fix.replacement),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_settings_text() {
+        assert_eq!(
+            problems(
+                r#"{
+                    // Comments are fine.
+                    "tab_size": 4,
+                    "theme": "One Dark",
+                    "soft_wrap": "editor_width",
+                    "format_on_save": null,
+                    "lsp": { "rust-analyzer": {} },
+                    "languages": { "Rust": { "tab_size": 2 } }
+                }"#
+            ),
+            vec![]
+        );
+
+        assert_eq!(
+            problems(
+                r#"{
+                    "tab_sise": 4,
+                    "unrelated": true,
+                    "format_on_save": "true",
+                    "tab_size": "2",
+                    "theme": 1,
+                    "file_scan_exclusions": "**/.git",
+                    "soft_wrap": "editor_widht",
+                    "languages": { "Rust": { "tab_size": 0.5 } }
+                }"#
+            ),
+            vec![
+                (
+                    r#""tab_sise""#,
+                    "Unknown setting \"tab_sise\"".to_string(),
+                    Some(r#""tab_size""#.to_string())
+                ),
+                (
+                    r#""unrelated""#,
+                    "Unknown setting \"unrelated\"".to_string(),
+                    None
+                ),
+                (
+                    r#""true""#,
+                    "Expected a boolean, found a string".to_string(),
+                    Some("true".to_string())
+                ),
+                (
+                    r#""2""#,
+                    "Expected an integer, found a string".to_string(),
+                    Some("2".to_string())
+                ),
+                (
+                    "1",
+                    "Expected a string, found a number".to_string(),
+                    Some(r#""1""#.to_string())
+                ),
+                (
+                    r#""**/.git""#,
+                    "Expected an array, found a string".to_string(),
+                    Some(r#"["**/.git"]"#.to_string())
+                ),
+                (
+                    r#""editor_widht""#,
+                    "Unknown value \"editor_widht\", expected one of \"none\", \"editor_width\""
+                        .to_string(),
+                    Some(r#""editor_width""#.to_string())
+                ),
+                (
+                    "0.5",
+                    "Expected an integer, found a number".to_string(),
+                    None
+                ),
+            ]
+        );
+
+        assert_eq!(
+            problems(r#"{ "tab_size": 0 }"#),
+            vec![("0", "Must be at least 1".to_string(), None)]
+        );
+    }
+
+    #[test]
+    fn test_key_path_at_offset() {
+        let text = r#"{ "languages": { "Rust": { "tab_size": 2 } } }"#;
+        let offset = text.find("tab_size").unwrap();
+        let (key_path, range) = key_path_at_offset(text, offset).unwrap();
+        assert_eq!(key_path, vec!["languages", "Rust", "tab_size"]);
+        assert_eq!(&text[range], r#""tab_size""#);
+        assert_eq!(key_path_at_offset(text, text.find('2').unwrap()), None);
+
+        assert_eq!(
+            setting_description(&schema(), &key_path).as_deref(),
+            Some("How many columns a tab occupies.")
+        );
+        assert_eq!(
+            setting_description(&schema(), &key_path[..2]).as_deref(),
+            Some("The settings of a language.")
+        );
+    }
+}
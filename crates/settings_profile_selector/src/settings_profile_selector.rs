@@ -0,0 +1,274 @@
+//! Switches between the settings profiles defined in the user settings, whose
+//! settings apply over the user settings while they're active.
+use db::kvp::KEY_VALUE_STORE;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusableView, Render, Task, UpdateGlobal,
+    View, ViewContext, VisualContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use settings::SettingsStore;
+use std::sync::Arc;
+use ui::{prelude::*, v_flex, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ui::HighlightedLabel, ModalView, Workspace};
+
+actions!(settings_profile_selector, [Toggle]);
+
+const ACTIVE_PROFILE_KEY: &str = "active_settings_profile";
+
+pub fn init(cx: &mut AppContext) {
+    // The active profile is kept per machine, rather than in the settings file.
+    if let Some(profile) = KEY_VALUE_STORE
+        .read_kvp(ACTIVE_PROFILE_KEY)
+        .log_err()
+        .flatten()
+    {
+        set_active_profile(Some(profile), cx);
+    }
+
+    cx.observe_new_views(
+        |workspace: &mut Workspace, _cx: &mut ViewContext<Workspace>| {
+            workspace.register_action(toggle);
+        },
+    )
+    .detach();
+}
+
+pub fn toggle(workspace: &mut Workspace, _: &Toggle, cx: &mut ViewContext<Workspace>) {
+    workspace.toggle_modal(cx, |cx| {
+        let delegate = SettingsProfileSelectorDelegate::new(cx.view().downgrade(), cx);
+        SettingsProfileSelector::new(delegate, cx)
+    });
+}
+
+fn set_active_profile(profile: Option<String>, cx: &mut AppContext) {
+    SettingsStore::update_global(cx, |store, cx| {
+        store.set_active_profile(profile, cx).log_err();
+    });
+    cx.refresh();
+}
+
+impl ModalView for SettingsProfileSelector {}
+
+pub struct SettingsProfileSelector {
+    picker: View<Picker<SettingsProfileSelectorDelegate>>,
+}
+
+impl EventEmitter<DismissEvent> for SettingsProfileSelector {}
+
+impl FocusableView for SettingsProfileSelector {
+    fn focus_handle(&self, cx: &AppContext) -> gpui::FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for SettingsProfileSelector {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl SettingsProfileSelector {
+    pub fn new(delegate: SettingsProfileSelectorDelegate, cx: &mut ViewContext<Self>) -> Self {
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+pub struct SettingsProfileSelectorDelegate {
+    /// The profiles, the first of which is `None` for using the user settings alone.
+    profiles: Vec<Option<String>>,
+    matches: Vec<StringMatch>,
+    original_profile: Option<String>,
+    selection_completed: bool,
+    selected_index: usize,
+    view: WeakView<SettingsProfileSelector>,
+}
+
+impl SettingsProfileSelectorDelegate {
+    fn new(
+        weak_view: WeakView<SettingsProfileSelector>,
+        cx: &mut ViewContext<SettingsProfileSelector>,
+    ) -> Self {
+        let store = cx.global::<SettingsStore>();
+        let original_profile = store.active_profile().map(ToString::to_string);
+        let profiles = [None]
+            .into_iter()
+            .chain(store.profile_names().into_iter().map(Some))
+            .collect::<Vec<_>>();
+        let matches = profiles
+            .iter()
+            .enumerate()
+            .map(|(candidate_id, profile)| StringMatch {
+                candidate_id,
+                score: 0.0,
+                positions: Default::default(),
+                string: profile_label(profile.as_deref()),
+            })
+            .collect();
+        let selected_index = profiles
+            .iter()
+            .position(|profile| *profile == original_profile)
+            .unwrap_or(0);
+        Self {
+            profiles,
+            matches,
+            original_profile,
+            selection_completed: false,
+            selected_index,
+            view: weak_view,
+        }
+    }
+
+    fn selected_profile(&self) -> Option<Option<String>> {
+        let mat = self.matches.get(self.selected_index)?;
+        self.profiles.get(mat.candidate_id).cloned()
+    }
+
+    fn show_selected_profile(&self, cx: &mut AppContext) {
+        if let Some(profile) = self.selected_profile() {
+            set_active_profile(profile, cx);
+        }
+    }
+}
+
+fn profile_label(profile: Option<&str>) -> String {
+    profile.map_or_else(|| "Default".to_string(), ToString::to_string)
+}
+
+impl PickerDelegate for SettingsProfileSelectorDelegate {
+    type ListItem = ui::ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Select Settings Profile...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        self.selection_completed = true;
+
+        if let Some(profile) = self.selected_profile() {
+            set_active_profile(profile.clone(), cx);
+            db::write_and_log(cx, move || async move {
+                match profile {
+                    Some(profile) => {
+                        KEY_VALUE_STORE
+                            .write_kvp(ACTIVE_PROFILE_KEY.to_string(), profile)
+                            .await
+                    }
+                    None => {
+                        KEY_VALUE_STORE
+                            .delete_kvp(ACTIVE_PROFILE_KEY.to_string())
+                            .await
+                    }
+                }
+            });
+        }
+
+        self.view
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        if !self.selection_completed {
+            set_active_profile(self.original_profile.clone(), cx);
+            self.selection_completed = true;
+        }
+
+        self.view
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+        self.show_selected_profile(cx);
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .profiles
+            .iter()
+            .enumerate()
+            .map(|(id, profile)| {
+                let label = profile_label(profile.as_deref());
+                StringMatchCandidate {
+                    id,
+                    char_bag: label.as_str().into(),
+                    string: label,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = this
+                    .delegate
+                    .selected_index
+                    .min(this.delegate.matches.len().saturating_sub(1));
+                this.delegate.show_selected_profile(cx);
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let profile_match = &self.matches[ix];
+        let is_active = self
+            .profiles
+            .get(profile_match.candidate_id)
+            .map_or(false, |profile| *profile == self.original_profile);
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(
+                    profile_match.string.clone(),
+                    profile_match.positions.clone(),
+                ))
+                .when(is_active, |this| {
+                    this.end_slot(Label::new("Active").color(Color::Muted))
+                }),
+        )
+    }
+}
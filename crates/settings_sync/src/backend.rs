@@ -0,0 +1,144 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{anyhow, Context as _, Result};
+use async_trait::async_trait;
+use client::{proto, Client};
+use fs::Fs;
+
+/// Where the synced files are stored between machines.
+#[async_trait]
+pub trait SyncBackend: Send + Sync {
+    /// Returns the snapshot of the synced files, or `None` if nothing was synced yet.
+    async fn pull(&self) -> Result<Option<String>>;
+    /// Replaces the snapshot of the synced files.
+    async fn push(&self, snapshot: String) -> Result<()>;
+}
+
+/// Syncs the files with the user's Zed account.
+pub struct AccountBackend {
+    client: Arc<Client>,
+}
+
+impl AccountBackend {
+    pub fn new(client: Arc<Client>) -> Self {
+        Self { client }
+    }
+
+    fn ensure_signed_in(&self) -> Result<()> {
+        if self.client.user_id().is_none() {
+            return Err(anyhow!("sign in to sync settings with your Zed account"));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SyncBackend for AccountBackend {
+    async fn pull(&self) -> Result<Option<String>> {
+        self.ensure_signed_in()?;
+        let response = self.client.request(proto::GetSyncedSettings {}).await?;
+        Ok(response.snapshot)
+    }
+
+    async fn push(&self, snapshot: String) -> Result<()> {
+        self.ensure_signed_in()?;
+        self.client
+            .request(proto::UpdateSyncedSettings { snapshot })
+            .await?;
+        Ok(())
+    }
+}
+
+/// Syncs the files with a Git repository, which is cloned in the settings sync directory
+/// and which the snapshot is committed to, using the user's Git credentials.
+pub struct GitBackend {
+    fs: Arc<dyn Fs>,
+    url: String,
+    machine: String,
+    directory: PathBuf,
+}
+
+const GIT_BRANCH: &str = "main";
+const GIT_SNAPSHOT_FILE: &str = "zed-settings.json";
+
+impl GitBackend {
+    pub fn new(fs: Arc<dyn Fs>, url: String, machine: String) -> Self {
+        Self {
+            fs,
+            url,
+            machine,
+            directory: paths::settings_sync_dir().join("repository"),
+        }
+    }
+
+    async fn git(&self, args: &[&str]) -> Result<String> {
+        let output = smol::process::Command::new("git")
+            .args(args)
+            .current_dir(&self.directory)
+            .output()
+            .await
+            .with_context(|| format!("running git {}", args.join(" ")))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Clones the repository, or fetches it if it's already cloned, returning whether
+    /// its branch has any commits.
+    async fn fetch(&self) -> Result<bool> {
+        if self.fs.is_dir(&self.directory.join(".git")).await {
+            // The repository may have been changed in the settings since it was cloned.
+            self.git(&["remote", "set-url", "origin", &self.url])
+                .await?;
+            self.git(&["fetch", "origin"]).await?;
+        } else {
+            self.fs.create_dir(&self.directory).await?;
+            self.git(&["clone", &self.url, "."]).await?;
+        }
+
+        let remote_branch = format!("origin/{GIT_BRANCH}");
+        if self
+            .git(&["rev-parse", "--verify", "--quiet", &remote_branch])
+            .await
+            .is_err()
+        {
+            return Ok(false);
+        }
+        self.git(&["checkout", "-B", GIT_BRANCH, &remote_branch])
+            .await?;
+        self.git(&["reset", "--hard", &remote_branch]).await?;
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl SyncBackend for GitBackend {
+    async fn pull(&self) -> Result<Option<String>> {
+        if !self.fetch().await? {
+            return Ok(None);
+        }
+        let path = self.directory.join(GIT_SNAPSHOT_FILE);
+        if !self.fs.is_file(&path).await {
+            return Ok(None);
+        }
+        Ok(Some(self.fs.load(&path).await?))
+    }
+
+    async fn push(&self, snapshot: String) -> Result<()> {
+        self.fs
+            .atomic_write(self.directory.join(GIT_SNAPSHOT_FILE), snapshot)
+            .await?;
+        self.git(&["add", GIT_SNAPSHOT_FILE]).await?;
+        let message = format!("Sync settings from {}", self.machine);
+        self.git(&["commit", "--quiet", "--message", &message])
+            .await?;
+        self.git(&["push", "origin", &format!("HEAD:{GIT_BRANCH}")])
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use editor::Editor;
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render, View, ViewContext,
+};
+use ui::prelude::*;
+use workspace::ModalView;
+
+use crate::SettingsSync;
+
+/// Asks for the passphrase the secrets of the synced files are encrypted with, which
+/// must be the same on every synced machine.
+pub struct PassphrasePrompt {
+    passphrase_editor: View<Editor>,
+}
+
+impl PassphrasePrompt {
+    pub fn new(cx: &mut ViewContext<Self>) -> Self {
+        let passphrase_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_masked(true, cx);
+            editor.set_placeholder_text("Passphrase", cx);
+            editor
+        });
+        Self { passphrase_editor }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let passphrase = self.passphrase_editor.read(cx).text(cx);
+        if passphrase.trim().is_empty() {
+            return;
+        }
+        if let Some(settings_sync) = SettingsSync::global(cx) {
+            settings_sync.update(cx, |settings_sync, cx| {
+                settings_sync.set_passphrase(passphrase, cx)
+            });
+        }
+        cx.emit(DismissEvent);
+    }
+}
+
+impl ModalView for PassphrasePrompt {}
+
+impl EventEmitter<DismissEvent> for PassphrasePrompt {}
+
+impl FocusableView for PassphrasePrompt {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.passphrase_editor.focus_handle(cx)
+    }
+}
+
+impl Render for PassphrasePrompt {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("PassphrasePrompt")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .elevation_2(cx)
+            .w_96()
+            .p_2()
+            .gap_2()
+            .child(Label::new(
+                "Enter the passphrase that encrypts the API keys, tokens and passwords \
+                of your synced settings. Use the same passphrase on each of your machines.",
+            ))
+            .child(
+                div()
+                    .px_2()
+                    .py_1()
+                    .rounded_md()
+                    .border_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(self.passphrase_editor.clone()),
+            )
+            .child(
+                Label::new("The passphrase is kept in your system's keychain.")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+    }
+}
@@ -0,0 +1,197 @@
+use std::{num::NonZeroU32, sync::OnceLock};
+
+use anyhow::{anyhow, Context as _, Result};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use regex::{Captures, Regex};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+
+/// The prefix of the secrets encrypted before being synced.
+const ENCRYPTED_PREFIX: &str = "zed-encrypted:v1:";
+const PBKDF2_ITERATIONS: u32 = 100_000;
+const PBKDF2_SALT: &[u8] = b"zed-settings-sync";
+
+/// The string values of JSON keys that name secrets, such as `api_key` or `password`.
+fn secret_value_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r#"(?i)("[^"]*(?:api_?key|token|password|secret)[^"]*"\s*:\s*)"((?:[^"\\]|\\.)*)""#,
+        )
+        .unwrap()
+    })
+}
+
+fn encrypted_value_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r#""zed-encrypted:v1:([A-Za-z0-9+/=]*)""#).unwrap())
+}
+
+/// The key the secrets found in the synced files are encrypted with, derived from a
+/// passphrase that's the same on every synced machine.
+pub struct EncryptionKey {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl EncryptionKey {
+    pub fn from_passphrase(passphrase: &str) -> Self {
+        let mut key = [0; 32];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+            PBKDF2_SALT,
+            passphrase.as_bytes(),
+            &mut key,
+        );
+        Self {
+            key: LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &key).unwrap()),
+            rng: SystemRandom::new(),
+        }
+    }
+
+    fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let mut nonce = [0; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| anyhow!("failed to generate a nonce"))?;
+        let mut data = plaintext.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .map_err(|_| anyhow!("failed to encrypt a secret"))?;
+        let mut encrypted = nonce.to_vec();
+        encrypted.extend(data);
+        Ok(format!("{ENCRYPTED_PREFIX}{}", STANDARD.encode(encrypted)))
+    }
+
+    fn decrypt(&self, encoded: &str) -> Result<String> {
+        let mut data = STANDARD
+            .decode(encoded)
+            .context("invalid encrypted secret")?;
+        if data.len() < NONCE_LEN {
+            return Err(anyhow!("invalid encrypted secret"));
+        }
+        let mut nonce = [0; NONCE_LEN];
+        nonce.copy_from_slice(&data[..NONCE_LEN]);
+        let plaintext = self
+            .key
+            .open_in_place(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::empty(),
+                &mut data[NONCE_LEN..],
+            )
+            .map_err(|_| {
+                anyhow!("failed to decrypt a synced secret, is the passphrase the same everywhere?")
+            })?;
+        Ok(String::from_utf8(plaintext.to_vec())?)
+    }
+}
+
+/// Whether a synced file contains secrets that need encrypting before being synced.
+pub fn contains_secrets(content: &str) -> bool {
+    secret_value_regex()
+        .captures_iter(content)
+        .any(|captures| !captures[2].is_empty() && !captures[2].starts_with(ENCRYPTED_PREFIX))
+}
+
+/// Encrypts the values of the secrets in a synced file, leaving the rest of the file,
+/// including its comments and formatting, as it is.
+pub fn encrypt_secrets(content: &str, key: Option<&EncryptionKey>) -> Result<String> {
+    if !contains_secrets(content) {
+        return Ok(content.to_string());
+    }
+    let key = key
+        .ok_or_else(|| anyhow!("set an encryption passphrase to sync files containing secrets"))?;
+
+    let mut error = None;
+    let encrypted = secret_value_regex().replace_all(content, |captures: &Captures| {
+        let value = &captures[2];
+        if value.is_empty() || value.starts_with(ENCRYPTED_PREFIX) {
+            return captures[0].to_string();
+        }
+        // The value is encrypted as it's escaped in the file, so that it's restored as is.
+        match key.encrypt(value) {
+            Ok(value) => format!("{}\"{value}\"", &captures[1]),
+            Err(err) => {
+                error.get_or_insert(err);
+                captures[0].to_string()
+            }
+        }
+    });
+    match error {
+        Some(error) => Err(error),
+        None => Ok(encrypted.into_owned()),
+    }
+}
+
+/// Decrypts the secrets of a synced file.
+pub fn decrypt_secrets(content: &str, key: Option<&EncryptionKey>) -> Result<String> {
+    if !encrypted_value_regex().is_match(content) {
+        return Ok(content.to_string());
+    }
+    let key = key.ok_or_else(|| {
+        anyhow!("set the encryption passphrase used on your other machines to sync secrets")
+    })?;
+
+    let mut error = None;
+    let decrypted = encrypted_value_regex().replace_all(content, |captures: &Captures| {
+        match key.decrypt(&captures[1]) {
+            Ok(value) => format!("\"{value}\""),
+            Err(err) => {
+                error.get_or_insert(err);
+                captures[0].to_string()
+            }
+        }
+    });
+    match error {
+        Some(error) => Err(error),
+        None => Ok(decrypted.into_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_secrets() {
+        let settings = r#"{
+            // The theme is synced as is.
+            "theme": "One Dark",
+            "language_models": {
+                "openai": { "api_key": "sk-\"quoted\"", "api_url": "https://api.openai.com" }
+            },
+            "proxy_password": "hunter2",
+            "empty_token": ""
+        }"#;
+        assert!(contains_secrets(settings));
+        assert!(encrypt_secrets(settings, None).is_err());
+
+        let key = EncryptionKey::from_passphrase("correct horse battery staple");
+        let encrypted = encrypt_secrets(settings, Some(&key)).unwrap();
+        assert!(!contains_secrets(&encrypted));
+        assert!(!encrypted.contains("hunter2"));
+        assert!(!encrypted.contains("quoted"));
+        assert!(encrypted.contains("// The theme is synced as is."));
+        assert!(encrypted.contains(r#""api_url": "https://api.openai.com""#));
+        assert!(encrypted.contains(r#""empty_token": """#));
+
+        assert_eq!(decrypt_secrets(&encrypted, Some(&key)).unwrap(), settings);
+        assert!(decrypt_secrets(&encrypted, None).is_err());
+        let other_key = EncryptionKey::from_passphrase("another passphrase");
+        assert!(decrypt_secrets(&encrypted, Some(&other_key)).is_err());
+
+        let without_secrets = r#"{ "theme": "One Dark" }"#;
+        assert_eq!(
+            encrypt_secrets(without_secrets, None).unwrap(),
+            without_secrets
+        );
+        assert_eq!(
+            decrypt_secrets(without_secrets, None).unwrap(),
+            without_secrets
+        );
+    }
+}
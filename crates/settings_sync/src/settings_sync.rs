@@ -0,0 +1,592 @@
+//! Syncs the user settings, keymap, snippets and installed extensions between machines,
+//! through a Git repository or the user's Zed account.
+mod backend;
+mod passphrase_prompt;
+mod secrets;
+mod snapshot;
+mod sync_status_indicator;
+
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Local};
+use client::Client;
+use collections::{BTreeMap, BTreeSet};
+use extension::ExtensionStore;
+use fs::Fs;
+use futures::StreamExt as _;
+use gpui::{
+    actions, AppContext, Context, Global, Model, ModelContext, SharedString, Task, ViewContext,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources, SettingsStore};
+use util::ResultExt;
+use workspace::Workspace;
+
+pub use backend::{AccountBackend, GitBackend, SyncBackend};
+pub use passphrase_prompt::PassphrasePrompt;
+pub use secrets::{decrypt_secrets, encrypt_secrets, EncryptionKey};
+pub use snapshot::*;
+pub use sync_status_indicator::SyncStatusIndicator;
+
+actions!(settings_sync, [SyncNow, SetEncryptionPassphrase]);
+
+/// The URL the encryption passphrase is stored under in the credentials provider.
+const PASSPHRASE_CREDENTIALS_URL: &str = "zed://settings-sync";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncBackendKind {
+    /// Sync with your Zed account.
+    #[default]
+    Account,
+    /// Sync with a private Git repository.
+    Git,
+}
+
+/// The kinds of files that are synced.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct SyncCategories {
+    pub settings: bool,
+    pub keymap: bool,
+    pub snippets: bool,
+    pub extensions: bool,
+}
+
+impl Default for SyncCategories {
+    fn default() -> Self {
+        Self {
+            settings: true,
+            keymap: true,
+            snippets: true,
+            extensions: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettingsSyncSettings {
+    pub enabled: bool,
+    pub backend: SyncBackendKind,
+    pub git_repository: Option<String>,
+    pub categories: SyncCategories,
+    pub interval_minutes: u64,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SettingsSyncSettingsContent {
+    /// Whether to sync the settings between machines.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// Where the synced files are stored.
+    ///
+    /// Default: account
+    pub backend: Option<SyncBackendKind>,
+    /// The URL of the private Git repository used by the `git` backend, which is
+    /// cloned and pushed to with your Git credentials.
+    ///
+    /// Default: null
+    pub git_repository: Option<String>,
+    /// Which files are synced.
+    ///
+    /// Default: {"settings": true, "keymap": true, "snippets": true, "extensions": true}
+    pub categories: Option<SyncCategories>,
+    /// How often to sync, in minutes.
+    ///
+    /// Default: 15
+    pub interval_minutes: Option<u64>,
+}
+
+impl Settings for SettingsSyncSettings {
+    const KEY: Option<&'static str> = Some("settings_sync");
+
+    type FileContent = SettingsSyncSettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        // Sync is configured per user, not per project.
+        let content: SettingsSyncSettingsContent =
+            SettingsSources::<Self::FileContent>::json_merge_with(
+                [sources.default]
+                    .into_iter()
+                    .chain(sources.user_customizations()),
+            )?;
+        Ok(Self {
+            enabled: content.enabled.unwrap_or(false),
+            backend: content.backend.unwrap_or_default(),
+            git_repository: content.git_repository.filter(|url| !url.trim().is_empty()),
+            categories: content.categories.unwrap_or_default(),
+            interval_minutes: content.interval_minutes.unwrap_or(15).max(1),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyncStatus {
+    Disabled,
+    Syncing,
+    Synced {
+        at: DateTime<Local>,
+        /// How many files changed on both sides, whose older version was replaced.
+        conflicts: usize,
+    },
+    Failed(SharedString),
+}
+
+struct GlobalSettingsSync(Model<SettingsSync>);
+
+impl Global for GlobalSettingsSync {}
+
+pub fn init(fs: Arc<dyn Fs>, client: Arc<Client>, cx: &mut AppContext) {
+    SettingsSyncSettings::register(cx);
+    let settings_sync = cx.new_model(|cx| SettingsSync::new(fs, client, cx));
+    cx.set_global(GlobalSettingsSync(settings_sync));
+
+    cx.observe_new_views(
+        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+            workspace.register_action(|_, _: &SyncNow, cx| {
+                if let Some(settings_sync) = SettingsSync::global(cx) {
+                    settings_sync.update(cx, |settings_sync, cx| settings_sync.sync(cx));
+                }
+            });
+            workspace.register_action(|workspace, _: &SetEncryptionPassphrase, cx| {
+                workspace.toggle_modal(cx, PassphrasePrompt::new);
+            });
+        },
+    )
+    .detach();
+}
+
+pub struct SettingsSync {
+    fs: Arc<dyn Fs>,
+    client: Arc<Client>,
+    machine: String,
+    settings: SettingsSyncSettings,
+    encryption_key: Option<Arc<EncryptionKey>>,
+    /// Whether the passphrase was read from the credentials provider, which is only
+    /// done once sync is enabled.
+    passphrase_read: bool,
+    status: SyncStatus,
+    sync_task: Option<Task<()>>,
+    _periodic_sync: Task<()>,
+    _settings_subscription: gpui::Subscription,
+}
+
+impl SettingsSync {
+    pub fn global(cx: &AppContext) -> Option<Model<Self>> {
+        cx.try_global::<GlobalSettingsSync>()
+            .map(|settings_sync| settings_sync.0.clone())
+    }
+
+    fn new(fs: Arc<dyn Fs>, client: Arc<Client>, cx: &mut ModelContext<Self>) -> Self {
+        let settings = SettingsSyncSettings::get_global(cx).clone();
+        let settings_subscription = cx.observe_global::<SettingsStore>(|this, cx| {
+            let settings = SettingsSyncSettings::get_global(cx).clone();
+            if settings != this.settings {
+                this.settings = settings;
+                this.sync(cx);
+            }
+        });
+
+        let periodic_sync = cx.spawn(|this, mut cx| async move {
+            if this.update(&mut cx, |this, cx| this.sync(cx)).is_err() {
+                return;
+            }
+            loop {
+                let Ok(interval_minutes) =
+                    this.update(&mut cx, |this, _| this.settings.interval_minutes)
+                else {
+                    break;
+                };
+                cx.background_executor()
+                    .timer(Duration::from_secs(interval_minutes * 60))
+                    .await;
+                if this.update(&mut cx, |this, cx| this.sync(cx)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let status = if settings.enabled {
+            SyncStatus::Syncing
+        } else {
+            SyncStatus::Disabled
+        };
+        Self {
+            fs,
+            client,
+            machine: sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string()),
+            settings,
+            encryption_key: None,
+            passphrase_read: false,
+            status,
+            sync_task: None,
+            _periodic_sync: periodic_sync,
+            _settings_subscription: settings_subscription,
+        }
+    }
+
+    pub fn status(&self) -> &SyncStatus {
+        &self.status
+    }
+
+    /// Sets the passphrase the secrets of the synced files are encrypted with, storing
+    /// it in the credentials provider, and syncs with it.
+    pub fn set_passphrase(&mut self, passphrase: String, cx: &mut ModelContext<Self>) {
+        let write_passphrase = cx.write_credentials(
+            PASSPHRASE_CREDENTIALS_URL,
+            "passphrase",
+            passphrase.as_bytes(),
+        );
+        self.encryption_key = Some(Arc::new(EncryptionKey::from_passphrase(&passphrase)));
+        self.passphrase_read = true;
+        cx.background_executor()
+            .spawn(async move { write_passphrase.await.log_err() })
+            .detach();
+        self.sync(cx);
+    }
+
+    /// Syncs the files now, unless they're already being synced.
+    pub fn sync(&mut self, cx: &mut ModelContext<Self>) {
+        if !self.settings.enabled {
+            self.status = SyncStatus::Disabled;
+            cx.notify();
+            return;
+        }
+        if self.sync_task.is_some() {
+            return;
+        }
+
+        let backend: Arc<dyn SyncBackend> = match self.settings.backend {
+            SyncBackendKind::Account => Arc::new(AccountBackend::new(self.client.clone())),
+            SyncBackendKind::Git => match self.settings.git_repository.clone() {
+                Some(url) => Arc::new(GitBackend::new(self.fs.clone(), url, self.machine.clone())),
+                None => {
+                    self.status = SyncStatus::Failed(
+                        "Set `settings_sync.git_repository` to sync with a Git repository".into(),
+                    );
+                    cx.notify();
+                    return;
+                }
+            },
+        };
+        // Dev extensions are built from a local directory, so they can't be installed elsewhere.
+        let installed_extensions = ExtensionStore::try_global(cx)
+            .filter(|_| self.settings.categories.extensions)
+            .map(|store| {
+                store
+                    .read(cx)
+                    .installed_extensions()
+                    .iter()
+                    .filter(|(_, entry)| !entry.dev)
+                    .map(|(id, _)| id.to_string())
+                    .collect()
+            });
+
+        let read_passphrase =
+            (!self.passphrase_read).then(|| cx.read_credentials(PASSPHRASE_CREDENTIALS_URL));
+        self.passphrase_read = true;
+        let mut params = SyncParams {
+            fs: self.fs.clone(),
+            backend,
+            categories: self.settings.categories.clone(),
+            machine: self.machine.clone(),
+            encryption_key: self.encryption_key.clone(),
+            installed_extensions,
+        };
+        self.status = SyncStatus::Syncing;
+        cx.notify();
+        self.sync_task = Some(cx.spawn(|this, mut cx| async move {
+            if let Some(read_passphrase) = read_passphrase {
+                let passphrase = read_passphrase
+                    .await
+                    .log_err()
+                    .flatten()
+                    .and_then(|(_, passphrase)| String::from_utf8(passphrase).log_err());
+                if let Some(passphrase) = passphrase {
+                    let encryption_key = Arc::new(EncryptionKey::from_passphrase(&passphrase));
+                    params.encryption_key = Some(encryption_key.clone());
+                    this.update(&mut cx, |this, _| {
+                        this.encryption_key.get_or_insert(encryption_key);
+                    })
+                    .ok();
+                }
+            }
+
+            let result = sync_files(params).await;
+            this.update(&mut cx, |this, cx| {
+                this.sync_task = None;
+                this.status = match result {
+                    Ok(outcome) => {
+                        if let Some(store) = ExtensionStore::try_global(cx) {
+                            store.update(cx, |store, cx| {
+                                for extension_id in outcome.extensions_to_install {
+                                    store.install_latest_extension(extension_id.into(), cx);
+                                }
+                            });
+                        }
+                        SyncStatus::Synced {
+                            at: Local::now(),
+                            conflicts: outcome.conflicts,
+                        }
+                    }
+                    Err(error) => {
+                        log::error!("failed to sync settings: {error:#}");
+                        SyncStatus::Failed(format!("{error:#}").into())
+                    }
+                };
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+}
+
+struct SyncParams {
+    fs: Arc<dyn Fs>,
+    backend: Arc<dyn SyncBackend>,
+    categories: SyncCategories,
+    machine: String,
+    encryption_key: Option<Arc<EncryptionKey>>,
+    /// The identifiers of the installed extensions, if they're synced.
+    installed_extensions: Option<Vec<String>>,
+}
+
+#[derive(Default)]
+struct SyncOutcome {
+    extensions_to_install: Vec<String>,
+    conflicts: usize,
+}
+
+/// Syncs the local files with the backend, pushing the ones changed on this machine and
+/// writing the ones changed elsewhere.
+async fn sync_files(params: SyncParams) -> Result<SyncOutcome> {
+    let SyncParams {
+        fs,
+        backend,
+        categories,
+        machine,
+        encryption_key,
+        installed_extensions,
+    } = params;
+    let encryption_key = encryption_key.as_deref();
+    let state_path = paths::settings_sync_dir().join("state.json");
+    let state = match fs.load(&state_path).await {
+        Ok(state) => serde_json::from_str::<SyncState>(&state)
+            .log_err()
+            .unwrap_or_default(),
+        Err(_) => SyncState::default(),
+    };
+
+    let remote = backend
+        .pull()
+        .await?
+        .map(|snapshot| serde_json::from_str::<SyncSnapshot>(&snapshot))
+        .transpose()
+        .context("parsing the synced settings")?
+        .unwrap_or_default();
+    let local = read_local_items(fs.as_ref(), &categories).await?;
+
+    let mut outcome = SyncOutcome::default();
+    let mut new_remote = remote.clone();
+    let mut new_state = state.clone();
+    let mut changed_remote = false;
+
+    let keys = local
+        .keys()
+        .chain(remote.items.keys())
+        .filter(|key| key.as_str() != EXTENSIONS_ITEM && is_item_synced(key, &categories))
+        .cloned()
+        .collect::<BTreeSet<_>>();
+    for key in keys {
+        let local_item = local.get(&key);
+        let remote_item = remote
+            .items
+            .get(&key)
+            .map(|item| {
+                anyhow::Ok(SyncedItem {
+                    content: decrypt_secrets(&item.content, encryption_key)?,
+                    ..item.clone()
+                })
+            })
+            .transpose()
+            .with_context(|| format!("syncing {key}"))?;
+        let resolution = resolve(local_item, remote_item.as_ref(), state.items.get(&key));
+        match (resolution, local_item, remote_item) {
+            (Resolution::Push { conflict }, Some(local_item), remote_item) => {
+                if let Some(remote_item) = remote_item.filter(|_| conflict) {
+                    back_up_conflict(fs.as_ref(), &key, &remote_item.content).await?;
+                    outcome.conflicts += 1;
+                }
+                let content = encrypt_secrets(&local_item.content, encryption_key)
+                    .with_context(|| format!("syncing {key}"))?;
+                new_remote.items.insert(
+                    key.clone(),
+                    SyncedItem {
+                        content,
+                        modified_at: local_item.modified_at,
+                        machine: machine.clone(),
+                    },
+                );
+                new_state.items.insert(
+                    key,
+                    SyncedItemState {
+                        content_hash: content_hash(&local_item.content),
+                        remote_modified_at: local_item.modified_at,
+                    },
+                );
+                changed_remote = true;
+            }
+            (Resolution::Pull { conflict }, local_item, Some(remote_item)) => {
+                if let Some(local_item) = local_item.filter(|_| conflict) {
+                    back_up_conflict(fs.as_ref(), &key, &local_item.content).await?;
+                    outcome.conflicts += 1;
+                }
+                let Some(path) = local_path(&key) else {
+                    continue;
+                };
+                if let Some(parent) = path.parent() {
+                    fs.create_dir(parent).await?;
+                }
+                fs.atomic_write(path, remote_item.content.clone()).await?;
+                new_state.items.insert(
+                    key,
+                    SyncedItemState {
+                        content_hash: content_hash(&remote_item.content),
+                        remote_modified_at: remote_item.modified_at,
+                    },
+                );
+            }
+            (_, Some(local_item), Some(remote_item)) => {
+                new_state.items.insert(
+                    key,
+                    SyncedItemState {
+                        content_hash: content_hash(&local_item.content),
+                        remote_modified_at: remote_item.modified_at,
+                    },
+                );
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(installed_extensions) = installed_extensions {
+        let synced_extensions = remote
+            .items
+            .get(EXTENSIONS_ITEM)
+            .and_then(|item| serde_json::from_str::<Vec<String>>(&item.content).log_err())
+            .unwrap_or_default();
+        let extensions = merge_extensions(&installed_extensions, &synced_extensions);
+        outcome.extensions_to_install = extensions
+            .iter()
+            .filter(|extension| !installed_extensions.contains(extension))
+            .cloned()
+            .collect();
+        if extensions != synced_extensions {
+            new_remote.items.insert(
+                EXTENSIONS_ITEM.to_string(),
+                SyncedItem {
+                    content: serde_json::to_string(&extensions)?,
+                    modified_at: timestamp(SystemTime::now()),
+                    machine: machine.clone(),
+                },
+            );
+            changed_remote = true;
+        }
+    }
+
+    if changed_remote {
+        backend
+            .push(serde_json::to_string_pretty(&new_remote)?)
+            .await
+            .context("pushing the synced settings")?;
+    }
+    if new_state != state {
+        fs.create_dir(paths::settings_sync_dir()).await?;
+        fs.atomic_write(state_path, serde_json::to_string_pretty(&new_state)?)
+            .await?;
+    }
+    Ok(outcome)
+}
+
+/// Reads the files of the synced categories.
+async fn read_local_items(
+    fs: &dyn Fs,
+    categories: &SyncCategories,
+) -> Result<BTreeMap<String, LocalItem>> {
+    let mut paths = Vec::new();
+    if categories.settings {
+        paths.push((SETTINGS_ITEM.to_string(), paths::settings_file().clone()));
+    }
+    if categories.keymap {
+        paths.push((KEYMAP_ITEM.to_string(), paths::keymap_file().clone()));
+    }
+    if categories.snippets && fs.is_dir(paths::snippets_dir()).await {
+        let mut entries = fs.read_dir(paths::snippets_dir()).await?;
+        while let Some(path) = entries.next().await {
+            let path = path?;
+            let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let key = format!("{SNIPPETS_PREFIX}{file_name}");
+            if is_snippet_item(&key) {
+                paths.push((key, path));
+            }
+        }
+    }
+
+    let mut items = BTreeMap::default();
+    for (key, path) in paths {
+        let Some(metadata) = fs.metadata(&path).await? else {
+            continue;
+        };
+        if metadata.is_dir {
+            continue;
+        }
+        let content = fs.load(&path).await?;
+        items.insert(
+            key,
+            LocalItem {
+                content,
+                modified_at: timestamp(metadata.mtime),
+            },
+        );
+    }
+    Ok(items)
+}
+
+fn local_path(key: &str) -> Option<PathBuf> {
+    match key {
+        SETTINGS_ITEM => Some(paths::settings_file().clone()),
+        KEYMAP_ITEM => Some(paths::keymap_file().clone()),
+        key if is_snippet_item(key) => key
+            .strip_prefix(SNIPPETS_PREFIX)
+            .map(|file_name| paths::snippets_dir().join(file_name)),
+        _ => None,
+    }
+}
+
+/// Keeps the version of a file that was replaced because the other side changed it
+/// more recently.
+async fn back_up_conflict(fs: &dyn Fs, key: &str, content: &str) -> Result<()> {
+    let directory = paths::settings_sync_dir().join("conflicts");
+    fs.create_dir(&directory).await?;
+    let file_name = format!(
+        "{}-{}",
+        Local::now().format("%Y%m%d-%H%M%S"),
+        key.replace('/', "-")
+    );
+    fs.atomic_write(directory.join(file_name), content.to_string())
+        .await
+}
+
+fn timestamp(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as i64)
+}
@@ -0,0 +1,257 @@
+use collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::SyncCategories;
+
+/// The item holding the user settings.
+pub const SETTINGS_ITEM: &str = "settings.json";
+/// The item holding the user keymap.
+pub const KEYMAP_ITEM: &str = "keymap.json";
+/// The item holding the identifiers of the installed extensions.
+pub const EXTENSIONS_ITEM: &str = "extensions.json";
+/// The prefix of the items holding the snippet files.
+pub const SNIPPETS_PREFIX: &str = "snippets/";
+
+/// The synced files, as stored by a sync backend.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncSnapshot {
+    pub items: BTreeMap<String, SyncedItem>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SyncedItem {
+    pub content: String,
+    /// When the content was changed, in milliseconds since the Unix epoch.
+    pub modified_at: i64,
+    /// The host name of the machine the content was synced from.
+    pub machine: String,
+}
+
+/// A synced file, as found on this machine.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocalItem {
+    pub content: String,
+    /// When the file was modified, in milliseconds since the Unix epoch.
+    pub modified_at: i64,
+}
+
+/// What was synced for an item the last time this machine synced.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncedItemState {
+    /// The hash of the item's content on this machine.
+    pub content_hash: String,
+    /// The modification time of the item in the backend.
+    pub remote_modified_at: i64,
+}
+
+/// The state of the last sync, stored on this machine to tell which side
+/// changed an item since then.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncState {
+    pub items: BTreeMap<String, SyncedItemState>,
+}
+
+/// How an item is synced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    Unchanged,
+    /// The local item replaces the one in the backend.
+    Push {
+        /// Whether the item in the backend changed too, and is replaced because it's older.
+        conflict: bool,
+    },
+    /// The item in the backend replaces the local one.
+    Pull {
+        /// Whether the local item changed too, and is replaced because it's older.
+        conflict: bool,
+    },
+}
+
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// Decides how to sync an item, from its local content, its decrypted content in the
+/// backend, and what was synced last time. When both sides changed, the most recently
+/// modified one wins.
+pub fn resolve(
+    local: Option<&LocalItem>,
+    remote: Option<&SyncedItem>,
+    last: Option<&SyncedItemState>,
+) -> Resolution {
+    let local_changed = local.map_or(false, |local| {
+        last.map_or(true, |last| {
+            last.content_hash != content_hash(&local.content)
+        })
+    });
+    let remote_changed = remote.map_or(false, |remote| {
+        last.map_or(true, |last| last.remote_modified_at != remote.modified_at)
+    });
+
+    match (local, remote) {
+        (Some(local), Some(remote)) if local_changed && remote_changed => {
+            if local.content == remote.content {
+                Resolution::Unchanged
+            } else if local.modified_at >= remote.modified_at {
+                Resolution::Push { conflict: true }
+            } else {
+                Resolution::Pull { conflict: true }
+            }
+        }
+        _ if local_changed => Resolution::Push { conflict: false },
+        _ if remote_changed => Resolution::Pull { conflict: false },
+        _ => Resolution::Unchanged,
+    }
+}
+
+/// Whether an item belongs to one of the synced categories.
+pub fn is_item_synced(key: &str, categories: &SyncCategories) -> bool {
+    match key {
+        SETTINGS_ITEM => categories.settings,
+        KEYMAP_ITEM => categories.keymap,
+        EXTENSIONS_ITEM => categories.extensions,
+        key => categories.snippets && is_snippet_item(key),
+    }
+}
+
+/// Whether an item is a snippet file, which, unlike a path, is a single file name.
+pub fn is_snippet_item(key: &str) -> bool {
+    key.strip_prefix(SNIPPETS_PREFIX)
+        .map_or(false, |file_name| {
+            !file_name.is_empty()
+                && !file_name.starts_with('.')
+                && !file_name.contains(['/', '\\'])
+                && file_name.ends_with(".json")
+        })
+}
+
+/// Merges the extensions installed on this machine with the synced ones. Extensions
+/// are only ever added, so that uninstalling one on a machine doesn't uninstall it
+/// everywhere.
+pub fn merge_extensions(local: &[String], remote: &[String]) -> Vec<String> {
+    let mut extensions = local.iter().chain(remote).cloned().collect::<Vec<_>>();
+    extensions.sort();
+    extensions.dedup();
+    extensions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(content: &str, modified_at: i64) -> LocalItem {
+        LocalItem {
+            content: content.into(),
+            modified_at,
+        }
+    }
+
+    fn remote(content: &str, modified_at: i64) -> SyncedItem {
+        SyncedItem {
+            content: content.into(),
+            modified_at,
+            machine: "other".into(),
+        }
+    }
+
+    fn last(content: &str, remote_modified_at: i64) -> SyncedItemState {
+        SyncedItemState {
+            content_hash: content_hash(content),
+            remote_modified_at,
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        // Only one side changed.
+        assert_eq!(
+            resolve(
+                Some(&local("a", 1)),
+                Some(&remote("a", 1)),
+                Some(&last("a", 1))
+            ),
+            Resolution::Unchanged
+        );
+        assert_eq!(
+            resolve(
+                Some(&local("b", 2)),
+                Some(&remote("a", 1)),
+                Some(&last("a", 1))
+            ),
+            Resolution::Push { conflict: false }
+        );
+        assert_eq!(
+            resolve(
+                Some(&local("a", 1)),
+                Some(&remote("b", 2)),
+                Some(&last("a", 1))
+            ),
+            Resolution::Pull { conflict: false }
+        );
+
+        // Items only found on one side.
+        assert_eq!(
+            resolve(Some(&local("a", 1)), None, None),
+            Resolution::Push { conflict: false }
+        );
+        assert_eq!(
+            resolve(None, Some(&remote("a", 1)), None),
+            Resolution::Pull { conflict: false }
+        );
+        assert_eq!(
+            resolve(None, Some(&remote("a", 1)), Some(&last("a", 1))),
+            Resolution::Unchanged
+        );
+
+        // Both sides changed, and the most recent change wins.
+        assert_eq!(
+            resolve(
+                Some(&local("b", 3)),
+                Some(&remote("c", 2)),
+                Some(&last("a", 1))
+            ),
+            Resolution::Push { conflict: true }
+        );
+        assert_eq!(
+            resolve(
+                Some(&local("b", 2)),
+                Some(&remote("c", 3)),
+                Some(&last("a", 1))
+            ),
+            Resolution::Pull { conflict: true }
+        );
+        assert_eq!(
+            resolve(Some(&local("b", 2)), Some(&remote("b", 3)), None),
+            Resolution::Unchanged
+        );
+    }
+
+    #[test]
+    fn test_synced_items() {
+        let categories = SyncCategories {
+            settings: true,
+            keymap: false,
+            snippets: true,
+            extensions: false,
+        };
+        assert!(is_item_synced(SETTINGS_ITEM, &categories));
+        assert!(!is_item_synced(KEYMAP_ITEM, &categories));
+        assert!(!is_item_synced(EXTENSIONS_ITEM, &categories));
+        assert!(is_item_synced("snippets/rust.json", &categories));
+        assert!(!is_item_synced("snippets/../settings.json", &categories));
+        assert!(!is_item_synced("snippets/.hidden.json", &categories));
+        assert!(!is_item_synced("themes/one.json", &categories));
+    }
+
+    #[test]
+    fn test_merge_extensions() {
+        assert_eq!(
+            merge_extensions(
+                &["html".into(), "zig".into()],
+                &["toml".into(), "html".into()]
+            ),
+            vec!["html".to_string(), "toml".into(), "zig".into()]
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use gpui::{Render, Subscription, ViewContext};
+use ui::{prelude::*, ButtonLike, Tooltip};
+use workspace::{item::ItemHandle, StatusItemView};
+
+use crate::{SettingsSync, SyncNow, SyncStatus};
+
+/// Shows the status of settings sync, and syncs when clicked.
+pub struct SyncStatusIndicator {
+    _subscription: Option<Subscription>,
+}
+
+impl SyncStatusIndicator {
+    pub fn new(cx: &mut ViewContext<Self>) -> Self {
+        let subscription = SettingsSync::global(cx)
+            .map(|settings_sync| cx.observe(&settings_sync, |_, _, cx| cx.notify()));
+        Self {
+            _subscription: subscription,
+        }
+    }
+}
+
+impl Render for SyncStatusIndicator {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let Some(status) =
+            SettingsSync::global(cx).map(|settings_sync| settings_sync.read(cx).status().clone())
+        else {
+            return div().into_any_element();
+        };
+
+        let (icon, color, tooltip) = match status {
+            SyncStatus::Disabled => return div().into_any_element(),
+            SyncStatus::Syncing => (
+                IconName::ArrowCircle,
+                Color::Muted,
+                "Syncing settings…".to_string(),
+            ),
+            SyncStatus::Synced { at, conflicts: 0 } => (
+                IconName::Check,
+                Color::Muted,
+                format!("Settings synced at {}", at.format("%H:%M")),
+            ),
+            SyncStatus::Synced { at, conflicts } => (
+                IconName::Check,
+                Color::Warning,
+                format!(
+                    "Settings synced at {}, replacing {conflicts} file(s) changed on both \
+                    sides with their most recent version. The replaced files are kept in {}",
+                    at.format("%H:%M"),
+                    paths::settings_sync_dir().join("conflicts").display()
+                ),
+            ),
+            SyncStatus::Failed(error) => (
+                IconName::ExclamationTriangle,
+                Color::Error,
+                format!("Failed to sync settings: {error}"),
+            ),
+        };
+
+        ButtonLike::new("settings-sync-indicator")
+            .child(Icon::new(icon).size(IconSize::Small).color(color))
+            .tooltip(move |cx| {
+                Tooltip::with_meta("Sync Settings", Some(&SyncNow), tooltip.clone(), cx)
+            })
+            .on_click(|_, cx| cx.dispatch_action(Box::new(SyncNow)))
+            .into_any_element()
+    }
+}
+
+impl StatusItemView for SyncStatusIndicator {
+    fn set_active_pane_item(&mut self, _: Option<&dyn ItemHandle>, _: &mut ViewContext<Self>) {}
+}
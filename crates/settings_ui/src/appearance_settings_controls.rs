@@ -157,6 +157,14 @@ impl RenderOnce for ThemeModeControl {
                     .on_click(|_, cx| Self::write(ThemeMode::System, cx))
                     .middle(),
             )
+            .child(
+                ToggleButton::new("schedule", "Schedule")
+                    .style(ButtonStyle::Filled)
+                    .size(ButtonSize::Large)
+                    .selected(value == ThemeMode::Schedule)
+                    .on_click(|_, cx| Self::write(ThemeMode::Schedule, cx))
+                    .middle(),
+            )
             .child(
                 ToggleButton::new("dark", "Dark")
                     .style(ButtonStyle::Filled)
@@ -0,0 +1,208 @@
+use fs::Fs;
+use gpui::AppContext;
+use serde_json::Value;
+use settings::{SettingField, SettingFieldKind, SettingsStore};
+use ui::{prelude::*, Checkbox, ContextMenu, DropdownMenu, NumericStepper, Tooltip};
+use zed_actions::OpenSettings;
+
+/// Edits a setting found in the settings JSON schema, showing its default value and
+/// whether the user settings change it.
+#[derive(IntoElement)]
+pub struct SettingFieldControl {
+    field: SettingField,
+}
+
+impl SettingFieldControl {
+    pub fn new(field: SettingField) -> Self {
+        Self { field }
+    }
+
+    fn write(key_path: Vec<String>, value: Option<Value>, cx: &AppContext) {
+        let fs = <dyn Fs>::global(cx);
+        cx.global::<SettingsStore>()
+            .update_settings_file_value(fs, key_path, value);
+    }
+
+    fn render_control(
+        &self,
+        id: SharedString,
+        value: Option<&Value>,
+        cx: &mut WindowContext,
+    ) -> AnyElement {
+        let key_path = self.field.key_path.clone();
+        match &self.field.kind {
+            SettingFieldKind::Boolean => {
+                let value = value.and_then(Value::as_bool).unwrap_or(false);
+                Checkbox::new(id, value.into())
+                    .on_click(move |_, cx| {
+                        Self::write(key_path.clone(), Some(Value::Bool(!value)), cx)
+                    })
+                    .into_any_element()
+            }
+            SettingFieldKind::Enum(options) => {
+                let label = value.map(display_value).unwrap_or_default();
+                let options = options.clone();
+                DropdownMenu::new(
+                    id,
+                    label,
+                    ContextMenu::build(cx, move |mut menu, _| {
+                        for option in options {
+                            let key_path = key_path.clone();
+                            let value = Value::String(option.clone());
+                            menu = menu.entry(option, None, move |cx| {
+                                Self::write(key_path.clone(), Some(value.clone()), cx)
+                            });
+                        }
+                        menu
+                    }),
+                )
+                .into_any_element()
+            }
+            SettingFieldKind::Integer { minimum, maximum }
+            | SettingFieldKind::Number { minimum, maximum } => {
+                let is_integer = matches!(self.field.kind, SettingFieldKind::Integer { .. });
+                let value = value.and_then(Value::as_f64).unwrap_or(0.);
+                // Fractional settings, such as opacities, are stepped by tenths.
+                let step = if is_integer || maximum.map_or(true, |maximum| maximum > 1.) {
+                    1.
+                } else {
+                    0.1
+                };
+                let (minimum, maximum) = (*minimum, *maximum);
+                let write_value = move |value: f64, key_path: Vec<String>, cx: &WindowContext| {
+                    let value = minimum.map_or(value, |minimum| value.max(minimum));
+                    let value = maximum.map_or(value, |maximum| value.min(maximum));
+                    let value = if is_integer {
+                        Value::from(value.round() as i64)
+                    } else {
+                        Value::from((value * 10.).round() / 10.)
+                    };
+                    Self::write(key_path, Some(value), cx)
+                };
+                let decrement_key_path = key_path.clone();
+                NumericStepper::new(
+                    id,
+                    value.to_string(),
+                    move |_, cx| write_value(value - step, decrement_key_path.clone(), cx),
+                    move |_, cx| write_value(value + step, key_path.clone(), cx),
+                )
+                .into_any_element()
+            }
+            SettingFieldKind::String | SettingFieldKind::Other => {
+                Button::new(id, "Edit in settings.json")
+                    .style(ButtonStyle::Subtle)
+                    .label_size(LabelSize::Small)
+                    .on_click(|_, cx| cx.dispatch_action(Box::new(OpenSettings)))
+                    .into_any_element()
+            }
+        }
+    }
+}
+
+impl RenderOnce for SettingFieldControl {
+    fn render(self, cx: &mut WindowContext) -> impl IntoElement {
+        let store = cx.global::<SettingsStore>();
+        let user_value = self.field.value_in(store.raw_user_settings()).cloned();
+        let default_value = self.field.value_in(store.raw_default_settings()).cloned();
+        let value = user_value.as_ref().or(default_value.as_ref());
+
+        let key = SharedString::from(self.field.key_path.join("."));
+        let name = self
+            .field
+            .key_path
+            .last()
+            .map(|key| humanize(key))
+            .unwrap_or_default();
+        // The first paragraph of the description; the rest documents the default value.
+        let description = self
+            .field
+            .description
+            .as_deref()
+            .and_then(|description| description.split("\n\n").next())
+            .map(|description| description.replace('\n', " "));
+        let value_summary = match (&user_value, &default_value) {
+            (Some(user_value), Some(default_value)) => format!(
+                "{} in settings.json, {} by default",
+                display_value(user_value),
+                display_value(default_value)
+            ),
+            (Some(user_value), None) => format!("{} in settings.json", display_value(user_value)),
+            (None, Some(default_value)) => format!("{} by default", display_value(default_value)),
+            (None, None) => "Not set".to_string(),
+        };
+        let control = self.render_control(key.clone(), value, cx);
+
+        h_flex()
+            .id(key.clone())
+            .w_full()
+            .py_1()
+            .gap_4()
+            .justify_between()
+            .child(
+                v_flex()
+                    .flex_1()
+                    .min_w_0()
+                    .child(
+                        h_flex().gap_2().child(Label::new(name)).child(
+                            Label::new(key.clone())
+                                .size(LabelSize::XSmall)
+                                .color(Color::Muted),
+                        ),
+                    )
+                    .when_some(description, |this, description| {
+                        this.child(
+                            Label::new(description)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    })
+                    .child(Label::new(value_summary).size(LabelSize::XSmall).color(
+                        if user_value.is_some() {
+                            Color::Accent
+                        } else {
+                            Color::Muted
+                        },
+                    )),
+            )
+            .child(
+                h_flex()
+                    .flex_none()
+                    .gap_1()
+                    .child(control)
+                    .when(user_value.is_some(), |this| {
+                        let key_path = self.field.key_path.clone();
+                        this.child(
+                            IconButton::new(
+                                SharedString::from(format!("{key}-reset")),
+                                IconName::RotateCcw,
+                            )
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::text("Reset to Default", cx))
+                            .on_click(move |_, cx| Self::write(key_path.clone(), None, cx)),
+                        )
+                    }),
+            )
+    }
+}
+
+/// Turns a settings key, such as `tab_size`, into a title, such as "Tab Size".
+pub(crate) fn humanize(key: &str) -> String {
+    key.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
@@ -1,16 +1,20 @@
 mod appearance_settings_controls;
+mod setting_field_control;
 
 use std::any::TypeId;
 
+use collections::BTreeMap;
 use command_palette_hooks::CommandPaletteFilter;
-use editor::EditorSettingsControls;
-use feature_flags::{FeatureFlag, FeatureFlagViewExt};
-use gpui::{actions, AppContext, EventEmitter, FocusHandle, FocusableView, View};
-use ui::prelude::*;
+use editor::{Editor, EditorEvent, EditorSettingsControls};
+use feature_flags::{FeatureFlag, FeatureFlagAppExt as _, FeatureFlagViewExt};
+use gpui::{actions, AppContext, EventEmitter, FocusHandle, FocusableView, Subscription, View};
+use settings::{setting_fields, SettingField, SettingsJsonSchemaParams, SettingsStore};
+use ui::{prelude::*, SettingsContainer, SettingsGroup};
 use workspace::item::{Item, ItemEvent};
 use workspace::Workspace;
 
 use crate::appearance_settings_controls::AppearanceSettingsControls;
+use crate::setting_field_control::{humanize, SettingFieldControl};
 
 pub struct SettingsUiFeatureFlag;
 
@@ -61,14 +65,68 @@ pub fn init(cx: &mut AppContext) {
 
 pub struct SettingsPage {
     focus_handle: FocusHandle,
+    search_editor: View<Editor>,
+    /// The settings described by the settings JSON schema.
+    fields: Vec<SettingField>,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl SettingsPage {
     pub fn new(_workspace: &Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
-        cx.new_view(|cx| Self {
-            focus_handle: cx.focus_handle(),
+        cx.new_view(|cx| {
+            let search_editor = cx.new_view(|cx| {
+                let mut editor = Editor::single_line(cx);
+                editor.set_placeholder_text("Search settings…", cx);
+                editor
+            });
+            let font_names = cx.text_system().all_font_names();
+            let schema = cx.global::<SettingsStore>().json_schema(
+                &SettingsJsonSchemaParams {
+                    staff_mode: cx.is_staff(),
+                    language_names: &[],
+                    font_names: &font_names,
+                },
+                cx,
+            );
+            let subscriptions = vec![
+                cx.subscribe(&search_editor, |_, _, event: &EditorEvent, cx| {
+                    if let EditorEvent::BufferEdited = event {
+                        cx.notify();
+                    }
+                }),
+                cx.observe_global::<SettingsStore>(|_, cx| cx.notify()),
+            ];
+            Self {
+                focus_handle: cx.focus_handle(),
+                search_editor,
+                fields: setting_fields(&schema),
+                _subscriptions: subscriptions,
+            }
         })
     }
+
+    /// The settings matching the search query, grouped by the object containing them,
+    /// with the settings at the root of the settings file first.
+    fn matching_sections(&self, query: &str) -> BTreeMap<Option<String>, Vec<SettingField>> {
+        let query = query.to_lowercase();
+        let mut sections = BTreeMap::<Option<String>, Vec<SettingField>>::default();
+        for field in &self.fields {
+            let key = field.key_path.join(".");
+            let matches = query.is_empty()
+                || key.to_lowercase().contains(&query)
+                || humanize(&key).to_lowercase().contains(&query)
+                || field.description.as_ref().map_or(false, |description| {
+                    description.to_lowercase().contains(&query)
+                });
+            if matches {
+                sections
+                    .entry(field.section().map(ToString::to_string))
+                    .or_default()
+                    .push(field.clone());
+            }
+        }
+        sections
+    }
 }
 
 impl EventEmitter<ItemEvent> for SettingsPage {}
@@ -101,24 +159,59 @@ impl Item for SettingsPage {
 
 impl Render for SettingsPage {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let query = self.search_editor.read(cx).text(cx);
+        let is_searching = !query.trim().is_empty();
+        let sections = self.matching_sections(query.trim());
+
         v_flex()
+            .id("settings-page")
             .p_4()
             .size_full()
             .gap_4()
+            .overflow_y_scroll()
             .child(Label::new("Settings").size(LabelSize::Large))
             .child(
-                v_flex().gap_1().child(Label::new("Appearance")).child(
-                    v_flex()
-                        .elevation_2(cx)
-                        .child(AppearanceSettingsControls::new()),
-                ),
-            )
-            .child(
-                v_flex().gap_1().child(Label::new("Editor")).child(
-                    v_flex()
-                        .elevation_2(cx)
-                        .child(EditorSettingsControls::new()),
-                ),
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_2()
+                    .rounded_md()
+                    .border_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(
+                        Icon::new(IconName::MagnifyingGlass)
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(self.search_editor.clone()),
             )
+            .when(!is_searching, |this| {
+                this.child(
+                    v_flex().gap_1().child(Label::new("Appearance")).child(
+                        v_flex()
+                            .elevation_2(cx)
+                            .child(AppearanceSettingsControls::new()),
+                    ),
+                )
+                .child(
+                    v_flex().gap_1().child(Label::new("Editor")).child(
+                        v_flex()
+                            .elevation_2(cx)
+                            .child(EditorSettingsControls::new()),
+                    ),
+                )
+            })
+            .when(sections.is_empty(), |this| {
+                this.child(Label::new("No settings match your search.").color(Color::Muted))
+            })
+            .children(sections.into_iter().map(|(section, fields)| {
+                let title = section.as_deref().map_or("General".to_string(), humanize);
+                v_flex().elevation_2(cx).child(
+                    SettingsContainer::new().child(
+                        SettingsGroup::new(title)
+                            .children(fields.into_iter().map(SettingFieldControl::new)),
+                    ),
+                )
+            }))
     }
 }
@@ -0,0 +1,353 @@
+//! Shares the selection of an editor, or its whole buffer, by uploading it to a
+//! paste service and copying the URL of the snippet to the clipboard.
+mod paste_service;
+
+use anyhow::{anyhow, Result};
+use editor::Editor;
+use futures::AsyncReadExt as _;
+use gpui::{
+    actions, AppContext, AsyncWindowContext, ClipboardItem, DismissEvent, EventEmitter,
+    FocusHandle, FocusableView, PromptLevel, Render, View, ViewContext, WeakView,
+};
+use http_client::{AsyncBody, HttpClient, Method, Request};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use ui::prelude::*;
+use util::ResultExt;
+use workspace::{
+    notifications::{DetachAndPromptErr, NotificationId},
+    ModalView, Toast, Workspace,
+};
+
+pub use paste_service::*;
+
+actions!(snippet_share, [ShareSelection, ForgetPasteServiceToken]);
+
+/// Settings for sharing snippets with `snippet share: share selection`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct SnippetShareSettings {
+    /// The service snippets are uploaded to.
+    ///
+    /// Default: paste_rs
+    pub service: PasteService,
+    /// Whether gists are public, rather than secret and only seen by those
+    /// given their URL.
+    ///
+    /// Default: false
+    pub public_gists: bool,
+    /// The service used when `service` is `custom`.
+    ///
+    /// Default: null
+    pub custom: Option<CustomPasteService>,
+}
+
+impl Settings for SnippetShareSettings {
+    const KEY: Option<&'static str> = Some("snippet_sharing");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+pub fn init(cx: &mut AppContext) {
+    SnippetShareSettings::register(cx);
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(share_selection);
+        workspace.register_action(forget_token);
+    })
+    .detach();
+}
+
+struct ShareSnippet;
+
+fn share_selection(workspace: &mut Workspace, _: &ShareSelection, cx: &mut ViewContext<Workspace>) {
+    let Some(editor) = workspace
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx))
+    else {
+        return;
+    };
+    let (snippet, is_private) = snippet_for_editor(&editor, cx);
+    if snippet.text.trim().is_empty() {
+        return;
+    }
+    let settings = SnippetShareSettings::get_global(cx).clone();
+
+    let mut warnings = Vec::new();
+    if is_private {
+        warnings.push(format!(
+            "{} is marked as private in your settings.",
+            snippet.file_name
+        ));
+    }
+    let secrets = find_secrets(&snippet.text);
+    if !secrets.is_empty() {
+        warnings.push(format!(
+            "The snippet seems to contain {}.",
+            secrets.join(", ")
+        ));
+    }
+    let confirmation = (!warnings.is_empty()).then(|| {
+        cx.prompt(
+            PromptLevel::Warning,
+            "Share this snippet?",
+            Some(&format!(
+                "{}\nAnyone with its link will be able to read it.",
+                warnings.join("\n")
+            )),
+            &["Share", "Cancel"],
+        )
+    });
+
+    cx.spawn(|workspace, mut cx| async move {
+        if let Some(confirmation) = confirmation {
+            if confirmation.await? != 0 {
+                return Ok(());
+            }
+        }
+
+        let token = match settings.credentials_url() {
+            Some(credentials_url) => {
+                let credentials = cx
+                    .update(|cx| cx.read_credentials(&credentials_url))?
+                    .await
+                    .log_err()
+                    .flatten();
+                match credentials {
+                    Some((_, token)) => Some(String::from_utf8(token)?),
+                    None => {
+                        // Ask for a token, and share the snippet once it's entered.
+                        let weak_workspace = workspace.clone();
+                        return workspace.update(&mut cx, |workspace, cx| {
+                            workspace.toggle_modal(cx, |cx| {
+                                TokenPrompt::new(
+                                    weak_workspace,
+                                    settings,
+                                    credentials_url,
+                                    snippet,
+                                    cx,
+                                )
+                            })
+                        });
+                    }
+                }
+            }
+            None => None,
+        };
+
+        upload_snippet(workspace, settings, snippet, token, &mut cx).await
+    })
+    .detach_and_prompt_err("Failed to share snippet", cx, |_, _| None);
+}
+
+fn forget_token(_: &mut Workspace, _: &ForgetPasteServiceToken, cx: &mut ViewContext<Workspace>) {
+    if let Some(credentials_url) = SnippetShareSettings::get_global(cx).credentials_url() {
+        cx.delete_credentials(&credentials_url)
+            .detach_and_log_err(cx);
+    }
+}
+
+/// Returns the snippet to share from an editor, which is its selections, or its
+/// whole buffer if nothing is selected, and whether its file is private.
+fn snippet_for_editor(editor: &View<Editor>, cx: &mut ViewContext<Workspace>) -> (Snippet, bool) {
+    editor.update(cx, |editor, cx| {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let selected_text = editor
+            .selections
+            .all::<usize>(cx)
+            .into_iter()
+            .filter(|selection| !selection.is_empty())
+            .map(|selection| {
+                snapshot
+                    .text_for_range(selection.start..selection.end)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        let text = if selected_text.is_empty() {
+            snapshot.text()
+        } else {
+            selected_text.join("\n")
+        };
+
+        let buffer = editor.buffer().read(cx).as_singleton();
+        let file = buffer.as_ref().and_then(|buffer| buffer.read(cx).file());
+        let snippet = Snippet {
+            file_name: file
+                .map(|file| file.file_name(cx).to_string_lossy().into_owned())
+                .unwrap_or_else(|| "untitled.txt".to_string()),
+            language: buffer
+                .as_ref()
+                .and_then(|buffer| buffer.read(cx).language())
+                .map(|language| language.name().to_string()),
+            text,
+        };
+        let is_private = file.map_or(false, |file| file.is_private());
+        (snippet, is_private)
+    })
+}
+
+async fn upload_snippet(
+    workspace: WeakView<Workspace>,
+    settings: SnippetShareSettings,
+    snippet: Snippet,
+    token: Option<String>,
+    cx: &mut AsyncWindowContext,
+) -> Result<()> {
+    let request = settings.paste_request(&snippet, token.as_deref())?;
+    let http_client = cx.update(|cx| cx.http_client())?;
+    let (status, body) = send_request(http_client, request).await?;
+    let url = match settings.snippet_url(status, &body) {
+        Ok(url) => url,
+        Err(error) => {
+            // Forget a token that was rejected, so that a new one is asked for.
+            if matches!(status, 401 | 403) {
+                if let Some(credentials_url) = settings.credentials_url() {
+                    cx.update(|cx| cx.delete_credentials(&credentials_url))?
+                        .await
+                        .log_err();
+                }
+            }
+            return Err(error);
+        }
+    };
+
+    workspace.update(cx, |workspace, cx| {
+        cx.write_to_clipboard(ClipboardItem::new_string(url.clone()));
+        workspace.show_toast(
+            Toast::new(
+                NotificationId::unique::<ShareSnippet>(),
+                format!("Copied the link to the snippet: {url}"),
+            )
+            .on_click("Open", move |cx| cx.open_url(&url)),
+            cx,
+        );
+    })
+}
+
+async fn send_request(
+    http_client: std::sync::Arc<dyn HttpClient>,
+    request: PasteRequest,
+) -> Result<(u16, String)> {
+    let method = Method::from_bytes(request.method.as_bytes())?;
+    let mut builder = Request::builder().method(method).uri(request.url.as_str());
+    for (name, value) in &request.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    let request = builder
+        .body(AsyncBody::from(request.body))
+        .map_err(|error| anyhow!("invalid request: {error}"))?;
+
+    let mut response = http_client.send(request).await?;
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+    Ok((response.status().as_u16(), body))
+}
+
+/// Asks for the token of a paste service, which is stored in the credentials
+/// provider before the snippet is shared.
+struct TokenPrompt {
+    workspace: WeakView<Workspace>,
+    settings: SnippetShareSettings,
+    credentials_url: String,
+    snippet: Option<Snippet>,
+    token_editor: View<Editor>,
+}
+
+impl TokenPrompt {
+    fn new(
+        workspace: WeakView<Workspace>,
+        settings: SnippetShareSettings,
+        credentials_url: String,
+        snippet: Snippet,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let token_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_masked(true, cx);
+            editor.set_placeholder_text("Token", cx);
+            editor
+        });
+        Self {
+            workspace,
+            settings,
+            credentials_url,
+            snippet: Some(snippet),
+            token_editor,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let token = self.token_editor.read(cx).text(cx).trim().to_string();
+        if token.is_empty() {
+            return;
+        }
+        let Some(snippet) = self.snippet.take() else {
+            return;
+        };
+        let workspace = self.workspace.clone();
+        let write_credentials =
+            cx.write_credentials(&self.credentials_url, "token", token.as_bytes());
+        let settings = self.settings.clone();
+        cx.spawn(|_, mut cx| async move {
+            write_credentials.await?;
+            upload_snippet(workspace, settings, snippet, Some(token), &mut cx).await
+        })
+        .detach_and_prompt_err("Failed to share snippet", cx, |_, _| None);
+        cx.emit(DismissEvent);
+    }
+}
+
+impl ModalView for TokenPrompt {}
+
+impl EventEmitter<DismissEvent> for TokenPrompt {}
+
+impl FocusableView for TokenPrompt {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.token_editor.focus_handle(cx)
+    }
+}
+
+impl Render for TokenPrompt {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let instructions = match self.settings.service {
+            PasteService::Gist => {
+                "Enter a GitHub personal access token with the `gist` scope to create gists."
+            }
+            PasteService::PasteRs | PasteService::Custom => {
+                "Enter the token of your paste service."
+            }
+        };
+
+        v_flex()
+            .key_context("TokenPrompt")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .elevation_2(cx)
+            .w_96()
+            .p_2()
+            .gap_2()
+            .child(Label::new(instructions))
+            .child(
+                div()
+                    .px_2()
+                    .py_1()
+                    .rounded_md()
+                    .border_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(self.token_editor.clone()),
+            )
+            .child(
+                Label::new("The token is kept in your system's keychain.")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+    }
+}
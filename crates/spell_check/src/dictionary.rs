@@ -0,0 +1,631 @@
+use anyhow::{anyhow, Context as _, Result};
+use collections::{HashMap, HashSet};
+
+use crate::MAX_SUGGESTIONS;
+
+/// The characters tried when suggesting corrections, for dictionaries without a `TRY` directive.
+const DEFAULT_TRY_CHARACTERS: &str = "esianrtolcdugmphbyfvkwzESIANRTOLCDUGMPHBYFVKWZ'";
+
+/// An affix flag, decoded according to the `FLAG` directive of the affix file.
+type Flag = u32;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum FlagType {
+    /// Each character is a flag.
+    #[default]
+    Short,
+    /// Each pair of characters is a flag.
+    Long,
+    /// Flags are comma-separated numbers.
+    Numeric,
+}
+
+impl FlagType {
+    fn parse(self, flags: &str) -> Vec<Flag> {
+        match self {
+            FlagType::Short => flags.chars().map(|c| c as Flag).collect(),
+            FlagType::Long => {
+                let chars = flags.chars().collect::<Vec<_>>();
+                chars
+                    .chunks(2)
+                    .map(|pair| pair.iter().fold(0, |flag, c| (flag << 16) | *c as Flag))
+                    .collect()
+            }
+            FlagType::Numeric => flags
+                .split(',')
+                .filter_map(|flag| flag.trim().parse().ok())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum CharMatcher {
+    Any,
+    Char(char),
+    Set { chars: Vec<char>, negated: bool },
+}
+
+impl CharMatcher {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            CharMatcher::Any => true,
+            CharMatcher::Char(expected) => c == *expected,
+            CharMatcher::Set { chars, negated } => chars.contains(&c) != *negated,
+        }
+    }
+}
+
+/// The condition a stem has to meet for an affix to apply, a simplified
+/// regular expression made of characters, `.` and bracketed character sets.
+#[derive(Debug, PartialEq)]
+struct Condition(Vec<CharMatcher>);
+
+impl Condition {
+    fn parse(condition: &str) -> Self {
+        let mut matchers = Vec::new();
+        let mut chars = condition.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => matchers.push(CharMatcher::Any),
+                '[' => {
+                    let mut set = Vec::new();
+                    let mut negated = false;
+                    for c in chars.by_ref() {
+                        match c {
+                            ']' => break,
+                            '^' if set.is_empty() && !negated => negated = true,
+                            c => set.push(c),
+                        }
+                    }
+                    matchers.push(CharMatcher::Set {
+                        chars: set,
+                        negated,
+                    });
+                }
+                c => matchers.push(CharMatcher::Char(c)),
+            }
+        }
+        Self(matchers)
+    }
+
+    fn matches_start(&self, stem: &str) -> bool {
+        let mut chars = stem.chars();
+        self.0
+            .iter()
+            .all(|matcher| chars.next().map_or(false, |c| matcher.matches(c)))
+    }
+
+    fn matches_end(&self, stem: &str) -> bool {
+        let mut chars = stem.chars().rev();
+        self.0
+            .iter()
+            .rev()
+            .all(|matcher| chars.next().map_or(false, |c| matcher.matches(c)))
+    }
+}
+
+#[derive(Debug)]
+struct Affix {
+    flag: Flag,
+    cross_product: bool,
+    strip: String,
+    add: String,
+    condition: Condition,
+}
+
+impl Affix {
+    /// Returns the stem of `word`, when it ends with this suffix.
+    fn stem_of_suffixed(&self, word: &str) -> Option<String> {
+        let base = word.strip_suffix(self.add.as_str())?;
+        if base.is_empty() {
+            return None;
+        }
+        let stem = format!("{base}{}", self.strip);
+        self.condition.matches_end(&stem).then_some(stem)
+    }
+
+    /// Returns the stem of `word`, when it starts with this prefix.
+    fn stem_of_prefixed(&self, word: &str) -> Option<String> {
+        let base = word.strip_prefix(self.add.as_str())?;
+        if base.is_empty() {
+            return None;
+        }
+        let stem = format!("{}{base}", self.strip);
+        self.condition.matches_start(&stem).then_some(stem)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Casing {
+    Lower,
+    Title,
+    Upper,
+    Mixed,
+}
+
+impl Casing {
+    fn of(word: &str) -> Self {
+        let mut chars = word.chars().filter(|c| c.is_alphabetic());
+        let Some(first) = chars.next() else {
+            return Casing::Lower;
+        };
+        let (mut lower, mut upper) = (0, 0);
+        for c in chars {
+            if c.is_uppercase() {
+                upper += 1;
+            } else if c.is_lowercase() {
+                lower += 1;
+            }
+        }
+        match (first.is_uppercase(), lower, upper) {
+            (false, _, 0) => Casing::Lower,
+            (true, _, 0) => Casing::Title,
+            (true, 0, _) => Casing::Upper,
+            _ => Casing::Mixed,
+        }
+    }
+
+    fn apply(self, word: &str) -> String {
+        match self {
+            Casing::Title => {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+            Casing::Upper => word.to_uppercase(),
+            Casing::Lower | Casing::Mixed => word.to_string(),
+        }
+    }
+}
+
+/// A Hunspell dictionary, made of an affix file (`.aff`) and a list of words
+/// with the affixes that apply to them (`.dic`).
+///
+/// Only the subset of the Hunspell format needed to check the spelling of words
+/// and suggest corrections is supported: prefixes, suffixes, flag aliases,
+/// replacement tables and the `FORBIDDENWORD`, `NEEDAFFIX` and `NOSUGGEST` flags.
+#[derive(Debug)]
+pub struct Dictionary {
+    words: HashMap<String, Vec<Flag>>,
+    prefixes: Vec<Affix>,
+    suffixes: Vec<Affix>,
+    replacements: Vec<(String, String)>,
+    try_characters: Vec<char>,
+    forbidden_word: Option<Flag>,
+    need_affix: Option<Flag>,
+    no_suggest: Option<Flag>,
+}
+
+impl Dictionary {
+    /// Parses a dictionary from the contents of its affix and word files.
+    pub fn new(aff: &[u8], dic: &[u8]) -> Result<Self> {
+        let aff = decode(aff, None).context("decoding affix file")?;
+        let encoding = aff
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("SET "));
+        let dic = decode(dic, encoding).context("decoding dictionary file")?;
+
+        let mut dictionary = Self {
+            words: HashMap::default(),
+            prefixes: Vec::new(),
+            suffixes: Vec::new(),
+            replacements: Vec::new(),
+            try_characters: DEFAULT_TRY_CHARACTERS.chars().collect(),
+            forbidden_word: None,
+            need_affix: None,
+            no_suggest: None,
+        };
+        let mut flag_type = FlagType::default();
+        let mut aliases = Vec::new();
+        let mut cross_products = HashMap::default();
+
+        for line in aff.lines() {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            match fields.as_slice() {
+                ["FLAG", "long", ..] => flag_type = FlagType::Long,
+                ["FLAG", "num", ..] => flag_type = FlagType::Numeric,
+                ["TRY", characters, ..] => dictionary.try_characters = characters.chars().collect(),
+                ["FORBIDDENWORD", flag, ..] => {
+                    dictionary.forbidden_word = flag_type.parse(flag).first().copied()
+                }
+                ["NEEDAFFIX", flag, ..] => {
+                    dictionary.need_affix = flag_type.parse(flag).first().copied()
+                }
+                ["NOSUGGEST", flag, ..] => {
+                    dictionary.no_suggest = flag_type.parse(flag).first().copied()
+                }
+                // The first line of a table is its size.
+                ["AF", count] if count.parse::<usize>().is_ok() && aliases.is_empty() => {}
+                ["AF", flags, ..] => aliases.push(flag_type.parse(flags)),
+                ["REP", count] if count.parse::<usize>().is_ok() => {}
+                ["REP", from, to, ..] => dictionary
+                    .replacements
+                    .push((from.replace('_', " "), to.replace('_', " "))),
+                [kind @ ("PFX" | "SFX"), flag, cross_product, count]
+                    if count.parse::<usize>().is_ok() =>
+                {
+                    if let Some(flag) = flag_type.parse(flag).first() {
+                        cross_products.insert((*kind, *flag), *cross_product == "Y");
+                    }
+                }
+                [kind @ ("PFX" | "SFX"), flag, strip, add, rest @ ..] => {
+                    let Some(flag) = flag_type.parse(flag).first().copied() else {
+                        continue;
+                    };
+                    // Continuation classes, after a slash, aren't supported.
+                    let add = add.split('/').next().unwrap_or_default();
+                    let affix = Affix {
+                        flag,
+                        cross_product: cross_products.get(&(*kind, flag)).copied().unwrap_or(false),
+                        strip: if *strip == "0" { "" } else { strip }.to_string(),
+                        add: if add == "0" { "" } else { add }.to_string(),
+                        condition: Condition::parse(rest.first().copied().unwrap_or(".")),
+                    };
+                    if *kind == "PFX" {
+                        dictionary.prefixes.push(affix);
+                    } else {
+                        dictionary.suffixes.push(affix);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut lines = dic.lines();
+        let count = lines
+            .next()
+            .and_then(|line| line.trim().parse::<usize>().ok())
+            .ok_or_else(|| anyhow!("dictionary file doesn't start with its word count"))?;
+        dictionary.words.reserve(count);
+        for line in lines {
+            // Morphological fields follow the word, after whitespace.
+            let Some(entry) = line.split_whitespace().next() else {
+                continue;
+            };
+            let (word, flags) = match entry.find('/').filter(|ix| *ix > 0) {
+                Some(ix) => (&entry[..ix], &entry[ix + 1..]),
+                None => (entry, ""),
+            };
+            let flags = if aliases.is_empty() {
+                flag_type.parse(flags)
+            } else {
+                flags
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|ix| aliases.get(ix.wrapping_sub(1)))
+                    .cloned()
+                    .unwrap_or_default()
+            };
+            dictionary
+                .words
+                .entry(word.replace("\\/", "/"))
+                .or_default()
+                .extend(flags);
+        }
+
+        Ok(dictionary)
+    }
+
+    /// Returns whether `word` is spelled correctly.
+    pub fn check(&self, word: &str) -> bool {
+        let word = word.replace('’', "'");
+        if self.check_exact(&word) {
+            return true;
+        }
+        match Casing::of(&word) {
+            Casing::Title => self.check_exact(&word.to_lowercase()),
+            Casing::Upper => {
+                let lowercase = word.to_lowercase();
+                self.check_exact(&lowercase) || self.check_exact(&Casing::Title.apply(&lowercase))
+            }
+            Casing::Lower | Casing::Mixed => false,
+        }
+    }
+
+    /// Returns the suggested replacements for a misspelled word, best first.
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let word = word.replace('’', "'");
+        let casing = Casing::of(&word);
+        let word = match casing {
+            Casing::Title | Casing::Upper => word.to_lowercase(),
+            Casing::Lower | Casing::Mixed => word,
+        };
+        let chars = word.chars().collect::<Vec<_>>();
+
+        let mut seen = HashSet::default();
+        let mut suggestions = Vec::new();
+        let mut suggest = |suggestions: &mut Vec<_>, candidate: String, distance: usize| {
+            if seen.insert(candidate.clone()) && self.is_suggestible(&candidate) {
+                suggestions.push((distance, candidate));
+            }
+        };
+
+        for (from, to) in &self.replacements {
+            for (ix, _) in word.match_indices(from.as_str()) {
+                let candidate = format!("{}{to}{}", &word[..ix], &word[ix + from.len()..]);
+                suggest(&mut suggestions, candidate, 0);
+            }
+        }
+        for candidate in edits(&chars, &self.try_characters) {
+            suggest(&mut suggestions, candidate, 1);
+        }
+        for ix in 1..chars.len() {
+            let (first, second) = chars.split_at(ix);
+            let (first, second) = (String::from_iter(first), String::from_iter(second));
+            if self.check(&first) && self.check(&second) {
+                suggest(&mut suggestions, format!("{first} {second}"), 1);
+            }
+        }
+
+        if suggestions.len() < MAX_SUGGESTIONS {
+            let mut similar_words = self
+                .words
+                .keys()
+                .filter_map(|candidate| {
+                    let candidate_chars = candidate.chars().collect::<Vec<_>>();
+                    if candidate_chars.len().abs_diff(chars.len()) > 2 {
+                        return None;
+                    }
+                    let distance = edit_distance(&chars, &candidate_chars);
+                    (distance <= 2).then_some((distance, candidate))
+                })
+                .collect::<Vec<_>>();
+            similar_words.sort();
+            for (distance, candidate) in similar_words {
+                suggest(&mut suggestions, candidate.clone(), distance);
+            }
+        }
+
+        suggestions.sort_by_key(|(distance, _)| *distance);
+        suggestions
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, suggestion)| casing.apply(&suggestion))
+            .collect()
+    }
+
+    fn check_exact(&self, word: &str) -> bool {
+        if let Some(flags) = self.words.get(word) {
+            if self.has_flag(flags, self.forbidden_word) {
+                return false;
+            }
+            if !self.has_flag(flags, self.need_affix) {
+                return true;
+            }
+        }
+        self.check_affixed(word)
+    }
+
+    fn check_affixed(&self, word: &str) -> bool {
+        for suffix in &self.suffixes {
+            let Some(stem) = suffix.stem_of_suffixed(word) else {
+                continue;
+            };
+            if self.stem_has_flags(&stem, &[suffix.flag]) {
+                return true;
+            }
+            if suffix.cross_product {
+                for prefix in self.prefixes.iter().filter(|prefix| prefix.cross_product) {
+                    if let Some(root) = prefix.stem_of_prefixed(&stem) {
+                        if self.stem_has_flags(&root, &[prefix.flag, suffix.flag]) {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+        self.prefixes.iter().any(|prefix| {
+            prefix
+                .stem_of_prefixed(word)
+                .map_or(false, |stem| self.stem_has_flags(&stem, &[prefix.flag]))
+        })
+    }
+
+    fn stem_has_flags(&self, stem: &str, required: &[Flag]) -> bool {
+        self.words.get(stem).map_or(false, |flags| {
+            required.iter().all(|flag| flags.contains(flag))
+                && !self.has_flag(flags, self.forbidden_word)
+        })
+    }
+
+    fn is_suggestible(&self, candidate: &str) -> bool {
+        let no_suggest = self
+            .words
+            .get(candidate)
+            .map_or(false, |flags| self.has_flag(flags, self.no_suggest));
+        !no_suggest && candidate.split(' ').all(|word| self.check(word))
+    }
+
+    fn has_flag(&self, flags: &[Flag], flag: Option<Flag>) -> bool {
+        flag.map_or(false, |flag| flags.contains(&flag))
+    }
+}
+
+/// Decodes the contents of a dictionary file, which are in UTF-8 or ISO 8859-1
+/// according to the `SET` directive of the affix file.
+fn decode(contents: &[u8], encoding: Option<&str>) -> Result<String> {
+    let contents = contents.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(contents);
+    let encoding = match encoding {
+        Some(encoding) => encoding.trim().to_string(),
+        None => {
+            // The affix file declares its own encoding, in ASCII.
+            let header = String::from_utf8_lossy(contents);
+            header
+                .lines()
+                .find_map(|line| {
+                    line.trim()
+                        .strip_prefix("SET ")
+                        .map(|e| e.trim().to_string())
+                })
+                .unwrap_or_else(|| "ISO8859-1".to_string())
+        }
+    };
+    match encoding.to_ascii_uppercase().as_str() {
+        "UTF-8" | "UTF8" => Ok(String::from_utf8_lossy(contents).into_owned()),
+        "ISO8859-1" | "ISO-8859-1" | "LATIN1" => Ok(contents.iter().map(|b| *b as char).collect()),
+        _ => Err(anyhow!("unsupported dictionary encoding {encoding:?}")),
+    }
+}
+
+/// Returns the words one deletion, transposition, replacement or insertion away from `word`.
+fn edits(word: &[char], try_characters: &[char]) -> Vec<String> {
+    let mut edits = Vec::new();
+    for ix in 0..word.len() {
+        let mut deleted = word.to_vec();
+        deleted.remove(ix);
+        edits.push(String::from_iter(deleted));
+    }
+    for ix in 1..word.len() {
+        let mut transposed = word.to_vec();
+        transposed.swap(ix - 1, ix);
+        edits.push(String::from_iter(transposed));
+    }
+    for c in try_characters {
+        for ix in 0..word.len() {
+            if word[ix] != *c {
+                let mut replaced = word.to_vec();
+                replaced[ix] = *c;
+                edits.push(String::from_iter(replaced));
+            }
+        }
+        for ix in 0..=word.len() {
+            let mut inserted = word.to_vec();
+            inserted.insert(ix, *c);
+            edits.push(String::from_iter(inserted));
+        }
+    }
+    edits
+}
+
+/// Returns the number of insertions, deletions, replacements and transpositions
+/// needed to turn `a` into `b`.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![(0..=b.len()).collect::<Vec<_>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (rows[i - 1][j] + 1)
+                .min(row[j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFF: &str = "
+SET UTF-8
+TRY esiantrolcdugmphbyfvkwz
+NOSUGGEST !
+FORBIDDENWORD *
+
+PFX U Y 1
+PFX U   0     un         .
+
+SFX S Y 2
+SFX S   y     ies        [^aeiou]y
+SFX S   0     s          [aeiou]y
+
+SFX D Y 4
+SFX D   y     ied        [^aeiou]y
+SFX D   0     ed         [aeiou]y
+SFX D   0     ed         [^ey]
+SFX D   0     d          e
+
+REP 1
+REP f ph
+";
+
+    const DIC: &str = "8
+hello
+world
+try/SD
+play/SDU
+bake/D
+phone
+damn/!
+irregardless/*
+";
+
+    fn dictionary() -> Dictionary {
+        Dictionary::new(AFF.as_bytes(), DIC.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_check() {
+        let dictionary = dictionary();
+        for word in [
+            "hello", "Hello", "HELLO", "tries", "tried", "plays", "played", "unplayed", "unplays",
+            "baked", "damn",
+        ] {
+            assert!(dictionary.check(word), "{word:?} should be correct");
+        }
+        for word in [
+            "helo",
+            "hELLO",
+            "trys",
+            "tryed",
+            "bakeed",
+            "untry",
+            "irregardless",
+            "unbake",
+        ] {
+            assert!(!dictionary.check(word), "{word:?} should be misspelled");
+        }
+    }
+
+    #[test]
+    fn test_suggest() {
+        let dictionary = dictionary();
+        assert_eq!(dictionary.suggest("helo"), vec!["hello"]);
+        assert_eq!(dictionary.suggest("Wrold"), vec!["World"]);
+        assert_eq!(dictionary.suggest("fone"), vec!["phone"]);
+        assert_eq!(dictionary.suggest("helloworld"), vec!["hello world"]);
+        assert_eq!(dictionary.suggest("tryed")[0], "tried");
+        assert!(dictionary.suggest("dam").iter().all(|s| s != "damn"));
+    }
+
+    #[test]
+    fn test_flag_types_and_aliases() {
+        let aff = "FLAG long\nSFX Aa Y 1\nSFX Aa 0 s .\n";
+        let dictionary = Dictionary::new(aff.as_bytes(), b"1\ncat/Aa\n").unwrap();
+        assert!(dictionary.check("cats"));
+
+        let aff = "FLAG num\nAF 2\nAF 12,7\nAF 7\nSFX 12 Y 1\nSFX 12 0 s .\n";
+        let dictionary = Dictionary::new(aff.as_bytes(), b"2\ndog/1\nfish/2\n").unwrap();
+        assert!(dictionary.check("dogs"));
+        assert!(!dictionary.check("fishs"));
+    }
+
+    #[test]
+    fn test_latin1_encoding() {
+        let aff = b"SET ISO8859-1\n";
+        let dictionary = Dictionary::new(aff, b"1\ncaf\xE9\n").unwrap();
+        assert!(dictionary.check("café"));
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        let distance = |a: &str, b: &str| {
+            edit_distance(
+                &a.chars().collect::<Vec<_>>(),
+                &b.chars().collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(distance("hello", "hello"), 0);
+        assert_eq!(distance("hello", "helo"), 1);
+        assert_eq!(distance("hello", "hlelo"), 1);
+        assert_eq!(distance("kitten", "sitting"), 3);
+    }
+}
@@ -0,0 +1,69 @@
+//! Spell checking of prose, comments and strings, with Hunspell dictionaries.
+mod dictionary;
+mod words;
+
+use collections::HashSet;
+
+pub use dictionary::Dictionary;
+pub use words::words;
+
+/// The maximum number of suggestions offered for a misspelled word.
+pub const MAX_SUGGESTIONS: usize = 5;
+
+/// Returns the words of a personal dictionary file, which lists one word per line.
+pub fn parse_personal_dictionary(text: &str) -> HashSet<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|word| !word.is_empty() && !word.starts_with('#'))
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Returns whether `word` is spelled correctly according to any of the given
+/// dictionaries, or is part of the personal dictionary.
+pub fn is_correct(
+    word: &str,
+    dictionaries: &[impl AsRef<Dictionary>],
+    personal: &HashSet<String>,
+) -> bool {
+    personal.contains(word)
+        || personal.contains(&word.to_lowercase())
+        || dictionaries
+            .iter()
+            .any(|dictionary| dictionary.as_ref().check(word))
+}
+
+/// Returns the suggested replacements for a misspelled word, from all of the
+/// given dictionaries.
+pub fn suggestions(word: &str, dictionaries: &[impl AsRef<Dictionary>]) -> Vec<String> {
+    let mut suggestions = Vec::new();
+    for dictionary in dictionaries {
+        for suggestion in dictionary.as_ref().suggest(word) {
+            if !suggestions.contains(&suggestion) {
+                suggestions.push(suggestion);
+            }
+        }
+    }
+    suggestions.truncate(MAX_SUGGESTIONS);
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_personal_dictionary() {
+        let dictionary = Arc::new(Dictionary::new(b"SET UTF-8\n", b"2\nhello\nworld\n").unwrap());
+        let personal = parse_personal_dictionary("# My words\nZed\n\n  rustfmt  \n");
+        assert_eq!(personal.len(), 2);
+
+        let dictionaries = [dictionary];
+        assert!(is_correct("hello", &dictionaries, &personal));
+        assert!(is_correct("Zed", &dictionaries, &personal));
+        assert!(is_correct("rustfmt", &dictionaries, &personal));
+        assert!(!is_correct("helo", &dictionaries, &personal));
+        assert_eq!(suggestions("helo", &dictionaries), vec!["hello"]);
+    }
+}
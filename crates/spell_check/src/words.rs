@@ -0,0 +1,167 @@
+use std::ops::Range;
+
+/// Characters that join words into paths, member accesses, generic types and the
+/// like, which aren't spell checked.
+const JOINERS: &[char] = &['.', ':', '/', '\\', '<', '>', '=', '|', '+', '*', '^'];
+
+/// Characters that mark the start of variables, tags and other identifiers.
+const SIGILS: &[char] = &['$', '#', '%', '&', '~', '<', '/', '\\', '@'];
+
+/// Returns the words to spell check in `text`, with their byte ranges.
+///
+/// Identifiers, such as `snake_case` and `camelCase` words, code between
+/// backticks, paths, URLs, email addresses, acronyms and single letters are
+/// skipped.
+pub fn words(text: &str) -> Vec<(Range<usize>, &str)> {
+    let mut words = Vec::new();
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        let backticks = line
+            .match_indices('`')
+            .map(|(ix, _)| ix)
+            .collect::<Vec<_>>();
+        let mut segment_start = 0;
+        for code_span in backticks.chunks_exact(2) {
+            push_words(
+                text,
+                line_start + segment_start..line_start + code_span[0],
+                &mut words,
+            );
+            segment_start = code_span[1] + 1;
+        }
+        push_words(
+            text,
+            line_start + segment_start..line_start + line.len(),
+            &mut words,
+        );
+        line_start += line.len();
+    }
+    words
+}
+
+fn push_words<'a>(text: &'a str, range: Range<usize>, words: &mut Vec<(Range<usize>, &'a str)>) {
+    let mut chunk_start = None;
+    for (ix, c) in text[range.clone()]
+        .char_indices()
+        .map(|(ix, c)| (range.start + ix, c))
+        .chain([(range.end, ' ')])
+    {
+        match (c.is_whitespace(), chunk_start) {
+            (false, None) => chunk_start = Some(ix),
+            (true, Some(start)) => {
+                push_chunk_words(text, start..ix, words);
+                chunk_start = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Pushes the words of a chunk of text without whitespace.
+fn push_chunk_words<'a>(
+    text: &'a str,
+    range: Range<usize>,
+    words: &mut Vec<(Range<usize>, &'a str)>,
+) {
+    let chunk = &text[range.clone()];
+    if chunk.contains("://") || chunk.contains('@') {
+        return;
+    }
+
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_' || is_apostrophe(c);
+    let mut ix = 0;
+    while ix < chunk.len() {
+        let Some(c) = chunk[ix..].chars().next() else {
+            break;
+        };
+        if !is_word_char(c) {
+            ix += c.len_utf8();
+            continue;
+        }
+        let end = chunk[ix..]
+            .find(|c| !is_word_char(c))
+            .map_or(chunk.len(), |len| ix + len);
+        let before = &chunk[..ix];
+        let after = &chunk[end..];
+        let word = chunk[ix..end].trim_matches(is_apostrophe);
+        let word_start = ix + chunk[ix..end].find(word).unwrap_or(0);
+        ix = end;
+
+        if word.chars().count() < 2
+            || !word.chars().all(|c| c.is_alphabetic() || is_apostrophe(c))
+            || word.chars().skip(1).any(char::is_uppercase)
+        {
+            continue;
+        }
+        let mut before_chars = before.chars().rev();
+        if let Some(previous) = before_chars.next() {
+            if SIGILS.contains(&previous)
+                || (JOINERS.contains(&previous) && before_chars.any(char::is_alphanumeric))
+            {
+                continue;
+            }
+        }
+        let mut after_chars = after.chars();
+        if let Some(next) = after_chars.next() {
+            if next == '(' || next == '[' {
+                continue;
+            }
+            if JOINERS.contains(&next) && after_chars.any(char::is_alphanumeric) {
+                continue;
+            }
+        }
+
+        let start = range.start + word_start;
+        words.push((start..start + word.len(), word));
+    }
+}
+
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '’'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word_list(text: &str) -> Vec<&str> {
+        words(text).into_iter().map(|(_, word)| word).collect()
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(
+            word_list("// Returns the user's \"first\" name, or 'none'."),
+            vec!["Returns", "the", "user's", "first", "name", "or", "none"]
+        );
+        assert_eq!(
+            word_list("Call `read_file` with self.path, not some_path or fooBar()."),
+            vec!["Call", "with", "not", "or"]
+        );
+        assert_eq!(
+            word_list("See https://zed.dev or mail hi@zed.dev, and ./src/main.rs."),
+            vec!["See", "or", "mail", "and"]
+        );
+        assert_eq!(
+            word_list("A Vec<String> of HTTP headers, like x2 and $HOME or std::env."),
+            vec!["of", "headers", "like", "and", "or"]
+        );
+        assert_eq!(
+            word_list("well-known -- café\n# Heading"),
+            vec!["well", "known", "café", "Heading"]
+        );
+    }
+
+    #[test]
+    fn test_word_ranges() {
+        let text = "fn main() { // Prnt `it`\n    \"hllo wrld\" }";
+        let ranges = words(text);
+        for (range, word) in &ranges {
+            assert_eq!(&text[range.clone()], *word);
+        }
+        assert_eq!(
+            ranges.into_iter().map(|(_, word)| word).collect::<Vec<_>>(),
+            vec!["fn", "Prnt", "hllo", "wrld"]
+        );
+    }
+}
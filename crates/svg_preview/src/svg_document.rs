@@ -0,0 +1,278 @@
+//! SVG documents, rendered with the positions of their elements in the source kept,
+//! so that a point on the rendered image can be traced back to the element drawn there.
+
+use std::{ops::Range, sync::Arc};
+
+use collections::HashMap;
+use gpui::RenderImage;
+use resvg::tiny_skia;
+
+/// The elements that don't have an id are given one with this prefix, so that they
+/// can be found in the rendered tree.
+const ID_PREFIX: &str = "zed-svg-preview-";
+
+/// Images are rendered so that their longest side has at least this many pixels, so
+/// that small icons stay sharp when they're scaled up to fit the preview.
+const MIN_RENDER_SIZE: f32 = 1024.;
+const MAX_RENDER_SIZE: f32 = 4096.;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgError {
+    pub message: String,
+    /// The offset in the source that the error is at, if it's known.
+    pub offset: Option<usize>,
+    /// The one-based row and column that the error is at, if it's known.
+    pub position: Option<(u32, u32)>,
+}
+
+impl SvgError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            offset: None,
+            position: None,
+        }
+    }
+
+    fn from_xml(error: roxmltree::Error, source: &str) -> Self {
+        let position = error.pos();
+        Self {
+            message: error.to_string(),
+            offset: Some(offset_for_position(source, position.row, position.col)),
+            position: Some((position.row, position.col)),
+        }
+    }
+}
+
+pub struct SvgDocument {
+    /// The source, with an id added to each element that doesn't have one.
+    instrumented: String,
+    /// The ranges of the start tags of the elements, by their ids.
+    elements: HashMap<String, Range<usize>>,
+}
+
+pub struct RenderedSvg {
+    pub image: Arc<RenderImage>,
+    /// The size of the image, in the units of the document.
+    pub width: f32,
+    pub height: f32,
+    tree: usvg::Tree,
+}
+
+impl SvgDocument {
+    pub fn parse(source: &str) -> Result<Self, SvgError> {
+        let options = roxmltree::ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        };
+        let document = roxmltree::Document::parse_with_options(source, options)
+            .map_err(|error| SvgError::from_xml(error, source))?;
+
+        let mut instrumented = String::with_capacity(source.len());
+        let mut elements = HashMap::default();
+        let mut copied_up_to = 0;
+        for (index, node) in document
+            .descendants()
+            .filter(|node| node.is_element())
+            .enumerate()
+        {
+            let start = node.range().start;
+            // Elements expanded from entities have no start tag of their own.
+            if !source[start..].starts_with('<') {
+                continue;
+            }
+            let tag_range = start..start_tag_end(source, start);
+            match node.attribute("id").filter(|id| !id.is_empty()) {
+                Some(id) => {
+                    elements.insert(id.to_string(), tag_range);
+                }
+                None => {
+                    let id = format!("{ID_PREFIX}{index}");
+                    let name_end = source[start + 1..]
+                        .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+                        .map_or(source.len(), |end| start + 1 + end);
+                    instrumented.push_str(&source[copied_up_to..name_end]);
+                    instrumented.push_str(&format!(" id=\"{id}\""));
+                    copied_up_to = name_end;
+                    elements.insert(id, tag_range);
+                }
+            }
+        }
+        instrumented.push_str(&source[copied_up_to..]);
+
+        Ok(Self {
+            instrumented,
+            elements,
+        })
+    }
+
+    /// Renders the document, at a scale that keeps it sharp on a display with the
+    /// given scale factor.
+    pub fn render(&self, scale_factor: f32) -> Result<RenderedSvg, SvgError> {
+        let tree = usvg::Tree::from_str(&self.instrumented, &usvg::Options::default())
+            .map_err(|error| SvgError::new(error.to_string()))?;
+
+        let (width, height) = (tree.size().width(), tree.size().height());
+        let longest_side = width.max(height);
+        let scale =
+            (scale_factor.max(MIN_RENDER_SIZE / longest_side)).min(MAX_RENDER_SIZE / longest_side);
+        let pixel_width = (width * scale).ceil().max(1.) as u32;
+        let pixel_height = (height * scale).ceil().max(1.) as u32;
+        let mut pixmap = tiny_skia::Pixmap::new(pixel_width, pixel_height)
+            .ok_or_else(|| SvgError::new("The image has an invalid size"))?;
+        let size = tiny_skia::Size::from_wh(pixel_width as f32, pixel_height as f32)
+            .ok_or_else(|| SvgError::new("The image has an invalid size"))?;
+        resvg::render(
+            &tree,
+            tree.view_box().to_transform(size),
+            &mut pixmap.as_mut(),
+        );
+
+        // GPUI expects images with straight alpha, in BGRA order.
+        let mut pixels = Vec::with_capacity(pixmap.data().len());
+        for pixel in pixmap.pixels() {
+            let color = pixel.demultiply();
+            pixels.extend([color.blue(), color.green(), color.red(), color.alpha()]);
+        }
+        let buffer = image::RgbaImage::from_raw(pixel_width, pixel_height, pixels)
+            .ok_or_else(|| SvgError::new("The image has an invalid size"))?;
+
+        Ok(RenderedSvg {
+            image: Arc::new(RenderImage::new(vec![image::Frame::new(buffer)])),
+            width,
+            height,
+            tree,
+        })
+    }
+
+    /// Returns the range of the start tag of the topmost element drawn at a point of
+    /// the rendered image, in the units of the document.
+    pub fn element_at(&self, rendered: &RenderedSvg, x: f32, y: f32) -> Option<Range<usize>> {
+        let size = tiny_skia::Size::from_wh(rendered.width, rendered.height)?;
+        let mut point = tiny_skia::Point::from_xy(x, y);
+        rendered
+            .tree
+            .view_box()
+            .to_transform(size)
+            .invert()?
+            .map_points(std::slice::from_mut(&mut point));
+
+        let mut hit = None;
+        hit_test(rendered.tree.root(), point, &mut hit);
+        self.elements.get(hit?).cloned()
+    }
+}
+
+/// Finds the last, and so topmost, node with an id whose bounds contain a point.
+fn hit_test<'a>(group: &'a usvg::Group, point: tiny_skia::Point, hit: &mut Option<&'a str>) {
+    for node in group.children() {
+        let bounds = node.abs_stroke_bounding_box();
+        if point.x < bounds.left()
+            || point.x > bounds.right()
+            || point.y < bounds.top()
+            || point.y > bounds.bottom()
+        {
+            continue;
+        }
+        if !node.id().is_empty() {
+            *hit = Some(node.id());
+        }
+        if let usvg::Node::Group(group) = node {
+            hit_test(group, point, hit);
+        }
+    }
+}
+
+/// Returns the end of the start tag that begins at an offset, skipping over any `>`
+/// in the values of its attributes.
+fn start_tag_end(source: &str, start: usize) -> usize {
+    let mut quote = None;
+    for (index, c) in source[start..].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            (None, '>') => return start + index + 1,
+            _ => {}
+        }
+    }
+    source.len()
+}
+
+/// Converts a one-based row and column, in characters, into an offset.
+fn offset_for_position(source: &str, row: u32, column: u32) -> usize {
+    let mut line_start = 0;
+    for _ in 1..row {
+        match source[line_start..].find('\n') {
+            Some(end) => line_start += end + 1,
+            None => return source.len(),
+        }
+    }
+    source[line_start..]
+        .char_indices()
+        .nth(column.saturating_sub(1) as usize)
+        .map_or(source.len(), |(index, _)| line_start + index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="50">
+  <rect id="left" x="0" y="0" width="50" height="50" fill="red"/>
+  <rect x="50" y="0" width="50" height="50" fill="blue" title="a > b"/>
+</svg>"#;
+
+    #[test]
+    fn test_parse_adds_ids() {
+        let document = SvgDocument::parse(SOURCE).unwrap();
+        assert!(document
+            .instrumented
+            .starts_with(r#"<svg id="zed-svg-preview-0" xmlns"#));
+        assert!(document
+            .instrumented
+            .contains(r#"<rect id="zed-svg-preview-2" x="50""#));
+        assert!(document.instrumented.contains(r#"<rect id="left" x="0""#));
+
+        let left = &document.elements["left"];
+        assert_eq!(
+            &SOURCE[left.clone()],
+            r#"<rect id="left" x="0" y="0" width="50" height="50" fill="red"/>"#
+        );
+        let right = &document.elements["zed-svg-preview-2"];
+        assert_eq!(
+            &SOURCE[right.clone()],
+            r#"<rect x="50" y="0" width="50" height="50" fill="blue" title="a > b"/>"#
+        );
+    }
+
+    #[test]
+    fn test_parse_error_position() {
+        let source = "<svg xmlns=\"http://www.w3.org/2000/svg\">\n  <rect></circle>\n</svg>";
+        let error = SvgDocument::parse(source).err().unwrap();
+        let (row, column) = error.position.unwrap();
+        assert_eq!(row, 2);
+        assert_eq!(error.offset, Some(offset_for_position(source, row, column)));
+        assert!(error.offset.unwrap() > source.find("<rect>").unwrap());
+    }
+
+    #[test]
+    fn test_element_at() {
+        let document = SvgDocument::parse(SOURCE).unwrap();
+        let rendered = document.render(1.).unwrap();
+        assert_eq!((rendered.width, rendered.height), (100., 50.));
+
+        let left = document.element_at(&rendered, 25., 25.).unwrap();
+        assert!(SOURCE[left].contains("id=\"left\""));
+        let right = document.element_at(&rendered, 75., 25.).unwrap();
+        assert!(SOURCE[right].contains("fill=\"blue\""));
+    }
+
+    #[test]
+    fn test_offset_for_position() {
+        let source = "ab\ncdé\nf";
+        assert_eq!(offset_for_position(source, 1, 1), 0);
+        assert_eq!(offset_for_position(source, 2, 3), 5);
+        assert_eq!(offset_for_position(source, 3, 1), 8);
+        assert_eq!(offset_for_position(source, 9, 1), source.len());
+    }
+}
@@ -0,0 +1,14 @@
+use gpui::{actions, AppContext};
+use workspace::Workspace;
+
+pub mod svg_document;
+pub mod svg_preview_view;
+
+actions!(svg, [OpenPreview, OpenPreviewToTheSide]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, cx| {
+        svg_preview_view::SvgPreviewView::register(workspace, cx);
+    })
+    .detach();
+}
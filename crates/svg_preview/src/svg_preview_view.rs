@@ -0,0 +1,304 @@
+use std::{cell::Cell, ops::Range, rc::Rc, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use editor::{scroll::Autoscroll, Bias, Editor, EditorEvent};
+use gpui::{
+    canvas, img, AppContext, Bounds, EventEmitter, FocusHandle, FocusableView, MouseButton,
+    MouseDownEvent, ObjectFit, Render, Subscription, Task, View, ViewContext,
+};
+use ui::prelude::*;
+use workspace::item::{Item, ItemEvent};
+use workspace::{Pane, Workspace};
+
+use crate::svg_document::{RenderedSvg, SvgDocument, SvgError};
+use crate::{OpenPreview, OpenPreviewToTheSide};
+
+const RERENDER_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Shows how an SVG file looks, updated as it's edited. Clicking on the image
+/// selects the element drawn there in the file.
+pub struct SvgPreviewView {
+    editor: View<Editor>,
+    focus_handle: FocusHandle,
+    /// The last version of the file that could be rendered, which is kept while
+    /// the file has errors.
+    document: Option<Arc<SvgDocument>>,
+    rendered: Option<Arc<RenderedSvg>>,
+    error: Option<SvgError>,
+    /// The bounds the image is fitted into, as of the last time it was painted.
+    image_area: Rc<Cell<Bounds<Pixels>>>,
+    tab_description: Option<String>,
+    rendering_task: Option<Task<Result<()>>>,
+    _subscription: Subscription,
+}
+
+impl SvgPreviewView {
+    pub fn register(workspace: &mut Workspace, _cx: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &OpenPreview, cx| {
+            if let Some(editor) = Self::resolve_active_item_as_svg_editor(workspace, cx) {
+                let pane = workspace.active_pane().clone();
+                Self::open_in_pane(editor, &pane, true, cx);
+            }
+        });
+
+        workspace.register_action(|workspace, _: &OpenPreviewToTheSide, cx| {
+            if let Some(editor) = Self::resolve_active_item_as_svg_editor(workspace, cx) {
+                let pane = workspace
+                    .find_pane_in_direction(workspace::SplitDirection::Right, cx)
+                    .unwrap_or_else(|| {
+                        workspace.split_pane(
+                            workspace.active_pane().clone(),
+                            workspace::SplitDirection::Right,
+                            cx,
+                        )
+                    });
+                Self::open_in_pane(editor.clone(), &pane, false, cx);
+                editor.focus_handle(cx).focus(cx);
+            }
+        });
+    }
+
+    /// Shows the preview of an editor in a pane, reusing the one already there.
+    fn open_in_pane(
+        editor: View<Editor>,
+        pane: &View<Pane>,
+        focus: bool,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        pane.update(cx, |pane, cx| {
+            let existing_view = pane
+                .items_of_type::<SvgPreviewView>()
+                .find(|view| view.read(cx).editor == editor);
+            if let Some(index) = existing_view.and_then(|view| pane.index_for_item(&view)) {
+                pane.activate_item(index, focus, focus, cx);
+            } else {
+                let view = cx.new_view(|cx| SvgPreviewView::new(editor, cx));
+                pane.add_item(Box::new(view), focus, focus, None, cx);
+            }
+        });
+    }
+
+    pub fn resolve_active_item_as_svg_editor(
+        workspace: &Workspace,
+        cx: &mut ViewContext<Workspace>,
+    ) -> Option<View<Editor>> {
+        let editor = workspace
+            .active_item(cx)
+            .and_then(|item| item.act_as::<Editor>(cx))?;
+        Self::is_svg_file(&editor, cx).then_some(editor)
+    }
+
+    /// Returns whether the editor shows an SVG file, by its extension.
+    pub fn is_svg_file(editor: &View<Editor>, cx: &AppContext) -> bool {
+        let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+            return false;
+        };
+        let buffer = buffer.read(cx);
+        buffer
+            .file()
+            .and_then(|file| file.path().extension())
+            .map_or(false, |extension| extension.eq_ignore_ascii_case("svg"))
+    }
+
+    fn new(editor: View<Editor>, cx: &mut ViewContext<Self>) -> Self {
+        let subscription = cx.subscribe(&editor, |this, _, event: &EditorEvent, cx| {
+            if let EditorEvent::BufferEdited = event {
+                this.render_document(true, cx);
+            }
+        });
+        let tab_description = editor
+            .read(cx)
+            .tab_description(0, cx)
+            .map(|description| format!("Preview {description}"));
+
+        let mut this = Self {
+            editor,
+            focus_handle: cx.focus_handle(),
+            document: None,
+            rendered: None,
+            error: None,
+            image_area: Rc::new(Cell::new(Bounds::default())),
+            tab_description,
+            rendering_task: None,
+            _subscription: subscription,
+        };
+        this.render_document(false, cx);
+        this
+    }
+
+    fn render_document(&mut self, wait_for_debounce: bool, cx: &mut ViewContext<Self>) {
+        let editor = self.editor.clone();
+        self.rendering_task = Some(cx.spawn(|view, mut cx| async move {
+            if wait_for_debounce {
+                // Wait for the user to stop typing
+                cx.background_executor().timer(RERENDER_DEBOUNCE).await;
+            }
+
+            let (text, scale_factor) = view.update(&mut cx, |_, cx| {
+                let text = editor.read(cx).buffer().read(cx).snapshot(cx).text();
+                (text, cx.scale_factor())
+            })?;
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    let document = SvgDocument::parse(&text)?;
+                    let rendered = document.render(scale_factor)?;
+                    Ok::<_, SvgError>((Arc::new(document), Arc::new(rendered)))
+                })
+                .await;
+            view.update(&mut cx, |view, cx| {
+                match result {
+                    Ok((document, rendered)) => {
+                        view.document = Some(document);
+                        view.rendered = Some(rendered);
+                        view.error = None;
+                    }
+                    Err(error) => view.error = Some(error),
+                }
+                cx.notify();
+            })
+        }));
+    }
+
+    /// Selects a range of the file in the editor, and scrolls it into view.
+    fn select_in_editor(&self, range: Range<usize>, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, |editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            // The file may have been edited since it was rendered.
+            let start = snapshot.clip_offset(range.start.min(snapshot.len()), Bias::Left);
+            let end = snapshot.clip_offset(range.end.min(snapshot.len()), Bias::Right);
+            editor.change_selections(Some(Autoscroll::center()), cx, |selections| {
+                selections.select_ranges([start..end])
+            });
+        });
+    }
+
+    /// Returns the scale the image is shown at, and the bounds it's shown in, fitted
+    /// into the area of the preview.
+    fn image_bounds(&self, rendered: &RenderedSvg) -> (f32, Bounds<Pixels>) {
+        let area = self.image_area.get();
+        let scale = (area.size.width.0 / rendered.width).min(area.size.height.0 / rendered.height);
+        let size = gpui::size(px(rendered.width * scale), px(rendered.height * scale));
+        (scale, Bounds::centered_at(area.center(), size))
+    }
+
+    fn handle_mouse_down(&mut self, event: &MouseDownEvent, cx: &mut ViewContext<Self>) {
+        let (Some(document), Some(rendered)) = (&self.document, &self.rendered) else {
+            return;
+        };
+        let (scale, bounds) = self.image_bounds(rendered);
+        if scale <= 0. || !bounds.contains(&event.position) {
+            return;
+        }
+        let position = event.position - bounds.origin;
+        let x = position.x.0 / scale;
+        let y = position.y.0 / scale;
+        if let Some(range) = document.element_at(rendered, x, y) {
+            self.select_in_editor(range, cx);
+        }
+    }
+
+    fn render_error(&self, error: &SvgError, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let message = match error.position {
+            Some((row, column)) => format!("Line {row}, column {column}: {}", error.message),
+            None => error.message.clone(),
+        };
+        h_flex()
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().status().error_background)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .overflow_hidden()
+                    .child(
+                        Icon::new(IconName::XCircle)
+                            .size(IconSize::Small)
+                            .color(Color::Error),
+                    )
+                    .child(Label::new(message).size(LabelSize::Small)),
+            )
+            .children(error.offset.map(|offset| {
+                Button::new("go-to-error", "Go to Error")
+                    .style(ButtonStyle::Subtle)
+                    .label_size(LabelSize::Small)
+                    .on_click(cx.listener(move |this, _, cx| {
+                        this.select_in_editor(offset..offset, cx);
+                        this.editor.focus_handle(cx).focus(cx);
+                    }))
+            }))
+    }
+}
+
+impl FocusableView for SvgPreviewView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<()> for SvgPreviewView {}
+
+impl Item for SvgPreviewView {
+    type Event = ();
+
+    fn tab_icon(&self, _cx: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::Eye))
+    }
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some(
+            self.tab_description
+                .clone()
+                .unwrap_or_else(|| "SVG Preview".to_string())
+                .into(),
+        )
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("svg preview")
+    }
+
+    fn to_item_events(_event: &Self::Event, _f: impl FnMut(ItemEvent)) {}
+}
+
+impl Render for SvgPreviewView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let image_area = self.image_area.clone();
+        let record_image_area = canvas(move |bounds, _| image_area.set(bounds), |_, _, _| {})
+            .size_full()
+            .absolute()
+            .top_0()
+            .left_0();
+
+        v_flex()
+            .key_context("SvgPreview")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .children(
+                self.error
+                    .as_ref()
+                    .map(|error| self.render_error(error, cx)),
+            )
+            .child(
+                div().flex_1().w_full().p_4().child(
+                    div()
+                        .relative()
+                        .size_full()
+                        .cursor_pointer()
+                        .on_mouse_down(MouseButton::Left, cx.listener(Self::handle_mouse_down))
+                        .child(record_image_area)
+                        .children(self.rendered.as_ref().map(|rendered| {
+                            img(rendered.image.clone())
+                                .size_full()
+                                .object_fit(ObjectFit::Contain)
+                        })),
+                ),
+            )
+    }
+}
@@ -0,0 +1,403 @@
+use anyhow::{anyhow, Context};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Parses the output of a task into problems, shown as diagnostics in the project.
+///
+/// Either the name of a preset, such as `$rustc`, or a custom definition.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcher {
+    /// A built-in problem matcher: `$rustc`, `$gcc`, `$tsc`, `$eslint-compact` or `$go`.
+    Preset(String),
+    /// A problem matcher defined with regular expressions.
+    Custom(ProblemMatcherDefinition),
+}
+
+/// A problem matcher defined with regular expressions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemMatcherDefinition {
+    /// The pattern matching the lines of a problem, or a list of patterns matching
+    /// consecutive lines, for problems reported over multiple lines.
+    pub pattern: ProblemPatterns,
+    /// The severity of problems whose pattern doesn't capture it.
+    #[serde(default)]
+    pub severity: ProblemSeverity,
+    /// The source shown along the problems, defaults to the label of the task.
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// One or more patterns matching consecutive lines of output.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemPatterns {
+    /// A pattern matching a single line.
+    Single(ProblemPattern),
+    /// Patterns matching consecutive lines.
+    Multiple(Vec<ProblemPattern>),
+}
+
+/// A regular expression matching a line of output, along with the indices of its
+/// capture groups containing the parts of the problem.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemPattern {
+    /// The regular expression matching the line.
+    pub regexp: String,
+    /// The group containing the path of the file, relative to the working directory of the task.
+    #[serde(default)]
+    pub file: Option<usize>,
+    /// The group containing the 1-based line of the problem.
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// The group containing the 1-based column of the problem.
+    #[serde(default)]
+    pub column: Option<usize>,
+    /// The group containing the severity, such as `error` or `warning`.
+    #[serde(default)]
+    pub severity: Option<usize>,
+    /// The group containing the code of the problem.
+    #[serde(default)]
+    pub code: Option<usize>,
+    /// The group containing the message.
+    #[serde(default)]
+    pub message: Option<usize>,
+}
+
+/// How severe a problem is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    /// An error.
+    #[default]
+    Error,
+    /// A warning.
+    Warning,
+    /// An informational message.
+    Info,
+    /// A hint.
+    Hint,
+}
+
+/// A problem found in the output of a task.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    /// The path of the file, as printed by the task.
+    pub path: String,
+    /// The 1-based line of the problem.
+    pub line: u32,
+    /// The 1-based column of the problem, if reported.
+    pub column: Option<u32>,
+    /// How severe the problem is.
+    pub severity: ProblemSeverity,
+    /// The code of the problem, if reported.
+    pub code: Option<String>,
+    /// The message describing the problem.
+    pub message: String,
+}
+
+impl ProblemMatcher {
+    /// Compiles the matcher into a scanner of task output.
+    pub fn scanner(&self) -> anyhow::Result<ProblemScanner> {
+        let definition = match self {
+            Self::Preset(name) => {
+                preset(name).ok_or_else(|| anyhow!("unknown problem matcher {name}"))?
+            }
+            Self::Custom(definition) => definition.clone(),
+        };
+        let patterns = match definition.pattern {
+            ProblemPatterns::Single(pattern) => vec![pattern],
+            ProblemPatterns::Multiple(patterns) => patterns,
+        };
+        anyhow::ensure!(!patterns.is_empty(), "problem matcher has no patterns");
+        let patterns = patterns
+            .into_iter()
+            .map(|pattern| {
+                let regex = Regex::new(&pattern.regexp)
+                    .with_context(|| format!("invalid problem pattern {:?}", pattern.regexp))?;
+                Ok((regex, pattern))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(ProblemScanner {
+            patterns,
+            default_severity: definition.severity,
+            source: definition.source,
+            next_pattern: 0,
+            pending: PartialProblem::default(),
+        })
+    }
+}
+
+/// Finds problems in the output of a task, line by line.
+pub struct ProblemScanner {
+    patterns: Vec<(Regex, ProblemPattern)>,
+    default_severity: ProblemSeverity,
+    source: Option<String>,
+    next_pattern: usize,
+    pending: PartialProblem,
+}
+
+#[derive(Default)]
+struct PartialProblem {
+    path: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+    severity: Option<ProblemSeverity>,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl ProblemScanner {
+    /// The source of the problems, if the matcher specifies one.
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// Scans all lines of an output.
+    pub fn scan<'a>(&mut self, lines: impl IntoIterator<Item = &'a str>) -> Vec<Problem> {
+        lines
+            .into_iter()
+            .filter_map(|line| self.scan_line(line))
+            .collect()
+    }
+
+    /// Scans the next line of output, returning a problem when the line completes one.
+    pub fn scan_line(&mut self, line: &str) -> Option<Problem> {
+        if self.next_pattern > 0 {
+            let (regex, pattern) = &self.patterns[self.next_pattern];
+            if let Some(captures) = regex.captures(line) {
+                self.pending.merge(pattern, &captures);
+                self.next_pattern += 1;
+                return self.finish_if_complete();
+            }
+            self.next_pattern = 0;
+        }
+
+        let (regex, pattern) = &self.patterns[0];
+        let captures = regex.captures(line)?;
+        self.pending = PartialProblem::default();
+        self.pending.merge(pattern, &captures);
+        self.next_pattern = 1;
+        self.finish_if_complete()
+    }
+
+    fn finish_if_complete(&mut self) -> Option<Problem> {
+        if self.next_pattern < self.patterns.len() {
+            return None;
+        }
+        self.next_pattern = 0;
+        let pending = std::mem::take(&mut self.pending);
+        Some(Problem {
+            path: pending.path?,
+            line: pending.line?,
+            column: pending.column,
+            severity: pending.severity.unwrap_or(self.default_severity),
+            code: pending.code,
+            message: pending.message.unwrap_or_default(),
+        })
+    }
+}
+
+impl PartialProblem {
+    fn merge(&mut self, pattern: &ProblemPattern, captures: &regex::Captures) {
+        let group = |ix: Option<usize>| {
+            ix.and_then(|ix| captures.get(ix))
+                .map(|capture| capture.as_str().trim())
+                .filter(|capture| !capture.is_empty())
+        };
+        if let Some(path) = group(pattern.file) {
+            self.path = Some(path.to_string());
+        }
+        if let Some(line) = group(pattern.line).and_then(|line| line.parse().ok()) {
+            self.line = Some(line);
+        }
+        if let Some(column) = group(pattern.column).and_then(|column| column.parse().ok()) {
+            self.column = Some(column);
+        }
+        if let Some(severity) = group(pattern.severity).and_then(parse_severity) {
+            self.severity = Some(severity);
+        }
+        if let Some(code) = group(pattern.code) {
+            self.code = Some(code.to_string());
+        }
+        if let Some(message) = group(pattern.message) {
+            self.message = Some(message.to_string());
+        }
+    }
+}
+
+fn parse_severity(severity: &str) -> Option<ProblemSeverity> {
+    let severity = severity.to_lowercase();
+    if severity.starts_with("err") || severity.starts_with("fatal") {
+        Some(ProblemSeverity::Error)
+    } else if severity.starts_with("warn") {
+        Some(ProblemSeverity::Warning)
+    } else if severity.starts_with("info") || severity.starts_with("note") {
+        Some(ProblemSeverity::Info)
+    } else if severity.starts_with("hint") || severity.starts_with("help") {
+        Some(ProblemSeverity::Hint)
+    } else {
+        None
+    }
+}
+
+fn preset(name: &str) -> Option<ProblemMatcherDefinition> {
+    let (source, patterns) = match name {
+        "$rustc" => (
+            "rustc",
+            vec![
+                ProblemPattern {
+                    regexp: r"^(error|warning)(?:\[(\w+)\])?: (.*)$".to_string(),
+                    severity: Some(1),
+                    code: Some(2),
+                    message: Some(3),
+                    ..Default::default()
+                },
+                ProblemPattern {
+                    regexp: r"^\s*--> (.+?):(\d+):(\d+)$".to_string(),
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    ..Default::default()
+                },
+            ],
+        ),
+        "$gcc" => (
+            "gcc",
+            vec![ProblemPattern {
+                regexp: r"^(.+?):(\d+):(\d+):\s+(?:fatal\s+)?(error|warning|note):\s+(.*)$"
+                    .to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                message: Some(5),
+                ..Default::default()
+            }],
+        ),
+        "$tsc" => (
+            "tsc",
+            vec![ProblemPattern {
+                regexp: r"^(.+?)[(:](\d+)[,:](\d+)\)?:?\s+-?\s*(error|warning|info)\s+(TS\d+)\s*:\s*(.*)$"
+                    .to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                code: Some(5),
+                message: Some(6),
+            }],
+        ),
+        "$eslint-compact" => (
+            "eslint",
+            vec![ProblemPattern {
+                regexp: r"^(.+?): line (\d+), col (\d+), (Error|Warning) - (.*?)(?: \((.+)\))?$"
+                    .to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                message: Some(5),
+                code: Some(6),
+            }],
+        ),
+        "$go" => (
+            "go",
+            vec![ProblemPattern {
+                regexp: r"^\s*([^:\s]+\.go):(\d+):(?:(\d+):)?\s*(.*)$".to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                message: Some(4),
+                ..Default::default()
+            }],
+        ),
+        _ => return None,
+    };
+    Some(ProblemMatcherDefinition {
+        pattern: ProblemPatterns::Multiple(patterns),
+        severity: ProblemSeverity::Error,
+        source: Some(source.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rustc_problem_matcher() {
+        let output = r#"   Compiling example v0.1.0 (/example)
+error[E0425]: cannot find value `x` in this scope
+ --> src/main.rs:2:13
+  |
+2 |     let y = x;
+  |             ^ not found in this scope
+
+warning: unused variable: `y`
+ --> src/lib.rs:10:9
+error: aborting due to 1 previous error"#;
+        let mut scanner = ProblemMatcher::Preset("$rustc".to_string())
+            .scanner()
+            .unwrap();
+        assert_eq!(scanner.source(), Some("rustc"));
+        assert_eq!(
+            scanner.scan(output.lines()),
+            vec![
+                Problem {
+                    path: "src/main.rs".to_string(),
+                    line: 2,
+                    column: Some(13),
+                    severity: ProblemSeverity::Error,
+                    code: Some("E0425".to_string()),
+                    message: "cannot find value `x` in this scope".to_string(),
+                },
+                Problem {
+                    path: "src/lib.rs".to_string(),
+                    line: 10,
+                    column: Some(9),
+                    severity: ProblemSeverity::Warning,
+                    code: None,
+                    message: "unused variable: `y`".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_custom_problem_matcher() {
+        let matcher: ProblemMatcher = serde_json_lenient::from_str(
+            r#"{
+                "pattern": {
+                    "regexp": "^(.*)\\((\\d+)\\): (.*)$",
+                    "file": 1,
+                    "line": 2,
+                    "message": 3
+                },
+                "severity": "warning",
+                "source": "lint"
+            }"#,
+        )
+        .unwrap();
+        let mut scanner = matcher.scanner().unwrap();
+        assert_eq!(scanner.source(), Some("lint"));
+        assert_eq!(
+            scanner.scan(["ok", "a/b.py(7): line too long", "a/c.py(x): not a line"]),
+            vec![Problem {
+                path: "a/b.py".to_string(),
+                line: 7,
+                column: None,
+                severity: ProblemSeverity::Warning,
+                code: None,
+                message: "line too long".to_string(),
+            }]
+        );
+
+        assert!(ProblemMatcher::Preset("$unknown".to_string())
+            .scanner()
+            .is_err());
+    }
+}
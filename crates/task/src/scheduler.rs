@@ -0,0 +1,264 @@
+use std::{future::Future, sync::Arc};
+
+use anyhow::{anyhow, bail};
+use collections::HashMap;
+use futures::{
+    future::{try_join_all, LocalBoxFuture, Shared},
+    FutureExt as _,
+};
+
+use crate::{DependsOrder, TaskTemplate};
+
+/// The tasks to run for a task and its dependencies, declared with [`TaskTemplate::depends_on`].
+///
+/// Every task is run at most once, even if several tasks depend on it. Each task is
+/// run after its dependencies, which are run one after the other or all at once,
+/// depending on its [`TaskTemplate::depends_order`]. Compound tasks, without a command,
+/// only run their dependencies.
+pub struct TaskGraph<T> {
+    nodes: Vec<TaskNode<T>>,
+    /// The indices of the nodes, dependencies first.
+    order: Vec<usize>,
+}
+
+struct TaskNode<T> {
+    source: T,
+    template: TaskTemplate,
+    dependencies: Vec<usize>,
+}
+
+type SharedResult = Result<(), Arc<anyhow::Error>>;
+
+impl<T: Clone> TaskGraph<T> {
+    /// Finds the dependencies of the root task among the available tasks, by label.
+    ///
+    /// Fails if a dependency can't be found, or if tasks depend on each other.
+    pub fn new(root: (T, TaskTemplate), available: &[(T, TaskTemplate)]) -> anyhow::Result<Self> {
+        let mut graph = Self {
+            nodes: Vec::new(),
+            order: Vec::new(),
+        };
+        let mut indices_by_label = HashMap::default();
+        let mut stack = Vec::new();
+        graph.add_node(root.0, root.1, available, &mut indices_by_label, &mut stack)?;
+        Ok(graph)
+    }
+
+    fn add_node(
+        &mut self,
+        source: T,
+        template: TaskTemplate,
+        available: &[(T, TaskTemplate)],
+        indices_by_label: &mut HashMap<String, usize>,
+        stack: &mut Vec<String>,
+    ) -> anyhow::Result<usize> {
+        if let Some(position) = stack.iter().position(|label| *label == template.label) {
+            let mut cycle = stack[position..].to_vec();
+            cycle.push(template.label);
+            bail!("tasks depend on each other: {}", cycle.join(" -> "));
+        }
+        if let Some(ix) = indices_by_label.get(&template.label) {
+            return Ok(*ix);
+        }
+
+        stack.push(template.label.clone());
+        let mut dependencies = Vec::new();
+        for label in &template.depends_on {
+            let (source, dependency) = available
+                .iter()
+                .find(|(_, task)| task.label == *label)
+                .cloned()
+                .ok_or_else(|| {
+                    anyhow!(
+                        "task `{}` depends on unknown task `{label}`",
+                        template.label
+                    )
+                })?;
+            dependencies.push(self.add_node(
+                source,
+                dependency,
+                available,
+                indices_by_label,
+                stack,
+            )?);
+        }
+        stack.pop();
+
+        let ix = self.nodes.len();
+        indices_by_label.insert(template.label.clone(), ix);
+        self.nodes.push(TaskNode {
+            source,
+            template,
+            dependencies,
+        });
+        self.order.push(ix);
+        Ok(ix)
+    }
+
+    /// The tasks running a command, dependencies first.
+    pub fn tasks(&self) -> impl Iterator<Item = (&T, &TaskTemplate)> {
+        self.order
+            .iter()
+            .map(|ix| &self.nodes[*ix])
+            .filter(|node| !node.template.command.trim().is_empty())
+            .map(|node| (&node.source, &node.template))
+    }
+
+    /// Runs the root task after its dependencies, stopping at the first task that fails.
+    ///
+    /// `run_task` completes once the task is done, or for background tasks, once it's ready.
+    pub async fn run<F, Fut>(&self, run_task: F) -> anyhow::Result<()>
+    where
+        F: Fn(&T, &TaskTemplate) -> Fut,
+        Fut: Future<Output = anyhow::Result<()>>,
+    {
+        let run_task = &run_task;
+        let mut runs: Vec<Option<Shared<LocalBoxFuture<'_, SharedResult>>>> =
+            self.nodes.iter().map(|_| None).collect();
+        for &ix in &self.order {
+            let node = &self.nodes[ix];
+            let dependencies = node
+                .dependencies
+                .iter()
+                .map(|dependency| runs[*dependency].clone().expect("dependencies run first"))
+                .collect::<Vec<_>>();
+            let run = async move {
+                match node.template.depends_order {
+                    DependsOrder::Sequence => {
+                        for dependency in dependencies {
+                            dependency.await?;
+                        }
+                    }
+                    DependsOrder::Parallel => {
+                        try_join_all(dependencies).await?;
+                    }
+                }
+                if !node.template.command.trim().is_empty() {
+                    run_task(&node.source, &node.template)
+                        .await
+                        .map_err(Arc::new)?;
+                }
+                Ok(())
+            };
+            runs[ix] = Some(run.boxed_local().shared());
+        }
+
+        let root = self.order.last().and_then(|ix| runs[*ix].clone());
+        match root {
+            Some(root) => root.await.map_err(|error| anyhow!("{error:#}")),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, task::Poll};
+
+    use futures::{executor::block_on, future::poll_fn};
+
+    use super::*;
+
+    fn task(label: &str, command: &str, depends_on: &[&str]) -> ((), TaskTemplate) {
+        let template = TaskTemplate {
+            label: label.to_string(),
+            command: command.to_string(),
+            depends_on: depends_on.iter().map(|label| label.to_string()).collect(),
+            ..TaskTemplate::default()
+        };
+        ((), template)
+    }
+
+    #[test]
+    fn test_task_graph_errors() {
+        let available = [
+            task("a", "a", &["b"]),
+            task("b", "b", &["c"]),
+            task("c", "c", &["a"]),
+        ];
+        let error = TaskGraph::new(available[0].clone(), &available)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "tasks depend on each other: a -> b -> c -> a"
+        );
+
+        let error = TaskGraph::new(task("d", "d", &["e"]), &available)
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "task `d` depends on unknown task `e`");
+    }
+
+    async fn yield_once() {
+        let mut yielded = false;
+        poll_fn(|cx| {
+            if yielded {
+                Poll::Ready(())
+            } else {
+                yielded = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    #[test]
+    fn test_run_task_graph() {
+        let available = [
+            task("build", "cargo build", &["generate", "fetch"]),
+            task("generate", "generate", &["fetch"]),
+            task("fetch", "fetch", &[]),
+            task("lint", "lint", &[]),
+            task("check", "", &["build", "lint"]),
+        ];
+        let mut root = available[4].clone();
+        root.1.depends_order = DependsOrder::Parallel;
+        let graph = TaskGraph::new(root, &available).unwrap();
+        assert_eq!(
+            graph
+                .tasks()
+                .map(|(_, task)| task.label.as_str())
+                .collect::<Vec<_>>(),
+            vec!["fetch", "generate", "build", "lint"]
+        );
+
+        // Shared dependencies run once, and the parallel dependencies of the compound
+        // task start together.
+        let started = Rc::new(RefCell::new(Vec::new()));
+        block_on(graph.run(|_, task| {
+            let started = started.clone();
+            let label = task.label.clone();
+            async move {
+                started.borrow_mut().push(label);
+                yield_once().await;
+                Ok(())
+            }
+        }))
+        .unwrap();
+        let started = started.borrow().clone();
+        assert_eq!(started.len(), 4);
+        assert_eq!(started[..2], ["fetch", "lint"]);
+        assert_eq!(started[2..], ["generate", "build"]);
+
+        // A failed task stops the tasks depending on it.
+        let graph = TaskGraph::new(available[0].clone(), &available).unwrap();
+        let started = Rc::new(RefCell::new(Vec::new()));
+        let error = block_on(graph.run(|_, task| {
+            let started = started.clone();
+            let label = task.label.clone();
+            async move {
+                started.borrow_mut().push(label.clone());
+                if label == "generate" {
+                    bail!("`{label}` failed");
+                }
+                Ok(())
+            }
+        }))
+        .err()
+        .unwrap();
+        assert_eq!(error.to_string(), "`generate` failed");
+        assert_eq!(*started.borrow(), ["fetch", "generate"]);
+    }
+}
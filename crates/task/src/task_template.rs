@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{borrow::Cow, path::PathBuf};
 
 use anyhow::{bail, Context};
 use collections::{HashMap, HashSet};
@@ -8,10 +8,13 @@ use sha2::{Digest, Sha256};
 use util::{truncate_and_remove_front, ResultExt};
 
 use crate::{
-    ResolvedTask, Shell, SpawnInTerminal, TaskContext, TaskId, VariableName,
+    ProblemMatcher, ResolvedTask, Shell, SpawnInTerminal, TaskContext, TaskId, VariableName,
     ZED_VARIABLE_NAME_PREFIX,
 };
 
+/// A prefix of the variables referencing a [`TaskInput`], as in `${input:name}`.
+const INPUT_VARIABLE_PREFIX: &str = "input:";
+
 /// A template definition of a Zed task to run.
 /// May use the [`VariableName`] to get the corresponding substitutions into its fields.
 ///
@@ -57,6 +60,56 @@ pub struct TaskTemplate {
     /// Which shell to use when spawning the task.
     #[serde(default)]
     pub shell: Shell,
+    /// Parses the output of the task into diagnostics shown in the project, either with
+    /// a preset such as `$rustc` or with custom regular expressions.
+    #[serde(default)]
+    pub problem_matcher: Option<ProblemMatcher>,
+    /// The labels of the tasks to run before this one. A task without a command, but with
+    /// dependencies, is a compound task only running its dependencies.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Whether to run the dependencies one after the other (default) or all at once.
+    #[serde(default)]
+    pub depends_order: DependsOrder,
+    /// Whether the task keeps running in the background, like a watcher or a server.
+    /// Tasks depending on it start once it's ready, rather than once it finishes.
+    #[serde(default)]
+    pub is_background: bool,
+    /// A regular expression matching the line of output signaling that the background
+    /// task is ready. Without it, the background task is ready once started.
+    #[serde(default)]
+    pub ready_pattern: Option<String>,
+    /// Values to ask the user for before spawning the task, referenced as `${input:id}`.
+    #[serde(default)]
+    pub inputs: Vec<TaskInput>,
+}
+
+/// A value the user is asked for before spawning a task, either picked among options or typed in.
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TaskInput {
+    /// The name of the input, as referenced in `${input:id}`.
+    pub id: String,
+    /// The prompt to show when asking for the value.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The values to pick from. Without options, the value is typed in.
+    #[serde(default)]
+    pub options: Vec<String>,
+    /// The value initially picked or typed in.
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+/// How to run the dependencies of a task.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependsOrder {
+    /// Run the dependencies one after the other, in the order they're listed.
+    #[default]
+    Sequence,
+    /// Run all dependencies at once.
+    Parallel,
 }
 
 /// What to do with the terminal pane and tab, after the command was started.
@@ -104,15 +157,20 @@ impl TaskTemplate {
     /// If any replacement fails or the new string substitutions still have [`ZED_VARIABLE_NAME_PREFIX`],
     /// `None` is returned.
     ///
+    /// Inputs without a value in the context are left as is, and listed in [`ResolvedTask::missing_inputs`].
+    ///
     /// Every [`ResolvedTask`] gets a [`TaskId`], based on the `id_base` (to avoid collision with various task sources),
     /// and hashes of its template and [`TaskContext`], see [`ResolvedTask`] fields' documentation for more details.
     pub fn resolve_task(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
-        if self.label.trim().is_empty() || self.command.trim().is_empty() {
+        if self.label.trim().is_empty()
+            || (self.command.trim().is_empty() && self.depends_on.is_empty())
+        {
             return None;
         }
 
         let mut variable_names = HashMap::default();
         let mut substituted_variables = HashSet::default();
+        let mut missing_inputs = HashSet::default();
         let task_variables = cx
             .task_variables
             .0
@@ -133,6 +191,7 @@ impl TaskTemplate {
                     &task_variables,
                     &variable_names,
                     &mut substituted_variables,
+                    &mut missing_inputs,
                 )?;
                 Some(PathBuf::from(substitured_cwd))
             }
@@ -144,6 +203,7 @@ impl TaskTemplate {
             &truncated_variables,
             &variable_names,
             &mut substituted_variables,
+            &mut missing_inputs,
         )?
         .lines()
         .fold(String::new(), |mut string, line| {
@@ -160,18 +220,21 @@ impl TaskTemplate {
             &task_variables,
             &variable_names,
             &mut substituted_variables,
+            &mut missing_inputs,
         )?;
         let command = substitute_all_template_variables_in_str(
             &self.command,
             &task_variables,
             &variable_names,
             &mut substituted_variables,
+            &mut missing_inputs,
         )?;
         let args_with_substitutions = substitute_all_template_variables_in_vec(
             &self.args,
             &task_variables,
             &variable_names,
             &mut substituted_variables,
+            &mut missing_inputs,
         )?;
 
         let task_hash = to_hex_hash(&self)
@@ -195,6 +258,7 @@ impl TaskTemplate {
                 &task_variables,
                 &variable_names,
                 &mut substituted_variables,
+                &mut missing_inputs,
             )?;
 
             // Last step: set the task variables as environment variables too
@@ -202,10 +266,27 @@ impl TaskTemplate {
             env
         };
 
+        // Inputs are asked for in the order they're declared, followed by the undeclared ones.
+        let mut declared_inputs = self
+            .inputs
+            .iter()
+            .filter(|input| missing_inputs.remove(&input.id))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut undeclared_inputs = missing_inputs.into_iter().collect::<Vec<_>>();
+        undeclared_inputs.sort();
+        declared_inputs.extend(undeclared_inputs.into_iter().map(|id| TaskInput {
+            id,
+            ..TaskInput::default()
+        }));
+        let missing_inputs = declared_inputs;
+
         Some(ResolvedTask {
             id: id.clone(),
             substituted_variables,
+            missing_inputs,
             original_task: self.clone(),
+            task_context: cx.clone(),
             resolved_label: full_label.clone(),
             resolved: Some(SpawnInTerminal {
                 id,
@@ -228,6 +309,7 @@ impl TaskTemplate {
                 reveal: self.reveal,
                 hide: self.hide,
                 shell: self.shell.clone(),
+                problem_matcher: self.problem_matcher.clone(),
             }),
         })
     }
@@ -259,11 +341,25 @@ fn substitute_all_template_variables_in_str<A: AsRef<str>>(
     task_variables: &HashMap<String, A>,
     variable_names: &HashMap<String, VariableName>,
     substituted_variables: &mut HashSet<VariableName>,
+    missing_inputs: &mut HashSet<String>,
 ) -> Option<String> {
     let substituted_string = shellexpand::env_with_context(template_str, |var| {
+        if let Some(input_id) = var.strip_prefix(INPUT_VARIABLE_PREFIX) {
+            let input_variable = VariableName::Input(Cow::Owned(input_id.to_owned()));
+            if let Some(value) = task_variables.get(&input_variable.to_string()) {
+                let value = value.as_ref().to_owned();
+                substituted_variables.insert(input_variable);
+                return Ok(Some(value));
+            }
+            // Keep the input reference until the user enters a value for it.
+            missing_inputs.insert(input_id.to_owned());
+            return Ok(Some(format!("${{{var}}}")));
+        }
         // Colons denote a default value in case the variable is not set. We want to preserve that default, as otherwise shellexpand will substitute it for us.
         let colon_position = var.find(':').unwrap_or(var.len());
         let (variable_name, default) = var.split_at(colon_position);
+        let aliased_name = aliased_variable(variable_name).map(|name| name.to_string());
+        let variable_name = aliased_name.as_deref().unwrap_or(variable_name);
         if let Some(name) = task_variables.get(variable_name) {
             if let Some(substituted_variable) = variable_names.get(variable_name) {
                 substituted_variables.insert(substituted_variable.clone());
@@ -291,11 +387,23 @@ fn substitute_all_template_variables_in_str<A: AsRef<str>>(
     Some(substituted_string.into_owned())
 }
 
+/// Lowercase names of the task variables, as in `${selected_text}`.
+fn aliased_variable(name: &str) -> Option<VariableName> {
+    match name {
+        "selected_text" => Some(VariableName::SelectedText),
+        "current_symbol" => Some(VariableName::Symbol),
+        "relative_file" => Some(VariableName::RelativeFile),
+        "workspace_root" => Some(VariableName::WorktreeRoot),
+        _ => None,
+    }
+}
+
 fn substitute_all_template_variables_in_vec(
     template_strs: &[String],
     task_variables: &HashMap<String, &str>,
     variable_names: &HashMap<String, VariableName>,
     substituted_variables: &mut HashSet<VariableName>,
+    missing_inputs: &mut HashSet<String>,
 ) -> Option<Vec<String>> {
     let mut expanded = Vec::with_capacity(template_strs.len());
     for variable in template_strs {
@@ -304,6 +412,7 @@ fn substitute_all_template_variables_in_vec(
             task_variables,
             variable_names,
             substituted_variables,
+            missing_inputs,
         )?;
         expanded.push(new_value);
     }
@@ -315,6 +424,7 @@ fn substitute_all_template_variables_in_map(
     task_variables: &HashMap<String, &str>,
     variable_names: &HashMap<String, VariableName>,
     substituted_variables: &mut HashSet<VariableName>,
+    missing_inputs: &mut HashSet<String>,
 ) -> Option<HashMap<String, String>> {
     let mut new_map: HashMap<String, String> = Default::default();
     for (key, value) in keys_and_values {
@@ -323,12 +433,14 @@ fn substitute_all_template_variables_in_map(
             task_variables,
             variable_names,
             substituted_variables,
+            missing_inputs,
         )?;
         let new_key = substitute_all_template_variables_in_str(
             &key,
             task_variables,
             variable_names,
             substituted_variables,
+            missing_inputs,
         )?;
         new_map.insert(new_key, new_value);
     }
@@ -804,4 +916,90 @@ mod tests {
             "overwritten"
         );
     }
+
+    #[test]
+    fn test_variable_aliases_and_inputs() {
+        let template = TaskTemplate {
+            label: "build ${input:target}".to_string(),
+            command: "cargo build --profile ${input:profile} -p ${current_symbol}".to_string(),
+            cwd: Some("${workspace_root}".to_string()),
+            inputs: vec![
+                TaskInput {
+                    id: "profile".to_string(),
+                    options: vec!["dev".to_string(), "release".to_string()],
+                    default: Some("dev".to_string()),
+                    ..TaskInput::default()
+                },
+                TaskInput {
+                    id: "unused".to_string(),
+                    ..TaskInput::default()
+                },
+            ],
+            ..TaskTemplate::default()
+        };
+        let mut context = TaskContext {
+            cwd: None,
+            task_variables: TaskVariables::from_iter([
+                (VariableName::Symbol, "my_crate".to_string()),
+                (VariableName::WorktreeRoot, "/dir".to_string()),
+            ]),
+            project_env: HashMap::default(),
+        };
+
+        // Inputs without a value are kept, to be asked for in their declaration order.
+        let resolved_task = template.resolve_task(TEST_ID_BASE, &context).unwrap();
+        assert_eq!(
+            resolved_task
+                .missing_inputs()
+                .iter()
+                .map(|input| input.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["profile", "target"]
+        );
+        assert_eq!(resolved_task.missing_inputs()[0], template.inputs[0]);
+        let resolved = resolved_task.resolved.unwrap();
+        assert_eq!(resolved.label, "build ${input:target}");
+        assert_eq!(
+            resolved.command,
+            "cargo build --profile ${input:profile} -p my_crate"
+        );
+        assert_eq!(resolved.cwd, Some(PathBuf::from("/dir")));
+
+        context.task_variables.insert(
+            VariableName::Input(Cow::Borrowed("profile")),
+            "release".to_string(),
+        );
+        context.task_variables.insert(
+            VariableName::Input(Cow::Borrowed("target")),
+            "x86_64".to_string(),
+        );
+        let resolved_task = template.resolve_task(TEST_ID_BASE, &context).unwrap();
+        assert!(resolved_task.missing_inputs().is_empty());
+        assert_substituted_variables(
+            &resolved_task,
+            vec![
+                VariableName::Symbol,
+                VariableName::WorktreeRoot,
+                VariableName::Input(Cow::Borrowed("profile")),
+                VariableName::Input(Cow::Borrowed("target")),
+            ],
+        );
+        let resolved = resolved_task.resolved.unwrap();
+        assert_eq!(resolved.label, "build x86_64");
+        assert_eq!(
+            resolved.command,
+            "cargo build --profile release -p my_crate"
+        );
+        assert_eq!(resolved.env["ZED_INPUT_profile"], "release");
+
+        // Like the other task variables, aliases have to be available for the task to resolve.
+        let selection_task = TaskTemplate {
+            label: "grep".to_string(),
+            command: "grep '${selected_text}'".to_string(),
+            ..TaskTemplate::default()
+        };
+        assert!(selection_task
+            .resolve_task(TEST_ID_BASE, &context)
+            .is_none());
+    }
 }
@@ -0,0 +1,194 @@
+use std::sync::Arc;
+
+use futures::channel::oneshot;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{rems, DismissEvent, Task, ViewContext};
+use picker::{Picker, PickerDelegate};
+use task::{TaskInput, TaskVariables, VariableName};
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, Selectable as _, WindowContext};
+use util::ResultExt as _;
+use workspace::Workspace;
+
+/// Asks for the values of the inputs one after the other, each in its own picker.
+pub(crate) fn prompt_for_inputs(
+    inputs: Vec<TaskInput>,
+    cx: &mut ViewContext<Workspace>,
+) -> Task<Option<TaskVariables>> {
+    cx.spawn(|workspace, mut cx| async move {
+        let mut values = TaskVariables::default();
+        for input in inputs {
+            let (tx, rx) = oneshot::channel();
+            let id = input.id.clone();
+            workspace
+                .update(&mut cx, |workspace, cx| {
+                    // Free text inputs start with their default value typed in.
+                    let default_query = input.default.clone().filter(|_| input.options.is_empty());
+                    workspace.toggle_modal(cx, |cx| {
+                        Picker::uniform_list(TaskInputDelegate::new(input, tx), cx).width(rems(34.))
+                    });
+                    if let Some(default_query) = default_query {
+                        if let Some(picker) =
+                            workspace.active_modal::<Picker<TaskInputDelegate>>(cx)
+                        {
+                            picker.update(cx, |picker, cx| picker.set_query(default_query, cx));
+                        }
+                    }
+                })
+                .log_err()?;
+            let value = rx.await.ok()?;
+            values.insert(VariableName::Input(id.into()), value);
+        }
+        Some(values)
+    })
+}
+
+/// Picks one of the options of an input, or takes the typed in text as its value.
+struct TaskInputDelegate {
+    input: TaskInput,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    query: String,
+    tx: Option<oneshot::Sender<String>>,
+}
+
+impl TaskInputDelegate {
+    fn new(input: TaskInput, tx: oneshot::Sender<String>) -> Self {
+        let candidates = input
+            .options
+            .iter()
+            .enumerate()
+            .map(|(id, option)| StringMatchCandidate::new(id, option.clone()))
+            .collect();
+        let selected_index = input
+            .default
+            .as_ref()
+            .and_then(|default| input.options.iter().position(|option| option == default))
+            .unwrap_or(0);
+        Self {
+            input,
+            candidates,
+            matches: Vec::new(),
+            selected_index,
+            query: String::new(),
+            tx: Some(tx),
+        }
+    }
+
+    fn has_options(&self) -> bool {
+        !self.input.options.is_empty()
+    }
+}
+
+impl PickerDelegate for TaskInputDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        match &self.input.description {
+            Some(description) => Arc::from(description.as_str()),
+            None => Arc::from(format!("Enter a value for `{}`", self.input.id)),
+        }
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> gpui::SharedString {
+        if self.has_options() {
+            "No matching options".into()
+        } else {
+            "Press enter to use the typed in value".into()
+        }
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        self.query = query.clone();
+        if !self.has_options() {
+            return Task::ready(());
+        }
+
+        let candidates = self.candidates.clone();
+        let background = cx.background_executor().clone();
+        cx.spawn(|picker, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+            picker
+                .update(&mut cx, |picker, cx| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    delegate.selected_index = delegate
+                        .selected_index
+                        .min(delegate.matches.len().saturating_sub(1));
+                    cx.notify();
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let value = if self.has_options() {
+            let Some(mat) = self.matches.get(self.selected_index) else {
+                return;
+            };
+            mat.string.clone()
+        } else {
+            self.query.clone()
+        };
+        if let Some(tx) = self.tx.take() {
+            tx.send(value).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut ViewContext<Picker<Self>>) {
+        // Dropping the sender cancels the spawning of the task.
+        self.tx.take();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                )),
+        )
+    }
+}
@@ -1,11 +1,14 @@
+use std::sync::Arc;
+
 use ::settings::Settings;
 use editor::{tasks::task_context, Editor};
 use gpui::{AppContext, Task as AsyncTask, ViewContext, WindowContext};
 use modal::TasksModal;
 use project::{Location, WorktreeId};
-use workspace::tasks::schedule_task;
+use workspace::tasks::{schedule_task, TaskInputsPrompt};
 use workspace::{tasks::schedule_resolved_task, Workspace};
 
+mod inputs;
 mod modal;
 mod settings;
 
@@ -13,6 +16,7 @@ pub use modal::{Rerun, Spawn};
 
 pub fn init(cx: &mut AppContext) {
     settings::TaskSettings::register(cx);
+    cx.set_global(TaskInputsPrompt(Arc::new(inputs::prompt_for_inputs)));
     cx.observe_new_views(
         |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
             workspace
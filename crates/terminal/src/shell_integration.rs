@@ -0,0 +1,480 @@
+//! Shell integration, based on the prompt marks (`OSC 133`) shells print around
+//! their prompts and the commands they run.
+//!
+//! Alacritty ignores these sequences, so they're picked out of the output of the
+//! shell before it is parsed, by wrapping the PTY.
+
+use std::{collections::VecDeque, io, sync::Arc};
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    grid::Dimensions,
+    index::{Column, Line, Point as AlacPoint},
+    term::{cell::Flags, TermMode},
+    tty::{ChildEvent, EventedPty, EventedReadWrite},
+    Term,
+};
+use futures::channel::mpsc::UnboundedSender;
+use polling::{Event as PollingEvent, PollMode, Poller};
+
+const OSC_133_PREFIX: &[u8] = b"133;";
+/// Prompt marks are short, longer operating system commands are something else.
+const MAX_OSC_133_LEN: usize = 64;
+
+/// A prompt mark, printed by the shell as `OSC 133 ; <mark> ST`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShellMark {
+    /// `A`, printed before the prompt.
+    PromptStart,
+    /// `B`, printed after the prompt, where the command is typed in.
+    CommandStart,
+    /// `C`, printed when the command starts running, before its output.
+    CommandExecuted,
+    /// `D[;<exit code>]`, printed once the command finished.
+    CommandFinished { exit_code: Option<i32> },
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ScanState {
+    #[default]
+    Ground,
+    Escape,
+    OperatingSystemCommand,
+    OperatingSystemCommandEscape,
+}
+
+/// Finds the prompt marks in the output of the shell, which may be split anywhere
+/// between reads.
+#[derive(Debug, Default)]
+pub struct OscScanner {
+    state: ScanState,
+    params: Vec<u8>,
+}
+
+impl OscScanner {
+    pub fn scan(&mut self, bytes: &[u8], mut on_mark: impl FnMut(ShellMark)) {
+        let mut ix = 0;
+        while ix < bytes.len() {
+            if self.state == ScanState::Ground {
+                match bytes[ix..].iter().position(|byte| *byte == 0x1b) {
+                    Some(escape_ix) => ix += escape_ix,
+                    None => return,
+                }
+            }
+
+            let byte = bytes[ix];
+            ix += 1;
+            match self.state {
+                ScanState::Ground | ScanState::Escape => self.state = self.escape_state(byte),
+                ScanState::OperatingSystemCommand => match byte {
+                    0x07 => {
+                        self.finish(&mut on_mark);
+                    }
+                    0x1b => self.state = ScanState::OperatingSystemCommandEscape,
+                    _ => {
+                        self.params.push(byte);
+                        let len = self.params.len();
+                        let is_prompt_mark = if len <= OSC_133_PREFIX.len() {
+                            self.params[..] == OSC_133_PREFIX[..len]
+                        } else {
+                            len <= MAX_OSC_133_LEN
+                        };
+                        if !is_prompt_mark {
+                            self.state = ScanState::Ground;
+                        }
+                    }
+                },
+                ScanState::OperatingSystemCommandEscape => {
+                    if byte == b'\\' {
+                        self.finish(&mut on_mark);
+                    } else {
+                        // The command was cut short by another escape sequence.
+                        self.state = self.escape_state(byte);
+                    }
+                }
+            }
+        }
+    }
+
+    fn escape_state(&mut self, byte: u8) -> ScanState {
+        match byte {
+            0x1b => ScanState::Escape,
+            b']' if self.state != ScanState::Ground => {
+                self.params.clear();
+                ScanState::OperatingSystemCommand
+            }
+            _ => ScanState::Ground,
+        }
+    }
+
+    fn finish(&mut self, on_mark: &mut impl FnMut(ShellMark)) {
+        self.state = ScanState::Ground;
+        if let Some(mark) = self
+            .params
+            .strip_prefix(OSC_133_PREFIX)
+            .and_then(parse_mark)
+        {
+            on_mark(mark);
+        }
+    }
+}
+
+fn parse_mark(params: &[u8]) -> Option<ShellMark> {
+    let params = std::str::from_utf8(params).ok()?;
+    let mut params = params.split(';');
+    match params.next()? {
+        "A" => Some(ShellMark::PromptStart),
+        "B" => Some(ShellMark::CommandStart),
+        "C" => Some(ShellMark::CommandExecuted),
+        "D" => Some(ShellMark::CommandFinished {
+            exit_code: params.next().and_then(|code| code.parse().ok()),
+        }),
+        _ => None,
+    }
+}
+
+/// A PTY reporting the prompt marks in the output it reads.
+pub struct ShellIntegrationPty<P> {
+    pty: P,
+    scanner: OscScanner,
+    marks_tx: UnboundedSender<ShellMark>,
+}
+
+impl<P> ShellIntegrationPty<P> {
+    pub fn new(pty: P, marks_tx: UnboundedSender<ShellMark>) -> Self {
+        Self {
+            pty,
+            scanner: OscScanner::default(),
+            marks_tx,
+        }
+    }
+}
+
+impl<P: EventedReadWrite> io::Read for ShellIntegrationPty<P> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.pty.reader().read(buf)?;
+        let marks_tx = &self.marks_tx;
+        self.scanner.scan(&buf[..len], |mark| {
+            marks_tx.unbounded_send(mark).ok();
+        });
+        Ok(len)
+    }
+}
+
+impl<P: EventedReadWrite> EventedReadWrite for ShellIntegrationPty<P> {
+    type Reader = Self;
+    type Writer = P::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.register(poll, interest, mode)
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poll, interest, mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poll)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        self
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.pty.writer()
+    }
+}
+
+impl<P: EventedPty> EventedPty for ShellIntegrationPty<P> {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl<P: OnResize> OnResize for ShellIntegrationPty<P> {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.pty.on_resize(window_size)
+    }
+}
+
+/// A command run at a prompt of the shell, with its lines counted from the top of
+/// the scrollback.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShellCommand {
+    pub prompt_line: usize,
+    /// Where the command is typed in, after the prompt.
+    pub input_start: Option<(usize, usize)>,
+    pub command: Option<String>,
+    pub output_start: Option<usize>,
+    /// The line after the output, once the command finished.
+    pub output_end: Option<usize>,
+    pub exit_code: Option<i32>,
+}
+
+impl ShellCommand {
+    pub fn is_running(&self) -> bool {
+        self.output_start.is_some() && self.output_end.is_none()
+    }
+}
+
+/// How many commands are kept, the oldest ones being dropped first.
+pub const MAX_SHELL_COMMANDS: usize = 1000;
+
+/// Updates the commands run in the shell, oldest first, with the prompt marks read
+/// since the terminal last woke up.
+///
+/// The marks are placed at the cursor, as the shell is waiting at its prompt when
+/// it prints most of them. Lines are counted from the top of the scrollback, so
+/// they're wrong once the scrollback is full and its first lines are dropped.
+pub fn apply_shell_marks<T>(
+    commands: &mut VecDeque<ShellCommand>,
+    marks: impl IntoIterator<Item = ShellMark>,
+    term: &Term<T>,
+) {
+    // Full screen applications don't have prompts.
+    if term.mode().contains(TermMode::ALT_SCREEN) {
+        return;
+    }
+    let cursor = term.grid().cursor.point;
+    let cursor_line = (term.history_size() as i32 + cursor.line.0) as usize;
+    for mark in marks {
+        match mark {
+            ShellMark::PromptStart => {
+                // The output of the previous command ends before the next prompt, at
+                // the latest, when both were read at once.
+                if let Some(previous) = commands.back_mut() {
+                    if let (Some(output_start), Some(output_end)) =
+                        (previous.output_start, previous.output_end.as_mut())
+                    {
+                        *output_end = (*output_end).min(cursor_line).max(output_start);
+                    }
+                }
+                if commands.len() == MAX_SHELL_COMMANDS {
+                    commands.pop_front();
+                }
+                commands.push_back(ShellCommand {
+                    prompt_line: cursor_line,
+                    ..ShellCommand::default()
+                });
+            }
+            ShellMark::CommandStart => {
+                if let Some(command) = commands.back_mut() {
+                    command
+                        .input_start
+                        .get_or_insert((cursor_line, cursor.column.0));
+                }
+            }
+            ShellMark::CommandExecuted => {
+                if let Some(command) = commands.back_mut() {
+                    start_command_output(command, term);
+                }
+            }
+            ShellMark::CommandFinished { exit_code } => {
+                if let Some(command) = commands.back_mut() {
+                    if let Some(output_start) = command.output_start {
+                        if command.output_end.is_none() {
+                            // Output that doesn't end with a newline ends on the line
+                            // of the cursor.
+                            let output_end = if cursor.column.0 == 0 {
+                                cursor_line
+                            } else {
+                                cursor_line + 1
+                            };
+                            command.output_end = Some(output_end.max(output_start));
+                            command.exit_code = exit_code;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Reads the command typed in after the prompt, starting its output after the lines
+/// it wraps over.
+fn start_command_output<T>(command: &mut ShellCommand, term: &Term<T>) {
+    let Some((line, column)) = command.input_start else {
+        return;
+    };
+    if command.output_start.is_some() {
+        return;
+    }
+    let history_size = term.history_size() as i32;
+    let start = AlacPoint::new(Line(line as i32 - history_size), Column(column));
+    if start.line < term.topmost_line()
+        || start.line > term.bottommost_line()
+        || start.column > term.last_column()
+    {
+        return;
+    }
+    let mut end = AlacPoint::new(start.line, term.last_column());
+    while end.line < term.bottommost_line()
+        && term.grid()[end.line][end.column]
+            .flags
+            .contains(Flags::WRAPLINE)
+    {
+        end.line += 1;
+    }
+    command.command = Some(term.bounds_to_string(start, end).trim().to_string());
+    command.output_start = Some((end.line.0 + history_size) as usize + 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TerminalSize;
+    use alacritty_terminal::{event::VoidListener, term::Config, vte::ansi::Processor};
+    use gpui::{px, size};
+
+    fn scan(scanner: &mut OscScanner, bytes: &[u8]) -> Vec<ShellMark> {
+        let mut marks = Vec::new();
+        scanner.scan(bytes, |mark| marks.push(mark));
+        marks
+    }
+
+    #[test]
+    fn test_scan_prompt_marks() {
+        let mut scanner = OscScanner::default();
+        assert_eq!(
+            scan(
+                &mut scanner,
+                b"\x1b]133;D;1\x07\x1b]133;A\x07$ \x1b]133;B\x1b\\ls\r\n\x1b]133;C\x07"
+            ),
+            vec![
+                ShellMark::CommandFinished { exit_code: Some(1) },
+                ShellMark::PromptStart,
+                ShellMark::CommandStart,
+                ShellMark::CommandExecuted,
+            ]
+        );
+        assert_eq!(
+            scan(&mut scanner, b"\x1b]133;D\x07\x1b]133;Z\x07"),
+            vec![ShellMark::CommandFinished { exit_code: None }]
+        );
+
+        // Other sequences are skipped.
+        assert_eq!(
+            scan(
+                &mut scanner,
+                b"\x1b]0;title\x07\x1b[31mred\x1b]1337;A\x07\x1b]133;\x1b]133;A\x07"
+            ),
+            vec![ShellMark::PromptStart]
+        );
+    }
+
+    #[test]
+    fn test_scan_split_prompt_marks() {
+        let mut scanner = OscScanner::default();
+        let bytes = b"out\x1b]133;D;0\x1b\\\x1b]133;A\x07";
+        let mut marks = Vec::new();
+        for chunk in bytes.chunks(1) {
+            marks.extend(scan(&mut scanner, chunk));
+        }
+        assert_eq!(
+            marks,
+            vec![
+                ShellMark::CommandFinished { exit_code: Some(0) },
+                ShellMark::PromptStart,
+            ]
+        );
+    }
+
+    /// Parses the output of the shell, one read at a time, applying the prompt marks
+    /// of each read once it's parsed, as the terminal does when it wakes up.
+    fn run_shell(
+        term: &mut Term<VoidListener>,
+        commands: &mut VecDeque<ShellCommand>,
+        reads: &[&[u8]],
+    ) {
+        let mut scanner = OscScanner::default();
+        let mut parser: Processor = Processor::new();
+        for read in reads {
+            let marks = scan(&mut scanner, read);
+            for byte in *read {
+                parser.advance(term, *byte);
+            }
+            apply_shell_marks(commands, marks, term);
+        }
+    }
+
+    #[test]
+    fn test_command_ranges() {
+        // A terminal of 5 lines, which scroll as the commands run.
+        let terminal_size = TerminalSize::new(px(10.), px(5.), size(px(100.), px(50.)));
+        let mut term = Term::new(Config::default(), &terminal_size, VoidListener);
+        let mut commands = VecDeque::new();
+
+        // The output of the command is read along with the next prompt, after which
+        // the cursor is lines below the marks before it.
+        run_shell(
+            &mut term,
+            &mut commands,
+            &[
+                b"\x1b]133;A\x07$ \x1b]133;B\x07",
+                b"ls\r\n\x1b]133;C\x07a\r\nb\r\nc\r\nd\r\ne\r\nf\r\n\x1b]133;D;1\x07\x1b]133;A\x07$ ",
+            ],
+        );
+        assert_eq!(
+            commands,
+            [
+                ShellCommand {
+                    prompt_line: 0,
+                    input_start: Some((0, 2)),
+                    command: Some("ls".into()),
+                    output_start: Some(1),
+                    output_end: Some(7),
+                    exit_code: Some(1),
+                },
+                ShellCommand {
+                    prompt_line: 7,
+                    ..ShellCommand::default()
+                },
+            ]
+        );
+
+        // Output that doesn't end with a newline, read one byte at a time.
+        let bytes =
+            b"\x1b]133;B\x07printf x\r\n\x1b]133;C\x07x\x1b]133;D;0\x07\r\n\x1b]133;A\x07$ ";
+        run_shell(
+            &mut term,
+            &mut commands,
+            &bytes.chunks(1).collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            commands.iter().skip(1).cloned().collect::<Vec<_>>(),
+            [
+                ShellCommand {
+                    prompt_line: 7,
+                    input_start: Some((7, 2)),
+                    command: Some("printf x".into()),
+                    output_start: Some(8),
+                    output_end: Some(9),
+                    exit_code: Some(0),
+                },
+                ShellCommand {
+                    prompt_line: 9,
+                    ..ShellCommand::default()
+                },
+            ]
+        );
+
+        // Full screen applications don't have prompts.
+        run_shell(
+            &mut term,
+            &mut commands,
+            &[b"\x1b[?1049h\x1b]133;A\x07", b"\x1b[?1049l"],
+        );
+        assert_eq!(commands.len(), 3);
+    }
+}
@@ -3,6 +3,7 @@ pub mod mappings;
 pub use alacritty_terminal;
 
 mod pty_info;
+pub mod shell_integration;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -13,7 +14,7 @@ use alacritty_terminal::{
     selection::{Selection, SelectionRange, SelectionType},
     sync::FairMutex,
     term::{
-        cell::Cell,
+        cell::{Cell, Flags},
         search::{Match, RegexIter, RegexSearch},
         Config, RenderableCursor, TermMode,
     },
@@ -21,7 +22,7 @@ use alacritty_terminal::{
     vte::ansi::{ClearMode, Handler, NamedPrivateMode, PrivateMode},
     Term,
 };
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Context as _, Result};
 
 use futures::{
     channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
@@ -38,11 +39,12 @@ use futures::StreamExt;
 use pty_info::PtyProcessInfo;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{apply_shell_marks, ShellCommand, ShellIntegrationPty, ShellMark};
 use smol::channel::{Receiver, Sender};
 use task::{HideStrategy, Shell, TaskId};
 use terminal_settings::{AlternateScroll, TerminalBlink, TerminalSettings};
 use theme::{ActiveTheme, Theme};
-use util::truncate_and_trailoff;
+use util::{truncate_and_trailoff, ResultExt as _};
 
 use std::{
     cmp::{self, min},
@@ -76,6 +78,10 @@ actions!(
         ScrollPageDown,
         ScrollToTop,
         ScrollToBottom,
+        ScrollToPreviousCommand,
+        ScrollToNextCommand,
+        SelectCommandOutput,
+        RerunCommand,
     ]
 );
 
@@ -407,12 +413,13 @@ impl TerminalBuilder {
         };
 
         let pty_info = PtyProcessInfo::new(&pty);
+        let (shell_marks_tx, shell_marks_rx) = unbounded();
 
         //And connect them together
         let event_loop = EventLoop::new(
             term.clone(),
             ZedListener(events_tx.clone()),
-            pty,
+            ShellIntegrationPty::new(pty, shell_marks_tx),
             pty_options.hold,
             false,
         )?;
@@ -426,6 +433,15 @@ impl TerminalBuilder {
         // https://learn.microsoft.com/en-us/visualstudio/msbuild/msbuild-diagnostic-format-for-tasks
         let word_regex =
             RegexSearch::new(r#"[\$\+\w.\[\]:/\\@\-~]+(?:\((?:\d+|\d+,\d+)\))?"#).unwrap();
+        let path_hyperlink_regexes = TerminalSettings::get_global(cx)
+            .path_hyperlink_regexes
+            .iter()
+            .filter_map(|regex| {
+                PathHyperlinkRegex::new(regex)
+                    .with_context(|| format!("invalid path hyperlink regex `{regex}`"))
+                    .log_err()
+            })
+            .collect();
 
         let terminal = Terminal {
             task,
@@ -447,6 +463,9 @@ impl TerminalBuilder {
             hovered_word: false,
             url_regex,
             word_regex,
+            path_hyperlink_regexes,
+            shell_marks_rx,
+            shell_commands: VecDeque::new(),
         };
 
         Ok(TerminalBuilder {
@@ -545,6 +564,15 @@ pub struct TerminalContent {
     pub cursor_char: char,
     pub size: TerminalSize,
     pub last_hovered_word: Option<HoveredWord>,
+    pub command_marks: Vec<CommandMark>,
+}
+
+/// A finished shell command, to show how it exited next to the terminal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CommandMark {
+    /// Where the command is, from 0 at the top of the scrollback to 1 at the bottom.
+    pub position: f32,
+    pub success: bool,
 }
 
 #[derive(Clone)]
@@ -569,6 +597,7 @@ impl Default for TerminalContent {
             cursor_char: Default::default(),
             size: Default::default(),
             last_hovered_word: None,
+            command_marks: Vec::new(),
         }
     }
 }
@@ -600,6 +629,10 @@ pub struct Terminal {
     hovered_word: bool,
     url_regex: RegexSearch,
     word_regex: RegexSearch,
+    path_hyperlink_regexes: Vec<PathHyperlinkRegex>,
+    shell_marks_rx: UnboundedReceiver<ShellMark>,
+    /// The commands run in the shell, reported by its prompt marks, oldest first.
+    shell_commands: VecDeque<ShellCommand>,
     task: Option<TaskState>,
 }
 
@@ -677,6 +710,9 @@ impl Terminal {
                 //NOOP, Handled in render
             }
             AlacTermEvent::Wakeup => {
+                // The prompt marks are read before the output around them is parsed,
+                // which is done by the time the terminal wakes up.
+                self.process_shell_marks();
                 cx.emit(Event::Wakeup);
 
                 if self.pty_info.has_changed() {
@@ -703,6 +739,20 @@ impl Terminal {
         }
     }
 
+    fn process_shell_marks(&mut self) {
+        let mut marks = Vec::new();
+        while let Ok(Some(mark)) = self.shell_marks_rx.try_next() {
+            marks.push(mark);
+        }
+        if marks.is_empty() {
+            return;
+        }
+
+        let term = self.term.clone();
+        let term = term.lock();
+        apply_shell_marks(&mut self.shell_commands, marks, &term);
+    }
+
     pub fn selection_started(&self) -> bool {
         self.selection_phase == SelectionPhase::Selecting
     }
@@ -754,6 +804,18 @@ impl Terminal {
                     AlacPoint::new(Line(0), term.grid_mut().cursor.point.column);
                 let new_cursor = term.grid().cursor.point;
 
+                // Only the prompt at the cursor is left.
+                let prompt = self
+                    .shell_commands
+                    .pop_back()
+                    .filter(|command| command.output_start.is_none())
+                    .map(|command| ShellCommand {
+                        input_start: command.input_start.map(|(_, column)| (0, column)),
+                        ..ShellCommand::default()
+                    });
+                self.shell_commands.clear();
+                self.shell_commands.extend(prompt);
+
                 // Clear the lines below the new cursor
                 if (new_cursor.line.0 as usize) < term.screen_lines() - 1 {
                     term.grid_mut().reset_region((new_cursor.line + 1)..);
@@ -851,6 +913,14 @@ impl Terminal {
                 } else if let Some(url_match) = regex_match_at(term, point, &mut self.url_regex) {
                     let url = term.bounds_to_string(*url_match.start(), *url_match.end());
                     Some((url, true, url_match))
+                } else if let Some((path, path_match)) =
+                    self.path_hyperlink_regexes.iter_mut().find_map(|regex| {
+                        let path_match = regex_match_at(term, point, &mut regex.search)?;
+                        let text = term.bounds_to_string(*path_match.start(), *path_match.end());
+                        Some((regex.path_with_position(&text)?, path_match))
+                    })
+                {
+                    Some((path, false, path_match))
                 } else if let Some(word_match) = regex_match_at(term, point, &mut self.word_regex) {
                     let file_path = term.bounds_to_string(*word_match.start(), *word_match.end());
 
@@ -1052,6 +1122,119 @@ impl Terminal {
             .push_back(InternalEvent::Scroll(AlacScroll::Bottom));
     }
 
+    /// Scrolls the prompt of the first command above the top of the viewport to the top.
+    pub fn scroll_to_previous_command(&mut self) {
+        let (history_size, viewport_top) = self.viewport_top();
+        if let Some(command) = self
+            .shell_commands
+            .iter()
+            .rev()
+            .find(|command| command.prompt_line < viewport_top)
+        {
+            let delta = viewport_top - command.prompt_line;
+            self.events
+                .push_back(InternalEvent::Scroll(AlacScroll::Delta(
+                    delta.min(history_size) as i32,
+                )));
+        }
+    }
+
+    /// Scrolls the prompt of the first command below the top of the viewport to the top,
+    /// or to the bottom after the last command.
+    pub fn scroll_to_next_command(&mut self) {
+        let (_, viewport_top) = self.viewport_top();
+        match self
+            .shell_commands
+            .iter()
+            .find(|command| command.prompt_line > viewport_top)
+        {
+            Some(command) => {
+                let delta = command.prompt_line - viewport_top;
+                self.events
+                    .push_back(InternalEvent::Scroll(AlacScroll::Delta(-(delta as i32))));
+            }
+            None => self.scroll_to_bottom(),
+        }
+    }
+
+    /// Selects the output of the command at the top of the viewport, or of the last
+    /// command when scrolled to the bottom.
+    pub fn select_command_output(&mut self) {
+        let Some(command) = self.viewport_command() else {
+            return;
+        };
+        let Some(output_start) = command.output_start else {
+            return;
+        };
+        let term = self.term.lock();
+        let history_size = term.history_size() as i32;
+        let start = AlacPoint::new(Line(output_start as i32 - history_size), Column(0));
+        let end_line = match command.output_end {
+            Some(output_end) if output_end > output_start => {
+                Line(output_end as i32 - history_size - 1)
+            }
+            Some(_) => return,
+            None => term.bottommost_line(),
+        };
+        let end = AlacPoint::new(end_line, term.last_column());
+        drop(term);
+        if start.line <= end.line {
+            self.set_selection(Some((make_selection(&(start..=end)), end)));
+            self.events
+                .push_back(InternalEvent::ScrollToAlacPoint(start));
+        }
+    }
+
+    /// Runs the command at the top of the viewport again, or the last command when
+    /// scrolled to the bottom, unless a command is still running.
+    pub fn rerun_command(&mut self) {
+        if self
+            .shell_commands
+            .back()
+            .map_or(false, |command| command.is_running())
+        {
+            return;
+        }
+        let Some(command) = self
+            .viewport_command()
+            .and_then(|command| command.command.clone())
+            .filter(|command| !command.is_empty())
+        else {
+            return;
+        };
+        self.input(format!("{command}\r"));
+    }
+
+    /// Whether the shell reported the commands it ran with prompt marks.
+    pub fn has_shell_commands(&self) -> bool {
+        !self.shell_commands.is_empty()
+    }
+
+    /// The size of the scrollback, and the line at the top of the viewport counted from
+    /// the top of the scrollback.
+    fn viewport_top(&self) -> (usize, usize) {
+        let term = self.term.lock();
+        let history_size = term.history_size();
+        (
+            history_size,
+            history_size - term.grid().display_offset().min(history_size),
+        )
+    }
+
+    fn viewport_command(&self) -> Option<&ShellCommand> {
+        let (history_size, viewport_top) = self.viewport_top();
+        let mut commands = self
+            .shell_commands
+            .iter()
+            .rev()
+            .filter(|command| command.output_start.is_some());
+        if viewport_top == history_size {
+            commands.next()
+        } else {
+            commands.find(|command| command.prompt_line <= viewport_top)
+        }
+    }
+
     ///Resize the terminal and the PTY.
     pub fn set_size(&mut self, new_size: TerminalSize) {
         if self.last_content.size != new_size {
@@ -1114,6 +1297,13 @@ impl Terminal {
         self.input(paste_text);
     }
 
+    /// Sends code to the program running in the terminal, like a shell or a REPL, to run it.
+    pub fn send_code(&mut self, code: &str, bracketed_paste: bool, end_with_empty_line: bool) {
+        let bracketed_paste =
+            bracketed_paste && self.term.lock().mode().contains(TermMode::BRACKETED_PASTE);
+        self.input(code_input(code, bracketed_paste, end_with_empty_line));
+    }
+
     pub fn sync(&mut self, cx: &mut ModelContext<Self>) {
         let term = self.term.clone();
         let mut terminal = term.lock_unfair();
@@ -1123,6 +1313,21 @@ impl Terminal {
         }
 
         self.last_content = Self::make_content(&terminal, &self.last_content);
+        self.last_content.command_marks = self.command_marks(&terminal);
+    }
+
+    fn command_marks(&self, term: &Term<ZedListener>) -> Vec<CommandMark> {
+        let total_lines = term.total_lines().max(1) as f32;
+        self.shell_commands
+            .iter()
+            .filter(|command| command.output_end.is_some())
+            .filter_map(|command| {
+                Some(CommandMark {
+                    position: command.prompt_line as f32 / total_lines,
+                    success: command.exit_code? == 0,
+                })
+            })
+            .collect()
     }
 
     fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
@@ -1150,6 +1355,7 @@ impl Terminal {
             cursor_char: term.grid()[content.cursor.point].c,
             size: last_content.size,
             last_hovered_word: last_content.last_hovered_word.clone(),
+            command_marks: Vec::new(),
         }
     }
 
@@ -1178,6 +1384,53 @@ impl Terminal {
         lines
     }
 
+    /// Returns all lines of the terminal, including its scrollback history,
+    /// with the lines wrapped by the terminal width joined back together.
+    pub fn output_lines(&self) -> Vec<String> {
+        let term = self.term.clone();
+        let terminal = term.lock_unfair();
+        let last_column = Column(terminal.columns().saturating_sub(1));
+
+        let mut lines = Vec::new();
+        let mut line_buffer = String::new();
+        for line in terminal.topmost_line().0..=terminal.bottommost_line().0 {
+            let row = &terminal.grid()[Line(line)];
+            for cell in row {
+                if !cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                    line_buffer.push(cell.c);
+                }
+            }
+            if !row[last_column].flags.contains(Flags::WRAPLINE) {
+                lines.push(line_buffer.trim_end().to_string());
+                line_buffer.clear();
+            }
+        }
+        if !line_buffer.is_empty() {
+            lines.push(line_buffer.trim_end().to_string());
+        }
+        lines
+    }
+
+    /// Returns the lines of the terminal's screen, ignoring its scrollback
+    /// history, along with the row and column of the cursor.
+    pub fn screen_lines(&self) -> (Vec<String>, usize, usize) {
+        let term = self.term.clone();
+        let terminal = term.lock_unfair();
+
+        let mut lines = Vec::new();
+        for line in 0..terminal.screen_lines() as i32 {
+            let mut line_buffer = String::new();
+            for cell in &terminal.grid()[Line(line)] {
+                if !cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                    line_buffer.push(cell.c);
+                }
+            }
+            lines.push(line_buffer.trim_end().to_string());
+        }
+        let cursor = terminal.grid().cursor.point;
+        (lines, cursor.line.0.max(0) as usize, cursor.column.0)
+    }
+
     pub fn focus_in(&self) {
         if self.last_content.mode.contains(TermMode::FOCUS_IN_OUT) {
             self.write_to_pty("\x1b[I".to_string());
@@ -1647,6 +1900,39 @@ impl Drop for Terminal {
 
 impl EventEmitter<Event> for Terminal {}
 
+/// A regex from the `path_hyperlink_regexes` setting, matching a file path with a position.
+struct PathHyperlinkRegex {
+    search: RegexSearch,
+    captures: regex::Regex,
+}
+
+impl PathHyperlinkRegex {
+    fn new(regex: &str) -> Result<Self> {
+        Ok(Self {
+            search: RegexSearch::new(regex).map_err(|error| anyhow!("{error}"))?,
+            captures: regex::Regex::new(regex)?,
+        })
+    }
+
+    /// The path in the text matched by the regex, with its position attached as `path:line:column`.
+    fn path_with_position(&self, text: &str) -> Option<String> {
+        let captures = self.captures.captures(text)?;
+        let mut path = captures
+            .name("path")
+            .map_or(text, |path| path.as_str())
+            .to_owned();
+        if let Some(line) = captures.name("line") {
+            path.push(':');
+            path.push_str(line.as_str());
+            if let Some(column) = captures.name("column") {
+                path.push(':');
+                path.push_str(column.as_str());
+            }
+        }
+        Some(path)
+    }
+}
+
 /// Based on alacritty/src/display/hint.rs > regex_match_at
 /// Retrieve the match, if the specified point is inside the content matching the regex.
 fn regex_match_at<T>(term: &Term<T>, point: AlacPoint, regex: &mut RegexSearch) -> Option<Match> {
@@ -1671,6 +1957,21 @@ pub fn visible_regex_match_iter<'a, T>(
         .take_while(move |rm| rm.start().line <= viewport_end)
 }
 
+/// The input running the code: pasted at once, or typed in line by line, then entered.
+fn code_input(code: &str, bracketed_paste: bool, end_with_empty_line: bool) -> String {
+    let code = code.trim_end_matches(['\r', '\n']);
+    let mut input = if bracketed_paste && code.contains('\n') {
+        format!("\x1b[200~{}\x1b[201~", code.replace('\x1b', ""))
+    } else {
+        code.replace("\r\n", "\r").replace('\n', "\r")
+    };
+    input.push('\r');
+    if end_with_empty_line {
+        input.push('\r');
+    }
+    input
+}
+
 fn make_selection(range: &RangeInclusive<AlacPoint>) -> Selection {
     let mut selection = Selection::new(SelectionType::Simple, *range.start(), AlacDirection::Left);
     selection.update(*range.end(), AlacDirection::Right);
@@ -1788,9 +2089,53 @@ mod tests {
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
 
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
+        code_input, content_index_for_mouse, rgb_for_index, IndexedCell, PathHyperlinkRegex,
+        TerminalContent, TerminalSize,
     };
 
+    #[test]
+    fn test_code_input() {
+        assert_eq!(code_input("ls\n", true, false), "ls\r");
+        assert_eq!(
+            code_input("for i in 1 2\r\ndo echo $i\ndone\n", false, false),
+            "for i in 1 2\rdo echo $i\rdone\r"
+        );
+        assert_eq!(
+            code_input("if x:\n    y()", true, false),
+            "\x1b[200~if x:\n    y()\x1b[201~\r"
+        );
+        assert_eq!(
+            code_input("if x:\n    y()\n", false, true),
+            "if x:\r    y()\r\r"
+        );
+    }
+
+    #[test]
+    fn test_path_hyperlink_regex() {
+        let python =
+            PathHyperlinkRegex::new(r#"File "(?<path>[^"]+)", line (?<line>[0-9]+)"#).unwrap();
+        assert_eq!(
+            python
+                .path_with_position(r#"File "my app/main.py", line 12"#)
+                .as_deref(),
+            Some("my app/main.py:12")
+        );
+        assert_eq!(python.path_with_position("main.py:12"), None);
+
+        let with_column =
+            PathHyperlinkRegex::new(r"(?<path>\S+)\((?<line>\d+),(?<column>\d+)\)").unwrap();
+        assert_eq!(
+            with_column.path_with_position("src/lib.c(3,14)").as_deref(),
+            Some("src/lib.c:3:14")
+        );
+
+        let without_groups = PathHyperlinkRegex::new(r"\S+\.rs").unwrap();
+        assert_eq!(
+            without_groups.path_with_position("src/main.rs").as_deref(),
+            Some("src/main.rs")
+        );
+    }
+
     #[test]
     fn test_rgb_for_index() {
         // Test every possible value in the color cube.
@@ -43,6 +43,7 @@ pub struct TerminalSettings {
     pub detect_venv: VenvSettings,
     pub max_scroll_history_lines: Option<usize>,
     pub toolbar: Toolbar,
+    pub path_hyperlink_regexes: Vec<String>,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -177,6 +178,14 @@ pub struct TerminalSettingsContent {
     pub max_scroll_history_lines: Option<usize>,
     /// Toolbar related settings
     pub toolbar: Option<ToolbarContent>,
+    /// Regexes matching file paths with a position in the terminal output, like the
+    /// locations in the errors of a tool, which can be cmd-clicked to open the file.
+    /// The path is captured by a `path` group, and its position by `line` and `column` groups.
+    /// Paths without spaces, like `src/main.rs:12:5`, are always detected.
+    /// Existing terminals will not pick up this change until they are recreated.
+    ///
+    /// Default: Python tracebacks, Perl and Ruby errors
+    pub path_hyperlink_regexes: Option<Vec<String>>,
 }
 
 impl settings::Settings for TerminalSettings {
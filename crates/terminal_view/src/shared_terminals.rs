@@ -0,0 +1,727 @@
+use std::{collections::BTreeMap, mem, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, Context as _, Result};
+use client::{proto, Client, TypedEnvelope};
+use collections::{HashMap, HashSet};
+use gpui::{
+    div, AnyElement, AppContext, AsyncAppContext, EventEmitter, FocusHandle, FocusableView,
+    InteractiveElement, KeyDownEvent, Keystroke, Model, ModelContext, ParentElement, Render,
+    Styled, Subscription, Task, ViewContext, WeakModel,
+};
+use project::Project;
+use settings::Settings;
+use terminal::{terminal_settings::TerminalSettings, Event, Terminal};
+use theme::ThemeSettings;
+use ui::{prelude::*, Icon, IconName, Label};
+use util::ResultExt;
+use workspace::item::{Item, TabContentParams};
+
+/// How long the host waits for more output before sending the screen of a
+/// shared terminal to the guests.
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+pub fn init(client: &Arc<Client>) {
+    client.add_model_message_handler(SharedTerminalStore::handle_advertise_shared_terminals);
+    client.add_model_request_handler(SharedTerminalStore::handle_open_shared_terminal);
+    client.add_model_message_handler(SharedTerminalStore::handle_update_shared_terminal);
+    client.add_model_request_handler(SharedTerminalStore::handle_shared_terminal_input);
+}
+
+/// A terminal shared by the host of the project.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HostTerminal {
+    pub id: u64,
+    pub title: String,
+    /// The users allowed to type into the terminal.
+    pub writer_user_ids: Vec<u64>,
+}
+
+/// The visible contents of a shared terminal.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SharedTerminalScreen {
+    pub lines: Vec<String>,
+    pub cursor_row: usize,
+    pub cursor_column: usize,
+}
+
+impl SharedTerminalScreen {
+    fn from_proto(screen: proto::SharedTerminalScreen) -> Self {
+        Self {
+            lines: screen.lines,
+            cursor_row: screen.cursor_row as usize,
+            cursor_column: screen.cursor_column as usize,
+        }
+    }
+
+    fn to_proto(&self) -> proto::SharedTerminalScreen {
+        proto::SharedTerminalScreen {
+            lines: self.lines.clone(),
+            cursor_row: self.cursor_row as u32,
+            cursor_column: self.cursor_column as u32,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SharedTerminalStoreEvent {
+    /// The host started sharing a terminal.
+    TerminalShared(u64),
+}
+
+/// The terminals shared with the collaborators of a project.
+///
+/// The host shares its terminals and decides who may type into them, while
+/// guests see the terminals the host shares and replicate the screens of the
+/// ones they open.
+pub struct SharedTerminalStore {
+    project: Model<Project>,
+    client: Arc<Client>,
+    shared_terminals: BTreeMap<u64, SharedTerminal>,
+    next_terminal_id: u64,
+    host_terminals: Vec<HostTerminal>,
+    remote_screens: HashMap<u64, SharedTerminalScreen>,
+    project_is_shared: bool,
+    client_subscription: Option<client::Subscription>,
+    _project_subscriptions: Vec<Subscription>,
+}
+
+struct SharedTerminal {
+    terminal: WeakModel<Terminal>,
+    writer_user_ids: HashSet<u64>,
+    pending_update: Option<Task<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl EventEmitter<SharedTerminalStoreEvent> for SharedTerminalStore {}
+
+impl SharedTerminalStore {
+    pub fn new(project: Model<Project>, cx: &mut ModelContext<Self>) -> Self {
+        let mut this = Self {
+            client: project.read(cx).client(),
+            shared_terminals: BTreeMap::default(),
+            next_terminal_id: 0,
+            host_terminals: Vec::new(),
+            remote_screens: HashMap::default(),
+            project_is_shared: false,
+            client_subscription: None,
+            _project_subscriptions: vec![
+                cx.observe(&project, Self::handle_project_changed),
+                cx.subscribe(&project, Self::handle_project_event),
+            ],
+            project: project.clone(),
+        };
+        this.handle_project_changed(project, cx);
+        this
+    }
+
+    fn handle_project_changed(&mut self, _: Model<Project>, cx: &mut ModelContext<Self>) {
+        let is_shared = self.project.read(cx).is_shared();
+        let was_shared = mem::replace(&mut self.project_is_shared, is_shared);
+        if is_shared == was_shared {
+            return;
+        }
+
+        if is_shared {
+            let remote_id = self.project.read(cx).remote_id().unwrap();
+            self.client_subscription = self
+                .client
+                .subscribe_to_entity(remote_id)
+                .log_err()
+                .map(|subscription| subscription.set_model(&cx.handle(), &mut cx.to_async()));
+        } else {
+            self.client_subscription = None;
+            self.shared_terminals.clear();
+            self.host_terminals.clear();
+            self.remote_screens.clear();
+        }
+        cx.notify();
+    }
+
+    fn handle_project_event(
+        &mut self,
+        _: Model<Project>,
+        event: &project::Event,
+        cx: &mut ModelContext<Self>,
+    ) {
+        match event {
+            project::Event::Reshared | project::Event::CollaboratorJoined(_) => {
+                self.advertise_terminals(cx);
+            }
+            project::Event::DisconnectedFromHost => {
+                self.host_terminals.clear();
+                self.remote_screens.clear();
+                cx.notify();
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether terminals can be shared, which requires hosting a shared
+    /// project.
+    pub fn can_share(&self, cx: &AppContext) -> bool {
+        let project = self.project.read(cx);
+        project.is_shared() && !project.is_via_collab()
+    }
+
+    pub fn is_shared(&self, terminal: &Model<Terminal>) -> bool {
+        self.terminal_id(terminal).is_some()
+    }
+
+    pub fn share_terminal(&mut self, terminal: &Model<Terminal>, cx: &mut ModelContext<Self>) {
+        if !self.can_share(cx) || self.is_shared(terminal) {
+            return;
+        }
+
+        let id = self.next_terminal_id;
+        self.next_terminal_id += 1;
+        let subscriptions = vec![
+            cx.subscribe(terminal, move |this, terminal, event, cx| match event {
+                Event::Wakeup => this.schedule_update(id, cx),
+                Event::TitleChanged => this.advertise_terminals(cx),
+                Event::CloseTerminal => this.unshare_terminal(&terminal, cx),
+                _ => {}
+            }),
+            cx.observe_release(terminal, move |this, _, cx| {
+                if this.shared_terminals.remove(&id).is_some() {
+                    this.advertise_terminals(cx);
+                    cx.notify();
+                }
+            }),
+        ];
+        self.shared_terminals.insert(
+            id,
+            SharedTerminal {
+                terminal: terminal.downgrade(),
+                writer_user_ids: HashSet::default(),
+                pending_update: None,
+                _subscriptions: subscriptions,
+            },
+        );
+        self.advertise_terminals(cx);
+        cx.notify();
+    }
+
+    pub fn unshare_terminal(&mut self, terminal: &Model<Terminal>, cx: &mut ModelContext<Self>) {
+        if let Some(id) = self.terminal_id(terminal) {
+            self.shared_terminals.remove(&id);
+            self.advertise_terminals(cx);
+            cx.notify();
+        }
+    }
+
+    /// Whether the user may type into the shared terminal.
+    pub fn can_write(&self, terminal: &Model<Terminal>, user_id: u64) -> bool {
+        self.terminal_id(terminal)
+            .and_then(|id| self.shared_terminals.get(&id))
+            .map_or(false, |shared| shared.writer_user_ids.contains(&user_id))
+    }
+
+    pub fn set_can_write(
+        &mut self,
+        terminal: &Model<Terminal>,
+        user_id: u64,
+        can_write: bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(shared) = self
+            .terminal_id(terminal)
+            .and_then(|id| self.shared_terminals.get_mut(&id))
+        else {
+            return;
+        };
+        let changed = if can_write {
+            shared.writer_user_ids.insert(user_id)
+        } else {
+            shared.writer_user_ids.remove(&user_id)
+        };
+        if changed {
+            self.advertise_terminals(cx);
+            cx.notify();
+        }
+    }
+
+    /// The terminals the host of the project shares.
+    pub fn host_terminals(&self) -> &[HostTerminal] {
+        &self.host_terminals
+    }
+
+    pub fn host_terminal(&self, id: u64) -> Option<&HostTerminal> {
+        self.host_terminals
+            .iter()
+            .find(|terminal| terminal.id == id)
+    }
+
+    /// Whether the current user may type into a terminal the host shares.
+    pub fn can_write_host_terminal(&self, id: u64) -> bool {
+        let Some(user_id) = self.client.user_id() else {
+            return false;
+        };
+        self.host_terminal(id).map_or(false, |terminal| {
+            terminal.writer_user_ids.contains(&user_id)
+        })
+    }
+
+    /// The screen of a terminal the host shares, once it has been opened.
+    pub fn remote_screen(&self, id: u64) -> Option<&SharedTerminalScreen> {
+        self.remote_screens.get(&id)
+    }
+
+    /// Starts replicating the screen of a terminal the host shares.
+    pub fn open_remote_terminal(
+        &mut self,
+        id: u64,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(project_id) = self.project.read(cx).remote_id() else {
+            return Task::ready(Err(anyhow!("project was not remote")));
+        };
+        let request = self.client.request(proto::OpenSharedTerminal {
+            project_id,
+            terminal_id: id,
+        });
+        cx.spawn(|this, mut cx| async move {
+            let response = request.await?;
+            let screen = response.screen.context("invalid terminal screen")?;
+            this.update(&mut cx, |this, cx| {
+                this.remote_screens
+                    .insert(id, SharedTerminalScreen::from_proto(screen));
+                cx.notify();
+            })
+        })
+    }
+
+    pub fn close_remote_terminal(&mut self, id: u64, cx: &mut ModelContext<Self>) {
+        if self.remote_screens.remove(&id).is_some() {
+            cx.notify();
+        }
+    }
+
+    /// Types into a terminal the host shares.
+    pub fn send_input(
+        &self,
+        id: u64,
+        input: proto::shared_terminal_input::Input,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        let Some(project_id) = self.project.read(cx).remote_id() else {
+            return Task::ready(Err(anyhow!("project was not remote")));
+        };
+        let request = self.client.request(proto::SharedTerminalInput {
+            project_id,
+            terminal_id: id,
+            input: Some(input),
+        });
+        cx.background_executor().spawn(async move {
+            request.await?;
+            Ok(())
+        })
+    }
+
+    fn terminal_id(&self, terminal: &Model<Terminal>) -> Option<u64> {
+        let terminal = terminal.downgrade();
+        self.shared_terminals
+            .iter()
+            .find(|(_, shared)| shared.terminal == terminal)
+            .map(|(id, _)| *id)
+    }
+
+    fn screen(&self, id: u64, cx: &AppContext) -> Option<SharedTerminalScreen> {
+        let terminal = self.shared_terminals.get(&id)?.terminal.upgrade()?;
+        let (lines, cursor_row, cursor_column) = terminal.read(cx).screen_lines();
+        Some(SharedTerminalScreen {
+            lines,
+            cursor_row,
+            cursor_column,
+        })
+    }
+
+    fn schedule_update(&mut self, id: u64, cx: &mut ModelContext<Self>) {
+        let Some(shared) = self.shared_terminals.get_mut(&id) else {
+            return;
+        };
+        if shared.pending_update.is_some() {
+            return;
+        }
+        shared.pending_update = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+            this.update(&mut cx, |this, cx| {
+                if let Some(shared) = this.shared_terminals.get_mut(&id) {
+                    shared.pending_update = None;
+                }
+                this.send_update(id, cx);
+            })
+            .ok();
+        }));
+    }
+
+    fn send_update(&self, id: u64, cx: &AppContext) {
+        let Some(project_id) = self.project.read(cx).remote_id() else {
+            return;
+        };
+        if let Some(screen) = self.screen(id, cx) {
+            self.client
+                .send(proto::UpdateSharedTerminal {
+                    project_id,
+                    terminal_id: id,
+                    screen: Some(screen.to_proto()),
+                })
+                .log_err();
+        }
+    }
+
+    fn advertise_terminals(&self, cx: &AppContext) {
+        let Some(project_id) = self.project.read(cx).remote_id() else {
+            return;
+        };
+        // Only the host can share their terminals.
+        if self.project.read(cx).is_via_collab() {
+            return;
+        }
+
+        let terminals = self
+            .shared_terminals
+            .iter()
+            .filter_map(|(id, shared)| {
+                let terminal = shared.terminal.upgrade()?;
+                let mut writer_user_ids =
+                    shared.writer_user_ids.iter().copied().collect::<Vec<_>>();
+                writer_user_ids.sort_unstable();
+                Some(proto::SharedTerminalMetadata {
+                    terminal_id: *id,
+                    title: terminal.read(cx).title(false),
+                    writer_user_ids,
+                })
+            })
+            .collect();
+        self.client
+            .send(proto::AdvertiseSharedTerminals {
+                project_id,
+                terminals,
+            })
+            .log_err();
+    }
+
+    async fn handle_advertise_shared_terminals(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::AdvertiseSharedTerminals>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            let host_terminals = envelope
+                .payload
+                .terminals
+                .into_iter()
+                .map(|terminal| HostTerminal {
+                    id: terminal.terminal_id,
+                    title: terminal.title,
+                    writer_user_ids: terminal.writer_user_ids,
+                })
+                .collect::<Vec<_>>();
+            let old_host_terminals = mem::replace(&mut this.host_terminals, host_terminals);
+            this.remote_screens.retain(|id, _| {
+                this.host_terminals
+                    .iter()
+                    .any(|terminal| terminal.id == *id)
+            });
+            for terminal in &this.host_terminals {
+                if !old_host_terminals.iter().any(|old| old.id == terminal.id) {
+                    cx.emit(SharedTerminalStoreEvent::TerminalShared(terminal.id));
+                }
+            }
+            cx.notify();
+        })
+    }
+
+    async fn handle_open_shared_terminal(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::OpenSharedTerminal>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::OpenSharedTerminalResponse> {
+        let screen = this.update(&mut cx, |this, cx| {
+            this.screen(envelope.payload.terminal_id, cx)
+                .context("terminal is not shared")
+        })??;
+        Ok(proto::OpenSharedTerminalResponse {
+            screen: Some(screen.to_proto()),
+        })
+    }
+
+    async fn handle_update_shared_terminal(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UpdateSharedTerminal>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            let id = envelope.payload.terminal_id;
+            if let (Some(screen), Some(remote_screen)) =
+                (envelope.payload.screen, this.remote_screens.get_mut(&id))
+            {
+                *remote_screen = SharedTerminalScreen::from_proto(screen);
+                cx.notify();
+            }
+        })
+    }
+
+    async fn handle_shared_terminal_input(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::SharedTerminalInput>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let sender_id = envelope.original_sender_id()?;
+        this.update(&mut cx, |this, cx| {
+            let user_id = this
+                .project
+                .read(cx)
+                .collaborators()
+                .get(&sender_id)
+                .map(|collaborator| collaborator.user_id)
+                .context("unknown collaborator")?;
+            let shared = this
+                .shared_terminals
+                .get(&envelope.payload.terminal_id)
+                .context("terminal is not shared")?;
+            if !shared.writer_user_ids.contains(&user_id) {
+                return Err(anyhow!("not allowed to type into the terminal"));
+            }
+            let terminal = shared.terminal.upgrade().context("terminal was closed")?;
+            let alt_is_meta = TerminalSettings::get_global(cx).option_as_meta;
+            terminal.update(cx, |terminal, _| match envelope.payload.input {
+                Some(proto::shared_terminal_input::Input::Text(text)) => terminal.input(text),
+                Some(proto::shared_terminal_input::Input::Keystroke(keystroke)) => {
+                    if let Some(keystroke) = Keystroke::parse(&keystroke).log_err() {
+                        terminal.try_keystroke(&keystroke, alt_is_meta);
+                    }
+                }
+                None => {}
+            });
+            anyhow::Ok(proto::Ack {})
+        })?
+    }
+}
+
+/// Returns the source of a keystroke, in the form `Keystroke::parse` accepts.
+fn keystroke_source(keystroke: &Keystroke) -> String {
+    let mut source = String::new();
+    for (pressed, modifier) in [
+        (keystroke.modifiers.control, "ctrl-"),
+        (keystroke.modifiers.alt, "alt-"),
+        (keystroke.modifiers.shift, "shift-"),
+        (keystroke.modifiers.platform, "cmd-"),
+        (keystroke.modifiers.function, "fn-"),
+    ] {
+        if pressed {
+            source.push_str(modifier);
+        }
+    }
+    source.push_str(&keystroke.key);
+    source
+}
+
+/// A guest's view of a terminal the host shares.
+pub struct SharedTerminalView {
+    store: Model<SharedTerminalStore>,
+    terminal_id: u64,
+    focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl SharedTerminalView {
+    pub fn new(
+        store: Model<SharedTerminalStore>,
+        terminal_id: u64,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        store
+            .update(cx, |store, cx| store.open_remote_terminal(terminal_id, cx))
+            .detach_and_log_err(cx);
+        Self {
+            _subscriptions: vec![
+                cx.observe(&store, |_, _, cx| cx.notify()),
+                cx.on_release(|this, _, cx| {
+                    this.store.update(cx, |store, cx| {
+                        store.close_remote_terminal(this.terminal_id, cx)
+                    });
+                }),
+            ],
+            store,
+            terminal_id,
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    pub fn terminal_id(&self) -> u64 {
+        self.terminal_id
+    }
+
+    fn title(&self, cx: &AppContext) -> String {
+        self.store
+            .read(cx)
+            .host_terminal(self.terminal_id)
+            .map_or_else(
+                || "Shared Terminal".to_string(),
+                |terminal| terminal.title.clone(),
+            )
+    }
+
+    fn key_down(&mut self, event: &KeyDownEvent, cx: &mut ViewContext<Self>) {
+        let store = self.store.read(cx);
+        if !store.can_write_host_terminal(self.terminal_id) {
+            return;
+        }
+
+        let keystroke = &event.keystroke;
+        let modifiers = keystroke.modifiers;
+        let input = match &keystroke.ime_key {
+            Some(text) if !modifiers.control && !modifiers.platform && !modifiers.function => {
+                proto::shared_terminal_input::Input::Text(text.clone())
+            }
+            _ => proto::shared_terminal_input::Input::Keystroke(keystroke_source(keystroke)),
+        };
+        store
+            .send_input(self.terminal_id, input, cx)
+            .detach_and_log_err(cx);
+        cx.stop_propagation();
+    }
+}
+
+impl EventEmitter<()> for SharedTerminalView {}
+
+impl FocusableView for SharedTerminalView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for SharedTerminalView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let store = self.store.read(cx);
+        let is_shared = store.host_terminal(self.terminal_id).is_some();
+        let can_write = store.can_write_host_terminal(self.terminal_id);
+        let screen = store.remote_screen(self.terminal_id).cloned();
+        let settings = ThemeSettings::get_global(cx).clone();
+        let terminal_settings = TerminalSettings::get_global(cx);
+        let font_family = terminal_settings
+            .font_family
+            .as_ref()
+            .unwrap_or(&settings.buffer_font.family)
+            .clone();
+        let font_size = terminal_settings.font_size;
+        let font_size = font_size.map_or(settings.buffer_font_size(cx), |size| {
+            theme::adjusted_font_size(size, cx)
+        });
+        let cursor_color = cx.theme().players().local().cursor;
+
+        v_flex()
+            .id("shared-terminal")
+            .key_context("SharedTerminal")
+            .track_focus(&self.focus_handle)
+            .on_key_down(cx.listener(Self::key_down))
+            .size_full()
+            .bg(cx.theme().colors().terminal_background)
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        Icon::new(if can_write {
+                            IconName::Pencil
+                        } else {
+                            IconName::Eye
+                        })
+                        .size(IconSize::Small)
+                        .color(Color::Muted),
+                    )
+                    .child(
+                        Label::new(if !is_shared {
+                            "The host stopped sharing this terminal"
+                        } else if can_write {
+                            "Shared by the host, you can type into it"
+                        } else {
+                            "Shared by the host, read-only"
+                        })
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    ),
+            )
+            .child(
+                div()
+                    .id("shared-terminal-screen")
+                    .flex_1()
+                    .p_1()
+                    .overflow_y_scroll()
+                    .font_family(font_family)
+                    .text_size(font_size)
+                    .text_color(cx.theme().colors().terminal_foreground)
+                    .children(screen.into_iter().flat_map(|screen| {
+                        let cursor_row = screen.cursor_row;
+                        let cursor_column = screen.cursor_column;
+                        screen
+                            .lines
+                            .into_iter()
+                            .enumerate()
+                            .map(move |(row, line)| {
+                                if row != cursor_row || !is_shared {
+                                    return div().whitespace_nowrap().child(line + " ");
+                                }
+                                let mut chars = line.chars();
+                                let before = chars.by_ref().take(cursor_column).collect::<String>();
+                                let before = format!("{before:<width$}", width = cursor_column);
+                                let at_cursor = chars.next().unwrap_or(' ');
+                                let after = chars.collect::<String>();
+                                h_flex()
+                                    .whitespace_nowrap()
+                                    .child(before)
+                                    .child(div().bg(cursor_color).child(at_cursor.to_string()))
+                                    .child(after)
+                            })
+                    })),
+            )
+    }
+}
+
+impl Item for SharedTerminalView {
+    type Event = ();
+
+    fn tab_tooltip_text(&self, cx: &AppContext) -> Option<SharedString> {
+        Some(self.title(cx).into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, cx: &WindowContext) -> AnyElement {
+        h_flex()
+            .gap_2()
+            .child(Icon::new(IconName::Terminal).color(Color::Muted))
+            .child(Label::new(self.title(cx)).color(params.text_color()))
+            .into_any()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keystroke_source_round_trips() {
+        for source in [
+            "enter",
+            "ctrl-c",
+            "alt-shift-left",
+            "ctrl--",
+            "fn-f1",
+            "cmd-k",
+        ] {
+            let keystroke = Keystroke::parse(source).unwrap();
+            assert_eq!(
+                Keystroke::parse(&keystroke_source(&keystroke)).unwrap(),
+                keystroke,
+                "{source}"
+            );
+        }
+    }
+}
@@ -47,6 +47,7 @@ pub struct LayoutState {
     gutter: Pixels,
     last_hovered_word: Option<HoveredWord>,
     block_below_cursor_element: Option<AnyElement>,
+    command_marks: Vec<(Bounds<Pixels>, Hsla)>,
 }
 
 /// Helper struct for converting data between Alacritty's cursor points, and displayed cursor points.
@@ -738,11 +739,31 @@ impl Element for TerminalElement {
                     cursor_char,
                     selection,
                     cursor,
+                    command_marks,
                     ..
                 } = &self.terminal.read(cx).last_content;
                 let mode = *mode;
                 let display_offset = *display_offset;
 
+                // Finished commands are marked along the right edge, like search results
+                // in an editor's scrollbar.
+                let command_marks = command_marks
+                    .iter()
+                    .map(|mark| {
+                        let mark_size = size(px(4.), px(2.));
+                        let origin = point(
+                            bounds.right() - mark_size.width,
+                            bounds.top() + (bounds.size.height - mark_size.height) * mark.position,
+                        );
+                        let color = if mark.success {
+                            theme.status().success
+                        } else {
+                            theme.status().error
+                        };
+                        (Bounds::new(origin, mark_size), color)
+                    })
+                    .collect();
+
                 // searches, highlights to a single range representations
                 let mut relative_highlighted_ranges = Vec::new();
                 for search_match in search_matches {
@@ -860,6 +881,7 @@ impl Element for TerminalElement {
                     gutter,
                     last_hovered_word,
                     block_below_cursor_element,
+                    command_marks,
                 }
             })
     }
@@ -953,6 +975,10 @@ impl Element for TerminalElement {
                         element.paint(cx);
                     }
 
+                    for (bounds, color) in &layout.command_marks {
+                        cx.paint_quad(fill(*bounds, *color));
+                    }
+
                     if let Some(mut element) = hyperlink_tooltip {
                         element.paint(cx);
                     }
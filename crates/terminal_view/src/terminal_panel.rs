@@ -1,16 +1,24 @@
 use std::{ops::ControlFlow, path::PathBuf, sync::Arc};
 
-use crate::{default_working_directory, TerminalView};
+use crate::{
+    default_working_directory,
+    shared_terminals::{SharedTerminalStore, SharedTerminalStoreEvent, SharedTerminalView},
+    TerminalView,
+};
 use collections::{HashMap, HashSet};
 use db::kvp::KEY_VALUE_STORE;
+use editor::{actions::SendSelectionToTerminal, Editor};
 use futures::future::join_all;
 use gpui::{
-    actions, Action, AnchorCorner, AnyView, AppContext, AsyncWindowContext, Entity, EventEmitter,
-    ExternalPaths, FocusHandle, FocusableView, IntoElement, Model, ParentElement, Pixels, Render,
-    Styled, Subscription, Task, View, ViewContext, VisualContext, WeakView, WindowContext,
+    actions, Action, AnchorCorner, AnyView, AppContext, AsyncWindowContext, Axis, Bounds, Entity,
+    EntityId, EventEmitter, ExternalPaths, FocusHandle, FocusableView, InteractiveElement,
+    IntoElement, Model, ParentElement, Pixels, Render, Styled, Subscription, Task, View,
+    ViewContext, VisualContext, WeakView, WindowContext,
 };
 use itertools::Itertools;
-use project::{terminals::TerminalKind, Fs, ProjectEntryId};
+use language::language_settings::SendToTerminalSettings;
+use parking_lot::Mutex;
+use project::{terminals::TerminalKind, Fs, Project, ProjectEntryId};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use serde::{Deserialize, Serialize};
 use settings::Settings;
@@ -27,9 +35,11 @@ use util::{ResultExt, TryFutureExt};
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent},
     item::SerializableItem,
-    pane,
+    notifications::NotificationId,
+    pane, pane_axis,
     ui::IconName,
-    DraggedTab, ItemId, NewTerminal, Pane, ToggleZoom, Workspace,
+    ActivatePaneInDirection, DraggedTab, ItemId, NewTerminal, Pane, SplitDirection, Toast,
+    ToggleZoom, Workspace,
 };
 
 use anyhow::Result;
@@ -37,13 +47,14 @@ use zed_actions::InlineAssist;
 
 const TERMINAL_PANEL_KEY: &str = "TerminalPanel";
 
-actions!(terminal_panel, [ToggleFocus]);
+actions!(terminal_panel, [ToggleFocus, OpenSharedTerminals]);
 
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(
         |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
             workspace.register_action(TerminalPanel::new_terminal);
             workspace.register_action(TerminalPanel::open_terminal);
+            workspace.register_action(TerminalPanel::open_shared_terminals);
             workspace.register_action(|workspace, _: &ToggleFocus, cx| {
                 if workspace
                     .panel::<TerminalPanel>(cx)
@@ -56,113 +67,198 @@ pub fn init(cx: &mut AppContext) {
         },
     )
     .detach();
+
+    cx.observe_new_views(|editor: &mut Editor, cx: &mut ViewContext<Editor>| {
+        let editor_handle = cx.view().downgrade();
+        editor
+            .register_action(move |action: &SendSelectionToTerminal, cx| {
+                send_selection_to_terminal(&editor_handle, action, cx);
+            })
+            .detach();
+    })
+    .detach();
+}
+
+fn send_selection_to_terminal(
+    editor: &WeakView<Editor>,
+    action: &SendSelectionToTerminal,
+    cx: &mut WindowContext,
+) {
+    let Some(editor) = editor.upgrade() else {
+        return;
+    };
+    let Some(workspace) = editor.read(cx).workspace() else {
+        return;
+    };
+    let Some((code, settings)) =
+        editor.update(cx, |editor, cx| editor.take_code_for_terminal(action, cx))
+    else {
+        return;
+    };
+    workspace.update(cx, |workspace, cx| {
+        TerminalPanel::send_code(workspace, code, settings, cx)
+    });
 }
 
 pub struct TerminalPanel {
-    pane: View<Pane>,
+    /// The splits of the panel, side by side along `split_axis`.
+    panes: Vec<View<Pane>>,
+    active_pane: View<Pane>,
+    split_axis: Axis,
+    flexes: Arc<Mutex<Vec<f32>>>,
+    bounding_boxes: Arc<Mutex<Vec<Option<Bounds<Pixels>>>>>,
     fs: Arc<dyn Fs>,
     workspace: WeakView<Workspace>,
+    project: Model<Project>,
     width: Option<Pixels>,
     height: Option<Pixels>,
     pending_serialization: Task<Option<()>>,
     pending_terminals_to_add: usize,
+    pane_subscriptions: HashMap<EntityId, Vec<Subscription>>,
     _subscriptions: Vec<Subscription>,
     deferred_tasks: HashMap<TaskId, Task<()>>,
     enabled: bool,
     assistant_enabled: bool,
     assistant_tab_bar_button: Option<AnyView>,
+    shared_terminals: Model<SharedTerminalStore>,
 }
 
 impl TerminalPanel {
     fn new(workspace: &Workspace, cx: &mut ViewContext<Self>) -> Self {
-        let pane = cx.new_view(|cx| {
-            let mut pane = Pane::new(
-                workspace.weak_handle(),
-                workspace.project().clone(),
-                Default::default(),
-                None,
-                NewTerminal.boxed_clone(),
-                cx,
-            );
-            pane.set_can_split(false, cx);
-            pane.set_can_navigate(false, cx);
-            pane.display_nav_history_buttons(None);
-            pane.set_should_display_tab_bar(|_| true);
-
-            let workspace = workspace.weak_handle();
-            pane.set_custom_drop_handle(cx, move |pane, dropped_item, cx| {
-                if let Some(tab) = dropped_item.downcast_ref::<DraggedTab>() {
-                    let item = if &tab.pane == cx.view() {
-                        pane.item_for_index(tab.ix)
-                    } else {
-                        tab.pane.read(cx).item_for_index(tab.ix)
-                    };
-                    if let Some(item) = item {
-                        if item.downcast::<TerminalView>().is_some() {
-                            return ControlFlow::Continue(());
-                        } else if let Some(project_path) = item.project_path(cx) {
-                            if let Some(entry_path) = workspace
-                                .update(cx, |workspace, cx| {
-                                    workspace
-                                        .project()
-                                        .read(cx)
-                                        .absolute_path(&project_path, cx)
-                                })
-                                .log_err()
-                                .flatten()
-                            {
-                                add_paths_to_terminal(pane, &[entry_path], cx);
-                            }
-                        }
-                    }
-                } else if let Some(&entry_id) = dropped_item.downcast_ref::<ProjectEntryId>() {
-                    if let Some(entry_path) = workspace
-                        .update(cx, |workspace, cx| {
-                            let project = workspace.project().read(cx);
-                            project
-                                .path_for_entry(entry_id, cx)
-                                .and_then(|project_path| project.absolute_path(&project_path, cx))
-                        })
-                        .log_err()
-                        .flatten()
-                    {
-                        add_paths_to_terminal(pane, &[entry_path], cx);
-                    }
-                } else if let Some(paths) = dropped_item.downcast_ref::<ExternalPaths>() {
-                    add_paths_to_terminal(pane, paths.paths(), cx);
-                }
-
-                ControlFlow::Break(())
-            });
-            let buffer_search_bar = cx.new_view(search::BufferSearchBar::new);
-            pane.toolbar()
-                .update(cx, |toolbar, cx| toolbar.add_item(buffer_search_bar, cx));
-            pane
-        });
-        let subscriptions = vec![
-            cx.observe(&pane, |_, _, cx| cx.notify()),
-            cx.subscribe(&pane, Self::handle_pane_event),
-        ];
-        let project = workspace.project().read(cx);
-        let enabled = project.is_local_or_ssh() || project.supports_remote_terminal(cx);
-        let this = Self {
-            pane,
+        let project = workspace.project().clone();
+        let pane = new_terminal_pane(workspace.weak_handle(), project.clone(), cx);
+        let enabled = {
+            let project = project.read(cx);
+            project.is_local_or_ssh() || project.supports_remote_terminal(cx)
+        };
+        let shared_terminals = cx.new_model(|cx| SharedTerminalStore::new(project.clone(), cx));
+        let shared_terminals_subscription =
+            cx.subscribe(&shared_terminals, Self::handle_shared_terminals_event);
+        let mut this = Self {
+            panes: Vec::new(),
+            active_pane: pane.clone(),
+            split_axis: Axis::Horizontal,
+            flexes: Arc::default(),
+            bounding_boxes: Arc::default(),
             fs: workspace.app_state().fs.clone(),
             workspace: workspace.weak_handle(),
+            project,
             pending_serialization: Task::ready(None),
             width: None,
             height: None,
             pending_terminals_to_add: 0,
+            pane_subscriptions: HashMap::default(),
             deferred_tasks: HashMap::default(),
-            _subscriptions: subscriptions,
+            _subscriptions: vec![shared_terminals_subscription],
             enabled,
             assistant_enabled: false,
             assistant_tab_bar_button: None,
+            shared_terminals,
         };
-        this.apply_tab_bar_buttons(cx);
+        this.insert_pane(0, pane, cx);
         this
     }
 
+    /// Adds a split to the panel at the given index, resetting the sizes of the splits.
+    fn insert_pane(&mut self, ix: usize, pane: View<Pane>, cx: &mut ViewContext<Self>) {
+        self.pane_subscriptions.insert(
+            pane.entity_id(),
+            vec![
+                cx.observe(&pane, |_, _, cx| cx.notify()),
+                cx.subscribe(&pane, Self::handle_pane_event),
+            ],
+        );
+        self.set_tab_bar_buttons(&pane, cx);
+        self.panes.insert(ix, pane);
+        self.reset_split_sizes();
+        cx.notify();
+    }
+
+    fn remove_pane(&mut self, pane: &View<Pane>, cx: &mut ViewContext<Self>) {
+        let Some(ix) = self.panes.iter().position(|candidate| candidate == pane) else {
+            return;
+        };
+        self.panes.remove(ix);
+        self.pane_subscriptions.remove(&pane.entity_id());
+        self.reset_split_sizes();
+        if &self.active_pane == pane {
+            self.active_pane = self.panes[ix.min(self.panes.len() - 1)].clone();
+            if pane.focus_handle(cx).contains_focused(cx) {
+                cx.focus_view(&self.active_pane);
+            }
+        }
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn reset_split_sizes(&self) {
+        *self.flexes.lock() = vec![1.; self.panes.len()];
+        *self.bounding_boxes.lock() = vec![None; self.panes.len()];
+    }
+
+    /// Opens a new terminal next to the given split, in the working directory of
+    /// the terminal active in it.
+    fn split(&mut self, pane: View<Pane>, direction: SplitDirection, cx: &mut ViewContext<Self>) {
+        let Some(mut ix) = self.panes.iter().position(|candidate| *candidate == pane) else {
+            return;
+        };
+        if self.panes.len() == 1 {
+            self.split_axis = direction.axis();
+        }
+        if direction.increasing() {
+            ix += 1;
+        }
+
+        let working_directory = pane
+            .read(cx)
+            .active_item()
+            .and_then(|item| item.downcast::<TerminalView>())
+            .and_then(|terminal_view| terminal_view.read(cx).terminal().read(cx).get_cwd())
+            .or_else(|| {
+                self.workspace
+                    .upgrade()
+                    .and_then(|workspace| default_working_directory(workspace.read(cx), cx))
+            });
+        let new_pane = new_terminal_pane(self.workspace.clone(), self.project.clone(), cx);
+        self.insert_pane(ix, new_pane.clone(), cx);
+        self.active_pane = new_pane.clone();
+        cx.focus_view(&new_pane);
+        self.add_terminal(
+            TerminalKind::Shell(working_directory),
+            RevealStrategy::Always,
+            cx,
+        )
+        .detach_and_log_err(cx);
+    }
+
+    /// Focuses the next split in the direction, if the splits are laid out along it.
+    fn activate_pane_in_direction(
+        &mut self,
+        direction: SplitDirection,
+        cx: &mut ViewContext<Self>,
+    ) -> bool {
+        if direction.axis() != self.split_axis {
+            return false;
+        }
+        let Some(ix) = self.panes.iter().position(|pane| *pane == self.active_pane) else {
+            return false;
+        };
+        let next_ix = if direction.increasing() {
+            ix + 1
+        } else if let Some(ix) = ix.checked_sub(1) {
+            ix
+        } else {
+            return false;
+        };
+        match self.panes.get(next_ix) {
+            Some(pane) => {
+                cx.focus_view(pane);
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn asssistant_enabled(&mut self, enabled: bool, cx: &mut ViewContext<Self>) {
         self.assistant_enabled = enabled;
         if enabled {
@@ -174,8 +270,14 @@ impl TerminalPanel {
     }
 
     fn apply_tab_bar_buttons(&self, cx: &mut ViewContext<Self>) {
+        for pane in &self.panes {
+            self.set_tab_bar_buttons(pane, cx);
+        }
+    }
+
+    fn set_tab_bar_buttons(&self, pane: &View<Pane>, cx: &mut ViewContext<Self>) {
         let assistant_tab_bar_button = self.assistant_tab_bar_button.clone();
-        self.pane.update(cx, |pane, cx| {
+        pane.update(cx, |pane, cx| {
             pane.set_render_tab_bar_buttons(cx, move |pane, cx| {
                 if !pane.has_focus(cx) && !pane.context_menu_focused(cx) {
                     return (None, None);
@@ -208,6 +310,9 @@ impl TerminalPanel {
                                             "Spawn task",
                                             tasks_ui::Spawn::modal().boxed_clone(),
                                         )
+                                        .separator()
+                                        .action("Split Right", pane::SplitRight.boxed_clone())
+                                        .action("Split Down", pane::SplitDown.boxed_clone())
                                 });
 
                                 Some(menu)
@@ -252,36 +357,55 @@ impl TerminalPanel {
             .log_err()
             .flatten();
 
-        let (panel, pane, items) = workspace.update(&mut cx, |workspace, cx| {
+        let (panel, splits) = workspace.update(&mut cx, |workspace, cx| {
             let panel = cx.new_view(|cx| TerminalPanel::new(workspace, cx));
-            let items = if let Some((serialized_panel, database_id)) =
+            let splits = if let Some((serialized_panel, database_id)) =
                 serialized_panel.as_ref().zip(workspace.database_id())
             {
                 panel.update(cx, |panel, cx| {
                     cx.notify();
                     panel.height = serialized_panel.height.map(|h| h.round());
                     panel.width = serialized_panel.width.map(|w| w.round());
-                    panel.pane.update(cx, |_, cx| {
-                        serialized_panel
-                            .items
-                            .iter()
-                            .map(|item_id| {
-                                TerminalView::deserialize(
-                                    workspace.project().clone(),
-                                    workspace.weak_handle(),
-                                    database_id,
-                                    *item_id,
+                    panel.split_axis = serialized_panel.split_axis.into();
+                    serialized_panel
+                        .splits()
+                        .into_iter()
+                        .enumerate()
+                        .map(|(ix, split)| {
+                            let pane = if ix == 0 {
+                                panel.active_pane.clone()
+                            } else {
+                                let pane = new_terminal_pane(
+                                    panel.workspace.clone(),
+                                    panel.project.clone(),
                                     cx,
-                                )
-                            })
-                            .collect::<Vec<_>>()
-                    })
+                                );
+                                panel.insert_pane(ix, pane.clone(), cx);
+                                pane
+                            };
+                            let items = pane.update(cx, |_, cx| {
+                                split
+                                    .items
+                                    .iter()
+                                    .map(|item_id| {
+                                        TerminalView::deserialize(
+                                            workspace.project().clone(),
+                                            workspace.weak_handle(),
+                                            database_id,
+                                            *item_id,
+                                            cx,
+                                        )
+                                    })
+                                    .collect::<Vec<_>>()
+                            });
+                            (pane, split, items)
+                        })
+                        .collect::<Vec<_>>()
                 })
             } else {
                 Vec::new()
             };
-            let pane = panel.read(cx).pane.clone();
-            (panel, pane, items)
+            (panel, splits)
         })?;
 
         if let Some(workspace) = workspace.upgrade() {
@@ -299,27 +423,56 @@ impl TerminalPanel {
                 .ok();
         }
 
-        let pane = pane.downgrade();
-        let items = futures::future::join_all(items).await;
+        let active_pane = serialized_panel
+            .as_ref()
+            .and_then(|panel| splits.get(panel.active_split))
+            .map(|(pane, _, _)| pane.clone());
+        let flexes = splits
+            .iter()
+            .map(|(_, split, _)| split.flex)
+            .collect::<Vec<_>>();
         let mut alive_item_ids = Vec::new();
-        pane.update(&mut cx, |pane, cx| {
-            let active_item_id = serialized_panel
-                .as_ref()
-                .and_then(|panel| panel.active_item_id);
-            let mut active_ix = None;
-            for item in items {
-                if let Some(item) = item.log_err() {
-                    let item_id = item.entity_id().as_u64();
-                    pane.add_item(Box::new(item), false, false, None, cx);
-                    alive_item_ids.push(item_id as ItemId);
-                    if Some(item_id) == active_item_id {
-                        active_ix = Some(pane.items_len() - 1);
+        for (pane, split, items) in splits {
+            let items = futures::future::join_all(items).await;
+            pane.update(&mut cx, |pane, cx| {
+                let mut active_ix = None;
+                for item in items {
+                    if let Some(item) = item.log_err() {
+                        let item_id = item.entity_id().as_u64();
+                        pane.add_item(Box::new(item), false, false, None, cx);
+                        alive_item_ids.push(item_id as ItemId);
+                        if Some(item_id) == split.active_item_id {
+                            active_ix = Some(pane.items_len() - 1);
+                        }
                     }
                 }
-            }
 
-            if let Some(active_ix) = active_ix {
-                pane.activate_item(active_ix, false, false, cx)
+                if let Some(active_ix) = active_ix {
+                    pane.activate_item(active_ix, false, false, cx)
+                }
+            })?;
+        }
+
+        panel.update(&mut cx, |panel, cx| {
+            // Splits whose terminals couldn't be restored are dropped, resetting the sizes of the others.
+            let empty_panes = panel
+                .panes
+                .iter()
+                .filter(|pane| pane.read(cx).items_len() == 0)
+                .cloned()
+                .collect::<Vec<_>>();
+            for pane in &empty_panes {
+                if panel.panes.len() > 1 {
+                    panel.remove_pane(pane, cx);
+                }
+            }
+            if flexes.len() == panel.panes.len()
+                && (flexes.iter().sum::<f32>() - flexes.len() as f32).abs() < 0.001
+            {
+                *panel.flexes.lock() = flexes;
+            }
+            if let Some(active_pane) = active_pane.filter(|pane| panel.panes.contains(pane)) {
+                panel.active_pane = active_pane;
             }
         })?;
 
@@ -340,20 +493,33 @@ impl TerminalPanel {
 
     fn handle_pane_event(
         &mut self,
-        _pane: View<Pane>,
+        pane: View<Pane>,
         event: &pane::Event,
         cx: &mut ViewContext<Self>,
     ) {
         match event {
             pane::Event::ActivateItem { .. } => self.serialize(cx),
             pane::Event::RemovedItem { .. } => self.serialize(cx),
-            pane::Event::Remove { .. } => cx.emit(PanelEvent::Close),
+            pane::Event::Remove { .. } => {
+                if self.panes.len() > 1 {
+                    self.remove_pane(&pane, cx);
+                } else {
+                    cx.emit(PanelEvent::Close)
+                }
+            }
             pane::Event::ZoomIn => cx.emit(PanelEvent::ZoomIn),
             pane::Event::ZoomOut => cx.emit(PanelEvent::ZoomOut),
+            pane::Event::Split(direction) => self.split(pane, *direction, cx),
+            pane::Event::Focus => {
+                if self.active_pane != pane {
+                    self.active_pane = pane;
+                    self.serialize(cx);
+                    cx.notify();
+                }
+            }
 
             pane::Event::AddItem { item } => {
                 if let Some(workspace) = self.workspace.upgrade() {
-                    let pane = self.pane.clone();
                     workspace.update(cx, |workspace, cx| item.added_to_pane(workspace, pane, cx))
                 }
             }
@@ -464,7 +630,7 @@ impl TerminalPanel {
                 .detach_and_log_err(cx);
             return;
         }
-        let (existing_item_index, existing_terminal) = terminals_for_task
+        let (existing_terminal_pane, existing_terminal) = terminals_for_task
             .last()
             .expect("covered no terminals case above")
             .clone();
@@ -473,7 +639,7 @@ impl TerminalPanel {
                 !use_new_terminal,
                 "Should have handled 'allow_concurrent_runs && use_new_terminal' case above"
             );
-            self.replace_terminal(spawn_task, existing_item_index, existing_terminal, cx);
+            self.replace_terminal(spawn_task, existing_terminal_pane, existing_terminal, cx);
         } else {
             self.deferred_tasks.insert(
                 spawn_in_terminal.id.clone(),
@@ -488,7 +654,7 @@ impl TerminalPanel {
                             } else {
                                 terminal_panel.replace_terminal(
                                     spawn_task,
-                                    existing_item_index,
+                                    existing_terminal_pane,
                                     existing_terminal,
                                     cx,
                                 );
@@ -528,20 +694,62 @@ impl TerminalPanel {
             .detach_and_log_err(cx);
     }
 
+    /// Runs code in the active terminal of the panel, or in a new one, leaving the focus
+    /// where it is.
+    fn send_code(
+        workspace: &mut Workspace,
+        code: String,
+        settings: SendToTerminalSettings,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(terminal_panel) = workspace.panel::<Self>(cx) else {
+            return;
+        };
+
+        let kind = TerminalKind::Shell(default_working_directory(workspace, cx));
+        workspace.open_panel::<Self>(cx);
+        let terminal = terminal_panel.update(cx, |this, cx| {
+            let active_terminal = this
+                .active_pane
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<TerminalView>())
+                .map(|terminal_view| terminal_view.read(cx).terminal().clone());
+            match active_terminal {
+                Some(terminal) => Task::ready(Ok(terminal)),
+                None => this.add_terminal(kind, RevealStrategy::Never, cx),
+            }
+        });
+        cx.spawn(|_, mut cx| async move {
+            let terminal = terminal.await?;
+            terminal.update(&mut cx, |terminal, _| {
+                terminal.send_code(
+                    &code,
+                    settings.bracketed_paste,
+                    settings.end_with_empty_line,
+                )
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn terminals_for_task(
         &self,
         label: &str,
         cx: &mut AppContext,
-    ) -> Vec<(usize, View<TerminalView>)> {
-        self.pane
-            .read(cx)
-            .items()
-            .enumerate()
-            .filter_map(|(index, item)| Some((index, item.act_as::<TerminalView>(cx)?)))
-            .filter_map(|(index, terminal_view)| {
+    ) -> Vec<(View<Pane>, View<TerminalView>)> {
+        self.panes
+            .iter()
+            .flat_map(|pane| {
+                pane.read(cx)
+                    .items()
+                    .filter_map(|item| Some((pane.clone(), item.act_as::<TerminalView>(cx)?)))
+                    .collect::<Vec<_>>()
+            })
+            .filter_map(|(pane, terminal_view)| {
                 let task_state = terminal_view.read(cx).terminal().read(cx).task()?;
                 if &task_state.full_label == label {
-                    Some((index, terminal_view))
+                    Some((pane, terminal_view))
                 } else {
                     None
                 }
@@ -549,9 +757,16 @@ impl TerminalPanel {
             .collect()
     }
 
-    fn activate_terminal_view(&self, item_index: usize, cx: &mut WindowContext) {
-        self.pane.update(cx, |pane, cx| {
-            pane.activate_item(item_index, true, true, cx)
+    fn activate_terminal_view(
+        &self,
+        pane: &View<Pane>,
+        terminal_view: &View<TerminalView>,
+        cx: &mut WindowContext,
+    ) {
+        pane.update(cx, |pane, cx| {
+            if let Some(item_index) = pane.index_for_item(terminal_view) {
+                pane.activate_item(item_index, true, true, cx)
+            }
         })
     }
 
@@ -571,7 +786,7 @@ impl TerminalPanel {
         self.pending_terminals_to_add += 1;
 
         cx.spawn(|terminal_panel, mut cx| async move {
-            let pane = terminal_panel.update(&mut cx, |this, _| this.pane.clone())?;
+            let pane = terminal_panel.update(&mut cx, |this, _| this.active_pane.clone())?;
             let result = workspace.update(&mut cx, |workspace, cx| {
                 let window = cx.window_handle();
                 let terminal = workspace
@@ -604,28 +819,45 @@ impl TerminalPanel {
     }
 
     fn serialize(&mut self, cx: &mut ViewContext<Self>) {
-        let mut items_to_serialize = HashSet::default();
-        let items = self
-            .pane
-            .read(cx)
-            .items()
-            .filter_map(|item| {
-                let terminal_view = item.act_as::<TerminalView>(cx)?;
-                if terminal_view.read(cx).terminal().read(cx).task().is_some() {
-                    None
-                } else {
-                    let id = item.item_id().as_u64();
-                    items_to_serialize.insert(id);
-                    Some(id)
+        let flexes = self.flexes.lock().clone();
+        let splits = self
+            .panes
+            .iter()
+            .zip(flexes)
+            .map(|(pane, flex)| {
+                let mut items_to_serialize = HashSet::default();
+                let items = pane
+                    .read(cx)
+                    .items()
+                    .filter_map(|item| {
+                        let terminal_view = item.act_as::<TerminalView>(cx)?;
+                        if terminal_view.read(cx).terminal().read(cx).task().is_some() {
+                            None
+                        } else {
+                            let id = item.item_id().as_u64();
+                            items_to_serialize.insert(id);
+                            Some(id)
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let active_item_id = pane
+                    .read(cx)
+                    .active_item()
+                    .map(|item| item.item_id().as_u64())
+                    .filter(|active_id| items_to_serialize.contains(active_id));
+                SerializedTerminalSplit {
+                    items,
+                    active_item_id,
+                    flex,
                 }
             })
             .collect::<Vec<_>>();
-        let active_item_id = self
-            .pane
-            .read(cx)
-            .active_item()
-            .map(|item| item.item_id().as_u64())
-            .filter(|active_id| items_to_serialize.contains(active_id));
+        let active_split = self
+            .panes
+            .iter()
+            .position(|pane| *pane == self.active_pane)
+            .unwrap_or(0);
+        let split_axis = self.split_axis.into();
         let height = self.height;
         let width = self.width;
         self.pending_serialization = cx.background_executor().spawn(
@@ -634,8 +866,11 @@ impl TerminalPanel {
                     .write_kvp(
                         TERMINAL_PANEL_KEY.into(),
                         serde_json::to_string(&SerializedTerminalPanel {
-                            items,
-                            active_item_id,
+                            items: Vec::new(),
+                            active_item_id: None,
+                            splits,
+                            active_split,
+                            split_axis,
                             height,
                             width,
                         })?,
@@ -650,7 +885,7 @@ impl TerminalPanel {
     fn replace_terminal(
         &self,
         spawn_task: SpawnInTerminal,
-        terminal_item_index: usize,
+        terminal_pane: View<Pane>,
         terminal_to_replace: View<TerminalView>,
         cx: &mut ViewContext<'_, Self>,
     ) -> Option<()> {
@@ -672,7 +907,7 @@ impl TerminalPanel {
 
         match reveal {
             RevealStrategy::Always => {
-                self.activate_terminal_view(terminal_item_index, cx);
+                self.activate_terminal_view(&terminal_pane, &terminal_to_replace, cx);
                 let task_workspace = self.workspace.clone();
                 cx.spawn(|_, mut cx| async move {
                     task_workspace
@@ -688,16 +923,105 @@ impl TerminalPanel {
     }
 
     fn has_no_terminals(&self, cx: &WindowContext) -> bool {
-        self.pane.read(cx).items_len() == 0 && self.pending_terminals_to_add == 0
+        self.panes.iter().all(|pane| pane.read(cx).items_len() == 0)
+            && self.pending_terminals_to_add == 0
     }
 
     pub fn assistant_enabled(&self) -> bool {
         self.assistant_enabled
     }
+
+    /// The terminals shared with the collaborators of the project.
+    pub fn shared_terminals(&self) -> &Model<SharedTerminalStore> {
+        &self.shared_terminals
+    }
+
+    fn handle_shared_terminals_event(
+        &mut self,
+        store: Model<SharedTerminalStore>,
+        event: &SharedTerminalStoreEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let SharedTerminalStoreEvent::TerminalShared(terminal_id) = *event;
+        let Some(title) = store
+            .read(cx)
+            .host_terminal(terminal_id)
+            .map(|terminal| terminal.title.clone())
+        else {
+            return;
+        };
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+
+        struct SharedTerminalToast;
+
+        let weak_workspace = self.workspace.clone();
+        workspace.update(cx, |workspace, cx| {
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::identified::<SharedTerminalToast>(terminal_id as usize),
+                    format!("The host shared the terminal \"{title}\""),
+                )
+                .on_click("Open", move |cx| {
+                    weak_workspace
+                        .update(cx, |workspace, cx| {
+                            Self::open_shared_terminal(workspace, store.clone(), terminal_id, cx)
+                        })
+                        .ok();
+                }),
+                cx,
+            );
+        });
+    }
+
+    /// Opens the terminals the host of the project shares which aren't open
+    /// yet.
+    fn open_shared_terminals(
+        workspace: &mut Workspace,
+        _: &OpenSharedTerminals,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(store) = workspace
+            .panel::<Self>(cx)
+            .map(|panel| panel.read(cx).shared_terminals.clone())
+        else {
+            return;
+        };
+        let terminal_ids = store
+            .read(cx)
+            .host_terminals()
+            .iter()
+            .map(|terminal| terminal.id)
+            .collect::<Vec<_>>();
+        for terminal_id in terminal_ids {
+            Self::open_shared_terminal(workspace, store.clone(), terminal_id, cx);
+        }
+    }
+
+    fn open_shared_terminal(
+        workspace: &mut Workspace,
+        store: Model<SharedTerminalStore>,
+        terminal_id: u64,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let existing = workspace
+            .items_of_type::<SharedTerminalView>(cx)
+            .find(|view| view.read(cx).terminal_id() == terminal_id);
+        match existing {
+            Some(view) => {
+                workspace.activate_item(&view, true, true, cx);
+            }
+            None => {
+                let view = cx.new_view(|cx| SharedTerminalView::new(store, terminal_id, cx));
+                workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+            }
+        }
+    }
 }
 
 async fn wait_for_terminals_tasks(
-    terminals_for_task: Vec<(usize, View<TerminalView>)>,
+    terminals_for_task: Vec<(View<Pane>, View<TerminalView>)>,
     cx: &mut AsyncWindowContext,
 ) {
     let pending_tasks = terminals_for_task.iter().filter_map(|(_, terminal)| {
@@ -712,6 +1036,76 @@ async fn wait_for_terminals_tasks(
     let _: Vec<()> = join_all(pending_tasks).await;
 }
 
+fn new_terminal_pane(
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    cx: &mut ViewContext<TerminalPanel>,
+) -> View<Pane> {
+    cx.new_view(|cx| {
+        let mut pane = Pane::new(
+            workspace.clone(),
+            project,
+            Default::default(),
+            None,
+            NewTerminal.boxed_clone(),
+            cx,
+        );
+        pane.set_can_split(false, cx);
+        pane.set_can_navigate(false, cx);
+        pane.display_nav_history_buttons(None);
+        pane.set_should_display_tab_bar(|_| true);
+
+        pane.set_custom_drop_handle(cx, move |pane, dropped_item, cx| {
+            if let Some(tab) = dropped_item.downcast_ref::<DraggedTab>() {
+                let item = if &tab.pane == cx.view() {
+                    pane.item_for_index(tab.ix)
+                } else {
+                    tab.pane.read(cx).item_for_index(tab.ix)
+                };
+                if let Some(item) = item {
+                    if item.downcast::<TerminalView>().is_some() {
+                        return ControlFlow::Continue(());
+                    } else if let Some(project_path) = item.project_path(cx) {
+                        if let Some(entry_path) = workspace
+                            .update(cx, |workspace, cx| {
+                                workspace
+                                    .project()
+                                    .read(cx)
+                                    .absolute_path(&project_path, cx)
+                            })
+                            .log_err()
+                            .flatten()
+                        {
+                            add_paths_to_terminal(pane, &[entry_path], cx);
+                        }
+                    }
+                }
+            } else if let Some(&entry_id) = dropped_item.downcast_ref::<ProjectEntryId>() {
+                if let Some(entry_path) = workspace
+                    .update(cx, |workspace, cx| {
+                        let project = workspace.project().read(cx);
+                        project
+                            .path_for_entry(entry_id, cx)
+                            .and_then(|project_path| project.absolute_path(&project_path, cx))
+                    })
+                    .log_err()
+                    .flatten()
+                {
+                    add_paths_to_terminal(pane, &[entry_path], cx);
+                }
+            } else if let Some(paths) = dropped_item.downcast_ref::<ExternalPaths>() {
+                add_paths_to_terminal(pane, paths.paths(), cx);
+            }
+
+            ControlFlow::Break(())
+        });
+        let buffer_search_bar = cx.new_view(search::BufferSearchBar::new);
+        pane.toolbar()
+            .update(cx, |toolbar, cx| toolbar.add_item(buffer_search_bar, cx));
+        pane
+    })
+}
+
 fn add_paths_to_terminal(pane: &mut Pane, paths: &[PathBuf], cx: &mut ViewContext<'_, Pane>) {
     if let Some(terminal_view) = pane
         .active_item()
@@ -735,7 +1129,7 @@ impl Render for TerminalPanel {
         let mut registrar = DivRegistrar::new(
             |panel, cx| {
                 panel
-                    .pane
+                    .active_pane
                     .read(cx)
                     .toolbar()
                     .read(cx)
@@ -744,13 +1138,46 @@ impl Render for TerminalPanel {
             cx,
         );
         BufferSearchBar::register(&mut registrar);
-        registrar.into_div().size_full().child(self.pane.clone())
+        let div = registrar.into_div().size_full().on_action(cx.listener(
+            |panel, action: &ActivatePaneInDirection, cx| {
+                if !panel.activate_pane_in_direction(action.0, cx) {
+                    cx.propagate();
+                }
+            },
+        ));
+        // A zoomed split takes up the whole panel.
+        if let Some(zoomed_pane) = self.panes.iter().find(|pane| pane.read(cx).is_zoomed()) {
+            return div.child(zoomed_pane.clone());
+        }
+        if self.panes.len() == 1 {
+            return div.child(self.active_pane.clone());
+        }
+
+        let active_pane_ix = self.panes.iter().position(|pane| *pane == self.active_pane);
+        let panel = cx.view().downgrade();
+        div.child(
+            pane_axis(
+                self.split_axis,
+                0,
+                self.flexes.clone(),
+                self.bounding_boxes.clone(),
+                move |cx| {
+                    panel.update(cx, |panel, cx| panel.serialize(cx)).ok();
+                },
+            )
+            .children(
+                self.panes
+                    .iter()
+                    .map(|pane| pane.clone().into_any_element()),
+            )
+            .with_active_pane(active_pane_ix),
+        )
     }
 }
 
 impl FocusableView for TerminalPanel {
     fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
-        self.pane.focus_handle(cx)
+        self.active_pane.focus_handle(cx)
     }
 }
 
@@ -802,11 +1229,14 @@ impl Panel for TerminalPanel {
     }
 
     fn is_zoomed(&self, cx: &WindowContext) -> bool {
-        self.pane.read(cx).is_zoomed()
+        self.panes.iter().any(|pane| pane.read(cx).is_zoomed())
     }
 
     fn set_zoomed(&mut self, zoomed: bool, cx: &mut ViewContext<Self>) {
-        self.pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        for pane in &self.panes {
+            let zoomed = zoomed && *pane == self.active_pane;
+            pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        }
     }
 
     fn set_active(&mut self, active: bool, cx: &mut ViewContext<Self>) {
@@ -826,7 +1256,11 @@ impl Panel for TerminalPanel {
     }
 
     fn icon_label(&self, cx: &WindowContext) -> Option<String> {
-        let count = self.pane.read(cx).items_len();
+        let count = self
+            .panes
+            .iter()
+            .map(|pane| pane.read(cx).items_len())
+            .sum::<usize>();
         if count == 0 {
             None
         } else {
@@ -855,7 +1289,7 @@ impl Panel for TerminalPanel {
     }
 
     fn pane(&self) -> Option<View<Pane>> {
-        Some(self.pane.clone())
+        Some(self.active_pane.clone())
     }
 }
 
@@ -874,12 +1308,68 @@ impl Render for InlineAssistTabBarButton {
 
 #[derive(Serialize, Deserialize)]
 struct SerializedTerminalPanel {
+    /// The terminals of panels serialized before they could be split.
+    #[serde(default, skip_serializing)]
     items: Vec<u64>,
+    #[serde(default, skip_serializing)]
     active_item_id: Option<u64>,
+    #[serde(default)]
+    splits: Vec<SerializedTerminalSplit>,
+    #[serde(default)]
+    active_split: usize,
+    #[serde(default)]
+    split_axis: SerializedSplitAxis,
     width: Option<Pixels>,
     height: Option<Pixels>,
 }
 
+impl SerializedTerminalPanel {
+    fn splits(&self) -> Vec<SerializedTerminalSplit> {
+        if self.splits.is_empty() {
+            vec![SerializedTerminalSplit {
+                items: self.items.clone(),
+                active_item_id: self.active_item_id,
+                flex: 1.,
+            }]
+        } else {
+            self.splits.clone()
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct SerializedTerminalSplit {
+    items: Vec<u64>,
+    active_item_id: Option<u64>,
+    flex: f32,
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SerializedSplitAxis {
+    #[default]
+    Horizontal,
+    Vertical,
+}
+
+impl From<Axis> for SerializedSplitAxis {
+    fn from(axis: Axis) -> Self {
+        match axis {
+            Axis::Horizontal => Self::Horizontal,
+            Axis::Vertical => Self::Vertical,
+        }
+    }
+}
+
+impl From<SerializedSplitAxis> for Axis {
+    fn from(axis: SerializedSplitAxis) -> Self {
+        match axis {
+            SerializedSplitAxis::Horizontal => Self::Horizontal,
+            SerializedSplitAxis::Vertical => Self::Vertical,
+        }
+    }
+}
+
 fn retrieve_system_shell() -> Option<String> {
     #[cfg(not(target_os = "windows"))]
     {
@@ -973,3 +1463,60 @@ enum WindowsShellType {
     Cmd,
     Other,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::px;
+
+    #[test]
+    fn test_serialize_splits() {
+        let splits = vec![
+            SerializedTerminalSplit {
+                items: vec![1, 2],
+                active_item_id: Some(2),
+                flex: 0.25,
+            },
+            SerializedTerminalSplit {
+                items: vec![3],
+                active_item_id: Some(3),
+                flex: 0.75,
+            },
+        ];
+        let json = serde_json::to_string(&SerializedTerminalPanel {
+            items: Vec::new(),
+            active_item_id: None,
+            splits: splits.clone(),
+            active_split: 1,
+            split_axis: SerializedSplitAxis::Vertical,
+            width: Some(px(400.)),
+            height: None,
+        })
+        .unwrap();
+        assert!(!json.contains("\"items\":[]"));
+
+        let panel = serde_json::from_str::<SerializedTerminalPanel>(&json).unwrap();
+        assert_eq!(panel.splits(), splits);
+        assert_eq!(panel.active_split, 1);
+        assert_eq!(Axis::from(panel.split_axis), Axis::Vertical);
+        assert_eq!(panel.width, Some(px(400.)));
+        assert_eq!(panel.height, None);
+    }
+
+    #[test]
+    fn test_deserialize_panel_without_splits() {
+        let json = r#"{"items":[1,2],"active_item_id":1,"width":null,"height":300.0}"#;
+        let panel = serde_json::from_str::<SerializedTerminalPanel>(json).unwrap();
+        assert_eq!(
+            panel.splits(),
+            [SerializedTerminalSplit {
+                items: vec![1, 2],
+                active_item_id: Some(1),
+                flex: 1.,
+            }]
+        );
+        assert_eq!(panel.active_split, 0);
+        assert_eq!(Axis::from(panel.split_axis), Axis::Horizontal);
+        assert_eq!(panel.height, Some(px(300.)));
+    }
+}
@@ -1,4 +1,5 @@
 mod persistence;
+pub mod shared_terminals;
 pub mod terminal_element;
 pub mod terminal_panel;
 
@@ -14,15 +15,16 @@ use gpui::{
 use language::Bias;
 use persistence::TERMINAL_DB;
 use project::{search::SearchQuery, terminals::TerminalKind, Fs, Metadata, Project};
+use shared_terminals::SharedTerminalStore;
 use terminal::{
     alacritty_terminal::{
         index::Point,
         term::{search::RegexSearch, TermMode},
     },
     terminal_settings::{TerminalBlink, TerminalSettings, WorkingDirectory},
-    Clear, Copy, Event, MaybeNavigationTarget, Paste, ScrollLineDown, ScrollLineUp, ScrollPageDown,
-    ScrollPageUp, ScrollToBottom, ScrollToTop, ShowCharacterPalette, TaskStatus, Terminal,
-    TerminalSize,
+    Clear, Copy, Event, MaybeNavigationTarget, Paste, RerunCommand, ScrollLineDown, ScrollLineUp,
+    ScrollPageDown, ScrollPageUp, ScrollToBottom, ScrollToNextCommand, ScrollToPreviousCommand,
+    ScrollToTop, SelectCommandOutput, ShowCharacterPalette, TaskStatus, Terminal, TerminalSize,
 };
 use terminal_element::{is_blank, TerminalElement};
 use terminal_panel::TerminalPanel;
@@ -219,6 +221,8 @@ impl TerminalView {
             .map_or(false, |terminal_panel| {
                 terminal_panel.read(cx).assistant_enabled()
             });
+        let has_shell_commands = self.terminal.read(cx).has_shell_commands();
+        let sharing = self.sharing_menu_entries(cx);
         let context_menu = ContextMenu::build(cx, |menu, _| {
             menu.context(self.focus_handle.clone())
                 .action("New Terminal", Box::new(NewTerminal))
@@ -227,10 +231,53 @@ impl TerminalView {
                 .action("Paste", Box::new(Paste))
                 .action("Select All", Box::new(SelectAll))
                 .action("Clear", Box::new(Clear))
+                .when(has_shell_commands, |menu| {
+                    menu.separator()
+                        .action("Select Command Output", Box::new(SelectCommandOutput))
+                        .action("Rerun Command", Box::new(RerunCommand))
+                })
                 .when(assistant_enabled, |menu| {
                     menu.separator()
                         .action("Inline Assist", Box::new(InlineAssist::default()))
                 })
+                .when_some(sharing, |menu, (store, is_shared, collaborators)| {
+                    let terminal = self.terminal.clone();
+                    let mut menu = menu.separator().toggleable_entry(
+                        "Share with Collaborators",
+                        is_shared,
+                        IconPosition::Start,
+                        None,
+                        {
+                            let store = store.clone();
+                            let terminal = terminal.clone();
+                            move |cx| {
+                                store.update(cx, |store, cx| {
+                                    if is_shared {
+                                        store.unshare_terminal(&terminal, cx);
+                                    } else {
+                                        store.share_terminal(&terminal, cx);
+                                    }
+                                });
+                            }
+                        },
+                    );
+                    for (user_id, login, can_write) in collaborators {
+                        let store = store.clone();
+                        let terminal = terminal.clone();
+                        menu = menu.toggleable_entry(
+                            format!("Allow @{login} to Type"),
+                            can_write,
+                            IconPosition::Start,
+                            None,
+                            move |cx| {
+                                store.update(cx, |store, cx| {
+                                    store.set_can_write(&terminal, user_id, !can_write, cx);
+                                });
+                            },
+                        );
+                    }
+                    menu
+                })
                 .separator()
                 .action("Close", Box::new(CloseActiveItem { save_intent: None }))
         });
@@ -250,6 +297,44 @@ impl TerminalView {
         self.context_menu = Some((context_menu, position, subscription));
     }
 
+    /// Returns the store of the terminals shared with collaborators, whether
+    /// this terminal is shared, and the collaborators with whether they may
+    /// type into it, when the project is hosted and shared.
+    fn sharing_menu_entries(
+        &self,
+        cx: &WindowContext,
+    ) -> Option<(Model<SharedTerminalStore>, bool, Vec<(u64, String, bool)>)> {
+        let workspace = self.workspace.upgrade()?;
+        let store = workspace
+            .read(cx)
+            .panel::<TerminalPanel>(cx)?
+            .read(cx)
+            .shared_terminals()
+            .clone();
+        if !store.read(cx).can_share(cx) {
+            return None;
+        }
+
+        let is_shared = store.read(cx).is_shared(&self.terminal);
+        let mut collaborators = Vec::new();
+        if is_shared {
+            let project = workspace.read(cx).project().read(cx);
+            let user_store = project.user_store().read(cx);
+            collaborators = project
+                .collaborators()
+                .values()
+                .filter_map(|collaborator| {
+                    let user = user_store.get_cached_user(collaborator.user_id)?;
+                    let can_write = store.read(cx).can_write(&self.terminal, user.id);
+                    Some((user.id, user.github_login.clone(), can_write))
+                })
+                .collect::<Vec<_>>();
+            collaborators.sort_by(|a, b| a.1.cmp(&b.1));
+            collaborators.dedup_by_key(|(user_id, _, _)| *user_id);
+        }
+        Some((store, is_shared, collaborators))
+    }
+
     fn settings_changed(&mut self, cx: &mut ViewContext<Self>) {
         let settings = TerminalSettings::get_global(cx);
         self.show_title = settings.toolbar.title;
@@ -406,6 +491,33 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn scroll_to_previous_command(
+        &mut self,
+        _: &ScrollToPreviousCommand,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_command());
+        cx.notify();
+    }
+
+    fn scroll_to_next_command(&mut self, _: &ScrollToNextCommand, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_command());
+        cx.notify();
+    }
+
+    fn select_command_output(&mut self, _: &SelectCommandOutput, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.select_command_output());
+        cx.notify();
+    }
+
+    fn rerun_command(&mut self, _: &RerunCommand, cx: &mut ViewContext<Self>) {
+        self.terminal.update(cx, |term, _| term.rerun_command());
+        cx.notify();
+    }
+
     fn scroll_to_bottom(&mut self, _: &ScrollToBottom, cx: &mut ViewContext<Self>) {
         self.terminal.update(cx, |term, _| term.scroll_to_bottom());
         if self.block_below_cursor.is_some() {
@@ -429,6 +541,10 @@ impl TerminalView {
             return true;
         }
 
+        if cx.reduce_motion() {
+            return true;
+        }
+
         match TerminalSettings::get_global(cx).blinking {
             //If the user requested to never blink, don't blink it.
             TerminalBlink::Off => true,
@@ -863,12 +979,30 @@ fn regex_to_literal(regex: &str) -> String {
 }
 
 pub fn regex_search_for_query(query: &project::search::SearchQuery) -> Option<RegexSearch> {
-    let query = query.as_str();
-    if query == "." {
+    if query.is_regex() && query.as_str() == "." {
         return None;
     }
-    let searcher = RegexSearch::new(&query);
-    searcher.ok()
+    RegexSearch::new(&search_pattern(query)).ok()
+}
+
+/// The regex searching the terminal for the query, with its options applied.
+fn search_pattern(query: &SearchQuery) -> String {
+    let mut pattern = if query.is_regex() {
+        query.as_str().to_owned()
+    } else {
+        regex_to_literal(query.as_str())
+    };
+    if query.whole_word() {
+        pattern = format!(r"\b(?:{pattern})\b");
+    }
+    // The flag overrides the smart case of the terminal search, which is case
+    // sensitive only for queries with uppercase letters.
+    let case_flag = if query.case_sensitive() {
+        "(?-i)"
+    } else {
+        "(?i)"
+    };
+    format!("{case_flag}{pattern}")
 }
 
 impl TerminalView {
@@ -925,6 +1059,10 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::scroll_page_down))
             .on_action(cx.listener(TerminalView::scroll_to_top))
             .on_action(cx.listener(TerminalView::scroll_to_bottom))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_command))
+            .on_action(cx.listener(TerminalView::scroll_to_next_command))
+            .on_action(cx.listener(TerminalView::select_command_output))
+            .on_action(cx.listener(TerminalView::rerun_command))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
             .on_key_down(cx.listener(Self::key_down))
@@ -1187,8 +1325,8 @@ impl SearchableItem for TerminalView {
 
     fn supported_options() -> SearchOptions {
         SearchOptions {
-            case: false,
-            word: false,
+            case: true,
+            word: true,
             regex: true,
             replacement: false,
             selection: false,
@@ -1236,23 +1374,7 @@ impl SearchableItem for TerminalView {
         query: Arc<SearchQuery>,
         cx: &mut ViewContext<Self>,
     ) -> Task<Vec<Self::Match>> {
-        let searcher = match &*query {
-            SearchQuery::Text { .. } => regex_search_for_query(
-                &(SearchQuery::text(
-                    regex_to_literal(&query.as_str()),
-                    query.whole_word(),
-                    query.case_sensitive(),
-                    query.include_ignored(),
-                    query.files_to_include().clone(),
-                    query.files_to_exclude().clone(),
-                    None,
-                )
-                .unwrap()),
-            ),
-            SearchQuery::Regex { .. } => regex_search_for_query(&query),
-        };
-
-        if let Some(s) = searcher {
+        if let Some(s) = regex_search_for_query(&query) {
             self.terminal()
                 .update(cx, |term, cx| term.find_matches(s, cx))
         } else {
@@ -1525,4 +1647,41 @@ mod tests {
     fn empty_string_stays_empty() {
         assert_eq!(regex_to_literal(""), "".to_string());
     }
+
+    #[test]
+    fn search_pattern_applies_options() {
+        let text = |query: &str, whole_word, case_sensitive| {
+            SearchQuery::text(
+                query,
+                whole_word,
+                case_sensitive,
+                false,
+                Default::default(),
+                Default::default(),
+                None,
+            )
+            .unwrap()
+        };
+        let regex = |query: &str, whole_word, case_sensitive| {
+            SearchQuery::regex(
+                query,
+                whole_word,
+                case_sensitive,
+                false,
+                Default::default(),
+                Default::default(),
+                None,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(search_pattern(&text("a.b", false, false)), r"(?i)a\.b");
+        assert_eq!(search_pattern(&text("Error", false, true)), "(?-i)Error");
+        assert_eq!(
+            search_pattern(&regex(r"err(or)?", true, false)),
+            r"(?i)\b(?:err(or)?)\b"
+        );
+        assert!(regex_search_for_query(&regex(".", false, false)).is_none());
+        assert!(regex_search_for_query(&text(".", false, false)).is_some());
+    }
 }
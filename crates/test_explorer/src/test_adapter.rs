@@ -0,0 +1,643 @@
+use collections::HashMap;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// An update about a test, parsed from the output of a test run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TestEvent {
+    Passed(String),
+    Failed(String),
+    Ignored(String),
+    /// A line of the output of a failed test.
+    FailureOutput {
+        test: String,
+        line: String,
+    },
+}
+
+/// A command to run in the root of a worktree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestCommand {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl TestCommand {
+    fn new(program: &str, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            program: program.to_string(),
+            args: args.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Discovers and runs the tests of a worktree with a language-specific test
+/// runner, such as `cargo test`.
+pub trait TestAdapter: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// The files at the root of a worktree that indicate this adapter applies.
+    fn root_markers(&self) -> &'static [&'static str];
+
+    /// The tags of the runnables, as set in `runnables.scm`, that are tests
+    /// run by this adapter.
+    fn runnable_tags(&self) -> &'static [&'static str];
+
+    /// The command listing the tests of the worktree, if the test runner supports it.
+    fn discover_command(&self) -> Option<TestCommand>;
+
+    /// Parses the output of the [`TestAdapter::discover_command`].
+    fn parse_discovered_tests(&self, output: &str) -> Vec<String>;
+
+    /// Returns the id of the test defined by a runnable named `name` in the
+    /// file at `path`, if the id can be derived from the source alone.
+    fn test_id_for_runnable(
+        &self,
+        path: &Path,
+        name: &str,
+        extra_captures: &HashMap<String, String>,
+    ) -> Option<String>;
+
+    /// Splits a test id into the path of the test in the test tree.
+    fn test_path(&self, test: &str) -> Vec<String>;
+
+    /// Joins a path of the test tree back into the filter passed to
+    /// [`TestAdapter::run_command`].
+    fn test_filter(&self, path: &[String]) -> String;
+
+    /// The command running the tests matching `filter`, or all tests.
+    /// When `exact` is true, the filter is the id of a single test.
+    fn run_command(&self, filter: Option<&str>, exact: bool) -> TestCommand;
+
+    fn output_parser(&self) -> Box<dyn TestOutputParser>;
+}
+
+/// Parses the output of a test run, line by line.
+pub trait TestOutputParser: Send {
+    fn parse_line(&mut self, line: &str) -> Vec<TestEvent>;
+}
+
+pub fn test_adapters() -> Vec<Arc<dyn TestAdapter>> {
+    vec![
+        Arc::new(CargoTestAdapter),
+        Arc::new(PytestAdapter),
+        Arc::new(JestAdapter),
+    ]
+}
+
+/// Finds the first source location, such as `src/lib.rs:10:5`, in a line of
+/// the output of a failed test. The path is relative to the worktree root.
+pub fn failure_location(line: &str) -> Option<(PathBuf, u32)> {
+    line.split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .find_map(|token| {
+            let token = token.trim_matches(|c| c == ':' || c == ',');
+            let mut parts = token.split(':');
+            let path = parts.next()?;
+            let row = parts.next()?.parse::<u32>().ok()?;
+            let has_extension = Path::new(path).extension().is_some();
+            let is_dependency = path.starts_with('/') || path.contains("node_modules");
+            (has_extension && !is_dependency).then(|| (PathBuf::from(path), row.saturating_sub(1)))
+        })
+}
+
+pub struct CargoTestAdapter;
+
+impl TestAdapter for CargoTestAdapter {
+    fn name(&self) -> &'static str {
+        "cargo test"
+    }
+
+    fn root_markers(&self) -> &'static [&'static str] {
+        &["Cargo.toml"]
+    }
+
+    fn runnable_tags(&self) -> &'static [&'static str] {
+        &["rust-test"]
+    }
+
+    fn discover_command(&self) -> Option<TestCommand> {
+        Some(TestCommand::new(
+            "cargo",
+            ["test", "--workspace", "--", "--list"],
+        ))
+    }
+
+    fn parse_discovered_tests(&self, output: &str) -> Vec<String> {
+        output
+            .lines()
+            .filter_map(|line| line.strip_suffix(": test"))
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn test_id_for_runnable(
+        &self,
+        _: &Path,
+        _: &str,
+        _: &HashMap<String, String>,
+    ) -> Option<String> {
+        // The module path of a test can't be derived from the source alone.
+        None
+    }
+
+    fn test_path(&self, test: &str) -> Vec<String> {
+        test.split("::").map(str::to_string).collect()
+    }
+
+    fn test_filter(&self, path: &[String]) -> String {
+        path.join("::")
+    }
+
+    fn run_command(&self, filter: Option<&str>, exact: bool) -> TestCommand {
+        let mut args = vec!["test", "--workspace", "--"];
+        if let Some(filter) = filter {
+            args.push(filter);
+            if exact {
+                args.push("--exact");
+            }
+        }
+        TestCommand::new("cargo", args)
+    }
+
+    fn output_parser(&self) -> Box<dyn TestOutputParser> {
+        Box::new(CargoTestOutputParser::default())
+    }
+}
+
+#[derive(Default)]
+struct CargoTestOutputParser {
+    failure_section: Option<String>,
+}
+
+impl TestOutputParser for CargoTestOutputParser {
+    fn parse_line(&mut self, line: &str) -> Vec<TestEvent> {
+        if let Some(test) = line
+            .strip_prefix("---- ")
+            .and_then(|line| line.strip_suffix(" stdout ----"))
+        {
+            self.failure_section = Some(test.to_string());
+            return Vec::new();
+        }
+        if line == "failures:" || line.starts_with("test result:") {
+            self.failure_section = None;
+            return Vec::new();
+        }
+        if let Some(test) = &self.failure_section {
+            return vec![TestEvent::FailureOutput {
+                test: test.clone(),
+                line: line.to_string(),
+            }];
+        }
+
+        let Some((test, result)) = line
+            .strip_prefix("test ")
+            .and_then(|line| line.split_once(" ... "))
+        else {
+            return Vec::new();
+        };
+        let test = test.to_string();
+        match result.trim() {
+            "ok" => vec![TestEvent::Passed(test)],
+            "FAILED" => vec![TestEvent::Failed(test)],
+            result if result.starts_with("ignored") => vec![TestEvent::Ignored(test)],
+            _ => Vec::new(),
+        }
+    }
+}
+
+pub struct PytestAdapter;
+
+impl TestAdapter for PytestAdapter {
+    fn name(&self) -> &'static str {
+        "pytest"
+    }
+
+    fn root_markers(&self) -> &'static [&'static str] {
+        &[
+            "pytest.ini",
+            "conftest.py",
+            "pyproject.toml",
+            "setup.cfg",
+            "tox.ini",
+        ]
+    }
+
+    fn runnable_tags(&self) -> &'static [&'static str] {
+        &["python-pytest", "python-unittest-method"]
+    }
+
+    fn discover_command(&self) -> Option<TestCommand> {
+        Some(TestCommand::new(
+            "python",
+            ["-m", "pytest", "--collect-only", "-q"],
+        ))
+    }
+
+    fn parse_discovered_tests(&self, output: &str) -> Vec<String> {
+        output
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.contains("::") && !line.contains(' '))
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn test_id_for_runnable(
+        &self,
+        path: &Path,
+        name: &str,
+        extra_captures: &HashMap<String, String>,
+    ) -> Option<String> {
+        let path = path.to_str()?;
+        Some(match extra_captures.get("_unittest_class_name") {
+            Some(class_name) if class_name != name => format!("{path}::{class_name}::{name}"),
+            _ => format!("{path}::{name}"),
+        })
+    }
+
+    fn test_path(&self, test: &str) -> Vec<String> {
+        test.split("::").map(str::to_string).collect()
+    }
+
+    fn test_filter(&self, path: &[String]) -> String {
+        path.join("::")
+    }
+
+    fn run_command(&self, filter: Option<&str>, _: bool) -> TestCommand {
+        let mut args = vec!["-m", "pytest", "-v"];
+        args.extend(filter);
+        TestCommand::new("python", args)
+    }
+
+    fn output_parser(&self) -> Box<dyn TestOutputParser> {
+        Box::new(PytestOutputParser::default())
+    }
+}
+
+#[derive(Default)]
+struct PytestOutputParser {
+    failed_tests: Vec<String>,
+    failure_section: Option<String>,
+}
+
+impl TestOutputParser for PytestOutputParser {
+    fn parse_line(&mut self, line: &str) -> Vec<TestEvent> {
+        if line.starts_with('_') && line.ends_with('_') {
+            // Failure sections are titled with the name of the test, without its file.
+            let name = line
+                .trim_matches(|c| c == '_' || c == ' ')
+                .replace('.', "::");
+            self.failure_section = self
+                .failed_tests
+                .iter()
+                .find(|test| test.ends_with(&format!("::{name}")))
+                .cloned();
+            return Vec::new();
+        }
+        if line.starts_with('=') {
+            self.failure_section = None;
+            return Vec::new();
+        }
+        if let Some(test) = &self.failure_section {
+            return vec![TestEvent::FailureOutput {
+                test: test.clone(),
+                line: line.to_string(),
+            }];
+        }
+
+        let mut parts = line.split_whitespace();
+        let (Some(test), Some(result)) = (parts.next(), parts.next()) else {
+            return Vec::new();
+        };
+        if !test.contains("::") {
+            return Vec::new();
+        }
+        let test = test.to_string();
+        match result {
+            "PASSED" | "XPASS" => vec![TestEvent::Passed(test)],
+            "FAILED" | "ERROR" => {
+                self.failed_tests.push(test.clone());
+                vec![TestEvent::Failed(test)]
+            }
+            "SKIPPED" | "XFAIL" => vec![TestEvent::Ignored(test)],
+            _ => Vec::new(),
+        }
+    }
+}
+
+pub struct JestAdapter;
+
+impl JestAdapter {
+    const SEPARATOR: &'static str = " > ";
+}
+
+impl TestAdapter for JestAdapter {
+    fn name(&self) -> &'static str {
+        "jest"
+    }
+
+    fn root_markers(&self) -> &'static [&'static str] {
+        &[
+            "jest.config.js",
+            "jest.config.ts",
+            "jest.config.mjs",
+            "jest.config.cjs",
+            "jest.config.json",
+        ]
+    }
+
+    fn runnable_tags(&self) -> &'static [&'static str] {
+        &["js-test", "ts-test", "tsx-test"]
+    }
+
+    fn discover_command(&self) -> Option<TestCommand> {
+        // Jest only lists test files, so tests are discovered from the runnables
+        // of the open buffers and from test runs.
+        None
+    }
+
+    fn parse_discovered_tests(&self, _: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn test_id_for_runnable(
+        &self,
+        path: &Path,
+        name: &str,
+        _: &HashMap<String, String>,
+    ) -> Option<String> {
+        Some(format!("{}{}{name}", path.to_str()?, Self::SEPARATOR))
+    }
+
+    fn test_path(&self, test: &str) -> Vec<String> {
+        test.splitn(2, Self::SEPARATOR)
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn test_filter(&self, path: &[String]) -> String {
+        path.join(Self::SEPARATOR)
+    }
+
+    fn run_command(&self, filter: Option<&str>, _: bool) -> TestCommand {
+        let mut args = vec!["jest".to_string(), "--verbose".to_string()];
+        if let Some(filter) = filter {
+            match filter.split_once(Self::SEPARATOR) {
+                Some((path, name)) => {
+                    args.extend([path.to_string(), "-t".to_string(), name.to_string()])
+                }
+                None => args.push(filter.to_string()),
+            }
+        }
+        TestCommand::new("npx", args)
+    }
+
+    fn output_parser(&self) -> Box<dyn TestOutputParser> {
+        Box::new(JestOutputParser::default())
+    }
+}
+
+#[derive(Default)]
+struct JestOutputParser {
+    file: Option<String>,
+    failed_tests: Vec<String>,
+    failure_section: Option<String>,
+}
+
+impl JestOutputParser {
+    fn test_id(&self, name: &str) -> Option<String> {
+        let name = match name.rsplit_once(" (") {
+            Some((name, duration)) if duration.ends_with("ms)") || duration.ends_with("s)") => name,
+            _ => name,
+        };
+        Some(format!(
+            "{}{}{name}",
+            self.file.as_ref()?,
+            JestAdapter::SEPARATOR
+        ))
+    }
+}
+
+impl TestOutputParser for JestOutputParser {
+    fn parse_line(&mut self, line: &str) -> Vec<TestEvent> {
+        let trimmed = line.trim();
+        if let Some(file) = trimmed
+            .strip_prefix("PASS ")
+            .or_else(|| trimmed.strip_prefix("FAIL "))
+        {
+            self.file = Some(file.trim().to_string());
+            self.failure_section = None;
+            return Vec::new();
+        }
+        if let Some(title) = trimmed.strip_prefix("● ") {
+            let name = title.rsplit(" › ").next().unwrap_or(title);
+            self.failure_section = self
+                .failed_tests
+                .iter()
+                .find(|test| test.ends_with(&format!("{}{name}", JestAdapter::SEPARATOR)))
+                .cloned();
+            return Vec::new();
+        }
+        if trimmed.starts_with("Test Suites:") {
+            self.failure_section = None;
+            return Vec::new();
+        }
+        if let Some(test) = &self.failure_section {
+            return vec![TestEvent::FailureOutput {
+                test: test.clone(),
+                line: line.to_string(),
+            }];
+        }
+
+        if let Some(name) = trimmed
+            .strip_prefix("✓ ")
+            .or_else(|| trimmed.strip_prefix("√ "))
+        {
+            return self
+                .test_id(name)
+                .map(TestEvent::Passed)
+                .into_iter()
+                .collect();
+        }
+        if let Some(name) = trimmed
+            .strip_prefix("✕ ")
+            .or_else(|| trimmed.strip_prefix("× "))
+        {
+            let Some(test) = self.test_id(name) else {
+                return Vec::new();
+            };
+            self.failed_tests.push(test.clone());
+            return vec![TestEvent::Failed(test)];
+        }
+        if let Some(name) = trimmed.strip_prefix("○ ") {
+            let name = name.strip_prefix("skipped ").unwrap_or(name);
+            return self
+                .test_id(name)
+                .map(TestEvent::Ignored)
+                .into_iter()
+                .collect();
+        }
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(parser: &mut dyn TestOutputParser, output: &str) -> Vec<TestEvent> {
+        output
+            .lines()
+            .flat_map(|line| parser.parse_line(line))
+            .collect()
+    }
+
+    #[test]
+    fn test_cargo_test_output() {
+        let adapter = CargoTestAdapter;
+        assert_eq!(
+            adapter.parse_discovered_tests(
+                "tests::it_adds: test\ntests::it_fails: test\n\n2 tests, 0 benchmarks\n"
+            ),
+            vec!["tests::it_adds", "tests::it_fails"]
+        );
+
+        let output = "\
+running 3 tests
+test tests::it_adds ... ok
+test tests::it_is_slow ... ignored, too slow
+test tests::it_fails ... FAILED
+
+failures:
+
+---- tests::it_fails stdout ----
+thread 'tests::it_fails' panicked at src/lib.rs:12:9:
+assertion failed
+
+failures:
+    tests::it_fails
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored
+";
+        assert_eq!(
+            parse(adapter.output_parser().as_mut(), output),
+            vec![
+                TestEvent::Passed("tests::it_adds".into()),
+                TestEvent::Ignored("tests::it_is_slow".into()),
+                TestEvent::Failed("tests::it_fails".into()),
+                TestEvent::FailureOutput {
+                    test: "tests::it_fails".into(),
+                    line: "thread 'tests::it_fails' panicked at src/lib.rs:12:9:".into(),
+                },
+                TestEvent::FailureOutput {
+                    test: "tests::it_fails".into(),
+                    line: "assertion failed".into(),
+                },
+                TestEvent::FailureOutput {
+                    test: "tests::it_fails".into(),
+                    line: "".into(),
+                },
+            ]
+        );
+        assert_eq!(
+            adapter.run_command(Some("tests::it_adds"), true).args,
+            vec!["test", "--workspace", "--", "tests::it_adds", "--exact"]
+        );
+    }
+
+    #[test]
+    fn test_pytest_output() {
+        let adapter = PytestAdapter;
+        assert_eq!(
+            adapter.parse_discovered_tests(
+                "tests/test_math.py::test_add\ntests/test_math.py::TestDiv::test_div\n\n2 tests collected in 0.01s\n"
+            ),
+            vec!["tests/test_math.py::test_add", "tests/test_math.py::TestDiv::test_div"]
+        );
+
+        let output = "\
+tests/test_math.py::test_add PASSED                    [ 50%]
+tests/test_math.py::TestDiv::test_div FAILED           [100%]
+
+=================================== FAILURES ===================================
+______________________________ TestDiv.test_div _______________________________
+>       assert 1 / 1 == 2
+tests/test_math.py:9: AssertionError
+=========================== short test summary info ============================
+";
+        assert_eq!(
+            parse(adapter.output_parser().as_mut(), output),
+            vec![
+                TestEvent::Passed("tests/test_math.py::test_add".into()),
+                TestEvent::Failed("tests/test_math.py::TestDiv::test_div".into()),
+                TestEvent::FailureOutput {
+                    test: "tests/test_math.py::TestDiv::test_div".into(),
+                    line: ">       assert 1 / 1 == 2".into(),
+                },
+                TestEvent::FailureOutput {
+                    test: "tests/test_math.py::TestDiv::test_div".into(),
+                    line: "tests/test_math.py:9: AssertionError".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_jest_output() {
+        let adapter = JestAdapter;
+        let output = "\
+FAIL src/sum.test.js
+  sum
+    ✓ adds numbers (3 ms)
+    ✕ subtracts numbers (2 ms)
+    ○ skipped multiplies numbers
+
+  ● sum › subtracts numbers
+
+    expect(received).toBe(expected)
+      at Object.<anonymous> (src/sum.test.js:10:5)
+
+Test Suites: 1 failed, 1 total
+";
+        let events = parse(adapter.output_parser().as_mut(), output);
+        assert_eq!(
+            &events[..3],
+            &[
+                TestEvent::Passed("src/sum.test.js > adds numbers".into()),
+                TestEvent::Failed("src/sum.test.js > subtracts numbers".into()),
+                TestEvent::Ignored("src/sum.test.js > multiplies numbers".into()),
+            ]
+        );
+        assert!(events[3..].iter().all(|event| matches!(
+            event,
+            TestEvent::FailureOutput { test, .. } if test == "src/sum.test.js > subtracts numbers"
+        )));
+        assert_eq!(
+            adapter.test_path("src/sum.test.js > subtracts numbers"),
+            vec!["src/sum.test.js", "subtracts numbers"]
+        );
+    }
+
+    #[test]
+    fn test_failure_location() {
+        assert_eq!(
+            failure_location("thread 'tests::it_fails' panicked at src/lib.rs:12:9:"),
+            Some((PathBuf::from("src/lib.rs"), 11))
+        );
+        assert_eq!(
+            failure_location("tests/test_math.py:9: AssertionError"),
+            Some((PathBuf::from("tests/test_math.py"), 8))
+        );
+        assert_eq!(
+            failure_location("      at Object.<anonymous> (src/sum.test.js:10:5)"),
+            Some((PathBuf::from("src/sum.test.js"), 9))
+        );
+        assert_eq!(
+            failure_location("    at node_modules/jest-circus/build/run.js:316:40"),
+            None
+        );
+        assert_eq!(failure_location("assertion failed"), None);
+    }
+}
@@ -0,0 +1,878 @@
+mod test_adapter;
+mod test_explorer_settings;
+mod test_failures;
+mod test_tree;
+
+use std::{ops::Range, path::Path, sync::Arc};
+
+use anyhow::Context as _;
+use collections::HashMap;
+use editor::{Editor, EditorEvent};
+use futures::StreamExt as _;
+use gpui::{
+    actions, uniform_list, Action, AnyElement, AppContext, AsyncWindowContext, EntityId,
+    EventEmitter, FocusHandle, FocusableView, Model, Subscription, Task, UniformListScrollHandle,
+    View, WeakView,
+};
+use language::{Anchor, BufferSnapshot};
+use project::{worktree_trust::WorktreeTrust, Fs, Project, WorktreeId};
+use settings::Settings;
+use smol::io::{AsyncBufReadExt as _, BufReader};
+use test_adapter::{test_adapters, TestAdapter, TestEvent};
+use test_explorer_settings::{TestExplorerPanelDockPosition, TestExplorerPanelSettings};
+use test_failures::{open_test_failures, TestFailure};
+use test_tree::{TestEntry, TestStatus, TestTree};
+use ui::{prelude::*, IconButtonShape, ListItem, Tooltip};
+use util::ResultExt;
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    Workspace,
+};
+
+actions!(
+    test_explorer,
+    [ToggleFocus, RunAllTests, DiscoverTests, ShowTestFailures]
+);
+
+pub fn init(cx: &mut AppContext) {
+    TestExplorerPanelSettings::register(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, cx| {
+            workspace.toggle_panel_focus::<TestExplorerPanel>(cx);
+        });
+        workspace.register_action(|workspace, _: &RunAllTests, cx| {
+            if let Some(panel) = workspace.panel::<TestExplorerPanel>(cx) {
+                panel.update(cx, |panel, cx| panel.run_all_tests(cx));
+            }
+        });
+        workspace.register_action(|workspace, _: &DiscoverTests, cx| {
+            if let Some(panel) = workspace.panel::<TestExplorerPanel>(cx) {
+                panel.update(cx, |panel, cx| panel.discover_all_tests(cx));
+            }
+        });
+        workspace.register_action(|workspace, _: &ShowTestFailures, cx| {
+            if let Some(panel) = workspace.panel::<TestExplorerPanel>(cx) {
+                panel.update(cx, |panel, cx| panel.show_all_failures(cx));
+            }
+        });
+    })
+    .detach();
+}
+
+struct PassedTestGutter;
+struct FailedTestGutter;
+
+/// The tests of a worktree run by one test adapter.
+struct TestSuite {
+    worktree_id: WorktreeId,
+    name: SharedString,
+    root: Arc<Path>,
+    adapter: Arc<dyn TestAdapter>,
+    tree: TestTree,
+    /// The output of the failed tests of the last run, by test.
+    failures: HashMap<String, Vec<String>>,
+    is_expanded: bool,
+    /// Whether the tests were listed with the adapter's discover command.
+    is_discovered: bool,
+    _discover_task: Option<Task<()>>,
+    run_task: Option<Task<()>>,
+}
+
+impl TestSuite {
+    fn insert(&mut self, test: String) {
+        if !self.tree.contains(&test) {
+            let path = self.adapter.test_path(&test);
+            self.tree.insert(path, test);
+        }
+    }
+
+    fn set_status(&mut self, test: String, status: TestStatus) {
+        self.insert(test.clone());
+        self.tree.set_status(&test, status);
+    }
+
+    fn is_running(&self) -> bool {
+        self.tree.status_of_all() == TestStatus::Running
+    }
+
+    fn failures(&self, tests: &[&str]) -> Vec<TestFailure> {
+        tests
+            .iter()
+            .filter(|test| self.tree.status(test) == TestStatus::Failed)
+            .filter_map(|test| {
+                Some(TestFailure {
+                    test: test.to_string(),
+                    output: self.failures.get(*test)?.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// A test defined in a buffer, as found by the runnables query of its language.
+struct RunnableTest {
+    suite_ix: usize,
+    range: Range<usize>,
+    name: String,
+    test: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+enum PanelEntry {
+    Suite(usize),
+    Test(usize, TestEntry),
+}
+
+pub struct TestExplorerPanel {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    width: Option<Pixels>,
+    /// Whether the panel is open in its dock.
+    active: bool,
+    suites: Vec<TestSuite>,
+    entries: Vec<PanelEntry>,
+    editor_subscriptions: HashMap<EntityId, Subscription>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl TestExplorerPanel {
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<View<Self>> {
+        workspace.update(&mut cx, |workspace, cx| Self::new(workspace, cx))
+    }
+
+    fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let project = workspace.project().clone();
+        let fs = workspace.app_state().fs.clone();
+        let workspace_handle = cx.view().clone();
+        cx.new_view(|cx| {
+            let project_subscription = cx.subscribe(&project, |this, _, event, cx| match event {
+                project::Event::WorktreeAdded
+                | project::Event::WorktreeRemoved(_)
+                | project::Event::WorktreeUpdatedEntries(..) => this.update_suites(cx),
+                _ => {}
+            });
+            let workspace_subscription =
+                cx.subscribe(&workspace_handle, |this, _, event, cx| match event {
+                    workspace::Event::ItemAdded | workspace::Event::ItemRemoved => {
+                        this.update_editor_subscriptions(cx);
+                        this.discover_runnable_tests(cx);
+                        this.update_gutters(cx);
+                    }
+                    _ => {}
+                });
+            let mut settings = *TestExplorerPanelSettings::get_global(cx);
+            let settings_subscription =
+                cx.observe_global::<settings::SettingsStore>(move |this, cx| {
+                    let new_settings = *TestExplorerPanelSettings::get_global(cx);
+                    if settings != new_settings {
+                        settings = new_settings;
+                        this.update_gutters(cx);
+                        cx.notify();
+                    }
+                });
+
+            let mut this = Self {
+                workspace: workspace_handle.downgrade(),
+                project,
+                fs,
+                focus_handle: cx.focus_handle(),
+                scroll_handle: UniformListScrollHandle::new(),
+                width: None,
+                active: false,
+                suites: Vec::new(),
+                entries: Vec::new(),
+                editor_subscriptions: HashMap::default(),
+                _subscriptions: vec![
+                    project_subscription,
+                    workspace_subscription,
+                    settings_subscription,
+                ],
+            };
+            this.update_suites(cx);
+            this
+        })
+    }
+
+    /// Creates a suite for every adapter whose root markers are present in a
+    /// worktree. Tests are run as local processes, so remote projects have no
+    /// suites.
+    fn update_suites(&mut self, cx: &mut ViewContext<Self>) {
+        let project = self.project.read(cx);
+        let worktrees = if project.is_local() {
+            project.visible_worktrees(cx).collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
+
+        let mut new_suites = Vec::new();
+        for worktree in &worktrees {
+            let worktree = worktree.read(cx);
+            for adapter in test_adapters() {
+                let is_present = adapter
+                    .root_markers()
+                    .iter()
+                    .any(|marker| worktree.entry_for_path(marker).is_some());
+                let exists = self.suites.iter().any(|suite| {
+                    suite.worktree_id == worktree.id() && suite.adapter.name() == adapter.name()
+                });
+                if is_present && !exists {
+                    new_suites.push(TestSuite {
+                        worktree_id: worktree.id(),
+                        name: format!("{} ({})", worktree.root_name(), adapter.name()).into(),
+                        root: worktree.abs_path().clone(),
+                        adapter,
+                        tree: TestTree::default(),
+                        failures: HashMap::default(),
+                        is_expanded: true,
+                        is_discovered: false,
+                        _discover_task: None,
+                        run_task: None,
+                    });
+                }
+            }
+        }
+
+        let old_len = self.suites.len();
+        self.suites.retain(|suite| {
+            worktrees
+                .iter()
+                .any(|worktree| worktree.read(cx).id() == suite.worktree_id)
+        });
+        if new_suites.is_empty() && self.suites.len() == old_len {
+            return;
+        }
+
+        self.suites.extend(new_suites);
+        if self.active {
+            self.discover_undiscovered_tests(cx);
+        }
+        self.discover_runnable_tests(cx);
+        self.update_entries(cx);
+        self.update_gutters(cx);
+    }
+
+    fn suite_ix(&self, worktree_id: WorktreeId, adapter_name: &str) -> Option<usize> {
+        self.suites.iter().position(|suite| {
+            suite.worktree_id == worktree_id && suite.adapter.name() == adapter_name
+        })
+    }
+
+    fn discover_all_tests(&mut self, cx: &mut ViewContext<Self>) {
+        for suite_ix in 0..self.suites.len() {
+            self.discover_tests(suite_ix, cx);
+        }
+        self.discover_runnable_tests(cx);
+        self.update_entries(cx);
+    }
+
+    /// Lists the tests of the suites whose tests weren't listed yet, unless the
+    /// `auto_discover` setting is disabled.
+    fn discover_undiscovered_tests(&mut self, cx: &mut ViewContext<Self>) {
+        if !TestExplorerPanelSettings::get_global(cx).auto_discover {
+            return;
+        }
+        for suite_ix in 0..self.suites.len() {
+            if !self.suites[suite_ix].is_discovered {
+                self.discover_tests(suite_ix, cx);
+            }
+        }
+    }
+
+    /// Lists the tests of the suite by running the adapter's discover command,
+    /// which builds and runs code of the project, so restricted worktrees are
+    /// skipped.
+    fn discover_tests(&mut self, suite_ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(suite) = self.suites.get(suite_ix) else {
+            return;
+        };
+        if WorktreeTrust::is_restricted(suite.worktree_id, cx) {
+            return;
+        }
+        let suite = &mut self.suites[suite_ix];
+        let Some(command) = suite.adapter.discover_command() else {
+            return;
+        };
+        suite.is_discovered = true;
+        let worktree_id = suite.worktree_id;
+        let adapter = suite.adapter.clone();
+        let root = suite.root.clone();
+        let environment = self.project.update(cx, |project, cx| {
+            project.worktree_environment(worktree_id, cx)
+        });
+        let suite = &mut self.suites[suite_ix];
+        suite._discover_task = Some(cx.spawn(|this, mut cx| async move {
+            let environment = environment.await.unwrap_or_default();
+            let output = smol::process::Command::new(&command.program)
+                .args(&command.args)
+                .envs(environment)
+                .current_dir(&root)
+                .output()
+                .await
+                .with_context(|| format!("discovering tests with {}", command.program))
+                .log_err();
+            let Some(output) = output else {
+                return;
+            };
+            let tests = adapter.parse_discovered_tests(&String::from_utf8_lossy(&output.stdout));
+            this.update(&mut cx, |this, cx| {
+                if let Some(suite_ix) = this.suite_ix(worktree_id, adapter.name()) {
+                    for test in tests {
+                        this.suites[suite_ix].insert(test);
+                    }
+                    this.update_entries(cx);
+                    this.update_gutters(cx);
+                }
+            })
+            .ok();
+        }));
+    }
+
+    /// Adds the tests defined in the open buffers, which covers test runners
+    /// that can't list their tests and tests that were added since the last
+    /// discovery.
+    fn discover_runnable_tests(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let mut discovered = Vec::new();
+        for editor in workspace.read(cx).items_of_type::<Editor>(cx) {
+            if let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() {
+                discovered.extend(self.runnable_tests(&buffer.read(cx).snapshot()));
+            }
+        }
+
+        let mut changed = false;
+        for runnable in discovered {
+            if let Some(test) = runnable.test {
+                let suite = &mut self.suites[runnable.suite_ix];
+                changed |= !suite.tree.contains(&test);
+                suite.insert(test);
+            }
+        }
+        if changed {
+            self.update_entries(cx);
+        }
+    }
+
+    fn runnable_tests(&self, buffer: &BufferSnapshot) -> Vec<RunnableTest> {
+        let Some(file) = buffer.file() else {
+            return Vec::new();
+        };
+        let worktree_id = WorktreeId::from_usize(file.worktree_id());
+        if !self
+            .suites
+            .iter()
+            .any(|suite| suite.worktree_id == worktree_id)
+        {
+            return Vec::new();
+        }
+
+        let mut tests = Vec::new();
+        for runnable in buffer.runnable_ranges(Anchor::MIN..Anchor::MAX) {
+            for (suite_ix, suite) in self.suites.iter().enumerate() {
+                let is_test = suite.worktree_id == worktree_id
+                    && runnable
+                        .runnable
+                        .tags
+                        .iter()
+                        .any(|tag| suite.adapter.runnable_tags().contains(&tag.0.as_ref()));
+                if !is_test {
+                    continue;
+                }
+                let name = buffer
+                    .text_for_range(runnable.run_range.clone())
+                    .collect::<String>();
+                let test = suite.adapter.test_id_for_runnable(
+                    file.path(),
+                    &name,
+                    &runnable.extra_captures,
+                );
+                tests.push(RunnableTest {
+                    suite_ix,
+                    range: runnable.run_range.clone(),
+                    name,
+                    test,
+                });
+            }
+        }
+        tests
+    }
+
+    /// The status of a test defined in a buffer. When the id of the test can't
+    /// be derived from the source, the status of the tests with the same name
+    /// is used.
+    fn runnable_status(&self, runnable: &RunnableTest) -> TestStatus {
+        let suite = &self.suites[runnable.suite_ix];
+        if let Some(test) = &runnable.test {
+            return suite.tree.status(test);
+        }
+        let mut status = TestStatus::NotRun;
+        for test in suite.tree.tests(&[]) {
+            let is_match = suite
+                .adapter
+                .test_path(test)
+                .last()
+                .map_or(false, |name| *name == runnable.name);
+            if is_match {
+                match suite.tree.status(test) {
+                    TestStatus::Failed => return TestStatus::Failed,
+                    TestStatus::Passed => status = TestStatus::Passed,
+                    _ => {}
+                }
+            }
+        }
+        status
+    }
+
+    fn update_editor_subscriptions(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let editors = workspace
+            .read(cx)
+            .items_of_type::<Editor>(cx)
+            .collect::<Vec<_>>();
+        self.editor_subscriptions
+            .retain(|id, _| editors.iter().any(|editor| editor.entity_id() == *id));
+        for editor in editors {
+            if self.editor_subscriptions.contains_key(&editor.entity_id()) {
+                continue;
+            }
+            let subscription = cx.subscribe(&editor, |this, _, event, cx| {
+                if let EditorEvent::Reparsed(_) = event {
+                    this.discover_runnable_tests(cx);
+                    this.update_gutters(cx);
+                }
+            });
+            self.editor_subscriptions
+                .insert(editor.entity_id(), subscription);
+        }
+    }
+
+    /// Marks the tests in the gutter of the open editors with the status of
+    /// their last run.
+    fn update_gutters(&self, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let show_status = TestExplorerPanelSettings::get_global(cx).gutter_status;
+        let editors = workspace
+            .read(cx)
+            .items_of_type::<Editor>(cx)
+            .collect::<Vec<_>>();
+        for editor in editors {
+            let mut passed = Vec::new();
+            let mut failed = Vec::new();
+            let multi_buffer = editor.read(cx).buffer().clone();
+            if let Some(buffer) = multi_buffer.read(cx).as_singleton().filter(|_| show_status) {
+                let snapshot = multi_buffer.read(cx).snapshot(cx);
+                for runnable in self.runnable_tests(&buffer.read(cx).snapshot()) {
+                    let range = snapshot.anchor_before(runnable.range.start)
+                        ..snapshot.anchor_after(runnable.range.end);
+                    match self.runnable_status(&runnable) {
+                        TestStatus::Passed => passed.push(range),
+                        TestStatus::Failed => failed.push(range),
+                        _ => {}
+                    }
+                }
+            }
+            editor.update(cx, |editor, cx| {
+                editor.highlight_gutter::<PassedTestGutter>(
+                    &passed,
+                    |cx| cx.theme().status().success,
+                    cx,
+                );
+                editor.highlight_gutter::<FailedTestGutter>(
+                    &failed,
+                    |cx| cx.theme().status().error,
+                    cx,
+                );
+            });
+        }
+    }
+
+    fn run_all_tests(&mut self, cx: &mut ViewContext<Self>) {
+        for suite_ix in 0..self.suites.len() {
+            self.run_tests(suite_ix, Vec::new(), cx);
+        }
+    }
+
+    /// Runs the tests at or below `path` in the test tree of a suite,
+    /// streaming their results into the tree as the test runner reports them.
+    fn run_tests(&mut self, suite_ix: usize, path: Vec<String>, cx: &mut ViewContext<Self>) {
+        let Some(suite) = self.suites.get_mut(suite_ix) else {
+            return;
+        };
+
+        // Starting a new run cancels the previous one.
+        suite.run_task.take();
+        suite.tree.reset_running();
+        let tests = suite
+            .tree
+            .tests(&path)
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        for test in &tests {
+            suite.tree.set_status(test, TestStatus::Running);
+            suite.failures.remove(test);
+        }
+
+        let adapter = suite.adapter.clone();
+        let command = if path.is_empty() {
+            adapter.run_command(None, false)
+        } else {
+            let filter = adapter.test_filter(&path);
+            let exact = tests.len() == 1 && tests[0] == filter;
+            adapter.run_command(Some(&filter), exact)
+        };
+        let mut parser = adapter.output_parser();
+        let worktree_id = suite.worktree_id;
+        let root = suite.root.clone();
+        let environment = self.project.update(cx, |project, cx| {
+            project.worktree_environment(worktree_id, cx)
+        });
+        let suite = &mut self.suites[suite_ix];
+        suite.run_task = Some(cx.spawn(|this, mut cx| async move {
+            let run = async {
+                let environment = environment.await.unwrap_or_default();
+                let mut child = smol::process::Command::new(&command.program)
+                    .args(&command.args)
+                    .envs(environment)
+                    .current_dir(&root)
+                    .stdout(smol::process::Stdio::piped())
+                    .stderr(smol::process::Stdio::piped())
+                    .kill_on_drop(true)
+                    .spawn()
+                    .with_context(|| format!("running tests with {}", command.program))?;
+                let stdout = BufReader::new(child.stdout.take().context("no test stdout")?);
+                let stderr = BufReader::new(child.stderr.take().context("no test stderr")?);
+                let mut lines = futures::stream::select(stdout.lines(), stderr.lines());
+                while let Some(line) = lines.next().await {
+                    let events = parser.parse_line(&line?);
+                    if !events.is_empty() {
+                        this.update(&mut cx, |this, cx| {
+                            this.apply_test_events(worktree_id, adapter.name(), events, cx)
+                        })?;
+                    }
+                }
+                child.status().await?;
+                anyhow::Ok(())
+            };
+            run.await.log_err();
+
+            this.update(&mut cx, |this, cx| {
+                if let Some(suite_ix) = this.suite_ix(worktree_id, adapter.name()) {
+                    this.suites[suite_ix].tree.reset_running();
+                    this.update_entries(cx);
+                    this.update_gutters(cx);
+                }
+            })
+            .ok();
+        }));
+        self.update_entries(cx);
+    }
+
+    fn apply_test_events(
+        &mut self,
+        worktree_id: WorktreeId,
+        adapter_name: &str,
+        events: Vec<TestEvent>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(suite_ix) = self.suite_ix(worktree_id, adapter_name) else {
+            return;
+        };
+        let suite = &mut self.suites[suite_ix];
+        let mut status_changed = false;
+        for event in events {
+            match event {
+                TestEvent::Passed(test) => {
+                    suite.set_status(test, TestStatus::Passed);
+                    status_changed = true;
+                }
+                TestEvent::Failed(test) => {
+                    suite.set_status(test, TestStatus::Failed);
+                    status_changed = true;
+                }
+                TestEvent::Ignored(test) => {
+                    suite.set_status(test, TestStatus::Ignored);
+                    status_changed = true;
+                }
+                TestEvent::FailureOutput { test, line } => {
+                    suite.failures.entry(test).or_default().push(line);
+                }
+            }
+        }
+        if status_changed {
+            self.update_entries(cx);
+            self.update_gutters(cx);
+        }
+    }
+
+    fn show_failures(&mut self, suite_ix: usize, path: &[String], cx: &mut ViewContext<Self>) {
+        let (Some(workspace), Some(suite)) = (self.workspace.upgrade(), self.suites.get(suite_ix))
+        else {
+            return;
+        };
+        let failures = suite.failures(&suite.tree.tests(path));
+        if !failures.is_empty() {
+            open_test_failures(workspace, suite.worktree_id, failures, cx).detach_and_log_err(cx);
+        }
+    }
+
+    fn show_all_failures(&mut self, cx: &mut ViewContext<Self>) {
+        for suite_ix in 0..self.suites.len() {
+            self.show_failures(suite_ix, &[], cx);
+        }
+    }
+
+    fn toggle_expanded(&mut self, entry: &PanelEntry, cx: &mut ViewContext<Self>) {
+        match entry {
+            PanelEntry::Suite(suite_ix) => {
+                if let Some(suite) = self.suites.get_mut(*suite_ix) {
+                    suite.is_expanded = !suite.is_expanded;
+                }
+            }
+            PanelEntry::Test(suite_ix, entry) => {
+                if let Some(suite) = self.suites.get_mut(*suite_ix) {
+                    suite.tree.toggle_expanded(&entry.path);
+                }
+            }
+        }
+        self.update_entries(cx);
+    }
+
+    fn update_entries(&mut self, cx: &mut ViewContext<Self>) {
+        self.entries.clear();
+        for (suite_ix, suite) in self.suites.iter().enumerate() {
+            self.entries.push(PanelEntry::Suite(suite_ix));
+            if suite.is_expanded {
+                self.entries.extend(
+                    suite
+                        .tree
+                        .entries()
+                        .into_iter()
+                        .map(|entry| PanelEntry::Test(suite_ix, entry)),
+                );
+            }
+        }
+        cx.notify();
+    }
+
+    fn render_entry(&self, ix: usize, entry: &PanelEntry, cx: &ViewContext<Self>) -> AnyElement {
+        let settings = TestExplorerPanelSettings::get_global(cx);
+        let (suite_ix, depth, label, status, is_expanded, has_children, path) = match entry {
+            PanelEntry::Suite(suite_ix) => {
+                let suite = &self.suites[*suite_ix];
+                (
+                    *suite_ix,
+                    0,
+                    suite.name.clone(),
+                    suite.tree.status_of_all(),
+                    suite.is_expanded,
+                    !suite.tree.is_empty(),
+                    Vec::new(),
+                )
+            }
+            PanelEntry::Test(suite_ix, entry) => (
+                *suite_ix,
+                entry.depth() + 1,
+                SharedString::from(entry.name().to_string()),
+                entry.status,
+                entry.is_expanded,
+                entry.has_children,
+                entry.path.clone(),
+            ),
+        };
+        let is_running = self.suites[suite_ix].is_running();
+
+        let toggle_entry = entry.clone();
+        let click_entry = entry.clone();
+        let run_path = path.clone();
+        ListItem::new(ix)
+            .indent_level(depth)
+            .indent_step_size(px(settings.indent_size))
+            .toggle(has_children.then_some(is_expanded))
+            .on_toggle(cx.listener(move |this, _, cx| this.toggle_expanded(&toggle_entry, cx)))
+            .start_slot(status_icon(status))
+            .child(Label::new(label).single_line())
+            .end_slot(
+                IconButton::new(("run-tests", ix), IconName::Play)
+                    .shape(IconButtonShape::Square)
+                    .icon_size(IconSize::Small)
+                    .icon_color(Color::Success)
+                    .disabled(is_running)
+                    .tooltip(|cx| Tooltip::text("Run Tests", cx))
+                    .on_click(cx.listener(move |this, _, cx| {
+                        this.run_tests(suite_ix, run_path.clone(), cx)
+                    })),
+            )
+            .on_click(cx.listener(move |this, _, cx| {
+                if status == TestStatus::Failed {
+                    this.show_failures(suite_ix, &path, cx);
+                } else if has_children {
+                    this.toggle_expanded(&click_entry, cx);
+                }
+            }))
+            .into_any_element()
+    }
+}
+
+fn status_icon(status: TestStatus) -> Icon {
+    let (icon, color) = match status {
+        TestStatus::NotRun => (IconName::Dash, Color::Muted),
+        TestStatus::Running => (IconName::ArrowCircle, Color::Accent),
+        TestStatus::Passed => (IconName::Check, Color::Success),
+        TestStatus::Failed => (IconName::XCircle, Color::Error),
+        TestStatus::Ignored => (IconName::Slash, Color::Muted),
+    };
+    Icon::new(icon).size(IconSize::Small).color(color)
+}
+
+impl Panel for TestExplorerPanel {
+    fn persistent_name() -> &'static str {
+        "Test Explorer Panel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        match TestExplorerPanelSettings::get_global(cx).dock {
+            TestExplorerPanelDockPosition::Left => DockPosition::Left,
+            TestExplorerPanelDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<TestExplorerPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| {
+                let dock = match position {
+                    DockPosition::Left | DockPosition::Bottom => {
+                        TestExplorerPanelDockPosition::Left
+                    }
+                    DockPosition::Right => TestExplorerPanelDockPosition::Right,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.width
+            .unwrap_or_else(|| TestExplorerPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        TestExplorerPanelSettings::get_global(cx)
+            .button
+            .then(|| IconName::Check)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Test Explorer")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn set_active(&mut self, active: bool, cx: &mut ViewContext<Self>) {
+        self.active = active;
+        if active {
+            self.discover_undiscovered_tests(cx);
+        }
+    }
+}
+
+impl FocusableView for TestExplorerPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for TestExplorerPanel {}
+
+impl Render for TestExplorerPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let panel = v_flex()
+            .id("test-explorer-panel")
+            .size_full()
+            .key_context("TestExplorerPanel")
+            .on_action(cx.listener(|this, _: &RunAllTests, cx| this.run_all_tests(cx)))
+            .on_action(cx.listener(|this, _: &DiscoverTests, cx| this.discover_all_tests(cx)))
+            .on_action(cx.listener(|this, _: &ShowTestFailures, cx| this.show_all_failures(cx)))
+            .track_focus(&self.focus_handle)
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_1()
+                    .justify_end()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(
+                        IconButton::new("discover-tests", IconName::Update)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::for_action("Discover Tests", &DiscoverTests, cx))
+                            .on_click(cx.listener(|this, _, cx| this.discover_all_tests(cx))),
+                    )
+                    .child(
+                        IconButton::new("show-test-failures", IconName::XCircle)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| {
+                                Tooltip::for_action("Show Test Failures", &ShowTestFailures, cx)
+                            })
+                            .on_click(cx.listener(|this, _, cx| this.show_all_failures(cx))),
+                    )
+                    .child(
+                        IconButton::new("run-all-tests", IconName::Play)
+                            .icon_size(IconSize::Small)
+                            .icon_color(Color::Success)
+                            .tooltip(|cx| Tooltip::for_action("Run All Tests", &RunAllTests, cx))
+                            .on_click(cx.listener(|this, _, cx| this.run_all_tests(cx))),
+                    ),
+            );
+
+        if self.entries.is_empty() {
+            panel.child(
+                v_flex().size_full().justify_center().child(
+                    h_flex()
+                        .justify_center()
+                        .child(Label::new("No tests found").color(Color::Muted)),
+                ),
+            )
+        } else {
+            panel.child(
+                uniform_list(
+                    cx.view().clone(),
+                    "test-explorer-entries",
+                    self.entries.len(),
+                    |this, range, cx| {
+                        range
+                            .map(|ix| this.render_entry(ix, &this.entries[ix], cx))
+                            .collect()
+                    },
+                )
+                .size_full()
+                .track_scroll(self.scroll_handle.clone()),
+            )
+        }
+    }
+}
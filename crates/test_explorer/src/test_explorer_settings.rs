@@ -0,0 +1,64 @@
+use gpui::Pixels;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TestExplorerPanelDockPosition {
+    Left,
+    Right,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct TestExplorerPanelSettings {
+    pub button: bool,
+    pub default_width: Pixels,
+    pub dock: TestExplorerPanelDockPosition,
+    pub indent_size: f32,
+    pub gutter_status: bool,
+    pub auto_discover: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct TestExplorerPanelSettingsContent {
+    /// Whether to show the test explorer panel button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Customize default width (in pixels) taken by the test explorer panel
+    ///
+    /// Default: 240
+    pub default_width: Option<f32>,
+    /// The position of the test explorer panel
+    ///
+    /// Default: left
+    pub dock: Option<TestExplorerPanelDockPosition>,
+    /// Amount of indentation (in pixels) for nested tests.
+    ///
+    /// Default: 20
+    pub indent_size: Option<f32>,
+    /// Whether to mark the tests in the editor gutter with the status of their last run.
+    ///
+    /// Default: true
+    pub gutter_status: Option<bool>,
+    /// Whether to list the tests of the project when the panel is opened, which
+    /// builds and runs code of the project. When disabled, tests are only listed
+    /// with `test_explorer: discover tests`.
+    ///
+    /// Default: false
+    pub auto_discover: Option<bool>,
+}
+
+impl Settings for TestExplorerPanelSettings {
+    const KEY: Option<&'static str> = Some("test_explorer_panel");
+
+    type FileContent = TestExplorerPanelSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
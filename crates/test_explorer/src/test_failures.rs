@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use editor::{
+    display_map::{BlockContext, BlockDisposition, BlockProperties, BlockStyle, RenderBlock},
+    Anchor, Editor, MultiBuffer,
+};
+use futures::future::join_all;
+use gpui::{Task, View, WindowContext};
+use language::{Capability, Point};
+use project::{ProjectPath, WorktreeId};
+use ui::prelude::*;
+use util::ResultExt;
+use workspace::Workspace;
+
+use crate::test_adapter::failure_location;
+
+/// The number of output lines shown below each failure, to keep a single
+/// failure from filling the editor.
+const MAX_OUTPUT_LINES: usize = 20;
+
+/// A failed test and the lines it printed.
+pub(crate) struct TestFailure {
+    pub test: String,
+    pub output: Vec<String>,
+}
+
+/// Opens a multibuffer with an excerpt for each failure whose output
+/// references a source location in the worktree, with the failure output
+/// shown below the failing line.
+pub(crate) fn open_test_failures(
+    workspace: View<Workspace>,
+    worktree_id: WorktreeId,
+    failures: Vec<TestFailure>,
+    cx: &mut WindowContext,
+) -> Task<Result<()>> {
+    let project = workspace.read(cx).project().clone();
+    let failures = failures
+        .into_iter()
+        .filter_map(|failure| {
+            let (path, row) = failure
+                .output
+                .iter()
+                .find_map(|line| failure_location(line))?;
+            let buffer = project.update(cx, |project, cx| {
+                project.open_buffer(
+                    ProjectPath {
+                        worktree_id,
+                        path: Arc::from(path.as_path()),
+                    },
+                    cx,
+                )
+            });
+            Some(async move { (buffer.await, row, failure) })
+        })
+        .collect::<Vec<_>>();
+
+    cx.spawn(|mut cx| async move {
+        let failures = join_all(failures)
+            .await
+            .into_iter()
+            .filter_map(|(buffer, row, failure)| Some((buffer.log_err()?, row, failure)))
+            .collect::<Vec<_>>();
+        if failures.is_empty() {
+            return Ok(());
+        }
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let project = workspace.project().clone();
+            let replica_id = project.read(cx).replica_id();
+            let mut blocks = Vec::new();
+            let multibuffer = cx.new_model(|cx| {
+                let mut multibuffer = MultiBuffer::new(replica_id, Capability::ReadOnly)
+                    .with_title("Test Failures".into());
+                for (ix, (buffer, row, failure)) in failures.into_iter().enumerate() {
+                    let row = row.min(buffer.read(cx).max_point().row);
+                    let point = Point::new(row, 0);
+                    let ranges = multibuffer.push_excerpts_with_context_lines(
+                        buffer,
+                        vec![point..point],
+                        2,
+                        cx,
+                    );
+                    if let Some(range) = ranges.into_iter().next() {
+                        blocks.push(failure_block(ix, range.start, failure));
+                    }
+                }
+                multibuffer
+            });
+            let editor = cx.new_view(|cx| {
+                let mut editor = Editor::for_multibuffer(multibuffer, Some(project), true, cx);
+                editor.insert_blocks(blocks, None, cx);
+                editor
+            });
+            workspace.add_item_to_active_pane(Box::new(editor), None, true, cx);
+        })
+    })
+}
+
+fn failure_block(ix: usize, position: Anchor, failure: TestFailure) -> BlockProperties<Anchor> {
+    let mut output = failure.output;
+    while output.last().map_or(false, |line| line.trim().is_empty()) {
+        output.pop();
+    }
+    output.truncate(MAX_OUTPUT_LINES);
+    let test = failure.test;
+
+    BlockProperties {
+        position,
+        height: output.len() as u32 + 1,
+        style: BlockStyle::Flex,
+        render: render_failure(ix, test, output),
+        disposition: BlockDisposition::Below,
+        priority: 0,
+    }
+}
+
+fn render_failure(ix: usize, test: String, output: Vec<String>) -> RenderBlock {
+    Box::new(move |cx: &mut BlockContext| {
+        let line_height = cx.line_height;
+        v_flex()
+            .id(("test-failure", ix))
+            .w_full()
+            .pl(cx.gutter_dimensions.full_width())
+            .border_l_2()
+            .border_color(cx.theme().status().error)
+            .child(
+                h_flex()
+                    .h(line_height)
+                    .gap_1()
+                    .child(Icon::new(IconName::XCircle).color(Color::Error))
+                    .child(Label::new(test.clone()).color(Color::Error)),
+            )
+            .children(output.iter().map(|line| {
+                div()
+                    .h(line_height)
+                    .child(Label::new(line.clone()).color(Color::Muted))
+            }))
+            .into_any_element()
+    })
+}
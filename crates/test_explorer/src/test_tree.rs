@@ -0,0 +1,239 @@
+use collections::{HashMap, HashSet};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TestStatus {
+    #[default]
+    NotRun,
+    Running,
+    Passed,
+    Failed,
+    Ignored,
+}
+
+impl TestStatus {
+    /// Combines the statuses of the tests of a group into the status of the group.
+    fn combine(self, other: TestStatus) -> TestStatus {
+        use TestStatus::*;
+        match (self, other) {
+            (Running, _) | (_, Running) => Running,
+            (Failed, _) | (_, Failed) => Failed,
+            (NotRun, _) | (_, NotRun) => NotRun,
+            (Passed, _) | (_, Passed) => Passed,
+            (Ignored, Ignored) => Ignored,
+        }
+    }
+}
+
+#[derive(Default)]
+struct TestNode {
+    name: String,
+    test: Option<String>,
+    children: Vec<TestNode>,
+}
+
+impl TestNode {
+    fn child_mut(&mut self, name: &str) -> &mut TestNode {
+        let ix = match self.children.iter().position(|child| child.name == name) {
+            Some(ix) => ix,
+            None => {
+                let ix = self
+                    .children
+                    .partition_point(|child| child.name.as_str() < name);
+                self.children.insert(
+                    ix,
+                    TestNode {
+                        name: name.to_string(),
+                        ..Default::default()
+                    },
+                );
+                ix
+            }
+        };
+        &mut self.children[ix]
+    }
+
+    fn status(&self, statuses: &HashMap<String, TestStatus>) -> TestStatus {
+        let own_status = self
+            .test
+            .as_ref()
+            .map(|test| statuses.get(test).copied().unwrap_or_default());
+        self.children
+            .iter()
+            .map(|child| child.status(statuses))
+            .chain(own_status)
+            .reduce(TestStatus::combine)
+            .unwrap_or_default()
+    }
+
+    fn tests<'a>(&'a self, tests: &mut Vec<&'a str>) {
+        tests.extend(self.test.as_deref());
+        for child in &self.children {
+            child.tests(tests);
+        }
+    }
+}
+
+/// A visible row of the test tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestEntry {
+    pub path: Vec<String>,
+    pub test: Option<String>,
+    pub status: TestStatus,
+    pub has_children: bool,
+    pub is_expanded: bool,
+}
+
+impl TestEntry {
+    pub fn depth(&self) -> usize {
+        self.path.len() - 1
+    }
+
+    pub fn name(&self) -> &str {
+        self.path.last().map_or("", String::as_str)
+    }
+}
+
+/// The tests of a worktree, organized by their path, such as the modules of
+/// a Rust test or the file and class of a Python test.
+#[derive(Default)]
+pub struct TestTree {
+    root: TestNode,
+    tests: HashSet<String>,
+    statuses: HashMap<String, TestStatus>,
+    expanded: HashSet<Vec<String>>,
+}
+
+impl TestTree {
+    pub fn insert(&mut self, path: Vec<String>, test: String) {
+        if !self.tests.insert(test.clone()) {
+            return;
+        }
+        let mut node = &mut self.root;
+        for segment in &path {
+            node = node.child_mut(segment);
+        }
+        node.test = Some(test);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.children.is_empty()
+    }
+
+    pub fn contains(&self, test: &str) -> bool {
+        self.tests.contains(test)
+    }
+
+    pub fn status(&self, test: &str) -> TestStatus {
+        self.statuses.get(test).copied().unwrap_or_default()
+    }
+
+    pub fn set_status(&mut self, test: &str, status: TestStatus) {
+        self.statuses.insert(test.to_string(), status);
+    }
+
+    /// The combined status of all tests.
+    pub fn status_of_all(&self) -> TestStatus {
+        self.root.status(&self.statuses)
+    }
+
+    /// Marks the tests of an interrupted run as not run.
+    pub fn reset_running(&mut self) {
+        for status in self.statuses.values_mut() {
+            if *status == TestStatus::Running {
+                *status = TestStatus::NotRun;
+            }
+        }
+    }
+
+    /// Returns the tests at or below the given path.
+    pub fn tests(&self, path: &[String]) -> Vec<&str> {
+        let mut tests = Vec::new();
+        if let Some(node) = self.node(path) {
+            node.tests(&mut tests);
+        }
+        tests
+    }
+
+    pub fn toggle_expanded(&mut self, path: &[String]) {
+        if !self.expanded.remove(path) {
+            self.expanded.insert(path.to_vec());
+        }
+    }
+
+    pub fn entries(&self) -> Vec<TestEntry> {
+        let mut entries = Vec::new();
+        self.push_entries(&self.root, &mut Vec::new(), &mut entries);
+        entries
+    }
+
+    fn push_entries(&self, node: &TestNode, path: &mut Vec<String>, entries: &mut Vec<TestEntry>) {
+        for child in &node.children {
+            path.push(child.name.clone());
+            let is_expanded = self.expanded.contains(path);
+            entries.push(TestEntry {
+                path: path.clone(),
+                test: child.test.clone(),
+                status: child.status(&self.statuses),
+                has_children: !child.children.is_empty(),
+                is_expanded,
+            });
+            if is_expanded {
+                self.push_entries(child, path, entries);
+            }
+            path.pop();
+        }
+    }
+
+    fn node(&self, path: &[String]) -> Option<&TestNode> {
+        let mut node = &self.root;
+        for segment in path {
+            node = node.children.iter().find(|child| &child.name == segment)?;
+        }
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(test: &str) -> Vec<String> {
+        test.split("::").map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_test_tree() {
+        let mut tree = TestTree::default();
+        for test in ["b::tests::two", "a::one", "b::tests::one"] {
+            tree.insert(path(test), test.to_string());
+        }
+
+        let names = |tree: &TestTree| {
+            tree.entries()
+                .iter()
+                .map(|entry| format!("{}{}", "  ".repeat(entry.depth()), entry.name()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&tree), vec!["a", "b"]);
+
+        tree.toggle_expanded(&path("b"));
+        tree.toggle_expanded(&path("b::tests"));
+        assert_eq!(
+            names(&tree),
+            vec!["a", "b", "  tests", "    one", "    two"]
+        );
+        assert_eq!(
+            tree.tests(&path("b")),
+            vec!["b::tests::one", "b::tests::two"]
+        );
+
+        tree.set_status("b::tests::one", TestStatus::Passed);
+        tree.set_status("b::tests::two", TestStatus::Ignored);
+        assert_eq!(tree.entries()[1].status, TestStatus::Passed);
+        tree.set_status("b::tests::two", TestStatus::Failed);
+        assert_eq!(tree.entries()[1].status, TestStatus::Failed);
+        tree.set_status("b::tests::one", TestStatus::Running);
+        assert_eq!(tree.entries()[1].status, TestStatus::Running);
+        assert_eq!(tree.entries()[0].status, TestStatus::NotRun);
+    }
+}
@@ -1837,6 +1837,12 @@ impl BufferSnapshot {
         self.visible_text.len()
     }
 
+    /// An estimate of the bytes used by the buffer's text, including the text that was
+    /// deleted and is kept for undo and collaboration.
+    pub fn estimated_memory_usage(&self) -> usize {
+        self.visible_text.estimated_memory_usage() + self.deleted_text.estimated_memory_usage()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
@@ -0,0 +1,111 @@
+use gpui::Hsla;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::Theme;
+
+/// How far the lightness of cursors and diagnostics is kept from the lightness of the
+/// editor background in high-contrast mode.
+const MIN_LIGHTNESS_DIFFERENCE: f32 = 0.45;
+/// The minimum opacity of selections in high-contrast mode.
+const MIN_SELECTION_ALPHA: f32 = 0.5;
+
+/// Settings for people who use Zed with visual or vestibular impairments.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AccessibilitySettings {
+    /// Whether cursors, selections, and diagnostics are drawn with more contrast than
+    /// the theme gives them.
+    pub high_contrast: bool,
+    /// Whether animations, such as cursor blinking and spinners, are turned off.
+    pub reduce_motion: bool,
+}
+
+/// The `accessibility` settings.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct AccessibilitySettingsContent {
+    /// Whether to draw cursors, selections, and diagnostics with more contrast than
+    /// the theme gives them.
+    ///
+    /// Default: false
+    pub high_contrast: Option<bool>,
+    /// Whether to turn off animations, such as cursor blinking and spinners.
+    ///
+    /// Default: false
+    pub reduce_motion: Option<bool>,
+}
+
+impl AccessibilitySettings {
+    pub(crate) fn merge(&mut self, content: &AccessibilitySettingsContent) {
+        if let Some(high_contrast) = content.high_contrast {
+            self.high_contrast = high_contrast;
+        }
+        if let Some(reduce_motion) = content.reduce_motion {
+            self.reduce_motion = reduce_motion;
+        }
+    }
+}
+
+/// Boosts the contrast of the cursors, selections, and diagnostics of a theme against
+/// its editor background. Applying it more than once has no further effect.
+pub(crate) fn apply_high_contrast(theme: &mut Theme) {
+    let background = theme.styles.colors.editor_background;
+
+    for player in &mut theme.styles.player.0 {
+        player.cursor = with_contrast(player.cursor, background);
+        player.selection.a = player.selection.a.max(MIN_SELECTION_ALPHA);
+    }
+
+    let status = &mut theme.styles.status;
+    for color in [
+        &mut status.error,
+        &mut status.error_border,
+        &mut status.warning,
+        &mut status.warning_border,
+        &mut status.info,
+        &mut status.info_border,
+        &mut status.hint,
+        &mut status.hint_border,
+    ] {
+        *color = with_contrast(*color, background);
+    }
+}
+
+/// Makes a color opaque, and moves its lightness away from the background's.
+fn with_contrast(color: Hsla, background: Hsla) -> Hsla {
+    let l = if background.l < 0.5 {
+        color
+            .l
+            .max((background.l + MIN_LIGHTNESS_DIFFERENCE).min(1.))
+    } else {
+        color
+            .l
+            .min((background.l - MIN_LIGHTNESS_DIFFERENCE).max(0.))
+    };
+    Hsla { l, a: 1., ..color }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::one_themes::one_dark;
+
+    #[test]
+    fn test_apply_high_contrast() {
+        let mut theme = one_dark();
+        let background = theme.styles.colors.editor_background;
+        apply_high_contrast(&mut theme);
+
+        let local = theme.styles.player.local();
+        assert_eq!(local.cursor.a, 1.);
+        assert!(local.cursor.l >= background.l + MIN_LIGHTNESS_DIFFERENCE);
+        assert!(local.selection.a >= MIN_SELECTION_ALPHA);
+        assert!(theme.styles.status.error.l >= background.l + MIN_LIGHTNESS_DIFFERENCE);
+
+        let once = theme.clone();
+        apply_high_contrast(&mut theme);
+        let local_once = once.styles.player.local();
+        assert_eq!(theme.styles.player.local().cursor, local_once.cursor);
+        assert_eq!(theme.styles.player.local().selection, local_once.selection);
+        assert_eq!(theme.styles.status.error, once.styles.status.error);
+    }
+}
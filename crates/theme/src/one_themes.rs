@@ -252,6 +252,7 @@ pub(crate) fn one_dark() -> Theme {
                     ("variable.special".into(), red.into()),
                     ("variant".into(), HighlightStyle::default()),
                 ],
+                language_highlights: Default::default(),
             }),
         },
     }
@@ -0,0 +1,329 @@
+use std::f64::consts::PI;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveTime, Timelike};
+use gpui::{AppContext, Global, Task};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+
+use crate::{Appearance, SystemAppearance, ThemeMode, ThemeSettings};
+
+/// How often to check whether the scheduled appearance has changed.
+const SCHEDULE_INTERVAL: Duration = Duration::from_secs(60);
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+
+/// When to use the light and dark themes, in the `schedule` theme mode.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ThemeSchedule {
+    /// Switch between the themes at fixed times of day.
+    Fixed {
+        /// The local time at which to switch to the light theme, as `HH:MM`.
+        light_at: String,
+        /// The local time at which to switch to the dark theme, as `HH:MM`.
+        dark_at: String,
+    },
+    /// Use the light theme from sunrise until sunset at the given location.
+    Sun {
+        /// The latitude of the location, in degrees north.
+        latitude: f64,
+        /// The longitude of the location, in degrees east.
+        longitude: f64,
+    },
+}
+
+impl ThemeSchedule {
+    /// Returns the appearance scheduled for the given time, or `None` if the
+    /// schedule is invalid.
+    pub fn appearance_at(&self, now: DateTime<FixedOffset>) -> Option<Appearance> {
+        match self {
+            Self::Fixed { light_at, dark_at } => {
+                let light_at = parse_time_of_day(light_at)?;
+                let dark_at = parse_time_of_day(dark_at)?;
+                let now = minute_of_day(now.time());
+                Some(appearance_between(now, light_at, dark_at))
+            }
+            Self::Sun {
+                latitude,
+                longitude,
+            } => {
+                if !(-90.0..=90.0).contains(latitude) || !(-180.0..=180.0).contains(longitude) {
+                    return None;
+                }
+                let now = now.naive_utc();
+                let day_of_year = now.ordinal() as f64;
+                Some(
+                    match sunrise_and_sunset(day_of_year, *latitude, *longitude) {
+                        Daylight::Always => Appearance::Light,
+                        Daylight::Never => Appearance::Dark,
+                        Daylight::Between { sunrise, sunset } => {
+                            appearance_between(minute_of_day(now.time()), sunrise, sunset)
+                        }
+                    },
+                )
+            }
+        }
+    }
+
+    /// Returns the appearance scheduled for the current time.
+    pub fn current_appearance(&self) -> Option<Appearance> {
+        self.appearance_at(Local::now().fixed_offset())
+    }
+}
+
+fn parse_time_of_day(time: &str) -> Option<i64> {
+    let time = NaiveTime::parse_from_str(time, "%H:%M")
+        .map_err(|_| log::error!("invalid time of day in theme schedule: {time:?}"))
+        .ok()?;
+    Some(minute_of_day(time))
+}
+
+fn minute_of_day(time: NaiveTime) -> i64 {
+    (time.hour() * 60 + time.minute()) as i64
+}
+
+/// Returns the appearance at the given minute of the day, if the light theme is
+/// used from `light_at` until `dark_at`, wrapping around midnight.
+fn appearance_between(now: i64, light_at: i64, dark_at: i64) -> Appearance {
+    let light_at = light_at.rem_euclid(MINUTES_PER_DAY);
+    let dark_at = dark_at.rem_euclid(MINUTES_PER_DAY);
+    let is_light = if light_at <= dark_at {
+        light_at <= now && now < dark_at
+    } else {
+        now >= light_at || now < dark_at
+    };
+    if is_light {
+        Appearance::Light
+    } else {
+        Appearance::Dark
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Daylight {
+    /// The sun doesn't set, as in a polar summer.
+    Always,
+    /// The sun doesn't rise, as in a polar winter.
+    Never,
+    /// The sun rises and sets at the given minutes of the day, in UTC.
+    Between { sunrise: i64, sunset: i64 },
+}
+
+/// Approximates the times of sunrise and sunset on the given day of the year,
+/// using NOAA's general solar position equations.
+fn sunrise_and_sunset(day_of_year: f64, latitude: f64, longitude: f64) -> Daylight {
+    let year_angle = 2. * PI / 365. * (day_of_year - 1.);
+    let equation_of_time = 229.18
+        * (0.000075 + 0.001868 * year_angle.cos()
+            - 0.032077 * year_angle.sin()
+            - 0.014615 * (2. * year_angle).cos()
+            - 0.040849 * (2. * year_angle).sin());
+    let declination = 0.006918 - 0.399912 * year_angle.cos() + 0.070257 * year_angle.sin()
+        - 0.006758 * (2. * year_angle).cos()
+        + 0.000907 * (2. * year_angle).sin()
+        - 0.002697 * (3. * year_angle).cos()
+        + 0.00148 * (3. * year_angle).sin();
+
+    // The zenith of the sun's center at sunrise and sunset, accounting for
+    // atmospheric refraction and the size of the sun's disk.
+    let zenith = 90.833_f64.to_radians();
+    let latitude = latitude.to_radians();
+    let cos_hour_angle =
+        zenith.cos() / (latitude.cos() * declination.cos()) - latitude.tan() * declination.tan();
+    if cos_hour_angle < -1. {
+        return Daylight::Always;
+    }
+    if cos_hour_angle > 1. {
+        return Daylight::Never;
+    }
+
+    let hour_angle = cos_hour_angle.acos().to_degrees();
+    let sunrise = 720. - 4. * (longitude + hour_angle) - equation_of_time;
+    let sunset = 720. - 4. * (longitude - hour_angle) - equation_of_time;
+    Daylight::Between {
+        sunrise: sunrise.round() as i64,
+        sunset: sunset.round() as i64,
+    }
+}
+
+#[derive(Default)]
+struct GlobalThemeSchedule(Option<Task<()>>);
+
+impl Global for GlobalThemeSchedule {}
+
+/// Switches between the light and dark themes over time while the theme mode
+/// is `schedule`, and stops doing so when it's changed.
+pub(crate) fn update_theme_schedule(cx: &mut AppContext) {
+    let scheduled = ThemeSettings::get_global(cx)
+        .theme_selection
+        .as_ref()
+        .and_then(|selection| selection.mode())
+        == Some(ThemeMode::Schedule);
+    let schedule = cx.default_global::<GlobalThemeSchedule>();
+    if !scheduled {
+        schedule.0 = None;
+        return;
+    }
+    if schedule.0.is_some() {
+        return;
+    }
+
+    let task = cx.spawn(|mut cx| async move {
+        loop {
+            cx.background_executor().timer(SCHEDULE_INTERVAL).await;
+            let result = cx.update(|cx| {
+                let settings = ThemeSettings::get_global(cx);
+                let Some(selection) = settings.theme_selection.as_ref() else {
+                    return;
+                };
+                let theme_name = selection.theme(*SystemAppearance::global(cx));
+                if settings.active_theme.name.as_ref() != theme_name {
+                    ThemeSettings::reload_current_theme(cx);
+                }
+            });
+            if result.is_err() {
+                break;
+            }
+        }
+    });
+    cx.global_mut::<GlobalThemeSchedule>().0 = Some(task);
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn time(
+        offset_hours: i32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+    ) -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(offset_hours * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2024, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fixed_schedule() {
+        let schedule = ThemeSchedule::Fixed {
+            light_at: "07:30".into(),
+            dark_at: "19:00".into(),
+        };
+        assert_eq!(
+            schedule.appearance_at(time(0, 6, 1, 7, 29)),
+            Some(Appearance::Dark)
+        );
+        assert_eq!(
+            schedule.appearance_at(time(0, 6, 1, 7, 30)),
+            Some(Appearance::Light)
+        );
+        assert_eq!(
+            schedule.appearance_at(time(2, 6, 1, 18, 59)),
+            Some(Appearance::Light)
+        );
+        assert_eq!(
+            schedule.appearance_at(time(-5, 6, 1, 19, 0)),
+            Some(Appearance::Dark)
+        );
+
+        // Schedules may wrap around midnight.
+        let schedule = ThemeSchedule::Fixed {
+            light_at: "22:00".into(),
+            dark_at: "06:00".into(),
+        };
+        assert_eq!(
+            schedule.appearance_at(time(0, 6, 1, 23, 0)),
+            Some(Appearance::Light)
+        );
+        assert_eq!(
+            schedule.appearance_at(time(0, 6, 1, 12, 0)),
+            Some(Appearance::Dark)
+        );
+
+        let schedule = ThemeSchedule::Fixed {
+            light_at: "7am".into(),
+            dark_at: "19:00".into(),
+        };
+        assert_eq!(schedule.appearance_at(time(0, 6, 1, 12, 0)), None);
+    }
+
+    #[test]
+    fn test_sun_schedule() {
+        // London, where the sun rises around 04:45 and sets around 21:20 BST
+        // at midsummer, and rises around 08:05 and sets around 15:55 GMT at
+        // midwinter.
+        let schedule = ThemeSchedule::Sun {
+            latitude: 51.5,
+            longitude: -0.13,
+        };
+        assert_eq!(
+            schedule.appearance_at(time(1, 6, 21, 4, 30)),
+            Some(Appearance::Dark)
+        );
+        assert_eq!(
+            schedule.appearance_at(time(1, 6, 21, 5, 0)),
+            Some(Appearance::Light)
+        );
+        assert_eq!(
+            schedule.appearance_at(time(1, 6, 21, 21, 0)),
+            Some(Appearance::Light)
+        );
+        assert_eq!(
+            schedule.appearance_at(time(1, 6, 21, 21, 45)),
+            Some(Appearance::Dark)
+        );
+        assert_eq!(
+            schedule.appearance_at(time(0, 12, 21, 7, 45)),
+            Some(Appearance::Dark)
+        );
+        assert_eq!(
+            schedule.appearance_at(time(0, 12, 21, 12, 0)),
+            Some(Appearance::Light)
+        );
+        assert_eq!(
+            schedule.appearance_at(time(0, 12, 21, 16, 15)),
+            Some(Appearance::Dark)
+        );
+
+        // Tokyo, whose daylight spans midnight in UTC.
+        let schedule = ThemeSchedule::Sun {
+            latitude: 35.68,
+            longitude: 139.69,
+        };
+        assert_eq!(
+            schedule.appearance_at(time(9, 3, 20, 12, 0)),
+            Some(Appearance::Light)
+        );
+        assert_eq!(
+            schedule.appearance_at(time(9, 3, 20, 23, 0)),
+            Some(Appearance::Dark)
+        );
+
+        // Tromsø, during the polar night and the midnight sun.
+        let schedule = ThemeSchedule::Sun {
+            latitude: 69.65,
+            longitude: 18.96,
+        };
+        assert_eq!(
+            schedule.appearance_at(time(1, 12, 21, 12, 0)),
+            Some(Appearance::Dark)
+        );
+        assert_eq!(
+            schedule.appearance_at(time(2, 6, 21, 0, 30)),
+            Some(Appearance::Light)
+        );
+
+        let schedule = ThemeSchedule::Sun {
+            latitude: 95.,
+            longitude: 0.,
+        };
+        assert_eq!(schedule.appearance_at(time(0, 6, 1, 12, 0)), None);
+    }
+}
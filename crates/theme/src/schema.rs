@@ -107,34 +107,41 @@ impl ThemeStyleContent {
 
     /// Returns the syntax style overrides in the [`ThemeContent`].
     pub fn syntax_overrides(&self) -> Vec<(String, HighlightStyle)> {
-        self.syntax
-            .iter()
-            .map(|(key, style)| {
-                (
-                    key.clone(),
-                    HighlightStyle {
-                        color: style
-                            .color
-                            .as_ref()
-                            .and_then(|color| try_parse_color(color).ok()),
-                        background_color: style
-                            .background_color
-                            .as_ref()
-                            .and_then(|color| try_parse_color(color).ok()),
-                        font_style: style
-                            .font_style
-                            .map(|font_style| FontStyle::from(font_style)),
-                        font_weight: style
-                            .font_weight
-                            .map(|font_weight| FontWeight::from(font_weight)),
-                        ..Default::default()
-                    },
-                )
-            })
-            .collect()
+        syntax_overrides(&self.syntax)
     }
 }
 
+/// Returns the highlight styles described by the given syntax styles.
+pub(crate) fn syntax_overrides(
+    syntax: &IndexMap<String, HighlightStyleContent>,
+) -> Vec<(String, HighlightStyle)> {
+    syntax
+        .iter()
+        .map(|(key, style)| {
+            (
+                key.clone(),
+                HighlightStyle {
+                    color: style
+                        .color
+                        .as_ref()
+                        .and_then(|color| try_parse_color(color).ok()),
+                    background_color: style
+                        .background_color
+                        .as_ref()
+                        .and_then(|color| try_parse_color(color).ok()),
+                    font_style: style
+                        .font_style
+                        .map(|font_style| FontStyle::from(font_style)),
+                    font_weight: style
+                        .font_weight
+                        .map(|font_weight| FontWeight::from(font_weight)),
+                    ..Default::default()
+                },
+            )
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(default)]
 pub struct ThemeColorsContent {
@@ -1,11 +1,17 @@
+use crate::accessibility::apply_high_contrast;
 use crate::one_themes::one_dark;
-use crate::{Appearance, SyntaxTheme, Theme, ThemeRegistry, ThemeStyleContent};
+use crate::schema::syntax_overrides;
+use crate::{
+    AccessibilitySettings, AccessibilitySettingsContent, Appearance, HighlightStyleContent,
+    SyntaxTheme, Theme, ThemeRegistry, ThemeSchedule, ThemeStyleContent,
+};
 use anyhow::Result;
 use derive_more::{Deref, DerefMut};
 use gpui::{
     px, AppContext, Font, FontFallbacks, FontFeatures, FontStyle, FontWeight, Global, Pixels,
     Subscription, ViewContext, WindowContext,
 };
+use indexmap::IndexMap;
 use refineable::Refineable;
 use schemars::{
     gen::SchemaGenerator,
@@ -89,9 +95,11 @@ pub struct ThemeSettings {
     pub buffer_line_height: BufferLineHeight,
     pub theme_selection: Option<ThemeSelection>,
     pub active_theme: Arc<Theme>,
-    pub theme_overrides: Option<ThemeStyleContent>,
+    pub theme_overrides: Option<ThemeOverridesContent>,
     pub ui_density: UiDensity,
     pub unnecessary_code_fade: f32,
+    pub deprecated_code_strikethrough: bool,
+    pub accessibility: AccessibilitySettings,
 }
 
 impl ThemeSettings {
@@ -192,6 +200,9 @@ pub enum ThemeSelection {
         light: String,
         #[schemars(schema_with = "theme_name_ref")]
         dark: String,
+        /// When to use the light and dark themes, in the `schedule` mode.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        schedule: Option<ThemeSchedule>,
     },
 }
 
@@ -211,19 +222,42 @@ pub enum ThemeMode {
     /// Use the theme based on the system's appearance.
     #[default]
     System,
+
+    /// Use the theme based on the time of day, as given by the `schedule`.
+    Schedule,
+}
+
+impl ThemeMode {
+    /// Returns the appearance of the theme to use in this mode.
+    fn appearance(
+        &self,
+        schedule: Option<&ThemeSchedule>,
+        system_appearance: Appearance,
+    ) -> Appearance {
+        match self {
+            ThemeMode::Light => Appearance::Light,
+            ThemeMode::Dark => Appearance::Dark,
+            ThemeMode::System => system_appearance,
+            // Without a valid schedule, follow the system's appearance.
+            ThemeMode::Schedule => schedule
+                .and_then(|schedule| schedule.current_appearance())
+                .unwrap_or(system_appearance),
+        }
+    }
 }
 
 impl ThemeSelection {
     pub fn theme(&self, system_appearance: Appearance) -> &str {
         match self {
             Self::Static(theme) => theme,
-            Self::Dynamic { mode, light, dark } => match mode {
-                ThemeMode::Light => light,
-                ThemeMode::Dark => dark,
-                ThemeMode::System => match system_appearance {
-                    Appearance::Light => light,
-                    Appearance::Dark => dark,
-                },
+            Self::Dynamic {
+                mode,
+                light,
+                dark,
+                schedule,
+            } => match mode.appearance(schedule.as_ref(), system_appearance) {
+                Appearance::Light => light,
+                Appearance::Dark => dark,
             },
         }
     }
@@ -290,11 +324,59 @@ pub struct ThemeSettingsContent {
     #[serde(default)]
     pub unnecessary_code_fade: Option<f32>,
 
-    /// EXPERIMENTAL: Overrides for the current theme.
+    /// Whether to strike through deprecated code.
+    #[serde(default)]
+    pub deprecated_code_strikethrough: Option<bool>,
+
+    /// Settings for people who use Zed with visual or vestibular impairments.
+    #[serde(default)]
+    pub accessibility: Option<AccessibilitySettingsContent>,
+
+    /// Overrides for the current theme.
     ///
     /// These values will override the ones on the current theme specified in `theme`.
-    #[serde(rename = "experimental.theme_overrides", default)]
-    pub theme_overrides: Option<ThemeStyleContent>,
+    #[serde(
+        rename = "theme_overrides",
+        alias = "experimental.theme_overrides",
+        default
+    )]
+    pub theme_overrides: Option<ThemeOverridesContent>,
+}
+
+/// Overrides for the current theme.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ThemeOverridesContent {
+    /// Overrides for all themes.
+    #[serde(flatten)]
+    pub style: ThemeStyleContent,
+    /// Syntax style overrides for files of the given languages, by language
+    /// name, which apply to all themes.
+    #[serde(default)]
+    pub languages: IndexMap<String, LanguageThemeOverridesContent>,
+    /// Overrides for the given themes, by theme name, which are applied over
+    /// the overrides for all themes.
+    #[serde(default)]
+    pub themes: IndexMap<String, ThemeSpecificOverridesContent>,
+}
+
+/// Overrides for a single theme.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ThemeSpecificOverridesContent {
+    /// Overrides for the theme.
+    #[serde(flatten)]
+    pub style: ThemeStyleContent,
+    /// Syntax style overrides for files of the given languages, by language
+    /// name.
+    #[serde(default)]
+    pub languages: IndexMap<String, LanguageThemeOverridesContent>,
+}
+
+/// Overrides for files of a single language.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct LanguageThemeOverridesContent {
+    /// The styles for syntax nodes.
+    #[serde(default)]
+    pub syntax: IndexMap<String, HighlightStyleContent>,
 }
 
 fn default_font_features() -> Option<FontFeatures> {
@@ -311,13 +393,14 @@ impl ThemeSettingsContent {
         if let Some(selection) = self.theme.as_mut() {
             let theme_to_update = match selection {
                 ThemeSelection::Static(theme) => theme,
-                ThemeSelection::Dynamic { mode, light, dark } => match mode {
-                    ThemeMode::Light => light,
-                    ThemeMode::Dark => dark,
-                    ThemeMode::System => match appearance {
-                        Appearance::Light => light,
-                        Appearance::Dark => dark,
-                    },
+                ThemeSelection::Dynamic {
+                    mode,
+                    light,
+                    dark,
+                    schedule,
+                } => match mode.appearance(schedule.as_ref(), appearance) {
+                    Appearance::Light => light,
+                    Appearance::Dark => dark,
                 },
             };
 
@@ -338,6 +421,7 @@ impl ThemeSettingsContent {
                         mode,
                         light: theme.clone(),
                         dark: theme.clone(),
+                        schedule: None,
                     });
                 }
                 ThemeSelection::Dynamic {
@@ -350,6 +434,7 @@ impl ThemeSettingsContent {
                 mode,
                 light: ThemeSettings::DEFAULT_LIGHT_THEME.into(),
                 dark: ThemeSettings::DEFAULT_DARK_THEME.into(),
+                schedule: None,
             });
         }
     }
@@ -404,32 +489,64 @@ impl ThemeSettings {
         new_theme
     }
 
-    /// Applies the theme overrides, if there are any, to the current theme.
+    /// Applies the theme overrides, if there are any, to the current theme, followed by
+    /// the high-contrast adjustments when they're turned on.
     pub fn apply_theme_overrides(&mut self) {
         if let Some(theme_overrides) = &self.theme_overrides {
             let mut base_theme = (*self.active_theme).clone();
+            let theme_specific_overrides = theme_overrides.themes.get(base_theme.name.as_ref());
 
-            if let Some(window_background_appearance) = theme_overrides.window_background_appearance
-            {
-                base_theme.styles.window_background_appearance =
-                    window_background_appearance.into();
+            // Apply the overrides for all languages before those for specific
+            // languages, which start from the styles for all languages.
+            apply_style_overrides(&mut base_theme, &theme_overrides.style);
+            if let Some(theme_specific_overrides) = theme_specific_overrides {
+                apply_style_overrides(&mut base_theme, &theme_specific_overrides.style);
+            }
+            apply_language_overrides(&mut base_theme, &theme_overrides.languages);
+            if let Some(theme_specific_overrides) = theme_specific_overrides {
+                apply_language_overrides(&mut base_theme, &theme_specific_overrides.languages);
             }
-
-            base_theme
-                .styles
-                .colors
-                .refine(&theme_overrides.theme_colors_refinement());
-            base_theme
-                .styles
-                .status
-                .refine(&theme_overrides.status_colors_refinement());
-            base_theme.styles.player.merge(&theme_overrides.players);
-            base_theme.styles.accents.merge(&theme_overrides.accents);
-            base_theme.styles.syntax =
-                SyntaxTheme::merge(base_theme.styles.syntax, theme_overrides.syntax_overrides());
 
             self.active_theme = Arc::new(base_theme);
         }
+
+        if self.accessibility.high_contrast {
+            let mut theme = (*self.active_theme).clone();
+            apply_high_contrast(&mut theme);
+            self.active_theme = Arc::new(theme);
+        }
+    }
+}
+
+fn apply_style_overrides(theme: &mut Theme, overrides: &ThemeStyleContent) {
+    if let Some(window_background_appearance) = overrides.window_background_appearance {
+        theme.styles.window_background_appearance = window_background_appearance.into();
+    }
+
+    theme
+        .styles
+        .colors
+        .refine(&overrides.theme_colors_refinement());
+    theme
+        .styles
+        .status
+        .refine(&overrides.status_colors_refinement());
+    theme.styles.player.merge(&overrides.players);
+    theme.styles.accents.merge(&overrides.accents);
+    theme.styles.syntax =
+        SyntaxTheme::merge(theme.styles.syntax.clone(), overrides.syntax_overrides());
+}
+
+fn apply_language_overrides(
+    theme: &mut Theme,
+    overrides: &IndexMap<String, LanguageThemeOverridesContent>,
+) {
+    for (language, overrides) in overrides {
+        theme.styles.syntax = SyntaxTheme::merge_language(
+            theme.styles.syntax.clone(),
+            language,
+            syntax_overrides(&overrides.syntax),
+        );
     }
 }
 
@@ -520,6 +637,50 @@ pub fn reset_ui_font_size(cx: &mut WindowContext) {
     }
 }
 
+/// The buffer and UI font size adjustments in effect before the font sizes were scaled.
+pub struct FontSizeAdjustments {
+    buffer_font_size: Option<Pixels>,
+    ui_font_size: Option<Pixels>,
+}
+
+/// Scales the buffer and UI font sizes by a factor, returning the adjustments they had
+/// so that they can be restored.
+pub fn scale_font_sizes(cx: &mut WindowContext, factor: f32) -> FontSizeAdjustments {
+    let adjustments = FontSizeAdjustments {
+        buffer_font_size: cx
+            .try_global::<AdjustedBufferFontSize>()
+            .map(|adjusted_size| adjusted_size.0),
+        ui_font_size: cx
+            .try_global::<AdjustedUiFontSize>()
+            .map(|adjusted_size| adjusted_size.0),
+    };
+    let buffer_font_size = (get_buffer_font_size(cx) * factor).max(MIN_FONT_SIZE);
+    let ui_font_size = (get_ui_font_size(cx) * factor).max(MIN_FONT_SIZE);
+    cx.set_global(AdjustedBufferFontSize(buffer_font_size));
+    cx.set_global(AdjustedUiFontSize(ui_font_size));
+    cx.refresh();
+    adjustments
+}
+
+/// Restores the font size adjustments in effect before the font sizes were scaled.
+pub fn restore_font_sizes(cx: &mut WindowContext, adjustments: FontSizeAdjustments) {
+    match adjustments.buffer_font_size {
+        Some(size) => cx.set_global(AdjustedBufferFontSize(size)),
+        None if cx.has_global::<AdjustedBufferFontSize>() => {
+            cx.remove_global::<AdjustedBufferFontSize>();
+        }
+        None => {}
+    }
+    match adjustments.ui_font_size {
+        Some(size) => cx.set_global(AdjustedUiFontSize(size)),
+        None if cx.has_global::<AdjustedUiFontSize>() => {
+            cx.remove_global::<AdjustedUiFontSize>();
+        }
+        None => {}
+    }
+    cx.refresh();
+}
+
 impl settings::Settings for ThemeSettings {
     const KEY: Option<&'static str> = None;
 
@@ -562,9 +723,17 @@ impl settings::Settings for ThemeSettings {
             theme_overrides: None,
             ui_density: defaults.ui_density.unwrap_or(UiDensity::Default),
             unnecessary_code_fade: defaults.unnecessary_code_fade.unwrap_or(0.0),
+            deprecated_code_strikethrough: defaults.deprecated_code_strikethrough.unwrap_or(false),
+            accessibility: AccessibilitySettings::default(),
         };
+        if let Some(accessibility) = &defaults.accessibility {
+            this.accessibility.merge(accessibility);
+        }
+        if this.accessibility.high_contrast {
+            this.apply_theme_overrides();
+        }
 
-        for value in sources.user.into_iter().chain(sources.release_channel) {
+        for value in sources.user_customizations() {
             if let Some(value) = value.ui_density {
                 this.ui_density = value;
             }
@@ -605,6 +774,9 @@ impl settings::Settings for ThemeSettings {
                 }
             }
 
+            if let Some(accessibility) = &value.accessibility {
+                this.accessibility.merge(accessibility);
+            }
             this.theme_overrides.clone_from(&value.theme_overrides);
             this.apply_theme_overrides();
 
@@ -618,6 +790,10 @@ impl settings::Settings for ThemeSettings {
             // Clamp the `unnecessary_code_fade` to ensure text can't disappear entirely.
             merge(&mut this.unnecessary_code_fade, value.unnecessary_code_fade);
             this.unnecessary_code_fade = this.unnecessary_code_fade.clamp(0.0, 0.9);
+            merge(
+                &mut this.deprecated_code_strikethrough,
+                value.deprecated_code_strikethrough,
+            );
         }
 
         Ok(this)
@@ -1,10 +1,13 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use gpui::{HighlightStyle, Hsla};
 
 #[derive(Debug, PartialEq, Eq, Clone, Default)]
 pub struct SyntaxTheme {
     pub highlights: Vec<(String, HighlightStyle)>,
+    /// The indices of the entries of `highlights` that only apply to files of
+    /// a given language, by language name.
+    pub language_highlights: BTreeMap<String, Vec<usize>>,
 }
 
 impl SyntaxTheme {
@@ -30,13 +33,13 @@ impl SyntaxTheme {
                 .into_iter()
                 .map(|(key, style)| (key.to_owned(), style))
                 .collect(),
+            language_highlights: BTreeMap::default(),
         }
     }
 
     pub fn get(&self, name: &str) -> HighlightStyle {
-        self.highlights
-            .iter()
-            .find_map(|entry| if entry.0 == name { Some(entry.1) } else { None })
+        self.highlight_id(name)
+            .map(|ix| self.highlights[ix as usize].1)
             .unwrap_or_default()
     }
 
@@ -44,9 +47,30 @@ impl SyntaxTheme {
         self.get(name).color.unwrap_or_default()
     }
 
+    /// Returns the index of the entry of `highlights` with the given name that
+    /// applies to files of all languages.
     pub fn highlight_id(&self, name: &str) -> Option<u32> {
-        let ix = self.highlights.iter().position(|entry| entry.0 == name)?;
-        Some(ix as u32)
+        self.highlights.iter().enumerate().find_map(|(ix, entry)| {
+            (entry.0 == name && !self.is_language_specific(ix)).then_some(ix as u32)
+        })
+    }
+
+    /// Returns whether the entry of `highlights` with the given index only
+    /// applies to files of some language.
+    pub fn is_language_specific(&self, ix: usize) -> bool {
+        self.language_highlights
+            .values()
+            .any(|language_highlights| language_highlights.contains(&ix))
+    }
+
+    /// Returns whether the entry of `highlights` with the given index applies
+    /// to files of the given language.
+    pub fn applies_to_language(&self, ix: usize, language: &str) -> bool {
+        self.language_highlights
+            .iter()
+            .all(|(name, language_highlights)| {
+                name == language || !language_highlights.contains(&ix)
+            })
     }
 
     /// Returns a new [`Arc<SyntaxTheme>`] with the given syntax styles merged in.
@@ -55,42 +79,153 @@ impl SyntaxTheme {
             return base;
         }
 
-        let mut merged_highlights = base.highlights.clone();
+        let mut merged = (*base).clone();
 
         for (name, highlight) in user_syntax_styles {
-            if let Some((_, existing_highlight)) = merged_highlights
-                .iter_mut()
-                .find(|(existing_name, _)| existing_name == &name)
-            {
-                existing_highlight.color = highlight.color.or(existing_highlight.color);
-                existing_highlight.font_weight =
-                    highlight.font_weight.or(existing_highlight.font_weight);
-                existing_highlight.font_style =
-                    highlight.font_style.or(existing_highlight.font_style);
-                existing_highlight.background_color = highlight
-                    .background_color
-                    .or(existing_highlight.background_color);
-                existing_highlight.underline = highlight.underline.or(existing_highlight.underline);
-                existing_highlight.strikethrough =
-                    highlight.strikethrough.or(existing_highlight.strikethrough);
-                existing_highlight.fade_out = highlight.fade_out.or(existing_highlight.fade_out);
+            if let Some(ix) = merged.highlight_id(&name) {
+                merge_highlight_style(&mut merged.highlights[ix as usize].1, highlight);
             } else {
-                merged_highlights.push((name, highlight));
+                merged.highlights.push((name, highlight));
             }
         }
 
-        Arc::new(Self {
-            highlights: merged_highlights,
-        })
+        Arc::new(merged)
+    }
+
+    /// Returns a new [`Arc<SyntaxTheme>`] with the given syntax styles merged
+    /// in for files of the given language only.
+    ///
+    /// Each style is merged over the language's existing style with the same
+    /// name or, if there isn't one, the style with the same name for all
+    /// languages.
+    pub fn merge_language(
+        base: Arc<Self>,
+        language: &str,
+        user_syntax_styles: Vec<(String, HighlightStyle)>,
+    ) -> Arc<Self> {
+        if user_syntax_styles.is_empty() {
+            return base;
+        }
+
+        let mut merged = (*base).clone();
+        let mut language_highlights = merged
+            .language_highlights
+            .remove(language)
+            .unwrap_or_default();
+
+        for (name, highlight) in user_syntax_styles {
+            let existing_ix = language_highlights
+                .iter()
+                .copied()
+                .find(|ix| merged.highlights[*ix].0 == name);
+            if let Some(ix) = existing_ix {
+                merge_highlight_style(&mut merged.highlights[ix].1, highlight);
+            } else {
+                let mut style = merged.get(&name);
+                merge_highlight_style(&mut style, highlight);
+                language_highlights.push(merged.highlights.len());
+                merged.highlights.push((name, style));
+            }
+        }
+
+        merged
+            .language_highlights
+            .insert(language.to_string(), language_highlights);
+        Arc::new(merged)
     }
 }
 
+fn merge_highlight_style(existing_highlight: &mut HighlightStyle, highlight: HighlightStyle) {
+    existing_highlight.color = highlight.color.or(existing_highlight.color);
+    existing_highlight.font_weight = highlight.font_weight.or(existing_highlight.font_weight);
+    existing_highlight.font_style = highlight.font_style.or(existing_highlight.font_style);
+    existing_highlight.background_color = highlight
+        .background_color
+        .or(existing_highlight.background_color);
+    existing_highlight.underline = highlight.underline.or(existing_highlight.underline);
+    existing_highlight.strikethrough = highlight.strikethrough.or(existing_highlight.strikethrough);
+    existing_highlight.fade_out = highlight.fade_out.or(existing_highlight.fade_out);
+}
+
 #[cfg(test)]
 mod tests {
     use gpui::FontStyle;
 
     use super::*;
 
+    #[test]
+    fn test_syntax_theme_merge_language() {
+        let base = Arc::new(SyntaxTheme::new_test([
+            ("comment", gpui::red()),
+            ("keyword", gpui::green()),
+        ]));
+
+        let syntax_theme = SyntaxTheme::merge_language(
+            base.clone(),
+            "Rust",
+            vec![
+                (
+                    "comment".to_string(),
+                    HighlightStyle {
+                        font_style: Some(FontStyle::Italic),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "string".to_string(),
+                    HighlightStyle {
+                        color: Some(gpui::blue()),
+                        ..Default::default()
+                    },
+                ),
+            ],
+        );
+
+        // The language's styles start from the styles for all languages, which
+        // are unchanged.
+        assert_eq!(syntax_theme.highlights.len(), 4);
+        assert_eq!(syntax_theme.get("comment"), base.get("comment"));
+        assert_eq!(syntax_theme.get("string"), HighlightStyle::default());
+        assert_eq!(
+            syntax_theme.highlights[2],
+            (
+                "comment".to_string(),
+                HighlightStyle {
+                    color: Some(gpui::red()),
+                    font_style: Some(FontStyle::Italic),
+                    ..Default::default()
+                }
+            )
+        );
+        assert!(syntax_theme.is_language_specific(2));
+        assert!(syntax_theme.is_language_specific(3));
+        assert!(syntax_theme.applies_to_language(2, "Rust"));
+        assert!(!syntax_theme.applies_to_language(2, "Python"));
+        assert!(syntax_theme.applies_to_language(0, "Python"));
+
+        // Merging into the same language again updates its existing styles.
+        let syntax_theme = SyntaxTheme::merge_language(
+            syntax_theme,
+            "Rust",
+            vec![(
+                "comment".to_string(),
+                HighlightStyle {
+                    color: Some(gpui::yellow()),
+                    ..Default::default()
+                },
+            )],
+        );
+        assert_eq!(syntax_theme.highlights.len(), 4);
+        assert_eq!(
+            syntax_theme.highlights[2].1,
+            HighlightStyle {
+                color: Some(gpui::yellow()),
+                font_style: Some(FontStyle::Italic),
+                ..Default::default()
+            }
+        );
+    }
+
     #[test]
     fn test_syntax_theme_merge() {
         // Merging into an empty `SyntaxTheme` keeps all the user-defined styles.
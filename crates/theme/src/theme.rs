@@ -6,6 +6,7 @@
 //!
 //! A theme is a collection of colors used to build a consistent appearance for UI components across the application.
 
+mod accessibility;
 mod default_colors;
 mod default_theme;
 mod font_family_cache;
@@ -13,6 +14,7 @@ mod one_themes;
 pub mod prelude;
 mod registry;
 mod scale;
+mod schedule;
 mod schema;
 mod settings;
 mod styles;
@@ -20,11 +22,13 @@ mod styles;
 use std::sync::Arc;
 
 use ::settings::{Settings, SettingsStore};
+pub use accessibility::{AccessibilitySettings, AccessibilitySettingsContent};
 pub use default_colors::*;
 pub use default_theme::*;
 pub use font_family_cache::*;
 pub use registry::*;
 pub use scale::*;
+pub use schedule::ThemeSchedule;
 pub use schema::*;
 pub use settings::*;
 pub use styles::*;
@@ -86,8 +90,14 @@ pub fn init(themes_to_load: LoadThemes, cx: &mut AppContext) {
     ThemeSettings::register(cx);
     FontFamilyCache::init_global(cx);
 
+    schedule::update_theme_schedule(cx);
+    cx.set_reduce_motion(ThemeSettings::get_global(cx).accessibility.reduce_motion);
+
     let mut prev_buffer_font_size = ThemeSettings::get_global(cx).buffer_font_size;
     cx.observe_global::<SettingsStore>(move |cx| {
+        schedule::update_theme_schedule(cx);
+        cx.set_reduce_motion(ThemeSettings::get_global(cx).accessibility.reduce_motion);
+
         let buffer_font_size = ThemeSettings::get_global(cx).buffer_font_size;
         if buffer_font_size != prev_buffer_font_size {
             prev_buffer_font_size = buffer_font_size;
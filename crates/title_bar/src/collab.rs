@@ -9,7 +9,7 @@ use theme::ActiveTheme;
 use ui::{prelude::*, Avatar, AvatarAudioStatusIndicator, Facepile, TintColor, Tooltip};
 use workspace::notifications::DetachAndPromptErr;
 
-use crate::TitleBar;
+use crate::{SelectScreenShareSource, TitleBar};
 
 actions!(
     collab,
@@ -259,9 +259,17 @@ impl TitleBar {
                                     })?
                                     .clone();
 
-                                Some(div().mt(-px(4.)).child(
-                                    Avatar::new(follower.avatar_uri.clone()).size(rems(0.75)),
-                                ))
+                                Some(
+                                    div().mt(-px(4.)).child(
+                                        Avatar::new(follower.avatar_uri.clone())
+                                            .size(rems(0.75))
+                                            .border_color(if room.is_user_speaking(follower.id) {
+                                                cx.theme().status().info
+                                            } else {
+                                                gpui::transparent_black()
+                                            }),
+                                    ),
+                                )
                             },
                         ))
                         .children(if extra_count > 0 {
@@ -330,6 +338,39 @@ impl TitleBar {
             );
         }
 
+        if project.can_request_edit_access() {
+            let requested = project.edit_access_requested();
+            children.push(
+                Button::new(
+                    "request_edit_access",
+                    if requested {
+                        "Edit Requested"
+                    } else {
+                        "Request to Edit"
+                    },
+                )
+                .tooltip(move |cx| {
+                    Tooltip::text(
+                        if requested {
+                            "Waiting for the host to allow you to edit the project"
+                        } else {
+                            "Ask the host for permission to edit the project"
+                        },
+                        cx,
+                    )
+                })
+                .style(ButtonStyle::Subtle)
+                .label_size(LabelSize::Small)
+                .disabled(requested)
+                .on_click(cx.listener(|this, _, cx| {
+                    this.project
+                        .update(cx, |project, cx| project.request_edit_access(cx))
+                        .detach_and_log_err(cx);
+                }))
+                .into_any_element(),
+            );
+        }
+
         children.push(
             div()
                 .pr_2()
@@ -431,6 +472,24 @@ impl TitleBar {
                     .on_click(move |_, cx| toggle_screen_sharing(&Default::default(), cx))
                     .into_any_element(),
             );
+            children.push(
+                IconButton::new("screen-share-source", ui::IconName::ChevronDown)
+                    .style(ButtonStyle::Subtle)
+                    .icon_size(IconSize::XSmall)
+                    .disabled(!platform_supported)
+                    .tooltip(move |cx| {
+                        Tooltip::text(
+                            if is_screen_sharing {
+                                "Share Something Else"
+                            } else {
+                                "Share a Screen, Region or Window"
+                            },
+                            cx,
+                        )
+                    })
+                    .on_click(|_, cx| cx.dispatch_action(Box::new(SelectScreenShareSource)))
+                    .into_any_element(),
+            );
         }
 
         children.push(div().pr_2().into_any_element());
@@ -44,7 +44,8 @@ actions!(
         UnshareProject,
         ToggleUserMenu,
         ToggleProjectMenu,
-        SwitchBranch
+        SwitchBranch,
+        SelectScreenShareSource
     ]
 );
 
@@ -392,9 +393,9 @@ impl TitleBar {
                 .label_size(LabelSize::Small)
                 .tooltip(move |cx| {
                     Tooltip::with_meta(
-                        "Recent Branches",
+                        "Switch Branch",
                         Some(&ToggleVcsMenu),
-                        "Local branches only",
+                        "Create, rename, and delete local branches",
                         cx,
                     )
                 })
@@ -220,6 +220,7 @@ pub enum IconName {
     Quote,
     Regex,
     ReplNeutral,
+    ReplPause,
     Replace,
     ReplaceAll,
     ReplaceNext,
@@ -391,6 +392,7 @@ impl IconName {
             IconName::Quote => "icons/quote.svg",
             IconName::Regex => "icons/regex.svg",
             IconName::ReplNeutral => "icons/repl_neutral.svg",
+            IconName::ReplPause => "icons/repl_pause.svg",
             IconName::Replace => "icons/replace.svg",
             IconName::ReplaceAll => "icons/replace_all.svg",
             IconName::ReplaceNext => "icons/replace_next.svg",
@@ -1,16 +1,20 @@
 use anyhow::{Context, Result};
 use fuzzy::{StringMatch, StringMatchCandidate};
-use git::repository::Branch;
+use git::{
+    repository::{Branch, GitRepository},
+    status::FileChange,
+};
 use gpui::{
     actions, rems, AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView,
-    InteractiveElement, IntoElement, ParentElement, Render, SharedString, Styled, Subscription,
-    Task, View, ViewContext, VisualContext, WindowContext,
+    InteractiveElement, IntoElement, ParentElement, PromptLevel, Render, SharedString, Styled,
+    Subscription, Task, View, ViewContext, VisualContext, WindowContext,
 };
 use picker::{Picker, PickerDelegate};
 use std::{ops::Not, sync::Arc};
 use ui::{
-    h_flex, v_flex, Button, ButtonCommon, Clickable, Color, HighlightedLabel, Label, LabelCommon,
-    LabelSize, ListItem, ListItemSpacing, Selectable,
+    h_flex, v_flex, Button, ButtonCommon, Clickable, Color, HighlightedLabel, Icon, IconButton,
+    IconName, IconSize, Label, LabelCommon, LabelSize, ListItem, ListItemSpacing, Selectable,
+    Tooltip,
 };
 use util::ResultExt;
 use workspace::notifications::NotificationId;
@@ -79,12 +83,16 @@ impl Render for BranchList {
 
 pub struct BranchListDelegate {
     matches: Vec<StringMatch>,
+    /// The local branches, the current one first and then the ones with the
+    /// most recent commits.
     all_branches: Vec<Branch>,
     workspace: View<Workspace>,
     selected_index: usize,
     last_query: String,
     /// Max length of branch name before we truncate it and add a trailing `...`.
     branch_name_trailoff_after: usize,
+    /// The branch being renamed, whose new name is typed in the query.
+    renaming: Option<SharedString>,
 }
 
 impl BranchListDelegate {
@@ -99,7 +107,8 @@ impl BranchListDelegate {
             .get_first_worktree_root_repo(cx)
             .context("failed to get root repository for first worktree")?;
 
-        let all_branches = repo.branches()?;
+        let mut all_branches = repo.branches()?;
+        sort_branches(&mut all_branches);
         Ok(Self {
             matches: vec![],
             workspace: handle,
@@ -107,17 +116,211 @@ impl BranchListDelegate {
             selected_index: 0,
             last_query: Default::default(),
             branch_name_trailoff_after,
+            renaming: None,
         })
     }
 
-    fn display_error_toast(&self, message: String, cx: &mut WindowContext<'_>) {
-        self.workspace.update(cx, |model, ctx| {
-            struct GitCheckoutFailure;
-            let id = NotificationId::unique::<GitCheckoutFailure>();
+    fn repo(&self, cx: &AppContext) -> Result<Arc<dyn GitRepository>> {
+        self.workspace
+            .read(cx)
+            .project()
+            .read(cx)
+            .get_first_worktree_root_repo(cx)
+            .context("failed to get root repository for first worktree")
+    }
+
+    fn reload_branches(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(branches) = self.repo(cx).and_then(|repo| repo.branches()).log_err() else {
+            return;
+        };
+        self.all_branches = branches;
+        sort_branches(&mut self.all_branches);
+        cx.spawn(|picker, mut cx| async move {
+            picker.update(&mut cx, |picker, cx| picker.refresh(cx)).ok();
+        })
+        .detach();
+    }
+
+    /// Checks out the branch. When the working tree has uncommitted changes,
+    /// which could conflict with the branch, asks whether to stash them first.
+    fn checkout(&self, branch: SharedString, cx: &mut ViewContext<Picker<Self>>) {
+        let repo = match self.repo(cx) {
+            Ok(repo) => repo,
+            Err(error) => {
+                display_error_toast(&self.workspace, error.to_string(), cx);
+                return;
+            }
+        };
+        let workspace = self.workspace.clone();
+        cx.spawn(|picker, mut cx| async move {
+            let statuses = cx
+                .background_executor()
+                .spawn({
+                    let repo = repo.clone();
+                    async move { repo.file_statuses() }
+                })
+                .await?;
+            let is_dirty = statuses.iter().any(|status| {
+                status.staged.is_some()
+                    || status
+                        .unstaged
+                        .map_or(false, |change| change != FileChange::Untracked)
+            });
+            let mut stash = false;
+            if is_dirty {
+                let answer = workspace.update(&mut cx, |_, cx| {
+                    cx.prompt(
+                        PromptLevel::Warning,
+                        &format!("Stash the uncommitted changes before checking out '{branch}'?"),
+                        Some("Changes that conflict with the branch prevent checking it out."),
+                        &["Stash and Checkout", "Checkout", "Cancel"],
+                    )
+                })?;
+                match answer.await? {
+                    0 => stash = true,
+                    1 => {}
+                    _ => return Ok(()),
+                }
+            }
 
-            model.show_toast(Toast::new(id, message), ctx)
+            let result = cx
+                .background_executor()
+                .spawn({
+                    let branch = branch.clone();
+                    async move {
+                        if stash {
+                            repo.stash_all(&format!("Checking out {branch}"))?;
+                        }
+                        repo.change_branch(&branch)
+                    }
+                })
+                .await;
+            if result.is_err() {
+                cx.update(|cx| {
+                    display_error_toast(
+                        &workspace,
+                        format!("Failed to checkout branch '{branch}', check for conflicts or unstashed files"),
+                        cx,
+                    )
+                })?;
+            }
+            picker.update(&mut cx, |_, cx| cx.emit(DismissEvent)).ok();
+            result
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn create_branch(&self, branch: String, cx: &mut ViewContext<Picker<Self>>) {
+        let result = self.repo(cx).and_then(|repo| {
+            repo.create_branch(&branch)
+                .context("failed to create branch")?;
+            repo.change_branch(&branch)
         });
+        match result {
+            Ok(()) => cx.emit(DismissEvent),
+            Err(error) => display_error_toast(
+                &self.workspace,
+                format!("Failed to create branch '{branch}': {error}"),
+                cx,
+            ),
+        }
+    }
+
+    fn start_renaming(&mut self, branch: SharedString, cx: &mut ViewContext<Picker<Self>>) {
+        self.renaming = Some(branch.clone());
+        cx.spawn(|picker, mut cx| async move {
+            picker
+                .update(&mut cx, |picker, cx| {
+                    picker.set_query(branch.as_ref(), cx);
+                    picker.refresh(cx);
+                })
+                .ok();
+        })
+        .detach();
     }
+
+    fn rename_branch(&mut self, new_name: String, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(old_name) = self.renaming.take() else {
+            return;
+        };
+        if !new_name.is_empty() && new_name != old_name.as_ref() {
+            let result = self
+                .repo(cx)
+                .and_then(|repo| repo.rename_branch(&old_name, &new_name));
+            if let Err(error) = result {
+                display_error_toast(
+                    &self.workspace,
+                    format!("Failed to rename branch '{old_name}': {error}"),
+                    cx,
+                );
+            }
+        }
+        cx.spawn(|picker, mut cx| async move {
+            picker
+                .update(&mut cx, |picker, cx| {
+                    picker.set_query("", cx);
+                    picker.delegate.reload_branches(cx);
+                })
+                .ok();
+        })
+        .detach();
+    }
+
+    fn delete_branch(&self, branch: SharedString, cx: &mut ViewContext<Picker<Self>>) {
+        let repo = match self.repo(cx) {
+            Ok(repo) => repo,
+            Err(error) => {
+                display_error_toast(&self.workspace, error.to_string(), cx);
+                return;
+            }
+        };
+        let workspace = self.workspace.clone();
+        let answer = cx.prompt(
+            PromptLevel::Warning,
+            &format!("Delete branch '{branch}'?"),
+            Some("Commits that aren't in another branch will only be reachable from the reflog."),
+            &["Delete", "Cancel"],
+        );
+        cx.spawn(|picker, mut cx| async move {
+            if answer.await? != 0 {
+                return Ok(());
+            }
+            if let Err(error) = repo.delete_branch(&branch) {
+                cx.update(|cx| {
+                    display_error_toast(
+                        &workspace,
+                        format!("Failed to delete branch '{branch}': {error}"),
+                        cx,
+                    )
+                })?;
+            }
+            picker
+                .update(&mut cx, |picker, cx| picker.delegate.reload_branches(cx))
+                .ok();
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+/// Puts the current branch first, followed by the branches with the most
+/// recent commits.
+fn sort_branches(branches: &mut [Branch]) {
+    branches.sort_by(|lhs, rhs| {
+        rhs.is_head
+            .cmp(&lhs.is_head)
+            .then(rhs.unix_timestamp.cmp(&lhs.unix_timestamp))
+            .then(lhs.name.cmp(&rhs.name))
+    });
+}
+
+fn display_error_toast(workspace: &View<Workspace>, message: String, cx: &mut WindowContext<'_>) {
+    workspace.update(cx, |model, ctx| {
+        struct GitCheckoutFailure;
+        let id = NotificationId::unique::<GitCheckoutFailure>();
+
+        model.show_toast(Toast::new(id, message), ctx)
+    });
 }
 
 impl PickerDelegate for BranchListDelegate {
@@ -127,6 +330,16 @@ impl PickerDelegate for BranchListDelegate {
         "Select branch...".into()
     }
 
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        if let Some(branch) = &self.renaming {
+            format!("Confirm to rename '{branch}'").into()
+        } else if self.last_query.is_empty() {
+            "No branches".into()
+        } else {
+            format!("Confirm to create branch '{}'", self.last_query).into()
+        }
+    }
+
     fn match_count(&self) -> usize {
         self.matches.len()
     }
@@ -140,38 +353,24 @@ impl PickerDelegate for BranchListDelegate {
     }
 
     fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        if self.renaming.is_some() {
+            self.matches.clear();
+            self.selected_index = 0;
+            self.last_query = query;
+            return Task::ready(());
+        }
+
+        let candidates = self
+            .all_branches
+            .iter()
+            .enumerate()
+            .map(|(ix, branch)| StringMatchCandidate {
+                id: ix,
+                char_bag: branch.name.chars().collect(),
+                string: branch.name.to_string(),
+            })
+            .collect::<Vec<StringMatchCandidate>>();
         cx.spawn(move |picker, mut cx| async move {
-            let candidates = picker.update(&mut cx, |view, _| {
-                const RECENT_BRANCHES_COUNT: usize = 10;
-                let mut branches = view.delegate.all_branches.clone();
-                if query.is_empty() {
-                    if branches.len() > RECENT_BRANCHES_COUNT {
-                        // Truncate list of recent branches
-                        // Do a partial sort to show recent-ish branches first.
-                        branches.select_nth_unstable_by(RECENT_BRANCHES_COUNT - 1, |lhs, rhs| {
-                            rhs.is_head
-                                .cmp(&lhs.is_head)
-                                .then(rhs.unix_timestamp.cmp(&lhs.unix_timestamp))
-                        });
-                        branches.truncate(RECENT_BRANCHES_COUNT);
-                    }
-                    branches.sort_unstable_by(|lhs, rhs| {
-                        rhs.is_head.cmp(&lhs.is_head).then(lhs.name.cmp(&rhs.name))
-                    });
-                }
-                branches
-                    .into_iter()
-                    .enumerate()
-                    .map(|(ix, command)| StringMatchCandidate {
-                        id: ix,
-                        char_bag: command.name.chars().collect(),
-                        string: command.name.into(),
-                    })
-                    .collect::<Vec<StringMatchCandidate>>()
-            });
-            let Some(candidates) = candidates.log_err() else {
-                return;
-            };
             let matches = if query.is_empty() {
                 candidates
                     .into_iter()
@@ -211,34 +410,23 @@ impl PickerDelegate for BranchListDelegate {
     }
 
     fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
-        let current_pick = self.selected_index();
-        let Some(current_pick) = self
-            .matches
-            .get(current_pick)
-            .map(|pick| pick.string.clone())
-        else {
+        let query = self.last_query.trim().to_string();
+        if self.renaming.is_some() {
+            self.rename_branch(query, cx);
             return;
-        };
-        cx.spawn(|picker, mut cx| async move {
-            picker
-                .update(&mut cx, |this, cx| {
-                    let project = this.delegate.workspace.read(cx).project().read(cx);
-                    let repo = project
-                        .get_first_worktree_root_repo(cx)
-                        .context("failed to get root repository for first worktree")?;
-                    let status = repo
-                        .change_branch(&current_pick);
-                    if status.is_err() {
-                        this.delegate.display_error_toast(format!("Failed to checkout branch '{current_pick}', check for conflicts or unstashed files"), cx);
-                        status?;
-                    }
+        }
+        match self.matches.get(self.selected_index()) {
+            Some(pick) => {
+                let branch = &self.all_branches[pick.candidate_id];
+                if branch.is_head {
                     cx.emit(DismissEvent);
-
-                    Ok::<(), anyhow::Error>(())
-                })
-                .log_err();
-        })
-        .detach();
+                } else {
+                    self.checkout(pick.string.clone().into(), cx);
+                }
+            }
+            None if !query.is_empty() => self.create_branch(query, cx),
+            None => {}
+        }
     }
 
     fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
@@ -249,9 +437,10 @@ impl PickerDelegate for BranchListDelegate {
         &self,
         ix: usize,
         selected: bool,
-        _cx: &mut ViewContext<Picker<Self>>,
+        cx: &mut ViewContext<Picker<Self>>,
     ) -> Option<Self::ListItem> {
         let hit = &self.matches[ix];
+        let branch = &self.all_branches[hit.candidate_id];
         let shortened_branch_name =
             util::truncate_and_trailoff(&hit.string, self.branch_name_trailoff_after);
         let highlights: Vec<_> = hit
@@ -260,17 +449,71 @@ impl PickerDelegate for BranchListDelegate {
             .filter(|index| index < &&self.branch_name_trailoff_after)
             .copied()
             .collect();
+
+        let tracking = branch.upstream.and_then(|upstream| {
+            let mut counts = Vec::new();
+            if upstream.ahead > 0 {
+                counts.push(format!("↑{}", upstream.ahead));
+            }
+            if upstream.behind > 0 {
+                counts.push(format!("↓{}", upstream.behind));
+            }
+            counts.is_empty().not().then(|| {
+                Label::new(counts.join(" "))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+            })
+        });
+        let branch_name = SharedString::from(hit.string.clone());
+        let rename_branch = branch_name.clone();
+        let delete_branch = branch_name.clone();
+        let actions = h_flex()
+            .gap_1()
+            .child(
+                IconButton::new(("rename-branch", ix), IconName::Pencil)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| Tooltip::text("Rename Branch", cx))
+                    .on_click(cx.listener(move |picker, _, cx| {
+                        picker.delegate.start_renaming(rename_branch.clone(), cx)
+                    })),
+            )
+            .children(branch.is_head.not().then(|| {
+                IconButton::new(("delete-branch", ix), IconName::Trash)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| Tooltip::text("Delete Branch", cx))
+                    .on_click(cx.listener(move |picker, _, cx| {
+                        picker.delegate.delete_branch(delete_branch.clone(), cx)
+                    }))
+            }));
+
         Some(
             ListItem::new(SharedString::from(format!("vcs-menu-{ix}")))
                 .inset(true)
                 .spacing(ListItemSpacing::Sparse)
                 .selected(selected)
-                .start_slot(HighlightedLabel::new(shortened_branch_name, highlights)),
+                .start_slot(
+                    h_flex()
+                        .gap_1()
+                        .child(HighlightedLabel::new(shortened_branch_name, highlights))
+                        .children(branch.is_head.then(|| {
+                            Icon::new(IconName::Check)
+                                .size(IconSize::Small)
+                                .color(Color::Accent)
+                        })),
+                )
+                .end_slot(tracking)
+                .end_hover_slot(actions),
         )
     }
 
     fn render_header(&self, _: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
-        let label = if self.last_query.is_empty() {
+        let label = if let Some(branch) = &self.renaming {
+            Label::new(format!("Rename '{branch}' to"))
+                .size(LabelSize::Small)
+                .mt_1()
+                .ml_3()
+                .into_any_element()
+        } else if self.last_query.is_empty() {
             Label::new("Recent Branches")
                 .size(LabelSize::Small)
                 .mt_1()
@@ -298,41 +541,230 @@ impl PickerDelegate for BranchListDelegate {
             return None;
         }
 
+        let button = if self.renaming.is_some() {
+            Button::new("branch-picker-rename-branch-button", "Rename branch").on_click(
+                cx.listener(|picker, _, cx| {
+                    let new_name = picker.delegate.last_query.trim().to_string();
+                    picker.delegate.rename_branch(new_name, cx);
+                }),
+            )
+        } else {
+            Button::new("branch-picker-create-branch-button", "Create branch").on_click(
+                cx.listener(|picker, _, cx| {
+                    let branch = picker.delegate.last_query.trim().to_string();
+                    picker.delegate.create_branch(branch, cx);
+                }),
+            )
+        };
         Some(
             h_flex()
                 .mr_3()
                 .pb_2()
                 .child(h_flex().w_full())
-                .child(
-                    Button::new("branch-picker-create-branch-button", "Create branch")
-                        .on_click(cx.listener(|_, _, cx| {
-                            cx.spawn(|picker, mut cx| async move {
-                                picker.update(&mut cx, |this, cx| {
-                                    let project =
-                                        this.delegate.workspace.read(cx).project().read(cx);
-                                    let current_pick = &this.delegate.last_query;
-                                    let repo = project.get_first_worktree_root_repo(cx).context(
-                                        "failed to get root repository for first worktree",
-                                    )?;
-                                    let status = repo.create_branch(&current_pick);
-                                    if status.is_err() {
-                                        this.delegate.display_error_toast(format!("Failed to create branch '{current_pick}', check for conflicts or unstashed files"), cx);
-                                        status?;
-                                    }
-                                    let status = repo.change_branch(&current_pick);
-                                    if status.is_err() {
-                                        this.delegate.display_error_toast(format!("Failed to check branch '{current_pick}', check for conflicts or unstashed files"), cx);
-                                        status?;
-                                    }
-                                    this.cancel(&Default::default(), cx);
-                                    Ok::<(), anyhow::Error>(())
-                                })
-                            })
-                            .detach_and_log_err(cx);
-                        }))
-                        .style(ui::ButtonStyle::Filled),
-                )
+                .child(button.style(ui::ButtonStyle::Filled))
                 .into_any_element(),
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git::repository::GitFileStatus;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use std::path::Path;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_create_and_switch_branches(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ ".git": {}, "a.txt": "a" }))
+            .await;
+        fs.insert_branches(Path::new("/dir/.git"), &["main", "release"]);
+        fs.set_branch_name(Path::new("/dir/.git"), Some("release"));
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.run_until_parked();
+        let repo = project.update(cx, |project, cx| {
+            project.get_first_worktree_root_repo(cx).unwrap()
+        });
+
+        let picker = open_branch_list(&workspace, cx);
+        assert_eq!(
+            picker.update(cx, |picker, _| branch_names(&picker.delegate)),
+            vec!["release", "main"],
+            "The current branch should be listed first"
+        );
+
+        cx.simulate_input("feature");
+        cx.dispatch_action(menu::Confirm);
+        cx.run_until_parked();
+        assert_eq!(repo.branch_name().as_deref(), Some("feature"));
+        assert!(workspace.update(cx, |workspace, cx| workspace
+            .active_modal::<BranchList>(cx)
+            .is_none()));
+
+        open_branch_list(&workspace, cx);
+        cx.simulate_input("main");
+        cx.dispatch_action(menu::Confirm);
+        cx.run_until_parked();
+        assert!(!cx.has_pending_prompt());
+        assert_eq!(repo.branch_name().as_deref(), Some("main"));
+        assert_eq!(
+            sorted_branch_names(&repo),
+            vec!["feature", "main", "release"]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_switch_branches_with_uncommitted_changes(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ ".git": {}, "a.txt": "a" }))
+            .await;
+        fs.insert_branches(Path::new("/dir/.git"), &["main", "release"]);
+        fs.set_branch_name(Path::new("/dir/.git"), Some("main"));
+        fs.set_status_for_repo_via_working_copy_change(
+            Path::new("/dir/.git"),
+            &[(Path::new("a.txt"), GitFileStatus::Modified)],
+        );
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.run_until_parked();
+        let repo = project.update(cx, |project, cx| {
+            project.get_first_worktree_root_repo(cx).unwrap()
+        });
+
+        // Checking out without stashing fails, as the changes conflict with the branch.
+        open_branch_list(&workspace, cx);
+        cx.simulate_input("release");
+        cx.dispatch_action(menu::Confirm);
+        cx.run_until_parked();
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer(1);
+        cx.run_until_parked();
+        assert_eq!(repo.branch_name().as_deref(), Some("main"));
+        assert_eq!(
+            workspace.update(cx, |workspace, _| workspace.notification_ids().len()),
+            1,
+            "A toast should report the failed checkout"
+        );
+
+        // Cancelling leaves everything as is, and the picker open.
+        open_branch_list(&workspace, cx);
+        cx.simulate_input("release");
+        cx.dispatch_action(menu::Confirm);
+        cx.run_until_parked();
+        cx.simulate_prompt_answer(2);
+        cx.run_until_parked();
+        assert_eq!(repo.branch_name().as_deref(), Some("main"));
+        assert_eq!(repo.file_statuses().unwrap().len(), 1);
+        assert!(workspace.update(cx, |workspace, cx| workspace
+            .active_modal::<BranchList>(cx)
+            .is_some()));
+
+        // Stashing the changes first lets the branch be checked out.
+        cx.dispatch_action(menu::Confirm);
+        cx.run_until_parked();
+        cx.simulate_prompt_answer(0);
+        cx.run_until_parked();
+        assert_eq!(repo.branch_name().as_deref(), Some("release"));
+        assert!(repo.file_statuses().unwrap().is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_delete_branches(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ ".git": {}, "a.txt": "a" }))
+            .await;
+        fs.insert_branches(Path::new("/dir/.git"), &["main", "release"]);
+        fs.set_branch_name(Path::new("/dir/.git"), Some("main"));
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.run_until_parked();
+        let repo = project.update(cx, |project, cx| {
+            project.get_first_worktree_root_repo(cx).unwrap()
+        });
+
+        let picker = open_branch_list(&workspace, cx);
+        picker.update(cx, |picker, cx| {
+            picker.delegate.delete_branch("release".into(), cx)
+        });
+        cx.run_until_parked();
+        cx.simulate_prompt_answer(0);
+        cx.run_until_parked();
+        assert_eq!(sorted_branch_names(&repo), vec!["main"]);
+        assert_eq!(
+            picker.update(cx, |picker, _| branch_names(&picker.delegate)),
+            vec!["main"],
+            "The deleted branch should no longer be listed"
+        );
+
+        // The current branch can't be deleted.
+        picker.update(cx, |picker, cx| {
+            picker.delegate.delete_branch("main".into(), cx)
+        });
+        cx.run_until_parked();
+        cx.simulate_prompt_answer(0);
+        cx.run_until_parked();
+        assert_eq!(sorted_branch_names(&repo), vec!["main"]);
+        assert_eq!(
+            workspace.update(cx, |workspace, _| workspace.notification_ids().len()),
+            1,
+            "A toast should report the failed deletion"
+        );
+    }
+
+    fn open_branch_list(
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> View<Picker<BranchListDelegate>> {
+        cx.dispatch_action(OpenRecent);
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<BranchList>(cx)
+                .unwrap()
+                .read(cx)
+                .picker
+                .clone()
+        })
+    }
+
+    fn branch_names(delegate: &BranchListDelegate) -> Vec<String> {
+        delegate
+            .matches
+            .iter()
+            .map(|branch| branch.string.clone())
+            .collect()
+    }
+
+    fn sorted_branch_names(repo: &Arc<dyn GitRepository>) -> Vec<String> {
+        let mut names = repo
+            .branches()
+            .unwrap()
+            .into_iter()
+            .map(|branch| branch.name.to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            language::init(cx);
+            crate::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            state
+        })
+    }
+}
@@ -6,7 +6,7 @@ use std::time::Duration;
 
 use collections::HashMap;
 use command_palette::CommandPalette;
-use editor::{actions::DeleteLine, display_map::DisplayRow, DisplayPoint};
+use editor::{actions::DeleteLine, display_map::DisplayRow, DisplayPoint, EditorSettings};
 use futures::StreamExt;
 use gpui::{KeyBinding, Modifiers, MouseButton, TestAppContext};
 pub use neovim_backed_test_context::*;
@@ -14,10 +14,11 @@ use settings::SettingsStore;
 pub use vim_test_context::*;
 
 use indoc::indoc;
+use language::CursorShape;
 use search::BufferSearchBar;
 use workspace::WorkspaceSettings;
 
-use crate::{insert::NormalBefore, motion, state::Mode};
+use crate::{insert::NormalBefore, motion, state::Mode, CursorShapeSettings, VimSettings};
 
 #[gpui::test]
 async fn test_initially_disabled(cx: &mut gpui::TestAppContext) {
@@ -1309,6 +1310,41 @@ async fn test_command_alias(cx: &mut gpui::TestAppContext) {
     cx.set_state("ˇHello world", Mode::Normal);
 }
 
+#[gpui::test]
+async fn test_cursor_shape_setting(cx: &mut gpui::TestAppContext) {
+    let mut cx = VimTestContext::new(cx, true).await;
+    cx.update_global(|store: &mut SettingsStore, cx| {
+        store.update_user_settings::<EditorSettings>(cx, |s| {
+            s.cursor_shape = Some(CursorShape::Underscore);
+        });
+    });
+    cx.update_editor(|editor, _| assert_eq!(editor.cursor_shape(), CursorShape::Block));
+
+    // Insert mode uses the editor's shape, unless Vim sets one for it.
+    cx.simulate_keystrokes("i");
+    cx.update_editor(|editor, _| assert_eq!(editor.cursor_shape(), CursorShape::Underscore));
+    cx.update_global(|store: &mut SettingsStore, cx| {
+        store.update_user_settings::<VimSettings>(cx, |s| {
+            s.cursor_shape = Some(CursorShapeSettings {
+                insert: Some(CursorShape::Hollow),
+                ..Default::default()
+            });
+        });
+    });
+    cx.update_editor(|editor, _| assert_eq!(editor.cursor_shape(), CursorShape::Hollow));
+
+    cx.simulate_keystrokes("escape");
+    cx.update_global(|store: &mut SettingsStore, cx| {
+        store.update_user_settings::<EditorSettings>(cx, |s| {
+            s.cursor_shape = Some(CursorShape::Bar);
+        });
+    });
+    cx.update_editor(|editor, _| assert_eq!(editor.cursor_shape(), CursorShape::Block));
+
+    cx.disable_vim();
+    cx.update_editor(|editor, _| assert_eq!(editor.cursor_shape(), CursorShape::Bar));
+}
+
 #[gpui::test]
 async fn test_remap_adjacent_dog_cat(cx: &mut gpui::TestAppContext) {
     let mut cx = NeovimBackedTestContext::new(cx).await;
@@ -20,7 +20,7 @@ use anyhow::Result;
 use collections::HashMap;
 use editor::{
     movement::{self, FindRange},
-    Anchor, Bias, Editor, EditorEvent, EditorMode, ToPoint,
+    Anchor, Bias, Editor, EditorEvent, EditorMode, EditorSettings, ToPoint,
 };
 use gpui::{
     actions, impl_actions, Action, AppContext, Entity, EventEmitter, KeyContext, KeystrokeEvent,
@@ -243,6 +243,12 @@ impl Vim {
                 }
             }
             was_toggle = VimSettings::get_global(cx).toggle_relative_line_numbers;
+            if enabled && was_enabled {
+                if let Some(vim) = editor.addon::<VimAddon>() {
+                    let cursor_shape = vim.view.read(cx).cursor_shape(cx);
+                    editor.set_cursor_shape(cursor_shape, cx);
+                }
+            }
             if was_enabled == enabled {
                 return;
             }
@@ -302,7 +308,7 @@ impl Vim {
     }
 
     fn deactivate(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
-        editor.set_cursor_shape(CursorShape::Bar, cx);
+        editor.reset_cursor_shape(cx);
         editor.set_clip_at_line_ends(false, cx);
         editor.set_collapse_matches(false);
         editor.set_input_enabled(true);
@@ -539,18 +545,23 @@ impl Vim {
         count
     }
 
-    pub fn cursor_shape(&self) -> CursorShape {
+    pub fn cursor_shape(&self, cx: &AppContext) -> CursorShape {
+        let cursor_shape = &VimSettings::get_global(cx).cursor_shape;
         match self.mode {
             Mode::Normal => {
                 if self.operator_stack.is_empty() {
-                    CursorShape::Block
+                    cursor_shape.normal.unwrap_or(CursorShape::Block)
                 } else {
                     CursorShape::Underscore
                 }
             }
-            Mode::Replace => CursorShape::Underscore,
-            Mode::Visual | Mode::VisualLine | Mode::VisualBlock => CursorShape::Block,
-            Mode::Insert => CursorShape::Bar,
+            Mode::Replace => cursor_shape.replace.unwrap_or(CursorShape::Underscore),
+            Mode::Visual | Mode::VisualLine | Mode::VisualBlock => {
+                cursor_shape.visual.unwrap_or(CursorShape::Block)
+            }
+            Mode::Insert => cursor_shape
+                .insert
+                .unwrap_or(EditorSettings::get_global(cx).cursor_shape),
         }
     }
 
@@ -1055,7 +1066,7 @@ impl Vim {
 
     fn sync_vim_settings(&mut self, cx: &mut ViewContext<Self>) {
         self.update_editor(cx, |vim, editor, cx| {
-            editor.set_cursor_shape(vim.cursor_shape(), cx);
+            editor.set_cursor_shape(vim.cursor_shape(cx), cx);
             editor.set_clip_at_line_ends(vim.clip_at_line_ends(), cx);
             editor.set_collapse_matches(true);
             editor.set_input_enabled(vim.editor_input_enabled());
@@ -1073,9 +1084,13 @@ impl Settings for VimModeSetting {
     type FileContent = Option<bool>;
 
     fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
-        Ok(Self(sources.user.copied().flatten().unwrap_or(
-            sources.default.ok_or_else(Self::missing_default)?,
-        )))
+        Ok(Self(
+            sources
+                .user_customizations()
+                .filter_map(|value| *value)
+                .last()
+                .unwrap_or(sources.default.ok_or_else(Self::missing_default)?),
+        ))
     }
 }
 
@@ -1098,6 +1113,17 @@ struct VimSettings {
     pub use_multiline_find: bool,
     pub use_smartcase_find: bool,
     pub custom_digraphs: HashMap<String, Arc<str>>,
+    pub cursor_shape: CursorShapeSettings,
+}
+
+/// The shapes of the cursor in each mode. Unset modes use the default shape of the
+/// mode, except for insert mode, which uses the editor's `cursor_shape`.
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+struct CursorShapeSettings {
+    pub normal: Option<CursorShape>,
+    pub insert: Option<CursorShape>,
+    pub replace: Option<CursorShape>,
+    pub visual: Option<CursorShape>,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -1107,6 +1133,7 @@ struct VimSettingsContent {
     pub use_multiline_find: Option<bool>,
     pub use_smartcase_find: Option<bool>,
     pub custom_digraphs: Option<HashMap<String, Arc<str>>>,
+    pub cursor_shape: Option<CursorShapeSettings>,
 }
 
 impl Settings for VimSettings {
@@ -0,0 +1,666 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use db::kvp::KEY_VALUE_STORE;
+use gpui::{
+    svg, Action, AppContext, EventEmitter, FocusHandle, FocusableView, Subscription, Task, View,
+    WeakView,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources, SettingsStore};
+use ui::{prelude::*, KeyBinding, ListItem, ListItemSpacing, Tooltip};
+use util::{paths::PathExt as _, ResultExt as _};
+use workspace::{
+    item::{Item, ItemEvent},
+    SerializedWorkspaceLocation, Workspace, WORKSPACE_DB,
+};
+
+const PINNED_PROJECTS_KEY: &str = "dashboard_pinned_projects";
+
+/// The tips listed on the dashboard, with the name of the action each one is about.
+const TIPS: &[(&str, &str)] = &[
+    (
+        "Run any command from the command palette",
+        "command_palette::Toggle",
+    ),
+    (
+        "Open any file of the project by name",
+        "file_finder::Toggle",
+    ),
+    (
+        "Search across all of the files of the project",
+        "workspace::NewSearch",
+    ),
+    ("Jump to a symbol of the current file", "outline::Toggle"),
+    (
+        "Show or hide the project panel",
+        "workspace::ToggleLeftDock",
+    ),
+    ("Open a terminal", "workspace::NewTerminal"),
+    (
+        "Add a cursor to the next match of the selection",
+        "editor::SelectNext",
+    ),
+    (
+        "Dim the code outside of the current function",
+        "editor::ToggleFocusMode",
+    ),
+];
+
+#[derive(Clone, Deserialize)]
+pub struct DashboardSettings {
+    pub enabled: bool,
+    pub max_recent_projects: usize,
+    pub show_tips: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct DashboardSettingsContent {
+    /// Whether to show the dashboard instead of an empty buffer when Zed starts
+    /// without a project to restore.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// How many recent projects are listed on the dashboard.
+    ///
+    /// Default: 8
+    pub max_recent_projects: Option<usize>,
+    /// Whether to list tips on the dashboard.
+    ///
+    /// Default: true
+    pub show_tips: Option<bool>,
+}
+
+impl Settings for DashboardSettings {
+    const KEY: Option<&'static str> = Some("dashboard");
+
+    type FileContent = DashboardSettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+enum DashboardEntry {
+    Project {
+        paths: Vec<PathBuf>,
+        pinned: bool,
+    },
+    QuickAction {
+        label: &'static str,
+        icon: IconName,
+        action: Box<dyn Action>,
+    },
+    Tip {
+        text: &'static str,
+        action: Box<dyn Action>,
+    },
+}
+
+impl DashboardEntry {
+    fn section(&self) -> &'static str {
+        match self {
+            DashboardEntry::Project { pinned: true, .. } => "Pinned Projects",
+            DashboardEntry::Project { pinned: false, .. } => "Recent Projects",
+            DashboardEntry::QuickAction { .. } => "Get Started",
+            DashboardEntry::Tip { .. } => "Tips",
+        }
+    }
+}
+
+/// The page shown when Zed starts without a project, listing the pinned and recent
+/// projects, quick actions and tips, which can all be navigated with the keyboard.
+pub struct Dashboard {
+    workspace: WeakView<Workspace>,
+    focus_handle: FocusHandle,
+    recent_projects: Vec<Vec<PathBuf>>,
+    pinned_projects: Vec<Vec<PathBuf>>,
+    entries: Vec<DashboardEntry>,
+    selected_index: usize,
+    _load_projects: Task<()>,
+    _settings_subscription: Subscription,
+}
+
+impl Dashboard {
+    pub fn new(workspace: &Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let workspace = workspace.weak_handle();
+        cx.new_view(|cx| {
+            let load_projects = cx.spawn(|this, mut cx| async move {
+                let recent_projects = WORKSPACE_DB
+                    .recent_workspaces_on_disk()
+                    .await
+                    .log_err()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|(_, location)| match location {
+                        SerializedWorkspaceLocation::Local(paths, _) => {
+                            Some(paths.paths().to_vec())
+                        }
+                        SerializedWorkspaceLocation::DevServer(_) => None,
+                    })
+                    .collect();
+                let pinned_projects = KEY_VALUE_STORE
+                    .read_kvp(PINNED_PROJECTS_KEY)
+                    .log_err()
+                    .flatten()
+                    .and_then(|json| serde_json::from_str(&json).log_err())
+                    .unwrap_or_default();
+                this.update(&mut cx, |this, cx| {
+                    this.recent_projects = recent_projects;
+                    this.pinned_projects = pinned_projects;
+                    this.update_entries(cx);
+                })
+                .ok();
+            });
+
+            let mut this = Self {
+                workspace,
+                focus_handle: cx.focus_handle(),
+                recent_projects: Vec::new(),
+                pinned_projects: Vec::new(),
+                entries: Vec::new(),
+                selected_index: 0,
+                _load_projects: load_projects,
+                _settings_subscription: cx
+                    .observe_global::<SettingsStore>(|this, cx| this.update_entries(cx)),
+            };
+            this.update_entries(cx);
+            this
+        })
+    }
+
+    /// Adds a dashboard to the active pane of the workspace, and focuses it.
+    pub fn show(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+        let dashboard = Self::new(workspace, cx);
+        workspace.add_item_to_active_pane(Box::new(dashboard.clone()), None, true, cx);
+        cx.focus_view(&dashboard);
+    }
+
+    /// Shows a dashboard in the workspace unless the `dashboard.enabled` setting is off,
+    /// returning whether it was shown.
+    pub fn show_if_enabled(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> bool {
+        let enabled = DashboardSettings::get_global(cx).enabled;
+        if enabled {
+            Self::show(workspace, cx);
+        }
+        enabled
+    }
+
+    fn update_entries(&mut self, cx: &mut ViewContext<Self>) {
+        let settings = DashboardSettings::get_global(cx);
+        let max_recent_projects = settings.max_recent_projects;
+        let show_tips = settings.show_tips;
+
+        let mut entries = self
+            .pinned_projects
+            .iter()
+            .map(|paths| DashboardEntry::Project {
+                paths: paths.clone(),
+                pinned: true,
+            })
+            .collect::<Vec<_>>();
+        entries.extend(
+            self.recent_projects
+                .iter()
+                .filter(|paths| !self.pinned_projects.contains(paths))
+                .take(max_recent_projects)
+                .map(|paths| DashboardEntry::Project {
+                    paths: paths.clone(),
+                    pinned: false,
+                }),
+        );
+        entries.extend(quick_actions());
+        if show_tips {
+            entries.extend(TIPS.iter().filter_map(|&(text, action_name)| {
+                Some(DashboardEntry::Tip {
+                    text,
+                    action: cx.build_action(action_name, None).ok()?,
+                })
+            }));
+        }
+
+        self.selected_index = self.selected_index.min(entries.len().saturating_sub(1));
+        self.entries = entries;
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, cx: &mut ViewContext<Self>) {
+        if self.selected_index + 1 < self.entries.len() {
+            self.selected_index += 1;
+            cx.notify();
+        }
+    }
+
+    fn select_prev(&mut self, _: &menu::SelectPrev, cx: &mut ViewContext<Self>) {
+        if self.selected_index > 0 {
+            self.selected_index -= 1;
+            cx.notify();
+        }
+    }
+
+    fn select_first(&mut self, _: &menu::SelectFirst, cx: &mut ViewContext<Self>) {
+        self.selected_index = 0;
+        cx.notify();
+    }
+
+    fn select_last(&mut self, _: &menu::SelectLast, cx: &mut ViewContext<Self>) {
+        self.selected_index = self.entries.len().saturating_sub(1);
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        self.confirm_entry(self.selected_index, false, cx);
+    }
+
+    fn secondary_confirm(&mut self, _: &menu::SecondaryConfirm, cx: &mut ViewContext<Self>) {
+        self.confirm_entry(self.selected_index, true, cx);
+    }
+
+    /// Opens the project, or runs the action, of an entry. The secondary confirmation
+    /// of a project pins or unpins it instead.
+    fn confirm_entry(&mut self, ix: usize, secondary: bool, cx: &mut ViewContext<Self>) {
+        match self.entries.get(ix) {
+            Some(DashboardEntry::Project { paths, .. }) if secondary => {
+                self.toggle_pinned(paths.clone(), cx);
+            }
+            Some(DashboardEntry::Project { paths, .. }) => {
+                let paths = paths.clone();
+                self.workspace
+                    .update(cx, |workspace, cx| {
+                        // Replace the window when it has no project, as when Zed starts.
+                        let replace_current_window = workspace
+                            .project()
+                            .read(cx)
+                            .visible_worktrees(cx)
+                            .next()
+                            .is_none();
+                        workspace
+                            .open_workspace_for_paths(replace_current_window, paths, cx)
+                            .detach_and_log_err(cx);
+                    })
+                    .ok();
+            }
+            Some(
+                DashboardEntry::QuickAction { action, .. } | DashboardEntry::Tip { action, .. },
+            ) => {
+                cx.dispatch_action(action.boxed_clone());
+            }
+            None => {}
+        }
+    }
+
+    fn toggle_pinned(&mut self, paths: Vec<PathBuf>, cx: &mut ViewContext<Self>) {
+        if let Some(ix) = self
+            .pinned_projects
+            .iter()
+            .position(|pinned| *pinned == paths)
+        {
+            self.pinned_projects.remove(ix);
+        } else {
+            self.pinned_projects.push(paths);
+        }
+        if let Some(json) = serde_json::to_string(&self.pinned_projects).log_err() {
+            db::write_and_log(cx, move || {
+                KEY_VALUE_STORE.write_kvp(PINNED_PROJECTS_KEY.to_string(), json)
+            });
+        }
+        self.update_entries(cx);
+    }
+
+    fn render_entry(
+        &self,
+        ix: usize,
+        entry: &DashboardEntry,
+        cx: &mut ViewContext<Self>,
+    ) -> ListItem {
+        let item = ListItem::new(ix)
+            .spacing(ListItemSpacing::Sparse)
+            .selected(ix == self.selected_index)
+            .on_click(cx.listener(move |this, _, cx| {
+                this.selected_index = ix;
+                this.confirm_entry(ix, false, cx);
+            }));
+
+        match entry {
+            DashboardEntry::Project { paths, pinned } => {
+                let pinned = *pinned;
+                let name = paths
+                    .iter()
+                    .filter_map(|path| Some(path.file_name()?.to_string_lossy()))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let location = paths
+                    .iter()
+                    .map(|path| path.compact().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                item.start_slot(Icon::new(IconName::Folder).color(Color::Muted))
+                    .child(
+                        h_flex().gap_2().child(Label::new(name)).child(
+                            Label::new(location)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                    )
+                    .end_hover_slot(
+                        IconButton::new(
+                            ("toggle-pinned", ix),
+                            if pinned {
+                                IconName::Unpin
+                            } else {
+                                IconName::Pin
+                            },
+                        )
+                        .icon_size(IconSize::Small)
+                        .tooltip(move |cx| {
+                            Tooltip::text(
+                                if pinned {
+                                    "Unpin Project"
+                                } else {
+                                    "Pin Project"
+                                },
+                                cx,
+                            )
+                        })
+                        .on_click(cx.listener(move |this, _, cx| {
+                            this.confirm_entry(ix, true, cx);
+                        })),
+                    )
+            }
+            DashboardEntry::QuickAction {
+                label,
+                icon,
+                action,
+            } => item
+                .start_slot(Icon::new(*icon).color(Color::Muted))
+                .child(Label::new(*label))
+                .end_slot(KeyBinding::for_action(action.as_ref(), cx)),
+            DashboardEntry::Tip { text, action } => item
+                .start_slot(Icon::new(IconName::Sparkle).color(Color::Muted))
+                .child(Label::new(*text))
+                .end_slot(KeyBinding::for_action(action.as_ref(), cx)),
+        }
+    }
+}
+
+fn quick_actions() -> [DashboardEntry; 4] {
+    [
+        DashboardEntry::QuickAction {
+            label: "Open a Project",
+            icon: IconName::FolderOpen,
+            action: Box::new(workspace::Open),
+        },
+        DashboardEntry::QuickAction {
+            label: "Clone a Repository",
+            icon: IconName::Github,
+            action: Box::new(recent_projects::CloneRepository),
+        },
+        DashboardEntry::QuickAction {
+            label: "Open Settings",
+            icon: IconName::Settings,
+            action: Box::new(zed_actions::OpenSettings),
+        },
+        DashboardEntry::QuickAction {
+            label: "Open Keymap",
+            icon: IconName::Command,
+            action: Box::new(zed_actions::OpenKeymap),
+        },
+    ]
+}
+
+impl Render for Dashboard {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let mut children = Vec::new();
+        let mut current_section = None;
+        for (ix, entry) in self.entries.iter().enumerate() {
+            let section = entry.section();
+            if current_section != Some(section) {
+                current_section = Some(section);
+                children.push(
+                    div()
+                        .pt_4()
+                        .pb_1()
+                        .px_2()
+                        .child(
+                            Label::new(section)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .into_any_element(),
+                );
+            }
+            children.push(self.render_entry(ix, entry, cx).into_any_element());
+        }
+
+        h_flex()
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .key_context("Dashboard")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::secondary_confirm))
+            .child(
+                v_flex()
+                    .id("dashboard")
+                    .w(rems(36.))
+                    .h_full()
+                    .mx_auto()
+                    .py_8()
+                    .overflow_y_scroll()
+                    .child(
+                        svg()
+                            .path("icons/logo_96.svg")
+                            .text_color(cx.theme().colors().text_muted)
+                            .w(px(64.))
+                            .h(px(64.))
+                            .mx_auto(),
+                    )
+                    .children(children),
+            )
+    }
+}
+
+impl EventEmitter<ItemEvent> for Dashboard {}
+
+impl FocusableView for Dashboard {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for Dashboard {
+    type Event = ItemEvent;
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some("Dashboard".into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("dashboard")
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(workspace::item::ItemEvent)) {
+        f(*event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::Project;
+    use std::sync::Arc;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_dashboard_projects(cx: &mut TestAppContext) {
+        let (dashboard, cx) = open_dashboard(cx).await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<DashboardSettings>(cx, |settings| {
+                    settings.max_recent_projects = Some(2);
+                });
+            });
+        });
+        dashboard.update(cx, |dashboard, cx| {
+            dashboard.recent_projects = vec![
+                vec![PathBuf::from("/a")],
+                vec![PathBuf::from("/b")],
+                vec![PathBuf::from("/c")],
+            ];
+            dashboard.pinned_projects = vec![vec![PathBuf::from("/c")]];
+            dashboard.update_entries(cx);
+        });
+        // Pinned projects come first, and aren't listed again, or counted, as recent ones.
+        assert_eq!(
+            project_entries(&dashboard, cx),
+            [("/c", true), ("/a", false), ("/b", false)]
+        );
+
+        // Pinning a recent project moves it to the pinned ones.
+        cx.dispatch_action(menu::SelectNext);
+        cx.dispatch_action(menu::SecondaryConfirm);
+        assert_eq!(
+            project_entries(&dashboard, cx),
+            [("/c", true), ("/a", true), ("/b", false)]
+        );
+        cx.dispatch_action(menu::SelectFirst);
+        cx.dispatch_action(menu::SecondaryConfirm);
+        assert_eq!(
+            project_entries(&dashboard, cx),
+            [("/a", true), ("/b", false), ("/c", false)]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_dashboard_keyboard_navigation(cx: &mut TestAppContext) {
+        let (dashboard, cx) = open_dashboard(cx).await;
+        dashboard.update(cx, |dashboard, cx| {
+            dashboard.recent_projects = vec![vec![PathBuf::from("/a")]];
+            dashboard.pinned_projects = Vec::new();
+            dashboard.update_entries(cx);
+        });
+        let entry_count = dashboard.update(cx, |dashboard, _| dashboard.entries.len());
+        let selected_index = |cx: &mut VisualTestContext| {
+            dashboard.update(cx, |dashboard, _| dashboard.selected_index)
+        };
+
+        assert_eq!(selected_index(cx), 0);
+        cx.dispatch_action(menu::SelectPrev);
+        assert_eq!(selected_index(cx), 0);
+        cx.dispatch_action(menu::SelectNext);
+        cx.dispatch_action(menu::SelectNext);
+        assert_eq!(selected_index(cx), 2);
+        cx.dispatch_action(menu::SelectLast);
+        assert_eq!(selected_index(cx), entry_count - 1);
+        cx.dispatch_action(menu::SelectNext);
+        assert_eq!(selected_index(cx), entry_count - 1);
+        cx.dispatch_action(menu::SelectPrev);
+        assert_eq!(selected_index(cx), entry_count - 2);
+        cx.dispatch_action(menu::SelectFirst);
+        assert_eq!(selected_index(cx), 0);
+
+        // Listing fewer entries keeps the selection within the remaining ones.
+        cx.dispatch_action(menu::SelectLast);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<DashboardSettings>(cx, |settings| {
+                    settings.max_recent_projects = Some(0);
+                });
+            });
+        });
+        let entry_count = dashboard.update(cx, |dashboard, _| dashboard.entries.len());
+        assert_eq!(selected_index(cx), entry_count - 1);
+    }
+
+    #[gpui::test]
+    async fn test_dashboard_disabled(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<DashboardSettings>(cx, |settings| {
+                    settings.enabled = Some(false);
+                });
+            });
+        });
+        let shown = workspace.update(cx, |workspace, cx| {
+            Dashboard::show_if_enabled(workspace, cx)
+        });
+        assert!(!shown);
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_item(cx).is_none())
+        });
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<DashboardSettings>(cx, |settings| {
+                    settings.enabled = Some(true);
+                });
+            });
+        });
+        let shown = workspace.update(cx, |workspace, cx| {
+            Dashboard::show_if_enabled(workspace, cx)
+        });
+        assert!(shown);
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_item_as::<Dashboard>(cx).is_some());
+        });
+    }
+
+    fn project_entries(
+        dashboard: &View<Dashboard>,
+        cx: &mut VisualTestContext,
+    ) -> Vec<(&'static str, bool)> {
+        dashboard.update(cx, |dashboard, _| {
+            dashboard
+                .entries
+                .iter()
+                .filter_map(|entry| match entry {
+                    DashboardEntry::Project { paths, pinned } => {
+                        let path = ["/a", "/b", "/c"]
+                            .into_iter()
+                            .find(|path| paths == &[PathBuf::from(path)])?;
+                        Some((path, *pinned))
+                    }
+                    _ => None,
+                })
+                .collect()
+        })
+    }
+
+    /// Opens a dashboard in a new workspace, once it has loaded the projects stored in
+    /// the database.
+    async fn open_dashboard(cx: &mut TestAppContext) -> (View<Dashboard>, &mut VisualTestContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let dashboard = workspace.update(cx, |workspace, cx| {
+            Dashboard::show(workspace, cx);
+            workspace.active_item_as::<Dashboard>(cx).unwrap()
+        });
+        cx.run_until_parked();
+        (dashboard, cx)
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            crate::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            state
+        })
+    }
+}
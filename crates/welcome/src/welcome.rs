@@ -1,5 +1,6 @@
 mod base_keymap_picker;
 mod base_keymap_setting;
+mod dashboard;
 mod multibuffer_hint;
 
 use client::{telemetry::Telemetry, TelemetrySettings};
@@ -20,14 +21,16 @@ use workspace::{
 };
 
 pub use base_keymap_setting::BaseKeymap;
+pub use dashboard::{Dashboard, DashboardSettings};
 pub use multibuffer_hint::*;
 
-actions!(welcome, [ResetHints]);
+actions!(welcome, [ResetHints, ShowDashboard]);
 
 pub const FIRST_OPEN: &str = "first_open";
 
 pub fn init(cx: &mut AppContext) {
     BaseKeymap::register(cx);
+    DashboardSettings::register(cx);
 
     cx.observe_new_views(|workspace: &mut Workspace, _cx| {
         workspace.register_action(|workspace, _: &Welcome, cx| {
@@ -36,6 +39,9 @@ pub fn init(cx: &mut AppContext) {
         });
         workspace
             .register_action(|_workspace, _: &ResetHints, cx| MultibufferHint::set_count(0, cx));
+        workspace.register_action(|workspace, _: &ShowDashboard, cx| {
+            Dashboard::show(workspace, cx);
+        });
     })
     .detach();
 
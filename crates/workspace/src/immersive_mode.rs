@@ -0,0 +1,133 @@
+use crate::Workspace;
+use anyhow::Result;
+use gpui::{AppContext, FocusableView as _, Keystroke, KeystrokeEvent, MouseMoveEvent, Task};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use std::time::Duration;
+use ui::prelude::*;
+
+/// How close to the edges of the workspace the mouse reveals what is hidden while typing.
+const REVEAL_EDGE_DISTANCE: Pixels = px(16.);
+
+#[derive(Clone, Deserialize)]
+pub struct ImmersiveModeSettings {
+    pub enabled: bool,
+    pub idle_timeout_ms: u64,
+    pub hide_docks: bool,
+    pub hide_status_bar: bool,
+    pub hide_tab_bar: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ImmersiveModeSettingsContent {
+    /// Whether to hide the side docks, the status bar and the tab bar while typing.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// How long after the last key press typing is considered to have stopped, in
+    /// milliseconds.
+    ///
+    /// Default: 1500
+    pub idle_timeout_ms: Option<u64>,
+    /// Whether to hide the left and right docks, such as the project panel, while typing.
+    ///
+    /// Default: true
+    pub hide_docks: Option<bool>,
+    /// Whether to hide the status bar while typing.
+    ///
+    /// Default: true
+    pub hide_status_bar: Option<bool>,
+    /// Whether to hide the tab bar of the center panes while typing.
+    ///
+    /// Default: true
+    pub hide_tab_bar: Option<bool>,
+}
+
+impl Settings for ImmersiveModeSettings {
+    const KEY: Option<&'static str> = Some("immersive_mode");
+
+    type FileContent = ImmersiveModeSettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+/// Whether the user is typing in the center panes of a workspace, which lasts until
+/// no key is pressed for a while, a key is pressed with a command modifier or outside
+/// of the center panes, or the mouse approaches the edges of the workspace.
+#[derive(Default)]
+pub(crate) struct TypingState {
+    typing: bool,
+    idle_task: Option<Task<()>>,
+}
+
+/// Whether a keystroke types or moves around, rather than running a command.
+fn is_typing_keystroke(keystroke: &Keystroke) -> bool {
+    let modifiers = &keystroke.modifiers;
+    !(modifiers.control || modifiers.alt || modifiers.platform || modifiers.function)
+        && keystroke.key != "escape"
+}
+
+impl Workspace {
+    /// Whether the user is typing in the center panes of the workspace.
+    pub fn is_typing(&self) -> bool {
+        self.typing_state.typing
+    }
+
+    /// Returns the settings of immersive mode when it hides parts of the workspace, as
+    /// the user is typing.
+    pub(crate) fn immersive_mode<'a>(
+        &self,
+        cx: &'a AppContext,
+    ) -> Option<&'a ImmersiveModeSettings> {
+        let settings = ImmersiveModeSettings::get_global(cx);
+        (settings.enabled && self.is_typing()).then_some(settings)
+    }
+
+    pub(crate) fn observe_typing(&mut self, event: &KeystrokeEvent, cx: &mut ViewContext<Self>) {
+        let in_center = self
+            .center
+            .panes()
+            .into_iter()
+            .any(|pane| pane.focus_handle(cx).contains_focused(cx));
+        if !in_center || !is_typing_keystroke(&event.keystroke) {
+            self.stop_typing(cx);
+            return;
+        }
+
+        let idle_timeout =
+            Duration::from_millis(ImmersiveModeSettings::get_global(cx).idle_timeout_ms);
+        self.typing_state.idle_task = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(idle_timeout).await;
+            this.update(&mut cx, |this, cx| this.stop_typing(cx)).ok();
+        }));
+        if !self.typing_state.typing {
+            self.typing_state.typing = true;
+            cx.notify();
+        }
+    }
+
+    /// Stops typing when the mouse approaches the edges of the workspace, revealing
+    /// what immersive mode hides.
+    pub(crate) fn reveal_near_edges(&mut self, event: &MouseMoveEvent, cx: &mut ViewContext<Self>) {
+        let bounds = self.bounds;
+        let position = event.position;
+        let near_edges = position.x - bounds.left() < REVEAL_EDGE_DISTANCE
+            || bounds.right() - position.x < REVEAL_EDGE_DISTANCE
+            || position.y - bounds.top() < REVEAL_EDGE_DISTANCE
+            || bounds.bottom() - position.y < REVEAL_EDGE_DISTANCE;
+        if near_edges {
+            self.stop_typing(cx);
+        }
+    }
+
+    fn stop_typing(&mut self, cx: &mut ViewContext<Self>) {
+        self.typing_state.idle_task = None;
+        if self.typing_state.typing {
+            self.typing_state.typing = false;
+            cx.notify();
+        }
+    }
+}
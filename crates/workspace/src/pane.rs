@@ -584,6 +584,23 @@ impl Pane {
         &self.activation_history
     }
 
+    /// Whether the tab bar is hidden by immersive mode, which only hides the tab bar of
+    /// the center panes.
+    fn is_tab_bar_hidden_while_typing(&self, cx: &ViewContext<Self>) -> bool {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return false;
+        };
+        let workspace = workspace.read(cx);
+        workspace
+            .immersive_mode(cx)
+            .map_or(false, |settings| settings.hide_tab_bar)
+            && workspace
+                .center
+                .panes()
+                .into_iter()
+                .any(|pane| pane.entity_id() == cx.entity_id())
+    }
+
     pub fn set_should_display_tab_bar<F>(&mut self, should_display_tab_bar: F)
     where
         F: 'static + Fn(&ViewContext<Pane>) -> bool,
@@ -2322,7 +2339,8 @@ impl Render for Pane {
         }
 
         let should_display_tab_bar = self.should_display_tab_bar.clone();
-        let display_tab_bar = should_display_tab_bar(cx);
+        let display_tab_bar =
+            should_display_tab_bar(cx) && !self.is_tab_bar_hidden_while_typing(cx);
 
         v_flex()
             .key_context(key_context)
@@ -1,5 +1,5 @@
+pub use crate::pane_group::element::{pane_axis, PaneAxisElement};
 use crate::{
-    pane_group::element::pane_axis,
     workspace_settings::{PaneSplitDirectionHorizontal, PaneSplitDirectionVertical},
     AppState, FollowerState, Pane, Workspace, WorkspaceSettings,
 };
@@ -17,6 +17,7 @@ use serde::Deserialize;
 use settings::Settings;
 use std::sync::Arc;
 use ui::prelude::*;
+use util::ResultExt as _;
 
 pub const HANDLE_HITBOX_SIZE: f32 = 4.0;
 const HORIZONTAL_MIN_SIZE: f32 = 80.;
@@ -513,12 +514,17 @@ impl PaneAxis {
         debug_assert!(self.members.len() == self.flexes.lock().len());
         let mut active_pane_ix = None;
 
+        let workspace = cx.view().downgrade();
         pane_axis(
             self.axis,
             basis,
             self.flexes.clone(),
             self.bounding_boxes.clone(),
-            cx.view().downgrade(),
+            move |cx| {
+                workspace
+                    .update(cx, |workspace, cx| workspace.serialize_workspace(cx))
+                    .log_err();
+            },
         )
         .children(self.members.iter().enumerate().map(|(ix, member)| {
             if member.contains(active_pane) {
@@ -633,16 +639,13 @@ mod element {
     use gpui::{
         px, relative, Along, AnyElement, Axis, Bounds, Element, GlobalElementId, IntoElement,
         MouseDownEvent, MouseMoveEvent, MouseUpEvent, ParentElement, Pixels, Point, Size, Style,
-        WeakView, WindowContext,
+        WindowContext,
     };
     use gpui::{CursorStyle, Hitbox};
     use parking_lot::Mutex;
     use settings::Settings;
     use smallvec::SmallVec;
     use ui::prelude::*;
-    use util::ResultExt;
-
-    use crate::Workspace;
 
     use crate::WorkspaceSettings;
 
@@ -650,12 +653,15 @@ mod element {
 
     const DIVIDER_SIZE: f32 = 1.0;
 
-    pub(super) fn pane_axis(
+    /// Lays out its children along the axis, sized by their flexes, with a divider
+    /// between them that can be dragged to resize them. `on_resize` is called once
+    /// the flexes were changed by dragging a divider.
+    pub fn pane_axis(
         axis: Axis,
         basis: usize,
         flexes: Arc<Mutex<Vec<f32>>>,
         bounding_boxes: Arc<Mutex<Vec<Option<Bounds<Pixels>>>>>,
-        workspace: WeakView<Workspace>,
+        on_resize: impl Fn(&mut WindowContext) + 'static,
     ) -> PaneAxisElement {
         PaneAxisElement {
             axis,
@@ -664,7 +670,7 @@ mod element {
             bounding_boxes,
             children: SmallVec::new(),
             active_pane_ix: None,
-            workspace,
+            on_resize: Rc::new(on_resize),
         }
     }
 
@@ -675,7 +681,7 @@ mod element {
         bounding_boxes: Arc<Mutex<Vec<Option<Bounds<Pixels>>>>>,
         children: SmallVec<[AnyElement; 2]>,
         active_pane_ix: Option<usize>,
-        workspace: WeakView<Workspace>,
+        on_resize: Rc<dyn Fn(&mut WindowContext)>,
     }
 
     pub struct PaneAxisLayout {
@@ -708,7 +714,7 @@ mod element {
             axis: Axis,
             child_start: Point<Pixels>,
             container_size: Size<Pixels>,
-            workspace: WeakView<Workspace>,
+            on_resize: &dyn Fn(&mut WindowContext),
             cx: &mut WindowContext,
         ) {
             let min_size = match axis {
@@ -782,9 +788,8 @@ mod element {
                 proposed_current_pixel_change -= current_pixel_change;
             }
 
-            workspace
-                .update(cx, |this, cx| this.serialize_workspace(cx))
-                .log_err();
+            drop(flexes);
+            on_resize(cx);
             cx.stop_propagation();
             cx.refresh();
         }
@@ -960,7 +965,7 @@ mod element {
                     cx.on_mouse_event({
                         let dragged_handle = layout.dragged_handle.clone();
                         let flexes = self.flexes.clone();
-                        let workspace = self.workspace.clone();
+                        let on_resize = self.on_resize.clone();
                         let handle_hitbox = handle.hitbox.clone();
                         move |e: &MouseDownEvent, phase, cx| {
                             if phase.bubble() && handle_hitbox.is_hovered(cx) {
@@ -968,9 +973,8 @@ mod element {
                                 if e.click_count >= 2 {
                                     let mut borrow = flexes.lock();
                                     *borrow = vec![1.; borrow.len()];
-                                    workspace
-                                        .update(cx, |this, cx| this.serialize_workspace(cx))
-                                        .log_err();
+                                    drop(borrow);
+                                    on_resize(cx);
 
                                     cx.refresh();
                                 }
@@ -979,7 +983,7 @@ mod element {
                         }
                     });
                     cx.on_mouse_event({
-                        let workspace = self.workspace.clone();
+                        let on_resize = self.on_resize.clone();
                         let dragged_handle = layout.dragged_handle.clone();
                         let flexes = self.flexes.clone();
                         let child_bounds = child.bounds;
@@ -995,7 +999,7 @@ mod element {
                                         axis,
                                         child_bounds.origin,
                                         bounds.size,
-                                        workspace.clone(),
+                                        on_resize.as_ref(),
                                         cx,
                                     )
                                 }
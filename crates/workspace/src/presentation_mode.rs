@@ -0,0 +1,212 @@
+use crate::{dock::Dock, Workspace};
+use anyhow::Result;
+use collections::VecDeque;
+use gpui::{AppContext, Global, Keystroke, KeystrokeEvent, Subscription, Task, View};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use std::time::Duration;
+use theme::FontSizeAdjustments;
+use ui::prelude::*;
+
+/// How many of the last key presses are displayed.
+const MAX_DISPLAYED_KEY_PRESSES: usize = 8;
+/// How long the key presses stay displayed after the last one.
+const KEY_PRESSES_DISPLAY_DURATION: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Deserialize)]
+pub struct PresentationModeSettings {
+    pub font_scale: f32,
+    pub hide_docks: bool,
+    pub hide_status_bar: bool,
+    pub high_contrast_cursor: bool,
+    pub show_key_presses: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PresentationModeSettingsContent {
+    /// The factor by which the UI and buffer font sizes are scaled in presentation mode.
+    ///
+    /// Default: 1.5
+    pub font_scale: Option<f32>,
+    /// Whether to close the docks in presentation mode, reopening them on exit.
+    ///
+    /// Default: true
+    pub hide_docks: Option<bool>,
+    /// Whether to hide the status bar in presentation mode.
+    ///
+    /// Default: true
+    pub hide_status_bar: Option<bool>,
+    /// Whether to draw a wider cursor in the color of the text in presentation mode.
+    ///
+    /// Default: true
+    pub high_contrast_cursor: Option<bool>,
+    /// Whether to display the keys pressed in presentation mode, such as for screencasts.
+    ///
+    /// Default: false
+    pub show_key_presses: Option<bool>,
+}
+
+impl Settings for PresentationModeSettings {
+    const KEY: Option<&'static str> = Some("presentation_mode");
+
+    type FileContent = PresentationModeSettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+/// The number of workspaces in presentation mode with a high-contrast cursor.
+#[derive(Default)]
+struct HighContrastCursor(usize);
+
+impl Global for HighContrastCursor {}
+
+/// Whether editors draw a high-contrast cursor, as a workspace is in presentation mode.
+pub fn high_contrast_cursor(cx: &AppContext) -> bool {
+    cx.has_global::<HighContrastCursor>()
+}
+
+/// The font sizes are shared by all windows, so they're scaled when the first workspace
+/// enters presentation mode, and restored once the last one exits.
+struct ScaledFontSizes {
+    workspaces: usize,
+    adjustments: FontSizeAdjustments,
+}
+
+impl Global for ScaledFontSizes {}
+
+/// What presentation mode changed in a workspace, to restore it on exit.
+pub(crate) struct PresentationMode {
+    closed_docks: Vec<View<Dock>>,
+    high_contrast_cursor: bool,
+    pub(crate) hide_status_bar: bool,
+    pub(crate) key_presses: Option<View<KeyPresses>>,
+}
+
+impl PresentationMode {
+    pub(crate) fn enter(workspace: &Workspace, cx: &mut ViewContext<Workspace>) -> Self {
+        let settings = PresentationModeSettings::get_global(cx).clone();
+        if cx.has_global::<ScaledFontSizes>() {
+            cx.global_mut::<ScaledFontSizes>().workspaces += 1;
+        } else {
+            let adjustments = theme::scale_font_sizes(cx, settings.font_scale);
+            cx.set_global(ScaledFontSizes {
+                workspaces: 1,
+                adjustments,
+            });
+        }
+
+        let closed_docks = if settings.hide_docks {
+            [
+                &workspace.left_dock,
+                &workspace.bottom_dock,
+                &workspace.right_dock,
+            ]
+            .into_iter()
+            .filter(|dock| dock.read(cx).is_open())
+            .cloned()
+            .collect()
+        } else {
+            Vec::new()
+        };
+        for dock in &closed_docks {
+            dock.update(cx, |dock, cx| dock.set_open(false, cx));
+        }
+
+        if settings.high_contrast_cursor {
+            cx.default_global::<HighContrastCursor>().0 += 1;
+        }
+
+        Self {
+            closed_docks,
+            high_contrast_cursor: settings.high_contrast_cursor,
+            hide_status_bar: settings.hide_status_bar,
+            key_presses: settings
+                .show_key_presses
+                .then(|| cx.new_view(KeyPresses::new)),
+        }
+    }
+
+    pub(crate) fn exit(self, cx: &mut ViewContext<Workspace>) {
+        if cx.has_global::<ScaledFontSizes>() {
+            let workspaces = &mut cx.global_mut::<ScaledFontSizes>().workspaces;
+            *workspaces -= 1;
+            if *workspaces == 0 {
+                let scaled_font_sizes = cx.remove_global::<ScaledFontSizes>();
+                theme::restore_font_sizes(cx, scaled_font_sizes.adjustments);
+            }
+        }
+        for dock in self.closed_docks {
+            dock.update(cx, |dock, cx| dock.set_open(true, cx));
+        }
+        if self.high_contrast_cursor {
+            let workspaces = &mut cx.default_global::<HighContrastCursor>().0;
+            *workspaces = workspaces.saturating_sub(1);
+            if *workspaces == 0 {
+                cx.remove_global::<HighContrastCursor>();
+            }
+            cx.refresh();
+        }
+    }
+}
+
+/// Displays the last keys pressed, until no key is pressed for a while.
+pub(crate) struct KeyPresses {
+    keystrokes: VecDeque<Keystroke>,
+    _clear_task: Option<Task<()>>,
+    _subscription: Subscription,
+}
+
+impl KeyPresses {
+    fn new(cx: &mut ViewContext<Self>) -> Self {
+        let listener = cx.listener(Self::observe_keystroke);
+        Self {
+            keystrokes: VecDeque::new(),
+            _clear_task: None,
+            _subscription: cx.observe_keystrokes(listener),
+        }
+    }
+
+    fn observe_keystroke(&mut self, event: &KeystrokeEvent, cx: &mut ViewContext<Self>) {
+        if self.keystrokes.len() == MAX_DISPLAYED_KEY_PRESSES {
+            self.keystrokes.pop_front();
+        }
+        self.keystrokes.push_back(event.keystroke.clone());
+        self._clear_task = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor()
+                .timer(KEY_PRESSES_DISPLAY_DURATION)
+                .await;
+            this.update(&mut cx, |this, cx| {
+                this.keystrokes.clear();
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+}
+
+impl Render for KeyPresses {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        if self.keystrokes.is_empty() {
+            return div();
+        }
+
+        div()
+            .absolute()
+            .bottom_8()
+            .left_0()
+            .right_0()
+            .flex()
+            .justify_center()
+            .child(
+                h_flex().elevation_3(cx).px_3().py_2().gap_3().children(
+                    self.keystrokes
+                        .iter()
+                        .map(|keystroke| Label::new(keystroke.to_string()).size(LabelSize::Large)),
+                ),
+            )
+    }
+}
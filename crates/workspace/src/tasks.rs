@@ -1,8 +1,21 @@
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use gpui::{Global, Task};
 use project::TaskSourceKind;
-use task::{ResolvedTask, TaskContext, TaskTemplate};
+use task::{ResolvedTask, TaskContext, TaskGraph, TaskInput, TaskTemplate, TaskVariables};
 use ui::ViewContext;
 
-use crate::Workspace;
+use crate::{notifications::NotifyTaskExt as _, Workspace};
+
+/// Asks the user for the values of the inputs of a task, before spawning it.
+/// Resolves to `None` if the user dismissed the prompt.
+#[derive(Clone)]
+pub struct TaskInputsPrompt(
+    pub Arc<dyn Fn(Vec<TaskInput>, &mut ViewContext<Workspace>) -> Task<Option<TaskVariables>>>,
+);
+
+impl Global for TaskInputsPrompt {}
 
 pub fn schedule_task(
     workspace: &Workspace,
@@ -32,15 +45,130 @@ pub fn schedule_resolved_task(
     omit_history: bool,
     cx: &mut ViewContext<'_, Workspace>,
 ) {
+    if !resolved_task.missing_inputs().is_empty() {
+        schedule_task_with_inputs(task_source_kind, resolved_task, omit_history, cx);
+        return;
+    }
     if let Some(spawn_in_terminal) = resolved_task.resolved.take() {
         if !omit_history {
             resolved_task.resolved = Some(spawn_in_terminal.clone());
             workspace.project().update(cx, |project, cx| {
                 project.task_inventory().update(cx, |inventory, _| {
-                    inventory.task_scheduled(task_source_kind, resolved_task);
+                    inventory.task_scheduled(task_source_kind.clone(), resolved_task.clone());
                 })
             });
         }
-        cx.emit(crate::Event::SpawnTask(Box::new(spawn_in_terminal)));
+        if resolved_task.original_task().depends_on.is_empty() {
+            cx.emit(crate::Event::SpawnTask(Box::new(spawn_in_terminal)));
+        } else {
+            schedule_task_with_dependencies(workspace, task_source_kind, resolved_task, cx);
+        }
+    }
+}
+
+/// Starts the command of the task in the debugger, rather than in a terminal.
+pub fn debug_resolved_task(resolved_task: ResolvedTask, cx: &mut ViewContext<'_, Workspace>) {
+    if !resolved_task.missing_inputs().is_empty() {
+        log::error!(
+            "cannot debug task `{}` asking for inputs",
+            resolved_task.resolved_label
+        );
+        return;
     }
+    if let Some(spawn_in_terminal) = resolved_task.resolved {
+        cx.emit(crate::Event::DebugTask(Box::new(spawn_in_terminal)));
+    }
+}
+
+/// Asks for the inputs of the task, then schedules it resolved with their values.
+fn schedule_task_with_inputs(
+    task_source_kind: TaskSourceKind,
+    resolved_task: ResolvedTask,
+    omit_history: bool,
+    cx: &mut ViewContext<'_, Workspace>,
+) {
+    let Some(prompt) = cx.try_global::<TaskInputsPrompt>().cloned() else {
+        log::error!(
+            "cannot ask for the inputs of task `{}`",
+            resolved_task.resolved_label
+        );
+        return;
+    };
+    let values = (prompt.0)(resolved_task.missing_inputs().to_vec(), cx);
+    cx.spawn(|workspace, mut cx| async move {
+        let Some(values) = values.await else {
+            return Ok(());
+        };
+        let mut task_context = resolved_task.task_context().clone();
+        task_context.task_variables.extend(values);
+        let template = resolved_task.original_task();
+        let resolved_task = template
+            .resolve_task(&task_source_kind.to_id_base(), &task_context)
+            .with_context(|| format!("failed to resolve task `{}`", template.label))?;
+        workspace.update(&mut cx, |workspace, cx| {
+            schedule_resolved_task(workspace, task_source_kind, resolved_task, omit_history, cx)
+        })
+    })
+    .detach_and_notify_err(cx);
+}
+
+/// Runs the dependencies of the task before it, waiting for each task to finish,
+/// or for background tasks, to be ready.
+fn schedule_task_with_dependencies(
+    workspace: &Workspace,
+    task_source_kind: TaskSourceKind,
+    resolved_task: ResolvedTask,
+    cx: &mut ViewContext<'_, Workspace>,
+) {
+    let project = workspace.project().clone();
+    let templates = project.update(cx, |project, cx| {
+        project.task_templates(task_source_kind.worktree(), None, cx)
+    });
+    let task_context = resolved_task.task_context().clone();
+    let root = (task_source_kind, resolved_task.original_task().clone());
+    cx.spawn(|workspace, cx| async move {
+        let templates = templates.await?;
+        let graph = TaskGraph::new(root, &templates)?;
+        graph
+            .run(|task_source_kind, template| {
+                let workspace = workspace.clone();
+                let project = project.clone();
+                let mut cx = cx.clone();
+                let resolved = template
+                    .resolve_task(&task_source_kind.to_id_base(), &task_context)
+                    // Dependencies are only given the inputs entered for the task depending on them.
+                    .filter(|task| task.missing_inputs().is_empty())
+                    .and_then(|task| task.resolved)
+                    .with_context(|| format!("failed to resolve task `{}`", template.label));
+                let is_background = template.is_background;
+                let ready_pattern = template.ready_pattern.clone();
+                async move {
+                    let spawn_in_terminal = resolved?;
+                    let task_id = spawn_in_terminal.id.clone();
+                    // Background tasks started earlier are reused while they're running.
+                    let is_running = project.update(&mut cx, |project, cx| {
+                        is_background && project.is_task_running(&task_id, cx)
+                    })?;
+                    if is_running {
+                        return Ok(());
+                    }
+
+                    let task_done = project.update(&mut cx, |project, cx| {
+                        project.wait_for_task(
+                            task_id,
+                            spawn_in_terminal.full_label.clone(),
+                            is_background,
+                            ready_pattern,
+                            cx,
+                        )
+                    })?;
+                    workspace.update(&mut cx, |_, cx| {
+                        cx.emit(crate::Event::SpawnTask(Box::new(spawn_in_terminal)));
+                    })?;
+                    task_done.await
+                }
+            })
+            .await
+    })
+    .detach_and_notify_err(cx);
 }
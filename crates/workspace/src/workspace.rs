@@ -1,10 +1,12 @@
 pub mod dock;
+pub mod immersive_mode;
 pub mod item;
 mod modal_layer;
 pub mod notifications;
 pub mod pane;
 pub mod pane_group;
 mod persistence;
+pub mod presentation_mode;
 pub mod searchable;
 pub mod shared_screen;
 mod status_bar;
@@ -38,6 +40,7 @@ use gpui::{
     ResizeEdge, Size, Stateful, Subscription, Task, Tiling, View, WeakView, WindowBounds,
     WindowHandle, WindowId, WindowOptions,
 };
+use immersive_mode::{ImmersiveModeSettings, TypingState};
 pub use item::{
     FollowableItem, FollowableItemHandle, Item, ItemHandle, ItemSettings, PreviewTabsSettings,
     ProjectItem, SerializableItem, SerializableItemHandle, WeakItemHandle,
@@ -55,6 +58,7 @@ pub use persistence::{
     WorkspaceDb, DB as WORKSPACE_DB,
 };
 use postage::stream::Stream;
+use presentation_mode::{PresentationMode, PresentationModeSettings};
 use project::{
     DirectoryLister, Project, ProjectEntryId, ProjectPath, ResolvedPath, Worktree, WorktreeId,
 };
@@ -138,6 +142,7 @@ actions!(
         NewFile,
         NewFileSplitVertical,
         NewFileSplitHorizontal,
+        NewScratchBuffer,
         NewSearch,
         NewTerminal,
         NewWindow,
@@ -149,6 +154,7 @@ actions!(
         ToggleBottomDock,
         ToggleCenteredLayout,
         ToggleLeftDock,
+        TogglePresentationMode,
         ToggleRightDock,
         ToggleZoom,
         Unfollow,
@@ -311,6 +317,8 @@ pub fn init_settings(cx: &mut AppContext) {
     ItemSettings::register(cx);
     PreviewTabsSettings::register(cx);
     TabBarSettings::register(cx);
+    PresentationModeSettings::register(cx);
+    ImmersiveModeSettings::register(cx);
 }
 
 pub fn init(app_state: Arc<AppState>, cx: &mut AppContext) {
@@ -682,6 +690,7 @@ pub enum Event {
     ContactRequestedJoin(u64),
     WorkspaceCreated(WeakView<Workspace>),
     SpawnTask(Box<SpawnInTerminal>),
+    DebugTask(Box<SpawnInTerminal>),
     OpenBundledFile {
         text: Cow<'static, str>,
         title: &'static str,
@@ -750,6 +759,8 @@ pub struct Workspace {
     pane_history_timestamp: Arc<AtomicUsize>,
     bounds: Bounds<Pixels>,
     centered_layout: bool,
+    presentation_mode: Option<PresentationMode>,
+    typing_state: TypingState,
     bounds_save_task_queued: Option<Task<()>>,
     on_prompt_for_new_path: Option<PromptForNewPath>,
     on_prompt_for_open_path: Option<PromptForOpenPath>,
@@ -837,6 +848,42 @@ impl Workspace {
                     )
                 }
 
+                project::Event::EditAccessRequested { user_id } => {
+                    struct EditAccessRequest;
+
+                    let user_id = *user_id;
+                    let login = this
+                        .app_state
+                        .user_store
+                        .read(cx)
+                        .get_cached_user(user_id)
+                        .map_or_else(
+                            || "A guest".to_string(),
+                            |user| format!("@{}", user.github_login),
+                        );
+                    let project = this.project.downgrade();
+                    this.show_notification(
+                        NotificationId::identified::<EditAccessRequest>(user_id as usize),
+                        cx,
+                        |cx| {
+                            cx.new_view(|_| {
+                                MessageNotification::new(format!(
+                                    "{login} asked to edit the project"
+                                ))
+                                .with_click_message("Allow")
+                                .on_click(move |cx| {
+                                    project
+                                        .update(cx, |project, cx| {
+                                            project.set_guest_can_edit(user_id, true, cx)
+                                        })
+                                        .ok();
+                                })
+                                .with_secondary_click_message("Deny")
+                            })
+                        },
+                    )
+                }
+
                 project::Event::LanguageServerPrompt(request) => {
                     struct LanguageServerPrompt;
 
@@ -951,6 +998,7 @@ impl Workspace {
             Self::serialize_items(&this, serializable_items_rx, &mut cx).await
         });
 
+        let observe_typing = cx.listener(Self::observe_typing);
         let subscriptions = vec![
             cx.observe_window_activation(Self::on_window_activation_changed),
             cx.observe_window_bounds(move |this, cx| {
@@ -989,6 +1037,7 @@ impl Workspace {
 
                 ThemeSettings::reload_current_theme(cx);
             }),
+            cx.observe_keystrokes(observe_typing),
             cx.observe(&left_dock, |this, _, cx| {
                 this.serialize_workspace(cx);
                 cx.notify();
@@ -1047,6 +1096,8 @@ impl Workspace {
             // This data will be incorrect, but it will be overwritten by the time it needs to be used.
             bounds: Default::default(),
             centered_layout: false,
+            presentation_mode: None,
+            typing_state: TypingState::default(),
             bounds_save_task_queued: None,
             on_prompt_for_new_path: None,
             on_prompt_for_open_path: None,
@@ -4413,6 +4464,7 @@ impl Workspace {
                 }),
             )
             .on_action(cx.listener(Workspace::toggle_centered_layout))
+            .on_action(cx.listener(Workspace::toggle_presentation_mode))
     }
 
     #[cfg(any(test, feature = "test-support"))]
@@ -4489,6 +4541,24 @@ impl Workspace {
         cx.notify();
     }
 
+    /// Enters or exits presentation mode, which scales the fonts and hides the docks and
+    /// the status bar, restoring them on exit.
+    pub fn toggle_presentation_mode(
+        &mut self,
+        _: &TogglePresentationMode,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match self.presentation_mode.take() {
+            Some(presentation_mode) => presentation_mode.exit(cx),
+            None => self.presentation_mode = Some(PresentationMode::enter(self, cx)),
+        }
+        cx.notify();
+    }
+
+    pub fn is_in_presentation_mode(&self) -> bool {
+        self.presentation_mode.is_some()
+    }
+
     fn adjust_padding(padding: Option<f32>) -> f32 {
         padding
             .unwrap_or(Self::DEFAULT_PADDING)
@@ -4504,6 +4574,13 @@ impl Workspace {
         if self.zoomed_position == Some(position) {
             return None;
         }
+        if position != DockPosition::Bottom
+            && self
+                .immersive_mode(cx)
+                .map_or(false, |settings| settings.hide_docks)
+        {
+            return None;
+        }
 
         let leader_border = dock.read(cx).active_panel().and_then(|panel| {
             let pane = panel.pane(cx)?;
@@ -4719,6 +4796,13 @@ impl Render for Workspace {
             (None, None)
         };
         let ui_font = theme::setup_ui_font(cx);
+        let hide_status_bar = self
+            .presentation_mode
+            .as_ref()
+            .map_or(false, |mode| mode.hide_status_bar)
+            || self
+                .immersive_mode(cx)
+                .map_or(false, |settings| settings.hide_status_bar);
 
         let theme = cx.theme().clone();
         let colors = theme.colors();
@@ -4736,6 +4820,9 @@ impl Render for Workspace {
                 .items_start()
                 .text_color(colors.text)
                 .overflow_hidden()
+                .when(self.is_typing(), |this| {
+                    this.on_mouse_move(cx.listener(Self::reveal_near_edges))
+                })
                 .children(self.titlebar_item.clone())
                 .child(
                     div()
@@ -4849,9 +4936,14 @@ impl Render for Workspace {
                             })
                         }))
                         .child(self.modal_layer.clone())
-                        .children(self.render_notifications(cx)),
+                        .children(self.render_notifications(cx))
+                        .children(
+                            self.presentation_mode
+                                .as_ref()
+                                .and_then(|mode| mode.key_presses.clone()),
+                        ),
                 )
-                .child(self.status_bar.clone())
+                .when(!hide_status_bar, |this| this.child(self.status_bar.clone()))
                 .children(if self.project.read(cx).is_disconnected() {
                     if let Some(render) = self.render_disconnected_overlay.take() {
                         let result = render(self, cx);
@@ -6360,6 +6452,55 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_immersive_mode(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        let item = cx.new_view(|cx| TestItem::new(cx));
+        workspace.update(cx, |workspace, cx| {
+            workspace.add_item_to_active_pane(Box::new(item.clone()), None, true, cx);
+        });
+        item.update(cx, |_, cx| {
+            cx.focus_self();
+            SettingsStore::update_global(cx, |settings, cx| {
+                settings.update_user_settings::<ImmersiveModeSettings>(cx, |settings| {
+                    settings.enabled = Some(true);
+                    settings.idle_timeout_ms = Some(500);
+                })
+            });
+        });
+
+        // Typing in the center pane hides the status bar and the side docks.
+        cx.simulate_keystrokes("a b");
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.is_typing());
+            assert!(workspace
+                .immersive_mode(cx)
+                .map_or(false, |settings| settings.hide_status_bar));
+        });
+
+        // They reappear once typing stops for a while.
+        cx.executor().advance_clock(Duration::from_millis(400));
+        cx.simulate_keystrokes("c");
+        cx.executor().advance_clock(Duration::from_millis(400));
+        workspace.update(cx, |workspace, _| assert!(workspace.is_typing()));
+        cx.executor().advance_clock(Duration::from_millis(200));
+        workspace.update(cx, |workspace, cx| {
+            assert!(!workspace.is_typing());
+            assert!(workspace.immersive_mode(cx).is_none());
+        });
+
+        // Or when a command is run.
+        cx.simulate_keystrokes("d");
+        workspace.update(cx, |workspace, _| assert!(workspace.is_typing()));
+        cx.simulate_keystrokes("ctrl-x");
+        workspace.update(cx, |workspace, _| assert!(!workspace.is_typing()));
+    }
+
     #[gpui::test]
     async fn test_toggle_docks_and_panels(cx: &mut gpui::TestAppContext) {
         init_test(cx);
@@ -7178,6 +7319,51 @@ mod tests {
         }
     }
 
+    #[gpui::test]
+    async fn test_presentation_mode_font_sizes(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |settings, cx| {
+                settings.update_user_settings::<PresentationModeSettings>(cx, |settings| {
+                    settings.font_scale = Some(2.);
+                })
+            });
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let window_1 = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let window_2 = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let toggle = |window: WindowHandle<Workspace>, cx: &mut TestAppContext| {
+            window
+                .update(cx, |workspace, cx| {
+                    workspace.toggle_presentation_mode(&TogglePresentationMode, cx)
+                })
+                .unwrap();
+        };
+        let buffer_font_size =
+            |cx: &mut TestAppContext| cx.update(|cx| theme::get_buffer_font_size(cx));
+        let font_size = buffer_font_size(cx);
+
+        toggle(window_1, cx);
+        assert_eq!(buffer_font_size(cx), font_size * 2.);
+
+        // The fonts are scaled once, and stay scaled until every workspace exits.
+        toggle(window_2, cx);
+        assert_eq!(buffer_font_size(cx), font_size * 2.);
+        toggle(window_1, cx);
+        assert_eq!(buffer_font_size(cx), font_size * 2.);
+        toggle(window_2, cx);
+        assert_eq!(buffer_font_size(cx), font_size);
+
+        // The size adjusted before entering is restored on exit.
+        cx.update(|cx| theme::adjust_buffer_font_size(cx, |size| *size += px(1.)));
+        toggle(window_2, cx);
+        assert_eq!(buffer_font_size(cx), (font_size + px(1.)) * 2.);
+        toggle(window_2, cx);
+        assert_eq!(buffer_font_size(cx), font_size + px(1.));
+    }
+
     pub fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let settings_store = SettingsStore::test(cx);
@@ -0,0 +1,68 @@
+use crate::{folder_names, restricted_folders, TrustWorkspace};
+use gpui::{Render, Subscription, ViewContext, WeakView};
+use project::worktree_trust::WorktreeTrust;
+use ui::{prelude::*, ButtonLike, Tooltip};
+use workspace::{item::ItemHandle, StatusItemView, Workspace};
+
+/// Shows that folders of the workspace are open in restricted mode, and trusts them
+/// when clicked.
+pub struct RestrictedModeIndicator {
+    workspace: WeakView<Workspace>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl RestrictedModeIndicator {
+    pub fn new(workspace: &Workspace, cx: &mut ViewContext<Self>) -> Self {
+        let mut subscriptions = vec![cx.observe(workspace.project(), |_, _, cx| cx.notify())];
+        if let Some(worktree_trust) = WorktreeTrust::global(cx) {
+            subscriptions.push(cx.observe(&worktree_trust, |_, _, cx| cx.notify()));
+        }
+        Self {
+            workspace: workspace.weak_handle(),
+            _subscriptions: subscriptions,
+        }
+    }
+}
+
+impl Render for RestrictedModeIndicator {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let restricted_folders = self
+            .workspace
+            .upgrade()
+            .map(|workspace| restricted_folders(workspace.read(cx), cx))
+            .unwrap_or_default();
+        if restricted_folders.is_empty() {
+            return div().into_any_element();
+        }
+
+        let folder_names = folder_names(&restricted_folders);
+        ButtonLike::new("restricted-mode-indicator")
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Icon::new(IconName::FileLock)
+                            .size(IconSize::Small)
+                            .color(Color::Warning),
+                    )
+                    .child(Label::new("Restricted Mode").size(LabelSize::Small)),
+            )
+            .tooltip(move |cx| {
+                Tooltip::with_meta(
+                    "Trust Folders",
+                    Some(&TrustWorkspace),
+                    format!(
+                        "Until they're trusted, {folder_names} won't run the tasks, \
+                        language servers and environment they configure"
+                    ),
+                    cx,
+                )
+            })
+            .on_click(|_, cx| cx.dispatch_action(Box::new(TrustWorkspace)))
+            .into_any_element()
+    }
+}
+
+impl StatusItemView for RestrictedModeIndicator {
+    fn set_active_pane_item(&mut self, _: Option<&dyn ItemHandle>, _: &mut ViewContext<Self>) {}
+}
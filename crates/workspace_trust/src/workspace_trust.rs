@@ -0,0 +1,193 @@
+//! Asks whether to trust the folders opened for the first time, and shows when the
+//! folders of a workspace are open in restricted mode.
+mod restricted_mode_indicator;
+
+use fs::Fs;
+use gpui::{actions, AppContext, Context as _, PromptLevel, ViewContext, VisualContext as _};
+use project::{
+    worktree_trust::{Trust, WorktreeTrust, WorktreeTrustEvent, WorktreeTrustSettings},
+    WorktreeId,
+};
+use std::{path::Path, sync::Arc};
+use ui::SharedString;
+use util::ResultExt as _;
+use workspace::{
+    notifications::{simple_message_notification::MessageNotification, NotificationId},
+    Workspace,
+};
+
+pub use restricted_mode_indicator::RestrictedModeIndicator;
+
+actions!(workspace_trust, [TrustWorkspace, RestrictWorkspace]);
+
+pub fn init(fs: Arc<dyn Fs>, cx: &mut AppContext) {
+    WorktreeTrust::init(fs, cx);
+    let Some(worktree_trust) = WorktreeTrust::global(cx) else {
+        return;
+    };
+
+    cx.observe_new_views(
+        move |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
+            workspace.register_action(|workspace, _: &TrustWorkspace, cx| {
+                trust_workspace(workspace, cx);
+            });
+            workspace.register_action(|workspace, _: &RestrictWorkspace, cx| {
+                set_workspace_trust(workspace, Trust::Restricted, cx);
+            });
+
+            cx.subscribe(&worktree_trust, |workspace, _, event, cx| {
+                if let WorktreeTrustEvent::TrustRequested(worktree_id) = event {
+                    request_trust(workspace, *worktree_id, cx);
+                }
+            })
+            .detach();
+            // The worktrees opened before the workspace was created ask for trust too.
+            let worktree_ids = worktree_ids(workspace, cx);
+            for worktree_id in worktree_ids {
+                if worktree_trust
+                    .read(cx)
+                    .worktree_trust(worktree_id)
+                    .is_none()
+                {
+                    request_trust(workspace, worktree_id, cx);
+                }
+            }
+        },
+    )
+    .detach();
+}
+
+struct TrustRequest;
+
+/// Asks whether to trust the folder of a worktree of the workspace, whose trust
+/// wasn't decided yet.
+fn request_trust(
+    workspace: &mut Workspace,
+    worktree_id: WorktreeId,
+    cx: &mut ViewContext<Workspace>,
+) {
+    if !WorktreeTrustSettings::get_global(cx).enabled {
+        return;
+    }
+    let Some(worktree) = workspace
+        .project()
+        .read(cx)
+        .worktree_for_id(worktree_id, cx)
+    else {
+        return;
+    };
+    let abs_path = worktree.read(cx).abs_path();
+    let message = format!(
+        "Do you trust the authors of the files in {}? Until you do, the folder is open in \
+        restricted mode, without running its tasks, the language servers its settings \
+        configure, or the environment its shell hooks set up.",
+        abs_path.display()
+    );
+    workspace.show_notification(
+        NotificationId::identified::<TrustRequest>(("trust-request", worktree_id.to_usize())),
+        cx,
+        |cx| {
+            cx.new_view(|_| {
+                let trusted_path = abs_path.clone();
+                MessageNotification::new(message)
+                    .with_click_message("Trust Folder")
+                    .on_click(move |cx| set_trust(&trusted_path, Trust::Trusted, cx))
+                    .with_secondary_click_message("Stay in Restricted Mode")
+                    .on_secondary_click(move |cx| set_trust(&abs_path, Trust::Restricted, cx))
+            })
+        },
+    );
+}
+
+/// Asks for confirmation, then trusts the folders of the workspace.
+pub fn trust_workspace(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let restricted_folders = restricted_folders(workspace, cx);
+    if restricted_folders.is_empty() {
+        return;
+    }
+    let folders = restricted_folders
+        .iter()
+        .map(|folder| folder.display().to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let answer = cx.prompt(
+        PromptLevel::Warning,
+        "Do you trust the authors of the files in these folders?",
+        Some(&format!(
+            "{folders}\n\nTrusting them runs their tasks, the language servers their \
+            settings configure, and the environment their shell hooks set up."
+        )),
+        &["Trust", "Cancel"],
+    );
+    cx.spawn(|workspace, mut cx| async move {
+        if answer.await == Ok(0) {
+            workspace
+                .update(&mut cx, |workspace, cx| {
+                    set_workspace_trust(workspace, Trust::Trusted, cx)
+                })
+                .log_err();
+        }
+    })
+    .detach();
+}
+
+fn set_workspace_trust(workspace: &mut Workspace, trust: Trust, cx: &mut ViewContext<Workspace>) {
+    let worktree_ids = worktree_ids(workspace, cx);
+    for worktree_id in worktree_ids {
+        workspace.dismiss_notification(
+            &NotificationId::identified::<TrustRequest>(("trust-request", worktree_id.to_usize())),
+            cx,
+        );
+        if let Some(worktree) = workspace
+            .project()
+            .read(cx)
+            .worktree_for_id(worktree_id, cx)
+        {
+            let abs_path = worktree.read(cx).abs_path();
+            set_trust(&abs_path, trust, cx);
+        }
+    }
+}
+
+fn set_trust(abs_path: &Path, trust: Trust, cx: &mut AppContext) {
+    if let Some(worktree_trust) = WorktreeTrust::global(cx) {
+        worktree_trust.update(cx, |worktree_trust, cx| {
+            worktree_trust.set_trust(abs_path, trust, cx)
+        });
+    }
+}
+
+/// The folders of the workspace's visible worktrees that are open in restricted mode.
+fn restricted_folders(workspace: &Workspace, cx: &AppContext) -> Vec<Arc<Path>> {
+    if !WorktreeTrustSettings::get_global(cx).enabled {
+        return Vec::new();
+    }
+    WorktreeTrust::global(cx).map_or_else(Vec::new, |worktree_trust| {
+        worktree_trust
+            .read(cx)
+            .restricted_folders(&worktree_ids(workspace, cx))
+    })
+}
+
+fn worktree_ids(workspace: &Workspace, cx: &AppContext) -> Vec<WorktreeId> {
+    workspace
+        .project()
+        .read(cx)
+        .visible_worktrees(cx)
+        .map(|worktree| worktree.read(cx).id())
+        .collect()
+}
+
+fn folder_names(folders: &[Arc<Path>]) -> SharedString {
+    folders
+        .iter()
+        .map(|folder| {
+            folder.file_name().map_or_else(
+                || folder.display().to_string(),
+                |name| name.to_string_lossy().into(),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+        .into()
+}
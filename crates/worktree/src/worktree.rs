@@ -7,7 +7,7 @@ use ::ignore::gitignore::{Gitignore, GitignoreBuilder};
 use anyhow::{anyhow, Context as _, Result};
 use clock::ReplicaId;
 use collections::{HashMap, HashSet, VecDeque};
-use fs::{copy_recursive, Fs, PathEvent, RemoveOptions, Watcher};
+use fs::{copy_recursive, Fs, PathEvent, RemoveOptions, SaveOptions, Watcher};
 use futures::{
     channel::{
         mpsc::{self, UnboundedSender},
@@ -53,7 +53,7 @@ use std::{
     path::{Path, PathBuf},
     pin::Pin,
     sync::{
-        atomic::{AtomicUsize, Ordering::SeqCst},
+        atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst},
         Arc,
     },
     time::{Duration, Instant, SystemTime},
@@ -61,7 +61,7 @@ use std::{
 use sum_tree::{Bias, Edit, SeekTarget, SumTree, TreeMap, TreeSet};
 use text::{LineEnding, Rope};
 use util::{paths::home_dir, ResultExt};
-pub use worktree_settings::WorktreeSettings;
+pub use worktree_settings::{SaveSettings, WorktreeSettings};
 
 #[cfg(feature = "test-support")]
 pub const FS_WATCH_LATENCY: Duration = Duration::from_millis(100);
@@ -113,6 +113,7 @@ pub struct LocalWorktree {
     scan_requests_tx: channel::Sender<ScanRequest>,
     path_prefixes_to_scan_tx: channel::Sender<Arc<Path>>,
     is_scanning: (watch::Sender<bool>, watch::Receiver<bool>),
+    scan_cancelled: Arc<AtomicBool>,
     _background_scanner_tasks: Vec<Task<()>>,
     update_observer: Option<UpdateObservationState>,
     fs: Arc<dyn Fs>,
@@ -435,6 +436,7 @@ impl Worktree {
                 next_entry_id,
                 snapshot,
                 is_scanning: watch::channel_with(true),
+                scan_cancelled: Default::default(),
                 update_observer: None,
                 scan_requests_tx,
                 path_prefixes_to_scan_tx,
@@ -683,7 +685,23 @@ impl Worktree {
         cx: &mut ModelContext<Worktree>,
     ) -> Task<Result<Arc<File>>> {
         match self {
-            Worktree::Local(this) => this.write_file(path, text, line_ending, cx),
+            Worktree::Local(this) => this.write_file(path, text, line_ending, false, cx),
+            Worktree::Remote(_) => {
+                Task::ready(Err(anyhow!("remote worktree can't yet write files")))
+            }
+        }
+    }
+
+    /// Writes a file as an administrator, for files the user isn't allowed to write.
+    pub fn write_file_elevated(
+        &self,
+        path: &Path,
+        text: Rope,
+        line_ending: LineEnding,
+        cx: &mut ModelContext<Worktree>,
+    ) -> Task<Result<Arc<File>>> {
+        match self {
+            Worktree::Local(this) => this.write_file(path, text, line_ending, true, cx),
             Worktree::Remote(_) => {
                 Task::ready(Err(anyhow!("remote worktree can't yet write files")))
             }
@@ -1010,6 +1028,8 @@ impl LocalWorktree {
         let next_entry_id = self.next_entry_id.clone();
         let fs = self.fs.clone();
         let settings = self.settings.clone();
+        let scan_cancelled = Arc::new(AtomicBool::new(false));
+        self.scan_cancelled = scan_cancelled.clone();
         let (scan_states_tx, mut scan_states_rx) = mpsc::unbounded();
         let background_scanner = cx.background_executor().spawn({
             let abs_path = &snapshot.abs_path;
@@ -1048,6 +1068,7 @@ impl LocalWorktree {
                     phase: BackgroundScannerPhase::InitialScan,
                     share_private_files,
                     settings,
+                    scan_cancelled,
                     watcher,
                 };
 
@@ -1223,6 +1244,16 @@ impl LocalWorktree {
         }
     }
 
+    pub fn is_scanning(&self) -> bool {
+        *self.is_scanning.1.borrow()
+    }
+
+    /// Stops the initial scan. The directories that weren't scanned yet are left
+    /// unloaded, and are scanned when they're expanded or a file in them is opened.
+    pub fn cancel_scan(&self) {
+        self.scan_cancelled.store(true, SeqCst);
+    }
+
     pub fn snapshot(&self) -> LocalSnapshot {
         self.snapshot.clone()
     }
@@ -1387,6 +1418,7 @@ impl LocalWorktree {
         path: impl Into<Arc<Path>>,
         text: Rope,
         line_ending: LineEnding,
+        elevated: bool,
         cx: &mut ModelContext<Worktree>,
     ) -> Task<Result<Arc<File>>> {
         let path = path.into();
@@ -1395,11 +1427,26 @@ impl LocalWorktree {
         let Ok(abs_path) = self.absolutize(&path) else {
             return Task::ready(Err(anyhow!("invalid path {path:?}")));
         };
+        let settings = SaveSettings::get(
+            Some(SettingsLocation {
+                worktree_id: cx.handle().entity_id().as_u64() as usize,
+                path: &path,
+            }),
+            cx,
+        );
+        let options = SaveOptions {
+            atomic: settings.atomic_save,
+            backup: settings.backup_on_save,
+            elevated,
+        };
 
         let write = cx.background_executor().spawn({
             let fs = fs.clone();
             let abs_path = abs_path.clone();
-            async move { fs.save(&abs_path, &text, line_ending).await }
+            async move {
+                fs.save_with_options(&abs_path, &text, line_ending, options)
+                    .await
+            }
         });
 
         cx.spawn(move |this, mut cx| async move {
@@ -2700,8 +2747,9 @@ impl LocalSnapshot {
 }
 
 impl BackgroundScannerState {
-    fn should_scan_directory(&self, entry: &Entry) -> bool {
-        (!entry.is_external && !entry.is_ignored)
+    fn should_scan_directory(&self, entry: &Entry, settings: &WorktreeSettings) -> bool {
+        (!entry.is_external && !entry.is_ignored && settings.is_within_scan_depth(&entry.path))
+            || settings.is_path_included(&entry.path)
             || entry.path.file_name() == Some(*DOT_GIT)
             || entry.path.file_name() == Some(local_settings_folder_relative_path().as_os_str())
             || self.scanned_dirs.contains(&entry.id) // If we've ever scanned it, keep scanning
@@ -3515,6 +3563,7 @@ struct BackgroundScanner {
     phase: BackgroundScannerPhase,
     watcher: Arc<dyn Watcher>,
     settings: WorktreeSettings,
+    scan_cancelled: Arc<AtomicBool>,
     share_private_files: bool,
 }
 
@@ -3895,7 +3944,9 @@ impl BackgroundScanner {
                                 // Recursively load directories from the file system.
                                 job = scan_jobs_rx.recv().fuse() => {
                                     let Ok(job) = job else { break };
-                                    if let Err(err) = self.scan_dir(&job).await {
+                                    if enable_progress_updates && self.scan_cancelled.load(SeqCst) {
+                                        self.defer_scan_dir(&job);
+                                    } else if let Err(err) = self.scan_dir(&job).await {
                                         if job.path.as_ref() != Path::new("") {
                                             log::error!("error scanning directory {:?}: {}", job.abs_path, err);
                                         }
@@ -3930,6 +3981,19 @@ impl BackgroundScanner {
             .is_ok()
     }
 
+    /// Leaves a directory unloaded instead of scanning it, once the initial scan is
+    /// cancelled.
+    fn defer_scan_dir(&self, job: &ScanJob) {
+        let mut state = self.state.lock();
+        if let Some(mut entry) = state.snapshot.entry_for_path(&job.path).cloned() {
+            if entry.kind == EntryKind::PendingDir {
+                log::debug!("defer scanning directory {:?}", job.path);
+                entry.kind = EntryKind::UnloadedDir;
+                state.insert_entry(entry, self.fs.as_ref());
+            }
+        }
+    }
+
     async fn scan_dir(&self, job: &ScanJob) -> Result<()> {
         let root_abs_path;
         let root_char_bag;
@@ -4123,7 +4187,7 @@ impl BackgroundScanner {
         for entry in &mut new_entries {
             state.reuse_entry_id(entry);
             if entry.is_dir() {
-                if state.should_scan_directory(entry) {
+                if state.should_scan_directory(entry, &self.settings) {
                     job_ix += 1;
                 } else {
                     log::debug!("defer scanning directory {:?}", entry.path);
@@ -4249,7 +4313,7 @@ impl BackgroundScanner {
                     fs_entry.is_private = self.is_path_private(path);
 
                     if let (Some(scan_queue_tx), true) = (&scan_queue_tx, is_dir) {
-                        if state.should_scan_directory(&fs_entry)
+                        if state.should_scan_directory(&fs_entry, &self.settings)
                             || (fs_entry.path.as_os_str().is_empty()
                                 && abs_path.file_name() == Some(*DOT_GIT))
                         {
@@ -4409,7 +4473,7 @@ impl BackgroundScanner {
                 // Scan any directories that were previously ignored and weren't previously scanned.
                 if was_ignored && !entry.is_ignored && entry.kind.is_unloaded() {
                     let state = self.state.lock();
-                    if state.should_scan_directory(&entry) {
+                    if state.should_scan_directory(&entry, &self.settings) {
                         state.enqueue_scan_dir(abs_path.clone(), &entry, &job.scan_queue);
                     }
                 }
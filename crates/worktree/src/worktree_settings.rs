@@ -1,6 +1,7 @@
 use std::path::Path;
 
 use anyhow::Context;
+use globset::Glob;
 use gpui::AppContext;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -10,7 +11,10 @@ use util::paths::PathMatcher;
 #[derive(Clone, PartialEq, Eq)]
 pub struct WorktreeSettings {
     pub file_scan_exclusions: PathMatcher,
+    pub file_scan_inclusions: PathMatcher,
     pub private_files: PathMatcher,
+    /// How many directories deep the initial scan goes, or 0 to scan everything.
+    pub lazy_scan_depth: usize,
 }
 
 impl WorktreeSettings {
@@ -23,6 +27,25 @@ impl WorktreeSettings {
         path.ancestors()
             .any(|ancestor| self.file_scan_exclusions.is_match(&ancestor))
     }
+
+    /// Whether the directory at `path` is always scanned, even when it's ignored or
+    /// deeper than `lazy_scan_depth`, as it matches `file_scan_inclusions` or may
+    /// contain paths that do.
+    pub fn is_path_included(&self, path: &Path) -> bool {
+        path.ancestors()
+            .any(|ancestor| self.file_scan_inclusions.is_match(&ancestor))
+            || self
+                .file_scan_inclusions
+                .sources()
+                .iter()
+                .any(|glob| may_contain_match(path, glob))
+    }
+
+    /// Whether the directory at `path` is scanned eagerly, rather than when it's
+    /// expanded or a file in it is opened.
+    pub fn is_within_scan_depth(&self, path: &Path) -> bool {
+        self.lazy_scan_depth == 0 || path.components().count() <= self.lazy_scan_depth
+    }
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -42,6 +65,19 @@ pub struct WorktreeSettingsContent {
     #[serde(default)]
     pub file_scan_exclusions: Option<Vec<String>>,
 
+    /// Always scan the directories matching these globs, even when they're ignored
+    /// by git or deeper than `lazy_scan_depth`.
+    ///
+    /// Default: []
+    pub file_scan_inclusions: Option<Vec<String>>,
+
+    /// How many directories deep to scan a worktree when it's opened. Deeper
+    /// directories are scanned when they're expanded in the project panel, or a file
+    /// in them is opened. 0 scans every directory.
+    ///
+    /// Default: 0
+    pub lazy_scan_depth: Option<usize>,
+
     /// Treat the files matching these globs as `.env` files.
     /// Default: [ "**/.env*" ]
     pub private_files: Option<Vec<String>>,
@@ -58,16 +94,90 @@ impl Settings for WorktreeSettings {
     ) -> anyhow::Result<Self> {
         let result: WorktreeSettingsContent = sources.json_merge()?;
         let mut file_scan_exclusions = result.file_scan_exclusions.unwrap_or_default();
+        let mut file_scan_inclusions = result.file_scan_inclusions.unwrap_or_default();
         let mut private_files = result.private_files.unwrap_or_default();
         file_scan_exclusions.sort();
+        file_scan_inclusions.sort();
         private_files.sort();
         Ok(Self {
             file_scan_exclusions: path_matchers(&file_scan_exclusions, "file_scan_exclusions")?,
+            file_scan_inclusions: path_matchers(&file_scan_inclusions, "file_scan_inclusions")?,
             private_files: path_matchers(&private_files, "private_files")?,
+            lazy_scan_depth: result.lazy_scan_depth.unwrap_or_default(),
         })
     }
 }
 
+/// Whether the directory at `path` may contain paths matching the glob, as its
+/// components match the leading components of the glob.
+///
+/// Globs starting with `**` could match in any directory, so they don't make
+/// directories scanned before their matches are found.
+fn may_contain_match(path: &Path, glob: &str) -> bool {
+    if glob.starts_with("**") {
+        return false;
+    }
+    let mut glob_components = glob.split('/').filter(|component| !component.is_empty());
+    for component in path.components() {
+        let Some(glob_component) = glob_components.next() else {
+            return false;
+        };
+        if glob_component == "**" {
+            return true;
+        }
+        let name = component.as_os_str().to_string_lossy();
+        let is_match = if glob_component.contains(['*', '?', '[', '{']) {
+            Glob::new(glob_component)
+                .map_or(false, |glob| glob.compile_matcher().is_match(name.as_ref()))
+        } else {
+            glob_component == name
+        };
+        if !is_match {
+            return false;
+        }
+    }
+    glob_components.next().is_some()
+}
+
 fn path_matchers(values: &[String], context: &'static str) -> anyhow::Result<PathMatcher> {
     PathMatcher::new(values).with_context(|| format!("Failed to parse globs from {}", context))
 }
+
+/// How files are written when buffers are saved.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SaveSettings {
+    pub atomic_save: bool,
+    pub backup_on_save: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SaveSettingsContent {
+    /// Whether to save files by writing a temporary file that then replaces them,
+    /// keeping their permissions and extended attributes, so that a file is never
+    /// left partially written.
+    ///
+    /// Default: false
+    pub atomic_save: Option<bool>,
+
+    /// Whether to copy a file to `file~` before saving over it.
+    ///
+    /// Default: false
+    pub backup_on_save: Option<bool>,
+}
+
+impl Settings for SaveSettings {
+    const KEY: Option<&'static str> = None;
+
+    type FileContent = SaveSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut AppContext,
+    ) -> anyhow::Result<Self> {
+        let result: SaveSettingsContent = sources.json_merge()?;
+        Ok(Self {
+            atomic_save: result.atomic_save.unwrap_or_default(),
+            backup_on_save: result.backup_on_save.unwrap_or_default(),
+        })
+    }
+}
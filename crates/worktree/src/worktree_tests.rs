@@ -1,6 +1,6 @@
 use crate::{
-    worktree_settings::WorktreeSettings, Entry, EntryKind, Event, PathChange, Snapshot, Worktree,
-    WorktreeModelHandle,
+    worktree_settings::{SaveSettings, WorktreeSettings},
+    Entry, EntryKind, Event, PathChange, Snapshot, Worktree, WorktreeModelHandle,
 };
 use anyhow::Result;
 use fs::{FakeFs, Fs, RealFs, RemoveOptions};
@@ -544,6 +544,153 @@ async fn test_open_gitignored_files(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_lazy_scan_depth(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings::<WorktreeSettings>(cx, |settings| {
+                settings.lazy_scan_depth = Some(1);
+                settings.file_scan_inclusions = Some(vec!["**/generated".to_string()]);
+            });
+        });
+    });
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/root",
+        json!({
+            ".gitignore": "generated\n",
+            "src": {
+                "lib.rs": "",
+                "deep": {
+                    "a.rs": "",
+                },
+            },
+            "generated": {
+                "nested": {
+                    "b.rs": "",
+                },
+            },
+        }),
+    )
+    .await;
+
+    let tree = Worktree::local(
+        Path::new("/root"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    // Directories deeper than the scan depth are left unloaded, except for those
+    // that are always scanned.
+    tree.read_with(cx, |tree, _| {
+        assert_eq!(
+            tree.entries(true, 0)
+                .map(|entry| (entry.path.as_ref(), entry.kind.is_unloaded()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Path::new(""), false),
+                (Path::new(".gitignore"), false),
+                (Path::new("generated"), false),
+                (Path::new("generated/nested"), false),
+                (Path::new("generated/nested/b.rs"), false),
+                (Path::new("src"), false),
+                (Path::new("src/deep"), true),
+                (Path::new("src/lib.rs"), false),
+            ]
+        );
+    });
+
+    // Opening a file in an unloaded directory loads it.
+    tree.update(cx, |tree, cx| tree.load_file("src/deep/a.rs".as_ref(), cx))
+        .await
+        .unwrap();
+    tree.read_with(cx, |tree, _| {
+        assert!(!tree.entry_for_path("src/deep").unwrap().kind.is_unloaded());
+        assert!(tree.entry_for_path("src/deep/a.rs").is_some());
+    });
+}
+
+#[gpui::test]
+async fn test_file_scan_inclusions_in_ignored_directory(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings::<WorktreeSettings>(cx, |settings| {
+                settings.file_scan_inclusions = Some(vec!["vendor/foo/**".to_string()]);
+            });
+        });
+    });
+    cx.read(|cx| {
+        let settings = WorktreeSettings::get_global(cx);
+        assert!(settings.is_path_included(Path::new("vendor")));
+        assert!(settings.is_path_included(Path::new("vendor/foo")));
+        assert!(settings.is_path_included(Path::new("vendor/foo/lib")));
+        assert!(!settings.is_path_included(Path::new("vendor/bar")));
+        assert!(!settings.is_path_included(Path::new("src")));
+    });
+
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/root",
+        json!({
+            ".gitignore": "vendor\n",
+            "src": {
+                "main.rs": "",
+            },
+            "vendor": {
+                "bar": {
+                    "b.rs": "",
+                },
+                "foo": {
+                    "lib": {
+                        "a.rs": "",
+                    },
+                },
+            },
+        }),
+    )
+    .await;
+
+    let tree = Worktree::local(
+        Path::new("/root"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    // The ignored directory is scanned down to the included paths, and no further.
+    tree.read_with(cx, |tree, _| {
+        assert_eq!(
+            tree.entries(true, 0)
+                .map(|entry| (entry.path.as_ref(), entry.kind.is_unloaded()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Path::new(""), false),
+                (Path::new(".gitignore"), false),
+                (Path::new("src"), false),
+                (Path::new("src/main.rs"), false),
+                (Path::new("vendor"), false),
+                (Path::new("vendor/bar"), true),
+                (Path::new("vendor/foo"), false),
+                (Path::new("vendor/foo/lib"), false),
+                (Path::new("vendor/foo/lib/a.rs"), false),
+            ]
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_dirs_no_longer_ignored(cx: &mut TestAppContext) {
     init_test(cx);
@@ -2626,6 +2773,7 @@ fn init_test(cx: &mut gpui::TestAppContext) {
         let settings_store = SettingsStore::test(cx);
         cx.set_global(settings_store);
         WorktreeSettings::register(cx);
+        SaveSettings::register(cx);
     });
 }
 
@@ -30,7 +30,7 @@ use log::LevelFilter;
 use assets::Assets;
 use node_runtime::RealNodeRuntime;
 use parking_lot::Mutex;
-use recent_projects::open_ssh_project;
+use recent_projects::{open_ssh_project, open_wsl_project};
 use release_channel::{AppCommitSha, AppVersion};
 use session::{AppSession, Session};
 use settings::{handle_settings_file_changes, watch_config_file, Settings, SettingsStore};
@@ -48,7 +48,7 @@ use theme::{ActiveTheme, SystemAppearance, ThemeRegistry, ThemeSettings};
 use time::UtcOffset;
 use util::{maybe, parse_env_output, ResultExt, TryFutureExt};
 use uuid::Uuid;
-use welcome::{show_welcome_view, BaseKeymap, FIRST_OPEN};
+use welcome::{show_welcome_view, BaseKeymap, Dashboard, FIRST_OPEN};
 use workspace::{
     notifications::{simple_message_notification::MessageNotification, NotificationId},
     AppState, WorkspaceSettings, WorkspaceStore,
@@ -237,13 +237,20 @@ fn init_ui(
     app_state.languages.set_theme(cx.theme().clone());
     editor::init(cx);
     image_viewer::init(cx);
+    repl::notebook_editor::init(cx);
     diagnostics::init(cx);
+    dictation::init(cx);
 
     audio::init(Assets, cx);
     workspace::init(app_state.clone(), cx);
 
     recent_projects::init(cx);
     go_to_line::init(cx);
+    filter_lines::init(cx);
+    log_viewer::init(cx);
+    rest_client::init(cx);
+    snippet_share::init(cx);
+    database::init(cx);
     file_finder::init(cx);
     tab_switcher::init(cx);
     dev_server_projects::init(app_state.client.clone(), cx);
@@ -251,20 +258,37 @@ fn init_ui(
     project_symbols::init(cx);
     project_panel::init(Assets, cx);
     outline_panel::init(Assets, cx);
+    test_explorer::init(cx);
+    git_panel::init(cx);
+    debugger_ui::init(cx);
+    review_panel::init(cx);
+    ports_panel::init(cx);
+    extension_panel::init(cx);
     tasks_ui::init(cx);
     channel::init(&app_state.client.clone(), app_state.user_store.clone(), cx);
     search::init(cx);
     vim::init(cx);
     terminal_view::init(cx);
+    terminal_view::shared_terminals::init(&app_state.client);
     journal::init(app_state.clone(), cx);
-    language_selector::init(cx);
+    json_tools::init(cx);
+    language_selector::init(app_state.languages.clone(), cx);
+    local_history::init(app_state.fs.clone(), cx);
+    buffer_recovery::init(&app_state, cx);
+    external_changes::init(cx);
+    workspace_trust::init(app_state.fs.clone(), cx);
     theme_selector::init(cx);
+    settings_profile_selector::init(cx);
+    settings_sync::init(app_state.fs.clone(), app_state.client.clone(), cx);
     language_tools::init(cx);
     call::init(app_state.client.clone(), app_state.user_store.clone(), cx);
     notifications::init(app_state.client.clone(), app_state.user_store.clone(), cx);
     collab_ui::init(&app_state, cx);
     feedback::init(cx);
     markdown_preview::init(cx);
+    csv_preview::init(cx);
+    svg_preview::init(cx);
+    pdf_viewer::init(cx);
     welcome::init(cx);
     settings_ui::init(cx);
     extensions_ui::init(cx);
@@ -445,11 +469,11 @@ fn main() {
         <dyn Fs>::set_global(fs.clone(), cx);
 
         GitHostingProviderRegistry::set_global(git_hosting_provider_registry, cx);
-        git_hosting_providers::init(cx);
 
         OpenListener::set_global(cx, open_listener.clone());
 
         settings::init(cx);
+        git_hosting_providers::init(cx);
         handle_settings_file_changes(user_settings_file_rx, cx, handle_settings_changed);
         handle_keymap_file_changes(user_keymap_file_rx, cx, handle_keymap_changed);
 
@@ -647,6 +671,14 @@ fn handle_open_request(
         return;
     }
 
+    if let Some(distro) = request.wsl_distro {
+        cx.spawn(|mut cx| async move {
+            open_wsl_project(distro, request.open_paths, app_state, &mut cx).await
+        })
+        .detach_and_log_err(cx);
+        return;
+    }
+
     let mut task = None;
     if !request.open_paths.is_empty() {
         let app_state = app_state.clone();
@@ -785,7 +817,9 @@ async fn restore_or_create_workspace(
     } else {
         cx.update(|cx| {
             workspace::open_new(Default::default(), app_state, cx, |workspace, cx| {
-                Editor::new_file(workspace, &Default::default(), cx)
+                if !Dashboard::show_if_enabled(workspace, cx) {
+                    Editor::new_file(workspace, &Default::default(), cx)
+                }
             })
         })?
         .await?;
@@ -4,6 +4,7 @@ pub mod inline_completion_registry;
 pub(crate) mod linux_prompts;
 #[cfg(target_os = "macos")]
 pub(crate) mod mac_only_instance;
+mod memory_report;
 mod open_listener;
 #[cfg(target_os = "windows")]
 pub(crate) mod windows_only_instance;
@@ -16,6 +17,7 @@ use collections::VecDeque;
 use command_palette_hooks::CommandPaletteFilter;
 use editor::{scroll::Autoscroll, Editor, MultiBuffer};
 use feature_flags::FeatureFlagAppExt;
+use git_panel::GitPanel;
 use gpui::{
     actions, point, px, AppContext, AsyncAppContext, Context, FocusableView, MenuItem, PromptLevel,
     ReadGlobal, TitlebarOptions, View, ViewContext, VisualContext, WindowKind, WindowOptions,
@@ -24,12 +26,16 @@ pub use open_listener::*;
 
 use anyhow::Context as _;
 use assets::Assets;
+use debugger_ui::DebugPanel;
+use extension_panel::ExtensionPanel;
 use futures::{channel::mpsc, select_biased, StreamExt};
 use outline_panel::OutlinePanel;
-use project::TaskSourceKind;
+use ports_panel::PortsPanel;
+use project::{Project, TaskSourceKind};
 use project_panel::ProjectPanel;
 use quick_action_bar::QuickActionBar;
 use release_channel::{AppCommitSha, ReleaseChannel};
+use review_panel::ReviewPanel;
 use rope::Rope;
 use search::project_search::ProjectSearchBar;
 use settings::{
@@ -39,6 +45,7 @@ use settings::{
 use std::any::TypeId;
 use std::{borrow::Cow, ops::Deref, path::Path, sync::Arc};
 use task::static_source::{StaticSource, TrackedFile};
+use test_explorer::TestExplorerPanel;
 use theme::ActiveTheme;
 use workspace::notifications::NotificationId;
 use workspace::CloseIntent;
@@ -62,12 +69,14 @@ actions!(
         DebugElements,
         Hide,
         HideOthers,
+        MemoryReport,
         Minimize,
         OpenDefaultKeymap,
         OpenDefaultSettings,
         OpenLocalSettings,
         OpenLocalTasks,
         OpenTasks,
+        ReleaseCaches,
         ResetDatabase,
         ShowAll,
         ToggleFullScreen,
@@ -76,6 +85,8 @@ actions!(
     ]
 );
 
+actions!(settings, [OpenProjectSettings]);
+
 pub fn init(cx: &mut AppContext) {
     #[cfg(target_os = "macos")]
     cx.on_action(|_: &Hide, cx| cx.hide());
@@ -204,14 +215,26 @@ pub fn initialize_workspace(
         let active_buffer_language =
             cx.new_view(|_| language_selector::ActiveBufferLanguage::new(workspace));
         let vim_mode_indicator = cx.new_view(|cx| vim::ModeIndicator::new(cx));
+        let json_node_path = cx.new_view(|_| json_tools::NodePathIndicator::default());
+        let project_index_status =
+            cx.new_view(|cx| semantic_index::ProjectIndexStatus::new(workspace, cx));
+        let dictation_indicator = cx.new_view(dictation::DictationIndicator::new);
+        let restricted_mode_indicator =
+            cx.new_view(|cx| workspace_trust::RestrictedModeIndicator::new(workspace, cx));
+        let settings_sync_indicator = cx.new_view(settings_sync::SyncStatusIndicator::new);
         let cursor_position =
             cx.new_view(|_| go_to_line::cursor_position::CursorPosition::new(workspace));
         workspace.status_bar().update(cx, |status_bar, cx| {
             status_bar.add_left_item(diagnostic_summary, cx);
             status_bar.add_left_item(activity_indicator, cx);
+            status_bar.add_right_item(restricted_mode_indicator, cx);
+            status_bar.add_right_item(settings_sync_indicator, cx);
+            status_bar.add_right_item(dictation_indicator, cx);
+            status_bar.add_right_item(project_index_status, cx);
             status_bar.add_right_item(inline_completion_button, cx);
             status_bar.add_right_item(active_buffer_language, cx);
             status_bar.add_right_item(vim_mode_indicator, cx);
+            status_bar.add_right_item(json_node_path, cx);
             status_bar.add_right_item(cursor_position, cx);
         });
 
@@ -256,6 +279,12 @@ pub fn initialize_workspace(
 
             let project_panel = ProjectPanel::load(workspace_handle.clone(), cx.clone());
             let outline_panel = OutlinePanel::load(workspace_handle.clone(), cx.clone());
+            let test_explorer_panel = TestExplorerPanel::load(workspace_handle.clone(), cx.clone());
+            let git_panel = GitPanel::load(workspace_handle.clone(), cx.clone());
+            let debug_panel = DebugPanel::load(workspace_handle.clone(), cx.clone());
+            let review_panel = ReviewPanel::load(workspace_handle.clone(), cx.clone());
+            let ports_panel = PortsPanel::load(workspace_handle.clone(), cx.clone());
+            let extension_panel = ExtensionPanel::load(workspace_handle.clone(), cx.clone());
             let terminal_panel = TerminalPanel::load(workspace_handle.clone(), cx.clone());
             let channels_panel =
                 collab_ui::collab_panel::CollabPanel::load(workspace_handle.clone(), cx.clone());
@@ -269,6 +298,12 @@ pub fn initialize_workspace(
             let (
                 project_panel,
                 outline_panel,
+                test_explorer_panel,
+                git_panel,
+                debug_panel,
+                review_panel,
+                ports_panel,
+                extension_panel,
                 terminal_panel,
                 assistant_panel,
                 channels_panel,
@@ -277,6 +312,12 @@ pub fn initialize_workspace(
             ) = futures::try_join!(
                 project_panel,
                 outline_panel,
+                test_explorer_panel,
+                git_panel,
+                debug_panel,
+                review_panel,
+                ports_panel,
+                extension_panel,
                 terminal_panel,
                 assistant_panel,
                 channels_panel,
@@ -288,6 +329,12 @@ pub fn initialize_workspace(
                 workspace.add_panel(assistant_panel, cx);
                 workspace.add_panel(project_panel, cx);
                 workspace.add_panel(outline_panel, cx);
+                workspace.add_panel(test_explorer_panel, cx);
+                workspace.add_panel(git_panel, cx);
+                workspace.add_panel(debug_panel, cx);
+                workspace.add_panel(review_panel, cx);
+                workspace.add_panel(ports_panel, cx);
+                workspace.add_panel(extension_panel, cx);
                 workspace.add_panel(terminal_panel, cx);
                 workspace.add_panel(channels_panel, cx);
                 workspace.add_panel(chat_panel, cx);
@@ -403,6 +450,13 @@ pub fn initialize_workspace(
             .register_action(|workspace, _: &OpenLog, cx| {
                 open_log_file(workspace, cx);
             })
+            .register_action(|workspace, _: &MemoryReport, cx| {
+                let report = memory_report::memory_report(workspace, cx);
+                open_bundled_file(workspace, report.into(), "Memory Report", "Markdown", cx);
+            })
+            .register_action(|workspace, _: &ReleaseCaches, cx| {
+                memory_report::release_caches(workspace, cx);
+            })
             .register_action(|workspace, _: &zed_actions::OpenLicenses, cx| {
                 open_bundled_file(
                     workspace,
@@ -451,6 +505,7 @@ pub fn initialize_workspace(
                 },
             )
             .register_action(open_local_settings_file)
+            .register_action(open_project_settings)
             .register_action(open_local_tasks_file)
             .register_action(
                 move |workspace: &mut Workspace,
@@ -585,7 +640,7 @@ fn initialize_pane(workspace: &mut Workspace, pane: &View<Pane>, cx: &mut ViewCo
             let quick_action_bar =
                 cx.new_view(|cx| QuickActionBar::new(buffer_search_bar, workspace, cx));
             toolbar.add_item(quick_action_bar, cx);
-            let diagnostic_editor_controls = cx.new_view(|_| diagnostics::ToolbarControls::new());
+            let diagnostic_editor_controls = cx.new_view(diagnostics::ToolbarControls::new);
             toolbar.add_item(diagnostic_editor_controls, cx);
             let project_search_bar = cx.new_view(|_| ProjectSearchBar::new());
             toolbar.add_item(project_search_bar, cx);
@@ -846,6 +901,74 @@ fn open_local_settings_file(
     )
 }
 
+/// Opens a read-only view of the settings of the project's `.zed/settings.json` files
+/// that differ from the user settings, kept up to date as either changes.
+fn open_project_settings(
+    workspace: &mut Workspace,
+    _: &OpenProjectSettings,
+    cx: &mut ViewContext<Workspace>,
+) {
+    const TITLE: &str = "Project Settings";
+
+    let language = workspace.app_state().languages.language_for_name("JSONC");
+    cx.spawn(|workspace, mut cx| async move {
+        let language = language.await.log_err();
+        workspace.update(&mut cx, |workspace, cx| {
+            let project = workspace.project().clone();
+            let buffer = project.update(cx, |project, cx| {
+                let text = project_settings_deltas_text(project, cx);
+                project.create_local_buffer(&text, language, cx)
+            });
+            let multi_buffer = cx.new_model(|cx| {
+                MultiBuffer::singleton(buffer.clone(), cx).with_title(TITLE.into())
+            });
+            let editor = cx.new_view(|cx| {
+                let mut editor =
+                    Editor::for_multibuffer(multi_buffer, Some(project.clone()), true, cx);
+                editor.set_read_only(true);
+                editor.set_breadcrumb_header(TITLE.into());
+                cx.observe_global::<SettingsStore>(move |_, cx| {
+                    let text = project_settings_deltas_text(project.read(cx), cx);
+                    buffer.update(cx, |buffer, cx| {
+                        if buffer.text() != text {
+                            buffer.set_text(text, cx);
+                        }
+                    });
+                })
+                .detach();
+                editor
+            });
+            workspace.add_item_to_active_pane(Box::new(editor), None, true, cx);
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+fn project_settings_deltas_text(project: &Project, cx: &AppContext) -> String {
+    let store = cx.global::<SettingsStore>();
+    let mut deltas = serde_json::Map::new();
+    for worktree in project.visible_worktrees(cx) {
+        let worktree = worktree.read(cx);
+        for (directory, delta) in store.local_settings_deltas(worktree.id().to_usize()) {
+            let path = Path::new(worktree.root_name())
+                .join(directory)
+                .join(local_settings_file_relative_path());
+            deltas.insert(path.to_string_lossy().into_owned(), delta);
+        }
+    }
+
+    let header = if deltas.is_empty() {
+        "// This project's settings don't differ from your settings.\n"
+    } else {
+        concat!(
+            "// The settings of this project's `.zed/settings.json` files that differ\n",
+            "// from your settings, which they override in the project.\n",
+        )
+    };
+    let deltas = serde_json::to_string_pretty(&deltas).unwrap_or_default();
+    format!("{header}{deltas}\n")
+}
+
 fn open_local_tasks_file(
     workspace: &mut Workspace,
     _: &OpenLocalTasks,
@@ -3416,6 +3539,12 @@ mod tests {
             collab_ui::init(&app_state, cx);
             project_panel::init((), cx);
             outline_panel::init((), cx);
+            test_explorer::init(cx);
+            git_panel::init(cx);
+            debugger_ui::init(cx);
+            review_panel::init(cx);
+            ports_panel::init(cx);
+            extension_panel::init(cx);
             terminal_view::init(cx);
             copilot::copilot_chat::init(
                 app_state.fs.clone(),
@@ -0,0 +1,124 @@
+use std::fmt::Write as _;
+
+use editor::{Editor, EditorMemoryUsage};
+use gpui::{AppContext, ViewContext};
+use language::BufferMemoryUsage;
+use workspace::{notifications::NotificationId, Toast, Workspace};
+
+struct ReleasedCaches;
+
+/// A Markdown report of the memory that the workspace's buffers and editors use, and
+/// of the images that GPUI has cached. The figures are estimates.
+pub(crate) fn memory_report(workspace: &Workspace, cx: &AppContext) -> String {
+    let mut buffers = workspace
+        .project()
+        .read(cx)
+        .opened_buffers(cx)
+        .into_iter()
+        .map(|buffer| {
+            let buffer = buffer.read(cx);
+            let name = buffer.file().map_or_else(
+                || "untitled".to_string(),
+                |file| file.full_path(cx).to_string_lossy().into_owned(),
+            );
+            (name, buffer.snapshot().memory_usage())
+        })
+        .collect::<Vec<_>>();
+    buffers.sort_by_key(|(_, usage)| std::cmp::Reverse(usage.text + usage.syntax_trees));
+    let buffers_total = buffers
+        .iter()
+        .fold(BufferMemoryUsage::default(), |total, (_, usage)| {
+            BufferMemoryUsage {
+                text: total.text + usage.text,
+                syntax_trees: total.syntax_trees + usage.syntax_trees,
+            }
+        });
+
+    let editors_total = workspace.items_of_type::<Editor>(cx).fold(
+        EditorMemoryUsage::default(),
+        |total, editor| {
+            let usage = editor.read(cx).memory_usage(cx);
+            EditorMemoryUsage {
+                inlay_hints: total.inlay_hints + usage.inlay_hints,
+                display_map: total.display_map + usage.display_map,
+            }
+        },
+    );
+
+    let mut report = String::new();
+    writeln!(report, "# Memory Report\n").unwrap();
+    writeln!(
+        report,
+        "Estimates of the memory used by each subsystem. \
+        Run `zed: release caches` to free the reclaimable ones.\n"
+    )
+    .unwrap();
+    writeln!(report, "| Subsystem | Memory | Reclaimable |").unwrap();
+    writeln!(report, "| --- | ---: | --- |").unwrap();
+    for (subsystem, bytes, reclaimable) in [
+        ("Buffer text (ropes)", buffers_total.text, false),
+        ("Syntax trees", buffers_total.syntax_trees, false),
+        ("Inlay hint caches", editors_total.inlay_hints, true),
+        ("Display maps", editors_total.display_map, false),
+        ("Image assets", cx.cached_image_bytes(), true),
+    ] {
+        let reclaimable = if reclaimable { "yes" } else { "no" };
+        writeln!(
+            report,
+            "| {subsystem} | {} | {reclaimable} |",
+            format_bytes(bytes)
+        )
+        .unwrap();
+    }
+
+    writeln!(report, "\n## Buffers\n").unwrap();
+    writeln!(report, "| Buffer | Text | Syntax trees |").unwrap();
+    writeln!(report, "| --- | ---: | ---: |").unwrap();
+    for (name, usage) in &buffers {
+        writeln!(
+            report,
+            "| {name} | {} | {} |",
+            format_bytes(usage.text),
+            format_bytes(usage.syntax_trees)
+        )
+        .unwrap();
+    }
+    report
+}
+
+/// Frees the caches that are rebuilt when they're needed: the inlay hints of every
+/// editor in the workspace, and GPUI's cached images.
+pub(crate) fn release_caches(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let editors = workspace.items_of_type::<Editor>(cx).collect::<Vec<_>>();
+    let mut released = cx.cached_image_bytes();
+    for editor in editors {
+        editor.update(cx, |editor, cx| {
+            released += editor.memory_usage(cx).inlay_hints;
+            editor.release_caches(cx);
+        });
+    }
+    cx.clear_cached_assets();
+
+    workspace.show_toast(
+        Toast::new(
+            NotificationId::unique::<ReleasedCaches>(),
+            format!("Released {} of caches", format_bytes(released)),
+        )
+        .autohide(),
+        cx,
+    );
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const KB: f64 = 1024.;
+    let bytes = bytes as f64;
+    if bytes < KB {
+        format!("{bytes} B")
+    } else if bytes < KB * KB {
+        format!("{:.1} KB", bytes / KB)
+    } else if bytes < KB * KB * KB {
+        format!("{:.1} MB", bytes / (KB * KB))
+    } else {
+        format!("{:.1} GB", bytes / (KB * KB * KB))
+    }
+}
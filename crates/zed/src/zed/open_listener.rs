@@ -14,13 +14,13 @@ use futures::channel::{mpsc, oneshot};
 use futures::{FutureExt, SinkExt, StreamExt};
 use gpui::{AppContext, AsyncAppContext, Global, WindowHandle};
 use language::{Bias, Point};
-use remote::SshConnectionOptions;
+use remote::{SshConnectionOptions, WslDistro};
 use std::sync::Arc;
 use std::time::Duration;
 use std::{process, thread};
 use util::paths::PathWithPosition;
 use util::ResultExt;
-use welcome::{show_welcome_view, FIRST_OPEN};
+use welcome::{show_welcome_view, Dashboard, FIRST_OPEN};
 use workspace::item::ItemHandle;
 use workspace::{AppState, OpenOptions, Workspace};
 
@@ -31,6 +31,7 @@ pub struct OpenRequest {
     pub open_channel_notes: Vec<(u64, Option<String>)>,
     pub join_channel: Option<u64>,
     pub ssh_connection: Option<SshConnectionOptions>,
+    pub wsl_distro: Option<WslDistro>,
 }
 
 impl OpenRequest {
@@ -40,9 +41,9 @@ impl OpenRequest {
             if let Some(server_name) = url.strip_prefix("zed-cli://") {
                 this.cli_connection = Some(connect_to_cli(server_name)?);
             } else if let Some(file) = url.strip_prefix("file://") {
-                this.parse_file_path(file)
+                this.parse_file_path(file)?
             } else if let Some(file) = url.strip_prefix("zed://file") {
-                this.parse_file_path(file)
+                this.parse_file_path(file)?
             } else if url.starts_with("ssh://") {
                 this.parse_ssh_file_path(&url)?
             } else if let Some(request_path) = parse_zed_link(&url, cx) {
@@ -55,11 +56,28 @@ impl OpenRequest {
         Ok(this)
     }
 
-    fn parse_file_path(&mut self, file: &str) {
-        if let Some(decoded) = urlencoding::decode(file).log_err() {
-            let path_buf = PathWithPosition::parse_str(&decoded);
-            self.open_paths.push(path_buf)
+    fn parse_file_path(&mut self, file: &str) -> Result<()> {
+        let Some(decoded) = urlencoding::decode(file).log_err() else {
+            return Ok(());
+        };
+        if let Some((distro, path)) = WslDistro::parse_path(&decoded) {
+            if self.wsl_distro.is_none() && !self.open_paths.is_empty() {
+                return Err(anyhow!("cannot open both local and WSL paths"));
+            }
+            if let Some(wsl_distro) = &self.wsl_distro {
+                if *wsl_distro != distro {
+                    return Err(anyhow!("cannot open paths in multiple WSL distros"));
+                }
+            }
+            self.wsl_distro = Some(distro);
+            self.open_paths.push(PathWithPosition::parse_str(&path));
+        } else {
+            if self.wsl_distro.is_some() {
+                return Err(anyhow!("cannot open both local and WSL paths"));
+            }
+            self.open_paths.push(PathWithPosition::parse_str(&decoded));
         }
+        Ok(())
     }
 
     fn parse_ssh_file_path(&mut self, file: &str) -> Result<()> {
@@ -79,6 +97,7 @@ impl OpenRequest {
             password,
             host,
             port,
+            port_forwards: Vec::new(),
         };
         if let Some(ssh_connection) = &self.ssh_connection {
             if *ssh_connection != connection {
@@ -86,8 +105,7 @@ impl OpenRequest {
             }
         }
         self.ssh_connection = Some(connection);
-        self.parse_file_path(url.path());
-        Ok(())
+        self.parse_file_path(url.path())
     }
 
     fn parse_request_path(&mut self, request_path: &str) -> Result<()> {
@@ -397,7 +415,7 @@ async fn open_workspaces(
             cx.update(|cx| show_welcome_view(app_state, cx).detach())
                 .log_err();
         }
-        // If not the first launch, show an empty window with empty editor
+        // If not the first launch, show a window with the dashboard or an empty editor
         else {
             cx.update(|cx| {
                 let open_options = OpenOptions {
@@ -405,7 +423,9 @@ async fn open_workspaces(
                     ..Default::default()
                 };
                 workspace::open_new(open_options, app_state, cx, |workspace, cx| {
-                    Editor::new_file(workspace, &Default::default(), cx)
+                    if !Dashboard::show_if_enabled(workspace, cx) {
+                        Editor::new_file(workspace, &Default::default(), cx)
+                    }
                 })
                 .detach();
             })